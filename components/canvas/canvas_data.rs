@@ -2,6 +2,7 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
+use crate::filter::apply_filters;
 use azure::azure::AzFloat;
 use azure::azure_hl::SurfacePattern;
 use azure::azure_hl::{AntialiasMode, AsAzurePoint, CapStyle, CompositionOp, JoinStyle};
@@ -15,6 +16,7 @@ use cssparser::RGBA;
 use euclid::{Point2D, Rect, Size2D, Transform2D, Vector2D};
 use ipc_channel::ipc::{IpcSender, IpcSharedMemory};
 use num_traits::ToPrimitive;
+use std::f64::consts::PI;
 use std::mem;
 use std::sync::Arc;
 use webrender::api::DirtyRect;
@@ -150,6 +152,97 @@ impl<'a> PathBuilderRef<'a> {
         );
     }
 
+    fn arc_to(&self, cp1: &Point2D<AzFloat>, cp2: &Point2D<AzFloat>, radius: AzFloat) {
+        let cp0 = match self.current_point() {
+            Some(p) => p.as_azure_point(),
+            None => return,
+        };
+        let cp1 = *cp1;
+        let cp2 = *cp2;
+
+        if (cp0.x == cp1.x && cp0.y == cp1.y) || cp1 == cp2 || radius == 0.0 {
+            self.line_to(&cp1);
+            return;
+        }
+
+        // if all three control points lie on a single straight line,
+        // connect the first two by a straight line
+        let direction = (cp2.x - cp1.x) * (cp0.y - cp1.y) + (cp2.y - cp1.y) * (cp1.x - cp0.x);
+        if direction == 0.0 {
+            self.line_to(&cp1);
+            return;
+        }
+
+        // otherwise, draw the Arc
+        let a2 = (cp0.x - cp1.x).powi(2) + (cp0.y - cp1.y).powi(2);
+        let b2 = (cp1.x - cp2.x).powi(2) + (cp1.y - cp2.y).powi(2);
+        let d = {
+            let c2 = (cp0.x - cp2.x).powi(2) + (cp0.y - cp2.y).powi(2);
+            let cosx = (a2 + b2 - c2) / (2.0 * (a2 * b2).sqrt());
+            let sinx = (1.0 - cosx.powi(2)).sqrt();
+            radius / ((1.0 - cosx) / sinx)
+        };
+
+        // first tangent point
+        let anx = (cp1.x - cp0.x) / a2.sqrt();
+        let any = (cp1.y - cp0.y) / a2.sqrt();
+        let tp1 = Point2D::new(cp1.x - anx * d, cp1.y - any * d);
+
+        // second tangent point
+        let bnx = (cp1.x - cp2.x) / b2.sqrt();
+        let bny = (cp1.y - cp2.y) / b2.sqrt();
+        let tp2 = Point2D::new(cp1.x - bnx * d, cp1.y - bny * d);
+
+        // arc center and angles
+        let anticlockwise = direction < 0.0;
+        let cx = tp1.x + any * radius * if anticlockwise { 1.0 } else { -1.0 };
+        let cy = tp1.y - anx * radius * if anticlockwise { 1.0 } else { -1.0 };
+        let angle_start = (tp1.y - cy).atan2(tp1.x - cx);
+        let angle_end = (tp2.y - cy).atan2(tp2.x - cx);
+
+        self.line_to(&tp1);
+        if [cx, cy, angle_start, angle_end]
+            .iter()
+            .all(|x| x.is_finite())
+        {
+            self.arc(
+                &Point2D::new(cx, cy),
+                radius,
+                angle_start,
+                angle_end,
+                anticlockwise,
+            );
+        }
+    }
+
+    /// Replays the segments of a path built by script.
+    fn add_segments(&self, segments: &[PathSegment]) {
+        for segment in segments {
+            match *segment {
+                PathSegment::ClosePath => self.builder.close(),
+                PathSegment::MoveTo(ref point) => self.move_to(point),
+                PathSegment::LineTo(ref point) => self.line_to(point),
+                PathSegment::QuadraticCurveTo(ref cp, ref pt) => self.quadratic_curve_to(cp, pt),
+                PathSegment::BezierCurveTo(ref cp1, ref cp2, ref pt) => {
+                    self.bezier_curve_to(cp1, cp2, pt)
+                },
+                PathSegment::ArcTo(ref cp1, ref cp2, radius) => self.arc_to(cp1, cp2, radius),
+                PathSegment::Rect(ref rect) => self.rect(rect),
+                PathSegment::Arc(ref center, radius, start, end, ccw) => {
+                    self.arc(center, radius, start, end, ccw)
+                },
+                PathSegment::Ellipse(ref center, radius_x, radius_y, rotation, start, end, ccw) => {
+                    self.ellipse(center, radius_x, radius_y, rotation, start, end, ccw)
+                },
+                PathSegment::AddPath(ref segments, ref transform) => PathBuilderRef {
+                    builder: self.builder,
+                    transform: transform.post_mul(&self.transform),
+                }
+                .add_segments(segments),
+            }
+        }
+    }
+
     fn current_point(&self) -> Option<Point2D<AzFloat>> {
         let inverse = match self.transform.inverse() {
             Some(i) => i,
@@ -213,15 +306,15 @@ impl<'a> CanvasData<'a> {
             image_data.into()
         };
 
-        let writer = |draw_target: &DrawTarget| {
+        let writer = |draw_target: &DrawTarget, draw_options: &DrawOptions| {
             write_image(
                 &draw_target,
                 image_data,
                 source_rect.size,
                 dest_rect,
                 smoothing_enabled,
-                self.state.draw_options.composition,
-                self.state.draw_options.alpha,
+                draw_options.composition,
+                draw_options.alpha,
             );
         };
 
@@ -231,9 +324,11 @@ impl<'a> CanvasData<'a> {
                 Size2D::new(dest_rect.size.width as f32, dest_rect.size.height as f32),
             );
 
-            self.draw_with_shadow(&rect, writer);
+            self.draw_with_shadow(&rect, |draw_target: &DrawTarget| {
+                writer(draw_target, &self.state.draw_options)
+            });
         } else {
-            writer(&self.drawtarget);
+            self.draw_with_filter(writer);
         }
     }
 
@@ -288,11 +383,13 @@ impl<'a> CanvasData<'a> {
                 );
             });
         } else {
-            self.drawtarget.fill_rect(
-                &draw_rect,
-                self.state.fill_style.to_pattern_ref(),
-                Some(&self.state.draw_options),
-            );
+            self.draw_with_filter(|draw_target, draw_options| {
+                draw_target.fill_rect(
+                    &draw_rect,
+                    self.state.fill_style.to_pattern_ref(),
+                    Some(draw_options),
+                );
+            });
         }
     }
 
@@ -327,20 +424,24 @@ impl<'a> CanvasData<'a> {
                 self.state.stroke_opts.miter_limit,
                 self.state.stroke_opts.mDashPattern,
             );
-            self.drawtarget.stroke_line(
-                rect.origin,
-                rect.bottom_right(),
-                self.state.stroke_style.to_pattern_ref(),
-                &stroke_opts,
-                &self.state.draw_options,
-            );
+            self.draw_with_filter(|draw_target, draw_options| {
+                draw_target.stroke_line(
+                    rect.origin,
+                    rect.bottom_right(),
+                    self.state.stroke_style.to_pattern_ref(),
+                    &stroke_opts,
+                    draw_options,
+                );
+            });
         } else {
-            self.drawtarget.stroke_rect(
-                rect,
-                self.state.stroke_style.to_pattern_ref(),
-                &self.state.stroke_opts,
-                &self.state.draw_options,
-            );
+            self.draw_with_filter(|draw_target, draw_options| {
+                draw_target.stroke_rect(
+                    rect,
+                    self.state.stroke_style.to_pattern_ref(),
+                    &self.state.stroke_opts,
+                    draw_options,
+                );
+            });
         }
     }
 
@@ -424,11 +525,21 @@ impl<'a> CanvasData<'a> {
         }
 
         self.ensure_path();
-        self.drawtarget.fill(
-            &self.path(),
-            self.state.fill_style.to_pattern_ref(),
-            &self.state.draw_options,
-        );
+        self.fill_user_space_path(self.path());
+    }
+
+    pub fn fill_path(&self, segments: &[PathSegment]) {
+        if is_zero_size_gradient(&self.state.fill_style) {
+            return; // Paint nothing if gradient size is zero.
+        }
+
+        self.fill_user_space_path(&self.path_from_segments(segments));
+    }
+
+    fn fill_user_space_path(&self, path: &Path) {
+        self.draw_with_filter(|draw_target, draw_options| {
+            draw_target.fill(path, self.state.fill_style.to_pattern_ref(), draw_options);
+        });
     }
 
     pub fn stroke(&mut self) {
@@ -437,12 +548,26 @@ impl<'a> CanvasData<'a> {
         }
 
         self.ensure_path();
-        self.drawtarget.stroke(
-            &self.path(),
-            self.state.stroke_style.to_pattern_ref(),
-            &self.state.stroke_opts,
-            &self.state.draw_options,
-        );
+        self.stroke_user_space_path(self.path());
+    }
+
+    pub fn stroke_path(&self, segments: &[PathSegment]) {
+        if is_zero_size_gradient(&self.state.stroke_style) {
+            return; // Paint nothing if gradient size is zero.
+        }
+
+        self.stroke_user_space_path(&self.path_from_segments(segments));
+    }
+
+    fn stroke_user_space_path(&self, path: &Path) {
+        self.draw_with_filter(|draw_target, draw_options| {
+            draw_target.stroke(
+                path,
+                self.state.stroke_style.to_pattern_ref(),
+                &self.state.stroke_opts,
+                draw_options,
+            );
+        });
     }
 
     pub fn clip(&mut self) {
@@ -450,6 +575,11 @@ impl<'a> CanvasData<'a> {
         self.drawtarget.push_clip(&self.path());
     }
 
+    pub fn clip_path(&self, segments: &[PathSegment]) {
+        self.drawtarget
+            .push_clip(&self.path_from_segments(segments));
+    }
+
     pub fn is_point_in_path(
         &mut self,
         x: f64,
@@ -469,6 +599,31 @@ impl<'a> CanvasData<'a> {
         chan.send(result).unwrap();
     }
 
+    pub fn is_point_in_path_path(
+        &self,
+        segments: &[PathSegment],
+        x: f64,
+        y: f64,
+        _fill_rule: FillRule,
+        chan: IpcSender<bool>,
+    ) {
+        let path = self.path_from_segments(segments);
+        let result = path.contains_point(x, y, &self.drawtarget.get_transform());
+        chan.send(result).unwrap();
+    }
+
+    /// Builds the user-space path of a `Path2D`, independently of the
+    /// current path.
+    fn path_from_segments(&self, segments: &[PathSegment]) -> Path {
+        let builder = self.drawtarget.create_path_builder();
+        PathBuilderRef {
+            builder: &builder,
+            transform: Transform2D::identity(),
+        }
+        .add_segments(segments);
+        builder.finish()
+    }
+
     pub fn move_to(&mut self, point: &Point2D<AzFloat>) {
         self.path_builder().move_to(point);
     }
@@ -571,66 +726,7 @@ impl<'a> CanvasData<'a> {
     }
 
     pub fn arc_to(&mut self, cp1: &Point2D<AzFloat>, cp2: &Point2D<AzFloat>, radius: AzFloat) {
-        let cp0 = match self.path_builder().current_point() {
-            Some(p) => p.as_azure_point(),
-            None => return,
-        };
-        let cp1 = *cp1;
-        let cp2 = *cp2;
-
-        if (cp0.x == cp1.x && cp0.y == cp1.y) || cp1 == cp2 || radius == 0.0 {
-            self.line_to(&cp1);
-            return;
-        }
-
-        // if all three control points lie on a single straight line,
-        // connect the first two by a straight line
-        let direction = (cp2.x - cp1.x) * (cp0.y - cp1.y) + (cp2.y - cp1.y) * (cp1.x - cp0.x);
-        if direction == 0.0 {
-            self.line_to(&cp1);
-            return;
-        }
-
-        // otherwise, draw the Arc
-        let a2 = (cp0.x - cp1.x).powi(2) + (cp0.y - cp1.y).powi(2);
-        let b2 = (cp1.x - cp2.x).powi(2) + (cp1.y - cp2.y).powi(2);
-        let d = {
-            let c2 = (cp0.x - cp2.x).powi(2) + (cp0.y - cp2.y).powi(2);
-            let cosx = (a2 + b2 - c2) / (2.0 * (a2 * b2).sqrt());
-            let sinx = (1.0 - cosx.powi(2)).sqrt();
-            radius / ((1.0 - cosx) / sinx)
-        };
-
-        // first tangent point
-        let anx = (cp1.x - cp0.x) / a2.sqrt();
-        let any = (cp1.y - cp0.y) / a2.sqrt();
-        let tp1 = Point2D::new(cp1.x - anx * d, cp1.y - any * d);
-
-        // second tangent point
-        let bnx = (cp1.x - cp2.x) / b2.sqrt();
-        let bny = (cp1.y - cp2.y) / b2.sqrt();
-        let tp2 = Point2D::new(cp1.x - bnx * d, cp1.y - bny * d);
-
-        // arc center and angles
-        let anticlockwise = direction < 0.0;
-        let cx = tp1.x + any * radius * if anticlockwise { 1.0 } else { -1.0 };
-        let cy = tp1.y - anx * radius * if anticlockwise { 1.0 } else { -1.0 };
-        let angle_start = (tp1.y - cy).atan2(tp1.x - cx);
-        let angle_end = (tp2.y - cy).atan2(tp2.x - cx);
-
-        self.line_to(&tp1);
-        if [cx, cy, angle_start, angle_end]
-            .iter()
-            .all(|x| x.is_finite())
-        {
-            self.arc(
-                &Point2D::new(cx, cy),
-                radius,
-                angle_start,
-                angle_end,
-                anticlockwise,
-            );
-        }
+        self.path_builder().arc_to(cp1, cp2, radius);
     }
 
     pub fn ellipse(
@@ -708,6 +804,10 @@ impl<'a> CanvasData<'a> {
             .set_composition_op(op.to_azure_style());
     }
 
+    pub fn set_filter(&mut self, filter: Vec<FilterFunction>) {
+        self.state.filter = filter;
+    }
+
     pub fn create(size: Size2D<u32>) -> DrawTarget {
         // FIXME(nox): Why is the size made of i32 values?
         DrawTarget::new(BackendType::Skia, size.to_i32(), SurfaceFormat::B8G8R8A8)
@@ -730,6 +830,36 @@ impl<'a> CanvasData<'a> {
         }
     }
 
+    pub fn get_size(&self) -> Size2D<u32> {
+        let size = self.drawtarget.get_size();
+        Size2D::new(size.width as u32, size.height as u32)
+    }
+
+    /// Replaces the whole bitmap with pixels in the format `read_pixels`
+    /// returns, resizing it if needed
+    pub fn replace_bitmap(&mut self, pixels: Vec<u8>, size: Size2D<u32>) {
+        if self.get_size() != size {
+            self.recreate(size);
+        }
+        if size.area() == 0 {
+            return;
+        }
+        let source_surface = self
+            .drawtarget
+            .create_source_surface_from_data(
+                &pixels,
+                size.to_i32(),
+                size.width as i32 * 4,
+                SurfaceFormat::B8G8R8A8,
+            )
+            .unwrap();
+        self.drawtarget.copy_surface(
+            source_surface,
+            Rect::from_size(size.to_i32()),
+            Point2D::zero(),
+        );
+    }
+
     #[allow(unsafe_code)]
     pub fn send_pixels(&mut self, chan: IpcSender<IpcSharedMemory>) {
         let data = IpcSharedMemory::from_bytes(unsafe {
@@ -865,6 +995,53 @@ impl<'a> CanvasData<'a> {
         );
     }
 
+    /// Draws with `draw` directly, or when there are filters, onto a layer
+    /// that gets composited once they are applied to it.
+    ///
+    /// <https://html.spec.whatwg.org/multipage/#drawing-model>
+    #[allow(unsafe_code)]
+    fn draw_with_filter<F>(&self, draw: F)
+    where
+        F: FnOnce(&DrawTarget, &DrawOptions),
+    {
+        if self.state.filter.is_empty() {
+            return draw(&self.drawtarget, &self.state.draw_options);
+        }
+
+        let size = self.drawtarget.get_size();
+        let layer = self
+            .drawtarget
+            .create_similar_draw_target(&size, self.drawtarget.get_format());
+        layer.set_transform(&self.state.transform);
+        // The global alpha and the composition operator only apply when
+        // compositing the layer.
+        let layer_options =
+            DrawOptions::new(1.0, CompositionOp::Over, self.state.draw_options.antialias);
+        draw(&layer, &layer_options);
+
+        let mut data = unsafe { layer.snapshot().get_data_surface().data().to_vec() };
+        apply_filters(&mut data, size.to_u32(), &self.state.filter);
+        let source_surface = self
+            .drawtarget
+            .create_source_surface_from_data(&data, size, size.width * 4, SurfaceFormat::B8G8R8A8)
+            .unwrap();
+        let rect = Rect::from_size(size.to_f32());
+        let draw_options = DrawOptions::new(
+            self.state.draw_options.alpha,
+            self.state.draw_options.composition,
+            AntialiasMode::None,
+        );
+        self.drawtarget.set_transform(&Transform2D::identity());
+        self.drawtarget.draw_surface(
+            source_surface,
+            rect,
+            rect,
+            DrawSurfaceOptions::new(Filter::Point, true),
+            draw_options,
+        );
+        self.drawtarget.set_transform(&self.state.transform);
+    }
+
     /// It reads image data from the canvas
     /// canvas_size: The size of the canvas we're reading from
     /// read_rect: The area of the canvas we want to read from
@@ -914,6 +1091,7 @@ struct CanvasPaintState<'a> {
     shadow_offset_y: f64,
     shadow_blur: f64,
     shadow_color: Color,
+    filter: Vec<FilterFunction>,
 }
 
 impl<'a> CanvasPaintState<'a> {
@@ -934,6 +1112,7 @@ impl<'a> CanvasPaintState<'a> {
             shadow_offset_y: 0.0,
             shadow_blur: 0.0,
             shadow_color: Color::transparent(),
+            filter: vec![],
         }
     }
 }
@@ -1197,6 +1376,26 @@ impl ToAzurePattern for FillOrStrokeStyle {
                     &Transform2D::identity(),
                 ))
             },
+            FillOrStrokeStyle::ConicGradient(ref conic_gradient_style) => {
+                // Azure has no conic gradients, so the gradient is drawn in
+                // device space, with the transform that is current, and
+                // used as a surface.
+                let transform = drawtarget.get_transform();
+                let size = drawtarget.get_size();
+                let data = conic_gradient_data(conic_gradient_style, size.to_u32(), &transform)?;
+                let source_surface = drawtarget.create_source_surface_from_data(
+                    &data,
+                    size,
+                    size.width * 4,
+                    SurfaceFormat::B8G8R8A8,
+                )?;
+                Pattern::Surface(SurfacePattern::new(
+                    source_surface.azure_source_surface,
+                    true,
+                    true,
+                    &transform.inverse()?,
+                ))
+            },
             FillOrStrokeStyle::Surface(ref surface_style) => {
                 let source_surface = drawtarget.create_source_surface_from_data(
                     &surface_style.surface_data,
@@ -1228,3 +1427,70 @@ impl ToAzureStyle for RGBA {
         )
     }
 }
+
+/// The premultiplied BGRA pixels of a conic gradient covering a device-space
+/// bitmap of the given size, none if the gradient can't be drawn with that
+/// transform.
+///
+/// <https://html.spec.whatwg.org/multipage/#dom-context-2d-createconicgradient>
+fn conic_gradient_data(
+    style: &ConicGradientStyle,
+    size: Size2D<u32>,
+    transform: &Transform2D<AzFloat>,
+) -> Option<Vec<u8>> {
+    let inverse = transform.inverse()?;
+    let mut stops = style.stops.clone();
+    // The stops added at the same offset must stay in order.
+    stops.sort_by(|a, b| a.offset.partial_cmp(&b.offset).unwrap());
+
+    let mut data = Vec::with_capacity(size.area() as usize * 4);
+    for y in 0..size.height {
+        for x in 0..size.width {
+            let point = inverse.transform_point(&Point2D::new(x as f32 + 0.5, y as f32 + 0.5));
+            let angle = (point.y as f64 - style.y).atan2(point.x as f64 - style.x) - style.angle;
+            let turns = angle / (2. * PI);
+            let offset = turns - turns.floor();
+            let (red, green, blue, alpha) = gradient_color_at(&stops, offset);
+            data.extend_from_slice(&[
+                (blue * alpha * 255.).round() as u8,
+                (green * alpha * 255.).round() as u8,
+                (red * alpha * 255.).round() as u8,
+                (alpha * 255.).round() as u8,
+            ]);
+        }
+    }
+    Some(data)
+}
+
+/// The color of a gradient at `offset`, given its sorted color stops.
+fn gradient_color_at(stops: &[CanvasGradientStop], offset: f64) -> (f64, f64, f64, f64) {
+    let components = |color: &RGBA| {
+        (
+            color.red_f32() as f64,
+            color.green_f32() as f64,
+            color.blue_f32() as f64,
+            color.alpha_f32() as f64,
+        )
+    };
+    let (first, last) = match (stops.first(), stops.last()) {
+        (Some(first), Some(last)) => (first, last),
+        // Gradients without stops are transparent black.
+        _ => return (0., 0., 0., 0.),
+    };
+    if offset <= first.offset {
+        return components(&first.color);
+    }
+    if offset >= last.offset {
+        return components(&last.color);
+    }
+    let end = stops.iter().position(|stop| stop.offset > offset).unwrap();
+    let (start, end) = (&stops[end - 1], &stops[end]);
+    let ratio = (offset - start.offset) / (end.offset - start.offset);
+    let (start, end) = (components(&start.color), components(&end.color));
+    (
+        start.0 + (end.0 - start.0) * ratio,
+        start.1 + (end.1 - start.1) * ratio,
+        start.2 + (end.2 - start.2) * ratio,
+        start.3 + (end.3 - start.3) * ratio,
+    )
+}
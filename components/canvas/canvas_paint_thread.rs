@@ -5,7 +5,7 @@
 use crate::canvas_data::*;
 use azure::azure_hl::AntialiasMode;
 use canvas_traits::canvas::*;
-use euclid::Size2D;
+use euclid::{Rect, Size2D};
 use ipc_channel::ipc::{self, IpcSender};
 use std::borrow::ToOwned;
 use std::collections::HashMap;
@@ -56,6 +56,9 @@ impl<'a> CanvasPaintThread<'a> {
                                 FromScriptMsg::SendPixels(chan) => {
                                     canvas_paint_thread.canvas(canvas_id).send_pixels(chan);
                                 },
+                                FromScriptMsg::Commit(placeholder_id) => {
+                                    canvas_paint_thread.commit(canvas_id, placeholder_id);
+                                },
                             },
                             CanvasMsg::FromLayout(message, canvas_id) => match message {
                                 FromLayoutMsg::SendData(chan) => {
@@ -107,11 +110,17 @@ impl<'a> CanvasPaintThread<'a> {
             Canvas2dMsg::BeginPath => self.canvas(canvas_id).begin_path(),
             Canvas2dMsg::ClosePath => self.canvas(canvas_id).close_path(),
             Canvas2dMsg::Fill => self.canvas(canvas_id).fill(),
+            Canvas2dMsg::FillPath(ref segments) => self.canvas(canvas_id).fill_path(segments),
             Canvas2dMsg::Stroke => self.canvas(canvas_id).stroke(),
+            Canvas2dMsg::StrokePath(ref segments) => self.canvas(canvas_id).stroke_path(segments),
             Canvas2dMsg::Clip => self.canvas(canvas_id).clip(),
+            Canvas2dMsg::ClipPath(ref segments) => self.canvas(canvas_id).clip_path(segments),
             Canvas2dMsg::IsPointInPath(x, y, fill_rule, chan) => self
                 .canvas(canvas_id)
                 .is_point_in_path(x, y, fill_rule, chan),
+            Canvas2dMsg::IsPointInPathPath(ref segments, x, y, fill_rule, chan) => self
+                .canvas(canvas_id)
+                .is_point_in_path_path(segments, x, y, fill_rule, chan),
             Canvas2dMsg::DrawImage(
                 imagedata,
                 image_size,
@@ -198,9 +207,19 @@ impl<'a> CanvasPaintThread<'a> {
             Canvas2dMsg::SetShadowColor(ref color) => self
                 .canvas(canvas_id)
                 .set_shadow_color(color.to_azure_style()),
+            Canvas2dMsg::SetFilter(filter) => self.canvas(canvas_id).set_filter(filter),
         }
     }
 
+    /// Copies the bitmap of a canvas controlling a placeholder canvas over
+    /// to the placeholder, which is the canvas that gets displayed
+    fn commit(&mut self, canvas_id: CanvasId, placeholder_id: CanvasId) {
+        let canvas = self.canvas(canvas_id);
+        let size = canvas.get_size();
+        let pixels = canvas.read_pixels(Rect::from_size(size), size);
+        self.canvas(placeholder_id).replace_bitmap(pixels, size);
+    }
+
     fn canvas(&mut self, canvas_id: CanvasId) -> &mut CanvasData<'a> {
         self.canvases.get_mut(&canvas_id).expect("Bogus canvas id")
     }
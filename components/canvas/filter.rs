@@ -0,0 +1,213 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! The filter functions of the `filter` attribute of 2D contexts, which
+//! Azure has no primitives for.
+
+use canvas_traits::canvas::FilterFunction;
+use euclid::Size2D;
+
+/// Applies `filters` in order to premultiplied BGRA `data`.
+///
+/// <https://drafts.fxtf.org/filter-effects/#supported-filter-functions>
+pub fn apply_filters(data: &mut [u8], size: Size2D<u32>, filters: &[FilterFunction]) {
+    for filter in filters {
+        match *filter {
+            FilterFunction::Blur(deviation) => blur(data, size, deviation),
+            FilterFunction::Opacity(amount) => {
+                let amount = amount.min(1.);
+                for value in data.iter_mut() {
+                    *value = (*value as f64 * amount).round() as u8;
+                }
+            },
+            _ => {
+                let matrix = color_matrix(*filter);
+                for pixel in data.chunks_mut(4) {
+                    transform_color(pixel, &matrix);
+                }
+            },
+        }
+    }
+}
+
+/// The matrix applied to the red, green and blue components of the colors,
+/// the last column being added to the result.
+type ColorMatrix = [[f64; 4]; 3];
+
+/// <https://drafts.fxtf.org/filter-effects/#ShorthandEquivalents>
+fn color_matrix(filter: FilterFunction) -> ColorMatrix {
+    match filter {
+        FilterFunction::Brightness(amount) => [
+            [amount, 0., 0., 0.],
+            [0., amount, 0., 0.],
+            [0., 0., amount, 0.],
+        ],
+        FilterFunction::Contrast(amount) => {
+            let intercept = 0.5 - 0.5 * amount;
+            [
+                [amount, 0., 0., intercept],
+                [0., amount, 0., intercept],
+                [0., 0., amount, intercept],
+            ]
+        },
+        FilterFunction::Grayscale(amount) => {
+            let s = 1. - amount.min(1.);
+            [
+                [
+                    0.2126 + 0.7874 * s,
+                    0.7152 - 0.7152 * s,
+                    0.0722 - 0.0722 * s,
+                    0.,
+                ],
+                [
+                    0.2126 - 0.2126 * s,
+                    0.7152 + 0.2848 * s,
+                    0.0722 - 0.0722 * s,
+                    0.,
+                ],
+                [
+                    0.2126 - 0.2126 * s,
+                    0.7152 - 0.7152 * s,
+                    0.0722 + 0.9278 * s,
+                    0.,
+                ],
+            ]
+        },
+        FilterFunction::HueRotate(degrees) => {
+            let (sin, cos) = degrees.to_radians().sin_cos();
+            [
+                [
+                    0.213 + cos * 0.787 - sin * 0.213,
+                    0.715 - cos * 0.715 - sin * 0.715,
+                    0.072 - cos * 0.072 + sin * 0.928,
+                    0.,
+                ],
+                [
+                    0.213 - cos * 0.213 + sin * 0.143,
+                    0.715 + cos * 0.285 + sin * 0.140,
+                    0.072 - cos * 0.072 - sin * 0.283,
+                    0.,
+                ],
+                [
+                    0.213 - cos * 0.213 - sin * 0.787,
+                    0.715 - cos * 0.715 + sin * 0.715,
+                    0.072 + cos * 0.928 + sin * 0.072,
+                    0.,
+                ],
+            ]
+        },
+        FilterFunction::Invert(amount) => {
+            let amount = amount.min(1.);
+            let slope = 1. - 2. * amount;
+            [
+                [slope, 0., 0., amount],
+                [0., slope, 0., amount],
+                [0., 0., slope, amount],
+            ]
+        },
+        FilterFunction::Saturate(s) => [
+            [0.213 + 0.787 * s, 0.715 - 0.715 * s, 0.072 - 0.072 * s, 0.],
+            [0.213 - 0.213 * s, 0.715 + 0.285 * s, 0.072 - 0.072 * s, 0.],
+            [0.213 - 0.213 * s, 0.715 - 0.715 * s, 0.072 + 0.928 * s, 0.],
+        ],
+        FilterFunction::Sepia(amount) => {
+            let s = 1. - amount.min(1.);
+            [
+                [0.393 + 0.607 * s, 0.769 - 0.769 * s, 0.189 - 0.189 * s, 0.],
+                [0.349 - 0.349 * s, 0.686 + 0.314 * s, 0.168 - 0.168 * s, 0.],
+                [0.272 - 0.272 * s, 0.534 - 0.534 * s, 0.131 + 0.869 * s, 0.],
+            ]
+        },
+        FilterFunction::Blur(_) | FilterFunction::Opacity(_) => unreachable!(),
+    }
+}
+
+/// Applies `matrix` to the unpremultiplied color of a BGRA pixel.
+fn transform_color(pixel: &mut [u8], matrix: &ColorMatrix) {
+    let alpha = pixel[3] as f64 / 255.;
+    if alpha == 0. {
+        return;
+    }
+    let color = [
+        pixel[2] as f64 / 255. / alpha,
+        pixel[1] as f64 / 255. / alpha,
+        pixel[0] as f64 / 255. / alpha,
+    ];
+    let component = |row: &[f64; 4]| {
+        let value = row[0] * color[0] + row[1] * color[1] + row[2] * color[2] + row[3];
+        (value.max(0.).min(1.) * alpha * 255.).round() as u8
+    };
+    pixel[2] = component(&matrix[0]);
+    pixel[1] = component(&matrix[1]);
+    pixel[0] = component(&matrix[2]);
+}
+
+/// A gaussian blur of the given standard deviation, approximated with three
+/// box blurs.
+///
+/// <https://drafts.fxtf.org/filter-effects/#feGaussianBlurElement>
+fn blur(data: &mut [u8], size: Size2D<u32>, deviation: f64) {
+    let box_size = (deviation * 3. * (2. * std::f64::consts::PI).sqrt() / 4. + 0.5).floor();
+    let radius = (box_size / 2.) as usize;
+    if radius == 0 {
+        return;
+    }
+    let (width, height) = (size.width as usize, size.height as usize);
+    let mut line = vec![];
+    for _ in 0..3 {
+        for y in 0..height {
+            box_blur_line(data, y * width * 4, 4, width, radius, &mut line);
+        }
+        for x in 0..width {
+            box_blur_line(data, x * 4, width * 4, height, radius, &mut line);
+        }
+    }
+}
+
+/// Blurs the `length` pixels starting at byte `start` and `stride` bytes
+/// apart with a box of `2 * radius + 1` pixels, the pixels past both ends
+/// being transparent.
+fn box_blur_line(
+    data: &mut [u8],
+    start: usize,
+    stride: usize,
+    length: usize,
+    radius: usize,
+    line: &mut Vec<[u32; 4]>,
+) {
+    line.clear();
+    line.extend((0..length).map(|i| {
+        let pixel = &data[start + i * stride..start + i * stride + 4];
+        [
+            pixel[0] as u32,
+            pixel[1] as u32,
+            pixel[2] as u32,
+            pixel[3] as u32,
+        ]
+    }));
+
+    let box_size = 2 * radius as u32 + 1;
+    let mut sums = [0; 4];
+    for pixel in line.iter().take(radius) {
+        for (sum, value) in sums.iter_mut().zip(pixel) {
+            *sum += value;
+        }
+    }
+    for i in 0..length {
+        if let Some(pixel) = line.get(i + radius) {
+            for (sum, value) in sums.iter_mut().zip(pixel) {
+                *sum += value;
+            }
+        }
+        let offset = start + i * stride;
+        for (channel, sum) in sums.iter().enumerate() {
+            data[offset + channel] = ((sum + box_size / 2) / box_size) as u8;
+        }
+        if i >= radius {
+            for (sum, value) in sums.iter_mut().zip(&line[i - radius]) {
+                *sum -= value;
+            }
+        }
+    }
+}
@@ -9,6 +9,7 @@ extern crate log;
 
 pub mod canvas_data;
 pub mod canvas_paint_thread;
+mod filter;
 pub mod gl_context;
 mod webgl_mode;
 pub mod webgl_thread;
@@ -288,7 +288,10 @@ impl<VR: WebVRRenderHandler + 'static> WebGLThread<VR> {
             .map_err(|msg: &str| msg.to_owned())?;
 
         let id = WebGLContextId(self.next_webgl_id);
-        let (size, texture_id, limits) = ctx.get_info();
+        let (size, texture_id, mut limits) = ctx.get_info();
+        if version == WebGLVersion::WebGL2 {
+            Self::query_webgl2_limits(&ctx, &mut limits);
+        }
         self.next_webgl_id += 1;
         self.contexts.insert(
             id,
@@ -692,6 +695,25 @@ impl<VR: WebVRRenderHandler + 'static> WebGLThread<VR> {
 
         WebGLSLVersion { major, minor }
     }
+
+    /// Queries the limits that only exist in OpenGL ES 3.0 contexts.
+    #[allow(unsafe_code)]
+    fn query_webgl2_limits(context: &GLContextWrapper, limits: &mut GLLimits) {
+        let gl = context.gl();
+        let get = |param| {
+            let mut value = [0];
+            unsafe {
+                gl.get_integer_v(param, &mut value);
+            }
+            value[0] as u32
+        };
+        limits.max_3d_texture_size = get(gl::MAX_3D_TEXTURE_SIZE);
+        limits.max_array_texture_layers = get(gl::MAX_ARRAY_TEXTURE_LAYERS);
+        limits.max_uniform_buffer_bindings = get(gl::MAX_UNIFORM_BUFFER_BINDINGS);
+        limits.uniform_buffer_offset_alignment = get(gl::UNIFORM_BUFFER_OFFSET_ALIGNMENT);
+        limits.max_transform_feedback_separate_attribs =
+            get(gl::MAX_TRANSFORM_FEEDBACK_SEPARATE_ATTRIBS);
+    }
 }
 
 impl<VR: WebVRRenderHandler + 'static> Drop for WebGLThread<VR> {
@@ -1255,6 +1277,30 @@ impl WebGLImpl {
                 }
                 sender.send(value[0]).unwrap()
             },
+            WebGLCommand::GetProgramActiveUniformBlocks(program, ref sender) => {
+                let mut value = [0];
+                unsafe {
+                    ctx.gl()
+                        .get_program_iv(program.get(), gl::ACTIVE_UNIFORM_BLOCKS, &mut value);
+                }
+                sender.send(value[0]).unwrap()
+            },
+            WebGLCommand::GetProgramTransformFeedbackVaryings(program, ref sender) => {
+                let (mut count, mut buffer_mode) = ([0], [0]);
+                unsafe {
+                    ctx.gl().get_program_iv(
+                        program.get(),
+                        gl::TRANSFORM_FEEDBACK_VARYINGS,
+                        &mut count,
+                    );
+                    ctx.gl().get_program_iv(
+                        program.get(),
+                        gl::TRANSFORM_FEEDBACK_BUFFER_MODE,
+                        &mut buffer_mode,
+                    );
+                }
+                sender.send((count[0], buffer_mode[0] as u32)).unwrap()
+            },
             WebGLCommand::GetCurrentVertexAttrib(index, ref sender) => {
                 let mut value = [0.; 4];
                 unsafe {
@@ -1422,6 +1468,222 @@ impl WebGLImpl {
                 depth,
                 stencil,
             } => Self::initialize_framebuffer(ctx.gl(), state, color, depth, stencil),
+            WebGLCommand::TexImage3D {
+                target,
+                level,
+                internal_format,
+                width,
+                height,
+                depth,
+                format,
+                data_type,
+                unpacking_alignment,
+                ref data,
+            } => {
+                ctx.gl()
+                    .pixel_store_i(gl::UNPACK_ALIGNMENT, unpacking_alignment as i32);
+                ctx.gl().tex_image_3d(
+                    target,
+                    level as i32,
+                    internal_format as i32,
+                    width as i32,
+                    height as i32,
+                    depth as i32,
+                    0,
+                    format,
+                    data_type,
+                    Some(&***data),
+                );
+            },
+            WebGLCommand::TexSubImage3D {
+                target,
+                level,
+                xoffset,
+                yoffset,
+                zoffset,
+                width,
+                height,
+                depth,
+                format,
+                data_type,
+                unpacking_alignment,
+                ref data,
+            } => {
+                ctx.gl()
+                    .pixel_store_i(gl::UNPACK_ALIGNMENT, unpacking_alignment as i32);
+                ctx.gl().tex_sub_image_3d(
+                    target,
+                    level as i32,
+                    xoffset,
+                    yoffset,
+                    zoffset,
+                    width as i32,
+                    height as i32,
+                    depth as i32,
+                    format,
+                    data_type,
+                    &data,
+                );
+            },
+            WebGLCommand::CopyTexSubImage3D(
+                target,
+                level,
+                xoffset,
+                yoffset,
+                zoffset,
+                x,
+                y,
+                width,
+                height,
+            ) => ctx.gl().copy_tex_sub_image_3d(
+                target, level, xoffset, yoffset, zoffset, x, y, width, height,
+            ),
+            WebGLCommand::TexStorage2D(target, levels, internal_format, width, height) => {
+                ctx.gl().tex_storage_2d(
+                    target,
+                    levels as i32,
+                    internal_format,
+                    width as i32,
+                    height as i32,
+                )
+            },
+            WebGLCommand::TexStorage3D(target, levels, internal_format, width, height, depth) => {
+                ctx.gl().tex_storage_3d(
+                    target,
+                    levels as i32,
+                    internal_format,
+                    width as i32,
+                    height as i32,
+                    depth as i32,
+                )
+            },
+            WebGLCommand::CreateSampler(ref chan) => Self::create_sampler(ctx.gl(), chan),
+            WebGLCommand::DeleteSampler(id) => ctx.gl().delete_samplers(&[id.get()]),
+            WebGLCommand::BindSampler(unit, id) => ctx
+                .gl()
+                .bind_sampler(unit, id.map_or(0, WebGLSamplerId::get)),
+            WebGLCommand::SetSamplerParameterInt(id, pname, value) => {
+                ctx.gl().sampler_parameter_i(id.get(), pname, value)
+            },
+            WebGLCommand::SetSamplerParameterFloat(id, pname, value) => {
+                ctx.gl().sampler_parameter_f(id.get(), pname, value)
+            },
+            WebGLCommand::GetSamplerParameterInt(id, pname, ref sender) => {
+                let mut value = [0];
+                unsafe {
+                    ctx.gl()
+                        .get_sampler_parameter_iv(id.get(), pname, &mut value);
+                }
+                sender.send(value[0]).unwrap();
+            },
+            WebGLCommand::GetSamplerParameterFloat(id, pname, ref sender) => {
+                let mut value = [0.];
+                unsafe {
+                    ctx.gl()
+                        .get_sampler_parameter_fv(id.get(), pname, &mut value);
+                }
+                sender.send(value[0]).unwrap();
+            },
+            WebGLCommand::CreateTransformFeedback(ref chan) => {
+                Self::create_transform_feedback(ctx.gl(), chan)
+            },
+            WebGLCommand::DeleteTransformFeedback(id) => {
+                ctx.gl().delete_transform_feedbacks(&[id.get()])
+            },
+            WebGLCommand::BindTransformFeedback(target, id) => ctx
+                .gl()
+                .bind_transform_feedback(target, id.map_or(0, WebGLTransformFeedbackId::get)),
+            WebGLCommand::BeginTransformFeedback(mode) => ctx.gl().begin_transform_feedback(mode),
+            WebGLCommand::EndTransformFeedback => ctx.gl().end_transform_feedback(),
+            WebGLCommand::PauseTransformFeedback => ctx.gl().pause_transform_feedback(),
+            WebGLCommand::ResumeTransformFeedback => ctx.gl().resume_transform_feedback(),
+            WebGLCommand::TransformFeedbackVaryings(program, ref varyings, buffer_mode) => {
+                let varyings = varyings
+                    .iter()
+                    .map(|name| to_name_in_compiled_shader(name))
+                    .collect::<Vec<_>>();
+                ctx.gl()
+                    .transform_feedback_varyings(program.get(), &varyings, buffer_mode)
+            },
+            WebGLCommand::GetTransformFeedbackVarying(program, index, ref sender) => {
+                let (size, type_, name) = ctx
+                    .gl()
+                    .get_transform_feedback_varying(program.get(), index);
+                sender
+                    .send((size, type_, from_name_in_compiled_shader(&name)))
+                    .unwrap();
+            },
+            WebGLCommand::BindBufferBase(target, index, id) => {
+                ctx.gl()
+                    .bind_buffer_base(target, index, id.map_or(0, WebGLBufferId::get))
+            },
+            WebGLCommand::BindBufferRange(target, index, id, offset, size) => {
+                ctx.gl().bind_buffer_range(
+                    target,
+                    index,
+                    id.map_or(0, WebGLBufferId::get),
+                    offset as isize,
+                    size as isize,
+                )
+            },
+            WebGLCommand::GetUniformBlockIndex(program, ref name, ref sender) => {
+                let index = ctx
+                    .gl()
+                    .get_uniform_block_index(program.get(), &to_name_in_compiled_shader(name));
+                sender.send(index).unwrap();
+            },
+            WebGLCommand::GetActiveUniformBlockName(program, index, ref sender) => {
+                let name = ctx.gl().get_active_uniform_block_name(program.get(), index);
+                sender.send(from_name_in_compiled_shader(&name)).unwrap();
+            },
+            WebGLCommand::GetActiveUniformBlockParameter(program, index, pname, ref sender) => {
+                let count = if pname == gl::UNIFORM_BLOCK_ACTIVE_UNIFORM_INDICES {
+                    let mut count = [0];
+                    unsafe {
+                        ctx.gl().get_active_uniform_block_iv(
+                            program.get(),
+                            index,
+                            gl::UNIFORM_BLOCK_ACTIVE_UNIFORMS,
+                            &mut count,
+                        );
+                    }
+                    count[0] as usize
+                } else {
+                    1
+                };
+                let mut value = vec![0; count];
+                unsafe {
+                    ctx.gl()
+                        .get_active_uniform_block_iv(program.get(), index, pname, &mut value);
+                }
+                sender.send(value).unwrap();
+            },
+            WebGLCommand::UniformBlockBinding(program, index, binding) => ctx
+                .gl()
+                .uniform_block_binding(program.get(), index, binding),
+            WebGLCommand::GetFragDataLocation(program, ref name, ref sender) => {
+                let location = ctx
+                    .gl()
+                    .get_frag_data_location(program.get(), &to_name_in_compiled_shader(name));
+                sender.send(location).unwrap();
+            },
+            WebGLCommand::Uniform1ui(uniform_id, v) => ctx.gl().uniform_1ui(uniform_id, v),
+            WebGLCommand::Uniform2ui(uniform_id, x, y) => ctx.gl().uniform_2ui(uniform_id, x, y),
+            WebGLCommand::Uniform3ui(uniform_id, x, y, z) => {
+                ctx.gl().uniform_3ui(uniform_id, x, y, z)
+            },
+            WebGLCommand::Uniform4ui(uniform_id, x, y, z, w) => {
+                ctx.gl().uniform_4ui(uniform_id, x, y, z, w)
+            },
+            WebGLCommand::VertexAttribI(attrib_id, x, y, z, w) => {
+                ctx.gl().vertex_attrib_i4i(attrib_id, x, y, z, w)
+            },
+            WebGLCommand::VertexAttribUI(attrib_id, x, y, z, w) => {
+                ctx.gl().vertex_attrib_i4ui(attrib_id, x, y, z, w)
+            },
+            WebGLCommand::VertexAttribIPointer(attrib_id, size, data_type, stride, offset) => ctx
+                .gl()
+                .vertex_attrib_i_pointer(attrib_id, size, data_type, stride, offset),
         }
 
         // TODO: update test expectations in order to enable debug assertions
@@ -1719,6 +1981,31 @@ impl WebGLImpl {
         chan.send(vao).unwrap();
     }
 
+    #[allow(unsafe_code)]
+    fn create_sampler(gl: &dyn gl::Gl, chan: &WebGLSender<Option<WebGLSamplerId>>) {
+        let sampler = gl.gen_samplers(1)[0];
+        let sampler = if sampler == 0 {
+            None
+        } else {
+            Some(unsafe { WebGLSamplerId::new(sampler) })
+        };
+        chan.send(sampler).unwrap();
+    }
+
+    #[allow(unsafe_code)]
+    fn create_transform_feedback(
+        gl: &dyn gl::Gl,
+        chan: &WebGLSender<Option<WebGLTransformFeedbackId>>,
+    ) {
+        let transform_feedback = gl.gen_transform_feedbacks(1)[0];
+        let transform_feedback = if transform_feedback == 0 {
+            None
+        } else {
+            Some(unsafe { WebGLTransformFeedbackId::new(transform_feedback) })
+        };
+        chan.send(transform_feedback).unwrap();
+    }
+
     #[inline]
     fn bind_framebuffer<Native: NativeGLContextMethods>(
         gl: &dyn gl::Gl,
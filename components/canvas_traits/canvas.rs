@@ -49,13 +49,16 @@ pub enum Canvas2dMsg {
     BezierCurveTo(Point2D<f32>, Point2D<f32>, Point2D<f32>),
     ClearRect(Rect<f32>),
     Clip,
+    ClipPath(Vec<PathSegment>),
     ClosePath,
     Ellipse(Point2D<f32>, f32, f32, f32, f32, f32, bool),
     Fill,
+    FillPath(Vec<PathSegment>),
     FillText(String, f64, f64, Option<f64>),
     FillRect(Rect<f32>),
     GetImageData(Rect<u32>, Size2D<u32>, IpcBytesSender),
     IsPointInPath(f64, f64, FillRule, IpcSender<bool>),
+    IsPointInPathPath(Vec<PathSegment>, f64, f64, FillRule, IpcSender<bool>),
     LineTo(Point2D<f32>),
     MoveTo(Point2D<f32>),
     PutImageData(Rect<u32>, IpcBytesReceiver),
@@ -65,6 +68,7 @@ pub enum Canvas2dMsg {
     SaveContext,
     StrokeRect(Rect<f32>),
     Stroke,
+    StrokePath(Vec<PathSegment>),
     SetFillStyle(FillOrStrokeStyle),
     SetStrokeStyle(FillOrStrokeStyle),
     SetLineWidth(f32),
@@ -78,6 +82,40 @@ pub enum Canvas2dMsg {
     SetShadowOffsetY(f64),
     SetShadowBlur(f64),
     SetShadowColor(RGBA),
+    SetFilter(Vec<FilterFunction>),
+}
+
+/// A step in building a path, the way the `CanvasPath` methods take them.
+#[derive(Clone, Debug, Deserialize, MallocSizeOf, Serialize)]
+pub enum PathSegment {
+    ClosePath,
+    MoveTo(Point2D<f32>),
+    LineTo(Point2D<f32>),
+    QuadraticCurveTo(Point2D<f32>, Point2D<f32>),
+    BezierCurveTo(Point2D<f32>, Point2D<f32>, Point2D<f32>),
+    ArcTo(Point2D<f32>, Point2D<f32>, f32),
+    Rect(Rect<f32>),
+    Arc(Point2D<f32>, f32, f32, f32, bool),
+    Ellipse(Point2D<f32>, f32, f32, f32, f32, f32, bool),
+    /// The segments of another path, with their points transformed.
+    AddPath(Vec<PathSegment>, Transform2D<f32>),
+}
+
+/// A CSS filter function of the `filter` attribute, with its argument
+/// resolved to a number of pixels, of degrees or to a factor.
+///
+/// <https://drafts.fxtf.org/filter-effects/#supported-filter-functions>
+#[derive(Clone, Copy, Debug, Deserialize, MallocSizeOf, PartialEq, Serialize)]
+pub enum FilterFunction {
+    Blur(f64),
+    Brightness(f64),
+    Contrast(f64),
+    Grayscale(f64),
+    HueRotate(f64),
+    Invert(f64),
+    Opacity(f64),
+    Saturate(f64),
+    Sepia(f64),
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -88,6 +126,8 @@ pub enum FromLayoutMsg {
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub enum FromScriptMsg {
     SendPixels(IpcSender<IpcSharedMemory>),
+    /// Copies the bitmap of the canvas over to the given placeholder canvas
+    Commit(CanvasId),
 }
 
 #[derive(Clone, Debug, Deserialize, MallocSizeOf, Serialize)]
@@ -156,6 +196,20 @@ impl RadialGradientStyle {
     }
 }
 
+#[derive(Clone, Debug, Deserialize, MallocSizeOf, Serialize)]
+pub struct ConicGradientStyle {
+    pub angle: f64,
+    pub x: f64,
+    pub y: f64,
+    pub stops: Vec<CanvasGradientStop>,
+}
+
+impl ConicGradientStyle {
+    pub fn new(angle: f64, x: f64, y: f64, stops: Vec<CanvasGradientStop>) -> ConicGradientStyle {
+        ConicGradientStyle { angle, x, y, stops }
+    }
+}
+
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct SurfaceStyle {
     pub surface_data: ByteBuf,
@@ -185,6 +239,7 @@ pub enum FillOrStrokeStyle {
     Color(RGBA),
     LinearGradient(LinearGradientStyle),
     RadialGradient(RadialGradientStyle),
+    ConicGradient(ConicGradientStyle),
     Surface(SurfaceStyle),
 }
 
@@ -349,6 +349,8 @@ pub enum WebGLCommand {
     GetParameterFloat4(ParameterFloat4, WebGLSender<[f32; 4]>),
     GetProgramValidateStatus(WebGLProgramId, WebGLSender<bool>),
     GetProgramActiveUniforms(WebGLProgramId, WebGLSender<i32>),
+    GetProgramActiveUniformBlocks(WebGLProgramId, WebGLSender<i32>),
+    GetProgramTransformFeedbackVaryings(WebGLProgramId, WebGLSender<(i32, u32)>),
     GetCurrentVertexAttrib(u32, WebGLSender<[f32; 4]>),
     GetTexParameterFloat(u32, TexParameterFloat, WebGLSender<f32>),
     GetTexParameterInt(u32, TexParameterInt, WebGLSender<i32>),
@@ -401,6 +403,65 @@ pub enum WebGLCommand {
         depth: bool,
         stencil: bool,
     },
+    TexImage3D {
+        target: u32,
+        level: u32,
+        internal_format: u32,
+        width: u32,
+        height: u32,
+        depth: u32,
+        format: u32,
+        data_type: u32,
+        unpacking_alignment: u32,
+        data: TruncatedDebug<IpcSharedMemory>,
+    },
+    TexSubImage3D {
+        target: u32,
+        level: u32,
+        xoffset: i32,
+        yoffset: i32,
+        zoffset: i32,
+        width: u32,
+        height: u32,
+        depth: u32,
+        format: u32,
+        data_type: u32,
+        unpacking_alignment: u32,
+        data: TruncatedDebug<IpcSharedMemory>,
+    },
+    CopyTexSubImage3D(u32, i32, i32, i32, i32, i32, i32, i32, i32),
+    TexStorage2D(u32, u32, u32, u32, u32),
+    TexStorage3D(u32, u32, u32, u32, u32, u32),
+    CreateSampler(WebGLSender<Option<WebGLSamplerId>>),
+    DeleteSampler(WebGLSamplerId),
+    BindSampler(u32, Option<WebGLSamplerId>),
+    SetSamplerParameterInt(WebGLSamplerId, u32, i32),
+    SetSamplerParameterFloat(WebGLSamplerId, u32, f32),
+    GetSamplerParameterInt(WebGLSamplerId, u32, WebGLSender<i32>),
+    GetSamplerParameterFloat(WebGLSamplerId, u32, WebGLSender<f32>),
+    CreateTransformFeedback(WebGLSender<Option<WebGLTransformFeedbackId>>),
+    DeleteTransformFeedback(WebGLTransformFeedbackId),
+    BindTransformFeedback(u32, Option<WebGLTransformFeedbackId>),
+    BeginTransformFeedback(u32),
+    EndTransformFeedback,
+    PauseTransformFeedback,
+    ResumeTransformFeedback,
+    TransformFeedbackVaryings(WebGLProgramId, Vec<String>, u32),
+    GetTransformFeedbackVarying(WebGLProgramId, u32, WebGLSender<(i32, u32, String)>),
+    BindBufferBase(u32, u32, Option<WebGLBufferId>),
+    BindBufferRange(u32, u32, Option<WebGLBufferId>, i64, i64),
+    GetUniformBlockIndex(WebGLProgramId, String, WebGLSender<u32>),
+    GetActiveUniformBlockName(WebGLProgramId, u32, WebGLSender<String>),
+    GetActiveUniformBlockParameter(WebGLProgramId, u32, u32, WebGLSender<Vec<i32>>),
+    UniformBlockBinding(WebGLProgramId, u32, u32),
+    GetFragDataLocation(WebGLProgramId, String, WebGLSender<i32>),
+    Uniform1ui(i32, u32),
+    Uniform2ui(i32, u32, u32),
+    Uniform3ui(i32, u32, u32, u32),
+    Uniform4ui(i32, u32, u32, u32, u32),
+    VertexAttribI(u32, i32, i32, i32, i32),
+    VertexAttribUI(u32, u32, u32, u32, u32),
+    VertexAttribIPointer(u32, i32, u32, i32, u32),
 }
 
 macro_rules! define_resource_id {
@@ -474,6 +535,8 @@ define_resource_id!(WebGLTextureId);
 define_resource_id!(WebGLProgramId);
 define_resource_id!(WebGLShaderId);
 define_resource_id!(WebGLVertexArrayId);
+define_resource_id!(WebGLSamplerId);
+define_resource_id!(WebGLTransformFeedbackId);
 
 #[derive(
     Clone, Copy, Debug, Deserialize, Eq, Hash, MallocSizeOf, Ord, PartialEq, PartialOrd, Serialize,
@@ -833,4 +896,11 @@ pub struct GLLimits {
     pub max_varying_vectors: u32,
     pub max_vertex_texture_image_units: u32,
     pub max_vertex_uniform_vectors: u32,
+    /// The limits below are only queried for WebGL 2 contexts, they are 0
+    /// otherwise.
+    pub max_3d_texture_size: u32,
+    pub max_array_texture_layers: u32,
+    pub max_uniform_buffer_bindings: u32,
+    pub uniform_buffer_offset_alignment: u32,
+    pub max_transform_feedback_separate_attribs: u32,
 }
@@ -6,7 +6,8 @@ use crate::compositor_thread::CompositorReceiver;
 use crate::compositor_thread::{InitialCompositorState, Msg};
 #[cfg(feature = "gl")]
 use crate::gl;
-use crate::touch::{TouchAction, TouchHandler};
+use crate::pdf::{self, POINTS_PER_PX};
+use crate::touch::{TouchAction, TouchHandler, TouchState};
 use crate::windowing::{
     self, EmbedderCoordinates, MouseWindowEvent, WebRenderDebugOption, WindowMethods,
 };
@@ -17,7 +18,7 @@ use embedder_traits::Cursor;
 use euclid::{TypedPoint2D, TypedScale, TypedVector2D};
 use gfx_traits::Epoch;
 #[cfg(feature = "gl")]
-use image::{DynamicImage, ImageFormat};
+use image::{DynamicImage, ImageFormat, RgbImage};
 use ipc_channel::ipc;
 use libc::c_void;
 use msg::constellation_msg::{PipelineId, PipelineIndex, PipelineNamespaceId};
@@ -35,8 +36,10 @@ use servo_geometry::DeviceIndependentPixel;
 use std::collections::HashMap;
 use std::env;
 use std::fs::{create_dir_all, File};
-use std::io::Write;
+use std::io::{self, Write};
+use std::mem;
 use std::num::NonZeroU32;
+use std::path::Path;
 use std::rc::Rc;
 use style_traits::viewport::ViewportConstraints;
 use style_traits::{CSSPixel, DevicePixel, PinchZoomFactor};
@@ -44,7 +47,8 @@ use time::{now, precise_time_ns, precise_time_s};
 use webrender_api::{
     self, DeviceIntPoint, DevicePoint, FramebufferIntSize, HitTestFlags, HitTestResult,
 };
-use webrender_api::{LayoutVector2D, ScrollLocation};
+use webrender_api::{ExternalScrollId, LayoutPoint, LayoutVector2D};
+use webrender_api::{ScrollClamping, ScrollLocation};
 use webvr_traits::WebVRMainThreadHeartbeat;
 
 #[derive(Debug, PartialEq)]
@@ -64,6 +68,9 @@ enum NotReadyToPaint {
 const MAX_ZOOM: f32 = 8.0;
 const MIN_ZOOM: f32 = 0.1;
 
+/// How long a smooth scroll lasts, in nanoseconds.
+const SMOOTH_SCROLL_DURATION_NS: u64 = 300_000_000;
+
 trait ConvertPipelineIdFromWebRender {
     fn from_webrender(&self) -> PipelineId;
 }
@@ -166,6 +173,16 @@ pub struct IOCompositor<Window: WindowMethods> {
     /// Whether we're waiting on a recomposite after dispatching a scroll.
     waiting_for_results_of_scroll: bool,
 
+    /// Whether a scroll gesture with distinct start and end phases is in progress.
+    scroll_gesture_in_progress: bool,
+
+    /// Whether the user finished scrolling since the pending scroll events were last
+    /// processed, so that layout can snap the scroll frames.
+    scroll_ended: bool,
+
+    /// The scroll frames script asked to be scrolled smoothly.
+    smooth_scrolls: Vec<SmoothScroll>,
+
     /// Used by the logic that determines when it is safe to output an
     /// image for the reftest framework.
     ready_to_save_state: ReadyState,
@@ -205,6 +222,17 @@ struct ScrollZoomEvent {
     event_count: u32,
 }
 
+/// A scroll frame being scrolled smoothly to an offset.
+struct SmoothScroll {
+    scroll_id: ExternalScrollId,
+    /// The offset the scroll frame had when the scroll started.
+    start_offset: LayoutVector2D,
+    /// The offset the scroll frame gets when the scroll ends.
+    end_offset: LayoutVector2D,
+    /// When the scroll started, in nanoseconds.
+    start_time: u64,
+}
+
 #[derive(Debug, PartialEq)]
 enum CompositionRequest {
     NoCompositingNecessary,
@@ -251,7 +279,8 @@ enum CompositeTarget {
     /// Compose as normal, but also return a PNG of the composed output
     WindowAndPng,
 
-    /// Compose to a PNG, write it to disk, and then exit the browser (used for reftests)
+    /// Compose to a PNG, or to a PDF if the output file has a `.pdf` extension, write it to
+    /// disk, and then exit the browser (used for reftests)
     PngFile,
 }
 
@@ -273,6 +302,9 @@ impl<Window: WindowMethods> IOCompositor<Window> {
             touch_handler: TouchHandler::new(),
             pending_scroll_zoom_events: Vec::new(),
             waiting_for_results_of_scroll: false,
+            scroll_gesture_in_progress: false,
+            scroll_ended: false,
+            smooth_scrolls: Vec::new(),
             composite_target,
             shutdown_state: ShutdownState::NotShuttingDown,
             page_zoom: TypedScale::new(1.0),
@@ -364,7 +396,7 @@ impl<Window: WindowMethods> IOCompositor<Window> {
 
             (Msg::SetFrameTree(frame_tree), ShutdownState::NotShuttingDown) => {
                 self.set_frame_tree(&frame_tree);
-                self.send_viewport_rects();
+                self.send_viewport_rects(false);
             },
 
             (Msg::Recomposite(reason), ShutdownState::NotShuttingDown) => {
@@ -375,6 +407,10 @@ impl<Window: WindowMethods> IOCompositor<Window> {
                 self.touch_handler.on_event_processed(result);
             },
 
+            (Msg::SmoothScroll(scroll_state), ShutdownState::NotShuttingDown) => {
+                self.start_smooth_scroll(scroll_state);
+            },
+
             (Msg::CreatePng(reply), ShutdownState::NotShuttingDown) => {
                 let res = self.composite_specific_target(CompositeTarget::WindowAndPng);
                 if let Err(ref e) = res {
@@ -450,6 +486,23 @@ impl<Window: WindowMethods> IOCompositor<Window> {
                 }
             },
 
+            (
+                Msg::WebDriverMouseButtonEvent(mouse_event_type, mouse_button, x, y),
+                ShutdownState::NotShuttingDown,
+            ) => {
+                let point = TypedPoint2D::new(x, y) * self.device_pixels_per_page_px();
+                self.on_mouse_window_event_class(match mouse_event_type {
+                    MouseEventType::Click => MouseWindowEvent::Click(mouse_button, point),
+                    MouseEventType::MouseDown => MouseWindowEvent::MouseDown(mouse_button, point),
+                    MouseEventType::MouseUp => MouseWindowEvent::MouseUp(mouse_button, point),
+                });
+            },
+
+            (Msg::WebDriverMouseMoveEvent(x, y), ShutdownState::NotShuttingDown) => {
+                let point = TypedPoint2D::new(x, y) * self.device_pixels_per_page_px();
+                self.on_mouse_window_move_event_class(point);
+            },
+
             (Msg::PendingPaintMetric(pipeline_id, epoch), _) => {
                 self.pending_paint_metrics.insert(pipeline_id, epoch);
             },
@@ -785,9 +838,13 @@ impl<Window: WindowMethods> IOCompositor<Window> {
     fn on_touch_up(&mut self, identifier: TouchId, point: DevicePoint) {
         self.send_touch_event(TouchEventType::Up, identifier, point);
 
+        let was_panning = self.touch_handler.state == TouchState::Panning;
         if let TouchAction::Click = self.touch_handler.on_touch_up(identifier, point) {
             self.simulate_mouse_click(point);
         }
+        if was_panning && self.touch_handler.state == TouchState::Nothing {
+            self.end_scroll();
+        }
     }
 
     fn on_touch_cancel(&mut self, identifier: TouchId, point: DevicePoint) {
@@ -812,17 +869,38 @@ impl<Window: WindowMethods> IOCompositor<Window> {
         phase: TouchEventType,
     ) {
         match phase {
-            TouchEventType::Move => self.on_scroll_window_event(delta, cursor),
+            TouchEventType::Move => {
+                // Scrolls without a start and an end phase, like the ones of mouse
+                // wheels and keys, are over as soon as they happen.
+                if !self.scroll_gesture_in_progress {
+                    self.scroll_ended = true;
+                }
+                self.on_scroll_window_event(delta, cursor)
+            },
             TouchEventType::Up | TouchEventType::Cancel => {
+                self.scroll_gesture_in_progress = false;
+                self.scroll_ended = true;
                 self.on_scroll_window_event(delta, cursor);
             },
             TouchEventType::Down => {
+                self.scroll_gesture_in_progress = true;
                 self.on_scroll_window_event(delta, cursor);
             },
         }
     }
 
+    /// Lets layout snap the scroll frames once the user is done scrolling them.
+    fn end_scroll(&mut self) {
+        if self.pending_scroll_zoom_events.is_empty() {
+            self.send_viewport_rects(true);
+        } else {
+            self.scroll_ended = true;
+        }
+    }
+
     fn on_scroll_window_event(&mut self, scroll_location: ScrollLocation, cursor: DeviceIntPoint) {
+        // Scrolling by hand stops the smooth scrolls.
+        self.smooth_scrolls.clear();
         self.pending_scroll_zoom_events.push(ScrollZoomEvent {
             magnification: 1.0,
             scroll_location: scroll_location,
@@ -909,7 +987,8 @@ impl<Window: WindowMethods> IOCompositor<Window> {
         }
 
         if had_events {
-            self.send_viewport_rects();
+            let scroll_ended = mem::replace(&mut self.scroll_ended, false);
+            self.send_viewport_rects(scroll_ended);
         }
     }
 
@@ -923,7 +1002,10 @@ impl<Window: WindowMethods> IOCompositor<Window> {
                 pipeline_ids.push(*pipeline_id);
             }
         }
-        let animation_state = if pipeline_ids.is_empty() && !self.webvr_heartbeats_racing() {
+        let animation_state = if pipeline_ids.is_empty() &&
+            self.smooth_scrolls.is_empty() &&
+            !self.webvr_heartbeats_racing()
+        {
             windowing::AnimationState::Idle
         } else {
             windowing::AnimationState::Animating
@@ -932,6 +1014,66 @@ impl<Window: WindowMethods> IOCompositor<Window> {
         for pipeline_id in &pipeline_ids {
             self.tick_animations_for_pipeline(*pipeline_id)
         }
+        self.tick_smooth_scrolls();
+    }
+
+    fn start_smooth_scroll(&mut self, scroll_state: ScrollState) {
+        let end_offset = LayoutVector2D::from_untyped(&scroll_state.scroll_offset);
+        let start_offset = self
+            .webrender_api
+            .get_scroll_node_state(self.webrender_document)
+            .into_iter()
+            .find(|node_state| node_state.id == scroll_state.scroll_id)
+            .map_or(end_offset, |node_state| node_state.scroll_offset);
+
+        // A new scroll of the frame replaces the one in progress.
+        self.smooth_scrolls
+            .retain(|scroll| scroll.scroll_id != scroll_state.scroll_id);
+        self.smooth_scrolls.push(SmoothScroll {
+            scroll_id: scroll_state.scroll_id,
+            start_offset,
+            end_offset,
+            start_time: precise_time_ns(),
+        });
+        self.tick_smooth_scrolls();
+    }
+
+    /// Moves the smooth scrolls along, and tells layout where the scroll
+    /// frames got to.
+    fn tick_smooth_scrolls(&mut self) {
+        if self.smooth_scrolls.is_empty() {
+            return;
+        }
+
+        let now = precise_time_ns();
+        let mut txn = webrender_api::Transaction::new();
+        let mut finished = false;
+        for scroll in mem::replace(&mut self.smooth_scrolls, vec![]) {
+            let progress = (now - scroll.start_time) as f32 / SMOOTH_SCROLL_DURATION_NS as f32;
+            let offset = if progress >= 1. {
+                finished = true;
+                scroll.end_offset
+            } else {
+                scroll
+                    .start_offset
+                    .lerp(scroll.end_offset, ease_in_out(progress))
+            };
+            txn.scroll_node_with_id(
+                LayoutPoint::new(-offset.x, -offset.y),
+                scroll.scroll_id,
+                ScrollClamping::ToContentBounds,
+            );
+            if progress < 1. {
+                self.smooth_scrolls.push(scroll);
+            }
+        }
+        txn.generate_frame();
+        self.webrender_api
+            .send_transaction(self.webrender_document, txn);
+        self.waiting_for_results_of_scroll = true;
+
+        // Layout snaps the scroll frames once they stop.
+        self.send_viewport_rects(finished);
     }
 
     fn webvr_heartbeats_racing(&self) -> bool {
@@ -1029,7 +1171,7 @@ impl<Window: WindowMethods> IOCompositor<Window> {
         });
     }
 
-    fn send_viewport_rects(&self) {
+    fn send_viewport_rects(&self, scroll_ended: bool) {
         let mut scroll_states_per_pipeline = HashMap::new();
         for scroll_layer_state in self
             .webrender_api
@@ -1048,7 +1190,7 @@ impl<Window: WindowMethods> IOCompositor<Window> {
 
         for (pipeline_id, scroll_states) in scroll_states_per_pipeline {
             if let Some(pipeline) = self.pipeline(pipeline_id.from_webrender()) {
-                let msg = LayoutControlMsg::SetScrollStates(scroll_states);
+                let msg = LayoutControlMsg::SetScrollStates(scroll_states, scroll_ended);
                 let _ = pipeline.layout_chan.send(msg);
             }
         }
@@ -1056,6 +1198,9 @@ impl<Window: WindowMethods> IOCompositor<Window> {
 
     // Check if any pipelines currently have active animations or animation callbacks.
     fn animations_active(&self) -> bool {
+        if !self.smooth_scrolls.is_empty() {
+            return true;
+        }
         for (_, details) in &self.pipeline_details {
             // If animations are currently running, then don't bother checking
             // with the constellation if the output image is stable.
@@ -1281,6 +1426,12 @@ impl<Window: WindowMethods> IOCompositor<Window> {
                     None,
                     self.time_profiler_chan.clone(),
                     || match opts::get().output_file.as_ref() {
+                        Some(path) if path.ends_with(".pdf") => {
+                            let img = gl::draw_img(gl, rt_info, width, height);
+                            if let Err(e) = self.write_pdf(Path::new(path), img) {
+                                error!("Failed to save {} ({}).", path, e);
+                            }
+                        },
                         Some(path) => match File::create(path) {
                             Ok(mut file) => {
                                 let img = gl::draw_img(gl, rt_info, width, height);
@@ -1436,6 +1587,36 @@ impl<Window: WindowMethods> IOCompositor<Window> {
             .send_transaction(self.webrender_document, txn);
     }
 
+    /// Writes the current output of the compositor to `path` as a PDF document.
+    pub fn print_to_pdf(&mut self, path: &Path) {
+        let img = match self.composite_specific_target(CompositeTarget::WindowAndPng) {
+            Ok(Some(img)) => img,
+            Ok(None) => return warn!("Printing is not supported without OpenGL."),
+            Err(e) => return warn!("Unable to print to {} ({:?}).", path.display(), e),
+        };
+        let img = match RgbImage::from_raw(img.width, img.height, img.bytes.to_vec()) {
+            Some(img) => img,
+            None => return warn!("Unexpected size of the output to print."),
+        };
+        if let Err(e) = self.write_pdf(path, img) {
+            error!("Failed to save {} ({}).", path.display(), e);
+        }
+    }
+
+    /// Writes `img`, the composited output, to `path` as a PDF document whose page is the size
+    /// of the output in CSS pixels.
+    fn write_pdf(&self, path: &Path, img: RgbImage) -> io::Result<()> {
+        let points_per_device_px = POINTS_PER_PX / self.hidpi_factor().get();
+        let (width, height) = img.dimensions();
+        let mut file = File::create(path)?;
+        pdf::write_pdf(
+            &mut file,
+            img,
+            width as f32 * points_per_device_px,
+            height as f32 * points_per_device_px,
+        )
+    }
+
     pub fn capture_webrender(&mut self) {
         let capture_id = now().to_timespec().sec.to_string();
         let available_path = [env::current_dir(), Ok(env::temp_dir())]
@@ -1482,6 +1663,15 @@ impl<Window: WindowMethods> IOCompositor<Window> {
     }
 }
 
+/// The cubic ease-in-out timing function, which smooth scrolls follow.
+fn ease_in_out(progress: f32) -> f32 {
+    if progress < 0.5 {
+        4. * progress * progress * progress
+    } else {
+        1. - (2. - 2. * progress).powi(3) / 2.
+    }
+}
+
 /// Why we performed a composite. This is used for debugging.
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub enum CompositingReason {
@@ -20,6 +20,7 @@ mod compositor;
 pub mod compositor_thread;
 #[cfg(feature = "gl")]
 mod gl;
+mod pdf;
 mod touch;
 pub mod windowing;
 
@@ -0,0 +1,85 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Writes the composited output as a PDF document, whose only page shows an
+//! image of it.
+
+use image::{DynamicImage, ImageFormat, RgbImage};
+use std::io::{self, Write};
+
+/// The number of PDF points in a CSS pixel, PDF points being 1/72 of an inch.
+pub const POINTS_PER_PX: f32 = 0.75;
+
+/// Writes a PDF document showing `image` on one page of `page_width` by
+/// `page_height` points, the image being embedded as a JPEG.
+pub fn write_pdf<W: Write>(
+    out: &mut W,
+    image: RgbImage,
+    page_width: f32,
+    page_height: f32,
+) -> io::Result<()> {
+    let (width, height) = image.dimensions();
+    let mut jpeg = vec![];
+    DynamicImage::ImageRgb8(image)
+        .write_to(&mut jpeg, ImageFormat::JPEG)
+        .map_err(|e| io::Error::new(io::ErrorKind::Other, e.to_string()))?;
+
+    let contents = format!("q {} 0 0 {} 0 0 cm /Im0 Do Q", page_width, page_height);
+    let objects: Vec<Vec<u8>> = vec![
+        b"<< /Type /Catalog /Pages 2 0 R >>".to_vec(),
+        b"<< /Type /Pages /Kids [3 0 R] /Count 1 >>".to_vec(),
+        format!(
+            "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {} {}] \
+             /Resources << /XObject << /Im0 4 0 R >> >> /Contents 5 0 R >>",
+            page_width, page_height
+        )
+        .into_bytes(),
+        stream(
+            &format!(
+                "/Type /XObject /Subtype /Image /Width {} /Height {} \
+                 /ColorSpace /DeviceRGB /BitsPerComponent 8 /Filter /DCTDecode ",
+                width, height
+            ),
+            &jpeg,
+        ),
+        stream("", contents.as_bytes()),
+    ];
+
+    let mut pdf = b"%PDF-1.4\n".to_vec();
+    let mut offsets = vec![];
+    for (index, object) in objects.iter().enumerate() {
+        offsets.push(pdf.len());
+        write!(&mut pdf, "{} 0 obj\n", index + 1)?;
+        pdf.extend_from_slice(object);
+        pdf.extend_from_slice(b"\nendobj\n");
+    }
+
+    // Each entry of the cross-reference table must be exactly 20 bytes long.
+    let xref_offset = pdf.len();
+    write!(
+        &mut pdf,
+        "xref\n0 {}\n0000000000 65535 f \n",
+        objects.len() + 1
+    )?;
+    for offset in offsets {
+        write!(&mut pdf, "{:010} 00000 n \n", offset)?;
+    }
+    write!(
+        &mut pdf,
+        "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{}\n%%EOF\n",
+        objects.len() + 1,
+        xref_offset
+    )?;
+
+    out.write_all(&pdf)
+}
+
+/// A stream object, `entries` being the other entries of its dictionary
+/// followed by a space, if any.
+fn stream(entries: &str, data: &[u8]) -> Vec<u8> {
+    let mut object = format!("<< {}/Length {} >>\nstream\n", entries, data.len()).into_bytes();
+    object.extend_from_slice(data);
+    object.extend_from_slice(b"\nendstream");
+    object
+}
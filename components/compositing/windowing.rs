@@ -4,16 +4,17 @@
 
 //! Abstract windowing methods. The concrete implementations of these can be found in `platform/`.
 
-use embedder_traits::EventLoopWaker;
+use embedder_traits::{EmbedderClipboard, EventLoopWaker, SessionHistory};
 use euclid::TypedScale;
 #[cfg(feature = "gl")]
 use gleam::gl;
-use keyboard_types::KeyboardEvent;
+use keyboard_types::{CompositionEvent, KeyboardEvent};
 use msg::constellation_msg::{PipelineId, TopLevelBrowsingContextId, TraversalDirection};
-use script_traits::{MouseButton, TouchEventType, TouchId};
+use script_traits::{GamepadEvent, MouseButton, TouchEventType, TouchId};
 use servo_geometry::DeviceIndependentPixel;
 use servo_url::ServoUrl;
 use std::fmt::{Debug, Error, Formatter};
+use std::path::PathBuf;
 #[cfg(feature = "gl")]
 use std::rc::Rc;
 use std::time::Duration;
@@ -82,10 +83,17 @@ pub enum WindowEvent {
     ExitFullScreen(TopLevelBrowsingContextId),
     /// Sent when a key input state changes
     Keyboard(KeyboardEvent),
+    /// Sent when the input method starts, updates or ends a composition
+    IMEComposition(CompositionEvent),
+    /// Sent when the state of a gamepad changes
+    Gamepad(GamepadEvent),
     /// Sent when Ctr+R/Apple+R is called to reload the current page.
     Reload(TopLevelBrowsingContextId),
     /// Create a new top level browsing context
     NewBrowser(ServoUrl, TopLevelBrowsingContextId),
+    /// Create a new top level browsing context with a saved session history,
+    /// loading its current entry
+    RestoreBrowser(SessionHistory, TopLevelBrowsingContextId),
     /// Close a top level browsing context
     CloseBrowser(TopLevelBrowsingContextId),
     /// Panic a top level browsing context.
@@ -97,6 +105,8 @@ pub enum WindowEvent {
     ToggleWebRenderDebug(WebRenderDebugOption),
     /// Capture current WebRender
     CaptureWebRender,
+    /// Write the current output to the given file as a PDF document.
+    PrintToPDF(PathBuf),
     /// Toggle sampling profiler with the given sampling rate and max duration.
     ToggleSamplingProfiler(Duration, Duration),
 }
@@ -108,6 +118,8 @@ impl Debug for WindowEvent {
             WindowEvent::Refresh => write!(f, "Refresh"),
             WindowEvent::Resize => write!(f, "Resize"),
             WindowEvent::Keyboard(..) => write!(f, "Keyboard"),
+            WindowEvent::IMEComposition(..) => write!(f, "IMEComposition"),
+            WindowEvent::Gamepad(..) => write!(f, "Gamepad"),
             WindowEvent::AllowNavigationResponse(..) => write!(f, "AllowNavigationResponse"),
             WindowEvent::LoadUrl(..) => write!(f, "LoadUrl"),
             WindowEvent::MouseWindowEventClass(..) => write!(f, "Mouse"),
@@ -121,11 +133,13 @@ impl Debug for WindowEvent {
             WindowEvent::Quit => write!(f, "Quit"),
             WindowEvent::Reload(..) => write!(f, "Reload"),
             WindowEvent::NewBrowser(..) => write!(f, "NewBrowser"),
+            WindowEvent::RestoreBrowser(..) => write!(f, "RestoreBrowser"),
             WindowEvent::SendError(..) => write!(f, "SendError"),
             WindowEvent::CloseBrowser(..) => write!(f, "CloseBrowser"),
             WindowEvent::SelectBrowser(..) => write!(f, "SelectBrowser"),
             WindowEvent::ToggleWebRenderDebug(..) => write!(f, "ToggleWebRenderDebug"),
             WindowEvent::CaptureWebRender => write!(f, "CaptureWebRender"),
+            WindowEvent::PrintToPDF(..) => write!(f, "PrintToPDF"),
             WindowEvent::ToggleSamplingProfiler(..) => write!(f, "ToggleSamplingProfiler"),
             WindowEvent::ExitFullScreen(..) => write!(f, "ExitFullScreen"),
         }
@@ -164,6 +178,11 @@ pub trait WindowMethods {
         _: &mut Vec<Box<WebVRMainThreadHeartbeat>>,
     ) {
     }
+    /// Returns the clipboard the pages read from and write to, if the
+    /// platform has one.
+    fn create_clipboard(&self) -> Option<Box<dyn EmbedderClipboard>> {
+        None
+    }
 }
 
 #[derive(Clone, Copy, Debug)]
@@ -50,6 +50,9 @@ pub struct Opts {
     /// visualizing the traces as a timeline.
     pub time_profiler_trace_path: Option<String>,
 
+    /// The format of the profiler traces dumped to `time_profiler_trace_path`.
+    pub time_profiler_trace_format: TraceFormat,
+
     /// `None` to disable the memory profiler or `Some` with an interval in seconds to enable it
     /// and cause it to produce output on that interval (`-m`).
     pub mem_profiler_period: Option<f64>,
@@ -484,6 +487,14 @@ pub enum OutputOptions {
     Stdout(f64),
 }
 
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub enum TraceFormat {
+    /// A self-contained HTML timeline.
+    Html,
+    /// A JSON file in the Chrome trace event format.
+    Chrome,
+}
+
 fn args_fail(msg: &str) -> ! {
     writeln!(io::stderr(), "{}", msg).unwrap();
     process::exit(1)
@@ -549,6 +560,7 @@ pub fn default_opts() -> Opts {
         device_pixels_per_px: None,
         time_profiling: None,
         time_profiler_trace_path: None,
+        time_profiler_trace_format: TraceFormat::Html,
         mem_profiler_period: None,
         nonincremental_layout: false,
         userscripts: None,
@@ -612,7 +624,12 @@ pub fn from_cmdline_args(args: &[String]) -> ArgumentParsingResult {
     let mut opts = Options::new();
     opts.optflag("c", "cpu", "CPU painting");
     opts.optflag("g", "gpu", "GPU painting");
-    opts.optopt("o", "output", "Output file", "output.png");
+    opts.optopt(
+        "o",
+        "output",
+        "Output file, a PDF document if it ends with .pdf and a PNG image otherwise",
+        "output.png",
+    );
     opts.optopt("s", "size", "Size of tiles", "512");
     opts.optopt("", "device-pixel-ratio", "Device pixels per px", "");
     opts.optflagopt(
@@ -629,6 +646,13 @@ pub fn from_cmdline_args(args: &[String]) -> ArgumentParsingResult {
         "Path to dump a self-contained HTML timeline of profiler traces",
         "",
     );
+    opts.optopt(
+        "",
+        "profiler-trace-format",
+        "Format of the profiler traces: a self-contained HTML timeline, or a \
+         Chrome trace event file to load in chrome://tracing or Perfetto",
+        "html|chrome",
+    );
     opts.optflagopt(
         "m",
         "memory-profile",
@@ -867,6 +891,16 @@ pub fn from_cmdline_args(args: &[String]) -> ArgumentParsingResult {
         }
     }
 
+    let time_profiler_trace_format = match opt_match.opt_str("profiler-trace-format") {
+        None => TraceFormat::Html,
+        Some(ref format) if format == "html" => TraceFormat::Html,
+        Some(ref format) if format == "chrome" => TraceFormat::Chrome,
+        Some(format) => args_fail(&format!(
+            "Error parsing option: --profiler-trace-format ({})",
+            format
+        )),
+    };
+
     let mem_profiler_period = opt_match.opt_default("m", "5").map(|period| {
         period
             .parse()
@@ -989,6 +1023,7 @@ pub fn from_cmdline_args(args: &[String]) -> ArgumentParsingResult {
         device_pixels_per_px: device_pixels_per_px,
         time_profiling: time_profiling,
         time_profiler_trace_path: opt_match.opt_str("profiler-trace-path"),
+        time_profiler_trace_format: time_profiler_trace_format,
         mem_profiler_period: mem_profiler_period,
         nonincremental_layout: nonincremental_layout,
         userscripts: opt_match.opt_default("userscripts", ""),
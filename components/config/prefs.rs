@@ -142,6 +142,9 @@ mod gen {
         gen_accessors = PREF_ACCESSORS,
         // tree of structs to generate
         gen_types = Prefs {
+            accessibility: {
+                enabled: bool,
+            },
             browser: {
                 display: {
                     #[serde(default = "white")]
@@ -169,6 +172,9 @@ mod gen {
                     #[serde(rename = "dom.canvas-text.enabled")]
                     enabled: bool,
                 },
+                clipboard: {
+                    enabled: bool,
+                },
                 composition_event: {
                     #[serde(rename = "dom.compositionevent.enabled")]
                     enabled: bool,
@@ -190,6 +196,15 @@ mod gen {
                 gamepad: {
                     enabled: bool,
                 },
+                indexeddb: {
+                    enabled: bool,
+                },
+                intersection_observer: {
+                    enabled: bool,
+                },
+                media_source: {
+                    enabled: bool,
+                },
                 microdata: {
                     testing: {
                         enabled: bool,
@@ -204,6 +219,9 @@ mod gen {
                 mutation_observer: {
                     enabled: bool,
                 },
+                notifications: {
+                    enabled: bool,
+                },
                 offscreen_canvas: {
                     enabled: bool,
                 },
@@ -213,6 +231,9 @@ mod gen {
                         allowed_in_nonsecure_contexts: bool,
                     }
                 },
+                resize_observer: {
+                    enabled: bool,
+                },
                 serviceworker: {
                     enabled: bool,
                     timeout_seconds: i64,
@@ -271,6 +292,9 @@ mod gen {
                     #[serde(default)]
                     enabled: bool,
                 },
+                web_animations: {
+                    enabled: bool,
+                },
                 webgl: {
                     dom_to_texture: {
                         enabled: bool,
@@ -279,6 +303,9 @@ mod gen {
                 webgl2: {
                     enabled: bool,
                 },
+                webgpu: {
+                    enabled: bool,
+                },
                 webrtc: {
                     #[serde(default)]
                     enabled: bool,
@@ -291,6 +318,8 @@ mod gen {
                 webxr: {
                     #[serde(default)]
                     enabled: bool,
+                    #[serde(default)]
+                    intern_transforms: bool,
                 },
                 worklet: {
                     blockingsleep: {
@@ -430,6 +459,10 @@ mod gen {
                 columns: {
                     enabled: bool,
                 },
+                scroll_snap: {
+                    #[serde(rename = "layout.scroll-snap.enabled")]
+                    enabled: bool,
+                },
                 #[serde(default = "default_layout_threads")]
                 threads: i64,
                 viewport: {
@@ -449,6 +482,11 @@ mod gen {
                 http_cache: {
                     #[serde(rename = "network.http-cache.disabled")]
                     disabled: bool,
+                    #[serde(rename = "network.http-cache.size")]
+                    size: i64,
+                },
+                http2: {
+                    enabled: bool,
                 },
                 mime: {
                     sniff: bool,
@@ -469,6 +507,9 @@ mod gen {
                     #[serde(rename = "shell.native-titlebar.enabled")]
                     enabled: bool,
                 },
+                restore_session: {
+                    enabled: bool,
+                },
                 searchpage: String,
             },
             webgl: {
@@ -95,6 +95,7 @@ use crate::browsingcontext::{
 };
 use crate::event_loop::EventLoop;
 use crate::network_listener::NetworkListener;
+use crate::permission_store::PermissionStore;
 use crate::pipeline::{InitialPipelineState, Pipeline};
 use crate::session_history::{
     JointSessionHistory, NeedsToReload, SessionHistoryChange, SessionHistoryDiff,
@@ -107,13 +108,12 @@ use canvas::canvas_paint_thread::CanvasPaintThread;
 use canvas::webgl_thread::WebGLThreads;
 use canvas_traits::canvas::CanvasId;
 use canvas_traits::canvas::CanvasMsg;
-use clipboard::{ClipboardContext, ClipboardProvider};
 use compositing::compositor_thread::CompositorProxy;
 use compositing::compositor_thread::Msg as ToCompositorMsg;
 use compositing::SendableFrameTree;
 use crossbeam_channel::{unbounded, Receiver, Sender};
 use devtools_traits::{ChromeToDevtoolsControlMsg, DevtoolsControlMsg};
-use embedder_traits::{Cursor, EmbedderMsg, EmbedderProxy};
+use embedder_traits::{Cursor, EmbedderClipboard, EmbedderMsg, EmbedderProxy, SessionHistory};
 use euclid::{Size2D, TypedScale, TypedSize2D};
 use gfx::font_cache_thread::FontCacheThread;
 use gfx_traits::Epoch;
@@ -121,7 +121,7 @@ use ipc_channel::ipc::{self, IpcReceiver, IpcSender};
 use ipc_channel::router::ROUTER;
 use ipc_channel::Error as IpcError;
 use keyboard_types::webdriver::Event as WebDriverInputEvent;
-use keyboard_types::KeyboardEvent;
+use keyboard_types::{CompositionEvent, KeyboardEvent};
 use layout_traits::LayoutThreadFactory;
 use log::{Level, LevelFilter, Log, Metadata, Record};
 use msg::constellation_msg::{BackgroundHangMonitorRegister, HangMonitorAlert, SamplerControlMsg};
@@ -129,6 +129,7 @@ use msg::constellation_msg::{
     BrowsingContextId, HistoryStateId, PipelineId, TopLevelBrowsingContextId,
 };
 use msg::constellation_msg::{PipelineNamespace, PipelineNamespaceId, TraversalDirection};
+use net_traits::indexeddb_thread::IndexedDBThreadMsg;
 use net_traits::pub_domains::reg_host;
 use net_traits::request::RequestInit;
 use net_traits::storage_thread::{StorageThreadMsg, StorageType};
@@ -142,11 +143,11 @@ use script_traits::{
 use script_traits::{
     ConstellationControlMsg, ConstellationMsg as FromCompositorMsg, DiscardBrowsingContext,
 };
-use script_traits::{DocumentActivity, DocumentState, LayoutControlMsg, LoadData};
+use script_traits::{DocumentActivity, DocumentState, GamepadEvent, LayoutControlMsg, LoadData};
 use script_traits::{
     IFrameLoadInfo, IFrameLoadInfoWithData, IFrameSandboxState, TimerSchedulerMsg,
 };
-use script_traits::{IFrameSizeMsg, WindowSizeData, WindowSizeType};
+use script_traits::{IFrameSizeMsg, PermissionGrant, WindowSizeData, WindowSizeType};
 use script_traits::{LayoutMsg as FromLayoutMsg, ScriptMsg as FromScriptMsg, ScriptThreadFactory};
 use script_traits::{SWManagerMsg, ScopeThings, UpdatePipelineIdReason, WebDriverCommandMsg};
 use serde::{Deserialize, Serialize};
@@ -165,6 +166,7 @@ use std::sync::Arc;
 use std::thread;
 use style_traits::viewport::ViewportConstraints;
 use style_traits::CSSPixel;
+use webgpu::{wgt, WebGPU, WebGPURequest, WebGPUResponseResult};
 use webvr_traits::{WebVREvent, WebVRMsg};
 
 type PendingApprovalNavigations = HashMap<PipelineId, (LoadData, bool)>;
@@ -339,12 +341,15 @@ pub struct Constellation<Message, LTF, STF> {
     /// The size of the top-level window.
     window_size: WindowSizeData,
 
-    /// Means of accessing the clipboard
-    clipboard_ctx: Option<ClipboardContext>,
+    /// Means of accessing the clipboard, provided by the embedder.
+    clipboard: Option<Box<dyn EmbedderClipboard>>,
 
     /// Bits of state used to interact with the webdriver implementation
     webdriver: WebDriverData,
 
+    /// The permissions the user gave to the origins.
+    permission_store: PermissionStore,
+
     /// Document states for loaded pipelines (used only when writing screenshots).
     document_states: HashMap<PipelineId, DocumentState>,
 
@@ -365,6 +370,10 @@ pub struct Constellation<Message, LTF, STF> {
     /// Entry point to create and get channels to a WebGLThread.
     webgl_threads: Option<WebGLThreads>,
 
+    /// A channel through which messages can be sent to the webgpu thread,
+    /// which gets started when content first requests an adapter.
+    webgpu: Option<WebGPU>,
+
     /// A channel through which messages can be sent to the webvr thread.
     webvr_chan: Option<IpcSender<WebVRMsg>>,
 
@@ -418,6 +427,9 @@ pub struct InitialConstellationState {
 
     /// A channel to the webgl thread.
     pub webvr_chan: Option<IpcSender<WebVRMsg>>,
+
+    /// The clipboard of the platform, if any.
+    pub clipboard: Option<Box<dyn EmbedderClipboard>>,
 }
 
 /// Data needed for webdriver
@@ -686,14 +698,9 @@ where
                         ),
                     },
                     phantom: PhantomData,
-                    clipboard_ctx: match ClipboardContext::new() {
-                        Ok(c) => Some(c),
-                        Err(e) => {
-                            warn!("Error creating clipboard context ({})", e);
-                            None
-                        },
-                    },
+                    clipboard: state.clipboard,
                     webdriver: WebDriverData::new(),
+                    permission_store: PermissionStore::new(opts::get().config_dir.clone()),
                     scheduler_chan: TimerScheduler::start(),
                     document_states: HashMap::new(),
                     webrender_document: state.webrender_document,
@@ -712,6 +719,7 @@ where
                         },
                     ),
                     webgl_threads: state.webgl_threads,
+                    webgpu: None,
                     webvr_chan: state.webvr_chan,
                     canvas_chan: CanvasPaintThread::start(),
                     pending_approval_navigations: HashMap::new(),
@@ -1080,6 +1088,12 @@ where
             FromCompositorMsg::Keyboard(key_event) => {
                 self.handle_key_msg(key_event);
             },
+            FromCompositorMsg::IMEComposition(composition_event) => {
+                self.handle_ime_composition_msg(composition_event);
+            },
+            FromCompositorMsg::Gamepad(gamepad_event) => {
+                self.handle_gamepad_msg(gamepad_event);
+            },
             // Perform a navigation previously requested by script, if approved by the embedder.
             // If there is already a pending page (self.pending_changes), it will not be overridden;
             // However, if the id is not encompassed by another change, it will be.
@@ -1173,6 +1187,12 @@ where
             FromCompositorMsg::NewBrowser(url, top_level_browsing_context_id) => {
                 self.handle_new_top_level_browsing_context(url, top_level_browsing_context_id);
             },
+            FromCompositorMsg::RestoreBrowser(session_history, top_level_browsing_context_id) => {
+                self.handle_restore_top_level_browsing_context(
+                    session_history,
+                    top_level_browsing_context_id,
+                );
+            },
             // Close a top level browsing context.
             FromCompositorMsg::CloseBrowser(top_level_browsing_context_id) => {
                 self.handle_close_top_level_browsing_context(top_level_browsing_context_id);
@@ -1337,9 +1357,9 @@ where
                 self.handle_focus_msg(source_pipeline_id);
             },
             FromScriptMsg::GetClipboardContents(sender) => {
-                let contents = match self.clipboard_ctx {
-                    Some(ref mut ctx) => {
-                        match ctx.get_contents() {
+                let contents = match self.clipboard {
+                    Some(ref mut clipboard) => {
+                        match clipboard.get_text() {
                             Ok(c) => c,
                             Err(e) => {
                                 warn!("Error getting clipboard contents ({}), defaulting to empty string", e);
@@ -1354,8 +1374,8 @@ where
                 }
             },
             FromScriptMsg::SetClipboardContents(s) => {
-                if let Some(ref mut ctx) = self.clipboard_ctx {
-                    if let Err(e) = ctx.set_contents(s) {
+                if let Some(ref mut clipboard) = self.clipboard {
+                    if let Err(e) = clipboard.set_text(s) {
                         warn!("Error setting clipboard contents ({})", e);
                     }
                 }
@@ -1387,12 +1407,18 @@ where
                 self.compositor_proxy
                     .send(ToCompositorMsg::GetScreenAvailSize(send));
             },
+            FromScriptMsg::RequestAdapter(sender, power_preference) => {
+                self.handle_request_wgpu_adapter(sender, power_preference)
+            },
             FromScriptMsg::LogEntry(thread_name, entry) => {
                 self.handle_log_entry(Some(source_top_ctx_id), thread_name, entry);
             },
             FromScriptMsg::TouchEventProcessed(result) => self
                 .compositor_proxy
                 .send(ToCompositorMsg::TouchEventProcessed(result)),
+            FromScriptMsg::ScrollSmoothly(scroll_state) => self
+                .compositor_proxy
+                .send(ToCompositorMsg::SmoothScroll(scroll_state)),
             FromScriptMsg::GetBrowsingContextInfo(pipeline_id, sender) => {
                 let result = self
                     .pipelines
@@ -1452,6 +1478,18 @@ where
                     new_value,
                 );
             },
+            FromScriptMsg::GetPermission(name, sender) => {
+                let grant = self
+                    .pipelines
+                    .get(&source_pipeline_id)
+                    .and_then(|pipeline| self.permission_store.get(&pipeline.url.origin(), &name));
+                if let Err(e) = sender.send(grant) {
+                    warn!("Failed to send permission ({}).", e);
+                }
+            },
+            FromScriptMsg::SetPermission(name, grant) => {
+                self.handle_set_permission(source_pipeline_id, name, grant);
+            },
         }
     }
 
@@ -1514,6 +1552,33 @@ where
         }
     }
 
+    fn handle_set_permission(
+        &mut self,
+        pipeline_id: PipelineId,
+        name: String,
+        grant: Option<PermissionGrant>,
+    ) {
+        let origin = match self.pipelines.get(&pipeline_id) {
+            Some(pipeline) => pipeline.url.origin(),
+            None => return warn!("Permission set by closed pipeline {}.", pipeline_id),
+        };
+        if !self.permission_store.set(&origin, name.clone(), grant) {
+            return;
+        }
+        for pipeline in self.pipelines.values() {
+            if pipeline.url.origin() == origin {
+                let msg =
+                    ConstellationControlMsg::PermissionChanged(pipeline.id, name.clone(), grant);
+                if let Err(err) = pipeline.event_loop.send(msg) {
+                    warn!(
+                        "Failed to send permission change to pipeline {} ({:?}).",
+                        pipeline.id, err
+                    );
+                }
+            }
+        }
+    }
+
     fn handle_exit(&mut self) {
         // TODO: add a timer, which forces shutdown if threads aren't responsive.
         if self.shutting_down {
@@ -1583,6 +1648,8 @@ where
         let (core_sender, core_receiver) = ipc::channel().expect("Failed to create IPC channel!");
         let (storage_sender, storage_receiver) =
             ipc::channel().expect("Failed to create IPC channel!");
+        let (indexeddb_sender, indexeddb_receiver) =
+            ipc::channel().expect("Failed to create IPC channel!");
 
         debug!("Exiting core resource threads.");
         if let Err(e) = self
@@ -1612,6 +1679,14 @@ where
             warn!("Exit storage thread failed ({})", e);
         }
 
+        debug!("Exiting IndexedDB thread.");
+        if let Err(e) = self
+            .public_resource_threads
+            .send(IndexedDBThreadMsg::Exit(indexeddb_sender))
+        {
+            warn!("Exit IndexedDB thread failed ({})", e);
+        }
+
         debug!("Exiting bluetooth thread.");
         if let Err(e) = self.bluetooth_thread.send(BluetoothRequest::Exit) {
             warn!("Exit bluetooth thread failed ({})", e);
@@ -1636,6 +1711,13 @@ where
             }
         }
 
+        if let Some(webgpu) = self.webgpu.as_ref() {
+            debug!("Exiting WebGPU thread.");
+            if let Err(e) = webgpu.exit() {
+                warn!("Exit WebGPU Thread failed ({})", e);
+            }
+        }
+
         if let Some(chan) = self.webvr_chan.as_ref() {
             debug!("Exiting WebVR thread.");
             if let Err(e) = chan.send(WebVRMsg::Exit) {
@@ -1658,6 +1740,9 @@ where
         if let Err(e) = storage_receiver.recv() {
             warn!("Exit storage thread failed ({})", e);
         }
+        if let Err(e) = indexeddb_receiver.recv() {
+            warn!("Exit IndexedDB thread failed ({})", e);
+        }
 
         debug!("Asking compositor to complete shutdown.");
         self.compositor_proxy
@@ -1820,8 +1905,59 @@ where
         url: ServoUrl,
         top_level_browsing_context_id: TopLevelBrowsingContextId,
     ) {
-        let window_size = self.window_size.initial_viewport;
+        self.new_top_level_browsing_context(
+            url,
+            top_level_browsing_context_id,
+            PipelineId::new(),
+            JointSessionHistory::new(),
+        );
+    }
+
+    /// Creates a top-level browsing context loading the current entry of a
+    /// saved session history. The other entries are restored as discarded
+    /// documents, which get reloaded if they are ever traversed to.
+    fn handle_restore_top_level_browsing_context(
+        &mut self,
+        session_history: SessionHistory,
+        top_level_browsing_context_id: TopLevelBrowsingContextId,
+    ) {
+        let SessionHistory { urls, current } = session_history;
+        let url = match urls.get(current) {
+            Some(url) => url.clone(),
+            None => return warn!("Restored session history has no current entry."),
+        };
+        let browsing_context_id = BrowsingContextId::from(top_level_browsing_context_id);
         let pipeline_id = PipelineId::new();
+        let reloaders: Vec<NeedsToReload> = urls
+            .into_iter()
+            .enumerate()
+            .map(|(index, url)| {
+                if index == current {
+                    NeedsToReload::No(pipeline_id)
+                } else {
+                    let load_data = LoadData::new(url, None, None, None);
+                    NeedsToReload::Yes(PipelineId::new(), load_data)
+                }
+            })
+            .collect();
+        let session_history = JointSessionHistory::restore(browsing_context_id, reloaders, current);
+
+        self.new_top_level_browsing_context(
+            url,
+            top_level_browsing_context_id,
+            pipeline_id,
+            session_history,
+        );
+    }
+
+    fn new_top_level_browsing_context(
+        &mut self,
+        url: ServoUrl,
+        top_level_browsing_context_id: TopLevelBrowsingContextId,
+        pipeline_id: PipelineId,
+        session_history: JointSessionHistory,
+    ) {
+        let window_size = self.window_size.initial_viewport;
         let msg = (
             Some(top_level_browsing_context_id),
             EmbedderMsg::BrowserCreated(top_level_browsing_context_id),
@@ -1839,7 +1975,7 @@ where
             top_level_browsing_context_id,
             Browser {
                 focused_browsing_context_id: browsing_context_id,
-                session_history: JointSessionHistory::new(),
+                session_history: session_history,
             },
         );
 
@@ -2904,6 +3040,76 @@ where
         }
     }
 
+    fn handle_ime_composition_msg(&mut self, event: CompositionEvent) {
+        // Compositions are only sent to the focused browsing context, the
+        // event is dropped if there is none.
+        let focused_browsing_context_id = match self
+            .active_browser_id
+            .and_then(|browser_id| self.browsers.get(&browser_id))
+        {
+            Some(browser) => browser.focused_browsing_context_id,
+            None => return,
+        };
+        let pipeline_id = match self.browsing_contexts.get(&focused_browsing_context_id) {
+            Some(ctx) => ctx.pipeline_id,
+            None => {
+                return warn!(
+                    "Got composition event for nonexistent browsing context {}.",
+                    focused_browsing_context_id,
+                );
+            },
+        };
+        let event = CompositorEvent::CompositionEvent(event);
+        let msg = ConstellationControlMsg::SendEvent(pipeline_id, event);
+        let result = match self.pipelines.get(&pipeline_id) {
+            Some(pipeline) => pipeline.event_loop.send(msg),
+            None => {
+                return debug!(
+                    "Pipeline {:?} got composition event after closure.",
+                    pipeline_id
+                );
+            },
+        };
+        if let Err(e) = result {
+            self.handle_send_error(pipeline_id, e);
+        }
+    }
+
+    fn handle_gamepad_msg(&mut self, event: GamepadEvent) {
+        // Gamepads are only exposed to the focused browsing context, the
+        // event is dropped if there is none.
+        let focused_browsing_context_id = match self
+            .active_browser_id
+            .and_then(|browser_id| self.browsers.get(&browser_id))
+        {
+            Some(browser) => browser.focused_browsing_context_id,
+            None => return,
+        };
+        let pipeline_id = match self.browsing_contexts.get(&focused_browsing_context_id) {
+            Some(ctx) => ctx.pipeline_id,
+            None => {
+                return warn!(
+                    "Got gamepad event for nonexistent browsing context {}.",
+                    focused_browsing_context_id,
+                );
+            },
+        };
+        let event = CompositorEvent::GamepadEvent(event);
+        let msg = ConstellationControlMsg::SendEvent(pipeline_id, event);
+        let result = match self.pipelines.get(&pipeline_id) {
+            Some(pipeline) => pipeline.event_loop.send(msg),
+            None => {
+                return debug!(
+                    "Pipeline {:?} got gamepad event after closure.",
+                    pipeline_id
+                );
+            },
+        };
+        if let Err(e) = result {
+            self.handle_send_error(pipeline_id, e);
+        }
+    }
+
     fn handle_reload_msg(&mut self, top_level_browsing_context_id: TopLevelBrowsingContextId) {
         let browsing_context_id = BrowsingContextId::from(top_level_browsing_context_id);
         let pipeline_id = match self.browsing_contexts.get(&browsing_context_id) {
@@ -3130,6 +3336,32 @@ where
         }
     }
 
+    fn handle_request_wgpu_adapter(
+        &mut self,
+        sender: IpcSender<WebGPUResponseResult>,
+        power_preference: wgt::PowerPreference,
+    ) {
+        if self.webgpu.is_none() {
+            self.webgpu = WebGPU::new(self.webrender_api_sender.clone());
+        }
+        let webgpu = match self.webgpu {
+            Some(ref webgpu) => webgpu,
+            None => {
+                if let Err(e) = sender.send(Err("Failed to start the WebGPU thread".to_owned())) {
+                    warn!("Request adapter response failed ({})", e);
+                }
+                return;
+            },
+        };
+        let request = WebGPURequest::RequestAdapter {
+            sender,
+            power_preference,
+        };
+        if let Err(e) = webgpu.0.send(request) {
+            warn!("Request adapter failed ({})", e);
+        }
+    }
+
     fn handle_webdriver_msg(&mut self, msg: WebDriverCommandMsg) {
         // Find the script channel for the given parent pipeline,
         // and pass the event to that script thread.
@@ -3218,6 +3450,19 @@ where
                 self.compositor_proxy
                     .send(ToCompositorMsg::CreatePng(reply));
             },
+            WebDriverCommandMsg::MouseButtonAction(mouse_event_type, mouse_button, x, y) => {
+                self.compositor_proxy
+                    .send(ToCompositorMsg::WebDriverMouseButtonEvent(
+                        mouse_event_type,
+                        mouse_button,
+                        x,
+                        y,
+                    ));
+            },
+            WebDriverCommandMsg::MouseMoveAction(x, y) => {
+                self.compositor_proxy
+                    .send(ToCompositorMsg::WebDriverMouseMoveEvent(x, y));
+            },
         }
     }
 
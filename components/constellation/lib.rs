@@ -16,6 +16,7 @@ mod browsingcontext;
 mod constellation;
 mod event_loop;
 mod network_listener;
+mod permission_store;
 mod pipeline;
 #[cfg(all(
     not(target_os = "windows"),
@@ -32,6 +33,7 @@ pub use crate::constellation::{
     Constellation, FromCompositorLogger, FromScriptLogger, InitialConstellationState,
 };
 pub use crate::pipeline::UnprivilegedPipelineContent;
+pub use crate::session_history::{JointSessionHistory, NeedsToReload, SessionHistoryDiff};
 #[cfg(all(
     not(target_os = "windows"),
     not(target_os = "ios"),
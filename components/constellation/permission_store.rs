@@ -0,0 +1,83 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! The permissions the user gave to the origins, stored in the profile
+//! directory when there is one.
+//!
+//! <https://w3c.github.io/permissions/#permission-store>
+
+use net::resource_thread::{read_json_from_file, write_json_to_file};
+use script_traits::PermissionGrant;
+use servo_url::ImmutableOrigin;
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+const PERMISSIONS_FILE: &'static str = "permissions.json";
+
+pub struct PermissionStore {
+    /// The permissions by serialized origin, then by feature name.
+    grants: HashMap<String, HashMap<String, PermissionGrant>>,
+    config_dir: Option<PathBuf>,
+}
+
+impl PermissionStore {
+    pub fn new(config_dir: Option<PathBuf>) -> PermissionStore {
+        let mut grants = HashMap::new();
+        if let Some(ref config_dir) = config_dir {
+            read_json_from_file(&mut grants, config_dir, PERMISSIONS_FILE);
+        }
+        PermissionStore { grants, config_dir }
+    }
+
+    pub fn get(&self, origin: &ImmutableOrigin, name: &str) -> Option<PermissionGrant> {
+        if !origin.is_tuple() {
+            return None;
+        }
+        self.grants
+            .get(&origin.ascii_serialization())
+            .and_then(|grants| grants.get(name))
+            .cloned()
+    }
+
+    /// Stores the permission of `origin` to use the feature `name`, or forgets
+    /// it, returning whether it changed.
+    ///
+    /// The permissions of the opaque origins are never stored, as they can't
+    /// be told apart once serialized.
+    pub fn set(
+        &mut self,
+        origin: &ImmutableOrigin,
+        name: String,
+        grant: Option<PermissionGrant>,
+    ) -> bool {
+        if !origin.is_tuple() || self.get(origin, &name) == grant {
+            return false;
+        }
+        let origin = origin.ascii_serialization();
+        match grant {
+            Some(grant) => {
+                self.grants
+                    .entry(origin)
+                    .or_insert_with(HashMap::new)
+                    .insert(name, grant);
+            },
+            None => {
+                let is_empty = match self.grants.get_mut(&origin) {
+                    Some(grants) => {
+                        grants.remove(&name);
+                        grants.is_empty()
+                    },
+                    None => false,
+                };
+                if is_empty {
+                    self.grants.remove(&origin);
+                }
+            },
+        }
+        if let Some(ref config_dir) = self.config_dir {
+            write_json_to_file(&self.grants, config_dir, PERMISSIONS_FILE);
+        }
+        true
+    }
+}
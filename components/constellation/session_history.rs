@@ -32,6 +32,29 @@ impl JointSessionHistory {
         }
     }
 
+    /// The joint session history of a browsing context whose entries are
+    /// loaded by `reloaders`, from the oldest to the most recent, the entry
+    /// at `current` being the active one.
+    pub fn restore(
+        browsing_context_id: BrowsingContextId,
+        reloaders: Vec<NeedsToReload>,
+        current: usize,
+    ) -> JointSessionHistory {
+        let mut past: Vec<SessionHistoryDiff> = reloaders
+            .windows(2)
+            .map(|reloaders| SessionHistoryDiff::BrowsingContextDiff {
+                browsing_context_id,
+                old_reloader: reloaders[0].clone(),
+                new_reloader: reloaders[1].clone(),
+            })
+            .collect();
+        // The diffs nearest to the current entry are the last ones of their
+        // lists, since traversals pop them.
+        let mut future = past.split_off(current);
+        future.reverse();
+        JointSessionHistory { past, future }
+    }
+
     pub fn history_length(&self) -> usize {
         self.past.len() + 1 + self.future.len()
     }
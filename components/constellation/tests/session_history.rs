@@ -0,0 +1,54 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use constellation::{JointSessionHistory, NeedsToReload, SessionHistoryDiff};
+use msg::constellation_msg::{PipelineId, PipelineNamespace};
+use msg::constellation_msg::{TEST_BROWSING_CONTEXT_ID, TEST_NAMESPACE};
+use script_traits::LoadData;
+use servo_url::ServoUrl;
+
+fn discarded(url: &str) -> NeedsToReload {
+    let url = ServoUrl::parse(url).unwrap();
+    NeedsToReload::Yes(PipelineId::new(), LoadData::new(url, None, None, None))
+}
+
+fn reloaders(diff: &SessionHistoryDiff) -> (&NeedsToReload, &NeedsToReload) {
+    match *diff {
+        SessionHistoryDiff::BrowsingContextDiff {
+            ref old_reloader,
+            ref new_reloader,
+            ..
+        } => (old_reloader, new_reloader),
+        _ => panic!("Restored entries only differ by their pipeline"),
+    }
+}
+
+#[test]
+fn test_restored_history_traverses_from_the_current_entry() {
+    PipelineNamespace::install(TEST_NAMESPACE);
+    let entries = vec![
+        discarded("https://servo.org/a"),
+        discarded("https://servo.org/b"),
+        NeedsToReload::No(PipelineId::new()),
+        discarded("https://servo.org/d"),
+    ];
+    let history = JointSessionHistory::restore(TEST_BROWSING_CONTEXT_ID, entries.clone(), 2);
+    assert_eq!(history.history_length(), 4);
+    assert_eq!(history.past.len(), 2);
+    assert_eq!(history.future.len(), 1);
+
+    // Going back first goes from the current entry to the one before it.
+    assert_eq!(reloaders(&history.past[1]), (&entries[1], &entries[2]));
+    assert_eq!(reloaders(&history.past[0]), (&entries[0], &entries[1]));
+    assert_eq!(reloaders(&history.future[0]), (&entries[2], &entries[3]));
+}
+
+#[test]
+fn test_restored_history_of_a_single_entry_has_no_diffs() {
+    PipelineNamespace::install(TEST_NAMESPACE);
+    let entries = vec![NeedsToReload::No(PipelineId::new())];
+    let history = JointSessionHistory::restore(TEST_BROWSING_CONTEXT_ID, entries, 0);
+    assert_eq!(history.history_length(), 1);
+    assert!(history.past.is_empty() && history.future.is_empty());
+}
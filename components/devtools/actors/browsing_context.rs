@@ -78,6 +78,7 @@ pub struct BrowsingContextActorMsg {
     profilerActor: String,
     performanceActor: String,
     styleSheetsActor: String,
+    storageActor: String,
 }
 
 pub struct BrowsingContextActor {
@@ -91,6 +92,7 @@ pub struct BrowsingContextActor {
     pub profiler: String,
     pub performance: String,
     pub styleSheets: String,
+    pub storage: String,
     pub thread: String,
 }
 
@@ -200,6 +202,7 @@ impl BrowsingContextActor {
             profilerActor: self.profiler.clone(),
             performanceActor: self.performance.clone(),
             styleSheetsActor: self.styleSheets.clone(),
+            storageActor: self.storage.clone(),
         }
     }
 }
@@ -10,6 +10,7 @@ use crate::actor::{Actor, ActorMessageStatus, ActorRegistry};
 use crate::protocol::JsonPacketStream;
 use devtools_traits::HttpRequest as DevtoolsHttpRequest;
 use devtools_traits::HttpResponse as DevtoolsHttpResponse;
+use devtools_traits::HttpResponseContent as DevtoolsHttpResponseContent;
 use headers_core::HeaderMapExt;
 use headers_ext::{ContentType, Cookie};
 use http::{header, HeaderMap};
@@ -33,6 +34,7 @@ struct HttpResponse {
     headers: Option<HeaderMap>,
     status: Option<(StatusCode, String)>,
     body: Option<Vec<u8>>,
+    receive_time: u64,
 }
 
 pub struct NetworkEventActor {
@@ -116,10 +118,17 @@ struct GetResponseHeadersReply {
     rawHeaders: String,
 }
 
+#[derive(Serialize)]
+struct ResponseContent {
+    mimeType: String,
+    size: usize,
+    text: String,
+}
+
 #[derive(Serialize)]
 struct GetResponseContentReply {
     from: String,
-    content: Option<Vec<u8>>,
+    content: Option<ResponseContent>,
     contentDiscarded: bool,
 }
 
@@ -149,7 +158,7 @@ struct Timings {
     connect: u64,
     send: u64,
     wait: u32,
-    receive: u32,
+    receive: u64,
 }
 
 #[derive(Serialize)]
@@ -258,10 +267,11 @@ impl Actor for NetworkEventActor {
             },
             "getResponseCookies" => {
                 let mut cookies = Vec::new();
-                // TODO: This seems quite broken
-                for cookie in self.request.headers.get_all(header::SET_COOKIE) {
-                    if let Ok(cookie_value) = String::from_utf8(cookie.as_bytes().to_vec()) {
-                        cookies = cookie_value.into_bytes();
+                if let Some(ref headers) = self.response.headers {
+                    for cookie in headers.get_all(header::SET_COOKIE) {
+                        if let Ok(cookie_value) = String::from_utf8(cookie.as_bytes().to_vec()) {
+                            cookies = cookie_value.into_bytes();
+                        }
                     }
                 }
 
@@ -273,30 +283,37 @@ impl Actor for NetworkEventActor {
                 ActorMessageStatus::Processed
             },
             "getResponseContent" => {
+                // Only textual bodies can be shown.
+                let content = self.response.body.as_ref().and_then(|body| {
+                    let text = String::from_utf8(body.clone()).ok()?;
+                    Some(ResponseContent {
+                        mimeType: self.mime_type(),
+                        size: body.len(),
+                        text: text,
+                    })
+                });
                 let msg = GetResponseContentReply {
                     from: self.name(),
-                    content: self.response.body.clone(),
-                    contentDiscarded: self.response.body.is_none(),
+                    contentDiscarded: content.is_none(),
+                    content: content,
                 };
                 stream.write_json_packet(&msg);
                 ActorMessageStatus::Processed
             },
             "getEventTimings" => {
-                // TODO: This is a fake timings msg
+                // TODO: Send the correct values for blocked, dns and wait.
                 let timingsObj = Timings {
                     blocked: 0,
                     dns: 0,
                     connect: self.request.connect_time,
                     send: self.request.send_time,
                     wait: 0,
-                    receive: 0,
+                    receive: self.response.receive_time,
                 };
-                let total = timingsObj.connect + timingsObj.send;
-                // TODO: Send the correct values for all these fields.
                 let msg = GetEventTimingsReply {
                     from: self.name(),
                     timings: timingsObj,
-                    totalTime: total,
+                    totalTime: self.total_time(),
                 };
                 stream.write_json_packet(&msg);
                 ActorMessageStatus::Processed
@@ -335,6 +352,7 @@ impl NetworkEventActor {
                 headers: None,
                 status: None,
                 body: None,
+                receive_time: 0,
             },
             is_xhr: false,
         }
@@ -361,6 +379,11 @@ impl NetworkEventActor {
         self.response.body = response.body;
     }
 
+    pub fn add_response_content(&mut self, content: DevtoolsHttpResponseContent) {
+        self.response.body = Some(content.body);
+        self.response.receive_time = content.receive_time;
+    }
+
     pub fn event_actor(&self) -> EventActor {
         // TODO: Send the correct values for startedDateTime, isXHR, private
         EventActor {
@@ -397,31 +420,29 @@ impl NetworkEventActor {
         }
     }
 
+    fn mime_type(&self) -> String {
+        self.response
+            .headers
+            .as_ref()
+            .and_then(|headers| headers.typed_get::<ContentType>())
+            .map_or("".to_owned(), |ct| ct.to_string())
+    }
+
     pub fn response_content(&self) -> ResponseContentMsg {
-        let mut mString = "".to_owned();
-        if let Some(ref headers) = self.response.headers {
-            mString = match headers.typed_get::<ContentType>() {
-                Some(ct) => ct.to_string(),
-                _ => "".to_owned(),
-            };
-        }
-        // TODO: Set correct values when response's body is sent to the devtools in http_loader.
+        let size = self.response.body.as_ref().map_or(0, |body| body.len()) as u32;
+        // TODO: Send the size of the body before it was decoded as transferredSize.
         ResponseContentMsg {
-            mimeType: mString,
-            contentSize: 0,
-            transferredSize: 0,
-            discardResponseBody: true,
+            mimeType: self.mime_type(),
+            contentSize: size,
+            transferredSize: size,
+            discardResponseBody: self.response.body.is_none(),
         }
     }
 
     pub fn response_cookies(&self) -> ResponseCookiesMsg {
-        let mut cookies_size = 0;
-        if let Some(ref headers) = self.response.headers {
-            cookies_size = match headers.typed_get::<Cookie>() {
-                Some(ref cookie) => cookie.len(),
-                _ => 0,
-            };
-        }
+        let cookies_size = self.response.headers.as_ref().map_or(0, |headers| {
+            headers.get_all(header::SET_COOKIE).iter().count()
+        });
         ResponseCookiesMsg {
             cookies: cookies_size,
         }
@@ -463,6 +484,6 @@ impl NetworkEventActor {
     }
 
     pub fn total_time(&self) -> u64 {
-        self.request.connect_time + self.request.send_time
+        self.request.connect_time + self.request.send_time + self.response.receive_time
     }
 }
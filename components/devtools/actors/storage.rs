@@ -0,0 +1,439 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Liberally derived from the [Firefox JS implementation]
+//! (https://searchfox.org/mozilla-central/source/devtools/server/actors/storage.js).
+//! Lists the cookies and local storage items of a browsing context for the storage inspector.
+
+use crate::actor::{Actor, ActorMessageStatus, ActorRegistry};
+use crate::protocol::JsonPacketStream;
+use devtools_traits::{CookieInfo, StorageEvent};
+use serde_json::{self, Map, Value};
+use std::collections::{BTreeMap, HashMap};
+use std::net::TcpStream;
+use std::sync::{Arc, Mutex};
+
+/// Separates the name, host and path of a cookie in its unique key, like Firefox does.
+const SEPARATOR_GUID: &str = "{9d414cc5-8319-0a04-0586-c0a6ae01670a}";
+
+/// The cookies and local storage items streamed from the net and storage
+/// threads, shared by all the store actors.
+#[derive(Default)]
+pub struct StoredData {
+    /// The cookies stored for each registrable domain.
+    cookies: HashMap<String, Vec<CookieInfo>>,
+    /// The local storage items of each origin.
+    local_storage: HashMap<String, BTreeMap<String, String>>,
+}
+
+impl StoredData {
+    pub fn update(&mut self, event: StorageEvent) {
+        match event {
+            StorageEvent::Cookies(domain, cookies) => {
+                self.cookies.insert(domain, cookies);
+            },
+            StorageEvent::LocalStorage(origin, items) => {
+                self.local_storage.insert(origin, items);
+            },
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+pub enum StoreType {
+    Cookies,
+    LocalStorage,
+}
+
+impl StoreType {
+    fn as_str(self) -> &'static str {
+        match self {
+            StoreType::Cookies => "cookies",
+            StoreType::LocalStorage => "localStorage",
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct CookieObject {
+    uniqueKey: String,
+    name: String,
+    host: String,
+    path: String,
+    expires: i64,
+    size: usize,
+    lastAccessed: i64,
+    creationTime: i64,
+    value: String,
+    hostOnly: bool,
+    isHttpOnly: bool,
+    isSecure: bool,
+    sameSite: String,
+}
+
+#[derive(Serialize)]
+struct StorageItem {
+    name: String,
+    value: String,
+}
+
+#[derive(Serialize)]
+struct Field {
+    name: &'static str,
+    editable: bool,
+}
+
+#[derive(Serialize)]
+struct StoreTraits {}
+
+#[derive(Serialize)]
+pub struct StoreActorMsg {
+    actor: String,
+    hosts: HashMap<String, Vec<String>>,
+    traits: StoreTraits,
+}
+
+#[derive(Serialize)]
+struct GetStoreObjectsReply {
+    from: String,
+    offset: usize,
+    total: usize,
+    data: Vec<Value>,
+}
+
+#[derive(Serialize)]
+struct GetFieldsReply {
+    from: String,
+    value: Vec<Field>,
+}
+
+/// The names of the changed objects of each host, for each type of store.
+type StoreChanges = HashMap<&'static str, HashMap<String, Vec<String>>>;
+
+#[derive(Serialize)]
+struct StoresUpdate {
+    added: StoreChanges,
+    changed: StoreChanges,
+    deleted: StoreChanges,
+}
+
+#[derive(Serialize)]
+pub struct StoresUpdateMsg {
+    from: String,
+    #[serde(rename = "type")]
+    type_: String,
+    data: StoresUpdate,
+}
+
+#[derive(Serialize)]
+struct ListStoresReply {
+    from: String,
+    cookies: StoreActorMsg,
+    localStorage: StoreActorMsg,
+}
+
+/// The cookies or the local storage items of a browsing context.
+pub struct StoreActor {
+    pub name: String,
+    store_type: StoreType,
+    /// The host of the browsing context for cookies, its origin for local
+    /// storage.
+    host: String,
+    data: Arc<Mutex<StoredData>>,
+}
+
+impl Actor for StoreActor {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn handle_message(
+        &self,
+        _registry: &ActorRegistry,
+        msg_type: &str,
+        msg: &Map<String, Value>,
+        stream: &mut TcpStream,
+    ) -> Result<ActorMessageStatus, ()> {
+        Ok(match msg_type {
+            "getStoreObjects" => {
+                let mut objects = vec![];
+                if msg.get("host").and_then(Value::as_str) == Some(&*self.host) {
+                    let names: Option<Vec<&str>> = msg
+                        .get("names")
+                        .and_then(Value::as_array)
+                        .map(|names| names.iter().filter_map(Value::as_str).collect());
+                    objects = self
+                        .objects()
+                        .into_iter()
+                        .filter(|&(ref key, _)| {
+                            names.as_ref().map_or(true, |names| names.contains(&&**key))
+                        })
+                        .map(|(_, object)| object)
+                        .collect();
+                }
+                let options = msg.get("options");
+                let option = |name: &str| options.and_then(|o| o.get(name)).and_then(Value::as_u64);
+                let total = objects.len();
+                let offset = option("offset").unwrap_or(0) as usize;
+                let size = option("size").map_or(total, |size| size as usize);
+                let msg = GetStoreObjectsReply {
+                    from: self.name(),
+                    offset: offset,
+                    total: total,
+                    data: objects.into_iter().skip(offset).take(size).collect(),
+                };
+                stream.write_json_packet(&msg);
+                ActorMessageStatus::Processed
+            },
+
+            "getFields" => {
+                let msg = GetFieldsReply {
+                    from: self.name(),
+                    value: self.fields(),
+                };
+                stream.write_json_packet(&msg);
+                ActorMessageStatus::Processed
+            },
+
+            // TODO: Support editing cookies and local storage items.
+            "getEditableFields" => {
+                let msg = GetFieldsReply {
+                    from: self.name(),
+                    value: vec![],
+                };
+                stream.write_json_packet(&msg);
+                ActorMessageStatus::Processed
+            },
+
+            _ => ActorMessageStatus::Ignored,
+        })
+    }
+}
+
+impl StoreActor {
+    pub fn new(
+        name: String,
+        store_type: StoreType,
+        host: String,
+        data: Arc<Mutex<StoredData>>,
+    ) -> StoreActor {
+        StoreActor {
+            name: name,
+            store_type: store_type,
+            host: host,
+            data: data,
+        }
+    }
+
+    /// The objects of this store, keyed by their name, or their unique key
+    /// for cookies.
+    fn objects(&self) -> BTreeMap<String, Value> {
+        let data = self.data.lock().unwrap();
+        match self.store_type {
+            StoreType::Cookies => data
+                .cookies
+                .values()
+                .flat_map(|cookies| cookies.iter())
+                .filter(|cookie| cookie_matches(cookie, &self.host))
+                .map(|cookie| {
+                    let object = cookie_object(cookie);
+                    (
+                        object.uniqueKey.clone(),
+                        serde_json::to_value(object).unwrap(),
+                    )
+                })
+                .collect(),
+            StoreType::LocalStorage => {
+                data.local_storage
+                    .get(&self.host)
+                    .map_or(BTreeMap::new(), |items| {
+                        items
+                            .iter()
+                            .map(|(name, value)| {
+                                let item = StorageItem {
+                                    name: name.clone(),
+                                    value: value.clone(),
+                                };
+                                (name.clone(), serde_json::to_value(item).unwrap())
+                            })
+                            .collect()
+                    })
+            },
+        }
+    }
+
+    fn fields(&self) -> Vec<Field> {
+        let names: &[&'static str] = match self.store_type {
+            StoreType::Cookies => &[
+                "uniqueKey",
+                "name",
+                "host",
+                "path",
+                "expires",
+                "value",
+                "isHttpOnly",
+                "isSecure",
+                "sameSite",
+                "lastAccessed",
+                "creationTime",
+                "hostOnly",
+            ],
+            StoreType::LocalStorage => &["name", "value"],
+        };
+        names
+            .iter()
+            .map(|&name| Field {
+                name: name,
+                editable: false,
+            })
+            .collect()
+    }
+
+    pub fn encodable(&self) -> StoreActorMsg {
+        let mut hosts = HashMap::new();
+        if !self.host.is_empty() {
+            hosts.insert(self.host.clone(), vec![]);
+        }
+        StoreActorMsg {
+            actor: self.name(),
+            hosts: hosts,
+            traits: StoreTraits {},
+        }
+    }
+}
+
+/// Whether `cookie` would be sent to `host`, whatever the path.
+fn cookie_matches(cookie: &CookieInfo, host: &str) -> bool {
+    if cookie.host_only {
+        return cookie.domain == host;
+    }
+    host == cookie.domain || host.ends_with(&format!(".{}", cookie.domain))
+}
+
+fn cookie_object(cookie: &CookieInfo) -> CookieObject {
+    CookieObject {
+        uniqueKey: format!(
+            "{}{}{}{}{}",
+            cookie.name, SEPARATOR_GUID, cookie.domain, SEPARATOR_GUID, cookie.path
+        ),
+        name: cookie.name.clone(),
+        host: cookie.domain.clone(),
+        path: cookie.path.clone(),
+        // Session cookies expire at 0, like in Firefox.
+        expires: cookie.expires.unwrap_or(0),
+        size: cookie.name.len() + cookie.value.len(),
+        lastAccessed: cookie.last_access,
+        creationTime: cookie.creation_time,
+        value: cookie.value.clone(),
+        hostOnly: cookie.host_only,
+        isHttpOnly: cookie.http_only,
+        isSecure: cookie.secure,
+        sameSite: "".to_owned(),
+    }
+}
+
+/// The storage inspector of a browsing context.
+pub struct StorageActor {
+    pub name: String,
+    pub cookies: String,
+    pub local_storage: String,
+}
+
+impl Actor for StorageActor {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn handle_message(
+        &self,
+        registry: &ActorRegistry,
+        msg_type: &str,
+        _msg: &Map<String, Value>,
+        stream: &mut TcpStream,
+    ) -> Result<ActorMessageStatus, ()> {
+        Ok(match msg_type {
+            "listStores" => {
+                let msg = ListStoresReply {
+                    from: self.name(),
+                    cookies: registry.find::<StoreActor>(&self.cookies).encodable(),
+                    localStorage: registry.find::<StoreActor>(&self.local_storage).encodable(),
+                };
+                stream.write_json_packet(&msg);
+                ActorMessageStatus::Processed
+            },
+            _ => ActorMessageStatus::Ignored,
+        })
+    }
+}
+
+impl StorageActor {
+    fn stores<'a>(&self, registry: &'a ActorRegistry) -> Vec<&'a StoreActor> {
+        vec![
+            registry.find::<StoreActor>(&self.cookies),
+            registry.find::<StoreActor>(&self.local_storage),
+        ]
+    }
+
+    /// Snapshots the objects of the stores of this actor, to find out how a
+    /// storage event changed them.
+    pub fn objects(&self, registry: &ActorRegistry) -> Vec<BTreeMap<String, Value>> {
+        self.stores(registry)
+            .iter()
+            .map(|store| store.objects())
+            .collect()
+    }
+
+    /// Describes how the objects of the stores of this actor changed since
+    /// `before` was snapshotted, if they did.
+    pub fn stores_update(
+        &self,
+        registry: &ActorRegistry,
+        before: Vec<BTreeMap<String, Value>>,
+    ) -> Option<StoresUpdateMsg> {
+        let mut update = StoresUpdate {
+            added: HashMap::new(),
+            changed: HashMap::new(),
+            deleted: HashMap::new(),
+        };
+        for (store, before) in self.stores(registry).into_iter().zip(before) {
+            let after = store.objects();
+            let added = after
+                .keys()
+                .filter(|key| !before.contains_key(*key))
+                .cloned()
+                .collect();
+            let changed = after
+                .iter()
+                .filter(|&(key, object)| before.get(key).map_or(false, |old| old != object))
+                .map(|(key, _)| key.clone())
+                .collect();
+            let deleted = before
+                .keys()
+                .filter(|key| !after.contains_key(*key))
+                .cloned()
+                .collect();
+            add_changes(&mut update.added, store, added);
+            add_changes(&mut update.changed, store, changed);
+            add_changes(&mut update.deleted, store, deleted);
+        }
+        if update.added.is_empty() && update.changed.is_empty() && update.deleted.is_empty() {
+            return None;
+        }
+        Some(StoresUpdateMsg {
+            from: self.name(),
+            type_: "storesUpdate".to_owned(),
+            data: update,
+        })
+    }
+}
+
+fn add_changes(changes: &mut StoreChanges, store: &StoreActor, names: Vec<String>) {
+    if names.is_empty() {
+        return;
+    }
+    changes
+        .entry(store.store_type.as_str())
+        .or_insert_with(HashMap::new)
+        .insert(store.host.clone(), names);
+}
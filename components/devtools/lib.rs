@@ -28,6 +28,7 @@ use crate::actors::network_event::{EventActor, NetworkEventActor, ResponseStartM
 use crate::actors::performance::PerformanceActor;
 use crate::actors::profiler::ProfilerActor;
 use crate::actors::root::RootActor;
+use crate::actors::storage::{StorageActor, StoreActor, StoreType, StoredData};
 use crate::actors::stylesheets::StyleSheetsActor;
 use crate::actors::thread::ThreadActor;
 use crate::actors::timeline::TimelineActor;
@@ -36,7 +37,7 @@ use crate::protocol::JsonPacketStream;
 use crossbeam_channel::{unbounded, Receiver, Sender};
 use devtools_traits::{ChromeToDevtoolsControlMsg, ConsoleMessage, DevtoolsControlMsg};
 use devtools_traits::{DevtoolScriptControlMsg, DevtoolsPageInfo, LogLevel, NetworkEvent};
-use devtools_traits::{ScriptToDevtoolsControlMsg, WorkerId};
+use devtools_traits::{ScriptToDevtoolsControlMsg, StorageEvent, WorkerId};
 use ipc_channel::ipc::IpcSender;
 use msg::constellation_msg::PipelineId;
 use std::borrow::ToOwned;
@@ -63,6 +64,7 @@ mod actors {
     pub mod performance;
     pub mod profiler;
     pub mod root;
+    pub mod storage;
     pub mod stylesheets;
     pub mod thread;
     pub mod timeline;
@@ -169,6 +171,8 @@ fn run_server(
 
     let mut actor_workers: HashMap<(PipelineId, WorkerId), String> = HashMap::new();
 
+    let stored_data = Arc::new(Mutex::new(StoredData::default()));
+
     /// Process the input from a single devtools client until EOF.
     fn handle_client(actors: Arc<Mutex<ActorRegistry>>, mut stream: TcpStream) {
         debug!("connection established to {}", stream.peer_addr().unwrap());
@@ -218,6 +222,7 @@ fn run_server(
         script_sender: IpcSender<DevtoolScriptControlMsg>,
         actor_pipelines: &mut HashMap<PipelineId, String>,
         actor_workers: &mut HashMap<(PipelineId, WorkerId), String>,
+        stored_data: &Arc<Mutex<StoredData>>,
         page_info: DevtoolsPageInfo,
     ) {
         let mut actors = actors.lock().unwrap();
@@ -234,6 +239,9 @@ fn run_server(
             profiler,
             performance,
             styleSheets,
+            storage,
+            cookies,
+            local_storage,
             thread,
         ) = {
             let console = ConsoleActor {
@@ -265,6 +273,25 @@ fn run_server(
             let thread = ThreadActor::new(actors.new_name("context"));
 
             let DevtoolsPageInfo { title, url } = page_info;
+
+            let cookies = StoreActor::new(
+                actors.new_name("cookies"),
+                StoreType::Cookies,
+                url.host_str().unwrap_or("").to_owned(),
+                stored_data.clone(),
+            );
+            let local_storage = StoreActor::new(
+                actors.new_name("localStorage"),
+                StoreType::LocalStorage,
+                url.origin().ascii_serialization(),
+                stored_data.clone(),
+            );
+            let storage = StorageActor {
+                name: actors.new_name("storage"),
+                cookies: cookies.name(),
+                local_storage: local_storage.name(),
+            };
+
             let target = BrowsingContextActor {
                 name: actors.new_name("target"),
                 title: String::from(title),
@@ -276,6 +303,7 @@ fn run_server(
                 profiler: profiler.name(),
                 performance: performance.name(),
                 styleSheets: styleSheets.name(),
+                storage: storage.name(),
                 thread: thread.name(),
             };
 
@@ -291,6 +319,9 @@ fn run_server(
                 profiler,
                 performance,
                 styleSheets,
+                storage,
+                cookies,
+                local_storage,
                 thread,
             )
         };
@@ -314,6 +345,9 @@ fn run_server(
         actors.register(Box::new(profiler));
         actors.register(Box::new(performance));
         actors.register(Box::new(styleSheets));
+        actors.register(Box::new(storage));
+        actors.register(Box::new(cookies));
+        actors.register(Box::new(local_storage));
         actors.register(Box::new(thread));
     }
 
@@ -459,10 +493,10 @@ fn run_server(
                 let msg = NetworkEventUpdateMsg {
                     from: netevent_actor_name.clone(),
                     type_: "networkEventUpdate".to_owned(),
-                    updateType: "eventTimings".to_owned(),
+                    updateType: "securityInfo".to_owned(),
                 };
-                let extra = EventTimingsUpdateMsg {
-                    totalTime: actor.total_time(),
+                let extra = SecurityInfoUpdateMsg {
+                    state: "insecure".to_owned(),
                 };
                 for stream in &mut connections {
                     stream.write_merged_json_packet(&msg, &extra);
@@ -471,45 +505,80 @@ fn run_server(
                 let msg = NetworkEventUpdateMsg {
                     from: netevent_actor_name.clone(),
                     type_: "networkEventUpdate".to_owned(),
-                    updateType: "securityInfo".to_owned(),
-                };
-                let extra = SecurityInfoUpdateMsg {
-                    state: "insecure".to_owned(),
+                    updateType: "responseCookies".to_owned(),
                 };
                 for stream in &mut connections {
-                    stream.write_merged_json_packet(&msg, &extra);
+                    stream.write_merged_json_packet(&msg, &actor.response_cookies());
                 }
 
                 let msg = NetworkEventUpdateMsg {
-                    from: netevent_actor_name.clone(),
+                    from: netevent_actor_name,
                     type_: "networkEventUpdate".to_owned(),
-                    updateType: "responseContent".to_owned(),
+                    updateType: "responseHeaders".to_owned(),
                 };
                 for stream in &mut connections {
-                    stream.write_merged_json_packet(&msg, &actor.response_content());
+                    stream.write_merged_json_packet(&msg, &actor.response_headers());
                 }
+            },
+            NetworkEvent::HttpResponseContent(content) => {
+                //Store the response body in the actor
+                actor.add_response_content(content);
 
                 let msg = NetworkEventUpdateMsg {
                     from: netevent_actor_name.clone(),
                     type_: "networkEventUpdate".to_owned(),
-                    updateType: "responseCookies".to_owned(),
+                    updateType: "responseContent".to_owned(),
                 };
                 for stream in &mut connections {
-                    stream.write_merged_json_packet(&msg, &actor.response_cookies());
+                    stream.write_merged_json_packet(&msg, &actor.response_content());
                 }
 
                 let msg = NetworkEventUpdateMsg {
                     from: netevent_actor_name,
                     type_: "networkEventUpdate".to_owned(),
-                    updateType: "responseHeaders".to_owned(),
+                    updateType: "eventTimings".to_owned(),
+                };
+                let extra = EventTimingsUpdateMsg {
+                    totalTime: actor.total_time(),
                 };
                 for stream in &mut connections {
-                    stream.write_merged_json_packet(&msg, &actor.response_headers());
+                    stream.write_merged_json_packet(&msg, &extra);
                 }
             },
         }
     }
 
+    /// Update the cookies or local storage items known to the store actors,
+    /// and tell the clients about the changes they see.
+    fn handle_storage_event(
+        actors: Arc<Mutex<ActorRegistry>>,
+        mut connections: Vec<TcpStream>,
+        actor_pipelines: &HashMap<PipelineId, String>,
+        stored_data: &Arc<Mutex<StoredData>>,
+        event: StorageEvent,
+    ) {
+        let actors = actors.lock().unwrap();
+        let storage_actors: Vec<&StorageActor> = actor_pipelines
+            .values()
+            .map(|name| {
+                let target = actors.find::<BrowsingContextActor>(name);
+                actors.find::<StorageActor>(&target.storage)
+            })
+            .collect();
+        let before: Vec<_> = storage_actors
+            .iter()
+            .map(|storage| storage.objects(&actors))
+            .collect();
+        stored_data.lock().unwrap().update(event);
+        for (storage, before) in storage_actors.into_iter().zip(before) {
+            if let Some(msg) = storage.stores_update(&actors, before) {
+                for stream in &mut connections {
+                    stream.write_json_packet(&msg);
+                }
+            }
+        }
+    }
+
     // Find the name of NetworkEventActor corresponding to request_id
     // Create a new one if it does not exist, add it to the actor_requests hashmap
     fn find_network_event_actor(
@@ -572,6 +641,7 @@ fn run_server(
                 script_sender,
                 &mut actor_pipelines,
                 &mut actor_workers,
+                &stored_data,
                 pageinfo,
             ),
             DevtoolsControlMsg::FromScript(ScriptToDevtoolsControlMsg::ConsoleAPI(
@@ -619,6 +689,7 @@ fn run_server(
                 let pipeline_id = match network_event {
                     NetworkEvent::HttpResponse(ref response) => response.pipeline_id,
                     NetworkEvent::HttpRequest(ref request) => request.pipeline_id,
+                    NetworkEvent::HttpResponseContent(ref content) => content.pipeline_id,
                 };
                 handle_network_event(
                     actors.clone(),
@@ -631,6 +702,19 @@ fn run_server(
                     network_event,
                 );
             },
+            DevtoolsControlMsg::FromChrome(ChromeToDevtoolsControlMsg::StorageEvent(event)) => {
+                let mut connections = Vec::<TcpStream>::new();
+                for stream in &accepted_connections {
+                    connections.push(stream.try_clone().unwrap());
+                }
+                handle_storage_event(
+                    actors.clone(),
+                    connections,
+                    &actor_pipelines,
+                    &stored_data,
+                    event,
+                );
+            },
             DevtoolsControlMsg::FromChrome(ChromeToDevtoolsControlMsg::ServerExitMsg) => break,
         }
     }
@@ -23,6 +23,7 @@ use http::HeaderMap;
 use ipc_channel::ipc::IpcSender;
 use msg::constellation_msg::PipelineId;
 use servo_url::ServoUrl;
+use std::collections::BTreeMap;
 use std::net::TcpStream;
 use time::{self, Duration, Tm};
 
@@ -62,6 +63,8 @@ pub enum ChromeToDevtoolsControlMsg {
     /// A network event occurred (request, reply, etc.). The actor with the
     /// provided name should be notified.
     NetworkEvent(String, NetworkEvent),
+    /// The cookies or storage items of a host or an origin changed.
+    StorageEvent(StorageEvent),
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -312,10 +315,47 @@ pub struct HttpResponse {
     pub pipeline_id: PipelineId,
 }
 
+/// The body of a response, sent once it was entirely received.
+#[derive(Debug, PartialEq)]
+pub struct HttpResponseContent {
+    pub body: Vec<u8>,
+    pub pipeline_id: PipelineId,
+    /// The time it took to receive the body, in milliseconds.
+    pub receive_time: u64,
+}
+
 #[derive(Debug)]
 pub enum NetworkEvent {
     HttpRequest(HttpRequest),
     HttpResponse(HttpResponse),
+    HttpResponseContent(HttpResponseContent),
+}
+
+/// A cookie, as shown in the storage inspector.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CookieInfo {
+    pub name: String,
+    pub value: String,
+    pub domain: String,
+    pub path: String,
+    /// The expiry time of the cookie in milliseconds since the epoch, if it
+    /// is not a session cookie.
+    pub expires: Option<i64>,
+    pub creation_time: i64,
+    pub last_access: i64,
+    pub host_only: bool,
+    pub http_only: bool,
+    pub secure: bool,
+}
+
+/// The current state of the data stored for a host or an origin, sent
+/// whenever it changes.
+#[derive(Debug)]
+pub enum StorageEvent {
+    /// The cookies stored for the given registrable domain.
+    Cookies(String, Vec<CookieInfo>),
+    /// The local storage items of the origin with the given serialization.
+    LocalStorage(String, BTreeMap<String, String>),
 }
 
 impl TimelineMarker {
@@ -17,8 +17,9 @@ use crossbeam_channel::{Receiver, Sender};
 use ipc_channel::ipc::IpcSender;
 use keyboard_types::KeyboardEvent;
 use msg::constellation_msg::{InputMethodType, PipelineId, TopLevelBrowsingContextId};
-use servo_url::ServoUrl;
+use servo_url::{ImmutableOrigin, ServoUrl};
 use std::fmt::{Debug, Error, Formatter};
+use uuid::Uuid;
 use webrender_api::{DeviceIntPoint, DeviceIntSize};
 
 /// A cursor for the window. This is different from a CSS cursor (see
@@ -69,6 +70,15 @@ pub trait EventLoopWaker: 'static + Send {
     fn wake(&self);
 }
 
+/// Gives access to the clipboard of the platform, provided by the servo
+/// port/embedder. It gets used on the constellation thread.
+pub trait EmbedderClipboard: 'static + Send {
+    /// Returns the text in the clipboard.
+    fn get_text(&mut self) -> Result<String, String>;
+    /// Replaces the contents of the clipboard with `text`.
+    fn set_text(&mut self, text: String) -> Result<(), String>;
+}
+
 /// Sends messages to the embedder.
 pub struct EmbedderProxy {
     pub sender: Sender<(Option<TopLevelBrowsingContextId>, EmbedderMsg)>,
@@ -162,6 +172,21 @@ pub enum EmbedderMsg {
     Shutdown,
     /// Report a complete sampled profile
     ReportProfile(Vec<u8>),
+    /// Present a notification to the user, reporting what the user does with
+    /// it on the sender, if there is one.
+    ShowNotification(
+        NotificationId,
+        Notification,
+        Option<IpcSender<NotificationEvent>>,
+    ),
+    /// Take down a notification presented to the user.
+    CloseNotification(NotificationId),
+    /// The accessibility tree of the document of a browser changed, for the
+    /// embedder to expose it to the accessibility APIs of the platform.
+    UpdateAccessibilityTree(AccessibilityTreeUpdate),
+    /// The document asked to be printed, which embedders can do by sending
+    /// `WindowEvent::PrintToPDF`.
+    Print,
 }
 
 impl Debug for EmbedderMsg {
@@ -192,6 +217,10 @@ impl Debug for EmbedderMsg {
             EmbedderMsg::AllowOpeningBrowser(..) => write!(f, "AllowOpeningBrowser"),
             EmbedderMsg::BrowserCreated(..) => write!(f, "BrowserCreated"),
             EmbedderMsg::ReportProfile(..) => write!(f, "ReportProfile"),
+            EmbedderMsg::ShowNotification(..) => write!(f, "ShowNotification"),
+            EmbedderMsg::CloseNotification(..) => write!(f, "CloseNotification"),
+            EmbedderMsg::UpdateAccessibilityTree(..) => write!(f, "UpdateAccessibilityTree"),
+            EmbedderMsg::Print => write!(f, "Print"),
         }
     }
 }
@@ -200,3 +229,143 @@ impl Debug for EmbedderMsg {
 /// the `String` content is expected to be extension (e.g, "doc", without the prefixing ".")
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct FilterPattern(pub String);
+
+/// The session history of a top-level browsing context, as reported by
+/// `EmbedderMsg::HistoryChanged`, which embedders can save to restore the
+/// browser later with `WindowEvent::RestoreBrowser`.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct SessionHistory {
+    /// The URLs of the entries, from the oldest to the most recent.
+    pub urls: Vec<ServoUrl>,
+    /// The index of the current entry.
+    pub current: usize,
+}
+
+/// Identifies a notification presented by the embedder.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct NotificationId(pub Uuid);
+
+impl NotificationId {
+    pub fn new() -> NotificationId {
+        NotificationId(Uuid::new_v4())
+    }
+}
+
+/// A notification to be presented by the embedder.
+///
+/// <https://notifications.spec.whatwg.org/#concept-notification>
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Notification {
+    pub title: String,
+    pub body: String,
+    /// A notification replaces the one of the same origin with the same tag,
+    /// unless the tag is empty.
+    pub tag: String,
+    pub origin: ImmutableOrigin,
+    pub image: Option<ServoUrl>,
+    pub icon: Option<ServoUrl>,
+    pub badge: Option<ServoUrl>,
+    /// The time the notification is about, in milliseconds since the epoch.
+    pub timestamp: u64,
+    /// Whether the user gets alerted again when the notification replaces
+    /// another one.
+    pub renotify: bool,
+    /// Whether the notification is to be presented without sound nor
+    /// vibration.
+    pub silent: bool,
+    /// Whether the notification stays until the user activates or dismisses
+    /// it.
+    pub require_interaction: bool,
+}
+
+/// What the user did with a notification presented by the embedder.
+#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+pub enum NotificationEvent {
+    /// The user activated the notification.
+    Click,
+    /// The user dismissed the notification, or it went away on its own.
+    Close,
+}
+
+/// Identifies a node of the accessibility tree of a document.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct AccessibilityNodeId(pub u64);
+
+/// What kind of object an accessibility node is, for assistive technologies.
+///
+/// <https://w3c.github.io/html-aam/#html-element-role-mappings>
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum AccessibilityRole {
+    Article,
+    Banner,
+    Button,
+    Cell,
+    CheckBox,
+    ColumnHeader,
+    ComboBox,
+    ContentInfo,
+    Dialog,
+    Document,
+    Form,
+    Generic,
+    Heading,
+    Image,
+    Link,
+    List,
+    ListBox,
+    ListBoxOption,
+    ListItem,
+    Main,
+    Navigation,
+    Paragraph,
+    ProgressIndicator,
+    RadioButton,
+    Region,
+    Row,
+    RowHeader,
+    Separator,
+    Slider,
+    StaticText,
+    Table,
+    TextInput,
+}
+
+/// A rectangle in CSS pixels, relative to the origin of the document.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub struct AccessibilityRect {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+/// A node of the accessibility tree of a document.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct AccessibilityNode {
+    pub role: AccessibilityRole,
+    /// <https://w3c.github.io/accname/#dfn-accessible-name>
+    pub name: Option<String>,
+    /// The current value of form controls.
+    pub value: Option<String>,
+    /// The level of headings.
+    pub level: Option<u32>,
+    /// The bounds of the node, if it is being rendered.
+    pub bounds: Option<AccessibilityRect>,
+    pub focusable: bool,
+    pub disabled: bool,
+    /// Whether checkboxes and radio buttons are checked.
+    pub checked: Option<bool>,
+    pub children: Vec<AccessibilityNodeId>,
+}
+
+/// The nodes of the accessibility tree of a document which changed since
+/// the last update, nodes not reachable from the root anymore being removed.
+/// This is the model of the tree updates of AccessKit.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct AccessibilityTreeUpdate {
+    pub nodes: Vec<(AccessibilityNodeId, AccessibilityNode)>,
+    /// The root of the tree, only sent with the first update of a document.
+    pub root: Option<AccessibilityNodeId>,
+    /// The node having the focus.
+    pub focus: AccessibilityNodeId,
+}
@@ -87,15 +87,20 @@ impl FontTemplates {
         None
     }
 
-    pub fn add_template(&mut self, identifier: Atom, maybe_data: Option<Vec<u8>>) {
+    /// Adds a font to the family, and returns whether the family has it.
+    pub fn add_template(&mut self, identifier: Atom, maybe_data: Option<Vec<u8>>) -> bool {
         for template in &self.templates {
             if *template.identifier() == identifier {
-                return;
+                return true;
             }
         }
 
-        if let Ok(template) = FontTemplate::new(identifier, maybe_data) {
-            self.templates.push(template);
+        match FontTemplate::new(identifier, maybe_data) {
+            Ok(template) => {
+                self.templates.push(template);
+                true
+            },
+            Err(_) => false,
         }
     }
 }
@@ -113,8 +118,9 @@ pub enum Command {
         Au,
         IpcSender<webrender_api::FontInstanceKey>,
     ),
-    AddWebFont(LowercaseString, EffectiveSources, IpcSender<()>),
-    AddDownloadedWebFont(LowercaseString, ServoUrl, Vec<u8>, IpcSender<()>),
+    AddWebFont(LowercaseString, EffectiveSources, IpcSender<bool>),
+    AddDownloadedWebFont(LowercaseString, ServoUrl, Vec<u8>, IpcSender<bool>),
+    AddWebFontData(LowercaseString, Vec<u8>, IpcSender<bool>),
     Exit(IpcSender<()>),
     Ping,
 }
@@ -138,6 +144,8 @@ struct FontCache {
     webrender_api: webrender_api::RenderApi,
     webrender_fonts: HashMap<Atom, webrender_api::FontKey>,
     font_instances: HashMap<(webrender_api::FontKey, Au), webrender_api::FontInstanceKey>,
+    /// The number of fonts given as data so far, which tells them apart.
+    web_font_data_count: usize,
 }
 
 fn populate_generic_fonts() -> HashMap<FontFamilyName, LowercaseString> {
@@ -200,8 +208,11 @@ impl FontCache {
                 },
                 Command::AddDownloadedWebFont(family_name, url, bytes, result) => {
                     let templates = &mut self.web_families.get_mut(&family_name).unwrap();
-                    templates.add_template(Atom::from(url.to_string()), Some(bytes));
-                    drop(result.send(()));
+                    let added = templates.add_template(Atom::from(url.to_string()), Some(bytes));
+                    drop(result.send(added));
+                },
+                Command::AddWebFontData(family_name, bytes, result) => {
+                    self.handle_add_web_font_data(family_name, bytes, result);
                 },
                 Command::Ping => (),
                 Command::Exit(result) => {
@@ -216,12 +227,12 @@ impl FontCache {
         &mut self,
         family_name: LowercaseString,
         mut sources: EffectiveSources,
-        sender: IpcSender<()>,
+        sender: IpcSender<bool>,
     ) {
         let src = if let Some(src) = sources.next() {
             src
         } else {
-            sender.send(()).unwrap();
+            sender.send(false).unwrap();
             return;
         };
 
@@ -319,7 +330,7 @@ impl FontCache {
                     templates.add_template(Atom::from(&*path), None);
                 });
                 if found {
-                    sender.send(()).unwrap();
+                    sender.send(true).unwrap();
                 } else {
                     let msg = Command::AddWebFont(family_name, sources, sender);
                     self.channel_to_self.send(msg).unwrap();
@@ -328,6 +339,32 @@ impl FontCache {
         }
     }
 
+    fn handle_add_web_font_data(
+        &mut self,
+        family_name: LowercaseString,
+        bytes: Vec<u8>,
+        sender: IpcSender<bool>,
+    ) {
+        let bytes = match fontsan::process(&bytes) {
+            Ok(san) => san,
+            Err(_) => {
+                debug!("Sanitiser rejected web font: family={}", family_name);
+                return sender.send(false).unwrap();
+            },
+        };
+        self.web_font_data_count += 1;
+        let identifier = Atom::from(format!(
+            "web-font-data:{}:{}",
+            family_name, self.web_font_data_count
+        ));
+        let added = self
+            .web_families
+            .entry(family_name)
+            .or_insert_with(FontTemplates::new)
+            .add_template(identifier, Some(bytes));
+        sender.send(added).unwrap();
+    }
+
     fn refresh_local_families(&mut self) {
         self.local_families.clear();
         for_each_available_family(|family_name| {
@@ -469,6 +506,7 @@ impl FontCacheThread {
                     webrender_api,
                     webrender_fonts: HashMap::new(),
                     font_instances: HashMap::new(),
+                    web_font_data_count: 0,
                 };
 
                 cache.refresh_local_families();
@@ -483,7 +521,7 @@ impl FontCacheThread {
         &self,
         family: FamilyName,
         sources: EffectiveSources,
-        sender: IpcSender<()>,
+        sender: IpcSender<bool>,
     ) {
         self.chan
             .send(Command::AddWebFont(
@@ -494,6 +532,18 @@ impl FontCacheThread {
             .unwrap();
     }
 
+    /// Adds a web font whose data is already at hand, instead of having to be
+    /// fetched from the sources of a `@font-face` rule.
+    pub fn add_web_font_data(&self, family: FamilyName, bytes: Vec<u8>, sender: IpcSender<bool>) {
+        self.chan
+            .send(Command::AddWebFontData(
+                LowercaseString::new(&family.name),
+                bytes,
+                sender,
+            ))
+            .unwrap();
+    }
+
     pub fn exit(&self) {
         let (response_chan, response_port) = ipc::channel().unwrap();
         self.chan
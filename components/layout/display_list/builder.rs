@@ -19,6 +19,7 @@ use crate::display_list::items::{ClippingRegion, DisplayItem, DisplayItemMetadat
 use crate::display_list::items::{CommonDisplayItem, DisplayListSection};
 use crate::display_list::items::{IframeDisplayItem, OpaqueNode};
 use crate::display_list::items::{PopAllTextShadowsDisplayItem, PushTextShadowDisplayItem};
+use crate::display_list::items::{ScrollSnapArea, ScrollSnapContainer};
 use crate::display_list::items::{StackingContext, StackingContextType, StickyFrameData};
 use crate::display_list::items::{TextOrientation, WebRenderImageInfo};
 use crate::display_list::ToLayout;
@@ -30,14 +31,14 @@ use crate::inline::InlineFragmentNodeFlags;
 use crate::model::MaybeAuto;
 use crate::table_cell::CollapsedBordersForCell;
 use app_units::{Au, AU_PER_PX};
-use canvas_traits::canvas::{CanvasMsg, FromLayoutMsg};
+use canvas_traits::canvas::{CanvasId, CanvasMsg, FromLayoutMsg};
 use embedder_traits::Cursor;
 use euclid::{rect, Point2D, Rect, SideOffsets2D, Size2D, TypedRect, TypedSize2D, Vector2D};
 use fnv::FnvHashMap;
 use gfx::text::glyph::ByteIndex;
 use gfx::text::TextRun;
 use gfx_traits::{combine_id_with_fragment_type, FragmentType, StackingContextId};
-use ipc_channel::ipc;
+use ipc_channel::ipc::{self, IpcSender};
 use msg::constellation_msg::PipelineId;
 use net_traits::image_cache::UsePlaceholder;
 use range::Range;
@@ -47,7 +48,7 @@ use servo_geometry::MaxRect;
 use std::default::Default;
 use std::f32;
 use std::mem;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 use style::computed_values::border_style::T as BorderStyle;
 use style::computed_values::overflow_x::T as StyleOverflow;
 use style::computed_values::pointer_events::T as PointerEvents;
@@ -58,7 +59,7 @@ use style::properties::{style_structs, ComputedValues};
 use style::servo::restyle_damage::ServoRestyleDamage;
 use style::values::computed::effects::SimpleShadow;
 use style::values::computed::image::Image as ComputedImage;
-use style::values::computed::{Gradient, LengthOrAuto};
+use style::values::computed::{Gradient, LengthOrAuto, ScrollSnapAlign, ScrollSnapType};
 use style::values::generics::background::BackgroundSize;
 use style::values::generics::image::{GradientKind, Image, PaintWorklet};
 use style::values::specified::ui::CursorKind;
@@ -193,6 +194,10 @@ pub struct StackingContextCollectionState {
 
     /// The flow parent's content box, used to calculate sticky constraints.
     parent_stacking_relative_content_box: Rect<Au>,
+
+    /// The scroll containers with a `scroll-snap-type`, by the index of their
+    /// ClipScrollNode.
+    scroll_snap_containers: FnvHashMap<ClipScrollNodeIndex, ScrollSnapContainer>,
 }
 
 impl StackingContextCollectionState {
@@ -227,6 +232,7 @@ impl StackingContextCollectionState {
             clip_stack: Vec::new(),
             containing_block_clip_stack: Vec::new(),
             parent_stacking_relative_content_box: Rect::zero(),
+            scroll_snap_containers: FnvHashMap::default(),
         }
     }
 
@@ -287,6 +293,20 @@ impl StackingContextCollectionState {
 
         index
     }
+
+    /// Returns the index of the scroll frame that content in the given ClipScrollNode
+    /// scrolls in, or `None` if it is positioned by a reference frame instead.
+    fn scroll_frame_for_node(&self, mut index: ClipScrollNodeIndex) -> Option<ClipScrollNodeIndex> {
+        while !index.is_root_scroll_node() {
+            let node = &self.clip_scroll_nodes[index.to_index()];
+            match node.node_type {
+                ClipScrollNodeType::ScrollFrame(..) => break,
+                ClipScrollNodeType::Placeholder => return None,
+                _ => index = node.parent_index,
+            }
+        }
+        Some(index)
+    }
 }
 
 pub struct DisplayListBuildState<'a> {
@@ -323,6 +343,10 @@ pub struct DisplayListBuildState<'a> {
 
     /// Stores text runs to answer text queries used to place a cursor inside text.
     pub indexable_text: IndexableText,
+
+    /// The scroll containers with a `scroll-snap-type`, by the index of their
+    /// ClipScrollNode.
+    pub scroll_snap_containers: FnvHashMap<ClipScrollNodeIndex, ScrollSnapContainer>,
 }
 
 impl<'a> DisplayListBuildState<'a> {
@@ -343,6 +367,7 @@ impl<'a> DisplayListBuildState<'a> {
             ),
             iframe_sizes: Vec::new(),
             indexable_text: IndexableText::default(),
+            scroll_snap_containers: state.scroll_snap_containers,
         }
     }
 
@@ -460,6 +485,11 @@ impl<'a> DisplayListBuildState<'a> {
         DisplayList {
             list: list,
             clip_scroll_nodes: self.clip_scroll_nodes,
+            scroll_snap_containers: self
+                .scroll_snap_containers
+                .drain()
+                .map(|(_, container)| container)
+                .collect(),
         }
     }
 
@@ -1780,8 +1810,7 @@ impl Fragment {
             SpecificFragmentInfo::InlineBlock(_) |
             SpecificFragmentInfo::InlineAbsoluteHypothetical(_) |
             SpecificFragmentInfo::InlineAbsolute(_) |
-            SpecificFragmentInfo::TruncatedFragment(_) |
-            SpecificFragmentInfo::Svg(_) => {
+            SpecificFragmentInfo::TruncatedFragment(_) => {
                 if opts::get().show_debug_fragment_borders {
                     self.build_debug_borders_around_fragment(
                         state,
@@ -1860,18 +1889,31 @@ impl Fragment {
                     CanvasFragmentSource::WebGL(image_key) => image_key,
                     CanvasFragmentSource::Image(ref ipc_renderer) => match *ipc_renderer {
                         Some(ref ipc_renderer) => {
-                            let ipc_renderer = ipc_renderer.lock().unwrap();
-                            let (sender, receiver) = ipc::channel().unwrap();
-                            ipc_renderer
-                                .send(CanvasMsg::FromLayout(
-                                    FromLayoutMsg::SendData(sender),
-                                    canvas_fragment_info.canvas_id.clone(),
-                                ))
-                                .unwrap();
-                            receiver.recv().unwrap().image_key
+                            canvas_image_key(ipc_renderer, &canvas_fragment_info.canvas_id)
                         },
                         None => return,
                     },
+                    CanvasFragmentSource::WebGPU(image_key) => image_key,
+                };
+
+                let base = create_base_display_item(state);
+                let display_item = webrender_api::ImageDisplayItem {
+                    image_key,
+                    stretch_size: stacking_relative_content_box.size.to_layout(),
+                    tile_spacing: LayoutSize::zero(),
+                    image_rendering: ImageRendering::Auto,
+                    alpha_type: webrender_api::AlphaType::PremultipliedAlpha,
+                    color: webrender_api::ColorF::WHITE,
+                };
+
+                state.add_image_item(base, display_item);
+            },
+            SpecificFragmentInfo::Svg(ref fragment_info) => {
+                let image_key = match fragment_info.canvas {
+                    Some((ref ipc_renderer, ref canvas_id)) => {
+                        canvas_image_key(ipc_renderer, canvas_id)
+                    },
+                    None => return,
                 };
 
                 let base = create_base_display_item(state);
@@ -2027,8 +2069,8 @@ impl Fragment {
             container_size,
         );
 
-        // Underline
-        if text_decorations.underline {
+        // Underline, which also marks the text being composed by an input method.
+        if text_decorations.underline || text_fragment.composing() {
             let mut stacking_relative_box = logical_stacking_relative_content_box;
             stacking_relative_box.start.b = logical_stacking_relative_content_box.start.b +
                 metrics.ascent -
@@ -2431,6 +2473,8 @@ impl BlockFlow {
             self.transform_clip_to_coordinate_space(state, preserved_state);
         }
 
+        self.setup_scroll_snapping(state);
+
         if !flags.contains(StackingContextCollectionFlags::NEVER_CREATES_CLIP_SCROLL_NODE) {
             self.setup_clip_scroll_node_for_position(state, stacking_relative_border_box);
             self.setup_clip_scroll_node_for_overflow(state, stacking_relative_border_box);
@@ -2601,12 +2645,92 @@ impl BlockFlow {
             content_rect: Rect::new(content_box.origin, content_size).to_layout(),
             node_type: ClipScrollNodeType::ScrollFrame(sensitivity, external_id),
         });
+        self.add_scroll_snap_container(
+            state,
+            new_clip_scroll_index,
+            external_id,
+            clip_rect.to_layout(),
+            Rect::new(content_box.origin, content_size).to_layout(),
+        );
 
         let new_clipping_and_scrolling = ClippingAndScrolling::simple(new_clip_scroll_index);
         self.base.clipping_and_scrolling = Some(new_clipping_and_scrolling);
         state.current_clipping_and_scrolling = new_clipping_and_scrolling;
     }
 
+    /// Registers this block as a snap area of the scroll container it is in, and the
+    /// root element's `scroll-snap-type` as the one of the viewport.
+    ///
+    /// <https://drafts.csswg.org/css-scroll-snap-1/#scroll-snap-area>
+    fn setup_scroll_snapping(&self, state: &mut StackingContextCollectionState) {
+        if self.is_root() {
+            // The viewport is only known to the layout thread, which fills
+            // in the scrollport and content rect of the root scroll frame.
+            let scroll_id = state.pipeline_id.root_scroll_id();
+            self.add_scroll_snap_container(
+                state,
+                ClipScrollNodeIndex::root_scroll_node(),
+                scroll_id,
+                LayoutRect::zero(),
+                LayoutRect::zero(),
+            );
+        }
+
+        let style = &self.fragment.style;
+        let align = style.get_box().scroll_snap_align;
+        if align == ScrollSnapAlign::none() {
+            return;
+        }
+        let scroll_frame =
+            match state.scroll_frame_for_node(state.current_clipping_and_scrolling.scrolling) {
+                Some(scroll_frame) => scroll_frame,
+                None => return,
+            };
+        let container = match state.scroll_snap_containers.get_mut(&scroll_frame) {
+            Some(container) => container,
+            None => return,
+        };
+        let (align_x, align_y) = if style.writing_mode.is_vertical() {
+            (align.block, align.inline)
+        } else {
+            (align.inline, align.block)
+        };
+        container.areas.push(ScrollSnapArea {
+            rect: self
+                .stacking_relative_border_box(CoordinateSystem::Parent)
+                .to_layout(),
+            align_x,
+            align_y,
+        });
+    }
+
+    fn add_scroll_snap_container(
+        &self,
+        state: &mut StackingContextCollectionState,
+        index: ClipScrollNodeIndex,
+        scroll_id: ExternalScrollId,
+        scrollport: LayoutRect,
+        content_rect: LayoutRect,
+    ) {
+        let box_style = self.fragment.style.get_box();
+        if box_style.scroll_snap_type_x == ScrollSnapType::None &&
+            box_style.scroll_snap_type_y == ScrollSnapType::None
+        {
+            return;
+        }
+        state.scroll_snap_containers.insert(
+            index,
+            ScrollSnapContainer {
+                scroll_id,
+                snap_type_x: box_style.scroll_snap_type_x,
+                snap_type_y: box_style.scroll_snap_type_y,
+                scrollport,
+                content_rect,
+                areas: Vec::new(),
+            },
+        );
+    }
+
     /// Adds a scroll root for a block to take the `clip` property into account
     /// per CSS 2.1 § 11.1.2.
     fn setup_clip_scroll_node_for_css_clip(
@@ -2904,6 +3028,22 @@ fn get_cursor(values: &ComputedValues, default_cursor: Cursor) -> Option<Cursor>
     })
 }
 
+/// Asks a canvas paint thread for the image key of the current bitmap of a canvas.
+fn canvas_image_key(
+    ipc_renderer: &Mutex<IpcSender<CanvasMsg>>,
+    canvas_id: &CanvasId,
+) -> webrender_api::ImageKey {
+    let ipc_renderer = ipc_renderer.lock().unwrap();
+    let (sender, receiver) = ipc::channel().unwrap();
+    ipc_renderer
+        .send(CanvasMsg::FromLayout(
+            FromLayoutMsg::SendData(sender),
+            canvas_id.clone(),
+        ))
+        .unwrap();
+    receiver.recv().unwrap().image_key
+}
+
 /// Adjusts `content_rect` as necessary for the given spread, and blur so that the resulting
 /// bounding rect contains all of a shadow's ink.
 fn shadow_bounds(content_rect: Rect<Au>, blur: Au, spread: Au) -> Rect<Au> {
@@ -22,7 +22,10 @@ use std::cmp::Ordering;
 use std::collections::HashMap;
 use std::f32;
 use std::fmt;
+use std::sync::{Arc, Weak};
 use style::computed_values::_servo_top_layer::T as InTopLayer;
+use style::values::computed::ScrollSnapType;
+use style::values::specified::box_::ScrollSnapAlignKeyword;
 use webrender_api as wr;
 use webrender_api::{BorderRadius, ClipMode};
 use webrender_api::{ComplexClipRegion, ExternalScrollId, FilterOp};
@@ -39,7 +42,7 @@ pub static BLUR_INFLATION_FACTOR: i32 = 3;
 
 /// An index into the vector of ClipScrollNodes. During WebRender conversion these nodes
 /// are given ClipIds.
-#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, Serialize)]
 pub struct ClipScrollNodeIndex(usize);
 
 impl ClipScrollNodeIndex {
@@ -100,6 +103,7 @@ impl ClippingAndScrolling {
 pub struct DisplayList {
     pub list: Vec<DisplayItem>,
     pub clip_scroll_nodes: Vec<ClipScrollNode>,
+    pub scroll_snap_containers: Vec<ScrollSnapContainer>,
 }
 
 impl DisplayList {
@@ -138,6 +142,45 @@ impl DisplayList {
     }
 }
 
+/// Remembers the WebRender display list that the last sent display list was
+/// converted to, so that display reflows which did not rebuild the display
+/// list don't convert it again.
+///
+/// The converted list is still sent on every display reflow, along with a new
+/// epoch and the current viewport size. Only whole display lists are
+/// compared: stacking contexts of a rebuilt list are converted again even if
+/// they did not change, since the builder does not give them the same clip
+/// and scroll node indices across builds.
+pub struct ConvertedDisplayList<T> {
+    last: Option<(Weak<DisplayList>, T)>,
+}
+
+impl<T> Default for ConvertedDisplayList<T> {
+    fn default() -> Self {
+        ConvertedDisplayList { last: None }
+    }
+}
+
+impl<T> ConvertedDisplayList<T> {
+    /// What the given display list was converted to, if it is the one that
+    /// was converted last.
+    pub fn get(&self, display_list: &Arc<DisplayList>) -> Option<&T> {
+        self.last
+            .as_ref()
+            .filter(|(last, _)| {
+                last.upgrade()
+                    .map_or(false, |last| Arc::ptr_eq(&last, display_list))
+            })
+            .map(|(_, converted)| converted)
+    }
+
+    pub fn set(&mut self, display_list: &Arc<DisplayList>, converted: T) {
+        // The weak reference keeps the allocation alive, so a later display
+        // list cannot be mistaken for this one by having the same address.
+        self.last = Some((Arc::downgrade(display_list), converted));
+    }
+}
+
 impl gfx_traits::DisplayList for DisplayList {
     /// Analyze the display list to figure out if this may be the first
     /// contentful paint (i.e. the display list contains items of type text,
@@ -388,6 +431,120 @@ impl ClipScrollNode {
     }
 }
 
+/// How close to a `proximity` snap position, as a fraction of the scrollport,
+/// a scroll container has to come to rest for it to snap.
+const SCROLL_SNAP_PROXIMITY: f32 = 0.3;
+
+/// An element with a `scroll-snap-align`, in the coordinates of the scroll
+/// container it snaps in.
+#[derive(Clone, Debug, Serialize)]
+pub struct ScrollSnapArea {
+    pub rect: LayoutRect,
+    pub align_x: ScrollSnapAlignKeyword,
+    pub align_y: ScrollSnapAlignKeyword,
+}
+
+/// A scroll container with a `scroll-snap-type`, along with its snap areas.
+#[derive(Clone, Debug, Serialize)]
+pub struct ScrollSnapContainer {
+    /// The scroll frame of this container.
+    pub scroll_id: ExternalScrollId,
+
+    pub snap_type_x: ScrollSnapType,
+    pub snap_type_y: ScrollSnapType,
+
+    /// The rect the contents are seen through.
+    pub scrollport: LayoutRect,
+
+    /// The rect of the contents that can be scrolled inside of the scrollport.
+    pub content_rect: LayoutRect,
+
+    pub areas: Vec<ScrollSnapArea>,
+}
+
+impl ScrollSnapContainer {
+    /// Returns the scroll offset this container comes to rest on after it
+    /// has been scrolled to `scroll_offset`. Like WebRender's, the offsets are
+    /// the opposite of the scroll position.
+    ///
+    /// <https://drafts.csswg.org/css-scroll-snap-1/#choosing>
+    pub fn snap(&self, scroll_offset: Vector2D<f32>) -> Vector2D<f32> {
+        let port = &self.scrollport;
+        let x = snap_axis(
+            self.snap_type_x,
+            -scroll_offset.x,
+            self.content_rect.max_x() - port.max_x(),
+            port.size.width,
+            self.areas.iter().filter_map(|area| {
+                snap_position(
+                    area.align_x,
+                    area.rect.min_x() - port.min_x(),
+                    area.rect.max_x() - port.max_x(),
+                )
+            }),
+        );
+        let y = snap_axis(
+            self.snap_type_y,
+            -scroll_offset.y,
+            self.content_rect.max_y() - port.max_y(),
+            port.size.height,
+            self.areas.iter().filter_map(|area| {
+                snap_position(
+                    area.align_y,
+                    area.rect.min_y() - port.min_y(),
+                    area.rect.max_y() - port.max_y(),
+                )
+            }),
+        );
+        Vector2D::new(-x, -y)
+    }
+}
+
+/// The scroll position on one axis that aligns a snap area with the
+/// scrollport, given the ones aligning their start and end edges.
+fn snap_position(align: ScrollSnapAlignKeyword, start: f32, end: f32) -> Option<f32> {
+    match align {
+        ScrollSnapAlignKeyword::None => None,
+        ScrollSnapAlignKeyword::Start => Some(start),
+        ScrollSnapAlignKeyword::End => Some(end),
+        ScrollSnapAlignKeyword::Center => Some((start + end) / 2.),
+    }
+}
+
+fn snap_axis<I>(
+    snap_type: ScrollSnapType,
+    position: f32,
+    max_position: f32,
+    scrollport_extent: f32,
+    snap_positions: I,
+) -> f32
+where
+    I: Iterator<Item = f32>,
+{
+    if snap_type == ScrollSnapType::None {
+        return position;
+    }
+
+    let max_position = max_position.max(0.);
+    let nearest = snap_positions
+        .map(|snap_position| snap_position.max(0.).min(max_position))
+        .min_by(|a, b| {
+            (a - position)
+                .abs()
+                .partial_cmp(&(b - position).abs())
+                .unwrap_or(Ordering::Equal)
+        });
+    match nearest {
+        Some(nearest)
+            if snap_type == ScrollSnapType::Mandatory ||
+                (nearest - position).abs() <= scrollport_extent * SCROLL_SNAP_PROXIMITY =>
+        {
+            nearest
+        },
+        _ => position,
+    }
+}
+
 /// One drawing command in the list.
 #[derive(Clone, Serialize)]
 pub enum DisplayItem {
@@ -338,6 +338,7 @@ impl InlineAbsoluteFragmentInfo {
 pub enum CanvasFragmentSource {
     WebGL(webrender_api::ImageKey),
     Image(Option<Arc<Mutex<IpcSender<CanvasMsg>>>>),
+    WebGPU(webrender_api::ImageKey),
 }
 
 #[derive(Clone)]
@@ -355,6 +356,7 @@ impl CanvasFragmentInfo {
             HTMLCanvasDataSource::Image(ipc_sender) => CanvasFragmentSource::Image(
                 ipc_sender.map(|renderer| Arc::new(Mutex::new(renderer))),
             ),
+            HTMLCanvasDataSource::WebGPU(image_key) => CanvasFragmentSource::WebGPU(image_key),
         };
 
         CanvasFragmentInfo {
@@ -383,6 +385,7 @@ impl MediaFragmentInfo {
 pub struct SvgFragmentInfo {
     pub dom_width: Au,
     pub dom_height: Au,
+    pub canvas: Option<(Arc<Mutex<IpcSender<CanvasMsg>>>, CanvasId)>,
 }
 
 impl SvgFragmentInfo {
@@ -390,6 +393,9 @@ impl SvgFragmentInfo {
         SvgFragmentInfo {
             dom_width: Au::from_px(data.width as i32),
             dom_height: Au::from_px(data.height as i32),
+            canvas: data
+                .canvas
+                .map(|(renderer, canvas_id)| (Arc::new(Mutex::new(renderer)), canvas_id)),
         }
     }
 }
@@ -537,6 +543,9 @@ bitflags! {
         ///
         /// This handles cases like Foo<span>bar</span>
         const SUPPRESS_LINE_BREAK_BEFORE = 0x04;
+
+        /// Is this fragment being composed by an input method?
+        const COMPOSING = 0x08;
     }
 }
 
@@ -571,6 +580,10 @@ impl ScannedTextFragmentInfo {
     pub fn selected(&self) -> bool {
         self.flags.contains(ScannedTextFlags::SELECTED)
     }
+
+    pub fn composing(&self) -> bool {
+        self.flags.contains(ScannedTextFlags::COMPOSING)
+    }
 }
 
 /// Describes how to split a fragment. This is used during line breaking as part of the return
@@ -620,6 +633,9 @@ pub struct UnscannedTextFragmentInfo {
 
     /// The selected text range.  An empty range represents the insertion point.
     pub selection: Option<Range<ByteIndex>>,
+
+    /// The range of the text being composed by an input method.
+    pub composition: Option<Range<ByteIndex>>,
 }
 
 impl UnscannedTextFragmentInfo {
@@ -629,6 +645,7 @@ impl UnscannedTextFragmentInfo {
         UnscannedTextFragmentInfo {
             text: text,
             selection: selection,
+            composition: None,
         }
     }
 }
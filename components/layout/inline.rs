@@ -429,6 +429,7 @@ impl LineBreaker {
                         result.border_padding.inline_end == Au(0) &&
                         candidate.border_padding.inline_start == Au(0) &&
                         result_info.selected() == candidate_info.selected() &&
+                        result_info.composing() == candidate_info.composing() &&
                         Arc::ptr_eq(&result_info.run, &candidate_info.run) &&
                         inline_contexts_are_equal(
                             &result.inline_context,
@@ -18,7 +18,7 @@ use app_units::Au;
 use euclid::{Point2D, Rect, Size2D, Vector2D};
 use ipc_channel::ipc::IpcSender;
 use msg::constellation_msg::PipelineId;
-use script_layout_interface::rpc::TextIndexResponse;
+use script_layout_interface::rpc::{BoxSizesResponse, TextIndexResponse};
 use script_layout_interface::rpc::{ContentBoxResponse, ContentBoxesResponse, LayoutRPC};
 use script_layout_interface::rpc::{NodeGeometryResponse, NodeScrollIdResponse};
 use script_layout_interface::rpc::{OffsetParentResponse, ResolvedStyleResponse, StyleResponse};
@@ -61,6 +61,9 @@ pub struct LayoutThreadData {
     /// A queued response for the content boxes of a node.
     pub content_boxes_response: Vec<Rect<Au>>,
 
+    /// A queued response for the content and border box sizes of a node.
+    pub box_sizes_response: BoxSizesResponse,
+
     /// A queued response for the client {top, left, width, height} of a node in pixels.
     pub client_rect_response: Rect<i32>,
 
@@ -133,6 +136,10 @@ impl LayoutRPC for LayoutRPCImpl {
         ContentBoxesResponse(rw_data.content_boxes_response.clone())
     }
 
+    fn box_sizes(&self) -> BoxSizesResponse {
+        self.0.lock().unwrap().box_sizes_response
+    }
+
     fn nodes_from_point_response(&self) -> Vec<UntrustedNodeAddress> {
         let &LayoutRPCImpl(ref rw_data) = self;
         let rw_data = rw_data.lock().unwrap();
@@ -386,6 +393,48 @@ pub fn process_content_boxes_request(
     iterator.rects
 }
 
+/// Finds the box sizes of the first fragment of a node
+struct BoxSizesFragmentIterator {
+    node_address: OpaqueNode,
+    box_sizes: Option<BoxSizesResponse>,
+}
+
+impl BoxSizesFragmentIterator {
+    fn new(node_address: OpaqueNode) -> BoxSizesFragmentIterator {
+        BoxSizesFragmentIterator {
+            node_address: node_address,
+            box_sizes: None,
+        }
+    }
+}
+
+impl FragmentBorderBoxIterator for BoxSizesFragmentIterator {
+    fn process(&mut self, fragment: &Fragment, _: i32, border_box: &Rect<Au>) {
+        let writing_mode = fragment.style.writing_mode;
+        let border_padding = fragment.border_padding.to_physical(writing_mode);
+        let border = fragment.border_width().to_physical(writing_mode);
+        let content_origin = Point2D::new(
+            border_padding.left - border.left,
+            border_padding.top - border.top,
+        );
+        let content_size = Size2D::new(
+            border_box.size.width - border_padding.horizontal(),
+            border_box.size.height - border_padding.vertical(),
+        );
+        self.box_sizes = Some(BoxSizesResponse {
+            content_rect: Rect::new(content_origin, content_size),
+            border_box: border_box.size,
+            vertical: writing_mode.is_vertical(),
+        });
+    }
+
+    fn should_process(&mut self, fragment: &Fragment) -> bool {
+        // the boxes of an element split across lines are measured on its
+        // first fragment
+        self.box_sizes.is_none() && fragment.node == self.node_address
+    }
+}
+
 struct FragmentLocatingFragmentIterator {
     node_address: OpaqueNode,
     client_rect: Rect<i32>,
@@ -669,6 +718,18 @@ impl FragmentBorderBoxIterator for ParentOffsetBorderBoxIterator {
     }
 }
 
+/// https://drafts.csswg.org/resize-observer/#calculate-box-size
+///
+/// Nodes without a box have sizes of zero.
+pub fn process_box_sizes_request(
+    requested_node: OpaqueNode,
+    layout_root: &mut dyn Flow,
+) -> BoxSizesResponse {
+    let mut iterator = BoxSizesFragmentIterator::new(requested_node);
+    sequential::iterate_through_flow_tree_fragment_border_boxes(layout_root, &mut iterator);
+    iterator.box_sizes.unwrap_or_else(BoxSizesResponse::empty)
+}
+
 pub fn process_node_geometry_request(
     requested_node: OpaqueNode,
     layout_root: &mut dyn Flow,
@@ -915,7 +976,7 @@ where
                 .result
                 .map(|r| r.to_css_string())
                 .unwrap_or(String::new())
-        }
+        },
 
         LonghandId::Bottom | LonghandId::Top | LonghandId::Right | LonghandId::Left
             if applies && positioned && style.get_box().display != Display::None =>
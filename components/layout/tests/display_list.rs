@@ -0,0 +1,45 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use layout::display_list::items::{ConvertedDisplayList, DisplayList};
+use std::sync::Arc;
+
+fn empty_display_list() -> Arc<DisplayList> {
+    Arc::new(DisplayList {
+        list: vec![],
+        clip_scroll_nodes: vec![],
+        scroll_snap_containers: vec![],
+    })
+}
+
+#[test]
+fn test_unchanged_display_list_is_not_converted_again() {
+    let mut converted = ConvertedDisplayList::default();
+    let display_list = empty_display_list();
+    assert_eq!(converted.get(&display_list), None);
+
+    converted.set(&display_list, "converted");
+    assert_eq!(converted.get(&display_list), Some(&"converted"));
+    assert_eq!(converted.get(&display_list.clone()), Some(&"converted"));
+}
+
+#[test]
+fn test_rebuilt_display_list_is_converted_again() {
+    let mut converted = ConvertedDisplayList::default();
+    let display_list = empty_display_list();
+    converted.set(&display_list, "first");
+
+    // A rebuilt display list gets converted even if it has the same items.
+    let rebuilt = empty_display_list();
+    assert_eq!(converted.get(&rebuilt), None);
+    converted.set(&rebuilt, "rebuilt");
+    assert_eq!(converted.get(&display_list), None);
+    assert_eq!(converted.get(&rebuilt), Some(&"rebuilt"));
+
+    // The converted display list being dropped doesn't make another one
+    // look converted.
+    drop(rebuilt);
+    let rebuilt_again = empty_display_list();
+    assert_eq!(converted.get(&rebuilt_again), None);
+}
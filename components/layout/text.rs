@@ -209,10 +209,12 @@ impl TextRunScanner {
                 let mut mapping = RunMapping::new(&run_info_list[..], fragment_index);
                 let text;
                 let selection;
+                let composition;
                 match in_fragment.specific {
                     SpecificFragmentInfo::UnscannedText(ref text_fragment_info) => {
                         text = &text_fragment_info.text;
                         selection = text_fragment_info.selection;
+                        composition = text_fragment_info.composition;
                     },
                     _ => panic!("Expected an unscanned text fragment!"),
                 };
@@ -253,12 +255,18 @@ impl TextRunScanner {
                             Some(range) => range.contains(ByteIndex(byte_index as isize)),
                             None => false,
                         };
+                        let composing = match composition {
+                            Some(range) => range.contains(ByteIndex(byte_index as isize)),
+                            None => false,
+                        };
 
                         // Now, if necessary, flush the mapping we were building up.
                         let flush_run = !run_info.has_font(&font) ||
                             run_info.bidi_level != bidi_level ||
                             !compatible_script;
-                        let new_mapping_needed = flush_run || mapping.selected != selected;
+                        let new_mapping_needed = flush_run ||
+                            mapping.selected != selected ||
+                            mapping.composing != composing;
 
                         if new_mapping_needed {
                             // We ignore empty mappings at the very start of a fragment.
@@ -287,6 +295,7 @@ impl TextRunScanner {
                             run_info.bidi_level = bidi_level;
                             run_info.script = script;
                             mapping.selected = selected;
+                            mapping.composing = composing;
                         }
                     }
 
@@ -425,6 +434,10 @@ impl TextRunScanner {
                     flags.insert(ScannedTextFlags::SELECTED);
                 }
 
+                if mapping.composing {
+                    flags.insert(ScannedTextFlags::COMPOSING);
+                }
+
                 let insertion_point =
                     if mapping.contains_insertion_point(scanned_run.insertion_point) {
                         scanned_run.insertion_point
@@ -651,6 +664,8 @@ struct RunMapping {
     text_run_index: usize,
     /// Is the text in this fragment selected?
     selected: bool,
+    /// Is the text in this fragment being composed by an input method?
+    composing: bool,
 }
 
 impl RunMapping {
@@ -662,6 +677,7 @@ impl RunMapping {
             old_fragment_index: fragment_index,
             text_run_index: run_info_list.len(),
             selected: false,
+            composing: false,
         }
     }
 
@@ -437,6 +437,14 @@ impl<'le> TElement for ServoLayoutElement<'le> {
         }
     }
 
+    fn animation_rule(&self) -> Option<Arc<StyleLocked<PropertyDeclarationBlock>>> {
+        unsafe { (*self.element.animation_declarations()).clone() }
+    }
+
+    fn may_have_animations(&self) -> bool {
+        self.has_animations()
+    }
+
     fn state(&self) -> ElementState {
         self.element.get_state_for_layout()
     }
@@ -548,10 +556,9 @@ impl<'le> TElement for ServoLayoutElement<'le> {
     }
 
     fn has_animations(&self) -> bool {
-        // We use this function not only for Gecko but also for Servo to know if this element has
-        // animations, so we maybe try to get the important rules of this element. This is used for
-        // off-main thread animations, but we don't support it on Servo, so return false directly.
-        false
+        // Servo only knows about the animations script runs through the Web
+        // Animations API, whose values it gets handed as a declaration block.
+        unsafe { (*self.element.animation_declarations()).is_some() }
     }
 
     fn has_css_animations(&self) -> bool {
@@ -1049,6 +1056,17 @@ impl<'ln> ThreadSafeLayoutNode for ServoThreadSafeLayoutNode<'ln> {
         })
     }
 
+    fn composition(&self) -> Option<Range<ByteIndex>> {
+        let this = unsafe { self.get_jsmanaged() };
+
+        this.composition().map(|range| {
+            Range::new(
+                ByteIndex(range.start as isize),
+                ByteIndex(range.len() as isize),
+            )
+        })
+    }
+
     fn image_url(&self) -> Option<ServoUrl> {
         let this = unsafe { self.get_jsmanaged() };
         this.image_url()
@@ -25,7 +25,7 @@ use crate::dom_wrapper::{ServoLayoutDocument, ServoLayoutElement, ServoLayoutNod
 use app_units::Au;
 use crossbeam_channel::{unbounded, Receiver, Sender};
 use embedder_traits::resources::{self, Resource};
-use euclid::{Point2D, Rect, Size2D, TypedScale, TypedSize2D};
+use euclid::{Point2D, Rect, Size2D, TypedScale, TypedSize2D, Vector2D};
 use fnv::FnvHashMap;
 use fxhash::{FxHashMap, FxHashSet};
 use gfx::font;
@@ -41,7 +41,8 @@ use layout::context::malloc_size_of_persistent_local_context;
 use layout::context::LayoutContext;
 use layout::context::RegisteredPainter;
 use layout::context::RegisteredPainters;
-use layout::display_list::items::{OpaqueNode, WebRenderImageInfo};
+use layout::display_list::items::{ClipScrollNodeIndex, OpaqueNode, ScrollOffsetMap};
+use layout::display_list::items::{ConvertedDisplayList, WebRenderImageInfo};
 use layout::display_list::{IndexableText, ToLayout, WebRenderDisplayListConverter};
 use layout::flow::{Flow, GetBaseFlow, ImmutableFlowUtils, MutableOwnedFlowUtils};
 use layout::flow_ref::FlowRef;
@@ -49,7 +50,8 @@ use layout::incremental::{RelayoutMode, SpecialRestyleDamage};
 use layout::layout_debug;
 use layout::parallel;
 use layout::query::{
-    process_content_box_request, process_content_boxes_request, LayoutRPCImpl, LayoutThreadData,
+    process_box_sizes_request, process_content_box_request, process_content_boxes_request,
+    LayoutRPCImpl, LayoutThreadData,
 };
 use layout::query::{process_element_inner_text_query, process_node_geometry_request};
 use layout::query::{process_node_scroll_area_request, process_node_scroll_id_request};
@@ -77,8 +79,10 @@ use profile_traits::time::{self as profile_time, profile, TimerMetadata};
 use profile_traits::time::{TimerMetadataFrameType, TimerMetadataReflowType};
 use script_layout_interface::message::{LayoutThreadInit, Msg, NodesFromPointQueryType, Reflow};
 use script_layout_interface::message::{QueryMsg, ReflowComplete, ReflowGoal, ScriptReflow};
+use script_layout_interface::message::WebFontSource;
 use script_layout_interface::rpc::TextIndexResponse;
-use script_layout_interface::rpc::{LayoutRPC, OffsetParentResponse, StyleResponse};
+use script_layout_interface::rpc::{BoxSizesResponse, LayoutRPC};
+use script_layout_interface::rpc::{OffsetParentResponse, StyleResponse};
 use script_layout_interface::wrapper_traits::LayoutNode;
 use script_traits::Painter;
 use script_traits::{ConstellationControlMsg, LayoutControlMsg, LayoutMsg as ConstellationMsg};
@@ -122,6 +126,7 @@ use style::thread_state::{self, ThreadState};
 use style::timer::Timer;
 use style::traversal::DomTraversal;
 use style::traversal_flags::TraversalFlags;
+use style::values::computed::font::FamilyName;
 use style_traits::CSSPixel;
 use style_traits::DevicePixel;
 use style_traits::SpeculativePainter;
@@ -150,10 +155,10 @@ pub struct LayoutThread {
     pipeline_port: Receiver<LayoutControlMsg>,
 
     /// The port on which we receive messages from the font cache thread.
-    font_cache_receiver: Receiver<()>,
+    font_cache_receiver: Receiver<bool>,
 
     /// The channel on which the font cache can send messages to us.
-    font_cache_sender: IpcSender<()>,
+    font_cache_sender: IpcSender<bool>,
 
     /// A means of communication with the background hang monitor.
     background_hang_monitor: Box<BackgroundHangMonitor>,
@@ -244,6 +249,16 @@ pub struct LayoutThread {
 
     /// The sizes of all iframes encountered during the last layout operation.
     last_iframe_sizes: RefCell<HashMap<BrowsingContextId, TypedSize2D<f32, CSSPixel>>>,
+
+    /// The WebRender display list that the last sent display list was
+    /// converted to, which reflows that don't rebuild the display list reuse.
+    converted_display_list: RefCell<
+        ConvertedDisplayList<(
+            webrender_api::PipelineId,
+            webrender_api::LayoutSize,
+            webrender_api::BuiltDisplayList,
+        )>,
+    >,
 }
 
 impl LayoutThreadFactory for LayoutThread {
@@ -416,7 +431,7 @@ fn add_font_face_rules(
     guard: &SharedRwLockReadGuard,
     device: &Device,
     font_cache_thread: &FontCacheThread,
-    font_cache_sender: &IpcSender<()>,
+    font_cache_sender: &IpcSender<bool>,
     outstanding_web_fonts_counter: &Arc<AtomicUsize>,
 ) {
     if opts::get().load_webfonts_synchronously {
@@ -524,6 +539,7 @@ impl LayoutThread {
                 indexable_text: IndexableText::default(),
                 content_box_response: None,
                 content_boxes_response: Vec::new(),
+                box_sizes_response: BoxSizesResponse::empty(),
                 client_rect_response: Rect::zero(),
                 scroll_id_response: None,
                 scroll_area_response: Rect::zero(),
@@ -544,6 +560,7 @@ impl LayoutThread {
             paint_time_metrics: paint_time_metrics,
             layout_query_waiting_time: Histogram::new(),
             last_iframe_sizes: Default::default(),
+            converted_display_list: Default::default(),
         }
     }
 
@@ -626,6 +643,7 @@ impl LayoutThread {
             Msg::RegisterPaint(..) => LayoutHangAnnotation::RegisterPaint,
             Msg::SetNavigationStart(..) => LayoutHangAnnotation::SetNavigationStart,
             Msg::GetRunningAnimations(..) => LayoutHangAnnotation::GetRunningAnimations,
+            Msg::LoadWebFont(..) => LayoutHangAnnotation::LoadWebFont,
         };
         self.background_hang_monitor
             .notify_activity(HangAnnotation::Layout(hang_annotation));
@@ -649,11 +667,13 @@ impl LayoutThread {
         };
 
         match request {
-            Request::FromPipeline(LayoutControlMsg::SetScrollStates(new_scroll_states)) => self
-                .handle_request_helper(
-                    Msg::SetScrollStates(new_scroll_states),
-                    possibly_locked_rw_data,
-                ),
+            Request::FromPipeline(LayoutControlMsg::SetScrollStates(
+                new_scroll_states,
+                scroll_ended,
+            )) => self.handle_request_helper(
+                Msg::SetScrollStates(new_scroll_states, scroll_ended),
+                possibly_locked_rw_data,
+            ),
             Request::FromPipeline(LayoutControlMsg::TickAnimations) => {
                 self.handle_request_helper(Msg::TickAnimations, possibly_locked_rw_data)
             },
@@ -727,22 +747,22 @@ impl LayoutThread {
                 );
             },
             Msg::TickAnimations => self.tick_all_animations(possibly_locked_rw_data),
-            Msg::SetScrollStates(new_scroll_states) => {
-                self.set_scroll_states(new_scroll_states, possibly_locked_rw_data);
+            Msg::SetScrollStates(new_scroll_states, scroll_ended) => {
+                self.set_scroll_states(new_scroll_states, scroll_ended, possibly_locked_rw_data);
             },
             Msg::UpdateScrollStateFromScript(state) => {
                 let mut rw_data = possibly_locked_rw_data.lock();
+                let scroll_offset = snap_scroll_offset(&rw_data, &state);
                 rw_data
                     .scroll_offsets
-                    .insert(state.scroll_id, state.scroll_offset);
+                    .insert(state.scroll_id, scroll_offset);
 
-                let point = Point2D::new(-state.scroll_offset.x, -state.scroll_offset.y);
                 let mut txn = webrender_api::Transaction::new();
-                txn.scroll_node_with_id(
-                    webrender_api::LayoutPoint::from_untyped(&point),
-                    state.scroll_id,
-                    webrender_api::ScrollClamping::ToContentBounds,
-                );
+                scroll_node(&mut txn, state.scroll_id, scroll_offset);
+                if scroll_offset != state.scroll_offset {
+                    // Script assumes its scroll offset was taken as is.
+                    self.send_scroll_states_to_script(&rw_data.scroll_offsets);
+                }
                 self.webrender_api
                     .send_transaction(self.webrender_document, txn);
             },
@@ -801,6 +821,9 @@ impl LayoutThread {
             Msg::GetRunningAnimations(sender) => {
                 let _ = sender.send(self.running_animations.read().len());
             },
+            Msg::LoadWebFont(family, source, sender) => {
+                self.load_web_font(family, source, sender);
+            },
         }
 
         true
@@ -920,6 +943,32 @@ impl LayoutThread {
         }
     }
 
+    /// Loads a font face the way the ones of the `@font-face` rules are,
+    /// except that `sender` is told when it is loaded.
+    fn load_web_font(&self, family: FamilyName, source: WebFontSource, sender: IpcSender<bool>) {
+        let (font_cache_sender, font_cache_receiver) = ipc::channel().unwrap();
+        let outstanding_web_fonts = self.outstanding_web_fonts.clone();
+        outstanding_web_fonts.fetch_add(1, Ordering::SeqCst);
+        ROUTER.add_route(
+            font_cache_receiver.to_opaque(),
+            Box::new(move |message| {
+                outstanding_web_fonts.fetch_sub(1, Ordering::SeqCst);
+                font_context::invalidate_font_caches();
+                let _ = sender.send(message.to().unwrap());
+            }),
+        );
+        match source {
+            WebFontSource::Sources(sources) => {
+                self.font_cache_thread
+                    .add_web_font(family, sources, font_cache_sender)
+            },
+            WebFontSource::Data(bytes) => {
+                self.font_cache_thread
+                    .add_web_font_data(family, bytes, font_cache_sender)
+            },
+        }
+    }
+
     /// Advances the animation clock of the document.
     fn handle_advance_clock_ms<'a, 'b>(
         &mut self,
@@ -1049,6 +1098,15 @@ impl LayoutThread {
                         build_state.root_stacking_context.bounds = origin;
                         build_state.root_stacking_context.overflow = origin;
 
+                        if let Some(root_snap_container) = build_state
+                            .scroll_snap_containers
+                            .get_mut(&ClipScrollNodeIndex::root_scroll_node())
+                        {
+                            root_snap_container.scrollport =
+                                Rect::new(Point2D::zero(), self.viewport_size).to_layout();
+                            root_snap_container.content_rect = origin;
+                        }
+
                         if !build_state.iframe_sizes.is_empty() {
                             // build_state.iframe_sizes is only used here, so its okay to replace
                             // it with an empty vector
@@ -1113,23 +1171,34 @@ impl LayoutThread {
                 if let Some(document) = document {
                     document.will_paint();
                 }
-                let display_list = (*rw_data.display_list.as_ref().unwrap()).clone();
+                let display_list = rw_data.display_list.as_ref().unwrap().clone();
 
                 if opts::get().dump_display_list {
                     display_list.print();
                 }
                 if opts::get().dump_display_list_json {
-                    println!("{}", serde_json::to_string_pretty(&display_list).unwrap());
+                    println!("{}", serde_json::to_string_pretty(&*display_list).unwrap());
                 }
 
                 debug!("Layout done!");
 
-                // TODO: Avoid the temporary conversion and build webrender sc/dl directly!
-                let builder = rw_data
-                    .display_list
-                    .as_ref()
-                    .unwrap()
-                    .convert_to_webrender(self.id);
+                // If the display list wasn't rebuilt since it was last sent,
+                // for instance because the restyled nodes got no repaint
+                // damage, the WebRender display list it was converted to can
+                // be sent again as is.
+                let converted = self
+                    .converted_display_list
+                    .borrow()
+                    .get(&display_list)
+                    .cloned();
+                let converted = converted.unwrap_or_else(|| {
+                    // TODO: Avoid the temporary conversion and build webrender sc/dl directly!
+                    let converted = display_list.convert_to_webrender(self.id).finalize();
+                    self.converted_display_list
+                        .borrow_mut()
+                        .set(&display_list, converted.clone());
+                    converted
+                });
 
                 let viewport_size = Size2D::new(
                     self.viewport_size.width.to_f32_px(),
@@ -1153,7 +1222,7 @@ impl LayoutThread {
                     webrender_api::Epoch(epoch.0),
                     Some(get_root_flow_background_color(layout_root)),
                     viewport_size,
-                    builder.finalize(),
+                    converted,
                     true,
                 );
                 txn.generate_frame();
@@ -1201,6 +1270,9 @@ impl LayoutThread {
                         &QueryMsg::ContentBoxesQuery(_) => {
                             rw_data.content_boxes_response = Vec::new();
                         },
+                        &QueryMsg::BoxSizesQuery(_) => {
+                            rw_data.box_sizes_response = BoxSizesResponse::empty();
+                        },
                         &QueryMsg::NodesFromPointQuery(..) => {
                             rw_data.nodes_from_point_response = Vec::new();
                         },
@@ -1508,6 +1580,9 @@ impl LayoutThread {
                 &QueryMsg::ContentBoxesQuery(node) => {
                     rw_data.content_boxes_response = process_content_boxes_request(node, root_flow);
                 },
+                &QueryMsg::BoxSizesQuery(node) => {
+                    rw_data.box_sizes_response = process_box_sizes_request(node, root_flow);
+                },
                 &QueryMsg::TextIndexQuery(node, point_in_node) => {
                     let point_in_node = Point2D::new(
                         Au::from_f32_px(point_in_node.x),
@@ -1577,19 +1652,44 @@ impl LayoutThread {
     fn set_scroll_states<'a, 'b>(
         &mut self,
         new_scroll_states: Vec<ScrollState>,
+        scroll_ended: bool,
         possibly_locked_rw_data: &mut RwData<'a, 'b>,
     ) {
         let mut rw_data = possibly_locked_rw_data.lock();
-        let mut script_scroll_states = vec![];
         let mut layout_scroll_states = HashMap::new();
+        let mut txn = webrender_api::Transaction::new();
+        let mut snapped = false;
         for new_state in &new_scroll_states {
-            let offset = new_state.scroll_offset;
+            let mut offset = new_state.scroll_offset;
+
+            // Only the scroll frames the user just scrolled come to rest on
+            // their snap positions.
+            let scrolled = rw_data.scroll_offsets.get(&new_state.scroll_id) != Some(&offset);
+            if scroll_ended && scrolled {
+                offset = snap_scroll_offset(&rw_data, new_state);
+                if offset != new_state.scroll_offset {
+                    scroll_node(&mut txn, new_state.scroll_id, offset);
+                    snapped = true;
+                }
+            }
             layout_scroll_states.insert(new_state.scroll_id, offset);
+        }
+        if snapped {
+            txn.generate_frame();
+            self.webrender_api
+                .send_transaction(self.webrender_document, txn);
+        }
+        self.send_scroll_states_to_script(&layout_scroll_states);
+        rw_data.scroll_offsets = layout_scroll_states
+    }
 
-            if new_state.scroll_id.is_root() {
-                script_scroll_states.push((UntrustedNodeAddress::from_id(0), offset))
-            } else if let Some(node_id) = node_id_from_scroll_id(new_state.scroll_id.0 as usize) {
-                script_scroll_states.push((UntrustedNodeAddress::from_id(node_id), offset))
+    fn send_scroll_states_to_script(&self, scroll_offsets: &ScrollOffsetMap) {
+        let mut script_scroll_states = vec![];
+        for (scroll_id, offset) in scroll_offsets {
+            if scroll_id.is_root() {
+                script_scroll_states.push((UntrustedNodeAddress::from_id(0), *offset))
+            } else if let Some(node_id) = node_id_from_scroll_id(scroll_id.0 as usize) {
+                script_scroll_states.push((UntrustedNodeAddress::from_id(node_id), *offset))
             }
         }
         let _ = self
@@ -1598,7 +1698,6 @@ impl LayoutThread {
                 self.id,
                 script_scroll_states,
             ));
-        rw_data.scroll_offsets = layout_scroll_states
     }
 
     fn tick_all_animations<'a, 'b>(&mut self, possibly_locked_rw_data: &mut RwData<'a, 'b>) {
@@ -1853,6 +1952,36 @@ impl ProfilerMetadataFactory for LayoutThread {
     }
 }
 
+/// Returns the scroll offset a scroll frame scrolled to the given state comes to rest
+/// on, which differs from the state's if the frame has a `scroll-snap-type`.
+fn snap_scroll_offset(rw_data: &LayoutThreadData, state: &ScrollState) -> Vector2D<f32> {
+    rw_data
+        .display_list
+        .as_ref()
+        .and_then(|display_list| {
+            display_list
+                .scroll_snap_containers
+                .iter()
+                .find(|container| container.scroll_id == state.scroll_id)
+        })
+        .map_or(state.scroll_offset, |container| {
+            container.snap(state.scroll_offset)
+        })
+}
+
+fn scroll_node(
+    txn: &mut webrender_api::Transaction,
+    scroll_id: webrender_api::ExternalScrollId,
+    scroll_offset: Vector2D<f32>,
+) {
+    let point = Point2D::new(-scroll_offset.x, -scroll_offset.y);
+    txn.scroll_node_with_id(
+        webrender_api::LayoutPoint::from_untyped(&point),
+        scroll_id,
+        webrender_api::ScrollClamping::ToContentBounds,
+    );
+}
+
 // The default computed value for background-color is transparent (see
 // http://dev.w3.org/csswg/css-backgrounds/#background-color). However, we
 // need to propagate the background color from the root HTML/Body
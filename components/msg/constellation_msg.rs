@@ -311,6 +311,7 @@ pub enum LayoutHangAnnotation {
     RegisterPaint,
     SetNavigationStart,
     GetRunningAnimations,
+    LoadWebFont,
 }
 
 #[derive(Clone, Copy, Debug, Deserialize, Serialize)]
@@ -0,0 +1,183 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! The storage of the caches of the Cache API, one list of named caches per
+//! origin.
+//!
+//! <https://w3c.github.io/ServiceWorker/#cache-objects>
+
+use headers_core::HeaderMapExt;
+use headers_ext::Vary;
+use http::Method;
+use net_traits::cache_storage::{CacheQueryOptions, CacheStorageMsg};
+use net_traits::cache_storage::{CachedRequest, CachedResponse};
+use servo_url::{ImmutableOrigin, ServoUrl};
+use std::collections::HashMap;
+
+/// <https://w3c.github.io/ServiceWorker/#request-response-list>
+type RequestResponseList = Vec<(CachedRequest, CachedResponse)>;
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct CacheStorage {
+    /// The name to cache maps of each origin, keyed by the serialization of
+    /// the origin, the caches being kept in creation order.
+    ///
+    /// <https://w3c.github.io/ServiceWorker/#name-to-cache-map>
+    origins: HashMap<String, Vec<(String, RequestResponseList)>>,
+}
+
+impl CacheStorage {
+    pub fn new() -> CacheStorage {
+        Default::default()
+    }
+
+    pub fn handle(&mut self, msg: CacheStorageMsg) {
+        match msg {
+            CacheStorageMsg::Has(sender, origin, name) => {
+                let _ = sender.send(self.cache(&origin, &name).is_some());
+            },
+            CacheStorageMsg::Open(sender, origin, name) => {
+                if self.cache(&origin, &name).is_none() {
+                    self.caches_mut(&origin).push((name, vec![]));
+                }
+                let _ = sender.send(());
+            },
+            CacheStorageMsg::Delete(sender, origin, name) => {
+                let caches = self.caches_mut(&origin);
+                let length = caches.len();
+                caches.retain(|&(ref cache_name, _)| *cache_name != name);
+                let _ = sender.send(caches.len() != length);
+            },
+            CacheStorageMsg::Keys(sender, origin) => {
+                let names = self
+                    .origins
+                    .get(&origin.ascii_serialization())
+                    .map_or(vec![], |caches| {
+                        caches.iter().map(|&(ref name, _)| name.clone()).collect()
+                    });
+                let _ = sender.send(names);
+            },
+            CacheStorageMsg::Match(sender, origin, name, request, options) => {
+                let entries =
+                    self.origins
+                        .get(&origin.ascii_serialization())
+                        .map_or(vec![], |caches| {
+                            caches
+                                .iter()
+                                .filter(|&&(ref cache_name, _)| {
+                                    name.as_ref().map_or(true, |name| name == cache_name)
+                                })
+                                .flat_map(|&(_, ref entries)| entries.iter())
+                                .filter(|&&(ref cached_request, ref cached_response)| {
+                                    request.as_ref().map_or(true, |request| {
+                                        request_matches(
+                                            request,
+                                            cached_request,
+                                            cached_response,
+                                            options,
+                                        )
+                                    })
+                                })
+                                .cloned()
+                                .collect()
+                        });
+                let _ = sender.send(entries);
+            },
+            CacheStorageMsg::Put(sender, origin, name, request, response) => {
+                if let Some(entries) = self.cache_mut(&origin, &name) {
+                    // https://w3c.github.io/ServiceWorker/#batch-cache-operations-algorithm
+                    // Step 4.2.4.
+                    let options = CacheQueryOptions::default();
+                    entries.retain(|&(ref cached_request, ref cached_response)| {
+                        !request_matches(&request, cached_request, cached_response, options)
+                    });
+                    entries.push((request, response));
+                }
+                let _ = sender.send(());
+            },
+            CacheStorageMsg::DeleteEntries(sender, origin, name, request, options) => {
+                let mut deleted = false;
+                if let Some(entries) = self.cache_mut(&origin, &name) {
+                    let length = entries.len();
+                    entries.retain(|&(ref cached_request, ref cached_response)| {
+                        !request_matches(&request, cached_request, cached_response, options)
+                    });
+                    deleted = entries.len() != length;
+                }
+                let _ = sender.send(deleted);
+            },
+        }
+    }
+
+    fn caches_mut(&mut self, origin: &ImmutableOrigin) -> &mut Vec<(String, RequestResponseList)> {
+        self.origins
+            .entry(origin.ascii_serialization())
+            .or_insert(vec![])
+    }
+
+    fn cache(&self, origin: &ImmutableOrigin, name: &str) -> Option<&RequestResponseList> {
+        self.origins
+            .get(&origin.ascii_serialization())?
+            .iter()
+            .find(|&&(ref cache_name, _)| cache_name == name)
+            .map(|&(_, ref entries)| entries)
+    }
+
+    fn cache_mut(
+        &mut self,
+        origin: &ImmutableOrigin,
+        name: &str,
+    ) -> Option<&mut RequestResponseList> {
+        self.origins
+            .get_mut(&origin.ascii_serialization())?
+            .iter_mut()
+            .find(|&&mut (ref cache_name, _)| cache_name == name)
+            .map(|&mut (_, ref mut entries)| entries)
+    }
+}
+
+/// <https://w3c.github.io/ServiceWorker/#request-matches-cached-item-algorithm>
+fn request_matches(
+    request: &CachedRequest,
+    cached_request: &CachedRequest,
+    cached_response: &CachedResponse,
+    options: CacheQueryOptions,
+) -> bool {
+    // Step 1.
+    if !options.ignore_method && request.method != Method::GET {
+        return false;
+    }
+    // Steps 2-6.
+    if !urls_match(&request.url, &cached_request.url, options.ignore_search) {
+        return false;
+    }
+    // Step 7.
+    if options.ignore_vary {
+        return true;
+    }
+    // Step 8.
+    let vary = match cached_response.headers.typed_get::<Vary>() {
+        Some(vary) => vary,
+        None => return true,
+    };
+    if vary.is_any() {
+        return false;
+    }
+    vary.iter_strs()
+        .all(|name| request.headers.get(name) == cached_request.headers.get(name))
+}
+
+/// Whether two URLs are equal once their fragments, and their queries if
+/// `ignore_search` is true, are excluded.
+fn urls_match(url: &ServoUrl, other: &ServoUrl, ignore_search: bool) -> bool {
+    let mut url = url.as_url().clone();
+    let mut other = other.as_url().clone();
+    url.set_fragment(None);
+    other.set_fragment(None);
+    if ignore_search {
+        url.set_query(None);
+        other.set_query(None);
+    }
+    url == other
+}
@@ -8,6 +8,7 @@ use hyper::client::HttpConnector as HyperHttpConnector;
 use hyper::rt::Future;
 use hyper::{Body, Client};
 use hyper_openssl::HttpsConnector;
+use openssl::error::ErrorStack;
 use openssl::ssl::{SslConnector, SslConnectorBuilder, SslMethod, SslOptions};
 use openssl::x509;
 use tokio::prelude::future::Executor;
@@ -82,21 +83,34 @@ pub fn create_ssl_connector_builder(certs: &str) -> SslConnectorBuilder {
     ssl_connector_builder
 }
 
+/// Creates the client fetches go through, which offers HTTP/2 to servers if
+/// `http2` is set.
 pub fn create_http_client<E>(
-    ssl_connector_builder: SslConnectorBuilder,
+    mut ssl_connector_builder: SslConnectorBuilder,
     executor: E,
-) -> Client<Connector, Body>
+    http2: bool,
+) -> Result<Client<Connector, Body>, ErrorStack>
 where
     E: Executor<Box<dyn Future<Error = (), Item = ()> + Send + 'static>> + Sync + Send + 'static,
 {
-    let connector =
-        HttpsConnector::with_connector(HttpConnector::new(), ssl_connector_builder).unwrap();
-    Client::builder()
+    // Servers picking h2 during the TLS handshake get all the requests to
+    // their origin multiplexed over a single pooled connection, the others
+    // keep being talked to over HTTP/1.1.
+    if http2 {
+        ssl_connector_builder.set_alpn_protos(ALPN_PROTOCOLS)?;
+    }
+    let connector = HttpsConnector::with_connector(HttpConnector::new(), ssl_connector_builder)?;
+    Ok(Client::builder()
         .http1_title_case_headers(true)
         .executor(executor)
-        .build(connector)
+        .build(connector))
 }
 
+/// The protocols offered through ALPN, in order of preference.
+///
+/// <https://tools.ietf.org/html/rfc7540#section-3.3>
+const ALPN_PROTOCOLS: &'static [u8] = b"\x02h2\x08http/1.1";
+
 // Prefer Forward Secrecy over plain RSA, AES-GCM over AES-CBC, ECDSA over RSA.
 // A complete discussion of the issues involved in TLS configuration can be found here:
 // https://wiki.mozilla.org/Security/Server_Side_TLS
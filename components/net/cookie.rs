@@ -5,6 +5,7 @@
 //! Implementation of cookie creation and matching as specified by
 //! http://tools.ietf.org/html/rfc6265
 
+use devtools_traits::CookieInfo;
 use hyper_serde::{self, Serde};
 use net_traits::pub_domains::is_pub_domain;
 use net_traits::CookieSource;
@@ -143,6 +144,21 @@ impl Cookie {
         self.last_access = now();
     }
 
+    pub fn to_devtools(&self) -> CookieInfo {
+        CookieInfo {
+            name: self.cookie.name().to_owned(),
+            value: self.cookie.value().to_owned(),
+            domain: self.cookie.domain().unwrap_or("").to_owned(),
+            path: self.cookie.path().unwrap_or("").to_owned(),
+            expires: self.expiry_time.as_ref().map(|time| milliseconds(&**time)),
+            creation_time: milliseconds(&self.creation_time),
+            last_access: milliseconds(&self.last_access),
+            host_only: self.host_only,
+            http_only: self.cookie.http_only().unwrap_or(false),
+            secure: self.cookie.secure().unwrap_or(false),
+        }
+    }
+
     // http://tools.ietf.org/html/rfc6265#section-5.1.4
     pub fn default_path(request_path: &str) -> &str {
         // Step 2
@@ -223,3 +239,9 @@ impl Cookie {
         true
     }
 }
+
+/// The number of milliseconds between the epoch and `time`.
+fn milliseconds(time: &Tm) -> i64 {
+    let time = time.to_timespec();
+    time.sec * 1000 + (time.nsec / 1_000_000) as i64
+}
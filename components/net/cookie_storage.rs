@@ -6,6 +6,7 @@
 //! http://tools.ietf.org/html/rfc6265
 
 use crate::cookie::Cookie;
+use devtools_traits::{CookieInfo, StorageEvent};
 use net_traits::pub_domains::reg_suffix;
 use net_traits::CookieSource;
 use servo_url::ServoUrl;
@@ -198,6 +199,33 @@ impl CookieStorage {
                 c.cookie.clone()
             })
     }
+
+    /// Describes the cookies stored for the registrable domain of `url`,
+    /// for the storage inspector of devtools.
+    pub fn devtools_event_for_url(&self, url: &ServoUrl) -> StorageEvent {
+        let domain = reg_host(url.host_str().unwrap_or(""));
+        let cookies = self
+            .cookies_map
+            .get(&domain)
+            .map_or(vec![], |cookies| cookies_info(cookies));
+        StorageEvent::Cookies(domain, cookies)
+    }
+
+    /// Describes all the stored cookies, one event per registrable domain.
+    pub fn devtools_events(&self) -> Vec<StorageEvent> {
+        self.cookies_map
+            .iter()
+            .map(|(domain, cookies)| StorageEvent::Cookies(domain.clone(), cookies_info(cookies)))
+            .collect()
+    }
+}
+
+fn cookies_info(cookies: &[Cookie]) -> Vec<CookieInfo> {
+    cookies
+        .iter()
+        .filter(|c| !is_cookie_expired(c))
+        .map(Cookie::to_devtools)
+        .collect()
 }
 
 fn reg_host<'a>(url: &'a str) -> String {
@@ -10,12 +10,15 @@ use crate::http_loader::{set_default_accept, set_default_accept_language};
 use crate::subresource_integrity::is_response_integrity_valid;
 use crossbeam_channel::{unbounded, Receiver, Sender};
 use devtools_traits::DevtoolsControlMsg;
+use futures::sync::oneshot;
+use futures::{future, Future};
 use headers_core::HeaderMapExt;
 use headers_ext::{AccessControlExposeHeaders, ContentType, Range};
 use http::header::{self, HeaderMap, HeaderName, HeaderValue};
 use hyper::Method;
 use hyper::StatusCode;
-use ipc_channel::ipc::IpcReceiver;
+use ipc_channel::ipc::{IpcReceiver, IpcSender};
+use ipc_channel::router::ROUTER;
 use mime::{self, Mime};
 use mime_guess::guess_mime_type;
 use net_traits::blob_url_store::{parse_blob_url, BlobURLStoreError};
@@ -23,7 +26,7 @@ use net_traits::filemanager_thread::RelativePos;
 use net_traits::request::{CredentialsMode, Destination, Referrer, Request, RequestMode};
 use net_traits::request::{Origin, ResponseTainting, Window};
 use net_traits::response::{Response, ResponseBody, ResponseType};
-use net_traits::ResourceAttribute;
+use net_traits::{CustomResponseMediator, ResourceAttribute};
 use net_traits::{FetchTaskTarget, NetworkError, ReferrerPolicy, ResourceFetchTiming};
 use servo_url::ServoUrl;
 use std::borrow::Cow;
@@ -53,36 +56,61 @@ pub struct FetchContext {
     pub user_agent: Cow<'static, str>,
     pub devtools_chan: Option<Sender<DevtoolsControlMsg>>,
     pub filemanager: FileManager,
+    /// Where fetches get sent for a service worker to handle them.
+    pub swmanager_chan: Option<IpcSender<CustomResponseMediator>>,
     pub cancellation_listener: Arc<Mutex<CancellationListener>>,
     pub timing: Arc<Mutex<ResourceFetchTiming>>,
 }
 
 pub struct CancellationListener {
-    cancel_chan: Option<IpcReceiver<()>>,
+    state: Arc<Mutex<CancellationState>>,
+}
+
+#[derive(Default)]
+struct CancellationState {
     cancelled: bool,
+    /// Futures to resolve as soon as the fetch gets cancelled
+    abort_senders: Vec<oneshot::Sender<()>>,
 }
 
 impl CancellationListener {
     pub fn new(cancel_chan: Option<IpcReceiver<()>>) -> Self {
-        Self {
-            cancel_chan: cancel_chan,
-            cancelled: false,
+        let state = Arc::new(Mutex::new(CancellationState::default()));
+        if let Some(cancel_chan) = cancel_chan {
+            // Listen on the router thread rather than polling the channel, so
+            // that ongoing network requests can be torn down right away.
+            let state = state.clone();
+            ROUTER.add_route(
+                cancel_chan.to_opaque(),
+                Box::new(move |_| {
+                    let mut state = state.lock().unwrap();
+                    state.cancelled = true;
+                    for sender in state.abort_senders.drain(..) {
+                        let _ = sender.send(());
+                    }
+                }),
+            );
         }
+        Self { state: state }
     }
 
     pub fn cancelled(&mut self) -> bool {
-        if let Some(ref cancel_chan) = self.cancel_chan {
-            if self.cancelled {
-                true
-            } else if cancel_chan.try_recv().is_ok() {
-                self.cancelled = true;
-                true
-            } else {
-                false
-            }
+        self.state.lock().unwrap().cancelled
+    }
+
+    /// Returns a future that resolves once the fetch gets cancelled, and
+    /// never does otherwise
+    pub fn aborted(&mut self) -> impl Future<Item = (), Error = ()> {
+        let (sender, receiver) = oneshot::channel();
+        let mut state = self.state.lock().unwrap();
+        if state.cancelled {
+            let _ = sender.send(());
         } else {
-            false
+            state.abort_senders.push(sender);
         }
+        // The sender goes away without cancelling when nobody can cancel the
+        // fetch anymore.
+        receiver.or_else(|_| future::empty())
     }
 }
 pub type DoneChannel = Option<(Sender<Data>, Receiver<Data>)>;
@@ -4,10 +4,15 @@
 
 #![deny(missing_docs)]
 
-//! A memory cache implementing the logic specified in <http://tools.ietf.org/html/rfc7234>
+//! A cache implementing the logic specified in <http://tools.ietf.org/html/rfc7234>
 //! and <http://tools.ietf.org/html/rfc7232>.
+//!
+//! The cache lives in memory and is kept in the profile directory as it
+//! changes: the bodies are written once complete, to files named after their
+//! SHA-256 digest, and the rest of the resources to an `index.json` file.
 
 use crate::fetch::methods::{Data, DoneChannel};
+use crate::resource_thread::{read_json_from_file, write_json_to_file_atomically};
 use crossbeam_channel::{unbounded, Sender};
 use headers_core::HeaderMapExt;
 use headers_ext::{CacheControl, ContentRange, Expires, LastModified, Pragma, Range, Vary};
@@ -21,11 +26,14 @@ use malloc_size_of::{
 use net_traits::request::Request;
 use net_traits::response::{HttpsState, Response, ResponseBody};
 use net_traits::{FetchMetadata, Metadata, ResourceFetchTiming};
+use openssl::sha::sha256;
 use servo_arc::Arc;
 use servo_url::ServoUrl;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::fs;
 use std::ops::Bound;
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::sync::Mutex;
 use std::time::SystemTime;
 use time::{Duration, Timespec, Tm};
@@ -62,6 +70,12 @@ struct CachedResource {
     body: Arc<Mutex<ResponseBody>>,
     aborted: Arc<AtomicBool>,
     awaiting_body: Arc<Mutex<Vec<Sender<Data>>>>,
+    /// The value of the access counter of the cache when this resource was
+    /// last used, to evict the least recently used resources first.
+    last_used: Arc<AtomicUsize>,
+    /// The size of the body once it is complete and counted in the size of
+    /// the cache.
+    body_size: Option<usize>,
     data: Measurable<MeasurableCachedResource>,
 }
 
@@ -83,6 +97,7 @@ impl MallocSizeOf for CachedResource {
         self.body.unconditional_size_of(ops) +
             self.aborted.unconditional_size_of(ops) +
             self.awaiting_body.unconditional_size_of(ops) +
+            self.last_used.unconditional_size_of(ops) +
             self.data.size_of(ops)
     }
 }
@@ -124,11 +139,70 @@ pub struct CachedResponse {
     pub needs_validation: bool,
 }
 
-/// A memory cache.
+/// A cached resource as written to disk, its body being stored in a file of
+/// its own.
+#[derive(Deserialize, Serialize)]
+struct StoredResource {
+    url: ServoUrl,
+    #[serde(
+        deserialize_with = "hyper_serde::deserialize",
+        serialize_with = "hyper_serde::serialize"
+    )]
+    request_headers: HeaderMap,
+    #[serde(
+        deserialize_with = "hyper_serde::deserialize",
+        serialize_with = "hyper_serde::serialize"
+    )]
+    headers: HeaderMap,
+    final_url: ServoUrl,
+    content_type: Option<String>,
+    charset: Option<String>,
+    metadata_status: Option<(u16, Vec<u8>)>,
+    location_url: Option<Result<ServoUrl, String>>,
+    https_state: HttpsState,
+    status: Option<(u16, String)>,
+    raw_status: Option<(u16, Vec<u8>)>,
+    url_list: Vec<ServoUrl>,
+    expires: i64,
+    #[serde(
+        deserialize_with = "hyper_serde::deserialize",
+        serialize_with = "hyper_serde::serialize"
+    )]
+    last_validated: Tm,
+    last_used: usize,
+    /// The name of the file holding the body.
+    body: String,
+}
+
+/// The name of the file listing the resources of a cache written to disk.
+const INDEX_FILE_NAME: &str = "index.json";
+
+/// An HTTP cache.
 #[derive(MallocSizeOf)]
 pub struct HttpCache {
     /// cached responses.
     entries: HashMap<CacheKey, Vec<CachedResource>>,
+    /// Incremented every time a resource is used.
+    #[ignore_malloc_size_of = "Atomic"]
+    access_count: AtomicUsize,
+    /// The total size of the complete bodies in the cache.
+    total_size: usize,
+    /// The size the complete bodies get evicted down to.
+    max_size: usize,
+    /// The directory the cache is kept in, if any.
+    #[ignore_malloc_size_of = "Defined in std"]
+    dir: Option<PathBuf>,
+    /// The number of resources sharing each body file in `dir`.
+    body_files: HashMap<String, usize>,
+}
+
+/// The name of the file holding a body, its SHA-256 digest, so that
+/// identical bodies are only stored once.
+fn body_file_name(body: &[u8]) -> String {
+    sha256(body)
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
 }
 
 /// Determine if a given response is cacheable based on the initial metadata received.
@@ -227,10 +301,13 @@ fn get_response_expiry(response: &Response) -> Duration {
             // If the response has a Last-Modified header field,
             // caches are encouraged to use a heuristic expiration value
             // that is no more than some fraction of the interval since that time.
-            response.headers.typed_get::<LastModified>() {
+            response.headers.typed_get::<LastModified>()
+        {
             let current = time::now().to_timespec();
             let last_modified: SystemTime = last_modified.into();
-            let last_modified = last_modified.duration_since(SystemTime::UNIX_EPOCH).unwrap();
+            let last_modified = last_modified
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .unwrap();
             let last_modified = Timespec::new(last_modified.as_secs() as i64, 0);
             // A typical setting of this fraction might be 10%.
             let raw_heuristic_calc = (current - last_modified) / 10;
@@ -352,6 +429,7 @@ fn create_resource_with_bytes_from_resource(
         body: Arc::new(Mutex::new(ResponseBody::Done(bytes.to_owned()))),
         aborted: Arc::new(AtomicBool::new(false)),
         awaiting_body: Arc::new(Mutex::new(vec![])),
+        last_used: resource.last_used.clone(),
         data: Measurable(MeasurableCachedResource {
             metadata: resource.data.metadata.clone(),
             location_url: resource.data.location_url.clone(),
@@ -564,9 +642,272 @@ impl HttpCache {
     pub fn new() -> HttpCache {
         HttpCache {
             entries: HashMap::new(),
+            access_count: AtomicUsize::new(0),
+            total_size: 0,
+            max_size: pref!(network.http_cache.size).max(0) as usize,
+            dir: None,
+            body_files: HashMap::new(),
+        }
+    }
+
+    /// Read a cache previously kept in `dir`, skipping the resources whose
+    /// body can't be read, and keep it there from now on.
+    pub fn from_disk(dir: &Path) -> HttpCache {
+        let mut cache = HttpCache::new();
+        if let Err(error) = fs::create_dir_all(dir) {
+            warn!("Error creating the HTTP cache directory: {}", error);
+            return cache;
+        }
+        cache.dir = Some(dir.to_path_buf());
+        let mut stored_resources: Vec<StoredResource> = vec![];
+        if dir.join(INDEX_FILE_NAME).exists() {
+            read_json_from_file(&mut stored_resources, dir, INDEX_FILE_NAME);
+        }
+        let mut access_count = 0;
+        for stored in stored_resources {
+            let body = match fs::read(dir.join(&stored.body)) {
+                Ok(body) => body,
+                Err(error) => {
+                    warn!("Error reading cached body {}: {}", stored.body, error);
+                    continue;
+                },
+            };
+            // Bodies are named after their digest, a mismatch means the body
+            // was not completely written
+            if body_file_name(&body) != stored.body {
+                warn!("Cached body {} is corrupted", stored.body);
+                continue;
+            }
+            let status = stored.status.and_then(|(code, reason)| {
+                StatusCode::from_u16(code).ok().map(|code| (code, reason))
+            });
+            access_count = access_count.max(stored.last_used + 1);
+            cache.total_size += body.len();
+            *cache.body_files.entry(stored.body).or_insert(0) += 1;
+            let resource = CachedResource {
+                request_headers: Arc::new(Mutex::new(stored.request_headers)),
+                body_size: Some(body.len()),
+                body: Arc::new(Mutex::new(ResponseBody::Done(body))),
+                aborted: Arc::new(AtomicBool::new(false)),
+                awaiting_body: Arc::new(Mutex::new(vec![])),
+                last_used: Arc::new(AtomicUsize::new(stored.last_used)),
+                data: Measurable(MeasurableCachedResource {
+                    metadata: CachedMetadata {
+                        headers: Arc::new(Mutex::new(stored.headers)),
+                        data: Measurable(MeasurableCachedMetadata {
+                            final_url: stored.final_url,
+                            content_type: stored.content_type,
+                            charset: stored.charset,
+                            status: stored.metadata_status,
+                        }),
+                    },
+                    location_url: stored.location_url,
+                    https_state: stored.https_state,
+                    status: status,
+                    raw_status: stored.raw_status,
+                    url_list: stored.url_list,
+                    expires: Duration::seconds(stored.expires),
+                    last_validated: stored.last_validated,
+                }),
+            };
+            cache
+                .entries
+                .entry(CacheKey::from_servo_url(&stored.url))
+                .or_insert(vec![])
+                .push(resource);
+        }
+        cache.access_count = AtomicUsize::new(access_count);
+
+        // Remove the bodies of the resources which are not in the cache anymore.
+        if let Ok(files) = fs::read_dir(dir) {
+            for file in files.filter_map(Result::ok) {
+                let name = file.file_name().to_string_lossy().into_owned();
+                if name != INDEX_FILE_NAME && !cache.body_files.contains_key(&name) {
+                    let _ = fs::remove_file(file.path());
+                }
+            }
+        }
+        if cache.evict() {
+            cache.write_index();
+        }
+        cache
+    }
+
+    /// Change the size the complete bodies get evicted down to, which
+    /// defaults to the `network.http-cache.size` pref.
+    pub fn set_max_size(&mut self, max_size: usize) {
+        self.max_size = max_size;
+        if self.evict() {
+            self.write_index();
         }
     }
 
+    /// Write the list of the complete resources of the cache to its
+    /// directory. The bodies are written as they complete, so only the
+    /// metadata, such as the last use of the resources, can be out of date.
+    pub fn write_index(&self) {
+        let dir = match self.dir {
+            Some(ref dir) => dir,
+            None => return,
+        };
+        let mut stored_resources = vec![];
+        for (key, resources) in self.entries.iter() {
+            for resource in resources {
+                if resource.body_size.is_none() {
+                    continue;
+                }
+                let body_name = match *resource.body.lock().unwrap() {
+                    ResponseBody::Done(ref body) => body_file_name(body),
+                    ResponseBody::Empty | ResponseBody::Receiving(..) => continue,
+                };
+                let data = &resource.data;
+                stored_resources.push(StoredResource {
+                    url: key.url(),
+                    request_headers: resource.request_headers.lock().unwrap().clone(),
+                    headers: data.metadata.headers.lock().unwrap().clone(),
+                    final_url: data.metadata.data.final_url.clone(),
+                    content_type: data.metadata.data.content_type.clone(),
+                    charset: data.metadata.data.charset.clone(),
+                    metadata_status: data.metadata.data.status.clone(),
+                    location_url: data.location_url.clone(),
+                    https_state: data.https_state.clone(),
+                    status: data
+                        .status
+                        .clone()
+                        .map(|(code, reason)| (code.as_u16(), reason)),
+                    raw_status: data.raw_status.clone(),
+                    url_list: data.url_list.clone(),
+                    expires: data.expires.num_seconds(),
+                    last_validated: data.last_validated,
+                    last_used: resource.last_used.load(Ordering::Relaxed),
+                    body: body_name,
+                });
+            }
+        }
+        if let Err(error) = write_json_to_file_atomically(&stored_resources, dir, INDEX_FILE_NAME)
+        {
+            warn!("Error writing the HTTP cache index: {}", error);
+        }
+    }
+
+    /// Count the bodies of the resources stored for `key` which completed
+    /// since the last call, writing them to the cache directory. Returns
+    /// whether there were any.
+    fn add_complete_bodies(&mut self, key: &CacheKey) -> bool {
+        let mut added = false;
+        let resources = match self.entries.get_mut(key) {
+            Some(resources) => resources,
+            None => return false,
+        };
+        for resource in resources.iter_mut() {
+            if resource.body_size.is_some() || resource.aborted.load(Ordering::Relaxed) {
+                continue;
+            }
+            let locked_body = resource.body.lock().unwrap();
+            let body = match *locked_body {
+                ResponseBody::Done(ref body) => body,
+                ResponseBody::Empty | ResponseBody::Receiving(..) => continue,
+            };
+            resource.body_size = Some(body.len());
+            self.total_size += body.len();
+            added = true;
+            let dir = match self.dir {
+                Some(ref dir) => dir,
+                None => continue,
+            };
+            let body_name = body_file_name(body);
+            let count = self.body_files.entry(body_name.clone()).or_insert(0);
+            if *count == 0 {
+                if let Err(error) = fs::write(dir.join(&body_name), body) {
+                    warn!("Error writing cached body {}: {}", body_name, error);
+                }
+            }
+            *count += 1;
+        }
+        added
+    }
+
+    /// Stop counting an evicted resource, removing its body from the cache
+    /// directory if no other resource shares it.
+    fn remove_body(&mut self, resource: &CachedResource) {
+        let size = match resource.body_size {
+            Some(size) => size,
+            None => return,
+        };
+        self.total_size -= size;
+        let dir = match self.dir {
+            Some(ref dir) => dir,
+            None => return,
+        };
+        let body_name = match *resource.body.lock().unwrap() {
+            ResponseBody::Done(ref body) => body_file_name(body),
+            ResponseBody::Empty | ResponseBody::Receiving(..) => return,
+        };
+        let remaining = match self.body_files.get_mut(&body_name) {
+            Some(count) => {
+                *count -= 1;
+                *count
+            },
+            None => return,
+        };
+        if remaining == 0 {
+            self.body_files.remove(&body_name);
+            let _ = fs::remove_file(dir.join(&body_name));
+        }
+    }
+
+    /// Evict the least recently used complete resources until the size of
+    /// their bodies fits in the size limit. Returns whether any got evicted.
+    fn evict(&mut self) -> bool {
+        if self.total_size <= self.max_size {
+            return false;
+        }
+        let mut resources = vec![];
+        for (key, cached_resources) in self.entries.iter() {
+            for (index, resource) in cached_resources.iter().enumerate() {
+                if let Some(size) = resource.body_size {
+                    resources.push((
+                        resource.last_used.load(Ordering::Relaxed),
+                        size,
+                        key.clone(),
+                        index,
+                    ));
+                }
+            }
+        }
+        resources.sort_by_key(|&(last_used, ..)| last_used);
+        let mut total_size = self.total_size;
+        let mut evicted: HashMap<CacheKey, HashSet<usize>> = HashMap::new();
+        for (_, size, key, index) in resources {
+            if total_size <= self.max_size {
+                break;
+            }
+            total_size -= size;
+            evicted
+                .entry(key)
+                .or_insert_with(HashSet::new)
+                .insert(index);
+        }
+        for (key, indices) in evicted {
+            let cached_resources = match self.entries.remove(&key) {
+                Some(cached_resources) => cached_resources,
+                None => continue,
+            };
+            let mut kept = vec![];
+            for (index, resource) in cached_resources.into_iter().enumerate() {
+                if indices.contains(&index) {
+                    self.remove_body(&resource);
+                } else {
+                    kept.push(resource);
+                }
+            }
+            if !kept.is_empty() {
+                self.entries.insert(key, kept);
+            }
+        }
+        true
+    }
+
     /// Constructing Responses from Caches.
     /// <https://tools.ietf.org/html/rfc7234#section-4>
     pub fn construct_response(
@@ -643,6 +984,10 @@ impl HttpCache {
                 // Returning the first response that can be constructed
                 // TODO: select the most appropriate one, using a known mechanism from a selecting header field,
                 // or using the Date header to return the most recent one.
+                cached_resource.last_used.store(
+                    self.access_count.fetch_add(1, Ordering::Relaxed),
+                    Ordering::Relaxed,
+                );
                 let cached_headers = cached_resource.data.metadata.headers.lock().unwrap();
                 let cached_response =
                     create_cached_response(request, cached_resource, &*cached_headers, done_chan);
@@ -669,6 +1014,10 @@ impl HttpCache {
                     }
                 }
             }
+            if self.add_complete_bodies(&entry_key) {
+                self.evict();
+                self.write_index();
+            }
         }
     }
 
@@ -719,9 +1068,18 @@ impl HttpCache {
                 constructed_response.raw_status = cached_resource.data.raw_status.clone();
                 constructed_response.url_list = cached_resource.data.url_list.clone();
                 cached_resource.data.expires = get_response_expiry(&constructed_response);
-                let mut stored_headers = cached_resource.data.metadata.headers.lock().unwrap();
-                stored_headers.extend(response.headers);
-                constructed_response.headers = stored_headers.clone();
+                cached_resource.data.last_validated = time::now();
+                cached_resource.last_used.store(
+                    self.access_count.fetch_add(1, Ordering::Relaxed),
+                    Ordering::Relaxed,
+                );
+                {
+                    let mut stored_headers =
+                        cached_resource.data.metadata.headers.lock().unwrap();
+                    stored_headers.extend(response.headers);
+                    constructed_response.headers = stored_headers.clone();
+                }
+                self.write_index();
                 return Some(constructed_response);
             }
         }
@@ -799,6 +1157,10 @@ impl HttpCache {
             body: response.body.clone(),
             aborted: response.aborted.clone(),
             awaiting_body: Arc::new(Mutex::new(vec![])),
+            last_used: Arc::new(AtomicUsize::new(
+                self.access_count.fetch_add(1, Ordering::Relaxed),
+            )),
+            body_size: None,
             data: Measurable(MeasurableCachedResource {
                 metadata: cacheable_metadata,
                 location_url: response.location_url.clone(),
@@ -810,7 +1172,15 @@ impl HttpCache {
                 last_validated: time::now(),
             }),
         };
-        let entry = self.entries.entry(entry_key).or_insert(vec![]);
-        entry.push(entry_resource);
+        self.entries
+            .entry(entry_key.clone())
+            .or_insert(vec![])
+            .push(entry_resource);
+        // Bodies which are still being received get added once complete,
+        // in update_awaiting_consumers
+        if self.add_complete_bodies(&entry_key) {
+            self.evict();
+            self.write_index();
+        }
     }
 }
@@ -2,6 +2,7 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
+use crate::cache_storage::CacheStorage;
 use crate::connector::{create_http_client, Connector};
 use crate::cookie;
 use crate::cookie_storage::CookieStorage;
@@ -15,6 +16,7 @@ use crate::hsts::HstsList;
 use crate::http_cache::HttpCache;
 use crate::resource_thread::AuthCache;
 use crossbeam_channel::{unbounded, Sender};
+use devtools_traits::HttpResponseContent as DevtoolsHttpResponseContent;
 use devtools_traits::{
     ChromeToDevtoolsControlMsg, DevtoolsControlMsg, HttpRequest as DevtoolsHttpRequest,
 };
@@ -35,6 +37,7 @@ use http::uri::Authority;
 use http::{HeaderMap, Request as HyperRequest};
 use hyper::{Body, Client, Method, Response as HyperResponse, StatusCode};
 use hyper_serde::Serde;
+use ipc_channel::ipc;
 use msg::constellation_msg::{HistoryStateId, PipelineId};
 use net_traits::quality::{quality_to_value, Quality, QualityItem};
 use net_traits::request::{CacheMode, CredentialsMode, Destination, Origin};
@@ -42,7 +45,7 @@ use net_traits::request::{RedirectMode, Referrer, Request, RequestMode};
 use net_traits::request::{ResponseTainting, ServiceWorkersMode};
 use net_traits::response::{HttpsState, Response, ResponseBody, ResponseType};
 use net_traits::{CookieSource, FetchMetadata, NetworkError, ReferrerPolicy};
-use net_traits::{RedirectStartValue, ResourceAttribute};
+use net_traits::{CustomResponseMediator, RedirectStartValue, ResourceAttribute};
 use openssl::ssl::SslConnectorBuilder;
 use servo_url::{ImmutableOrigin, ServoUrl};
 use std::collections::{HashMap, HashSet};
@@ -67,19 +70,35 @@ pub struct HttpState {
     pub cookie_jar: RwLock<CookieStorage>,
     pub http_cache: RwLock<HttpCache>,
     pub auth_cache: RwLock<AuthCache>,
+    pub cache_storage: RwLock<CacheStorage>,
     pub history_states: RwLock<HashMap<HistoryStateId, Vec<u8>>>,
     pub client: Client<Connector, Body>,
+    /// Whether `client` offers HTTP/2 to servers
+    pub http2: bool,
 }
 
 impl HttpState {
     pub fn new(ssl_connector_builder: SslConnectorBuilder) -> HttpState {
+        HttpState::new_with_http2(ssl_connector_builder, pref!(network.http2.enabled))
+    }
+
+    /// Like `new`, but offers HTTP/2 to servers if `http2` is set, whatever
+    /// the `network.http2.enabled` preference says.
+    pub fn new_with_http2(ssl_connector_builder: SslConnectorBuilder, http2: bool) -> HttpState {
         HttpState {
             hsts_list: RwLock::new(HstsList::new()),
             cookie_jar: RwLock::new(CookieStorage::new(150)),
             auth_cache: RwLock::new(AuthCache::new()),
+            cache_storage: RwLock::new(CacheStorage::new()),
             history_states: RwLock::new(HashMap::new()),
             http_cache: RwLock::new(HttpCache::new()),
-            client: create_http_client(ssl_connector_builder, HANDLE.lock().unwrap().executor()),
+            client: create_http_client(
+                ssl_connector_builder,
+                HANDLE.lock().unwrap().executor(),
+                http2,
+            )
+            .expect("could not create the HTTP client"),
+            http2,
         }
     }
 }
@@ -137,6 +156,30 @@ fn set_default_accept_encoding(headers: &mut HeaderMap) {
     );
 }
 
+/// Hints the server about how urgent the response is, mostly useful to the
+/// HTTP/2 servers multiplexing many responses over the same connection.
+///
+/// <https://www.rfc-editor.org/rfc/rfc9218#section-5>
+pub fn set_default_priority(destination: Destination, headers: &mut HeaderMap) {
+    let priority_header = HeaderName::from_static("priority");
+    if headers.contains_key(&priority_header) {
+        return;
+    }
+    let value = match destination {
+        Destination::Document => "u=0, i",
+        Destination::Style | Destination::Font => "u=1",
+        Destination::Script => "u=2",
+        // Partial images and media are still useful, so they are sent
+        // interleaved with the other incremental responses.
+        Destination::Image | Destination::Audio | Destination::Video | Destination::Track => {
+            "u=5, i"
+        },
+        // The other destinations have the default urgency, u=3.
+        _ => return,
+    };
+    headers.insert(priority_header, HeaderValue::from_static(value));
+}
+
 pub fn set_default_accept_language(headers: &mut HeaderMap) {
     if headers.contains_key(header::ACCEPT_LANGUAGE) {
         return;
@@ -320,6 +363,35 @@ fn send_response_to_devtools(
     let _ = devtools_chan.send(DevtoolsControlMsg::FromChrome(msg));
 }
 
+fn send_response_content_to_devtools(
+    devtools_chan: &Sender<DevtoolsControlMsg>,
+    request_id: String,
+    body: Vec<u8>,
+    pipeline_id: PipelineId,
+    receive_time: u64,
+) {
+    let content = DevtoolsHttpResponseContent {
+        body: body,
+        pipeline_id: pipeline_id,
+        receive_time: receive_time,
+    };
+    let net_event = NetworkEvent::HttpResponseContent(content);
+
+    let msg = ChromeToDevtoolsControlMsg::NetworkEvent(request_id, net_event);
+    let _ = devtools_chan.send(DevtoolsControlMsg::FromChrome(msg));
+}
+
+/// Tells devtools about the cookies stored for the registrable domain of `url`.
+pub fn send_cookies_to_devtools(
+    devtools_chan: &Sender<DevtoolsControlMsg>,
+    cookie_jar: &RwLock<CookieStorage>,
+    url: &ServoUrl,
+) {
+    let event = cookie_jar.read().unwrap().devtools_event_for_url(url);
+    let msg = ChromeToDevtoolsControlMsg::StorageEvent(event);
+    let _ = devtools_chan.send(DevtoolsControlMsg::FromChrome(msg));
+}
+
 fn auth_from_cache(
     auth_cache: &RwLock<AuthCache>,
     origin: &ImmutableOrigin,
@@ -455,6 +527,53 @@ fn obtain_response(
     // TODO(#21263) response_end (also needs to be set above if fetch is aborted due to an error)
 }
 
+/// Hands the fetch of `request` to the service worker manager, returning the
+/// response of the service worker controlling its client, if it responded.
+///
+/// <https://w3c.github.io/ServiceWorker/#handle-fetch>
+fn handle_fetch(request: &Request, context: &FetchContext) -> Option<Response> {
+    let swmanager_chan = context.swmanager_chan.as_ref()?;
+    if !pref!(dom.serviceworker.enabled) {
+        return None;
+    }
+    // Steps 12-13, the client of a subresource fetch is only known through
+    // its referrer.
+    let client_url = match request.destination {
+        Destination::Document => None,
+        Destination::Report |
+        Destination::ServiceWorker |
+        Destination::SharedWorker |
+        Destination::Worker => return None,
+        _ => match request.referrer {
+            Referrer::ReferrerUrl(ref url) => Some(url.clone()),
+            _ => return None,
+        },
+    };
+    let (response_chan, response_port) = ipc::channel().ok()?;
+    let mediator = CustomResponseMediator {
+        response_chan: response_chan,
+        load_url: request.current_url(),
+        method: request.method.clone(),
+        headers: request.headers.clone(),
+        destination: request.destination,
+        client_url: client_url,
+    };
+    swmanager_chan.send(mediator).ok()?;
+    let custom_response = match response_port.recv().ok()?? {
+        Ok(custom_response) => custom_response,
+        Err(error) => return Some(Response::network_error(error)),
+    };
+
+    let timing = context.timing.lock().unwrap().clone();
+    let mut response = Response::new(request.current_url(), timing);
+    let (status, reason) = custom_response.raw_status;
+    response.raw_status = Some((status.as_u16(), reason.clone().into_bytes()));
+    response.status = Some((status, reason));
+    response.headers = custom_response.headers;
+    *response.body.lock().unwrap() = ResponseBody::Done(custom_response.body);
+    Some(response)
+}
+
 /// [HTTP fetch](https://fetch.spec.whatwg.org#http-fetch)
 pub fn http_fetch(
     request: &mut Request,
@@ -476,8 +595,8 @@ pub fn http_fetch(
 
     // Step 3
     if request.service_workers_mode == ServiceWorkersMode::All {
-        // TODO: Substep 1
-        // Set response to the result of invoking handle fetch for request.
+        // Substep 1
+        response = handle_fetch(request, context);
 
         // Substep 2
         if let Some(ref res) = response {
@@ -911,6 +1030,11 @@ fn http_network_or_cache_fetch(
     // unlike http_loader, we should not set the accept header
     // here, according to the fetch spec
     set_default_accept_encoding(&mut http_request.headers);
+    // Priority hints are only worth sending to servers that may multiplex
+    // this response with others over HTTP/2
+    if context.state.http2 {
+        set_default_priority(http_request.destination, &mut http_request.headers);
+    }
 
     // Step 17
     // TODO some of this step can't be implemented yet
@@ -1199,10 +1323,18 @@ fn http_network_fetch(
     );
 
     let pipeline_id = request.pipeline_id;
+    let cancellation_listener = context.cancellation_listener.clone();
+    // Dropping the request when the fetch gets cancelled closes the connection
+    let aborted = cancellation_listener
+        .lock()
+        .unwrap()
+        .aborted()
+        .map_err(|_| NetworkError::Internal("Fetch aborted".into()))
+        .and_then(|_| Err(NetworkError::Internal("Fetch aborted".into())));
     // This will only get the headers, the body is read later
-    let (res, msg) = match response_future.wait() {
-        Ok(wrapped_response) => wrapped_response,
-        Err(error) => return Response::network_error(error),
+    let (res, msg) = match response_future.select(aborted).wait() {
+        Ok((wrapped_response, _)) => wrapped_response,
+        Err((error, _)) => return Response::network_error(error),
     };
 
     if log_enabled!(log::Level::Info) {
@@ -1242,7 +1374,6 @@ fn http_network_fetch(
     let devtools_sender = context.devtools_chan.clone();
     let meta_status = meta.status;
     let meta_headers = meta.headers;
-    let cancellation_listener = context.cancellation_listener.clone();
     if cancellation_listener.lock().unwrap().cancelled() {
         return Response::network_error(NetworkError::Internal("Fetch aborted".into()));
     }
@@ -1260,7 +1391,7 @@ fn http_network_fetch(
         if let Some(pipeline_id) = pipeline_id {
             send_response_to_devtools(
                 &sender,
-                request_id.unwrap(),
+                request_id.clone().unwrap(),
                 meta_headers.map(Serde::into_inner),
                 meta_status,
                 pipeline_id,
@@ -1268,44 +1399,73 @@ fn http_network_fetch(
         }
     }
 
+    let receive_start = precise_time_ms();
+    let devtools_response = match (devtools_sender, request_id, pipeline_id) {
+        (Some(sender), Some(request_id), Some(pipeline_id)) => {
+            Some((sender, request_id, pipeline_id))
+        },
+        _ => None,
+    };
+
     let done_sender2 = done_sender.clone();
     let done_sender3 = done_sender.clone();
-    HANDLE.lock().unwrap().spawn(
-        res.into_body()
-            .map_err(|_| ())
-            .fold(res_body, move |res_body, chunk| {
-                if cancellation_listener.lock().unwrap().cancelled() {
-                    *res_body.lock().unwrap() = ResponseBody::Done(vec![]);
-                    let _ = done_sender.send(Data::Cancelled);
-                    return future::failed(());
-                }
-                if let ResponseBody::Receiving(ref mut body) = *res_body.lock().unwrap() {
-                    let bytes = chunk.into_bytes();
-                    body.extend_from_slice(&*bytes);
-                    let _ = done_sender.send(Data::Payload(bytes.to_vec()));
-                }
-                future::ok(res_body)
-            })
-            .and_then(move |res_body| {
-                let mut body = res_body.lock().unwrap();
-                let completed_body = match *body {
-                    ResponseBody::Receiving(ref mut body) => mem::replace(body, vec![]),
-                    _ => vec![],
-                };
-                *body = ResponseBody::Done(completed_body);
-                let _ = done_sender2.send(Data::Done);
-                future::ok(())
-            })
-            .map_err(move |_| {
-                let mut body = res_body2.lock().unwrap();
-                let completed_body = match *body {
-                    ResponseBody::Receiving(ref mut body) => mem::replace(body, vec![]),
-                    _ => vec![],
-                };
-                *body = ResponseBody::Done(completed_body);
-                let _ = done_sender3.send(Data::Done);
-            }),
-    );
+    let done_sender4 = done_sender.clone();
+    let res_body3 = res_body.clone();
+    let aborted = cancellation_listener.lock().unwrap().aborted();
+    let body_future = res
+        .into_body()
+        .map_err(|_| ())
+        .fold(res_body, move |res_body, chunk| {
+            if cancellation_listener.lock().unwrap().cancelled() {
+                *res_body.lock().unwrap() = ResponseBody::Done(vec![]);
+                let _ = done_sender.send(Data::Cancelled);
+                return future::failed(());
+            }
+            if let ResponseBody::Receiving(ref mut body) = *res_body.lock().unwrap() {
+                let bytes = chunk.into_bytes();
+                body.extend_from_slice(&*bytes);
+                let _ = done_sender.send(Data::Payload(bytes.to_vec()));
+            }
+            future::ok(res_body)
+        })
+        .and_then(move |res_body| {
+            let mut body = res_body.lock().unwrap();
+            let completed_body = match *body {
+                ResponseBody::Receiving(ref mut body) => mem::replace(body, vec![]),
+                _ => vec![],
+            };
+            if let Some((sender, request_id, pipeline_id)) = devtools_response {
+                send_response_content_to_devtools(
+                    &sender,
+                    request_id,
+                    completed_body.clone(),
+                    pipeline_id,
+                    precise_time_ms() - receive_start,
+                );
+            }
+            *body = ResponseBody::Done(completed_body);
+            let _ = done_sender2.send(Data::Done);
+            future::ok(())
+        })
+        .map_err(move |_| {
+            let mut body = res_body2.lock().unwrap();
+            let completed_body = match *body {
+                ResponseBody::Receiving(ref mut body) => mem::replace(body, vec![]),
+                _ => vec![],
+            };
+            *body = ResponseBody::Done(completed_body);
+            let _ = done_sender3.send(Data::Done);
+        });
+    // Dropping the body when the fetch gets cancelled closes the connection,
+    // even if the server stopped sending anything.
+    let aborted = aborted.map(move |_| {
+        *res_body3.lock().unwrap() = ResponseBody::Done(vec![]);
+        let _ = done_sender4.send(Data::Cancelled);
+    });
+    HANDLE
+        .lock()
+        .unwrap()
+        .spawn(body_future.select(aborted).map(|_| ()).map_err(|_| ()));
 
     // TODO these substeps aren't possible yet
     // Substep 1
@@ -1345,6 +1505,11 @@ fn http_network_fetch(
     // Step 15
     if credentials_flag {
         set_cookies_from_headers(&url, &response.headers, &context.state.cookie_jar);
+        if let Some(ref devtools_chan) = context.devtools_chan {
+            if response.headers.contains_key(header::SET_COOKIE) {
+                send_cookies_to_devtools(devtools_chan, &context.state.cookie_jar, &url);
+            }
+        }
     }
 
     // TODO these steps
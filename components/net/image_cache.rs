@@ -4,6 +4,7 @@
 
 use embedder_traits::resources::{self, Resource};
 use immeta::load_from_buf;
+use ipc_channel::ipc::IpcSender;
 use net_traits::image::base::{load_from_memory, Image, ImageMetadata};
 use net_traits::image_cache::{CanRequestImages, ImageCache, ImageResponder};
 use net_traits::image_cache::{ImageOrMetadataAvailable, ImageResponse, ImageState};
@@ -540,4 +541,14 @@ impl ImageCache for ImageCacheImpl {
     fn set_webrender_image_key(&self, image: &mut Image) {
         set_webrender_image_key(&self.store.lock().unwrap().webrender_api, image);
     }
+
+    /// Decode the given bytes on another thread, sending the decoded image
+    /// back, or None if they aren't an image in a supported format.
+    fn decode_bytes(&self, bytes: Vec<u8>, sender: IpcSender<Option<Image>>) {
+        thread::spawn(move || {
+            debug!("Decoding {} bytes", bytes.len());
+            // This send can fail if the requester is gone.
+            let _ = sender.send(load_from_memory(&bytes));
+        });
+    }
 }
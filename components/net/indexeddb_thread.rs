@@ -0,0 +1,917 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::resource_thread;
+use ipc_channel::ipc::{self, IpcReceiver, IpcSender};
+use net_traits::indexeddb_thread::{AsyncOperation, BackendError, DatabaseMetadata};
+use net_traits::indexeddb_thread::{IndexedDBCursorDirection, IndexedDBKeyType, IndexedDBResult};
+use net_traits::indexeddb_thread::{IndexedDBThreadMsg, IndexedDBTxnMode, KeyPath};
+use net_traits::indexeddb_thread::{ObjectStoreMetadata, OpenResult};
+use servo_url::ImmutableOrigin;
+use std::borrow::ToOwned;
+use std::cmp;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::fs;
+use std::mem;
+use std::ops::Bound;
+use std::path::PathBuf;
+use std::thread;
+
+/// The largest key a key generator can generate
+///
+/// https://w3c.github.io/IndexedDB/#generate-a-key
+const MAX_GENERATED_KEY: u64 = 1 << 53;
+
+pub trait IndexedDBThreadFactory {
+    fn new(config_dir: Option<PathBuf>) -> Self;
+}
+
+impl IndexedDBThreadFactory for IpcSender<IndexedDBThreadMsg> {
+    /// Create an IndexedDB thread
+    fn new(config_dir: Option<PathBuf>) -> IpcSender<IndexedDBThreadMsg> {
+        let (chan, port) = ipc::channel().unwrap();
+        thread::Builder::new()
+            .name("IndexedDBManager".to_owned())
+            .spawn(move || {
+                IndexedDBManager::new(port, config_dir).start();
+            })
+            .expect("Thread spawning failed");
+        chan
+    }
+}
+
+/// https://w3c.github.io/IndexedDB/#object-store-construct
+#[derive(Clone, Deserialize, Serialize)]
+struct ObjectStore {
+    key_path: Option<KeyPath>,
+    auto_increment: bool,
+    /// https://w3c.github.io/IndexedDB/#key-generator-current-number
+    current_number: u64,
+    #[serde(with = "records")]
+    records: BTreeMap<IndexedDBKeyType, Vec<u8>>,
+}
+
+/// The records get serialized as a sequence, as JSON maps can only have
+/// strings as keys.
+mod records {
+    use net_traits::indexeddb_thread::IndexedDBKeyType;
+    use serde::{Deserialize, Deserializer, Serializer};
+    use std::collections::BTreeMap;
+
+    pub fn serialize<S>(
+        records: &BTreeMap<IndexedDBKeyType, Vec<u8>>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.collect_seq(records.iter())
+    }
+
+    pub fn deserialize<'de, D>(
+        deserializer: D,
+    ) -> Result<BTreeMap<IndexedDBKeyType, Vec<u8>>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let records = Vec::<(IndexedDBKeyType, Vec<u8>)>::deserialize(deserializer)?;
+        Ok(records.into_iter().collect())
+    }
+}
+
+/// https://w3c.github.io/IndexedDB/#database-construct
+#[derive(Clone, Deserialize, Serialize)]
+struct Database {
+    version: u64,
+    object_stores: BTreeMap<String, ObjectStore>,
+}
+
+impl Database {
+    fn metadata(&self) -> DatabaseMetadata {
+        DatabaseMetadata {
+            version: self.version,
+            object_stores: self
+                .object_stores
+                .iter()
+                .map(|(name, store)| ObjectStoreMetadata {
+                    name: name.clone(),
+                    key_path: store.key_path.clone(),
+                    auto_increment: store.auto_increment,
+                })
+                .collect(),
+        }
+    }
+
+    /// Reverts the changes of a transaction, from the last one to the first
+    fn revert(&mut self, undo_log: Vec<Undo>) {
+        for undo in undo_log.into_iter().rev() {
+            match undo {
+                Undo::Record { store, key, value } => {
+                    if let Some(store) = self.object_stores.get_mut(&store) {
+                        match value {
+                            Some(value) => store.records.insert(key, value),
+                            None => store.records.remove(&key),
+                        };
+                    }
+                },
+                Undo::CurrentNumber {
+                    store,
+                    current_number,
+                } => {
+                    if let Some(store) = self.object_stores.get_mut(&store) {
+                        store.current_number = current_number;
+                    }
+                },
+                Undo::StoreCreated(name) => {
+                    self.object_stores.remove(&name);
+                },
+                Undo::StoreDeleted(name, store) => {
+                    self.object_stores.insert(name, store);
+                },
+            }
+        }
+    }
+}
+
+/// A change made by a transaction, which gets reverted if it aborts
+#[derive(Clone)]
+enum Undo {
+    /// The value a record had before it got stored or deleted
+    Record {
+        store: String,
+        key: IndexedDBKeyType,
+        value: Option<Vec<u8>>,
+    },
+    /// The current number of a key generator before it got increased
+    CurrentNumber {
+        store: String,
+        current_number: u64,
+    },
+    StoreCreated(String),
+    StoreDeleted(String, ObjectStore),
+}
+
+/// The upgrade of a database done by a version change transaction
+struct Upgrade {
+    old_version: u64,
+    new_version: u64,
+    /// Where to report that the transaction started
+    sender: Option<IpcSender<OpenResult>>,
+}
+
+type RequestSender = IpcSender<Result<IndexedDBResult, BackendError>>;
+
+/// https://w3c.github.io/IndexedDB/#transaction-construct
+struct Transaction {
+    id: u64,
+    mode: IndexedDBTxnMode,
+    /// The object stores in scope, which are all of them for a version change
+    /// transaction
+    scope: Vec<String>,
+    started: bool,
+    /// The requests made before the transaction started
+    pending_requests: Vec<(RequestSender, String, AsyncOperation)>,
+    /// The commit requested before the transaction started
+    pending_commit: Option<IpcSender<Result<(), BackendError>>>,
+    undo_log: Vec<Undo>,
+    upgrade: Option<Upgrade>,
+}
+
+impl Transaction {
+    fn new(
+        id: u64,
+        mode: IndexedDBTxnMode,
+        scope: Vec<String>,
+        upgrade: Option<Upgrade>,
+    ) -> Transaction {
+        Transaction {
+            id: id,
+            mode: mode,
+            scope: scope,
+            started: false,
+            pending_requests: vec![],
+            pending_commit: None,
+            undo_log: vec![],
+            upgrade: upgrade,
+        }
+    }
+
+    /// Whether this transaction, created before `other`, prevents it from
+    /// starting as long as it did not finish
+    ///
+    /// https://w3c.github.io/IndexedDB/#transaction-scheduling
+    fn blocks(&self, other: &Transaction) -> bool {
+        if self.mode == IndexedDBTxnMode::Versionchange ||
+            other.mode == IndexedDBTxnMode::Versionchange
+        {
+            return true;
+        }
+        if self.mode == IndexedDBTxnMode::Readonly && other.mode == IndexedDBTxnMode::Readonly {
+            return false;
+        }
+        self.scope.iter().any(|name| other.scope.contains(name))
+    }
+}
+
+/// https://w3c.github.io/IndexedDB/#request-open
+enum ConnectionRequest {
+    Open(IpcSender<OpenResult>, Option<u64>),
+    Delete(IpcSender<Result<u64, BackendError>>),
+}
+
+/// What it takes to schedule the connection requests and transactions of a
+/// database, which does not get persisted
+#[derive(Default)]
+struct DatabaseQueue {
+    /// https://w3c.github.io/IndexedDB/#connection-queue
+    connection_requests: VecDeque<ConnectionRequest>,
+    /// The transactions that did not finish yet, in the order they got
+    /// created
+    transactions: Vec<Transaction>,
+}
+
+impl DatabaseQueue {
+    fn transaction_mut(&mut self, id: u64) -> Option<&mut Transaction> {
+        self.transactions.iter_mut().find(|txn| txn.id == id)
+    }
+
+    fn remove_transaction(&mut self, id: u64) -> Option<Transaction> {
+        let index = self.transactions.iter().position(|txn| txn.id == id)?;
+        Some(self.transactions.remove(index))
+    }
+
+    /// The id of the first transaction that can start, if any
+    fn next_startable_transaction(&self) -> Option<u64> {
+        self.transactions
+            .iter()
+            .enumerate()
+            .find(|&(index, txn)| {
+                !txn.started &&
+                    self.transactions[..index]
+                        .iter()
+                        .all(|earlier| !earlier.blocks(txn))
+            })
+            .map(|(_, txn)| txn.id)
+    }
+}
+
+struct IndexedDBManager {
+    port: IpcReceiver<IndexedDBThreadMsg>,
+    config_dir: Option<PathBuf>,
+    /// The databases of each origin, by name, which get loaded from disk
+    /// when the origin first uses them
+    databases: HashMap<String, HashMap<String, Database>>,
+    /// The queues of the databases, by origin and name
+    queues: HashMap<(String, String), DatabaseQueue>,
+    next_transaction_id: u64,
+}
+
+impl IndexedDBManager {
+    fn new(port: IpcReceiver<IndexedDBThreadMsg>, config_dir: Option<PathBuf>) -> IndexedDBManager {
+        IndexedDBManager {
+            port: port,
+            config_dir: config_dir,
+            databases: HashMap::new(),
+            queues: HashMap::new(),
+            next_transaction_id: 0,
+        }
+    }
+}
+
+impl IndexedDBManager {
+    fn start(&mut self) {
+        loop {
+            match self.port.recv().unwrap() {
+                IndexedDBThreadMsg::Open(sender, origin, name, version) => {
+                    let origin = self.origin_as_string(&origin);
+                    self.queue_mut(&origin, &name)
+                        .connection_requests
+                        .push_back(ConnectionRequest::Open(sender, version));
+                    self.update(&origin, &name);
+                },
+                IndexedDBThreadMsg::DeleteDatabase(sender, origin, name) => {
+                    let origin = self.origin_as_string(&origin);
+                    self.queue_mut(&origin, &name)
+                        .connection_requests
+                        .push_back(ConnectionRequest::Delete(sender));
+                    self.update(&origin, &name);
+                },
+                IndexedDBThreadMsg::CreateTransaction(sender, origin, name, mode, scope) => {
+                    let origin = self.origin_as_string(&origin);
+                    let id = self.new_transaction_id();
+                    self.queue_mut(&origin, &name)
+                        .transactions
+                        .push(Transaction::new(id, mode, scope, None));
+                    let _ = sender.send(id);
+                    self.update(&origin, &name);
+                },
+                IndexedDBThreadMsg::Request {
+                    sender,
+                    origin,
+                    db_name,
+                    txn,
+                    store_name,
+                    operation,
+                } => {
+                    let origin = self.origin_as_string(&origin);
+                    self.request(&origin, &db_name, txn, sender, store_name, operation);
+                },
+                IndexedDBThreadMsg::CreateObjectStore {
+                    origin,
+                    db_name,
+                    txn,
+                    store_name,
+                    key_path,
+                    auto_increment,
+                } => {
+                    let origin = self.origin_as_string(&origin);
+                    let store = ObjectStore {
+                        key_path: key_path,
+                        auto_increment: auto_increment,
+                        current_number: 1,
+                        records: BTreeMap::new(),
+                    };
+                    self.create_object_store(&origin, &db_name, txn, store_name, store);
+                },
+                IndexedDBThreadMsg::DeleteObjectStore {
+                    origin,
+                    db_name,
+                    txn,
+                    store_name,
+                } => {
+                    let origin = self.origin_as_string(&origin);
+                    self.delete_object_store(&origin, &db_name, txn, store_name);
+                },
+                IndexedDBThreadMsg::Commit(sender, origin, name, txn) => {
+                    let origin = self.origin_as_string(&origin);
+                    self.commit(&origin, &name, txn, sender);
+                    self.update(&origin, &name);
+                },
+                IndexedDBThreadMsg::Abort(sender, origin, name, txn) => {
+                    let origin = self.origin_as_string(&origin);
+                    self.abort(&origin, &name, txn);
+                    let _ = sender.send(());
+                    self.update(&origin, &name);
+                },
+                IndexedDBThreadMsg::Exit(sender) => {
+                    // Nothing to do since the databases get saved as the
+                    // transactions commit.
+                    let _ = sender.send(());
+                    break;
+                },
+            }
+        }
+    }
+
+    fn new_transaction_id(&mut self) -> u64 {
+        let id = self.next_transaction_id;
+        self.next_transaction_id += 1;
+        id
+    }
+
+    fn queue_mut(&mut self, origin: &str, name: &str) -> &mut DatabaseQueue {
+        self.queues
+            .entry((origin.to_owned(), name.to_owned()))
+            .or_insert_with(DatabaseQueue::default)
+    }
+
+    /// The databases of an origin, loading them from disk if needed
+    fn origin_databases(&mut self, origin: &str) -> &mut HashMap<String, Database> {
+        let config_dir = &self.config_dir;
+        self.databases.entry(origin.to_owned()).or_insert_with(|| {
+            let mut databases = HashMap::new();
+            if let Some(ref config_dir) = *config_dir {
+                resource_thread::read_json_from_file(
+                    &mut databases,
+                    &config_dir.join("indexeddb"),
+                    &origin_file_name(origin),
+                );
+            }
+            databases
+        })
+    }
+
+    /// Saves the databases of an origin as the transactions that committed
+    /// left them, the changes of the started ones being left out.
+    fn save_origin(&self, origin: &str) {
+        let config_dir = match self.config_dir {
+            Some(ref config_dir) => config_dir.join("indexeddb"),
+            None => return,
+        };
+        let databases = match self.databases.get(origin) {
+            Some(databases) => databases,
+            None => return,
+        };
+
+        let mut committed = databases.clone();
+        for ((queue_origin, name), queue) in &self.queues {
+            if queue_origin != origin {
+                continue;
+            }
+            for txn in &queue.transactions {
+                if !txn.started {
+                    continue;
+                }
+                if let Some(upgrade) = txn.upgrade.as_ref() {
+                    if upgrade.old_version == 0 {
+                        committed.remove(name);
+                        continue;
+                    }
+                }
+                if let Some(db) = committed.get_mut(name) {
+                    db.revert(txn.undo_log.clone());
+                    if let Some(upgrade) = txn.upgrade.as_ref() {
+                        db.version = upgrade.old_version;
+                    }
+                }
+            }
+        }
+
+        if let Err(error) = fs::create_dir_all(&config_dir) {
+            warn!("Could not create the IndexedDB directory ({})", error);
+            return;
+        }
+        let file_name = origin_file_name(origin);
+        if let Err(error) =
+            resource_thread::write_json_to_file_atomically(&committed, &config_dir, &file_name)
+        {
+            warn!(
+                "Could not save the IndexedDB databases of {} ({})",
+                origin, error
+            );
+        }
+    }
+
+    /// Starts the transactions and processes the connection requests that
+    /// are not waiting for anything anymore
+    fn update(&mut self, origin: &str, name: &str) {
+        loop {
+            if let Some(id) = self.queue_mut(origin, name).next_startable_transaction() {
+                self.start_transaction(origin, name, id);
+                continue;
+            }
+            if !self.process_connection_request(origin, name) {
+                break;
+            }
+        }
+    }
+
+    /// Processes the first connection request of a database, unless it has
+    /// to wait, returning whether one got processed
+    fn process_connection_request(&mut self, origin: &str, name: &str) -> bool {
+        let request = {
+            let queue = self.queue_mut(origin, name);
+            // The requests wait for the upgrade being done, if any.
+            if queue
+                .transactions
+                .iter()
+                .any(|txn| txn.mode == IndexedDBTxnMode::Versionchange)
+            {
+                return false;
+            }
+            // Deleting the database waits for its transactions to finish.
+            match queue.connection_requests.front() {
+                Some(&ConnectionRequest::Delete(_)) if !queue.transactions.is_empty() => {
+                    return false;
+                },
+                Some(_) => {},
+                None => return false,
+            }
+            queue.connection_requests.pop_front().unwrap()
+        };
+
+        match request {
+            ConnectionRequest::Open(sender, version) => {
+                self.open_database(origin, name, sender, version)
+            },
+            ConnectionRequest::Delete(sender) => {
+                let old_version = self
+                    .origin_databases(origin)
+                    .remove(name)
+                    .map_or(0, |db| db.version);
+                self.save_origin(origin);
+                let _ = sender.send(Ok(old_version));
+            },
+        }
+        true
+    }
+
+    /// https://w3c.github.io/IndexedDB/#open-a-database
+    fn open_database(
+        &mut self,
+        origin: &str,
+        name: &str,
+        sender: IpcSender<OpenResult>,
+        version: Option<u64>,
+    ) {
+        // Steps 4-5.
+        let (old_version, metadata) = self
+            .origin_databases(origin)
+            .get(name)
+            .map_or((0, None), |db| (db.version, Some(db.metadata())));
+
+        // Step 6.
+        let new_version = version.unwrap_or(cmp::max(old_version, 1));
+
+        // Step 7.
+        if new_version < old_version {
+            let _ = sender.send(OpenResult::Error(BackendError::Version));
+            return;
+        }
+
+        // Step 10.
+        if new_version == old_version {
+            let _ = sender.send(OpenResult::Success(metadata.unwrap()));
+            return;
+        }
+
+        // The database gets upgraded once the transactions created before
+        // finished.
+        let id = self.new_transaction_id();
+        let upgrade = Upgrade {
+            old_version: old_version,
+            new_version: new_version,
+            sender: Some(sender),
+        };
+        self.queue_mut(origin, name)
+            .transactions
+            .push(Transaction::new(
+                id,
+                IndexedDBTxnMode::Versionchange,
+                vec![],
+                Some(upgrade),
+            ));
+    }
+
+    fn start_transaction(&mut self, origin: &str, name: &str, id: u64) {
+        let (pending_requests, pending_commit, upgrade) = {
+            let txn = self.queue_mut(origin, name).transaction_mut(id).unwrap();
+            txn.started = true;
+            (
+                mem::replace(&mut txn.pending_requests, vec![]),
+                txn.pending_commit.take(),
+                txn.upgrade
+                    .as_mut()
+                    .and_then(|upgrade| Some((upgrade.new_version, upgrade.sender.take()?))),
+            )
+        };
+
+        // https://w3c.github.io/IndexedDB/#upgrade-a-database
+        if let Some((new_version, sender)) = upgrade {
+            let db = self
+                .origin_databases(origin)
+                .entry(name.to_owned())
+                .or_insert_with(|| Database {
+                    version: 0,
+                    object_stores: BTreeMap::new(),
+                });
+            let old_version = db.version;
+            db.version = new_version;
+            let _ = sender.send(OpenResult::Upgrade {
+                transaction: id,
+                old_version: old_version,
+                metadata: db.metadata(),
+            });
+        }
+
+        for (sender, store_name, operation) in pending_requests {
+            self.execute_request(origin, name, id, sender, store_name, operation);
+        }
+        if let Some(sender) = pending_commit {
+            self.commit(origin, name, id, sender);
+        }
+    }
+
+    fn request(
+        &mut self,
+        origin: &str,
+        name: &str,
+        id: u64,
+        sender: RequestSender,
+        store_name: String,
+        operation: AsyncOperation,
+    ) {
+        match self.queue_mut(origin, name).transaction_mut(id) {
+            Some(txn) => {
+                if !txn.started {
+                    txn.pending_requests.push((sender, store_name, operation));
+                    return;
+                }
+            },
+            None => {
+                let _ = sender.send(Err(BackendError::Abort));
+                return;
+            },
+        }
+        self.execute_request(origin, name, id, sender, store_name, operation);
+    }
+
+    fn execute_request(
+        &mut self,
+        origin: &str,
+        name: &str,
+        id: u64,
+        sender: RequestSender,
+        store_name: String,
+        operation: AsyncOperation,
+    ) {
+        self.origin_databases(origin);
+        let store = self
+            .databases
+            .get_mut(origin)
+            .and_then(|databases| databases.get_mut(name))
+            .and_then(|db| db.object_stores.get_mut(&store_name));
+        let txn = self
+            .queues
+            .get_mut(&(origin.to_owned(), name.to_owned()))
+            .and_then(|queue| queue.transaction_mut(id));
+        let result = match (store, txn) {
+            (Some(store), Some(txn)) => {
+                execute_operation(store, &store_name, &mut txn.undo_log, operation)
+            },
+            _ => Err(BackendError::NotFound),
+        };
+        let _ = sender.send(result);
+    }
+
+    fn create_object_store(
+        &mut self,
+        origin: &str,
+        name: &str,
+        id: u64,
+        store_name: String,
+        store: ObjectStore,
+    ) {
+        self.origin_databases(origin);
+        let db = self
+            .databases
+            .get_mut(origin)
+            .and_then(|databases| databases.get_mut(name));
+        let txn = self
+            .queues
+            .get_mut(&(origin.to_owned(), name.to_owned()))
+            .and_then(|queue| queue.transaction_mut(id))
+            .filter(|txn| txn.started && txn.mode == IndexedDBTxnMode::Versionchange);
+        let (db, txn) = match (db, txn) {
+            (Some(db), Some(txn)) => (db, txn),
+            _ => {
+                warn!("Creating an object store outside of an upgrade");
+                return;
+            },
+        };
+        if db.object_stores.contains_key(&store_name) {
+            warn!("The object store {} already exists", store_name);
+            return;
+        }
+        db.object_stores.insert(store_name.clone(), store);
+        txn.undo_log.push(Undo::StoreCreated(store_name));
+    }
+
+    fn delete_object_store(&mut self, origin: &str, name: &str, id: u64, store_name: String) {
+        self.origin_databases(origin);
+        let db = self
+            .databases
+            .get_mut(origin)
+            .and_then(|databases| databases.get_mut(name));
+        let txn = self
+            .queues
+            .get_mut(&(origin.to_owned(), name.to_owned()))
+            .and_then(|queue| queue.transaction_mut(id))
+            .filter(|txn| txn.started && txn.mode == IndexedDBTxnMode::Versionchange);
+        let (db, txn) = match (db, txn) {
+            (Some(db), Some(txn)) => (db, txn),
+            _ => {
+                warn!("Deleting an object store outside of an upgrade");
+                return;
+            },
+        };
+        if let Some(store) = db.object_stores.remove(&store_name) {
+            txn.undo_log.push(Undo::StoreDeleted(store_name, store));
+        }
+    }
+
+    /// https://w3c.github.io/IndexedDB/#commit-a-transaction
+    fn commit(
+        &mut self,
+        origin: &str,
+        name: &str,
+        id: u64,
+        sender: IpcSender<Result<(), BackendError>>,
+    ) {
+        let queue = self.queue_mut(origin, name);
+        match queue.transaction_mut(id) {
+            Some(txn) => {
+                if !txn.started {
+                    txn.pending_commit = Some(sender);
+                    return;
+                }
+            },
+            None => {
+                let _ = sender.send(Err(BackendError::Abort));
+                return;
+            },
+        }
+        let txn = queue.remove_transaction(id).unwrap();
+        if txn.mode != IndexedDBTxnMode::Readonly {
+            self.save_origin(origin);
+        }
+        let _ = sender.send(Ok(()));
+    }
+
+    /// https://w3c.github.io/IndexedDB/#abort-a-transaction
+    fn abort(&mut self, origin: &str, name: &str, id: u64) {
+        let txn = match self.queue_mut(origin, name).remove_transaction(id) {
+            Some(txn) => txn,
+            None => return,
+        };
+
+        if !txn.started {
+            for (sender, _, _) in txn.pending_requests {
+                let _ = sender.send(Err(BackendError::Abort));
+            }
+            if let Some(sender) = txn.pending_commit {
+                let _ = sender.send(Err(BackendError::Abort));
+            }
+            if let Some(sender) = txn.upgrade.and_then(|upgrade| upgrade.sender) {
+                let _ = sender.send(OpenResult::Error(BackendError::Abort));
+            }
+            return;
+        }
+
+        if txn.mode == IndexedDBTxnMode::Readonly {
+            return;
+        }
+        let databases = self.origin_databases(origin);
+        if let Some(db) = databases.get_mut(name) {
+            db.revert(txn.undo_log);
+        }
+        // A database created by the aborted upgrade does not exist anymore.
+        if let Some(upgrade) = txn.upgrade {
+            if upgrade.old_version == 0 {
+                databases.remove(name);
+            } else if let Some(db) = databases.get_mut(name) {
+                db.version = upgrade.old_version;
+            }
+        }
+        self.save_origin(origin);
+    }
+
+    fn origin_as_string(&self, origin: &ImmutableOrigin) -> String {
+        origin.ascii_serialization()
+    }
+}
+
+/// The name of the file the databases of an origin are saved to
+fn origin_file_name(origin: &str) -> String {
+    let hex: String = origin.bytes().map(|byte| format!("{:02x}", byte)).collect();
+    format!("{}.json", hex)
+}
+
+/// https://w3c.github.io/IndexedDB/#asynchronously-execute-a-request
+fn execute_operation(
+    store: &mut ObjectStore,
+    store_name: &str,
+    undo_log: &mut Vec<Undo>,
+    operation: AsyncOperation,
+) -> Result<IndexedDBResult, BackendError> {
+    match operation {
+        AsyncOperation::Put {
+            key,
+            value,
+            overwrite,
+        } => {
+            // https://w3c.github.io/IndexedDB/#store-a-record-into-an-object-store
+            // Steps 1-2.
+            let key = match key {
+                Some(key) => {
+                    if let IndexedDBKeyType::Number(number) = key {
+                        if store.auto_increment {
+                            possibly_update_key_generator(store, store_name, undo_log, number);
+                        }
+                    }
+                    key
+                },
+                None => {
+                    // https://w3c.github.io/IndexedDB/#generate-a-key
+                    if store.current_number > MAX_GENERATED_KEY {
+                        return Err(BackendError::Constraint);
+                    }
+                    undo_log.push(Undo::CurrentNumber {
+                        store: store_name.to_owned(),
+                        current_number: store.current_number,
+                    });
+                    let key = IndexedDBKeyType::Number(store.current_number as f64);
+                    store.current_number += 1;
+                    key
+                },
+            };
+
+            // Step 3.
+            if !overwrite && store.records.contains_key(&key) {
+                return Err(BackendError::Constraint);
+            }
+
+            // Steps 4-5.
+            let old_value = store.records.insert(key.clone(), value);
+            undo_log.push(Undo::Record {
+                store: store_name.to_owned(),
+                key: key.clone(),
+                value: old_value,
+            });
+
+            // Step 7.
+            Ok(IndexedDBResult::Key(key))
+        },
+        AsyncOperation::Get(key) => {
+            let value = store.records.get(&key).cloned();
+            Ok(IndexedDBResult::Record(value.map(|value| (key, value))))
+        },
+        AsyncOperation::Delete(key) => {
+            if let Some(old_value) = store.records.remove(&key) {
+                undo_log.push(Undo::Record {
+                    store: store_name.to_owned(),
+                    key: key,
+                    value: Some(old_value),
+                });
+            }
+            Ok(IndexedDBResult::Undefined)
+        },
+        AsyncOperation::Clear => {
+            let records = mem::replace(&mut store.records, BTreeMap::new());
+            undo_log.extend(records.into_iter().map(|(key, value)| Undo::Record {
+                store: store_name.to_owned(),
+                key: key,
+                value: Some(value),
+            }));
+            Ok(IndexedDBResult::Undefined)
+        },
+        AsyncOperation::Count(query) => {
+            let count = match query {
+                Some(key) => store.records.contains_key(&key) as u64,
+                None => store.records.len() as u64,
+            };
+            Ok(IndexedDBResult::Count(count))
+        },
+        AsyncOperation::Iterate {
+            query,
+            direction,
+            position,
+            key,
+            count,
+        } => {
+            // https://w3c.github.io/IndexedDB/#iterate-a-cursor
+            let bound = match (key.as_ref(), position.as_ref()) {
+                (Some(key), _) => Bound::Included(key),
+                (None, Some(position)) => Bound::Excluded(position),
+                (None, None) => Bound::Unbounded,
+            };
+            let in_range = |record_key: &IndexedDBKeyType| {
+                query.as_ref().map_or(true, |query| query == record_key)
+            };
+            let index = cmp::max(count, 1) as usize - 1;
+            let record = match direction {
+                IndexedDBCursorDirection::Next => store
+                    .records
+                    .range((bound, Bound::Unbounded))
+                    .filter(|&(record_key, _)| in_range(record_key))
+                    .nth(index),
+                IndexedDBCursorDirection::Prev => store
+                    .records
+                    .range((Bound::Unbounded, bound))
+                    .rev()
+                    .filter(|&(record_key, _)| in_range(record_key))
+                    .nth(index),
+            };
+            Ok(IndexedDBResult::Record(
+                record.map(|(key, value)| (key.clone(), value.clone())),
+            ))
+        },
+    }
+}
+
+/// https://w3c.github.io/IndexedDB/#possibly-update-the-key-generator
+fn possibly_update_key_generator(
+    store: &mut ObjectStore,
+    store_name: &str,
+    undo_log: &mut Vec<Undo>,
+    key: f64,
+) {
+    // Steps 2-4.
+    if key < store.current_number as f64 {
+        return;
+    }
+    let value = key.floor();
+    let current_number = if value >= MAX_GENERATED_KEY as f64 {
+        MAX_GENERATED_KEY + 1
+    } else {
+        value as u64 + 1
+    };
+
+    // Step 5.
+    undo_log.push(Undo::CurrentNumber {
+        store: store_name.to_owned(),
+        current_number: store.current_number,
+    });
+    store.current_number = current_number;
+}
@@ -19,6 +19,7 @@ extern crate serde;
 #[macro_use]
 extern crate servo_config;
 
+pub mod cache_storage;
 pub mod connector;
 pub mod cookie;
 pub mod cookie_storage;
@@ -30,6 +31,7 @@ pub mod hsts;
 pub mod http_cache;
 pub mod http_loader;
 pub mod image_cache;
+mod indexeddb_thread;
 pub mod mime_classifier;
 pub mod resource_thread;
 mod storage_thread;
@@ -45,4 +47,5 @@ pub mod fetch {
 pub mod test {
     pub use crate::hosts::{parse_hostsfile, replace_host_table};
     pub use crate::http_loader::HttpState;
+    pub use crate::indexeddb_thread::IndexedDBThreadFactory;
 }
@@ -4,6 +4,7 @@
 
 //! A thread that takes a URL and streams back the binary data.
 
+use crate::cache_storage::CacheStorage;
 use crate::connector::{create_http_client, create_ssl_connector_builder};
 use crate::cookie;
 use crate::cookie_storage::CookieStorage;
@@ -12,16 +13,18 @@ use crate::fetch::methods::{fetch, CancellationListener, FetchContext};
 use crate::filemanager_thread::FileManager;
 use crate::hsts::HstsList;
 use crate::http_cache::HttpCache;
-use crate::http_loader::{http_redirect_fetch, HttpState, HANDLE};
+use crate::http_loader::{self, http_redirect_fetch, HttpState, HANDLE};
+use crate::indexeddb_thread::IndexedDBThreadFactory;
 use crate::storage_thread::StorageThreadFactory;
 use crate::websocket_loader;
 use crossbeam_channel::Sender;
-use devtools_traits::DevtoolsControlMsg;
+use devtools_traits::{ChromeToDevtoolsControlMsg, DevtoolsControlMsg};
 use embedder_traits::resources::{self, Resource};
 use embedder_traits::EmbedderProxy;
 use hyper_serde::Serde;
 use ipc_channel::ipc::{self, IpcReceiver, IpcReceiverSet, IpcSender};
 use malloc_size_of::{MallocSizeOf, MallocSizeOfOps};
+use net_traits::indexeddb_thread::IndexedDBThreadMsg;
 use net_traits::request::{Destination, Request, RequestInit};
 use net_traits::response::{Response, ResponseInit};
 use net_traits::storage_thread::StorageThreadMsg;
@@ -40,7 +43,7 @@ use std::borrow::{Cow, ToOwned};
 use std::collections::HashMap;
 use std::error::Error;
 use std::fs::{self, File};
-use std::io::prelude::*;
+use std::io::{self, prelude::*};
 use std::ops::Deref;
 use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex, RwLock};
@@ -57,16 +60,18 @@ pub fn new_resource_threads(
 ) -> (ResourceThreads, ResourceThreads) {
     let (public_core, private_core) = new_core_resource_thread(
         user_agent,
-        devtools_chan,
+        devtools_chan.clone(),
         time_profiler_chan,
         mem_profiler_chan,
         embedder_proxy,
         config_dir.clone(),
     );
-    let storage: IpcSender<StorageThreadMsg> = StorageThreadFactory::new(config_dir);
+    let storage: IpcSender<StorageThreadMsg> =
+        StorageThreadFactory::new(config_dir.clone(), devtools_chan);
+    let indexeddb: IpcSender<IndexedDBThreadMsg> = IndexedDBThreadFactory::new(config_dir);
     (
-        ResourceThreads::new(public_core, storage.clone()),
-        ResourceThreads::new(private_core, storage),
+        ResourceThreads::new(public_core, storage.clone(), indexeddb.clone()),
+        ResourceThreads::new(private_core, storage, indexeddb),
     )
 }
 
@@ -117,12 +122,15 @@ struct ResourceChannelManager {
 fn create_http_states(config_dir: Option<&Path>) -> (Arc<HttpState>, Arc<HttpState>) {
     let mut hsts_list = HstsList::from_servo_preload();
     let mut auth_cache = AuthCache::new();
-    let http_cache = HttpCache::new();
+    let mut cache_storage = CacheStorage::new();
+    let mut http_cache = HttpCache::new();
     let mut cookie_jar = CookieStorage::new(150);
     if let Some(config_dir) = config_dir {
+        http_cache = HttpCache::from_disk(&config_dir.join("http_cache"));
         read_json_from_file(&mut auth_cache, config_dir, "auth_cache.json");
         read_json_from_file(&mut hsts_list, config_dir, "hsts_list.json");
         read_json_from_file(&mut cookie_jar, config_dir, "cookie_jar.json");
+        read_json_from_file(&mut cache_storage, config_dir, "cache_storage.json");
     }
 
     let certs = match opts::get().certificate_path {
@@ -131,13 +139,21 @@ fn create_http_states(config_dir: Option<&Path>) -> (Arc<HttpState>, Arc<HttpSta
     };
 
     let ssl_connector_builder = create_ssl_connector_builder(&certs);
+    let http2 = pref!(network.http2.enabled);
     let http_state = HttpState {
         cookie_jar: RwLock::new(cookie_jar),
         auth_cache: RwLock::new(auth_cache),
+        cache_storage: RwLock::new(cache_storage),
         http_cache: RwLock::new(http_cache),
         hsts_list: RwLock::new(hsts_list),
         history_states: RwLock::new(HashMap::new()),
-        client: create_http_client(ssl_connector_builder, HANDLE.lock().unwrap().executor()),
+        client: create_http_client(
+            ssl_connector_builder,
+            HANDLE.lock().unwrap().executor(),
+            http2,
+        )
+        .expect("could not create the HTTP client"),
+        http2,
     };
 
     let private_ssl_client = create_ssl_connector_builder(&certs);
@@ -157,6 +173,14 @@ impl ResourceChannelManager {
         let (public_http_state, private_http_state) =
             create_http_states(self.config_dir.as_ref().map(Deref::deref));
 
+        if let Some(ref devtools_chan) = self.resource_manager.devtools_chan {
+            let cookie_jar = public_http_state.cookie_jar.read().unwrap();
+            for event in cookie_jar.devtools_events() {
+                let msg = ChromeToDevtoolsControlMsg::StorageEvent(event);
+                let _ = devtools_chan.send(DevtoolsControlMsg::FromChrome(msg));
+            }
+        }
+
         let mut rx_set = IpcReceiverSet::new().unwrap();
         let private_id = rx_set.add(private_receiver).unwrap();
         let public_id = rx_set.add(public_receiver).unwrap();
@@ -289,6 +313,9 @@ impl ResourceChannelManager {
                 let _ = sender.send(());
             },
             CoreResourceMsg::ToFileManager(msg) => self.resource_manager.filemanager.handle(msg),
+            CoreResourceMsg::CacheStorage(msg) => {
+                http_state.cache_storage.write().unwrap().handle(msg)
+            },
             CoreResourceMsg::Exit(sender) => {
                 if let Some(ref config_dir) = self.config_dir {
                     match http_state.auth_cache.read() {
@@ -305,6 +332,17 @@ impl ResourceChannelManager {
                         Ok(hsts) => write_json_to_file(&*hsts, config_dir, "hsts_list.json"),
                         Err(_) => warn!("Error writing hsts list to disk"),
                     }
+                    match http_state.cache_storage.read() {
+                        Ok(cache_storage) => {
+                            write_json_to_file(&*cache_storage, config_dir, "cache_storage.json")
+                        },
+                        Err(_) => warn!("Error writing cache storage to disk"),
+                    }
+                    match http_state.http_cache.read() {
+                        // Only the last uses of the resources can have changed
+                        Ok(cache) => cache.write_index(),
+                        Err(_) => warn!("Error writing http cache to disk"),
+                    }
                 }
                 let _ = sender.send(());
                 return false;
@@ -372,6 +410,28 @@ where
     }
 }
 
+/// Writes the data to a temporary file which then replaces the old one, so
+/// that a crash in the middle of the write does not corrupt what got saved
+/// before. Unlike `write_json_to_file`, failures are returned to the caller.
+pub fn write_json_to_file_atomically<T>(
+    data: &T,
+    config_dir: &Path,
+    filename: &str,
+) -> io::Result<()>
+where
+    T: Serialize,
+{
+    let json_encoded = serde_json::to_string_pretty(&data)
+        .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+    let path = config_dir.join(filename);
+    let temp_path = config_dir.join(format!("{}.tmp", filename));
+
+    let mut file = File::create(&temp_path)?;
+    file.write_all(json_encoded.as_bytes())?;
+    file.sync_all()?;
+    fs::rename(&temp_path, &path)
+}
+
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct AuthCacheEntry {
     pub user_name: String,
@@ -423,8 +483,18 @@ impl CoreResourceManager {
         http_state: &Arc<HttpState>,
     ) {
         if let Some(cookie) = cookie::Cookie::new_wrapped(cookie, request, source) {
-            let mut cookie_jar = http_state.cookie_jar.write().unwrap();
-            cookie_jar.push(cookie, request, source)
+            http_state
+                .cookie_jar
+                .write()
+                .unwrap()
+                .push(cookie, request, source);
+            if let Some(ref devtools_chan) = self.devtools_chan {
+                http_loader::send_cookies_to_devtools(
+                    devtools_chan,
+                    &http_state.cookie_jar,
+                    request,
+                );
+            }
         }
     }
 
@@ -440,6 +510,7 @@ impl CoreResourceManager {
         let ua = self.user_agent.clone();
         let dc = self.devtools_chan.clone();
         let filemanager = self.filemanager.clone();
+        let swmanager_chan = self.swmanager_chan.clone();
 
         let timing_type = match req_init.destination {
             Destination::Document => ResourceTimingType::Navigation,
@@ -453,12 +524,12 @@ impl CoreResourceManager {
                 // XXXManishearth: Check origin against pipeline id (also ensure that the mode is allowed)
                 // todo load context / mimesniff in fetch
                 // todo referrer policy?
-                // todo service worker stuff
                 let context = FetchContext {
                     state: http_state,
                     user_agent: ua,
                     devtools_chan: dc,
                     filemanager: filemanager,
+                    swmanager_chan: swmanager_chan,
                     cancellation_listener: Arc::new(Mutex::new(CancellationListener::new(
                         cancel_chan,
                     ))),
@@ -3,6 +3,8 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
 use crate::resource_thread;
+use crossbeam_channel::Sender;
+use devtools_traits::{ChromeToDevtoolsControlMsg, DevtoolsControlMsg, StorageEvent};
 use ipc_channel::ipc::{self, IpcReceiver, IpcSender};
 use net_traits::storage_thread::{StorageThreadMsg, StorageType};
 use servo_url::ServoUrl;
@@ -15,17 +17,20 @@ use std::thread;
 const QUOTA_SIZE_LIMIT: usize = 5 * 1024 * 1024;
 
 pub trait StorageThreadFactory {
-    fn new(config_dir: Option<PathBuf>) -> Self;
+    fn new(config_dir: Option<PathBuf>, devtools_chan: Option<Sender<DevtoolsControlMsg>>) -> Self;
 }
 
 impl StorageThreadFactory for IpcSender<StorageThreadMsg> {
     /// Create a storage thread
-    fn new(config_dir: Option<PathBuf>) -> IpcSender<StorageThreadMsg> {
+    fn new(
+        config_dir: Option<PathBuf>,
+        devtools_chan: Option<Sender<DevtoolsControlMsg>>,
+    ) -> IpcSender<StorageThreadMsg> {
         let (chan, port) = ipc::channel().unwrap();
         thread::Builder::new()
             .name("StorageManager".to_owned())
             .spawn(move || {
-                StorageManager::new(port, config_dir).start();
+                StorageManager::new(port, config_dir, devtools_chan).start();
             })
             .expect("Thread spawning failed");
         chan
@@ -37,20 +42,30 @@ struct StorageManager {
     session_data: HashMap<String, (usize, BTreeMap<String, String>)>,
     local_data: HashMap<String, (usize, BTreeMap<String, String>)>,
     config_dir: Option<PathBuf>,
+    devtools_chan: Option<Sender<DevtoolsControlMsg>>,
 }
 
 impl StorageManager {
-    fn new(port: IpcReceiver<StorageThreadMsg>, config_dir: Option<PathBuf>) -> StorageManager {
+    fn new(
+        port: IpcReceiver<StorageThreadMsg>,
+        config_dir: Option<PathBuf>,
+        devtools_chan: Option<Sender<DevtoolsControlMsg>>,
+    ) -> StorageManager {
         let mut local_data = HashMap::new();
         if let Some(ref config_dir) = config_dir {
             resource_thread::read_json_from_file(&mut local_data, config_dir, "local_data.json");
         }
-        StorageManager {
+        let manager = StorageManager {
             port: port,
             session_data: HashMap::new(),
             local_data: local_data,
             config_dir: config_dir,
+            devtools_chan: devtools_chan,
+        };
+        for origin in manager.local_data.keys() {
+            manager.send_to_devtools(origin);
         }
+        manager
     }
 }
 
@@ -68,19 +83,22 @@ impl StorageManager {
                     self.keys(sender, url, storage_type)
                 },
                 StorageThreadMsg::SetItem(sender, url, storage_type, name, value) => {
-                    self.set_item(sender, url, storage_type, name, value);
-                    self.save_state()
+                    self.set_item(sender, url.clone(), storage_type, name, value);
+                    self.save_state();
+                    self.storage_changed(url, storage_type)
                 },
                 StorageThreadMsg::GetItem(sender, url, storage_type, name) => {
                     self.request_item(sender, url, storage_type, name)
                 },
                 StorageThreadMsg::RemoveItem(sender, url, storage_type, name) => {
-                    self.remove_item(sender, url, storage_type, name);
-                    self.save_state()
+                    self.remove_item(sender, url.clone(), storage_type, name);
+                    self.save_state();
+                    self.storage_changed(url, storage_type)
                 },
                 StorageThreadMsg::Clear(sender, url, storage_type) => {
-                    self.clear(sender, url, storage_type);
-                    self.save_state()
+                    self.clear(sender, url.clone(), storage_type);
+                    self.save_state();
+                    self.storage_changed(url, storage_type)
                 },
                 StorageThreadMsg::Exit(sender) => {
                     // Nothing to do since we save localstorage set eagerly.
@@ -97,6 +115,28 @@ impl StorageManager {
         }
     }
 
+    /// Tells devtools about the local storage items of the origin of `url`
+    /// after they may have changed.
+    fn storage_changed(&self, url: ServoUrl, storage_type: StorageType) {
+        if let StorageType::Local = storage_type {
+            self.send_to_devtools(&self.origin_as_string(url));
+        }
+    }
+
+    fn send_to_devtools(&self, origin: &str) {
+        let devtools_chan = match self.devtools_chan {
+            Some(ref devtools_chan) => devtools_chan,
+            None => return,
+        };
+        let items = self
+            .local_data
+            .get(origin)
+            .map_or(BTreeMap::new(), |&(_, ref entry)| entry.clone());
+        let event = StorageEvent::LocalStorage(origin.to_owned(), items);
+        let msg = ChromeToDevtoolsControlMsg::StorageEvent(event);
+        let _ = devtools_chan.send(DevtoolsControlMsg::FromChrome(msg));
+    }
+
     fn select_data(
         &self,
         storage_type: StorageType,
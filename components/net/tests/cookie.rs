@@ -2,6 +2,7 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
+use devtools_traits::StorageEvent;
 use net::cookie::Cookie;
 use net::cookie_storage::CookieStorage;
 use net_traits::CookieSource;
@@ -475,3 +476,32 @@ fn test_cookie_eviction_all_nonsecure_new_nonsecure() {
         "extra2=bar; extra3=bar; extra4=bar; extra5=bar; foo=bar"
     );
 }
+
+#[test]
+fn test_devtools_event_lists_the_cookies_of_the_registrable_domain() {
+    let mut storage = CookieStorage::new(5);
+    let url = ServoUrl::parse("https://home.example.org/foo").unwrap();
+    add_cookie_to_storage(&mut storage, &url, "foo=bar; HttpOnly");
+    add_cookie_to_storage(&mut storage, &url, "baz=qux; Domain=example.org; Path=/");
+    let other_url = ServoUrl::parse("https://example.com").unwrap();
+    add_cookie_to_storage(&mut storage, &other_url, "other=value");
+
+    let (domain, cookies) = match storage.devtools_event_for_url(&url) {
+        StorageEvent::Cookies(domain, cookies) => (domain, cookies),
+        _ => panic!("Expected the cookies of example.org"),
+    };
+    assert_eq!(domain, "example.org");
+    assert_eq!(cookies.len(), 2);
+    let foo = cookies.iter().find(|c| c.name == "foo").unwrap();
+    assert_eq!(foo.value, "bar");
+    assert_eq!(foo.domain, "home.example.org");
+    assert!(foo.host_only && foo.http_only && !foo.secure);
+    assert_eq!(foo.expires, None);
+    let baz = cookies.iter().find(|c| c.name == "baz").unwrap();
+    assert_eq!(baz.domain, "example.org");
+    assert_eq!(baz.path, "/");
+    assert!(!baz.host_only);
+
+    // Each registrable domain gets an event of its own.
+    assert_eq!(storage.devtools_events().len(), 2);
+}
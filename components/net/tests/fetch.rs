@@ -6,8 +6,8 @@ use crate::fetch_with_context;
 use crate::fetch_with_cors_cache;
 use crate::http_loader::{expect_devtools_http_request, expect_devtools_http_response};
 use crate::{
-    create_embedder_proxy, fetch, make_server, make_ssl_server, new_fetch_context,
-    DEFAULT_USER_AGENT,
+    create_embedder_proxy, fetch, make_h2_ssl_server, make_server, make_ssl_server,
+    new_fetch_context, DEFAULT_USER_AGENT,
 };
 use crossbeam_channel::{unbounded, Sender};
 use devtools_traits::HttpRequest as DevtoolsHttpRequest;
@@ -22,7 +22,7 @@ use headers_ext::{
 };
 use http::header::{self, HeaderMap, HeaderName, HeaderValue};
 use http::uri::Authority;
-use http::{Method, StatusCode};
+use http::{Method, StatusCode, Version};
 use hyper::body::Body;
 use hyper::{Request as HyperRequest, Response as HyperResponse};
 use mime::{self, Mime};
@@ -667,6 +667,7 @@ fn test_fetch_with_hsts() {
         user_agent: DEFAULT_USER_AGENT.into(),
         devtools_chan: None,
         filemanager: FileManager::new(create_embedder_proxy()),
+        swmanager_chan: None,
         cancellation_listener: Arc::new(Mutex::new(CancellationListener::new(None))),
         timing: Arc::new(Mutex::new(ResourceFetchTiming::new(
             ResourceTimingType::Navigation,
@@ -692,6 +693,70 @@ fn test_fetch_with_hsts() {
     );
 }
 
+#[test]
+fn test_fetch_negotiates_http2_and_reuses_the_connection() {
+    let h2_requests = Arc::new(AtomicUsize::new(0));
+    let h2_requests_clone = h2_requests.clone();
+    let handler = move |request: HyperRequest<Body>, response: &mut HyperResponse<Body>| {
+        let priority = request
+            .headers()
+            .get(HeaderName::from_static("priority"))
+            .cloned();
+        if request.version() == Version::HTTP_2 &&
+            priority == Some(HeaderValue::from_static("u=0, i"))
+        {
+            h2_requests_clone.fetch_add(1, Ordering::SeqCst);
+        }
+        *response.body_mut() = b"Yay!".to_vec().into();
+    };
+
+    let cert_path = Path::new("../../resources/self_signed_certificate_for_testing.crt")
+        .canonicalize()
+        .unwrap();
+    let key_path = Path::new("../../resources/privatekey_for_testing.key")
+        .canonicalize()
+        .unwrap();
+    let (server, url, connections) = make_h2_ssl_server(handler, cert_path.clone(), key_path);
+
+    let mut ca_content = String::new();
+    File::open(cert_path)
+        .unwrap()
+        .read_to_string(&mut ca_content)
+        .unwrap();
+    let ssl_client = create_ssl_connector_builder(&ca_content);
+
+    let mut context = FetchContext {
+        state: Arc::new(HttpState::new_with_http2(ssl_client, true)),
+        user_agent: DEFAULT_USER_AGENT.into(),
+        devtools_chan: None,
+        filemanager: FileManager::new(create_embedder_proxy()),
+        swmanager_chan: None,
+        cancellation_listener: Arc::new(Mutex::new(CancellationListener::new(None))),
+        timing: Arc::new(Mutex::new(ResourceFetchTiming::new(
+            ResourceTimingType::Navigation,
+        ))),
+    };
+
+    for _ in 0..2 {
+        let origin = Origin::Origin(url.origin());
+        let mut request = Request::new(url.clone(), Some(origin), None);
+        request.referrer = Referrer::NoReferrer;
+        request.destination = Destination::Document;
+        // Set the flag.
+        request.local_urls_only = false;
+        let response = fetch_with_context(&mut request, &mut context);
+        assert!(!response.is_network_error());
+        assert_eq!(
+            *response.body.lock().unwrap(),
+            ResponseBody::Done(b"Yay!".to_vec())
+        );
+    }
+    server.close();
+
+    assert_eq!(h2_requests.load(Ordering::SeqCst), 2);
+    assert_eq!(connections.load(Ordering::SeqCst), 1);
+}
+
 #[test]
 fn test_fetch_with_sri_network_error() {
     static MESSAGE: &'static [u8] = b"alert('Hello, Network Error');";
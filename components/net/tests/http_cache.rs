@@ -2,48 +2,190 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
-use hyper::header::{Expires, HttpDate};
-use hyper::method::Method;
-use hyper::status::StatusCode;
+use crossbeam_channel::unbounded;
+use headers_core::HeaderMapExt;
+use headers_ext::Expires;
+use http::StatusCode;
 use msg::constellation_msg::TEST_PIPELINE_ID;
 use net::http_cache::HttpCache;
-use net_traits::request::{Destination, Request, RequestInit};
+use net_traits::request::{Origin, Request};
 use net_traits::response::{Response, ResponseBody};
+use net_traits::{ResourceFetchTiming, ResourceTimingType};
 use servo_url::ServoUrl;
-use std::sync::mpsc::channel;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use uuid::Uuid;
 
+fn new_cache_dir() -> PathBuf {
+    env::temp_dir().join(format!("servo-http-cache-{}", Uuid::new_v4()))
+}
+
+fn request(url: &str) -> Request {
+    let url = ServoUrl::parse(url).unwrap();
+    let origin = Origin::Origin(url.origin());
+    Request::new(url, Some(origin), Some(TEST_PIPELINE_ID))
+}
+
+fn response(url: &str, body: ResponseBody) -> Response {
+    let url = ServoUrl::parse(url).unwrap();
+    let mut response = Response::new(
+        url,
+        ResourceFetchTiming::new(ResourceTimingType::Navigation),
+    );
+    // Expires header makes the response cacheable.
+    response
+        .headers
+        .typed_insert(Expires::from(SystemTime::now() + Duration::new(86400, 0)));
+    *response.body.lock().unwrap() = body;
+    response
+}
+
+fn store(cache: &mut HttpCache, url: &str, body: &[u8]) {
+    cache.store(
+        &request(url),
+        &response(url, ResponseBody::Done(body.to_vec())),
+    );
+}
+
+fn cached_body(cache: &HttpCache, url: &str) -> Option<Vec<u8>> {
+    let cached_response = cache.construct_response(&request(url), &mut None)?;
+    let body = cached_response.response.body.lock().unwrap();
+    match *body {
+        ResponseBody::Done(ref body) => Some(body.clone()),
+        _ => panic!("The cached body of {} is not complete", url),
+    }
+}
+
+fn file_count(dir: &Path) -> usize {
+    fs::read_dir(dir).unwrap().count()
+}
 
 #[test]
 fn test_refreshing_resource_sets_done_chan_the_appropriate_value() {
-    let response_bodies = vec![ResponseBody::Receiving(vec![]),
-                               ResponseBody::Empty,
-                               ResponseBody::Done(vec![])];
-    let url = ServoUrl::parse("https://servo.org").unwrap();
-    let request = Request::from_init(RequestInit {
-        url: url.clone(),
-        method: Method::Get,
-        destination: Destination::Document,
-        origin: url.clone().origin(),
-        pipeline_id: Some(TEST_PIPELINE_ID),
-        .. RequestInit::default()
-    });
-    let mut response = Response::new(url.clone());
-    // Expires header makes the response cacheable.
-    response.headers.set(Expires(HttpDate(time::now())));
-    response_bodies.iter().for_each(|body| {
+    let response_bodies = vec![
+        ResponseBody::Receiving(vec![]),
+        ResponseBody::Empty,
+        ResponseBody::Done(vec![]),
+    ];
+    let url = "https://servo.org";
+    response_bodies.into_iter().for_each(|body| {
         let mut cache = HttpCache::new();
-        *response.body.lock().unwrap() = body;
+        let is_receiving = match body {
+            ResponseBody::Receiving(_) => true,
+            ResponseBody::Empty | ResponseBody::Done(_) => false,
+        };
+        let mut response = response(url, body);
         // First, store the 'normal' response.
-        cache.store(&request, &response);
+        cache.store(&request(url), &response);
         // Second, mutate the response into a 304 response, and refresh the stored one.
-        response.status = Some(StatusCode::NotModified);
-        let mut done_chan = Some(channel());
-        let refreshed_response = cache.refresh(&request, response, &mut done_chan);
+        response.status = Some((StatusCode::NOT_MODIFIED, String::from("304")));
+        let mut done_chan = Some(unbounded());
+        let refreshed_response = cache.refresh(&request(url), response, &mut done_chan);
         // Ensure a resource was found, and refreshed.
         assert!(refreshed_response.is_some());
-        match body {
-            ResponseBody::Receiving(_) => assert!(done_chan.is_some()),
-            ResponseBody::Empty | ResponseBody::Done(_) => assert!(done_chan.is_none())
-        }
+        assert_eq!(done_chan.is_some(), is_receiving);
     })
 }
+
+#[test]
+fn test_cache_is_reloaded_from_disk() {
+    let dir = new_cache_dir();
+
+    let mut cache = HttpCache::from_disk(&dir);
+    store(&mut cache, "https://servo.org/a", b"first");
+    store(&mut cache, "https://servo.org/b", b"second");
+    // The cache gets written as it changes, not only on exit.
+    drop(cache);
+
+    let cache = HttpCache::from_disk(&dir);
+    assert_eq!(
+        cached_body(&cache, "https://servo.org/a"),
+        Some(b"first".to_vec())
+    );
+    assert_eq!(
+        cached_body(&cache, "https://servo.org/b"),
+        Some(b"second".to_vec())
+    );
+    assert_eq!(cached_body(&cache, "https://servo.org/c"), None);
+    // The index and a file per body.
+    assert_eq!(file_count(&dir), 3);
+
+    let _ = fs::remove_dir_all(dir);
+}
+
+#[test]
+fn test_identical_bodies_are_stored_once() {
+    let dir = new_cache_dir();
+
+    let mut cache = HttpCache::from_disk(&dir);
+    store(&mut cache, "https://servo.org/a", b"body");
+    store(&mut cache, "https://servo.org/b", b"body");
+    assert_eq!(file_count(&dir), 2);
+
+    // Evicting one of the resources keeps the body of the other.
+    cache.set_max_size(4);
+    assert_eq!(cached_body(&cache, "https://servo.org/a"), None);
+    drop(cache);
+    let cache = HttpCache::from_disk(&dir);
+    assert_eq!(
+        cached_body(&cache, "https://servo.org/b"),
+        Some(b"body".to_vec())
+    );
+
+    let _ = fs::remove_dir_all(dir);
+}
+
+#[test]
+fn test_least_recently_used_resources_are_evicted() {
+    let mut cache = HttpCache::new();
+    cache.set_max_size(10);
+    store(&mut cache, "https://servo.org/a", b"aaaaaa");
+    store(&mut cache, "https://servo.org/b", b"bbbb");
+    // Using a makes b the least recently used resource.
+    assert!(cached_body(&cache, "https://servo.org/a").is_some());
+    store(&mut cache, "https://servo.org/c", b"cccc");
+
+    assert!(cached_body(&cache, "https://servo.org/a").is_some());
+    assert_eq!(cached_body(&cache, "https://servo.org/b"), None);
+    assert!(cached_body(&cache, "https://servo.org/c").is_some());
+}
+
+#[test]
+fn test_evicted_bodies_are_removed_from_disk() {
+    let dir = new_cache_dir();
+
+    let mut cache = HttpCache::from_disk(&dir);
+    cache.set_max_size(6);
+    store(&mut cache, "https://servo.org/a", b"aaaa");
+    store(&mut cache, "https://servo.org/b", b"bbbb");
+    assert_eq!(file_count(&dir), 2);
+    drop(cache);
+
+    let cache = HttpCache::from_disk(&dir);
+    assert_eq!(cached_body(&cache, "https://servo.org/a"), None);
+    assert_eq!(
+        cached_body(&cache, "https://servo.org/b"),
+        Some(b"bbbb".to_vec())
+    );
+
+    let _ = fs::remove_dir_all(dir);
+}
+
+#[test]
+fn test_bodies_are_counted_once_received() {
+    let mut cache = HttpCache::new();
+    cache.set_max_size(6);
+    store(&mut cache, "https://servo.org/b", b"bbbb");
+    let url = "https://servo.org/a";
+    let request = request(url);
+    let receiving = response(url, ResponseBody::Receiving(vec![]));
+    cache.store(&request, &receiving);
+
+    // Completing the body of a makes the cache too big, evicting b.
+    *receiving.body.lock().unwrap() = ResponseBody::Done(b"aaaa".to_vec());
+    cache.update_awaiting_consumers(&request, &receiving);
+    assert_eq!(cached_body(&cache, "https://servo.org/b"), None);
+    assert_eq!(cached_body(&cache, url), Some(b"aaaa".to_vec()));
+}
@@ -62,17 +62,29 @@ fn assert_cookie_for_domain(
     assert_eq!(cookies.as_ref().map(|c| &**c), cookie);
 }
 
+/// Receives the next network event sent to devtools, skipping the bodies of
+/// responses, which are sent whenever they were entirely received.
+fn recv_devtools_network_event(devtools_port: &Receiver<DevtoolsControlMsg>) -> NetworkEvent {
+    loop {
+        match devtools_port.recv().unwrap() {
+            DevtoolsControlMsg::FromChrome(ChromeToDevtoolsControlMsg::NetworkEvent(
+                _,
+                NetworkEvent::HttpResponseContent(_),
+            )) => {},
+            DevtoolsControlMsg::FromChrome(ChromeToDevtoolsControlMsg::NetworkEvent(
+                _,
+                net_event,
+            )) => return net_event,
+            _ => panic!("No NetworkEvent Received"),
+        }
+    }
+}
+
 pub fn expect_devtools_http_request(
     devtools_port: &Receiver<DevtoolsControlMsg>,
 ) -> DevtoolsHttpRequest {
-    match devtools_port.recv().unwrap() {
-        DevtoolsControlMsg::FromChrome(ChromeToDevtoolsControlMsg::NetworkEvent(_, net_event)) => {
-            match net_event {
-                NetworkEvent::HttpRequest(httprequest) => httprequest,
-
-                _ => panic!("No HttpRequest Received"),
-            }
-        },
+    match recv_devtools_network_event(devtools_port) {
+        NetworkEvent::HttpRequest(httprequest) => httprequest,
         _ => panic!("No HttpRequest Received"),
     }
 }
@@ -80,15 +92,8 @@ pub fn expect_devtools_http_request(
 pub fn expect_devtools_http_response(
     devtools_port: &Receiver<DevtoolsControlMsg>,
 ) -> DevtoolsHttpResponse {
-    match devtools_port.recv().unwrap() {
-        DevtoolsControlMsg::FromChrome(ChromeToDevtoolsControlMsg::NetworkEvent(
-            _,
-            net_event_response,
-        )) => match net_event_response {
-            NetworkEvent::HttpResponse(httpresponse) => httpresponse,
-
-            _ => panic!("No HttpResponse Received"),
-        },
+    match recv_devtools_network_event(devtools_port) {
+        NetworkEvent::HttpResponse(httpresponse) => httpresponse,
         _ => panic!("No HttpResponse Received"),
     }
 }
@@ -318,6 +323,38 @@ fn test_request_and_response_data_with_network_messages() {
     assert_eq!(devhttpresponse, httpresponse);
 }
 
+#[test]
+fn test_response_content_with_network_messages() {
+    let handler = move |_: HyperRequest<Body>, response: &mut HyperResponse<Body>| {
+        *response.body_mut() = b"Yay!".to_vec().into();
+    };
+    let (server, url) = make_server(handler);
+
+    let mut request = Request::from_init(RequestInit {
+        url: url.clone(),
+        method: Method::GET,
+        destination: Destination::Document,
+        origin: mock_origin(),
+        pipeline_id: Some(TEST_PIPELINE_ID),
+        ..RequestInit::default()
+    });
+    let (devtools_chan, devtools_port) = unbounded();
+    let _ = fetch(&mut request, Some(devtools_chan));
+    let _ = server.close();
+
+    let content = loop {
+        if let DevtoolsControlMsg::FromChrome(ChromeToDevtoolsControlMsg::NetworkEvent(
+            _,
+            NetworkEvent::HttpResponseContent(content),
+        )) = devtools_port.recv().unwrap()
+        {
+            break content;
+        }
+    };
+    assert_eq!(content.body, b"Yay!".to_vec());
+    assert_eq!(content.pipeline_id, TEST_PIPELINE_ID);
+}
+
 #[test]
 fn test_request_and_response_message_from_devtool_without_pipeline_id() {
     let handler = move |_: HyperRequest<Body>, response: &mut HyperResponse<Body>| {
@@ -0,0 +1,219 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use ipc_channel::ipc::{self, IpcSender};
+use net::test::IndexedDBThreadFactory;
+use net_traits::indexeddb_thread::{AsyncOperation, BackendError, IndexedDBKeyType, OpenResult};
+use net_traits::indexeddb_thread::{IndexedDBResult, IndexedDBThreadMsg, IndexedDBTxnMode};
+use servo_url::{ImmutableOrigin, ServoUrl};
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use uuid::Uuid;
+
+const DB_NAME: &'static str = "db";
+const STORE_NAME: &'static str = "store";
+
+fn origin() -> ImmutableOrigin {
+    ServoUrl::parse("http://servo.org").unwrap().origin()
+}
+
+fn key(name: &str) -> IndexedDBKeyType {
+    IndexedDBKeyType::String(name.to_owned())
+}
+
+fn new_config_dir() -> PathBuf {
+    env::temp_dir().join(format!("servo-indexeddb-{}", Uuid::new_v4()))
+}
+
+fn exit(thread: IpcSender<IndexedDBThreadMsg>) {
+    let (sender, receiver) = ipc::channel().unwrap();
+    thread.send(IndexedDBThreadMsg::Exit(sender)).unwrap();
+    receiver.recv().unwrap();
+}
+
+fn open(thread: &IpcSender<IndexedDBThreadMsg>, version: Option<u64>) -> OpenResult {
+    let (sender, receiver) = ipc::channel().unwrap();
+    thread
+        .send(IndexedDBThreadMsg::Open(
+            sender,
+            origin(),
+            DB_NAME.to_owned(),
+            version,
+        ))
+        .unwrap();
+    receiver.recv().unwrap()
+}
+
+fn create_transaction(thread: &IpcSender<IndexedDBThreadMsg>, mode: IndexedDBTxnMode) -> u64 {
+    let (sender, receiver) = ipc::channel().unwrap();
+    thread
+        .send(IndexedDBThreadMsg::CreateTransaction(
+            sender,
+            origin(),
+            DB_NAME.to_owned(),
+            mode,
+            vec![STORE_NAME.to_owned()],
+        ))
+        .unwrap();
+    receiver.recv().unwrap()
+}
+
+fn request(
+    thread: &IpcSender<IndexedDBThreadMsg>,
+    txn: u64,
+    operation: AsyncOperation,
+) -> Result<IndexedDBResult, BackendError> {
+    let (sender, receiver) = ipc::channel().unwrap();
+    thread
+        .send(IndexedDBThreadMsg::Request {
+            sender: sender,
+            origin: origin(),
+            db_name: DB_NAME.to_owned(),
+            txn: txn,
+            store_name: STORE_NAME.to_owned(),
+            operation: operation,
+        })
+        .unwrap();
+    receiver.recv().unwrap()
+}
+
+fn put(thread: &IpcSender<IndexedDBThreadMsg>, txn: u64, name: &str, value: &[u8]) {
+    let result = request(
+        thread,
+        txn,
+        AsyncOperation::Put {
+            key: Some(key(name)),
+            value: value.to_vec(),
+            overwrite: false,
+        },
+    );
+    assert!(result.is_ok());
+}
+
+fn get(thread: &IpcSender<IndexedDBThreadMsg>, txn: u64, name: &str) -> Option<Vec<u8>> {
+    match request(thread, txn, AsyncOperation::Get(key(name))) {
+        Ok(IndexedDBResult::Record(record)) => record.map(|(record_key, value)| {
+            assert_eq!(record_key, key(name));
+            value
+        }),
+        _ => panic!("Getting {} failed", name),
+    }
+}
+
+fn commit(thread: &IpcSender<IndexedDBThreadMsg>, txn: u64) {
+    let (sender, receiver) = ipc::channel().unwrap();
+    thread
+        .send(IndexedDBThreadMsg::Commit(
+            sender,
+            origin(),
+            DB_NAME.to_owned(),
+            txn,
+        ))
+        .unwrap();
+    assert_eq!(receiver.recv().unwrap(), Ok(()));
+}
+
+fn abort(thread: &IpcSender<IndexedDBThreadMsg>, txn: u64) {
+    let (sender, receiver) = ipc::channel().unwrap();
+    thread
+        .send(IndexedDBThreadMsg::Abort(
+            sender,
+            origin(),
+            DB_NAME.to_owned(),
+            txn,
+        ))
+        .unwrap();
+    receiver.recv().unwrap();
+}
+
+/// Creates the database with an object store holding one record.
+fn create_database(thread: &IpcSender<IndexedDBThreadMsg>) {
+    let txn = match open(thread, Some(1)) {
+        OpenResult::Upgrade {
+            transaction,
+            old_version,
+            ..
+        } => {
+            assert_eq!(old_version, 0);
+            transaction
+        },
+        _ => panic!("The new database did not get upgraded"),
+    };
+    thread
+        .send(IndexedDBThreadMsg::CreateObjectStore {
+            origin: origin(),
+            db_name: DB_NAME.to_owned(),
+            txn: txn,
+            store_name: STORE_NAME.to_owned(),
+            key_path: None,
+            auto_increment: false,
+        })
+        .unwrap();
+    put(thread, txn, "committed", b"value");
+    commit(thread, txn);
+}
+
+#[test]
+fn test_databases_are_reloaded_after_a_restart() {
+    let config_dir = new_config_dir();
+
+    let thread: IpcSender<IndexedDBThreadMsg> =
+        IndexedDBThreadFactory::new(Some(config_dir.clone()));
+    create_database(&thread);
+    exit(thread);
+
+    let thread: IpcSender<IndexedDBThreadMsg> =
+        IndexedDBThreadFactory::new(Some(config_dir.clone()));
+    match open(&thread, None) {
+        OpenResult::Success(metadata) => {
+            assert_eq!(metadata.version, 1);
+            assert_eq!(metadata.object_stores.len(), 1);
+            assert_eq!(metadata.object_stores[0].name, STORE_NAME);
+        },
+        _ => panic!("The saved database did not get opened"),
+    }
+    let txn = create_transaction(&thread, IndexedDBTxnMode::Readonly);
+    assert_eq!(get(&thread, txn, "committed"), Some(b"value".to_vec()));
+    commit(&thread, txn);
+    exit(thread);
+
+    // The temporary file got renamed over the saved one.
+    let file_names: Vec<_> = fs::read_dir(config_dir.join("indexeddb"))
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name())
+        .collect();
+    assert_eq!(file_names.len(), 1);
+    assert!(file_names[0].to_str().unwrap().ends_with(".json"));
+
+    let _ = fs::remove_dir_all(config_dir);
+}
+
+#[test]
+fn test_aborted_transaction_is_not_saved() {
+    let config_dir = new_config_dir();
+
+    let thread: IpcSender<IndexedDBThreadMsg> =
+        IndexedDBThreadFactory::new(Some(config_dir.clone()));
+    create_database(&thread);
+    let txn = create_transaction(&thread, IndexedDBTxnMode::Readwrite);
+    put(&thread, txn, "aborted", b"value");
+    assert_eq!(get(&thread, txn, "aborted"), Some(b"value".to_vec()));
+    abort(&thread, txn);
+    exit(thread);
+
+    let thread: IpcSender<IndexedDBThreadMsg> =
+        IndexedDBThreadFactory::new(Some(config_dir.clone()));
+    assert!(match open(&thread, None) {
+        OpenResult::Success(_) => true,
+        _ => false,
+    });
+    let txn = create_transaction(&thread, IndexedDBTxnMode::Readonly);
+    assert_eq!(get(&thread, txn, "committed"), Some(b"value".to_vec()));
+    assert_eq!(get(&thread, txn, "aborted"), None);
+    commit(&thread, txn);
+    exit(thread);
+
+    let _ = fs::remove_dir_all(config_dir);
+}
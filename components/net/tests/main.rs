@@ -14,7 +14,9 @@ mod fetch;
 mod file_loader;
 mod filemanager_thread;
 mod hsts;
+mod http_cache;
 mod http_loader;
+mod indexeddb_thread;
 mod mime_classifier;
 mod resource_thread;
 mod subresource_integrity;
@@ -36,10 +38,11 @@ use net::test::HttpState;
 use net_traits::request::Request;
 use net_traits::response::Response;
 use net_traits::{FetchTaskTarget, ResourceFetchTiming, ResourceTimingType};
-use openssl::ssl::{SslAcceptor, SslFiletype, SslMethod};
+use openssl::ssl::{self, AlpnError, SslAcceptor, SslFiletype, SslMethod};
 use servo_url::ServoUrl;
 use std::net::TcpListener as StdTcpListener;
 use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::{Arc, Mutex};
 use tokio::net::TcpListener;
 use tokio::runtime::Runtime;
@@ -92,6 +95,7 @@ fn new_fetch_context(
         user_agent: DEFAULT_USER_AGENT.into(),
         devtools_chan: dc,
         filemanager: FileManager::new(sender),
+        swmanager_chan: None,
         cancellation_listener: Arc::new(Mutex::new(CancellationListener::new(None))),
         timing: Arc::new(Mutex::new(ResourceFetchTiming::new(
             ResourceTimingType::Navigation,
@@ -222,3 +226,73 @@ where
     let server = Server { close_channel: tx };
     (server, url)
 }
+
+/// Like `make_ssl_server`, but the server only speaks HTTP/2, selecting `h2`
+/// through ALPN. The counter tracks how many connections it accepted.
+fn make_h2_ssl_server<H>(
+    handler: H,
+    cert_path: PathBuf,
+    key_path: PathBuf,
+) -> (Server, ServoUrl, Arc<AtomicUsize>)
+where
+    H: Fn(HyperRequest<Body>, &mut HyperResponse<Body>) + Send + Sync + 'static,
+{
+    let handler = Arc::new(handler);
+    let listener = StdTcpListener::bind("[::0]:0").unwrap();
+    let listener = TcpListener::from_std(listener, &HANDLE.lock().unwrap().reactor()).unwrap();
+    let url_string = format!(
+        "https://localhost:{}",
+        listener.local_addr().unwrap().port()
+    );
+    let url = ServoUrl::parse(&url_string).unwrap();
+    let connections = Arc::new(AtomicUsize::new(0));
+    let connections_clone = connections.clone();
+
+    let server = listener.incoming().map_err(|_| ()).for_each(move |sock| {
+        let mut ssl_builder = SslAcceptor::mozilla_modern(SslMethod::tls()).unwrap();
+        ssl_builder
+            .set_certificate_file(&cert_path, SslFiletype::PEM)
+            .unwrap();
+        ssl_builder
+            .set_private_key_file(&key_path, SslFiletype::PEM)
+            .unwrap();
+        ssl_builder.set_alpn_select_callback(|_, client_protocols| {
+            ssl::select_next_proto(b"\x02h2", client_protocols).ok_or(AlpnError::NOACK)
+        });
+
+        connections_clone.fetch_add(1, Ordering::SeqCst);
+        let handler = handler.clone();
+        // The connection stays open for the client to reuse, so serve it
+        // separately instead of holding up the next accepted socket.
+        let connection = ssl_builder
+            .build()
+            .accept_async(sock)
+            .map_err(|_| ())
+            .and_then(move |ssl| {
+                Http::new()
+                    .http2_only(true)
+                    .serve_connection(
+                        ssl,
+                        service_fn_ok(move |req: HyperRequest<Body>| {
+                            let mut response = HyperResponse::new(Vec::<u8>::new().into());
+                            handler(req, &mut response);
+                            response
+                        }),
+                    )
+                    .map_err(|_| ())
+            });
+        tokio::spawn(connection);
+        Ok(())
+    });
+
+    let (tx, rx) = futures::sync::oneshot::channel::<()>();
+    let server = server
+        .select(rx.map_err(|_| ()))
+        .map(|_| ())
+        .map_err(|_| ());
+
+    HANDLE.lock().unwrap().spawn(server);
+
+    let server = Server { close_channel: tx };
+    (server, url, connections)
+}
@@ -8,6 +8,7 @@ use crate::fetch::methods::should_be_blocked_due_to_bad_port;
 use crate::hosts::replace_host;
 use crate::http_loader::HttpState;
 use embedder_traits::resources::{self, Resource};
+use flate2::{Compress, Compression, Decompress, FlushCompress, FlushDecompress};
 use headers_ext::Host;
 use http::header::{self, HeaderMap, HeaderName, HeaderValue};
 use http::uri::Authority;
@@ -18,18 +19,33 @@ use net_traits::{WebSocketDomAction, WebSocketNetworkEvent};
 use openssl::ssl::SslStream;
 use servo_config::opts;
 use servo_url::ServoUrl;
+use std::cell::RefCell;
+use std::collections::VecDeque;
 use std::fs;
+use std::rc::Rc;
 use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 use std::thread;
 use url::Url;
 use ws::util::TcpStream;
 use ws::{
-    CloseCode, Factory, Handler, Handshake, Message, Request, Response as WsResponse, Sender,
-    WebSocket,
+    CloseCode, Factory, Frame, Handler, Handshake, Message, OpCode, Request,
+    Response as WsResponse, Sender, WebSocket,
 };
 use ws::{Error as WebSocketError, ErrorKind as WebSocketErrorKind, Result as WebSocketResult};
 
+/// The number of bytes of messages handed to the connection at once, past
+/// which further messages wait in the send queue until they are framed.
+const MAX_BYTES_IN_FLIGHT: usize = 1 << 20;
+/// The number of messages handed to the connection at once, well below the
+/// capacity of the ws command queue.
+const MAX_MESSAGES_IN_FLIGHT: usize = 16;
+
+/// The name of the only extension offered to servers.
+const PERMESSAGE_DEFLATE: &str = "permessage-deflate";
+/// The empty stored block ending each compressed message.
+const DEFLATE_TRAILER: [u8; 4] = [0x00, 0x00, 0xff, 0xff];
+
 /// A client for connecting to a websocket server
 #[derive(Clone)]
 struct Client<'a> {
@@ -40,13 +56,21 @@ struct Client<'a> {
     resource_url: &'a ServoUrl,
     event_sender: &'a IpcSender<WebSocketNetworkEvent>,
     protocol_in_use: Option<String>,
+    extensions_in_use: Option<String>,
+    /// The compression state, if the server accepted permessage-deflate.
+    deflate: Option<Rc<RefCell<PerMessageDeflate>>>,
+    send_queue: Arc<Mutex<SendQueue>>,
+    sender: Option<Sender>,
 }
 
 impl<'a> Factory for Client<'a> {
     type Handler = Self;
 
-    fn connection_made(&mut self, _: Sender) -> Self::Handler {
-        self.clone()
+    fn connection_made(&mut self, sender: Sender) -> Self::Handler {
+        Client {
+            sender: Some(sender),
+            ..self.clone()
+        }
     }
 
     fn connection_lost(&mut self, _: Self::Handler) {
@@ -68,6 +92,8 @@ impl<'a> Handler for Client<'a> {
             req.add_protocol(protocol);
         }
 
+        req.add_extension(PERMESSAGE_DEFLATE);
+
         let mut cookie_jar = self.http_state.cookie_jar.write().unwrap();
         if let Some(cookie_list) = cookie_jar.cookies_for_url(self.resource_url, CookieSource::HTTP)
         {
@@ -103,6 +129,7 @@ impl<'a> Handler for Client<'a> {
             .event_sender
             .send(WebSocketNetworkEvent::ConnectionEstablished {
                 protocol_in_use: self.protocol_in_use.clone(),
+                extensions_in_use: self.extensions_in_use.clone(),
             });
         Ok(())
     }
@@ -137,9 +164,63 @@ impl<'a> Handler for Client<'a> {
             }
             self.protocol_in_use = Some(protocol_name.into());
         }
+
+        // https://tools.ietf.org/html/rfc6455#section-4.1
+        for extension in res.extensions()?.into_iter().filter(|e| !e.is_empty()) {
+            let mut parameters = extension.split(';').map(str::trim);
+            let name = parameters.next().unwrap_or("");
+            if name != PERMESSAGE_DEFLATE || self.deflate.is_some() {
+                return Err(WebSocketError::new(
+                    WebSocketErrorKind::Protocol,
+                    "Extension in use not in client-supplied extension list",
+                ));
+            }
+            let deflate = PerMessageDeflate::new(parameters)?;
+            self.deflate = Some(Rc::new(RefCell::new(deflate)));
+            self.extensions_in_use = Some(extension.into());
+        }
         Ok(())
     }
 
+    fn on_frame(&mut self, mut frame: Frame) -> WebSocketResult<Option<Frame>> {
+        if frame.has_rsv2() || frame.has_rsv3() || (frame.has_rsv1() && self.deflate.is_none()) {
+            return Err(WebSocketError::new(
+                WebSocketErrorKind::Protocol,
+                "Encountered frame with reserved bits set.",
+            ));
+        }
+        if let Some(ref deflate) = self.deflate {
+            deflate.borrow_mut().decompress_frame(&mut frame)?;
+        }
+        Ok(Some(frame))
+    }
+
+    fn on_send_frame(&mut self, mut frame: Frame) -> WebSocketResult<Option<Frame>> {
+        if frame.is_control() {
+            return Ok(Some(frame));
+        }
+
+        let mut queue = self.send_queue.lock().unwrap();
+        let length = frame.payload().len();
+        queue.bytes_in_flight = queue.bytes_in_flight.saturating_sub(length);
+        queue.bytes_framed += length;
+        if frame.is_final() {
+            queue.messages_in_flight = queue.messages_in_flight.saturating_sub(1);
+            let _ = self.event_sender.send(WebSocketNetworkEvent::MessageSent(
+                queue.bytes_framed as u64,
+            ));
+            queue.bytes_framed = 0;
+        }
+        if let Some(ref sender) = self.sender {
+            queue.feed(sender)?;
+        }
+
+        if let Some(ref deflate) = self.deflate {
+            deflate.borrow_mut().compress_frame(&mut frame)?;
+        }
+        Ok(Some(frame))
+    }
+
     fn on_close(&mut self, code: CloseCode, reason: &str) {
         debug!("Connection closing due to ({:?}) {}", code, reason);
         let _ = self.event_sender.send(WebSocketNetworkEvent::Close(
@@ -173,6 +254,214 @@ impl<'a> Handler for Client<'a> {
     }
 }
 
+/// The messages sent by the DOM which were not framed yet.
+///
+/// Messages are handed to the connection a few at a time, so that the ws
+/// command queue never fills up, and so that `bufferedAmount` only goes
+/// down as the messages actually get framed.
+#[derive(Default)]
+struct SendQueue {
+    /// The messages not handed to the connection yet.
+    pending: VecDeque<Message>,
+    /// The close frame to send once all pending messages are handed over.
+    close: Option<(CloseCode, String)>,
+    /// The bytes of the messages handed to the connection but not framed yet.
+    bytes_in_flight: usize,
+    /// The number of messages handed to the connection but not framed yet.
+    messages_in_flight: usize,
+    /// The bytes framed so far of the message being framed.
+    bytes_framed: usize,
+}
+
+impl SendQueue {
+    /// Hands pending messages to the connection until too many are in flight.
+    fn feed(&mut self, sender: &Sender) -> WebSocketResult<()> {
+        while self.messages_in_flight < MAX_MESSAGES_IN_FLIGHT &&
+            (self.messages_in_flight == 0 || self.bytes_in_flight < MAX_BYTES_IN_FLIGHT)
+        {
+            let message = match self.pending.pop_front() {
+                Some(message) => message,
+                None => break,
+            };
+            self.bytes_in_flight += message.len();
+            self.messages_in_flight += 1;
+            sender.send(message)?;
+        }
+        if self.pending.is_empty() {
+            if let Some((code, reason)) = self.close.take() {
+                sender.close_with_reason(code, reason)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The state of the permessage-deflate extension.
+///
+/// Only a 15 bits LZ77 window is supported for compression, so the client
+/// never offers `client_max_window_bits`, while any window the server
+/// compresses with can be inflated.
+///
+/// <https://tools.ietf.org/html/rfc7692>
+struct PerMessageDeflate {
+    compressor: Compress,
+    decompressor: Decompress,
+    /// Whether the message being received is compressed.
+    receiving_compressed: bool,
+    /// <https://tools.ietf.org/html/rfc7692#section-7.1.1.1>
+    server_no_context_takeover: bool,
+    /// <https://tools.ietf.org/html/rfc7692#section-7.1.1.2>
+    client_no_context_takeover: bool,
+}
+
+impl PerMessageDeflate {
+    /// Creates the compression state from the extension parameters the
+    /// server responded with, failing if they are not a valid response to
+    /// the offer.
+    ///
+    /// <https://tools.ietf.org/html/rfc7692#section-5.1>
+    fn new<'a, I>(parameters: I) -> WebSocketResult<PerMessageDeflate>
+    where
+        I: Iterator<Item = &'a str>,
+    {
+        let mut server_no_context_takeover = false;
+        let mut client_no_context_takeover = false;
+        let mut server_max_window_bits = false;
+        for parameter in parameters {
+            let mut parts = parameter.splitn(2, '=');
+            let name = parts.next().unwrap_or("").trim();
+            let value = parts.next().map(|value| value.trim().trim_matches('"'));
+            match (name, value) {
+                ("server_no_context_takeover", None) if !server_no_context_takeover => {
+                    server_no_context_takeover = true;
+                },
+                ("client_no_context_takeover", None) if !client_no_context_takeover => {
+                    client_no_context_takeover = true;
+                },
+                // https://tools.ietf.org/html/rfc7692#section-7.1.2.1
+                ("server_max_window_bits", Some(bits))
+                    if !server_max_window_bits && !bits.starts_with('0') =>
+                {
+                    match bits.parse::<u8>() {
+                        Ok(8..=15) => server_max_window_bits = true,
+                        _ => return Err(invalid_deflate_parameter(parameter)),
+                    }
+                },
+                _ => return Err(invalid_deflate_parameter(parameter)),
+            }
+        }
+        Ok(PerMessageDeflate {
+            compressor: Compress::new(Compression::default(), false),
+            decompressor: Decompress::new(false),
+            receiving_compressed: false,
+            server_no_context_takeover,
+            client_no_context_takeover,
+        })
+    }
+
+    /// <https://tools.ietf.org/html/rfc7692#section-7.2.2>
+    fn decompress_frame(&mut self, frame: &mut Frame) -> WebSocketResult<()> {
+        if frame.is_control() || frame.opcode() == OpCode::Continue {
+            if frame.has_rsv1() {
+                return Err(WebSocketError::new(
+                    WebSocketErrorKind::Protocol,
+                    "Encountered compressed control or continuation frame.",
+                ));
+            }
+            if frame.is_control() {
+                return Ok(());
+            }
+        } else {
+            self.receiving_compressed = frame.has_rsv1();
+        }
+        if !self.receiving_compressed {
+            return Ok(());
+        }
+
+        frame.set_rsv1(false);
+        if frame.is_final() {
+            frame.payload_mut().extend_from_slice(&DEFLATE_TRAILER);
+        }
+        let payload = inflate(&mut self.decompressor, frame.payload())?;
+        *frame.payload_mut() = payload;
+        if frame.is_final() && self.server_no_context_takeover {
+            self.decompressor.reset(false);
+        }
+        Ok(())
+    }
+
+    /// <https://tools.ietf.org/html/rfc7692#section-7.2.1>
+    fn compress_frame(&mut self, frame: &mut Frame) -> WebSocketResult<()> {
+        if frame.opcode() != OpCode::Continue {
+            frame.set_rsv1(true);
+        }
+        let mut payload = deflate(&mut self.compressor, frame.payload())?;
+        if frame.is_final() {
+            if payload.ends_with(&DEFLATE_TRAILER) {
+                let length = payload.len() - DEFLATE_TRAILER.len();
+                payload.truncate(length);
+            }
+            if self.client_no_context_takeover {
+                self.compressor.reset();
+            }
+        }
+        *frame.payload_mut() = payload;
+        Ok(())
+    }
+}
+
+fn invalid_deflate_parameter(parameter: &str) -> WebSocketError {
+    WebSocketError::new(
+        WebSocketErrorKind::Protocol,
+        format!("Invalid permessage-deflate parameter {}", parameter),
+    )
+}
+
+/// Compresses `input` and flushes it to a byte boundary.
+fn deflate(compressor: &mut Compress, input: &[u8]) -> WebSocketResult<Vec<u8>> {
+    let mut output = Vec::with_capacity(input.len() + 64);
+    let start = compressor.total_in();
+    loop {
+        let consumed = (compressor.total_in() - start) as usize;
+        compressor
+            .compress_vec(&input[consumed..], &mut output, FlushCompress::Sync)
+            .map_err(|_| {
+                WebSocketError::new(WebSocketErrorKind::Internal, "Failed to compress message")
+            })?;
+        let consumed = (compressor.total_in() - start) as usize;
+        if consumed == input.len() && output.len() < output.capacity() {
+            return Ok(output);
+        }
+        let length = output.len();
+        output.reserve(length.max(64));
+    }
+}
+
+/// Inflates `input`, which was flushed to a byte boundary.
+fn inflate(decompressor: &mut Decompress, input: &[u8]) -> WebSocketResult<Vec<u8>> {
+    let error = || WebSocketError::new(WebSocketErrorKind::Protocol, "Failed to inflate message");
+    let mut output = Vec::with_capacity(input.len() * 2 + 64);
+    let start = decompressor.total_in();
+    loop {
+        let (total_in, total_out) = (decompressor.total_in(), decompressor.total_out());
+        let consumed = (total_in - start) as usize;
+        decompressor
+            .decompress_vec(&input[consumed..], &mut output, FlushDecompress::Sync)
+            .map_err(|_| error())?;
+        let consumed = (decompressor.total_in() - start) as usize;
+        if output.len() < output.capacity() {
+            if consumed == input.len() {
+                return Ok(output);
+            }
+            if decompressor.total_in() == total_in && decompressor.total_out() == total_out {
+                return Err(error());
+            }
+        }
+        let length = output.len();
+        output.reserve(length.max(64));
+    }
+}
+
 pub fn init(
     req_init: RequestInit,
     resource_event_sender: IpcSender<WebSocketNetworkEvent>,
@@ -219,6 +508,7 @@ pub fn init(
                 .unwrap(),
             );
 
+            let send_queue = Arc::new(Mutex::new(SendQueue::default()));
             let client = Client {
                 origin: &req_init.origin.ascii_serialization(),
                 host: &host,
@@ -227,6 +517,10 @@ pub fn init(
                 resource_url: &req_init.url,
                 event_sender: &resource_event_sender,
                 protocol_in_use: None,
+                extensions_in_use: None,
+                deflate: None,
+                send_queue: send_queue.clone(),
+                sender: None,
             };
             let mut ws = WebSocket::new(client).unwrap();
 
@@ -240,27 +534,23 @@ pub fn init(
 
             thread::spawn(move || {
                 while let Ok(dom_action) = dom_action_receiver.recv() {
+                    let mut queue = send_queue.lock().unwrap();
                     match dom_action {
                         WebSocketDomAction::SendMessage(MessageData::Text(data)) => {
-                            ws_sender.send(Message::text(data)).unwrap();
+                            queue.pending.push_back(Message::text(data));
                         },
                         WebSocketDomAction::SendMessage(MessageData::Binary(data)) => {
-                            ws_sender.send(Message::binary(data)).unwrap();
+                            queue.pending.push_back(Message::binary(data));
                         },
                         WebSocketDomAction::Close(code, reason) => {
-                            if !initiated_close.fetch_or(true, Ordering::SeqCst) {
-                                match code {
-                                    Some(code) => ws_sender
-                                        .close_with_reason(
-                                            code.into(),
-                                            reason.unwrap_or("".to_owned()),
-                                        )
-                                        .unwrap(),
-                                    None => ws_sender.close(CloseCode::Status).unwrap(),
-                                };
+                            if initiated_close.fetch_or(true, Ordering::SeqCst) {
+                                continue;
                             }
+                            let code = code.map_or(CloseCode::Status, CloseCode::from);
+                            queue.close = Some((code, reason.unwrap_or_default()));
                         },
                     }
+                    queue.feed(&ws_sender).unwrap();
                 }
             });
 
@@ -0,0 +1,91 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::response::ResponseType;
+use http::{HeaderMap, Method};
+use ipc_channel::ipc::IpcSender;
+use servo_url::{ImmutableOrigin, ServoUrl};
+
+/// The parts of a request stored in a cache.
+///
+/// <https://w3c.github.io/ServiceWorker/#request-response-list>
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct CachedRequest {
+    pub url: ServoUrl,
+    #[serde(
+        deserialize_with = "::hyper_serde::deserialize",
+        serialize_with = "::hyper_serde::serialize"
+    )]
+    pub method: Method,
+    #[serde(
+        deserialize_with = "::hyper_serde::deserialize",
+        serialize_with = "::hyper_serde::serialize"
+    )]
+    pub headers: HeaderMap,
+}
+
+/// The parts of a response stored in a cache, along with its whole body.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct CachedResponse {
+    pub url: Option<ServoUrl>,
+    pub response_type: ResponseType,
+    pub status: Option<(u16, Vec<u8>)>,
+    #[serde(
+        deserialize_with = "::hyper_serde::deserialize",
+        serialize_with = "::hyper_serde::serialize"
+    )]
+    pub headers: HeaderMap,
+    pub body: Vec<u8>,
+}
+
+/// <https://w3c.github.io/ServiceWorker/#dictdef-cachequeryoptions>
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize)]
+pub struct CacheQueryOptions {
+    pub ignore_search: bool,
+    pub ignore_method: bool,
+    pub ignore_vary: bool,
+}
+
+/// Operations on the caches of an origin.
+///
+/// <https://w3c.github.io/ServiceWorker/#cachestorage>
+#[derive(Debug, Deserialize, Serialize)]
+pub enum CacheStorageMsg {
+    /// Whether the origin has a cache with the given name
+    Has(IpcSender<bool>, ImmutableOrigin, String),
+    /// Creates the cache with the given name if the origin does not have one
+    Open(IpcSender<()>, ImmutableOrigin, String),
+    /// Deletes the cache with the given name, replying whether it existed
+    Delete(IpcSender<bool>, ImmutableOrigin, String),
+    /// Gets the names of the caches of the origin, in creation order
+    Keys(IpcSender<Vec<String>>, ImmutableOrigin),
+    /// Gets the entries of the cache with the given name, or of all the caches
+    /// of the origin if it is none, matching the request, or all of them if
+    /// the request is none
+    Match(
+        IpcSender<Vec<(CachedRequest, CachedResponse)>>,
+        ImmutableOrigin,
+        Option<String>,
+        Option<CachedRequest>,
+        CacheQueryOptions,
+    ),
+    /// Stores an entry in the cache with the given name, replacing the
+    /// entries matching its request
+    Put(
+        IpcSender<()>,
+        ImmutableOrigin,
+        String,
+        CachedRequest,
+        CachedResponse,
+    ),
+    /// Removes the entries of the cache with the given name matching the
+    /// request, replying whether there were any
+    DeleteEntries(
+        IpcSender<bool>,
+        ImmutableOrigin,
+        String,
+        CachedRequest,
+        CacheQueryOptions,
+    ),
+}
@@ -123,4 +123,8 @@ pub trait ImageCache: Sync + Send {
 
     /// Ensure an image has a webrender key.
     fn set_webrender_image_key(&self, image: &mut Image);
+
+    /// Decode the given bytes on another thread, sending the decoded image
+    /// back, or None if they aren't an image in a supported format.
+    fn decode_bytes(&self, bytes: Vec<u8>, sender: IpcSender<Option<Image>>);
 }
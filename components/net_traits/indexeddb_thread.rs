@@ -0,0 +1,268 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use ipc_channel::ipc::IpcSender;
+use servo_url::ImmutableOrigin;
+use std::cmp::Ordering;
+
+/// https://w3c.github.io/IndexedDB/#key-construct
+#[derive(Clone, Debug, Deserialize, MallocSizeOf, Serialize)]
+pub enum IndexedDBKeyType {
+    Number(f64),
+    /// The time value of a date, in milliseconds since the epoch
+    Date(f64),
+    String(String),
+    Binary(Vec<u8>),
+    Array(Vec<IndexedDBKeyType>),
+}
+
+impl IndexedDBKeyType {
+    /// The rank of the type of this key, keys of a higher ranked type being
+    /// greater than all keys of lower ranked types
+    fn type_rank(&self) -> u8 {
+        match *self {
+            IndexedDBKeyType::Number(_) => 0,
+            IndexedDBKeyType::Date(_) => 1,
+            IndexedDBKeyType::String(_) => 2,
+            IndexedDBKeyType::Binary(_) => 3,
+            IndexedDBKeyType::Array(_) => 4,
+        }
+    }
+}
+
+/// https://w3c.github.io/IndexedDB/#compare-two-keys
+impl Ord for IndexedDBKeyType {
+    fn cmp(&self, other: &IndexedDBKeyType) -> Ordering {
+        match (self, other) {
+            (&IndexedDBKeyType::Number(a), &IndexedDBKeyType::Number(b)) |
+            (&IndexedDBKeyType::Date(a), &IndexedDBKeyType::Date(b)) => {
+                // Keys are never NaN.
+                a.partial_cmp(&b).unwrap_or(Ordering::Equal)
+            },
+            (&IndexedDBKeyType::String(ref a), &IndexedDBKeyType::String(ref b)) => {
+                // Strings are compared by code units.
+                a.encode_utf16().cmp(b.encode_utf16())
+            },
+            (&IndexedDBKeyType::Binary(ref a), &IndexedDBKeyType::Binary(ref b)) => a.cmp(b),
+            (&IndexedDBKeyType::Array(ref a), &IndexedDBKeyType::Array(ref b)) => a.cmp(b),
+            _ => self.type_rank().cmp(&other.type_rank()),
+        }
+    }
+}
+
+impl PartialOrd for IndexedDBKeyType {
+    fn partial_cmp(&self, other: &IndexedDBKeyType) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for IndexedDBKeyType {
+    fn eq(&self, other: &IndexedDBKeyType) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for IndexedDBKeyType {}
+
+/// https://w3c.github.io/IndexedDB/#key-path-construct
+#[derive(Clone, Debug, Deserialize, MallocSizeOf, PartialEq, Serialize)]
+pub enum KeyPath {
+    String(String),
+    Sequence(Vec<String>),
+}
+
+/// https://w3c.github.io/IndexedDB/#transaction-mode
+#[derive(Clone, Copy, Debug, Deserialize, Eq, MallocSizeOf, PartialEq, Serialize)]
+pub enum IndexedDBTxnMode {
+    Readonly,
+    Readwrite,
+    Versionchange,
+}
+
+/// https://w3c.github.io/IndexedDB/#dom-idbcursordirection
+#[derive(Clone, Copy, Debug, Deserialize, Eq, MallocSizeOf, PartialEq, Serialize)]
+pub enum IndexedDBCursorDirection {
+    Next,
+    Prev,
+}
+
+/// What script needs to know about an object store of a database
+#[derive(Clone, Debug, Deserialize, MallocSizeOf, Serialize)]
+pub struct ObjectStoreMetadata {
+    pub name: String,
+    pub key_path: Option<KeyPath>,
+    pub auto_increment: bool,
+}
+
+/// What script needs to know about a database when opening a connection
+#[derive(Clone, Debug, Deserialize, MallocSizeOf, Serialize)]
+pub struct DatabaseMetadata {
+    pub version: u64,
+    /// The object stores, sorted by name
+    pub object_stores: Vec<ObjectStoreMetadata>,
+}
+
+/// The failures of the operations of the IndexedDB thread, which script
+/// reports as the DOMException of the same name
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum BackendError {
+    /// A record with the given key already exists, or the key generator of
+    /// the object store reached its maximum value
+    Constraint,
+    /// The database has a higher version than the requested one
+    Version,
+    /// The database, object store or transaction does not exist
+    NotFound,
+    /// The transaction got aborted before it could start
+    Abort,
+}
+
+/// https://w3c.github.io/IndexedDB/#asynchronously-execute-a-request
+#[derive(Debug, Deserialize, Serialize)]
+pub enum AsyncOperation {
+    /// Stores a value, generating its key if none is given and the object
+    /// store has a key generator, replying with the key of the record.
+    /// Nothing gets stored if a record with that key exists, unless the
+    /// record may be overwritten.
+    ///
+    /// https://w3c.github.io/IndexedDB/#store-a-record-into-an-object-store
+    Put {
+        key: Option<IndexedDBKeyType>,
+        value: Vec<u8>,
+        overwrite: bool,
+    },
+
+    /// Replies with the record with the given key, if any
+    Get(IndexedDBKeyType),
+
+    /// Deletes the record with the given key, if any
+    Delete(IndexedDBKeyType),
+
+    /// Deletes all the records of the object store
+    Clear,
+
+    /// Replies with the number of records with the given key, or of all the
+    /// records of the object store
+    Count(Option<IndexedDBKeyType>),
+
+    /// Replies with the record a cursor moves to, if any
+    ///
+    /// https://w3c.github.io/IndexedDB/#iterate-a-cursor
+    Iterate {
+        /// The key the records iterated over must have, if any
+        query: Option<IndexedDBKeyType>,
+        direction: IndexedDBCursorDirection,
+        /// The key of the record the cursor is at, if any
+        position: Option<IndexedDBKeyType>,
+        /// The key given to `continue()`, which the key of the next record
+        /// must be at or beyond
+        key: Option<IndexedDBKeyType>,
+        /// The number of records to move forward by
+        count: u32,
+    },
+}
+
+/// The result of an `AsyncOperation`
+#[derive(Debug, Deserialize, Serialize)]
+pub enum IndexedDBResult {
+    Key(IndexedDBKeyType),
+    Count(u64),
+    Record(Option<(IndexedDBKeyType, Vec<u8>)>),
+    Undefined,
+}
+
+/// The outcome of opening a connection to a database
+#[derive(Debug, Deserialize, Serialize)]
+pub enum OpenResult {
+    /// The database is at the requested version
+    Success(DatabaseMetadata),
+    /// The database must be upgraded to the requested version, in the
+    /// version change transaction with the given id which already started
+    Upgrade {
+        transaction: u64,
+        old_version: u64,
+        metadata: DatabaseMetadata,
+    },
+    Error(BackendError),
+}
+
+/// Requests operations on the IndexedDB databases of an origin
+#[derive(Debug, Deserialize, Serialize)]
+pub enum IndexedDBThreadMsg {
+    /// Opens a connection to a database, creating or upgrading it if needed,
+    /// once the previous connection requests for the database are done
+    ///
+    /// https://w3c.github.io/IndexedDB/#open-a-database
+    Open(IpcSender<OpenResult>, ImmutableOrigin, String, Option<u64>),
+
+    /// Deletes a database once the previous connection requests and the
+    /// transactions of the database are done, replying with its version
+    ///
+    /// https://w3c.github.io/IndexedDB/#delete-a-database
+    DeleteDatabase(
+        IpcSender<Result<u64, BackendError>>,
+        ImmutableOrigin,
+        String,
+    ),
+
+    /// Creates a transaction over the given object stores, replying with
+    /// its id. The transaction starts once the transactions created before
+    /// it with overlapping scopes finished, unless they are all read-only.
+    ///
+    /// https://w3c.github.io/IndexedDB/#transaction-scheduling
+    CreateTransaction(
+        IpcSender<u64>,
+        ImmutableOrigin,
+        String,
+        IndexedDBTxnMode,
+        Vec<String>,
+    ),
+
+    /// Runs an operation on an object store in the given transaction, once
+    /// the transaction started
+    Request {
+        sender: IpcSender<Result<IndexedDBResult, BackendError>>,
+        origin: ImmutableOrigin,
+        db_name: String,
+        txn: u64,
+        store_name: String,
+        operation: AsyncOperation,
+    },
+
+    /// Creates an object store in a version change transaction
+    CreateObjectStore {
+        origin: ImmutableOrigin,
+        db_name: String,
+        txn: u64,
+        store_name: String,
+        key_path: Option<KeyPath>,
+        auto_increment: bool,
+    },
+
+    /// Deletes an object store in a version change transaction
+    DeleteObjectStore {
+        origin: ImmutableOrigin,
+        db_name: String,
+        txn: u64,
+        store_name: String,
+    },
+
+    /// Commits a transaction once it started and ran its requests
+    ///
+    /// https://w3c.github.io/IndexedDB/#commit-a-transaction
+    Commit(
+        IpcSender<Result<(), BackendError>>,
+        ImmutableOrigin,
+        String,
+        u64,
+    ),
+
+    /// Aborts a transaction, reverting the changes it made
+    ///
+    /// https://w3c.github.io/IndexedDB/#abort-a-transaction
+    Abort(IpcSender<()>, ImmutableOrigin, String, u64),
+
+    /// Sends a reply when done cleaning up thread resources and then shuts it down
+    Exit(IpcSender<()>),
+}
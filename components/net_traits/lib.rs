@@ -17,8 +17,10 @@ extern crate serde;
 #[macro_use]
 extern crate url;
 
+use crate::cache_storage::CacheStorageMsg;
 use crate::filemanager_thread::FileManagerThreadMsg;
-use crate::request::{Request, RequestInit};
+use crate::indexeddb_thread::IndexedDBThreadMsg;
+use crate::request::{Destination, Request, RequestInit};
 use crate::response::{HttpsState, Response, ResponseInit};
 use crate::storage_thread::StorageThreadMsg;
 use cookie::Cookie;
@@ -26,7 +28,7 @@ use headers_core::HeaderMapExt;
 use headers_ext::{ContentType, ReferrerPolicy as ReferrerPolicyHeader};
 use http::{Error as HttpError, HeaderMap};
 use hyper::Error as HyperError;
-use hyper::StatusCode;
+use hyper::{Method, StatusCode};
 use hyper_serde::Serde;
 use ipc_channel::ipc::{self, IpcReceiver, IpcSender};
 use ipc_channel::router::ROUTER;
@@ -39,8 +41,10 @@ use time::precise_time_ns;
 use url::percent_encoding;
 
 pub mod blob_url_store;
+pub mod cache_storage;
 pub mod filemanager_thread;
 pub mod image_cache;
+pub mod indexeddb_thread;
 pub mod net_error_list;
 pub mod pub_domains;
 pub mod quality;
@@ -103,10 +107,28 @@ impl CustomResponse {
     }
 }
 
+/// A fetch handed to the service worker manager, for the service worker
+/// controlling its client to respond to it.
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct CustomResponseMediator {
-    pub response_chan: IpcSender<Option<CustomResponse>>,
+    /// Receives `None` if no service worker responded to the fetch, which
+    /// then goes to the network, or the response or error it responded with.
+    pub response_chan: IpcSender<Option<Result<CustomResponse, NetworkError>>>,
     pub load_url: ServoUrl,
+    #[serde(
+        deserialize_with = "::hyper_serde::deserialize",
+        serialize_with = "::hyper_serde::serialize"
+    )]
+    pub method: Method,
+    #[serde(
+        deserialize_with = "::hyper_serde::deserialize",
+        serialize_with = "::hyper_serde::serialize"
+    )]
+    pub headers: HeaderMap,
+    pub destination: Destination,
+    /// The URL of the client of a subresource fetch, or `None` for a
+    /// navigation.
+    pub client_url: Option<ServoUrl>,
 }
 
 /// [Policies](https://w3c.github.io/webappsec-referrer-policy/#referrer-policy-states)
@@ -302,13 +324,19 @@ where
 pub struct ResourceThreads {
     core_thread: CoreResourceThread,
     storage_thread: IpcSender<StorageThreadMsg>,
+    indexeddb_thread: IpcSender<IndexedDBThreadMsg>,
 }
 
 impl ResourceThreads {
-    pub fn new(c: CoreResourceThread, s: IpcSender<StorageThreadMsg>) -> ResourceThreads {
+    pub fn new(
+        c: CoreResourceThread,
+        s: IpcSender<StorageThreadMsg>,
+        i: IpcSender<IndexedDBThreadMsg>,
+    ) -> ResourceThreads {
         ResourceThreads {
             core_thread: c,
             storage_thread: s,
+            indexeddb_thread: i,
         }
     }
 }
@@ -333,6 +361,16 @@ impl IpcSend<StorageThreadMsg> for ResourceThreads {
     }
 }
 
+impl IpcSend<IndexedDBThreadMsg> for ResourceThreads {
+    fn send(&self, msg: IndexedDBThreadMsg) -> IpcSendResult {
+        self.indexeddb_thread.send(msg)
+    }
+
+    fn sender(&self) -> IpcSender<IndexedDBThreadMsg> {
+        self.indexeddb_thread.clone()
+    }
+}
+
 // Ignore the sub-fields
 malloc_size_of_is_0!(ResourceThreads);
 
@@ -356,8 +394,13 @@ pub enum WebSocketDomAction {
 
 #[derive(Debug, Deserialize, Serialize)]
 pub enum WebSocketNetworkEvent {
-    ConnectionEstablished { protocol_in_use: Option<String> },
+    ConnectionEstablished {
+        protocol_in_use: Option<String>,
+        extensions_in_use: Option<String>,
+    },
     MessageReceived(MessageData),
+    /// A message of the given length in bytes was handed to the connection.
+    MessageSent(u64),
     Close(Option<u16>, String),
     Fail,
 }
@@ -409,6 +452,8 @@ pub enum CoreResourceMsg {
     NetworkMediator(IpcSender<CustomResponseMediator>),
     /// Message forwarded to file manager's handler
     ToFileManager(FileManagerThreadMsg),
+    /// Operation on the Cache API storage
+    CacheStorage(CacheStorageMsg),
     /// Break the load handler loop, send a reply when done cleaning up local resources
     /// and exit
     Exit(IpcSender<()>),
@@ -0,0 +1,194 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! A module for writing time profiler traces out in the [Chrome trace event
+//! format](https://docs.google.com/document/d/1CvAClvFfyA5R-PhYUmn5OOQtYMH4h6I0nSsKchNAySU),
+//! which chrome://tracing and Perfetto can load.
+
+use crate::time::category_name;
+use profile_traits::time::{ProfilerCategory, ProfilerThread, TimerMetadata};
+use profile_traits::time::{TimerMetadataFrameType, TimerMetadataReflowType};
+use serde_json::{self, json, Value};
+use std::collections::HashMap;
+use std::fs;
+use std::io::{self, Write};
+use std::path;
+
+/// An RAII class for writing the trace event file, one track per thread that
+/// reported a time.
+#[derive(Debug)]
+pub struct ChromeTrace {
+    file: fs::File,
+    /// The ids given to the threads seen so far, in each process.
+    threads: HashMap<ProfilerThread, u64>,
+    /// The id of the next async span.
+    next_async_id: u64,
+    /// Whether no event was written yet, so as to not write a separator.
+    empty: bool,
+}
+
+#[derive(Debug, Serialize)]
+struct TraceEvent<'a> {
+    name: &'a str,
+    cat: &'a str,
+    ph: &'static str,
+    /// The timestamp of the event, in microseconds.
+    ts: f64,
+    /// The duration of complete events, in microseconds.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    dur: Option<f64>,
+    pid: u32,
+    tid: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    id: Option<u64>,
+    args: Value,
+}
+
+impl ChromeTrace {
+    /// Create a new ChromeTrace and open the array of events.
+    pub fn new<P>(trace_file_path: P) -> io::Result<ChromeTrace>
+    where
+        P: AsRef<path::Path>,
+    {
+        let mut file = fs::File::create(trace_file_path)?;
+        writeln!(file, "[")?;
+        Ok(ChromeTrace {
+            file: file,
+            threads: HashMap::new(),
+            next_async_id: 0,
+            empty: true,
+        })
+    }
+
+    /// Write one trace to the trace event file.
+    ///
+    /// Times spanning several threads or processes, like the paint metrics
+    /// measured from the navigation start, are written as async spans, since
+    /// they do not nest with the other events of the thread reporting them.
+    pub fn write_one(
+        &mut self,
+        category: &(ProfilerCategory, Option<TimerMetadata>),
+        time: (u64, u64),
+        thread: Option<&ProfilerThread>,
+    ) {
+        let unknown = ProfilerThread {
+            process_id: 0,
+            name: "Unknown thread".to_owned(),
+        };
+        let thread = thread.unwrap_or(&unknown);
+        let tid = self.thread_id(thread);
+        let name = category_name(category.0);
+        let cat = category_group(category.0);
+        let args = match category.1 {
+            Some(ref meta) => json!({
+                "url": meta.url,
+                "incremental": meta.incremental == TimerMetadataReflowType::Incremental,
+                "iframe": meta.iframe == TimerMetadataFrameType::IFrame,
+            }),
+            None => json!({}),
+        };
+        let start = time.0 as f64 / 1000.;
+        let end = time.1 as f64 / 1000.;
+        if is_async(category.0) {
+            let id = self.next_async_id;
+            self.next_async_id += 1;
+            for &(ph, ts) in &[("b", start), ("e", end)] {
+                self.write_event(&TraceEvent {
+                    name: name,
+                    cat: cat,
+                    ph: ph,
+                    ts: ts,
+                    dur: None,
+                    pid: thread.process_id,
+                    tid: tid,
+                    id: Some(id),
+                    args: args.clone(),
+                });
+            }
+        } else {
+            self.write_event(&TraceEvent {
+                name: name,
+                cat: cat,
+                ph: "X",
+                ts: start,
+                dur: Some(end - start),
+                pid: thread.process_id,
+                tid: tid,
+                id: None,
+                args: args,
+            });
+        }
+    }
+
+    /// The id of the track of `thread`, naming the track the first time the
+    /// thread is seen.
+    fn thread_id(&mut self, thread: &ProfilerThread) -> u64 {
+        if let Some(&tid) = self.threads.get(thread) {
+            return tid;
+        }
+        let tid = self
+            .threads
+            .keys()
+            .filter(|other| other.process_id == thread.process_id)
+            .count() as u64;
+        self.threads.insert(thread.clone(), tid);
+        self.write_event(&TraceEvent {
+            name: "thread_name",
+            cat: "__metadata",
+            ph: "M",
+            ts: 0.,
+            dur: None,
+            pid: thread.process_id,
+            tid: tid,
+            id: None,
+            args: json!({ "name": thread.name }),
+        });
+        tid
+    }
+
+    fn write_event(&mut self, event: &TraceEvent) {
+        if !self.empty {
+            writeln!(&mut self.file, ",").unwrap();
+        }
+        self.empty = false;
+        serde_json::to_writer(&mut self.file, event).unwrap();
+    }
+}
+
+impl Drop for ChromeTrace {
+    /// Close the array of events on destruction.
+    fn drop(&mut self) {
+        writeln!(&mut self.file, "\n]").unwrap();
+    }
+}
+
+/// Whether times of `category` span several threads or processes.
+fn is_async(category: ProfilerCategory) -> bool {
+    match category {
+        ProfilerCategory::NetHTTPRequestResponse |
+        ProfilerCategory::TimeToFirstPaint |
+        ProfilerCategory::TimeToFirstContentfulPaint |
+        ProfilerCategory::TimeToInteractive |
+        ProfilerCategory::ApplicationHeartbeat => true,
+        _ => false,
+    }
+}
+
+/// The trace event category of `category`, from the group its value belongs
+/// to.
+fn category_group(category: ProfilerCategory) -> &'static str {
+    match category {
+        ProfilerCategory::IpcReceiver | ProfilerCategory::IpcBytesReceiver => "ipc",
+        _ => match (category as u32) >> 4 {
+            0x0 => "compositing",
+            0x1 => "layout",
+            0x3 => "net",
+            0x4 => "painting",
+            0x5 => "image",
+            0x6 | 0x7 => "script",
+            0x8 => "metrics",
+            _ => "servo",
+        },
+    }
+}
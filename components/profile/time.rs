@@ -4,6 +4,7 @@
 
 //! Timing functions.
 
+use crate::chrome_trace::ChromeTrace;
 use crate::heartbeats;
 use crate::trace_dump::TraceDump;
 use influent::client::{Client, Credentials};
@@ -12,10 +13,10 @@ use influent::measurement::{Measurement, Value};
 use ipc_channel::ipc::{self, IpcReceiver};
 use profile_traits::energy::{energy_interval_ms, read_energy_uj};
 use profile_traits::time::{
-    ProfilerCategory, ProfilerChan, ProfilerData, ProfilerMsg, TimerMetadata,
+    ProfilerCategory, ProfilerChan, ProfilerData, ProfilerMsg, ProfilerThread, TimerMetadata,
 };
 use profile_traits::time::{TimerMetadataFrameType, TimerMetadataReflowType};
-use servo_config::opts::OutputOptions;
+use servo_config::opts::{OutputOptions, TraceFormat};
 use std::borrow::ToOwned;
 use std::cmp::Ordering;
 use std::collections::BTreeMap;
@@ -99,69 +100,102 @@ impl Formattable for ProfilerCategory {
             ProfilerCategory::LayoutTextShaping => "| + ",
             _ => "",
         };
-        let name = match *self {
-            ProfilerCategory::Compositing => "Compositing",
-            ProfilerCategory::LayoutPerform => "Layout",
-            ProfilerCategory::LayoutStyleRecalc => "Style Recalc",
-            ProfilerCategory::LayoutTextShaping => "Text Shaping",
-            ProfilerCategory::LayoutRestyleDamagePropagation => "Restyle Damage Propagation",
-            ProfilerCategory::LayoutNonIncrementalReset => "Non-incremental reset (temporary)",
-            ProfilerCategory::LayoutSelectorMatch => "Selector Matching",
-            ProfilerCategory::LayoutTreeBuilder => "Tree Building",
-            ProfilerCategory::LayoutDamagePropagate => "Damage Propagation",
-            ProfilerCategory::LayoutDisplayListSorting => "Sorting Display List",
-            ProfilerCategory::LayoutGeneratedContent => "Generated Content Resolution",
-            ProfilerCategory::LayoutFloatPlacementSpeculation => "Float Placement Speculation",
-            ProfilerCategory::LayoutMain => "Primary Layout Pass",
-            ProfilerCategory::LayoutStoreOverflow => "Store Overflow",
-            ProfilerCategory::LayoutParallelWarmup => "Parallel Warmup",
-            ProfilerCategory::LayoutDispListBuild => "Display List Construction",
-            ProfilerCategory::NetHTTPRequestResponse => "Network HTTP Request/Response",
-            ProfilerCategory::PaintingPerTile => "Painting Per Tile",
-            ProfilerCategory::PaintingPrepBuff => "Buffer Prep",
-            ProfilerCategory::Painting => "Painting",
-            ProfilerCategory::ImageDecoding => "Image Decoding",
-            ProfilerCategory::ImageSaving => "Image Saving",
-            ProfilerCategory::ScriptAttachLayout => "Script Attach Layout",
-            ProfilerCategory::ScriptConstellationMsg => "Script Constellation Msg",
-            ProfilerCategory::ScriptDevtoolsMsg => "Script Devtools Msg",
-            ProfilerCategory::ScriptDocumentEvent => "Script Document Event",
-            ProfilerCategory::ScriptDomEvent => "Script Dom Event",
-            ProfilerCategory::ScriptEvaluate => "Script JS Evaluate",
-            ProfilerCategory::ScriptFileRead => "Script File Read",
-            ProfilerCategory::ScriptHistoryEvent => "Script History Event",
-            ProfilerCategory::ScriptImageCacheMsg => "Script Image Cache Msg",
-            ProfilerCategory::ScriptInputEvent => "Script Input Event",
-            ProfilerCategory::ScriptNetworkEvent => "Script Network Event",
-            ProfilerCategory::ScriptParseHTML => "Script Parse HTML",
-            ProfilerCategory::ScriptParseXML => "Script Parse XML",
-            ProfilerCategory::ScriptPlannedNavigation => "Script Planned Navigation",
-            ProfilerCategory::ScriptResize => "Script Resize",
-            ProfilerCategory::ScriptEvent => "Script Event",
-            ProfilerCategory::ScriptUpdateReplacedElement => "Script Update Replaced Element",
-            ProfilerCategory::ScriptSetScrollState => "Script Set Scroll State",
-            ProfilerCategory::ScriptSetViewport => "Script Set Viewport",
-            ProfilerCategory::ScriptTimerEvent => "Script Timer Event",
-            ProfilerCategory::ScriptStylesheetLoad => "Script Stylesheet Load",
-            ProfilerCategory::ScriptWebSocketEvent => "Script Web Socket Event",
-            ProfilerCategory::ScriptWorkerEvent => "Script Worker Event",
-            ProfilerCategory::ScriptServiceWorkerEvent => "Script Service Worker Event",
-            ProfilerCategory::ScriptEnterFullscreen => "Script Enter Fullscreen",
-            ProfilerCategory::ScriptExitFullscreen => "Script Exit Fullscreen",
-            ProfilerCategory::ScriptWebVREvent => "Script WebVR Event",
-            ProfilerCategory::ScriptWorkletEvent => "Script Worklet Event",
-            ProfilerCategory::ScriptPerformanceEvent => "Script Performance Event",
-            ProfilerCategory::TimeToFirstPaint => "Time To First Paint",
-            ProfilerCategory::TimeToFirstContentfulPaint => "Time To First Contentful Paint",
-            ProfilerCategory::TimeToInteractive => "Time to Interactive",
-            ProfilerCategory::IpcReceiver => "Blocked at IPC Receive",
-            ProfilerCategory::IpcBytesReceiver => "Blocked at IPC Bytes Receive",
-            ProfilerCategory::ApplicationHeartbeat => "Application Heartbeat",
-        };
+        let name = category_name(*self);
         format!("{}{}", padding, name)
     }
 }
 
+/// The human readable name of `category`.
+pub fn category_name(category: ProfilerCategory) -> &'static str {
+    match category {
+        ProfilerCategory::Compositing => "Compositing",
+        ProfilerCategory::LayoutPerform => "Layout",
+        ProfilerCategory::LayoutStyleRecalc => "Style Recalc",
+        ProfilerCategory::LayoutTextShaping => "Text Shaping",
+        ProfilerCategory::LayoutRestyleDamagePropagation => "Restyle Damage Propagation",
+        ProfilerCategory::LayoutNonIncrementalReset => "Non-incremental reset (temporary)",
+        ProfilerCategory::LayoutSelectorMatch => "Selector Matching",
+        ProfilerCategory::LayoutTreeBuilder => "Tree Building",
+        ProfilerCategory::LayoutDamagePropagate => "Damage Propagation",
+        ProfilerCategory::LayoutDisplayListSorting => "Sorting Display List",
+        ProfilerCategory::LayoutGeneratedContent => "Generated Content Resolution",
+        ProfilerCategory::LayoutFloatPlacementSpeculation => "Float Placement Speculation",
+        ProfilerCategory::LayoutMain => "Primary Layout Pass",
+        ProfilerCategory::LayoutStoreOverflow => "Store Overflow",
+        ProfilerCategory::LayoutParallelWarmup => "Parallel Warmup",
+        ProfilerCategory::LayoutDispListBuild => "Display List Construction",
+        ProfilerCategory::NetHTTPRequestResponse => "Network HTTP Request/Response",
+        ProfilerCategory::PaintingPerTile => "Painting Per Tile",
+        ProfilerCategory::PaintingPrepBuff => "Buffer Prep",
+        ProfilerCategory::Painting => "Painting",
+        ProfilerCategory::ImageDecoding => "Image Decoding",
+        ProfilerCategory::ImageSaving => "Image Saving",
+        ProfilerCategory::ScriptAttachLayout => "Script Attach Layout",
+        ProfilerCategory::ScriptConstellationMsg => "Script Constellation Msg",
+        ProfilerCategory::ScriptDevtoolsMsg => "Script Devtools Msg",
+        ProfilerCategory::ScriptDocumentEvent => "Script Document Event",
+        ProfilerCategory::ScriptDomEvent => "Script Dom Event",
+        ProfilerCategory::ScriptEvaluate => "Script JS Evaluate",
+        ProfilerCategory::ScriptFileRead => "Script File Read",
+        ProfilerCategory::ScriptHistoryEvent => "Script History Event",
+        ProfilerCategory::ScriptImageCacheMsg => "Script Image Cache Msg",
+        ProfilerCategory::ScriptInputEvent => "Script Input Event",
+        ProfilerCategory::ScriptNetworkEvent => "Script Network Event",
+        ProfilerCategory::ScriptParseHTML => "Script Parse HTML",
+        ProfilerCategory::ScriptParseXML => "Script Parse XML",
+        ProfilerCategory::ScriptPlannedNavigation => "Script Planned Navigation",
+        ProfilerCategory::ScriptResize => "Script Resize",
+        ProfilerCategory::ScriptEvent => "Script Event",
+        ProfilerCategory::ScriptUpdateReplacedElement => "Script Update Replaced Element",
+        ProfilerCategory::ScriptSetScrollState => "Script Set Scroll State",
+        ProfilerCategory::ScriptSetViewport => "Script Set Viewport",
+        ProfilerCategory::ScriptTimerEvent => "Script Timer Event",
+        ProfilerCategory::ScriptStylesheetLoad => "Script Stylesheet Load",
+        ProfilerCategory::ScriptWebSocketEvent => "Script Web Socket Event",
+        ProfilerCategory::ScriptWorkerEvent => "Script Worker Event",
+        ProfilerCategory::ScriptServiceWorkerEvent => "Script Service Worker Event",
+        ProfilerCategory::ScriptEnterFullscreen => "Script Enter Fullscreen",
+        ProfilerCategory::ScriptExitFullscreen => "Script Exit Fullscreen",
+        ProfilerCategory::ScriptWebVREvent => "Script WebVR Event",
+        ProfilerCategory::ScriptWorkletEvent => "Script Worklet Event",
+        ProfilerCategory::ScriptPerformanceEvent => "Script Performance Event",
+        ProfilerCategory::TimeToFirstPaint => "Time To First Paint",
+        ProfilerCategory::TimeToFirstContentfulPaint => "Time To First Contentful Paint",
+        ProfilerCategory::TimeToInteractive => "Time to Interactive",
+        ProfilerCategory::IpcReceiver => "Blocked at IPC Receive",
+        ProfilerCategory::IpcBytesReceiver => "Blocked at IPC Bytes Receive",
+        ProfilerCategory::ApplicationHeartbeat => "Application Heartbeat",
+    }
+}
+
+/// The file profiler traces get dumped to, in one of the trace formats.
+pub enum Trace {
+    Html(TraceDump),
+    Chrome(ChromeTrace),
+}
+
+impl Trace {
+    pub fn new(file_path: &str, format: TraceFormat) -> io::Result<Trace> {
+        Ok(match format {
+            TraceFormat::Html => Trace::Html(TraceDump::new(file_path)?),
+            TraceFormat::Chrome => Trace::Chrome(ChromeTrace::new(file_path)?),
+        })
+    }
+
+    fn write_one(
+        &mut self,
+        category: &(ProfilerCategory, Option<TimerMetadata>),
+        time: (u64, u64),
+        energy: (u64, u64),
+        thread: Option<&ProfilerThread>,
+    ) {
+        match *self {
+            Trace::Html(ref mut trace) => trace.write_one(category, time, energy),
+            Trace::Chrome(ref mut trace) => trace.write_one(category, time, thread),
+        }
+    }
+}
+
 type ProfilerBuckets = BTreeMap<(ProfilerCategory, Option<TimerMetadata>), Vec<f64>>;
 
 // back end of the profiler that handles data aggregation and performance metrics
@@ -170,11 +204,15 @@ pub struct Profiler {
     buckets: ProfilerBuckets,
     output: Option<OutputOptions>,
     pub last_msg: Option<ProfilerMsg>,
-    trace: Option<TraceDump>,
+    trace: Option<Trace>,
 }
 
 impl Profiler {
-    pub fn create(output: &Option<OutputOptions>, file_path: Option<String>) -> ProfilerChan {
+    pub fn create(
+        output: &Option<OutputOptions>,
+        file_path: Option<String>,
+        trace_format: TraceFormat,
+    ) -> ProfilerChan {
         let (chan, port) = ipc::channel().unwrap();
         match *output {
             Some(ref option) => {
@@ -183,7 +221,9 @@ impl Profiler {
                 thread::Builder::new()
                     .name("Time profiler".to_owned())
                     .spawn(move || {
-                        let trace = file_path.as_ref().and_then(|p| TraceDump::new(p).ok());
+                        let trace = file_path
+                            .as_ref()
+                            .and_then(|p| Trace::new(p, trace_format).ok());
                         let mut profiler = Profiler::new(port, trace, Some(outputoption));
                         profiler.start();
                     })
@@ -215,7 +255,9 @@ impl Profiler {
                     thread::Builder::new()
                         .name("Time profiler".to_owned())
                         .spawn(move || {
-                            let trace = file_path.as_ref().and_then(|p| TraceDump::new(p).ok());
+                            let trace = file_path
+                                .as_ref()
+                                .and_then(|p| Trace::new(p, trace_format).ok());
                             let mut profiler = Profiler::new(port, trace, None);
                             profiler.start();
                         })
@@ -281,6 +323,7 @@ impl Profiler {
                             (ProfilerCategory::ApplicationHeartbeat, None),
                             (start_time, end_time),
                             (start_energy, end_energy),
+                            None,
                         )) {
                             return;
                         }
@@ -296,7 +339,7 @@ impl Profiler {
 
     pub fn new(
         port: IpcReceiver<ProfilerMsg>,
-        trace: Option<TraceDump>,
+        trace: Option<Trace>,
         output: Option<OutputOptions>,
     ) -> Profiler {
         Profiler {
@@ -322,10 +365,10 @@ impl Profiler {
 
     fn handle_msg(&mut self, msg: ProfilerMsg) -> bool {
         match msg.clone() {
-            ProfilerMsg::Time(k, t, e) => {
+            ProfilerMsg::Time(k, t, e, thread) => {
                 heartbeats::maybe_heartbeat(&k.0, t.0, t.1, e.0, e.1);
                 if let Some(ref mut trace) = self.trace {
-                    trace.write_one(&k, t, e);
+                    trace.write_one(&k, t, e, thread.as_ref());
                 }
                 let ms = (t.1 - t.0) as f64 / 1000000f64;
                 self.find_or_insert(k, ms);
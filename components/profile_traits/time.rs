@@ -5,6 +5,7 @@
 use crate::energy::read_energy_uj;
 use ipc_channel::ipc::IpcSender;
 use servo_config::opts;
+use std::{process, thread};
 use time::precise_time_ns;
 
 #[derive(Clone, Debug, Deserialize, Eq, Ord, PartialEq, PartialOrd, Serialize)]
@@ -14,6 +15,23 @@ pub struct TimerMetadata {
     pub incremental: TimerMetadataReflowType,
 }
 
+/// The thread that reported a time, so that traces can show a track per
+/// thread.
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct ProfilerThread {
+    pub process_id: u32,
+    pub name: String,
+}
+
+impl ProfilerThread {
+    pub fn current() -> ProfilerThread {
+        ProfilerThread {
+            process_id: process::id(),
+            name: thread::current().name().unwrap_or("<unnamed>").to_owned(),
+        }
+    }
+}
+
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct ProfilerChan(pub IpcSender<ProfilerMsg>);
 
@@ -33,11 +51,13 @@ pub enum ProfilerData {
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub enum ProfilerMsg {
-    /// Normal message used for reporting time
+    /// Normal message used for reporting time, along with the thread it was
+    /// reported from when profiler traces are dumped
     Time(
         (ProfilerCategory, Option<TimerMetadata>),
         (u64, u64),
         (u64, u64),
+        Option<ProfilerThread>,
     ),
     /// Message used to get time spend entries for a particular ProfilerBuckets (in nanoseconds)
     Get(
@@ -168,9 +188,14 @@ pub fn send_profile_data(
     start_energy: u64,
     end_energy: u64,
 ) {
+    let thread = match opts::get().time_profiler_trace_path {
+        Some(_) => Some(ProfilerThread::current()),
+        None => None,
+    };
     profiler_chan.send(ProfilerMsg::Time(
         (category, meta),
         (start_time, end_time),
         (start_energy, end_energy),
+        thread,
     ));
 }
@@ -0,0 +1,502 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! The accessibility tree of documents, derived from the DOM and layout and
+//! sent to the embedder, which exposes it to the accessibility APIs of the
+//! platform.
+//!
+//! The tree gets updated after the reflows for display, from the elements
+//! the style system was notified of a change to since the previous one.
+
+use crate::dom::bindings::codegen::Bindings::AttrBinding::AttrMethods;
+use crate::dom::bindings::codegen::Bindings::HTMLInputElementBinding::HTMLInputElementMethods;
+use crate::dom::bindings::codegen::Bindings::HTMLTextAreaElementBinding::HTMLTextAreaElementMethods;
+use crate::dom::bindings::codegen::Bindings::NodeBinding::NodeMethods;
+use crate::dom::bindings::inheritance::{Castable, CharacterDataTypeId, NodeTypeId};
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::document::Document;
+use crate::dom::element::Element;
+use crate::dom::htmlelement::HTMLElement;
+use crate::dom::htmlinputelement::{HTMLInputElement, InputType};
+use crate::dom::htmltextareaelement::HTMLTextAreaElement;
+use crate::dom::node::{window_from_node, Node};
+use embedder_traits::{AccessibilityNode, AccessibilityNodeId, AccessibilityRect};
+use embedder_traits::{AccessibilityRole, AccessibilityTreeUpdate};
+use html5ever::LocalName;
+use servo_atoms::Atom;
+use std::collections::{HashMap, HashSet};
+
+/// The accessibility tree last sent to the embedder for a document, along
+/// with the DOM nodes that changed since.
+#[derive(JSTraceable, MallocSizeOf)]
+#[must_root]
+pub struct AccessibilityTree {
+    #[ignore_malloc_size_of = "Defined in embedder_traits"]
+    nodes: HashMap<AccessibilityNodeId, AccessibilityNode>,
+    #[ignore_malloc_size_of = "Defined in embedder_traits"]
+    focus: Option<AccessibilityNodeId>,
+    changes: HashSet<Dom<Node>>,
+}
+
+impl AccessibilityTree {
+    pub fn new() -> AccessibilityTree {
+        AccessibilityTree {
+            nodes: HashMap::new(),
+            focus: None,
+            changes: HashSet::new(),
+        }
+    }
+
+    /// Notes that `node` or its children changed, for the next update to
+    /// look at it.
+    pub fn note_change(&mut self, node: &Node) {
+        self.changes.insert(Dom::from_ref(node));
+    }
+
+    /// Updates the tree from the changes made to `document` since the
+    /// previous update, returning what changed, if anything did.
+    pub fn update(&mut self, document: &Document) -> Option<AccessibilityTreeUpdate> {
+        let root = document.upcast::<Node>();
+        let root_id = node_id(root);
+        let first = self.nodes.is_empty();
+
+        // The changed nodes, and their ancestors, whose name may be computed
+        // from their content.
+        let changes: Vec<DomRoot<Node>> = self
+            .changes
+            .iter()
+            .map(|node| DomRoot::from_ref(&**node))
+            .collect();
+        self.changes.clear();
+        let mut pending = vec![DomRoot::from_ref(root)];
+        let mut seen = HashSet::new();
+        seen.insert(root_id);
+        for node in changes {
+            if !node.is_in_doc() || &*node.owner_doc() != document {
+                continue;
+            }
+            // A node which just got excluded changes the children of its
+            // parent.
+            let node = if is_in_tree(&node) {
+                node
+            } else {
+                match node.GetParentNode() {
+                    Some(ref parent) if is_in_tree(parent) => DomRoot::from_ref(&**parent),
+                    _ => continue,
+                }
+            };
+            for node in node.inclusive_ancestors() {
+                if !seen.insert(node_id(&node)) {
+                    break;
+                }
+                pending.push(node);
+            }
+        }
+
+        let mut updated = vec![];
+        while let Some(node) = pending.pop() {
+            let id = node_id(&node);
+            let new = accessibility_node(&node);
+            let old = self.nodes.get(&id);
+            // Children the node did not have before may have never been sent,
+            // or may have moved, look at them too.
+            pending.extend(node.children().filter(|child| {
+                !is_excluded(child) &&
+                    old.map_or(true, |old| !old.children.contains(&node_id(child)))
+            }));
+            if old != Some(&new) {
+                updated.push((id, new.clone()));
+                self.nodes.insert(id, new);
+            }
+        }
+        remove_unreachable_nodes(&mut self.nodes, root_id);
+
+        let focus = document
+            .get_focused_element()
+            .map(|element| node_id(element.upcast()))
+            .filter(|id| self.nodes.contains_key(id))
+            .unwrap_or(root_id);
+        if !first && updated.is_empty() && self.focus == Some(focus) {
+            return None;
+        }
+        self.focus = Some(focus);
+        Some(AccessibilityTreeUpdate {
+            nodes: updated,
+            root: if first { Some(root_id) } else { None },
+            focus: focus,
+        })
+    }
+}
+
+/// Forgets the nodes which are not in the tree rooted at `root` anymore.
+pub fn remove_unreachable_nodes(
+    nodes: &mut HashMap<AccessibilityNodeId, AccessibilityNode>,
+    root: AccessibilityNodeId,
+) {
+    let mut reachable = HashSet::new();
+    let mut pending = vec![root];
+    while let Some(id) = pending.pop() {
+        if reachable.insert(id) {
+            if let Some(node) = nodes.get(&id) {
+                pending.extend(node.children.iter().cloned());
+            }
+        }
+    }
+    nodes.retain(|id, _| reachable.contains(id));
+}
+
+fn node_id(node: &Node) -> AccessibilityNodeId {
+    AccessibilityNodeId(node.to_opaque().0 as u64)
+}
+
+/// Whether `node` is part of the accessibility tree, none of its inclusive
+/// ancestors being excluded from it.
+fn is_in_tree(node: &Node) -> bool {
+    !node.inclusive_ancestors().any(|node| is_excluded(&node))
+}
+
+fn html_local_name(node: &Node) -> Option<&LocalName> {
+    let element = node.downcast::<Element>()?;
+    if *element.namespace() != ns!(html) {
+        return None;
+    }
+    Some(element.local_name())
+}
+
+fn attribute(element: &Element, name: &str) -> Option<String> {
+    let value = element.get_attribute(&ns!(), &LocalName::from(name))?;
+    Some(String::from(value.Value()))
+}
+
+/// Whether `node`, and the nodes it contains, are not part of the
+/// accessibility tree.
+fn is_excluded(node: &Node) -> bool {
+    match node.type_id() {
+        NodeTypeId::Document(_) => false,
+        NodeTypeId::CharacterData(CharacterDataTypeId::Text(_)) => node
+            .GetTextContent()
+            .map_or(true, |text| text.chars().all(char::is_whitespace)),
+        NodeTypeId::Element(_) => {
+            let element = node.downcast::<Element>().unwrap();
+            if element.has_attribute(&local_name!("hidden")) ||
+                attribute(element, "aria-hidden").map_or(false, |value| value == "true")
+            {
+                return true;
+            }
+            match html_local_name(node) {
+                Some(&local_name!("head")) |
+                Some(&local_name!("script")) |
+                Some(&local_name!("style")) |
+                Some(&local_name!("template")) |
+                Some(&local_name!("noscript")) => true,
+                Some(&local_name!("input")) => {
+                    node.downcast::<HTMLInputElement>().unwrap().input_type() == InputType::Hidden
+                },
+                _ => false,
+            }
+        },
+        _ => true,
+    }
+}
+
+/// <https://w3c.github.io/html-aam/#html-element-role-mappings>
+fn role(node: &Node) -> AccessibilityRole {
+    if node.is::<Document>() {
+        return AccessibilityRole::Document;
+    }
+    if !node.is::<Element>() {
+        return AccessibilityRole::StaticText;
+    }
+    let element = node.downcast::<Element>().unwrap();
+    if let Some(role) = attribute(element, "role").and_then(|role| aria_role(&role)) {
+        return role;
+    }
+    match html_local_name(node) {
+        Some(&local_name!("a")) | Some(&local_name!("area"))
+            if element.has_attribute(&local_name!("href")) =>
+        {
+            AccessibilityRole::Link
+        },
+        Some(&local_name!("article")) => AccessibilityRole::Article,
+        Some(&local_name!("aside")) | Some(&local_name!("section")) => AccessibilityRole::Region,
+        Some(&local_name!("button")) => AccessibilityRole::Button,
+        Some(&local_name!("dialog")) => AccessibilityRole::Dialog,
+        Some(&local_name!("footer")) => AccessibilityRole::ContentInfo,
+        Some(&local_name!("form")) => AccessibilityRole::Form,
+        Some(&local_name!("h1")) |
+        Some(&local_name!("h2")) |
+        Some(&local_name!("h3")) |
+        Some(&local_name!("h4")) |
+        Some(&local_name!("h5")) |
+        Some(&local_name!("h6")) => AccessibilityRole::Heading,
+        Some(&local_name!("header")) => AccessibilityRole::Banner,
+        Some(&local_name!("hr")) => AccessibilityRole::Separator,
+        Some(&local_name!("img")) => AccessibilityRole::Image,
+        Some(&local_name!("input")) => {
+            match node.downcast::<HTMLInputElement>().unwrap().input_type() {
+                InputType::Button | InputType::Image | InputType::Reset | InputType::Submit => {
+                    AccessibilityRole::Button
+                },
+                InputType::Checkbox => AccessibilityRole::CheckBox,
+                InputType::Radio => AccessibilityRole::RadioButton,
+                InputType::Range => AccessibilityRole::Slider,
+                _ => AccessibilityRole::TextInput,
+            }
+        },
+        Some(&local_name!("li")) => AccessibilityRole::ListItem,
+        Some(&local_name!("main")) => AccessibilityRole::Main,
+        Some(&local_name!("menu")) | Some(&local_name!("ol")) | Some(&local_name!("ul")) => {
+            AccessibilityRole::List
+        },
+        Some(&local_name!("nav")) => AccessibilityRole::Navigation,
+        Some(&local_name!("option")) => AccessibilityRole::ListBoxOption,
+        Some(&local_name!("p")) => AccessibilityRole::Paragraph,
+        Some(&local_name!("progress")) => AccessibilityRole::ProgressIndicator,
+        Some(&local_name!("select")) => {
+            if element.has_attribute(&local_name!("multiple")) {
+                AccessibilityRole::ListBox
+            } else {
+                AccessibilityRole::ComboBox
+            }
+        },
+        Some(&local_name!("table")) => AccessibilityRole::Table,
+        Some(&local_name!("td")) => AccessibilityRole::Cell,
+        Some(&local_name!("textarea")) => AccessibilityRole::TextInput,
+        Some(&local_name!("th")) => AccessibilityRole::ColumnHeader,
+        Some(&local_name!("tr")) => AccessibilityRole::Row,
+        _ => AccessibilityRole::Generic,
+    }
+}
+
+/// The role given by the first token of a `role` attribute that is known.
+///
+/// <https://w3c.github.io/aria/#role_definitions>
+pub fn aria_role(roles: &str) -> Option<AccessibilityRole> {
+    roles
+        .split_whitespace()
+        .filter_map(|role| {
+            Some(match role {
+                "article" => AccessibilityRole::Article,
+                "banner" => AccessibilityRole::Banner,
+                "button" => AccessibilityRole::Button,
+                "cell" | "gridcell" => AccessibilityRole::Cell,
+                "checkbox" | "switch" => AccessibilityRole::CheckBox,
+                "columnheader" => AccessibilityRole::ColumnHeader,
+                "combobox" => AccessibilityRole::ComboBox,
+                "contentinfo" => AccessibilityRole::ContentInfo,
+                "dialog" | "alertdialog" => AccessibilityRole::Dialog,
+                "form" => AccessibilityRole::Form,
+                "generic" | "none" | "presentation" => AccessibilityRole::Generic,
+                "heading" => AccessibilityRole::Heading,
+                "img" => AccessibilityRole::Image,
+                "link" => AccessibilityRole::Link,
+                "list" => AccessibilityRole::List,
+                "listbox" => AccessibilityRole::ListBox,
+                "listitem" => AccessibilityRole::ListItem,
+                "main" => AccessibilityRole::Main,
+                "navigation" => AccessibilityRole::Navigation,
+                "option" => AccessibilityRole::ListBoxOption,
+                "paragraph" => AccessibilityRole::Paragraph,
+                "progressbar" => AccessibilityRole::ProgressIndicator,
+                "radio" => AccessibilityRole::RadioButton,
+                "region" | "complementary" => AccessibilityRole::Region,
+                "row" => AccessibilityRole::Row,
+                "rowheader" => AccessibilityRole::RowHeader,
+                "separator" => AccessibilityRole::Separator,
+                "slider" => AccessibilityRole::Slider,
+                "table" | "grid" => AccessibilityRole::Table,
+                "textbox" | "searchbox" => AccessibilityRole::TextInput,
+                _ => return None,
+            })
+        })
+        .next()
+}
+
+/// Whether nodes of `role` get their name from their content.
+///
+/// <https://w3c.github.io/aria/#namefromcontent>
+pub fn is_named_from_content(role: AccessibilityRole) -> bool {
+    match role {
+        AccessibilityRole::Button |
+        AccessibilityRole::Cell |
+        AccessibilityRole::CheckBox |
+        AccessibilityRole::ColumnHeader |
+        AccessibilityRole::Heading |
+        AccessibilityRole::Link |
+        AccessibilityRole::ListBoxOption |
+        AccessibilityRole::RadioButton |
+        AccessibilityRole::RowHeader |
+        AccessibilityRole::StaticText => true,
+        _ => false,
+    }
+}
+
+/// The text content of `node` with its white space collapsed, if there is
+/// any.
+fn text(node: &Node) -> Option<String> {
+    let text = node.GetTextContent()?;
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    if text.is_empty() {
+        None
+    } else {
+        Some(text)
+    }
+}
+
+/// A simplified computation of the accessible name of `node`.
+///
+/// <https://w3c.github.io/accname/#mapping_additional_nd_te>
+fn name(node: &Node, role: AccessibilityRole) -> Option<String> {
+    let element = match node.downcast::<Element>() {
+        Some(element) => element,
+        None => return text(node),
+    };
+    // Step 2B.
+    if let Some(ids) = attribute(element, "aria-labelledby") {
+        let document = node.owner_doc();
+        let labels: Vec<String> = ids
+            .split_whitespace()
+            .filter_map(|id| document.get_element_by_id(&Atom::from(id)))
+            .filter_map(|label| text(label.upcast()))
+            .collect();
+        if !labels.is_empty() {
+            return Some(labels.join(" "));
+        }
+    }
+    // Step 2C.
+    if let Some(label) = attribute(element, "aria-label").filter(|label| !label.trim().is_empty()) {
+        return Some(label);
+    }
+    // Step 2D.
+    match html_local_name(node) {
+        Some(&local_name!("img")) | Some(&local_name!("area")) => {
+            if let Some(alt) = attribute(element, "alt") {
+                return Some(alt);
+            }
+        },
+        Some(&local_name!("input")) => {
+            let input = node.downcast::<HTMLInputElement>().unwrap();
+            match input.input_type() {
+                InputType::Button | InputType::Reset | InputType::Submit => {
+                    let value = String::from(input.Value());
+                    if !value.is_empty() {
+                        return Some(value);
+                    }
+                },
+                InputType::Image => {
+                    if let Some(alt) = attribute(element, "alt") {
+                        return Some(alt);
+                    }
+                },
+                _ => {},
+            }
+        },
+        _ => {},
+    }
+    if let Some(html_element) = node.downcast::<HTMLElement>() {
+        if html_element.is_labelable_element() {
+            let labels: Vec<String> = html_element
+                .labels()
+                .iter()
+                .filter_map(|label| text(&label))
+                .collect();
+            if !labels.is_empty() {
+                return Some(labels.join(" "));
+            }
+        }
+    }
+    // Step 2F.
+    if is_named_from_content(role) {
+        if let Some(text) = text(node) {
+            return Some(text);
+        }
+    }
+    // Step 2I.
+    attribute(element, "title")
+}
+
+fn value(node: &Node) -> Option<String> {
+    if let Some(input) = node.downcast::<HTMLInputElement>() {
+        return match input.input_type() {
+            InputType::Button |
+            InputType::Checkbox |
+            InputType::Image |
+            InputType::Password |
+            InputType::Radio |
+            InputType::Reset |
+            InputType::Submit => None,
+            _ => Some(String::from(input.Value())),
+        };
+    }
+    if let Some(textarea) = node.downcast::<HTMLTextAreaElement>() {
+        return Some(String::from(textarea.Value()));
+    }
+    None
+}
+
+fn level(node: &Node, role: AccessibilityRole) -> Option<u32> {
+    if role != AccessibilityRole::Heading {
+        return None;
+    }
+    let element = node.downcast::<Element>()?;
+    if let Some(level) = attribute(element, "aria-level").and_then(|level| level.parse().ok()) {
+        return Some(level);
+    }
+    match html_local_name(node)? {
+        &local_name!("h1") => Some(1),
+        &local_name!("h2") => Some(2),
+        &local_name!("h3") => Some(3),
+        &local_name!("h4") => Some(4),
+        &local_name!("h5") => Some(5),
+        &local_name!("h6") => Some(6),
+        _ => Some(2),
+    }
+}
+
+fn checked(node: &Node, role: AccessibilityRole) -> Option<bool> {
+    if role != AccessibilityRole::CheckBox && role != AccessibilityRole::RadioButton {
+        return None;
+    }
+    if let Some(input) = node.downcast::<HTMLInputElement>() {
+        return Some(input.Checked());
+    }
+    let element = node.downcast::<Element>()?;
+    Some(attribute(element, "aria-checked").map_or(false, |value| value == "true"))
+}
+
+fn bounds(node: &Node) -> Option<AccessibilityRect> {
+    if node.is::<Document>() {
+        return None;
+    }
+    let rect = window_from_node(node).content_box_query(node)?;
+    Some(AccessibilityRect {
+        x: rect.origin.x.to_f32_px(),
+        y: rect.origin.y.to_f32_px(),
+        width: rect.size.width.to_f32_px(),
+        height: rect.size.height.to_f32_px(),
+    })
+}
+
+fn accessibility_node(node: &Node) -> AccessibilityNode {
+    let role = role(node);
+    let element = node.downcast::<Element>();
+    AccessibilityNode {
+        role: role,
+        name: name(node, role),
+        value: value(node),
+        level: level(node, role),
+        bounds: bounds(node),
+        focusable: element.map_or(false, |element| element.is_focusable_area()),
+        disabled: element.map_or(false, |element| {
+            element.disabled_state() ||
+                attribute(element, "aria-disabled").map_or(false, |value| value == "true")
+        }),
+        checked: checked(node, role),
+        children: node
+            .children()
+            .filter(|child| !is_excluded(child))
+            .map(|child| node_id(&child))
+            .collect(),
+    }
+}
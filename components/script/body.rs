@@ -3,15 +3,19 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
 use crate::dom::bindings::codegen::Bindings::FormDataBinding::FormDataMethods;
+use crate::dom::bindings::codegen::Bindings::ResponseBinding::ResponseBodyInit;
 use crate::dom::bindings::error::{Error, Fallible};
 use crate::dom::bindings::reflector::DomObject;
-use crate::dom::bindings::root::DomRoot;
-use crate::dom::bindings::str::USVString;
-use crate::dom::bindings::trace::RootedTraceableBox;
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::bindings::str::{DOMString, USVString};
+use crate::dom::bindings::trace::{JSTraceable, RootedTraceableBox};
 use crate::dom::blob::{Blob, BlobImpl};
 use crate::dom::formdata::FormData;
 use crate::dom::globalscope::GlobalScope;
 use crate::dom::promise::Promise;
+use crate::dom::readablestream::ReadableStream;
+use crate::dom::readablestreamdefaultreader::ReadAllBytesHandler;
+use crate::dom::xmlhttprequest::Extractable;
 use js::jsapi::Heap;
 use js::jsapi::JSContext;
 use js::jsapi::JSObject;
@@ -21,6 +25,7 @@ use js::jsval::JSVal;
 use js::jsval::UndefinedValue;
 use js::rust::wrappers::JS_GetPendingException;
 use js::rust::wrappers::JS_ParseJSON;
+use js::rust::HandleValue;
 use js::typedarray::{ArrayBuffer, CreateWith};
 use mime::{self, Mime};
 use std::cell::Ref;
@@ -61,16 +66,47 @@ pub fn consume_body<T: BodyOperations + DomObject>(object: &T, body_type: BodyTy
         return promise;
     }
 
-    object.set_body_promise(&promise, body_type);
-
     // Steps 2-4
-    // TODO: Body does not yet have a stream.
+    if let Some(stream) = object.get_body_stream() {
+        let handler = Box::new(ConsumeBodyStream {
+            object: Dom::from_ref(object),
+            body_type: body_type,
+            promise: promise.clone(),
+        });
+        if let Err(error) = stream.read_all_bytes(handler) {
+            promise.reject_error(error);
+        }
+        return promise;
+    }
+
+    object.set_body_promise(&promise, body_type);
 
     consume_body_with_promise(object, body_type, &promise);
 
     promise
 }
 
+/// Packages the bytes of a body read from its stream
+#[derive(JSTraceable)]
+#[must_root]
+struct ConsumeBodyStream<T: BodyOperations + DomObject + 'static> {
+    object: Dom<T>,
+    body_type: BodyType,
+    promise: Rc<Promise>,
+}
+
+impl<T: BodyOperations + DomObject + JSTraceable + 'static> ReadAllBytesHandler
+    for ConsumeBodyStream<T>
+{
+    fn success(&self, bytes: Vec<u8>) {
+        consume_bytes(&*self.object, bytes, self.body_type, &self.promise);
+    }
+
+    fn failure(&self, _cx: *mut JSContext, error: HandleValue) {
+        self.promise.reject_native(&error);
+    }
+}
+
 // https://fetch.spec.whatwg.org/#concept-body-consume-body
 #[allow(unrooted_must_root)]
 pub fn consume_body_with_promise<T: BodyOperations + DomObject>(
@@ -84,6 +120,17 @@ pub fn consume_body_with_promise<T: BodyOperations + DomObject>(
         None => return,
     };
 
+    consume_bytes(object, body, body_type, promise);
+}
+
+// https://fetch.spec.whatwg.org/#concept-body-consume-body
+// Step 5.
+fn consume_bytes<T: BodyOperations + DomObject>(
+    object: &T,
+    body: Vec<u8>,
+    body_type: BodyType,
+    promise: &Promise,
+) {
     let pkg_data_results =
         run_package_data_algorithm(object, body, body_type, object.get_mime_type());
 
@@ -210,12 +257,42 @@ unsafe fn run_array_buffer_data_algorithm(
     Ok(FetchedData::ArrayBuffer(rooted_heap))
 }
 
+/// The body of a request or response given by script, which is either
+/// extracted to bytes, or to be read from a stream
+pub enum ExtractedBody {
+    Bytes(Vec<u8>, Option<DOMString>),
+    Stream(DomRoot<ReadableStream>),
+}
+
+// https://fetch.spec.whatwg.org/#concept-bodyinit-extract
+pub fn extract_body(body: &ResponseBodyInit) -> Fallible<ExtractedBody> {
+    let (bytes, content_type) = match *body {
+        ResponseBodyInit::ReadableStream(ref stream) => {
+            if stream.is_disturbed() || stream.is_locked() {
+                return Err(Error::Type(
+                    "The body stream is disturbed or locked".to_string(),
+                ));
+            }
+            return Ok(ExtractedBody::Stream(stream.clone()));
+        },
+        ResponseBodyInit::String(ref s) => s.extract(),
+        ResponseBodyInit::URLSearchParams(ref usp) => usp.extract(),
+        ResponseBodyInit::Blob(ref b) => b.extract(),
+        ResponseBodyInit::FormData(ref formdata) => formdata.extract(),
+        ResponseBodyInit::ArrayBuffer(ref typedarray) => (typedarray.to_vec(), None),
+        ResponseBodyInit::ArrayBufferView(ref typedarray) => (typedarray.to_vec(), None),
+    };
+    Ok(ExtractedBody::Bytes(bytes, content_type))
+}
+
 pub trait BodyOperations {
     fn get_body_used(&self) -> bool;
     fn set_body_promise(&self, p: &Rc<Promise>, body_type: BodyType);
     /// Returns `Some(_)` if the body is complete, `None` if there is more to
     /// come.
     fn take_body(&self) -> Option<Vec<u8>>;
+    /// Returns the stream the body has to be read from, if there is one.
+    fn get_body_stream(&self) -> Option<DomRoot<ReadableStream>>;
     fn is_locked(&self) -> bool;
     fn get_mime_type(&self) -> Ref<Vec<u8>>;
 }
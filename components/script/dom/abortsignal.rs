@@ -0,0 +1,150 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::AbortSignalBinding::{self, AbortSignalMethods};
+use crate::dom::bindings::error::Error;
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::reflector::reflect_dom_object;
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::eventtarget::EventTarget;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::promise::Promise;
+use crate::dom::response::Response;
+use dom_struct::dom_struct;
+use ipc_channel::ipc::IpcSender;
+use std::cell::Cell;
+use std::mem;
+use std::rc::Rc;
+
+/// https://dom.spec.whatwg.org/#abortsignal-abort-algorithms
+#[derive(JSTraceable)]
+#[must_root]
+pub enum AbortAlgorithm {
+    /// Signal abort on another signal following this one.
+    ///
+    /// https://dom.spec.whatwg.org/#abortsignal-follow
+    DomSignal(Dom<AbortSignal>),
+    /// Abort an ongoing fetch.
+    ///
+    /// https://fetch.spec.whatwg.org/#abort-fetch
+    Fetch {
+        promise: Rc<Promise>,
+        response: Dom<Response>,
+        /// The sender used to tell the fetch thread to stop the fetch,
+        /// which is not a `FetchCanceller` because a fetch that is not
+        /// aborted should not be cancelled once the signal gets collected
+        cancel_chan: IpcSender<()>,
+    },
+}
+
+impl AbortAlgorithm {
+    #[allow(unrooted_must_root)]
+    fn exec(self) {
+        match self {
+            AbortAlgorithm::DomSignal(signal) => signal.signal_abort(),
+            AbortAlgorithm::Fetch {
+                promise,
+                response,
+                cancel_chan,
+            } => {
+                // Step 1.
+                promise.reject_error(Error::Abort);
+                // Step 2.
+                response.abort();
+                // The fetch may have completed, in which case nobody listens
+                // to the cancellation anymore.
+                let _ = cancel_chan.send(());
+            },
+        }
+    }
+}
+
+#[dom_struct]
+pub struct AbortSignal {
+    eventtarget: EventTarget,
+    /// https://dom.spec.whatwg.org/#abortsignal-aborted-flag
+    aborted: Cell<bool>,
+    #[ignore_malloc_size_of = "Rc and channels are hard"]
+    abort_algorithms: DomRefCell<Vec<AbortAlgorithm>>,
+}
+
+impl AbortSignal {
+    fn new_inherited() -> AbortSignal {
+        AbortSignal {
+            eventtarget: EventTarget::new_inherited(),
+            aborted: Cell::new(false),
+            abort_algorithms: DomRefCell::new(vec![]),
+        }
+    }
+
+    pub fn new(global: &GlobalScope) -> DomRoot<AbortSignal> {
+        reflect_dom_object(
+            Box::new(AbortSignal::new_inherited()),
+            global,
+            AbortSignalBinding::Wrap,
+        )
+    }
+
+    /// https://dom.spec.whatwg.org/#abortsignal-add
+    #[allow(unrooted_must_root)]
+    pub fn add_abort_algorithm(&self, algorithm: AbortAlgorithm) {
+        // Step 1.
+        if self.aborted.get() {
+            return;
+        }
+
+        // Step 2.
+        self.abort_algorithms.borrow_mut().push(algorithm);
+    }
+
+    /// https://dom.spec.whatwg.org/#abortsignal-follow
+    #[allow(unrooted_must_root)]
+    pub fn follow(&self, parent: &AbortSignal) {
+        // Step 1.
+        if self.aborted.get() {
+            return;
+        }
+
+        // Step 2.
+        if parent.aborted.get() {
+            self.signal_abort();
+            return;
+        }
+
+        // Step 3.
+        parent.add_abort_algorithm(AbortAlgorithm::DomSignal(Dom::from_ref(self)));
+    }
+
+    /// https://dom.spec.whatwg.org/#abortsignal-signal-abort
+    #[allow(unrooted_must_root)]
+    pub fn signal_abort(&self) {
+        // Step 1.
+        if self.aborted.get() {
+            return;
+        }
+
+        // Step 2.
+        self.aborted.set(true);
+
+        // Steps 3-4.
+        let algorithms = mem::replace(&mut *self.abort_algorithms.borrow_mut(), vec![]);
+        for algorithm in algorithms {
+            algorithm.exec();
+        }
+
+        // Step 5.
+        self.upcast::<EventTarget>().fire_event(atom!("abort"));
+    }
+}
+
+impl AbortSignalMethods for AbortSignal {
+    // https://dom.spec.whatwg.org/#dom-abortsignal-aborted
+    fn Aborted(&self) -> bool {
+        self.aborted.get()
+    }
+
+    // https://dom.spec.whatwg.org/#dom-abortsignal-onabort
+    event_handler!(abort, GetOnabort, SetOnabort);
+}
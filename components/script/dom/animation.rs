@@ -0,0 +1,724 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::animationeffect::AnimationEffect;
+use crate::dom::animationtimeline::AnimationTimeline;
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::AnimationBinding;
+use crate::dom::bindings::codegen::Bindings::AnimationBinding::{
+    AnimationMethods, AnimationPlayState,
+};
+use crate::dom::bindings::codegen::Bindings::DocumentBinding::DocumentMethods;
+use crate::dom::bindings::codegen::Bindings::WindowBinding::WindowMethods;
+use crate::dom::bindings::error::{Error, ErrorResult, Fallible};
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::num::Finite;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject};
+use crate::dom::bindings::root::{DomRoot, MutNullableDom};
+use crate::dom::bindings::str::DOMString;
+use crate::dom::element::Element;
+use crate::dom::eventtarget::EventTarget;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::keyframeeffect::KeyframeEffect;
+use crate::dom::promise::Promise;
+use crate::dom::window::Window;
+use dom_struct::dom_struct;
+use js::jsapi::JSAutoCompartment;
+use std::cell::Cell;
+use std::rc::Rc;
+use style::properties::PropertyDeclaration;
+
+/// The task an animation waits for the next animation frame to run
+///
+/// https://drafts.csswg.org/web-animations/#pending-play-task
+/// https://drafts.csswg.org/web-animations/#pending-pause-task
+#[derive(Clone, Copy, JSTraceable, MallocSizeOf, PartialEq)]
+enum PendingTask {
+    Play,
+    Pause,
+}
+
+#[dom_struct]
+pub struct Animation {
+    eventtarget: EventTarget,
+    id: DomRefCell<DOMString>,
+    effect: MutNullableDom<AnimationEffect>,
+    timeline: MutNullableDom<AnimationTimeline>,
+    start_time: Cell<Option<f64>>,
+    hold_time: Cell<Option<f64>>,
+    /// https://drafts.csswg.org/web-animations/#previous-current-time
+    previous_current_time: Cell<Option<f64>>,
+    playback_rate: Cell<f64>,
+    pending_task: Cell<Option<PendingTask>>,
+    #[ignore_malloc_size_of = "Rc is hard"]
+    ready_promise: DomRefCell<Rc<Promise>>,
+    #[ignore_malloc_size_of = "Rc is hard"]
+    finished_promise: DomRefCell<Rc<Promise>>,
+}
+
+impl Animation {
+    fn new_inherited(
+        timeline: Option<&AnimationTimeline>,
+        ready_promise: Rc<Promise>,
+        finished_promise: Rc<Promise>,
+    ) -> Animation {
+        Animation {
+            eventtarget: EventTarget::new_inherited(),
+            id: DomRefCell::new(DOMString::new()),
+            effect: MutNullableDom::default(),
+            timeline: MutNullableDom::new(timeline),
+            start_time: Cell::new(None),
+            hold_time: Cell::new(None),
+            previous_current_time: Cell::new(None),
+            playback_rate: Cell::new(1.),
+            pending_task: Cell::new(None),
+            ready_promise: DomRefCell::new(ready_promise),
+            finished_promise: DomRefCell::new(finished_promise),
+        }
+    }
+
+    pub fn new(
+        window: &Window,
+        effect: Option<&AnimationEffect>,
+        timeline: Option<&AnimationTimeline>,
+    ) -> DomRoot<Animation> {
+        let ready_promise = new_promise(window);
+        let finished_promise = new_promise(window);
+        let animation = reflect_dom_object(
+            Box::new(Animation::new_inherited(
+                timeline,
+                ready_promise,
+                finished_promise,
+            )),
+            window,
+            AnimationBinding::Wrap,
+        );
+        // the ready promise starts out resolved
+        animation.resolve_ready_promise();
+        animation.SetEffect(effect);
+        animation
+    }
+
+    /// https://drafts.csswg.org/web-animations/#dom-animation-animation
+    pub fn Constructor(
+        window: &Window,
+        effect: Option<&AnimationEffect>,
+        timeline: Option<Option<&AnimationTimeline>>,
+    ) -> Fallible<DomRoot<Animation>> {
+        Ok(match timeline {
+            Some(timeline) => Animation::new(window, effect, timeline),
+            None => {
+                let timeline = window.Document().Timeline();
+                Animation::new(window, effect, Some(timeline.upcast()))
+            },
+        })
+    }
+
+    pub fn set_id(&self, id: DOMString) {
+        *self.id.borrow_mut() = id;
+    }
+
+    pub fn playback_rate(&self) -> f64 {
+        self.playback_rate.get()
+    }
+
+    fn timeline_time(&self) -> Option<f64> {
+        self.timeline.get()?.current_time()
+    }
+
+    /// https://drafts.csswg.org/web-animations/#effect-end
+    fn effect_end(&self) -> f64 {
+        self.effect.get().map_or(0., |effect| effect.end_time())
+    }
+
+    /// https://drafts.csswg.org/web-animations/#the-current-time-of-an-animation
+    pub fn current_time(&self) -> Option<f64> {
+        if let Some(hold_time) = self.hold_time.get() {
+            return Some(hold_time);
+        }
+        let start_time = self.start_time.get()?;
+        Some((self.timeline_time()? - start_time) * self.playback_rate.get())
+    }
+
+    /// https://drafts.csswg.org/web-animations/#play-states
+    fn play_state(&self) -> AnimationPlayState {
+        let current_time = self.current_time();
+        let pending_task = self.pending_task.get();
+        if current_time.is_none() && self.start_time.get().is_none() && pending_task.is_none() {
+            return AnimationPlayState::Idle;
+        }
+        if pending_task == Some(PendingTask::Pause) ||
+            (self.start_time.get().is_none() && pending_task != Some(PendingTask::Play))
+        {
+            return AnimationPlayState::Paused;
+        }
+        let rate = self.playback_rate.get();
+        match current_time {
+            Some(current_time)
+                if (rate > 0. && current_time >= self.effect_end()) ||
+                    (rate < 0. && current_time <= 0.) =>
+            {
+                AnimationPlayState::Finished
+            },
+            _ => AnimationPlayState::Running,
+        }
+    }
+
+    /// Whether the animation needs animation frames to move on
+    pub fn is_running(&self) -> bool {
+        self.pending_task.get().is_some() || self.play_state() == AnimationPlayState::Running
+    }
+
+    /// Whether the animation still matters to the document: it is running,
+    /// or its effect gives values to the properties it animates
+    pub fn is_relevant(&self) -> bool {
+        self.is_running() ||
+            self.effect
+                .get()
+                .map_or(false, |effect| effect.is_in_effect())
+    }
+
+    /// The element the effect of the animation targets, with the values it
+    /// gives to the properties of the element right now
+    pub fn effect_values(&self) -> Option<(DomRoot<Element>, Vec<PropertyDeclaration>)> {
+        self.effect
+            .get()?
+            .downcast::<KeyframeEffect>()?
+            .animated_declarations()
+    }
+
+    /// Lets the document know that it needs to update its animations
+    fn changed(&self) {
+        self.global()
+            .as_window()
+            .Document()
+            .register_animation(self);
+    }
+
+    /// Called when the timing of the effect changes behind the back of the
+    /// animation
+    pub fn effect_timing_changed(&self) {
+        self.update_finished_state(false);
+        self.changed();
+    }
+
+    /// Runs the pending play or pause task, if there is one, for the frame
+    /// the timeline is at, and updates the finished state
+    ///
+    /// https://drafts.csswg.org/web-animations/#update-animations-and-send-events
+    pub fn update(&self) {
+        if let Some(ready_time) = self.timeline_time() {
+            match self.pending_task.take() {
+                Some(PendingTask::Play) => self.finish_pending_play_task(ready_time),
+                Some(PendingTask::Pause) => self.finish_pending_pause_task(ready_time),
+                None => {},
+            }
+        }
+        self.update_finished_state(false);
+    }
+
+    fn resolve_ready_promise(&self) {
+        let promise = self.ready_promise.borrow().clone();
+        promise.resolve_native(&DomRoot::from_ref(self));
+    }
+
+    /// https://drafts.csswg.org/web-animations/#silently-set-the-current-time
+    fn silently_set_current_time(&self, seek_time: f64) {
+        let rate = self.playback_rate.get();
+        match self.timeline_time() {
+            Some(timeline_time)
+                if self.hold_time.get().is_none() &&
+                    self.start_time.get().is_some() &&
+                    rate != 0. =>
+            {
+                self.start_time.set(Some(timeline_time - seek_time / rate));
+            },
+            timeline_time => {
+                self.hold_time.set(Some(seek_time));
+                if timeline_time.is_none() {
+                    self.start_time.set(None);
+                }
+            },
+        }
+        self.previous_current_time.set(None);
+    }
+
+    /// https://drafts.csswg.org/web-animations/#playing-an-animation-section
+    ///
+    /// This always rewinds finished animations.
+    fn play_an_animation(&self) -> ErrorResult {
+        // Step 1
+        let aborted_pause = self.pending_task.get() == Some(PendingTask::Pause);
+
+        // Step 2
+        let mut has_pending_ready_promise = false;
+
+        // Step 3
+        let rate = self.playback_rate.get();
+        let current_time = self.current_time();
+        let end = self.effect_end();
+        let seek_time = if rate > 0. && current_time.map_or(true, |time| time < 0. || time >= end) {
+            Some(0.)
+        } else if rate < 0. && current_time.map_or(true, |time| time <= 0. || time > end) {
+            if end.is_infinite() {
+                return Err(Error::InvalidState);
+            }
+            Some(end)
+        } else if rate == 0. && current_time.is_none() {
+            Some(0.)
+        } else {
+            None
+        };
+
+        // Step 4
+        if let Some(seek_time) = seek_time {
+            self.hold_time.set(Some(seek_time));
+        }
+
+        // Step 5
+        if self.hold_time.get().is_some() {
+            self.start_time.set(None);
+        }
+
+        // Step 6
+        if self.pending_task.take().is_some() {
+            has_pending_ready_promise = true;
+        }
+
+        // Step 7
+        if self.hold_time.get().is_none() && seek_time.is_none() && !aborted_pause {
+            return Ok(());
+        }
+
+        // Step 8
+        if !has_pending_ready_promise {
+            *self.ready_promise.borrow_mut() = new_promise(self.global().as_window());
+        }
+
+        // Step 9
+        self.pending_task.set(Some(PendingTask::Play));
+
+        // Step 10
+        self.update_finished_state(false);
+        self.changed();
+        Ok(())
+    }
+
+    /// https://drafts.csswg.org/web-animations/#pending-play-task
+    fn finish_pending_play_task(&self, ready_time: f64) {
+        let rate = self.playback_rate.get();
+        if let Some(hold_time) = self.hold_time.get() {
+            if rate == 0. {
+                self.start_time.set(Some(ready_time));
+            } else {
+                self.start_time.set(Some(ready_time - hold_time / rate));
+                self.hold_time.set(None);
+            }
+        }
+        self.resolve_ready_promise();
+        self.update_finished_state(false);
+    }
+
+    /// https://drafts.csswg.org/web-animations/#pending-pause-task
+    fn finish_pending_pause_task(&self, ready_time: f64) {
+        if let (Some(start_time), None) = (self.start_time.get(), self.hold_time.get()) {
+            self.hold_time
+                .set(Some((ready_time - start_time) * self.playback_rate.get()));
+        }
+        self.start_time.set(None);
+        self.resolve_ready_promise();
+        self.update_finished_state(false);
+    }
+
+    /// https://drafts.csswg.org/web-animations/#updating-the-finished-state
+    ///
+    /// The finish notification steps run right away rather than from a
+    /// microtask, the finish event is queued from them.
+    fn update_finished_state(&self, did_seek: bool) {
+        let rate = self.playback_rate.get();
+        let end = self.effect_end();
+
+        // Step 1
+        let unconstrained_current_time = if did_seek {
+            self.current_time()
+        } else {
+            match (self.timeline_time(), self.start_time.get()) {
+                (Some(timeline_time), Some(start_time)) => {
+                    Some((timeline_time - start_time) * rate)
+                },
+                _ => None,
+            }
+        };
+
+        // Step 2
+        if let Some(unconstrained_current_time) = unconstrained_current_time {
+            if self.start_time.get().is_some() && self.pending_task.get() != Some(PendingTask::Play)
+            {
+                let previous_current_time = self.previous_current_time.get();
+                if rate > 0. && unconstrained_current_time >= end {
+                    self.hold_time.set(Some(if did_seek {
+                        unconstrained_current_time
+                    } else {
+                        previous_current_time.map_or(end, |time| time.max(end))
+                    }));
+                } else if rate < 0. && unconstrained_current_time <= 0. {
+                    self.hold_time.set(Some(if did_seek {
+                        unconstrained_current_time
+                    } else {
+                        previous_current_time.map_or(0., |time| time.min(0.))
+                    }));
+                } else if rate != 0. {
+                    if let (true, Some(hold_time), Some(timeline_time)) =
+                        (did_seek, self.hold_time.get(), self.timeline_time())
+                    {
+                        self.start_time.set(Some(timeline_time - hold_time / rate));
+                    }
+                    self.hold_time.set(None);
+                }
+            }
+        }
+
+        // Step 3
+        self.previous_current_time.set(self.current_time());
+
+        // Step 4
+        let finished = self.play_state() == AnimationPlayState::Finished;
+        let finished_promise = self.finished_promise.borrow().clone();
+
+        // Step 5
+        if finished && !finished_promise.is_fulfilled() {
+            finished_promise.resolve_native(&DomRoot::from_ref(self));
+            let window = self.global();
+            let window = window.as_window();
+            window
+                .task_manager()
+                .dom_manipulation_task_source()
+                .queue_simple_event(self.upcast(), atom!("finish"), window);
+        }
+
+        // Step 6
+        if !finished && finished_promise.is_fulfilled() {
+            *self.finished_promise.borrow_mut() = new_promise(self.global().as_window());
+        }
+    }
+
+    /// https://drafts.csswg.org/web-animations/#setting-the-current-time-of-an-animation
+    fn set_the_current_time(&self, seek_time: f64) {
+        // Step 1
+        self.silently_set_current_time(seek_time);
+
+        // Step 2
+        if self.pending_task.get() == Some(PendingTask::Pause) {
+            self.hold_time.set(Some(seek_time));
+            self.start_time.set(None);
+            self.pending_task.set(None);
+            self.resolve_ready_promise();
+        }
+
+        // Step 3
+        self.update_finished_state(true);
+        self.changed();
+    }
+
+    /// https://drafts.csswg.org/web-animations/#set-the-playback-rate
+    fn set_the_playback_rate(&self, rate: f64) {
+        let previous_time = self.current_time();
+        self.playback_rate.set(rate);
+        if let Some(previous_time) = previous_time {
+            self.set_the_current_time(previous_time);
+        }
+    }
+}
+
+/// A new promise in the realm of `window`, whether or not script is running
+#[allow(unsafe_code)]
+fn new_promise(window: &Window) -> Rc<Promise> {
+    let global = window.upcast::<GlobalScope>();
+    let _ac = JSAutoCompartment::new(global.get_cx(), window.reflector().get_jsobject().get());
+    unsafe { Promise::new_in_current_compartment(global) }
+}
+
+impl AnimationMethods for Animation {
+    /// https://drafts.csswg.org/web-animations/#dom-animation-id
+    fn Id(&self) -> DOMString {
+        self.id.borrow().clone()
+    }
+
+    /// https://drafts.csswg.org/web-animations/#dom-animation-id
+    fn SetId(&self, id: DOMString) {
+        self.set_id(id);
+    }
+
+    /// https://drafts.csswg.org/web-animations/#dom-animation-effect
+    fn GetEffect(&self) -> Option<DomRoot<AnimationEffect>> {
+        self.effect.get()
+    }
+
+    /// https://drafts.csswg.org/web-animations/#setting-the-associated-effect
+    fn SetEffect(&self, effect: Option<&AnimationEffect>) {
+        // Step 1
+        let old_effect = self.effect.get();
+
+        // Step 2
+        if old_effect.as_ref().map(|effect| &**effect as *const _) ==
+            effect.map(|effect| effect as *const _)
+        {
+            return;
+        }
+
+        // Step 5
+        if let Some(effect) = effect {
+            if let Some(previous_animation) = effect.animation() {
+                previous_animation.SetEffect(None);
+            }
+            effect.set_animation(Some(self));
+        }
+
+        // Step 6
+        if let Some(old_effect) = old_effect {
+            old_effect.set_animation(None);
+        }
+        self.effect.set(effect);
+
+        // Step 7
+        self.update_finished_state(false);
+        self.changed();
+    }
+
+    /// https://drafts.csswg.org/web-animations/#dom-animation-timeline
+    fn GetTimeline(&self) -> Option<DomRoot<AnimationTimeline>> {
+        self.timeline.get()
+    }
+
+    /// https://drafts.csswg.org/web-animations/#dom-animation-starttime
+    fn GetStartTime(&self) -> Option<Finite<f64>> {
+        self.start_time.get().map(Finite::wrap)
+    }
+
+    /// https://drafts.csswg.org/web-animations/#setting-the-start-time-of-an-animation
+    fn SetStartTime(&self, start_time: Option<Finite<f64>>) {
+        let new_start_time = start_time.map(|time| *time);
+
+        // Step 2
+        if self.timeline_time().is_none() && new_start_time.is_some() {
+            self.hold_time.set(None);
+        }
+
+        // Step 3
+        let previous_current_time = self.current_time();
+
+        // Step 5
+        self.start_time.set(new_start_time);
+
+        // Step 6
+        if new_start_time.is_some() {
+            if self.playback_rate.get() != 0. {
+                self.hold_time.set(None);
+            }
+        } else {
+            self.hold_time.set(previous_current_time);
+        }
+
+        // Step 7
+        if self.pending_task.take().is_some() {
+            self.resolve_ready_promise();
+        }
+
+        // Step 8
+        self.update_finished_state(true);
+        self.changed();
+    }
+
+    /// https://drafts.csswg.org/web-animations/#dom-animation-currenttime
+    fn GetCurrentTime(&self) -> Option<Finite<f64>> {
+        self.current_time().map(Finite::wrap)
+    }
+
+    /// https://drafts.csswg.org/web-animations/#setting-the-current-time-of-an-animation
+    fn SetCurrentTime(&self, current_time: Option<Finite<f64>>) -> ErrorResult {
+        match current_time {
+            Some(current_time) => {
+                self.set_the_current_time(*current_time);
+                Ok(())
+            },
+            None if self.current_time().is_some() => Err(Error::Type(
+                "the current time of a playing animation can't be unset".to_owned(),
+            )),
+            None => Ok(()),
+        }
+    }
+
+    /// https://drafts.csswg.org/web-animations/#dom-animation-playbackrate
+    fn PlaybackRate(&self) -> Finite<f64> {
+        Finite::wrap(self.playback_rate.get())
+    }
+
+    /// https://drafts.csswg.org/web-animations/#dom-animation-playbackrate
+    fn SetPlaybackRate(&self, rate: Finite<f64>) {
+        self.set_the_playback_rate(*rate);
+    }
+
+    /// https://drafts.csswg.org/web-animations/#dom-animation-playstate
+    fn PlayState(&self) -> AnimationPlayState {
+        self.play_state()
+    }
+
+    /// https://drafts.csswg.org/web-animations/#dom-animation-pending
+    fn Pending(&self) -> bool {
+        self.pending_task.get().is_some()
+    }
+
+    /// https://drafts.csswg.org/web-animations/#dom-animation-ready
+    fn Ready(&self) -> Rc<Promise> {
+        self.ready_promise.borrow().clone()
+    }
+
+    /// https://drafts.csswg.org/web-animations/#dom-animation-finished
+    fn Finished(&self) -> Rc<Promise> {
+        self.finished_promise.borrow().clone()
+    }
+
+    // https://drafts.csswg.org/web-animations/#dom-animation-onfinish
+    event_handler!(finish, GetOnfinish, SetOnfinish);
+
+    // https://drafts.csswg.org/web-animations/#dom-animation-oncancel
+    event_handler!(cancel, GetOncancel, SetOncancel);
+
+    /// https://drafts.csswg.org/web-animations/#canceling-an-animation-section
+    fn Cancel(&self) {
+        // Step 1
+        if self.play_state() != AnimationPlayState::Idle {
+            let window = self.global();
+            let window = window.as_window();
+
+            // https://drafts.csswg.org/web-animations/#reset-an-animations-pending-tasks
+            if self.pending_task.take().is_some() {
+                self.ready_promise.borrow().reject_error(Error::Abort);
+                *self.ready_promise.borrow_mut() = new_promise(window);
+                self.resolve_ready_promise();
+            }
+
+            self.finished_promise.borrow().reject_error(Error::Abort);
+            *self.finished_promise.borrow_mut() = new_promise(window);
+
+            window
+                .task_manager()
+                .dom_manipulation_task_source()
+                .queue_simple_event(self.upcast(), atom!("cancel"), window);
+        }
+
+        // Step 2
+        self.hold_time.set(None);
+
+        // Step 3
+        self.start_time.set(None);
+        self.changed();
+    }
+
+    /// https://drafts.csswg.org/web-animations/#finishing-an-animation-section
+    fn Finish(&self) -> ErrorResult {
+        // Step 1
+        let rate = self.playback_rate.get();
+        let end = self.effect_end();
+        if rate == 0. || (rate > 0. && end.is_infinite()) {
+            return Err(Error::InvalidState);
+        }
+
+        // Step 3
+        let limit = if rate > 0. { end } else { 0. };
+
+        // Step 4
+        self.silently_set_current_time(limit);
+
+        // Step 5
+        if self.start_time.get().is_none() {
+            if let Some(timeline_time) = self.timeline_time() {
+                self.start_time.set(Some(timeline_time - limit / rate));
+            }
+        }
+
+        // Step 6
+        if self.pending_task.get() == Some(PendingTask::Pause) && self.start_time.get().is_some() {
+            self.hold_time.set(None);
+            self.pending_task.set(None);
+            self.resolve_ready_promise();
+        }
+
+        // Step 7
+        if self.pending_task.get() == Some(PendingTask::Play) && self.start_time.get().is_some() {
+            self.pending_task.set(None);
+            self.resolve_ready_promise();
+        }
+
+        // Step 8
+        self.update_finished_state(true);
+        self.changed();
+        Ok(())
+    }
+
+    /// https://drafts.csswg.org/web-animations/#dom-animation-play
+    fn Play(&self) -> ErrorResult {
+        self.play_an_animation()
+    }
+
+    /// https://drafts.csswg.org/web-animations/#pausing-an-animation-section
+    fn Pause(&self) -> ErrorResult {
+        // Step 1
+        if self.pending_task.get() == Some(PendingTask::Pause) ||
+            self.play_state() == AnimationPlayState::Paused
+        {
+            return Ok(());
+        }
+
+        // Step 2
+        if self.current_time().is_none() {
+            if self.playback_rate.get() >= 0. {
+                self.hold_time.set(Some(0.));
+            } else {
+                let end = self.effect_end();
+                if end.is_infinite() {
+                    return Err(Error::InvalidState);
+                }
+                self.hold_time.set(Some(end));
+            }
+        }
+
+        // Step 3
+        let has_pending_ready_promise = self.pending_task.take() == Some(PendingTask::Play);
+
+        // Step 4
+        if !has_pending_ready_promise {
+            *self.ready_promise.borrow_mut() = new_promise(self.global().as_window());
+        }
+
+        // Step 5
+        self.pending_task.set(Some(PendingTask::Pause));
+
+        // Step 6
+        self.update_finished_state(false);
+        self.changed();
+        Ok(())
+    }
+
+    /// https://drafts.csswg.org/web-animations/#reversing-an-animation-section
+    ///
+    /// The new playback rate applies right away, not at the next frame.
+    fn Reverse(&self) -> ErrorResult {
+        // Step 1
+        if self.timeline_time().is_none() {
+            return Err(Error::InvalidState);
+        }
+
+        // Step 2
+        let original_rate = self.playback_rate.get();
+        self.set_the_playback_rate(-original_rate);
+
+        // Step 3
+        let result = self.play_an_animation();
+        if result.is_err() {
+            self.set_the_playback_rate(original_rate);
+        }
+        result
+    }
+}
@@ -0,0 +1,420 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::animation::Animation;
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::AnimationEffectBinding::{
+    AnimationEffectMethods, ComputedEffectTiming, EffectTiming, FillMode, OptionalEffectTiming,
+    PlaybackDirection,
+};
+use crate::dom::bindings::codegen::Bindings::WindowBinding::WindowMethods;
+use crate::dom::bindings::codegen::UnionTypes::UnrestrictedDoubleOrString;
+use crate::dom::bindings::error::{Error, Fallible};
+use crate::dom::bindings::num::Finite;
+use crate::dom::bindings::reflector::{DomObject, Reflector};
+use crate::dom::bindings::root::{DomRoot, MutNullableDom};
+use crate::dom::bindings::str::DOMString;
+use crate::dom::window::Window;
+use cssparser::{Parser, ParserInput};
+use dom_struct::dom_struct;
+use std::f64;
+use style::animation::timing_function_output;
+use style::context::QuirksMode;
+use style::parser::{Parse, ParserContext};
+use style::stylesheets::CssRuleType;
+use style::values::computed::TimingFunction;
+use style::values::generics::easing::TimingKeyword;
+use style::values::specified::TimingFunction as SpecifiedTimingFunction;
+use style_traits::{ParsingMode, ToCss};
+
+/// https://drafts.csswg.org/web-animations/#animation-effect-phases-and-states
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Phase {
+    Before,
+    Active,
+    After,
+}
+
+/// The timing properties of an animation effect
+///
+/// https://drafts.csswg.org/web-animations/#timing-model
+#[derive(Clone, JSTraceable, MallocSizeOf)]
+pub struct Timing {
+    pub delay: f64,
+    pub end_delay: f64,
+    pub fill: FillMode,
+    pub iteration_start: f64,
+    pub iterations: f64,
+    /// The iteration duration, none for `auto`
+    pub duration: Option<f64>,
+    pub direction: PlaybackDirection,
+    pub easing: TimingFunction,
+}
+
+impl Timing {
+    /// The timing of an effect that runs once, right away, for `duration`
+    pub fn new(duration: Option<f64>) -> Timing {
+        Timing {
+            delay: 0.,
+            end_delay: 0.,
+            fill: FillMode::Auto,
+            iteration_start: 0.,
+            iterations: 1.,
+            duration,
+            direction: PlaybackDirection::Normal,
+            easing: TimingFunction::Keyword(TimingKeyword::Linear),
+        }
+    }
+
+    /// The timing given as the options of `new KeyframeEffect()` or
+    /// `Element.animate()`, which may be just the duration
+    pub fn from_options(window: &Window, options: Option<&EffectTiming>) -> Fallible<Timing> {
+        let mut timing = Timing::new(None);
+        if let Some(options) = options {
+            timing.update(
+                window,
+                &OptionalEffectTiming {
+                    delay: Some(options.delay),
+                    endDelay: Some(options.endDelay),
+                    fill: Some(options.fill),
+                    iterationStart: Some(options.iterationStart),
+                    iterations: Some(options.iterations),
+                    duration: Some(match options.duration {
+                        UnrestrictedDoubleOrString::UnrestrictedDouble(duration) => {
+                            UnrestrictedDoubleOrString::UnrestrictedDouble(duration)
+                        },
+                        UnrestrictedDoubleOrString::String(ref duration) => {
+                            UnrestrictedDoubleOrString::String(duration.clone())
+                        },
+                    }),
+                    direction: Some(options.direction),
+                    easing: Some(options.easing.clone()),
+                },
+            )?;
+        }
+        Ok(timing)
+    }
+
+    /// https://drafts.csswg.org/web-animations/#update-the-timing-properties-of-an-animation-effect
+    ///
+    /// Nothing changes if any of the new values is invalid.
+    pub fn update(&mut self, window: &Window, input: &OptionalEffectTiming) -> Fallible<()> {
+        // Step 1
+        if let Some(iteration_start) = input.iterationStart {
+            if *iteration_start < 0. {
+                return Err(Error::Type("iterationStart can't be negative".to_owned()));
+            }
+        }
+        if let Some(iterations) = input.iterations {
+            if !(iterations >= 0.) {
+                return Err(Error::Type(format!(
+                    "{} is not a valid iteration count",
+                    iterations
+                )));
+            }
+        }
+        let duration = match input.duration {
+            Some(UnrestrictedDoubleOrString::UnrestrictedDouble(duration)) => {
+                if !(duration >= 0.) {
+                    return Err(Error::Type(format!("{} is not a valid duration", duration)));
+                }
+                Some(Some(duration))
+            },
+            Some(UnrestrictedDoubleOrString::String(ref duration)) => {
+                if &**duration != "auto" {
+                    return Err(Error::Type(format!("{} is not a valid duration", duration)));
+                }
+                Some(None)
+            },
+            None => None,
+        };
+        let easing = match input.easing {
+            Some(ref easing) => Some(parse_easing(window, easing)?),
+            None => None,
+        };
+
+        // Step 2
+        if let Some(delay) = input.delay {
+            self.delay = *delay;
+        }
+        if let Some(end_delay) = input.endDelay {
+            self.end_delay = *end_delay;
+        }
+        if let Some(fill) = input.fill {
+            self.fill = fill;
+        }
+        if let Some(iteration_start) = input.iterationStart {
+            self.iteration_start = *iteration_start;
+        }
+        if let Some(iterations) = input.iterations {
+            self.iterations = iterations;
+        }
+        if let Some(duration) = duration {
+            self.duration = duration;
+        }
+        if let Some(direction) = input.direction {
+            self.direction = direction;
+        }
+        if let Some(easing) = easing {
+            self.easing = easing;
+        }
+        Ok(())
+    }
+
+    /// https://drafts.csswg.org/web-animations/#iteration-duration
+    pub fn iteration_duration(&self) -> f64 {
+        self.duration.unwrap_or(0.)
+    }
+
+    /// https://drafts.csswg.org/web-animations/#active-duration
+    pub fn active_duration(&self) -> f64 {
+        let iteration_duration = self.iteration_duration();
+        if iteration_duration == 0. || self.iterations == 0. {
+            return 0.;
+        }
+        iteration_duration * self.iterations
+    }
+
+    /// https://drafts.csswg.org/web-animations/#end-time
+    pub fn end_time(&self) -> f64 {
+        (self.delay + self.active_duration() + self.end_delay).max(0.)
+    }
+
+    /// https://drafts.csswg.org/web-animations/#animation-effect-phases-and-states
+    ///
+    /// A negative playback rate makes the effect go backwards.
+    pub fn phase(&self, local_time: f64, playback_rate: f64) -> Phase {
+        let end_time = self.end_time();
+        let before_active_boundary = self.delay.min(end_time).max(0.);
+        let active_after_boundary = (self.delay + self.active_duration()).min(end_time).max(0.);
+        if local_time < before_active_boundary ||
+            (playback_rate < 0. && local_time == before_active_boundary)
+        {
+            Phase::Before
+        } else if local_time > active_after_boundary ||
+            (playback_rate >= 0. && local_time == active_after_boundary)
+        {
+            Phase::After
+        } else {
+            Phase::Active
+        }
+    }
+
+    /// https://drafts.csswg.org/web-animations/#calculating-the-active-time
+    ///
+    /// A fill mode of `auto` fills nothing, as keyframe effects are the only
+    /// effects there are.
+    pub fn active_time(&self, local_time: f64, playback_rate: f64) -> Option<f64> {
+        let fills_backwards = self.fill == FillMode::Backwards || self.fill == FillMode::Both;
+        let fills_forwards = self.fill == FillMode::Forwards || self.fill == FillMode::Both;
+        match self.phase(local_time, playback_rate) {
+            Phase::Before if fills_backwards => Some((local_time - self.delay).max(0.)),
+            Phase::Active => Some(local_time - self.delay),
+            Phase::After if fills_forwards => Some(
+                (local_time - self.delay)
+                    .min(self.active_duration())
+                    .max(0.),
+            ),
+            _ => None,
+        }
+    }
+
+    /// The transformed progress and the current iteration at `local_time`,
+    /// none if the effect isn't in effect then
+    ///
+    /// https://drafts.csswg.org/web-animations/#core-animation-effect-calculations
+    pub fn progress(&self, local_time: f64, playback_rate: f64) -> Option<(f64, f64)> {
+        let active_time = self.active_time(local_time, playback_rate)?;
+        let phase = self.phase(local_time, playback_rate);
+        let iteration_duration = self.iteration_duration();
+
+        // https://drafts.csswg.org/web-animations/#calculating-the-overall-progress
+        let overall_progress = if iteration_duration == 0. {
+            if phase == Phase::Before {
+                0.
+            } else {
+                self.iterations
+            }
+        } else {
+            active_time / iteration_duration
+        } + self.iteration_start;
+
+        // https://drafts.csswg.org/web-animations/#calculating-the-simple-iteration-progress
+        let mut simple_iteration_progress = if overall_progress.is_infinite() {
+            self.iteration_start % 1.
+        } else {
+            overall_progress % 1.
+        };
+        if simple_iteration_progress == 0. &&
+            phase != Phase::Before &&
+            active_time == self.active_duration() &&
+            self.iterations != 0.
+        {
+            simple_iteration_progress = 1.;
+        }
+
+        // https://drafts.csswg.org/web-animations/#calculating-the-current-iteration
+        let current_iteration = if phase == Phase::After && self.iterations.is_infinite() {
+            f64::INFINITY
+        } else if simple_iteration_progress == 1. {
+            overall_progress.floor() - 1.
+        } else {
+            overall_progress.floor()
+        };
+
+        // https://drafts.csswg.org/web-animations/#calculating-the-directed-progress
+        let alternate_forwards = current_iteration.is_infinite() || current_iteration % 2. == 0.;
+        let forwards = match self.direction {
+            PlaybackDirection::Normal => true,
+            PlaybackDirection::Reverse => false,
+            PlaybackDirection::Alternate => alternate_forwards,
+            PlaybackDirection::Alternate_reverse => !alternate_forwards,
+        };
+        let directed_progress = if forwards {
+            simple_iteration_progress
+        } else {
+            1. - simple_iteration_progress
+        };
+
+        // https://drafts.csswg.org/web-animations/#calculating-the-transformed-progress
+        let transformed_progress =
+            timing_function_output(&self.easing, directed_progress, epsilon(iteration_duration));
+
+        Some((transformed_progress, current_iteration))
+    }
+}
+
+/// The precision easing functions are solved with for an iteration of
+/// `duration` milliseconds, which gets finer the longer it lasts
+fn epsilon(duration: f64) -> f64 {
+    1. / (200. * (duration / 1000.).max(1.))
+}
+
+/// https://drafts.csswg.org/web-animations/#parsing-the-easing-property
+pub fn parse_easing(window: &Window, easing: &str) -> Fallible<TimingFunction> {
+    let url = window.Document().url();
+    let context = ParserContext::new_for_cssom(
+        &url,
+        Some(CssRuleType::Style),
+        ParsingMode::DEFAULT,
+        QuirksMode::NoQuirks,
+        None,
+        None,
+    );
+    let mut input = ParserInput::new(easing);
+    let mut parser = Parser::new(&mut input);
+    match parser.parse_entirely(|parser| SpecifiedTimingFunction::parse(&context, parser)) {
+        Ok(easing) => Ok(easing.to_computed_value_without_context()),
+        Err(_) => Err(Error::Type(format!("{} is not a valid easing", easing))),
+    }
+}
+
+#[dom_struct]
+pub struct AnimationEffect {
+    reflector_: Reflector,
+    timing: DomRefCell<Timing>,
+    /// The animation this effect is associated with
+    animation: MutNullableDom<Animation>,
+}
+
+impl AnimationEffect {
+    pub fn new_inherited(timing: Timing) -> AnimationEffect {
+        AnimationEffect {
+            reflector_: Reflector::new(),
+            timing: DomRefCell::new(timing),
+            animation: MutNullableDom::default(),
+        }
+    }
+
+    pub fn animation(&self) -> Option<DomRoot<Animation>> {
+        self.animation.get()
+    }
+
+    pub fn set_animation(&self, animation: Option<&Animation>) {
+        self.animation.set(animation);
+    }
+
+    pub fn end_time(&self) -> f64 {
+        self.timing.borrow().end_time()
+    }
+
+    /// https://drafts.csswg.org/web-animations/#local-time
+    pub fn local_time(&self) -> Option<f64> {
+        self.animation.get()?.current_time()
+    }
+
+    fn playback_rate(&self) -> f64 {
+        self.animation
+            .get()
+            .map_or(1., |animation| animation.playback_rate())
+    }
+
+    /// Whether the effect gives values to the properties it animates right
+    /// now
+    pub fn is_in_effect(&self) -> bool {
+        self.progress().is_some()
+    }
+
+    /// The transformed progress and current iteration of the effect right
+    /// now, see `Timing::progress`
+    pub fn progress(&self) -> Option<(f64, f64)> {
+        let local_time = self.local_time()?;
+        self.timing
+            .borrow()
+            .progress(local_time, self.playback_rate())
+    }
+}
+
+impl AnimationEffectMethods for AnimationEffect {
+    /// https://drafts.csswg.org/web-animations/#dom-animationeffect-gettiming
+    fn GetTiming(&self) -> EffectTiming {
+        let timing = self.timing.borrow();
+        EffectTiming {
+            delay: Finite::wrap(timing.delay),
+            endDelay: Finite::wrap(timing.end_delay),
+            fill: timing.fill,
+            iterationStart: Finite::wrap(timing.iteration_start),
+            iterations: timing.iterations,
+            duration: match timing.duration {
+                Some(duration) => UnrestrictedDoubleOrString::UnrestrictedDouble(duration),
+                None => UnrestrictedDoubleOrString::String(DOMString::from("auto")),
+            },
+            direction: timing.direction,
+            easing: DOMString::from(timing.easing.to_css_string()),
+        }
+    }
+
+    /// https://drafts.csswg.org/web-animations/#dom-animationeffect-getcomputedtiming
+    fn GetComputedTiming(&self) -> ComputedEffectTiming {
+        let mut parent = self.GetTiming();
+        let timing = self.timing.borrow();
+        if parent.fill == FillMode::Auto {
+            parent.fill = FillMode::None;
+        }
+        parent.duration =
+            UnrestrictedDoubleOrString::UnrestrictedDouble(timing.iteration_duration());
+        let local_time = self.local_time();
+        let progress =
+            local_time.and_then(|local_time| timing.progress(local_time, self.playback_rate()));
+        ComputedEffectTiming {
+            parent,
+            endTime: Some(timing.end_time()),
+            activeDuration: Some(timing.active_duration()),
+            localTime: Some(local_time.map(Finite::wrap)),
+            progress: Some(progress.map(|(progress, _)| Finite::wrap(progress))),
+            currentIteration: Some(progress.map(|(_, current_iteration)| current_iteration)),
+        }
+    }
+
+    /// https://drafts.csswg.org/web-animations/#dom-animationeffect-updatetiming
+    fn UpdateTiming(&self, timing: &OptionalEffectTiming) -> Fallible<()> {
+        self.timing
+            .borrow_mut()
+            .update(self.global().as_window(), timing)?;
+        if let Some(animation) = self.animation.get() {
+            animation.effect_timing_changed();
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,41 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::AnimationTimelineBinding::AnimationTimelineMethods;
+use crate::dom::bindings::num::Finite;
+use crate::dom::bindings::reflector::Reflector;
+use dom_struct::dom_struct;
+use std::cell::Cell;
+
+#[dom_struct]
+pub struct AnimationTimeline {
+    reflector_: Reflector,
+    /// The time animations were last updated for, none while the timeline
+    /// is inactive
+    current_time: Cell<Option<f64>>,
+}
+
+impl AnimationTimeline {
+    pub fn new_inherited(current_time: Option<f64>) -> AnimationTimeline {
+        AnimationTimeline {
+            reflector_: Reflector::new(),
+            current_time: Cell::new(current_time),
+        }
+    }
+
+    pub fn current_time(&self) -> Option<f64> {
+        self.current_time.get()
+    }
+
+    pub fn set_current_time(&self, current_time: Option<f64>) {
+        self.current_time.set(current_time);
+    }
+}
+
+impl AnimationTimelineMethods for AnimationTimeline {
+    /// https://drafts.csswg.org/web-animations/#dom-animationtimeline-currenttime
+    fn GetCurrentTime(&self) -> Option<Finite<f64>> {
+        self.current_time.get().map(Finite::wrap)
+    }
+}
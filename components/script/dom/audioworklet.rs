@@ -0,0 +1,33 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::AudioWorkletBinding;
+use crate::dom::bindings::reflector::reflect_dom_object;
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::window::Window;
+use crate::dom::worklet::Worklet;
+use crate::dom::workletglobalscope::WorkletGlobalScopeType;
+use dom_struct::dom_struct;
+
+/// <https://webaudio.github.io/web-audio-api/#audioworklet>
+#[dom_struct]
+pub struct AudioWorklet {
+    worklet: Worklet,
+}
+
+impl AudioWorklet {
+    fn new_inherited(window: &Window, sample_rate: f32) -> AudioWorklet {
+        AudioWorklet {
+            worklet: Worklet::new_inherited(window, WorkletGlobalScopeType::Audio(sample_rate)),
+        }
+    }
+
+    pub fn new(window: &Window, sample_rate: f32) -> DomRoot<AudioWorklet> {
+        reflect_dom_object(
+            Box::new(AudioWorklet::new_inherited(window, sample_rate)),
+            window,
+            AudioWorkletBinding::Wrap,
+        )
+    }
+}
@@ -0,0 +1,164 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::callback::CallbackContainer;
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::AudioParamDescriptorBinding::AudioParamDescriptor;
+use crate::dom::bindings::codegen::Bindings::AudioWorkletGlobalScopeBinding;
+use crate::dom::bindings::codegen::Bindings::AudioWorkletGlobalScopeBinding::AudioWorkletGlobalScopeMethods;
+use crate::dom::bindings::codegen::Bindings::VoidFunctionBinding::VoidFunction;
+use crate::dom::bindings::conversions::get_property;
+use crate::dom::bindings::conversions::get_property_jsval;
+use crate::dom::bindings::error::Error;
+use crate::dom::bindings::error::Fallible;
+use crate::dom::bindings::num::Finite;
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::str::DOMString;
+use crate::dom::worklet::WorkletExecutor;
+use crate::dom::workletglobalscope::WorkletGlobalScope;
+use crate::dom::workletglobalscope::WorkletGlobalScopeInit;
+use dom_struct::dom_struct;
+use js::jsapi::Heap;
+use js::jsapi::IsConstructor;
+use js::jsval::JSVal;
+use js::jsval::ObjectValue;
+use js::jsval::UndefinedValue;
+use js::rust::Runtime;
+use msg::constellation_msg::PipelineId;
+use servo_atoms::Atom;
+use servo_url::ServoUrl;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+/// <https://webaudio.github.io/web-audio-api/#audioworkletglobalscope>
+#[dom_struct]
+pub struct AudioWorkletGlobalScope {
+    /// The worklet global for this object
+    worklet_global: WorkletGlobalScope,
+    /// <https://webaudio.github.io/web-audio-api/#node-name-to-processor-constructor-map>
+    #[ignore_malloc_size_of = "mozjs"]
+    processor_constructors: DomRefCell<HashMap<Atom, Box<Heap<JSVal>>>>,
+    /// <https://webaudio.github.io/web-audio-api/#node-name-to-parameter-descriptor-map>
+    ///
+    /// This lives on the BaseAudioContext in the spec, but nothing reads it
+    /// until AudioWorkletNode is supported.
+    #[ignore_malloc_size_of = "mozjs"]
+    parameter_descriptors: DomRefCell<HashMap<Atom, Vec<AudioParamDescriptor>>>,
+    /// <https://webaudio.github.io/web-audio-api/#dom-audioworkletglobalscope-samplerate>
+    sample_rate: f32,
+}
+
+impl AudioWorkletGlobalScope {
+    #[allow(unsafe_code)]
+    pub fn new(
+        runtime: &Runtime,
+        pipeline_id: PipelineId,
+        base_url: ServoUrl,
+        executor: WorkletExecutor,
+        init: &WorkletGlobalScopeInit,
+        sample_rate: f32,
+    ) -> DomRoot<AudioWorkletGlobalScope> {
+        debug!(
+            "Creating audio worklet global scope for pipeline {}.",
+            pipeline_id
+        );
+        let global = Box::new(AudioWorkletGlobalScope {
+            worklet_global: WorkletGlobalScope::new_inherited(
+                pipeline_id,
+                base_url,
+                executor,
+                init,
+            ),
+            processor_constructors: Default::default(),
+            parameter_descriptors: Default::default(),
+            sample_rate,
+        });
+        unsafe { AudioWorkletGlobalScopeBinding::Wrap(runtime.cx(), global) }
+    }
+}
+
+impl AudioWorkletGlobalScopeMethods for AudioWorkletGlobalScope {
+    // https://webaudio.github.io/web-audio-api/#dom-audioworkletglobalscope-registerprocessor
+    #[allow(unsafe_code)]
+    fn RegisterProcessor(&self, name: DOMString, processor_ctor: Rc<VoidFunction>) -> Fallible<()> {
+        let name = Atom::from(name);
+        let cx = self.worklet_global.get_cx();
+        rooted!(in(cx) let processor_obj = processor_ctor.callback_holder().get());
+
+        debug!("Registering audio worklet processor {}.", name);
+
+        // Steps 1-2.
+        if name.is_empty() || self.processor_constructors.borrow().contains_key(&name) {
+            return Err(Error::NotSupported);
+        }
+
+        // Step 3.
+        if unsafe { !IsConstructor(processor_obj.get()) } {
+            return Err(Error::Type(String::from("Not a constructor.")));
+        }
+
+        // Steps 4-5.
+        rooted!(in(cx) let mut prototype = UndefinedValue());
+        unsafe {
+            get_property_jsval(
+                cx,
+                processor_obj.handle(),
+                "prototype",
+                prototype.handle_mut(),
+            )?;
+        }
+        if !prototype.is_object() {
+            return Err(Error::Type(String::from("Prototype is not an object.")));
+        }
+
+        // Steps 6-7.
+        let descriptors: Vec<AudioParamDescriptor> =
+            unsafe { get_property(cx, processor_obj.handle(), "parameterDescriptors", ()) }?
+                .unwrap_or_default();
+        for (i, descriptor) in descriptors.iter().enumerate() {
+            if descriptors[..i]
+                .iter()
+                .any(|other| other.name == descriptor.name)
+            {
+                return Err(Error::NotSupported);
+            }
+            if *descriptor.defaultValue < *descriptor.minValue ||
+                *descriptor.defaultValue > *descriptor.maxValue
+            {
+                return Err(Error::InvalidState);
+            }
+        }
+
+        // Step 8.
+        let constructor = Box::new(Heap::default());
+        constructor.set(ObjectValue(processor_obj.get()));
+        self.processor_constructors
+            .borrow_mut()
+            .insert(name.clone(), constructor);
+
+        // Step 9.
+        self.parameter_descriptors
+            .borrow_mut()
+            .insert(name, descriptors);
+
+        Ok(())
+    }
+
+    // https://webaudio.github.io/web-audio-api/#dom-audioworkletglobalscope-currentframe
+    fn CurrentFrame(&self) -> u64 {
+        // FIXME: Track the frame being rendered once processors run on the
+        // rendering thread.
+        0
+    }
+
+    // https://webaudio.github.io/web-audio-api/#dom-audioworkletglobalscope-currenttime
+    fn CurrentTime(&self) -> Finite<f64> {
+        Finite::wrap(self.CurrentFrame() as f64 / self.sample_rate as f64)
+    }
+
+    // https://webaudio.github.io/web-audio-api/#dom-audioworkletglobalscope-samplerate
+    fn SampleRate(&self) -> Finite<f32> {
+        Finite::wrap(self.sample_rate)
+    }
+}
@@ -0,0 +1,30 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::audioworkletglobalscope::AudioWorkletGlobalScope;
+use crate::dom::bindings::error::{Error, Fallible};
+use crate::dom::bindings::reflector::Reflector;
+use crate::dom::bindings::root::DomRoot;
+use dom_struct::dom_struct;
+
+/// <https://webaudio.github.io/web-audio-api/#audioworkletprocessor>
+#[dom_struct]
+pub struct AudioWorkletProcessor {
+    reflector_: Reflector,
+}
+
+impl AudioWorkletProcessor {
+    // https://webaudio.github.io/web-audio-api/#dom-audioworkletprocessor-audioworkletprocessor
+    pub fn Constructor(
+        _global: &AudioWorkletGlobalScope,
+    ) -> Fallible<DomRoot<AudioWorkletProcessor>> {
+        // Steps 1-2.
+        // Processors are only ever constructed by an AudioWorkletNode, which
+        // is not supported yet, so there is never a pending processor
+        // construction data.
+        Err(Error::Type(String::from(
+            "AudioWorkletProcessor can only be constructed by an AudioWorkletNode.",
+        )))
+    }
+}
@@ -8,6 +8,7 @@ use crate::dom::audiobuffersourcenode::AudioBufferSourceNode;
 use crate::dom::audiodestinationnode::AudioDestinationNode;
 use crate::dom::audiolistener::AudioListener;
 use crate::dom::audionode::MAX_CHANNEL_COUNT;
+use crate::dom::audioworklet::AudioWorklet;
 use crate::dom::bindings::callback::ExceptionHandling;
 use crate::dom::bindings::cell::DomRefCell;
 use crate::dom::bindings::codegen::Bindings::AnalyserNodeBinding::AnalyserOptions;
@@ -80,6 +81,8 @@ pub struct BaseAudioContext {
     /// https://webaudio.github.io/web-audio-api/#dom-baseaudiocontext-destination
     destination: MutNullableDom<AudioDestinationNode>,
     listener: MutNullableDom<AudioListener>,
+    /// https://webaudio.github.io/web-audio-api/#dom-baseaudiocontext-audioworklet
+    audio_worklet: MutNullableDom<AudioWorklet>,
     /// Resume promises which are soon to be fulfilled by a queued task.
     #[ignore_malloc_size_of = "promises are hard"]
     in_flight_resume_promises_queue: DomRefCell<VecDeque<(Box<[Rc<Promise>]>, ErrorResult)>>,
@@ -118,6 +121,7 @@ impl BaseAudioContext {
                 .create_audio_context(options.into()),
             destination: Default::default(),
             listener: Default::default(),
+            audio_worklet: Default::default(),
             in_flight_resume_promises_queue: Default::default(),
             pending_resume_promises: Default::default(),
             decode_resolvers: Default::default(),
@@ -323,6 +327,14 @@ impl BaseAudioContextMethods for BaseAudioContext {
         self.listener.or_init(|| AudioListener::new(&window, self))
     }
 
+    /// https://webaudio.github.io/web-audio-api/#dom-baseaudiocontext-audioworklet
+    fn AudioWorklet(&self) -> DomRoot<AudioWorklet> {
+        let global = self.global();
+        let window = global.as_window();
+        self.audio_worklet
+            .or_init(|| AudioWorklet::new(&window, self.sample_rate))
+    }
+
     /// https://webaudio.github.io/web-audio-api/#dom-baseaudiocontext-onstatechange
     event_handler!(statechange, GetOnstatechange, SetOnstatechange);
 
@@ -753,8 +753,23 @@ def getJSToNativeConversionInfo(type, descriptorProvider, failureCode=None,
                 default = "%s::Boolean(%s)" % (
                     union_native_type(type),
                     "true" if defaultValue.value else "false")
+            elif tag in [IDLType.Tags.float, IDLType.Tags.double]:
+                default = "%s::%s(Finite::wrap(%s))" % (
+                    union_native_type(type),
+                    defaultValue.type.name,
+                    defaultValue.value)
+            elif tag in numericTags:
+                default = "%s::%s(%s)" % (
+                    union_native_type(type),
+                    defaultValue.type.name,
+                    defaultValue.value)
+            elif tag is IDLType.Tags.domstring:
+                default = '%s::String(DOMString::from("%s"))' % (
+                    union_native_type(type),
+                    defaultValue.value)
             else:
-                raise("We don't currently support default values that aren't null or boolean")
+                raise TypeError("We don't currently support default values that aren't "
+                                "null, boolean, numeric or DOMString")
         elif dictionaries:
             if defaultValue:
                 assert isinstance(defaultValue, IDLNullValue)
@@ -4118,12 +4133,12 @@ def getEnumValueName(value):
     # characters in them.  Deal with the former by returning "_empty",
     # deal with possible name collisions from that by throwing if the
     # enum value is actually "_empty", and throw on any value
-    # containing non-ASCII chars for now. Replace all chars other than
-    # [0-9A-Za-z_] with '_'.
+    # containing non-ASCII chars for now. Prefix values starting with a
+    # digit with '_' and replace all chars other than [0-9A-Za-z_] with '_'.
     if re.match("[^\x20-\x7E]", value):
         raise SyntaxError('Enum value "' + value + '" contains non-ASCII characters')
     if re.match("^[0-9]", value):
-        raise SyntaxError('Enum value "' + value + '" starts with a digit')
+        value = '_' + value
     value = re.sub(r'[^0-9A-Za-z_]', '_', value)
     if re.match("^_[A-Z]|__", value):
         raise SyntaxError('Enum value "' + value + '" is reserved by the C++ spec')
@@ -4237,6 +4252,9 @@ def getUnionTypeTemplateVars(type, descriptorProvider):
     elif type.isDictionary():
         name = type.name
         typeName = name
+    elif type.isUnion():
+        name = type.name
+        typeName = name
     elif type.isSequence() or type.isRecord():
         name = type.name
         inner = getUnionTypeTemplateVars(innerContainerType(type), descriptorProvider)
@@ -4432,7 +4450,9 @@ class CGUnionConversionStruct(CGThing):
             conversions.append(CGIfWrapper("value.get().is_object()", templateBody))
 
         if dictionaryObject:
-            assert not hasObjectTypes
+            # Only "object" can't be told apart from a dictionary, objects
+            # implementing an interface are converted to the interface first.
+            assert not object
             conversions.append(dictionaryObject)
 
         stringTypes = [t for t in memberTypes if t.isString() or t.isEnum()]
@@ -6209,6 +6229,14 @@ class CGDictionary(CGThing):
             # Nothing else to do here
             return
 
+        for member in dictionary.members:
+            # Dictionaries with required members have no empty value, so
+            # optional members of such a type are left unset when missing.
+            if (member.optional and member.type.isDictionary() and
+                    isinstance(member.defaultValue, IDLNullValue) and
+                    self.hasRequiredFields(member.type.inner)):
+                member.defaultValue = None
+
         self.memberInfo = [
             (member,
              getJSToNativeConversionInfo(member.type,
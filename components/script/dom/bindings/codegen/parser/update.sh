@@ -6,6 +6,7 @@ patch < callback-location.patch
 patch < union-typedef.patch
 patch < inline.patch
 patch < undo-dictionary-optional.patch
+patch < readable-stream.patch
 
 wget https://hg.mozilla.org/mozilla-central/archive/tip.tar.gz/dom/bindings/parser/tests/ -O tests.tar.gz
 rm -r tests
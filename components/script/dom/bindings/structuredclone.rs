@@ -5,12 +5,26 @@
 //! This module implements structured cloning, as defined by [HTML]
 //! (https://html.spec.whatwg.org/multipage/#safe-passing-of-structured-data).
 
+use crate::dom::bindings::codegen::Bindings::DOMPointReadOnlyBinding::DOMPointReadOnlyMethods;
+use crate::dom::bindings::codegen::Bindings::DOMQuadBinding::DOMQuadMethods;
 use crate::dom::bindings::conversions::root_from_handleobject;
 use crate::dom::bindings::error::{Error, Fallible};
+use crate::dom::bindings::inheritance::Castable;
 use crate::dom::bindings::reflector::DomObject;
 use crate::dom::bindings::root::DomRoot;
 use crate::dom::blob::{Blob, BlobImpl};
+use crate::dom::dommatrix::DOMMatrix;
+use crate::dom::dommatrixreadonly::{
+    create_3d_matrix, transform3d_to_column_major, DOMMatrixReadOnly,
+};
+use crate::dom::dompoint::DOMPoint;
+use crate::dom::dompointreadonly::DOMPointReadOnly;
+use crate::dom::domquad::DOMQuad;
 use crate::dom::globalscope::GlobalScope;
+use crate::dom::imagebitmap::ImageBitmap;
+use crate::dom::offscreencanvas::{OffscreenCanvas, OffscreenCanvasPlaceholder};
+use euclid::Size2D;
+use js::conversions::ToJSValConvertible;
 use js::glue::CopyJSStructuredCloneData;
 use js::glue::DeleteJSAutoStructuredCloneBuffer;
 use js::glue::GetLengthOfJSStructuredCloneData;
@@ -27,8 +41,9 @@ use js::jsapi::{JSObject, JS_ClearPendingException};
 use js::jsapi::{JSStructuredCloneCallbacks, JSStructuredCloneReader, JSStructuredCloneWriter};
 use js::jsapi::{JS_ReadBytes, JS_WriteBytes};
 use js::jsapi::{JS_ReadUint32Pair, JS_WriteUint32Pair};
+use js::jsval::UndefinedValue;
 use js::rust::wrappers::{JS_ReadStructuredClone, JS_WriteStructuredClone};
-use js::rust::{Handle, HandleValue, MutableHandleValue};
+use js::rust::{CustomAutoRooterGuard, Handle, HandleValue, MutableHandleValue};
 use libc::size_t;
 use std::os::raw;
 use std::ptr;
@@ -43,6 +58,13 @@ enum StructuredCloneTags {
     /// To support additional types, add new tags with values incremented from the last one before Max.
     Min = 0xFFFF8000,
     DomBlob = 0xFFFF8001,
+    DomPointReadOnly = 0xFFFF8002,
+    DomPoint = 0xFFFF8003,
+    DomQuad = 0xFFFF8004,
+    DomMatrixReadOnly = 0xFFFF8005,
+    DomMatrix = 0xFFFF8006,
+    DomOffscreenCanvas = 0xFFFF8007,
+    DomImageBitmap = 0xFFFF8008,
     Max = 0xFFFFFFFF,
 }
 
@@ -154,11 +176,126 @@ unsafe fn write_blob(blob: DomRoot<Blob>, w: *mut JSStructuredCloneWriter) -> Re
     return Ok(());
 }
 
+unsafe fn write_f64(w: *mut JSStructuredCloneWriter, value: f64) {
+    let bits = value.to_bits();
+    assert!(JS_WriteUint32Pair(w, (bits >> 32) as u32, bits as u32));
+}
+
+unsafe fn read_f64(r: *mut JSStructuredCloneReader) -> f64 {
+    let mut high: u32 = 0;
+    let mut low: u32 = 0;
+    assert!(JS_ReadUint32Pair(
+        r,
+        &mut high as *mut u32,
+        &mut low as *mut u32
+    ));
+    f64::from_bits((high as u64) << 32 | low as u64)
+}
+
+/// Reads the x, y, z and w coordinates of a point
+unsafe fn read_point_coordinates(r: *mut JSStructuredCloneReader) -> (f64, f64, f64, f64) {
+    let x = read_f64(r);
+    let y = read_f64(r);
+    let z = read_f64(r);
+    let w = read_f64(r);
+    (x, y, z, w)
+}
+
+unsafe fn write_point_coordinates(point: &DOMPointReadOnly, w: *mut JSStructuredCloneWriter) {
+    write_f64(w, point.X());
+    write_f64(w, point.Y());
+    write_f64(w, point.Z());
+    write_f64(w, point.W());
+}
+
+unsafe fn read_point(
+    cx: *mut JSContext,
+    r: *mut JSStructuredCloneReader,
+    tag: u32,
+    sc_holder: &mut StructuredCloneHolder,
+) -> *mut JSObject {
+    let (x, y, z, w) = read_point_coordinates(r);
+    let target_global = GlobalScope::from_context(cx);
+    let point = if tag == StructuredCloneTags::DomPoint as u32 {
+        DomRoot::upcast(DOMPoint::new(&target_global, x, y, z, w))
+    } else {
+        DOMPointReadOnly::new(&target_global, x, y, z, w)
+    };
+    let js_object = point.reflector().get_jsobject().get();
+    sc_holder.points.push(point);
+    js_object
+}
+
+unsafe fn write_point(point: &DOMPointReadOnly, tag: u32, w: *mut JSStructuredCloneWriter) {
+    assert!(JS_WriteUint32Pair(w, tag, 0));
+    write_point_coordinates(point, w);
+}
+
+unsafe fn read_quad(
+    cx: *mut JSContext,
+    r: *mut JSStructuredCloneReader,
+    sc_holder: &mut StructuredCloneHolder,
+) -> *mut JSObject {
+    let target_global = GlobalScope::from_context(cx);
+    let read_corner = || {
+        let (x, y, z, w) = read_point_coordinates(r);
+        DOMPoint::new(&target_global, x, y, z, w)
+    };
+    let p1 = read_corner();
+    let p2 = read_corner();
+    let p3 = read_corner();
+    let p4 = read_corner();
+    let quad = DOMQuad::new(&target_global, &p1, &p2, &p3, &p4);
+    let js_object = quad.reflector().get_jsobject().get();
+    sc_holder.quads.push(quad);
+    js_object
+}
+
+unsafe fn write_quad(quad: &DOMQuad, w: *mut JSStructuredCloneWriter) {
+    assert!(JS_WriteUint32Pair(
+        w,
+        StructuredCloneTags::DomQuad as u32,
+        0
+    ));
+    for corner in &[quad.P1(), quad.P2(), quad.P3(), quad.P4()] {
+        write_point_coordinates(corner.upcast(), w);
+    }
+}
+
+/// The matrix elements are written in row-major order, and the is2D flag is
+/// stored alongside the tag
+unsafe fn read_matrix(
+    cx: *mut JSContext,
+    r: *mut JSStructuredCloneReader,
+    tag: u32,
+    is_2d: bool,
+    sc_holder: &mut StructuredCloneHolder,
+) -> *mut JSObject {
+    let entries: Vec<f64> = (0..16).map(|_| read_f64(r)).collect();
+    let matrix = create_3d_matrix(&entries);
+    let target_global = GlobalScope::from_context(cx);
+    let matrix = if tag == StructuredCloneTags::DomMatrix as u32 {
+        DomRoot::upcast(DOMMatrix::new(&target_global, is_2d, matrix))
+    } else {
+        DOMMatrixReadOnly::new(&target_global, is_2d, matrix)
+    };
+    let js_object = matrix.reflector().get_jsobject().get();
+    sc_holder.matrices.push(matrix);
+    js_object
+}
+
+unsafe fn write_matrix(matrix: &DOMMatrixReadOnly, tag: u32, w: *mut JSStructuredCloneWriter) {
+    assert!(JS_WriteUint32Pair(w, tag, matrix.is_2d() as u32));
+    for entry in transform3d_to_column_major(&matrix.matrix()).iter() {
+        write_f64(w, *entry);
+    }
+}
+
 unsafe extern "C" fn read_callback(
     cx: *mut JSContext,
     r: *mut JSStructuredCloneReader,
     tag: u32,
-    _data: u32,
+    data: u32,
     closure: *mut raw::c_void,
 ) -> *mut JSObject {
     assert!(
@@ -172,6 +309,20 @@ unsafe extern "C" fn read_callback(
     if tag == StructuredCloneTags::DomBlob as u32 {
         return read_blob(cx, r, &mut *(closure as *mut StructuredCloneHolder));
     }
+    if tag == StructuredCloneTags::DomPointReadOnly as u32 ||
+        tag == StructuredCloneTags::DomPoint as u32
+    {
+        return read_point(cx, r, tag, &mut *(closure as *mut StructuredCloneHolder));
+    }
+    if tag == StructuredCloneTags::DomQuad as u32 {
+        return read_quad(cx, r, &mut *(closure as *mut StructuredCloneHolder));
+    }
+    if tag == StructuredCloneTags::DomMatrixReadOnly as u32 ||
+        tag == StructuredCloneTags::DomMatrix as u32
+    {
+        let sc_holder = &mut *(closure as *mut StructuredCloneHolder);
+        return read_matrix(cx, r, tag, data != 0, sc_holder);
+    }
     return ptr::null_mut();
 }
 
@@ -184,33 +335,133 @@ unsafe extern "C" fn write_callback(
     if let Ok(blob) = root_from_handleobject::<Blob>(Handle::from_raw(obj)) {
         return write_blob(blob, w).is_ok();
     }
+    // DOMPoint inherits from DOMPointReadOnly, so it needs to be checked first
+    if let Ok(point) = root_from_handleobject::<DOMPoint>(Handle::from_raw(obj)) {
+        write_point(point.upcast(), StructuredCloneTags::DomPoint as u32, w);
+        return true;
+    }
+    if let Ok(point) = root_from_handleobject::<DOMPointReadOnly>(Handle::from_raw(obj)) {
+        write_point(&point, StructuredCloneTags::DomPointReadOnly as u32, w);
+        return true;
+    }
+    if let Ok(quad) = root_from_handleobject::<DOMQuad>(Handle::from_raw(obj)) {
+        write_quad(&quad, w);
+        return true;
+    }
+    // Likewise, DOMMatrix inherits from DOMMatrixReadOnly
+    if let Ok(matrix) = root_from_handleobject::<DOMMatrix>(Handle::from_raw(obj)) {
+        write_matrix(matrix.upcast(), StructuredCloneTags::DomMatrix as u32, w);
+        return true;
+    }
+    if let Ok(matrix) = root_from_handleobject::<DOMMatrixReadOnly>(Handle::from_raw(obj)) {
+        write_matrix(&matrix, StructuredCloneTags::DomMatrixReadOnly as u32, w);
+        return true;
+    }
     return false;
 }
 
+/// What a transferred offscreen canvas needs to be recreated in the global
+/// it got transferred to
+struct TransferredOffscreenCanvas {
+    width: u64,
+    height: u64,
+    placeholder: Option<OffscreenCanvasPlaceholder>,
+}
+
+/// The pixels, size and origin-clean flag of a transferred image bitmap
+struct TransferredImageBitmap {
+    data: Vec<u8>,
+    size: Size2D<u32>,
+    origin_clean: bool,
+}
+
+/// Transferred objects are moved out of their source object and into the
+/// content of the transfer map, which the reader takes ownership of. Hence
+/// transfers only work for buffers read in the same process.
 unsafe extern "C" fn read_transfer_callback(
-    _cx: *mut JSContext,
+    cx: *mut JSContext,
     _r: *mut JSStructuredCloneReader,
-    _tag: u32,
-    _content: *mut raw::c_void,
+    tag: u32,
+    content: *mut raw::c_void,
     _extra_data: u64,
-    _closure: *mut raw::c_void,
-    _return_object: RawMutableHandleObject,
+    closure: *mut raw::c_void,
+    return_object: RawMutableHandleObject,
 ) -> bool {
+    let sc_holder = &mut *(closure as *mut StructuredCloneHolder);
+    let target_global = GlobalScope::from_context(cx);
+    if tag == StructuredCloneTags::DomOffscreenCanvas as u32 {
+        let transferred = Box::from_raw(content as *mut TransferredOffscreenCanvas);
+        let canvas = OffscreenCanvas::new(
+            &target_global,
+            transferred.width,
+            transferred.height,
+            transferred.placeholder,
+        );
+        return_object.set(canvas.reflector().get_jsobject().get());
+        sc_holder.offscreen_canvases.push(canvas);
+        return true;
+    }
+    if tag == StructuredCloneTags::DomImageBitmap as u32 {
+        let transferred = Box::from_raw(content as *mut TransferredImageBitmap);
+        let bitmap = ImageBitmap::new(
+            &target_global,
+            transferred.data,
+            transferred.size,
+            transferred.origin_clean,
+        );
+        return_object.set(bitmap.reflector().get_jsobject().get());
+        sc_holder.image_bitmaps.push(bitmap);
+        return true;
+    }
     false
 }
 
 unsafe extern "C" fn write_transfer_callback(
     _cx: *mut JSContext,
-    _obj: RawHandleObject,
+    obj: RawHandleObject,
     _closure: *mut raw::c_void,
-    _tag: *mut u32,
-    _ownership: *mut TransferableOwnership,
-    _content: *mut *mut raw::c_void,
-    _extra_data: *mut u64,
+    tag: *mut u32,
+    ownership: *mut TransferableOwnership,
+    content: *mut *mut raw::c_void,
+    extra_data: *mut u64,
 ) -> bool {
+    if let Ok(canvas) = root_from_handleobject::<OffscreenCanvas>(Handle::from_raw(obj)) {
+        let (width, height, placeholder) = match canvas.transfer() {
+            Ok(transferred) => transferred,
+            Err(_) => return false,
+        };
+        let transferred = Box::new(TransferredOffscreenCanvas {
+            width,
+            height,
+            placeholder,
+        });
+        *tag = StructuredCloneTags::DomOffscreenCanvas as u32;
+        *ownership = TransferableOwnership::SCTAG_TMO_CUSTOM;
+        *content = Box::into_raw(transferred) as *mut raw::c_void;
+        *extra_data = 0;
+        return true;
+    }
+    if let Ok(bitmap) = root_from_handleobject::<ImageBitmap>(Handle::from_raw(obj)) {
+        let (data, size, origin_clean) = match bitmap.transfer() {
+            Some(transferred) => transferred,
+            None => return false,
+        };
+        let transferred = Box::new(TransferredImageBitmap {
+            data,
+            size,
+            origin_clean,
+        });
+        *tag = StructuredCloneTags::DomImageBitmap as u32;
+        *ownership = TransferableOwnership::SCTAG_TMO_CUSTOM;
+        *content = Box::into_raw(transferred) as *mut raw::c_void;
+        *extra_data = 0;
+        return true;
+    }
     false
 }
 
+/// The buffer the message is written to gets deleted as soon as its bytes
+/// are copied, the transferred content must outlive it until it is read.
 unsafe extern "C" fn free_transfer_callback(
     _tag: u32,
     _ownership: TransferableOwnership,
@@ -233,6 +484,11 @@ static STRUCTURED_CLONE_CALLBACKS: JSStructuredCloneCallbacks = JSStructuredClon
 
 struct StructuredCloneHolder {
     blob: Option<DomRoot<Blob>>,
+    points: Vec<DomRoot<DOMPointReadOnly>>,
+    quads: Vec<DomRoot<DOMQuad>>,
+    matrices: Vec<DomRoot<DOMMatrixReadOnly>>,
+    offscreen_canvases: Vec<DomRoot<OffscreenCanvas>>,
+    image_bitmaps: Vec<DomRoot<ImageBitmap>>,
 }
 
 /// A buffer for a structured clone.
@@ -245,9 +501,21 @@ pub enum StructuredCloneData {
 
 impl StructuredCloneData {
     // TODO: should this be unsafe?
-    /// Writes a structured clone. Returns a `DataClone` error if that fails.
-    pub fn write(cx: *mut JSContext, message: HandleValue) -> Fallible<StructuredCloneData> {
+    /// Writes a structured clone, moving the objects of the `transfer` list
+    /// over to it. Returns a `DataClone` error if that fails.
+    pub fn write(
+        cx: *mut JSContext,
+        message: HandleValue,
+        transfer: Option<CustomAutoRooterGuard<Vec<*mut JSObject>>>,
+    ) -> Fallible<StructuredCloneData> {
         unsafe {
+            rooted!(in(cx) let mut transfer_list = UndefinedValue());
+            if let Some(ref transfer) = transfer {
+                if !transfer.is_empty() {
+                    transfer.to_jsval(cx, transfer_list.handle_mut());
+                }
+            }
+
             let scbuf = NewJSAutoStructuredCloneBuffer(
                 StructuredCloneScope::DifferentProcess,
                 &STRUCTURED_CLONE_CALLBACKS,
@@ -265,7 +533,7 @@ impl StructuredCloneData {
                 policy,
                 &STRUCTURED_CLONE_CALLBACKS,
                 ptr::null_mut(),
-                HandleValue::undefined(),
+                transfer_list.handle(),
             );
             if !result {
                 JS_ClearPendingException(cx);
@@ -300,7 +568,14 @@ impl StructuredCloneData {
         let cx = global.get_cx();
         let globalhandle = global.reflector().get_jsobject();
         let _ac = JSAutoCompartment::new(cx, globalhandle.get());
-        let mut sc_holder = StructuredCloneHolder { blob: None };
+        let mut sc_holder = StructuredCloneHolder {
+            blob: None,
+            points: vec![],
+            quads: vec![],
+            matrices: vec![],
+            offscreen_canvases: vec![],
+            image_bitmaps: vec![],
+        };
         let sc_holder_ptr = &mut sc_holder as *mut _;
         unsafe {
             let scbuf = NewJSAutoStructuredCloneBuffer(
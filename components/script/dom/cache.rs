@@ -0,0 +1,353 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::CacheBinding::{
+    self, CacheMethods, CacheQueryOptions,
+};
+use crate::dom::bindings::codegen::Bindings::HeadersBinding::HeadersMethods;
+use crate::dom::bindings::codegen::Bindings::RequestBinding::{RequestInfo, RequestInit};
+use crate::dom::bindings::codegen::Bindings::ResponseBinding::ResponseBinding::ResponseMethods;
+use crate::dom::bindings::conversions::root_from_handlevalue;
+use crate::dom::bindings::error::{Error, Fallible};
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::bindings::str::ByteString;
+use crate::dom::cachestorage::query_cache_storage;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::promise::Promise;
+use crate::dom::promisenativehandler::{Callback, PromiseNativeHandler};
+use crate::dom::readablestreamdefaultreader::ReadAllBytesHandler;
+use crate::dom::request::Request;
+use crate::dom::response::Response;
+use crate::fetch;
+use dom_struct::dom_struct;
+use http::Method;
+use js::jsapi::JSContext;
+use js::rust::HandleValue;
+use net_traits::cache_storage::{self, CacheStorageMsg, CachedRequest, CachedResponse};
+use std::rc::Rc;
+
+// https://w3c.github.io/ServiceWorker/#cache-interface
+#[dom_struct]
+pub struct Cache {
+    reflector_: Reflector,
+    /// The name of this cache in the name to cache map of its origin.
+    name: String,
+}
+
+impl Cache {
+    fn new_inherited(name: String) -> Cache {
+        Cache {
+            reflector_: Reflector::new(),
+            name: name,
+        }
+    }
+
+    pub fn new(global: &GlobalScope, name: String) -> DomRoot<Cache> {
+        reflect_dom_object(
+            Box::new(Cache::new_inherited(name)),
+            global,
+            CacheBinding::Wrap,
+        )
+    }
+
+    /// <https://w3c.github.io/ServiceWorker/#dom-cache-put>
+    /// Steps 4-6.
+    fn validate_response(response: &Response) -> Fallible<()> {
+        // Step 4.
+        if response.Status() == 206 {
+            return Err(Error::Type("Partial responses cannot be cached".to_owned()));
+        }
+        // Step 5.
+        if let Ok(Some(vary)) = response.Headers().Get(ByteString::new(b"Vary".to_vec())) {
+            let vary = String::from_utf8_lossy(&vary).into_owned();
+            if vary.split(',').any(|name| name.trim() == "*") {
+                return Err(Error::Type(
+                    "Responses varying on every header cannot be cached".to_owned(),
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Reads the body of `response` and stores it along with `request` in
+    /// this cache, settling `promise` once done.
+    ///
+    /// <https://w3c.github.io/ServiceWorker/#dom-cache-put>
+    /// Steps 6-13.
+    fn put(&self, promise: &Rc<Promise>, request: &Request, response: &Response) {
+        let handler = Box::new(PutHandler {
+            cache: Dom::from_ref(self),
+            promise: promise.clone(),
+            request: Dom::from_ref(request),
+            response: Dom::from_ref(response),
+        });
+        if let Err(error) = response.read_all_bytes(handler) {
+            promise.reject_error(error);
+        }
+    }
+
+    fn store(&self, request: CachedRequest, response: CachedResponse) -> Fallible<()> {
+        let name = self.name.clone();
+        query_cache_storage(&self.global(), |sender, origin| {
+            CacheStorageMsg::Put(sender, origin, name, request, response)
+        })
+    }
+}
+
+/// Gets the request a `RequestInfo` stands for, constructing one from a URL.
+fn request_from_info(global: &GlobalScope, request: RequestInfo) -> Fallible<DomRoot<Request>> {
+    match request {
+        RequestInfo::Request(request) => Ok(request),
+        request => Request::Constructor(global, request, RequestInit::empty()),
+    }
+}
+
+fn query_options(options: &CacheQueryOptions) -> cache_storage::CacheQueryOptions {
+    cache_storage::CacheQueryOptions {
+        ignore_search: options.ignoreSearch,
+        ignore_method: options.ignoreMethod,
+        ignore_vary: options.ignoreVary,
+    }
+}
+
+/// Gets the entries of the cache named `name`, or of all the caches of the
+/// origin of `global` if it is none, matching `request`, or all of them if
+/// it is none.
+///
+/// <https://w3c.github.io/ServiceWorker/#query-cache>
+pub fn match_entries(
+    global: &GlobalScope,
+    name: Option<String>,
+    request: Option<RequestInfo>,
+    options: &CacheQueryOptions,
+) -> Fallible<Vec<(CachedRequest, CachedResponse)>> {
+    let request = match request {
+        Some(request) => Some(request_from_info(global, request)?.to_cached()),
+        None => None,
+    };
+    let options = query_options(options);
+    query_cache_storage(global, |sender, origin| {
+        CacheStorageMsg::Match(sender, origin, name, request, options)
+    })
+}
+
+impl CacheMethods for Cache {
+    // https://w3c.github.io/ServiceWorker/#dom-cache-match
+    #[allow(unsafe_code)]
+    fn Match(&self, request: RequestInfo, options: &CacheQueryOptions) -> Rc<Promise> {
+        let global = self.global();
+        let promise = unsafe { Promise::new_in_current_compartment(&global) };
+        match match_entries(&global, Some(self.name.clone()), Some(request), options) {
+            Ok(entries) => match entries.into_iter().next() {
+                Some((_, response)) => {
+                    promise.resolve_native(&Response::new_from_cache(&global, response))
+                },
+                None => promise.resolve_native(&()),
+            },
+            Err(error) => promise.reject_error(error),
+        }
+        promise
+    }
+
+    // https://w3c.github.io/ServiceWorker/#dom-cache-matchall
+    #[allow(unsafe_code)]
+    fn MatchAll(&self, request: Option<RequestInfo>, options: &CacheQueryOptions) -> Rc<Promise> {
+        let global = self.global();
+        let promise = unsafe { Promise::new_in_current_compartment(&global) };
+        match match_entries(&global, Some(self.name.clone()), request, options) {
+            Ok(entries) => {
+                let responses: Vec<DomRoot<Response>> = entries
+                    .into_iter()
+                    .map(|(_, response)| Response::new_from_cache(&global, response))
+                    .collect();
+                promise.resolve_native(&responses);
+            },
+            Err(error) => promise.reject_error(error),
+        }
+        promise
+    }
+
+    // https://w3c.github.io/ServiceWorker/#dom-cache-add
+    #[allow(unsafe_code)]
+    fn Add(&self, request: RequestInfo) -> Rc<Promise> {
+        let global = self.global();
+        let promise = unsafe { Promise::new_in_current_compartment(&global) };
+        let request = match request_from_info(&global, request) {
+            Ok(request) => request,
+            Err(error) => {
+                promise.reject_error(error);
+                return promise;
+            },
+        };
+        // https://w3c.github.io/ServiceWorker/#dom-cache-addall
+        // Step 3.2.
+        if !request.get_request().url().scheme().starts_with("http") {
+            promise.reject_error(Error::Type(
+                "Only http(s) requests can be cached".to_owned(),
+            ));
+            return promise;
+        }
+        // Steps 5-6.
+        let fetched = fetch::Fetch(
+            &global,
+            RequestInfo::Request(DomRoot::from_ref(&*request)),
+            RequestInit::empty(),
+        );
+        let handler = PromiseNativeHandler::new(
+            &global,
+            Some(Box::new(AddHandler {
+                cache: Dom::from_ref(self),
+                promise: promise.clone(),
+                request: Dom::from_ref(&*request),
+                fulfilled: true,
+            })),
+            Some(Box::new(AddHandler {
+                cache: Dom::from_ref(self),
+                promise: promise.clone(),
+                request: Dom::from_ref(&*request),
+                fulfilled: false,
+            })),
+        );
+        fetched.append_native_handler(&handler);
+        promise
+    }
+
+    // https://w3c.github.io/ServiceWorker/#dom-cache-put
+    #[allow(unsafe_code)]
+    fn Put(&self, request: RequestInfo, response: &Response) -> Rc<Promise> {
+        let global = self.global();
+        let promise = unsafe { Promise::new_in_current_compartment(&global) };
+        // Steps 1-3.
+        let request = match request_from_info(&global, request) {
+            Ok(request) => request,
+            Err(error) => {
+                promise.reject_error(error);
+                return promise;
+            },
+        };
+        let net_request = request.get_request();
+        if !net_request.url().scheme().starts_with("http") || net_request.method != Method::GET {
+            promise.reject_error(Error::Type(
+                "Only GET http(s) requests can be cached".to_owned(),
+            ));
+            return promise;
+        }
+        // Steps 4-5.
+        if let Err(error) = Cache::validate_response(response) {
+            promise.reject_error(error);
+            return promise;
+        }
+        // Steps 6-13.
+        self.put(&promise, &request, response);
+        promise
+    }
+
+    // https://w3c.github.io/ServiceWorker/#dom-cache-delete
+    #[allow(unsafe_code)]
+    fn Delete(&self, request: RequestInfo, options: &CacheQueryOptions) -> Rc<Promise> {
+        let global = self.global();
+        let promise = unsafe { Promise::new_in_current_compartment(&global) };
+        let request = match request_from_info(&global, request) {
+            Ok(request) => request.to_cached(),
+            Err(error) => {
+                promise.reject_error(error);
+                return promise;
+            },
+        };
+        let name = self.name.clone();
+        let options = query_options(options);
+        match query_cache_storage(&global, |sender, origin| {
+            CacheStorageMsg::DeleteEntries(sender, origin, name, request, options)
+        }) {
+            Ok(deleted) => promise.resolve_native(&deleted),
+            Err(error) => promise.reject_error(error),
+        }
+        promise
+    }
+
+    // https://w3c.github.io/ServiceWorker/#dom-cache-keys
+    #[allow(unsafe_code)]
+    fn Keys(&self, request: Option<RequestInfo>, options: &CacheQueryOptions) -> Rc<Promise> {
+        let global = self.global();
+        let promise = unsafe { Promise::new_in_current_compartment(&global) };
+        match match_entries(&global, Some(self.name.clone()), request, options) {
+            Ok(entries) => {
+                let requests: Vec<DomRoot<Request>> = entries
+                    .into_iter()
+                    .map(|(request, _)| Request::new_from_cache(&global, request))
+                    .collect();
+                promise.resolve_native(&requests);
+            },
+            Err(error) => promise.reject_error(error),
+        }
+        promise
+    }
+}
+
+/// The reactions to the fetch started by `add()`.
+#[derive(JSTraceable, MallocSizeOf)]
+#[must_root]
+struct AddHandler {
+    cache: Dom<Cache>,
+    #[ignore_malloc_size_of = "Rc is hard"]
+    promise: Rc<Promise>,
+    request: Dom<Request>,
+    fulfilled: bool,
+}
+
+impl Callback for AddHandler {
+    fn callback(&self, _cx: *mut JSContext, v: HandleValue) {
+        if !self.fulfilled {
+            return self
+                .promise
+                .reject_error(Error::Type("The request could not be fetched".to_owned()));
+        }
+        let response = match root_from_handlevalue::<Response>(v) {
+            Ok(response) => response,
+            Err(()) => {
+                return self
+                    .promise
+                    .reject_error(Error::Type("Not a response".to_owned()))
+            },
+        };
+        // https://w3c.github.io/ServiceWorker/#dom-cache-addall
+        // Steps 5.3.1-5.3.2.
+        if !response.Ok() {
+            return self.promise.reject_error(Error::Type(
+                "Only successful responses can be added".to_owned(),
+            ));
+        }
+        if let Err(error) = Cache::validate_response(&response) {
+            return self.promise.reject_error(error);
+        }
+        self.cache.put(&self.promise, &self.request, &response);
+    }
+}
+
+/// Stores an entry in a cache once the body of its response was read.
+#[derive(JSTraceable)]
+#[must_root]
+struct PutHandler {
+    cache: Dom<Cache>,
+    promise: Rc<Promise>,
+    request: Dom<Request>,
+    response: Dom<Response>,
+}
+
+impl ReadAllBytesHandler for PutHandler {
+    fn success(&self, bytes: Vec<u8>) {
+        let request = self.request.to_cached();
+        let response = self.response.to_cached(bytes);
+        match self.cache.store(request, response) {
+            Ok(()) => self.promise.resolve_native(&()),
+            Err(error) => self.promise.reject_error(error),
+        }
+    }
+
+    fn failure(&self, _cx: *mut JSContext, _error: HandleValue) {
+        self.promise
+            .reject_error(Error::Type("The response body stream errored".to_string()));
+    }
+}
@@ -0,0 +1,144 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::CacheStorageBinding::{
+    self, CacheStorageMethods, MultiCacheQueryOptions,
+};
+use crate::dom::bindings::codegen::Bindings::RequestBinding::RequestInfo;
+use crate::dom::bindings::error::{Error, Fallible};
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::str::DOMString;
+use crate::dom::cache::{self, Cache};
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::promise::Promise;
+use crate::dom::response::Response;
+use dom_struct::dom_struct;
+use ipc_channel::ipc::IpcSender;
+use net_traits::cache_storage::CacheStorageMsg;
+use net_traits::{CoreResourceMsg, IpcSend};
+use profile_traits::ipc;
+use serde::{Deserialize, Serialize};
+use servo_url::ImmutableOrigin;
+use std::rc::Rc;
+
+// https://w3c.github.io/ServiceWorker/#cachestorage-interface
+#[dom_struct]
+pub struct CacheStorage {
+    reflector_: Reflector,
+}
+
+impl CacheStorage {
+    fn new_inherited() -> CacheStorage {
+        CacheStorage {
+            reflector_: Reflector::new(),
+        }
+    }
+
+    pub fn new(global: &GlobalScope) -> DomRoot<CacheStorage> {
+        reflect_dom_object(
+            Box::new(CacheStorage::new_inherited()),
+            global,
+            CacheStorageBinding::Wrap,
+        )
+    }
+}
+
+/// Sends a message about the caches of the origin of `global` to the
+/// resource thread and waits for its reply, failing with a security error
+/// if the origin is opaque.
+pub fn query_cache_storage<T, F>(global: &GlobalScope, msg: F) -> Fallible<T>
+where
+    T: for<'de> Deserialize<'de> + Serialize,
+    F: FnOnce(IpcSender<T>, ImmutableOrigin) -> CacheStorageMsg,
+{
+    let origin = global.origin().immutable().clone();
+    if !origin.is_tuple() {
+        return Err(Error::Security);
+    }
+    let (sender, receiver) = ipc::channel(global.time_profiler_chan().clone()).unwrap();
+    global
+        .resource_threads()
+        .send(CoreResourceMsg::CacheStorage(msg(sender, origin)))
+        .unwrap();
+    receiver.recv().map_err(|_| Error::InvalidState)
+}
+
+impl CacheStorageMethods for CacheStorage {
+    // https://w3c.github.io/ServiceWorker/#cache-storage-match
+    #[allow(unsafe_code)]
+    fn Match(&self, request: RequestInfo, options: &MultiCacheQueryOptions) -> Rc<Promise> {
+        let global = self.global();
+        let promise = unsafe { Promise::new_in_current_compartment(&global) };
+        let name = options.cacheName.clone().map(String::from);
+        match cache::match_entries(&global, name, Some(request), &options.parent) {
+            Ok(entries) => match entries.into_iter().next() {
+                Some((_, response)) => {
+                    promise.resolve_native(&Response::new_from_cache(&global, response))
+                },
+                None => promise.resolve_native(&()),
+            },
+            Err(error) => promise.reject_error(error),
+        }
+        promise
+    }
+
+    // https://w3c.github.io/ServiceWorker/#cache-storage-has
+    #[allow(unsafe_code)]
+    fn Has(&self, cacheName: DOMString) -> Rc<Promise> {
+        let global = self.global();
+        let promise = unsafe { Promise::new_in_current_compartment(&global) };
+        match query_cache_storage(&global, |sender, origin| {
+            CacheStorageMsg::Has(sender, origin, cacheName.into())
+        }) {
+            Ok(has) => promise.resolve_native(&has),
+            Err(error) => promise.reject_error(error),
+        }
+        promise
+    }
+
+    // https://w3c.github.io/ServiceWorker/#cache-storage-open
+    #[allow(unsafe_code)]
+    fn Open(&self, cacheName: DOMString) -> Rc<Promise> {
+        let global = self.global();
+        let promise = unsafe { Promise::new_in_current_compartment(&global) };
+        let name = String::from(cacheName);
+        match query_cache_storage(&global, |sender, origin| {
+            CacheStorageMsg::Open(sender, origin, name.clone())
+        }) {
+            Ok(()) => promise.resolve_native(&Cache::new(&global, name)),
+            Err(error) => promise.reject_error(error),
+        }
+        promise
+    }
+
+    // https://w3c.github.io/ServiceWorker/#cache-storage-delete
+    #[allow(unsafe_code)]
+    fn Delete(&self, cacheName: DOMString) -> Rc<Promise> {
+        let global = self.global();
+        let promise = unsafe { Promise::new_in_current_compartment(&global) };
+        match query_cache_storage(&global, |sender, origin| {
+            CacheStorageMsg::Delete(sender, origin, cacheName.into())
+        }) {
+            Ok(deleted) => promise.resolve_native(&deleted),
+            Err(error) => promise.reject_error(error),
+        }
+        promise
+    }
+
+    // https://w3c.github.io/ServiceWorker/#cache-storage-keys
+    #[allow(unsafe_code)]
+    fn Keys(&self) -> Rc<Promise> {
+        let global = self.global();
+        let promise = unsafe { Promise::new_in_current_compartment(&global) };
+        match query_cache_storage(&global, CacheStorageMsg::Keys) {
+            Ok(names) => {
+                let names: Vec<DOMString> = names.into_iter().map(DOMString::from).collect();
+                promise.resolve_native(&names);
+            },
+            Err(error) => promise.reject_error(error),
+        }
+        promise
+    }
+}
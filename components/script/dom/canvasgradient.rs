@@ -12,7 +12,8 @@ use crate::dom::bindings::root::DomRoot;
 use crate::dom::bindings::str::DOMString;
 use crate::dom::globalscope::GlobalScope;
 use canvas_traits::canvas::{
-    CanvasGradientStop, FillOrStrokeStyle, LinearGradientStyle, RadialGradientStyle,
+    CanvasGradientStop, ConicGradientStyle, FillOrStrokeStyle, LinearGradientStyle,
+    RadialGradientStyle,
 };
 use cssparser::Color as CSSColor;
 use cssparser::{Parser, ParserInput, RGBA};
@@ -30,6 +31,7 @@ pub struct CanvasGradient {
 pub enum CanvasGradientStyle {
     Linear(LinearGradientStyle),
     Radial(RadialGradientStyle),
+    Conic(ConicGradientStyle),
 }
 
 impl CanvasGradient {
@@ -106,6 +108,9 @@ impl<'a> ToFillOrStrokeStyle for &'a CanvasGradient {
                     gradient_stops,
                 ))
             },
+            CanvasGradientStyle::Conic(ref gradient) => FillOrStrokeStyle::ConicGradient(
+                ConicGradientStyle::new(gradient.angle, gradient.x, gradient.y, gradient_stops),
+            ),
         }
     }
 }
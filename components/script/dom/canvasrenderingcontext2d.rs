@@ -22,15 +22,18 @@ use crate::dom::canvaspattern::CanvasPattern;
 use crate::dom::element::Element;
 use crate::dom::globalscope::GlobalScope;
 use crate::dom::htmlcanvaselement::{CanvasContext, HTMLCanvasElement};
+use crate::dom::imagebitmap::ImageBitmap;
 use crate::dom::imagedata::ImageData;
 use crate::dom::node::{window_from_node, Node, NodeDamage};
+use crate::dom::offscreencanvas::{OffscreenCanvas, OffscreenCanvasContext};
+use crate::dom::path2d::Path2D;
 use crate::unpremultiplytable::UNPREMULTIPLY_TABLE;
 use canvas_traits::canvas::{Canvas2dMsg, CanvasId, CanvasMsg};
-use canvas_traits::canvas::{CompositionOrBlending, FillOrStrokeStyle, FillRule};
-use canvas_traits::canvas::{LineCapStyle, LineJoinStyle, LinearGradientStyle};
-use canvas_traits::canvas::{RadialGradientStyle, RepetitionStyle};
+use canvas_traits::canvas::{CompositionOrBlending, ConicGradientStyle, FillOrStrokeStyle};
+use canvas_traits::canvas::{FillRule, FilterFunction, LineCapStyle, LineJoinStyle};
+use canvas_traits::canvas::{LinearGradientStyle, RadialGradientStyle, RepetitionStyle};
 use cssparser::Color as CSSColor;
-use cssparser::{Parser, ParserInput, RGBA};
+use cssparser::{Parser, ParserInput, Token, RGBA};
 use dom_struct::dom_struct;
 use euclid::{vec2, Point2D, Rect, Size2D, Transform2D};
 use ipc_channel::ipc::{self, IpcSender};
@@ -65,10 +68,14 @@ pub struct CanvasRenderingContext2D {
     #[ignore_malloc_size_of = "Defined in ipc-channel"]
     ipc_renderer: IpcSender<CanvasMsg>,
     /// For rendering contexts created by an HTML canvas element, this is Some,
-    /// for ones created by a paint worklet, this is None.
+    /// for ones created by a paint worklet or an offscreen canvas, this is None.
     canvas: Option<Dom<HTMLCanvasElement>>,
+    /// None for contexts of offscreen canvases living in a worker, which
+    /// has no image cache to draw images from.
     #[ignore_malloc_size_of = "Arc"]
-    image_cache: Arc<ImageCache>,
+    image_cache: Option<Arc<dyn ImageCache>>,
+    /// The size of the bitmap this context draws onto.
+    size: Cell<Size2D<u32>>,
     /// Any missing image URLs.
     missing_image_urls: DomRefCell<Vec<ServoUrl>>,
     /// The base URL for resolving CSS image URL values.
@@ -97,6 +104,7 @@ struct CanvasContextState {
     shadow_offset_y: f64,
     shadow_blur: f64,
     shadow_color: RGBA,
+    filter: DOMString,
 }
 
 impl CanvasContextState {
@@ -117,6 +125,7 @@ impl CanvasContextState {
             shadow_offset_y: 0.0,
             shadow_blur: 0.0,
             shadow_color: RGBA::transparent(),
+            filter: DOMString::from("none"),
         }
     }
 }
@@ -125,7 +134,7 @@ impl CanvasRenderingContext2D {
     pub fn new_inherited(
         global: &GlobalScope,
         canvas: Option<&HTMLCanvasElement>,
-        image_cache: Arc<dyn ImageCache>,
+        image_cache: Option<Arc<dyn ImageCache>>,
         base_url: ServoUrl,
         size: Size2D<u32>,
     ) -> CanvasRenderingContext2D {
@@ -144,6 +153,7 @@ impl CanvasRenderingContext2D {
             ipc_renderer: ipc_renderer,
             canvas: canvas.map(Dom::from_ref),
             image_cache: image_cache,
+            size: Cell::new(size),
             missing_image_urls: DomRefCell::new(Vec::new()),
             base_url: base_url,
             state: DomRefCell::new(CanvasContextState::new()),
@@ -164,7 +174,7 @@ impl CanvasRenderingContext2D {
         let boxed = Box::new(CanvasRenderingContext2D::new_inherited(
             global,
             Some(canvas),
-            image_cache,
+            Some(image_cache),
             base_url,
             size,
         ));
@@ -174,6 +184,7 @@ impl CanvasRenderingContext2D {
     // https://html.spec.whatwg.org/multipage/#concept-canvas-set-bitmap-dimensions
     pub fn set_bitmap_dimensions(&self, size: Size2D<u32>) {
         self.reset_to_initial_state();
+        self.size.set(size);
         self.ipc_renderer
             .send(CanvasMsg::Recreate(size, self.get_canvas_id()))
             .unwrap();
@@ -263,6 +274,8 @@ impl CanvasRenderingContext2D {
     fn is_origin_clean(&self, image: CanvasImageSource) -> bool {
         match image {
             CanvasImageSource::HTMLCanvasElement(canvas) => canvas.origin_is_clean(),
+            CanvasImageSource::OffscreenCanvas(canvas) => canvas.origin_is_clean(),
+            CanvasImageSource::ImageBitmap(bitmap) => bitmap.origin_is_clean(),
             CanvasImageSource::HTMLImageElement(image) => {
                 image.same_origin(GlobalScope::entry().origin())
             },
@@ -307,6 +320,12 @@ impl CanvasRenderingContext2D {
             CanvasImageSource::HTMLCanvasElement(ref canvas) => {
                 self.draw_html_canvas_element(&canvas, sx, sy, sw, sh, dx, dy, dw, dh)
             },
+            CanvasImageSource::OffscreenCanvas(ref canvas) => {
+                self.draw_offscreen_canvas(&canvas, sx, sy, sw, sh, dx, dy, dw, dh)
+            },
+            CanvasImageSource::ImageBitmap(ref bitmap) => {
+                self.draw_image_bitmap(&bitmap, sx, sy, sw, sh, dx, dy, dw, dh)
+            },
             CanvasImageSource::HTMLImageElement(ref image) => {
                 // https://html.spec.whatwg.org/multipage/#img-error
                 // If the image argument is an HTMLImageElement object that is in the broken state,
@@ -364,7 +383,7 @@ impl CanvasRenderingContext2D {
 
         if let Some(context) = canvas.context() {
             match *context {
-                CanvasContext::Context2d(ref context) => {
+                CanvasContext::Context2d(ref context) | CanvasContext::Placeholder(ref context) => {
                     context.send_canvas_2d_msg(Canvas2dMsg::DrawImageInOther(
                         self.get_canvas_id(),
                         image_size,
@@ -389,6 +408,107 @@ impl CanvasRenderingContext2D {
         Ok(())
     }
 
+    fn draw_offscreen_canvas(
+        &self,
+        canvas: &OffscreenCanvas,
+        sx: f64,
+        sy: f64,
+        sw: Option<f64>,
+        sh: Option<f64>,
+        dx: f64,
+        dy: f64,
+        dw: Option<f64>,
+        dh: Option<f64>,
+    ) -> ErrorResult {
+        // 1. Check the usability of the image argument
+        if !canvas.is_valid() {
+            return Err(Error::InvalidState);
+        }
+
+        let canvas_size = canvas.get_size();
+        let image_size = Size2D::new(canvas_size.width as f64, canvas_size.height as f64);
+        let dw = dw.unwrap_or(image_size.width);
+        let dh = dh.unwrap_or(image_size.height);
+        let sw = sw.unwrap_or(image_size.width);
+        let sh = sh.unwrap_or(image_size.height);
+
+        // 2. Establish the source and destination rectangles
+        let (source_rect, dest_rect) =
+            self.adjust_source_dest_rects(image_size, sx, sy, sw, sh, dx, dy, dw, dh);
+
+        if !is_rect_valid(source_rect) || !is_rect_valid(dest_rect) {
+            return Ok(());
+        }
+
+        let smoothing_enabled = self.state.borrow().image_smoothing_enabled;
+
+        if let Some(context) = canvas.context() {
+            match *context {
+                OffscreenCanvasContext::OffscreenContext2d(ref context) => {
+                    context.send_canvas_2d_msg(Canvas2dMsg::DrawImageInOther(
+                        self.get_canvas_id(),
+                        image_size,
+                        dest_rect,
+                        source_rect,
+                        smoothing_enabled,
+                    ));
+                },
+            }
+        } else {
+            self.send_canvas_2d_msg(Canvas2dMsg::DrawImage(
+                None,
+                image_size,
+                dest_rect,
+                source_rect,
+                smoothing_enabled,
+            ));
+        }
+
+        self.mark_as_dirty();
+        Ok(())
+    }
+
+    fn draw_image_bitmap(
+        &self,
+        bitmap: &ImageBitmap,
+        sx: f64,
+        sy: f64,
+        sw: Option<f64>,
+        sh: Option<f64>,
+        dx: f64,
+        dy: f64,
+        dw: Option<f64>,
+        dh: Option<f64>,
+    ) -> ErrorResult {
+        // A closed bitmap is not usable
+        let image_data = bitmap.bitmap_data().ok_or(Error::InvalidState)?;
+        let image_size = bitmap.get_size().to_f64();
+
+        let dw = dw.unwrap_or(image_size.width);
+        let dh = dh.unwrap_or(image_size.height);
+        let sw = sw.unwrap_or(image_size.width);
+        let sh = sh.unwrap_or(image_size.height);
+
+        // Establish the source and destination rectangles
+        let (source_rect, dest_rect) =
+            self.adjust_source_dest_rects(image_size, sx, sy, sw, sh, dx, dy, dw, dh);
+
+        if !is_rect_valid(source_rect) || !is_rect_valid(dest_rect) {
+            return Ok(());
+        }
+
+        let smoothing_enabled = self.state.borrow().image_smoothing_enabled;
+        self.send_canvas_2d_msg(Canvas2dMsg::DrawImage(
+            Some(image_data.into()),
+            image_size,
+            dest_rect,
+            source_rect,
+            smoothing_enabled,
+        ));
+        self.mark_as_dirty();
+        Ok(())
+    }
+
     fn fetch_and_draw_image_data(
         &self,
         url: ServoUrl,
@@ -452,7 +572,11 @@ impl CanvasRenderingContext2D {
 
     #[inline]
     fn request_image_from_cache(&self, url: ServoUrl) -> ImageResponse {
-        let response = self.image_cache.find_image_or_metadata(
+        let image_cache = match self.image_cache {
+            Some(ref image_cache) => image_cache,
+            None => return ImageResponse::None,
+        };
+        let response = image_cache.find_image_or_metadata(
             url.clone(),
             UsePlaceholder::No,
             CanRequestImages::No,
@@ -541,6 +665,17 @@ impl CanvasRenderingContext2D {
         self.ipc_renderer.clone()
     }
 
+    pub fn get_size(&self) -> Size2D<u32> {
+        self.size.get()
+    }
+
+    /// Takes note of the canvas paint thread having replaced the bitmap of
+    /// this context, as happens when an offscreen canvas commits a frame to
+    /// its placeholder canvas
+    pub fn bitmap_replaced(&self, size: Size2D<u32>) {
+        self.size.set(size);
+    }
+
     pub fn origin_is_clean(&self) -> bool {
         self.origin_clean.get()
     }
@@ -552,12 +687,7 @@ impl CanvasRenderingContext2D {
     pub fn get_rect(&self, rect: Rect<u32>) -> Vec<u8> {
         assert!(self.origin_is_clean());
 
-        // FIXME(nox): This is probably wrong when this is a context for an
-        // offscreen canvas.
-        let canvas_size = self
-            .canvas
-            .as_ref()
-            .map_or(Size2D::zero(), |c| c.get_size());
+        let canvas_size = self.size.get();
         assert!(Rect::from_size(canvas_size).contains_rect(&rect));
 
         let (sender, receiver) = ipc::bytes_channel().unwrap();
@@ -810,6 +940,43 @@ impl CanvasRenderingContext2DMethods for CanvasRenderingContext2D {
         receiver.recv().unwrap()
     }
 
+    // https://html.spec.whatwg.org/multipage/#dom-context-2d-fill
+    fn Fill_(&self, path: &Path2D, _: CanvasFillRule) {
+        // TODO: Process fill rule
+        self.send_canvas_2d_msg(Canvas2dMsg::FillPath(path.segments()));
+        self.mark_as_dirty();
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-context-2d-stroke
+    fn Stroke_(&self, path: &Path2D) {
+        self.send_canvas_2d_msg(Canvas2dMsg::StrokePath(path.segments()));
+        self.mark_as_dirty();
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-context-2d-clip
+    fn Clip_(&self, path: &Path2D, _: CanvasFillRule) {
+        // TODO: Process fill rule
+        self.send_canvas_2d_msg(Canvas2dMsg::ClipPath(path.segments()));
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-context-2d-ispointinpath
+    fn IsPointInPath_(&self, path: &Path2D, x: f64, y: f64, fill_rule: CanvasFillRule) -> bool {
+        let fill_rule = match fill_rule {
+            CanvasFillRule::Nonzero => FillRule::Nonzero,
+            CanvasFillRule::Evenodd => FillRule::Evenodd,
+        };
+        let (sender, receiver) =
+            profiled_ipc::channel::<bool>(self.global().time_profiler_chan().clone()).unwrap();
+        self.send_canvas_2d_msg(Canvas2dMsg::IsPointInPathPath(
+            path.segments(),
+            x,
+            y,
+            fill_rule,
+            sender,
+        ));
+        receiver.recv().unwrap()
+    }
+
     // https://html.spec.whatwg.org/multipage/#dom-context-2d-filltext
     fn FillText(&self, text: DOMString, x: f64, y: f64, max_width: Option<f64>) {
         let parsed_text: String = text.into();
@@ -1141,12 +1308,7 @@ impl CanvasRenderingContext2DMethods for CanvasRenderingContext2D {
         }
 
         let (origin, size) = adjust_size_sign(Point2D::new(sx, sy), Size2D::new(sw, sh));
-        // FIXME(nox): This is probably wrong when this is a context for an
-        // offscreen canvas.
-        let canvas_size = self
-            .canvas
-            .as_ref()
-            .map_or(Size2D::zero(), |c| c.get_size());
+        let canvas_size = self.size.get();
         let read_rect = match pixels::clip(origin, size, canvas_size) {
             Some(rect) => rect,
             None => {
@@ -1202,12 +1364,7 @@ impl CanvasRenderingContext2DMethods for CanvasRenderingContext2D {
         // Step 2.
         // TODO: throw InvalidState if buffer is detached.
 
-        // FIXME(nox): This is probably wrong when this is a context for an
-        // offscreen canvas.
-        let canvas_size = self
-            .canvas
-            .as_ref()
-            .map_or(Size2D::zero(), |c| c.get_size());
+        let canvas_size = self.size.get();
 
         // Steps 3-6.
         let (src_origin, src_size) = adjust_size_sign(
@@ -1279,6 +1436,19 @@ impl CanvasRenderingContext2DMethods for CanvasRenderingContext2D {
         ))
     }
 
+    // https://html.spec.whatwg.org/multipage/#dom-context-2d-createconicgradient
+    fn CreateConicGradient(
+        &self,
+        start_angle: Finite<f64>,
+        x: Finite<f64>,
+        y: Finite<f64>,
+    ) -> DomRoot<CanvasGradient> {
+        CanvasGradient::new(
+            &self.global(),
+            CanvasGradientStyle::Conic(ConicGradientStyle::new(*start_angle, *x, *y, Vec::new())),
+        )
+    }
+
     // https://html.spec.whatwg.org/multipage/#dom-context-2d-createpattern
     fn CreatePattern(
         &self,
@@ -1302,6 +1472,15 @@ impl CanvasRenderingContext2DMethods for CanvasRenderingContext2D {
                     .unwrap_or_else(|| vec![0; size.area() as usize * 4]);
                 (data, size)
             },
+            CanvasImageSource::OffscreenCanvas(ref canvas) => {
+                let (data, size) = canvas.fetch_all_data().ok_or(Error::InvalidState)?;
+                let data = data.unwrap_or_else(|| vec![0; size.area() as usize * 4]);
+                (data, size)
+            },
+            CanvasImageSource::ImageBitmap(ref bitmap) => {
+                let data = bitmap.bitmap_data().ok_or(Error::InvalidState)?;
+                (data, bitmap.get_size())
+            },
             CanvasImageSource::CSSStyleValue(ref value) => value
                 .get_url(self.base_url.clone())
                 .and_then(|url| self.fetch_image_data(url))
@@ -1453,6 +1632,19 @@ impl CanvasRenderingContext2DMethods for CanvasRenderingContext2D {
             self.send_canvas_2d_msg(Canvas2dMsg::SetShadowColor(color))
         }
     }
+
+    // https://html.spec.whatwg.org/multipage/#dom-context-2d-filter
+    fn Filter(&self) -> DOMString {
+        self.state.borrow().filter.clone()
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-context-2d-filter
+    fn SetFilter(&self, value: DOMString) {
+        if let Ok(filters) = parse_filter(&value) {
+            self.state.borrow_mut().filter = value;
+            self.send_canvas_2d_msg(Canvas2dMsg::SetFilter(filters))
+        }
+    }
 }
 
 impl Drop for CanvasRenderingContext2D {
@@ -1483,6 +1675,124 @@ pub fn parse_color(string: &str) -> Result<RGBA, ()> {
 
 // Used by drawImage to determine if a source or destination rectangle is valid
 // Origin coordinates and size cannot be negative. Size has to be greater than zero
+/// The filter functions of a `filter` attribute value, which is either
+/// `none` or a list of the CSS filter functions except `drop-shadow()`.
+///
+/// <https://html.spec.whatwg.org/multipage/#dom-context-2d-filter>
+fn parse_filter(string: &str) -> Result<Vec<FilterFunction>, ()> {
+    if string.trim().eq_ignore_ascii_case("none") {
+        return Ok(vec![]);
+    }
+
+    let mut input = ParserInput::new(string);
+    let mut parser = Parser::new(&mut input);
+    let mut filters = vec![];
+    while !parser.is_exhausted() {
+        let name = match parser.next() {
+            Ok(&Token::Function(ref name)) => name.clone(),
+            _ => return Err(()),
+        };
+        let filter = parser
+            .parse_nested_block(|input| {
+                parse_filter_function(&name, input)
+                    .map_err(|()| input.new_custom_error::<_, ()>(()))
+            })
+            .map_err(|_| ())?;
+        filters.push(filter);
+    }
+    if filters.is_empty() {
+        return Err(());
+    }
+    Ok(filters)
+}
+
+/// <https://drafts.fxtf.org/filter-effects/#supported-filter-functions>
+fn parse_filter_function(name: &str, input: &mut Parser) -> Result<FilterFunction, ()> {
+    Ok(match_ignore_ascii_case! { name,
+        "blur" => FilterFunction::Blur(parse_filter_length(input)?),
+        "brightness" => FilterFunction::Brightness(parse_filter_amount(input)?),
+        "contrast" => FilterFunction::Contrast(parse_filter_amount(input)?),
+        "grayscale" => FilterFunction::Grayscale(parse_filter_amount(input)?),
+        "hue-rotate" => FilterFunction::HueRotate(parse_filter_angle(input)?),
+        "invert" => FilterFunction::Invert(parse_filter_amount(input)?),
+        "opacity" => FilterFunction::Opacity(parse_filter_amount(input)?),
+        "saturate" => FilterFunction::Saturate(parse_filter_amount(input)?),
+        "sepia" => FilterFunction::Sepia(parse_filter_amount(input)?),
+        _ => return Err(()),
+    })
+}
+
+/// A non-negative number or percentage, 1 if omitted.
+fn parse_filter_amount(input: &mut Parser) -> Result<f64, ()> {
+    if input.is_exhausted() {
+        return Ok(1.);
+    }
+    let amount = match *input.next().map_err(|_| ())? {
+        Token::Number { value, .. } => value,
+        Token::Percentage { unit_value, .. } => unit_value,
+        _ => return Err(()),
+    };
+    if amount < 0. {
+        return Err(());
+    }
+    Ok(amount as f64)
+}
+
+/// An angle in degrees, 0 if omitted.
+fn parse_filter_angle(input: &mut Parser) -> Result<f64, ()> {
+    if input.is_exhausted() {
+        return Ok(0.);
+    }
+    match *input.next().map_err(|_| ())? {
+        Token::Number { value, .. } if value == 0. => Ok(0.),
+        Token::Dimension {
+            value, ref unit, ..
+        } => {
+            let value = value as f64;
+            Ok(match_ignore_ascii_case! { &**unit,
+                "deg" => value,
+                "grad" => value * 360. / 400.,
+                "rad" => value.to_degrees(),
+                "turn" => value * 360.,
+                _ => return Err(()),
+            })
+        },
+        _ => Err(()),
+    }
+}
+
+/// A non-negative length in pixels, 0 if omitted. Font-relative lengths are
+/// resolved against the default 10px font.
+fn parse_filter_length(input: &mut Parser) -> Result<f64, ()> {
+    if input.is_exhausted() {
+        return Ok(0.);
+    }
+    let length = match *input.next().map_err(|_| ())? {
+        Token::Number { value, .. } if value == 0. => 0.,
+        Token::Dimension {
+            value, ref unit, ..
+        } => {
+            let value = value as f64;
+            match_ignore_ascii_case! { &**unit,
+                "px" => value,
+                "in" => value * 96.,
+                "cm" => value * 96. / 2.54,
+                "mm" => value * 96. / 25.4,
+                "q" => value * 96. / 101.6,
+                "pt" => value * 96. / 72.,
+                "pc" => value * 16.,
+                "em" => value * 10.,
+                _ => return Err(()),
+            }
+        },
+        _ => return Err(()),
+    };
+    if length < 0. {
+        return Err(());
+    }
+    Ok(length)
+}
+
 fn is_rect_valid(rect: Rect<f64>) -> bool {
     rect.size.width > 0.0 && rect.size.height > 0.0
 }
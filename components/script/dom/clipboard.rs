@@ -0,0 +1,149 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::clipboard_provider::ClipboardProvider;
+use crate::dom::bindings::codegen::Bindings::ClipboardBinding::{self, ClipboardMethods};
+use crate::dom::bindings::codegen::Bindings::ClipboardItemBinding::PresentationStyle;
+use crate::dom::bindings::codegen::Bindings::PermissionStatusBinding::{
+    PermissionName, PermissionState,
+};
+use crate::dom::bindings::error::Error;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::str::DOMString;
+use crate::dom::blob::{Blob, BlobImpl};
+use crate::dom::clipboarditem::ClipboardItem;
+use crate::dom::eventtarget::EventTarget;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::permissions::request_permission_to_use;
+use crate::dom::promise::Promise;
+use dom_struct::dom_struct;
+use std::rc::Rc;
+
+/// The only type of data the clipboard of the embedder holds.
+const TEXT_PLAIN: &'static str = "text/plain";
+
+#[dom_struct]
+pub struct Clipboard {
+    eventtarget: EventTarget,
+}
+
+impl Clipboard {
+    fn new_inherited() -> Clipboard {
+        Clipboard {
+            eventtarget: EventTarget::new_inherited(),
+        }
+    }
+
+    pub fn new(global: &GlobalScope) -> DomRoot<Clipboard> {
+        reflect_dom_object(
+            Box::new(Clipboard::new_inherited()),
+            global,
+            ClipboardBinding::Wrap,
+        )
+    }
+
+    /// https://w3c.github.io/clipboard-apis/#check-clipboard-read-permission
+    fn check_read_permission(&self) -> bool {
+        request_permission_to_use(PermissionName::Clipboard_read, &self.global()) ==
+            PermissionState::Granted
+    }
+
+    /// https://w3c.github.io/clipboard-apis/#check-clipboard-write-permission
+    ///
+    /// The pages may write to the clipboard without asking, but only from
+    /// the handlers of the input of the user.
+    fn check_write_permission(&self) -> bool {
+        self.global().as_window().is_triggered_by_user_activation()
+    }
+
+    fn read_text(&self) -> String {
+        self.global()
+            .script_to_constellation_chan()
+            .clone()
+            .clipboard_contents()
+    }
+
+    fn write_text(&self, text: String) {
+        self.global()
+            .script_to_constellation_chan()
+            .clone()
+            .set_clipboard_contents(text);
+    }
+}
+
+impl ClipboardMethods for Clipboard {
+    #[allow(unsafe_code)]
+    // https://w3c.github.io/clipboard-apis/#dom-clipboard-read
+    fn Read(&self) -> Rc<Promise> {
+        let global = self.global();
+        let promise = unsafe { Promise::new_in_current_compartment(&global) };
+        if !self.check_read_permission() {
+            promise.reject_error(Error::NotAllowed);
+            return promise;
+        }
+        let blob = Blob::new(
+            &global,
+            BlobImpl::new_from_bytes(self.read_text().into_bytes()),
+            TEXT_PLAIN.to_owned(),
+        );
+        let item = ClipboardItem::new(
+            &global,
+            &[(DOMString::from(TEXT_PLAIN), blob)],
+            PresentationStyle::Unspecified,
+        );
+        promise.resolve_native(&vec![item]);
+        promise
+    }
+
+    #[allow(unsafe_code)]
+    // https://w3c.github.io/clipboard-apis/#dom-clipboard-readtext
+    fn ReadText(&self) -> Rc<Promise> {
+        let promise = unsafe { Promise::new_in_current_compartment(&self.global()) };
+        if !self.check_read_permission() {
+            promise.reject_error(Error::NotAllowed);
+            return promise;
+        }
+        promise.resolve_native(&DOMString::from(self.read_text()));
+        promise
+    }
+
+    #[allow(unsafe_code)]
+    // https://w3c.github.io/clipboard-apis/#dom-clipboard-write
+    fn Write(&self, data: Vec<DomRoot<ClipboardItem>>) -> Rc<Promise> {
+        let promise = unsafe { Promise::new_in_current_compartment(&self.global()) };
+        if !self.check_write_permission() {
+            promise.reject_error(Error::NotAllowed);
+            return promise;
+        }
+        // The clipboard of the embedder only holds a single piece of text.
+        let blob = match data.as_slice() {
+            [item] => item.get_blob(TEXT_PLAIN),
+            _ => None,
+        };
+        let bytes = match blob.map(|blob| blob.get_bytes()) {
+            Some(Ok(bytes)) => bytes,
+            _ => {
+                promise.reject_error(Error::NotAllowed);
+                return promise;
+            },
+        };
+        self.write_text(String::from_utf8_lossy(&bytes).into_owned());
+        promise.resolve_native(&());
+        promise
+    }
+
+    #[allow(unsafe_code)]
+    // https://w3c.github.io/clipboard-apis/#dom-clipboard-writetext
+    fn WriteText(&self, data: DOMString) -> Rc<Promise> {
+        let promise = unsafe { Promise::new_in_current_compartment(&self.global()) };
+        if !self.check_write_permission() {
+            promise.reject_error(Error::NotAllowed);
+            return promise;
+        }
+        self.write_text(data.into());
+        promise.resolve_native(&());
+        promise
+    }
+}
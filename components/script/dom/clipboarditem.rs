@@ -0,0 +1,139 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::ClipboardItemBinding;
+use crate::dom::bindings::codegen::Bindings::ClipboardItemBinding::{
+    ClipboardItemMethods, ClipboardItemOptions, PresentationStyle,
+};
+use crate::dom::bindings::codegen::UnionTypes::StringOrBlob;
+use crate::dom::bindings::error::{Error, Fallible};
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::mozmap::MozMap;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::bindings::str::DOMString;
+use crate::dom::blob::{Blob, BlobImpl};
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::promise::Promise;
+use crate::dom::window::Window;
+use dom_struct::dom_struct;
+use js::conversions::ToJSValConvertible;
+use js::jsapi::{Heap, JSContext};
+use js::jsval::{JSVal, UndefinedValue};
+use std::rc::Rc;
+
+#[dom_struct]
+pub struct ClipboardItem {
+    reflector_: Reflector,
+    /// The data of the item for each of its types, in the order they were
+    /// given.
+    items: Vec<(DOMString, Dom<Blob>)>,
+    presentation_style: PresentationStyle,
+    types: Heap<JSVal>,
+}
+
+impl ClipboardItem {
+    fn new_inherited(
+        items: &[(DOMString, DomRoot<Blob>)],
+        presentation_style: PresentationStyle,
+    ) -> ClipboardItem {
+        ClipboardItem {
+            reflector_: Reflector::new(),
+            items: items
+                .iter()
+                .map(|(type_, blob)| (type_.clone(), Dom::from_ref(&**blob)))
+                .collect(),
+            presentation_style,
+            types: Heap::default(),
+        }
+    }
+
+    #[allow(unsafe_code)]
+    pub fn new(
+        global: &GlobalScope,
+        items: &[(DOMString, DomRoot<Blob>)],
+        presentation_style: PresentationStyle,
+    ) -> DomRoot<ClipboardItem> {
+        let item = reflect_dom_object(
+            Box::new(ClipboardItem::new_inherited(items, presentation_style)),
+            global,
+            ClipboardItemBinding::Wrap,
+        );
+
+        let types: Vec<DOMString> = items.iter().map(|(type_, _)| type_.clone()).collect();
+        let cx = global.get_cx();
+        rooted!(in(cx) let mut types_value = UndefinedValue());
+        unsafe {
+            types.to_jsval(cx, types_value.handle_mut());
+        }
+        item.types.set(types_value.get());
+
+        item
+    }
+
+    // https://w3c.github.io/clipboard-apis/#dom-clipboarditem-clipboarditem
+    pub fn Constructor(
+        window: &Window,
+        items: MozMap<StringOrBlob>,
+        options: &ClipboardItemOptions,
+    ) -> Fallible<DomRoot<ClipboardItem>> {
+        // Step 1.
+        if items.is_empty() {
+            return Err(Error::Type("ClipboardItem needs some data".to_owned()));
+        }
+
+        let global = window.upcast::<GlobalScope>();
+        let items: Vec<_> = items
+            .iter()
+            .map(|(type_, data)| {
+                let blob = match *data {
+                    StringOrBlob::Blob(ref blob) => blob.clone(),
+                    StringOrBlob::String(ref text) => Blob::new(
+                        global,
+                        BlobImpl::new_from_bytes(text.as_bytes().to_vec()),
+                        type_.to_string(),
+                    ),
+                };
+                (type_.clone(), blob)
+            })
+            .collect();
+        Ok(ClipboardItem::new(
+            global,
+            &items,
+            options.presentationStyle,
+        ))
+    }
+
+    /// The blob holding the data of `type_`, if the item has some.
+    pub fn get_blob(&self, type_: &str) -> Option<DomRoot<Blob>> {
+        self.items
+            .iter()
+            .find(|(t, _)| &**t == type_)
+            .map(|(_, blob)| DomRoot::from_ref(&**blob))
+    }
+}
+
+impl ClipboardItemMethods for ClipboardItem {
+    // https://w3c.github.io/clipboard-apis/#dom-clipboarditem-presentationstyle
+    fn PresentationStyle(&self) -> PresentationStyle {
+        self.presentation_style
+    }
+
+    #[allow(unsafe_code)]
+    // https://w3c.github.io/clipboard-apis/#dom-clipboarditem-types
+    unsafe fn Types(&self, _cx: *mut JSContext) -> JSVal {
+        self.types.get()
+    }
+
+    #[allow(unsafe_code)]
+    // https://w3c.github.io/clipboard-apis/#dom-clipboarditem-gettype
+    fn GetType(&self, type_: DOMString) -> Rc<Promise> {
+        let promise = unsafe { Promise::new_in_current_compartment(&self.global()) };
+        match self.get_blob(&type_) {
+            Some(blob) => promise.resolve_native(&blob),
+            None => promise.reject_error(Error::NotFound),
+        }
+        promise
+    }
+}
@@ -26,6 +26,7 @@ use crate::dom::domexception::{DOMErrorName, DOMException};
 use crate::dom::element::{CustomElementState, Element};
 use crate::dom::globalscope::GlobalScope;
 use crate::dom::htmlelement::HTMLElement;
+use crate::dom::htmlformelement::{FormControl, HTMLFormElement};
 use crate::dom::node::{document_from_node, window_from_node, Node};
 use crate::dom::promise::Promise;
 use crate::dom::window::Window;
@@ -37,7 +38,7 @@ use js::conversions::ToJSValConvertible;
 use js::glue::UnwrapObject;
 use js::jsapi::{HandleValueArray, Heap, IsCallable, IsConstructor};
 use js::jsapi::{JSAutoCompartment, JSContext, JSObject};
-use js::jsval::{JSVal, NullValue, ObjectValue, UndefinedValue};
+use js::jsval::{BooleanValue, JSVal, NullValue, ObjectValue, UndefinedValue};
 use js::rust::wrappers::{Construct1, JS_GetProperty, JS_SameValue};
 use js::rust::{HandleObject, HandleValue, MutableHandleValue};
 use std::cell::Cell;
@@ -158,39 +159,93 @@ impl CustomElementRegistry {
             disconnected_callback: get_callback(cx, prototype, b"disconnectedCallback\0")?,
             adopted_callback: get_callback(cx, prototype, b"adoptedCallback\0")?,
             attribute_changed_callback: get_callback(cx, prototype, b"attributeChangedCallback\0")?,
+            form_associated_callback: None,
+            form_reset_callback: None,
+            form_disabled_callback: None,
         })
     }
 
     /// <https://html.spec.whatwg.org/multipage/#dom-customelementregistry-define>
-    /// Step 10.6
+    /// Step 10.11
     #[allow(unsafe_code)]
-    fn get_observed_attributes(&self, constructor: HandleObject) -> Fallible<Vec<DOMString>> {
+    unsafe fn get_form_callbacks(
+        &self,
+        prototype: HandleObject,
+        callbacks: &mut LifecycleCallbacks,
+    ) -> ErrorResult {
         let cx = self.window.get_cx();
-        rooted!(in(cx) let mut observed_attributes = UndefinedValue());
+
+        callbacks.form_associated_callback =
+            get_callback(cx, prototype, b"formAssociatedCallback\0")?;
+        callbacks.form_reset_callback = get_callback(cx, prototype, b"formResetCallback\0")?;
+        callbacks.form_disabled_callback = get_callback(cx, prototype, b"formDisabledCallback\0")?;
+        // Restoring the state of the elements isn't supported, but the
+        // callback is still checked to be callable.
+        get_callback(cx, prototype, b"formStateRestoreCallback\0")?;
+        Ok(())
+    }
+
+    /// Gets the property `name` of the constructor as a sequence of strings,
+    /// the way `observedAttributes` and `disabledFeatures` are.
+    ///
+    /// <https://html.spec.whatwg.org/multipage/#dom-customelementregistry-define>
+    /// Steps 10.6, 10.7
+    #[allow(unsafe_code)]
+    fn get_string_sequence(
+        &self,
+        constructor: HandleObject,
+        name: &[u8],
+    ) -> Fallible<Vec<DOMString>> {
+        let cx = self.window.get_cx();
+        rooted!(in(cx) let mut sequence = UndefinedValue());
         if unsafe {
             !JS_GetProperty(
                 cx,
                 constructor,
-                b"observedAttributes\0".as_ptr() as *const _,
-                observed_attributes.handle_mut(),
+                name.as_ptr() as *const _,
+                sequence.handle_mut(),
             )
         } {
             return Err(Error::JSFailed);
         }
 
-        if observed_attributes.is_undefined() {
+        if sequence.is_undefined() {
             return Ok(Vec::new());
         }
 
         let conversion = unsafe {
             FromJSValConvertible::from_jsval(
                 cx,
-                observed_attributes.handle(),
+                sequence.handle(),
                 StringificationBehavior::Default,
             )
         };
         match conversion {
-            Ok(ConversionResult::Success(attributes)) => Ok(attributes),
+            Ok(ConversionResult::Success(strings)) => Ok(strings),
+            Ok(ConversionResult::Failure(error)) => Err(Error::Type(error.into())),
+            _ => Err(Error::JSFailed),
+        }
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/#dom-customelementregistry-define>
+    /// Step 10.9
+    #[allow(unsafe_code)]
+    fn get_form_associated(&self, constructor: HandleObject) -> Fallible<bool> {
+        let cx = self.window.get_cx();
+        rooted!(in(cx) let mut form_associated = UndefinedValue());
+        if unsafe {
+            !JS_GetProperty(
+                cx,
+                constructor,
+                b"formAssociated\0".as_ptr() as *const _,
+                form_associated.handle_mut(),
+            )
+        } {
+            return Err(Error::JSFailed);
+        }
+
+        match unsafe { bool::from_jsval(cx, form_associated.handle(), ()) } {
+            Ok(ConversionResult::Success(form_associated)) => Ok(form_associated),
             Ok(ConversionResult::Failure(error)) => Err(Error::Type(error.into())),
             _ => Err(Error::JSFailed),
         }
@@ -317,7 +372,7 @@ impl CustomElementRegistryMethods for CustomElementRegistry {
 
         // Steps 10.3 - 10.4
         rooted!(in(cx) let proto_object = prototype.to_object());
-        let callbacks = {
+        let mut callbacks = {
             let _ac = JSAutoCompartment::new(cx, proto_object.get());
             match unsafe { self.get_callbacks(proto_object.handle()) } {
                 Ok(callbacks) => callbacks,
@@ -331,7 +386,7 @@ impl CustomElementRegistryMethods for CustomElementRegistry {
         // Step 10.5 - 10.6
         let observed_attributes = if callbacks.attribute_changed_callback.is_some() {
             let _ac = JSAutoCompartment::new(cx, constructor.get());
-            match self.get_observed_attributes(constructor.handle()) {
+            match self.get_string_sequence(constructor.handle(), b"observedAttributes\0") {
                 Ok(attributes) => attributes,
                 Err(error) => {
                     self.element_definition_is_running.set(false);
@@ -342,6 +397,38 @@ impl CustomElementRegistryMethods for CustomElementRegistry {
             Vec::new()
         };
 
+        // Steps 10.7 - 10.10
+        let (disable_internals, form_associated) = {
+            let _ac = JSAutoCompartment::new(cx, constructor.get());
+            let features = self
+                .get_string_sequence(constructor.handle(), b"disabledFeatures\0")
+                .and_then(|disabled_features| {
+                    let disable_internals = disabled_features
+                        .iter()
+                        .any(|feature| feature == "internals");
+                    self.get_form_associated(constructor.handle())
+                        .map(|form_associated| (disable_internals, form_associated))
+                });
+            match features {
+                Ok(features) => features,
+                Err(error) => {
+                    self.element_definition_is_running.set(false);
+                    return Err(error);
+                },
+            }
+        };
+
+        // Step 10.11
+        if form_associated {
+            let _ac = JSAutoCompartment::new(cx, proto_object.get());
+            if let Err(error) =
+                unsafe { self.get_form_callbacks(proto_object.handle(), &mut callbacks) }
+            {
+                self.element_definition_is_running.set(false);
+                return Err(error);
+            }
+        }
+
         self.element_definition_is_running.set(false);
 
         // Step 11
@@ -351,6 +438,8 @@ impl CustomElementRegistryMethods for CustomElementRegistry {
             constructor_,
             observed_attributes,
             callbacks,
+            form_associated,
+            disable_internals,
         ));
 
         // Step 12
@@ -446,6 +535,15 @@ pub struct LifecycleCallbacks {
 
     #[ignore_malloc_size_of = "Rc"]
     attribute_changed_callback: Option<Rc<Function>>,
+
+    #[ignore_malloc_size_of = "Rc"]
+    form_associated_callback: Option<Rc<Function>>,
+
+    #[ignore_malloc_size_of = "Rc"]
+    form_reset_callback: Option<Rc<Function>>,
+
+    #[ignore_malloc_size_of = "Rc"]
+    form_disabled_callback: Option<Rc<Function>>,
 }
 
 #[derive(Clone, JSTraceable, MallocSizeOf)]
@@ -468,6 +566,12 @@ pub struct CustomElementDefinition {
 
     pub callbacks: LifecycleCallbacks,
 
+    /// <https://html.spec.whatwg.org/multipage/#concept-custom-element-definition-form-associated>
+    pub form_associated: bool,
+
+    /// <https://html.spec.whatwg.org/multipage/#concept-custom-element-definition-disable-internals>
+    pub disable_internals: bool,
+
     pub construction_stack: DomRefCell<Vec<ConstructionStackEntry>>,
 }
 
@@ -478,6 +582,8 @@ impl CustomElementDefinition {
         constructor: Rc<CustomElementConstructor>,
         observed_attributes: Vec<DOMString>,
         callbacks: LifecycleCallbacks,
+        form_associated: bool,
+        disable_internals: bool,
     ) -> CustomElementDefinition {
         CustomElementDefinition {
             name: name,
@@ -485,6 +591,8 @@ impl CustomElementDefinition {
             constructor: constructor,
             observed_attributes: observed_attributes,
             callbacks: callbacks,
+            form_associated: form_associated,
+            disable_internals: disable_internals,
             construction_stack: Default::default(),
         }
     }
@@ -619,6 +727,16 @@ pub fn upgrade_element(definition: Rc<CustomElementDefinition>, element: &Elemen
 
     // Step 9
     element.set_custom_element_definition(definition);
+
+    // Step 10
+    if element.is_form_associated_custom_element() {
+        let element = element.downcast::<HTMLElement>().unwrap();
+        element.register_if_necessary();
+        element.reset_form_owner();
+        let element = element.upcast::<Element>();
+        element.check_disabled_attribute();
+        element.check_ancestors_disabled_state_for_form_control();
+    }
 }
 
 /// <https://html.spec.whatwg.org/multipage/#concept-upgrade-an-element>
@@ -722,6 +840,9 @@ pub enum CallbackReaction {
     Disconnected,
     Adopted(DomRoot<Document>, DomRoot<Document>),
     AttributeChanged(LocalName, Option<DOMString>, Option<DOMString>, Namespace),
+    FormAssociated(Option<DomRoot<HTMLFormElement>>),
+    FormDisabled(bool),
+    FormReset,
 }
 
 /// <https://html.spec.whatwg.org/multipage/#processing-the-backup-element-queue>
@@ -885,6 +1006,22 @@ impl CustomElementReactionStack {
                     args,
                 )
             },
+            CallbackReaction::FormAssociated(form) => {
+                let args = vec![Heap::default()];
+                match form {
+                    Some(form) => args[0].set(ObjectValue(form.reflector().get_jsobject().get())),
+                    None => args[0].set(NullValue()),
+                }
+                (definition.callbacks.form_associated_callback.clone(), args)
+            },
+            CallbackReaction::FormDisabled(disabled) => {
+                let args = vec![Heap::default()];
+                args[0].set(BooleanValue(disabled));
+                (definition.callbacks.form_disabled_callback.clone(), args)
+            },
+            CallbackReaction::FormReset => {
+                (definition.callbacks.form_reset_callback.clone(), Vec::new())
+            },
         };
 
         // Step 3
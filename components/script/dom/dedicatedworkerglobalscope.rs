@@ -32,10 +32,11 @@ use dom_struct::dom_struct;
 use ipc_channel::ipc::{self, IpcReceiver, IpcSender};
 use ipc_channel::router::ROUTER;
 use js::jsapi::JS_AddInterruptCallback;
-use js::jsapi::{JSAutoCompartment, JSContext};
+use js::jsapi::{JSAutoCompartment, JSContext, JSObject};
 use js::jsval::UndefinedValue;
-use js::rust::HandleValue;
+use js::rust::{CustomAutoRooterGuard, HandleValue};
 use msg::constellation_msg::{PipelineId, TopLevelBrowsingContextId};
+use net_traits::image_cache::ImageCache;
 use net_traits::request::{CredentialsMode, Destination, RequestInit};
 use net_traits::{load_whole_resource, IpcSend};
 use script_traits::{TimerEvent, TimerSource, WorkerGlobalScopeInit, WorkerScriptLoadOrigin};
@@ -171,6 +172,10 @@ pub struct DedicatedWorkerGlobalScope {
     #[ignore_malloc_size_of = "Can't measure trait objects"]
     /// Sender to the parent thread.
     parent_sender: Box<ScriptChan + Send>,
+    /// The image cache of the window that created this worker, if it
+    /// wasn't created by a service worker.
+    #[ignore_malloc_size_of = "Arc"]
+    image_cache: Option<Arc<dyn ImageCache>>,
 }
 
 impl WorkerEventLoopMethods for DedicatedWorkerGlobalScope {
@@ -220,6 +225,7 @@ impl DedicatedWorkerGlobalScope {
         timer_event_chan: IpcSender<TimerEvent>,
         timer_event_port: Receiver<(TrustedWorkerAddress, TimerEvent)>,
         closing: Arc<AtomicBool>,
+        image_cache: Option<Arc<dyn ImageCache>>,
     ) -> DedicatedWorkerGlobalScope {
         DedicatedWorkerGlobalScope {
             workerglobalscope: WorkerGlobalScope::new_inherited(
@@ -235,6 +241,7 @@ impl DedicatedWorkerGlobalScope {
             timer_event_port: timer_event_port,
             parent_sender: parent_sender,
             worker: DomRefCell::new(None),
+            image_cache: image_cache,
         }
     }
 
@@ -250,6 +257,7 @@ impl DedicatedWorkerGlobalScope {
         timer_event_chan: IpcSender<TimerEvent>,
         timer_event_port: Receiver<(TrustedWorkerAddress, TimerEvent)>,
         closing: Arc<AtomicBool>,
+        image_cache: Option<Arc<dyn ImageCache>>,
     ) -> DomRoot<DedicatedWorkerGlobalScope> {
         let cx = runtime.cx();
         let scope = Box::new(DedicatedWorkerGlobalScope::new_inherited(
@@ -263,6 +271,7 @@ impl DedicatedWorkerGlobalScope {
             timer_event_chan,
             timer_event_port,
             closing,
+            image_cache,
         ));
         unsafe { DedicatedWorkerGlobalScopeBinding::Wrap(cx, scope) }
     }
@@ -279,6 +288,7 @@ impl DedicatedWorkerGlobalScope {
         receiver: Receiver<DedicatedWorkerScriptMsg>,
         worker_load_origin: WorkerScriptLoadOrigin,
         closing: Arc<AtomicBool>,
+        image_cache: Option<Arc<dyn ImageCache>>,
     ) {
         let serialized_worker_url = worker_url.to_string();
         let name = format!("WebWorker for {}", serialized_worker_url);
@@ -366,6 +376,7 @@ impl DedicatedWorkerGlobalScope {
                     timer_ipc_chan,
                     timer_rx,
                     closing,
+                    image_cache,
                 );
                 // FIXME(njn): workers currently don't have a unique ID suitable for using in reporter
                 // registration (#6631), so we instead use a random number and cross our fingers.
@@ -408,6 +419,10 @@ impl DedicatedWorkerGlobalScope {
             .expect("Thread spawning failed");
     }
 
+    pub fn image_cache(&self) -> Option<Arc<dyn ImageCache>> {
+        self.image_cache.clone()
+    }
+
     pub fn script_chan(&self) -> Box<dyn ScriptChan + Send> {
         Box::new(WorkerThreadWorkerChan {
             sender: self.own_sender.clone(),
@@ -527,8 +542,13 @@ unsafe extern "C" fn interrupt_callback(cx: *mut JSContext) -> bool {
 impl DedicatedWorkerGlobalScopeMethods for DedicatedWorkerGlobalScope {
     #[allow(unsafe_code)]
     // https://html.spec.whatwg.org/multipage/#dom-dedicatedworkerglobalscope-postmessage
-    unsafe fn PostMessage(&self, cx: *mut JSContext, message: HandleValue) -> ErrorResult {
-        let data = StructuredCloneData::write(cx, message)?;
+    unsafe fn PostMessage(
+        &self,
+        cx: *mut JSContext,
+        message: HandleValue,
+        transfer: CustomAutoRooterGuard<Vec<*mut JSObject>>,
+    ) -> ErrorResult {
+        let data = StructuredCloneData::write(cx, message, Some(transfer))?;
         let worker = self.worker.borrow().as_ref().unwrap().clone();
         let pipeline_id = self.upcast::<GlobalScope>().pipeline_id();
         let task = Box::new(task!(post_worker_message: move || {
@@ -2,8 +2,11 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
+use crate::accessibility::AccessibilityTree;
 use crate::document_loader::{DocumentLoader, LoadType};
 use crate::dom::activation::{synthetic_click_activation, ActivationSource};
+use crate::dom::animation::Animation;
+use crate::dom::animationtimeline::AnimationTimeline;
 use crate::dom::attr::Attr;
 use crate::dom::beforeunloadevent::BeforeUnloadEvent;
 use crate::dom::bindings::callback::ExceptionHandling;
@@ -24,7 +27,7 @@ use crate::dom::bindings::codegen::Bindings::WindowBinding::{
     FrameRequestCallback, ScrollBehavior, WindowMethods,
 };
 use crate::dom::bindings::codegen::UnionTypes::NodeOrString;
-use crate::dom::bindings::error::{Error, ErrorResult, Fallible};
+use crate::dom::bindings::error::{Error, ErrorInfo, ErrorResult, Fallible};
 use crate::dom::bindings::inheritance::{Castable, ElementTypeId, HTMLElementTypeId, NodeTypeId};
 use crate::dom::bindings::num::Finite;
 use crate::dom::bindings::refcounted::{Trusted, TrustedPromise};
@@ -43,6 +46,7 @@ use crate::dom::cssstylesheet::CSSStyleSheet;
 use crate::dom::customelementregistry::CustomElementDefinition;
 use crate::dom::customevent::CustomEvent;
 use crate::dom::documentfragment::DocumentFragment;
+use crate::dom::documenttimeline::DocumentTimeline;
 use crate::dom::documenttype::DocumentType;
 use crate::dom::domimplementation::DOMImplementation;
 use crate::dom::element::CustomElementCreationMode;
@@ -53,6 +57,7 @@ use crate::dom::errorevent::ErrorEvent;
 use crate::dom::event::{Event, EventBubbles, EventCancelable, EventDefault, EventStatus};
 use crate::dom::eventtarget::EventTarget;
 use crate::dom::focusevent::FocusEvent;
+use crate::dom::fontfaceset::FontFaceSet;
 use crate::dom::globalscope::GlobalScope;
 use crate::dom::hashchangeevent::HashChangeEvent;
 use crate::dom::htmlanchorelement::HTMLAnchorElement;
@@ -70,6 +75,7 @@ use crate::dom::htmlimageelement::HTMLImageElement;
 use crate::dom::htmlmetaelement::HTMLMetaElement;
 use crate::dom::htmlscriptelement::{HTMLScriptElement, ScriptResult};
 use crate::dom::htmltitleelement::HTMLTitleElement;
+use crate::dom::intersectionobserver::IntersectionObserver;
 use crate::dom::keyboardevent::KeyboardEvent;
 use crate::dom::location::Location;
 use crate::dom::messageevent::MessageEvent;
@@ -80,14 +86,17 @@ use crate::dom::node::{LayoutNodeHelpers, Node, NodeDamage, NodeFlags};
 use crate::dom::nodeiterator::NodeIterator;
 use crate::dom::nodelist::NodeList;
 use crate::dom::pagetransitionevent::PageTransitionEvent;
+use crate::dom::pointerevent::{PointerEvent, MOUSE_POINTER_ID};
 use crate::dom::popstateevent::PopStateEvent;
 use crate::dom::processinginstruction::ProcessingInstruction;
 use crate::dom::progressevent::ProgressEvent;
 use crate::dom::promise::Promise;
 use crate::dom::range::Range;
+use crate::dom::resizeobserver::ResizeObserver;
 use crate::dom::servoparser::ServoParser;
 use crate::dom::storageevent::StorageEvent;
 use crate::dom::stylesheetlist::StyleSheetList;
+use crate::dom::svgsvgelement::SVGSVGElement;
 use crate::dom::text::Text;
 use crate::dom::touch::Touch;
 use crate::dom::touchevent::TouchEvent;
@@ -115,6 +124,7 @@ use hyper_serde::Serde;
 use ipc_channel::ipc::{self, IpcSender};
 use js::jsapi::JS_GetRuntime;
 use js::jsapi::{JSContext, JSObject, JSRuntime};
+use js::rust::HandleValue;
 use keyboard_types::{Key, KeyState, Modifiers};
 use metrics::{
     InteractiveFlag, InteractiveMetrics, InteractiveWindow, ProfilerMetadataFactory,
@@ -153,6 +163,7 @@ use style::attr::AttrValue;
 use style::context::QuirksMode;
 use style::invalidation::element::restyle_hints::RestyleHint;
 use style::media_queries::{Device, MediaList, MediaType};
+use style::properties::PropertyDeclaration;
 use style::selector_parser::{RestyleDamage, Snapshot};
 use style::shared_lock::{SharedRwLock as StyleSharedRwLock, SharedRwLockReadGuard};
 use style::str::{split_html_space_chars, str_join};
@@ -191,6 +202,49 @@ impl FireMouseEventType {
     }
 }
 
+/// The kind of device behind a pointer.
+///
+/// <https://w3c.github.io/pointerevents/#dom-pointerevent-pointertype>
+#[derive(Clone, Copy, JSTraceable, MallocSizeOf, PartialEq)]
+enum PointerType {
+    Mouse,
+    Touch,
+}
+
+impl PointerType {
+    fn as_str(&self) -> &'static str {
+        match *self {
+            PointerType::Mouse => "mouse",
+            PointerType::Touch => "touch",
+        }
+    }
+}
+
+/// A pointer the pointer events are fired for.
+#[derive(Clone, Copy, JSTraceable, MallocSizeOf)]
+struct ActivePointer {
+    id: i32,
+    pointer_type: PointerType,
+    is_primary: bool,
+}
+
+const MOUSE_POINTER: ActivePointer = ActivePointer {
+    id: MOUSE_POINTER_ID,
+    pointer_type: PointerType::Mouse,
+    is_primary: true,
+};
+
+/// The `button` and the bit in `buttons` of a mouse button.
+///
+/// <https://w3c.github.io/uievents/#dom-mouseevent-button>
+fn mouse_button_values(button: MouseButton) -> (i16, u16) {
+    match button {
+        MouseButton::Left => (0, 1),
+        MouseButton::Middle => (1, 4),
+        MouseButton::Right => (2, 2),
+    }
+}
+
 #[derive(Clone, Copy, Debug, JSTraceable, MallocSizeOf, PartialEq)]
 pub enum IsHTMLDocument {
     HTMLDocument,
@@ -296,6 +350,8 @@ pub struct Document {
     /// List of stylesheets associated with nodes in this document. |None| if the list needs to be refreshed.
     stylesheets: DomRefCell<DocumentStylesheetSet<StyleSheetInDocument>>,
     stylesheet_list: MutNullableDom<StyleSheetList>,
+    /// <https://drafts.csswg.org/css-font-loading/#dom-fontfacesource-fonts>
+    fonts: MutNullableDom<FontFaceSet>,
     ready_state: Cell<DocumentReadyState>,
     /// Whether the DOMContentLoaded event has already been dispatched.
     domcontentloaded_dispatched: Cell<bool>,
@@ -329,6 +385,8 @@ pub struct Document {
     /// Tracking this is not necessary for correctness. Instead, it is an optimization to avoid
     /// sending needless `ChangeRunningAnimationsState` messages to the compositor.
     running_animation_callbacks: Cell<bool>,
+    /// Whether animation frame callbacks are held back
+    animation_frame_suspension: Cell<AnimationFrameSuspension>,
     /// Tracks all outstanding loads related to this document.
     loader: DomRefCell<DocumentLoader>,
     /// The current active HTML parser, to allow resuming after interruptions.
@@ -343,11 +401,26 @@ pub struct Document {
     /// Information on elements needing restyle to ship over to the layout thread when the
     /// time comes.
     pending_restyles: DomRefCell<HashMap<Dom<Element>, PendingRestyle>>,
+    /// The accessibility tree last sent to the embedder, `None` until the
+    /// first one gets sent.
+    accessibility_tree: DomRefCell<Option<AccessibilityTree>>,
     /// This flag will be true if layout suppressed a reflow attempt that was
     /// needed in order for the page to be painted.
     needs_paint: Cell<bool>,
     /// <http://w3c.github.io/touch-events/#dfn-active-touch-point>
     active_touch_points: DomRefCell<Vec<Dom<Touch>>>,
+    /// The pointers of the active touch points, by touch identifier.
+    touch_pointers: DomRefCell<HashMap<i32, ActivePointer>>,
+    /// The `pointerId` given to the next touch point.
+    next_touch_pointer_id: Cell<i32>,
+    /// The mouse buttons that are pressed, as reported by `buttons`.
+    mouse_buttons: Cell<u16>,
+    /// <https://w3c.github.io/pointerevents/#dfn-prevent-mouse-event-flag>
+    prevent_mouse_events: Cell<bool>,
+    /// <https://w3c.github.io/pointerevents/#dfn-pending-pointer-capture-target-override>
+    pending_pointer_captures: DomRefCell<HashMap<i32, Dom<Element>>>,
+    /// <https://w3c.github.io/pointerevents/#dfn-pointer-capture-target-override>
+    pointer_captures: DomRefCell<HashMap<i32, Dom<Element>>>,
     /// Navigation Timing properties:
     /// <https://w3c.github.io/navigation-timing/#sec-PerformanceNavigationTiming>
     dom_loading: Cell<u64>,
@@ -410,6 +483,22 @@ pub struct Document {
     fired_unload: Cell<bool>,
     /// List of responsive images
     responsive_images: DomRefCell<Vec<Dom<HTMLImageElement>>>,
+    /// https://drafts.csswg.org/resize-observer/#dom-document-resizeobservers-slot
+    resize_observers: DomRefCell<Vec<Dom<ResizeObserver>>>,
+    /// https://w3c.github.io/IntersectionObserver/#document-intersectionobservers
+    intersection_observers: DomRefCell<Vec<Dom<IntersectionObserver>>>,
+    /// https://w3c.github.io/IntersectionObserver/#document-intersectionobservertaskqueued
+    intersection_observer_task_queued: Cell<bool>,
+    /// https://drafts.csswg.org/web-animations/#the-documents-default-timeline
+    timeline: MutNullableDom<DocumentTimeline>,
+    /// The animations that need updating on animation frames, because they
+    /// are running or their effects give values to properties
+    animations: DomRefCell<Vec<Dom<Animation>>>,
+    /// The elements that got values from animations on the last update
+    animated_elements: DomRefCell<Vec<Dom<Element>>>,
+    /// The outermost `svg` elements whose subtree changed since they were
+    /// last painted.
+    dirty_svgs: DomRefCell<Vec<Dom<SVGSVGElement>>>,
     /// Number of redirects for the document load
     redirect_count: Cell<u16>,
     /// Number of outstanding requests to prevent JS or layout from running.
@@ -853,7 +942,6 @@ impl Document {
         if let Some((x, y)) = point {
             // Step 3
             let global_scope = self.window.upcast::<GlobalScope>();
-            self.window.update_viewport_for_scroll(x, y);
             self.window.perform_a_scroll(
                 x,
                 y,
@@ -979,6 +1067,27 @@ impl Document {
         window.send_to_embedder(msg);
     }
 
+    /// Sends the changes made to the accessibility tree of this document
+    /// since the last update to the embedder.
+    #[allow(unrooted_must_root)]
+    pub fn update_accessibility_tree(&self) {
+        if !pref!(accessibility.enabled) || !self.window.is_top_level() {
+            return;
+        }
+        // The tree is taken out while the layout queries for the bounds of
+        // its nodes run, which don't change the DOM.
+        let mut tree = self
+            .accessibility_tree
+            .borrow_mut()
+            .take()
+            .unwrap_or_else(AccessibilityTree::new);
+        let update = tree.update(self);
+        *self.accessibility_tree.borrow_mut() = Some(tree);
+        if let Some(update) = update {
+            self.send_to_embedder(EmbedderMsg::UpdateAccessibilityTree(update));
+        }
+    }
+
     pub fn dirty_all_nodes(&self) {
         let root = self.upcast::<Node>();
         for node in root.traverse_preorder() {
@@ -990,7 +1099,7 @@ impl Document {
     pub fn handle_mouse_event(
         &self,
         js_runtime: *mut JSRuntime,
-        _button: MouseButton,
+        button: MouseButton,
         client_point: Point2D<f32>,
         mouse_event_type: MouseEventType,
         node_address: Option<UntrustedNodeAddress>,
@@ -1025,6 +1134,9 @@ impl Document {
             self.begin_focus_transaction();
         }
 
+        let mouse_target =
+            self.fire_mouse_button_pointer_event(button, client_point, &mouse_event_type, &el);
+
         // https://w3c.github.io/uievents/#event-type-click
         let client_x = client_point.x as i32;
         let client_y = client_point.y as i32;
@@ -1048,6 +1160,7 @@ impl Document {
             None,
             point_in_node,
         );
+        event.set_buttons(self.mouse_buttons.get());
         let event = event.upcast::<Event>();
 
         // https://w3c.github.io/uievents/#trusted-events
@@ -1061,16 +1174,18 @@ impl Document {
                     a.enter_formal_activation_state();
                 }
 
-                let target = node.upcast();
-                event.fire(target);
+                if let Some(target) = mouse_target {
+                    event.fire(target.upcast());
+                }
             },
             MouseEventType::MouseUp => {
                 if let Some(a) = activatable {
                     a.exit_formal_activation_state();
                 }
 
-                let target = node.upcast();
-                event.fire(target);
+                if let Some(target) = mouse_target {
+                    event.fire(target.upcast());
+                }
             },
         }
 
@@ -1165,6 +1280,7 @@ impl Document {
             None,
             None,
         );
+        mouse_event.set_buttons(self.mouse_buttons.get());
         let event = mouse_event.upcast::<Event>();
         event.fire(target);
     }
@@ -1201,7 +1317,23 @@ impl Document {
             None => return,
         };
 
-        self.fire_mouse_event(client_point, new_target.upcast(), FireMouseEventType::Move);
+        // The pointer events go to the element capturing the mouse, if any,
+        // and so do the compatibility mouse events.
+        // https://w3c.github.io/pointerevents/#the-pointermove-event
+        let move_target = self
+            .process_pending_pointer_capture(MOUSE_POINTER, client_point)
+            .unwrap_or_else(|| new_target.clone());
+        self.fire_pointer_event(
+            "pointermove",
+            move_target.upcast(),
+            client_point,
+            -1,
+            self.mouse_buttons.get(),
+            MOUSE_POINTER,
+        );
+        if !self.prevent_mouse_events.get() {
+            self.fire_mouse_event(client_point, move_target.upcast(), FireMouseEventType::Move);
+        }
 
         // Nothing more to do here, mousemove is sent,
         // and the element under the mouse hasn't changed.
@@ -1234,10 +1366,18 @@ impl Document {
             }
 
             // Remove hover state to old target and its parents
+            self.fire_pointer_event(
+                "pointerout",
+                old_target.upcast(),
+                client_point,
+                -1,
+                self.mouse_buttons.get(),
+                MOUSE_POINTER,
+            );
             self.fire_mouse_event(client_point, old_target.upcast(), FireMouseEventType::Out);
 
-            // TODO: Fire mouseleave here only if the old target is
-            // not an ancestor of the new target.
+            // TODO: Fire pointerleave and mouseleave here only if the old
+            // target is not an ancestor of the new target.
         }
 
         if let Some(ref new_target) = maybe_new_target {
@@ -1253,9 +1393,17 @@ impl Document {
                 element.set_hover_state(true);
             }
 
+            self.fire_pointer_event(
+                "pointerover",
+                new_target.upcast(),
+                client_point,
+                -1,
+                self.mouse_buttons.get(),
+                MOUSE_POINTER,
+            );
             self.fire_mouse_event(client_point, &new_target.upcast(), FireMouseEventType::Over);
 
-            // TODO: Fire mouseenter here.
+            // TODO: Fire pointerenter and mouseenter here.
         }
 
         // Store the current mouse over target for next frame.
@@ -1294,6 +1442,8 @@ impl Document {
             None => return TouchEventResult::Forwarded,
         };
 
+        self.fire_touch_pointer_event(event_type, identifier, point, &el);
+
         let target = DomRoot::upcast::<EventTarget>(el);
         let window = &*self.window;
 
@@ -1375,6 +1525,258 @@ impl Document {
         }
     }
 
+    /// Fires the pointer event of a change of a mouse button, and returns
+    /// the target of the compatibility mouse event, unless the pointer event
+    /// prevented it.
+    ///
+    /// <https://w3c.github.io/pointerevents/#compatibility-mapping-with-mouse-events>
+    fn fire_mouse_button_pointer_event(
+        &self,
+        button: MouseButton,
+        client_point: Point2D<f32>,
+        mouse_event_type: &MouseEventType,
+        hit_target: &Element,
+    ) -> Option<DomRoot<Element>> {
+        let (button, button_bit) = mouse_button_values(button);
+        let previous_buttons = self.mouse_buttons.get();
+        match *mouse_event_type {
+            MouseEventType::MouseDown => self.mouse_buttons.set(previous_buttons | button_bit),
+            MouseEventType::MouseUp => self.mouse_buttons.set(previous_buttons & !button_bit),
+            MouseEventType::Click => return Some(DomRoot::from_ref(hit_target)),
+        }
+
+        // Only the first button pressed and the last one released fire
+        // pointerdown and pointerup, the other changes are chorded.
+        // https://w3c.github.io/pointerevents/#chorded-button-interactions
+        let event_type = match *mouse_event_type {
+            MouseEventType::MouseDown if previous_buttons == 0 => "pointerdown",
+            MouseEventType::MouseUp if self.mouse_buttons.get() == 0 => "pointerup",
+            _ => "pointermove",
+        };
+        let target = self
+            .process_pending_pointer_capture(MOUSE_POINTER, client_point)
+            .unwrap_or_else(|| DomRoot::from_ref(hit_target));
+        let status = self.fire_pointer_event(
+            event_type,
+            target.upcast(),
+            client_point,
+            button,
+            self.mouse_buttons.get(),
+            MOUSE_POINTER,
+        );
+        if event_type == "pointerdown" && status == EventStatus::Canceled {
+            self.prevent_mouse_events.set(true);
+        }
+
+        let prevented = self.prevent_mouse_events.get();
+        if event_type == "pointerup" {
+            self.release_pointer_capture(MOUSE_POINTER_ID);
+            self.process_pending_pointer_capture(MOUSE_POINTER, client_point);
+            self.prevent_mouse_events.set(false);
+        }
+        if prevented {
+            None
+        } else {
+            Some(target)
+        }
+    }
+
+    /// Fires the pointer event of a change of the touch point `identifier`.
+    ///
+    /// <https://w3c.github.io/pointerevents/#mapping-for-devices-that-do-not-support-hover>
+    fn fire_touch_pointer_event(
+        &self,
+        event_type: TouchEventType,
+        identifier: i32,
+        client_point: Point2D<f32>,
+        hit_target: &Element,
+    ) {
+        let pointer = match event_type {
+            TouchEventType::Down => {
+                let mut touch_pointers = self.touch_pointers.borrow_mut();
+                let pointer = ActivePointer {
+                    id: self.next_touch_pointer_id.get(),
+                    pointer_type: PointerType::Touch,
+                    is_primary: touch_pointers.is_empty(),
+                };
+                self.next_touch_pointer_id.set(pointer.id + 1);
+                touch_pointers.insert(identifier, pointer);
+                pointer
+            },
+            _ => match self.touch_pointers.borrow().get(&identifier) {
+                Some(pointer) => *pointer,
+                None => return warn!("Got a touch event for a non-active touch point"),
+            },
+        };
+
+        let target = self
+            .process_pending_pointer_capture(pointer, client_point)
+            .unwrap_or_else(|| DomRoot::from_ref(hit_target));
+        let (pointer_event_type, button, buttons) = match event_type {
+            TouchEventType::Down => {
+                // https://w3c.github.io/pointerevents/#implicit-pointer-capture
+                self.set_pointer_capture(pointer.id, hit_target);
+                ("pointerdown", 0, 1)
+            },
+            TouchEventType::Move => ("pointermove", -1, 1),
+            TouchEventType::Up => ("pointerup", 0, 0),
+            TouchEventType::Cancel => ("pointercancel", -1, 0),
+        };
+        self.fire_pointer_event(
+            pointer_event_type,
+            target.upcast(),
+            client_point,
+            button,
+            buttons,
+            pointer,
+        );
+
+        if let TouchEventType::Up | TouchEventType::Cancel = event_type {
+            self.touch_pointers.borrow_mut().remove(&identifier);
+            self.release_pointer_capture(pointer.id);
+            self.process_pending_pointer_capture(pointer, client_point);
+        }
+    }
+
+    fn fire_pointer_event(
+        &self,
+        event_type: &str,
+        target: &EventTarget,
+        client_point: Point2D<f32>,
+        button: i16,
+        buttons: u16,
+        pointer: ActivePointer,
+    ) -> EventStatus {
+        // https://w3c.github.io/pointerevents/#pointer-event-types
+        let cancelable = match event_type {
+            "pointercancel" | "gotpointercapture" | "lostpointercapture" => {
+                EventCancelable::NotCancelable
+            },
+            _ => EventCancelable::Cancelable,
+        };
+        let event = PointerEvent::new(
+            &self.window,
+            DOMString::from(event_type),
+            EventBubbles::Bubbles,
+            cancelable,
+            client_point.x as i32,
+            client_point.y as i32,
+            button,
+            buttons,
+            None,
+            pointer.id,
+            pointer.pointer_type.as_str(),
+            pointer.is_primary,
+        );
+        event.upcast::<Event>().fire(target)
+    }
+
+    /// Whether `pointer_id` is the mouse or an active touch point.
+    pub fn is_active_pointer(&self, pointer_id: i32) -> bool {
+        pointer_id == MOUSE_POINTER_ID ||
+            self.touch_pointers
+                .borrow()
+                .values()
+                .any(|pointer| pointer.id == pointer_id)
+    }
+
+    /// <https://w3c.github.io/pointerevents/#dfn-set-pointer-capture>, once
+    /// the element and the pointer have been checked.
+    pub fn set_pointer_capture(&self, pointer_id: i32, element: &Element) {
+        // Only the mouse can be active without any button down, touch
+        // points are removed once they stop touching.
+        // https://w3c.github.io/pointerevents/#dfn-active-buttons-state
+        if pointer_id == MOUSE_POINTER_ID && self.mouse_buttons.get() == 0 {
+            return;
+        }
+        self.pending_pointer_captures
+            .borrow_mut()
+            .insert(pointer_id, Dom::from_ref(element));
+    }
+
+    /// <https://w3c.github.io/pointerevents/#dfn-release-pointer-capture>, once
+    /// the element and the pointer have been checked.
+    pub fn release_pointer_capture(&self, pointer_id: i32) {
+        self.pending_pointer_captures
+            .borrow_mut()
+            .remove(&pointer_id);
+    }
+
+    pub fn pending_pointer_capture(&self, pointer_id: i32) -> Option<DomRoot<Element>> {
+        self.pending_pointer_captures
+            .borrow()
+            .get(&pointer_id)
+            .map(|element| DomRoot::from_ref(&**element))
+    }
+
+    /// Fires the events of the changes of the capture of `pointer` since its
+    /// last event, and returns the element that captures it now, if any.
+    ///
+    /// <https://w3c.github.io/pointerevents/#process-pending-pointer-capture>
+    fn process_pending_pointer_capture(
+        &self,
+        pointer: ActivePointer,
+        client_point: Point2D<f32>,
+    ) -> Option<DomRoot<Element>> {
+        // An element loses its capture when it gets removed from the document.
+        let pending = self
+            .pending_pointer_capture(pointer.id)
+            .filter(|element| element.is_connected());
+        if pending.is_none() {
+            self.release_pointer_capture(pointer.id);
+        }
+        let current = self
+            .pointer_captures
+            .borrow()
+            .get(&pointer.id)
+            .map(|element| DomRoot::from_ref(&**element));
+        if current == pending {
+            return current;
+        }
+
+        match pending {
+            Some(ref element) => {
+                self.pointer_captures
+                    .borrow_mut()
+                    .insert(pointer.id, Dom::from_ref(&**element));
+            },
+            None => {
+                self.pointer_captures.borrow_mut().remove(&pointer.id);
+            },
+        }
+        let buttons = match pointer.pointer_type {
+            PointerType::Mouse => self.mouse_buttons.get(),
+            PointerType::Touch if self.is_active_pointer(pointer.id) => 1,
+            PointerType::Touch => 0,
+        };
+        if let Some(element) = current {
+            let target = if element.is_connected() {
+                DomRoot::upcast::<EventTarget>(element)
+            } else {
+                DomRoot::from_ref(self.upcast::<EventTarget>())
+            };
+            self.fire_pointer_event(
+                "lostpointercapture",
+                &target,
+                client_point,
+                -1,
+                buttons,
+                pointer,
+            );
+        }
+        if let Some(ref element) = pending {
+            self.fire_pointer_event(
+                "gotpointercapture",
+                element.upcast(),
+                client_point,
+                -1,
+                buttons,
+                pointer,
+            );
+        }
+        pending
+    }
+
     /// The entry point for all key processing for web content
     pub fn dispatch_key_event(&self, keyboard_event: ::keyboard_types::KeyboardEvent) {
         let focused = self.get_focused_element();
@@ -1457,7 +1859,7 @@ impl Document {
                             ActivationSource::NotFromClick,
                         )
                     }
-                }
+                },
                 Key::Enter if keyboard_event.state == KeyState::Up => {
                     let maybe_elem = target.downcast::<Element>();
                     if let Some(el) = maybe_elem {
@@ -1599,6 +2001,15 @@ impl Document {
 
         // TODO: Should tick animation only when document is visible
 
+        // The callbacks will get scheduled once the animation frames resume
+        if self.animation_frame_suspension.get().can_run() {
+            self.schedule_animation_frame_callbacks();
+        }
+
+        ident
+    }
+
+    fn schedule_animation_frame_callbacks(&self) {
         // If we are running 'fake' animation frames, we unconditionally
         // set up a one-shot timer for script to execute the rAF callbacks.
         if self.is_faking_animation_frames() {
@@ -1620,8 +2031,34 @@ impl Document {
                 ScriptMsg::ChangeRunningAnimationsState(AnimationState::AnimationCallbacksPresent);
             self.window().send_to_constellation(event);
         }
+    }
 
-        ident
+    /// Stops running animation frame callbacks until `resume_animation_frames()`
+    ///
+    /// https://immersive-web.github.io/webxr/#xr-animation-frame
+    pub fn suspend_animation_frames(&self) {
+        let mut suspension = self.animation_frame_suspension.get();
+        let was_running = suspension.suspend();
+        self.animation_frame_suspension.set(suspension);
+        if !was_running {
+            return;
+        }
+        // there is no need for the compositor to tick us in the meantime
+        let event =
+            ScriptMsg::ChangeRunningAnimationsState(AnimationState::NoAnimationCallbacksPresent);
+        self.window().send_to_constellation(event);
+    }
+
+    /// Runs the animation frame callbacks again, including those requested
+    /// while they were suspended
+    pub fn resume_animation_frames(&self) {
+        let mut suspension = self.animation_frame_suspension.get();
+        let has_callbacks = !self.animation_frame_list.borrow().is_empty();
+        let needs_scheduling = suspension.resume(has_callbacks);
+        self.animation_frame_suspension.set(suspension);
+        if needs_scheduling {
+            self.schedule_animation_frame_callbacks();
+        }
     }
 
     /// <https://html.spec.whatwg.org/multipage/#dom-window-cancelanimationframe>
@@ -1634,6 +2071,11 @@ impl Document {
 
     /// <https://html.spec.whatwg.org/multipage/#run-the-animation-frame-callbacks>
     pub fn run_the_animation_frame_callbacks(&self) {
+        // a tick may still have been on its way when the callbacks got suspended
+        if !self.animation_frame_suspension.get().can_run() {
+            return;
+        }
+
         rooted_vec!(let mut animation_frame_list);
         mem::swap(
             &mut *animation_frame_list,
@@ -1644,6 +2086,10 @@ impl Document {
         let was_faking_animation_frames = self.is_faking_animation_frames();
         let timing = self.global().performance().Now();
 
+        // https://drafts.csswg.org/web-animations/#update-animations-and-send-events
+        self.update_animations(*timing);
+        let animating = self.has_running_animations();
+
         for (_, callback) in animation_frame_list.drain(..) {
             if let Some(callback) = callback {
                 callback.call(self, *timing);
@@ -1681,7 +2127,13 @@ impl Document {
         // animation frame is one in which the callback did not mutate the DOM—that is, an
         // animation frame that wasn't actually used for animation.)
         let is_empty = self.animation_frame_list.borrow().is_empty();
-        if is_empty || (!was_faking_animation_frames && self.is_faking_animation_frames()) {
+        if animating && is_empty && self.is_faking_animation_frames() {
+            // nothing else is going to set up the next fake frame
+            self.schedule_animation_frame_callbacks();
+        }
+        if (is_empty && !animating) ||
+            (!was_faking_animation_frames && self.is_faking_animation_frames())
+        {
             if is_empty {
                 // If the current animation frame list in the DOM instance is empty,
                 // we can reuse the original `Vec<T>` that we put on the stack to
@@ -2469,6 +2921,215 @@ impl Document {
             self.responsive_images.borrow_mut().remove(i);
         }
     }
+
+    pub fn add_resize_observer(&self, observer: &ResizeObserver) {
+        self.resize_observers
+            .borrow_mut()
+            .push(Dom::from_ref(observer));
+    }
+
+    /// https://drafts.csswg.org/resize-observer/#gather-active-observations-h
+    fn gather_active_resize_observations_at_depth(&self, depth: usize) {
+        for observer in self.resize_observers.borrow().iter() {
+            observer.gather_active_observations_at_depth(&self.window, depth);
+        }
+    }
+
+    /// https://drafts.csswg.org/resize-observer/#has-active-observations-h
+    fn has_active_resize_observations(&self) -> bool {
+        self.resize_observers
+            .borrow()
+            .iter()
+            .any(|observer| observer.has_active_observations())
+    }
+
+    /// https://drafts.csswg.org/resize-observer/#has-skipped-observations-h
+    fn has_skipped_resize_observations(&self) -> bool {
+        self.resize_observers
+            .borrow()
+            .iter()
+            .any(|observer| observer.has_skipped_observations())
+    }
+
+    /// https://drafts.csswg.org/resize-observer/#broadcast-active-observations
+    fn broadcast_active_resize_observations(&self) -> usize {
+        // callbacks may create new observers
+        let observers: Vec<DomRoot<ResizeObserver>> = self
+            .resize_observers
+            .borrow()
+            .iter()
+            .map(|observer| DomRoot::from_ref(&**observer))
+            .collect();
+        observers
+            .iter()
+            .filter_map(|observer| observer.broadcast_active_observations(&self.window))
+            .min()
+            .unwrap_or(usize::max_value())
+    }
+
+    /// https://html.spec.whatwg.org/multipage/#update-the-rendering step 16,
+    /// which delivers resize observations until layout settles
+    pub fn update_resize_observations(&self) {
+        if self.resize_observers.borrow().is_empty() {
+            return;
+        }
+        self.gather_active_resize_observations_at_depth(0);
+        while self.has_active_resize_observations() {
+            let depth = self.broadcast_active_resize_observations();
+            // gathering queries the sizes again, which lays out whatever the
+            // callbacks changed
+            self.gather_active_resize_observations_at_depth(depth);
+        }
+        if self.has_skipped_resize_observations() {
+            // https://drafts.csswg.org/resize-observer/#deliver-resize-error
+            let error_info = ErrorInfo {
+                message: "ResizeObserver loop completed with undelivered notifications.".to_owned(),
+                filename: String::new(),
+                lineno: 0,
+                column: 0,
+            };
+            self.window
+                .upcast::<GlobalScope>()
+                .report_an_error(error_info, HandleValue::null());
+        }
+    }
+
+    pub fn add_intersection_observer(&self, observer: &IntersectionObserver) {
+        self.intersection_observers
+            .borrow_mut()
+            .push(Dom::from_ref(observer));
+    }
+
+    fn intersection_observers(&self) -> Vec<DomRoot<IntersectionObserver>> {
+        self.intersection_observers
+            .borrow()
+            .iter()
+            .map(|observer| DomRoot::from_ref(&**observer))
+            .collect()
+    }
+
+    /// https://w3c.github.io/IntersectionObserver/#update-intersection-observations-algo
+    pub fn update_intersection_observations(&self) {
+        if self.intersection_observers.borrow().is_empty() {
+            return;
+        }
+        let time = self.window.Performance().Now();
+        let mut queued = false;
+        for observer in self.intersection_observers() {
+            queued |= observer.update_intersection_observations(&self.window, time);
+        }
+        if queued {
+            self.queue_intersection_observer_task();
+        }
+    }
+
+    /// https://w3c.github.io/IntersectionObserver/#queue-intersection-observer-task
+    fn queue_intersection_observer_task(&self) {
+        if self.intersection_observer_task_queued.get() {
+            return;
+        }
+        self.intersection_observer_task_queued.set(true);
+        let document = Trusted::new(self);
+        self.window
+            .task_manager()
+            .dom_manipulation_task_source()
+            .queue(
+                task!(notify_intersection_observers: move || {
+                    document.root().notify_intersection_observers();
+                }),
+                self.window.upcast(),
+            )
+            .unwrap();
+    }
+
+    /// https://w3c.github.io/IntersectionObserver/#notify-intersection-observers-algo
+    fn notify_intersection_observers(&self) {
+        self.intersection_observer_task_queued.set(false);
+        for observer in self.intersection_observers() {
+            observer.notify();
+        }
+    }
+
+    /// Makes sure the animation gets updated on the next animation frame
+    pub fn register_animation(&self, animation: &Animation) {
+        {
+            let mut animations = self.animations.borrow_mut();
+            if !animations.iter().any(|other| &**other == animation) {
+                animations.push(Dom::from_ref(animation));
+            }
+        }
+        if self.animation_frame_suspension.get().can_run() {
+            self.schedule_animation_frame_callbacks();
+        }
+    }
+
+    fn has_running_animations(&self) -> bool {
+        self.animations
+            .borrow()
+            .iter()
+            .any(|animation| animation.is_running())
+    }
+
+    /// Moves the document timeline on to `now`, brings the animations up to
+    /// date and hands the values their effects give to properties over to
+    /// the elements they target
+    ///
+    /// https://drafts.csswg.org/web-animations/#update-animations-and-send-events
+    fn update_animations(&self, now: f64) {
+        if let Some(timeline) = self.timeline.get() {
+            timeline
+                .upcast::<AnimationTimeline>()
+                .set_current_time(Some(now));
+        }
+        if self.animations.borrow().is_empty() && self.animated_elements.borrow().is_empty() {
+            return;
+        }
+
+        // updating animations may run script that registers new ones
+        let animations: Vec<DomRoot<Animation>> = self
+            .animations
+            .borrow()
+            .iter()
+            .map(|animation| DomRoot::from_ref(&**animation))
+            .collect();
+        let mut effects: Vec<(DomRoot<Element>, Vec<PropertyDeclaration>)> = vec![];
+        for animation in &animations {
+            animation.update();
+            let (element, declarations) = match animation.effect_values() {
+                Some(values) => values,
+                None => continue,
+            };
+            match effects.iter_mut().find(|(other, _)| *other == element) {
+                Some((_, existing)) => {
+                    // later animations win over earlier ones
+                    existing.retain(|existing| {
+                        !declarations
+                            .iter()
+                            .any(|declaration| declaration.id() == existing.id())
+                    });
+                    existing.extend(declarations);
+                },
+                None => effects.push((element, declarations)),
+            }
+        }
+
+        for element in self.animated_elements.borrow().iter() {
+            if !effects.iter().any(|(other, _)| **other == **element) {
+                element.set_animation_declarations(vec![]);
+            }
+        }
+        *self.animated_elements.borrow_mut() = effects
+            .iter()
+            .map(|(element, _)| Dom::from_ref(&**element))
+            .collect();
+        for (element, declarations) in effects {
+            element.set_animation_declarations(declarations);
+        }
+
+        self.animations
+            .borrow_mut()
+            .retain(|animation| animation.is_relevant());
+    }
 }
 
 #[derive(MallocSizeOf, PartialEq)]
@@ -2679,6 +3340,7 @@ impl Document {
             },
             stylesheets: DomRefCell::new(DocumentStylesheetSet::new()),
             stylesheet_list: MutNullableDom::new(None),
+            fonts: MutNullableDom::new(None),
             ready_state: Cell::new(ready_state),
             domcontentloaded_dispatched: Cell::new(domcontentloaded_dispatched),
             possibly_focused: Default::default(),
@@ -2693,14 +3355,22 @@ impl Document {
             animation_frame_ident: Cell::new(0),
             animation_frame_list: DomRefCell::new(vec![]),
             running_animation_callbacks: Cell::new(false),
+            animation_frame_suspension: Cell::new(AnimationFrameSuspension::default()),
             loader: DomRefCell::new(doc_loader),
             current_parser: Default::default(),
             reflow_timeout: Cell::new(None),
             base_element: Default::default(),
             appropriate_template_contents_owner_document: Default::default(),
             pending_restyles: DomRefCell::new(HashMap::new()),
+            accessibility_tree: DomRefCell::new(None),
             needs_paint: Cell::new(false),
             active_touch_points: DomRefCell::new(Vec::new()),
+            touch_pointers: DomRefCell::new(HashMap::new()),
+            next_touch_pointer_id: Cell::new(MOUSE_POINTER_ID + 1),
+            mouse_buttons: Cell::new(0),
+            prevent_mouse_events: Cell::new(false),
+            pending_pointer_captures: DomRefCell::new(HashMap::new()),
+            pointer_captures: DomRefCell::new(HashMap::new()),
             dom_loading: Cell::new(Default::default()),
             dom_interactive: Cell::new(Default::default()),
             dom_content_loaded_event_start: Cell::new(Default::default()),
@@ -2731,6 +3401,13 @@ impl Document {
             salvageable: Cell::new(true),
             fired_unload: Cell::new(false),
             responsive_images: Default::default(),
+            resize_observers: Default::default(),
+            intersection_observers: Default::default(),
+            intersection_observer_task_queued: Cell::new(false),
+            timeline: Default::default(),
+            animations: Default::default(),
+            animated_elements: Default::default(),
+            dirty_svgs: Default::default(),
             redirect_count: Cell::new(0),
             completely_loaded: Cell::new(false),
             script_and_layout_blockers: Cell::new(0),
@@ -2882,6 +3559,31 @@ impl Document {
         have_changed
     }
 
+    /// Queues an outermost `svg` element to be painted again before the next
+    /// reflow.
+    pub fn invalidate_svg(&self, svg: &SVGSVGElement) {
+        let mut dirty_svgs = self.dirty_svgs.borrow_mut();
+        if !dirty_svgs.iter().any(|dirty| &**dirty == svg) {
+            dirty_svgs.push(Dom::from_ref(svg));
+        }
+    }
+
+    /// Paints the `svg` elements whose subtree changed, so that layout gets
+    /// their new image.
+    pub fn flush_dirty_svgs(&self) {
+        let dirty_svgs: Vec<DomRoot<SVGSVGElement>> = self
+            .dirty_svgs
+            .borrow_mut()
+            .drain(..)
+            .map(|svg| DomRoot::from_ref(&*svg))
+            .collect();
+        for svg in dirty_svgs {
+            if svg.upcast::<Node>().is_in_doc() {
+                svg.paint();
+            }
+        }
+    }
+
     /// Returns a `Device` suitable for media query evaluation.
     ///
     /// FIXME(emilio): This really needs to be somehow more in sync with layout.
@@ -3020,6 +3722,9 @@ impl Document {
     }
 
     pub fn ensure_pending_restyle(&self, el: &Element) -> RefMut<PendingRestyle> {
+        if let Some(ref mut tree) = *self.accessibility_tree.borrow_mut() {
+            tree.note_change(el.upcast());
+        }
         let map = self.pending_restyles.borrow_mut();
         RefMut::map(map, |m| {
             m.entry(Dom::from_ref(el))
@@ -3305,6 +4010,12 @@ impl DocumentMethods for Document {
             .or_init(|| StyleSheetList::new(&self.window, Dom::from_ref(&self)))
     }
 
+    // https://drafts.csswg.org/css-font-loading/#dom-fontfacesource-fonts
+    fn Fonts(&self) -> DomRoot<FontFaceSet> {
+        self.fonts
+            .or_init(|| FontFaceSet::new(self.window.upcast(), &[]))
+    }
+
     // https://dom.spec.whatwg.org/#dom-document-implementation
     fn Implementation(&self) -> DomRoot<DOMImplementation> {
         self.implementation.or_init(|| DOMImplementation::new(self))
@@ -4578,6 +5289,12 @@ impl DocumentMethods for Document {
     fn ExitFullscreen(&self) -> Rc<Promise> {
         self.exit_fullscreen()
     }
+
+    // https://drafts.csswg.org/web-animations/#dom-document-timeline
+    fn Timeline(&self) -> DomRoot<DocumentTimeline> {
+        self.timeline
+            .or_init(|| DocumentTimeline::new(&self.window))
+    }
 }
 
 fn update_with_current_time_ms(marker: &Cell<u64>) {
@@ -4617,6 +5334,31 @@ pub enum FocusEventType {
     Blur,  // Element lost focus. Doesn't bubble.
 }
 
+/// Whether a document's animation frame callbacks are held back, because an
+/// immersive XR session is rendering to the device instead of the page
+#[derive(Clone, Copy, Debug, Default, JSTraceable, MallocSizeOf, PartialEq)]
+pub struct AnimationFrameSuspension {
+    suspended: bool,
+}
+
+impl AnimationFrameSuspension {
+    /// Whether animation frame callbacks may be scheduled and run
+    pub fn can_run(&self) -> bool {
+        !self.suspended
+    }
+
+    /// Holds the callbacks back, returning whether they were running until now
+    pub fn suspend(&mut self) -> bool {
+        !mem::replace(&mut self.suspended, true)
+    }
+
+    /// Lets the callbacks run again, returning whether the ones requested
+    /// in the meantime need to be scheduled
+    pub fn resume(&mut self, has_callbacks: bool) -> bool {
+        mem::replace(&mut self.suspended, false) && has_callbacks
+    }
+}
+
 /// A fake `requestAnimationFrame()` callback—"fake" because it is not triggered by the video
 /// refresh but rather a simple timer.
 ///
@@ -0,0 +1,38 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::animationtimeline::AnimationTimeline;
+use crate::dom::bindings::codegen::Bindings::DocumentTimelineBinding;
+use crate::dom::bindings::codegen::Bindings::PerformanceBinding::PerformanceMethods;
+use crate::dom::bindings::codegen::Bindings::WindowBinding::WindowMethods;
+use crate::dom::bindings::reflector::reflect_dom_object;
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::window::Window;
+use dom_struct::dom_struct;
+
+/// The default timeline of a document, whose time is the time of the
+/// animation frame the document last updated its animations for
+#[dom_struct]
+pub struct DocumentTimeline {
+    animationtimeline: AnimationTimeline,
+}
+
+impl DocumentTimeline {
+    fn new_inherited(current_time: f64) -> DocumentTimeline {
+        DocumentTimeline {
+            animationtimeline: AnimationTimeline::new_inherited(Some(current_time)),
+        }
+    }
+
+    pub fn new(window: &Window) -> DomRoot<DocumentTimeline> {
+        // the origin time is the time origin of the window, so that the
+        // times of the timeline are those performance.now() gives
+        let current_time = *window.Performance().Now();
+        reflect_dom_object(
+            Box::new(DocumentTimeline::new_inherited(current_time)),
+            window,
+            DocumentTimelineBinding::Wrap,
+        )
+    }
+}
@@ -13,7 +13,7 @@ use crate::dom::globalscope::GlobalScope;
 use dom_struct::dom_struct;
 
 #[repr(u16)]
-#[derive(Clone, Copy, Debug, JSTraceable, MallocSizeOf)]
+#[derive(Clone, Copy, Debug, JSTraceable, MallocSizeOf, PartialEq, PartialOrd)]
 pub enum DOMErrorName {
     IndexSizeError = DOMExceptionConstants::INDEX_SIZE_ERR,
     HierarchyRequestError = DOMExceptionConstants::HIERARCHY_REQUEST_ERR,
@@ -37,6 +37,13 @@ pub enum DOMErrorName {
     InvalidNodeTypeError = DOMExceptionConstants::INVALID_NODE_TYPE_ERR,
     DataCloneError = DOMExceptionConstants::DATA_CLONE_ERR,
     NotReadableError = DOMExceptionConstants::NOT_READABLE_ERR,
+    ConstraintError,
+    DataError,
+    TransactionInactiveError,
+    ReadOnlyError,
+    VersionError,
+    OperationError,
+    NotAllowedError,
 }
 
 impl DOMErrorName {
@@ -64,6 +71,13 @@ impl DOMErrorName {
             "InvalidNodeTypeError" => Some(DOMErrorName::InvalidNodeTypeError),
             "DataCloneError" => Some(DOMErrorName::DataCloneError),
             "NotReadableError" => Some(DOMErrorName::NotReadableError),
+            "ConstraintError" => Some(DOMErrorName::ConstraintError),
+            "DataError" => Some(DOMErrorName::DataError),
+            "TransactionInactiveError" => Some(DOMErrorName::TransactionInactiveError),
+            "ReadOnlyError" => Some(DOMErrorName::ReadOnlyError),
+            "VersionError" => Some(DOMErrorName::VersionError),
+            "OperationError" => Some(DOMErrorName::OperationError),
+            "NotAllowedError" => Some(DOMErrorName::NotAllowedError),
             _ => None,
         }
     }
@@ -107,6 +121,25 @@ impl DOMException {
             },
             DOMErrorName::DataCloneError => "The object can not be cloned.",
             DOMErrorName::NotReadableError => "The I/O read operation failed.",
+            DOMErrorName::ConstraintError => {
+                "A mutation operation in the transaction failed because a constraint was not satisfied."
+            },
+            DOMErrorName::DataError => "The provided data is inadequate.",
+            DOMErrorName::TransactionInactiveError => {
+                "A request was placed against a transaction which is currently not active, or which is finished."
+            },
+            DOMErrorName::ReadOnlyError => {
+                "The mutating operation was attempted in a \"readonly\" transaction."
+            },
+            DOMErrorName::VersionError => {
+                "An attempt was made to open a database using a lower version than the existing version."
+            },
+            DOMErrorName::OperationError => {
+                "The operation failed for an operation-specific reason."
+            },
+            DOMErrorName::NotAllowedError => {
+                "The request is not allowed by the user agent or the platform in the current context."
+            },
         };
 
         (
@@ -150,8 +183,9 @@ impl DOMExceptionMethods for DOMException {
     // https://heycam.github.io/webidl/#dfn-DOMException
     fn Code(&self) -> u16 {
         match DOMErrorName::from(&self.name) {
-            Some(code) => code as u16,
-            None => 0 as u16,
+            // The names that come after NotReadableError have no legacy code.
+            Some(code) if code <= DOMErrorName::NotReadableError => code as u16,
+            _ => 0 as u16,
         }
     }
 
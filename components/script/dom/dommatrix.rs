@@ -405,4 +405,11 @@ impl DOMMatrixMethods for DOMMatrix {
         // Step 3.
         DomRoot::from_ref(&self)
     }
+
+    // Legacy alias, not part of the spec: a 2D scale that leaves is2D alone
+    fn ScaleNonUniformSelf(&self, scaleX: f64, scaleY: f64) -> DomRoot<DOMMatrix> {
+        self.upcast::<DOMMatrixReadOnly>()
+            .scale_self(scaleX, Some(scaleY), 1.0, 0.0, 0.0, 0.0);
+        DomRoot::from_ref(&self)
+    }
 }
@@ -12,17 +12,21 @@ use crate::dom::bindings::error;
 use crate::dom::bindings::error::Fallible;
 use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
 use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::str::DOMString;
+use crate::dom::bindings::utils::set_dictionary_property;
 use crate::dom::dommatrix::DOMMatrix;
 use crate::dom::dompoint::DOMPoint;
+use crate::dom::geometry::{all_finite, inverse_or_nan, normalized_vector, perspective_divide};
 use crate::dom::globalscope::GlobalScope;
 use dom_struct::dom_struct;
-use euclid::{Angle, Transform3D};
-use js::jsapi::{JSContext, JSObject};
+use euclid::{Angle, Point3D, Transform3D, Vector3D};
+use js::conversions::ToJSValConvertible;
+use js::jsapi::{JSContext, JSObject, JS_NewPlainObject};
+use js::jsval::UndefinedValue;
 use js::rust::CustomAutoRooterGuard;
 use js::typedarray::CreateWith;
 use js::typedarray::{Float32Array, Float64Array};
 use std::cell::{Cell, Ref};
-use std::f64;
 use std::ptr;
 use std::ptr::NonNull;
 
@@ -63,14 +67,99 @@ impl DOMMatrixReadOnly {
         dommatrixinit_to_matrix(&other).map(|(is2D, matrix)| Self::new(global, is2D, matrix))
     }
 
+    /// Creates an OpenGL-style perspective projection looking down the negative Z axis,
+    /// mapping the near plane to z = -1 and the far plane to z = 1 in clip space.
+    ///
+    /// `fovy` is the vertical field of view, in radians.
+    pub fn perspective(fovy: f64, aspect: f64, near: f64, far: f64) -> Transform3D<f64> {
+        let f = 1. / (fovy / 2.).tan();
+        let depth = near - far;
+        Transform3D::row_major(
+            f / aspect,
+            0.,
+            0.,
+            0.,
+            0.,
+            f,
+            0.,
+            0.,
+            0.,
+            0.,
+            (far + near) / depth,
+            -1.,
+            0.,
+            0.,
+            2. * far * near / depth,
+            0.,
+        )
+    }
+
+    /// Creates an OpenGL-style orthographic projection, with the same clip space
+    /// conventions as `perspective`
+    pub fn orthographic(
+        left: f64,
+        right: f64,
+        bottom: f64,
+        top: f64,
+        near: f64,
+        far: f64,
+    ) -> Transform3D<f64> {
+        Transform3D::ortho(left, right, bottom, top, near, far)
+    }
+
+    /// Transforms each of `points` (with an implicit w of 1) and projects the
+    /// results back into 3D by dividing by their w coordinate
+    ///
+    /// This is the bulk version of transformPoint(), for e.g. mesh vertices.
+    /// Points that end up with a w of zero are at infinity and come out as
+    /// NaNs.
+    pub fn transform_points(&self, points: &[Point3D<f64>]) -> Vec<Point3D<f64>> {
+        let mat = self.matrix.borrow();
+        points
+            .iter()
+            .map(|p| {
+                let x = p.x * mat.m11 + p.y * mat.m21 + p.z * mat.m31 + mat.m41;
+                let y = p.x * mat.m12 + p.y * mat.m22 + p.z * mat.m32 + mat.m42;
+                let z = p.x * mat.m13 + p.y * mat.m23 + p.z * mat.m33 + mat.m43;
+                let w = p.x * mat.m14 + p.y * mat.m24 + p.z * mat.m34 + mat.m44;
+                perspective_divide(x, y, z, w)
+            })
+            .collect()
+    }
+
     pub fn matrix(&self) -> Ref<Transform3D<f64>> {
         self.matrix.borrow()
     }
 
+    /// Whether both matrices have exactly the same elements and is2D flag
+    ///
+    /// Elements are compared with `==`, so a matrix containing NaN is never
+    /// equal to anything, not even to itself.
+    pub fn equals(&self, other: &DOMMatrixReadOnly) -> bool {
+        matrices_equal(
+            (self.is_2d(), &self.matrix()),
+            (other.is_2d(), &other.matrix()),
+        )
+    }
+
     pub fn is_2d(&self) -> bool {
         self.is2D.get()
     }
 
+    /// The determinant of the 4x4 matrix
+    pub fn determinant(&self) -> f64 {
+        self.matrix().determinant()
+    }
+
+    /// Whether `|determinant| > epsilon`, i.e. whether inverting the matrix
+    /// is expected to give a usable result rather than NaNs
+    ///
+    /// A matrix with NaN or infinite elements is never invertible.
+    pub fn is_invertible(&self, epsilon: f64) -> bool {
+        let determinant = self.determinant();
+        determinant.is_finite() && determinant.abs() > epsilon
+    }
+
     // https://drafts.fxtf.org/geometry-1/#dom-dommatrixreadonly-m11
     pub fn set_m11(&self, value: f64) {
         self.matrix.borrow_mut().m11 = value;
@@ -157,7 +246,7 @@ impl DOMMatrixReadOnly {
         dommatrixinit_to_matrix(&other).map(|(is2D, other_matrix)| {
             // Step 2.
             let mut matrix = self.matrix.borrow_mut();
-            *matrix = other_matrix.post_mul(&matrix);
+            *matrix = multiply_matrices(&matrix, &other_matrix);
             // Step 3.
             if !is2D {
                 self.is2D.set(false);
@@ -172,7 +261,7 @@ impl DOMMatrixReadOnly {
         dommatrixinit_to_matrix(&other).map(|(is2D, other_matrix)| {
             // Step 2.
             let mut matrix = self.matrix.borrow_mut();
-            *matrix = other_matrix.pre_mul(&matrix);
+            *matrix = multiply_matrices(&other_matrix, &matrix);
             // Step 3.
             if !is2D {
                 self.is2D.set(false);
@@ -262,6 +351,10 @@ impl DOMMatrixReadOnly {
         if rotX != 0.0 || rotY != 0.0 {
             self.is2D.set(false);
         }
+        // Steps 5-7 post-multiply the Z, Y and X rotations in that order, so the
+        // result is `current * Rz * Ry * Rx`: a point is rotated around X first
+        // and around Z last. `a.post_mul(&b)` applies `a` before `b`, which is
+        // why each rotation is post_mul'd with the current matrix.
         if rotZ != 0.0 {
             // Step 5.
             let rotation =
@@ -302,11 +395,11 @@ impl DOMMatrixReadOnly {
     // https://drafts.fxtf.org/geometry-1/#dom-dommatrix-rotateaxisangleself
     pub fn rotate_axis_angle_self(&self, x: f64, y: f64, z: f64, angle: f64) {
         // Step 1.
-        let (norm_x, norm_y, norm_z) = normalize_point(x, y, z);
+        let axis = normalized_vector(Vector3D::new(x, y, z));
         let rotation = Transform3D::create_rotation(
-            norm_x,
-            norm_y,
-            norm_z,
+            axis.x,
+            axis.y,
+            axis.z,
             Angle::radians(angle.to_radians()),
         );
         let mut matrix = self.matrix.borrow_mut();
@@ -340,28 +433,11 @@ impl DOMMatrixReadOnly {
     pub fn invert_self(&self) {
         let mut matrix = self.matrix.borrow_mut();
         // Step 1.
-        *matrix = matrix.inverse().unwrap_or_else(|| {
+        *matrix = inverse_or_nan(&matrix);
+        if !all_finite(&matrix.to_row_major_array()) {
             // Step 2.
             self.is2D.set(false);
-            Transform3D::row_major(
-                f64::NAN,
-                f64::NAN,
-                f64::NAN,
-                f64::NAN,
-                f64::NAN,
-                f64::NAN,
-                f64::NAN,
-                f64::NAN,
-                f64::NAN,
-                f64::NAN,
-                f64::NAN,
-                f64::NAN,
-                f64::NAN,
-                f64::NAN,
-                f64::NAN,
-                f64::NAN,
-            )
-        })
+        }
         // Step 3 in DOMMatrix.InvertSelf
     }
 
@@ -577,6 +653,12 @@ impl DOMMatrixReadOnlyMethods for DOMMatrixReadOnly {
         DOMMatrix::from_readonly(&self.global(), self).MultiplySelf(&other)
     }
 
+    // Not in the spec, the non-mutating counterpart of
+    // https://drafts.fxtf.org/geometry-1/#dom-dommatrix-premultiplyself
+    fn PreMultiply(&self, other: &DOMMatrixInit) -> Fallible<DomRoot<DOMMatrix>> {
+        DOMMatrix::from_readonly(&self.global(), self).PreMultiplySelf(&other)
+    }
+
     // https://drafts.fxtf.org/geometry-1/#dom-dommatrixreadonly-flipx
     fn FlipX(&self) -> DomRoot<DOMMatrix> {
         let is2D = self.is2D.get();
@@ -619,13 +701,38 @@ impl DOMMatrixReadOnlyMethods for DOMMatrixReadOnly {
         DOMPoint::new(&self.global(), x, y, z, w)
     }
 
+    // Not part of the spec, see transform_points()
+    #[allow(unsafe_code)]
+    unsafe fn TransformPoints(
+        &self,
+        cx: *mut JSContext,
+        points: CustomAutoRooterGuard<Float32Array>,
+    ) -> Fallible<NonNull<JSObject>> {
+        let coordinates = points.to_vec();
+        if coordinates.len() % 3 != 0 {
+            return Err(error::Error::Type(format!(
+                "Expected a multiple of 3 coordinates, but found {}.",
+                coordinates.len()
+            )));
+        }
+        let points: Vec<Point3D<f64>> = coordinates
+            .chunks(3)
+            .map(|c| Point3D::new(c[0] as f64, c[1] as f64, c[2] as f64))
+            .collect();
+        let vec: Vec<f32> = self
+            .transform_points(&points)
+            .iter()
+            .flat_map(|p| vec![p.x as f32, p.y as f32, p.z as f32])
+            .collect();
+        rooted!(in (cx) let mut array = ptr::null_mut::<JSObject>());
+        let _ = Float32Array::create(cx, CreateWith::Slice(&vec), array.handle_mut()).unwrap();
+        Ok(NonNull::new_unchecked(array.get()))
+    }
+
     // https://drafts.fxtf.org/geometry-1/#dom-dommatrixreadonly-tofloat32array
     #[allow(unsafe_code)]
     unsafe fn ToFloat32Array(&self, cx: *mut JSContext) -> NonNull<JSObject> {
-        let vec: Vec<f32> = self
-            .matrix
-            .borrow()
-            .to_row_major_array()
+        let vec: Vec<f32> = transform3d_to_column_major(&self.matrix.borrow())
             .iter()
             .map(|&x| x as f32)
             .collect();
@@ -637,11 +744,59 @@ impl DOMMatrixReadOnlyMethods for DOMMatrixReadOnly {
     // https://drafts.fxtf.org/geometry-1/#dom-dommatrixreadonly-tofloat64array
     #[allow(unsafe_code)]
     unsafe fn ToFloat64Array(&self, cx: *mut JSContext) -> NonNull<JSObject> {
-        let arr = self.matrix.borrow().to_row_major_array();
+        let arr = transform3d_to_column_major(&self.matrix.borrow());
         rooted!(in (cx) let mut array = ptr::null_mut::<JSObject>());
         let _ = Float64Array::create(cx, CreateWith::Slice(&arr), array.handle_mut()).unwrap();
         NonNull::new_unchecked(array.get())
     }
+
+    // https://drafts.fxtf.org/geometry-1/#dommatrixreadonly-stringification-behavior
+    fn Stringifier(&self) -> Fallible<DOMString> {
+        matrix_to_string(self.is2D.get(), &self.matrix.borrow())
+    }
+
+    // https://drafts.fxtf.org/geometry-1/#dom-dommatrixreadonly-tojson
+    #[allow(unsafe_code)]
+    unsafe fn ToJSON(&self, cx: *mut JSContext) -> NonNull<JSObject> {
+        // The default toJSON operation copies every attribute, in the order
+        // they are declared in
+        let numbers = [
+            ("a", self.A()),
+            ("b", self.B()),
+            ("c", self.C()),
+            ("d", self.D()),
+            ("e", self.E()),
+            ("f", self.F()),
+            ("m11", self.M11()),
+            ("m12", self.M12()),
+            ("m13", self.M13()),
+            ("m14", self.M14()),
+            ("m21", self.M21()),
+            ("m22", self.M22()),
+            ("m23", self.M23()),
+            ("m24", self.M24()),
+            ("m31", self.M31()),
+            ("m32", self.M32()),
+            ("m33", self.M33()),
+            ("m34", self.M34()),
+            ("m41", self.M41()),
+            ("m42", self.M42()),
+            ("m43", self.M43()),
+            ("m44", self.M44()),
+        ];
+        rooted!(in(cx) let object = JS_NewPlainObject(cx));
+        rooted!(in(cx) let mut value = UndefinedValue());
+        for &(name, number) in numbers.iter() {
+            number.to_jsval(cx, value.handle_mut());
+            set_dictionary_property(cx, object.handle(), name, value.handle()).unwrap();
+        }
+        let flags = [("is2D", self.Is2D()), ("isIdentity", self.IsIdentity())];
+        for &(name, flag) in flags.iter() {
+            flag.to_jsval(cx, value.handle_mut());
+            set_dictionary_property(cx, object.handle(), name, value.handle()).unwrap();
+        }
+        NonNull::new(object.get()).unwrap()
+    }
 }
 
 // https://drafts.fxtf.org/geometry-1/#create-a-2d-matrix
@@ -653,7 +808,36 @@ fn create_2d_matrix(entries: &[f64]) -> Transform3D<f64> {
 }
 
 // https://drafts.fxtf.org/geometry-1/#create-a-3d-matrix
-fn create_3d_matrix(entries: &[f64]) -> Transform3D<f64> {
+pub fn create_3d_matrix(entries: &[f64]) -> Transform3D<f64> {
+    let mut array = [0.; 16];
+    array.copy_from_slice(entries);
+    column_major_to_transform3d(&array)
+}
+
+/// Returns the elements of a matrix in the column-major order used by the
+/// geometry and WebXR specs, and by WebGL
+///
+/// euclid transforms row vectors, so its matrices are the transpose of the
+/// column-vector matrices in those specs, and its row-major order is their
+/// column-major order. Every matrix export should go through here.
+pub fn transform3d_to_column_major(matrix: &Transform3D<f64>) -> [f64; 16] {
+    matrix.to_row_major_array()
+}
+
+/// The product `a · b` of two matrices, in the column-vector notation of the
+/// geometry spec
+///
+/// The product transforms points by `b` first and by `a` second. With `t` a
+/// translation by (1, 0, 0) and `r` a rotation by 90° around z, `t · r`
+/// moves the origin to (1, 0, 0), but `r · t` moves it to (0, 1, 0).
+/// `a.multiply(b)` is `a · b`, `a.preMultiply(b)` is `b · a`.
+pub fn multiply_matrices(a: &Transform3D<f64>, b: &Transform3D<f64>) -> Transform3D<f64> {
+    // euclid uses row vectors, in which `b · a` applies `b` first
+    b.post_mul(a)
+}
+
+/// The inverse of transform3d_to_column_major()
+pub fn column_major_to_transform3d(entries: &[f64; 16]) -> Transform3D<f64> {
     Transform3D::row_major(
         entries[0],
         entries[1],
@@ -686,6 +870,74 @@ pub fn entries_to_matrix(entries: &[f64]) -> Fallible<(bool, Transform3D<f64>)>
     }
 }
 
+/// Compares two (is2D, matrix) pairs, see DOMMatrixReadOnly::equals()
+pub fn matrices_equal(a: (bool, &Transform3D<f64>), b: (bool, &Transform3D<f64>)) -> bool {
+    a.0 == b.0 &&
+        transform3d_to_column_major(a.1)
+            .iter()
+            .zip(transform3d_to_column_major(b.1).iter())
+            .all(|(a, b)| a == b)
+}
+
+// https://drafts.fxtf.org/geometry-1/#dommatrixreadonly-stringification-behavior
+pub fn matrix_to_string(is2D: bool, matrix: &Transform3D<f64>) -> Fallible<DOMString> {
+    let elements = transform3d_to_column_major(matrix);
+    // Step 1.
+    if !all_finite(&elements) {
+        return Err(error::Error::InvalidState);
+    }
+    // Steps 2-3.
+    let (prefix, elements) = if is2D {
+        let [a, b, _, _, c, d, _, _, _, _, _, _, e, f, _, _] = elements;
+        ("matrix", vec![a, b, c, d, e, f])
+    } else {
+        ("matrix3d", elements.to_vec())
+    };
+    let elements: Vec<String> = elements.into_iter().map(number_to_string).collect();
+    let serialization = format!("{}({})", prefix, elements.join(", "));
+    Ok(DOMString::from(serialization))
+}
+
+/// Serializes a finite number the way ECMAScript's Number::toString does
+///
+/// Rust's shortest round-trip digits are the same ones ECMAScript picks,
+/// only the placement of the decimal point and exponent differs.
+pub fn number_to_string(value: f64) -> String {
+    debug_assert!(value.is_finite());
+    // this also takes care of -0, which serializes as "0"
+    if value == 0. {
+        return "0".to_owned();
+    }
+    let sign = if value < 0. { "-" } else { "" };
+    let scientific = format!("{:e}", value.abs());
+    let mut parts = scientific.split('e');
+    let digits: String = parts
+        .next()
+        .unwrap()
+        .chars()
+        .filter(|&c| c != '.')
+        .collect();
+    let k = digits.len() as i32;
+    let n = parts.next().unwrap().parse::<i32>().unwrap() + 1;
+    let number = if k <= n && n <= 21 {
+        format!("{}{}", digits, "0".repeat((n - k) as usize))
+    } else if 0 < n && n <= 21 {
+        let (integer, fraction) = digits.split_at(n as usize);
+        format!("{}.{}", integer, fraction)
+    } else if -6 < n && n <= 0 {
+        format!("0.{}{}", "0".repeat(-n as usize), digits)
+    } else {
+        let (first, rest) = digits.split_at(1);
+        let exponent_sign = if n > 0 { "+" } else { "-" };
+        if rest.is_empty() {
+            format!("{}e{}{}", first, exponent_sign, (n - 1).abs())
+        } else {
+            format!("{}.{}e{}{}", first, rest, exponent_sign, (n - 1).abs())
+        }
+    };
+    format!("{}{}", sign, number)
+}
+
 // https://drafts.fxtf.org/geometry-1/#validate-and-fixup
 pub fn dommatrixinit_to_matrix(dict: &DOMMatrixInit) -> Fallible<(bool, Transform3D<f64>)> {
     // Step 1.
@@ -749,13 +1001,3 @@ pub fn dommatrixinit_to_matrix(dict: &DOMMatrixInit) -> Fallible<(bool, Transfor
         Ok((is2D.unwrap(), matrix))
     }
 }
-
-#[inline]
-fn normalize_point(x: f64, y: f64, z: f64) -> (f64, f64, f64) {
-    let len = (x * x + y * y + z * z).sqrt();
-    if len == 0.0 {
-        (0.0, 0.0, 0.0)
-    } else {
-        (x / len, y / len, z / len)
-    }
-}
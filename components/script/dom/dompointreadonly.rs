@@ -2,14 +2,17 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
+use crate::dom::bindings::codegen::Bindings::DOMPointBinding::DOMPointInit;
 use crate::dom::bindings::codegen::Bindings::DOMPointReadOnlyBinding::{
     DOMPointReadOnlyMethods, Wrap,
 };
 use crate::dom::bindings::error::Fallible;
 use crate::dom::bindings::reflector::{reflect_dom_object, Reflector};
 use crate::dom::bindings::root::DomRoot;
+use crate::dom::geometry::perspective_divide;
 use crate::dom::globalscope::GlobalScope;
 use dom_struct::dom_struct;
+use euclid::{Point3D, Vector3D};
 use std::cell::Cell;
 
 // http://dev.w3.org/fxtf/geometry/Overview.html#dompointreadonly
@@ -50,6 +53,105 @@ impl DOMPointReadOnly {
     ) -> Fallible<DomRoot<DOMPointReadOnly>> {
         Ok(DOMPointReadOnly::new(global, x, y, z, w))
     }
+
+    // https://drafts.fxtf.org/geometry/#dom-dompointreadonly-frompoint
+    pub fn FromPoint(global: &GlobalScope, init: &DOMPointInit) -> DomRoot<Self> {
+        Self::new(global, init.x, init.y, init.z, init.w)
+    }
+
+    /// Whether this homogeneous point is a direction rather than a position
+    pub fn is_vector(&self) -> bool {
+        self.w.get() == 0.
+    }
+
+    /// Converts a direction (w = 0) to a vector
+    pub fn as_vector3d(&self) -> Vector3D<f64> {
+        debug_assert!(self.is_vector(), "expected w = 0, got {}", self.w.get());
+        Vector3D::new(self.x.get(), self.y.get(), self.z.get())
+    }
+
+    /// Converts a position (w != 0) to a point, dividing by w
+    ///
+    /// Points that went through a projective transform keep the w it left
+    /// them with, so reading x, y and z as a translation without this
+    /// perspective divide gives the wrong position.
+    pub fn as_point3d(&self) -> Point3D<f64> {
+        debug_assert!(!self.is_vector(), "expected a point, got w = 0");
+        perspective_divide(self.x.get(), self.y.get(), self.z.get(), self.w.get())
+    }
+
+    /// Adds `other` to this point, returning the x, y, z and w of the sum
+    ///
+    /// Two directions add up to a direction (w = 0). A direction added to a
+    /// position moves it and keeps its w, with the direction scaled by that
+    /// w so that the perspective divide doesn't shrink the move. Adding two
+    /// positions has no meaning.
+    pub fn add(&self, other: &DOMPointReadOnly) -> [f64; 4] {
+        let (a, b) = match (self.is_vector(), other.is_vector()) {
+            (_, true) => (self, other),
+            (true, false) => (other, self),
+            (false, false) => {
+                debug_assert!(false, "can't add two positions");
+                (self, other)
+            },
+        };
+        let w = a.w.get();
+        let scale = if a.is_vector() { 1. } else { w };
+        [
+            a.x.get() + b.x.get() * scale,
+            a.y.get() + b.y.get() * scale,
+            a.z.get() + b.z.get() * scale,
+            w,
+        ]
+    }
+
+    /// Subtracts `other` from this point, returning the x, y, z and w of the
+    /// difference
+    ///
+    /// The difference of two positions is the direction between them
+    /// (w = 0), taken after the perspective divide of both. Subtracting a
+    /// direction works like adding its opposite, and subtracting a position
+    /// from a direction has no meaning.
+    pub fn sub(&self, other: &DOMPointReadOnly) -> [f64; 4] {
+        match (self.is_vector(), other.is_vector()) {
+            (false, false) => {
+                let difference = self.as_point3d() - other.as_point3d();
+                [difference.x, difference.y, difference.z, 0.]
+            },
+            (vector, true) => {
+                let scale = if vector { 1. } else { self.w.get() };
+                [
+                    self.x.get() - other.x.get() * scale,
+                    self.y.get() - other.y.get() * scale,
+                    self.z.get() - other.z.get() * scale,
+                    self.w.get(),
+                ]
+            },
+            (true, false) => {
+                debug_assert!(false, "can't subtract a position from a direction");
+                [
+                    self.x.get() - other.x.get(),
+                    self.y.get() - other.y.get(),
+                    self.z.get() - other.z.get(),
+                    0.,
+                ]
+            },
+        }
+    }
+
+    /// Scales x, y and z by `factor`, returning the x, y, z and w of the
+    /// result
+    ///
+    /// w is kept, so a direction stays a direction and a position is scaled
+    /// about the origin.
+    pub fn scale(&self, factor: f64) -> [f64; 4] {
+        [
+            self.x.get() * factor,
+            self.y.get() * factor,
+            self.z.get() * factor,
+            self.w.get(),
+        ]
+    }
 }
 
 impl DOMPointReadOnlyMethods for DOMPointReadOnly {
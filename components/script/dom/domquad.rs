@@ -12,6 +12,11 @@ use crate::dom::dompoint::DOMPoint;
 use crate::dom::domrect::DOMRect;
 use crate::dom::globalscope::GlobalScope;
 use dom_struct::dom_struct;
+use js::conversions::ToJSValConvertible;
+use js::jsapi::{JSContext, JSObject};
+use js::jsval::UndefinedValue;
+use std::f64;
+use std::ptr::NonNull;
 
 // https://drafts.fxtf.org/geometry/#DOMQuad
 #[dom_struct]
@@ -116,31 +121,44 @@ impl DOMQuadMethods for DOMQuad {
 
     // https://drafts.fxtf.org/geometry/#dom-domquad-getbounds
     fn GetBounds(&self) -> DomRoot<DOMRect> {
-        let left = self
-            .p1
-            .X()
-            .min(self.p2.X())
-            .min(self.p3.X())
-            .min(self.p4.X());
-        let top = self
-            .p1
-            .Y()
-            .min(self.p2.Y())
-            .min(self.p3.Y())
-            .min(self.p4.Y());
-        let right = self
-            .p1
-            .X()
-            .max(self.p2.X())
-            .max(self.p3.X())
-            .max(self.p4.X());
-        let bottom = self
-            .p1
-            .Y()
-            .max(self.p2.Y())
-            .max(self.p3.Y())
-            .max(self.p4.Y());
+        // The bounds are taken over all four corners, so the winding of the
+        // quad doesn't matter, even if it is self-intersecting.
+        let xs = [self.p1.X(), self.p2.X(), self.p3.X(), self.p4.X()];
+        let ys = [self.p1.Y(), self.p2.Y(), self.p3.Y(), self.p4.Y()];
+        let left = nan_safe_fold(&xs, |a, b| a.min(b));
+        let top = nan_safe_fold(&ys, |a, b| a.min(b));
+        let right = nan_safe_fold(&xs, |a, b| a.max(b));
+        let bottom = nan_safe_fold(&ys, |a, b| a.max(b));
 
         DOMRect::new(&self.global(), left, top, right - left, bottom - top)
     }
+
+    #[allow(unsafe_code)]
+    // https://drafts.fxtf.org/geometry/#dom-domquad-tojson
+    unsafe fn ToJSON(&self, cx: *mut JSContext) -> NonNull<JSObject> {
+        let corner = |point: &DOMPoint| DOMPointInit {
+            x: point.X(),
+            y: point.Y(),
+            z: point.Z(),
+            w: point.W(),
+        };
+        let init = DOMQuadInit {
+            p1: corner(&self.p1),
+            p2: corner(&self.p2),
+            p3: corner(&self.p3),
+            p4: corner(&self.p4),
+        };
+        rooted!(in(cx) let mut jsval = UndefinedValue());
+        init.to_jsval(cx, jsval.handle_mut());
+        NonNull::new(jsval.to_object()).unwrap()
+    }
+}
+
+// https://drafts.fxtf.org/geometry/#nan-safe-minimum
+// https://drafts.fxtf.org/geometry/#nan-safe-maximum
+fn nan_safe_fold(values: &[f64; 4], f: fn(f64, f64) -> f64) -> f64 {
+    if values.iter().any(|v| v.is_nan()) {
+        return f64::NAN;
+    }
+    values[1..].iter().fold(values[0], |acc, &v| f(acc, v))
 }
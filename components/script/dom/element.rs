@@ -5,19 +5,25 @@
 //! Element nodes.
 
 use crate::dom::activation::Activatable;
+use crate::dom::animation::Animation;
+use crate::dom::animationeffect::Timing;
 use crate::dom::attr::{Attr, AttrHelpersForLayout};
 use crate::dom::bindings::cell::DomRefCell;
 use crate::dom::bindings::codegen::Bindings::AttrBinding::AttrMethods;
 use crate::dom::bindings::codegen::Bindings::DocumentBinding::DocumentMethods;
 use crate::dom::bindings::codegen::Bindings::ElementBinding;
 use crate::dom::bindings::codegen::Bindings::ElementBinding::ElementMethods;
+use crate::dom::bindings::codegen::Bindings::ElementBinding::ScrollLogicalPosition;
 use crate::dom::bindings::codegen::Bindings::EventBinding::EventMethods;
 use crate::dom::bindings::codegen::Bindings::FunctionBinding::Function;
 use crate::dom::bindings::codegen::Bindings::HTMLTemplateElementBinding::HTMLTemplateElementMethods;
 use crate::dom::bindings::codegen::Bindings::NodeBinding::NodeMethods;
 use crate::dom::bindings::codegen::Bindings::WindowBinding::WindowMethods;
 use crate::dom::bindings::codegen::Bindings::WindowBinding::{ScrollBehavior, ScrollToOptions};
+use crate::dom::bindings::codegen::UnionTypes::BooleanOrScrollIntoViewOptions;
 use crate::dom::bindings::codegen::UnionTypes::NodeOrString;
+use crate::dom::bindings::codegen::UnionTypes::StringStringRecordSequenceOrStringStringOrStringSequenceRecord as Keyframes;
+use crate::dom::bindings::codegen::UnionTypes::UnrestrictedDoubleOrKeyframeAnimationOptions;
 use crate::dom::bindings::conversions::DerivedFrom;
 use crate::dom::bindings::error::{Error, ErrorResult, Fallible};
 use crate::dom::bindings::inheritance::{Castable, ElementTypeId, HTMLElementTypeId, NodeTypeId};
@@ -68,6 +74,7 @@ use crate::dom::htmltablesectionelement::{
 };
 use crate::dom::htmltemplateelement::HTMLTemplateElement;
 use crate::dom::htmltextareaelement::{HTMLTextAreaElement, LayoutHTMLTextAreaElementHelpers};
+use crate::dom::keyframeeffect::{timing_for_duration, KeyframeEffect};
 use crate::dom::mutationobserver::{Mutation, MutationObserver};
 use crate::dom::namednodemap::NamedNodeMap;
 use crate::dom::node::{document_from_node, window_from_node};
@@ -76,6 +83,7 @@ use crate::dom::node::{NodeDamage, NodeFlags, UnbindContext};
 use crate::dom::nodelist::NodeList;
 use crate::dom::promise::Promise;
 use crate::dom::servoparser::ServoParser;
+use crate::dom::svgsvgelement::SVGSVGElement;
 use crate::dom::text::Text;
 use crate::dom::validation::Validatable;
 use crate::dom::virtualmethods::{vtable_for, VirtualMethods};
@@ -154,6 +162,11 @@ pub struct Element {
     is: DomRefCell<Option<LocalName>>,
     #[ignore_malloc_size_of = "Arc"]
     style_attribute: DomRefCell<Option<Arc<Locked<PropertyDeclarationBlock>>>>,
+    /// The values the script-generated animations targeting this element
+    /// currently give to their properties, which style cascades at the
+    /// animations level
+    #[ignore_malloc_size_of = "Arc"]
+    animation_declarations: DomRefCell<Option<Arc<Locked<PropertyDeclarationBlock>>>>,
     attr_list: MutNullableDom<NamedNodeMap>,
     class_list: MutNullableDom<DOMTokenList>,
     state: Cell<ElementState>,
@@ -290,6 +303,7 @@ impl Element {
             id_attribute: DomRefCell::new(None),
             is: DomRefCell::new(None),
             style_attribute: DomRefCell::new(None),
+            animation_declarations: DomRefCell::new(None),
             attr_list: Default::default(),
             class_list: Default::default(),
             state: Cell::new(state),
@@ -352,6 +366,19 @@ impl Element {
         (*self.custom_element_definition.borrow()).clone()
     }
 
+    /// <https://html.spec.whatwg.org/multipage/#form-associated-custom-element>
+    pub fn is_form_associated_custom_element(&self) -> bool {
+        if self.get_custom_element_state() != CustomElementState::Custom {
+            return false;
+        }
+        self.custom_element_definition
+            .borrow()
+            .as_ref()
+            .map_or(false, |definition| {
+                definition.is_autonomous() && definition.form_associated
+            })
+    }
+
     pub fn push_callback_reaction(&self, function: Rc<Function>, args: Box<[Heap<JSVal>]>) {
         self.custom_element_reaction_queue
             .borrow_mut()
@@ -526,6 +553,7 @@ pub trait LayoutElementHelpers {
     unsafe fn is_html_element(&self) -> bool;
     fn id_attribute(&self) -> *const Option<Atom>;
     fn style_attribute(&self) -> *const Option<Arc<Locked<PropertyDeclarationBlock>>>;
+    fn animation_declarations(&self) -> *const Option<Arc<Locked<PropertyDeclarationBlock>>>;
     fn local_name(&self) -> &LocalName;
     fn namespace(&self) -> &Namespace;
     fn get_lang_for_layout(&self) -> String;
@@ -916,6 +944,15 @@ impl LayoutElementHelpers for LayoutDom<Element> {
         unsafe { (*self.unsafe_get()).style_attribute.borrow_for_layout() }
     }
 
+    #[allow(unsafe_code)]
+    fn animation_declarations(&self) -> *const Option<Arc<Locked<PropertyDeclarationBlock>>> {
+        unsafe {
+            (*self.unsafe_get())
+                .animation_declarations
+                .borrow_for_layout()
+        }
+    }
+
     #[allow(unsafe_code)]
     fn local_name(&self) -> &LocalName {
         unsafe { &(*self.unsafe_get()).local_name }
@@ -1079,6 +1116,33 @@ impl Element {
         &self.style_attribute
     }
 
+    /// Replaces the values script-generated animations give to the properties
+    /// of this element, and restyles it if they changed
+    pub fn set_animation_declarations(&self, declarations: Vec<PropertyDeclaration>) {
+        let doc = document_from_node(self);
+        let unchanged = match *self.animation_declarations.borrow() {
+            Some(ref block) => {
+                let guard = doc.style_shared_lock().read();
+                block.read_with(&guard).declarations() == &*declarations
+            },
+            None => declarations.is_empty(),
+        };
+        if unchanged {
+            return;
+        }
+
+        *self.animation_declarations.borrow_mut() = if declarations.is_empty() {
+            None
+        } else {
+            let mut block = PropertyDeclarationBlock::new();
+            for declaration in declarations {
+                block.push(declaration, Importance::Normal);
+            }
+            Some(Arc::new(doc.style_shared_lock().wrap(block)))
+        };
+        self.upcast::<Node>().dirty(NodeDamage::NodeStyleDamaged);
+    }
+
     pub fn summarize(&self) -> Vec<AttrInfo> {
         self.attrs
             .borrow()
@@ -1669,6 +1733,82 @@ impl Element {
         win.scroll_node(node, x, y, behavior);
     }
 
+    /// <https://drafts.csswg.org/cssom-view/#scroll-an-element-into-view>
+    ///
+    /// This assumes horizontal-tb writing modes, where the block axis is the
+    /// vertical one.
+    fn scroll_into_view(
+        &self,
+        behavior: ScrollBehavior,
+        block: ScrollLogicalPosition,
+        inline: ScrollLogicalPosition,
+    ) {
+        let node = self.upcast::<Node>();
+        let rect = node.bounding_content_box_or_zero();
+        // Where the border box of the element is relative to the scrolled
+        // content of the box being looked at, once the boxes inside that one
+        // have been scrolled.
+        let mut left = rect.origin.x.to_f64_px();
+        let mut top = rect.origin.y.to_f64_px();
+        let width = rect.size.width.to_f64_px();
+        let height = rect.size.height.to_f64_px();
+
+        // Step 1
+        let root = self.root_element();
+        for ancestor in node.ancestors().filter_map(DomRoot::downcast::<Element>) {
+            if *ancestor == *root {
+                break;
+            }
+            if !ancestor.has_scrolling_box() || !ancestor.has_overflow() {
+                continue;
+            }
+            let port = ancestor.upcast::<Node>().bounding_content_box_or_zero();
+            let element_left = left - port.origin.x.to_f64_px() - ancestor.ClientLeft() as f64;
+            let element_top = top - port.origin.y.to_f64_px() - ancestor.ClientTop() as f64;
+            let port_width = ancestor.ClientWidth() as f64;
+            let port_height = ancestor.ClientHeight() as f64;
+            let x = scroll_position_into_view(
+                inline,
+                element_left,
+                width,
+                port_width,
+                ancestor.ScrollLeft(),
+            )
+            .min(ancestor.ScrollWidth() as f64 - port_width)
+            .max(0.);
+            let y = scroll_position_into_view(
+                block,
+                element_top,
+                height,
+                port_height,
+                ancestor.ScrollTop(),
+            )
+            .min(ancestor.ScrollHeight() as f64 - port_height)
+            .max(0.);
+            ancestor.scroll(x, y, behavior);
+            left -= x;
+            top -= y;
+        }
+
+        // The viewport comes last, and clamps the position itself.
+        let win = window_from_node(self);
+        let x = scroll_position_into_view(
+            inline,
+            left,
+            width,
+            win.InnerWidth() as f64,
+            win.ScrollX() as f64,
+        );
+        let y = scroll_position_into_view(
+            block,
+            top,
+            height,
+            win.InnerHeight() as f64,
+            win.ScrollY() as f64,
+        );
+        win.scroll(x, y, behavior);
+    }
+
     // https://w3c.github.io/DOM-Parsing/#parsing
     pub fn parse_fragment(&self, markup: DOMString) -> Fallible<DomRoot<DocumentFragment>> {
         // Steps 1-2.
@@ -2052,6 +2192,32 @@ impl ElementMethods for Element {
         )
     }
 
+    // https://drafts.csswg.org/cssom-view/#dom-element-scrollintoview
+    fn ScrollIntoView(&self, arg: BooleanOrScrollIntoViewOptions) {
+        // Steps 1-3
+        let (behavior, block, inline) = match arg {
+            BooleanOrScrollIntoViewOptions::Boolean(align_to_top) => {
+                let block = if align_to_top {
+                    ScrollLogicalPosition::Start
+                } else {
+                    ScrollLogicalPosition::End
+                };
+                (ScrollBehavior::Auto, block, ScrollLogicalPosition::Nearest)
+            },
+            BooleanOrScrollIntoViewOptions::ScrollIntoViewOptions(options) => {
+                (options.parent.behavior, options.block, options.inline)
+            },
+        };
+
+        // Step 4
+        if !self.has_css_layout_box() {
+            return;
+        }
+
+        // Step 5
+        self.scroll_into_view(behavior, block, inline);
+    }
+
     // https://drafts.csswg.org/cssom-view/#dom-element-scroll
     fn Scroll(&self, options: &ScrollToOptions) {
         // Step 1
@@ -2585,6 +2751,62 @@ impl ElementMethods for Element {
         let doc = document_from_node(self);
         doc.enter_fullscreen(self)
     }
+
+    // https://w3c.github.io/pointerevents/#dom-element-setpointercapture
+    fn SetPointerCapture(&self, pointer_id: i32) -> ErrorResult {
+        let doc = document_from_node(self);
+        if !doc.is_active_pointer(pointer_id) {
+            return Err(Error::NotFound);
+        }
+        if !self.is_connected() {
+            return Err(Error::InvalidState);
+        }
+        doc.set_pointer_capture(pointer_id, self);
+        Ok(())
+    }
+
+    // https://w3c.github.io/pointerevents/#dom-element-releasepointercapture
+    fn ReleasePointerCapture(&self, pointer_id: i32) -> ErrorResult {
+        let doc = document_from_node(self);
+        if !doc.is_active_pointer(pointer_id) {
+            return Err(Error::NotFound);
+        }
+        if self.HasPointerCapture(pointer_id) {
+            doc.release_pointer_capture(pointer_id);
+        }
+        Ok(())
+    }
+
+    // https://w3c.github.io/pointerevents/#dom-element-haspointercapture
+    fn HasPointerCapture(&self, pointer_id: i32) -> bool {
+        document_from_node(self)
+            .pending_pointer_capture(pointer_id)
+            .map_or(false, |element| &*element == self)
+    }
+
+    // https://drafts.csswg.org/web-animations/#dom-animatable-animate
+    fn Animate(
+        &self,
+        keyframes: Option<Keyframes>,
+        options: UnrestrictedDoubleOrKeyframeAnimationOptions,
+    ) -> Fallible<DomRoot<Animation>> {
+        let window = window_from_node(self);
+        let (timing, id) = match options {
+            UnrestrictedDoubleOrKeyframeAnimationOptions::UnrestrictedDouble(duration) => {
+                (timing_for_duration(duration)?, DOMString::new())
+            },
+            UnrestrictedDoubleOrKeyframeAnimationOptions::KeyframeAnimationOptions(options) => (
+                Timing::from_options(&window, Some(&options.parent.parent))?,
+                options.id,
+            ),
+        };
+        let effect = KeyframeEffect::new(&window, Some(self), keyframes, timing)?;
+        let timeline = window.Document().Timeline();
+        let animation = Animation::new(&window, Some(effect.upcast()), Some(timeline.upcast()));
+        animation.set_id(id);
+        animation.Play()?;
+        Ok(animation)
+    }
 }
 
 impl VirtualMethods for Element {
@@ -2686,6 +2908,10 @@ impl VirtualMethods for Element {
             },
         };
 
+        if *self.namespace() == ns!(svg) {
+            SVGSVGElement::invalidate_outermost(node);
+        }
+
         // Make sure we rev the version even if we didn't dirty the node. If we
         // don't do this, various attribute-dependent htmlcollections (like those
         // generated by getElementsByClassName) might become stale.
@@ -2772,6 +2998,10 @@ impl VirtualMethods for Element {
                 }
             }
         }
+
+        if *self.namespace() == ns!(svg) {
+            SVGSVGElement::invalidate_outermost(self.upcast());
+        }
     }
 
     fn adopting_steps(&self, old_doc: &Document) {
@@ -3033,6 +3263,12 @@ impl Element {
                 let element = self.downcast::<HTMLTextAreaElement>().unwrap();
                 Some(element as &dyn Validatable)
             },
+            NodeTypeId::Element(ElementTypeId::HTMLElement(HTMLElementTypeId::HTMLElement))
+                if self.is_form_associated_custom_element() =>
+            {
+                let element = self.downcast::<HTMLElement>().unwrap();
+                Some(element as &dyn Validatable)
+            },
             _ => None,
         };
         element
@@ -3190,6 +3426,14 @@ impl Element {
     }
 
     pub fn set_disabled_state(&self, value: bool) {
+        // https://html.spec.whatwg.org/multipage/#face-disabled
+        if self.disabled_state() != value && self.is_form_associated_custom_element() {
+            ScriptThread::enqueue_callback_reaction(
+                self,
+                CallbackReaction::FormDisabled(value),
+                None,
+            );
+        }
         self.set_state(ElementState::IN_DISABLED_STATE, value)
     }
 
@@ -3492,3 +3736,38 @@ pub fn cors_setting_for_element(element: &Element) -> Option<CorsSettings> {
         _ => unreachable!(),
     })
 }
+
+/// The scroll position along one axis that brings an element, `start` away
+/// from the start of the scrolled content and `size` long, into a scrolling
+/// box whose scrollport is `port_size` long and currently scrolled to
+/// `scroll_position`.
+///
+/// <https://drafts.csswg.org/cssom-view/#scroll-an-element-into-view>
+fn scroll_position_into_view(
+    position: ScrollLogicalPosition,
+    start: f64,
+    size: f64,
+    port_size: f64,
+    scroll_position: f64,
+) -> f64 {
+    let align_start = start;
+    let align_end = start + size - port_size;
+    match position {
+        ScrollLogicalPosition::Start => align_start,
+        ScrollLogicalPosition::End => align_end,
+        ScrollLogicalPosition::Center => start + (size - port_size) / 2.,
+        ScrollLogicalPosition::Nearest => {
+            let start_outside = start < scroll_position;
+            let end_outside = start + size > scroll_position + port_size;
+            // Nothing to do if the element is entirely inside the
+            // scrollport, or overflows it on both sides.
+            if start_outside == end_outside {
+                scroll_position
+            } else if (start_outside && size <= port_size) || (end_outside && size > port_size) {
+                align_start
+            } else {
+                align_end
+            }
+        },
+    }
+}
@@ -0,0 +1,393 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::ElementInternalsBinding;
+use crate::dom::bindings::codegen::Bindings::ElementInternalsBinding::{
+    ElementInternalsMethods, ValidityStateFlags,
+};
+use crate::dom::bindings::codegen::UnionTypes::FileOrUSVStringOrFormData;
+use crate::dom::bindings::error::{Error, ErrorResult, Fallible};
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::reflector::{reflect_dom_object, Reflector};
+use crate::dom::bindings::root::{Dom, DomRoot, MutNullableDom};
+use crate::dom::bindings::str::DOMString;
+use crate::dom::element::Element;
+use crate::dom::eventtarget::EventTarget;
+use crate::dom::file::File;
+use crate::dom::htmlelement::HTMLElement;
+use crate::dom::htmlformelement::{FormDatum, FormDatumValue, HTMLFormElement};
+use crate::dom::node::{window_from_node, Node};
+use crate::dom::nodelist::NodeList;
+use crate::dom::validation::Validatable;
+use crate::dom::validitystate::{ValidationFlags, ValidityState};
+use dom_struct::dom_struct;
+use html5ever::LocalName;
+use std::cell::Cell;
+use std::collections::HashMap;
+
+/// <https://html.spec.whatwg.org/multipage/#face-submission-value>
+#[derive(JSTraceable, MallocSizeOf)]
+#[must_root]
+enum SubmissionValue {
+    None,
+    File(Dom<File>),
+    FormData(Vec<FormDatum>),
+    USVString(DOMString),
+}
+
+impl From<Option<FileOrUSVStringOrFormData>> for SubmissionValue {
+    #[allow(unrooted_must_root)]
+    fn from(value: Option<FileOrUSVStringOrFormData>) -> SubmissionValue {
+        match value {
+            None => SubmissionValue::None,
+            Some(FileOrUSVStringOrFormData::File(file)) => {
+                SubmissionValue::File(Dom::from_ref(&*file))
+            },
+            Some(FileOrUSVStringOrFormData::USVString(string)) => {
+                SubmissionValue::USVString(DOMString::from(string.0))
+            },
+            Some(FileOrUSVStringOrFormData::FormData(form_data)) => {
+                SubmissionValue::FormData(form_data.datums())
+            },
+        }
+    }
+}
+
+#[dom_struct]
+pub struct ElementInternals {
+    reflector_: Reflector,
+    target_element: Dom<HTMLElement>,
+    /// Whether `attachInternals()` already returned this object.
+    ///
+    /// <https://html.spec.whatwg.org/multipage/#attached-internals>
+    attached: Cell<bool>,
+    /// <https://html.spec.whatwg.org/multipage/#face-submission-value>
+    submission_value: DomRefCell<SubmissionValue>,
+    form_owner: MutNullableDom<HTMLFormElement>,
+    /// The flags set by `setValidity()`.
+    ///
+    /// <https://html.spec.whatwg.org/multipage/#face-validity-flags>
+    validity_flags: Cell<ValidationFlags>,
+    /// <https://html.spec.whatwg.org/multipage/#face-validation-message>
+    validation_message: DomRefCell<DOMString>,
+    /// The default ARIA roles, states and properties of the element, by
+    /// attribute name, which its own attributes override.
+    ///
+    /// <https://html.spec.whatwg.org/multipage/#wai-aria>
+    default_aria_properties: DomRefCell<HashMap<LocalName, DOMString>>,
+}
+
+impl ElementInternals {
+    fn new_inherited(target_element: &HTMLElement) -> ElementInternals {
+        ElementInternals {
+            reflector_: Reflector::new(),
+            target_element: Dom::from_ref(target_element),
+            attached: Cell::new(false),
+            submission_value: DomRefCell::new(SubmissionValue::None),
+            form_owner: Default::default(),
+            validity_flags: Cell::new(ValidationFlags::empty()),
+            validation_message: DomRefCell::new(DOMString::new()),
+            default_aria_properties: DomRefCell::new(HashMap::new()),
+        }
+    }
+
+    pub fn new(target_element: &HTMLElement) -> DomRoot<ElementInternals> {
+        let window = window_from_node(target_element);
+        reflect_dom_object(
+            Box::new(ElementInternals::new_inherited(target_element)),
+            &*window,
+            ElementInternalsBinding::Wrap,
+        )
+    }
+
+    pub fn attached(&self) -> bool {
+        self.attached.get()
+    }
+
+    pub fn set_attached(&self) {
+        self.attached.set(true);
+    }
+
+    pub fn form_owner(&self) -> Option<DomRoot<HTMLFormElement>> {
+        self.form_owner.get()
+    }
+
+    pub fn set_form_owner(&self, form: Option<&HTMLFormElement>) {
+        self.form_owner.set(form);
+    }
+
+    pub fn validity_flags(&self) -> ValidationFlags {
+        self.validity_flags.get()
+    }
+
+    /// The default value of the ARIA attribute `name` of the element.
+    pub fn default_aria_property(&self, name: &str) -> Option<DOMString> {
+        self.default_aria_properties
+            .borrow()
+            .get(&LocalName::from(name))
+            .cloned()
+    }
+
+    fn set_default_aria_property(&self, name: &str, value: Option<DOMString>) {
+        let mut properties = self.default_aria_properties.borrow_mut();
+        match value {
+            Some(value) => properties.insert(LocalName::from(name), value),
+            None => properties.remove(&LocalName::from(name)),
+        };
+    }
+
+    fn target_element(&self) -> &Element {
+        self.target_element.upcast::<Element>()
+    }
+
+    fn is_target_form_associated(&self) -> bool {
+        self.target_element().is_form_associated_custom_element()
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/#face-entry-construction>
+    pub fn push_form_data(&self, data_set: &mut Vec<FormDatum>) {
+        let name = self
+            .target_element()
+            .get_string_attribute(&local_name!("name"));
+        match *self.submission_value.borrow() {
+            // Step 1
+            SubmissionValue::FormData(ref datums) => data_set.extend(datums.iter().cloned()),
+            // Step 2
+            SubmissionValue::None => {},
+            _ if name.is_empty() => {},
+            SubmissionValue::File(ref file) => data_set.push(FormDatum {
+                ty: DOMString::from("file"),
+                name: name,
+                value: FormDatumValue::File(DomRoot::from_ref(&*file)),
+            }),
+            SubmissionValue::USVString(ref string) => data_set.push(FormDatum {
+                ty: DOMString::from("string"),
+                name: name,
+                value: FormDatumValue::String(string.clone()),
+            }),
+        }
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/#check-validity-steps>
+    fn check_validity(&self) -> bool {
+        let element = &*self.target_element;
+        if !element.is_instance_validatable() || element.validate(ValidationFlags::empty()) {
+            return true;
+        }
+        element
+            .upcast::<EventTarget>()
+            .fire_cancelable_event(atom!("invalid"));
+        false
+    }
+}
+
+macro_rules! aria_properties(
+    ( $([$getter:ident, $setter:ident, $name:expr],)* ) => (
+        $(
+            fn $getter(&self) -> Option<DOMString> {
+                self.default_aria_property($name)
+            }
+            fn $setter(&self, value: Option<DOMString>) {
+                self.set_default_aria_property($name, value)
+            }
+        )*
+    );
+);
+
+impl ElementInternalsMethods for ElementInternals {
+    // https://html.spec.whatwg.org/multipage/#dom-elementinternals-setformvalue
+    #[allow(unrooted_must_root)]
+    fn SetFormValue(
+        &self,
+        value: Option<FileOrUSVStringOrFormData>,
+        _state: Option<Option<FileOrUSVStringOrFormData>>,
+    ) -> ErrorResult {
+        // Step 1-2
+        if !self.is_target_form_associated() {
+            return Err(Error::NotSupported);
+        }
+
+        // Step 3
+        *self.submission_value.borrow_mut() = SubmissionValue::from(value);
+
+        // Steps 4-5
+        // The state is only used to restore the element, which isn't supported.
+        Ok(())
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-elementinternals-form
+    fn GetForm(&self) -> Fallible<Option<DomRoot<HTMLFormElement>>> {
+        if !self.is_target_form_associated() {
+            return Err(Error::NotSupported);
+        }
+        Ok(self.form_owner())
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-elementinternals-setvalidity
+    fn SetValidity(
+        &self,
+        flags: &ValidityStateFlags,
+        message: Option<DOMString>,
+        anchor: Option<&HTMLElement>,
+    ) -> ErrorResult {
+        // Step 1-2
+        if !self.is_target_form_associated() {
+            return Err(Error::NotSupported);
+        }
+
+        // Step 3
+        let mut validity_flags = ValidationFlags::empty();
+        validity_flags.set(ValidationFlags::VALUE_MISSING, flags.valueMissing);
+        validity_flags.set(ValidationFlags::TYPE_MISMATCH, flags.typeMismatch);
+        validity_flags.set(ValidationFlags::PATTERN_MISMATCH, flags.patternMismatch);
+        validity_flags.set(ValidationFlags::TOO_LONG, flags.tooLong);
+        validity_flags.set(ValidationFlags::TOO_SHORT, flags.tooShort);
+        validity_flags.set(ValidationFlags::RANGE_UNDERFLOW, flags.rangeUnderflow);
+        validity_flags.set(ValidationFlags::RANGE_OVERFLOW, flags.rangeOverflow);
+        validity_flags.set(ValidationFlags::STEP_MISMATCH, flags.stepMismatch);
+        validity_flags.set(ValidationFlags::BAD_INPUT, flags.badInput);
+        validity_flags.set(ValidationFlags::CUSTOM_ERROR, flags.customError);
+        let message = message.unwrap_or_default();
+        if !validity_flags.is_empty() && message.is_empty() {
+            return Err(Error::Type(
+                "A validation message is needed when a flag is set".to_owned(),
+            ));
+        }
+
+        // Step 4
+        if let Some(anchor) = anchor {
+            let element = self.target_element.upcast::<Node>();
+            if !element.is_inclusive_ancestor_of(anchor.upcast()) {
+                return Err(Error::NotFound);
+            }
+            // The validation anchor is only used to show the problems to
+            // the user, which isn't supported.
+        }
+
+        // Step 5
+        self.validity_flags.set(validity_flags);
+
+        // Step 6
+        *self.validation_message.borrow_mut() = if validity_flags.is_empty() {
+            DOMString::new()
+        } else {
+            message
+        };
+        Ok(())
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-elementinternals-willvalidate
+    fn GetWillValidate(&self) -> Fallible<bool> {
+        if !self.is_target_form_associated() {
+            return Err(Error::NotSupported);
+        }
+        Ok(self.target_element.is_instance_validatable())
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-elementinternals-validity
+    fn GetValidity(&self) -> Fallible<DomRoot<ValidityState>> {
+        if !self.is_target_form_associated() {
+            return Err(Error::NotSupported);
+        }
+        let window = window_from_node(&*self.target_element);
+        Ok(ValidityState::new(&window, self.target_element()))
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-elementinternals-validationmessage
+    fn GetValidationMessage(&self) -> Fallible<DOMString> {
+        if !self.is_target_form_associated() {
+            return Err(Error::NotSupported);
+        }
+        if !self.target_element.is_instance_validatable() {
+            return Ok(DOMString::new());
+        }
+        Ok(self.validation_message.borrow().clone())
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-elementinternals-checkvalidity
+    fn CheckValidity(&self) -> Fallible<bool> {
+        if !self.is_target_form_associated() {
+            return Err(Error::NotSupported);
+        }
+        Ok(self.check_validity())
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-elementinternals-reportvalidity
+    fn ReportValidity(&self) -> Fallible<bool> {
+        if !self.is_target_form_associated() {
+            return Err(Error::NotSupported);
+        }
+        // TODO: Report the problems to the user when the invalid event isn't
+        // canceled.
+        Ok(self.check_validity())
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-elementinternals-labels
+    fn GetLabels(&self) -> Fallible<DomRoot<NodeList>> {
+        if !self.is_target_form_associated() {
+            return Err(Error::NotSupported);
+        }
+        Ok(self.target_element.labels())
+    }
+
+    // https://w3c.github.io/aria/#ARIAMixin
+    aria_properties!(
+        [GetRole, SetRole, "role"],
+        [GetAriaAtomic, SetAriaAtomic, "aria-atomic"],
+        [
+            GetAriaAutoComplete,
+            SetAriaAutoComplete,
+            "aria-autocomplete"
+        ],
+        [GetAriaBusy, SetAriaBusy, "aria-busy"],
+        [GetAriaChecked, SetAriaChecked, "aria-checked"],
+        [GetAriaColCount, SetAriaColCount, "aria-colcount"],
+        [GetAriaColIndex, SetAriaColIndex, "aria-colindex"],
+        [GetAriaColSpan, SetAriaColSpan, "aria-colspan"],
+        [GetAriaCurrent, SetAriaCurrent, "aria-current"],
+        [GetAriaDescription, SetAriaDescription, "aria-description"],
+        [GetAriaDisabled, SetAriaDisabled, "aria-disabled"],
+        [GetAriaExpanded, SetAriaExpanded, "aria-expanded"],
+        [GetAriaHasPopup, SetAriaHasPopup, "aria-haspopup"],
+        [GetAriaHidden, SetAriaHidden, "aria-hidden"],
+        [GetAriaInvalid, SetAriaInvalid, "aria-invalid"],
+        [
+            GetAriaKeyShortcuts,
+            SetAriaKeyShortcuts,
+            "aria-keyshortcuts"
+        ],
+        [GetAriaLabel, SetAriaLabel, "aria-label"],
+        [GetAriaLevel, SetAriaLevel, "aria-level"],
+        [GetAriaLive, SetAriaLive, "aria-live"],
+        [GetAriaModal, SetAriaModal, "aria-modal"],
+        [GetAriaMultiLine, SetAriaMultiLine, "aria-multiline"],
+        [
+            GetAriaMultiSelectable,
+            SetAriaMultiSelectable,
+            "aria-multiselectable"
+        ],
+        [GetAriaOrientation, SetAriaOrientation, "aria-orientation"],
+        [GetAriaPlaceholder, SetAriaPlaceholder, "aria-placeholder"],
+        [GetAriaPosInSet, SetAriaPosInSet, "aria-posinset"],
+        [GetAriaPressed, SetAriaPressed, "aria-pressed"],
+        [GetAriaReadOnly, SetAriaReadOnly, "aria-readonly"],
+        [GetAriaRequired, SetAriaRequired, "aria-required"],
+        [
+            GetAriaRoleDescription,
+            SetAriaRoleDescription,
+            "aria-roledescription"
+        ],
+        [GetAriaRowCount, SetAriaRowCount, "aria-rowcount"],
+        [GetAriaRowIndex, SetAriaRowIndex, "aria-rowindex"],
+        [GetAriaRowSpan, SetAriaRowSpan, "aria-rowspan"],
+        [GetAriaSelected, SetAriaSelected, "aria-selected"],
+        [GetAriaSetSize, SetAriaSetSize, "aria-setsize"],
+        [GetAriaSort, SetAriaSort, "aria-sort"],
+        [GetAriaValueMax, SetAriaValueMax, "aria-valuemax"],
+        [GetAriaValueMin, SetAriaValueMin, "aria-valuemin"],
+        [GetAriaValueNow, SetAriaValueNow, "aria-valuenow"],
+        [GetAriaValueText, SetAriaValueText, "aria-valuetext"],
+    );
+}
@@ -18,6 +18,8 @@ use crate::dom::bindings::str::DOMString;
 use crate::dom::document::Document;
 use crate::dom::eventtarget::{CompiledEventListener, EventTarget, ListenerPhase};
 use crate::dom::globalscope::GlobalScope;
+use crate::dom::idbrequest::IDBRequest;
+use crate::dom::idbtransaction::IDBTransaction;
 use crate::dom::node::Node;
 use crate::dom::virtualmethods::vtable_for;
 use crate::dom::window::Window;
@@ -138,6 +140,14 @@ impl Event {
                     event_path.push(DomRoot::from_ref(document.window().upcast()));
                 }
             }
+        } else if let Some(request) = target.downcast::<IDBRequest>() {
+            // https://w3c.github.io/IndexedDB/#ref-for-get-the-parent
+            if let Some(transaction) = request.transaction() {
+                event_path.push(DomRoot::from_ref(transaction.upcast()));
+                event_path.push(DomRoot::from_ref(transaction.db().upcast()));
+            }
+        } else if let Some(transaction) = target.downcast::<IDBTransaction>() {
+            event_path.push(DomRoot::from_ref(transaction.db().upcast()));
         }
         event_path
     }
@@ -173,9 +183,23 @@ impl Event {
         // Step 3-4.
         let path = self.construct_event_path(&target);
         rooted_vec!(let event_path <- path.into_iter());
+        // Listeners of trusted activation-triggering events may use the APIs
+        // that need a user gesture
+        let global = target.global();
+        let activation_window = global
+            .downcast::<Window>()
+            .filter(|_| self.is_activation_triggering());
+        if let Some(window) = activation_window {
+            window.enter_user_activation();
+        }
+
         // Steps 5-9. In a separate function to short-circuit various things easily.
         dispatch_to_listeners(self, target, event_path.r());
 
+        if let Some(window) = activation_window {
+            window.exit_user_activation();
+        }
+
         // Default action.
         if let Some(target) = self.GetTarget() {
             if let Some(node) = target.downcast::<Node>() {
@@ -240,6 +264,21 @@ impl Event {
         self.trusted.set(trusted);
     }
 
+    /// https://html.spec.whatwg.org/multipage/#triggered-by-user-activation
+    ///
+    /// Whether this is a trusted event that was caused by the user
+    /// interacting with the page
+    fn is_activation_triggering(&self) -> bool {
+        if !self.trusted.get() {
+            return false;
+        }
+        match &*self.type_() {
+            "change" | "click" | "contextmenu" | "dblclick" | "mouseup" | "pointerup" |
+            "reset" | "submit" | "touchend" => true,
+            _ => false,
+        }
+    }
+
     // https://html.spec.whatwg.org/multipage/#fire-a-simple-event
     pub fn fire(&self, target: &EventTarget) -> EventStatus {
         self.set_trusted(true);
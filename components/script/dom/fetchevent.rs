@@ -0,0 +1,233 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::EventBinding::EventMethods;
+use crate::dom::bindings::codegen::Bindings::FetchEventBinding::{self, FetchEventMethods};
+use crate::dom::bindings::codegen::Bindings::ResponseBinding::ResponseBinding::ResponseMethods;
+use crate::dom::bindings::codegen::Bindings::ResponseBinding::ResponseType as DOMResponseType;
+use crate::dom::bindings::conversions::root_from_handlevalue;
+use crate::dom::bindings::error::{Error, ErrorResult, Fallible};
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject};
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::bindings::str::DOMString;
+use crate::dom::event::{Event, EventStatus};
+use crate::dom::extendableevent::ExtendableEvent;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::promise::Promise;
+use crate::dom::promisenativehandler::{Callback, PromiseNativeHandler};
+use crate::dom::readablestreamdefaultreader::ReadAllBytesHandler;
+use crate::dom::request::Request;
+use crate::dom::response::Response;
+use crate::dom::serviceworkerglobalscope::ServiceWorkerGlobalScope;
+use dom_struct::dom_struct;
+use hyper::StatusCode;
+use ipc_channel::ipc::IpcSender;
+use js::jsapi::JSContext;
+use js::rust::HandleValue;
+use net_traits::{CustomResponse, NetworkError};
+use servo_atoms::Atom;
+use std::cell::Cell;
+
+/// Where the response of a service worker to a fetch gets sent, `None`
+/// meaning that the fetch goes to the network.
+pub type ResponseChan = IpcSender<Option<Result<CustomResponse, NetworkError>>>;
+
+// https://w3c.github.io/ServiceWorker/#fetchevent-interface
+#[dom_struct]
+pub struct FetchEvent {
+    event: ExtendableEvent,
+    request: Dom<Request>,
+    /// <https://w3c.github.io/ServiceWorker/#fetchevent-respond-with-entered-flag>
+    respond_with_entered: Cell<bool>,
+    /// The channel of the fetch this event got dispatched for, taken once
+    /// the service worker responded.
+    #[ignore_malloc_size_of = "Defined in ipc-channel"]
+    response_chan: DomRefCell<Option<ResponseChan>>,
+}
+
+impl FetchEvent {
+    fn new_inherited(request: &Request, response_chan: Option<ResponseChan>) -> FetchEvent {
+        FetchEvent {
+            event: ExtendableEvent::new_inherited(),
+            request: Dom::from_ref(request),
+            respond_with_entered: Cell::new(false),
+            response_chan: DomRefCell::new(response_chan),
+        }
+    }
+
+    fn new(
+        global: &GlobalScope,
+        type_: Atom,
+        bubbles: bool,
+        cancelable: bool,
+        request: &Request,
+        response_chan: Option<ResponseChan>,
+    ) -> DomRoot<FetchEvent> {
+        let ev = reflect_dom_object(
+            Box::new(FetchEvent::new_inherited(request, response_chan)),
+            global,
+            FetchEventBinding::Wrap,
+        );
+        ev.upcast::<Event>().init_event(type_, bubbles, cancelable);
+        ev
+    }
+
+    pub fn Constructor(
+        worker: &ServiceWorkerGlobalScope,
+        type_: DOMString,
+        init: &FetchEventBinding::FetchEventInit,
+    ) -> Fallible<DomRoot<FetchEvent>> {
+        Ok(FetchEvent::new(
+            worker.upcast(),
+            Atom::from(type_),
+            init.parent.parent.bubbles,
+            init.parent.parent.cancelable,
+            &init.request,
+            None,
+        ))
+    }
+
+    /// Fires a fetch event for `request` at `worker`, the response of the
+    /// service worker being sent on `response_chan`.
+    ///
+    /// <https://w3c.github.io/ServiceWorker/#handle-fetch>
+    /// Steps 24.3.
+    pub fn dispatch(
+        worker: &ServiceWorkerGlobalScope,
+        request: &Request,
+        response_chan: ResponseChan,
+    ) {
+        let event = FetchEvent::new(
+            worker.upcast(),
+            atom!("fetch"),
+            false,
+            true,
+            request,
+            Some(response_chan),
+        );
+        let status = event.upcast::<Event>().fire(worker.upcast());
+        if event.respond_with_entered.get() {
+            return;
+        }
+        // Steps 24.3.16-24.3.17.
+        if status == EventStatus::Canceled {
+            event.respond_with_error();
+        } else {
+            event.respond(None);
+        }
+    }
+
+    fn respond(&self, response: Option<Result<CustomResponse, NetworkError>>) {
+        if let Some(response_chan) = self.response_chan.borrow_mut().take() {
+            let _ = response_chan.send(response);
+        }
+    }
+
+    fn respond_with_error(&self) {
+        self.respond(Some(Err(NetworkError::Internal(
+            "The service worker did not respond with a response".to_owned(),
+        ))));
+    }
+}
+
+impl FetchEventMethods for FetchEvent {
+    // https://w3c.github.io/ServiceWorker/#dom-fetchevent-request
+    fn Request(&self) -> DomRoot<Request> {
+        DomRoot::from_ref(&*self.request)
+    }
+
+    // https://w3c.github.io/ServiceWorker/#dom-fetchevent-respondwith
+    fn RespondWith(&self, r: &Promise) -> ErrorResult {
+        let event = self.upcast::<Event>();
+        // Step 1.
+        if !event.dispatching() {
+            return Err(Error::InvalidState);
+        }
+        // Step 2.
+        if self.respond_with_entered.get() {
+            return Err(Error::InvalidState);
+        }
+        // Steps 4-6.
+        event.StopImmediatePropagation();
+        self.respond_with_entered.set(true);
+        // Steps 7-8.
+        let handler = PromiseNativeHandler::new(
+            &self.global(),
+            Some(Box::new(RespondWithHandler {
+                event: Dom::from_ref(self),
+                fulfilled: true,
+            })),
+            Some(Box::new(RespondWithHandler {
+                event: Dom::from_ref(self),
+                fulfilled: false,
+            })),
+        );
+        r.append_native_handler(&handler);
+        Ok(())
+    }
+
+    // https://dom.spec.whatwg.org/#dom-event-istrusted
+    fn IsTrusted(&self) -> bool {
+        self.upcast::<Event>().IsTrusted()
+    }
+}
+
+/// The reactions to the promise given to `respondWith()`.
+#[derive(JSTraceable, MallocSizeOf)]
+#[must_root]
+struct RespondWithHandler {
+    event: Dom<FetchEvent>,
+    fulfilled: bool,
+}
+
+impl Callback for RespondWithHandler {
+    fn callback(&self, _cx: *mut JSContext, v: HandleValue) {
+        // Step 7.
+        if !self.fulfilled {
+            return self.event.respond_with_error();
+        }
+        // Step 8.1.
+        let response = match root_from_handlevalue::<Response>(v) {
+            Ok(response) if response.Type() != DOMResponseType::Error => response,
+            _ => return self.event.respond_with_error(),
+        };
+        // Steps 8.2-8.3.
+        let handler = Box::new(SendResponse {
+            event: Dom::from_ref(&*self.event),
+            response: Dom::from_ref(&*response),
+        });
+        if response.read_all_bytes(handler).is_err() {
+            self.event.respond_with_error();
+        }
+    }
+}
+
+/// Sends the response given to `respondWith()` once its body was read.
+#[derive(JSTraceable)]
+#[must_root]
+struct SendResponse {
+    event: Dom<FetchEvent>,
+    response: Dom<Response>,
+}
+
+impl ReadAllBytesHandler for SendResponse {
+    fn success(&self, bytes: Vec<u8>) {
+        let status = match StatusCode::from_u16(self.response.Status()) {
+            Ok(status) => status,
+            Err(_) => return self.event.respond_with_error(),
+        };
+        let reason = String::from_utf8_lossy(&self.response.StatusText()).into_owned();
+        self.event.respond(Some(Ok(CustomResponse::new(
+            self.response.Headers().get_headers_list(),
+            (status, reason),
+            bytes,
+        ))));
+    }
+
+    fn failure(&self, _cx: *mut JSContext, _error: HandleValue) {
+        self.event.respond_with_error();
+    }
+}
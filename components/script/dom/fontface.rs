@@ -0,0 +1,384 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::DocumentBinding::DocumentMethods;
+use crate::dom::bindings::codegen::Bindings::FontFaceBinding;
+use crate::dom::bindings::codegen::Bindings::FontFaceBinding::{
+    FontFaceDescriptors, FontFaceLoadStatus, FontFaceMethods,
+};
+use crate::dom::bindings::codegen::Bindings::WindowBinding::WindowMethods;
+use crate::dom::bindings::codegen::UnionTypes::StringOrBinaryData;
+use crate::dom::bindings::error::{Error, ErrorResult, Fallible};
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::refcounted::Trusted;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::bindings::str::DOMString;
+use crate::dom::fontfaceset::FontFaceSet;
+use crate::dom::promise::Promise;
+use crate::dom::window::{ReflowReason, Window};
+use crate::task_source::TaskSource;
+use cssparser::{Parser, ParserInput, SourceLocation};
+use dom_struct::dom_struct;
+use ipc_channel::ipc;
+use ipc_channel::router::ROUTER;
+use script_layout_interface::message::{Msg, ReflowGoal, WebFontSource};
+use servo_atoms::Atom;
+use std::cell::Cell;
+use std::rc::Rc;
+use style::font_face::{FontFaceRuleData, FontStretchRange, FontStyle, FontWeightRange, Source};
+use style::parser::{Parse, ParserContext};
+use style::stylesheets::CssRuleType;
+use style::values::computed::font::{FamilyName, FamilyNameSyntax};
+use style_traits::ParsingMode;
+
+#[dom_struct]
+pub struct FontFace {
+    reflector_: Reflector,
+    family: DomRefCell<DOMString>,
+    style: DomRefCell<DOMString>,
+    weight: DomRefCell<DOMString>,
+    stretch: DomRefCell<DOMString>,
+    unicode_range: DomRefCell<DOMString>,
+    variant: DomRefCell<DOMString>,
+    feature_settings: DomRefCell<DOMString>,
+    variation_settings: DomRefCell<DOMString>,
+    display: DomRefCell<DOMString>,
+    status: Cell<FontFaceLoadStatus>,
+    /// Where the font comes from, until it starts loading.
+    #[ignore_malloc_size_of = "Defined in script_layout_interface"]
+    source: DomRefCell<Option<WebFontSource>>,
+    /// <https://drafts.csswg.org/css-font-loading/#dom-fontface-fontstatuspromise-slot>
+    #[ignore_malloc_size_of = "Rc is hard"]
+    loaded: Rc<Promise>,
+    /// The font face sets this font face is in, which want to know when it
+    /// loads.
+    font_face_sets: DomRefCell<Vec<Dom<FontFaceSet>>>,
+}
+
+impl FontFace {
+    fn new_inherited(family: DOMString, loaded: Rc<Promise>) -> FontFace {
+        FontFace {
+            reflector_: Reflector::new(),
+            family: DomRefCell::new(family),
+            style: DomRefCell::new(DOMString::from("normal")),
+            weight: DomRefCell::new(DOMString::from("normal")),
+            stretch: DomRefCell::new(DOMString::from("normal")),
+            unicode_range: DomRefCell::new(DOMString::from("U+0-10FFFF")),
+            variant: DomRefCell::new(DOMString::from("normal")),
+            feature_settings: DomRefCell::new(DOMString::from("normal")),
+            variation_settings: DomRefCell::new(DOMString::from("normal")),
+            display: DomRefCell::new(DOMString::from("auto")),
+            status: Cell::new(FontFaceLoadStatus::Unloaded),
+            source: DomRefCell::new(None),
+            loaded,
+            font_face_sets: DomRefCell::new(vec![]),
+        }
+    }
+
+    // https://drafts.csswg.org/css-font-loading/#font-face-constructor
+    #[allow(unsafe_code)]
+    pub fn Constructor(
+        window: &Window,
+        family: DOMString,
+        source: StringOrBinaryData,
+        descriptors: &FontFaceDescriptors,
+    ) -> Fallible<DomRoot<FontFace>> {
+        let loaded = unsafe { Promise::new_in_current_compartment(window.upcast()) };
+        let font_face = reflect_dom_object(
+            Box::new(FontFace::new_inherited(family, loaded)),
+            window,
+            FontFaceBinding::Wrap,
+        );
+
+        // Step 2
+        let valid = font_face.SetStyle(descriptors.style.clone()).is_ok() &&
+            font_face.SetWeight(descriptors.weight.clone()).is_ok() &&
+            font_face.SetStretch(descriptors.stretch.clone()).is_ok() &&
+            font_face
+                .SetUnicodeRange(descriptors.unicodeRange.clone())
+                .is_ok();
+        font_face.SetVariant(descriptors.variant.clone());
+        font_face.SetFeatureSettings(descriptors.featureSettings.clone());
+        font_face.SetVariationSettings(descriptors.variationSettings.clone());
+        font_face.SetDisplay(descriptors.display.clone());
+
+        let source = match source {
+            // Step 5
+            StringOrBinaryData::String(source) => parse_descriptor::<Vec<Source>>(window, &source)
+                .map(|sources| {
+                    let mut rule = FontFaceRuleData::empty(SourceLocation { line: 0, column: 0 });
+                    rule.family = Some(font_face.family_name());
+                    rule.sources = Some(sources);
+                    WebFontSource::Sources(rule.font_face().unwrap().effective_sources())
+                }),
+            // Step 6
+            StringOrBinaryData::ArrayBuffer(buffer) => Some(WebFontSource::Data(buffer.to_vec())),
+            StringOrBinaryData::ArrayBufferView(view) => Some(WebFontSource::Data(view.to_vec())),
+        };
+
+        // Step 4
+        let source = match source {
+            Some(source) if valid => source,
+            _ => {
+                font_face.status.set(FontFaceLoadStatus::Error);
+                font_face.loaded.reject_error(Error::Syntax);
+                return Ok(font_face);
+            },
+        };
+        let has_data = match source {
+            WebFontSource::Data(_) => true,
+            WebFontSource::Sources(_) => false,
+        };
+        *font_face.source.borrow_mut() = Some(source);
+        if has_data {
+            font_face.start_loading();
+        }
+        Ok(font_face)
+    }
+
+    pub fn status(&self) -> FontFaceLoadStatus {
+        self.status.get()
+    }
+
+    /// Whether this is a face of the font family named `family`.
+    pub fn is_in_family(&self, family: &str) -> bool {
+        self.family.borrow().to_lowercase() == family.to_lowercase()
+    }
+
+    pub fn add_to_font_face_set(&self, font_face_set: &FontFaceSet) {
+        self.font_face_sets
+            .borrow_mut()
+            .push(Dom::from_ref(font_face_set));
+    }
+
+    pub fn remove_from_font_face_set(&self, font_face_set: &FontFaceSet) {
+        self.font_face_sets
+            .borrow_mut()
+            .retain(|set| &**set != font_face_set);
+    }
+
+    fn family_name(&self) -> FamilyName {
+        FamilyName {
+            name: Atom::from(&**self.family.borrow()),
+            syntax: FamilyNameSyntax::Quoted,
+        }
+    }
+
+    fn set_status(&self, status: FontFaceLoadStatus) {
+        self.status.set(status);
+        let font_face_sets: Vec<_> = self
+            .font_face_sets
+            .borrow()
+            .iter()
+            .map(|set| DomRoot::from_ref(&**set))
+            .collect();
+        for font_face_set in font_face_sets {
+            font_face_set.font_face_status_changed();
+        }
+    }
+
+    /// Has layout load the font, the way it loads the ones of the
+    /// `@font-face` rules, so that it can be used once it is loaded.
+    ///
+    /// <https://drafts.csswg.org/css-font-loading/#dom-fontface-load>
+    pub fn start_loading(&self) {
+        let source = match self.source.borrow_mut().take() {
+            Some(source) => source,
+            None => return,
+        };
+        self.set_status(FontFaceLoadStatus::Loading);
+
+        let window = self.global();
+        let window = window.as_window();
+        let (sender, receiver) = ipc::channel().unwrap();
+        let (task_source, canceller) = window
+            .task_manager()
+            .dom_manipulation_task_source_with_canceller();
+        let this = Trusted::new(self);
+        ROUTER.add_route(
+            receiver.to_opaque(),
+            Box::new(move |message| {
+                let this = this.clone();
+                let loaded: bool = message.to().unwrap();
+                let _ = task_source.queue_with_canceller(
+                    task!(font_face_loaded: move || {
+                        this.root().finish_loading(loaded);
+                    }),
+                    &canceller,
+                );
+            }),
+        );
+        let msg = Msg::LoadWebFont(self.family_name(), source, sender);
+        if window.layout_chan().send(msg).is_err() {
+            warn!("Layout is gone, so the font face won't load.");
+        }
+    }
+
+    fn finish_loading(&self, loaded: bool) {
+        if !loaded {
+            self.set_status(FontFaceLoadStatus::Error);
+            self.loaded.reject_error(Error::Network);
+            return;
+        }
+
+        // The text using the family has to be laid out again.
+        let window = self.global();
+        let window = window.as_window();
+        window.Document().dirty_all_nodes();
+        window.reflow(ReflowGoal::Full, ReflowReason::WebFontLoaded);
+
+        self.set_status(FontFaceLoadStatus::Loaded);
+        self.loaded.resolve_native(&DomRoot::from_ref(self));
+    }
+}
+
+/// Parses `value` the way the descriptor of `@font-face` rules `T` is for.
+fn parse_descriptor<T: Parse>(window: &Window, value: &str) -> Option<T> {
+    let document = window.Document();
+    let url = document.base_url();
+    let context = ParserContext::new_for_cssom(
+        &url,
+        Some(CssRuleType::FontFace),
+        ParsingMode::DEFAULT,
+        document.quirks_mode(),
+        None,
+        None,
+    );
+    let mut input = ParserInput::new(value);
+    Parser::new(&mut input)
+        .parse_entirely(|input| T::parse(&context, input))
+        .ok()
+}
+
+impl FontFaceMethods for FontFace {
+    // https://drafts.csswg.org/css-font-loading/#dom-fontface-family
+    fn Family(&self) -> DOMString {
+        self.family.borrow().clone()
+    }
+
+    // https://drafts.csswg.org/css-font-loading/#dom-fontface-family
+    fn SetFamily(&self, value: DOMString) -> ErrorResult {
+        if value.is_empty() {
+            return Err(Error::Syntax);
+        }
+        *self.family.borrow_mut() = value;
+        Ok(())
+    }
+
+    // https://drafts.csswg.org/css-font-loading/#dom-fontface-style
+    fn Style(&self) -> DOMString {
+        self.style.borrow().clone()
+    }
+
+    // https://drafts.csswg.org/css-font-loading/#dom-fontface-style
+    fn SetStyle(&self, value: DOMString) -> ErrorResult {
+        parse_descriptor::<FontStyle>(self.global().as_window(), &value).ok_or(Error::Syntax)?;
+        *self.style.borrow_mut() = value;
+        Ok(())
+    }
+
+    // https://drafts.csswg.org/css-font-loading/#dom-fontface-weight
+    fn Weight(&self) -> DOMString {
+        self.weight.borrow().clone()
+    }
+
+    // https://drafts.csswg.org/css-font-loading/#dom-fontface-weight
+    fn SetWeight(&self, value: DOMString) -> ErrorResult {
+        parse_descriptor::<FontWeightRange>(self.global().as_window(), &value)
+            .ok_or(Error::Syntax)?;
+        *self.weight.borrow_mut() = value;
+        Ok(())
+    }
+
+    // https://drafts.csswg.org/css-font-loading/#dom-fontface-stretch
+    fn Stretch(&self) -> DOMString {
+        self.stretch.borrow().clone()
+    }
+
+    // https://drafts.csswg.org/css-font-loading/#dom-fontface-stretch
+    fn SetStretch(&self, value: DOMString) -> ErrorResult {
+        parse_descriptor::<FontStretchRange>(self.global().as_window(), &value)
+            .ok_or(Error::Syntax)?;
+        *self.stretch.borrow_mut() = value;
+        Ok(())
+    }
+
+    // https://drafts.csswg.org/css-font-loading/#dom-fontface-unicoderange
+    fn UnicodeRange(&self) -> DOMString {
+        self.unicode_range.borrow().clone()
+    }
+
+    // https://drafts.csswg.org/css-font-loading/#dom-fontface-unicoderange
+    fn SetUnicodeRange(&self, value: DOMString) -> ErrorResult {
+        parse_descriptor::<Vec<cssparser::UnicodeRange>>(self.global().as_window(), &value)
+            .ok_or(Error::Syntax)?;
+        *self.unicode_range.borrow_mut() = value;
+        Ok(())
+    }
+
+    // The following descriptors aren't supported by the @font-face rules
+    // either, so they are only kept around.
+
+    // https://drafts.csswg.org/css-font-loading/#dom-fontface-variant
+    fn Variant(&self) -> DOMString {
+        self.variant.borrow().clone()
+    }
+
+    // https://drafts.csswg.org/css-font-loading/#dom-fontface-variant
+    fn SetVariant(&self, value: DOMString) {
+        *self.variant.borrow_mut() = value;
+    }
+
+    // https://drafts.csswg.org/css-font-loading/#dom-fontface-featuresettings
+    fn FeatureSettings(&self) -> DOMString {
+        self.feature_settings.borrow().clone()
+    }
+
+    // https://drafts.csswg.org/css-font-loading/#dom-fontface-featuresettings
+    fn SetFeatureSettings(&self, value: DOMString) {
+        *self.feature_settings.borrow_mut() = value;
+    }
+
+    // https://drafts.csswg.org/css-font-loading/#dom-fontface-variationsettings
+    fn VariationSettings(&self) -> DOMString {
+        self.variation_settings.borrow().clone()
+    }
+
+    // https://drafts.csswg.org/css-font-loading/#dom-fontface-variationsettings
+    fn SetVariationSettings(&self, value: DOMString) {
+        *self.variation_settings.borrow_mut() = value;
+    }
+
+    // https://drafts.csswg.org/css-font-loading/#dom-fontface-display
+    fn Display(&self) -> DOMString {
+        self.display.borrow().clone()
+    }
+
+    // https://drafts.csswg.org/css-font-loading/#dom-fontface-display
+    fn SetDisplay(&self, value: DOMString) {
+        *self.display.borrow_mut() = value;
+    }
+
+    // https://drafts.csswg.org/css-font-loading/#dom-fontface-status
+    fn Status(&self) -> FontFaceLoadStatus {
+        self.status.get()
+    }
+
+    // https://drafts.csswg.org/css-font-loading/#dom-fontface-load
+    fn Load(&self) -> Rc<Promise> {
+        // Step 2
+        if self.status.get() == FontFaceLoadStatus::Unloaded {
+            self.start_loading();
+        }
+        // Step 1
+        self.loaded.clone()
+    }
+
+    // https://drafts.csswg.org/css-font-loading/#dom-fontface-loaded
+    fn Loaded(&self) -> Rc<Promise> {
+        self.loaded.clone()
+    }
+}
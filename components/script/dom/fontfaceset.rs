@@ -0,0 +1,283 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::DocumentBinding::DocumentMethods;
+use crate::dom::bindings::codegen::Bindings::FontFaceBinding::{
+    FontFaceLoadStatus, FontFaceMethods,
+};
+use crate::dom::bindings::codegen::Bindings::FontFaceSetBinding;
+use crate::dom::bindings::codegen::Bindings::FontFaceSetBinding::{
+    FontFaceSetLoadStatus, FontFaceSetMethods,
+};
+use crate::dom::bindings::error::{Error, Fallible};
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject};
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::bindings::str::DOMString;
+use crate::dom::eventtarget::EventTarget;
+use crate::dom::fontface::FontFace;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::promise::Promise;
+use crate::dom::window::Window;
+use dom_struct::dom_struct;
+use js::jsapi::JSAutoCompartment;
+use std::cell::Cell;
+use std::rc::Rc;
+use style::properties::{
+    parse_one_declaration_into, PropertyDeclaration, PropertyId, ShorthandId,
+    SourcePropertyDeclaration,
+};
+use style::values::computed::font::SingleFontFamily;
+use style::values::specified::font::FontFamily;
+use style_traits::ParsingMode;
+
+/// A call to `load()` waiting for its font faces to load.
+///
+/// <https://drafts.csswg.org/css-font-loading/#dom-fontfaceset-load>
+#[derive(JSTraceable, MallocSizeOf)]
+#[must_root]
+struct PendingLoad {
+    #[ignore_malloc_size_of = "Rc is hard"]
+    promise: Rc<Promise>,
+    font_faces: Vec<Dom<FontFace>>,
+}
+
+#[dom_struct]
+pub struct FontFaceSet {
+    eventtarget: EventTarget,
+    /// <https://drafts.csswg.org/css-font-loading/#set-entries>
+    font_faces: DomRefCell<Vec<Dom<FontFace>>>,
+    /// <https://drafts.csswg.org/css-font-loading/#dom-fontfaceset-readypromise-slot>
+    #[ignore_malloc_size_of = "Rc is hard"]
+    ready: DomRefCell<Rc<Promise>>,
+    ready_resolved: Cell<bool>,
+    pending_loads: DomRefCell<Vec<PendingLoad>>,
+}
+
+impl FontFaceSet {
+    fn new_inherited(ready: Rc<Promise>) -> FontFaceSet {
+        FontFaceSet {
+            eventtarget: EventTarget::new_inherited(),
+            font_faces: DomRefCell::new(vec![]),
+            ready: DomRefCell::new(ready),
+            ready_resolved: Cell::new(false),
+            pending_loads: DomRefCell::new(vec![]),
+        }
+    }
+
+    #[allow(unsafe_code)]
+    pub fn new(global: &GlobalScope, initial_faces: &[DomRoot<FontFace>]) -> DomRoot<FontFaceSet> {
+        let ready = unsafe { Promise::new_in_current_compartment(global) };
+        let font_face_set = reflect_dom_object(
+            Box::new(FontFaceSet::new_inherited(ready)),
+            global,
+            FontFaceSetBinding::Wrap,
+        );
+        for font_face in initial_faces {
+            font_face_set.Add(font_face);
+        }
+        font_face_set.font_face_status_changed();
+        font_face_set
+    }
+
+    // https://drafts.csswg.org/css-font-loading/#dom-fontfaceset-fontfaceset
+    pub fn Constructor(
+        window: &Window,
+        initial_faces: Vec<DomRoot<FontFace>>,
+    ) -> Fallible<DomRoot<FontFaceSet>> {
+        Ok(FontFaceSet::new(window.upcast(), &initial_faces))
+    }
+
+    fn is_loading(&self) -> bool {
+        self.font_faces
+            .borrow()
+            .iter()
+            .any(|font_face| font_face.status() == FontFaceLoadStatus::Loading)
+    }
+
+    /// Updates the `ready` promise and settles the pending `load()` calls
+    /// after one of the font faces started or finished loading, or the
+    /// font faces changed.
+    pub fn font_face_status_changed(&self) {
+        if self.is_loading() {
+            // https://drafts.csswg.org/css-font-loading/#switch-the-fontfaceset-to-loading
+            if self.ready_resolved.get() {
+                let global = self.global();
+                let ac =
+                    JSAutoCompartment::new(global.get_cx(), self.reflector().get_jsobject().get());
+                *self.ready.borrow_mut() = Promise::new(&global, &ac);
+                self.ready_resolved.set(false);
+            }
+        } else if !self.ready_resolved.get() {
+            // https://drafts.csswg.org/css-font-loading/#switch-the-fontfaceset-to-loaded
+            self.ready_resolved.set(true);
+            let ready = self.ready.borrow().clone();
+            ready.resolve_native(&DomRoot::from_ref(self));
+        }
+
+        self.pending_loads.borrow_mut().retain(|load| {
+            let failed = load
+                .font_faces
+                .iter()
+                .any(|font_face| font_face.status() == FontFaceLoadStatus::Error);
+            let loaded = load
+                .font_faces
+                .iter()
+                .all(|font_face| font_face.status() == FontFaceLoadStatus::Loaded);
+            if failed {
+                load.promise.reject_error(Error::Network);
+            } else if loaded {
+                let font_faces: Vec<_> = load
+                    .font_faces
+                    .iter()
+                    .map(|font_face| DomRoot::from_ref(&**font_face))
+                    .collect();
+                load.promise.resolve_native(&font_faces);
+            }
+            !failed && !loaded
+        });
+    }
+
+    /// The font faces of the families `font` names, parsing it the way the
+    /// `font` shorthand is.
+    ///
+    /// <https://drafts.csswg.org/css-font-loading/#find-the-matching-font-faces>
+    fn matching_font_faces(&self, font: &str) -> Fallible<Vec<DomRoot<FontFace>>> {
+        // Step 1
+        let global = self.global();
+        let document = global.as_window().Document();
+        let mut declarations = SourcePropertyDeclaration::new();
+        let result = parse_one_declaration_into(
+            &mut declarations,
+            PropertyId::Shorthand(ShorthandId::Font),
+            font,
+            &document.base_url(),
+            None,
+            ParsingMode::DEFAULT,
+            document.quirks_mode(),
+        );
+        // Step 2
+        if result.is_err() {
+            return Err(Error::Syntax);
+        }
+
+        let mut families = vec![];
+        for declaration in declarations.drain() {
+            if let PropertyDeclaration::FontFamily(FontFamily::Values(ref list)) = declaration {
+                families.extend(list.iter().filter_map(|family| match *family {
+                    SingleFontFamily::FamilyName(ref name) => Some(name.name.clone()),
+                    SingleFontFamily::Generic(_) => None,
+                }));
+            }
+        }
+
+        // Steps 3-5
+        // TODO: Check the font faces' unicode ranges against `text`.
+        Ok(self
+            .font_faces
+            .borrow()
+            .iter()
+            .filter(|font_face| families.iter().any(|family| font_face.is_in_family(family)))
+            .map(|font_face| DomRoot::from_ref(&**font_face))
+            .collect())
+    }
+}
+
+impl FontFaceSetMethods for FontFaceSet {
+    // https://drafts.csswg.org/css-font-loading/#FontFaceSet-interface
+    fn Size(&self) -> u32 {
+        self.font_faces.borrow().len() as u32
+    }
+
+    // https://drafts.csswg.org/css-font-loading/#FontFaceSet-interface
+    fn Has(&self, font: &FontFace) -> bool {
+        self.font_faces
+            .borrow()
+            .iter()
+            .any(|font_face| &**font_face == font)
+    }
+
+    // https://drafts.csswg.org/css-font-loading/#dom-fontfaceset-add
+    fn Add(&self, font: &FontFace) -> DomRoot<FontFaceSet> {
+        // Step 1
+        if !self.Has(font) {
+            // Step 4
+            self.font_faces.borrow_mut().push(Dom::from_ref(font));
+            font.add_to_font_face_set(self);
+            // Step 5
+            self.font_face_status_changed();
+        }
+        DomRoot::from_ref(self)
+    }
+
+    // https://drafts.csswg.org/css-font-loading/#dom-fontfaceset-delete
+    fn Delete(&self, font: &FontFace) -> bool {
+        // Step 2
+        if !self.Has(font) {
+            return false;
+        }
+        self.font_faces
+            .borrow_mut()
+            .retain(|font_face| &**font_face != font);
+        font.remove_from_font_face_set(self);
+        self.font_face_status_changed();
+        true
+    }
+
+    // https://drafts.csswg.org/css-font-loading/#dom-fontfaceset-clear
+    fn Clear(&self) {
+        for font_face in self.font_faces.borrow().iter() {
+            font_face.remove_from_font_face_set(self);
+        }
+        self.font_faces.borrow_mut().clear();
+        self.font_face_status_changed();
+    }
+
+    // https://drafts.csswg.org/css-font-loading/#dom-fontfaceset-load
+    #[allow(unsafe_code)]
+    fn Load(&self, font: DOMString, _text: DOMString) -> Fallible<Rc<Promise>> {
+        // Step 3
+        let font_faces = self.matching_font_faces(&font)?;
+        // Step 1
+        let promise = unsafe { Promise::new_in_current_compartment(&self.global()) };
+        // Step 4
+        for font_face in &font_faces {
+            font_face.Load();
+        }
+        // Step 5
+        self.pending_loads.borrow_mut().push(PendingLoad {
+            promise: promise.clone(),
+            font_faces: font_faces
+                .iter()
+                .map(|font_face| Dom::from_ref(&**font_face))
+                .collect(),
+        });
+        self.font_face_status_changed();
+        Ok(promise)
+    }
+
+    // https://drafts.csswg.org/css-font-loading/#dom-fontfaceset-check
+    fn Check(&self, font: DOMString, _text: DOMString) -> Fallible<bool> {
+        // Steps 3-5
+        Ok(self
+            .matching_font_faces(&font)?
+            .iter()
+            .all(|font_face| font_face.Status() == FontFaceLoadStatus::Loaded))
+    }
+
+    // https://drafts.csswg.org/css-font-loading/#dom-fontfaceset-ready
+    fn Ready(&self) -> Rc<Promise> {
+        self.ready.borrow().clone()
+    }
+
+    // https://drafts.csswg.org/css-font-loading/#dom-fontfaceset-status
+    fn Status(&self) -> FontFaceSetLoadStatus {
+        if self.is_loading() {
+            FontFaceSetLoadStatus::Loading
+        } else {
+            FontFaceSetLoadStatus::Loaded
+        }
+    }
+}
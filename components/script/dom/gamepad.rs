@@ -2,8 +2,10 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
+use crate::dom::bindings::cell::DomRefCell;
 use crate::dom::bindings::codegen::Bindings::GamepadBinding;
 use crate::dom::bindings::codegen::Bindings::GamepadBinding::GamepadMethods;
+use crate::dom::bindings::codegen::Bindings::PerformanceBinding::PerformanceMethods;
 use crate::dom::bindings::inheritance::Castable;
 use crate::dom::bindings::num::Finite;
 use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
@@ -13,15 +15,19 @@ use crate::dom::event::Event;
 use crate::dom::eventtarget::EventTarget;
 use crate::dom::gamepadbuttonlist::GamepadButtonList;
 use crate::dom::gamepadevent::{GamepadEvent, GamepadEventType};
+use crate::dom::gamepadhapticactuator::GamepadHapticActuator;
 use crate::dom::globalscope::GlobalScope;
 use crate::dom::vrpose::VRPose;
 use dom_struct::dom_struct;
+use js::conversions::ToJSValConvertible;
 use js::jsapi::{Heap, JSContext, JSObject};
+use js::jsval::{JSVal, UndefinedValue};
 use js::typedarray::{CreateWith, Float64Array};
+use script_traits::GamepadUpdateType;
 use std::cell::Cell;
 use std::ptr;
 use std::ptr::NonNull;
-use webvr_traits::{WebVRGamepadData, WebVRGamepadHand, WebVRGamepadState};
+use webvr_traits::{WebVRGamepadData, WebVRGamepadHand, WebVRGamepadState, WebVRPose};
 
 #[dom_struct]
 pub struct Gamepad {
@@ -31,15 +37,30 @@ pub struct Gamepad {
     index: Cell<i32>,
     connected: Cell<bool>,
     timestamp: Cell<f64>,
-    mapping_type: String,
+    /// Changes to "xr-standard" when an XR session adopts the gamepad
+    mapping_type: DomRefCell<String>,
     axes: Heap<*mut JSObject>,
     buttons: Dom<GamepadButtonList>,
     pose: Option<Dom<VRPose>>,
     #[ignore_malloc_size_of = "Defined in rust-webvr"]
     hand: WebVRGamepadHand,
     display_id: u32,
+    haptic_actuators: Heap<JSVal>,
+    /// The latest pose reported by the device, kept around so that XR
+    /// doesn't have to read it back from the `pose` typed arrays
+    #[ignore_malloc_size_of = "Defined in rust-webvr"]
+    vr_pose: DomRefCell<WebVRPose>,
 }
 
+unsafe_no_jsmanaged_fields!(WebVRPose);
+
+/// The number of buttons and axes of the standard gamepad layout, which the
+/// embedder remaps every gamepad it polls to
+///
+/// https://w3c.github.io/gamepad/#remapping
+const STANDARD_BUTTON_COUNT: usize = 17;
+const STANDARD_AXIS_COUNT: usize = 4;
+
 impl Gamepad {
     fn new_inherited(
         gamepad_id: u32,
@@ -60,12 +81,14 @@ impl Gamepad {
             index: Cell::new(index),
             connected: Cell::new(connected),
             timestamp: Cell::new(timestamp),
-            mapping_type: mapping_type,
+            mapping_type: DomRefCell::new(mapping_type),
             axes: Heap::default(),
             buttons: Dom::from_ref(buttons),
             pose: pose.map(Dom::from_ref),
             hand: hand,
             display_id: display_id,
+            haptic_actuators: Heap::default(),
+            vr_pose: DomRefCell::new(WebVRPose::default()),
         }
     }
 
@@ -102,6 +125,48 @@ impl Gamepad {
             let _ = Float64Array::create(cx, CreateWith::Slice(&state.axes), array.handle_mut());
         }
         gamepad.axes.set(array.get());
+        *gamepad.vr_pose.borrow_mut() = state.pose.clone();
+        // The actuator is only exposed once an XR session adopts the gamepad
+        gamepad.set_haptic_actuators(vec![]);
+
+        gamepad
+    }
+
+    /// Creates a gamepad polled by the embedder, which has the standard
+    /// layout and starts at rest
+    #[allow(unsafe_code)]
+    pub fn new_standard(global: &GlobalScope, gamepad_id: u32, id: String) -> DomRoot<Gamepad> {
+        let buttons = GamepadButtonList::new_released(&global, STANDARD_BUTTON_COUNT);
+        let gamepad = reflect_dom_object(
+            Box::new(Gamepad::new_inherited(
+                gamepad_id,
+                id,
+                0,
+                true,
+                *global.performance().Now(),
+                "standard".into(),
+                &buttons,
+                None,
+                WebVRGamepadHand::Unknown,
+                0,
+            )),
+            global,
+            GamepadBinding::Wrap,
+        );
+
+        let cx = global.get_cx();
+        rooted!(in (cx) let mut array = ptr::null_mut::<JSObject>());
+        unsafe {
+            let _ = Float64Array::create(
+                cx,
+                CreateWith::Slice(&[0.; STANDARD_AXIS_COUNT]),
+                array.handle_mut(),
+            );
+        }
+        gamepad.axes.set(array.get());
+
+        // The embedder doesn't drive any rumble motor
+        gamepad.set_haptic_actuators(vec![]);
 
         gamepad
     }
@@ -130,7 +195,7 @@ impl GamepadMethods for Gamepad {
 
     // https://w3c.github.io/gamepad/#dom-gamepad-mapping
     fn Mapping(&self) -> DOMString {
-        DOMString::from(self.mapping_type.clone())
+        DOMString::from(self.mapping_type.borrow().clone())
     }
 
     #[allow(unsafe_code)]
@@ -163,6 +228,12 @@ impl GamepadMethods for Gamepad {
     fn DisplayId(&self) -> u32 {
         self.display_id
     }
+
+    #[allow(unsafe_code)]
+    // https://w3c.github.io/gamepad/extensions.html#dom-gamepad-hapticactuators
+    unsafe fn HapticActuators(&self, _cx: *mut JSContext) -> JSVal {
+        self.haptic_actuators.get()
+    }
 }
 
 impl Gamepad {
@@ -172,21 +243,80 @@ impl Gamepad {
         unsafe {
             let cx = self.global().get_cx();
             typedarray!(in(cx) let axes: Float64Array = self.axes.get());
-            if let Ok(mut array) = axes {
-                array.update(&state.axes);
+            let updated = match axes {
+                Ok(mut array) => sync_axes(array.as_mut_slice(), &state.axes),
+                Err(_) => false,
+            };
+            if !updated {
+                // The device mapping changed the number of axes
+                rooted!(in (cx) let mut array = ptr::null_mut::<JSObject>());
+                let _ =
+                    Float64Array::create(cx, CreateWith::Slice(&state.axes), array.handle_mut());
+                self.axes.set(array.get());
             }
         }
         self.buttons.sync_from_vr(&state.buttons);
         if let Some(ref pose) = self.pose {
             pose.update(&state.pose);
         }
+        *self.vr_pose.borrow_mut() = state.pose.clone();
         self.update_connected(state.connected);
     }
 
+    /// Applies a change of a button or an axis reported by the embedder
+    #[allow(unsafe_code)]
+    pub fn update_from_embedder(&self, update: GamepadUpdateType) {
+        match update {
+            GamepadUpdateType::Axis(index, value) => unsafe {
+                let cx = self.global().get_cx();
+                typedarray!(in(cx) let axes: Float64Array = self.axes.get());
+                if let Ok(mut array) = axes {
+                    if let Some(axis) = array.as_mut_slice().get_mut(index) {
+                        *axis = value;
+                    }
+                }
+            },
+            GamepadUpdateType::Button(index, value) => self.buttons.update_button(index, value),
+        }
+        self.timestamp.set(*self.global().performance().Now());
+    }
+
     pub fn gamepad_id(&self) -> u32 {
         self.gamepad_id
     }
 
+    pub fn vr_pose(&self) -> WebVRPose {
+        self.vr_pose.borrow().clone()
+    }
+
+    pub fn hand(&self) -> WebVRGamepadHand {
+        self.hand.clone()
+    }
+
+    pub fn set_mapping_type(&self, mapping_type: &str) {
+        *self.mapping_type.borrow_mut() = mapping_type.to_owned();
+    }
+
+    #[allow(unsafe_code)]
+    fn set_haptic_actuators(&self, actuators: Vec<DomRoot<GamepadHapticActuator>>) {
+        let cx = self.global().get_cx();
+        rooted!(in(cx) let mut value = UndefinedValue());
+        unsafe {
+            actuators.to_jsval(cx, value.handle_mut());
+        }
+        self.haptic_actuators.set(value.get());
+    }
+
+    /// Exposes the vibration actuator of the controller, for the gamepads
+    /// of XR input sources
+    ///
+    /// rust-webvr can't tell us whether a controller can rumble, so they all
+    /// get a single vibration actuator.
+    pub fn enable_haptic_actuator(&self) {
+        let actuator = GamepadHapticActuator::new(&self.global(), self.gamepad_id);
+        self.set_haptic_actuators(vec![actuator]);
+    }
+
     pub fn update_connected(&self, connected: bool) {
         if self.connected.get() == connected {
             return;
@@ -213,3 +343,16 @@ impl Gamepad {
             .fire(self.global().as_window().upcast::<EventTarget>());
     }
 }
+
+/// Copies the axis values the device reported into `axes`, returning false
+/// if the device now has a different number of axes
+///
+/// The values are exposed exactly as the device reports them, content is
+/// expected to apply its own deadzones.
+pub fn sync_axes(axes: &mut [f64], device_axes: &[f64]) -> bool {
+    if axes.len() != device_axes.len() {
+        return false;
+    }
+    axes.copy_from_slice(device_axes);
+    true
+}
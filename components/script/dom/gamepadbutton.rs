@@ -21,11 +21,12 @@ pub struct GamepadButton {
 
 impl GamepadButton {
     pub fn new_inherited(pressed: bool, touched: bool) -> GamepadButton {
+        let (pressed, touched, value) = button_state(pressed, touched);
         Self {
             reflector_: Reflector::new(),
             pressed: Cell::new(pressed),
             touched: Cell::new(touched),
-            value: Cell::new(0.0),
+            value: Cell::new(value),
         }
     }
 
@@ -57,7 +58,34 @@ impl GamepadButtonMethods for GamepadButton {
 
 impl GamepadButton {
     pub fn update(&self, pressed: bool, touched: bool) {
+        let (pressed, touched, value) = button_state(pressed, touched);
         self.pressed.set(pressed);
         self.touched.set(touched);
+        self.value.set(value);
     }
+
+    /// Updates a button of a gamepad polled by the embedder, which only
+    /// reports how far the button is pushed
+    pub fn update_value(&self, value: f64) {
+        let pressed = value > BUTTON_PRESSED_THRESHOLD;
+        self.pressed.set(pressed);
+        self.touched.set(pressed);
+        self.value.set(value);
+    }
+}
+
+/// How far an analog button, like a trigger, has to be pushed to count as
+/// pressed
+const BUTTON_PRESSED_THRESHOLD: f64 = 0.1;
+
+/// The pressed, touched and value attributes for a button the device
+/// reports as `pressed` and `touched`
+///
+/// Capacitive buttons are touched as soon as a finger rests on them, well
+/// before they're pressed, so the two are kept apart; but a button can't be
+/// pressed without being touched. WebVR buttons are digital, so the value is
+/// all or nothing.
+pub fn button_state(pressed: bool, touched: bool) -> (bool, bool, f64) {
+    let value = if pressed { 1.0 } else { 0.0 };
+    (pressed, touched || pressed, value)
 }
@@ -41,6 +41,23 @@ impl GamepadButtonList {
         )
     }
 
+    /// Creates a list of `count` released buttons
+    pub fn new_released(global: &GlobalScope, count: usize) -> DomRoot<GamepadButtonList> {
+        rooted_vec!(let list <- (0..count).map(|_| GamepadButton::new(&global, false, false)));
+
+        reflect_dom_object(
+            Box::new(GamepadButtonList::new_inherited(list.r())),
+            global,
+            GamepadButtonListBinding::Wrap,
+        )
+    }
+
+    pub fn update_button(&self, index: usize, value: f64) {
+        if let Some(button) = self.list.get(index) {
+            button.update_value(value);
+        }
+    }
+
     pub fn sync_from_vr(&self, vr_buttons: &[WebVRGamepadButton]) {
         for (gp_btn, btn) in self.list.iter().zip(vr_buttons.iter()) {
             gp_btn.update(btn.pressed, btn.touched);
@@ -0,0 +1,202 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::GamepadHapticActuatorBinding;
+use crate::dom::bindings::codegen::Bindings::GamepadHapticActuatorBinding::GamepadHapticActuatorMethods;
+use crate::dom::bindings::codegen::Bindings::GamepadHapticActuatorBinding::GamepadHapticActuatorType;
+use crate::dom::bindings::error::Error;
+use crate::dom::bindings::num::Finite;
+use crate::dom::bindings::refcounted::{Trusted, TrustedPromise};
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::promise::Promise;
+use crate::task_source::{TaskSource, TaskSourceName};
+use crate::timers::{OneshotTimerCallback, OneshotTimerHandle};
+use dom_struct::dom_struct;
+use ipc_channel::ipc;
+use ipc_channel::router::ROUTER;
+use script_traits::MsDuration;
+use std::cell::Cell;
+use std::rc::Rc;
+use std::sync::{Arc, Mutex};
+use webvr_traits::{WebVRMsg, WebVRResult};
+
+#[dom_struct]
+pub struct GamepadHapticActuator {
+    reflector_: Reflector,
+    gamepad_id: u32,
+    /// The promise for the pulse that is currently playing, if any
+    #[ignore_malloc_size_of = "Rc is hard"]
+    playing_pulse: DomRefCell<PlayingPulse<Rc<Promise>>>,
+    pulse_timer: Cell<Option<OneshotTimerHandle>>,
+}
+
+impl GamepadHapticActuator {
+    fn new_inherited(gamepad_id: u32) -> GamepadHapticActuator {
+        GamepadHapticActuator {
+            reflector_: Reflector::new(),
+            gamepad_id,
+            playing_pulse: DomRefCell::new(PlayingPulse::new()),
+            pulse_timer: Cell::new(None),
+        }
+    }
+
+    pub fn new(global: &GlobalScope, gamepad_id: u32) -> DomRoot<GamepadHapticActuator> {
+        reflect_dom_object(
+            Box::new(GamepadHapticActuator::new_inherited(gamepad_id)),
+            global,
+            GamepadHapticActuatorBinding::Wrap,
+        )
+    }
+
+    /// Asks the device to play a pulse, which is played once the device
+    /// accepted it. `promise` is rejected if the controller is gone.
+    fn send_pulse(&self, promise: &Rc<Promise>, intensity: f64, duration: f64) {
+        let global = self.global();
+        let webvr_thread = match global.as_window().webvr_thread() {
+            Some(webvr_thread) => webvr_thread,
+            None => return promise.reject_error(Error::InvalidState),
+        };
+        let (sender, receiver) = ipc::channel().unwrap();
+        let msg = WebVRMsg::HapticPulse(self.gamepad_id, intensity, duration, sender);
+        if webvr_thread.send(msg).is_err() {
+            return promise.reject_error(Error::InvalidState);
+        }
+
+        let gamepad_id = self.gamepad_id;
+        let actuator = Trusted::new(self);
+        let task_source = global.dom_manipulation_task_source();
+        let canceller = global.task_canceller(TaskSourceName::DOMManipulation);
+        let trusted_promise = Arc::new(Mutex::new(Some(TrustedPromise::new(promise.clone()))));
+        ROUTER.add_route(
+            receiver.to_opaque(),
+            Box::new(move |message| {
+                let result: WebVRResult<()> = message.to().unwrap_or_else(|e| Err(e.to_string()));
+                let trusted_promise = match trusted_promise.lock().unwrap().take() {
+                    Some(trusted_promise) => trusted_promise,
+                    None => return,
+                };
+                let actuator = actuator.clone();
+                let _ = task_source.queue_with_canceller(
+                    task!(haptic_pulse_accepted: move || {
+                        let promise = trusted_promise.root();
+                        match result {
+                            Ok(()) => actuator.root().play_pulse(promise, duration),
+                            Err(msg) => {
+                                warn!("Gamepad {} can't play a haptic pulse: {}", gamepad_id, msg);
+                                promise.reject_error(Error::InvalidState);
+                            },
+                        }
+                    }),
+                    &canceller,
+                );
+            }),
+        );
+    }
+
+    /// Plays a pulse the device accepted, which preempts the one currently
+    /// playing
+    fn play_pulse(&self, promise: Rc<Promise>, duration: f64) {
+        if let Some(handle) = self.pulse_timer.take() {
+            self.global().unschedule_callback(handle);
+        }
+        let (pulse_id, preempted) = self.playing_pulse.borrow_mut().start(promise);
+        if let Some(preempted) = preempted {
+            preempted.resolve_native(&false);
+        }
+
+        let callback = OneshotTimerCallback::HapticPulse(HapticPulseCallback {
+            actuator: Trusted::new(self),
+            pulse_id,
+        });
+        let handle = self
+            .global()
+            .schedule_callback(callback, MsDuration::new(duration as u64));
+        self.pulse_timer.set(Some(handle));
+    }
+
+    /// Called when the timer of the pulse `pulse_id` fires
+    fn complete_pulse(&self, pulse_id: u32) {
+        if let Some(promise) = self.playing_pulse.borrow_mut().complete(pulse_id) {
+            // the timer has already fired, there is nothing to unschedule
+            self.pulse_timer.set(None);
+            promise.resolve_native(&true);
+        }
+    }
+}
+
+impl GamepadHapticActuatorMethods for GamepadHapticActuator {
+    /// https://w3c.github.io/gamepad/extensions.html#dom-gamepadhapticactuator-type
+    fn Type(&self) -> GamepadHapticActuatorType {
+        GamepadHapticActuatorType::Vibration
+    }
+
+    /// https://w3c.github.io/gamepad/extensions.html#dom-gamepadhapticactuator-pulse
+    #[allow(unsafe_code)]
+    fn Pulse(&self, value: Finite<f64>, duration: Finite<f64>) -> Rc<Promise> {
+        let promise = unsafe { Promise::new_in_current_compartment(&self.global()) };
+
+        let (intensity, duration) = pulse_parameters(*value, *duration);
+        self.send_pulse(&promise, intensity, duration);
+        promise
+    }
+}
+
+#[derive(JSTraceable, MallocSizeOf)]
+pub struct HapticPulseCallback {
+    #[ignore_malloc_size_of = "Because it is non-owning"]
+    actuator: Trusted<GamepadHapticActuator>,
+    pulse_id: u32,
+}
+
+impl HapticPulseCallback {
+    pub fn invoke(self) {
+        self.actuator.root().complete_pulse(self.pulse_id);
+    }
+}
+
+/// The intensity and duration (in milliseconds) of a pulse requested by
+/// content, kept within what a device can play
+pub fn pulse_parameters(value: f64, duration: f64) -> (f64, f64) {
+    (value.max(0.).min(1.), duration.max(0.))
+}
+
+/// The pulse an actuator is playing, with what to resolve once it is over
+///
+/// Only one pulse plays at a time, starting a new one preempts the pulse
+/// that is playing.
+#[derive(JSTraceable)]
+pub struct PlayingPulse<T> {
+    /// Incremented on every pulse, so that stale completions can be told
+    /// apart from the completion of the pulse that is playing
+    id: u32,
+    pending: Option<T>,
+}
+
+impl<T> PlayingPulse<T> {
+    pub fn new() -> PlayingPulse<T> {
+        PlayingPulse {
+            id: 0,
+            pending: None,
+        }
+    }
+
+    /// Starts a new pulse, returning its id along with the pending value of
+    /// the pulse it preempted, if any
+    pub fn start(&mut self, pending: T) -> (u32, Option<T>) {
+        self.id = self.id.wrapping_add(1);
+        (self.id, self.pending.replace(pending))
+    }
+
+    /// Completes the pulse `id`, returning its pending value unless another
+    /// pulse preempted it in the meantime
+    pub fn complete(&mut self, id: u32) -> Option<T> {
+        if id != self.id {
+            return None;
+        }
+        self.pending.take()
+    }
+}
@@ -36,12 +36,9 @@ impl GamepadList {
 
     pub fn add_if_not_exists(&self, gamepads: &[DomRoot<Gamepad>]) {
         for gamepad in gamepads {
-            if !self
-                .list
-                .borrow()
-                .iter()
-                .any(|g| g.gamepad_id() == gamepad.gamepad_id())
-            {
+            // WebVR and the embedder number their gamepads separately, so
+            // the ids can't tell them apart.
+            if !self.list.borrow().iter().any(|g| **g == **gamepad) {
                 self.list.borrow_mut().push(Dom::from_ref(&*gamepad));
                 // Ensure that the gamepad has the correct index
                 gamepad.update_index(self.list.borrow().len() as i32 - 1);
@@ -0,0 +1,77 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Edge cases shared by the geometry and WebXR interfaces.
+//!
+//! DOMMatrix, DOMPoint, XRRigidTransform and XRRay all run into NaNs,
+//! singular matrices and vectors or quaternions of length zero. They go
+//! through the helpers here, so that they agree on what those turn into:
+//! matrices and points that can't be computed are NaN, while directions and
+//! orientations that can't be normalized fall back to none at all.
+
+use euclid::{Point3D, Rotation3D, Transform3D, Vector3D};
+use std::f64;
+
+/// Whether none of `values` is NaN or infinite
+pub fn all_finite(values: &[f64]) -> bool {
+    values.iter().all(|value| value.is_finite())
+}
+
+/// The matrix whose elements are all NaN, which stands for the result of
+/// operations that have none
+pub fn nan_matrix() -> Transform3D<f64> {
+    let nan = f64::NAN;
+    Transform3D::row_major(
+        nan, nan, nan, nan, nan, nan, nan, nan, nan, nan, nan, nan, nan, nan, nan, nan,
+    )
+}
+
+/// The inverse of `matrix`, or `nan_matrix()` if it isn't invertible
+///
+/// A matrix with NaN or infinite elements has no inverse either, even if
+/// euclid manages to compute one from it.
+pub fn inverse_or_nan(matrix: &Transform3D<f64>) -> Transform3D<f64> {
+    match matrix.inverse() {
+        Some(inverse) if all_finite(&inverse.to_row_major_array()) => inverse,
+        _ => nan_matrix(),
+    }
+}
+
+/// Divides the homogeneous point (x, y, z, w) by w
+///
+/// A point with a w of zero is a direction, which has no position to
+/// divide to, so it gives NaNs rather than infinities.
+pub fn perspective_divide(x: f64, y: f64, z: f64, w: f64) -> Point3D<f64> {
+    if w == 0. {
+        let nan = f64::NAN;
+        return Point3D::new(nan, nan, nan);
+    }
+    Point3D::new(x / w, y / w, z / w)
+}
+
+/// `vector` scaled to length one
+///
+/// A vector of length zero, or one that isn't finite, has no direction and
+/// stays the zero vector.
+pub fn normalized_vector(vector: Vector3D<f64>) -> Vector3D<f64> {
+    let length = vector.length();
+    if length > 0. && length.is_finite() {
+        vector / length
+    } else {
+        Vector3D::zero()
+    }
+}
+
+/// The rotation described by the quaternion (x, y, z, w), normalized first
+///
+/// A quaternion of length zero, or one that isn't finite, has no direction
+/// to normalize to and is taken as no rotation at all.
+pub fn normalized_rotation(x: f64, y: f64, z: f64, w: f64) -> Rotation3D<f64> {
+    let length = (x * x + y * y + z * z + w * w).sqrt();
+    if length > 0. && length.is_finite() {
+        Rotation3D::unit_quaternion(x / length, y / length, z / length, w / length)
+    } else {
+        Rotation3D::identity()
+    }
+}
@@ -0,0 +1,161 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::GPUBinding::GPURequestAdapterOptions;
+use crate::dom::bindings::codegen::Bindings::GPUBinding::{self, GPUMethods, GPUPowerPreference};
+use crate::dom::bindings::error::Error;
+use crate::dom::bindings::refcounted::{Trusted, TrustedPromise};
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::str::DOMString;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::gpuadapter::GPUAdapter;
+use crate::dom::promise::Promise;
+use crate::task::TaskOnce;
+use crate::task_source::{TaskSource, TaskSourceName};
+use dom_struct::dom_struct;
+use ipc_channel::ipc::{self, IpcSender};
+use ipc_channel::router::ROUTER;
+use script_traits::ScriptMsg;
+use std::rc::Rc;
+use std::sync::{Arc, Mutex};
+use webgpu::{wgt, WebGPUResponse, WebGPUResponseResult};
+
+struct WGPUContext<T: AsyncWGPUListener + DomObject> {
+    promise: Option<TrustedPromise>,
+    receiver: Trusted<T>,
+}
+
+/// An object waiting for the WebGPU thread to answer a request made on
+/// behalf of a promise it returned.
+pub trait AsyncWGPUListener {
+    fn handle_response(&self, response: WebGPUResponseResult, promise: &Rc<Promise>);
+}
+
+impl<T> WGPUContext<T>
+where
+    T: AsyncWGPUListener + DomObject,
+{
+    #[allow(unrooted_must_root)]
+    fn response(&mut self, response: WebGPUResponseResult) {
+        let promise = self
+            .promise
+            .take()
+            .expect("WebGPU promise is missing")
+            .root();
+        self.receiver.root().handle_response(response, &promise);
+    }
+}
+
+/// Returns a channel whose response to the request it gets sent with is
+/// handed to `receiver` in a task, along with `promise`.
+pub fn response_async<T: AsyncWGPUListener + DomObject + 'static>(
+    promise: &Rc<Promise>,
+    receiver: &T,
+) -> IpcSender<WebGPUResponseResult> {
+    let (action_sender, action_receiver) = ipc::channel().unwrap();
+    let global = receiver.global();
+    let task_source = global.dom_manipulation_task_source();
+    let canceller = global.task_canceller(TaskSourceName::DOMManipulation);
+    let context = Arc::new(Mutex::new(WGPUContext {
+        promise: Some(TrustedPromise::new(promise.clone())),
+        receiver: Trusted::new(receiver),
+    }));
+    ROUTER.add_route(
+        action_receiver.to_opaque(),
+        Box::new(move |message| {
+            struct ListenerTask<T: AsyncWGPUListener + DomObject> {
+                context: Arc<Mutex<WGPUContext<T>>>,
+                action: WebGPUResponseResult,
+            }
+
+            impl<T> TaskOnce for ListenerTask<T>
+            where
+                T: AsyncWGPUListener + DomObject,
+            {
+                fn run_once(self) {
+                    let mut context = self.context.lock().unwrap();
+                    context.response(self.action);
+                }
+            }
+
+            let task = ListenerTask {
+                context: context.clone(),
+                action: message.to().unwrap(),
+            };
+
+            let result = task_source.queue_with_canceller(task, &canceller);
+            if let Err(err) = result {
+                warn!("Failed to deliver the WebGPU response: {:?}", err);
+            }
+        }),
+    );
+    action_sender
+}
+
+#[dom_struct]
+pub struct GPU {
+    reflector_: Reflector,
+}
+
+impl GPU {
+    pub fn new_inherited() -> GPU {
+        GPU {
+            reflector_: Reflector::new(),
+        }
+    }
+
+    pub fn new(global: &GlobalScope) -> DomRoot<GPU> {
+        reflect_dom_object(Box::new(GPU::new_inherited()), global, GPUBinding::Wrap)
+    }
+}
+
+impl GPUMethods for GPU {
+    // https://gpuweb.github.io/gpuweb/#dom-gpu-requestadapter
+    #[allow(unsafe_code)]
+    fn RequestAdapter(&self, options: &GPURequestAdapterOptions) -> Rc<Promise> {
+        let global = self.global();
+        let promise = unsafe { Promise::new_in_current_compartment(&global) };
+        let sender = response_async(&promise, self);
+        let power_preference = match options.powerPreference {
+            Some(GPUPowerPreference::Low_power) => wgt::PowerPreference::LowPower,
+            Some(GPUPowerPreference::High_performance) => wgt::PowerPreference::HighPerformance,
+            None => wgt::PowerPreference::Default,
+        };
+        if global
+            .script_to_constellation_chan()
+            .send(ScriptMsg::RequestAdapter(sender, power_preference))
+            .is_err()
+        {
+            promise.reject_error(Error::Type("Failed to send the adapter request".to_owned()));
+        }
+        promise
+    }
+}
+
+impl AsyncWGPUListener for GPU {
+    fn handle_response(&self, response: WebGPUResponseResult, promise: &Rc<Promise>) {
+        match response {
+            Ok(WebGPUResponse::RequestAdapter {
+                adapter_name,
+                adapter_id,
+                channel,
+            }) => {
+                let adapter = GPUAdapter::new(
+                    &self.global(),
+                    channel,
+                    DOMString::from(adapter_name),
+                    adapter_id,
+                );
+                promise.resolve_native(&adapter);
+            },
+            Err(error) => {
+                promise.reject_error(Error::Type(format!("Failed to get an adapter: {}", error)))
+            },
+            Ok(_) => promise.reject_error(Error::Type(
+                "Received an unexpected response to the adapter request".to_owned(),
+            )),
+        }
+    }
+}
@@ -0,0 +1,109 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::GPUAdapterBinding::{
+    self, GPUAdapterMethods, GPUDeviceDescriptor,
+};
+use crate::dom::bindings::error::Error;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::str::DOMString;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::gpu::{response_async, AsyncWGPUListener};
+use crate::dom::gpudevice::GPUDevice;
+use crate::dom::promise::Promise;
+use dom_struct::dom_struct;
+use std::rc::Rc;
+use webgpu::{wgt, WebGPU, WebGPUAdapter, WebGPURequest, WebGPUResponse, WebGPUResponseResult};
+
+#[dom_struct]
+pub struct GPUAdapter {
+    reflector_: Reflector,
+    #[ignore_malloc_size_of = "channels are hard"]
+    channel: WebGPU,
+    name: DOMString,
+    adapter: WebGPUAdapter,
+}
+
+impl GPUAdapter {
+    fn new_inherited(channel: WebGPU, name: DOMString, adapter: WebGPUAdapter) -> GPUAdapter {
+        GPUAdapter {
+            reflector_: Reflector::new(),
+            channel,
+            name,
+            adapter,
+        }
+    }
+
+    pub fn new(
+        global: &GlobalScope,
+        channel: WebGPU,
+        name: DOMString,
+        adapter: WebGPUAdapter,
+    ) -> DomRoot<GPUAdapter> {
+        reflect_dom_object(
+            Box::new(GPUAdapter::new_inherited(channel, name, adapter)),
+            global,
+            GPUAdapterBinding::Wrap,
+        )
+    }
+}
+
+impl GPUAdapterMethods for GPUAdapter {
+    // https://gpuweb.github.io/gpuweb/#dom-gpuadapter-name
+    fn Name(&self) -> DOMString {
+        self.name.clone()
+    }
+
+    // https://gpuweb.github.io/gpuweb/#dom-gpuadapter-requestdevice
+    #[allow(unsafe_code)]
+    fn RequestDevice(&self, descriptor: &GPUDeviceDescriptor) -> Rc<Promise> {
+        let promise = unsafe { Promise::new_in_current_compartment(&self.global()) };
+        let sender = response_async(&promise, self);
+        let limits = wgt::Limits {
+            max_bind_groups: descriptor.limits.maxBindGroups,
+        };
+        if self
+            .channel
+            .0
+            .send(WebGPURequest::RequestDevice {
+                sender,
+                adapter_id: self.adapter,
+                limits,
+            })
+            .is_err()
+        {
+            promise.reject_error(Error::Type("Failed to send the device request".to_owned()));
+        }
+        promise
+    }
+}
+
+impl AsyncWGPUListener for GPUAdapter {
+    fn handle_response(&self, response: WebGPUResponseResult, promise: &Rc<Promise>) {
+        match response {
+            Ok(WebGPUResponse::RequestDevice {
+                device_id,
+                queue_id,
+                limits,
+            }) => {
+                let device = GPUDevice::new(
+                    &self.global(),
+                    self.channel.clone(),
+                    self,
+                    limits,
+                    device_id,
+                    queue_id,
+                );
+                promise.resolve_native(&device);
+            },
+            Err(error) => {
+                promise.reject_error(Error::Type(format!("Failed to get a device: {}", error)))
+            },
+            Ok(_) => promise.reject_error(Error::Type(
+                "Received an unexpected response to the device request".to_owned(),
+            )),
+        }
+    }
+}
@@ -0,0 +1,53 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::GPUBindGroupBinding;
+use crate::dom::bindings::reflector::{reflect_dom_object, Reflector};
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::gpubuffer::GPUBuffer;
+use dom_struct::dom_struct;
+use webgpu::WebGPUBindGroup;
+
+#[dom_struct]
+pub struct GPUBindGroup {
+    reflector_: Reflector,
+    bind_group: WebGPUBindGroup,
+    /// The buffers bound to this group, the command buffers using the
+    /// group can't be submitted while one of them is mapped.
+    buffers: Vec<Dom<GPUBuffer>>,
+}
+
+impl GPUBindGroup {
+    fn new_inherited(bind_group: WebGPUBindGroup, buffers: &[DomRoot<GPUBuffer>]) -> GPUBindGroup {
+        GPUBindGroup {
+            reflector_: Reflector::new(),
+            bind_group,
+            buffers: buffers.iter().map(|b| Dom::from_ref(&**b)).collect(),
+        }
+    }
+
+    pub fn new(
+        global: &GlobalScope,
+        bind_group: WebGPUBindGroup,
+        buffers: &[DomRoot<GPUBuffer>],
+    ) -> DomRoot<GPUBindGroup> {
+        reflect_dom_object(
+            Box::new(GPUBindGroup::new_inherited(bind_group, buffers)),
+            global,
+            GPUBindGroupBinding::Wrap,
+        )
+    }
+
+    pub fn id(&self) -> WebGPUBindGroup {
+        self.bind_group
+    }
+
+    pub fn buffers(&self) -> Vec<DomRoot<GPUBuffer>> {
+        self.buffers
+            .iter()
+            .map(|buffer| DomRoot::from_ref(&**buffer))
+            .collect()
+    }
+}
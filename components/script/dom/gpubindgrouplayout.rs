@@ -0,0 +1,40 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::GPUBindGroupLayoutBinding;
+use crate::dom::bindings::reflector::{reflect_dom_object, Reflector};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::globalscope::GlobalScope;
+use dom_struct::dom_struct;
+use webgpu::WebGPUBindGroupLayout;
+
+#[dom_struct]
+pub struct GPUBindGroupLayout {
+    reflector_: Reflector,
+    bind_group_layout: WebGPUBindGroupLayout,
+}
+
+impl GPUBindGroupLayout {
+    fn new_inherited(bind_group_layout: WebGPUBindGroupLayout) -> GPUBindGroupLayout {
+        GPUBindGroupLayout {
+            reflector_: Reflector::new(),
+            bind_group_layout,
+        }
+    }
+
+    pub fn new(
+        global: &GlobalScope,
+        bind_group_layout: WebGPUBindGroupLayout,
+    ) -> DomRoot<GPUBindGroupLayout> {
+        reflect_dom_object(
+            Box::new(GPUBindGroupLayout::new_inherited(bind_group_layout)),
+            global,
+            GPUBindGroupLayoutBinding::Wrap,
+        )
+    }
+
+    pub fn id(&self) -> WebGPUBindGroupLayout {
+        self.bind_group_layout
+    }
+}
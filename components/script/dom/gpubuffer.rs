@@ -0,0 +1,354 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::GPUBufferBinding::{self, GPUBufferMethods};
+use crate::dom::bindings::codegen::Bindings::GPUMapModeBinding::GPUMapModeConstants;
+use crate::dom::bindings::error::{Error, Fallible};
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::gpu::{response_async, AsyncWGPUListener};
+use crate::dom::promise::Promise;
+use dom_struct::dom_struct;
+use ipc_channel::ipc::IpcSharedMemory;
+use js::jsapi::{Heap, JSContext, JSObject};
+use js::rust::wrappers::JS_DetachArrayBuffer;
+use js::typedarray::{ArrayBuffer, CreateWith};
+use std::cell::Cell;
+use std::ptr::{self, NonNull};
+use std::rc::Rc;
+use webgpu::{wgt, HostMap, WebGPU, WebGPUBuffer, WebGPUDevice, WebGPURequest};
+use webgpu::{WebGPUResponse, WebGPUResponseResult};
+
+// https://gpuweb.github.io/gpuweb/#buffer-state
+#[derive(Clone, Copy, Debug, JSTraceable, MallocSizeOf, PartialEq)]
+pub enum GPUBufferState {
+    Mapped,
+    MappedAtCreation,
+    MappingPending,
+    Unmapped,
+    Destroyed,
+}
+
+/// The range of a buffer that is mapped, or getting mapped, to the content.
+#[derive(JSTraceable, MallocSizeOf)]
+pub struct GPUBufferMapInfo {
+    #[ignore_malloc_size_of = "Defined in webgpu"]
+    host_map: HostMap,
+    offset: u64,
+    size: u64,
+    data: Vec<u8>,
+    /// The ArrayBuffers returned by `getMappedRange`, with the offset and
+    /// the size of the range they cover. They get detached on unmap.
+    #[ignore_malloc_size_of = "Defined in rust-mozjs"]
+    js_buffers: Vec<(u64, u64, Box<Heap<*mut JSObject>>)>,
+}
+
+#[dom_struct]
+pub struct GPUBuffer {
+    reflector_: Reflector,
+    #[ignore_malloc_size_of = "channels are hard"]
+    channel: WebGPU,
+    buffer: WebGPUBuffer,
+    device: WebGPUDevice,
+    size: u64,
+    #[ignore_malloc_size_of = "Defined in wgpu-types"]
+    usage: wgt::BufferUsage,
+    state: Cell<GPUBufferState>,
+    mapping: DomRefCell<Option<GPUBufferMapInfo>>,
+    #[ignore_malloc_size_of = "promises are hard"]
+    map_promise: DomRefCell<Option<Rc<Promise>>>,
+}
+
+impl GPUBuffer {
+    fn new_inherited(
+        channel: WebGPU,
+        buffer: WebGPUBuffer,
+        device: WebGPUDevice,
+        size: u64,
+        usage: wgt::BufferUsage,
+        mapped_at_creation: bool,
+    ) -> GPUBuffer {
+        let (state, mapping) = if mapped_at_creation {
+            let mapping = GPUBufferMapInfo {
+                host_map: HostMap::Write,
+                offset: 0,
+                size,
+                data: vec![0; size as usize],
+                js_buffers: Vec::new(),
+            };
+            (GPUBufferState::MappedAtCreation, Some(mapping))
+        } else {
+            (GPUBufferState::Unmapped, None)
+        };
+        GPUBuffer {
+            reflector_: Reflector::new(),
+            channel,
+            buffer,
+            device,
+            size,
+            usage,
+            state: Cell::new(state),
+            mapping: DomRefCell::new(mapping),
+            map_promise: DomRefCell::new(None),
+        }
+    }
+
+    pub fn new(
+        global: &GlobalScope,
+        channel: WebGPU,
+        buffer: WebGPUBuffer,
+        device: WebGPUDevice,
+        size: u64,
+        usage: wgt::BufferUsage,
+        mapped_at_creation: bool,
+    ) -> DomRoot<GPUBuffer> {
+        reflect_dom_object(
+            Box::new(GPUBuffer::new_inherited(
+                channel,
+                buffer,
+                device,
+                size,
+                usage,
+                mapped_at_creation,
+            )),
+            global,
+            GPUBufferBinding::Wrap,
+        )
+    }
+
+    pub fn id(&self) -> WebGPUBuffer {
+        self.buffer
+    }
+
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    pub fn is_unmapped(&self) -> bool {
+        self.state.get() == GPUBufferState::Unmapped
+    }
+
+    fn send_unmap(&self, data: Option<(u64, IpcSharedMemory)>) {
+        if let Err(e) = self.channel.0.send(WebGPURequest::UnmapBuffer {
+            buffer_id: self.buffer.0,
+            data,
+        }) {
+            warn!(
+                "Failed to send the unmapping of buffer {:?} ({})",
+                self.buffer, e
+            );
+        }
+    }
+}
+
+impl GPUBufferMethods for GPUBuffer {
+    // https://gpuweb.github.io/gpuweb/#dom-gpubuffer-mapasync
+    #[allow(unsafe_code)]
+    fn MapAsync(&self, mode: u32, offset: u64, size: Option<u64>) -> Rc<Promise> {
+        let promise = unsafe { Promise::new_in_current_compartment(&self.global()) };
+        if self.state.get() != GPUBufferState::Unmapped {
+            promise.reject_error(Error::Operation);
+            return promise;
+        }
+        let (host_map, usage) = match mode {
+            GPUMapModeConstants::READ => (HostMap::Read, wgt::BufferUsage::MAP_READ),
+            GPUMapModeConstants::WRITE => (HostMap::Write, wgt::BufferUsage::MAP_WRITE),
+            _ => {
+                promise.reject_error(Error::Operation);
+                return promise;
+            },
+        };
+        let size = size.unwrap_or(self.size.saturating_sub(offset));
+        if !self.usage.contains(usage) ||
+            offset.checked_add(size).map_or(true, |end| end > self.size)
+        {
+            promise.reject_error(Error::Operation);
+            return promise;
+        }
+
+        let sender = response_async(&promise, self);
+        if let Err(e) = self.channel.0.send(WebGPURequest::BufferMapAsync {
+            sender,
+            buffer_id: self.buffer.0,
+            device_id: self.device.0,
+            host_map,
+            offset,
+            size,
+        }) {
+            warn!(
+                "Failed to send the mapping of buffer {:?} ({})",
+                self.buffer, e
+            );
+            promise.reject_error(Error::Operation);
+            return promise;
+        }
+
+        self.state.set(GPUBufferState::MappingPending);
+        *self.mapping.borrow_mut() = Some(GPUBufferMapInfo {
+            host_map,
+            offset,
+            size,
+            data: Vec::new(),
+            js_buffers: Vec::new(),
+        });
+        *self.map_promise.borrow_mut() = Some(promise.clone());
+        promise
+    }
+
+    // https://gpuweb.github.io/gpuweb/#dom-gpubuffer-getmappedrange
+    #[allow(unsafe_code)]
+    unsafe fn GetMappedRange(
+        &self,
+        cx: *mut JSContext,
+        offset: u64,
+        size: Option<u64>,
+    ) -> Fallible<NonNull<JSObject>> {
+        match self.state.get() {
+            GPUBufferState::Mapped | GPUBufferState::MappedAtCreation => {},
+            _ => return Err(Error::Operation),
+        }
+        let mut mapping = self.mapping.borrow_mut();
+        let mapping = mapping.as_mut().expect("Mapped buffer without a mapping");
+        let mapping_end = mapping.offset + mapping.size;
+        let size = size.unwrap_or(mapping_end.saturating_sub(offset));
+        let end = offset.checked_add(size).ok_or(Error::Operation)?;
+        if offset < mapping.offset || end > mapping_end {
+            return Err(Error::Operation);
+        }
+        // The ranges returned by `getMappedRange` can't overlap.
+        if mapping
+            .js_buffers
+            .iter()
+            .any(|&(range_offset, range_size, _)| {
+                offset < range_offset + range_size && range_offset < end
+            })
+        {
+            return Err(Error::Operation);
+        }
+
+        let start = (offset - mapping.offset) as usize;
+        rooted!(in(cx) let mut array_buffer = ptr::null_mut::<JSObject>());
+        if ArrayBuffer::create(
+            cx,
+            CreateWith::Slice(&mapping.data[start..start + size as usize]),
+            array_buffer.handle_mut(),
+        )
+        .is_err()
+        {
+            return Err(Error::JSFailed);
+        }
+        mapping
+            .js_buffers
+            .push((offset, size, Heap::boxed(array_buffer.get())));
+        Ok(NonNull::new_unchecked(array_buffer.get()))
+    }
+
+    // https://gpuweb.github.io/gpuweb/#dom-gpubuffer-unmap
+    #[allow(unsafe_code)]
+    fn Unmap(&self) {
+        match self.state.get() {
+            GPUBufferState::Unmapped | GPUBufferState::Destroyed => return,
+            GPUBufferState::MappingPending => {
+                // The WebGPU thread unmaps the buffer once the mapping
+                // it got asked for is done, see `handle_response`.
+                if let Some(promise) = self.map_promise.borrow_mut().take() {
+                    promise.reject_error(Error::Abort);
+                }
+                *self.mapping.borrow_mut() = None;
+                self.state.set(GPUBufferState::Unmapped);
+                return;
+            },
+            GPUBufferState::Mapped | GPUBufferState::MappedAtCreation => {},
+        }
+
+        let mut mapping = self
+            .mapping
+            .borrow_mut()
+            .take()
+            .expect("Mapped buffer without a mapping");
+        let writable = mapping.host_map == HostMap::Write;
+        let cx = self.global().get_cx();
+        for (offset, _, js_buffer) in mapping.js_buffers.drain(..) {
+            unsafe {
+                rooted!(in(cx) let object = js_buffer.get());
+                if writable {
+                    typedarray!(in(cx) let array: ArrayBuffer = object.get());
+                    if let Ok(array) = array {
+                        let start = (offset - mapping.offset) as usize;
+                        let contents = array.as_slice();
+                        mapping.data[start..start + contents.len()].copy_from_slice(contents);
+                    }
+                }
+                let _ = JS_DetachArrayBuffer(cx, object.handle());
+            }
+        }
+
+        let data = if writable {
+            Some((mapping.offset, IpcSharedMemory::from_bytes(&mapping.data)))
+        } else {
+            None
+        };
+        self.send_unmap(data);
+        self.state.set(GPUBufferState::Unmapped);
+    }
+
+    // https://gpuweb.github.io/gpuweb/#dom-gpubuffer-destroy
+    fn Destroy(&self) {
+        match self.state.get() {
+            GPUBufferState::Destroyed => return,
+            GPUBufferState::Mapped |
+            GPUBufferState::MappedAtCreation |
+            GPUBufferState::MappingPending => self.Unmap(),
+            GPUBufferState::Unmapped => {},
+        }
+        if let Err(e) = self
+            .channel
+            .0
+            .send(WebGPURequest::DestroyBuffer(self.buffer.0))
+        {
+            warn!(
+                "Failed to send the destruction of buffer {:?} ({})",
+                self.buffer, e
+            );
+        }
+        self.state.set(GPUBufferState::Destroyed);
+    }
+}
+
+impl AsyncWGPUListener for GPUBuffer {
+    fn handle_response(&self, response: WebGPUResponseResult, promise: &Rc<Promise>) {
+        // The buffer may have been unmapped, and even mapped again, while
+        // the mapping was pending, in which case the promise was rejected.
+        let pending = self
+            .map_promise
+            .borrow()
+            .as_ref()
+            .map_or(false, |map_promise| Rc::ptr_eq(map_promise, promise));
+        match response {
+            Ok(WebGPUResponse::BufferMapAsync(data)) => {
+                if !pending {
+                    self.send_unmap(None);
+                    return;
+                }
+                if let Some(ref mut mapping) = *self.mapping.borrow_mut() {
+                    mapping.data = data.to_vec();
+                }
+                self.map_promise.borrow_mut().take();
+                self.state.set(GPUBufferState::Mapped);
+                promise.resolve_native(&());
+            },
+            _ => {
+                if !pending {
+                    return;
+                }
+                self.map_promise.borrow_mut().take();
+                *self.mapping.borrow_mut() = None;
+                self.state.set(GPUBufferState::Unmapped);
+                promise.reject_error(Error::Operation);
+            },
+        }
+    }
+}
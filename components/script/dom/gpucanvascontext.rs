@@ -0,0 +1,134 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::GPUCanvasContextBinding::{
+    self, GPUCanvasContextMethods, GPUSwapChainDescriptor,
+};
+use crate::dom::bindings::codegen::Bindings::GPUDeviceBinding::GPUDeviceMethods;
+use crate::dom::bindings::codegen::Bindings::GPUTextureBinding::GPUTextureFormat;
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
+use crate::dom::bindings::root::{Dom, DomRoot, LayoutDom, MutNullableDom};
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::gpudevice::{convert_texture_format, GPUDevice};
+use crate::dom::gpuswapchain::GPUSwapChain;
+use crate::dom::htmlcanvaselement::HTMLCanvasElement;
+use crate::dom::node::{Node, NodeDamage};
+use crate::dom::promise::Promise;
+use dom_struct::dom_struct;
+use ipc_channel::ipc;
+use script_layout_interface::HTMLCanvasDataSource;
+use std::cell::Cell;
+use std::rc::Rc;
+use webgpu::{wgt, TextureDescriptor, WebGPURequest};
+use webrender_api::ImageKey;
+
+#[dom_struct]
+pub struct GPUCanvasContext {
+    reflector_: Reflector,
+    canvas: Dom<HTMLCanvasElement>,
+    swap_chain: MutNullableDom<GPUSwapChain>,
+    /// The image the frames of the swap chain get presented to, read by
+    /// layout.
+    image_key: Cell<Option<ImageKey>>,
+}
+
+impl GPUCanvasContext {
+    fn new_inherited(canvas: &HTMLCanvasElement) -> GPUCanvasContext {
+        GPUCanvasContext {
+            reflector_: Reflector::new(),
+            canvas: Dom::from_ref(canvas),
+            swap_chain: MutNullableDom::new(None),
+            image_key: Cell::new(None),
+        }
+    }
+
+    pub fn new(global: &GlobalScope, canvas: &HTMLCanvasElement) -> DomRoot<GPUCanvasContext> {
+        reflect_dom_object(
+            Box::new(GPUCanvasContext::new_inherited(canvas)),
+            global,
+            GPUCanvasContextBinding::Wrap,
+        )
+    }
+
+    pub fn mark_as_dirty(&self) {
+        self.canvas
+            .upcast::<Node>()
+            .dirty(NodeDamage::OtherNodeDamage);
+    }
+}
+
+pub trait LayoutGPUCanvasContextHelpers {
+    #[allow(unsafe_code)]
+    unsafe fn canvas_data_source(&self) -> HTMLCanvasDataSource;
+}
+
+impl LayoutGPUCanvasContextHelpers for LayoutDom<GPUCanvasContext> {
+    #[allow(unsafe_code)]
+    unsafe fn canvas_data_source(&self) -> HTMLCanvasDataSource {
+        match (*self.unsafe_get()).image_key.get() {
+            Some(image_key) => HTMLCanvasDataSource::WebGPU(image_key),
+            None => HTMLCanvasDataSource::Image(None),
+        }
+    }
+}
+
+impl GPUCanvasContextMethods for GPUCanvasContext {
+    // https://gpuweb.github.io/gpuweb/#dom-gpucanvascontext-configureswapchain
+    fn ConfigureSwapChain(&self, descriptor: &GPUSwapChainDescriptor) -> DomRoot<GPUSwapChain> {
+        if let Some(swap_chain) = self.swap_chain.take() {
+            swap_chain.destroy();
+        }
+
+        let device = &descriptor.device;
+        let size = self.canvas.get_size();
+        let (sender, receiver) = ipc::channel().unwrap();
+        device
+            .channel()
+            .0
+            .send(WebGPURequest::CreateSwapChain {
+                sender,
+                device_id: device.id().0,
+                queue_id: device.DefaultQueue().id().0,
+                width: size.width,
+                height: size.height,
+            })
+            .expect("Failed to create WebGPU swap chain");
+        let image_key = receiver.recv().unwrap();
+
+        let usage = wgt::TextureUsage::from_bits(descriptor.usage).unwrap_or_else(|| {
+            warn!("Invalid swap chain usage {:#x}", descriptor.usage);
+            wgt::TextureUsage::empty()
+        });
+        // The frames get copied out of the texture when they get presented.
+        let texture = device.create_texture(TextureDescriptor {
+            size: wgt::Extent3d {
+                width: size.width,
+                height: size.height,
+                depth: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgt::TextureDimension::D2,
+            format: convert_texture_format(descriptor.format),
+            usage: usage | wgt::TextureUsage::COPY_SRC,
+        });
+
+        let swap_chain =
+            GPUSwapChain::new(&self.global(), device.channel(), self, &texture, image_key);
+        self.swap_chain.set(Some(&swap_chain));
+        self.image_key.set(Some(image_key));
+        self.mark_as_dirty();
+        swap_chain
+    }
+
+    // https://gpuweb.github.io/gpuweb/#dom-gpucanvascontext-getswapchainpreferredformat
+    #[allow(unsafe_code)]
+    fn GetSwapChainPreferredFormat(&self, _device: &GPUDevice) -> Rc<Promise> {
+        let promise = unsafe { Promise::new_in_current_compartment(&self.global()) };
+        // The frames are presented as BGRA8 images.
+        promise.resolve_native(&GPUTextureFormat::Bgra8unorm);
+        promise
+    }
+}
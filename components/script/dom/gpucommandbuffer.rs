@@ -0,0 +1,61 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::GPUCommandBufferBinding;
+use crate::dom::bindings::reflector::{reflect_dom_object, Reflector};
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::gpubuffer::GPUBuffer;
+use dom_struct::dom_struct;
+use std::cell::Cell;
+use webgpu::WebGPUCommandBuffer;
+
+#[dom_struct]
+pub struct GPUCommandBuffer {
+    reflector_: Reflector,
+    command_buffer: WebGPUCommandBuffer,
+    /// The buffers the commands use, which have to be unmapped when the
+    /// command buffer gets submitted.
+    buffers: Vec<Dom<GPUBuffer>>,
+    valid: Cell<bool>,
+}
+
+impl GPUCommandBuffer {
+    fn new_inherited(
+        command_buffer: WebGPUCommandBuffer,
+        buffers: &[DomRoot<GPUBuffer>],
+    ) -> GPUCommandBuffer {
+        GPUCommandBuffer {
+            reflector_: Reflector::new(),
+            command_buffer,
+            buffers: buffers.iter().map(|b| Dom::from_ref(&**b)).collect(),
+            valid: Cell::new(true),
+        }
+    }
+
+    pub fn new(
+        global: &GlobalScope,
+        command_buffer: WebGPUCommandBuffer,
+        buffers: &[DomRoot<GPUBuffer>],
+    ) -> DomRoot<GPUCommandBuffer> {
+        reflect_dom_object(
+            Box::new(GPUCommandBuffer::new_inherited(command_buffer, buffers)),
+            global,
+            GPUCommandBufferBinding::Wrap,
+        )
+    }
+
+    pub fn id(&self) -> WebGPUCommandBuffer {
+        self.command_buffer
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.valid.get() && self.buffers.iter().all(|buffer| buffer.is_unmapped())
+    }
+
+    /// Marks the command buffer as submitted.
+    pub fn invalidate(&self) {
+        self.valid.set(false);
+    }
+}
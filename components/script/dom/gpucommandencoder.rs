@@ -0,0 +1,288 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::GPUCommandEncoderBinding::{
+    self, GPUBufferCopyView, GPUColorDict, GPUCommandBufferDescriptor, GPUCommandEncoderMethods,
+    GPUComputePassDescriptor, GPURenderPassDescriptor, GPUStoreOp, GPUTextureCopyView,
+};
+use crate::dom::bindings::codegen::UnionTypes::GPULoadOpOrDoubleSequenceOrGPUColorDict as GPUColorLoad;
+use crate::dom::bindings::codegen::UnionTypes::GPULoadOpOrFloat;
+use crate::dom::bindings::codegen::UnionTypes::GPULoadOpOrGPUStencilValue;
+use crate::dom::bindings::codegen::UnionTypes::RangeEnforcedUnsignedLongSequenceOrGPUExtent3DDict as GPUExtent3D;
+use crate::dom::bindings::codegen::UnionTypes::RangeEnforcedUnsignedLongSequenceOrGPUOrigin3DDict as GPUOrigin3D;
+use crate::dom::bindings::num::Finite;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::gpubuffer::GPUBuffer;
+use crate::dom::gpucommandbuffer::GPUCommandBuffer;
+use crate::dom::gpucomputepassencoder::GPUComputePassEncoder;
+use crate::dom::gpudevice::convert_texture_size;
+use crate::dom::gpurenderpassencoder::GPURenderPassEncoder;
+use dom_struct::dom_struct;
+use ipc_channel::ipc;
+use webgpu::{wgt, BufferCopyView, RenderPassColorAttachment, RenderPassDepthStencilAttachment};
+use webgpu::{RenderPassDescriptor, TextureCopyView, WebGPU, WebGPUCommandEncoder, WebGPURequest};
+
+#[dom_struct]
+pub struct GPUCommandEncoder {
+    reflector_: Reflector,
+    #[ignore_malloc_size_of = "channels are hard"]
+    channel: WebGPU,
+    encoder: WebGPUCommandEncoder,
+    /// The buffers used by the recorded commands.
+    buffers: DomRefCell<Vec<Dom<GPUBuffer>>>,
+}
+
+impl GPUCommandEncoder {
+    fn new_inherited(channel: WebGPU, encoder: WebGPUCommandEncoder) -> GPUCommandEncoder {
+        GPUCommandEncoder {
+            reflector_: Reflector::new(),
+            channel,
+            encoder,
+            buffers: DomRefCell::new(Vec::new()),
+        }
+    }
+
+    pub fn new(
+        global: &GlobalScope,
+        channel: WebGPU,
+        encoder: WebGPUCommandEncoder,
+    ) -> DomRoot<GPUCommandEncoder> {
+        reflect_dom_object(
+            Box::new(GPUCommandEncoder::new_inherited(channel, encoder)),
+            global,
+            GPUCommandEncoderBinding::Wrap,
+        )
+    }
+
+    pub fn id(&self) -> WebGPUCommandEncoder {
+        self.encoder
+    }
+
+    pub fn channel(&self) -> WebGPU {
+        self.channel.clone()
+    }
+
+    /// Records that `buffer` is used by the commands of this encoder.
+    pub fn use_buffer(&self, buffer: &GPUBuffer) {
+        let mut buffers = self.buffers.borrow_mut();
+        if !buffers.iter().any(|b| *b == buffer) {
+            buffers.push(Dom::from_ref(buffer));
+        }
+    }
+
+    fn send(&self, request: WebGPURequest) {
+        if let Err(e) = self.channel.0.send(request) {
+            warn!(
+                "Failed to send a command of encoder {:?} ({})",
+                self.encoder, e
+            );
+        }
+    }
+}
+
+impl GPUCommandEncoderMethods for GPUCommandEncoder {
+    // https://gpuweb.github.io/gpuweb/#dom-gpucommandencoder-beginrenderpass
+    fn BeginRenderPass(
+        &self,
+        descriptor: &GPURenderPassDescriptor,
+    ) -> DomRoot<GPURenderPassEncoder> {
+        let color_attachments = descriptor
+            .colorAttachments
+            .iter()
+            .map(|color| {
+                let (load_op, clear_color) = match color.loadValue {
+                    GPUColorLoad::GPULoadOp(_) => (wgt::LoadOp::Load, wgt::Color::TRANSPARENT),
+                    GPUColorLoad::DoubleSequence(ref sequence) => {
+                        (wgt::LoadOp::Clear, convert_color_sequence(sequence))
+                    },
+                    GPUColorLoad::GPUColorDict(ref dict) => {
+                        (wgt::LoadOp::Clear, convert_color_dict(dict))
+                    },
+                };
+                RenderPassColorAttachment {
+                    attachment: color.attachment.id().0,
+                    resolve_target: color.resolveTarget.as_ref().map(|target| target.id().0),
+                    load_op,
+                    store_op: convert_store_op(color.storeOp),
+                    clear_color,
+                }
+            })
+            .collect();
+        let depth_stencil_attachment =
+            descriptor
+                .depthStencilAttachment
+                .as_ref()
+                .map(|depth_stencil| {
+                    let (depth_load_op, clear_depth) = match depth_stencil.depthLoadValue {
+                        GPULoadOpOrFloat::GPULoadOp(_) => (wgt::LoadOp::Load, 0.),
+                        GPULoadOpOrFloat::Float(depth) => (wgt::LoadOp::Clear, *depth),
+                    };
+                    let (stencil_load_op, clear_stencil) = match depth_stencil.stencilLoadValue {
+                        GPULoadOpOrGPUStencilValue::GPULoadOp(_) => (wgt::LoadOp::Load, 0),
+                        GPULoadOpOrGPUStencilValue::RangeEnforcedUnsignedLong(stencil) => {
+                            (wgt::LoadOp::Clear, stencil)
+                        },
+                    };
+                    RenderPassDepthStencilAttachment {
+                        attachment: depth_stencil.attachment.id().0,
+                        depth_load_op,
+                        depth_store_op: convert_store_op(depth_stencil.depthStoreOp),
+                        clear_depth,
+                        stencil_load_op,
+                        stencil_store_op: convert_store_op(depth_stencil.stencilStoreOp),
+                        clear_stencil,
+                    }
+                });
+        GPURenderPassEncoder::new(
+            &self.global(),
+            self,
+            RenderPassDescriptor {
+                color_attachments,
+                depth_stencil_attachment,
+            },
+        )
+    }
+
+    // https://gpuweb.github.io/gpuweb/#dom-gpucommandencoder-begincomputepass
+    fn BeginComputePass(
+        &self,
+        _descriptor: &GPUComputePassDescriptor,
+    ) -> DomRoot<GPUComputePassEncoder> {
+        GPUComputePassEncoder::new(&self.global(), self)
+    }
+
+    // https://gpuweb.github.io/gpuweb/#dom-gpucommandencoder-copybuffertobuffer
+    fn CopyBufferToBuffer(
+        &self,
+        source: &GPUBuffer,
+        source_offset: u64,
+        destination: &GPUBuffer,
+        destination_offset: u64,
+        size: u64,
+    ) {
+        self.use_buffer(source);
+        self.use_buffer(destination);
+        self.send(WebGPURequest::CopyBufferToBuffer {
+            command_encoder_id: self.encoder.0,
+            source_id: source.id().0,
+            source_offset,
+            destination_id: destination.id().0,
+            destination_offset,
+            size,
+        });
+    }
+
+    // https://gpuweb.github.io/gpuweb/#dom-gpucommandencoder-copybuffertotexture
+    fn CopyBufferToTexture(
+        &self,
+        source: &GPUBufferCopyView,
+        destination: &GPUTextureCopyView,
+        copy_size: GPUExtent3D,
+    ) {
+        self.use_buffer(&source.buffer);
+        self.send(WebGPURequest::CopyBufferToTexture {
+            command_encoder_id: self.encoder.0,
+            source: convert_buffer_copy_view(source),
+            destination: convert_texture_copy_view(destination),
+            copy_size: convert_texture_size(&copy_size),
+        });
+    }
+
+    // https://gpuweb.github.io/gpuweb/#dom-gpucommandencoder-copytexturetobuffer
+    fn CopyTextureToBuffer(
+        &self,
+        source: &GPUTextureCopyView,
+        destination: &GPUBufferCopyView,
+        copy_size: GPUExtent3D,
+    ) {
+        self.use_buffer(&destination.buffer);
+        self.send(WebGPURequest::CopyTextureToBuffer {
+            command_encoder_id: self.encoder.0,
+            source: convert_texture_copy_view(source),
+            destination: convert_buffer_copy_view(destination),
+            copy_size: convert_texture_size(&copy_size),
+        });
+    }
+
+    // https://gpuweb.github.io/gpuweb/#dom-gpucommandencoder-finish
+    fn Finish(&self, _descriptor: &GPUCommandBufferDescriptor) -> DomRoot<GPUCommandBuffer> {
+        let (sender, receiver) = ipc::channel().unwrap();
+        self.channel
+            .0
+            .send(WebGPURequest::CommandEncoderFinish {
+                sender,
+                command_encoder_id: self.encoder.0,
+            })
+            .expect("Failed to finish WebGPU command encoder");
+        let command_buffer = receiver.recv().unwrap();
+        let buffers: Vec<_> = self
+            .buffers
+            .borrow_mut()
+            .drain(..)
+            .map(|buffer| DomRoot::from_ref(&*buffer))
+            .collect();
+        GPUCommandBuffer::new(&self.global(), command_buffer, &buffers)
+    }
+}
+
+/// Converts a `GPUColor` sequence, the missing components are 0.
+pub fn convert_color_sequence(sequence: &[Finite<f64>]) -> wgt::Color {
+    let component = |index: usize| sequence.get(index).map_or(0., |value| **value);
+    wgt::Color {
+        r: component(0),
+        g: component(1),
+        b: component(2),
+        a: component(3),
+    }
+}
+
+pub fn convert_color_dict(dict: &GPUColorDict) -> wgt::Color {
+    wgt::Color {
+        r: *dict.r,
+        g: *dict.g,
+        b: *dict.b,
+        a: *dict.a,
+    }
+}
+
+fn convert_store_op(store_op: GPUStoreOp) -> wgt::StoreOp {
+    match store_op {
+        GPUStoreOp::Store => wgt::StoreOp::Store,
+        GPUStoreOp::Clear => wgt::StoreOp::Clear,
+    }
+}
+
+fn convert_buffer_copy_view(view: &GPUBufferCopyView) -> BufferCopyView {
+    BufferCopyView {
+        buffer: view.buffer.id().0,
+        offset: view.offset,
+        bytes_per_row: view.bytesPerRow,
+        rows_per_image: view.rowsPerImage,
+    }
+}
+
+/// Converts a `GPUTextureCopyView`, the missing coordinates of the origin
+/// are 0.
+fn convert_texture_copy_view(view: &GPUTextureCopyView) -> TextureCopyView {
+    let origin = match view.origin {
+        GPUOrigin3D::RangeEnforcedUnsignedLongSequence(ref sequence) => wgt::Origin3d {
+            x: sequence.get(0).cloned().unwrap_or(0),
+            y: sequence.get(1).cloned().unwrap_or(0),
+            z: sequence.get(2).cloned().unwrap_or(0),
+        },
+        GPUOrigin3D::GPUOrigin3DDict(ref dict) => wgt::Origin3d {
+            x: dict.x,
+            y: dict.y,
+            z: dict.z,
+        },
+    };
+    TextureCopyView {
+        texture: view.texture.id().0,
+        mip_level: view.mipLevel,
+        origin,
+    }
+}
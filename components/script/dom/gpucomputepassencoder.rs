@@ -0,0 +1,101 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::GPUComputePassEncoderBinding::{
+    self, GPUComputePassEncoderMethods,
+};
+use crate::dom::bindings::reflector::{reflect_dom_object, Reflector};
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::gpubindgroup::GPUBindGroup;
+use crate::dom::gpucommandencoder::GPUCommandEncoder;
+use crate::dom::gpucomputepipeline::GPUComputePipeline;
+use dom_struct::dom_struct;
+use std::cell::Cell;
+use webgpu::{ComputePassCommand, WebGPURequest};
+
+#[dom_struct]
+pub struct GPUComputePassEncoder {
+    reflector_: Reflector,
+    encoder: Dom<GPUCommandEncoder>,
+    /// The commands recorded so far, sent to the WebGPU thread when the
+    /// pass ends.
+    #[ignore_malloc_size_of = "Defined in webgpu"]
+    commands: DomRefCell<Vec<ComputePassCommand>>,
+    ended: Cell<bool>,
+}
+
+impl GPUComputePassEncoder {
+    fn new_inherited(encoder: &GPUCommandEncoder) -> GPUComputePassEncoder {
+        GPUComputePassEncoder {
+            reflector_: Reflector::new(),
+            encoder: Dom::from_ref(encoder),
+            commands: DomRefCell::new(Vec::new()),
+            ended: Cell::new(false),
+        }
+    }
+
+    pub fn new(
+        global: &GlobalScope,
+        encoder: &GPUCommandEncoder,
+    ) -> DomRoot<GPUComputePassEncoder> {
+        reflect_dom_object(
+            Box::new(GPUComputePassEncoder::new_inherited(encoder)),
+            global,
+            GPUComputePassEncoderBinding::Wrap,
+        )
+    }
+
+    fn record(&self, command: ComputePassCommand) {
+        if self.ended.get() {
+            warn!("Recording a command in an ended compute pass");
+            return;
+        }
+        self.commands.borrow_mut().push(command);
+    }
+}
+
+impl GPUComputePassEncoderMethods for GPUComputePassEncoder {
+    // https://gpuweb.github.io/gpuweb/#dom-gpucomputepassencoder-setpipeline
+    fn SetPipeline(&self, pipeline: &GPUComputePipeline) {
+        self.record(ComputePassCommand::SetPipeline(pipeline.id().0));
+    }
+
+    // https://gpuweb.github.io/gpuweb/#dom-gpucomputepassencoder-dispatch
+    fn Dispatch(&self, x: u32, y: u32, z: u32) {
+        self.record(ComputePassCommand::Dispatch(x, y, z));
+    }
+
+    // https://gpuweb.github.io/gpuweb/#dom-gpucomputepassencoder-endpass
+    fn EndPass(&self) {
+        if self.ended.replace(true) {
+            return;
+        }
+        let commands = self.commands.borrow_mut().drain(..).collect();
+        if let Err(e) = self
+            .encoder
+            .channel()
+            .0
+            .send(WebGPURequest::RunComputePass {
+                command_encoder_id: self.encoder.id().0,
+                commands,
+            })
+        {
+            warn!("Failed to send the compute pass ({})", e);
+        }
+    }
+
+    // https://gpuweb.github.io/gpuweb/#dom-gpuprogrammablepassencoder-setbindgroup
+    fn SetBindGroup(&self, index: u32, bind_group: &GPUBindGroup, dynamic_offsets: Vec<u32>) {
+        for buffer in bind_group.buffers() {
+            self.encoder.use_buffer(&buffer);
+        }
+        self.record(ComputePassCommand::SetBindGroup {
+            index,
+            bind_group_id: bind_group.id().0,
+            offsets: dynamic_offsets,
+        });
+    }
+}
@@ -0,0 +1,40 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::GPUComputePipelineBinding;
+use crate::dom::bindings::reflector::{reflect_dom_object, Reflector};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::globalscope::GlobalScope;
+use dom_struct::dom_struct;
+use webgpu::WebGPUComputePipeline;
+
+#[dom_struct]
+pub struct GPUComputePipeline {
+    reflector_: Reflector,
+    compute_pipeline: WebGPUComputePipeline,
+}
+
+impl GPUComputePipeline {
+    fn new_inherited(compute_pipeline: WebGPUComputePipeline) -> GPUComputePipeline {
+        GPUComputePipeline {
+            reflector_: Reflector::new(),
+            compute_pipeline,
+        }
+    }
+
+    pub fn new(
+        global: &GlobalScope,
+        compute_pipeline: WebGPUComputePipeline,
+    ) -> DomRoot<GPUComputePipeline> {
+        reflect_dom_object(
+            Box::new(GPUComputePipeline::new_inherited(compute_pipeline)),
+            global,
+            GPUComputePipelineBinding::Wrap,
+        )
+    }
+
+    pub fn id(&self) -> WebGPUComputePipeline {
+        self.compute_pipeline
+    }
+}
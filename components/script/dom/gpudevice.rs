@@ -0,0 +1,721 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::GPUAdapterBinding::GPULimits;
+use crate::dom::bindings::codegen::Bindings::GPUBindGroupBinding::GPUBindGroupDescriptor;
+use crate::dom::bindings::codegen::Bindings::GPUBindGroupLayoutBinding::{
+    GPUBindGroupLayoutDescriptor, GPUBindingType, GPUTextureComponentType,
+};
+use crate::dom::bindings::codegen::Bindings::GPUBufferBinding::GPUBufferDescriptor;
+use crate::dom::bindings::codegen::Bindings::GPUCommandEncoderBinding::GPUCommandEncoderDescriptor;
+use crate::dom::bindings::codegen::Bindings::GPUComputePipelineBinding::{
+    GPUComputePipelineDescriptor, GPUProgrammableStageDescriptor,
+};
+use crate::dom::bindings::codegen::Bindings::GPUDeviceBinding::{self, GPUDeviceMethods};
+use crate::dom::bindings::codegen::Bindings::GPUPipelineLayoutBinding::GPUPipelineLayoutDescriptor;
+use crate::dom::bindings::codegen::Bindings::GPURenderPipelineBinding::{
+    GPUBlendDescriptor, GPUBlendFactor, GPUBlendOperation, GPUCullMode, GPUFrontFace,
+    GPUIndexFormat, GPUInputStepMode, GPUPrimitiveTopology, GPURenderPipelineDescriptor,
+    GPUStencilOperation, GPUStencilStateFaceDescriptor, GPUVertexFormat,
+};
+use crate::dom::bindings::codegen::Bindings::GPUSamplerBinding::{
+    GPUAddressMode, GPUCompareFunction, GPUFilterMode, GPUSamplerDescriptor,
+};
+use crate::dom::bindings::codegen::Bindings::GPUShaderModuleBinding::GPUShaderModuleDescriptor;
+use crate::dom::bindings::codegen::Bindings::GPUTextureBinding::{
+    GPUTextureDescriptor, GPUTextureDimension, GPUTextureFormat,
+};
+use crate::dom::bindings::codegen::Bindings::GPUTextureViewBinding::GPUTextureViewDimension;
+use crate::dom::bindings::codegen::UnionTypes::GPUSamplerOrGPUTextureViewOrGPUBufferBindings as GPUBindingResource;
+use crate::dom::bindings::codegen::UnionTypes::RangeEnforcedUnsignedLongSequenceOrGPUExtent3DDict as GPUExtent3D;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject};
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::bindings::trace::RootedTraceableBox;
+use crate::dom::eventtarget::EventTarget;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::gpuadapter::GPUAdapter;
+use crate::dom::gpubindgroup::GPUBindGroup;
+use crate::dom::gpubindgrouplayout::GPUBindGroupLayout;
+use crate::dom::gpubuffer::GPUBuffer;
+use crate::dom::gpucommandencoder::GPUCommandEncoder;
+use crate::dom::gpucomputepipeline::GPUComputePipeline;
+use crate::dom::gpupipelinelayout::GPUPipelineLayout;
+use crate::dom::gpuqueue::GPUQueue;
+use crate::dom::gpurenderpipeline::GPURenderPipeline;
+use crate::dom::gpusampler::GPUSampler;
+use crate::dom::gpushadermodule::GPUShaderModule;
+use crate::dom::gputexture::GPUTexture;
+use dom_struct::dom_struct;
+use ipc_channel::ipc;
+use js::conversions::ToJSValConvertible;
+use js::jsapi::{Heap, JSAutoCompartment, JSContext, JSObject};
+use js::jsval::UndefinedValue;
+use std::ptr::NonNull;
+use webgpu::{wgt, WebGPU, WebGPUDevice, WebGPUQueue, WebGPURequest};
+use webgpu::{BindGroupEntry, BindGroupLayoutEntry, BindingResource, BindingType};
+use webgpu::{BufferDescriptor, ProgrammableStage, RenderPipelineDescriptor, SamplerDescriptor};
+use webgpu::{TextureDescriptor, VertexBufferLayoutDescriptor, VertexStateDescriptor};
+
+#[dom_struct]
+pub struct GPUDevice {
+    eventtarget: EventTarget,
+    #[ignore_malloc_size_of = "channels are hard"]
+    channel: WebGPU,
+    adapter: Dom<GPUAdapter>,
+    #[ignore_malloc_size_of = "mozjs"]
+    limits: Heap<*mut JSObject>,
+    device: WebGPUDevice,
+    default_queue: Dom<GPUQueue>,
+}
+
+impl GPUDevice {
+    fn new_inherited(
+        channel: WebGPU,
+        adapter: &GPUAdapter,
+        device: WebGPUDevice,
+        queue: &GPUQueue,
+    ) -> GPUDevice {
+        GPUDevice {
+            eventtarget: EventTarget::new_inherited(),
+            channel,
+            adapter: Dom::from_ref(adapter),
+            limits: Heap::default(),
+            device,
+            default_queue: Dom::from_ref(queue),
+        }
+    }
+
+    #[allow(unsafe_code)]
+    pub fn new(
+        global: &GlobalScope,
+        channel: WebGPU,
+        adapter: &GPUAdapter,
+        limits: wgt::Limits,
+        device: WebGPUDevice,
+        queue: WebGPUQueue,
+    ) -> DomRoot<GPUDevice> {
+        let queue = GPUQueue::new(global, channel.clone(), queue);
+        let device = reflect_dom_object(
+            Box::new(GPUDevice::new_inherited(channel, adapter, device, &queue)),
+            global,
+            GPUDeviceBinding::Wrap,
+        );
+
+        let cx = global.get_cx();
+        let _ac = JSAutoCompartment::new(cx, global.reflector().get_jsobject().get());
+        rooted!(in(cx) let mut limits_object = UndefinedValue());
+        let limits = GPULimits {
+            maxBindGroups: limits.max_bind_groups,
+        };
+        unsafe {
+            limits.to_jsval(cx, limits_object.handle_mut());
+        }
+        device.limits.set(limits_object.to_object());
+        device
+    }
+
+    pub fn id(&self) -> WebGPUDevice {
+        self.device
+    }
+
+    pub fn channel(&self) -> WebGPU {
+        self.channel.clone()
+    }
+
+    /// Creates a texture with an already converted descriptor, which is
+    /// also how the swap chains of this device get their texture.
+    pub fn create_texture(&self, descriptor: TextureDescriptor) -> DomRoot<GPUTexture> {
+        let (sender, receiver) = ipc::channel().unwrap();
+        let size = descriptor.size;
+        let mip_level_count = descriptor.mip_level_count;
+        let dimension = descriptor.dimension;
+        let format = descriptor.format;
+        self.channel
+            .0
+            .send(WebGPURequest::CreateTexture {
+                sender,
+                device_id: self.device.0,
+                descriptor,
+            })
+            .expect("Failed to create WebGPU texture");
+        let texture = receiver.recv().unwrap();
+        GPUTexture::new(
+            &self.global(),
+            self.channel.clone(),
+            texture,
+            size,
+            mip_level_count,
+            dimension,
+            format,
+        )
+    }
+
+    fn convert_programmable_stage(stage: &GPUProgrammableStageDescriptor) -> ProgrammableStage {
+        ProgrammableStage {
+            module: stage.module.id().0,
+            entry_point: stage.entryPoint.to_string(),
+        }
+    }
+}
+
+impl GPUDeviceMethods for GPUDevice {
+    // https://gpuweb.github.io/gpuweb/#dom-gpudevice-adapter
+    fn Adapter(&self) -> DomRoot<GPUAdapter> {
+        DomRoot::from_ref(&self.adapter)
+    }
+
+    // https://gpuweb.github.io/gpuweb/#dom-gpudevice-limits
+    #[allow(unsafe_code)]
+    unsafe fn Limits(&self, _cx: *mut JSContext) -> NonNull<JSObject> {
+        NonNull::new(self.limits.get()).unwrap()
+    }
+
+    // https://gpuweb.github.io/gpuweb/#dom-gpudevice-defaultqueue
+    fn DefaultQueue(&self) -> DomRoot<GPUQueue> {
+        DomRoot::from_ref(&self.default_queue)
+    }
+
+    // https://gpuweb.github.io/gpuweb/#dom-gpudevice-createbuffer
+    fn CreateBuffer(&self, descriptor: &GPUBufferDescriptor) -> DomRoot<GPUBuffer> {
+        let usage = wgt::BufferUsage::from_bits(descriptor.usage).unwrap_or_else(|| {
+            warn!("Invalid buffer usage {:#x}", descriptor.usage);
+            wgt::BufferUsage::empty()
+        });
+        let (sender, receiver) = ipc::channel().unwrap();
+        self.channel
+            .0
+            .send(WebGPURequest::CreateBuffer {
+                sender,
+                device_id: self.device.0,
+                descriptor: BufferDescriptor {
+                    size: descriptor.size,
+                    usage,
+                    mapped_at_creation: descriptor.mappedAtCreation,
+                },
+            })
+            .expect("Failed to create WebGPU buffer");
+        let buffer = receiver.recv().unwrap();
+        GPUBuffer::new(
+            &self.global(),
+            self.channel.clone(),
+            buffer,
+            self.device,
+            descriptor.size,
+            usage,
+            descriptor.mappedAtCreation,
+        )
+    }
+
+    // https://gpuweb.github.io/gpuweb/#dom-gpudevice-createtexture
+    fn CreateTexture(&self, descriptor: &GPUTextureDescriptor) -> DomRoot<GPUTexture> {
+        let usage = wgt::TextureUsage::from_bits(descriptor.usage).unwrap_or_else(|| {
+            warn!("Invalid texture usage {:#x}", descriptor.usage);
+            wgt::TextureUsage::empty()
+        });
+        self.create_texture(TextureDescriptor {
+            size: convert_texture_size(&descriptor.size),
+            mip_level_count: descriptor.mipLevelCount,
+            sample_count: descriptor.sampleCount,
+            dimension: match descriptor.dimension {
+                GPUTextureDimension::_1d => wgt::TextureDimension::D1,
+                GPUTextureDimension::_2d => wgt::TextureDimension::D2,
+                GPUTextureDimension::_3d => wgt::TextureDimension::D3,
+            },
+            format: convert_texture_format(descriptor.format),
+            usage,
+        })
+    }
+
+    // https://gpuweb.github.io/gpuweb/#dom-gpudevice-createsampler
+    fn CreateSampler(&self, descriptor: &GPUSamplerDescriptor) -> DomRoot<GPUSampler> {
+        let (sender, receiver) = ipc::channel().unwrap();
+        self.channel
+            .0
+            .send(WebGPURequest::CreateSampler {
+                sender,
+                device_id: self.device.0,
+                descriptor: SamplerDescriptor {
+                    address_mode_u: convert_address_mode(descriptor.addressModeU),
+                    address_mode_v: convert_address_mode(descriptor.addressModeV),
+                    address_mode_w: convert_address_mode(descriptor.addressModeW),
+                    mag_filter: convert_filter_mode(descriptor.magFilter),
+                    min_filter: convert_filter_mode(descriptor.minFilter),
+                    mipmap_filter: convert_filter_mode(descriptor.mipmapFilter),
+                    lod_min_clamp: *descriptor.lodMinClamp,
+                    lod_max_clamp: *descriptor.lodMaxClamp,
+                    compare: descriptor.compare.map(convert_compare_function),
+                },
+            })
+            .expect("Failed to create WebGPU sampler");
+        let sampler = receiver.recv().unwrap();
+        GPUSampler::new(&self.global(), sampler, descriptor.compare.is_some())
+    }
+
+    // https://gpuweb.github.io/gpuweb/#dom-gpudevice-createbindgrouplayout
+    fn CreateBindGroupLayout(
+        &self,
+        descriptor: &GPUBindGroupLayoutDescriptor,
+    ) -> DomRoot<GPUBindGroupLayout> {
+        let entries = descriptor
+            .entries
+            .iter()
+            .map(|entry| BindGroupLayoutEntry {
+                binding: entry.binding,
+                visibility: wgt::ShaderStage::from_bits(entry.visibility).unwrap_or_else(|| {
+                    warn!("Invalid shader stages {:#x}", entry.visibility);
+                    wgt::ShaderStage::NONE
+                }),
+                ty: match entry.type_ {
+                    GPUBindingType::Uniform_buffer => BindingType::UniformBuffer,
+                    GPUBindingType::Storage_buffer => BindingType::StorageBuffer,
+                    GPUBindingType::Readonly_storage_buffer => BindingType::ReadonlyStorageBuffer,
+                    GPUBindingType::Sampler => BindingType::Sampler,
+                    GPUBindingType::Comparison_sampler => BindingType::ComparisonSampler,
+                    GPUBindingType::Sampled_texture => BindingType::SampledTexture,
+                    GPUBindingType::Readonly_storage_texture => BindingType::ReadonlyStorageTexture,
+                    GPUBindingType::Writeonly_storage_texture => {
+                        BindingType::WriteonlyStorageTexture
+                    },
+                },
+                has_dynamic_offset: entry.hasDynamicOffset,
+                multisampled: entry.multisampled,
+                view_dimension: convert_texture_view_dimension(entry.viewDimension),
+                texture_component_type: match entry.textureComponentType {
+                    GPUTextureComponentType::Float => wgt::TextureComponentType::Float,
+                    GPUTextureComponentType::Sint => wgt::TextureComponentType::Sint,
+                    GPUTextureComponentType::Uint => wgt::TextureComponentType::Uint,
+                },
+                // Only storage textures have a format, wgpu ignores it for
+                // the other bindings.
+                storage_texture_format: entry
+                    .storageTextureFormat
+                    .map_or(wgt::TextureFormat::Rgba8Unorm, convert_texture_format),
+            })
+            .collect();
+        let (sender, receiver) = ipc::channel().unwrap();
+        self.channel
+            .0
+            .send(WebGPURequest::CreateBindGroupLayout {
+                sender,
+                device_id: self.device.0,
+                entries,
+            })
+            .expect("Failed to create WebGPU bind group layout");
+        let bind_group_layout = receiver.recv().unwrap();
+        GPUBindGroupLayout::new(&self.global(), bind_group_layout)
+    }
+
+    // https://gpuweb.github.io/gpuweb/#dom-gpudevice-createpipelinelayout
+    fn CreatePipelineLayout(
+        &self,
+        descriptor: &GPUPipelineLayoutDescriptor,
+    ) -> DomRoot<GPUPipelineLayout> {
+        let bind_group_layouts = descriptor
+            .bindGroupLayouts
+            .iter()
+            .map(|layout| layout.id().0)
+            .collect();
+        let (sender, receiver) = ipc::channel().unwrap();
+        self.channel
+            .0
+            .send(WebGPURequest::CreatePipelineLayout {
+                sender,
+                device_id: self.device.0,
+                bind_group_layouts,
+            })
+            .expect("Failed to create WebGPU pipeline layout");
+        let pipeline_layout = receiver.recv().unwrap();
+        GPUPipelineLayout::new(&self.global(), pipeline_layout)
+    }
+
+    // https://gpuweb.github.io/gpuweb/#dom-gpudevice-createbindgroup
+    fn CreateBindGroup(&self, descriptor: &GPUBindGroupDescriptor) -> DomRoot<GPUBindGroup> {
+        let mut buffers = vec![];
+        let entries = descriptor
+            .entries
+            .iter()
+            .map(|entry| BindGroupEntry {
+                binding: entry.binding,
+                resource: match entry.resource {
+                    GPUBindingResource::GPUSampler(ref sampler) => {
+                        BindingResource::Sampler(sampler.id().0)
+                    },
+                    GPUBindingResource::GPUTextureView(ref view) => {
+                        BindingResource::TextureView(view.id().0)
+                    },
+                    GPUBindingResource::GPUBufferBindings(ref bindings) => {
+                        buffers.push(DomRoot::from_ref(&*bindings.buffer));
+                        BindingResource::Buffer {
+                            buffer_id: bindings.buffer.id().0,
+                            offset: bindings.offset,
+                            size: bindings.size.unwrap_or_else(|| {
+                                bindings.buffer.size().saturating_sub(bindings.offset)
+                            }),
+                        }
+                    },
+                },
+            })
+            .collect();
+        let (sender, receiver) = ipc::channel().unwrap();
+        self.channel
+            .0
+            .send(WebGPURequest::CreateBindGroup {
+                sender,
+                device_id: self.device.0,
+                layout_id: descriptor.layout.id().0,
+                entries,
+            })
+            .expect("Failed to create WebGPU bind group");
+        let bind_group = receiver.recv().unwrap();
+        GPUBindGroup::new(&self.global(), bind_group, &buffers)
+    }
+
+    // https://gpuweb.github.io/gpuweb/#dom-gpudevice-createshadermodule
+    fn CreateShaderModule(
+        &self,
+        descriptor: RootedTraceableBox<GPUShaderModuleDescriptor>,
+    ) -> DomRoot<GPUShaderModule> {
+        let code = descriptor.code.to_vec();
+        let (sender, receiver) = ipc::channel().unwrap();
+        self.channel
+            .0
+            .send(WebGPURequest::CreateShaderModule {
+                sender,
+                device_id: self.device.0,
+                code,
+            })
+            .expect("Failed to create WebGPU shader module");
+        let shader_module = receiver.recv().unwrap();
+        GPUShaderModule::new(&self.global(), shader_module)
+    }
+
+    // https://gpuweb.github.io/gpuweb/#dom-gpudevice-createcomputepipeline
+    fn CreateComputePipeline(
+        &self,
+        descriptor: &GPUComputePipelineDescriptor,
+    ) -> DomRoot<GPUComputePipeline> {
+        let (sender, receiver) = ipc::channel().unwrap();
+        self.channel
+            .0
+            .send(WebGPURequest::CreateComputePipeline {
+                sender,
+                device_id: self.device.0,
+                layout_id: descriptor.parent.layout.id().0,
+                compute_stage: GPUDevice::convert_programmable_stage(&descriptor.computeStage),
+            })
+            .expect("Failed to create WebGPU compute pipeline");
+        let compute_pipeline = receiver.recv().unwrap();
+        GPUComputePipeline::new(&self.global(), compute_pipeline)
+    }
+
+    // https://gpuweb.github.io/gpuweb/#dom-gpudevice-createrenderpipeline
+    fn CreateRenderPipeline(
+        &self,
+        descriptor: &GPURenderPipelineDescriptor,
+    ) -> DomRoot<GPURenderPipeline> {
+        let rasterization_state = &descriptor.rasterizationState;
+        let vertex_state = &descriptor.vertexState;
+        let descriptor = RenderPipelineDescriptor {
+            layout: descriptor.parent.layout.id().0,
+            vertex_stage: GPUDevice::convert_programmable_stage(&descriptor.vertexStage),
+            fragment_stage: descriptor
+                .fragmentStage
+                .as_ref()
+                .map(GPUDevice::convert_programmable_stage),
+            primitive_topology: match descriptor.primitiveTopology {
+                GPUPrimitiveTopology::Point_list => wgt::PrimitiveTopology::PointList,
+                GPUPrimitiveTopology::Line_list => wgt::PrimitiveTopology::LineList,
+                GPUPrimitiveTopology::Line_strip => wgt::PrimitiveTopology::LineStrip,
+                GPUPrimitiveTopology::Triangle_list => wgt::PrimitiveTopology::TriangleList,
+                GPUPrimitiveTopology::Triangle_strip => wgt::PrimitiveTopology::TriangleStrip,
+            },
+            rasterization_state: wgt::RasterizationStateDescriptor {
+                front_face: match rasterization_state.frontFace {
+                    GPUFrontFace::Ccw => wgt::FrontFace::Ccw,
+                    GPUFrontFace::Cw => wgt::FrontFace::Cw,
+                },
+                cull_mode: match rasterization_state.cullMode {
+                    GPUCullMode::None => wgt::CullMode::None,
+                    GPUCullMode::Front => wgt::CullMode::Front,
+                    GPUCullMode::Back => wgt::CullMode::Back,
+                },
+                depth_bias: rasterization_state.depthBias,
+                depth_bias_slope_scale: *rasterization_state.depthBiasSlopeScale,
+                depth_bias_clamp: *rasterization_state.depthBiasClamp,
+            },
+            color_states: descriptor
+                .colorStates
+                .iter()
+                .map(|state| wgt::ColorStateDescriptor {
+                    format: convert_texture_format(state.format),
+                    alpha_blend: convert_blend_descriptor(&state.alphaBlend),
+                    color_blend: convert_blend_descriptor(&state.colorBlend),
+                    write_mask: wgt::ColorWrite::from_bits(state.writeMask).unwrap_or_else(|| {
+                        warn!("Invalid color write mask {:#x}", state.writeMask);
+                        wgt::ColorWrite::empty()
+                    }),
+                })
+                .collect(),
+            depth_stencil_state: descriptor.depthStencilState.as_ref().map(|state| {
+                wgt::DepthStencilStateDescriptor {
+                    format: convert_texture_format(state.format),
+                    depth_write_enabled: state.depthWriteEnabled,
+                    depth_compare: convert_compare_function(state.depthCompare),
+                    stencil_front: convert_stencil_face(&state.stencilFront),
+                    stencil_back: convert_stencil_face(&state.stencilBack),
+                    stencil_read_mask: state.stencilReadMask,
+                    stencil_write_mask: state.stencilWriteMask,
+                }
+            }),
+            vertex_state: VertexStateDescriptor {
+                index_format: match vertex_state.indexFormat {
+                    GPUIndexFormat::Uint16 => wgt::IndexFormat::Uint16,
+                    GPUIndexFormat::Uint32 => wgt::IndexFormat::Uint32,
+                },
+                vertex_buffers: vertex_state
+                    .vertexBuffers
+                    .iter()
+                    .map(|buffer| VertexBufferLayoutDescriptor {
+                        array_stride: buffer.arrayStride,
+                        step_mode: match buffer.stepMode {
+                            GPUInputStepMode::Vertex => wgt::InputStepMode::Vertex,
+                            GPUInputStepMode::Instance => wgt::InputStepMode::Instance,
+                        },
+                        attributes: buffer
+                            .attributes
+                            .iter()
+                            .map(|attribute| wgt::VertexAttributeDescriptor {
+                                offset: attribute.offset,
+                                format: convert_vertex_format(attribute.format),
+                                shader_location: attribute.shaderLocation,
+                            })
+                            .collect(),
+                    })
+                    .collect(),
+            },
+            sample_count: descriptor.sampleCount,
+            sample_mask: descriptor.sampleMask,
+            alpha_to_coverage_enabled: descriptor.alphaToCoverageEnabled,
+        };
+        let (sender, receiver) = ipc::channel().unwrap();
+        self.channel
+            .0
+            .send(WebGPURequest::CreateRenderPipeline {
+                sender,
+                device_id: self.device.0,
+                descriptor,
+            })
+            .expect("Failed to create WebGPU render pipeline");
+        let render_pipeline = receiver.recv().unwrap();
+        GPURenderPipeline::new(&self.global(), render_pipeline)
+    }
+
+    // https://gpuweb.github.io/gpuweb/#dom-gpudevice-createcommandencoder
+    fn CreateCommandEncoder(
+        &self,
+        _descriptor: &GPUCommandEncoderDescriptor,
+    ) -> DomRoot<GPUCommandEncoder> {
+        let (sender, receiver) = ipc::channel().unwrap();
+        self.channel
+            .0
+            .send(WebGPURequest::CreateCommandEncoder {
+                sender,
+                device_id: self.device.0,
+            })
+            .expect("Failed to create WebGPU command encoder");
+        let encoder = receiver.recv().unwrap();
+        GPUCommandEncoder::new(&self.global(), self.channel.clone(), encoder)
+    }
+}
+
+pub fn convert_texture_format(format: GPUTextureFormat) -> wgt::TextureFormat {
+    match format {
+        GPUTextureFormat::R8unorm => wgt::TextureFormat::R8Unorm,
+        GPUTextureFormat::R8snorm => wgt::TextureFormat::R8Snorm,
+        GPUTextureFormat::R8uint => wgt::TextureFormat::R8Uint,
+        GPUTextureFormat::R8sint => wgt::TextureFormat::R8Sint,
+        GPUTextureFormat::R16uint => wgt::TextureFormat::R16Uint,
+        GPUTextureFormat::R16sint => wgt::TextureFormat::R16Sint,
+        GPUTextureFormat::R16float => wgt::TextureFormat::R16Float,
+        GPUTextureFormat::Rg8unorm => wgt::TextureFormat::Rg8Unorm,
+        GPUTextureFormat::Rg8snorm => wgt::TextureFormat::Rg8Snorm,
+        GPUTextureFormat::Rg8uint => wgt::TextureFormat::Rg8Uint,
+        GPUTextureFormat::Rg8sint => wgt::TextureFormat::Rg8Sint,
+        GPUTextureFormat::R32uint => wgt::TextureFormat::R32Uint,
+        GPUTextureFormat::R32sint => wgt::TextureFormat::R32Sint,
+        GPUTextureFormat::R32float => wgt::TextureFormat::R32Float,
+        GPUTextureFormat::Rg16uint => wgt::TextureFormat::Rg16Uint,
+        GPUTextureFormat::Rg16sint => wgt::TextureFormat::Rg16Sint,
+        GPUTextureFormat::Rg16float => wgt::TextureFormat::Rg16Float,
+        GPUTextureFormat::Rgba8unorm => wgt::TextureFormat::Rgba8Unorm,
+        GPUTextureFormat::Rgba8unorm_srgb => wgt::TextureFormat::Rgba8UnormSrgb,
+        GPUTextureFormat::Rgba8snorm => wgt::TextureFormat::Rgba8Snorm,
+        GPUTextureFormat::Rgba8uint => wgt::TextureFormat::Rgba8Uint,
+        GPUTextureFormat::Rgba8sint => wgt::TextureFormat::Rgba8Sint,
+        GPUTextureFormat::Bgra8unorm => wgt::TextureFormat::Bgra8Unorm,
+        GPUTextureFormat::Bgra8unorm_srgb => wgt::TextureFormat::Bgra8UnormSrgb,
+        GPUTextureFormat::Rgb10a2unorm => wgt::TextureFormat::Rgb10a2Unorm,
+        GPUTextureFormat::Rg11b10float => wgt::TextureFormat::Rg11b10Float,
+        GPUTextureFormat::Rg32uint => wgt::TextureFormat::Rg32Uint,
+        GPUTextureFormat::Rg32sint => wgt::TextureFormat::Rg32Sint,
+        GPUTextureFormat::Rg32float => wgt::TextureFormat::Rg32Float,
+        GPUTextureFormat::Rgba16uint => wgt::TextureFormat::Rgba16Uint,
+        GPUTextureFormat::Rgba16sint => wgt::TextureFormat::Rgba16Sint,
+        GPUTextureFormat::Rgba16float => wgt::TextureFormat::Rgba16Float,
+        GPUTextureFormat::Rgba32uint => wgt::TextureFormat::Rgba32Uint,
+        GPUTextureFormat::Rgba32sint => wgt::TextureFormat::Rgba32Sint,
+        GPUTextureFormat::Rgba32float => wgt::TextureFormat::Rgba32Float,
+        GPUTextureFormat::Depth32float => wgt::TextureFormat::Depth32Float,
+        GPUTextureFormat::Depth24plus => wgt::TextureFormat::Depth24Plus,
+        GPUTextureFormat::Depth24plus_stencil8 => wgt::TextureFormat::Depth24PlusStencil8,
+    }
+}
+
+pub fn convert_texture_view_dimension(
+    dimension: GPUTextureViewDimension,
+) -> wgt::TextureViewDimension {
+    match dimension {
+        GPUTextureViewDimension::_1d => wgt::TextureViewDimension::D1,
+        GPUTextureViewDimension::_2d => wgt::TextureViewDimension::D2,
+        GPUTextureViewDimension::_2d_array => wgt::TextureViewDimension::D2Array,
+        GPUTextureViewDimension::Cube => wgt::TextureViewDimension::Cube,
+        GPUTextureViewDimension::Cube_array => wgt::TextureViewDimension::CubeArray,
+        GPUTextureViewDimension::_3d => wgt::TextureViewDimension::D3,
+    }
+}
+
+/// Converts a `GPUExtent3D`, the missing dimensions of a sequence are 1.
+pub fn convert_texture_size(size: &GPUExtent3D) -> wgt::Extent3d {
+    match *size {
+        GPUExtent3D::RangeEnforcedUnsignedLongSequence(ref sequence) => wgt::Extent3d {
+            width: sequence.get(0).cloned().unwrap_or(1),
+            height: sequence.get(1).cloned().unwrap_or(1),
+            depth: sequence.get(2).cloned().unwrap_or(1),
+        },
+        GPUExtent3D::GPUExtent3DDict(ref dict) => wgt::Extent3d {
+            width: dict.width,
+            height: dict.height,
+            depth: dict.depth,
+        },
+    }
+}
+
+pub fn convert_compare_function(compare: GPUCompareFunction) -> wgt::CompareFunction {
+    match compare {
+        GPUCompareFunction::Never => wgt::CompareFunction::Never,
+        GPUCompareFunction::Less => wgt::CompareFunction::Less,
+        GPUCompareFunction::Equal => wgt::CompareFunction::Equal,
+        GPUCompareFunction::Less_equal => wgt::CompareFunction::LessEqual,
+        GPUCompareFunction::Greater => wgt::CompareFunction::Greater,
+        GPUCompareFunction::Not_equal => wgt::CompareFunction::NotEqual,
+        GPUCompareFunction::Greater_equal => wgt::CompareFunction::GreaterEqual,
+        GPUCompareFunction::Always => wgt::CompareFunction::Always,
+    }
+}
+
+fn convert_address_mode(address_mode: GPUAddressMode) -> wgt::AddressMode {
+    match address_mode {
+        GPUAddressMode::Clamp_to_edge => wgt::AddressMode::ClampToEdge,
+        GPUAddressMode::Repeat => wgt::AddressMode::Repeat,
+        GPUAddressMode::Mirror_repeat => wgt::AddressMode::MirrorRepeat,
+    }
+}
+
+fn convert_filter_mode(filter_mode: GPUFilterMode) -> wgt::FilterMode {
+    match filter_mode {
+        GPUFilterMode::Nearest => wgt::FilterMode::Nearest,
+        GPUFilterMode::Linear => wgt::FilterMode::Linear,
+    }
+}
+
+fn convert_blend_descriptor(descriptor: &GPUBlendDescriptor) -> wgt::BlendDescriptor {
+    wgt::BlendDescriptor {
+        src_factor: convert_blend_factor(descriptor.srcFactor),
+        dst_factor: convert_blend_factor(descriptor.dstFactor),
+        operation: match descriptor.operation {
+            GPUBlendOperation::Add => wgt::BlendOperation::Add,
+            GPUBlendOperation::Subtract => wgt::BlendOperation::Subtract,
+            GPUBlendOperation::Reverse_subtract => wgt::BlendOperation::ReverseSubtract,
+            GPUBlendOperation::Min => wgt::BlendOperation::Min,
+            GPUBlendOperation::Max => wgt::BlendOperation::Max,
+        },
+    }
+}
+
+fn convert_blend_factor(factor: GPUBlendFactor) -> wgt::BlendFactor {
+    match factor {
+        GPUBlendFactor::Zero => wgt::BlendFactor::Zero,
+        GPUBlendFactor::One => wgt::BlendFactor::One,
+        GPUBlendFactor::Src_color => wgt::BlendFactor::SrcColor,
+        GPUBlendFactor::One_minus_src_color => wgt::BlendFactor::OneMinusSrcColor,
+        GPUBlendFactor::Src_alpha => wgt::BlendFactor::SrcAlpha,
+        GPUBlendFactor::One_minus_src_alpha => wgt::BlendFactor::OneMinusSrcAlpha,
+        GPUBlendFactor::Dst_color => wgt::BlendFactor::DstColor,
+        GPUBlendFactor::One_minus_dst_color => wgt::BlendFactor::OneMinusDstColor,
+        GPUBlendFactor::Dst_alpha => wgt::BlendFactor::DstAlpha,
+        GPUBlendFactor::One_minus_dst_alpha => wgt::BlendFactor::OneMinusDstAlpha,
+        GPUBlendFactor::Src_alpha_saturated => wgt::BlendFactor::SrcAlphaSaturated,
+        GPUBlendFactor::Blend_color => wgt::BlendFactor::BlendColor,
+        GPUBlendFactor::One_minus_blend_color => wgt::BlendFactor::OneMinusBlendColor,
+    }
+}
+
+fn convert_stencil_face(
+    descriptor: &GPUStencilStateFaceDescriptor,
+) -> wgt::StencilStateFaceDescriptor {
+    wgt::StencilStateFaceDescriptor {
+        compare: convert_compare_function(descriptor.compare),
+        fail_op: convert_stencil_op(descriptor.failOp),
+        depth_fail_op: convert_stencil_op(descriptor.depthFailOp),
+        pass_op: convert_stencil_op(descriptor.passOp),
+    }
+}
+
+fn convert_stencil_op(operation: GPUStencilOperation) -> wgt::StencilOperation {
+    match operation {
+        GPUStencilOperation::Keep => wgt::StencilOperation::Keep,
+        GPUStencilOperation::Zero => wgt::StencilOperation::Zero,
+        GPUStencilOperation::Replace => wgt::StencilOperation::Replace,
+        GPUStencilOperation::Invert => wgt::StencilOperation::Invert,
+        GPUStencilOperation::Increment_clamp => wgt::StencilOperation::IncrementClamp,
+        GPUStencilOperation::Decrement_clamp => wgt::StencilOperation::DecrementClamp,
+        GPUStencilOperation::Increment_wrap => wgt::StencilOperation::IncrementWrap,
+        GPUStencilOperation::Decrement_wrap => wgt::StencilOperation::DecrementWrap,
+    }
+}
+
+fn convert_vertex_format(format: GPUVertexFormat) -> wgt::VertexFormat {
+    match format {
+        GPUVertexFormat::Uchar2 => wgt::VertexFormat::Uchar2,
+        GPUVertexFormat::Uchar4 => wgt::VertexFormat::Uchar4,
+        GPUVertexFormat::Char2 => wgt::VertexFormat::Char2,
+        GPUVertexFormat::Char4 => wgt::VertexFormat::Char4,
+        GPUVertexFormat::Uchar2norm => wgt::VertexFormat::Uchar2Norm,
+        GPUVertexFormat::Uchar4norm => wgt::VertexFormat::Uchar4Norm,
+        GPUVertexFormat::Char2norm => wgt::VertexFormat::Char2Norm,
+        GPUVertexFormat::Char4norm => wgt::VertexFormat::Char4Norm,
+        GPUVertexFormat::Ushort2 => wgt::VertexFormat::Ushort2,
+        GPUVertexFormat::Ushort4 => wgt::VertexFormat::Ushort4,
+        GPUVertexFormat::Short2 => wgt::VertexFormat::Short2,
+        GPUVertexFormat::Short4 => wgt::VertexFormat::Short4,
+        GPUVertexFormat::Ushort2norm => wgt::VertexFormat::Ushort2Norm,
+        GPUVertexFormat::Ushort4norm => wgt::VertexFormat::Ushort4Norm,
+        GPUVertexFormat::Short2norm => wgt::VertexFormat::Short2Norm,
+        GPUVertexFormat::Short4norm => wgt::VertexFormat::Short4Norm,
+        GPUVertexFormat::Half2 => wgt::VertexFormat::Half2,
+        GPUVertexFormat::Half4 => wgt::VertexFormat::Half4,
+        GPUVertexFormat::Float => wgt::VertexFormat::Float,
+        GPUVertexFormat::Float2 => wgt::VertexFormat::Float2,
+        GPUVertexFormat::Float3 => wgt::VertexFormat::Float3,
+        GPUVertexFormat::Float4 => wgt::VertexFormat::Float4,
+        GPUVertexFormat::Uint => wgt::VertexFormat::Uint,
+        GPUVertexFormat::Uint2 => wgt::VertexFormat::Uint2,
+        GPUVertexFormat::Uint3 => wgt::VertexFormat::Uint3,
+        GPUVertexFormat::Uint4 => wgt::VertexFormat::Uint4,
+        GPUVertexFormat::Int => wgt::VertexFormat::Int,
+        GPUVertexFormat::Int2 => wgt::VertexFormat::Int2,
+        GPUVertexFormat::Int3 => wgt::VertexFormat::Int3,
+        GPUVertexFormat::Int4 => wgt::VertexFormat::Int4,
+    }
+}
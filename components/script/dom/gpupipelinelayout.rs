@@ -0,0 +1,40 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::GPUPipelineLayoutBinding;
+use crate::dom::bindings::reflector::{reflect_dom_object, Reflector};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::globalscope::GlobalScope;
+use dom_struct::dom_struct;
+use webgpu::WebGPUPipelineLayout;
+
+#[dom_struct]
+pub struct GPUPipelineLayout {
+    reflector_: Reflector,
+    pipeline_layout: WebGPUPipelineLayout,
+}
+
+impl GPUPipelineLayout {
+    fn new_inherited(pipeline_layout: WebGPUPipelineLayout) -> GPUPipelineLayout {
+        GPUPipelineLayout {
+            reflector_: Reflector::new(),
+            pipeline_layout,
+        }
+    }
+
+    pub fn new(
+        global: &GlobalScope,
+        pipeline_layout: WebGPUPipelineLayout,
+    ) -> DomRoot<GPUPipelineLayout> {
+        reflect_dom_object(
+            Box::new(GPUPipelineLayout::new_inherited(pipeline_layout)),
+            global,
+            GPUPipelineLayoutBinding::Wrap,
+        )
+    }
+
+    pub fn id(&self) -> WebGPUPipelineLayout {
+        self.pipeline_layout
+    }
+}
@@ -0,0 +1,66 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::GPUQueueBinding::{self, GPUQueueMethods};
+use crate::dom::bindings::reflector::{reflect_dom_object, Reflector};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::gpucommandbuffer::GPUCommandBuffer;
+use dom_struct::dom_struct;
+use webgpu::{WebGPU, WebGPUQueue, WebGPURequest};
+
+#[dom_struct]
+pub struct GPUQueue {
+    reflector_: Reflector,
+    #[ignore_malloc_size_of = "channels are hard"]
+    channel: WebGPU,
+    queue: WebGPUQueue,
+}
+
+impl GPUQueue {
+    fn new_inherited(channel: WebGPU, queue: WebGPUQueue) -> GPUQueue {
+        GPUQueue {
+            reflector_: Reflector::new(),
+            channel,
+            queue,
+        }
+    }
+
+    pub fn new(global: &GlobalScope, channel: WebGPU, queue: WebGPUQueue) -> DomRoot<GPUQueue> {
+        reflect_dom_object(
+            Box::new(GPUQueue::new_inherited(channel, queue)),
+            global,
+            GPUQueueBinding::Wrap,
+        )
+    }
+
+    pub fn id(&self) -> WebGPUQueue {
+        self.queue
+    }
+}
+
+impl GPUQueueMethods for GPUQueue {
+    // https://gpuweb.github.io/gpuweb/#dom-gpuqueue-submit
+    fn Submit(&self, command_buffers: Vec<DomRoot<GPUCommandBuffer>>) {
+        // A command buffer can only be submitted once, and not while one
+        // of the buffers it uses is mapped.
+        if command_buffers.iter().any(|cb| !cb.is_valid()) {
+            warn!("Submitting an invalid command buffer");
+            return;
+        }
+        let command_buffers = command_buffers
+            .iter()
+            .map(|cb| {
+                cb.invalidate();
+                cb.id().0
+            })
+            .collect();
+        if let Err(e) = self.channel.0.send(WebGPURequest::Submit {
+            queue_id: self.queue.0,
+            command_buffers,
+        }) {
+            warn!("Failed to send the command buffers to the queue ({})", e);
+        }
+    }
+}
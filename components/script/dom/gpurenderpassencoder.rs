@@ -0,0 +1,205 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::GPURenderPassEncoderBinding::{
+    self, GPURenderPassEncoderMethods,
+};
+use crate::dom::bindings::codegen::UnionTypes::DoubleSequenceOrGPUColorDict as GPUColor;
+use crate::dom::bindings::num::Finite;
+use crate::dom::bindings::reflector::{reflect_dom_object, Reflector};
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::gpubindgroup::GPUBindGroup;
+use crate::dom::gpubuffer::GPUBuffer;
+use crate::dom::gpucommandencoder::{
+    convert_color_dict, convert_color_sequence, GPUCommandEncoder,
+};
+use crate::dom::gpurenderpipeline::GPURenderPipeline;
+use dom_struct::dom_struct;
+use std::cell::Cell;
+use webgpu::{RenderPassCommand, RenderPassDescriptor, WebGPURequest};
+
+#[dom_struct]
+pub struct GPURenderPassEncoder {
+    reflector_: Reflector,
+    encoder: Dom<GPUCommandEncoder>,
+    #[ignore_malloc_size_of = "Defined in webgpu"]
+    descriptor: DomRefCell<Option<RenderPassDescriptor>>,
+    /// The commands recorded so far, sent to the WebGPU thread when the
+    /// pass ends.
+    #[ignore_malloc_size_of = "Defined in webgpu"]
+    commands: DomRefCell<Vec<RenderPassCommand>>,
+}
+
+impl GPURenderPassEncoder {
+    fn new_inherited(
+        encoder: &GPUCommandEncoder,
+        descriptor: RenderPassDescriptor,
+    ) -> GPURenderPassEncoder {
+        GPURenderPassEncoder {
+            reflector_: Reflector::new(),
+            encoder: Dom::from_ref(encoder),
+            descriptor: DomRefCell::new(Some(descriptor)),
+            commands: DomRefCell::new(Vec::new()),
+        }
+    }
+
+    pub fn new(
+        global: &GlobalScope,
+        encoder: &GPUCommandEncoder,
+        descriptor: RenderPassDescriptor,
+    ) -> DomRoot<GPURenderPassEncoder> {
+        reflect_dom_object(
+            Box::new(GPURenderPassEncoder::new_inherited(encoder, descriptor)),
+            global,
+            GPURenderPassEncoderBinding::Wrap,
+        )
+    }
+
+    fn record(&self, command: RenderPassCommand) {
+        if self.descriptor.borrow().is_none() {
+            warn!("Recording a command in an ended render pass");
+            return;
+        }
+        self.commands.borrow_mut().push(command);
+    }
+}
+
+/// A size of 0 binds the rest of the buffer.
+fn binding_size(buffer: &GPUBuffer, offset: u64, size: u64) -> u64 {
+    if size == 0 {
+        buffer.size().saturating_sub(offset)
+    } else {
+        size
+    }
+}
+
+impl GPURenderPassEncoderMethods for GPURenderPassEncoder {
+    // https://gpuweb.github.io/gpuweb/#dom-gpurenderpassencoder-setviewport
+    fn SetViewport(
+        &self,
+        x: Finite<f32>,
+        y: Finite<f32>,
+        width: Finite<f32>,
+        height: Finite<f32>,
+        min_depth: Finite<f32>,
+        max_depth: Finite<f32>,
+    ) {
+        self.record(RenderPassCommand::SetViewport {
+            x: *x,
+            y: *y,
+            width: *width,
+            height: *height,
+            min_depth: *min_depth,
+            max_depth: *max_depth,
+        });
+    }
+
+    // https://gpuweb.github.io/gpuweb/#dom-gpurenderpassencoder-setscissorrect
+    fn SetScissorRect(&self, x: u32, y: u32, width: u32, height: u32) {
+        self.record(RenderPassCommand::SetScissorRect {
+            x,
+            y,
+            width,
+            height,
+        });
+    }
+
+    // https://gpuweb.github.io/gpuweb/#dom-gpurenderpassencoder-setblendcolor
+    fn SetBlendColor(&self, color: GPUColor) {
+        let color = match color {
+            GPUColor::DoubleSequence(ref sequence) => convert_color_sequence(sequence),
+            GPUColor::GPUColorDict(ref dict) => convert_color_dict(dict),
+        };
+        self.record(RenderPassCommand::SetBlendColor(color));
+    }
+
+    // https://gpuweb.github.io/gpuweb/#dom-gpurenderpassencoder-setstencilreference
+    fn SetStencilReference(&self, reference: u32) {
+        self.record(RenderPassCommand::SetStencilReference(reference));
+    }
+
+    // https://gpuweb.github.io/gpuweb/#dom-gpurenderpassencoder-endpass
+    fn EndPass(&self) {
+        let descriptor = match self.descriptor.borrow_mut().take() {
+            Some(descriptor) => descriptor,
+            None => return,
+        };
+        let commands = self.commands.borrow_mut().drain(..).collect();
+        if let Err(e) = self.encoder.channel().0.send(WebGPURequest::RunRenderPass {
+            command_encoder_id: self.encoder.id().0,
+            descriptor,
+            commands,
+        }) {
+            warn!("Failed to send the render pass ({})", e);
+        }
+    }
+
+    // https://gpuweb.github.io/gpuweb/#dom-gpuprogrammablepassencoder-setbindgroup
+    fn SetBindGroup(&self, index: u32, bind_group: &GPUBindGroup, dynamic_offsets: Vec<u32>) {
+        for buffer in bind_group.buffers() {
+            self.encoder.use_buffer(&buffer);
+        }
+        self.record(RenderPassCommand::SetBindGroup {
+            index,
+            bind_group_id: bind_group.id().0,
+            offsets: dynamic_offsets,
+        });
+    }
+
+    // https://gpuweb.github.io/gpuweb/#dom-gpurenderencoderbase-setpipeline
+    fn SetPipeline(&self, pipeline: &GPURenderPipeline) {
+        self.record(RenderPassCommand::SetPipeline(pipeline.id().0));
+    }
+
+    // https://gpuweb.github.io/gpuweb/#dom-gpurenderencoderbase-setindexbuffer
+    fn SetIndexBuffer(&self, buffer: &GPUBuffer, offset: u64, size: u64) {
+        self.encoder.use_buffer(buffer);
+        self.record(RenderPassCommand::SetIndexBuffer {
+            buffer_id: buffer.id().0,
+            offset,
+            size: binding_size(buffer, offset, size),
+        });
+    }
+
+    // https://gpuweb.github.io/gpuweb/#dom-gpurenderencoderbase-setvertexbuffer
+    fn SetVertexBuffer(&self, slot: u32, buffer: &GPUBuffer, offset: u64, size: u64) {
+        self.encoder.use_buffer(buffer);
+        self.record(RenderPassCommand::SetVertexBuffer {
+            slot,
+            buffer_id: buffer.id().0,
+            offset,
+            size: binding_size(buffer, offset, size),
+        });
+    }
+
+    // https://gpuweb.github.io/gpuweb/#dom-gpurenderencoderbase-draw
+    fn Draw(&self, vertex_count: u32, instance_count: u32, first_vertex: u32, first_instance: u32) {
+        self.record(RenderPassCommand::Draw {
+            vertex_count,
+            instance_count,
+            first_vertex,
+            first_instance,
+        });
+    }
+
+    // https://gpuweb.github.io/gpuweb/#dom-gpurenderencoderbase-drawindexed
+    fn DrawIndexed(
+        &self,
+        index_count: u32,
+        instance_count: u32,
+        first_index: u32,
+        base_vertex: i32,
+        first_instance: u32,
+    ) {
+        self.record(RenderPassCommand::DrawIndexed {
+            index_count,
+            instance_count,
+            first_index,
+            base_vertex,
+            first_instance,
+        });
+    }
+}
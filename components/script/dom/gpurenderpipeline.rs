@@ -0,0 +1,40 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::GPURenderPipelineBinding;
+use crate::dom::bindings::reflector::{reflect_dom_object, Reflector};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::globalscope::GlobalScope;
+use dom_struct::dom_struct;
+use webgpu::WebGPURenderPipeline;
+
+#[dom_struct]
+pub struct GPURenderPipeline {
+    reflector_: Reflector,
+    render_pipeline: WebGPURenderPipeline,
+}
+
+impl GPURenderPipeline {
+    fn new_inherited(render_pipeline: WebGPURenderPipeline) -> GPURenderPipeline {
+        GPURenderPipeline {
+            reflector_: Reflector::new(),
+            render_pipeline,
+        }
+    }
+
+    pub fn new(
+        global: &GlobalScope,
+        render_pipeline: WebGPURenderPipeline,
+    ) -> DomRoot<GPURenderPipeline> {
+        reflect_dom_object(
+            Box::new(GPURenderPipeline::new_inherited(render_pipeline)),
+            global,
+            GPURenderPipelineBinding::Wrap,
+        )
+    }
+
+    pub fn id(&self) -> WebGPURenderPipeline {
+        self.render_pipeline
+    }
+}
@@ -0,0 +1,48 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::GPUSamplerBinding;
+use crate::dom::bindings::reflector::{reflect_dom_object, Reflector};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::globalscope::GlobalScope;
+use dom_struct::dom_struct;
+use webgpu::WebGPUSampler;
+
+#[dom_struct]
+pub struct GPUSampler {
+    reflector_: Reflector,
+    sampler: WebGPUSampler,
+    compare_enable: bool,
+}
+
+impl GPUSampler {
+    fn new_inherited(sampler: WebGPUSampler, compare_enable: bool) -> GPUSampler {
+        GPUSampler {
+            reflector_: Reflector::new(),
+            sampler,
+            compare_enable,
+        }
+    }
+
+    pub fn new(
+        global: &GlobalScope,
+        sampler: WebGPUSampler,
+        compare_enable: bool,
+    ) -> DomRoot<GPUSampler> {
+        reflect_dom_object(
+            Box::new(GPUSampler::new_inherited(sampler, compare_enable)),
+            global,
+            GPUSamplerBinding::Wrap,
+        )
+    }
+
+    pub fn id(&self) -> WebGPUSampler {
+        self.sampler
+    }
+
+    /// Whether this is a comparison sampler.
+    pub fn compare_enable(&self) -> bool {
+        self.compare_enable
+    }
+}
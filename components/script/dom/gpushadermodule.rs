@@ -0,0 +1,40 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::GPUShaderModuleBinding;
+use crate::dom::bindings::reflector::{reflect_dom_object, Reflector};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::globalscope::GlobalScope;
+use dom_struct::dom_struct;
+use webgpu::WebGPUShaderModule;
+
+#[dom_struct]
+pub struct GPUShaderModule {
+    reflector_: Reflector,
+    shader_module: WebGPUShaderModule,
+}
+
+impl GPUShaderModule {
+    fn new_inherited(shader_module: WebGPUShaderModule) -> GPUShaderModule {
+        GPUShaderModule {
+            reflector_: Reflector::new(),
+            shader_module,
+        }
+    }
+
+    pub fn new(
+        global: &GlobalScope,
+        shader_module: WebGPUShaderModule,
+    ) -> DomRoot<GPUShaderModule> {
+        reflect_dom_object(
+            Box::new(GPUShaderModule::new_inherited(shader_module)),
+            global,
+            GPUShaderModuleBinding::Wrap,
+        )
+    }
+
+    pub fn id(&self) -> WebGPUShaderModule {
+        self.shader_module
+    }
+}
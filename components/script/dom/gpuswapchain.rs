@@ -0,0 +1,127 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::GPUSwapChainBinding::{self, GPUSwapChainMethods};
+use crate::dom::bindings::codegen::Bindings::GPUTextureBinding::GPUTextureMethods;
+use crate::dom::bindings::refcounted::Trusted;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::gpucanvascontext::GPUCanvasContext;
+use crate::dom::gputexture::GPUTexture;
+use crate::task_source::{TaskSource, TaskSourceName};
+use dom_struct::dom_struct;
+use std::cell::Cell;
+use webgpu::{wgt, WebGPU, WebGPURequest};
+use webrender_api::ImageKey;
+
+#[dom_struct]
+pub struct GPUSwapChain {
+    reflector_: Reflector,
+    #[ignore_malloc_size_of = "channels are hard"]
+    channel: WebGPU,
+    context: Dom<GPUCanvasContext>,
+    /// The texture of every frame, its contents get copied to the image
+    /// of the swap chain when the frame is presented.
+    texture: Dom<GPUTexture>,
+    image_key: ImageKey,
+    present_pending: Cell<bool>,
+    destroyed: Cell<bool>,
+}
+
+impl GPUSwapChain {
+    fn new_inherited(
+        channel: WebGPU,
+        context: &GPUCanvasContext,
+        texture: &GPUTexture,
+        image_key: ImageKey,
+    ) -> GPUSwapChain {
+        GPUSwapChain {
+            reflector_: Reflector::new(),
+            channel,
+            context: Dom::from_ref(context),
+            texture: Dom::from_ref(texture),
+            image_key,
+            present_pending: Cell::new(false),
+            destroyed: Cell::new(false),
+        }
+    }
+
+    pub fn new(
+        global: &GlobalScope,
+        channel: WebGPU,
+        context: &GPUCanvasContext,
+        texture: &GPUTexture,
+        image_key: ImageKey,
+    ) -> DomRoot<GPUSwapChain> {
+        reflect_dom_object(
+            Box::new(GPUSwapChain::new_inherited(
+                channel, context, texture, image_key,
+            )),
+            global,
+            GPUSwapChainBinding::Wrap,
+        )
+    }
+
+    /// Destroys the swap chain when its context gets configured again.
+    pub fn destroy(&self) {
+        if self.destroyed.replace(true) {
+            return;
+        }
+        self.texture.Destroy();
+        if let Err(e) = self
+            .channel
+            .0
+            .send(WebGPURequest::DestroySwapChain(self.image_key))
+        {
+            warn!(
+                "Failed to send the destruction of swap chain {:?} ({})",
+                self.image_key, e
+            );
+        }
+    }
+
+    fn present(&self) {
+        self.present_pending.set(false);
+        if self.destroyed.get() {
+            return;
+        }
+        // Only BGRA8 frames can be copied to the image.
+        if self.texture.format() != wgt::TextureFormat::Bgra8Unorm {
+            warn!(
+                "Presenting a swap chain of format {:?} is not supported",
+                self.texture.format()
+            );
+            return;
+        }
+        if let Err(e) = self.channel.0.send(WebGPURequest::SwapChainPresent {
+            image_key: self.image_key,
+            texture_id: self.texture.id().0,
+        }) {
+            warn!("Failed to present swap chain {:?} ({})", self.image_key, e);
+            return;
+        }
+        self.context.mark_as_dirty();
+    }
+}
+
+impl GPUSwapChainMethods for GPUSwapChain {
+    // https://gpuweb.github.io/gpuweb/#dom-gpuswapchain-getcurrenttexture
+    fn GetCurrentTexture(&self) -> DomRoot<GPUTexture> {
+        // The frame gets presented once the task drawing it is done.
+        if !self.present_pending.replace(true) {
+            let global = self.global();
+            let task_source = global.dom_manipulation_task_source();
+            let canceller = global.task_canceller(TaskSourceName::DOMManipulation);
+            let this = Trusted::new(self);
+            let _ = task_source.queue_with_canceller(
+                task!(present_swap_chain: move || {
+                    this.root().present();
+                }),
+                &canceller,
+            );
+        }
+        DomRoot::from_ref(&*self.texture)
+    }
+}
@@ -0,0 +1,171 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::GPUTextureBinding::{self, GPUTextureMethods};
+use crate::dom::bindings::codegen::Bindings::GPUTextureViewBinding::{
+    GPUTextureAspect, GPUTextureViewDescriptor,
+};
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::gpudevice::{convert_texture_format, convert_texture_view_dimension};
+use crate::dom::gputextureview::GPUTextureView;
+use dom_struct::dom_struct;
+use ipc_channel::ipc;
+use std::cell::Cell;
+use webgpu::{wgt, TextureViewDescriptor, WebGPU, WebGPURequest, WebGPUTexture};
+
+#[dom_struct]
+pub struct GPUTexture {
+    reflector_: Reflector,
+    #[ignore_malloc_size_of = "channels are hard"]
+    channel: WebGPU,
+    texture: WebGPUTexture,
+    #[ignore_malloc_size_of = "Defined in wgpu-types"]
+    size: wgt::Extent3d,
+    mip_level_count: u32,
+    #[ignore_malloc_size_of = "Defined in wgpu-types"]
+    dimension: wgt::TextureDimension,
+    #[ignore_malloc_size_of = "Defined in wgpu-types"]
+    format: wgt::TextureFormat,
+    destroyed: Cell<bool>,
+}
+
+impl GPUTexture {
+    fn new_inherited(
+        channel: WebGPU,
+        texture: WebGPUTexture,
+        size: wgt::Extent3d,
+        mip_level_count: u32,
+        dimension: wgt::TextureDimension,
+        format: wgt::TextureFormat,
+    ) -> GPUTexture {
+        GPUTexture {
+            reflector_: Reflector::new(),
+            channel,
+            texture,
+            size,
+            mip_level_count,
+            dimension,
+            format,
+            destroyed: Cell::new(false),
+        }
+    }
+
+    pub fn new(
+        global: &GlobalScope,
+        channel: WebGPU,
+        texture: WebGPUTexture,
+        size: wgt::Extent3d,
+        mip_level_count: u32,
+        dimension: wgt::TextureDimension,
+        format: wgt::TextureFormat,
+    ) -> DomRoot<GPUTexture> {
+        reflect_dom_object(
+            Box::new(GPUTexture::new_inherited(
+                channel,
+                texture,
+                size,
+                mip_level_count,
+                dimension,
+                format,
+            )),
+            global,
+            GPUTextureBinding::Wrap,
+        )
+    }
+
+    pub fn id(&self) -> WebGPUTexture {
+        self.texture
+    }
+
+    pub fn format(&self) -> wgt::TextureFormat {
+        self.format
+    }
+
+    fn array_layer_count(&self) -> u32 {
+        match self.dimension {
+            wgt::TextureDimension::D2 => self.size.depth,
+            wgt::TextureDimension::D1 | wgt::TextureDimension::D3 => 1,
+        }
+    }
+}
+
+impl GPUTextureMethods for GPUTexture {
+    // https://gpuweb.github.io/gpuweb/#dom-gputexture-createview
+    fn CreateView(&self, descriptor: &GPUTextureViewDescriptor) -> DomRoot<GPUTextureView> {
+        let dimension = descriptor.dimension.map_or_else(
+            || match self.dimension {
+                wgt::TextureDimension::D1 => wgt::TextureViewDimension::D1,
+                wgt::TextureDimension::D2 if self.size.depth > 1 => {
+                    wgt::TextureViewDimension::D2Array
+                },
+                wgt::TextureDimension::D2 => wgt::TextureViewDimension::D2,
+                wgt::TextureDimension::D3 => wgt::TextureViewDimension::D3,
+            },
+            convert_texture_view_dimension,
+        );
+        let level_count = if descriptor.mipLevelCount == 0 {
+            self.mip_level_count.saturating_sub(descriptor.baseMipLevel)
+        } else {
+            descriptor.mipLevelCount
+        };
+        let array_layer_count = if descriptor.arrayLayerCount == 0 {
+            match dimension {
+                wgt::TextureViewDimension::D1 |
+                wgt::TextureViewDimension::D2 |
+                wgt::TextureViewDimension::D3 => 1,
+                wgt::TextureViewDimension::Cube => 6,
+                wgt::TextureViewDimension::D2Array | wgt::TextureViewDimension::CubeArray => self
+                    .array_layer_count()
+                    .saturating_sub(descriptor.baseArrayLayer),
+            }
+        } else {
+            descriptor.arrayLayerCount
+        };
+
+        let (sender, receiver) = ipc::channel().unwrap();
+        self.channel
+            .0
+            .send(WebGPURequest::CreateTextureView {
+                sender,
+                texture_id: self.texture.0,
+                descriptor: TextureViewDescriptor {
+                    format: descriptor
+                        .format
+                        .map_or(self.format, convert_texture_format),
+                    dimension,
+                    aspect: match descriptor.aspect {
+                        GPUTextureAspect::All => wgt::TextureAspect::All,
+                        GPUTextureAspect::Stencil_only => wgt::TextureAspect::StencilOnly,
+                        GPUTextureAspect::Depth_only => wgt::TextureAspect::DepthOnly,
+                    },
+                    base_mip_level: descriptor.baseMipLevel,
+                    level_count,
+                    base_array_layer: descriptor.baseArrayLayer,
+                    array_layer_count,
+                },
+            })
+            .expect("Failed to create WebGPU texture view");
+        let texture_view = receiver.recv().unwrap();
+        GPUTextureView::new(&self.global(), texture_view)
+    }
+
+    // https://gpuweb.github.io/gpuweb/#dom-gputexture-destroy
+    fn Destroy(&self) {
+        if self.destroyed.replace(true) {
+            return;
+        }
+        if let Err(e) = self
+            .channel
+            .0
+            .send(WebGPURequest::DestroyTexture(self.texture.0))
+        {
+            warn!(
+                "Failed to send the destruction of texture {:?} ({})",
+                self.texture, e
+            );
+        }
+    }
+}
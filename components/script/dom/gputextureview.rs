@@ -0,0 +1,37 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::GPUTextureViewBinding;
+use crate::dom::bindings::reflector::{reflect_dom_object, Reflector};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::globalscope::GlobalScope;
+use dom_struct::dom_struct;
+use webgpu::WebGPUTextureView;
+
+#[dom_struct]
+pub struct GPUTextureView {
+    reflector_: Reflector,
+    texture_view: WebGPUTextureView,
+}
+
+impl GPUTextureView {
+    fn new_inherited(texture_view: WebGPUTextureView) -> GPUTextureView {
+        GPUTextureView {
+            reflector_: Reflector::new(),
+            texture_view,
+        }
+    }
+
+    pub fn new(global: &GlobalScope, texture_view: WebGPUTextureView) -> DomRoot<GPUTextureView> {
+        reflect_dom_object(
+            Box::new(GPUTextureView::new_inherited(texture_view)),
+            global,
+            GPUTextureViewBinding::Wrap,
+        )
+    }
+
+    pub fn id(&self) -> WebGPUTextureView {
+        self.texture_view
+    }
+}
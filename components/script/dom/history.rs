@@ -183,7 +183,7 @@ impl History {
         // TODO: Step 4
 
         // Step 5
-        let serialized_data = StructuredCloneData::write(cx, data)?.move_to_arraybuffer();
+        let serialized_data = StructuredCloneData::write(cx, data, None)?.move_to_arraybuffer();
 
         let new_url: ServoUrl = match url {
             // Step 6
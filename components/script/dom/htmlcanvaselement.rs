@@ -21,8 +21,10 @@ use crate::dom::canvasrenderingcontext2d::{
 use crate::dom::document::Document;
 use crate::dom::element::{AttributeMutation, Element, RawLayoutElementHelpers};
 use crate::dom::globalscope::GlobalScope;
+use crate::dom::gpucanvascontext::{GPUCanvasContext, LayoutGPUCanvasContextHelpers};
 use crate::dom::htmlelement::HTMLElement;
-use crate::dom::node::{window_from_node, Node};
+use crate::dom::node::{window_from_node, Node, NodeDamage};
+use crate::dom::offscreencanvas::{OffscreenCanvas, OffscreenCanvasPlaceholder};
 use crate::dom::virtualmethods::VirtualMethods;
 use crate::dom::webgl2renderingcontext::WebGL2RenderingContext;
 use crate::dom::webglrenderingcontext::{
@@ -55,6 +57,10 @@ pub enum CanvasContext {
     Context2d(Dom<CanvasRenderingContext2D>),
     WebGL(Dom<WebGLRenderingContext>),
     WebGL2(Dom<WebGL2RenderingContext>),
+    WebGPU(Dom<GPUCanvasContext>),
+    /// The canvas is a placeholder for an offscreen canvas, this context
+    /// only holds the bitmap the offscreen canvas commits its frames to.
+    Placeholder(Dom<CanvasRenderingContext2D>),
 }
 
 #[dom_struct]
@@ -97,12 +103,19 @@ impl HTMLCanvasElement {
                 CanvasContext::Context2d(ref context) => context.set_bitmap_dimensions(size),
                 CanvasContext::WebGL(ref context) => context.recreate(size),
                 CanvasContext::WebGL2(ref context) => context.recreate(size),
+                // The swap chain keeps its size until it gets configured again.
+                CanvasContext::WebGPU(_) => {},
+                // The size of the bitmap is controlled by the offscreen canvas.
+                CanvasContext::Placeholder(_) => {},
             }
         }
     }
 
     pub fn get_size(&self) -> Size2D<u32> {
-        Size2D::new(self.Width(), self.Height())
+        match *self.context.borrow() {
+            Some(CanvasContext::Placeholder(ref context)) => context.get_size(),
+            _ => Size2D::new(self.Width(), self.Height()),
+        }
     }
 
     pub fn origin_is_clean(&self) -> bool {
@@ -126,7 +139,8 @@ impl LayoutHTMLCanvasElementHelpers for LayoutDom<HTMLCanvasElement> {
         unsafe {
             let canvas = &*self.unsafe_get();
             let source = match canvas.context.borrow_for_layout().as_ref() {
-                Some(&CanvasContext::Context2d(ref context)) => {
+                Some(&CanvasContext::Context2d(ref context)) |
+                Some(&CanvasContext::Placeholder(ref context)) => {
                     HTMLCanvasDataSource::Image(Some(context.to_layout().get_ipc_renderer()))
                 },
                 Some(&CanvasContext::WebGL(ref context)) => {
@@ -135,6 +149,9 @@ impl LayoutHTMLCanvasElementHelpers for LayoutDom<HTMLCanvasElement> {
                 Some(&CanvasContext::WebGL2(ref context)) => {
                     context.to_layout().canvas_data_source()
                 },
+                Some(&CanvasContext::WebGPU(ref context)) => {
+                    context.to_layout().canvas_data_source()
+                },
                 None => HTMLCanvasDataSource::Image(None),
             };
 
@@ -177,11 +194,12 @@ impl LayoutHTMLCanvasElementHelpers for LayoutDom<HTMLCanvasElement> {
     fn get_canvas_id_for_layout(&self) -> CanvasId {
         unsafe {
             let canvas = &*self.unsafe_get();
-            if let &Some(CanvasContext::Context2d(ref context)) = canvas.context.borrow_for_layout()
-            {
-                context.to_layout().get_canvas_id()
-            } else {
-                CanvasId(0)
+            match canvas.context.borrow_for_layout() {
+                &Some(CanvasContext::Context2d(ref context)) |
+                &Some(CanvasContext::Placeholder(ref context)) => {
+                    context.to_layout().get_canvas_id()
+                },
+                _ => CanvasId(0),
             }
         }
     }
@@ -249,6 +267,22 @@ impl HTMLCanvasElement {
         Some(context)
     }
 
+    fn get_or_init_webgpu_context(&self) -> Option<DomRoot<GPUCanvasContext>> {
+        if !pref!(dom.webgpu.enabled) {
+            return None;
+        }
+        if let Some(ctx) = self.context() {
+            return match *ctx {
+                CanvasContext::WebGPU(ref ctx) => Some(DomRoot::from_ref(ctx)),
+                _ => None,
+            };
+        }
+        let window = window_from_node(self);
+        let context = GPUCanvasContext::new(window.upcast::<GlobalScope>(), self);
+        *self.context.borrow_mut() = Some(CanvasContext::WebGPU(Dom::from_ref(&*context)));
+        Some(context)
+    }
+
     /// Gets the base WebGLRenderingContext for WebGL or WebGL 2, if exists.
     pub fn get_base_webgl_context(&self) -> Option<DomRoot<WebGLRenderingContext>> {
         match *self.context.borrow() {
@@ -277,7 +311,18 @@ impl HTMLCanvasElement {
     }
 
     pub fn is_valid(&self) -> bool {
-        self.Height() != 0 && self.Width() != 0
+        let size = self.get_size();
+        size.height != 0 && size.width != 0
+    }
+
+    /// Takes note of a frame committed by the offscreen canvas this canvas
+    /// is a placeholder for, the canvas paint thread already copied it over
+    /// to the bitmap of the placeholder.
+    pub fn placeholder_committed(&self, size: Size2D<u32>) {
+        if let Some(CanvasContext::Placeholder(ref context)) = *self.context.borrow() {
+            context.bitmap_replaced(size);
+        }
+        self.upcast::<Node>().dirty(NodeDamage::OtherNodeDamage);
     }
 
     pub fn fetch_all_data(&self) -> Option<(Option<IpcSharedMemory>, Size2D<u32>)> {
@@ -288,7 +333,8 @@ impl HTMLCanvasElement {
         }
 
         let data = match self.context.borrow().as_ref() {
-            Some(&CanvasContext::Context2d(ref context)) => {
+            Some(&CanvasContext::Context2d(ref context)) |
+            Some(&CanvasContext::Placeholder(ref context)) => {
                 let (sender, receiver) =
                     ipc::channel(self.global().time_profiler_chan().clone()).unwrap();
                 let msg = CanvasMsg::FromScript(
@@ -307,6 +353,10 @@ impl HTMLCanvasElement {
                 // TODO: add a method in WebGL2RenderingContext to get the pixels.
                 return None;
             },
+            Some(&CanvasContext::WebGPU(_)) => {
+                // TODO: add a method in GPUCanvasContext to get the pixels.
+                return None;
+            },
             None => None,
         };
 
@@ -334,8 +384,11 @@ impl HTMLCanvasElementMethods for HTMLCanvasElement {
         cx: *mut JSContext,
         id: DOMString,
         options: HandleValue,
-    ) -> Option<RenderingContext> {
-        match &*id {
+    ) -> Fallible<Option<RenderingContext>> {
+        if let Some(CanvasContext::Placeholder(_)) = *self.context.borrow() {
+            return Err(Error::InvalidState);
+        }
+        Ok(match &*id {
             "2d" => self
                 .get_or_init_2d_context()
                 .map(RenderingContext::CanvasRenderingContext2D),
@@ -345,8 +398,11 @@ impl HTMLCanvasElementMethods for HTMLCanvasElement {
             "webgl2" | "experimental-webgl2" => self
                 .get_or_init_webgl2_context(cx, options)
                 .map(RenderingContext::WebGL2RenderingContext),
+            "gpupresent" => self
+                .get_or_init_webgpu_context()
+                .map(RenderingContext::GPUCanvasContext),
             _ => None,
-        }
+        })
     }
 
     // https://html.spec.whatwg.org/multipage/#dom-canvas-todataurl
@@ -363,30 +419,32 @@ impl HTMLCanvasElementMethods for HTMLCanvasElement {
         }
 
         // Step 2.
-        if self.Width() == 0 || self.Height() == 0 {
+        let size = self.get_size();
+        if size.width == 0 || size.height == 0 {
             return Ok(USVString("data:,".into()));
         }
 
         // Step 3.
         let file = match *self.context.borrow() {
-            Some(CanvasContext::Context2d(ref context)) => {
-                context.get_rect(Rect::from_size(self.get_size()))
+            Some(CanvasContext::Context2d(ref context)) |
+            Some(CanvasContext::Placeholder(ref context)) => {
+                context.get_rect(Rect::from_size(size))
             },
-            Some(CanvasContext::WebGL(ref context)) => {
-                match context.get_image_data(self.get_size()) {
-                    Some(data) => data,
-                    None => return Ok(USVString("data:,".into())),
-                }
+            Some(CanvasContext::WebGL(ref context)) => match context.get_image_data(size) {
+                Some(data) => data,
+                None => return Ok(USVString("data:,".into())),
             },
             Some(CanvasContext::WebGL2(ref context)) => {
-                match context.base_context().get_image_data(self.get_size()) {
+                match context.base_context().get_image_data(size) {
                     Some(data) => data,
                     None => return Ok(USVString("data:,".into())),
                 }
             },
+            // TODO: add a method in GPUCanvasContext to get the pixels.
+            Some(CanvasContext::WebGPU(_)) => return Ok(USVString("data:,".into())),
             None => {
                 // Each pixel is fully-transparent black.
-                vec![0; (self.Width() * self.Height() * 4) as usize]
+                vec![0; size.area() as usize * 4]
             },
         };
 
@@ -395,7 +453,7 @@ impl HTMLCanvasElementMethods for HTMLCanvasElement {
         // FIXME(nox): https://github.com/PistonDevelopers/image-png/issues/86
         // FIXME(nox): https://github.com/PistonDevelopers/image-png/issues/87
         PNGEncoder::new(&mut png)
-            .encode(&file, self.Width(), self.Height(), ColorType::RGBA(8))
+            .encode(&file, size.width, size.height, ColorType::RGBA(8))
             .unwrap();
         let mut url = "data:image/png;base64,".to_owned();
         // FIXME(nox): Should this use base64::URL_SAFE?
@@ -403,6 +461,29 @@ impl HTMLCanvasElementMethods for HTMLCanvasElement {
         base64::encode_config_buf(&png, base64::STANDARD, &mut url);
         Ok(USVString(url))
     }
+
+    // https://html.spec.whatwg.org/multipage/#dom-canvas-transfercontroltooffscreen
+    fn TransferControlToOffscreen(&self) -> Fallible<DomRoot<OffscreenCanvas>> {
+        // Step 1.
+        if self.context.borrow().is_some() {
+            return Err(Error::InvalidState);
+        }
+
+        // Steps 2-3.
+        let window = window_from_node(self);
+        let size = self.get_size();
+        let bitmap = CanvasRenderingContext2D::new(window.upcast(), self, size);
+        let placeholder = OffscreenCanvasPlaceholder::new(self, bitmap.get_canvas_id());
+        *self.context.borrow_mut() = Some(CanvasContext::Placeholder(Dom::from_ref(&*bitmap)));
+
+        // Step 4.
+        Ok(OffscreenCanvas::new(
+            window.upcast(),
+            size.width as u64,
+            size.height as u64,
+            Some(placeholder),
+        ))
+    }
 }
 
 impl VirtualMethods for HTMLCanvasElement {
@@ -10,28 +10,35 @@ use crate::dom::bindings::codegen::Bindings::HTMLElementBinding;
 use crate::dom::bindings::codegen::Bindings::HTMLElementBinding::HTMLElementMethods;
 use crate::dom::bindings::codegen::Bindings::NodeBinding::NodeBinding::NodeMethods;
 use crate::dom::bindings::codegen::Bindings::WindowBinding::WindowMethods;
-use crate::dom::bindings::error::{Error, ErrorResult};
+use crate::dom::bindings::error::{Error, ErrorResult, Fallible};
 use crate::dom::bindings::inheritance::Castable;
 use crate::dom::bindings::inheritance::{ElementTypeId, HTMLElementTypeId, NodeTypeId};
 use crate::dom::bindings::root::{Dom, DomRoot, MutNullableDom};
 use crate::dom::bindings::str::DOMString;
 use crate::dom::cssstyledeclaration::{CSSModificationAccess, CSSStyleDeclaration, CSSStyleOwner};
+use crate::dom::customelementregistry::CallbackReaction;
 use crate::dom::document::{Document, FocusType};
 use crate::dom::documentfragment::DocumentFragment;
 use crate::dom::domstringmap::DOMStringMap;
 use crate::dom::element::{AttributeMutation, Element};
+use crate::dom::elementinternals::ElementInternals;
 use crate::dom::eventtarget::EventTarget;
 use crate::dom::htmlbodyelement::HTMLBodyElement;
 use crate::dom::htmlbrelement::HTMLBRElement;
+use crate::dom::htmlfieldsetelement::HTMLFieldSetElement;
+use crate::dom::htmlformelement::{FormControl, HTMLFormElement};
 use crate::dom::htmlframesetelement::HTMLFrameSetElement;
 use crate::dom::htmlhtmlelement::HTMLHtmlElement;
 use crate::dom::htmlinputelement::{HTMLInputElement, InputType};
 use crate::dom::htmllabelelement::HTMLLabelElement;
 use crate::dom::node::{document_from_node, window_from_node};
-use crate::dom::node::{Node, NodeFlags};
+use crate::dom::node::{Node, NodeFlags, UnbindContext};
 use crate::dom::nodelist::NodeList;
 use crate::dom::text::Text;
+use crate::dom::validation::Validatable;
+use crate::dom::validitystate::ValidationFlags;
 use crate::dom::virtualmethods::VirtualMethods;
+use crate::script_thread::ScriptThread;
 use dom_struct::dom_struct;
 use html5ever::{LocalName, Prefix};
 use script_layout_interface::message::QueryMsg;
@@ -46,6 +53,7 @@ pub struct HTMLElement {
     element: Element,
     style_decl: MutNullableDom<CSSStyleDeclaration>,
     dataset: MutNullableDom<DOMStringMap>,
+    element_internals: MutNullableDom<ElementInternals>,
 }
 
 impl HTMLElement {
@@ -73,6 +81,7 @@ impl HTMLElement {
             ),
             style_decl: Default::default(),
             dataset: Default::default(),
+            element_internals: Default::default(),
         }
     }
 
@@ -89,6 +98,15 @@ impl HTMLElement {
         )
     }
 
+    pub fn element_internals(&self) -> Option<DomRoot<ElementInternals>> {
+        self.element_internals.get()
+    }
+
+    fn ensure_element_internals(&self) -> DomRoot<ElementInternals> {
+        self.element_internals
+            .or_init(|| ElementInternals::new(self))
+    }
+
     fn is_body_or_frameset(&self) -> bool {
         let eventtarget = self.upcast::<EventTarget>();
         eventtarget.is::<HTMLBodyElement>() || eventtarget.is::<HTMLFrameSetElement>()
@@ -517,6 +535,41 @@ impl HTMLElementMethods for HTMLElement {
         // Step 7.
         Node::replace_all(Some(fragment.upcast()), self.upcast::<Node>());
     }
+
+    // https://html.spec.whatwg.org/multipage/#dom-attachinternals
+    fn AttachInternals(&self) -> Fallible<DomRoot<ElementInternals>> {
+        let element = self.upcast::<Element>();
+
+        // Step 1
+        if element.get_is().is_some() {
+            return Err(Error::NotSupported);
+        }
+
+        // Step 2
+        let definition = match document_from_node(self).lookup_custom_element_definition(
+            element.namespace(),
+            element.local_name(),
+            None,
+        ) {
+            Some(definition) => definition,
+            None => return Err(Error::InvalidState),
+        };
+
+        // Step 3
+        if definition.disable_internals {
+            return Err(Error::NotSupported);
+        }
+
+        // Step 4
+        let internals = self.ensure_element_internals();
+        if internals.attached() {
+            return Err(Error::InvalidState);
+        }
+
+        // Steps 5-6
+        internals.set_attached();
+        Ok(internals)
+    }
 }
 
 fn append_text_node_to_fragment(document: &Document, fragment: &DocumentFragment, text: String) {
@@ -635,6 +688,9 @@ impl HTMLElement {
                 HTMLElementTypeId::HTMLProgressElement |
                 HTMLElementTypeId::HTMLSelectElement |
                 HTMLElementTypeId::HTMLTextAreaElement => true,
+                HTMLElementTypeId::HTMLElement => {
+                    self.upcast::<Element>().is_form_associated_custom_element()
+                },
                 _ => false,
             },
             _ => false,
@@ -658,6 +714,9 @@ impl HTMLElement {
                 HTMLElementTypeId::HTMLOutputElement |
                 HTMLElementTypeId::HTMLSelectElement |
                 HTMLElementTypeId::HTMLTextAreaElement => true,
+                HTMLElementTypeId::HTMLElement => {
+                    self.upcast::<Element>().is_form_associated_custom_element()
+                },
                 _ => false,
             },
             _ => false,
@@ -738,6 +797,31 @@ impl VirtualMethods for HTMLElement {
             },
             _ => {},
         }
+
+        if !self.upcast::<Element>().is_form_associated_custom_element() {
+            return;
+        }
+        match attr.local_name() {
+            &local_name!("disabled") => {
+                let el = self.upcast::<Element>();
+                match mutation {
+                    AttributeMutation::Set(Some(_)) => {},
+                    AttributeMutation::Set(None) => {
+                        el.set_disabled_state(true);
+                        el.set_enabled_state(false);
+                    },
+                    AttributeMutation::Removed => {
+                        el.set_disabled_state(false);
+                        el.set_enabled_state(true);
+                        el.check_ancestors_disabled_state_for_form_control();
+                    },
+                }
+            },
+            &local_name!("form") => {
+                self.form_attribute_mutated(mutation);
+            },
+            _ => {},
+        }
     }
 
     fn bind_to_tree(&self, tree_in_doc: bool) {
@@ -745,6 +829,29 @@ impl VirtualMethods for HTMLElement {
             s.bind_to_tree(tree_in_doc);
         }
         self.update_sequentially_focusable_status();
+
+        let el = self.upcast::<Element>();
+        if el.is_form_associated_custom_element() {
+            el.check_ancestors_disabled_state_for_form_control();
+        }
+    }
+
+    fn unbind_from_tree(&self, context: &UnbindContext) {
+        self.super_type().unwrap().unbind_from_tree(context);
+
+        let el = self.upcast::<Element>();
+        if !el.is_form_associated_custom_element() {
+            return;
+        }
+        if self
+            .upcast::<Node>()
+            .ancestors()
+            .any(|ancestor| ancestor.is::<HTMLFieldSetElement>())
+        {
+            el.check_ancestors_disabled_state_for_form_control();
+        } else {
+            el.check_disabled_attribute();
+        }
     }
 
     fn parse_plain_attribute(&self, name: &LocalName, value: DOMString) -> AttrValue {
@@ -758,3 +865,39 @@ impl VirtualMethods for HTMLElement {
         }
     }
 }
+
+// https://html.spec.whatwg.org/multipage/#form-associated-custom-element
+impl FormControl for HTMLElement {
+    fn form_owner(&self) -> Option<DomRoot<HTMLFormElement>> {
+        self.element_internals()
+            .and_then(|internals| internals.form_owner())
+    }
+
+    fn set_form_owner(&self, form: Option<&HTMLFormElement>) {
+        self.ensure_element_internals().set_form_owner(form);
+        ScriptThread::enqueue_callback_reaction(
+            self.upcast::<Element>(),
+            CallbackReaction::FormAssociated(form.map(DomRoot::from_ref)),
+            None,
+        );
+    }
+
+    fn to_element<'a>(&'a self) -> &'a Element {
+        self.upcast::<Element>()
+    }
+}
+
+impl Validatable for HTMLElement {
+    // https://html.spec.whatwg.org/multipage/#candidate-for-constraint-validation
+    fn is_instance_validatable(&self) -> bool {
+        // TODO: The element shouldn't be barred by having a datalist ancestor
+        // nor being read-only.
+        !self.upcast::<Element>().disabled_state()
+    }
+
+    // https://html.spec.whatwg.org/multipage/#face-validity-flags
+    fn validate(&self, _validate_flags: ValidationFlags) -> bool {
+        self.element_internals()
+            .map_or(true, |internals| internals.validity_flags().is_empty())
+    }
+}
@@ -142,6 +142,12 @@ impl VirtualMethods for HTMLFieldSetElement {
                             NodeTypeId::Element(ElementTypeId::HTMLElement(
                                 HTMLElementTypeId::HTMLTextAreaElement,
                             )) => true,
+                            NodeTypeId::Element(ElementTypeId::HTMLElement(
+                                HTMLElementTypeId::HTMLElement,
+                            )) => descendant
+                                .downcast::<Element>()
+                                .unwrap()
+                                .is_form_associated_custom_element(),
                             _ => false,
                         })
                 });
@@ -18,6 +18,7 @@ use crate::dom::bindings::reflector::DomObject;
 use crate::dom::bindings::root::{Dom, DomOnceCell, DomRoot};
 use crate::dom::bindings::str::DOMString;
 use crate::dom::blob::Blob;
+use crate::dom::customelementregistry::CallbackReaction;
 use crate::dom::document::Document;
 use crate::dom::element::{AttributeMutation, Element};
 use crate::dom::event::{Event, EventBubbles, EventCancelable};
@@ -45,7 +46,7 @@ use crate::dom::node::{Node, NodeFlags, UnbindContext, VecPreOrderInsertionHelpe
 use crate::dom::validitystate::ValidationFlags;
 use crate::dom::virtualmethods::VirtualMethods;
 use crate::dom::window::Window;
-use crate::script_thread::MainThreadScriptMsg;
+use crate::script_thread::{MainThreadScriptMsg, ScriptThread};
 use crate::task_source::TaskSource;
 use dom_struct::dom_struct;
 use encoding_rs::{Encoding, UTF_8};
@@ -217,6 +218,11 @@ impl HTMLFormElementMethods for HTMLFormElement {
                         HTMLElementTypeId::HTMLTextAreaElement => {
                             elem.downcast::<HTMLTextAreaElement>().unwrap().form_owner()
                         },
+                        HTMLElementTypeId::HTMLElement
+                            if elem.is_form_associated_custom_element() =>
+                        {
+                            elem.downcast::<HTMLElement>().unwrap().form_owner()
+                        },
                         _ => {
                             debug_assert!(
                                 !elem.downcast::<HTMLElement>().unwrap().is_listed_element() ||
@@ -676,6 +682,12 @@ impl HTMLFormElement {
                             });
                         }
                     },
+                    HTMLElementTypeId::HTMLElement => {
+                        let element = child.downcast::<HTMLElement>().unwrap();
+                        if let Some(internals) = element.element_internals() {
+                            internals.push_form_data(&mut data_set);
+                        }
+                    },
                     _ => (),
                 }
             }
@@ -809,6 +821,14 @@ impl HTMLFormElement {
                 )) => {
                     // Unimplemented
                 },
+                NodeTypeId::Element(ElementTypeId::HTMLElement(HTMLElementTypeId::HTMLElement)) => {
+                    // https://html.spec.whatwg.org/multipage/#face-reset-algorithm
+                    ScriptThread::enqueue_callback_reaction(
+                        child.downcast::<Element>().unwrap(),
+                        CallbackReaction::FormReset,
+                        None,
+                    );
+                },
                 _ => {},
             }
         }
@@ -884,6 +904,7 @@ pub enum FormSubmittableElement {
     ObjectElement(DomRoot<HTMLObjectElement>),
     SelectElement(DomRoot<HTMLSelectElement>),
     TextAreaElement(DomRoot<HTMLTextAreaElement>),
+    CustomElement(DomRoot<HTMLElement>),
 }
 
 impl FormSubmittableElement {
@@ -894,6 +915,7 @@ impl FormSubmittableElement {
             FormSubmittableElement::ObjectElement(ref object) => object.upcast(),
             FormSubmittableElement::SelectElement(ref select) => select.upcast(),
             FormSubmittableElement::TextAreaElement(ref textarea) => textarea.upcast(),
+            FormSubmittableElement::CustomElement(ref element) => element.upcast(),
         }
     }
 
@@ -908,6 +930,9 @@ impl FormSubmittableElement {
             FormSubmittableElement::SelectElement(DomRoot::from_ref(&input))
         } else if let Some(input) = element.downcast::<HTMLTextAreaElement>() {
             FormSubmittableElement::TextAreaElement(DomRoot::from_ref(&input))
+        } else if element.is_form_associated_custom_element() {
+            let element = element.downcast::<HTMLElement>().unwrap();
+            FormSubmittableElement::CustomElement(DomRoot::from_ref(&element))
         } else {
             unreachable!()
         }
@@ -1270,6 +1295,11 @@ impl FormControlElementHelpers for Element {
             NodeTypeId::Element(ElementTypeId::HTMLElement(
                 HTMLElementTypeId::HTMLTextAreaElement,
             )) => Some(self.downcast::<HTMLTextAreaElement>().unwrap() as &dyn FormControl),
+            NodeTypeId::Element(ElementTypeId::HTMLElement(HTMLElementTypeId::HTMLElement))
+                if self.is_form_associated_custom_element() =>
+            {
+                Some(self.downcast::<HTMLElement>().unwrap() as &dyn FormControl)
+            },
             _ => None,
         }
     }
@@ -366,6 +366,8 @@ pub trait LayoutHTMLInputElementHelpers {
     #[allow(unsafe_code)]
     unsafe fn selection_for_layout(self) -> Option<Range<usize>>;
     #[allow(unsafe_code)]
+    unsafe fn composition_for_layout(self) -> Option<Range<usize>>;
+    #[allow(unsafe_code)]
     unsafe fn checked_state_for_layout(self) -> bool;
     #[allow(unsafe_code)]
     unsafe fn indeterminate_state_for_layout(self) -> bool;
@@ -379,6 +381,16 @@ unsafe fn get_raw_textinput_value(input: LayoutDom<HTMLInputElement>) -> DOMStri
         .get_content()
 }
 
+/// Translates a range of the raw value of a password input to the same characters in the
+/// replacement value that layout displays.
+fn password_range_for_layout(text: &str, range: Range<usize>) -> Range<usize> {
+    let char_start = text[..range.start].chars().count();
+    let char_end = char_start + text[range].chars().count();
+
+    let bytes_per_char = PASSWORD_REPLACEMENT_CHAR.len_utf8();
+    char_start * bytes_per_char..char_end * bytes_per_char
+}
+
 impl LayoutHTMLInputElementHelpers for LayoutDom<HTMLInputElement> {
     #[allow(unsafe_code)]
     unsafe fn value_for_layout(self) -> String {
@@ -435,13 +447,7 @@ impl LayoutHTMLInputElementHelpers for LayoutDom<HTMLInputElement> {
             InputType::Password => {
                 let text = get_raw_textinput_value(self);
                 let sel = textinput.sorted_selection_offsets_range();
-
-                // Translate indices from the raw value to indices in the replacement value.
-                let char_start = text[..sel.start].chars().count();
-                let char_end = char_start + text[sel].chars().count();
-
-                let bytes_per_char = PASSWORD_REPLACEMENT_CHAR.len_utf8();
-                Some(char_start * bytes_per_char..char_end * bytes_per_char)
+                Some(password_range_for_layout(&text, sel))
             },
             input_type if input_type.is_textual() => {
                 Some(textinput.sorted_selection_offsets_range())
@@ -450,6 +456,28 @@ impl LayoutHTMLInputElementHelpers for LayoutDom<HTMLInputElement> {
         }
     }
 
+    #[allow(unrooted_must_root)]
+    #[allow(unsafe_code)]
+    unsafe fn composition_for_layout(self) -> Option<Range<usize>> {
+        if !(*self.unsafe_get()).upcast::<Element>().focus_state() {
+            return None;
+        }
+
+        let composition = (*self.unsafe_get())
+            .textinput
+            .borrow_for_layout()
+            .composition()?;
+
+        match (*self.unsafe_get()).input_type() {
+            InputType::Password => {
+                let text = get_raw_textinput_value(self);
+                Some(password_range_for_layout(&text, composition))
+            },
+            input_type if input_type.is_textual() => Some(composition),
+            _ => None,
+        }
+    }
+
     #[allow(unrooted_must_root)]
     #[allow(unsafe_code)]
     unsafe fn checked_state_for_layout(self) -> bool {
@@ -1530,15 +1558,9 @@ impl VirtualMethods for HTMLInputElement {
             event.type_() == atom!("compositionend")) &&
             self.input_type().is_textual_or_password()
         {
-            // TODO: Update DOM on start and continue
-            // and generally do proper CompositionEvent handling.
             if let Some(compositionevent) = event.downcast::<CompositionEvent>() {
-                if event.type_() == atom!("compositionend") {
-                    let _ = self
-                        .textinput
-                        .borrow_mut()
-                        .handle_compositionend(compositionevent);
-                    self.upcast::<Node>().dirty(NodeDamage::OtherNodeDamage);
+                if self.selection().handle_composition_event(compositionevent) {
+                    self.update_placeholder_shown_state();
                 }
                 event.mark_as_handled();
             }
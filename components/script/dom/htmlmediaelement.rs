@@ -14,11 +14,14 @@ use crate::dom::bindings::codegen::Bindings::HTMLMediaElementBinding::HTMLMediaE
 use crate::dom::bindings::codegen::Bindings::HTMLSourceElementBinding::HTMLSourceElementMethods;
 use crate::dom::bindings::codegen::Bindings::MediaErrorBinding::MediaErrorConstants::*;
 use crate::dom::bindings::codegen::Bindings::MediaErrorBinding::MediaErrorMethods;
+use crate::dom::bindings::codegen::Bindings::MediaSourceBinding::EndOfStreamError;
+use crate::dom::bindings::codegen::Bindings::MediaSourceBinding::MediaSourceMethods;
+use crate::dom::bindings::codegen::Bindings::MediaSourceBinding::ReadyState as MediaSourceReadyState;
 use crate::dom::bindings::codegen::Bindings::TextTrackBinding::{TextTrackKind, TextTrackMode};
 use crate::dom::bindings::codegen::InheritTypes::{ElementTypeId, HTMLElementTypeId};
 use crate::dom::bindings::codegen::InheritTypes::{HTMLMediaElementTypeId, NodeTypeId};
 use crate::dom::bindings::codegen::UnionTypes::{
-    MediaStreamOrBlob, VideoTrackOrAudioTrackOrTextTrack,
+    MediaStreamOrMediaSourceOrBlob, VideoTrackOrAudioTrackOrTextTrack,
 };
 use crate::dom::bindings::error::{Error, ErrorResult, Fallible};
 use crate::dom::bindings::inheritance::Castable;
@@ -37,6 +40,7 @@ use crate::dom::htmlelement::HTMLElement;
 use crate::dom::htmlsourceelement::HTMLSourceElement;
 use crate::dom::htmlvideoelement::HTMLVideoElement;
 use crate::dom::mediaerror::MediaError;
+use crate::dom::mediasource::MediaSource;
 use crate::dom::mediastream::MediaStream;
 use crate::dom::node::{document_from_node, window_from_node, Node, NodeDamage, UnbindContext};
 use crate::dom::performanceresourcetiming::InitiatorType;
@@ -164,17 +168,21 @@ impl FrameRenderer for MediaFrameRenderer {
 #[derive(JSTraceable, MallocSizeOf)]
 enum SrcObject {
     MediaStream(Dom<MediaStream>),
+    MediaSource(Dom<MediaSource>),
     Blob(Dom<Blob>),
 }
 
-impl From<MediaStreamOrBlob> for SrcObject {
+impl From<MediaStreamOrMediaSourceOrBlob> for SrcObject {
     #[allow(unrooted_must_root)]
-    fn from(src_object: MediaStreamOrBlob) -> SrcObject {
+    fn from(src_object: MediaStreamOrMediaSourceOrBlob) -> SrcObject {
         match src_object {
-            MediaStreamOrBlob::Blob(blob) => SrcObject::Blob(Dom::from_ref(&*blob)),
-            MediaStreamOrBlob::MediaStream(stream) => {
+            MediaStreamOrMediaSourceOrBlob::Blob(blob) => SrcObject::Blob(Dom::from_ref(&*blob)),
+            MediaStreamOrMediaSourceOrBlob::MediaStream(stream) => {
                 SrcObject::MediaStream(Dom::from_ref(&*stream))
             },
+            MediaStreamOrMediaSourceOrBlob::MediaSource(source) => {
+                SrcObject::MediaSource(Dom::from_ref(&*source))
+            },
         }
     }
 }
@@ -251,6 +259,10 @@ pub struct HTMLMediaElement {
     next_timeupdate_event: Cell<Timespec>,
     /// Latest fetch request context.
     current_fetch_context: DomRefCell<Option<HTMLMediaElementFetchContext>>,
+    /// <https://w3c.github.io/media-source/#mediasource-attach>
+    ///
+    /// The media source attached to this element, if any.
+    media_source: MutNullableDom<MediaSource>,
 }
 
 /// <https://html.spec.whatwg.org/multipage/#dom-media-networkstate>
@@ -313,6 +325,7 @@ impl HTMLMediaElement {
             text_tracks_list: Default::default(),
             next_timeupdate_event: Cell::new(time::get_time() + Duration::milliseconds(250)),
             current_fetch_context: DomRefCell::new(None),
+            media_source: Default::default(),
         }
     }
 
@@ -778,6 +791,12 @@ impl HTMLMediaElement {
         // Step 4.
         match resource {
             Resource::Url(url) => {
+                // https://w3c.github.io/media-source/#dom-url-createobjecturl
+                if let Some(media_source) = window_from_node(self).media_source_for_url(&url) {
+                    self.attach_media_source(&media_source);
+                    return;
+                }
+
                 // Step 4.remote.1.
                 if self.Preload() == "none" && !self.autoplaying.get() {
                     // Step 4.remote.1.1.
@@ -827,6 +846,9 @@ impl HTMLMediaElement {
                                 Some(ServoUrl::parse(&blob_url).expect("infallible"));
                             self.fetch_request(None);
                         },
+                        SrcObject::MediaSource(media_source) => {
+                            self.attach_media_source(media_source);
+                        },
                         SrcObject::MediaStream(_) => {
                             self.queue_dedicated_media_source_failure_steps();
                         },
@@ -836,6 +858,88 @@ impl HTMLMediaElement {
         }
     }
 
+    /// <https://w3c.github.io/media-source/#mediasource-attach>
+    fn attach_media_source(&self, media_source: &MediaSource) {
+        // Step 1.
+        if media_source.ReadyState() != MediaSourceReadyState::Closed {
+            self.queue_dedicated_media_source_failure_steps();
+            return;
+        }
+        // Step 2.
+        // The appended segments are pushed to the player as a single
+        // stream, which it demuxes itself.
+        self.delay_load_event(false);
+        self.media_source.set(Some(media_source));
+        media_source.attach(self);
+    }
+
+    /// Pushes the segments appended to the attached media source to the
+    /// player.
+    pub fn push_media_source_data(&self, data: Vec<u8>) -> Result<(), ()> {
+        match self.player.push_data(data) {
+            // Unlike fetched data, appended segments can't be requested
+            // again later, so the player has to hold on to them anyway.
+            Ok(()) | Err(PlayerError::EnoughData) => Ok(()),
+            Err(e) => {
+                warn!("Could not push media source data to player {:?}", e);
+                Err(())
+            },
+        }
+    }
+
+    /// <https://w3c.github.io/media-source/#end-of-stream-algorithm>
+    ///
+    /// Step 3 of the end of stream algorithm of the attached media source.
+    pub fn media_source_ended(&self, error: Option<EndOfStreamError>) {
+        let code = match error {
+            None => {
+                if let Err(e) = self.player.end_of_stream() {
+                    warn!("Could not signal EOS to player {:?}", e);
+                }
+                return;
+            },
+            Some(EndOfStreamError::Network) => MEDIA_ERR_NETWORK,
+            Some(EndOfStreamError::Decode) => MEDIA_ERR_DECODE,
+        };
+        if self.ready_state.get() == ReadyState::HaveNothing {
+            self.queue_dedicated_media_source_failure_steps();
+            return;
+        }
+        // https://html.spec.whatwg.org/multipage/#media-data-processing-steps-list
+        // => "If the media data is corrupted" and "If the media data fetching
+        //    process is aborted by the user"
+        let window = window_from_node(self);
+        self.error.set(Some(&*MediaError::new(&*window, code)));
+        self.network_state.set(NetworkState::Idle);
+        self.delay_load_event(false);
+        window
+            .task_manager()
+            .media_element_task_source()
+            .queue_simple_event(self.upcast(), atom!("error"), &window);
+    }
+
+    /// <https://w3c.github.io/media-source/#duration-change-algorithm>
+    ///
+    /// Step 5 of the duration change algorithm of the attached media source.
+    pub fn media_source_duration_changed(&self, duration: f64) {
+        self.duration.set(duration);
+        let window = window_from_node(self);
+        window
+            .task_manager()
+            .media_element_task_source()
+            .queue_simple_event(self.upcast(), atom!("durationchange"), &window);
+    }
+
+    pub fn buffered_ranges(&self) -> TimeRangesContainer {
+        let mut buffered = TimeRangesContainer::new();
+        if let Ok(ranges) = self.player.buffered() {
+            for range in ranges {
+                let _ = buffered.add(range.start as f64, range.end as f64);
+            }
+        }
+        buffered
+    }
+
     /// Queues a task to run the [dedicated media source failure steps][steps].
     ///
     /// [steps]: https://html.spec.whatwg.org/multipage/#dedicated-media-source-failure-steps
@@ -959,7 +1063,9 @@ impl HTMLMediaElement {
             }
 
             // Step 6.3.
-            // FIXME(nox): Detach MediaSource media provider object.
+            if let Some(media_source) = self.media_source.take() {
+                media_source.detach();
+            }
 
             // Step 6.4.
             self.AudioTracks().clear();
@@ -1592,12 +1698,17 @@ impl HTMLMediaElementMethods for HTMLMediaElement {
     }
 
     // https://html.spec.whatwg.org/multipage/#dom-media-srcobject
-    fn GetSrcObject(&self) -> Option<MediaStreamOrBlob> {
+    fn GetSrcObject(&self) -> Option<MediaStreamOrMediaSourceOrBlob> {
         match *self.src_object.borrow() {
             Some(ref src_object) => Some(match src_object {
-                SrcObject::Blob(blob) => MediaStreamOrBlob::Blob(DomRoot::from_ref(&*blob)),
+                SrcObject::Blob(blob) => {
+                    MediaStreamOrMediaSourceOrBlob::Blob(DomRoot::from_ref(&*blob))
+                },
                 SrcObject::MediaStream(stream) => {
-                    MediaStreamOrBlob::MediaStream(DomRoot::from_ref(&*stream))
+                    MediaStreamOrMediaSourceOrBlob::MediaStream(DomRoot::from_ref(&*stream))
+                },
+                SrcObject::MediaSource(source) => {
+                    MediaStreamOrMediaSourceOrBlob::MediaSource(DomRoot::from_ref(&*source))
                 },
             }),
             None => None,
@@ -1605,7 +1716,7 @@ impl HTMLMediaElementMethods for HTMLMediaElement {
     }
 
     // https://html.spec.whatwg.org/multipage/#dom-media-srcobject
-    fn SetSrcObject(&self, value: Option<MediaStreamOrBlob>) {
+    fn SetSrcObject(&self, value: Option<MediaStreamOrMediaSourceOrBlob>) {
         *self.src_object.borrow_mut() = value.map(|value| value.into());
         self.media_element_load_algorithm();
     }
@@ -1855,13 +1966,7 @@ impl HTMLMediaElementMethods for HTMLMediaElement {
 
     // https://html.spec.whatwg.org/multipage/#dom-media-buffered
     fn Buffered(&self) -> DomRoot<TimeRanges> {
-        let mut buffered = TimeRangesContainer::new();
-        if let Ok(ranges) = self.player.buffered() {
-            for range in ranges {
-                let _ = buffered.add(range.start as f64, range.end as f64);
-            }
-        }
-        TimeRanges::new(self.global().as_window(), buffered)
+        TimeRanges::new(self.global().as_window(), self.buffered_ranges())
     }
 
     // https://html.spec.whatwg.org/multipage/#dom-media-audiotracks
@@ -56,6 +56,8 @@ pub trait LayoutHTMLTextAreaElementHelpers {
     #[allow(unsafe_code)]
     unsafe fn selection_for_layout(self) -> Option<Range<usize>>;
     #[allow(unsafe_code)]
+    unsafe fn composition_for_layout(self) -> Option<Range<usize>>;
+    #[allow(unsafe_code)]
     fn get_cols(self) -> u32;
     #[allow(unsafe_code)]
     fn get_rows(self) -> u32;
@@ -90,6 +92,18 @@ impl LayoutHTMLTextAreaElementHelpers for LayoutDom<HTMLTextAreaElement> {
         Some(textinput.sorted_selection_offsets_range())
     }
 
+    #[allow(unrooted_must_root)]
+    #[allow(unsafe_code)]
+    unsafe fn composition_for_layout(self) -> Option<Range<usize>> {
+        if !(*self.unsafe_get()).upcast::<Element>().focus_state() {
+            return None;
+        }
+        (*self.unsafe_get())
+            .textinput
+            .borrow_for_layout()
+            .composition()
+    }
+
     #[allow(unsafe_code)]
     fn get_cols(self) -> u32 {
         unsafe {
@@ -581,15 +595,9 @@ impl VirtualMethods for HTMLTextAreaElement {
             event.type_() == atom!("compositionupdate") ||
             event.type_() == atom!("compositionend")
         {
-            // TODO: Update DOM on start and continue
-            // and generally do proper CompositionEvent handling.
             if let Some(compositionevent) = event.downcast::<CompositionEvent>() {
-                if event.type_() == atom!("compositionend") {
-                    let _ = self
-                        .textinput
-                        .borrow_mut()
-                        .handle_compositionend(compositionevent);
-                    self.upcast::<Node>().dirty(NodeDamage::OtherNodeDamage);
+                if self.selection().handle_composition_event(compositionevent) {
+                    self.update_placeholder_shown_state();
                 }
                 event.mark_as_handled();
             }
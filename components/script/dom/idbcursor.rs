@@ -0,0 +1,268 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::IDBCursorBinding::IDBCursorDirection;
+use crate::dom::bindings::codegen::Bindings::IDBCursorBinding::IDBCursorMethods;
+use crate::dom::bindings::error::{Error, ErrorResult, Fallible};
+use crate::dom::bindings::reflector::{DomObject, Reflector};
+use crate::dom::bindings::root::{Dom, DomRoot, MutNullableDom};
+use crate::dom::bindings::structuredclone::StructuredCloneData;
+use crate::dom::idbobjectstore::IDBObjectStore;
+use crate::dom::idbrequest::{IDBRequest, IDBRequestSource};
+use crate::indexed_db::{convert_value_to_key, extract_key, key_type_to_jsval};
+use dom_struct::dom_struct;
+use js::jsapi::{Heap, JSContext};
+use js::jsval::{JSVal, UndefinedValue};
+use js::rust::HandleValue;
+use net_traits::indexeddb_thread::{AsyncOperation, IndexedDBCursorDirection, IndexedDBKeyType};
+use std::cell::Cell;
+
+#[dom_struct]
+pub struct IDBCursor {
+    reflector_: Reflector,
+    source: Dom<IDBObjectStore>,
+    direction: IDBCursorDirection,
+    /// The key the records iterated over must have, if any
+    query: Option<IndexedDBKeyType>,
+    /// https://w3c.github.io/IndexedDB/#cursor-request
+    request: MutNullableDom<IDBRequest>,
+    /// https://w3c.github.io/IndexedDB/#cursor-position
+    position: DomRefCell<Option<IndexedDBKeyType>>,
+    /// https://w3c.github.io/IndexedDB/#cursor-key
+    #[ignore_malloc_size_of = "mozjs"]
+    key: Heap<JSVal>,
+    /// https://w3c.github.io/IndexedDB/#cursor-value
+    #[ignore_malloc_size_of = "mozjs"]
+    value: Heap<JSVal>,
+    /// https://w3c.github.io/IndexedDB/#cursor-got-value-flag
+    got_value: Cell<bool>,
+}
+
+impl IDBCursor {
+    pub fn new_inherited(
+        source: &IDBObjectStore,
+        direction: IDBCursorDirection,
+        query: Option<IndexedDBKeyType>,
+    ) -> IDBCursor {
+        IDBCursor {
+            reflector_: Reflector::new(),
+            source: Dom::from_ref(source),
+            direction: direction,
+            query: query,
+            request: Default::default(),
+            position: DomRefCell::new(None),
+            key: Heap::default(),
+            value: Heap::default(),
+            got_value: Cell::new(false),
+        }
+    }
+
+    pub fn value(&self) -> JSVal {
+        self.value.get()
+    }
+
+    /// Makes the cursor iterate with `request`, and moves it to its first
+    /// record
+    pub fn start(&self, request: &IDBRequest) {
+        request.set_cursor(self);
+        self.request.set(Some(request));
+        self.iterate(None, 1);
+    }
+
+    /// https://w3c.github.io/IndexedDB/#iterate-a-cursor
+    fn iterate(&self, key: Option<IndexedDBKeyType>, count: u32) {
+        // The keys of the records of an object store are unique.
+        let direction = match self.direction {
+            IDBCursorDirection::Next | IDBCursorDirection::Nextunique => {
+                IndexedDBCursorDirection::Next
+            },
+            IDBCursorDirection::Prev | IDBCursorDirection::Prevunique => {
+                IndexedDBCursorDirection::Prev
+            },
+        };
+        let request = self.request.get().unwrap();
+        request.execute(
+            self.source.name(),
+            AsyncOperation::Iterate {
+                query: self.query.clone(),
+                direction: direction,
+                position: self.position.borrow().clone(),
+                key: key,
+                count: count,
+            },
+        );
+    }
+
+    /// https://w3c.github.io/IndexedDB/#iterate-a-cursor
+    /// Steps 7-12.
+    #[allow(unsafe_code)]
+    pub fn set_record(&self, cx: *mut JSContext, record: Option<(IndexedDBKeyType, Vec<u8>)>) {
+        match record {
+            Some((key, bytes)) => {
+                rooted!(in(cx) let mut value = UndefinedValue());
+                unsafe { key_type_to_jsval(cx, &key, value.handle_mut()) };
+                self.key.set(value.get());
+                self.source.read_value(cx, &key, bytes, value.handle_mut());
+                self.value.set(value.get());
+                *self.position.borrow_mut() = Some(key);
+                self.got_value.set(true);
+            },
+            None => {
+                self.key.set(UndefinedValue());
+                self.value.set(UndefinedValue());
+                *self.position.borrow_mut() = None;
+                self.got_value.set(false);
+            },
+        }
+    }
+
+    /// Checks that the cursor is at a record it can change, returning the
+    /// key of that record
+    fn check_writable(&self) -> Fallible<IndexedDBKeyType> {
+        self.source.check_writable()?;
+        if !self.got_value.get() {
+            return Err(Error::InvalidState);
+        }
+        self.position.borrow().clone().ok_or(Error::InvalidState)
+    }
+
+    /// Runs an operation on the source of this cursor in a new request
+    #[allow(unrooted_must_root)]
+    fn execute(&self, operation: AsyncOperation) -> DomRoot<IDBRequest> {
+        let request = IDBRequest::new(
+            &self.global(),
+            IDBRequestSource::Cursor(Dom::from_ref(self)),
+            self.source.transaction(),
+        );
+        request.execute(self.source.name(), operation);
+        request
+    }
+}
+
+impl IDBCursorMethods for IDBCursor {
+    // https://w3c.github.io/IndexedDB/#dom-idbcursor-source
+    fn Source(&self) -> DomRoot<IDBObjectStore> {
+        DomRoot::from_ref(&*self.source)
+    }
+
+    // https://w3c.github.io/IndexedDB/#dom-idbcursor-direction
+    fn Direction(&self) -> IDBCursorDirection {
+        self.direction
+    }
+
+    // https://w3c.github.io/IndexedDB/#dom-idbcursor-key
+    #[allow(unsafe_code)]
+    unsafe fn Key(&self, _cx: *mut JSContext) -> JSVal {
+        self.key.get()
+    }
+
+    // https://w3c.github.io/IndexedDB/#dom-idbcursor-primarykey
+    #[allow(unsafe_code)]
+    unsafe fn PrimaryKey(&self, _cx: *mut JSContext) -> JSVal {
+        // The primary key of a record of an object store is its key.
+        self.key.get()
+    }
+
+    // https://w3c.github.io/IndexedDB/#dom-idbcursor-advance
+    fn Advance(&self, count: u32) -> ErrorResult {
+        // Step 1.
+        if count == 0 {
+            return Err(Error::Type(
+                "The count must be greater than zero".to_string(),
+            ));
+        }
+
+        // Steps 2-3.
+        if !self.source.transaction().is_active() {
+            return Err(Error::TransactionInactive);
+        }
+
+        // Step 5.
+        if !self.got_value.get() {
+            return Err(Error::InvalidState);
+        }
+
+        // Steps 6-10.
+        self.got_value.set(false);
+        self.iterate(None, count);
+        Ok(())
+    }
+
+    // https://w3c.github.io/IndexedDB/#dom-idbcursor-continue
+    #[allow(unsafe_code)]
+    unsafe fn Continue(&self, cx: *mut JSContext, key: HandleValue) -> ErrorResult {
+        // Steps 2-3.
+        if !self.source.transaction().is_active() {
+            return Err(Error::TransactionInactive);
+        }
+
+        // Step 5.
+        if !self.got_value.get() {
+            return Err(Error::InvalidState);
+        }
+
+        // Step 6.
+        let key = if key.is_undefined() {
+            None
+        } else {
+            let key = convert_value_to_key(cx, key, None)?;
+            if let Some(ref position) = *self.position.borrow() {
+                let is_behind = match self.direction {
+                    IDBCursorDirection::Next | IDBCursorDirection::Nextunique => key <= *position,
+                    IDBCursorDirection::Prev | IDBCursorDirection::Prevunique => key >= *position,
+                };
+                if is_behind {
+                    return Err(Error::Data);
+                }
+            }
+            Some(key)
+        };
+
+        // Steps 7-11.
+        self.got_value.set(false);
+        self.iterate(key, 1);
+        Ok(())
+    }
+
+    // https://w3c.github.io/IndexedDB/#dom-idbcursor-update
+    #[allow(unsafe_code)]
+    unsafe fn Update(
+        &self,
+        cx: *mut JSContext,
+        value: HandleValue,
+    ) -> Fallible<DomRoot<IDBRequest>> {
+        // Steps 2-6.
+        let position = self.check_writable()?;
+
+        // Steps 8-9.
+        let bytes = StructuredCloneData::write(cx, value, None)?.move_to_arraybuffer();
+
+        // Step 10.
+        if let Some(key_path) = self.source.key_path() {
+            rooted!(in(cx) let mut clone = UndefinedValue());
+            StructuredCloneData::Vector(bytes.clone()).read(&self.global(), clone.handle_mut());
+            match extract_key(cx, clone.handle(), key_path)? {
+                Some(ref key) if *key == position => {},
+                _ => return Err(Error::Data),
+            }
+        }
+
+        // Steps 11-12.
+        Ok(self.execute(AsyncOperation::Put {
+            key: Some(position),
+            value: bytes,
+            overwrite: true,
+        }))
+    }
+
+    // https://w3c.github.io/IndexedDB/#dom-idbcursor-delete
+    fn Delete(&self) -> Fallible<DomRoot<IDBRequest>> {
+        // Steps 2-6.
+        let position = self.check_writable()?;
+
+        // Steps 7-8.
+        Ok(self.execute(AsyncOperation::Delete(position)))
+    }
+}
@@ -0,0 +1,54 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::IDBCursorBinding::IDBCursorDirection;
+use crate::dom::bindings::codegen::Bindings::IDBCursorWithValueBinding;
+use crate::dom::bindings::codegen::Bindings::IDBCursorWithValueBinding::IDBCursorWithValueMethods;
+use crate::dom::bindings::reflector::reflect_dom_object;
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::idbcursor::IDBCursor;
+use crate::dom::idbobjectstore::IDBObjectStore;
+use dom_struct::dom_struct;
+use js::jsapi::JSContext;
+use js::jsval::JSVal;
+use net_traits::indexeddb_thread::IndexedDBKeyType;
+
+#[dom_struct]
+pub struct IDBCursorWithValue {
+    cursor: IDBCursor,
+}
+
+impl IDBCursorWithValue {
+    fn new_inherited(
+        source: &IDBObjectStore,
+        direction: IDBCursorDirection,
+        query: Option<IndexedDBKeyType>,
+    ) -> IDBCursorWithValue {
+        IDBCursorWithValue {
+            cursor: IDBCursor::new_inherited(source, direction, query),
+        }
+    }
+
+    pub fn new(
+        global: &GlobalScope,
+        source: &IDBObjectStore,
+        direction: IDBCursorDirection,
+        query: Option<IndexedDBKeyType>,
+    ) -> DomRoot<IDBCursorWithValue> {
+        reflect_dom_object(
+            Box::new(IDBCursorWithValue::new_inherited(source, direction, query)),
+            global,
+            IDBCursorWithValueBinding::Wrap,
+        )
+    }
+}
+
+impl IDBCursorWithValueMethods for IDBCursorWithValue {
+    // https://w3c.github.io/IndexedDB/#dom-idbcursorwithvalue-value
+    #[allow(unsafe_code)]
+    unsafe fn Value(&self, _cx: *mut JSContext) -> JSVal {
+        self.cursor.value()
+    }
+}
@@ -0,0 +1,318 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::IDBDatabaseBinding::IDBObjectStoreParameters;
+use crate::dom::bindings::codegen::Bindings::IDBDatabaseBinding::{self, IDBDatabaseMethods};
+use crate::dom::bindings::codegen::Bindings::IDBTransactionBinding::IDBTransactionMethods;
+use crate::dom::bindings::codegen::Bindings::IDBTransactionBinding::IDBTransactionMode;
+use crate::dom::bindings::codegen::UnionTypes::StringOrStringSequence;
+use crate::dom::bindings::error::{Error, ErrorResult, Fallible};
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject};
+use crate::dom::bindings::root::{DomRoot, MutNullableDom};
+use crate::dom::bindings::str::DOMString;
+use crate::dom::domstringlist::DOMStringList;
+use crate::dom::eventtarget::EventTarget;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::idbobjectstore::IDBObjectStore;
+use crate::dom::idbtransaction::{txn_mode, IDBTransaction};
+use crate::indexed_db::is_valid_key_path;
+use dom_struct::dom_struct;
+use net_traits::indexeddb_thread::ObjectStoreMetadata;
+use net_traits::indexeddb_thread::{DatabaseMetadata, IndexedDBThreadMsg, KeyPath};
+use net_traits::IpcSend;
+use profile_traits::ipc;
+use std::cell::Cell;
+
+#[dom_struct]
+pub struct IDBDatabase {
+    eventtarget: EventTarget,
+    name: DOMString,
+    version: Cell<u64>,
+    /// The object stores of the database, sorted by name
+    object_stores: DomRefCell<Vec<ObjectStoreMetadata>>,
+    /// https://w3c.github.io/IndexedDB/#connection-close-pending-flag
+    close_pending: Cell<bool>,
+    /// https://w3c.github.io/IndexedDB/#connection-upgrade-transaction
+    upgrade_transaction: MutNullableDom<IDBTransaction>,
+    /// The version and object stores from before the upgrade transaction,
+    /// which get restored if it aborts
+    old_version: Cell<u64>,
+    old_object_stores: DomRefCell<Vec<ObjectStoreMetadata>>,
+}
+
+impl IDBDatabase {
+    fn new_inherited(name: DOMString, metadata: DatabaseMetadata) -> IDBDatabase {
+        IDBDatabase {
+            eventtarget: EventTarget::new_inherited(),
+            name: name,
+            version: Cell::new(metadata.version),
+            object_stores: DomRefCell::new(metadata.object_stores),
+            close_pending: Cell::new(false),
+            upgrade_transaction: Default::default(),
+            old_version: Cell::new(0),
+            old_object_stores: DomRefCell::new(vec![]),
+        }
+    }
+
+    pub fn new(
+        global: &GlobalScope,
+        name: DOMString,
+        metadata: DatabaseMetadata,
+    ) -> DomRoot<IDBDatabase> {
+        reflect_dom_object(
+            Box::new(IDBDatabase::new_inherited(name, metadata)),
+            global,
+            IDBDatabaseBinding::Wrap,
+        )
+    }
+
+    pub fn object_store_names(&self) -> Vec<DOMString> {
+        self.object_stores
+            .borrow()
+            .iter()
+            .map(|store| DOMString::from(&*store.name))
+            .collect()
+    }
+
+    pub fn object_store_metadata(&self, name: &DOMString) -> Option<ObjectStoreMetadata> {
+        self.object_stores
+            .borrow()
+            .iter()
+            .find(|store| *store.name == **name)
+            .cloned()
+    }
+
+    /// Starts the upgrade transaction of the connection, from the given
+    /// version
+    pub fn start_upgrade(&self, transaction: &IDBTransaction, old_version: u64) {
+        self.upgrade_transaction.set(Some(transaction));
+        self.old_version.set(old_version);
+        *self.old_object_stores.borrow_mut() = self.object_stores.borrow().clone();
+    }
+
+    /// https://w3c.github.io/IndexedDB/#abort-an-upgrade-transaction
+    pub fn abort_upgrade(&self) {
+        // Steps 1-2.
+        self.close_pending.set(true);
+
+        // Steps 3-4.
+        self.version.set(self.old_version.get());
+        *self.object_stores.borrow_mut() = self.old_object_stores.borrow().clone();
+    }
+
+    /// The upgrade transaction of the connection, if it did not finish
+    fn live_upgrade_transaction(&self) -> Option<DomRoot<IDBTransaction>> {
+        self.upgrade_transaction
+            .get()
+            .filter(|transaction| !transaction.is_finished())
+    }
+}
+
+/// The key path an object store got created with
+fn key_path_from_union(key_path: &StringOrStringSequence) -> KeyPath {
+    match *key_path {
+        StringOrStringSequence::String(ref path) => KeyPath::String(path.to_string()),
+        StringOrStringSequence::StringSequence(ref paths) => {
+            KeyPath::Sequence(paths.iter().map(|path| path.to_string()).collect())
+        },
+    }
+}
+
+impl IDBDatabaseMethods for IDBDatabase {
+    // https://w3c.github.io/IndexedDB/#dom-idbdatabase-name
+    fn Name(&self) -> DOMString {
+        self.name.clone()
+    }
+
+    // https://w3c.github.io/IndexedDB/#dom-idbdatabase-version
+    fn Version(&self) -> u64 {
+        self.version.get()
+    }
+
+    // https://w3c.github.io/IndexedDB/#dom-idbdatabase-objectstorenames
+    fn ObjectStoreNames(&self) -> DomRoot<DOMStringList> {
+        DOMStringList::new(&self.global(), self.object_store_names())
+    }
+
+    // https://w3c.github.io/IndexedDB/#dom-idbdatabase-transaction
+    fn Transaction(
+        &self,
+        store_names: StringOrStringSequence,
+        mode: IDBTransactionMode,
+    ) -> Fallible<DomRoot<IDBTransaction>> {
+        // Step 1.
+        if self.live_upgrade_transaction().is_some() {
+            return Err(Error::InvalidState);
+        }
+
+        // Step 2.
+        if self.close_pending.get() {
+            return Err(Error::InvalidState);
+        }
+
+        // Steps 3-4.
+        let mut scope = match store_names {
+            StringOrStringSequence::String(name) => vec![name],
+            StringOrStringSequence::StringSequence(names) => names,
+        };
+        scope.sort();
+        scope.dedup();
+        let stores = self.object_store_names();
+        if scope.iter().any(|name| !stores.contains(name)) {
+            return Err(Error::NotFound);
+        }
+
+        // Step 5.
+        if scope.is_empty() {
+            return Err(Error::InvalidAccess);
+        }
+
+        // Step 6.
+        if mode == IDBTransactionMode::Versionchange {
+            return Err(Error::Type(
+                "Transactions cannot be created in the versionchange mode".to_string(),
+            ));
+        }
+
+        // Steps 7-8.
+        let global = self.global();
+        let (sender, receiver) = ipc::channel(global.time_profiler_chan().clone()).unwrap();
+        global
+            .resource_threads()
+            .send(IndexedDBThreadMsg::CreateTransaction(
+                sender,
+                global.origin().immutable().clone(),
+                self.name.to_string(),
+                txn_mode(mode),
+                scope.iter().map(|name| name.to_string()).collect(),
+            ))
+            .unwrap();
+        let id = receiver.recv().unwrap();
+        Ok(IDBTransaction::new(&global, self, id, mode, scope))
+    }
+
+    // https://w3c.github.io/IndexedDB/#dom-idbdatabase-close
+    fn Close(&self) {
+        // FIXME: The IndexedDB thread does not wait for the closed
+        // connections when opening or deleting a database yet.
+        self.close_pending.set(true);
+    }
+
+    // https://w3c.github.io/IndexedDB/#dom-idbdatabase-createobjectstore
+    fn CreateObjectStore(
+        &self,
+        name: DOMString,
+        options: &IDBObjectStoreParameters,
+    ) -> Fallible<DomRoot<IDBObjectStore>> {
+        // Steps 2-4.
+        let transaction = match self.live_upgrade_transaction() {
+            Some(transaction) => transaction,
+            None => return Err(Error::InvalidState),
+        };
+
+        // Step 5.
+        if !transaction.is_active() {
+            return Err(Error::TransactionInactive);
+        }
+
+        // Steps 6-7.
+        let key_path = options.keyPath.as_ref().map(key_path_from_union);
+        if let Some(ref key_path) = key_path {
+            if !is_valid_key_path(key_path) {
+                return Err(Error::Syntax);
+            }
+        }
+
+        // Step 8.
+        if self.object_store_metadata(&name).is_some() {
+            return Err(Error::Constraint);
+        }
+
+        // Step 9.
+        let auto_increment = options.autoIncrement;
+        let is_empty_or_sequence = match key_path {
+            Some(KeyPath::String(ref path)) => path.is_empty(),
+            Some(KeyPath::Sequence(_)) => true,
+            None => false,
+        };
+        if auto_increment && is_empty_or_sequence {
+            return Err(Error::InvalidAccess);
+        }
+
+        // Step 10.
+        let global = self.global();
+        global
+            .resource_threads()
+            .send(IndexedDBThreadMsg::CreateObjectStore {
+                origin: global.origin().immutable().clone(),
+                db_name: self.name.to_string(),
+                txn: transaction.id(),
+                store_name: name.to_string(),
+                key_path: key_path.clone(),
+                auto_increment: auto_increment,
+            })
+            .unwrap();
+        {
+            let mut object_stores = self.object_stores.borrow_mut();
+            object_stores.push(ObjectStoreMetadata {
+                name: name.to_string(),
+                key_path: key_path,
+                auto_increment: auto_increment,
+            });
+            object_stores.sort_by(|a, b| a.name.cmp(&b.name));
+        }
+
+        // Steps 11-12.
+        transaction.ObjectStore(name)
+    }
+
+    // https://w3c.github.io/IndexedDB/#dom-idbdatabase-deleteobjectstore
+    fn DeleteObjectStore(&self, name: DOMString) -> ErrorResult {
+        // Steps 2-4.
+        let transaction = match self.live_upgrade_transaction() {
+            Some(transaction) => transaction,
+            None => return Err(Error::InvalidState),
+        };
+
+        // Step 5.
+        if !transaction.is_active() {
+            return Err(Error::TransactionInactive);
+        }
+
+        // Step 6.
+        if self.object_store_metadata(&name).is_none() {
+            return Err(Error::NotFound);
+        }
+
+        // Steps 7-8.
+        self.object_stores
+            .borrow_mut()
+            .retain(|store| *store.name != *name);
+        transaction.forget_object_store(&name);
+        let global = self.global();
+        global
+            .resource_threads()
+            .send(IndexedDBThreadMsg::DeleteObjectStore {
+                origin: global.origin().immutable().clone(),
+                db_name: self.name.to_string(),
+                txn: transaction.id(),
+                store_name: name.to_string(),
+            })
+            .unwrap();
+        Ok(())
+    }
+
+    // https://w3c.github.io/IndexedDB/#dom-idbdatabase-onabort
+    event_handler!(abort, GetOnabort, SetOnabort);
+
+    // https://w3c.github.io/IndexedDB/#dom-idbdatabase-onclose
+    event_handler!(close, GetOnclose, SetOnclose);
+
+    // https://w3c.github.io/IndexedDB/#dom-idbdatabase-onerror
+    event_handler!(error, GetOnerror, SetOnerror);
+
+    // https://w3c.github.io/IndexedDB/#dom-idbdatabase-onversionchange
+    event_handler!(versionchange, GetOnversionchange, SetOnversionchange);
+}
@@ -0,0 +1,95 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::IDBFactoryBinding;
+use crate::dom::bindings::codegen::Bindings::IDBFactoryBinding::IDBFactoryMethods;
+use crate::dom::bindings::error::{Error, Fallible};
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::str::DOMString;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::idbopendbrequest::IDBOpenDBRequest;
+use crate::indexed_db::convert_value_to_key;
+use dom_struct::dom_struct;
+use js::jsapi::JSContext;
+use js::rust::HandleValue;
+use std::cmp::Ordering;
+
+#[dom_struct]
+pub struct IDBFactory {
+    reflector_: Reflector,
+}
+
+impl IDBFactory {
+    pub fn new_inherited() -> IDBFactory {
+        IDBFactory {
+            reflector_: Reflector::new(),
+        }
+    }
+
+    pub fn new(global: &GlobalScope) -> DomRoot<IDBFactory> {
+        reflect_dom_object(
+            Box::new(IDBFactory::new_inherited()),
+            global,
+            IDBFactoryBinding::Wrap,
+        )
+    }
+}
+
+impl IDBFactoryMethods for IDBFactory {
+    // https://w3c.github.io/IndexedDB/#dom-idbfactory-open
+    fn Open(&self, name: DOMString, version: Option<u64>) -> Fallible<DomRoot<IDBOpenDBRequest>> {
+        // Step 1.
+        if version == Some(0) {
+            return Err(Error::Type(
+                "The version of a database must be greater than zero".to_string(),
+            ));
+        }
+
+        // Steps 2-3.
+        let global = self.global();
+        if !global.origin().is_tuple() {
+            return Err(Error::Security);
+        }
+
+        // Steps 4-6.
+        let request = IDBOpenDBRequest::new(&global);
+        request.open(name.into(), version);
+        Ok(request)
+    }
+
+    // https://w3c.github.io/IndexedDB/#dom-idbfactory-deletedatabase
+    fn DeleteDatabase(&self, name: DOMString) -> Fallible<DomRoot<IDBOpenDBRequest>> {
+        // Steps 1-2.
+        let global = self.global();
+        if !global.origin().is_tuple() {
+            return Err(Error::Security);
+        }
+
+        // Steps 3-5.
+        let request = IDBOpenDBRequest::new(&global);
+        request.delete(name.into());
+        Ok(request)
+    }
+
+    // https://w3c.github.io/IndexedDB/#dom-idbfactory-cmp
+    #[allow(unsafe_code)]
+    unsafe fn Cmp(
+        &self,
+        cx: *mut JSContext,
+        first: HandleValue,
+        second: HandleValue,
+    ) -> Fallible<i16> {
+        // Steps 1-4.
+        let first = convert_value_to_key(cx, first, None)?;
+        let second = convert_value_to_key(cx, second, None)?;
+
+        // Step 5.
+        Ok(match first.cmp(&second) {
+            Ordering::Less => -1,
+            Ordering::Equal => 0,
+            Ordering::Greater => 1,
+        })
+    }
+}
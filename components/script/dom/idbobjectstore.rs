@@ -0,0 +1,332 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::IDBCursorBinding::IDBCursorDirection;
+use crate::dom::bindings::codegen::Bindings::IDBObjectStoreBinding::{self, IDBObjectStoreMethods};
+use crate::dom::bindings::codegen::Bindings::IDBTransactionBinding::IDBTransactionMode;
+use crate::dom::bindings::error::{Error, Fallible};
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::bindings::str::DOMString;
+use crate::dom::bindings::structuredclone::StructuredCloneData;
+use crate::dom::domstringlist::DOMStringList;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::idbcursor::IDBCursor;
+use crate::dom::idbcursorwithvalue::IDBCursorWithValue;
+use crate::dom::idbrequest::{IDBRequest, IDBRequestSource};
+use crate::dom::idbtransaction::IDBTransaction;
+use crate::indexed_db::inject_key_into_value;
+use crate::indexed_db::{can_inject_key_into_value, convert_value_to_key, extract_key};
+use dom_struct::dom_struct;
+use js::conversions::ToJSValConvertible;
+use js::jsapi::JSContext;
+use js::jsval::{JSVal, NullValue, UndefinedValue};
+use js::rust::{HandleValue, MutableHandleValue};
+use net_traits::indexeddb_thread::ObjectStoreMetadata;
+use net_traits::indexeddb_thread::{AsyncOperation, IndexedDBKeyType, KeyPath};
+
+#[dom_struct]
+pub struct IDBObjectStore {
+    reflector_: Reflector,
+    name: DOMString,
+    transaction: Dom<IDBTransaction>,
+    key_path: Option<KeyPath>,
+    auto_increment: bool,
+}
+
+impl IDBObjectStore {
+    fn new_inherited(
+        transaction: &IDBTransaction,
+        metadata: ObjectStoreMetadata,
+    ) -> IDBObjectStore {
+        IDBObjectStore {
+            reflector_: Reflector::new(),
+            name: DOMString::from(metadata.name),
+            transaction: Dom::from_ref(transaction),
+            key_path: metadata.key_path,
+            auto_increment: metadata.auto_increment,
+        }
+    }
+
+    pub fn new(
+        global: &GlobalScope,
+        transaction: &IDBTransaction,
+        metadata: ObjectStoreMetadata,
+    ) -> DomRoot<IDBObjectStore> {
+        reflect_dom_object(
+            Box::new(IDBObjectStore::new_inherited(transaction, metadata)),
+            global,
+            IDBObjectStoreBinding::Wrap,
+        )
+    }
+
+    pub fn name(&self) -> &DOMString {
+        &self.name
+    }
+
+    pub fn key_path(&self) -> Option<&KeyPath> {
+        self.key_path.as_ref()
+    }
+
+    pub fn transaction(&self) -> &IDBTransaction {
+        &self.transaction
+    }
+
+    /// Reads the value of a record, injecting its key in it if the key got
+    /// generated
+    ///
+    /// https://w3c.github.io/IndexedDB/#store-a-record-into-an-object-store
+    /// Step 2.
+    #[allow(unsafe_code)]
+    pub fn read_value(
+        &self,
+        cx: *mut JSContext,
+        key: &IndexedDBKeyType,
+        bytes: Vec<u8>,
+        mut rval: MutableHandleValue,
+    ) {
+        rooted!(in(cx) let mut value = UndefinedValue());
+        StructuredCloneData::Vector(bytes).read(&self.global(), value.handle_mut());
+        if let Some(KeyPath::String(ref key_path)) = self.key_path {
+            if self.auto_increment {
+                // The key was checked to be injectable when storing the value.
+                let _ = unsafe { inject_key_into_value(cx, value.handle(), key, key_path) };
+            }
+        }
+        rval.set(value.get());
+    }
+
+    #[allow(unrooted_must_root)]
+    fn new_request(&self) -> DomRoot<IDBRequest> {
+        IDBRequest::new(
+            &self.global(),
+            IDBRequestSource::ObjectStore(Dom::from_ref(self)),
+            &self.transaction,
+        )
+    }
+
+    /// Runs an operation on this object store in a new request
+    fn execute(&self, operation: AsyncOperation) -> DomRoot<IDBRequest> {
+        let request = self.new_request();
+        request.execute(&self.name, operation);
+        request
+    }
+
+    /// Checks that the transaction of this object store can be used to
+    /// change it
+    pub fn check_writable(&self) -> Fallible<()> {
+        if !self.transaction.is_active() {
+            return Err(Error::TransactionInactive);
+        }
+        if self.transaction.mode() == IDBTransactionMode::Readonly {
+            return Err(Error::ReadOnly);
+        }
+        Ok(())
+    }
+
+    /// https://w3c.github.io/IndexedDB/#add-or-put
+    #[allow(unsafe_code)]
+    unsafe fn put(
+        &self,
+        cx: *mut JSContext,
+        value: HandleValue,
+        key: HandleValue,
+        overwrite: bool,
+    ) -> Fallible<DomRoot<IDBRequest>> {
+        // Steps 5-6.
+        self.check_writable()?;
+
+        // Step 7.
+        if self.key_path.is_some() && !key.is_undefined() {
+            return Err(Error::Data);
+        }
+
+        // Step 8.
+        if self.key_path.is_none() && !self.auto_increment && key.is_undefined() {
+            return Err(Error::Data);
+        }
+
+        // Step 9.
+        let mut key = if key.is_undefined() {
+            None
+        } else {
+            Some(convert_value_to_key(cx, key, None)?)
+        };
+
+        // Steps 10-11.
+        let bytes = StructuredCloneData::write(cx, value, None)?.move_to_arraybuffer();
+
+        // Step 12.
+        if let Some(ref key_path) = self.key_path {
+            rooted!(in(cx) let mut clone = UndefinedValue());
+            StructuredCloneData::Vector(bytes.clone()).read(&self.global(), clone.handle_mut());
+            key = extract_key(cx, clone.handle(), key_path)?;
+            if key.is_none() {
+                if !self.auto_increment {
+                    return Err(Error::Data);
+                }
+                if let KeyPath::String(ref path) = *key_path {
+                    if !can_inject_key_into_value(cx, clone.handle(), path)? {
+                        return Err(Error::Data);
+                    }
+                }
+            }
+        }
+
+        // Steps 13-15.
+        Ok(self.execute(AsyncOperation::Put {
+            key: key,
+            value: bytes,
+            overwrite: overwrite,
+        }))
+    }
+}
+
+impl IDBObjectStoreMethods for IDBObjectStore {
+    // https://w3c.github.io/IndexedDB/#dom-idbobjectstore-name
+    fn Name(&self) -> DOMString {
+        self.name.clone()
+    }
+
+    // https://w3c.github.io/IndexedDB/#dom-idbobjectstore-keypath
+    #[allow(unsafe_code)]
+    unsafe fn KeyPath(&self, cx: *mut JSContext) -> JSVal {
+        rooted!(in(cx) let mut key_path = NullValue());
+        match self.key_path {
+            Some(KeyPath::String(ref path)) => path.to_jsval(cx, key_path.handle_mut()),
+            Some(KeyPath::Sequence(ref paths)) => paths.to_jsval(cx, key_path.handle_mut()),
+            None => {},
+        }
+        key_path.get()
+    }
+
+    // https://w3c.github.io/IndexedDB/#dom-idbobjectstore-indexnames
+    fn IndexNames(&self) -> DomRoot<DOMStringList> {
+        DOMStringList::new(&self.global(), vec![])
+    }
+
+    // https://w3c.github.io/IndexedDB/#dom-idbobjectstore-transaction
+    fn Transaction(&self) -> DomRoot<IDBTransaction> {
+        DomRoot::from_ref(&*self.transaction)
+    }
+
+    // https://w3c.github.io/IndexedDB/#dom-idbobjectstore-autoincrement
+    fn AutoIncrement(&self) -> bool {
+        self.auto_increment
+    }
+
+    // https://w3c.github.io/IndexedDB/#dom-idbobjectstore-put
+    #[allow(unsafe_code)]
+    unsafe fn Put(
+        &self,
+        cx: *mut JSContext,
+        value: HandleValue,
+        key: HandleValue,
+    ) -> Fallible<DomRoot<IDBRequest>> {
+        self.put(cx, value, key, true)
+    }
+
+    // https://w3c.github.io/IndexedDB/#dom-idbobjectstore-add
+    #[allow(unsafe_code)]
+    unsafe fn Add(
+        &self,
+        cx: *mut JSContext,
+        value: HandleValue,
+        key: HandleValue,
+    ) -> Fallible<DomRoot<IDBRequest>> {
+        self.put(cx, value, key, false)
+    }
+
+    // https://w3c.github.io/IndexedDB/#dom-idbobjectstore-delete
+    #[allow(unsafe_code)]
+    unsafe fn Delete(
+        &self,
+        cx: *mut JSContext,
+        query: HandleValue,
+    ) -> Fallible<DomRoot<IDBRequest>> {
+        // Steps 4-5.
+        self.check_writable()?;
+
+        // Step 6.
+        let key = convert_value_to_key(cx, query, None)?;
+
+        // Step 7.
+        Ok(self.execute(AsyncOperation::Delete(key)))
+    }
+
+    // https://w3c.github.io/IndexedDB/#dom-idbobjectstore-clear
+    fn Clear(&self) -> Fallible<DomRoot<IDBRequest>> {
+        // Steps 4-5.
+        self.check_writable()?;
+
+        // Step 6.
+        Ok(self.execute(AsyncOperation::Clear))
+    }
+
+    // https://w3c.github.io/IndexedDB/#dom-idbobjectstore-get
+    #[allow(unsafe_code)]
+    unsafe fn Get(&self, cx: *mut JSContext, query: HandleValue) -> Fallible<DomRoot<IDBRequest>> {
+        // Step 4.
+        if !self.transaction.is_active() {
+            return Err(Error::TransactionInactive);
+        }
+
+        // Step 5.
+        let key = convert_value_to_key(cx, query, None)?;
+
+        // Step 6.
+        Ok(self.execute(AsyncOperation::Get(key)))
+    }
+
+    // https://w3c.github.io/IndexedDB/#dom-idbobjectstore-count
+    #[allow(unsafe_code)]
+    unsafe fn Count(
+        &self,
+        cx: *mut JSContext,
+        query: HandleValue,
+    ) -> Fallible<DomRoot<IDBRequest>> {
+        // Step 4.
+        if !self.transaction.is_active() {
+            return Err(Error::TransactionInactive);
+        }
+
+        // Step 5.
+        let key = if query.is_null_or_undefined() {
+            None
+        } else {
+            Some(convert_value_to_key(cx, query, None)?)
+        };
+
+        // Step 6.
+        Ok(self.execute(AsyncOperation::Count(key)))
+    }
+
+    // https://w3c.github.io/IndexedDB/#dom-idbobjectstore-opencursor
+    #[allow(unsafe_code)]
+    unsafe fn OpenCursor(
+        &self,
+        cx: *mut JSContext,
+        query: HandleValue,
+        direction: IDBCursorDirection,
+    ) -> Fallible<DomRoot<IDBRequest>> {
+        // Step 4.
+        if !self.transaction.is_active() {
+            return Err(Error::TransactionInactive);
+        }
+
+        // Step 5.
+        let query = if query.is_null_or_undefined() {
+            None
+        } else {
+            Some(convert_value_to_key(cx, query, None)?)
+        };
+
+        // Steps 6-10.
+        let cursor = IDBCursorWithValue::new(&self.global(), self, direction, query);
+        let request = self.new_request();
+        cursor.upcast::<IDBCursor>().start(&request);
+        Ok(request)
+    }
+}
@@ -0,0 +1,183 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::IDBOpenDBRequestBinding;
+use crate::dom::bindings::codegen::Bindings::IDBOpenDBRequestBinding::IDBOpenDBRequestMethods;
+use crate::dom::bindings::codegen::Bindings::IDBTransactionBinding::IDBTransactionMode;
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::str::DOMString;
+use crate::dom::domexception::{DOMErrorName, DOMException};
+use crate::dom::event::{Event, EventBubbles, EventCancelable};
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::idbdatabase::IDBDatabase;
+use crate::dom::idbrequest::{backend_error_name, IDBRequest};
+use crate::dom::idbtransaction::IDBTransaction;
+use crate::dom::idbversionchangeevent::IDBVersionChangeEvent;
+use crate::indexed_db::route_reply;
+use dom_struct::dom_struct;
+use js::jsval::{ObjectValue, UndefinedValue};
+use net_traits::indexeddb_thread::{BackendError, IndexedDBThreadMsg, OpenResult};
+use net_traits::IpcSend;
+
+#[dom_struct]
+pub struct IDBOpenDBRequest {
+    request: IDBRequest,
+}
+
+impl IDBOpenDBRequest {
+    fn new_inherited() -> IDBOpenDBRequest {
+        IDBOpenDBRequest {
+            request: IDBRequest::new_inherited(None, None),
+        }
+    }
+
+    pub fn new(global: &GlobalScope) -> DomRoot<IDBOpenDBRequest> {
+        reflect_dom_object(
+            Box::new(IDBOpenDBRequest::new_inherited()),
+            global,
+            IDBOpenDBRequestBinding::Wrap,
+        )
+    }
+
+    /// https://w3c.github.io/IndexedDB/#open-a-database
+    pub fn open(&self, name: String, version: Option<u64>) {
+        // FIXME: The other connections to the database are not sent
+        // versionchange events, so the request is never blocked.
+        let global = self.global();
+        let db_name = name.clone();
+        let sender = route_reply(self, move |request: &IDBOpenDBRequest, result| {
+            request.handle_open_result(db_name, result);
+        });
+        global
+            .resource_threads()
+            .send(IndexedDBThreadMsg::Open(
+                sender,
+                global.origin().immutable().clone(),
+                name,
+                version,
+            ))
+            .unwrap();
+    }
+
+    /// https://w3c.github.io/IndexedDB/#dom-idbfactory-open
+    /// Step 6.
+    fn handle_open_result(&self, name: String, result: OpenResult) {
+        let global = self.global();
+        match result {
+            OpenResult::Success(metadata) => {
+                let db = IDBDatabase::new(&global, DOMString::from(name), metadata);
+                self.request
+                    .set_result(ObjectValue(db.reflector().get_jsobject().get()));
+                self.request.fire_success();
+            },
+            OpenResult::Upgrade {
+                transaction,
+                old_version,
+                metadata,
+            } => {
+                // https://w3c.github.io/IndexedDB/#upgrade-a-database
+                // Steps 1-6.
+                let new_version = metadata.version;
+                let db = IDBDatabase::new(&global, DOMString::from(name), metadata);
+                let transaction = IDBTransaction::new(
+                    &global,
+                    &db,
+                    transaction,
+                    IDBTransactionMode::Versionchange,
+                    vec![],
+                );
+                db.start_upgrade(&transaction, old_version);
+                transaction.set_open_request(self);
+
+                // Steps 7-9.
+                self.request
+                    .set_result(ObjectValue(db.reflector().get_jsobject().get()));
+                self.request.set_transaction(Some(&transaction));
+
+                // Step 10.
+                let event = IDBVersionChangeEvent::new(
+                    &global,
+                    atom!("upgradeneeded"),
+                    EventBubbles::DoesNotBubble,
+                    EventCancelable::NotCancelable,
+                    old_version,
+                    Some(new_version),
+                );
+                transaction.set_active(true);
+                event.upcast::<Event>().fire(self.upcast());
+                transaction.set_active(false);
+                transaction.maybe_commit();
+            },
+            OpenResult::Error(error) => {
+                self.request
+                    .set_error(&DOMException::new(&global, backend_error_name(error)));
+                self.request.fire_error();
+            },
+        }
+    }
+
+    /// https://w3c.github.io/IndexedDB/#open-a-database
+    /// Steps 10.4-10.5, once the upgrade transaction finished.
+    pub fn upgrade_done(&self, committed: bool) {
+        self.request.set_transaction(None);
+        if committed {
+            self.request.fire_success();
+        } else {
+            self.request
+                .set_error(&DOMException::new(&self.global(), DOMErrorName::AbortError));
+            self.request.fire_error();
+        }
+    }
+
+    /// https://w3c.github.io/IndexedDB/#delete-a-database
+    pub fn delete(&self, name: String) {
+        let global = self.global();
+        let sender = route_reply(self, |request: &IDBOpenDBRequest, result| {
+            request.handle_delete_result(result);
+        });
+        global
+            .resource_threads()
+            .send(IndexedDBThreadMsg::DeleteDatabase(
+                sender,
+                global.origin().immutable().clone(),
+                name,
+            ))
+            .unwrap();
+    }
+
+    /// https://w3c.github.io/IndexedDB/#dom-idbfactory-deletedatabase
+    /// Step 5.
+    fn handle_delete_result(&self, result: Result<u64, BackendError>) {
+        let global = self.global();
+        match result {
+            Ok(old_version) => {
+                self.request.set_result(UndefinedValue());
+                let event = IDBVersionChangeEvent::new(
+                    &global,
+                    atom!("success"),
+                    EventBubbles::DoesNotBubble,
+                    EventCancelable::NotCancelable,
+                    old_version,
+                    None,
+                );
+                event.upcast::<Event>().fire(self.upcast());
+            },
+            Err(error) => {
+                self.request
+                    .set_error(&DOMException::new(&global, backend_error_name(error)));
+                self.request.fire_error();
+            },
+        }
+    }
+}
+
+impl IDBOpenDBRequestMethods for IDBOpenDBRequest {
+    // https://w3c.github.io/IndexedDB/#dom-idbopendbrequest-onblocked
+    event_handler!(blocked, GetOnblocked, SetOnblocked);
+
+    // https://w3c.github.io/IndexedDB/#dom-idbopendbrequest-onupgradeneeded
+    event_handler!(upgradeneeded, GetOnupgradeneeded, SetOnupgradeneeded);
+}
@@ -0,0 +1,280 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::IDBRequestBinding::IDBRequestReadyState;
+use crate::dom::bindings::codegen::Bindings::IDBRequestBinding::{self, IDBRequestMethods};
+use crate::dom::bindings::codegen::UnionTypes::IDBObjectStoreOrIDBCursor;
+use crate::dom::bindings::error::{Error, Fallible};
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject};
+use crate::dom::bindings::root::{Dom, DomRoot, MutNullableDom};
+use crate::dom::domexception::{DOMErrorName, DOMException};
+use crate::dom::event::{Event, EventBubbles, EventCancelable, EventStatus};
+use crate::dom::eventtarget::EventTarget;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::idbcursor::IDBCursor;
+use crate::dom::idbobjectstore::IDBObjectStore;
+use crate::dom::idbtransaction::IDBTransaction;
+use crate::indexed_db::{key_type_to_jsval, route_reply};
+use dom_struct::dom_struct;
+use js::jsapi::{Heap, JSAutoCompartment, JSContext};
+use js::jsval::{DoubleValue, JSVal, NullValue, ObjectValue, UndefinedValue};
+use js::rust::MutableHandleValue;
+use net_traits::indexeddb_thread::{AsyncOperation, BackendError, IndexedDBResult};
+use std::cell::Cell;
+
+/// The object a request is made against
+///
+/// https://w3c.github.io/IndexedDB/#request-source
+#[derive(JSTraceable, MallocSizeOf)]
+#[must_root]
+pub enum IDBRequestSource {
+    ObjectStore(Dom<IDBObjectStore>),
+    Cursor(Dom<IDBCursor>),
+}
+
+#[dom_struct]
+pub struct IDBRequest {
+    eventtarget: EventTarget,
+    #[ignore_malloc_size_of = "mozjs"]
+    result: Heap<JSVal>,
+    error: MutNullableDom<DOMException>,
+    source: Option<IDBRequestSource>,
+    transaction: MutNullableDom<IDBTransaction>,
+    ready_state: Cell<IDBRequestReadyState>,
+    /// The cursor that iterates with this request, if any
+    cursor: MutNullableDom<IDBCursor>,
+}
+
+impl IDBRequest {
+    #[allow(unrooted_must_root)]
+    pub fn new_inherited(
+        source: Option<IDBRequestSource>,
+        transaction: Option<&IDBTransaction>,
+    ) -> IDBRequest {
+        IDBRequest {
+            eventtarget: EventTarget::new_inherited(),
+            result: Heap::default(),
+            error: Default::default(),
+            source: source,
+            transaction: MutNullableDom::new(transaction),
+            ready_state: Cell::new(IDBRequestReadyState::Pending),
+            cursor: Default::default(),
+        }
+    }
+
+    #[allow(unrooted_must_root)]
+    pub fn new(
+        global: &GlobalScope,
+        source: IDBRequestSource,
+        transaction: &IDBTransaction,
+    ) -> DomRoot<IDBRequest> {
+        reflect_dom_object(
+            Box::new(IDBRequest::new_inherited(Some(source), Some(transaction))),
+            global,
+            IDBRequestBinding::Wrap,
+        )
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.ready_state.get() == IDBRequestReadyState::Done
+    }
+
+    pub fn transaction(&self) -> Option<DomRoot<IDBTransaction>> {
+        self.transaction.get()
+    }
+
+    pub fn set_transaction(&self, transaction: Option<&IDBTransaction>) {
+        self.transaction.set(transaction);
+    }
+
+    pub fn set_cursor(&self, cursor: &IDBCursor) {
+        self.cursor.set(Some(cursor));
+    }
+
+    /// Sets the result of the request, and marks it as done
+    pub fn set_result(&self, result: JSVal) {
+        self.ready_state.set(IDBRequestReadyState::Done);
+        self.result.set(result);
+        self.error.set(None);
+    }
+
+    /// Sets the error of the request, and marks it as done
+    pub fn set_error(&self, error: &DOMException) {
+        self.ready_state.set(IDBRequestReadyState::Done);
+        self.result.set(UndefinedValue());
+        self.error.set(Some(error));
+    }
+
+    /// https://w3c.github.io/IndexedDB/#asynchronously-execute-a-request
+    /// Runs `operation` on the object store called `store_name`, in the
+    /// transaction of this request.
+    pub fn execute(&self, store_name: &str, operation: AsyncOperation) {
+        // Steps 3-4.
+        self.ready_state.set(IDBRequestReadyState::Pending);
+        self.result.set(UndefinedValue());
+        self.error.set(None);
+
+        // Step 5.
+        let transaction = self.transaction.get().unwrap();
+        transaction.add_request(self);
+        let sender = route_reply(self, |request: &IDBRequest, result| {
+            request.handle_result(result);
+        });
+        transaction.send_request(sender, store_name, operation);
+    }
+
+    /// https://w3c.github.io/IndexedDB/#asynchronously-execute-a-request
+    /// Step 5.
+    fn handle_result(&self, result: Result<IndexedDBResult, BackendError>) {
+        let transaction = self.transaction.get().unwrap();
+        // The request already failed if its transaction got aborted.
+        if transaction.is_finished() || !transaction.remove_request(self) {
+            return;
+        }
+
+        let global = self.global();
+        match result {
+            Ok(result) => {
+                let cx = global.get_cx();
+                let _ac = JSAutoCompartment::new(cx, global.reflector().get_jsobject().get());
+                rooted!(in(cx) let mut value = UndefinedValue());
+                self.result_to_jsval(cx, result, value.handle_mut());
+                self.set_result(value.get());
+                self.fire_success();
+            },
+            Err(error) => {
+                let error = DOMException::new(&global, backend_error_name(error));
+                self.set_error(&error);
+                self.fire_error();
+            },
+        }
+        transaction.maybe_commit();
+    }
+
+    #[allow(unsafe_code)]
+    fn result_to_jsval(
+        &self,
+        cx: *mut JSContext,
+        result: IndexedDBResult,
+        mut rval: MutableHandleValue,
+    ) {
+        match result {
+            IndexedDBResult::Key(key) => unsafe { key_type_to_jsval(cx, &key, rval) },
+            IndexedDBResult::Count(count) => rval.set(DoubleValue(count as f64)),
+            IndexedDBResult::Record(record) => {
+                if let Some(cursor) = self.cursor.get() {
+                    let found = record.is_some();
+                    cursor.set_record(cx, record);
+                    if found {
+                        rval.set(ObjectValue(cursor.reflector().get_jsobject().get()));
+                    } else {
+                        rval.set(NullValue());
+                    }
+                } else if let Some((key, bytes)) = record {
+                    if let Some(IDBRequestSource::ObjectStore(ref store)) = self.source {
+                        store.read_value(cx, &key, bytes, rval);
+                    }
+                }
+            },
+            IndexedDBResult::Undefined => rval.set(UndefinedValue()),
+        }
+    }
+
+    /// https://w3c.github.io/IndexedDB/#fire-a-success-event
+    pub fn fire_success(&self) {
+        let event = Event::new(
+            &self.global(),
+            atom!("success"),
+            EventBubbles::DoesNotBubble,
+            EventCancelable::NotCancelable,
+        );
+        let transaction = self.transaction.get();
+        if let Some(ref transaction) = transaction {
+            transaction.set_active(true);
+        }
+        event.fire(self.upcast());
+        if let Some(ref transaction) = transaction {
+            transaction.set_active(false);
+        }
+    }
+
+    /// https://w3c.github.io/IndexedDB/#fire-an-error-event
+    pub fn fire_error(&self) {
+        let event = Event::new(
+            &self.global(),
+            atom!("error"),
+            EventBubbles::Bubbles,
+            EventCancelable::Cancelable,
+        );
+        let transaction = self.transaction.get();
+        if let Some(ref transaction) = transaction {
+            transaction.set_active(true);
+        }
+        let status = event.fire(self.upcast());
+        if let Some(ref transaction) = transaction {
+            transaction.set_active(false);
+            if status == EventStatus::NotCanceled && !transaction.is_finished() {
+                transaction.abort(self.error.get());
+            }
+        }
+    }
+}
+
+/// The name of the DOMException reporting a failure of the IndexedDB thread
+pub fn backend_error_name(error: BackendError) -> DOMErrorName {
+    match error {
+        BackendError::Constraint => DOMErrorName::ConstraintError,
+        BackendError::Version => DOMErrorName::VersionError,
+        BackendError::NotFound => DOMErrorName::NotFoundError,
+        BackendError::Abort => DOMErrorName::AbortError,
+    }
+}
+
+impl IDBRequestMethods for IDBRequest {
+    // https://w3c.github.io/IndexedDB/#dom-idbrequest-result
+    #[allow(unsafe_code)]
+    unsafe fn GetResult(&self, _cx: *mut JSContext) -> Fallible<JSVal> {
+        if !self.is_done() {
+            return Err(Error::InvalidState);
+        }
+        Ok(self.result.get())
+    }
+
+    // https://w3c.github.io/IndexedDB/#dom-idbrequest-error
+    fn GetError(&self) -> Fallible<Option<DomRoot<DOMException>>> {
+        if !self.is_done() {
+            return Err(Error::InvalidState);
+        }
+        Ok(self.error.get())
+    }
+
+    // https://w3c.github.io/IndexedDB/#dom-idbrequest-source
+    fn GetSource(&self) -> Option<IDBObjectStoreOrIDBCursor> {
+        self.source.as_ref().map(|source| match *source {
+            IDBRequestSource::ObjectStore(ref store) => {
+                IDBObjectStoreOrIDBCursor::IDBObjectStore(DomRoot::from_ref(&**store))
+            },
+            IDBRequestSource::Cursor(ref cursor) => {
+                IDBObjectStoreOrIDBCursor::IDBCursor(DomRoot::from_ref(&**cursor))
+            },
+        })
+    }
+
+    // https://w3c.github.io/IndexedDB/#dom-idbrequest-transaction
+    fn GetTransaction(&self) -> Option<DomRoot<IDBTransaction>> {
+        self.transaction.get()
+    }
+
+    // https://w3c.github.io/IndexedDB/#dom-idbrequest-readystate
+    fn ReadyState(&self) -> IDBRequestReadyState {
+        self.ready_state.get()
+    }
+
+    // https://w3c.github.io/IndexedDB/#dom-idbrequest-onsuccess
+    event_handler!(success, GetOnsuccess, SetOnsuccess);
+
+    // https://w3c.github.io/IndexedDB/#dom-idbrequest-onerror
+    event_handler!(error, GetOnerror, SetOnerror);
+}
@@ -0,0 +1,382 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::IDBDatabaseBinding::IDBDatabaseMethods;
+use crate::dom::bindings::codegen::Bindings::IDBTransactionBinding::IDBTransactionMode;
+use crate::dom::bindings::codegen::Bindings::IDBTransactionBinding::{self, IDBTransactionMethods};
+use crate::dom::bindings::error::{Error, ErrorResult, Fallible};
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::refcounted::Trusted;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject};
+use crate::dom::bindings::root::{Dom, DomRoot, MutNullableDom};
+use crate::dom::bindings::str::DOMString;
+use crate::dom::domexception::{DOMErrorName, DOMException};
+use crate::dom::domstringlist::DOMStringList;
+use crate::dom::event::{Event, EventBubbles, EventCancelable};
+use crate::dom::eventtarget::EventTarget;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::idbdatabase::IDBDatabase;
+use crate::dom::idbobjectstore::IDBObjectStore;
+use crate::dom::idbopendbrequest::IDBOpenDBRequest;
+use crate::dom::idbrequest::{backend_error_name, IDBRequest};
+use crate::indexed_db::route_reply;
+use crate::task_source::{TaskSource, TaskSourceName};
+use dom_struct::dom_struct;
+use ipc_channel::ipc::IpcSender;
+use net_traits::indexeddb_thread::{AsyncOperation, BackendError, IndexedDBResult};
+use net_traits::indexeddb_thread::{IndexedDBThreadMsg, IndexedDBTxnMode};
+use net_traits::IpcSend;
+use std::cell::Cell;
+use std::collections::HashMap;
+use std::ptr;
+
+#[dom_struct]
+pub struct IDBTransaction {
+    eventtarget: EventTarget,
+    db: Dom<IDBDatabase>,
+    /// The id of the transaction in the IndexedDB thread
+    id: u64,
+    mode: IDBTransactionMode,
+    /// The names of the object stores the transaction can access, sorted,
+    /// unless it is an upgrade transaction which can access them all
+    scope: Vec<DOMString>,
+    /// The object store handles of the transaction, by name
+    store_handles: DomRefCell<HashMap<DOMString, Dom<IDBObjectStore>>>,
+    /// https://w3c.github.io/IndexedDB/#transaction-active
+    active: Cell<bool>,
+    /// Whether the transaction is waiting for the IndexedDB thread to commit it
+    committing: Cell<bool>,
+    /// https://w3c.github.io/IndexedDB/#transaction-finished
+    finished: Cell<bool>,
+    /// The requests of the transaction whose result did not arrive yet
+    requests: DomRefCell<Vec<Dom<IDBRequest>>>,
+    error: MutNullableDom<DOMException>,
+    /// The request which opened the connection, if this is an upgrade
+    /// transaction
+    open_request: MutNullableDom<IDBOpenDBRequest>,
+}
+
+impl IDBTransaction {
+    fn new_inherited(
+        db: &IDBDatabase,
+        id: u64,
+        mode: IDBTransactionMode,
+        scope: Vec<DOMString>,
+    ) -> IDBTransaction {
+        IDBTransaction {
+            eventtarget: EventTarget::new_inherited(),
+            db: Dom::from_ref(db),
+            id: id,
+            mode: mode,
+            scope: scope,
+            store_handles: DomRefCell::new(HashMap::new()),
+            active: Cell::new(mode != IDBTransactionMode::Versionchange),
+            committing: Cell::new(false),
+            finished: Cell::new(false),
+            requests: DomRefCell::new(vec![]),
+            error: Default::default(),
+            open_request: Default::default(),
+        }
+    }
+
+    /// Creates a transaction for the transaction with the given id in the
+    /// IndexedDB thread. Transactions other than upgrade transactions are
+    /// active until control returns to the event loop.
+    pub fn new(
+        global: &GlobalScope,
+        db: &IDBDatabase,
+        id: u64,
+        mode: IDBTransactionMode,
+        scope: Vec<DOMString>,
+    ) -> DomRoot<IDBTransaction> {
+        let transaction = reflect_dom_object(
+            Box::new(IDBTransaction::new_inherited(db, id, mode, scope)),
+            global,
+            IDBTransactionBinding::Wrap,
+        );
+        if mode != IDBTransactionMode::Versionchange {
+            let trusted = Trusted::new(&*transaction);
+            let _ = global.dom_manipulation_task_source().queue_with_canceller(
+                task!(deactivate_transaction: move || {
+                    let transaction = trusted.root();
+                    transaction.set_active(false);
+                    transaction.maybe_commit();
+                }),
+                &global.task_canceller(TaskSourceName::DOMManipulation),
+            );
+        }
+        transaction
+    }
+
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    pub fn db(&self) -> &IDBDatabase {
+        &self.db
+    }
+
+    pub fn mode(&self) -> IDBTransactionMode {
+        self.mode
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.active.get() && !self.finished.get()
+    }
+
+    pub fn set_active(&self, active: bool) {
+        self.active.set(active);
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.finished.get()
+    }
+
+    pub fn set_open_request(&self, request: &IDBOpenDBRequest) {
+        self.open_request.set(Some(request));
+    }
+
+    /// Drops the handle of an object store deleted in this upgrade transaction
+    pub fn forget_object_store(&self, name: &DOMString) {
+        self.store_handles.borrow_mut().remove(name);
+    }
+
+    /// The names of the object stores the transaction can access
+    fn object_store_names(&self) -> Vec<DOMString> {
+        if self.mode == IDBTransactionMode::Versionchange {
+            self.db.object_store_names()
+        } else {
+            self.scope.clone()
+        }
+    }
+
+    pub fn add_request(&self, request: &IDBRequest) {
+        self.requests.borrow_mut().push(Dom::from_ref(request));
+    }
+
+    /// Removes a request once its result arrived, returning whether it was
+    /// still pending
+    pub fn remove_request(&self, request: &IDBRequest) -> bool {
+        let mut requests = self.requests.borrow_mut();
+        match requests
+            .iter()
+            .position(|pending| ptr::eq(&**pending, request))
+        {
+            Some(index) => {
+                requests.remove(index);
+                true
+            },
+            None => false,
+        }
+    }
+
+    pub fn send_request(
+        &self,
+        sender: IpcSender<Result<IndexedDBResult, BackendError>>,
+        store_name: &str,
+        operation: AsyncOperation,
+    ) {
+        let global = self.global();
+        global
+            .resource_threads()
+            .send(IndexedDBThreadMsg::Request {
+                sender: sender,
+                origin: global.origin().immutable().clone(),
+                db_name: self.db.Name().into(),
+                txn: self.id,
+                store_name: store_name.to_owned(),
+                operation: operation,
+            })
+            .unwrap();
+    }
+
+    /// https://w3c.github.io/IndexedDB/#commit-a-transaction
+    /// Commits the transaction once it is inactive and all its requests got
+    /// their result.
+    pub fn maybe_commit(&self) {
+        if self.active.get() ||
+            self.committing.get() ||
+            self.finished.get() ||
+            !self.requests.borrow().is_empty()
+        {
+            return;
+        }
+        self.committing.set(true);
+
+        let global = self.global();
+        let sender = route_reply(self, |transaction: &IDBTransaction, result| {
+            transaction.handle_commit(result);
+        });
+        global
+            .resource_threads()
+            .send(IndexedDBThreadMsg::Commit(
+                sender,
+                global.origin().immutable().clone(),
+                self.db.Name().into(),
+                self.id,
+            ))
+            .unwrap();
+    }
+
+    /// https://w3c.github.io/IndexedDB/#commit-a-transaction
+    /// Steps 2-5.
+    fn handle_commit(&self, result: Result<(), BackendError>) {
+        self.committing.set(false);
+        if self.finished.get() {
+            return;
+        }
+        let global = self.global();
+        if let Err(error) = result {
+            let error = DOMException::new(&global, backend_error_name(error));
+            return self.abort(Some(error));
+        }
+
+        self.finished.set(true);
+        let event = Event::new(
+            &global,
+            atom!("complete"),
+            EventBubbles::DoesNotBubble,
+            EventCancelable::NotCancelable,
+        );
+        event.fire(self.upcast());
+        if let Some(request) = self.open_request.get() {
+            request.upgrade_done(true);
+        }
+    }
+
+    /// https://w3c.github.io/IndexedDB/#abort-a-transaction
+    pub fn abort(&self, error: Option<DomRoot<DOMException>>) {
+        self.active.set(false);
+        self.finished.set(true);
+
+        // Step 2.
+        if self.mode == IDBTransactionMode::Versionchange {
+            self.db.abort_upgrade();
+        }
+
+        // Step 3.
+        self.error.set(error.as_ref().map(|error| &**error));
+
+        // Step 1.
+        let global = self.global();
+        let sender = route_reply(self, |transaction: &IDBTransaction, ()| {
+            transaction.finish_abort();
+        });
+        global
+            .resource_threads()
+            .send(IndexedDBThreadMsg::Abort(
+                sender,
+                global.origin().immutable().clone(),
+                self.db.Name().into(),
+                self.id,
+            ))
+            .unwrap();
+    }
+
+    /// https://w3c.github.io/IndexedDB/#abort-a-transaction
+    /// Steps 4-5.
+    fn finish_abort(&self) {
+        let global = self.global();
+        let requests: Vec<_> = self
+            .requests
+            .borrow_mut()
+            .drain(..)
+            .map(|request| DomRoot::from_ref(&*request))
+            .collect();
+        for request in requests {
+            request.set_error(&DOMException::new(&global, DOMErrorName::AbortError));
+            request.fire_error();
+        }
+
+        let event = Event::new(
+            &global,
+            atom!("abort"),
+            EventBubbles::Bubbles,
+            EventCancelable::NotCancelable,
+        );
+        event.fire(self.upcast());
+        if let Some(request) = self.open_request.get() {
+            request.upgrade_done(false);
+        }
+    }
+}
+
+/// The mode of a transaction, as the IndexedDB thread knows it
+pub fn txn_mode(mode: IDBTransactionMode) -> IndexedDBTxnMode {
+    match mode {
+        IDBTransactionMode::Readonly => IndexedDBTxnMode::Readonly,
+        IDBTransactionMode::Readwrite => IndexedDBTxnMode::Readwrite,
+        IDBTransactionMode::Versionchange => IndexedDBTxnMode::Versionchange,
+    }
+}
+
+impl IDBTransactionMethods for IDBTransaction {
+    // https://w3c.github.io/IndexedDB/#dom-idbtransaction-objectstorenames
+    fn ObjectStoreNames(&self) -> DomRoot<DOMStringList> {
+        DOMStringList::new(&self.global(), self.object_store_names())
+    }
+
+    // https://w3c.github.io/IndexedDB/#dom-idbtransaction-mode
+    fn Mode(&self) -> IDBTransactionMode {
+        self.mode
+    }
+
+    // https://w3c.github.io/IndexedDB/#dom-idbtransaction-db
+    fn Db(&self) -> DomRoot<IDBDatabase> {
+        DomRoot::from_ref(&*self.db)
+    }
+
+    // https://w3c.github.io/IndexedDB/#dom-idbtransaction-error
+    fn GetError(&self) -> Option<DomRoot<DOMException>> {
+        self.error.get()
+    }
+
+    // https://w3c.github.io/IndexedDB/#dom-idbtransaction-objectstore
+    fn ObjectStore(&self, name: DOMString) -> Fallible<DomRoot<IDBObjectStore>> {
+        // Step 1.
+        if self.finished.get() {
+            return Err(Error::InvalidState);
+        }
+
+        // Step 2.
+        if !self.object_store_names().contains(&name) {
+            return Err(Error::NotFound);
+        }
+        let metadata = match self.db.object_store_metadata(&name) {
+            Some(metadata) => metadata,
+            None => return Err(Error::NotFound),
+        };
+
+        // Step 3.
+        let mut store_handles = self.store_handles.borrow_mut();
+        if let Some(store) = store_handles.get(&name) {
+            return Ok(DomRoot::from_ref(&**store));
+        }
+        let store = IDBObjectStore::new(&self.global(), self, metadata);
+        store_handles.insert(name, Dom::from_ref(&*store));
+        Ok(store)
+    }
+
+    // https://w3c.github.io/IndexedDB/#dom-idbtransaction-abort
+    fn Abort(&self) -> ErrorResult {
+        // Step 1.
+        if self.committing.get() || self.finished.get() {
+            return Err(Error::InvalidState);
+        }
+
+        // Step 2.
+        self.abort(None);
+        Ok(())
+    }
+
+    // https://w3c.github.io/IndexedDB/#dom-idbtransaction-onabort
+    event_handler!(abort, GetOnabort, SetOnabort);
+
+    // https://w3c.github.io/IndexedDB/#dom-idbtransaction-oncomplete
+    event_handler!(complete, GetOncomplete, SetOncomplete);
+
+    // https://w3c.github.io/IndexedDB/#dom-idbtransaction-onerror
+    event_handler!(error, GetOnerror, SetOnerror);
+}
@@ -0,0 +1,454 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::ImageBitmapBinding;
+use crate::dom::bindings::codegen::Bindings::ImageBitmapBinding::{
+    ImageBitmapMethods, ImageBitmapOptions, ImageBitmapSource, ImageOrientation, ResizeQuality,
+};
+use crate::dom::bindings::error::{Error, Fallible};
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::refcounted::TrustedPromise;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::htmlcanvaselement::utils as canvas_utils;
+use crate::dom::node::window_from_node;
+use crate::dom::promise::Promise;
+use crate::dom::window::Window;
+use crate::task_source::{TaskSource, TaskSourceName};
+use dom_struct::dom_struct;
+use euclid::{Rect, Size2D};
+use ipc_channel::ipc;
+use ipc_channel::router::ROUTER;
+use net_traits::image::base::Image;
+use net_traits::image_cache::ImageResponse;
+use pixels::PixelFormat;
+use servo_url::ServoUrl;
+use std::rc::Rc;
+use std::sync::{Arc, Mutex};
+
+// https://html.spec.whatwg.org/multipage/#imagebitmap
+#[dom_struct]
+pub struct ImageBitmap {
+    reflector_: Reflector,
+    width: u32,
+    height: u32,
+    /// The premultiplied BGRA pixels of the bitmap, None once the bitmap
+    /// got closed or transferred.
+    bitmap_data: DomRefCell<Option<Vec<u8>>>,
+    origin_clean: bool,
+}
+
+impl ImageBitmap {
+    fn new_inherited(bitmap_data: Vec<u8>, size: Size2D<u32>, origin_clean: bool) -> ImageBitmap {
+        ImageBitmap {
+            reflector_: Reflector::new(),
+            width: size.width,
+            height: size.height,
+            bitmap_data: DomRefCell::new(Some(bitmap_data)),
+            origin_clean: origin_clean,
+        }
+    }
+
+    pub fn new(
+        global: &GlobalScope,
+        bitmap_data: Vec<u8>,
+        size: Size2D<u32>,
+        origin_clean: bool,
+    ) -> DomRoot<ImageBitmap> {
+        reflect_dom_object(
+            Box::new(ImageBitmap::new_inherited(bitmap_data, size, origin_clean)),
+            global,
+            ImageBitmapBinding::Wrap,
+        )
+    }
+
+    pub fn get_size(&self) -> Size2D<u32> {
+        Size2D::new(self.width, self.height)
+    }
+
+    pub fn bitmap_data(&self) -> Option<Vec<u8>> {
+        self.bitmap_data.borrow().clone()
+    }
+
+    pub fn origin_is_clean(&self) -> bool {
+        self.origin_clean
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/#dom-createimagebitmap>
+    #[allow(unsafe_code)]
+    pub fn create(
+        global: &GlobalScope,
+        image: ImageBitmapSource,
+        crop: Option<Rect<i32>>,
+        options: &ImageBitmapOptions,
+    ) -> Rc<Promise> {
+        let promise = unsafe { Promise::new_in_current_compartment(global) };
+
+        // Step 1
+        if let Some(crop) = crop {
+            if crop.size.width == 0 || crop.size.height == 0 {
+                promise.reject_error(Error::Range("The source rectangle is empty".to_owned()));
+                return promise;
+            }
+        }
+        // Step 2
+        if options.resizeWidth == Some(0) || options.resizeHeight == Some(0) {
+            promise.reject_error(Error::InvalidState);
+            return promise;
+        }
+
+        let format = BitmapFormat {
+            crop: crop,
+            resize_width: options.resizeWidth,
+            resize_height: options.resizeHeight,
+            resize_quality: options.resizeQuality,
+            flip_y: options.imageOrientation == ImageOrientation::FlipY,
+        };
+
+        // Steps 3-6
+        let blob = match image {
+            ImageBitmapSource::Blob(blob) => blob,
+            image => {
+                match source_bitmap(global, image) {
+                    Ok((data, size, origin_clean)) => {
+                        let (data, size) = format.apply(&data, size);
+                        promise.resolve_native(&ImageBitmap::new(global, data, size, origin_clean));
+                    },
+                    Err(error) => promise.reject_error(error),
+                }
+                return promise;
+            },
+        };
+
+        // Blobs get decoded by the image cache, off this thread.
+        let image_cache = match global.image_cache() {
+            Some(image_cache) => image_cache,
+            None => {
+                promise.reject_error(Error::InvalidState);
+                return promise;
+            },
+        };
+        let bytes = match blob.get_bytes() {
+            Ok(bytes) => bytes,
+            Err(()) => {
+                promise.reject_error(Error::InvalidState);
+                return promise;
+            },
+        };
+        let (sender, receiver) = ipc::channel().unwrap();
+        let task_source = global.dom_manipulation_task_source();
+        let canceller = global.task_canceller(TaskSourceName::DOMManipulation);
+        let trusted_promise = Arc::new(Mutex::new(Some(TrustedPromise::new(promise.clone()))));
+        ROUTER.add_route(
+            receiver.to_opaque(),
+            Box::new(move |message| {
+                let image: Option<Image> = message.to().unwrap();
+                let trusted_promise = match trusted_promise.lock().unwrap().take() {
+                    Some(trusted_promise) => trusted_promise,
+                    None => return,
+                };
+                let _ = task_source.queue_with_canceller(
+                    task!(image_bitmap_decoded: move || {
+                        let promise = trusted_promise.root();
+                        // https://html.spec.whatwg.org/multipage/#dom-createimagebitmap
+                        // Step 6.5 of the blob case.
+                        let image = match image {
+                            Some(image) => image,
+                            None => return promise.reject_error(Error::InvalidState),
+                        };
+                        let data = match premultiplied_image_data(&image) {
+                            Ok(data) => data,
+                            Err(error) => return promise.reject_error(error),
+                        };
+                        let size = Size2D::new(image.width, image.height);
+                        let (data, size) = format.apply(&data, size);
+                        promise.resolve_native(&ImageBitmap::new(&promise.global(), data, size, true));
+                    }),
+                    &canceller,
+                );
+            }),
+        );
+        image_cache.decode_bytes(bytes, sender);
+        promise
+    }
+
+    /// Takes the pixels out of this bitmap to move them to another global,
+    /// leaving it detached
+    pub fn transfer(&self) -> Option<(Vec<u8>, Size2D<u32>, bool)> {
+        let data = self.bitmap_data.borrow_mut().take()?;
+        Some((data, self.get_size(), self.origin_clean))
+    }
+}
+
+impl ImageBitmapMethods for ImageBitmap {
+    // https://html.spec.whatwg.org/multipage/#dom-imagebitmap-width
+    fn Width(&self) -> u32 {
+        if self.bitmap_data.borrow().is_none() {
+            return 0;
+        }
+        self.width
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-imagebitmap-height
+    fn Height(&self) -> u32 {
+        if self.bitmap_data.borrow().is_none() {
+            return 0;
+        }
+        self.height
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-imagebitmap-close
+    fn Close(&self) {
+        *self.bitmap_data.borrow_mut() = None;
+    }
+}
+
+/// The premultiplied BGRA pixels of an image bitmap source, along with
+/// their size and whether they are origin-clean.
+///
+/// <https://html.spec.whatwg.org/multipage/#check-the-usability-of-the-image-argument>
+fn source_bitmap(
+    global: &GlobalScope,
+    image: ImageBitmapSource,
+) -> Fallible<(Vec<u8>, Size2D<u32>, bool)> {
+    match image {
+        ImageBitmapSource::HTMLImageElement(image) => {
+            let url = image.get_url().ok_or(Error::InvalidState)?;
+            let (data, size) = cached_image_data(&window_from_node(&*image), url)?;
+            Ok((data, size, image.same_origin(global.origin())))
+        },
+        ImageBitmapSource::CSSStyleValue(value) => {
+            let window = global.downcast::<Window>().ok_or(Error::InvalidState)?;
+            let url = value
+                .get_url(global.api_base_url())
+                .ok_or(Error::InvalidState)?;
+            let (data, size) = cached_image_data(window, url)?;
+            Ok((data, size, true))
+        },
+        ImageBitmapSource::HTMLCanvasElement(canvas) => {
+            let (data, size) = canvas.fetch_all_data().ok_or(Error::InvalidState)?;
+            let data = data
+                .map(|data| data.to_vec())
+                .unwrap_or_else(|| vec![0; size.area() as usize * 4]);
+            Ok((data, size, canvas.origin_is_clean()))
+        },
+        ImageBitmapSource::OffscreenCanvas(canvas) => {
+            let (data, size) = canvas.fetch_all_data().ok_or(Error::InvalidState)?;
+            let data = data.unwrap_or_else(|| vec![0; size.area() as usize * 4]);
+            Ok((data, size, canvas.origin_is_clean()))
+        },
+        ImageBitmapSource::ImageBitmap(bitmap) => {
+            let data = bitmap.bitmap_data().ok_or(Error::InvalidState)?;
+            Ok((data, bitmap.get_size(), bitmap.origin_is_clean()))
+        },
+        ImageBitmapSource::ImageData(image_data) => {
+            let mut data = image_data.to_shared_memory().to_vec();
+            pixels::rgba8_byte_swap_and_premultiply_inplace(&mut data);
+            Ok((data, image_data.get_size(), true))
+        },
+        ImageBitmapSource::Blob(_) => unreachable!("Blobs need to be decoded first"),
+    }
+}
+
+/// The premultiplied pixels and size of the image at `url`, which has to be
+/// fully decoded already.
+fn cached_image_data(window: &Window, url: ServoUrl) -> Fallible<(Vec<u8>, Size2D<u32>)> {
+    match canvas_utils::request_image_from_cache(window, url) {
+        ImageResponse::Loaded(image, _) => Ok((
+            premultiplied_image_data(&image)?,
+            Size2D::new(image.width, image.height),
+        )),
+        ImageResponse::PlaceholderLoaded(..) |
+        ImageResponse::MetadataLoaded(_) |
+        ImageResponse::None => Err(Error::InvalidState),
+    }
+}
+
+/// Fails for the pixel formats other than BGRA8, which the bitmap data can't
+/// be made from.
+fn premultiplied_image_data(image: &Image) -> Fallible<Vec<u8>> {
+    let mut data = image.bytes.to_vec();
+    match image.format {
+        PixelFormat::BGRA8 => pixels::rgba8_premultiply_inplace(&mut data),
+        pixel_format => {
+            warn!(
+                "Unsupported pixel format for an image bitmap ({:?})",
+                pixel_format
+            );
+            return Err(Error::InvalidState);
+        },
+    };
+    Ok(data)
+}
+
+/// The options of `createImageBitmap()` that shape the bitmap it creates.
+#[derive(Clone, Copy)]
+struct BitmapFormat {
+    crop: Option<Rect<i32>>,
+    resize_width: Option<u32>,
+    resize_height: Option<u32>,
+    resize_quality: ResizeQuality,
+    flip_y: bool,
+}
+
+impl BitmapFormat {
+    /// Crops and scales the premultiplied BGRA `data` of a source of the
+    /// given size.
+    ///
+    /// The bitmap is always premultiplied, which is how both 2D canvases
+    /// and WebGL want it by default.
+    ///
+    /// <https://html.spec.whatwg.org/multipage/#cropped-to-the-source-rectangle-with-formatting>
+    fn apply(&self, data: &[u8], size: Size2D<u32>) -> (Vec<u8>, Size2D<u32>) {
+        // Steps 1-2
+        let (mut sx, mut sy, mut sw, mut sh) = match self.crop {
+            Some(crop) => (
+                crop.origin.x as f64,
+                crop.origin.y as f64,
+                crop.size.width as f64,
+                crop.size.height as f64,
+            ),
+            None => (0., 0., size.width as f64, size.height as f64),
+        };
+        if sw < 0. {
+            sx += sw;
+            sw = -sw;
+        }
+        if sh < 0. {
+            sy += sh;
+            sh = -sh;
+        }
+
+        // Steps 3-4
+        let width = match (self.resize_width, self.resize_height) {
+            (Some(width), _) => width,
+            (None, Some(height)) => (sw * height as f64 / sh).ceil() as u32,
+            (None, None) => sw as u32,
+        };
+        let height = match (self.resize_width, self.resize_height) {
+            (_, Some(height)) => height,
+            (Some(width), None) => (sh * width as f64 / sw).ceil() as u32,
+            (None, None) => sh as u32,
+        };
+
+        // Steps 5-7
+        let source = Source {
+            data: data,
+            size: size,
+        };
+        let (scale_x, scale_y) = (sw / width as f64, sh / height as f64);
+        let downscaling = scale_x > 1. || scale_y > 1.;
+        let mut bitmap = Vec::with_capacity(width as usize * height as usize * 4);
+        for y in 0..height {
+            // Step 9
+            let y = if self.flip_y { height - 1 - y } else { y };
+            let top = sy + y as f64 * scale_y;
+            for x in 0..width {
+                let left = sx + x as f64 * scale_x;
+                let pixel = match self.resize_quality {
+                    ResizeQuality::Pixelated => {
+                        source.nearest(left + scale_x / 2., top + scale_y / 2.)
+                    },
+                    ResizeQuality::Medium | ResizeQuality::High if downscaling => {
+                        source.area_average(left, top, scale_x, scale_y)
+                    },
+                    _ => source.bilinear(left + scale_x / 2., top + scale_y / 2.),
+                };
+                bitmap.extend_from_slice(&pixel);
+            }
+        }
+        (bitmap, Size2D::new(width, height))
+    }
+}
+
+/// The pixels of an image bitmap source, those outside of it being
+/// transparent black.
+struct Source<'a> {
+    data: &'a [u8],
+    size: Size2D<u32>,
+}
+
+impl<'a> Source<'a> {
+    fn pixel(&self, x: i64, y: i64) -> [f64; 4] {
+        if x < 0 || y < 0 || x >= self.size.width as i64 || y >= self.size.height as i64 {
+            return [0.; 4];
+        }
+        let offset = (y as usize * self.size.width as usize + x as usize) * 4;
+        let pixel = &self.data[offset..offset + 4];
+        [
+            pixel[0] as f64,
+            pixel[1] as f64,
+            pixel[2] as f64,
+            pixel[3] as f64,
+        ]
+    }
+
+    /// The pixel the point `(x, y)` lies in.
+    fn nearest(&self, x: f64, y: f64) -> [u8; 4] {
+        to_pixel(self.pixel(x.floor() as i64, y.floor() as i64))
+    }
+
+    /// The interpolation of the four pixels whose centers surround the
+    /// point `(x, y)`.
+    fn bilinear(&self, x: f64, y: f64) -> [u8; 4] {
+        let (x, y) = (x - 0.5, y - 0.5);
+        let (left, top) = (x.floor(), y.floor());
+        let (tx, ty) = (x - left, y - top);
+        let (left, top) = (left as i64, top as i64);
+        let mut result = [0.; 4];
+        for &(dx, dy, weight) in &[
+            (0, 0, (1. - tx) * (1. - ty)),
+            (1, 0, tx * (1. - ty)),
+            (0, 1, (1. - tx) * ty),
+            (1, 1, tx * ty),
+        ] {
+            if weight == 0. {
+                continue;
+            }
+            let pixel = self.pixel(left + dx, top + dy);
+            for (value, component) in result.iter_mut().zip(&pixel) {
+                *value += component * weight;
+            }
+        }
+        to_pixel(result)
+    }
+
+    /// The average of the pixels in the `width` by `height` area at
+    /// `(left, top)`, weighted by how much of them it covers.
+    fn area_average(&self, left: f64, top: f64, width: f64, height: f64) -> [u8; 4] {
+        let (right, bottom) = (left + width, top + height);
+        let mut result = [0.; 4];
+        let mut y = top.floor();
+        while y < bottom {
+            let coverage_y = (y + 1.).min(bottom) - y.max(top);
+            let mut x = left.floor();
+            while x < right {
+                let coverage = ((x + 1.).min(right) - x.max(left)) * coverage_y;
+                let pixel = self.pixel(x as i64, y as i64);
+                for (value, component) in result.iter_mut().zip(&pixel) {
+                    *value += component * coverage;
+                }
+                x += 1.;
+            }
+            y += 1.;
+        }
+        let area = width * height;
+        for value in result.iter_mut() {
+            *value /= area;
+        }
+        to_pixel(result)
+    }
+}
+
+fn to_pixel(components: [f64; 4]) -> [u8; 4] {
+    let component = |value: f64| value.round().max(0.).min(255.) as u8;
+    [
+        component(components[0]),
+        component(components[1]),
+        component(components[2]),
+        component(components[3]),
+    ]
+}
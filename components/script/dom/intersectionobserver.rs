@@ -0,0 +1,410 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::callback::ExceptionHandling;
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::IntersectionObserverBinding;
+use crate::dom::bindings::codegen::Bindings::IntersectionObserverBinding::IntersectionObserverCallback;
+use crate::dom::bindings::codegen::Bindings::IntersectionObserverBinding::IntersectionObserverInit;
+use crate::dom::bindings::codegen::Bindings::IntersectionObserverBinding::IntersectionObserverMethods;
+use crate::dom::bindings::codegen::Bindings::PerformanceBinding::DOMHighResTimeStamp;
+use crate::dom::bindings::codegen::Bindings::WindowBinding::WindowMethods;
+use crate::dom::bindings::codegen::UnionTypes::DoubleOrDoubleSequence;
+use crate::dom::bindings::error::{Error, Fallible};
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::bindings::str::DOMString;
+use crate::dom::element::Element;
+use crate::dom::intersectionobserverentry::IntersectionObserverEntry;
+use crate::dom::node::Node;
+use crate::dom::window::Window;
+use app_units::Au;
+use dom_struct::dom_struct;
+use euclid::{Point2D, Rect, Size2D, Vector2D};
+use js::conversions::ToJSValConvertible;
+use js::jsapi::{Heap, JSContext};
+use js::jsval::{JSVal, UndefinedValue};
+use js::rust::wrappers::JS_FreezeObject;
+use std::cmp::Ordering;
+use std::fmt;
+use std::rc::Rc;
+
+/// One of the lengths of a root margin
+#[derive(Clone, Copy, Debug, JSTraceable, MallocSizeOf, PartialEq)]
+pub enum MarginLength {
+    Pixels(f64),
+    /// Of the width of the root for the left and right margins, of its
+    /// height for the top and bottom ones
+    Percentage(f64),
+}
+
+impl MarginLength {
+    fn parse(token: &str) -> Option<MarginLength> {
+        let token = token.to_ascii_lowercase();
+        let (number, length): (&str, fn(f64) -> MarginLength) = if token.ends_with("px") {
+            (&token[..token.len() - 2], MarginLength::Pixels)
+        } else if token.ends_with('%') {
+            (&token[..token.len() - 1], MarginLength::Percentage)
+        } else {
+            return None;
+        };
+        match number.parse::<f64>() {
+            Ok(number) if number.is_finite() => Some(length(number)),
+            _ => None,
+        }
+    }
+
+    /// The length in pixels, for a root that is `size` long along its axis
+    pub fn resolve(&self, size: f64) -> f64 {
+        match *self {
+            MarginLength::Pixels(pixels) => pixels,
+            MarginLength::Percentage(percentage) => size * percentage / 100.,
+        }
+    }
+}
+
+impl fmt::Display for MarginLength {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            MarginLength::Pixels(pixels) => write!(f, "{}px", pixels),
+            MarginLength::Percentage(percentage) => write!(f, "{}%", percentage),
+        }
+    }
+}
+
+/// https://w3c.github.io/IntersectionObserver/#parse-a-root-margin
+///
+/// Returns the top, right, bottom and left margins, or None if `margin`
+/// isn't a list of one to four pixel or percentage lengths.
+pub fn parse_root_margin(margin: &str) -> Option<[MarginLength; 4]> {
+    let lengths = margin
+        .split_whitespace()
+        .map(MarginLength::parse)
+        .collect::<Option<Vec<_>>>()?;
+    match lengths[..] {
+        [all] => Some([all, all, all, all]),
+        [vertical, horizontal] => Some([vertical, horizontal, vertical, horizontal]),
+        [top, horizontal, bottom] => Some([top, horizontal, bottom, horizontal]),
+        [top, right, bottom, left] => Some([top, right, bottom, left]),
+        _ => None,
+    }
+}
+
+/// `rect` grown by the top, right, bottom and left `margin`
+pub fn expand_by_margin(rect: &Rect<f64>, margin: &[MarginLength; 4]) -> Rect<f64> {
+    let (width, height) = (rect.size.width, rect.size.height);
+    let top = margin[0].resolve(height);
+    let right = margin[1].resolve(width);
+    let bottom = margin[2].resolve(height);
+    let left = margin[3].resolve(width);
+    Rect::new(
+        Point2D::new(rect.origin.x - left, rect.origin.y - top),
+        Size2D::new(width + left + right, height + top + bottom),
+    )
+}
+
+/// The part of `a` that is inside `b`
+///
+/// Rects that merely share an edge intersect too, in an empty rect: a
+/// target that is scrolled exactly to the edge of its root counts as
+/// intersecting it.
+pub fn edge_inclusive_intersection(a: &Rect<f64>, b: &Rect<f64>) -> Option<Rect<f64>> {
+    let (min_x, max_x) = (a.min_x().max(b.min_x()), a.max_x().min(b.max_x()));
+    let (min_y, max_y) = (a.min_y().max(b.min_y()), a.max_y().min(b.max_y()));
+    if min_x > max_x || min_y > max_y {
+        return None;
+    }
+    Some(Rect::new(
+        Point2D::new(min_x, min_y),
+        Size2D::new(max_x - min_x, max_y - min_y),
+    ))
+}
+
+/// How much of `target` is visible through `intersection`
+///
+/// A target without an area is entirely visible as long as it intersects.
+pub fn intersection_ratio(target: &Rect<f64>, intersection: Option<&Rect<f64>>) -> f64 {
+    let intersection = match intersection {
+        Some(intersection) => intersection,
+        None => return 0.,
+    };
+    let target_area = target.size.width * target.size.height;
+    if target_area > 0. {
+        intersection.size.width * intersection.size.height / target_area
+    } else {
+        1.
+    }
+}
+
+/// The index of the first of the sorted `thresholds` that is above `ratio`,
+/// or their number if `ratio` has reached all of them
+pub fn threshold_index(thresholds: &[f64], ratio: f64) -> usize {
+    thresholds
+        .iter()
+        .take_while(|&&threshold| threshold <= ratio)
+        .count()
+}
+
+fn rect_to_f64(rect: &Rect<Au>) -> Rect<f64> {
+    Rect::new(
+        Point2D::new(rect.origin.x.to_f64_px(), rect.origin.y.to_f64_px()),
+        Size2D::new(rect.size.width.to_f64_px(), rect.size.height.to_f64_px()),
+    )
+}
+
+/// https://w3c.github.io/IntersectionObserver/#intersectionobserverregistration
+#[derive(JSTraceable, MallocSizeOf)]
+struct IntersectionObservation {
+    target: Dom<Element>,
+    /// None until the first update, so that observing a target always
+    /// queues an entry for it
+    previous_threshold_index: Option<usize>,
+    previous_is_intersecting: bool,
+}
+
+#[dom_struct]
+pub struct IntersectionObserver {
+    reflector_: Reflector,
+    #[ignore_malloc_size_of = "can't measure Rc values"]
+    callback: Rc<IntersectionObserverCallback>,
+    root: Option<Dom<Element>>,
+    /// The top, right, bottom and left margins
+    root_margin: [MarginLength; 4],
+    /// In ascending order
+    thresholds: Vec<f64>,
+    /// The frozen array reflecting `thresholds`, created on first access
+    thresholds_array: Heap<JSVal>,
+    observation_targets: DomRefCell<Vec<IntersectionObservation>>,
+    /// https://w3c.github.io/IntersectionObserver/#dom-intersectionobserver-queuedentries-slot
+    queued_entries: DomRefCell<Vec<Dom<IntersectionObserverEntry>>>,
+}
+
+impl IntersectionObserver {
+    fn new_inherited(
+        callback: Rc<IntersectionObserverCallback>,
+        root: Option<&Element>,
+        root_margin: [MarginLength; 4],
+        thresholds: Vec<f64>,
+    ) -> IntersectionObserver {
+        IntersectionObserver {
+            reflector_: Reflector::new(),
+            callback,
+            root: root.map(Dom::from_ref),
+            root_margin,
+            thresholds,
+            thresholds_array: Heap::default(),
+            observation_targets: DomRefCell::new(vec![]),
+            queued_entries: DomRefCell::new(vec![]),
+        }
+    }
+
+    /// https://w3c.github.io/IntersectionObserver/#dom-intersectionobserver-intersectionobserver
+    pub fn Constructor(
+        window: &Window,
+        callback: Rc<IntersectionObserverCallback>,
+        options: &IntersectionObserverInit,
+    ) -> Fallible<DomRoot<IntersectionObserver>> {
+        // Steps 2-3
+        let root_margin = match parse_root_margin(&options.rootMargin) {
+            Some(root_margin) => root_margin,
+            None => return Err(Error::Syntax),
+        };
+        // Steps 4-6
+        let mut thresholds: Vec<f64> = match options.threshold {
+            DoubleOrDoubleSequence::Double(threshold) => vec![*threshold],
+            DoubleOrDoubleSequence::DoubleSequence(ref thresholds) => {
+                thresholds.iter().map(|threshold| **threshold).collect()
+            },
+        };
+        if thresholds
+            .iter()
+            .any(|&threshold| threshold < 0. || threshold > 1.)
+        {
+            return Err(Error::Range(
+                "threshold values must be between 0 and 1".to_owned(),
+            ));
+        }
+        thresholds.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+        if thresholds.is_empty() {
+            thresholds.push(0.);
+        }
+        let observer = reflect_dom_object(
+            Box::new(IntersectionObserver::new_inherited(
+                callback,
+                options.root.as_ref().map(|root| &**root),
+                root_margin,
+                thresholds,
+            )),
+            window,
+            IntersectionObserverBinding::Wrap,
+        );
+        window.Document().add_intersection_observer(&observer);
+        Ok(observer)
+    }
+
+    /// The rect targets are intersected with, grown by the root margin, in
+    /// the coordinates of layout: those of the page, which don't move when
+    /// scrolling
+    fn root_intersection_rect(&self, window: &Window) -> Rect<f64> {
+        let rect = match self.root {
+            Some(ref root) => {
+                // the padding box of the root
+                let root = root.upcast::<Node>();
+                let border_box = rect_to_f64(&root.bounding_content_box_or_zero());
+                let client = window.client_rect_query(root);
+                Rect::new(
+                    border_box.origin +
+                        Vector2D::new(client.origin.x as f64, client.origin.y as f64),
+                    Size2D::new(client.size.width as f64, client.size.height as f64),
+                )
+            },
+            None => Rect::new(
+                Point2D::new(window.ScrollX() as f64, window.ScrollY() as f64),
+                Size2D::new(window.InnerWidth() as f64, window.InnerHeight() as f64),
+            ),
+        };
+        expand_by_margin(&rect, &self.root_margin)
+    }
+
+    /// Whether `target` is where it can intersect the root at all
+    fn can_intersect(&self, window: &Window, target: &Element) -> bool {
+        let target = target.upcast::<Node>();
+        match self.root {
+            Some(ref root) => root.upcast::<Node>().is_ancestor_of(target),
+            None => target.is_in_doc() && *target.owner_doc() == *window.Document(),
+        }
+    }
+
+    /// https://w3c.github.io/IntersectionObserver/#update-intersection-observations-algo
+    ///
+    /// Step 2, for the targets of this observer. Returns whether any entries
+    /// were queued.
+    pub fn update_intersection_observations(
+        &self,
+        window: &Window,
+        time: DOMHighResTimeStamp,
+    ) -> bool {
+        let root_rect = self.root_intersection_rect(window);
+        // entries are in the coordinates of the viewport
+        let scroll = Vector2D::new(window.ScrollX() as f64, window.ScrollY() as f64);
+        let mut queued = false;
+        for observation in self.observation_targets.borrow_mut().iter_mut() {
+            let target = &observation.target;
+            let bounding_box = target.upcast::<Node>().bounding_content_box();
+            let (target_rect, intersection) = match bounding_box {
+                Some(ref rect) if self.can_intersect(window, target) => {
+                    let rect = rect_to_f64(rect);
+                    (rect, edge_inclusive_intersection(&rect, &root_rect))
+                },
+                Some(ref rect) => (rect_to_f64(rect), None),
+                None => (Rect::zero(), None),
+            };
+            let is_intersecting = intersection.is_some();
+            let ratio = intersection_ratio(&target_rect, intersection.as_ref());
+            let index = threshold_index(&self.thresholds, ratio);
+            if observation.previous_threshold_index == Some(index) &&
+                observation.previous_is_intersecting == is_intersecting
+            {
+                continue;
+            }
+            let entry = IntersectionObserverEntry::new(
+                window,
+                time,
+                &root_rect.translate(&-scroll),
+                &target_rect.translate(&-scroll),
+                &intersection.unwrap_or_else(Rect::zero).translate(&-scroll),
+                is_intersecting,
+                ratio,
+                target,
+            );
+            self.queued_entries
+                .borrow_mut()
+                .push(Dom::from_ref(&*entry));
+            observation.previous_threshold_index = Some(index);
+            observation.previous_is_intersecting = is_intersecting;
+            queued = true;
+        }
+        queued
+    }
+
+    /// https://w3c.github.io/IntersectionObserver/#notify-intersection-observers-algo
+    ///
+    /// Step 3, for this observer
+    pub fn notify(&self) {
+        let entries = self.TakeRecords();
+        if !entries.is_empty() {
+            let _ = self
+                .callback
+                .Call_(self, entries, self, ExceptionHandling::Report);
+        }
+    }
+}
+
+impl IntersectionObserverMethods for IntersectionObserver {
+    /// https://w3c.github.io/IntersectionObserver/#dom-intersectionobserver-root
+    fn GetRoot(&self) -> Option<DomRoot<Element>> {
+        self.root.as_ref().map(|root| DomRoot::from_ref(&**root))
+    }
+
+    /// https://w3c.github.io/IntersectionObserver/#dom-intersectionobserver-rootmargin
+    fn RootMargin(&self) -> DOMString {
+        let margin = &self.root_margin;
+        DOMString::from(format!(
+            "{} {} {} {}",
+            margin[0], margin[1], margin[2], margin[3]
+        ))
+    }
+
+    /// https://w3c.github.io/IntersectionObserver/#dom-intersectionobserver-thresholds
+    #[allow(unsafe_code)]
+    unsafe fn Thresholds(&self, _cx: *mut JSContext) -> JSVal {
+        if self.thresholds_array.get().is_undefined() {
+            let cx = self.global().get_cx();
+            rooted!(in(cx) let mut jsval = UndefinedValue());
+            self.thresholds.to_jsval(cx, jsval.handle_mut());
+            rooted!(in(cx) let array = jsval.to_object());
+            assert!(JS_FreezeObject(cx, array.handle()));
+            self.thresholds_array.set(jsval.get());
+        }
+        self.thresholds_array.get()
+    }
+
+    /// https://w3c.github.io/IntersectionObserver/#dom-intersectionobserver-observe
+    fn Observe(&self, target: &Element) {
+        let mut targets = self.observation_targets.borrow_mut();
+        if targets
+            .iter()
+            .any(|observation| observation.target == target)
+        {
+            return;
+        }
+        targets.push(IntersectionObservation {
+            target: Dom::from_ref(target),
+            previous_threshold_index: None,
+            previous_is_intersecting: false,
+        });
+    }
+
+    /// https://w3c.github.io/IntersectionObserver/#dom-intersectionobserver-unobserve
+    fn Unobserve(&self, target: &Element) {
+        self.observation_targets
+            .borrow_mut()
+            .retain(|observation| observation.target != target);
+    }
+
+    /// https://w3c.github.io/IntersectionObserver/#dom-intersectionobserver-disconnect
+    fn Disconnect(&self) {
+        self.observation_targets.borrow_mut().clear();
+    }
+
+    /// https://w3c.github.io/IntersectionObserver/#dom-intersectionobserver-takerecords
+    fn TakeRecords(&self) -> Vec<DomRoot<IntersectionObserverEntry>> {
+        self.queued_entries
+            .borrow_mut()
+            .drain(..)
+            .map(|entry| DomRoot::from_ref(&*entry))
+            .collect()
+    }
+}
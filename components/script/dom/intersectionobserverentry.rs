@@ -0,0 +1,171 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::DOMRectReadOnlyBinding::DOMRectInit;
+use crate::dom::bindings::codegen::Bindings::IntersectionObserverEntryBinding;
+use crate::dom::bindings::codegen::Bindings::IntersectionObserverEntryBinding::IntersectionObserverEntryInit;
+use crate::dom::bindings::codegen::Bindings::IntersectionObserverEntryBinding::IntersectionObserverEntryMethods;
+use crate::dom::bindings::codegen::Bindings::PerformanceBinding::DOMHighResTimeStamp;
+use crate::dom::bindings::error::Fallible;
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::num::Finite;
+use crate::dom::bindings::reflector::{reflect_dom_object, Reflector};
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::domrectreadonly::DOMRectReadOnly;
+use crate::dom::element::Element;
+use crate::dom::window::Window;
+use dom_struct::dom_struct;
+use euclid::Rect;
+
+#[dom_struct]
+pub struct IntersectionObserverEntry {
+    reflector_: Reflector,
+    time: DOMHighResTimeStamp,
+    root_bounds: Option<Dom<DOMRectReadOnly>>,
+    bounding_client_rect: Dom<DOMRectReadOnly>,
+    intersection_rect: Dom<DOMRectReadOnly>,
+    is_intersecting: bool,
+    intersection_ratio: Finite<f64>,
+    target: Dom<Element>,
+}
+
+impl IntersectionObserverEntry {
+    fn new_inherited(
+        time: DOMHighResTimeStamp,
+        root_bounds: Option<&DOMRectReadOnly>,
+        bounding_client_rect: &DOMRectReadOnly,
+        intersection_rect: &DOMRectReadOnly,
+        is_intersecting: bool,
+        intersection_ratio: Finite<f64>,
+        target: &Element,
+    ) -> IntersectionObserverEntry {
+        IntersectionObserverEntry {
+            reflector_: Reflector::new(),
+            time,
+            root_bounds: root_bounds.map(Dom::from_ref),
+            bounding_client_rect: Dom::from_ref(bounding_client_rect),
+            intersection_rect: Dom::from_ref(intersection_rect),
+            is_intersecting,
+            intersection_ratio,
+            target: Dom::from_ref(target),
+        }
+    }
+
+    fn new_from_rects(
+        window: &Window,
+        time: DOMHighResTimeStamp,
+        root_bounds: Option<DomRoot<DOMRectReadOnly>>,
+        bounding_client_rect: &DOMRectReadOnly,
+        intersection_rect: &DOMRectReadOnly,
+        is_intersecting: bool,
+        intersection_ratio: Finite<f64>,
+        target: &Element,
+    ) -> DomRoot<IntersectionObserverEntry> {
+        reflect_dom_object(
+            Box::new(IntersectionObserverEntry::new_inherited(
+                time,
+                root_bounds.as_ref().map(|bounds| &**bounds),
+                bounding_client_rect,
+                intersection_rect,
+                is_intersecting,
+                intersection_ratio,
+                target,
+            )),
+            window,
+            IntersectionObserverEntryBinding::Wrap,
+        )
+    }
+
+    /// Creates the entry for an update of the intersection observation
+    /// steps, with all rects in CSS pixels relative to the viewport
+    pub fn new(
+        window: &Window,
+        time: DOMHighResTimeStamp,
+        root_bounds: &Rect<f64>,
+        bounding_client_rect: &Rect<f64>,
+        intersection_rect: &Rect<f64>,
+        is_intersecting: bool,
+        intersection_ratio: f64,
+        target: &Element,
+    ) -> DomRoot<IntersectionObserverEntry> {
+        let rect = |rect: &Rect<f64>| {
+            DOMRectReadOnly::new(
+                window.upcast(),
+                rect.origin.x,
+                rect.origin.y,
+                rect.size.width,
+                rect.size.height,
+            )
+        };
+        IntersectionObserverEntry::new_from_rects(
+            window,
+            time,
+            Some(rect(root_bounds)),
+            &rect(bounding_client_rect),
+            &rect(intersection_rect),
+            is_intersecting,
+            Finite::wrap(intersection_ratio),
+            target,
+        )
+    }
+
+    /// https://w3c.github.io/IntersectionObserver/#dom-intersectionobserverentry-intersectionobserverentry
+    pub fn Constructor(
+        window: &Window,
+        init: &IntersectionObserverEntryInit,
+    ) -> Fallible<DomRoot<IntersectionObserverEntry>> {
+        let rect = |rect: &DOMRectInit| {
+            DOMRectReadOnly::new(window.upcast(), rect.x, rect.y, rect.width, rect.height)
+        };
+        Ok(IntersectionObserverEntry::new_from_rects(
+            window,
+            init.time,
+            Some(rect(&init.rootBounds)),
+            &rect(&init.boundingClientRect),
+            &rect(&init.intersectionRect),
+            init.isIntersecting,
+            init.intersectionRatio,
+            &init.target,
+        ))
+    }
+}
+
+impl IntersectionObserverEntryMethods for IntersectionObserverEntry {
+    /// https://w3c.github.io/IntersectionObserver/#dom-intersectionobserverentry-time
+    fn Time(&self) -> DOMHighResTimeStamp {
+        self.time
+    }
+
+    /// https://w3c.github.io/IntersectionObserver/#dom-intersectionobserverentry-rootbounds
+    fn GetRootBounds(&self) -> Option<DomRoot<DOMRectReadOnly>> {
+        self.root_bounds
+            .as_ref()
+            .map(|bounds| DomRoot::from_ref(&**bounds))
+    }
+
+    /// https://w3c.github.io/IntersectionObserver/#dom-intersectionobserverentry-boundingclientrect
+    fn BoundingClientRect(&self) -> DomRoot<DOMRectReadOnly> {
+        DomRoot::from_ref(&*self.bounding_client_rect)
+    }
+
+    /// https://w3c.github.io/IntersectionObserver/#dom-intersectionobserverentry-intersectionrect
+    fn IntersectionRect(&self) -> DomRoot<DOMRectReadOnly> {
+        DomRoot::from_ref(&*self.intersection_rect)
+    }
+
+    /// https://w3c.github.io/IntersectionObserver/#dom-intersectionobserverentry-isintersecting
+    fn IsIntersecting(&self) -> bool {
+        self.is_intersecting
+    }
+
+    /// https://w3c.github.io/IntersectionObserver/#dom-intersectionobserverentry-intersectionratio
+    fn IntersectionRatio(&self) -> Finite<f64> {
+        self.intersection_ratio
+    }
+
+    /// https://w3c.github.io/IntersectionObserver/#dom-intersectionobserverentry-target
+    fn Target(&self) -> DomRoot<Element> {
+        DomRoot::from_ref(&*self.target)
+    }
+}
@@ -0,0 +1,465 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::animationeffect::{parse_easing, AnimationEffect, Timing};
+use crate::dom::bindings::codegen::Bindings::DocumentBinding::DocumentMethods;
+use crate::dom::bindings::codegen::Bindings::KeyframeEffectBinding;
+use crate::dom::bindings::codegen::Bindings::KeyframeEffectBinding::KeyframeEffectMethods;
+use crate::dom::bindings::codegen::Bindings::WindowBinding::WindowMethods;
+use crate::dom::bindings::codegen::UnionTypes::StringOrStringSequence;
+use crate::dom::bindings::codegen::UnionTypes::StringStringRecordSequenceOrStringStringOrStringSequenceRecord as Keyframes;
+use crate::dom::bindings::codegen::UnionTypes::UnrestrictedDoubleOrKeyframeEffectOptions;
+use crate::dom::bindings::error::{Error, Fallible};
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::reflector::reflect_dom_object;
+use crate::dom::bindings::root::{DomRoot, MutNullableDom};
+use crate::dom::element::Element;
+use crate::dom::node::document_from_node;
+use crate::dom::window::Window;
+use dom_struct::dom_struct;
+use std::cell::RefCell;
+use style::animation::timing_function_output;
+use style::font_metrics::ServoMetricsProvider;
+use style::properties::animated_properties::AnimationValue;
+use style::properties::{
+    parse_one_declaration_into, ComputedValues, LonghandId, PropertyDeclaration, PropertyId,
+    SourcePropertyDeclaration, StyleBuilder,
+};
+use style::rule_cache::RuleCacheConditions;
+use style::values::animated::{Animate, Procedure};
+use style::values::computed::{Context, TimingFunction};
+use style::values::generics::easing::TimingKeyword;
+use style_traits::ParsingMode;
+
+/// https://drafts.csswg.org/web-animations/#keyframe
+#[derive(JSTraceable, MallocSizeOf)]
+struct Keyframe {
+    /// The offset the keyframe was given, if any
+    offset: Option<f64>,
+    /// https://drafts.csswg.org/web-animations/#computed-keyframe-offset
+    computed_offset: f64,
+    /// The easing of the interval from this keyframe to the next one
+    easing: TimingFunction,
+    declarations: Vec<PropertyDeclaration>,
+}
+
+impl Keyframe {
+    fn declaration(&self, property: LonghandId) -> Option<&PropertyDeclaration> {
+        self.declarations
+            .iter()
+            .find(|declaration| declaration.id().as_longhand() == Some(property))
+    }
+}
+
+#[dom_struct]
+pub struct KeyframeEffect {
+    animationeffect: AnimationEffect,
+    target: MutNullableDom<Element>,
+    /// Sorted by computed offset
+    keyframes: Vec<Keyframe>,
+}
+
+impl KeyframeEffect {
+    fn new_inherited(
+        target: Option<&Element>,
+        keyframes: Vec<Keyframe>,
+        timing: Timing,
+    ) -> KeyframeEffect {
+        KeyframeEffect {
+            animationeffect: AnimationEffect::new_inherited(timing),
+            target: MutNullableDom::new(target),
+            keyframes,
+        }
+    }
+
+    pub fn new(
+        window: &Window,
+        target: Option<&Element>,
+        keyframes: Option<Keyframes>,
+        timing: Timing,
+    ) -> Fallible<DomRoot<KeyframeEffect>> {
+        let keyframes = process_keyframes(window, keyframes)?;
+        Ok(reflect_dom_object(
+            Box::new(KeyframeEffect::new_inherited(target, keyframes, timing)),
+            window,
+            KeyframeEffectBinding::Wrap,
+        ))
+    }
+
+    /// https://drafts.csswg.org/web-animations/#dom-keyframeeffect-keyframeeffect
+    pub fn Constructor(
+        window: &Window,
+        target: Option<&Element>,
+        keyframes: Option<Keyframes>,
+        options: UnrestrictedDoubleOrKeyframeEffectOptions,
+    ) -> Fallible<DomRoot<KeyframeEffect>> {
+        let timing = match options {
+            UnrestrictedDoubleOrKeyframeEffectOptions::UnrestrictedDouble(duration) => {
+                timing_for_duration(duration)?
+            },
+            UnrestrictedDoubleOrKeyframeEffectOptions::KeyframeEffectOptions(options) => {
+                Timing::from_options(window, Some(&options.parent))?
+            },
+        };
+        KeyframeEffect::new(window, target, keyframes, timing)
+    }
+
+    /// The target of the effect, with the values the effect gives to the
+    /// properties of the target right now
+    ///
+    /// https://drafts.csswg.org/web-animations/#the-effect-value-of-a-keyframe-animation-effect
+    pub fn animated_declarations(&self) -> Option<(DomRoot<Element>, Vec<PropertyDeclaration>)> {
+        let target = self.target.get()?;
+        let (progress, _) = self.upcast::<AnimationEffect>().progress()?;
+
+        let document = document_from_node(&*target);
+        let device = document.device();
+        let style = target.style();
+        let style = style
+            .as_ref()
+            .map_or(device.default_computed_values(), |style| &**style);
+        let is_root_element = document
+            .GetDocumentElement()
+            .map_or(false, |root| root == target);
+        let mut rule_cache_conditions = RuleCacheConditions::default();
+        let mut context = Context {
+            is_root_element,
+            builder: StyleBuilder::for_animation(&device, style, None),
+            cached_system_font: None,
+            font_metrics_provider: &ServoMetricsProvider,
+            in_media_query: false,
+            quirks_mode: document.quirks_mode(),
+            for_smil_animation: false,
+            for_non_inherited_property: None,
+            rule_cache_conditions: RefCell::new(&mut rule_cache_conditions),
+        };
+
+        let declarations = animated_properties(&self.keyframes)
+            .into_iter()
+            .filter_map(|property| self.interpolate(property, progress, &mut context, style))
+            .collect();
+        Some((target, declarations))
+    }
+
+    /// The value of `property` at the iteration progress `progress`
+    fn interpolate(
+        &self,
+        property: LonghandId,
+        progress: f64,
+        context: &mut Context,
+        initial: &ComputedValues,
+    ) -> Option<PropertyDeclaration> {
+        // https://drafts.csswg.org/web-animations/#property-specific-keyframes
+        let keyframes: Vec<(&Keyframe, &PropertyDeclaration)> = self
+            .keyframes
+            .iter()
+            .filter_map(|keyframe| Some((keyframe, keyframe.declaration(property)?)))
+            .collect();
+
+        // Step 10
+        let at_offset = |offset: f64| {
+            keyframes
+                .iter()
+                .filter(|&&(keyframe, _)| keyframe.computed_offset == offset)
+                .count()
+        };
+        let (start, end) = if progress < 0. && at_offset(0.) > 1 {
+            (0, None)
+        } else if progress >= 1. && at_offset(1.) > 1 {
+            (keyframes.len() - 1, None)
+        } else {
+            let start = keyframes
+                .iter()
+                .rposition(|&(keyframe, _)| {
+                    keyframe.computed_offset <= progress && keyframe.computed_offset < 1.
+                })
+                .or_else(|| {
+                    keyframes
+                        .iter()
+                        .rposition(|&(keyframe, _)| keyframe.computed_offset == 0.)
+                })?;
+            (start, Some(start + 1).filter(|&end| end < keyframes.len()))
+        };
+
+        let (start_keyframe, start_declaration) = keyframes[start];
+        let from = AnimationValue::from_declaration(start_declaration, context, None, initial)?;
+        let end = match end {
+            Some(end) => end,
+            // Step 11
+            None => return Some(from.uncompute()),
+        };
+        let (end_keyframe, end_declaration) = keyframes[end];
+        let to = AnimationValue::from_declaration(end_declaration, context, None, initial)?;
+
+        // Steps 12 to 14
+        let interval = end_keyframe.computed_offset - start_keyframe.computed_offset;
+        let interval_progress = if interval > 0. {
+            (progress - start_keyframe.computed_offset) / interval
+        } else {
+            0.
+        };
+        let interval_progress = timing_function_output(
+            &start_keyframe.easing,
+            interval_progress,
+            KEYFRAME_EASING_EPSILON,
+        );
+
+        // Step 15
+        let value = from
+            .animate(
+                &to,
+                Procedure::Interpolate {
+                    progress: interval_progress,
+                },
+            )
+            .unwrap_or_else(|()| if interval_progress < 0.5 { from } else { to });
+        Some(value.uncompute())
+    }
+}
+
+/// The precision the easing between two keyframes is solved with
+const KEYFRAME_EASING_EPSILON: f64 = 1. / 200.;
+
+/// The properties `keyframes` give values to
+fn animated_properties(keyframes: &[Keyframe]) -> Vec<LonghandId> {
+    let mut properties = vec![];
+    for keyframe in keyframes {
+        for declaration in &keyframe.declarations {
+            if let Some(property) = declaration.id().as_longhand() {
+                if !properties.contains(&property) {
+                    properties.push(property);
+                }
+            }
+        }
+    }
+    properties
+}
+
+/// The timing of an effect given as just its duration
+pub fn timing_for_duration(duration: f64) -> Fallible<Timing> {
+    if !(duration >= 0.) {
+        return Err(Error::Type(format!("{} is not a valid duration", duration)));
+    }
+    Ok(Timing::new(Some(duration)))
+}
+
+/// The property a member of a keyframe gives values to, like
+/// `background-color` for `backgroundColor`
+///
+/// https://drafts.csswg.org/web-animations/#animation-property-name-to-idl-attribute-name
+fn property_for_member(member: &str) -> Option<PropertyId> {
+    let name = match member {
+        "cssFloat" => "float".to_owned(),
+        "cssOffset" => "offset".to_owned(),
+        "float" | "offset" => return None,
+        _ if member.contains('-') => return None,
+        _ => {
+            let mut name = String::with_capacity(member.len());
+            for c in member.chars() {
+                if c.is_ascii_uppercase() {
+                    name.push('-');
+                    name.push(c.to_ascii_lowercase());
+                } else {
+                    name.push(c);
+                }
+            }
+            name
+        },
+    };
+    PropertyId::parse_enabled_for_all_content(&name).ok()
+}
+
+/// The longhand declarations `value` gives to the property a keyframe member
+/// stands for, none for members that aren't properties and for invalid values
+fn parse_member(window: &Window, member: &str, value: &str) -> Vec<PropertyDeclaration> {
+    let id = match property_for_member(member) {
+        Some(id) => id,
+        None => return vec![],
+    };
+    let document = window.Document();
+    let mut declarations = SourcePropertyDeclaration::new();
+    let result = parse_one_declaration_into(
+        &mut declarations,
+        id,
+        value,
+        &document.base_url(),
+        window.css_error_reporter(),
+        ParsingMode::DEFAULT,
+        document.quirks_mode(),
+    );
+    match result {
+        Ok(()) => declarations.drain().collect(),
+        Err(()) => vec![],
+    }
+}
+
+fn parse_offset(offset: &str) -> Fallible<Option<f64>> {
+    if offset == "null" {
+        return Ok(None);
+    }
+    match offset.parse::<f64>() {
+        Ok(offset) if offset >= 0. && offset <= 1. => Ok(Some(offset)),
+        _ => Err(Error::Type(format!(
+            "{} is not a valid keyframe offset",
+            offset
+        ))),
+    }
+}
+
+fn values(value: &StringOrStringSequence) -> Vec<&str> {
+    match *value {
+        StringOrStringSequence::String(ref value) => vec![&**value],
+        StringOrStringSequence::StringSequence(ref values) => {
+            values.iter().map(|value| &**value).collect()
+        },
+    }
+}
+
+/// https://drafts.csswg.org/web-animations/#processing-a-keyframes-argument
+///
+/// Composite operations aren't supported, and neither are the implicit
+/// keyframes at offsets 0 and 1: every property needs keyframes there.
+fn process_keyframes(window: &Window, keyframes: Option<Keyframes>) -> Fallible<Vec<Keyframe>> {
+    let linear = TimingFunction::Keyword(TimingKeyword::Linear);
+    let mut processed = vec![];
+    match keyframes {
+        None => return Ok(processed),
+        Some(Keyframes::StringStringRecordSequence(keyframes)) => {
+            for keyframe in keyframes.iter() {
+                let mut offset = None;
+                let mut easing = linear;
+                let mut declarations = vec![];
+                for (member, value) in keyframe.iter() {
+                    match &**member {
+                        "offset" => offset = parse_offset(value)?,
+                        "easing" => easing = parse_easing(window, value)?,
+                        "composite" => {},
+                        member => declarations.extend(parse_member(window, member, value)),
+                    }
+                }
+                processed.push(Keyframe {
+                    offset,
+                    computed_offset: 0.,
+                    easing,
+                    declarations,
+                });
+            }
+            // Step 6 of processing a keyframes argument
+            let mut previous_offset = 0.;
+            for keyframe in &processed {
+                if let Some(offset) = keyframe.offset {
+                    if offset < previous_offset {
+                        return Err(Error::Type(
+                            "keyframe offsets must be in increasing order".to_owned(),
+                        ));
+                    }
+                    previous_offset = offset;
+                }
+            }
+            compute_missing_offsets(&mut processed);
+        },
+        Some(Keyframes::StringStringOrStringSequenceRecord(keyframes)) => {
+            let mut offsets = vec![];
+            let mut easings = vec![];
+            for (member, value) in keyframes.iter() {
+                match &**member {
+                    "offset" => {
+                        for offset in values(value) {
+                            offsets.push(parse_offset(offset)?);
+                        }
+                    },
+                    "easing" => {
+                        for easing in values(value) {
+                            easings.push(parse_easing(window, easing)?);
+                        }
+                    },
+                    _ => {},
+                }
+            }
+            for (member, value) in keyframes.iter() {
+                if &**member == "offset" || &**member == "easing" || &**member == "composite" {
+                    continue;
+                }
+                let mut property_keyframes: Vec<Keyframe> = values(value)
+                    .into_iter()
+                    .enumerate()
+                    .map(|(index, value)| Keyframe {
+                        offset: offsets.get(index).cloned().unwrap_or(None),
+                        computed_offset: 0.,
+                        easing: easings
+                            .get(index % easings.len().max(1))
+                            .cloned()
+                            .unwrap_or(linear),
+                        declarations: parse_member(window, member, value),
+                    })
+                    .collect();
+                compute_missing_offsets(&mut property_keyframes);
+                processed.extend(property_keyframes);
+            }
+            // Keep the keyframes of every property in order, they are
+            // looked up by property later.
+            processed.sort_by(|a, b| a.computed_offset.partial_cmp(&b.computed_offset).unwrap());
+        },
+    }
+
+    for property in animated_properties(&processed) {
+        let has_keyframe_at = |offset: f64| {
+            processed.iter().any(|keyframe| {
+                keyframe.computed_offset == offset && keyframe.declaration(property).is_some()
+            })
+        };
+        if !has_keyframe_at(0.) || !has_keyframe_at(1.) {
+            return Err(Error::NotSupported);
+        }
+    }
+    Ok(processed)
+}
+
+/// https://drafts.csswg.org/web-animations/#compute-missing-keyframe-offsets
+///
+/// Keyframes without an offset are spaced out evenly between their
+/// neighbours, the first and last keyframes default to 0 and 1.
+fn compute_missing_offsets(keyframes: &mut [Keyframe]) {
+    let len = keyframes.len();
+    if len == 0 {
+        return;
+    }
+    let mut offsets: Vec<Option<f64>> = keyframes.iter().map(|keyframe| keyframe.offset).collect();
+    if len > 1 && offsets[0].is_none() {
+        offsets[0] = Some(0.);
+    }
+    if offsets[len - 1].is_none() {
+        offsets[len - 1] = Some(1.);
+    }
+    let mut previous = 0;
+    for index in 1..len {
+        if let Some(offset) = offsets[index] {
+            let start = offsets[previous].unwrap();
+            let steps = (index - previous) as f64;
+            for between in previous + 1..index {
+                let step = (between - previous) as f64;
+                offsets[between] = Some(start + (offset - start) * step / steps);
+            }
+            previous = index;
+        }
+    }
+    for (keyframe, offset) in keyframes.iter_mut().zip(offsets) {
+        keyframe.computed_offset = offset.unwrap();
+    }
+}
+
+impl KeyframeEffectMethods for KeyframeEffect {
+    /// https://drafts.csswg.org/web-animations/#dom-keyframeeffect-target
+    fn GetTarget(&self) -> Option<DomRoot<Element>> {
+        self.target.get()
+    }
+
+    /// https://drafts.csswg.org/web-animations/#dom-keyframeeffect-target
+    fn SetTarget(&self, target: Option<&Element>) {
+        self.target.set(target);
+        if let Some(animation) = self.upcast::<AnimationEffect>().animation() {
+            animation.effect_timing_changed();
+        }
+    }
+}
@@ -446,6 +446,7 @@ macro_rules! global_event_handlers(
         event_handler!(emptied, GetOnemptied, SetOnemptied);
         event_handler!(ended, GetOnended, SetOnended);
         error_event_handler!(error, GetOnerror, SetOnerror);
+        event_handler!(gotpointercapture, GetOngotpointercapture, SetOngotpointercapture);
         event_handler!(input, GetOninput, SetOninput);
         event_handler!(invalid, GetOninvalid, SetOninvalid);
         event_handler!(keydown, GetOnkeydown, SetOnkeydown);
@@ -454,6 +455,7 @@ macro_rules! global_event_handlers(
         event_handler!(loadeddata, GetOnloadeddata, SetOnloadeddata);
         event_handler!(loadedmetadata, GetOnloadedmetadata, SetOnloadedmetadata);
         event_handler!(loadstart, GetOnloadstart, SetOnloadstart);
+        event_handler!(lostpointercapture, GetOnlostpointercapture, SetOnlostpointercapture);
         event_handler!(mousedown, GetOnmousedown, SetOnmousedown);
         event_handler!(mouseenter, GetOnmouseenter, SetOnmouseenter);
         event_handler!(mouseleave, GetOnmouseleave, SetOnmouseleave);
@@ -465,6 +467,14 @@ macro_rules! global_event_handlers(
         event_handler!(pause, GetOnpause, SetOnpause);
         event_handler!(play, GetOnplay, SetOnplay);
         event_handler!(playing, GetOnplaying, SetOnplaying);
+        event_handler!(pointercancel, GetOnpointercancel, SetOnpointercancel);
+        event_handler!(pointerdown, GetOnpointerdown, SetOnpointerdown);
+        event_handler!(pointerenter, GetOnpointerenter, SetOnpointerenter);
+        event_handler!(pointerleave, GetOnpointerleave, SetOnpointerleave);
+        event_handler!(pointermove, GetOnpointermove, SetOnpointermove);
+        event_handler!(pointerout, GetOnpointerout, SetOnpointerout);
+        event_handler!(pointerover, GetOnpointerover, SetOnpointerover);
+        event_handler!(pointerup, GetOnpointerup, SetOnpointerup);
         event_handler!(progress, GetOnprogress, SetOnprogress);
         event_handler!(ratechange, GetOnratechange, SetOnratechange);
         event_handler!(reset, GetOnreset, SetOnreset);
@@ -515,6 +525,8 @@ macro_rules! window_event_handlers(
         event_handler!(vrdisplayblur, GetOnvrdisplayblur, SetOnvrdisplayblur);
         event_handler!(vrdisplayfocus, GetOnvrdisplayfocus, SetOnvrdisplayfocus);
         event_handler!(vrdisplaypresentchange, GetOnvrdisplaypresentchange, SetOnvrdisplaypresentchange);
+        event_handler!(gamepadconnected, GetOngamepadconnected, SetOngamepadconnected);
+        event_handler!(gamepaddisconnected, GetOngamepaddisconnected, SetOngamepaddisconnected);
     );
     (ForwardToWindow) => (
         window_owned_event_handler!(afterprint, GetOnafterprint,
@@ -549,6 +561,9 @@ macro_rules! window_event_handlers(
         window_owned_event_handler!(vrdisplayblur, GetOnvrdisplayblur, SetOnvrdisplayblur);
         window_owned_event_handler!(vrdisplayfocus, GetOnvrdisplayfocus, SetOnvrdisplayfocus);
         window_owned_event_handler!(vrdisplaypresentchange, GetOnvrdisplaypresentchange, SetOnvrdisplaypresentchange);
+
+        window_owned_event_handler!(gamepadconnected, GetOngamepadconnected, SetOngamepadconnected);
+        window_owned_event_handler!(gamepaddisconnected, GetOngamepaddisconnected, SetOngamepaddisconnected);
     );
 );
 
@@ -632,3 +647,13 @@ macro_rules! handle_potential_webgl_error {
         handle_potential_webgl_error!($context, $call, ());
     };
 }
+
+macro_rules! optional_root_object_to_js_or_null {
+    ($cx: expr, $binding:expr) => {{
+        rooted!(in($cx) let mut rval = NullValue());
+        if let Some(object) = $binding {
+            object.to_jsval($cx, rval.handle_mut());
+        }
+        rval.get()
+    }};
+}
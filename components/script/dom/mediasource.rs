@@ -0,0 +1,323 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::MediaSourceBinding::{
+    self, EndOfStreamError, MediaSourceMethods, ReadyState,
+};
+use crate::dom::bindings::error::{Error, ErrorResult, Fallible};
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::num::Finite;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject};
+use crate::dom::bindings::root::{Dom, DomRoot, MutNullableDom};
+use crate::dom::bindings::str::DOMString;
+use crate::dom::eventtarget::EventTarget;
+use crate::dom::htmlmediaelement::HTMLMediaElement;
+use crate::dom::sourcebuffer::SourceBuffer;
+use crate::dom::sourcebufferlist::SourceBufferList;
+use crate::dom::window::Window;
+use dom_struct::dom_struct;
+use mime::{self, Mime};
+use servo_atoms::Atom;
+use std::cell::Cell;
+use std::f64;
+
+#[dom_struct]
+pub struct MediaSource {
+    eventtarget: EventTarget,
+    /// <https://w3c.github.io/media-source/#dom-mediasource-sourcebuffers>
+    source_buffers: Dom<SourceBufferList>,
+    /// <https://w3c.github.io/media-source/#dom-mediasource-activesourcebuffers>
+    active_source_buffers: Dom<SourceBufferList>,
+    /// <https://w3c.github.io/media-source/#dom-mediasource-readystate>
+    ready_state: Cell<ReadyState>,
+    /// <https://w3c.github.io/media-source/#dom-mediasource-duration>
+    duration: Cell<f64>,
+    /// The media element this media source is attached to, if any.
+    media_element: MutNullableDom<HTMLMediaElement>,
+    /// <https://w3c.github.io/media-source/#live-seekable-range>
+    live_seekable_range: Cell<Option<(f64, f64)>>,
+}
+
+impl MediaSource {
+    fn new_inherited(
+        source_buffers: &SourceBufferList,
+        active_source_buffers: &SourceBufferList,
+    ) -> MediaSource {
+        MediaSource {
+            eventtarget: EventTarget::new_inherited(),
+            source_buffers: Dom::from_ref(source_buffers),
+            active_source_buffers: Dom::from_ref(active_source_buffers),
+            ready_state: Cell::new(ReadyState::Closed),
+            duration: Cell::new(f64::NAN),
+            media_element: Default::default(),
+            live_seekable_range: Cell::new(None),
+        }
+    }
+
+    pub fn new(window: &Window) -> DomRoot<MediaSource> {
+        reflect_dom_object(
+            Box::new(MediaSource::new_inherited(
+                &SourceBufferList::new(window),
+                &SourceBufferList::new(window),
+            )),
+            window,
+            MediaSourceBinding::Wrap,
+        )
+    }
+
+    // https://w3c.github.io/media-source/#dom-mediasource-constructor
+    pub fn Constructor(window: &Window) -> Fallible<DomRoot<MediaSource>> {
+        Ok(MediaSource::new(window))
+    }
+
+    pub fn media_element(&self) -> Option<DomRoot<HTMLMediaElement>> {
+        self.media_element.get()
+    }
+
+    /// <https://w3c.github.io/media-source/#mediasource-attach>
+    ///
+    /// Step 2, the media element already checked that this media source is
+    /// closed.
+    pub fn attach(&self, media_element: &HTMLMediaElement) {
+        self.media_element.set(Some(media_element));
+        self.ready_state.set(ReadyState::Open);
+        self.queue_event(atom!("sourceopen"));
+    }
+
+    /// <https://w3c.github.io/media-source/#mediasource-detach>
+    pub fn detach(&self) {
+        // Steps 1-2.
+        self.ready_state.set(ReadyState::Closed);
+        self.duration.set(f64::NAN);
+        // Steps 3-4.
+        for source_buffer in self.active_source_buffers.source_buffers() {
+            self.active_source_buffers.remove(&source_buffer);
+        }
+        for source_buffer in self.source_buffers.source_buffers() {
+            source_buffer.removed_from_parent();
+            self.source_buffers.remove(&source_buffer);
+        }
+        // Step 5.
+        self.queue_event(atom!("sourceclose"));
+        self.media_element.set(None);
+    }
+
+    /// Sets the ready state back to open after an append or removal on an
+    /// ended media source.
+    pub fn reopen(&self) {
+        self.ready_state.set(ReadyState::Open);
+        self.queue_event(atom!("sourceopen"));
+    }
+
+    /// <https://w3c.github.io/media-source/#end-of-stream-algorithm>
+    pub fn end_of_stream(&self, error: Option<EndOfStreamError>) {
+        // Steps 1-2.
+        self.ready_state.set(ReadyState::Ended);
+        self.queue_event(atom!("sourceended"));
+
+        let media_element = match self.media_element.get() {
+            Some(media_element) => media_element,
+            None => return,
+        };
+        // Step 3.
+        if error.is_none() {
+            // Step 3.1.
+            let buffered = media_element.buffered_ranges();
+            if buffered.len() > 0 {
+                if let Ok(end) = buffered.end(buffered.len() - 1) {
+                    self.change_duration(end);
+                }
+            }
+        }
+        // Step 3.2 and the error cases.
+        media_element.media_source_ended(error);
+    }
+
+    /// <https://w3c.github.io/media-source/#duration-change-algorithm>
+    fn change_duration(&self, duration: f64) {
+        // Step 1.
+        if self.duration.get() == duration {
+            return;
+        }
+        // Steps 2-4.
+        // FIXME: Reject durations that would truncate the buffered media once
+        // the coded frames can be told apart.
+        self.duration.set(duration);
+        // Step 5.
+        if let Some(media_element) = self.media_element.get() {
+            media_element.media_source_duration_changed(duration);
+        }
+    }
+
+    fn is_updating(&self) -> bool {
+        self.source_buffers
+            .source_buffers()
+            .iter()
+            .any(|source_buffer| source_buffer.is_updating())
+    }
+
+    fn queue_event(&self, name: Atom) {
+        let global = self.global();
+        let window = global.as_window();
+        window
+            .task_manager()
+            .media_element_task_source()
+            .queue_simple_event(self.upcast(), name, window);
+    }
+
+    // https://w3c.github.io/media-source/#dom-mediasource-istypesupported
+    pub fn IsTypeSupported(_window: &Window, type_: DOMString) -> bool {
+        // Steps 1-2.
+        let mime = match type_.parse::<Mime>() {
+            Ok(mime) => mime,
+            Err(_) => return false,
+        };
+        // Steps 3-6.
+        // https://www.w3.org/TR/mse-byte-stream-format-registry/
+        match (mime.type_().as_str(), mime.subtype().as_str()) {
+            ("audio", "webm") |
+            ("video", "webm") |
+            ("audio", "mp4") |
+            ("video", "mp4") |
+            ("audio", "mpeg") |
+            ("audio", "aac") |
+            ("video", "mp2t") => true,
+            _ => false,
+        }
+    }
+}
+
+impl MediaSourceMethods for MediaSource {
+    // https://w3c.github.io/media-source/#dom-mediasource-sourcebuffers
+    fn SourceBuffers(&self) -> DomRoot<SourceBufferList> {
+        DomRoot::from_ref(&*self.source_buffers)
+    }
+
+    // https://w3c.github.io/media-source/#dom-mediasource-activesourcebuffers
+    fn ActiveSourceBuffers(&self) -> DomRoot<SourceBufferList> {
+        DomRoot::from_ref(&*self.active_source_buffers)
+    }
+
+    // https://w3c.github.io/media-source/#dom-mediasource-readystate
+    fn ReadyState(&self) -> ReadyState {
+        self.ready_state.get()
+    }
+
+    // https://w3c.github.io/media-source/#dom-mediasource-duration
+    fn Duration(&self) -> f64 {
+        if self.ready_state.get() == ReadyState::Closed {
+            return f64::NAN;
+        }
+        self.duration.get()
+    }
+
+    // https://w3c.github.io/media-source/#dom-mediasource-duration
+    fn SetDuration(&self, duration: f64) -> ErrorResult {
+        // Step 1.
+        if duration < 0. || duration.is_nan() {
+            return Err(Error::Type("The duration is out of range".to_owned()));
+        }
+        // Steps 2-3.
+        if self.ready_state.get() != ReadyState::Open || self.is_updating() {
+            return Err(Error::InvalidState);
+        }
+        // Step 4.
+        self.change_duration(duration);
+        Ok(())
+    }
+
+    // https://w3c.github.io/media-source/#dom-mediasource-onsourceopen
+    event_handler!(sourceopen, GetOnsourceopen, SetOnsourceopen);
+
+    // https://w3c.github.io/media-source/#dom-mediasource-onsourceended
+    event_handler!(sourceended, GetOnsourceended, SetOnsourceended);
+
+    // https://w3c.github.io/media-source/#dom-mediasource-onsourceclose
+    event_handler!(sourceclose, GetOnsourceclose, SetOnsourceclose);
+
+    // https://w3c.github.io/media-source/#dom-mediasource-addsourcebuffer
+    fn AddSourceBuffer(&self, type_: DOMString) -> Fallible<DomRoot<SourceBuffer>> {
+        // Step 1.
+        if type_.is_empty() {
+            return Err(Error::Type("The type is empty".to_owned()));
+        }
+        // Step 2.
+        if !MediaSource::IsTypeSupported(self.global().as_window(), type_.clone()) {
+            return Err(Error::NotSupported);
+        }
+        // Step 3.
+        // The player takes a single stream of segments.
+        if self.source_buffers.Length() > 0 {
+            return Err(Error::QuotaExceeded);
+        }
+        // Step 4.
+        if self.ready_state.get() != ReadyState::Open {
+            return Err(Error::InvalidState);
+        }
+        // Steps 5-6.
+        // https://www.w3.org/TR/mse-byte-stream-format-mpeg-audio/
+        let generate_timestamps = type_.parse::<Mime>().map_or(false, |mime| {
+            mime.type_() == mime::AUDIO && ["mpeg", "aac"].contains(&mime.subtype().as_str())
+        });
+        let source_buffer = SourceBuffer::new(self.global().as_window(), self, generate_timestamps);
+        // Step 7.
+        self.source_buffers.add(&source_buffer);
+        // The player plays all the tracks it gets.
+        self.active_source_buffers.add(&source_buffer);
+        // Step 8.
+        Ok(source_buffer)
+    }
+
+    // https://w3c.github.io/media-source/#dom-mediasource-removesourcebuffer
+    fn RemoveSourceBuffer(&self, source_buffer: &SourceBuffer) -> ErrorResult {
+        // Step 1.
+        if !self.source_buffers.contains(source_buffer) {
+            return Err(Error::NotFound);
+        }
+        // Steps 2-3.
+        source_buffer.removed_from_parent();
+        // Steps 4-9.
+        self.active_source_buffers.remove(source_buffer);
+        // Steps 10-11.
+        self.source_buffers.remove(source_buffer);
+        Ok(())
+    }
+
+    // https://w3c.github.io/media-source/#dom-mediasource-endofstream
+    fn EndOfStream(&self, error: Option<EndOfStreamError>) -> ErrorResult {
+        // Steps 1-2.
+        if self.ready_state.get() != ReadyState::Open || self.is_updating() {
+            return Err(Error::InvalidState);
+        }
+        // Step 3.
+        self.end_of_stream(error);
+        Ok(())
+    }
+
+    // https://w3c.github.io/media-source/#dom-mediasource-setliveseekablerange
+    fn SetLiveSeekableRange(&self, start: Finite<f64>, end: Finite<f64>) -> ErrorResult {
+        // Step 1.
+        if self.ready_state.get() != ReadyState::Open {
+            return Err(Error::InvalidState);
+        }
+        // Step 2.
+        if *start < 0. || *start > *end {
+            return Err(Error::Type("The seekable range is out of range".to_owned()));
+        }
+        // Step 3.
+        self.live_seekable_range.set(Some((*start, *end)));
+        Ok(())
+    }
+
+    // https://w3c.github.io/media-source/#dom-mediasource-clearliveseekablerange
+    fn ClearLiveSeekableRange(&self) -> ErrorResult {
+        // Step 1.
+        if self.ready_state.get() != ReadyState::Open {
+            return Err(Error::InvalidState);
+        }
+        // Step 2.
+        self.live_seekable_range.set(None);
+        Ok(())
+    }
+}
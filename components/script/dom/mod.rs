@@ -212,10 +212,15 @@ pub mod types {
     include!(concat!(env!("OUT_DIR"), "/build/InterfaceTypes.rs"));
 }
 
+pub mod abortcontroller;
+pub mod abortsignal;
 pub mod abstractworker;
 pub mod abstractworkerglobalscope;
 pub mod activation;
 pub mod analysernode;
+pub mod animation;
+pub mod animationeffect;
+pub mod animationtimeline;
 pub mod attr;
 pub mod audiobuffer;
 pub mod audiobuffersourcenode;
@@ -227,6 +232,9 @@ pub mod audioparam;
 pub mod audioscheduledsourcenode;
 pub mod audiotrack;
 pub mod audiotracklist;
+pub mod audioworklet;
+pub mod audioworkletglobalscope;
+pub mod audioworkletprocessor;
 pub mod baseaudiocontext;
 pub mod beforeunloadevent;
 pub mod bindings;
@@ -242,6 +250,8 @@ pub mod bluetoothremotegattdescriptor;
 pub mod bluetoothremotegattserver;
 pub mod bluetoothremotegattservice;
 pub mod bluetoothuuid;
+pub mod cache;
+pub mod cachestorage;
 pub mod canvasgradient;
 pub mod canvaspattern;
 pub mod canvasrenderingcontext2d;
@@ -250,6 +260,8 @@ pub mod channelmergernode;
 pub mod channelsplitternode;
 pub mod characterdata;
 pub mod client;
+pub mod clipboard;
+pub mod clipboarditem;
 pub mod closeevent;
 pub mod comment;
 pub mod compositionevent;
@@ -280,6 +292,7 @@ pub mod dissimilaroriginlocation;
 pub mod dissimilaroriginwindow;
 pub mod document;
 pub mod documentfragment;
+pub mod documenttimeline;
 pub mod documenttype;
 pub mod domexception;
 pub mod domimplementation;
@@ -291,20 +304,25 @@ pub mod dompointreadonly;
 pub mod domquad;
 pub mod domrect;
 pub mod domrectreadonly;
+pub mod domstringlist;
 pub mod domstringmap;
 pub mod domtokenlist;
 pub mod element;
+pub mod elementinternals;
 pub mod errorevent;
 pub mod event;
 pub mod eventsource;
 pub mod eventtarget;
 pub mod extendableevent;
 pub mod extendablemessageevent;
+pub mod fetchevent;
 pub mod file;
 pub mod filelist;
 pub mod filereader;
 pub mod filereadersync;
 pub mod focusevent;
+pub mod fontface;
+pub mod fontfaceset;
 pub mod formdata;
 pub mod formdataevent;
 pub mod gainnode;
@@ -312,8 +330,35 @@ pub mod gamepad;
 pub mod gamepadbutton;
 pub mod gamepadbuttonlist;
 pub mod gamepadevent;
+pub mod gamepadhapticactuator;
 pub mod gamepadlist;
+pub mod geometry;
 pub mod globalscope;
+pub mod gpu;
+pub mod gpuadapter;
+pub mod gpubindgroup;
+pub mod gpubindgrouplayout;
+pub mod gpubuffer;
+pub mod gpubufferusage;
+pub mod gpucanvascontext;
+pub mod gpucolorwrite;
+pub mod gpucommandbuffer;
+pub mod gpucommandencoder;
+pub mod gpucomputepassencoder;
+pub mod gpucomputepipeline;
+pub mod gpudevice;
+pub mod gpumapmode;
+pub mod gpupipelinelayout;
+pub mod gpuqueue;
+pub mod gpurenderpassencoder;
+pub mod gpurenderpipeline;
+pub mod gpusampler;
+pub mod gpushadermodule;
+pub mod gpushaderstage;
+pub mod gpuswapchain;
+pub mod gputexture;
+pub mod gputextureusage;
+pub mod gputextureview;
 pub mod hashchangeevent;
 pub mod headers;
 pub mod history;
@@ -388,15 +433,29 @@ pub mod htmltrackelement;
 pub mod htmlulistelement;
 pub mod htmlunknownelement;
 pub mod htmlvideoelement;
+pub mod idbcursor;
+pub mod idbcursorwithvalue;
+pub mod idbdatabase;
+pub mod idbfactory;
+pub mod idbobjectstore;
+pub mod idbopendbrequest;
+pub mod idbrequest;
+pub mod idbtransaction;
+pub mod idbversionchangeevent;
+pub mod imagebitmap;
 pub mod imagedata;
 pub mod inputevent;
+pub mod intersectionobserver;
+pub mod intersectionobserverentry;
 pub mod keyboardevent;
+pub mod keyframeeffect;
 pub mod location;
 pub mod mediadevices;
 pub mod mediaerror;
 pub mod medialist;
 pub mod mediaquerylist;
 pub mod mediaquerylistevent;
+pub mod mediasource;
 pub mod mediastream;
 pub mod messageevent;
 pub mod mimetype;
@@ -411,6 +470,7 @@ pub mod navigatorinfo;
 pub mod node;
 pub mod nodeiterator;
 pub mod nodelist;
+pub mod notification;
 pub mod offlineaudiocompletionevent;
 pub mod offlineaudiocontext;
 pub mod offscreencanvas;
@@ -421,6 +481,7 @@ pub mod paintrenderingcontext2d;
 pub mod paintsize;
 pub mod paintworkletglobalscope;
 pub mod pannernode;
+pub mod path2d;
 pub mod performance;
 pub mod performanceentry;
 pub mod performancemark;
@@ -434,6 +495,7 @@ pub mod permissions;
 pub mod permissionstatus;
 pub mod plugin;
 pub mod pluginarray;
+pub mod pointerevent;
 pub mod popstateevent;
 pub mod processinginstruction;
 pub mod progressevent;
@@ -442,8 +504,17 @@ pub mod promisenativehandler;
 pub mod promiserejectionevent;
 pub mod radionodelist;
 pub mod range;
+pub mod readablebytestreamcontroller;
+pub mod readablestream;
+pub mod readablestreamdefaultcontroller;
+pub mod readablestreamdefaultreader;
 pub mod request;
+pub mod resizeobserver;
+pub mod resizeobserverentry;
+pub mod resizeobserversize;
 pub mod response;
+pub mod rtcdatachannel;
+pub mod rtcdatachannelevent;
 pub mod rtcicecandidate;
 pub mod rtcpeerconnection;
 pub mod rtcpeerconnectioniceevent;
@@ -454,6 +525,8 @@ pub mod serviceworkercontainer;
 pub mod serviceworkerglobalscope;
 pub mod serviceworkerregistration;
 pub mod servoparser;
+pub mod sourcebuffer;
+pub mod sourcebufferlist;
 pub mod storage;
 pub mod storageevent;
 pub mod stylepropertymapreadonly;
@@ -515,9 +588,11 @@ pub mod webglobject;
 pub mod webglprogram;
 pub mod webglrenderbuffer;
 pub mod webglrenderingcontext;
+pub mod webglsampler;
 pub mod webglshader;
 pub mod webglshaderprecisionformat;
 pub mod webgltexture;
+pub mod webgltransformfeedback;
 pub mod webgluniformlocation;
 pub mod webglvertexarrayobjectoes;
 pub mod websocket;
@@ -535,16 +610,33 @@ pub mod xmlhttprequesteventtarget;
 pub mod xmlhttprequestupload;
 pub mod xmlserializer;
 pub mod xr;
+pub mod xranchor;
+pub mod xrboundedreferencespace;
+pub mod xrcamera;
+pub mod xrcompositionlayer;
 pub mod xrframe;
+pub mod xrhand;
+pub mod xrhittestresult;
+pub mod xrhittestsource;
+pub mod xrimagetrackingresult;
+pub mod xrinputsource;
+pub mod xrinputsourceevent;
+pub mod xrinputsourceschangeevent;
+pub mod xrjointpose;
+pub mod xrjointspace;
 pub mod xrlayer;
 pub mod xrpose;
+pub mod xrquadlayer;
+pub mod xrray;
 pub mod xrreferencespace;
 pub mod xrrenderstate;
 pub mod xrrigidtransform;
 pub mod xrsession;
+pub mod xrsessionevent;
 pub mod xrspace;
 pub mod xrstationaryreferencespace;
 pub mod xrview;
 pub mod xrviewerpose;
 pub mod xrviewport;
+pub mod xrwebglbinding;
 pub mod xrwebgllayer;
@@ -32,12 +32,13 @@ pub struct MouseEvent {
     alt_key: Cell<bool>,
     meta_key: Cell<bool>,
     button: Cell<i16>,
+    buttons: Cell<u16>,
     related_target: MutNullableDom<EventTarget>,
     point_in_target: Cell<Option<Point2D<f32>>>,
 }
 
 impl MouseEvent {
-    fn new_inherited() -> MouseEvent {
+    pub fn new_inherited() -> MouseEvent {
         MouseEvent {
             uievent: UIEvent::new_inherited(),
             screen_x: Cell::new(0),
@@ -49,6 +50,7 @@ impl MouseEvent {
             alt_key: Cell::new(false),
             meta_key: Cell::new(false),
             button: Cell::new(0),
+            buttons: Cell::new(0),
             related_target: Default::default(),
             point_in_target: Cell::new(None),
         }
@@ -129,12 +131,19 @@ impl MouseEvent {
             init.relatedTarget.deref(),
             None,
         );
+        event.set_buttons(init.buttons);
         Ok(event)
     }
 
     pub fn point_in_target(&self) -> Option<Point2D<f32>> {
         self.point_in_target.get()
     }
+
+    /// Sets the buttons that are pressed, which `initMouseEvent` leaves
+    /// untouched.
+    pub fn set_buttons(&self, buttons: u16) {
+        self.buttons.set(buttons);
+    }
 }
 
 impl MouseEventMethods for MouseEvent {
@@ -183,6 +192,11 @@ impl MouseEventMethods for MouseEvent {
         self.button.get()
     }
 
+    // https://w3c.github.io/uievents/#dom-mouseevent-buttons
+    fn Buttons(&self) -> u16 {
+        self.buttons.get()
+    }
+
     // https://w3c.github.io/uievents/#widl-MouseEvent-relatedTarget
     fn GetRelatedTarget(&self) -> Option<DomRoot<EventTarget>> {
         self.related_target.get()
@@ -2,14 +2,19 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
+use crate::dom::bindings::cell::DomRefCell;
 use crate::dom::bindings::codegen::Bindings::NavigatorBinding;
 use crate::dom::bindings::codegen::Bindings::NavigatorBinding::NavigatorMethods;
 use crate::dom::bindings::error::Error;
 use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
-use crate::dom::bindings::root::{DomRoot, MutNullableDom};
+use crate::dom::bindings::root::{Dom, DomRoot, MutNullableDom};
 use crate::dom::bindings::str::DOMString;
 use crate::dom::bluetooth::Bluetooth;
+use crate::dom::clipboard::Clipboard;
+use crate::dom::gamepad::Gamepad;
+use crate::dom::gamepadevent::GamepadEventType;
 use crate::dom::gamepadlist::GamepadList;
+use crate::dom::gpu::GPU;
 use crate::dom::mediadevices::MediaDevices;
 use crate::dom::mimetypearray::MimeTypeArray;
 use crate::dom::navigatorinfo;
@@ -20,6 +25,8 @@ use crate::dom::serviceworkercontainer::ServiceWorkerContainer;
 use crate::dom::window::Window;
 use crate::dom::xr::XR;
 use dom_struct::dom_struct;
+use script_traits::{GamepadEvent, GamepadIndex};
+use servo_config::pref;
 use std::rc::Rc;
 
 #[dom_struct]
@@ -32,7 +39,12 @@ pub struct Navigator {
     xr: MutNullableDom<XR>,
     mediadevices: MutNullableDom<MediaDevices>,
     gamepads: MutNullableDom<GamepadList>,
+    /// The gamepads polled by the embedder, which keep their place in
+    /// `gamepads` when they get disconnected
+    embedder_gamepads: DomRefCell<Vec<Dom<Gamepad>>>,
     permissions: MutNullableDom<Permissions>,
+    gpu: MutNullableDom<GPU>,
+    clipboard: MutNullableDom<Clipboard>,
 }
 
 impl Navigator {
@@ -46,7 +58,10 @@ impl Navigator {
             xr: Default::default(),
             mediadevices: Default::default(),
             gamepads: Default::default(),
+            embedder_gamepads: DomRefCell::new(Vec::new()),
             permissions: Default::default(),
+            gpu: Default::default(),
+            clipboard: Default::default(),
         }
     }
 
@@ -57,6 +72,52 @@ impl Navigator {
             NavigatorBinding::Wrap,
         )
     }
+
+    fn gamepad_list(&self) -> DomRoot<GamepadList> {
+        self.gamepads
+            .or_init(|| GamepadList::new(&self.global(), &[]))
+    }
+
+    fn find_embedder_gamepad(&self, index: GamepadIndex) -> Option<DomRoot<Gamepad>> {
+        self.embedder_gamepads
+            .borrow()
+            .iter()
+            .find(|g| g.gamepad_id() as usize == index.0)
+            .map(|g| DomRoot::from_ref(&**g))
+    }
+
+    /// Updates the gamepads polled by the embedder, firing the
+    /// gamepadconnected and gamepaddisconnected events
+    pub fn handle_gamepad_event(&self, event: GamepadEvent) {
+        if !pref!(dom.gamepad.enabled) {
+            return;
+        }
+        match event {
+            GamepadEvent::Connected(index, name) => {
+                // A gamepad that comes back gets the same object.
+                if let Some(gamepad) = self.find_embedder_gamepad(index) {
+                    return gamepad.update_connected(true);
+                }
+                let gamepad = Gamepad::new_standard(&self.global(), index.0 as u32, name);
+                self.embedder_gamepads
+                    .borrow_mut()
+                    .push(Dom::from_ref(&*gamepad));
+                self.gamepad_list()
+                    .add_if_not_exists(&[DomRoot::from_ref(&*gamepad)]);
+                gamepad.notify_event(GamepadEventType::Connected);
+            },
+            GamepadEvent::Disconnected(index) => {
+                if let Some(gamepad) = self.find_embedder_gamepad(index) {
+                    gamepad.update_connected(false);
+                }
+            },
+            GamepadEvent::Updated(index, update) => {
+                if let Some(gamepad) = self.find_embedder_gamepad(index) {
+                    gamepad.update_from_embedder(update);
+                }
+            },
+        }
+    }
 }
 
 impl NavigatorMethods for Navigator {
@@ -134,13 +195,12 @@ impl NavigatorMethods for Navigator {
 
     // https://www.w3.org/TR/gamepad/#navigator-interface-extension
     fn GetGamepads(&self) -> DomRoot<GamepadList> {
-        let root = self
-            .gamepads
-            .or_init(|| GamepadList::new(&self.global(), &[]));
+        let root = self.gamepad_list();
 
+        // The gamepads polled by the embedder were added as they got
+        // connected, WebVR ones are synced now.
         let vr_gamepads = self.Xr().get_gamepads();
         root.add_if_not_exists(&vr_gamepads);
-        // TODO: Add not VR related gamepads
         root
     }
     // https://w3c.github.io/permissions/#navigator-and-workernavigator-extension
@@ -171,4 +231,14 @@ impl NavigatorMethods for Navigator {
         self.mediadevices
             .or_init(|| MediaDevices::new(&self.global()))
     }
+
+    // https://gpuweb.github.io/gpuweb/#dom-navigator-gpu
+    fn Gpu(&self) -> DomRoot<GPU> {
+        self.gpu.or_init(|| GPU::new(&self.global()))
+    }
+
+    // https://w3c.github.io/clipboard-apis/#dom-navigator-clipboard
+    fn Clipboard(&self) -> DomRoot<Clipboard> {
+        self.clipboard.or_init(|| Clipboard::new(&self.global()))
+    }
 }
@@ -1100,6 +1100,7 @@ pub trait LayoutNodeHelpers {
 
     fn text_content(&self) -> String;
     fn selection(&self) -> Option<Range<usize>>;
+    fn composition(&self) -> Option<Range<usize>>;
     fn image_url(&self) -> Option<ServoUrl>;
     fn image_density(&self) -> Option<f64>;
     fn image_data(&self) -> Option<(Option<StdArc<Image>>, Option<ImageMetadata>)>;
@@ -1241,6 +1242,19 @@ impl LayoutNodeHelpers for LayoutDom<Node> {
         None
     }
 
+    #[allow(unsafe_code)]
+    fn composition(&self) -> Option<Range<usize>> {
+        if let Some(area) = self.downcast::<HTMLTextAreaElement>() {
+            return unsafe { area.composition_for_layout() };
+        }
+
+        if let Some(input) = self.downcast::<HTMLInputElement>() {
+            return unsafe { input.composition_for_layout() };
+        }
+
+        None
+    }
+
     #[allow(unsafe_code)]
     fn image_url(&self) -> Option<ServoUrl> {
         unsafe {
@@ -0,0 +1,363 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::callback::ExceptionHandling;
+use crate::dom::bindings::codegen::Bindings::NotificationBinding::{
+    self, NotificationDirection, NotificationMethods, NotificationOptions, NotificationPermission,
+    NotificationPermissionCallback,
+};
+use crate::dom::bindings::codegen::Bindings::PermissionStatusBinding::{
+    PermissionName, PermissionState,
+};
+use crate::dom::bindings::error::{Error, Fallible};
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::refcounted::Trusted;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::str::{DOMString, USVString};
+use crate::dom::eventtarget::EventTarget;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::permissions::{get_descriptor_permission_state, request_permission_to_use};
+use crate::dom::promise::Promise;
+use crate::dom::window::Window;
+use crate::task_source::{TaskSource, TaskSourceName};
+use dom_struct::dom_struct;
+use embedder_traits::Notification as EmbedderNotification;
+use embedder_traits::{EmbedderMsg, NotificationEvent, NotificationId};
+use ipc_channel::ipc::{self, IpcSender};
+use ipc_channel::router::ROUTER;
+use script_traits::ScriptMsg;
+use servo_url::ServoUrl;
+use std::cell::Cell;
+use std::rc::Rc;
+
+unsafe_no_jsmanaged_fields!(EmbedderNotification, NotificationId);
+
+#[dom_struct]
+pub struct Notification {
+    eventtarget: EventTarget,
+    #[ignore_malloc_size_of = "Defined in uuid"]
+    id: NotificationId,
+    #[ignore_malloc_size_of = "Defined in embedder_traits"]
+    data: EmbedderNotification,
+    dir: NotificationDirection,
+    lang: DOMString,
+    /// Whether the notification is in the list of notifications, i.e. it got
+    /// shown and wasn't closed yet.
+    shown: Cell<bool>,
+}
+
+impl Notification {
+    fn new_inherited(
+        data: EmbedderNotification,
+        dir: NotificationDirection,
+        lang: DOMString,
+    ) -> Notification {
+        Notification {
+            eventtarget: EventTarget::new_inherited(),
+            id: NotificationId::new(),
+            data,
+            dir,
+            lang,
+            shown: Cell::new(false),
+        }
+    }
+
+    pub fn new(
+        global: &GlobalScope,
+        data: EmbedderNotification,
+        dir: NotificationDirection,
+        lang: DOMString,
+    ) -> DomRoot<Notification> {
+        reflect_dom_object(
+            Box::new(Notification::new_inherited(data, dir, lang)),
+            global,
+            NotificationBinding::Wrap,
+        )
+    }
+
+    // https://notifications.spec.whatwg.org/#dom-notification-notification
+    pub fn Constructor(
+        window: &Window,
+        title: DOMString,
+        options: &NotificationOptions,
+    ) -> Fallible<DomRoot<Notification>> {
+        // Step 1 is about service workers, which don't get this interface.
+
+        // Step 2 - 3.
+        let global = window.upcast::<GlobalScope>();
+        let data = create_notification(global, title, options)?;
+        let notification = Notification::new(global, data, options.dir, options.lang.clone());
+
+        // Step 4 - 5.
+        let trusted = Trusted::new(&*notification);
+        let _ = global.dom_manipulation_task_source().queue(
+            task!(show_notification: move || {
+                trusted.root().show();
+            }),
+            global,
+        );
+
+        // Step 6.
+        Ok(notification)
+    }
+
+    // https://notifications.spec.whatwg.org/#dom-notification-permission
+    pub fn Permission(window: &Window) -> NotificationPermission {
+        notification_permission(window.upcast())
+    }
+
+    #[allow(unsafe_code)]
+    // https://notifications.spec.whatwg.org/#dom-notification-requestpermission
+    pub fn RequestPermission(
+        window: &Window,
+        deprecated_callback: Option<Rc<NotificationPermissionCallback>>,
+    ) -> Rc<Promise> {
+        // Step 1 - 2.
+        let global = window.upcast::<GlobalScope>();
+        let promise = unsafe { Promise::new_in_current_compartment(global) };
+
+        // Step 3.
+        let permission = convert_permission_state(request_permission_to_use(
+            PermissionName::Notifications,
+            global,
+        ));
+        if let Some(callback) = deprecated_callback {
+            let _ = callback.Call__(permission, ExceptionHandling::Report);
+        }
+        promise.resolve_native(&permission);
+
+        // Step 4.
+        promise
+    }
+
+    /// https://notifications.spec.whatwg.org/#show-steps
+    fn show(&self) {
+        let global = self.global();
+        if notification_permission(&global) != NotificationPermission::Granted {
+            self.upcast::<EventTarget>().fire_event(atom!("error"));
+            return;
+        }
+
+        let (sender, receiver) = ipc::channel().unwrap();
+        let trusted = Trusted::new(self);
+        let task_source = global.dom_manipulation_task_source();
+        let canceller = global.task_canceller(TaskSourceName::DOMManipulation);
+        ROUTER.add_route(
+            receiver.to_opaque(),
+            Box::new(move |message| {
+                let event: NotificationEvent = match message.to() {
+                    Ok(event) => event,
+                    Err(e) => return warn!("Invalid notification event ({})", e),
+                };
+                let this = trusted.clone();
+                let _ = task_source.queue_with_canceller(
+                    task!(handle_notification_event: move || {
+                        this.root().handle_event(event);
+                    }),
+                    &canceller,
+                );
+            }),
+        );
+
+        self.shown.set(true);
+        show_notification(&global, self.id, self.data.clone(), Some(sender));
+        self.upcast::<EventTarget>().fire_event(atom!("show"));
+    }
+
+    fn handle_event(&self, event: NotificationEvent) {
+        match event {
+            // https://notifications.spec.whatwg.org/#activating-a-notification
+            NotificationEvent::Click => {
+                if self.shown.get() {
+                    // TODO: Focus the window in the default action.
+                    self.upcast::<EventTarget>()
+                        .fire_cancelable_event(atom!("click"));
+                }
+            },
+            NotificationEvent::Close => self.close_steps(),
+        }
+    }
+
+    /// https://notifications.spec.whatwg.org/#close-steps
+    fn close_steps(&self) {
+        // Step 1.
+        if !self.shown.get() {
+            return;
+        }
+
+        // Step 3.
+        let trusted = Trusted::new(self);
+        let global = self.global();
+        let _ = global.dom_manipulation_task_source().queue(
+            task!(fire_notification_close: move || {
+                trusted.root().upcast::<EventTarget>().fire_event(atom!("close"));
+            }),
+            &global,
+        );
+
+        // Step 4.
+        self.shown.set(false);
+    }
+}
+
+impl NotificationMethods for Notification {
+    // https://notifications.spec.whatwg.org/#dom-notification-onclick
+    event_handler!(click, GetOnclick, SetOnclick);
+
+    // https://notifications.spec.whatwg.org/#dom-notification-onshow
+    event_handler!(show, GetOnshow, SetOnshow);
+
+    // https://notifications.spec.whatwg.org/#dom-notification-onerror
+    event_handler!(error, GetOnerror, SetOnerror);
+
+    // https://notifications.spec.whatwg.org/#dom-notification-onclose
+    event_handler!(close, GetOnclose, SetOnclose);
+
+    // https://notifications.spec.whatwg.org/#dom-notification-title
+    fn Title(&self) -> DOMString {
+        DOMString::from(self.data.title.clone())
+    }
+
+    // https://notifications.spec.whatwg.org/#dom-notification-dir
+    fn Dir(&self) -> NotificationDirection {
+        self.dir
+    }
+
+    // https://notifications.spec.whatwg.org/#dom-notification-lang
+    fn Lang(&self) -> DOMString {
+        self.lang.clone()
+    }
+
+    // https://notifications.spec.whatwg.org/#dom-notification-body
+    fn Body(&self) -> DOMString {
+        DOMString::from(self.data.body.clone())
+    }
+
+    // https://notifications.spec.whatwg.org/#dom-notification-tag
+    fn Tag(&self) -> DOMString {
+        DOMString::from(self.data.tag.clone())
+    }
+
+    // https://notifications.spec.whatwg.org/#dom-notification-image
+    fn Image(&self) -> USVString {
+        serialize_url(&self.data.image)
+    }
+
+    // https://notifications.spec.whatwg.org/#dom-notification-icon
+    fn Icon(&self) -> USVString {
+        serialize_url(&self.data.icon)
+    }
+
+    // https://notifications.spec.whatwg.org/#dom-notification-badge
+    fn Badge(&self) -> USVString {
+        serialize_url(&self.data.badge)
+    }
+
+    // https://notifications.spec.whatwg.org/#dom-notification-timestamp
+    fn Timestamp(&self) -> u64 {
+        self.data.timestamp
+    }
+
+    // https://notifications.spec.whatwg.org/#dom-notification-renotify
+    fn Renotify(&self) -> bool {
+        self.data.renotify
+    }
+
+    // https://notifications.spec.whatwg.org/#dom-notification-silent
+    fn Silent(&self) -> bool {
+        self.data.silent
+    }
+
+    // https://notifications.spec.whatwg.org/#dom-notification-requireinteraction
+    fn RequireInteraction(&self) -> bool {
+        self.data.require_interaction
+    }
+
+    // https://notifications.spec.whatwg.org/#dom-notification-close
+    fn Close(&self) {
+        if self.shown.get() {
+            let msg = ScriptMsg::ForwardToEmbedder(EmbedderMsg::CloseNotification(self.id));
+            if let Err(e) = self.global().script_to_constellation_chan().send(msg) {
+                warn!("Failed to close notification ({}).", e);
+            }
+        }
+        self.close_steps();
+    }
+}
+
+/// https://notifications.spec.whatwg.org/#create-a-notification
+pub fn create_notification(
+    global: &GlobalScope,
+    title: DOMString,
+    options: &NotificationOptions,
+) -> Fallible<EmbedderNotification> {
+    // Step 2 is about the actions, which aren't supported.
+
+    // Step 3.
+    if options.renotify && options.tag.is_empty() {
+        return Err(Error::Type(
+            "A notification can't renotify without a tag".to_owned(),
+        ));
+    }
+
+    // Step 7 - 9.
+    let base_url = global.api_base_url();
+    let parse_url =
+        |url: &Option<USVString>| url.as_ref().and_then(|url| base_url.join(&url.0).ok());
+
+    Ok(EmbedderNotification {
+        title: title.into(),
+        body: options.body.to_string(),
+        tag: options.tag.to_string(),
+        origin: global.origin().immutable().clone(),
+        image: parse_url(&options.image),
+        icon: parse_url(&options.icon),
+        badge: parse_url(&options.badge),
+        // Step 11.
+        timestamp: options.timestamp.unwrap_or_else(|| {
+            let time = time::get_time();
+            (time.sec * 1000 + (time.nsec / 1000000) as i64) as u64
+        }),
+        renotify: options.renotify,
+        silent: options.silent,
+        require_interaction: options.requireInteraction,
+    })
+}
+
+/// Asks the embedder to present `notification`, reporting what the user does
+/// with it to `listener`. The service worker registrations show their
+/// notifications this way too.
+pub fn show_notification(
+    global: &GlobalScope,
+    id: NotificationId,
+    notification: EmbedderNotification,
+    listener: Option<IpcSender<NotificationEvent>>,
+) {
+    let msg =
+        ScriptMsg::ForwardToEmbedder(EmbedderMsg::ShowNotification(id, notification, listener));
+    if let Err(e) = global.script_to_constellation_chan().send(msg) {
+        warn!("Failed to show notification ({}).", e);
+    }
+}
+
+/// https://notifications.spec.whatwg.org/#permission-model
+pub fn notification_permission(global: &GlobalScope) -> NotificationPermission {
+    convert_permission_state(get_descriptor_permission_state(
+        PermissionName::Notifications,
+        Some(global),
+    ))
+}
+
+fn convert_permission_state(state: PermissionState) -> NotificationPermission {
+    match state {
+        PermissionState::Granted => NotificationPermission::Granted,
+        PermissionState::Denied => NotificationPermission::Denied,
+        PermissionState::Prompt => NotificationPermission::Default,
+    }
+}
+
+fn serialize_url(url: &Option<ServoUrl>) -> USVString {
+    USVString(url.as_ref().map_or(String::new(), |url| url.to_string()))
+}
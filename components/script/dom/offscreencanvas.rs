@@ -6,7 +6,8 @@ use crate::dom::bindings::cell::DomRefCell;
 use crate::dom::bindings::codegen::Bindings::OffscreenCanvasBinding::{
     OffscreenCanvasMethods, OffscreenRenderingContext, Wrap as OffscreenCanvasWrap,
 };
-use crate::dom::bindings::error::Fallible;
+use crate::dom::bindings::error::{Error, Fallible};
+use crate::dom::bindings::refcounted::Trusted;
 use crate::dom::bindings::reflector::reflect_dom_object;
 use crate::dom::bindings::reflector::DomObject;
 use crate::dom::bindings::root::{Dom, DomRoot};
@@ -14,11 +15,18 @@ use crate::dom::bindings::str::DOMString;
 use crate::dom::eventtarget::EventTarget;
 use crate::dom::globalscope::GlobalScope;
 use crate::dom::htmlcanvaselement::HTMLCanvasElement;
+use crate::dom::imagebitmap::ImageBitmap;
+use crate::dom::node::window_from_node;
 use crate::dom::offscreencanvasrenderingcontext2d::OffscreenCanvasRenderingContext2D;
+use crate::task::TaskCanceller;
+use crate::task_source::dom_manipulation::DOMManipulationTaskSource;
+use crate::task_source::{TaskSource, TaskSourceName};
+use canvas_traits::canvas::{CanvasId, CanvasMsg, FromScriptMsg};
 use dom_struct::dom_struct;
 use euclid::Size2D;
 use js::jsapi::JSContext;
 use js::rust::HandleValue;
+use profile_traits::ipc;
 use ref_filter_map;
 use std::cell::Cell;
 use std::cell::Ref;
@@ -31,38 +39,69 @@ pub enum OffscreenCanvasContext {
     //WebGL2(Dom<WebGL2RenderingContext>),
 }
 
+/// What an offscreen canvas created by `transferControlToOffscreen` needs
+/// to reach its placeholder canvas element, even once it got transferred
+/// to a worker.
+pub struct OffscreenCanvasPlaceholder {
+    canvas: Trusted<HTMLCanvasElement>,
+    /// The bitmap of the placeholder in the canvas paint thread, which the
+    /// committed frames get copied to
+    canvas_id: CanvasId,
+    task_source: DOMManipulationTaskSource,
+    canceller: TaskCanceller,
+}
+
+unsafe_no_jsmanaged_fields!(OffscreenCanvasPlaceholder);
+
+impl OffscreenCanvasPlaceholder {
+    pub fn new(canvas: &HTMLCanvasElement, canvas_id: CanvasId) -> OffscreenCanvasPlaceholder {
+        let window = window_from_node(canvas);
+        let task_manager = window.task_manager();
+        OffscreenCanvasPlaceholder {
+            canvas: Trusted::new(canvas),
+            canvas_id: canvas_id,
+            task_source: task_manager.dom_manipulation_task_source(),
+            canceller: task_manager.task_canceller(TaskSourceName::DOMManipulation),
+        }
+    }
+}
+
 #[dom_struct]
 pub struct OffscreenCanvas {
     eventtarget: EventTarget,
-    height: Cell<u64>,
     width: Cell<u64>,
+    height: Cell<u64>,
     context: DomRefCell<Option<OffscreenCanvasContext>>,
-    placeholder: Option<Dom<HTMLCanvasElement>>,
+    #[ignore_malloc_size_of = "Trusted<T> has unclear ownership like Dom<T>"]
+    placeholder: DomRefCell<Option<OffscreenCanvasPlaceholder>>,
+    /// Whether this canvas got transferred to another global.
+    detached: Cell<bool>,
 }
 
 impl OffscreenCanvas {
     pub fn new_inherited(
-        height: u64,
         width: u64,
-        placeholder: Option<&HTMLCanvasElement>,
+        height: u64,
+        placeholder: Option<OffscreenCanvasPlaceholder>,
     ) -> OffscreenCanvas {
         OffscreenCanvas {
             eventtarget: EventTarget::new_inherited(),
-            height: Cell::new(height),
             width: Cell::new(width),
+            height: Cell::new(height),
             context: DomRefCell::new(None),
-            placeholder: placeholder.map(Dom::from_ref),
+            placeholder: DomRefCell::new(placeholder),
+            detached: Cell::new(false),
         }
     }
 
     pub fn new(
         global: &GlobalScope,
-        height: u64,
         width: u64,
-        placeholder: Option<&HTMLCanvasElement>,
+        height: u64,
+        placeholder: Option<OffscreenCanvasPlaceholder>,
     ) -> DomRoot<OffscreenCanvas> {
         reflect_dom_object(
-            Box::new(OffscreenCanvas::new_inherited(height, width, placeholder)),
+            Box::new(OffscreenCanvas::new_inherited(width, height, placeholder)),
             global,
             OffscreenCanvasWrap,
         )
@@ -70,21 +109,109 @@ impl OffscreenCanvas {
 
     pub fn Constructor(
         global: &GlobalScope,
-        height: u64,
         width: u64,
+        height: u64,
     ) -> Fallible<DomRoot<OffscreenCanvas>> {
-        let offscreencanvas = OffscreenCanvas::new(global, height, width, None);
+        let offscreencanvas = OffscreenCanvas::new(global, width, height, None);
         Ok(offscreencanvas)
     }
 
-    pub fn get_size(&self) -> Size2D<u64> {
-        Size2D::new(self.Width(), self.Height())
+    pub fn get_size(&self) -> Size2D<u32> {
+        Size2D::new(self.Width() as u32, self.Height() as u32)
     }
 
     pub fn context(&self) -> Option<Ref<OffscreenCanvasContext>> {
         ref_filter_map::ref_filter_map(self.context.borrow(), |ctx| ctx.as_ref())
     }
 
+    pub fn origin_is_clean(&self) -> bool {
+        match *self.context.borrow() {
+            Some(OffscreenCanvasContext::OffscreenContext2d(ref context)) => {
+                context.origin_is_clean()
+            },
+            None => true,
+        }
+    }
+
+    pub fn is_valid(&self) -> bool {
+        let size = self.get_size();
+        !self.detached.get() && size.width != 0 && size.height != 0
+    }
+
+    /// The premultiplied BGRA pixels of the bitmap, which are None when there
+    /// is no context yet, and thus all transparent black
+    pub fn fetch_all_data(&self) -> Option<(Option<Vec<u8>>, Size2D<u32>)> {
+        if !self.is_valid() {
+            return None;
+        }
+
+        let size = self.get_size();
+        let data = match *self.context.borrow() {
+            Some(OffscreenCanvasContext::OffscreenContext2d(ref context)) => {
+                let (sender, receiver) =
+                    ipc::channel(self.global().time_profiler_chan().clone()).unwrap();
+                let msg = CanvasMsg::FromScript(
+                    FromScriptMsg::SendPixels(sender),
+                    context.get_canvas_id(),
+                );
+                context.get_ipc_renderer().send(msg).unwrap();
+
+                Some(receiver.recv().unwrap().to_vec())
+            },
+            None => None,
+        };
+
+        Some((data, size))
+    }
+
+    /// Pushes the current bitmap to the placeholder canvas element, if this
+    /// canvas has one
+    pub fn commit(&self) {
+        let placeholder = self.placeholder.borrow();
+        let placeholder = match *placeholder {
+            Some(ref placeholder) => placeholder,
+            None => return,
+        };
+        let context = match self.context() {
+            Some(context) => context,
+            None => return,
+        };
+        let OffscreenCanvasContext::OffscreenContext2d(ref context) = *context;
+
+        let msg = CanvasMsg::FromScript(
+            FromScriptMsg::Commit(placeholder.canvas_id.clone()),
+            context.get_canvas_id(),
+        );
+        context.get_ipc_renderer().send(msg).unwrap();
+
+        // The placeholder lives in the event loop of the window that created
+        // it, which this canvas may not be running in anymore.
+        let canvas = placeholder.canvas.clone();
+        let size = context.get_size();
+        let _ = placeholder.task_source.queue_with_canceller(
+            task!(commit_to_placeholder: move || {
+                canvas.root().placeholder_committed(size);
+            }),
+            &placeholder.canceller,
+        );
+    }
+
+    /// Detaches this canvas for it to be transferred to another global,
+    /// returning its dimensions and placeholder
+    ///
+    /// https://html.spec.whatwg.org/multipage/#the-offscreencanvas-interface:transfer-steps
+    pub fn transfer(&self) -> Fallible<(u64, u64, Option<OffscreenCanvasPlaceholder>)> {
+        // Step 1.
+        if self.detached.get() || self.context.borrow().is_some() {
+            return Err(Error::InvalidState);
+        }
+
+        // Steps 2-4.
+        self.detached.set(true);
+        let placeholder = self.placeholder.borrow_mut().take();
+        Ok((self.Width(), self.Height(), placeholder))
+    }
+
     #[allow(unsafe_code)]
     fn get_or_init_2d_context(&self) -> Option<DomRoot<OffscreenCanvasRenderingContext2D>> {
         if let Some(ctx) = self.context() {
@@ -99,6 +226,15 @@ impl OffscreenCanvas {
         ));
         Some(context)
     }
+
+    // https://html.spec.whatwg.org/multipage/#offscreencanvas-set-bitmap-dimensions
+    fn recreate_context(&self) {
+        if let Some(OffscreenCanvasContext::OffscreenContext2d(ref context)) =
+            *self.context.borrow()
+        {
+            context.set_bitmap_dimensions(self.get_size());
+        }
+    }
 }
 
 impl OffscreenCanvasMethods for OffscreenCanvas {
@@ -109,8 +245,13 @@ impl OffscreenCanvasMethods for OffscreenCanvas {
         _cx: *mut JSContext,
         id: DOMString,
         _options: HandleValue,
-    ) -> Option<OffscreenRenderingContext> {
-        match &*id {
+    ) -> Fallible<Option<OffscreenRenderingContext>> {
+        // Step 2.
+        if self.detached.get() {
+            return Err(Error::InvalidState);
+        }
+
+        Ok(match &*id {
             "2d" => self
                 .get_or_init_2d_context()
                 .map(OffscreenRenderingContext::OffscreenCanvasRenderingContext2D),
@@ -121,7 +262,31 @@ impl OffscreenCanvasMethods for OffscreenCanvas {
                 .get_or_init_webgl2_context(cx, options)
                 .map(OffscreenRenderingContext::WebGL2RenderingContext),*/
             _ => None,
+        })
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-offscreencanvas-transfertoimagebitmap
+    fn TransferToImageBitmap(&self) -> Fallible<DomRoot<ImageBitmap>> {
+        // Steps 1-2.
+        if self.detached.get() || self.context.borrow().is_none() {
+            return Err(Error::InvalidState);
         }
+
+        // Step 3.
+        let size = self.get_size();
+        let data = match self.fetch_all_data() {
+            Some((Some(data), _)) => data,
+            _ => vec![0; size.area() as usize * 4],
+        };
+        let bitmap = ImageBitmap::new(&self.global(), data, size, self.origin_is_clean());
+
+        // Step 4.
+        // FIXME: This also resets the state of the context, it should only
+        // start over with a transparent black bitmap.
+        self.recreate_context();
+
+        // Step 5.
+        Ok(bitmap)
     }
 
     // https://html.spec.whatwg.org/multipage/#dom-offscreencanvas-width
@@ -132,6 +297,7 @@ impl OffscreenCanvasMethods for OffscreenCanvas {
     // https://html.spec.whatwg.org/multipage/#dom-offscreencanvas-width
     fn SetWidth(&self, value: u64) {
         self.width.set(value);
+        self.recreate_context();
     }
 
     // https://html.spec.whatwg.org/multipage/#dom-offscreencanvas-height
@@ -142,5 +308,6 @@ impl OffscreenCanvasMethods for OffscreenCanvas {
     // https://html.spec.whatwg.org/multipage/#dom-offscreencanvas-height
     fn SetHeight(&self, value: u64) {
         self.height.set(value);
+        self.recreate_context();
     }
 }
@@ -21,6 +21,7 @@ use crate::dom::canvasgradient::CanvasGradient;
 use crate::dom::canvaspattern::CanvasPattern;
 use crate::dom::canvasrenderingcontext2d::CanvasRenderingContext2D;
 use crate::dom::paintworkletglobalscope::PaintWorkletGlobalScope;
+use crate::dom::path2d::Path2D;
 use crate::dom::workletglobalscope::WorkletGlobalScope;
 use canvas_traits::canvas::CanvasImageData;
 use canvas_traits::canvas::CanvasMsg;
@@ -50,7 +51,7 @@ impl PaintRenderingContext2D {
             context: CanvasRenderingContext2D::new_inherited(
                 global.upcast(),
                 None,
-                image_cache,
+                Some(image_cache),
                 base_url,
                 size,
             ),
@@ -206,6 +207,26 @@ impl PaintRenderingContext2DMethods for PaintRenderingContext2D {
         self.context.IsPointInPath(x, y, fill_rule)
     }
 
+    // https://html.spec.whatwg.org/multipage/#dom-context-2d-fill
+    fn Fill_(&self, path: &Path2D, fill_rule: CanvasFillRule) {
+        self.context.Fill_(path, fill_rule)
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-context-2d-stroke
+    fn Stroke_(&self, path: &Path2D) {
+        self.context.Stroke_(path)
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-context-2d-clip
+    fn Clip_(&self, path: &Path2D, fill_rule: CanvasFillRule) {
+        self.context.Clip_(path, fill_rule)
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-context-2d-ispointinpath
+    fn IsPointInPath_(&self, path: &Path2D, x: f64, y: f64, fill_rule: CanvasFillRule) -> bool {
+        self.context.IsPointInPath_(path, x, y, fill_rule)
+    }
+
     // https://html.spec.whatwg.org/multipage/#dom-context-2d-drawimage
     fn DrawImage(&self, image: CanvasImageSource, dx: f64, dy: f64) -> ErrorResult {
         self.context.DrawImage(image, dx, dy)
@@ -345,6 +366,16 @@ impl PaintRenderingContext2DMethods for PaintRenderingContext2D {
         self.context.CreateRadialGradient(x0, y0, r0, x1, y1, r1)
     }
 
+    // https://html.spec.whatwg.org/multipage/#dom-context-2d-createconicgradient
+    fn CreateConicGradient(
+        &self,
+        start_angle: Finite<f64>,
+        x: Finite<f64>,
+        y: Finite<f64>,
+    ) -> DomRoot<CanvasGradient> {
+        self.context.CreateConicGradient(start_angle, x, y)
+    }
+
     // https://html.spec.whatwg.org/multipage/#dom-context-2d-createpattern
     fn CreatePattern(
         &self,
@@ -0,0 +1,406 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::DOMMatrixBinding::DOMMatrixInit;
+use crate::dom::bindings::codegen::Bindings::Path2DBinding;
+use crate::dom::bindings::codegen::Bindings::Path2DBinding::Path2DMethods;
+use crate::dom::bindings::codegen::UnionTypes::Path2DOrString;
+use crate::dom::bindings::error::{Error, ErrorResult, Fallible};
+use crate::dom::bindings::reflector::{reflect_dom_object, Reflector};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::dommatrixreadonly::dommatrixinit_to_matrix;
+use crate::dom::globalscope::GlobalScope;
+use canvas_traits::canvas::PathSegment;
+use dom_struct::dom_struct;
+use euclid::{Point2D, Rect, Size2D, Transform2D};
+use std::f32::consts::PI;
+use style::values::specified::svg_path::{CoordPair, PathCommand, SVGPathData};
+
+// https://html.spec.whatwg.org/multipage/#path2d
+#[dom_struct]
+pub struct Path2D {
+    reflector_: Reflector,
+    segments: DomRefCell<Vec<PathSegment>>,
+}
+
+impl Path2D {
+    fn new_inherited(segments: Vec<PathSegment>) -> Path2D {
+        Path2D {
+            reflector_: Reflector::new(),
+            segments: DomRefCell::new(segments),
+        }
+    }
+
+    pub fn new(global: &GlobalScope, segments: Vec<PathSegment>) -> DomRoot<Path2D> {
+        reflect_dom_object(
+            Box::new(Path2D::new_inherited(segments)),
+            global,
+            Path2DBinding::Wrap,
+        )
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-path2d
+    pub fn Constructor(
+        global: &GlobalScope,
+        path: Option<Path2DOrString>,
+    ) -> Fallible<DomRoot<Path2D>> {
+        let segments = match path {
+            None => vec![],
+            Some(Path2DOrString::Path2D(path)) => path.segments(),
+            Some(Path2DOrString::String(string)) => segments_from_svg_path(&string),
+        };
+        Ok(Path2D::new(global, segments))
+    }
+
+    /// The segments the path is made of, for the canvas paint thread.
+    pub fn segments(&self) -> Vec<PathSegment> {
+        self.segments.borrow().clone()
+    }
+
+    fn push(&self, segment: PathSegment) {
+        self.segments.borrow_mut().push(segment);
+    }
+}
+
+impl Path2DMethods for Path2D {
+    // https://html.spec.whatwg.org/multipage/#dom-path2d-addpath
+    fn AddPath(&self, path: &Path2D, transform: &DOMMatrixInit) -> ErrorResult {
+        // Step 1
+        let (_, matrix) = dommatrixinit_to_matrix(transform)?;
+        let transform = Transform2D::row_major(
+            matrix.m11 as f32,
+            matrix.m12 as f32,
+            matrix.m21 as f32,
+            matrix.m22 as f32,
+            matrix.m41 as f32,
+            matrix.m42 as f32,
+        );
+        // Step 2
+        if ![
+            transform.m11,
+            transform.m12,
+            transform.m21,
+            transform.m22,
+            transform.m31,
+            transform.m32,
+        ]
+        .iter()
+        .all(|value| value.is_finite())
+        {
+            return Ok(());
+        }
+
+        // Steps 3-5
+        let segments = path.segments();
+        self.push(PathSegment::AddPath(segments, transform));
+        Ok(())
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-context-2d-closepath
+    fn ClosePath(&self) {
+        self.push(PathSegment::ClosePath);
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-context-2d-moveto
+    fn MoveTo(&self, x: f64, y: f64) {
+        if !(x.is_finite() && y.is_finite()) {
+            return;
+        }
+        self.push(PathSegment::MoveTo(Point2D::new(x as f32, y as f32)));
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-context-2d-lineto
+    fn LineTo(&self, x: f64, y: f64) {
+        if !(x.is_finite() && y.is_finite()) {
+            return;
+        }
+        self.push(PathSegment::LineTo(Point2D::new(x as f32, y as f32)));
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-context-2d-quadraticcurveto
+    fn QuadraticCurveTo(&self, cpx: f64, cpy: f64, x: f64, y: f64) {
+        if !(cpx.is_finite() && cpy.is_finite() && x.is_finite() && y.is_finite()) {
+            return;
+        }
+        self.push(PathSegment::QuadraticCurveTo(
+            Point2D::new(cpx as f32, cpy as f32),
+            Point2D::new(x as f32, y as f32),
+        ));
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-context-2d-beziercurveto
+    fn BezierCurveTo(&self, cp1x: f64, cp1y: f64, cp2x: f64, cp2y: f64, x: f64, y: f64) {
+        if !([cp1x, cp1y, cp2x, cp2y, x, y]
+            .iter()
+            .all(|value| value.is_finite()))
+        {
+            return;
+        }
+        self.push(PathSegment::BezierCurveTo(
+            Point2D::new(cp1x as f32, cp1y as f32),
+            Point2D::new(cp2x as f32, cp2y as f32),
+            Point2D::new(x as f32, y as f32),
+        ));
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-context-2d-arcto
+    fn ArcTo(&self, cp1x: f64, cp1y: f64, cp2x: f64, cp2y: f64, r: f64) -> ErrorResult {
+        if !([cp1x, cp1y, cp2x, cp2y, r].iter().all(|x| x.is_finite())) {
+            return Ok(());
+        }
+        if r < 0.0 {
+            return Err(Error::IndexSize);
+        }
+
+        self.push(PathSegment::ArcTo(
+            Point2D::new(cp1x as f32, cp1y as f32),
+            Point2D::new(cp2x as f32, cp2y as f32),
+            r as f32,
+        ));
+        Ok(())
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-context-2d-rect
+    fn Rect(&self, x: f64, y: f64, width: f64, height: f64) {
+        if !([x, y, width, height].iter().all(|value| value.is_finite())) {
+            return;
+        }
+        self.push(PathSegment::Rect(Rect::new(
+            Point2D::new(x as f32, y as f32),
+            Size2D::new(width as f32, height as f32),
+        )));
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-context-2d-arc
+    fn Arc(&self, x: f64, y: f64, r: f64, start: f64, end: f64, ccw: bool) -> ErrorResult {
+        if !([x, y, r, start, end].iter().all(|x| x.is_finite())) {
+            return Ok(());
+        }
+        if r < 0.0 {
+            return Err(Error::IndexSize);
+        }
+
+        self.push(PathSegment::Arc(
+            Point2D::new(x as f32, y as f32),
+            r as f32,
+            start as f32,
+            end as f32,
+            ccw,
+        ));
+        Ok(())
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-context-2d-ellipse
+    fn Ellipse(
+        &self,
+        x: f64,
+        y: f64,
+        rx: f64,
+        ry: f64,
+        rotation: f64,
+        start: f64,
+        end: f64,
+        ccw: bool,
+    ) -> ErrorResult {
+        if !([x, y, rx, ry, rotation, start, end]
+            .iter()
+            .all(|x| x.is_finite()))
+        {
+            return Ok(());
+        }
+        if rx < 0.0 || ry < 0.0 {
+            return Err(Error::IndexSize);
+        }
+
+        self.push(PathSegment::Ellipse(
+            Point2D::new(x as f32, y as f32),
+            rx as f32,
+            ry as f32,
+            rotation as f32,
+            start as f32,
+            end as f32,
+            ccw,
+        ));
+        Ok(())
+    }
+}
+
+/// The segments of the path an SVG path string describes, which are none if
+/// the string isn't valid path data.
+///
+/// <https://svgwg.org/svg2-draft/paths.html#PathData>
+pub fn segments_from_svg_path(string: &str) -> Vec<PathSegment> {
+    if string.trim().is_empty() {
+        return vec![];
+    }
+    let data = match SVGPathData::parse_path_string(string) {
+        Ok(data) => data.normalize(),
+        Err(()) => return vec![],
+    };
+
+    let point = |pair: CoordPair| Point2D::new(pair.0, pair.1);
+    let reflect = |control: Point2D<f32>, around: Point2D<f32>| around + (around - control);
+
+    let mut segments = vec![];
+    let mut current = Point2D::zero();
+    let mut subpath_start = Point2D::zero();
+    // The last control points of the previous command if it was a cubic
+    // or a quadratic Bézier curve, which smooth curves reflect.
+    let mut cubic_control = None;
+    let mut quad_control = None;
+    for command in data.commands() {
+        let (mut next_cubic_control, mut next_quad_control) = (None, None);
+        match *command {
+            PathCommand::Unknown => {},
+            PathCommand::ClosePath => {
+                segments.push(PathSegment::ClosePath);
+                current = subpath_start;
+            },
+            PathCommand::MoveTo { point: to, .. } => {
+                current = point(to);
+                subpath_start = current;
+                segments.push(PathSegment::MoveTo(current));
+            },
+            PathCommand::LineTo { point: to, .. } => {
+                current = point(to);
+                segments.push(PathSegment::LineTo(current));
+            },
+            PathCommand::HorizontalLineTo { x, .. } => {
+                current = Point2D::new(x, current.y);
+                segments.push(PathSegment::LineTo(current));
+            },
+            PathCommand::VerticalLineTo { y, .. } => {
+                current = Point2D::new(current.x, y);
+                segments.push(PathSegment::LineTo(current));
+            },
+            PathCommand::CurveTo {
+                control1,
+                control2,
+                point: to,
+                ..
+            } => {
+                next_cubic_control = Some(point(control2));
+                current = point(to);
+                segments.push(PathSegment::BezierCurveTo(
+                    point(control1),
+                    point(control2),
+                    current,
+                ));
+            },
+            PathCommand::SmoothCurveTo {
+                control2,
+                point: to,
+                ..
+            } => {
+                let control1 = cubic_control.map_or(current, |control| reflect(control, current));
+                next_cubic_control = Some(point(control2));
+                current = point(to);
+                segments.push(PathSegment::BezierCurveTo(
+                    control1,
+                    point(control2),
+                    current,
+                ));
+            },
+            PathCommand::QuadBezierCurveTo {
+                control1,
+                point: to,
+                ..
+            } => {
+                next_quad_control = Some(point(control1));
+                current = point(to);
+                segments.push(PathSegment::QuadraticCurveTo(point(control1), current));
+            },
+            PathCommand::SmoothQuadBezierCurveTo { point: to, .. } => {
+                let control = quad_control.map_or(current, |control| reflect(control, current));
+                next_quad_control = Some(control);
+                current = point(to);
+                segments.push(PathSegment::QuadraticCurveTo(control, current));
+            },
+            PathCommand::EllipticalArc {
+                rx,
+                ry,
+                angle,
+                large_arc_flag,
+                sweep_flag,
+                point: to,
+                ..
+            } => {
+                let to = point(to);
+                segments.extend(elliptical_arc(
+                    current,
+                    to,
+                    rx,
+                    ry,
+                    angle,
+                    large_arc_flag.0,
+                    sweep_flag.0,
+                ));
+                current = to;
+            },
+        }
+        cubic_control = next_cubic_control;
+        quad_control = next_quad_control;
+    }
+    segments
+}
+
+/// The segment of an SVG elliptical arc from `from` to `to`, converted to
+/// the center parameterization of canvas ellipses.
+///
+/// <https://svgwg.org/svg2-draft/implnote.html#ArcConversionEndpointToCenter>
+fn elliptical_arc(
+    from: Point2D<f32>,
+    to: Point2D<f32>,
+    rx: f32,
+    ry: f32,
+    angle: f32,
+    large_arc: bool,
+    sweep: bool,
+) -> Option<PathSegment> {
+    // https://svgwg.org/svg2-draft/implnote.html#ArcOutOfRangeParameters
+    if from == to {
+        return None;
+    }
+    if rx == 0. || ry == 0. {
+        return Some(PathSegment::LineTo(to));
+    }
+    let (mut rx, mut ry) = (rx.abs(), ry.abs());
+    let rotation = (angle % 360.) * PI / 180.;
+    let (sin, cos) = rotation.sin_cos();
+
+    // Step 1
+    let (dx, dy) = ((from.x - to.x) / 2., (from.y - to.y) / 2.);
+    let x1 = cos * dx + sin * dy;
+    let y1 = -sin * dx + cos * dy;
+
+    let lambda = (x1 * x1) / (rx * rx) + (y1 * y1) / (ry * ry);
+    if lambda > 1. {
+        rx *= lambda.sqrt();
+        ry *= lambda.sqrt();
+    }
+
+    // Step 2
+    let numerator = rx * rx * ry * ry - rx * rx * y1 * y1 - ry * ry * x1 * x1;
+    let denominator = rx * rx * y1 * y1 + ry * ry * x1 * x1;
+    let mut coefficient = (numerator / denominator).max(0.).sqrt();
+    if large_arc == sweep {
+        coefficient = -coefficient;
+    }
+    let cx1 = coefficient * rx * y1 / ry;
+    let cy1 = -coefficient * ry * x1 / rx;
+
+    // Step 3
+    let center = Point2D::new(
+        cos * cx1 - sin * cy1 + (from.x + to.x) / 2.,
+        sin * cx1 + cos * cy1 + (from.y + to.y) / 2.,
+    );
+
+    // Step 4
+    let start = ((y1 - cy1) / ry).atan2((x1 - cx1) / rx);
+    let end = ((-y1 - cy1) / ry).atan2((-x1 - cx1) / rx);
+    Some(PathSegment::Ellipse(
+        center, rx, ry, rotation, start, end, !sweep,
+    ))
+}
@@ -17,9 +17,11 @@ use crate::dom::globalscope::GlobalScope;
 use crate::dom::permissionstatus::PermissionStatus;
 use crate::dom::promise::Promise;
 use dom_struct::dom_struct;
+use ipc_channel::ipc;
 use js::conversions::ConversionResult;
 use js::jsapi::{JSContext, JSObject};
 use js::jsval::{ObjectValue, UndefinedValue};
+use script_traits::{PermissionGrant, ScriptMsg};
 #[cfg(target_os = "linux")]
 use servo_config::opts;
 use servo_config::pref;
@@ -140,12 +142,11 @@ impl Permissions {
 
                     &Operation::Revoke => {
                         // (Revoke) Step 3.
-                        let globalscope = self.global();
-                        globalscope
-                            .as_window()
-                            .permission_state_invocation_results()
-                            .borrow_mut()
-                            .remove(&root_desc.name.to_string());
+                        store_permission_state(
+                            &self.global(),
+                            root_desc.name,
+                            PermissionState::Prompt,
+                        );
 
                         // (Revoke) Step 4.
                         Bluetooth::permission_revoke(&bluetooth_desc, &result)
@@ -173,12 +174,11 @@ impl Permissions {
 
                     &Operation::Revoke => {
                         // (Revoke) Step 3.
-                        let globalscope = self.global();
-                        globalscope
-                            .as_window()
-                            .permission_state_invocation_results()
-                            .borrow_mut()
-                            .remove(&root_desc.name.to_string());
+                        store_permission_state(
+                            &self.global(),
+                            root_desc.name,
+                            PermissionState::Prompt,
+                        );
 
                         // (Revoke) Step 4.
                         Permissions::permission_revoke(&root_desc, &status);
@@ -262,20 +262,8 @@ impl PermissionAlgorithm for Permissions {
         match status.State() {
             // Step 3.
             PermissionState::Prompt => {
-                let perm_name = status.get_query();
-                // https://w3c.github.io/permissions/#request-permission-to-use (Step 3 - 4)
-                let state = prompt_user(&format!(
-                    "{} {} ?",
-                    REQUEST_DIALOG_MESSAGE,
-                    perm_name.clone()
-                ));
-
                 let globalscope = GlobalScope::current().expect("No current global object");
-                globalscope
-                    .as_window()
-                    .permission_state_invocation_results()
-                    .borrow_mut()
-                    .insert(perm_name.to_string(), state);
+                request_permission_to_use(status.get_query(), &globalscope);
             },
 
             // Step 2.
@@ -305,45 +293,86 @@ pub fn get_descriptor_permission_state(
     // The current solution is a workaround with a message box to warn about this,
     // if the feature is not allowed in non-secure contexcts,
     // and let the user decide to grant the permission or not.
-    let state = if allowed_in_nonsecure_contexts(&permission_name) {
-        PermissionState::Prompt
-    } else {
-        if pref!(dom.permissions.testing.allowed_in_nonsecure_contexts) {
-            PermissionState::Granted
-        } else {
-            settings
-                .as_window()
-                .permission_state_invocation_results()
-                .borrow_mut()
-                .remove(&permission_name.to_string());
-            prompt_user(&format!(
-                "The {} {}",
-                permission_name, NONSECURE_DIALOG_MESSAGE
-            ))
-        }
-    };
-
-    // Step 3.
-    if let Some(prev_result) = settings
-        .as_window()
-        .permission_state_invocation_results()
-        .borrow()
-        .get(&permission_name.to_string())
+    let allowed_in_nonsecure_contexts = allowed_in_nonsecure_contexts(&permission_name);
+    if !allowed_in_nonsecure_contexts &&
+        pref!(dom.permissions.testing.allowed_in_nonsecure_contexts)
     {
-        return prev_result.clone();
+        return PermissionState::Granted;
     }
 
-    // Store the invocation result
-    settings
-        .as_window()
-        .permission_state_invocation_results()
-        .borrow_mut()
-        .insert(permission_name.to_string(), state);
+    // Step 3.
+    if let Some(state) = stored_permission_state(&settings, permission_name) {
+        return state;
+    }
+    if !allowed_in_nonsecure_contexts {
+        let state = prompt_user(&format!(
+            "The {} {}",
+            permission_name, NONSECURE_DIALOG_MESSAGE
+        ));
+        store_permission_state(&settings, permission_name, state);
+        return state;
+    }
 
     // Step 4.
+    PermissionState::Prompt
+}
+
+// https://w3c.github.io/permissions/#request-permission-to-use
+pub fn request_permission_to_use(
+    permission_name: PermissionName,
+    env_settings_obj: &GlobalScope,
+) -> PermissionState {
+    // Step 1 - 2.
+    let state = get_descriptor_permission_state(permission_name, Some(env_settings_obj));
+    if state != PermissionState::Prompt {
+        return state;
+    }
+
+    // Step 3 - 4.
+    let state = prompt_user(&format!("{} {} ?", REQUEST_DIALOG_MESSAGE, permission_name));
+    store_permission_state(env_settings_obj, permission_name, state);
+
+    // Step 5.
     state
 }
 
+/// The permission of the origin of `global` to use the feature, if the user
+/// answered, from the store of the constellation.
+fn stored_permission_state(
+    global: &GlobalScope,
+    permission_name: PermissionName,
+) -> Option<PermissionState> {
+    let (sender, receiver) = ipc::channel().expect("ipc channel failure");
+    let msg = ScriptMsg::GetPermission(permission_name.to_string(), sender);
+    if let Err(e) = global.script_to_constellation_chan().send(msg) {
+        warn!("Failed to get permission ({}).", e);
+        return None;
+    }
+    match receiver.recv() {
+        Ok(Some(PermissionGrant::Granted)) => Some(PermissionState::Granted),
+        Ok(Some(PermissionGrant::Denied)) => Some(PermissionState::Denied),
+        Ok(None) | Err(_) => None,
+    }
+}
+
+/// Stores the permission of the origin of `global` to use the feature, which
+/// gets forgotten for `PermissionState::Prompt`.
+fn store_permission_state(
+    global: &GlobalScope,
+    permission_name: PermissionName,
+    state: PermissionState,
+) {
+    let grant = match state {
+        PermissionState::Granted => Some(PermissionGrant::Granted),
+        PermissionState::Denied => Some(PermissionGrant::Denied),
+        PermissionState::Prompt => None,
+    };
+    let msg = ScriptMsg::SetPermission(permission_name.to_string(), grant);
+    if let Err(e) = global.script_to_constellation_chan().send(msg) {
+        warn!("Failed to store permission ({}).", e);
+    }
+}
+
 #[cfg(target_os = "linux")]
 fn prompt_user(message: &str) -> PermissionState {
     if opts::get().headless {
@@ -391,5 +420,11 @@ fn allowed_in_nonsecure_contexts(permission_name: &PermissionName) -> bool {
         PermissionName::Bluetooth => false,
         // https://storage.spec.whatwg.org/#dom-permissionname-persistent-storage
         PermissionName::Persistent_storage => false,
+        // https://w3c.github.io/clipboard-apis/#dom-permissionname-clipboard-read
+        PermissionName::Clipboard_read => false,
+        // https://w3c.github.io/clipboard-apis/#dom-permissionname-clipboard-write
+        PermissionName::Clipboard_write => false,
+        // https://immersive-web.github.io/webxr/#permissions
+        PermissionName::Xr => false,
     }
 }
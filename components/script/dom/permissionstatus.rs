@@ -7,10 +7,12 @@ use crate::dom::bindings::codegen::Bindings::PermissionStatusBinding::Permission
 use crate::dom::bindings::codegen::Bindings::PermissionStatusBinding::{
     self, PermissionDescriptor, PermissionName,
 };
+use crate::dom::bindings::inheritance::Castable;
 use crate::dom::bindings::reflector::reflect_dom_object;
 use crate::dom::bindings::root::DomRoot;
 use crate::dom::eventtarget::EventTarget;
 use crate::dom::globalscope::GlobalScope;
+use crate::dom::window::Window;
 use dom_struct::dom_struct;
 use std::cell::Cell;
 use std::fmt::{self, Display, Formatter};
@@ -33,11 +35,16 @@ impl PermissionStatus {
     }
 
     pub fn new(global: &GlobalScope, query: &PermissionDescriptor) -> DomRoot<PermissionStatus> {
-        reflect_dom_object(
+        let status = reflect_dom_object(
             Box::new(PermissionStatus::new_inherited(query.name)),
             global,
             PermissionStatusBinding::Wrap,
-        )
+        );
+        // TODO: Update the statuses of the workers too.
+        if let Some(window) = global.downcast::<Window>() {
+            window.track_permission_status(&status);
+        }
+        status
     }
 
     pub fn set_state(&self, state: PermissionState) {
@@ -0,0 +1,208 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::MouseEventBinding::MouseEventMethods;
+use crate::dom::bindings::codegen::Bindings::PointerEventBinding;
+use crate::dom::bindings::codegen::Bindings::PointerEventBinding::PointerEventMethods;
+use crate::dom::bindings::error::Fallible;
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::num::Finite;
+use crate::dom::bindings::reflector::reflect_dom_object;
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::str::DOMString;
+use crate::dom::event::{EventBubbles, EventCancelable};
+use crate::dom::eventtarget::EventTarget;
+use crate::dom::mouseevent::MouseEvent;
+use crate::dom::window::Window;
+use dom_struct::dom_struct;
+use std::cell::Cell;
+
+/// The `pointerId` of the mouse, which is always active.
+///
+/// <https://w3c.github.io/pointerevents/#dom-pointerevent-pointerid>
+pub const MOUSE_POINTER_ID: i32 = 1;
+
+#[dom_struct]
+pub struct PointerEvent {
+    mouseevent: MouseEvent,
+    pointer_id: Cell<i32>,
+    width: Cell<f64>,
+    height: Cell<f64>,
+    pressure: Cell<f32>,
+    tangential_pressure: Cell<f32>,
+    tilt_x: Cell<i32>,
+    tilt_y: Cell<i32>,
+    twist: Cell<i32>,
+    pointer_type: DomRefCell<DOMString>,
+    is_primary: Cell<bool>,
+}
+
+impl PointerEvent {
+    fn new_inherited() -> PointerEvent {
+        PointerEvent {
+            mouseevent: MouseEvent::new_inherited(),
+            pointer_id: Cell::new(0),
+            width: Cell::new(1.),
+            height: Cell::new(1.),
+            pressure: Cell::new(0.),
+            tangential_pressure: Cell::new(0.),
+            tilt_x: Cell::new(0),
+            tilt_y: Cell::new(0),
+            twist: Cell::new(0),
+            pointer_type: DomRefCell::new(DOMString::new()),
+            is_primary: Cell::new(false),
+        }
+    }
+
+    pub fn new_uninitialized(window: &Window) -> DomRoot<PointerEvent> {
+        reflect_dom_object(
+            Box::new(PointerEvent::new_inherited()),
+            window,
+            PointerEventBinding::Wrap,
+        )
+    }
+
+    /// Creates a pointer event for a pointer without pressure sensitivity
+    /// nor tilt, the way the mouse and the touch points are reported.
+    pub fn new(
+        window: &Window,
+        type_: DOMString,
+        can_bubble: EventBubbles,
+        cancelable: EventCancelable,
+        client_x: i32,
+        client_y: i32,
+        button: i16,
+        buttons: u16,
+        related_target: Option<&EventTarget>,
+        pointer_id: i32,
+        pointer_type: &str,
+        is_primary: bool,
+    ) -> DomRoot<PointerEvent> {
+        let ev = PointerEvent::new_uninitialized(window);
+        let mouse_event = ev.upcast::<MouseEvent>();
+        mouse_event.InitMouseEvent(
+            type_,
+            bool::from(can_bubble),
+            bool::from(cancelable),
+            Some(window),
+            0,
+            client_x,
+            client_y, // TODO: Get real screen coordinates?
+            client_x,
+            client_y,
+            false,
+            false,
+            false,
+            false,
+            button,
+            related_target,
+        );
+        mouse_event.set_buttons(buttons);
+        ev.pointer_id.set(pointer_id);
+        // https://w3c.github.io/pointerevents/#dom-pointerevent-pressure
+        ev.pressure.set(if buttons != 0 { 0.5 } else { 0. });
+        *ev.pointer_type.borrow_mut() = DOMString::from(pointer_type);
+        ev.is_primary.set(is_primary);
+        ev
+    }
+
+    pub fn Constructor(
+        window: &Window,
+        type_: DOMString,
+        init: &PointerEventBinding::PointerEventInit,
+    ) -> Fallible<DomRoot<PointerEvent>> {
+        let mouse_init = &init.parent;
+        let bubbles = EventBubbles::from(mouse_init.parent.parent.parent.bubbles);
+        let cancelable = EventCancelable::from(mouse_init.parent.parent.parent.cancelable);
+        let ev = PointerEvent::new_uninitialized(window);
+        let mouse_event = ev.upcast::<MouseEvent>();
+        mouse_event.InitMouseEvent(
+            type_,
+            bool::from(bubbles),
+            bool::from(cancelable),
+            mouse_init.parent.parent.view.deref(),
+            mouse_init.parent.parent.detail,
+            mouse_init.screenX,
+            mouse_init.screenY,
+            mouse_init.clientX,
+            mouse_init.clientY,
+            mouse_init.parent.ctrlKey,
+            mouse_init.parent.altKey,
+            mouse_init.parent.shiftKey,
+            mouse_init.parent.metaKey,
+            mouse_init.button,
+            mouse_init.relatedTarget.deref(),
+        );
+        mouse_event.set_buttons(mouse_init.buttons);
+        ev.pointer_id.set(init.pointerId);
+        ev.width.set(*init.width);
+        ev.height.set(*init.height);
+        ev.pressure.set(*init.pressure);
+        ev.tangential_pressure.set(*init.tangentialPressure);
+        ev.tilt_x.set(init.tiltX);
+        ev.tilt_y.set(init.tiltY);
+        ev.twist.set(init.twist);
+        *ev.pointer_type.borrow_mut() = init.pointerType.clone();
+        ev.is_primary.set(init.isPrimary);
+        Ok(ev)
+    }
+}
+
+impl PointerEventMethods for PointerEvent {
+    // https://w3c.github.io/pointerevents/#dom-pointerevent-pointerid
+    fn PointerId(&self) -> i32 {
+        self.pointer_id.get()
+    }
+
+    // https://w3c.github.io/pointerevents/#dom-pointerevent-width
+    fn Width(&self) -> Finite<f64> {
+        Finite::wrap(self.width.get())
+    }
+
+    // https://w3c.github.io/pointerevents/#dom-pointerevent-height
+    fn Height(&self) -> Finite<f64> {
+        Finite::wrap(self.height.get())
+    }
+
+    // https://w3c.github.io/pointerevents/#dom-pointerevent-pressure
+    fn Pressure(&self) -> Finite<f32> {
+        Finite::wrap(self.pressure.get())
+    }
+
+    // https://w3c.github.io/pointerevents/#dom-pointerevent-tangentialpressure
+    fn TangentialPressure(&self) -> Finite<f32> {
+        Finite::wrap(self.tangential_pressure.get())
+    }
+
+    // https://w3c.github.io/pointerevents/#dom-pointerevent-tiltx
+    fn TiltX(&self) -> i32 {
+        self.tilt_x.get()
+    }
+
+    // https://w3c.github.io/pointerevents/#dom-pointerevent-tilty
+    fn TiltY(&self) -> i32 {
+        self.tilt_y.get()
+    }
+
+    // https://w3c.github.io/pointerevents/#dom-pointerevent-twist
+    fn Twist(&self) -> i32 {
+        self.twist.get()
+    }
+
+    // https://w3c.github.io/pointerevents/#dom-pointerevent-pointertype
+    fn PointerType(&self) -> DOMString {
+        self.pointer_type.borrow().clone()
+    }
+
+    // https://w3c.github.io/pointerevents/#dom-pointerevent-isprimary
+    fn IsPrimary(&self) -> bool {
+        self.is_primary.get()
+    }
+
+    // https://dom.spec.whatwg.org/#dom-event-istrusted
+    fn IsTrusted(&self) -> bool {
+        self.mouseevent.IsTrusted()
+    }
+}
@@ -0,0 +1,279 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::ReadableByteStreamControllerBinding::{
+    self, ReadableByteStreamControllerMethods,
+};
+use crate::dom::bindings::error::{Error, ErrorResult};
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::promise::Promise;
+use crate::dom::readablestream::{Chunk, ReadableStream, ReadableStreamController};
+use crate::dom::readablestream::{ReadableStreamState, SourceReaction, UnderlyingSource};
+use dom_struct::dom_struct;
+use js::jsapi::JSContext;
+use js::rust::{CustomAutoRooterGuard, HandleValue};
+use js::typedarray::ArrayBufferView;
+use std::cell::Cell;
+use std::collections::VecDeque;
+use std::rc::Rc;
+
+#[dom_struct]
+pub struct ReadableByteStreamController {
+    reflector_: Reflector,
+    /// https://streams.spec.whatwg.org/#readablebytestreamcontroller-controlledreadablebytestream
+    stream: Dom<ReadableStream>,
+    #[ignore_malloc_size_of = "Defined in rust-mozjs"]
+    source: UnderlyingSource,
+    /// The chunks enqueued by the underlying source, which get copied instead
+    /// of having their buffer transferred
+    ///
+    /// https://streams.spec.whatwg.org/#readablebytestreamcontroller-queue
+    queue: DomRefCell<VecDeque<Vec<u8>>>,
+    /// https://streams.spec.whatwg.org/#readablebytestreamcontroller-queuetotalsize
+    queue_total_size: Cell<usize>,
+    /// https://streams.spec.whatwg.org/#readablebytestreamcontroller-started
+    started: Cell<bool>,
+    /// https://streams.spec.whatwg.org/#readablebytestreamcontroller-closerequested
+    close_requested: Cell<bool>,
+    /// https://streams.spec.whatwg.org/#readablebytestreamcontroller-pullagain
+    pull_again: Cell<bool>,
+    /// https://streams.spec.whatwg.org/#readablebytestreamcontroller-pulling
+    pulling: Cell<bool>,
+    /// https://streams.spec.whatwg.org/#readablebytestreamcontroller-strategyhwm
+    strategy_hwm: f64,
+}
+
+impl ReadableByteStreamController {
+    fn new_inherited(
+        stream: &ReadableStream,
+        source: UnderlyingSource,
+        strategy_hwm: f64,
+    ) -> ReadableByteStreamController {
+        ReadableByteStreamController {
+            reflector_: Reflector::new(),
+            stream: Dom::from_ref(stream),
+            source: source,
+            queue: DomRefCell::new(VecDeque::new()),
+            queue_total_size: Cell::new(0),
+            started: Cell::new(false),
+            close_requested: Cell::new(false),
+            pull_again: Cell::new(false),
+            pulling: Cell::new(false),
+            strategy_hwm: strategy_hwm,
+        }
+    }
+
+    /// https://streams.spec.whatwg.org/#set-up-readable-byte-stream-controller
+    /// Steps 1-11.
+    pub fn new(
+        global: &GlobalScope,
+        stream: &ReadableStream,
+        source: UnderlyingSource,
+        strategy_hwm: f64,
+    ) -> DomRoot<ReadableByteStreamController> {
+        reflect_dom_object(
+            Box::new(ReadableByteStreamController::new_inherited(
+                stream,
+                source,
+                strategy_hwm,
+            )),
+            global,
+            ReadableByteStreamControllerBinding::Wrap,
+        )
+    }
+
+    pub fn source(&self) -> &UnderlyingSource {
+        &self.source
+    }
+
+    fn as_controller(&self) -> ReadableStreamController {
+        ReadableStreamController::Byte(Dom::from_ref(self))
+    }
+
+    /// https://streams.spec.whatwg.org/#rbs-controller-private-pull
+    /// Step 3.
+    #[allow(unrooted_must_root)]
+    pub fn take_chunk(&self) -> Option<Chunk> {
+        let bytes = self.queue.borrow_mut().pop_front()?;
+        self.queue_total_size
+            .set(self.queue_total_size.get() - bytes.len());
+
+        // https://streams.spec.whatwg.org/#readable-byte-stream-controller-handle-queue-drain
+        if self.queue_total_size.get() == 0 && self.close_requested.get() {
+            self.stream.close();
+        } else {
+            self.call_pull_if_needed();
+        }
+        Some(Chunk::Bytes(bytes))
+    }
+
+    /// https://streams.spec.whatwg.org/#readable-byte-stream-controller-call-pull-if-needed
+    #[allow(unrooted_must_root)]
+    pub fn call_pull_if_needed(&self) {
+        // Steps 1-2.
+        if !self.should_call_pull() {
+            return;
+        }
+
+        // Step 3.
+        if self.pulling.get() {
+            self.pull_again.set(true);
+            return;
+        }
+
+        // Steps 5-7.
+        let controller = self.as_controller();
+        if let Some(pull_promise) = self.source.pull(&controller) {
+            self.pulling.set(true);
+            controller.upon_source_promise(&pull_promise, SourceReaction::PullFulfilled);
+        }
+    }
+
+    /// https://streams.spec.whatwg.org/#readable-byte-stream-controller-should-call-pull
+    fn should_call_pull(&self) -> bool {
+        // Steps 2-4.
+        if self.stream.state() != ReadableStreamState::Readable ||
+            self.close_requested.get() ||
+            !self.started.get()
+        {
+            return false;
+        }
+
+        // Step 5.
+        if self.stream.num_read_requests() > 0 {
+            return true;
+        }
+
+        // Steps 7-9.
+        self.desired_size().map_or(false, |size| size > 0.)
+    }
+
+    /// https://streams.spec.whatwg.org/#set-up-readable-byte-stream-controller
+    /// Step 16.
+    pub fn start_fulfilled(&self) {
+        self.started.set(true);
+        self.call_pull_if_needed();
+    }
+
+    /// https://streams.spec.whatwg.org/#readable-byte-stream-controller-call-pull-if-needed
+    /// Step 7.
+    pub fn pull_fulfilled(&self) {
+        self.pulling.set(false);
+        if self.pull_again.get() {
+            self.pull_again.set(false);
+            self.call_pull_if_needed();
+        }
+    }
+
+    /// https://streams.spec.whatwg.org/#rbs-controller-private-cancel
+    pub fn cancel_steps(&self, reason: HandleValue) -> Rc<Promise> {
+        // Step 2.
+        self.reset_queue();
+
+        // Step 3.
+        self.source.cancel(&self.global(), reason)
+    }
+
+    /// https://streams.spec.whatwg.org/#reset-queue
+    fn reset_queue(&self) {
+        self.queue.borrow_mut().clear();
+        self.queue_total_size.set(0);
+    }
+
+    /// https://streams.spec.whatwg.org/#readable-byte-stream-controller-get-desired-size
+    fn desired_size(&self) -> Option<f64> {
+        match self.stream.state() {
+            ReadableStreamState::Errored => None,
+            ReadableStreamState::Closed => Some(0.),
+            ReadableStreamState::Readable => {
+                Some(self.strategy_hwm - self.queue_total_size.get() as f64)
+            },
+        }
+    }
+
+    /// https://streams.spec.whatwg.org/#readable-byte-stream-controller-error
+    pub fn error(&self, e: HandleValue) {
+        // Step 2.
+        if self.stream.state() != ReadableStreamState::Readable {
+            return;
+        }
+
+        // Steps 3-4.
+        self.reset_queue();
+
+        // Step 6.
+        self.stream.error(e);
+    }
+
+    /// https://streams.spec.whatwg.org/#readable-byte-stream-controller-close
+    pub fn close(&self) -> ErrorResult {
+        // Step 1.
+        if self.close_requested.get() || self.stream.state() != ReadableStreamState::Readable {
+            return Err(Error::Type("The stream cannot be closed".to_string()));
+        }
+
+        // Step 4.
+        if self.queue_total_size.get() > 0 {
+            self.close_requested.set(true);
+            return Ok(());
+        }
+
+        // Step 7.
+        self.stream.close();
+        Ok(())
+    }
+
+    /// https://streams.spec.whatwg.org/#readable-byte-stream-controller-enqueue
+    #[allow(unrooted_must_root)]
+    pub fn enqueue_bytes(&self, bytes: Vec<u8>) {
+        // Step 9.
+        if self.stream.num_read_requests() > 0 {
+            self.stream.fulfill_read_request(Chunk::Bytes(bytes));
+        } else {
+            self.queue_total_size
+                .set(self.queue_total_size.get() + bytes.len());
+            self.queue.borrow_mut().push_back(bytes);
+        }
+
+        // Step 12.
+        self.call_pull_if_needed();
+    }
+}
+
+impl ReadableByteStreamControllerMethods for ReadableByteStreamController {
+    // https://streams.spec.whatwg.org/#rbs-controller-desired-size
+    fn GetDesiredSize(&self) -> Option<f64> {
+        self.desired_size()
+    }
+
+    // https://streams.spec.whatwg.org/#rbs-controller-close
+    fn Close(&self) -> ErrorResult {
+        self.close()
+    }
+
+    // https://streams.spec.whatwg.org/#rbs-controller-enqueue
+    fn Enqueue(&self, chunk: CustomAutoRooterGuard<ArrayBufferView>) -> ErrorResult {
+        // Steps 2-3.
+        if self.close_requested.get() || self.stream.state() != ReadableStreamState::Readable {
+            return Err(Error::Type(
+                "The stream cannot have chunks enqueued".to_string(),
+            ));
+        }
+
+        // Step 4.
+        // FIXME: The buffer of the chunk is not transferred, as there is no
+        // way to detach it yet.
+        self.enqueue_bytes(chunk.to_vec());
+        Ok(())
+    }
+
+    // https://streams.spec.whatwg.org/#rbs-controller-error
+    #[allow(unsafe_code)]
+    unsafe fn Error(&self, _cx: *mut JSContext, e: HandleValue) {
+        self.error(e);
+    }
+}
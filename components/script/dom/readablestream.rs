@@ -0,0 +1,768 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::ReadableStreamBinding::{
+    self, QueuingStrategy, ReadableStreamGetReaderOptions, ReadableStreamMethods,
+};
+use crate::dom::bindings::conversions::{
+    get_property_jsval, ConversionResult, FromJSValConvertible, StringificationBehavior,
+};
+use crate::dom::bindings::error::{Error, Fallible};
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
+use crate::dom::bindings::root::{Dom, DomRoot, MutNullableDom};
+use crate::dom::bindings::str::DOMString;
+use crate::dom::bindings::trace::RootedTraceableBox;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::promise::Promise;
+use crate::dom::promisenativehandler::{Callback, PromiseNativeHandler};
+use crate::dom::readablebytestreamcontroller::ReadableByteStreamController;
+use crate::dom::readablestreamdefaultcontroller::ReadableStreamDefaultController;
+use crate::dom::readablestreamdefaultreader::ReadableStreamDefaultReader;
+use crate::dom::readablestreamdefaultreader::{ReadAllBytes, ReadAllBytesHandler};
+use dom_struct::dom_struct;
+use ipc_channel::ipc::IpcSender;
+use js::jsapi::{HandleValueArray, Heap, IsCallable, JSAutoCompartment, JSContext, JSObject};
+use js::jsapi::{JS_ClearPendingException, JS_NewPlainObject};
+use js::jsval::{JSVal, ObjectValue, UndefinedValue};
+use js::rust::wrappers::{Call, JS_GetPendingException};
+use js::rust::{HandleObject, HandleValue, MutableHandleValue};
+use js::typedarray::{CreateWith, Uint8Array};
+use std::cell::Cell;
+use std::ptr;
+use std::rc::Rc;
+
+/// https://streams.spec.whatwg.org/#readablestream-state
+#[derive(Clone, Copy, Debug, JSTraceable, MallocSizeOf, PartialEq)]
+pub enum ReadableStreamState {
+    Readable,
+    Closed,
+    Errored,
+}
+
+/// https://streams.spec.whatwg.org/#readablestream-readablestreamcontroller
+#[derive(Clone, JSTraceable, MallocSizeOf)]
+#[must_root]
+pub enum ReadableStreamController {
+    Default(Dom<ReadableStreamDefaultController>),
+    Byte(Dom<ReadableByteStreamController>),
+}
+
+impl ReadableStreamController {
+    /// Dequeues a chunk for a read request, doing what the pull steps of the
+    /// controller do once the queue is left with one chunk less, or returns
+    /// `None` if the queue is empty
+    pub fn take_chunk(&self) -> Option<Chunk> {
+        match *self {
+            ReadableStreamController::Default(ref controller) => controller.take_chunk(),
+            ReadableStreamController::Byte(ref controller) => controller.take_chunk(),
+        }
+    }
+
+    pub fn call_pull_if_needed(&self) {
+        match *self {
+            ReadableStreamController::Default(ref controller) => controller.call_pull_if_needed(),
+            ReadableStreamController::Byte(ref controller) => controller.call_pull_if_needed(),
+        }
+    }
+
+    /// https://streams.spec.whatwg.org/#abstract-opdef-readablestreamcontroller-cancelsteps
+    pub fn cancel_steps(&self, reason: HandleValue) -> Rc<Promise> {
+        match *self {
+            ReadableStreamController::Default(ref controller) => controller.cancel_steps(reason),
+            ReadableStreamController::Byte(ref controller) => controller.cancel_steps(reason),
+        }
+    }
+
+    fn start_fulfilled(&self) {
+        match *self {
+            ReadableStreamController::Default(ref controller) => controller.start_fulfilled(),
+            ReadableStreamController::Byte(ref controller) => controller.start_fulfilled(),
+        }
+    }
+
+    fn pull_fulfilled(&self) {
+        match *self {
+            ReadableStreamController::Default(ref controller) => controller.pull_fulfilled(),
+            ReadableStreamController::Byte(ref controller) => controller.pull_fulfilled(),
+        }
+    }
+
+    pub fn error(&self, e: HandleValue) {
+        match *self {
+            ReadableStreamController::Default(ref controller) => controller.error(e),
+            ReadableStreamController::Byte(ref controller) => controller.error(e),
+        }
+    }
+
+    fn global(&self) -> DomRoot<GlobalScope> {
+        match *self {
+            ReadableStreamController::Default(ref controller) => controller.global(),
+            ReadableStreamController::Byte(ref controller) => controller.global(),
+        }
+    }
+
+    /// The controller object script gets passed to the underlying source
+    fn to_jsval(&self) -> JSVal {
+        match *self {
+            ReadableStreamController::Default(ref controller) => {
+                ObjectValue(controller.reflector().get_jsobject().get())
+            },
+            ReadableStreamController::Byte(ref controller) => {
+                ObjectValue(controller.reflector().get_jsobject().get())
+            },
+        }
+    }
+
+    /// Reacts to the settlement of a promise returned by the start or pull
+    /// algorithm of the underlying source
+    pub fn upon_source_promise(&self, promise: &Promise, fulfilled: SourceReaction) {
+        let global = self.global();
+        let handler = PromiseNativeHandler::new(
+            &global,
+            Some(Box::new(SourcePromiseHandler {
+                controller: self.clone(),
+                reaction: fulfilled,
+            })),
+            Some(Box::new(SourcePromiseHandler {
+                controller: self.clone(),
+                reaction: SourceReaction::Rejected,
+            })),
+        );
+        promise.append_native_handler(&handler);
+    }
+}
+
+/// What a controller does once a promise returned by its underlying source
+/// settles
+#[derive(Clone, Copy, JSTraceable, MallocSizeOf)]
+pub enum SourceReaction {
+    StartFulfilled,
+    PullFulfilled,
+    Rejected,
+}
+
+#[derive(JSTraceable, MallocSizeOf)]
+#[must_root]
+struct SourcePromiseHandler {
+    controller: ReadableStreamController,
+    reaction: SourceReaction,
+}
+
+impl Callback for SourcePromiseHandler {
+    fn callback(&self, _cx: *mut JSContext, v: HandleValue) {
+        match self.reaction {
+            SourceReaction::StartFulfilled => self.controller.start_fulfilled(),
+            SourceReaction::PullFulfilled => self.controller.pull_fulfilled(),
+            SourceReaction::Rejected => self.controller.error(v),
+        }
+    }
+}
+
+/// Where the chunks of a stream come from
+#[derive(JSTraceable)]
+pub enum UnderlyingSource {
+    /// The underlying source object given by script to the constructor,
+    /// along with its pull and cancel methods, which may be undefined
+    Js {
+        object: Box<Heap<*mut JSObject>>,
+        pull: Box<Heap<JSVal>>,
+        cancel: Box<Heap<JSVal>>,
+    },
+    /// Chunks enqueued by native code, such as the body of a fetch response,
+    /// along with the sender used to cancel that fetch
+    Native { cancel_chan: Option<IpcSender<()>> },
+}
+
+impl UnderlyingSource {
+    /// Reads the pull and cancel methods of the underlying source object
+    ///
+    /// https://streams.spec.whatwg.org/#create-algorithm-from-underlying-method
+    #[allow(unsafe_code)]
+    unsafe fn from_js(cx: *mut JSContext, object: HandleObject) -> Fallible<UnderlyingSource> {
+        rooted!(in(cx) let mut pull = UndefinedValue());
+        get_method(cx, object, "pull", pull.handle_mut())?;
+        rooted!(in(cx) let mut cancel = UndefinedValue());
+        get_method(cx, object, "cancel", cancel.handle_mut())?;
+        Ok(UnderlyingSource::Js {
+            object: Heap::boxed(object.get()),
+            pull: Heap::boxed(pull.get()),
+            cancel: Heap::boxed(cancel.get()),
+        })
+    }
+
+    /// Runs the start algorithm of the underlying source, returning the
+    /// promise its result gets resolved to
+    ///
+    /// https://streams.spec.whatwg.org/#set-up-readable-stream-default-controller
+    /// Steps 8-9.
+    #[allow(unsafe_code)]
+    unsafe fn start(
+        &self,
+        cx: *mut JSContext,
+        global: &GlobalScope,
+        controller: &ReadableStreamController,
+    ) -> Fallible<Rc<Promise>> {
+        rooted!(in(cx) let mut result = UndefinedValue());
+        if let UnderlyingSource::Js { ref object, .. } = *self {
+            rooted!(in(cx) let object = object.get());
+            rooted!(in(cx) let mut start = UndefinedValue());
+            get_method(cx, object.handle(), "start", start.handle_mut())?;
+            if !start.is_undefined() {
+                rooted!(in(cx) let this = ObjectValue(object.get()));
+                let args = [controller.to_jsval()];
+                let args = HandleValueArray::from_rooted_slice(&args);
+                if !Call(
+                    cx,
+                    this.handle(),
+                    start.handle(),
+                    &args,
+                    result.handle_mut(),
+                ) {
+                    return Err(Error::JSFailed);
+                }
+            }
+        }
+        Promise::new_resolved(global, cx, result.handle())
+    }
+
+    /// Calls the pull method of the underlying source, returning `None` for
+    /// native sources, which push their chunks instead
+    #[allow(unsafe_code)]
+    pub fn pull(&self, controller: &ReadableStreamController) -> Option<Rc<Promise>> {
+        match *self {
+            UnderlyingSource::Js {
+                ref object,
+                ref pull,
+                ..
+            } => {
+                let global = controller.global();
+                let cx = global.get_cx();
+                rooted!(in(cx) let controller = controller.to_jsval());
+                Some(unsafe { promise_call(cx, &global, object, pull, controller.handle()) })
+            },
+            UnderlyingSource::Native { .. } => None,
+        }
+    }
+
+    /// Calls the cancel method of the underlying source, or cancels the fetch
+    /// a native source gets its chunks from
+    #[allow(unsafe_code)]
+    pub fn cancel(&self, global: &GlobalScope, reason: HandleValue) -> Rc<Promise> {
+        let cx = global.get_cx();
+        match *self {
+            UnderlyingSource::Js {
+                ref object,
+                ref cancel,
+                ..
+            } => unsafe { promise_call(cx, global, object, cancel, reason) },
+            UnderlyingSource::Native { ref cancel_chan } => {
+                if let Some(ref cancel_chan) = *cancel_chan {
+                    // The fetch may have completed, in which case nobody
+                    // listens to the cancellation anymore.
+                    let _ = cancel_chan.send(());
+                }
+                rooted!(in(cx) let undefined = UndefinedValue());
+                unsafe { Promise::new_resolved(global, cx, undefined.handle()).unwrap() }
+            },
+        }
+    }
+}
+
+/// https://streams.spec.whatwg.org/#get-method
+#[allow(unsafe_code)]
+unsafe fn get_method(
+    cx: *mut JSContext,
+    object: HandleObject,
+    name: &str,
+    rval: MutableHandleValue,
+) -> Fallible<()> {
+    get_property_jsval(cx, object, name, rval)?;
+    if !rval.is_undefined() && (!rval.is_object() || !IsCallable(rval.to_object())) {
+        return Err(Error::Type(format!(
+            "The underlying source's {} member is not a function",
+            name
+        )));
+    }
+    Ok(())
+}
+
+/// Calls `method` with `arg`, returning a promise rejected with the exception
+/// it throws, if any, or resolved to its result
+///
+/// https://streams.spec.whatwg.org/#promise-call
+#[allow(unsafe_code)]
+unsafe fn promise_call(
+    cx: *mut JSContext,
+    global: &GlobalScope,
+    object: &Heap<*mut JSObject>,
+    method: &Heap<JSVal>,
+    arg: HandleValue,
+) -> Rc<Promise> {
+    rooted!(in(cx) let mut result = UndefinedValue());
+    if !method.get().is_undefined() {
+        rooted!(in(cx) let this = ObjectValue(object.get()));
+        rooted!(in(cx) let method = method.get());
+        let args = [arg.get()];
+        let args = HandleValueArray::from_rooted_slice(&args);
+        if !Call(
+            cx,
+            this.handle(),
+            method.handle(),
+            &args,
+            result.handle_mut(),
+        ) {
+            take_pending_exception(cx, result.handle_mut());
+            return Promise::new_rejected(global, cx, result.handle()).unwrap();
+        }
+    }
+    Promise::new_resolved(global, cx, result.handle()).unwrap()
+}
+
+/// Moves the exception a call into script left pending to `rval`
+#[allow(unsafe_code)]
+pub unsafe fn take_pending_exception(cx: *mut JSContext, rval: MutableHandleValue) {
+    assert!(JS_GetPendingException(cx, rval));
+    JS_ClearPendingException(cx);
+}
+
+/// https://streams.spec.whatwg.org/#validate-and-normalize-high-water-mark
+pub fn validate_high_water_mark(high_water_mark: f64) -> Fallible<f64> {
+    if high_water_mark.is_nan() || high_water_mark < 0. {
+        return Err(Error::Range(
+            "The high water mark must be a non-negative number".to_string(),
+        ));
+    }
+    Ok(high_water_mark)
+}
+
+/// A chunk dequeued from, or fulfilling a read request of, a stream
+#[must_root]
+pub enum Chunk {
+    /// A chunk of a byte stream, which script reads as an `Uint8Array`
+    Bytes(Vec<u8>),
+    /// A chunk of a default stream
+    Value(RootedTraceableBox<Heap<JSVal>>),
+}
+
+impl Chunk {
+    /// Converts this chunk to what script reads from the stream
+    #[allow(unsafe_code)]
+    pub unsafe fn to_jsval(
+        &self,
+        cx: *mut JSContext,
+        mut rval: MutableHandleValue,
+    ) -> Fallible<()> {
+        match *self {
+            Chunk::Bytes(ref bytes) => {
+                rooted!(in(cx) let mut array = ptr::null_mut::<JSObject>());
+                if Uint8Array::create(cx, CreateWith::Slice(bytes), array.handle_mut()).is_err() {
+                    return Err(Error::JSFailed);
+                }
+                rval.set(ObjectValue(array.get()));
+            },
+            Chunk::Value(ref value) => rval.set(value.get()),
+        }
+        Ok(())
+    }
+
+    /// The bytes of this chunk, if it is one script can get the body of a
+    /// request or response from
+    #[allow(unsafe_code)]
+    pub unsafe fn into_bytes(self, cx: *mut JSContext) -> Option<Vec<u8>> {
+        match self {
+            Chunk::Bytes(bytes) => Some(bytes),
+            Chunk::Value(value) => {
+                if !value.get().is_object() {
+                    return None;
+                }
+                typedarray!(in(cx) let array: Uint8Array = value.get().to_object());
+                array.ok().map(|array| array.to_vec())
+            },
+        }
+    }
+}
+
+#[dom_struct]
+pub struct ReadableStream {
+    reflector_: Reflector,
+    /// https://streams.spec.whatwg.org/#readablestream-state
+    state: Cell<ReadableStreamState>,
+    /// https://streams.spec.whatwg.org/#readablestream-storederror
+    #[ignore_malloc_size_of = "Defined in rust-mozjs"]
+    stored_error: Heap<JSVal>,
+    /// https://streams.spec.whatwg.org/#readablestream-disturbed
+    disturbed: Cell<bool>,
+    /// https://streams.spec.whatwg.org/#readablestream-reader
+    reader: MutNullableDom<ReadableStreamDefaultReader>,
+    /// https://streams.spec.whatwg.org/#readablestream-readablestreamcontroller
+    controller: DomRefCell<Option<ReadableStreamController>>,
+}
+
+impl ReadableStream {
+    fn new_inherited() -> ReadableStream {
+        ReadableStream {
+            reflector_: Reflector::new(),
+            state: Cell::new(ReadableStreamState::Readable),
+            stored_error: Heap::default(),
+            disturbed: Cell::new(false),
+            reader: Default::default(),
+            controller: DomRefCell::new(None),
+        }
+    }
+
+    fn new(global: &GlobalScope) -> DomRoot<ReadableStream> {
+        reflect_dom_object(
+            Box::new(ReadableStream::new_inherited()),
+            global,
+            ReadableStreamBinding::Wrap,
+        )
+    }
+
+    /// Creates a byte stream whose chunks get enqueued by native code, and
+    /// whose cancellation sends a message to `cancel_chan`, if any
+    pub fn new_native(
+        global: &GlobalScope,
+        cancel_chan: Option<IpcSender<()>>,
+    ) -> DomRoot<ReadableStream> {
+        let stream = ReadableStream::new(global);
+        let controller = ReadableByteStreamController::new(
+            global,
+            &stream,
+            UnderlyingSource::Native { cancel_chan },
+            0.,
+        );
+        controller.start_fulfilled();
+        stream.set_controller(ReadableStreamController::Byte(Dom::from_ref(&*controller)));
+        stream
+    }
+
+    // https://streams.spec.whatwg.org/#rs-constructor
+    #[allow(unsafe_code)]
+    pub unsafe fn Constructor(
+        cx: *mut JSContext,
+        global: &GlobalScope,
+        underlying_source: Option<*mut JSObject>,
+        strategy: RootedTraceableBox<QueuingStrategy>,
+    ) -> Fallible<DomRoot<ReadableStream>> {
+        // Step 1.
+        let stream = ReadableStream::new(global);
+
+        // The underlying source defaults to an empty object.
+        rooted!(in(cx) let object = match underlying_source {
+            Some(object) => object,
+            None => JS_NewPlainObject(cx),
+        });
+
+        // Steps 3-4.
+        rooted!(in(cx) let mut type_ = UndefinedValue());
+        get_property_jsval(cx, object.handle(), "type", type_.handle_mut())?;
+
+        let source = UnderlyingSource::from_js(cx, object.handle())?;
+
+        let controller = if type_.is_undefined() {
+            // Step 6.
+            let size = strategy.size.get();
+            if !size.is_undefined() && (!size.is_object() || !IsCallable(size.to_object())) {
+                return Err(Error::Type(
+                    "The queuing strategy's size member is not a function".to_string(),
+                ));
+            }
+            let high_water_mark = validate_high_water_mark(strategy.highWaterMark.unwrap_or(1.))?;
+            let controller = ReadableStreamDefaultController::new(
+                global,
+                &stream,
+                source,
+                high_water_mark,
+                strategy.size.handle(),
+            );
+            ReadableStreamController::Default(Dom::from_ref(&*controller))
+        } else {
+            // Step 5.
+            let type_ =
+                match DOMString::from_jsval(cx, type_.handle(), StringificationBehavior::Default) {
+                    Ok(ConversionResult::Success(type_)) => type_,
+                    _ => return Err(Error::JSFailed),
+                };
+            if &*type_ != "bytes" {
+                return Err(Error::Range(format!(
+                    "{} is not a valid underlying source type",
+                    type_
+                )));
+            }
+            if !strategy.size.get().is_undefined() {
+                return Err(Error::Range(
+                    "A byte stream cannot have a size function".to_string(),
+                ));
+            }
+            let high_water_mark = validate_high_water_mark(strategy.highWaterMark.unwrap_or(0.))?;
+            // FIXME: autoAllocateChunkSize is ignored, as there are no BYOB
+            // requests yet.
+            let controller =
+                ReadableByteStreamController::new(global, &stream, source, high_water_mark);
+            ReadableStreamController::Byte(Dom::from_ref(&*controller))
+        };
+        stream.set_controller(controller.clone());
+
+        let start_promise = match *controller {
+            ReadableStreamController::Default(ref default) => {
+                default.source().start(cx, global, &controller)?
+            },
+            ReadableStreamController::Byte(ref byte) => {
+                byte.source().start(cx, global, &controller)?
+            },
+        };
+        controller.upon_source_promise(&start_promise, SourceReaction::StartFulfilled);
+
+        Ok(stream)
+    }
+
+    fn set_controller(&self, controller: ReadableStreamController) {
+        *self.controller.borrow_mut() = Some(controller);
+    }
+
+    pub fn controller(&self) -> ReadableStreamController {
+        self.controller
+            .borrow()
+            .clone()
+            .expect("stream has no controller")
+    }
+
+    pub fn state(&self) -> ReadableStreamState {
+        self.state.get()
+    }
+
+    pub fn stored_error(&self) -> HandleValue {
+        self.stored_error.handle()
+    }
+
+    pub fn reader(&self) -> Option<DomRoot<ReadableStreamDefaultReader>> {
+        self.reader.get()
+    }
+
+    pub fn set_reader(&self, reader: Option<&ReadableStreamDefaultReader>) {
+        self.reader.set(reader);
+    }
+
+    /// https://streams.spec.whatwg.org/#is-readable-stream-disturbed
+    pub fn is_disturbed(&self) -> bool {
+        self.disturbed.get()
+    }
+
+    pub fn set_disturbed(&self) {
+        self.disturbed.set(true);
+    }
+
+    /// https://streams.spec.whatwg.org/#is-readable-stream-locked
+    pub fn is_locked(&self) -> bool {
+        self.reader.get().is_some()
+    }
+
+    /// https://streams.spec.whatwg.org/#readable-stream-get-num-read-requests
+    pub fn num_read_requests(&self) -> usize {
+        self.reader
+            .get()
+            .map_or(0, |reader| reader.num_read_requests())
+    }
+
+    /// https://streams.spec.whatwg.org/#readable-stream-fulfill-read-request
+    #[allow(unrooted_must_root)]
+    pub fn fulfill_read_request(&self, chunk: Chunk) {
+        let reader = self.reader.get().expect("stream has no reader");
+        reader.take_read_request().chunk_steps(&reader, chunk);
+    }
+
+    /// https://streams.spec.whatwg.org/#readable-stream-close
+    #[allow(unrooted_must_root)]
+    pub fn close(&self) {
+        // Steps 1-2.
+        assert_eq!(self.state.get(), ReadableStreamState::Readable);
+        self.state.set(ReadableStreamState::Closed);
+
+        // Steps 3-6.
+        if let Some(reader) = self.reader.get() {
+            for request in reader.take_read_requests() {
+                request.close_steps(&reader);
+            }
+            reader.closed_promise().resolve_native(&());
+        }
+    }
+
+    /// https://streams.spec.whatwg.org/#readable-stream-error
+    #[allow(unrooted_must_root)]
+    pub fn error(&self, e: HandleValue) {
+        // Steps 1-4.
+        assert_eq!(self.state.get(), ReadableStreamState::Readable);
+        self.state.set(ReadableStreamState::Errored);
+        self.stored_error.set(e.get());
+
+        // Steps 5-10.
+        if let Some(reader) = self.reader.get() {
+            for request in reader.take_read_requests() {
+                request.error_steps(&reader, e);
+            }
+            let closed_promise = reader.closed_promise();
+            closed_promise.reject_native(&e);
+            mark_as_handled(&self.global(), &closed_promise);
+        }
+    }
+
+    /// https://streams.spec.whatwg.org/#readable-stream-cancel
+    #[allow(unsafe_code)]
+    pub fn cancel(&self, reason: HandleValue) -> Rc<Promise> {
+        let global = self.global();
+        let cx = global.get_cx();
+
+        // Step 1.
+        self.disturbed.set(true);
+
+        // Steps 2-3.
+        match self.state.get() {
+            ReadableStreamState::Closed => {
+                rooted!(in(cx) let undefined = UndefinedValue());
+                return unsafe { Promise::new_resolved(&global, cx, undefined.handle()).unwrap() };
+            },
+            ReadableStreamState::Errored => unsafe {
+                return Promise::new_rejected(&global, cx, self.stored_error.handle()).unwrap();
+            },
+            ReadableStreamState::Readable => {},
+        }
+
+        // Step 4.
+        self.close();
+
+        // Steps 5-6.
+        let source_cancel_promise = self.controller().cancel_steps(reason);
+        let _ac = JSAutoCompartment::new(cx, global.reflector().get_jsobject().get());
+        let promise = Promise::new(&global, &_ac);
+        let handler = PromiseNativeHandler::new(
+            &global,
+            Some(Box::new(TransformToUndefined {
+                promise: promise.clone(),
+                fulfilled: true,
+            })),
+            Some(Box::new(TransformToUndefined {
+                promise: promise.clone(),
+                fulfilled: false,
+            })),
+        );
+        source_cancel_promise.append_native_handler(&handler);
+        promise
+    }
+
+    /// https://streams.spec.whatwg.org/#acquire-readable-stream-reader
+    pub fn acquire_default_reader(&self) -> Fallible<DomRoot<ReadableStreamDefaultReader>> {
+        ReadableStreamDefaultReader::Constructor(&self.global(), self)
+    }
+
+    /// Reads all the chunks of this stream, which must all be `Uint8Array`
+    /// objects, and then calls the success steps of `handler` with their
+    /// concatenated bytes, or its failure steps if the stream errors
+    ///
+    /// https://fetch.spec.whatwg.org/#concept-read-all-bytes-from-readablestream
+    #[allow(unrooted_must_root)]
+    pub fn read_all_bytes(&self, handler: Box<dyn ReadAllBytesHandler>) -> Fallible<()> {
+        let reader = self.acquire_default_reader()?;
+        reader.read_all_bytes(ReadAllBytes::new(handler));
+        Ok(())
+    }
+
+    /// Enqueues bytes in this native stream, unless script cancelled it
+    pub fn enqueue_native(&self, bytes: Vec<u8>) {
+        if self.state.get() != ReadableStreamState::Readable {
+            return;
+        }
+        match self.controller() {
+            ReadableStreamController::Byte(controller) => controller.enqueue_bytes(bytes),
+            ReadableStreamController::Default(_) => unreachable!("native streams are byte streams"),
+        }
+    }
+
+    /// Closes this native stream once all its bytes got enqueued
+    pub fn close_native(&self) {
+        if self.state.get() != ReadableStreamState::Readable {
+            return;
+        }
+        match self.controller() {
+            ReadableStreamController::Byte(controller) => {
+                let _ = controller.close();
+            },
+            ReadableStreamController::Default(_) => unreachable!("native streams are byte streams"),
+        }
+    }
+
+    /// Errors this native stream with `error`
+    #[allow(unsafe_code)]
+    pub fn error_native(&self, error: Error) {
+        if self.state.get() != ReadableStreamState::Readable {
+            return;
+        }
+        let global = self.global();
+        let cx = global.get_cx();
+        let _ac = JSAutoCompartment::new(cx, self.reflector().get_jsobject().get());
+        rooted!(in(cx) let mut e = UndefinedValue());
+        unsafe { error.to_jsval(cx, &global, e.handle_mut()) };
+        self.controller().error(e.handle());
+    }
+}
+
+/// Marks `promise` as handled, for its rejection not to get reported
+pub fn mark_as_handled(global: &GlobalScope, promise: &Promise) {
+    promise.append_native_handler(&PromiseNativeHandler::new(global, None, None));
+}
+
+/// The reactions to a promise that settle another one with undefined, or
+/// the same reason
+#[derive(JSTraceable, MallocSizeOf)]
+struct TransformToUndefined {
+    #[ignore_malloc_size_of = "Rc is hard"]
+    promise: Rc<Promise>,
+    fulfilled: bool,
+}
+
+impl Callback for TransformToUndefined {
+    #[allow(unsafe_code)]
+    fn callback(&self, cx: *mut JSContext, v: HandleValue) {
+        if self.fulfilled {
+            self.promise.resolve_native(&());
+        } else {
+            unsafe { self.promise.reject(cx, v) };
+        }
+    }
+}
+
+impl ReadableStreamMethods for ReadableStream {
+    // https://streams.spec.whatwg.org/#rs-locked
+    fn Locked(&self) -> bool {
+        self.is_locked()
+    }
+
+    // https://streams.spec.whatwg.org/#rs-cancel
+    #[allow(unsafe_code)]
+    unsafe fn Cancel(&self, _cx: *mut JSContext, reason: HandleValue) -> Rc<Promise> {
+        // Step 2.
+        if self.is_locked() {
+            let promise = Promise::new_in_current_compartment(&self.global());
+            promise.reject_error(Error::Type("The stream is locked".to_string()));
+            return promise;
+        }
+
+        // Step 3.
+        self.cancel(reason)
+    }
+
+    // https://streams.spec.whatwg.org/#rs-get-reader
+    fn GetReader(
+        &self,
+        options: &ReadableStreamGetReaderOptions,
+    ) -> Fallible<DomRoot<ReadableStreamDefaultReader>> {
+        // Steps 2-5.
+        if options.mode.is_some() {
+            return Err(Error::Type(
+                "BYOB readers are not supported yet".to_string(),
+            ));
+        }
+        self.acquire_default_reader()
+    }
+}
@@ -0,0 +1,334 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::ReadableStreamDefaultControllerBinding::{
+    self, ReadableStreamDefaultControllerMethods,
+};
+use crate::dom::bindings::conversions::{ConversionResult, FromJSValConvertible};
+use crate::dom::bindings::error::{Error, ErrorResult};
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::bindings::trace::RootedTraceableBox;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::promise::Promise;
+use crate::dom::readablestream::{take_pending_exception, Chunk, ReadableStream};
+use crate::dom::readablestream::{ReadableStreamController, ReadableStreamState};
+use crate::dom::readablestream::{SourceReaction, UnderlyingSource};
+use dom_struct::dom_struct;
+use js::jsapi::{HandleValueArray, Heap, JSContext};
+use js::jsval::{JSVal, UndefinedValue};
+use js::rust::wrappers::{Call, JS_SetPendingException};
+use js::rust::HandleValue;
+use std::cell::Cell;
+use std::collections::VecDeque;
+use std::rc::Rc;
+
+/// https://streams.spec.whatwg.org/#value-with-size
+#[derive(JSTraceable)]
+struct ValueWithSize {
+    value: Box<Heap<JSVal>>,
+    size: f64,
+}
+
+#[dom_struct]
+pub struct ReadableStreamDefaultController {
+    reflector_: Reflector,
+    /// https://streams.spec.whatwg.org/#readablestreamdefaultcontroller-controlledreadablestream
+    stream: Dom<ReadableStream>,
+    #[ignore_malloc_size_of = "Defined in rust-mozjs"]
+    source: UnderlyingSource,
+    /// https://streams.spec.whatwg.org/#readablestreamdefaultcontroller-queue
+    #[ignore_malloc_size_of = "Defined in rust-mozjs"]
+    queue: DomRefCell<VecDeque<ValueWithSize>>,
+    /// https://streams.spec.whatwg.org/#readablestreamdefaultcontroller-queuetotalsize
+    queue_total_size: Cell<f64>,
+    /// https://streams.spec.whatwg.org/#readablestreamdefaultcontroller-started
+    started: Cell<bool>,
+    /// https://streams.spec.whatwg.org/#readablestreamdefaultcontroller-closerequested
+    close_requested: Cell<bool>,
+    /// https://streams.spec.whatwg.org/#readablestreamdefaultcontroller-pullagain
+    pull_again: Cell<bool>,
+    /// https://streams.spec.whatwg.org/#readablestreamdefaultcontroller-pulling
+    pulling: Cell<bool>,
+    /// https://streams.spec.whatwg.org/#readablestreamdefaultcontroller-strategyhwm
+    strategy_hwm: f64,
+    /// The size function of the queuing strategy, if not undefined
+    ///
+    /// https://streams.spec.whatwg.org/#readablestreamdefaultcontroller-strategysizealgorithm
+    #[ignore_malloc_size_of = "Defined in rust-mozjs"]
+    strategy_size: Box<Heap<JSVal>>,
+}
+
+impl ReadableStreamDefaultController {
+    fn new_inherited(
+        stream: &ReadableStream,
+        source: UnderlyingSource,
+        strategy_hwm: f64,
+        strategy_size: HandleValue,
+    ) -> ReadableStreamDefaultController {
+        ReadableStreamDefaultController {
+            reflector_: Reflector::new(),
+            stream: Dom::from_ref(stream),
+            source: source,
+            queue: DomRefCell::new(VecDeque::new()),
+            queue_total_size: Cell::new(0.),
+            started: Cell::new(false),
+            close_requested: Cell::new(false),
+            pull_again: Cell::new(false),
+            pulling: Cell::new(false),
+            strategy_hwm: strategy_hwm,
+            strategy_size: Heap::boxed(strategy_size.get()),
+        }
+    }
+
+    /// https://streams.spec.whatwg.org/#set-up-readable-stream-default-controller
+    /// Steps 1-7.
+    pub fn new(
+        global: &GlobalScope,
+        stream: &ReadableStream,
+        source: UnderlyingSource,
+        strategy_hwm: f64,
+        strategy_size: HandleValue,
+    ) -> DomRoot<ReadableStreamDefaultController> {
+        reflect_dom_object(
+            Box::new(ReadableStreamDefaultController::new_inherited(
+                stream,
+                source,
+                strategy_hwm,
+                strategy_size,
+            )),
+            global,
+            ReadableStreamDefaultControllerBinding::Wrap,
+        )
+    }
+
+    pub fn source(&self) -> &UnderlyingSource {
+        &self.source
+    }
+
+    fn as_controller(&self) -> ReadableStreamController {
+        ReadableStreamController::Default(Dom::from_ref(self))
+    }
+
+    /// https://streams.spec.whatwg.org/#readable-stream-default-controller-pull-steps
+    /// Steps 2.a-c.
+    #[allow(unrooted_must_root)]
+    pub fn take_chunk(&self) -> Option<Chunk> {
+        let chunk = {
+            let mut queue = self.queue.borrow_mut();
+            let chunk = queue.pop_front()?;
+            // https://streams.spec.whatwg.org/#dequeue-value
+            let total_size = self.queue_total_size.get() - chunk.size;
+            self.queue_total_size
+                .set(if total_size < 0. { 0. } else { total_size });
+            chunk
+        };
+        let chunk = Chunk::Value(RootedTraceableBox::from_box(chunk.value));
+        if self.close_requested.get() && self.queue.borrow().is_empty() {
+            self.stream.close();
+        } else {
+            self.call_pull_if_needed();
+        }
+        Some(chunk)
+    }
+
+    /// https://streams.spec.whatwg.org/#readable-stream-default-controller-call-pull-if-needed
+    #[allow(unrooted_must_root)]
+    pub fn call_pull_if_needed(&self) {
+        // Steps 1-2.
+        if !self.should_call_pull() {
+            return;
+        }
+
+        // Step 3.
+        if self.pulling.get() {
+            self.pull_again.set(true);
+            return;
+        }
+
+        // Steps 5-7.
+        let controller = self.as_controller();
+        if let Some(pull_promise) = self.source.pull(&controller) {
+            self.pulling.set(true);
+            controller.upon_source_promise(&pull_promise, SourceReaction::PullFulfilled);
+        }
+    }
+
+    /// https://streams.spec.whatwg.org/#readable-stream-default-controller-should-call-pull
+    fn should_call_pull(&self) -> bool {
+        // Steps 2-4.
+        if !self.can_close_or_enqueue() || !self.started.get() {
+            return false;
+        }
+
+        // Step 5.
+        if self.stream.is_locked() && self.stream.num_read_requests() > 0 {
+            return true;
+        }
+
+        // Steps 6-8.
+        self.desired_size().map_or(false, |size| size > 0.)
+    }
+
+    /// https://streams.spec.whatwg.org/#set-up-readable-stream-default-controller
+    /// Step 11.
+    pub fn start_fulfilled(&self) {
+        self.started.set(true);
+        self.call_pull_if_needed();
+    }
+
+    /// https://streams.spec.whatwg.org/#readable-stream-default-controller-call-pull-if-needed
+    /// Step 7.
+    pub fn pull_fulfilled(&self) {
+        self.pulling.set(false);
+        if self.pull_again.get() {
+            self.pull_again.set(false);
+            self.call_pull_if_needed();
+        }
+    }
+
+    /// https://streams.spec.whatwg.org/#rs-default-controller-private-cancel
+    pub fn cancel_steps(&self, reason: HandleValue) -> Rc<Promise> {
+        // Step 1.
+        self.reset_queue();
+
+        // Step 2.
+        self.source.cancel(&self.global(), reason)
+    }
+
+    /// https://streams.spec.whatwg.org/#reset-queue
+    fn reset_queue(&self) {
+        self.queue.borrow_mut().clear();
+        self.queue_total_size.set(0.);
+    }
+
+    /// https://streams.spec.whatwg.org/#readable-stream-default-controller-can-close-or-enqueue
+    fn can_close_or_enqueue(&self) -> bool {
+        !self.close_requested.get() && self.stream.state() == ReadableStreamState::Readable
+    }
+
+    /// https://streams.spec.whatwg.org/#readable-stream-default-controller-get-desired-size
+    fn desired_size(&self) -> Option<f64> {
+        match self.stream.state() {
+            ReadableStreamState::Errored => None,
+            ReadableStreamState::Closed => Some(0.),
+            ReadableStreamState::Readable => Some(self.strategy_hwm - self.queue_total_size.get()),
+        }
+    }
+
+    /// https://streams.spec.whatwg.org/#readable-stream-default-controller-error
+    pub fn error(&self, e: HandleValue) {
+        // Step 2.
+        if self.stream.state() != ReadableStreamState::Readable {
+            return;
+        }
+
+        // Steps 3-5.
+        self.reset_queue();
+        self.stream.error(e);
+    }
+
+    /// Calls the size function of the queuing strategy with `chunk`
+    #[allow(unsafe_code)]
+    unsafe fn chunk_size(&self, cx: *mut JSContext, chunk: HandleValue) -> Result<f64, ()> {
+        rooted!(in(cx) let size = self.strategy_size.get());
+        if size.is_undefined() {
+            return Ok(1.);
+        }
+        rooted!(in(cx) let this = UndefinedValue());
+        rooted!(in(cx) let mut result = UndefinedValue());
+        let args = [chunk.get()];
+        let args = HandleValueArray::from_rooted_slice(&args);
+        if !Call(cx, this.handle(), size.handle(), &args, result.handle_mut()) {
+            return Err(());
+        }
+        match f64::from_jsval(cx, result.handle(), ()) {
+            Ok(ConversionResult::Success(size)) => Ok(size),
+            _ => Err(()),
+        }
+    }
+}
+
+impl ReadableStreamDefaultControllerMethods for ReadableStreamDefaultController {
+    // https://streams.spec.whatwg.org/#rs-default-controller-desired-size
+    fn GetDesiredSize(&self) -> Option<f64> {
+        self.desired_size()
+    }
+
+    // https://streams.spec.whatwg.org/#rs-default-controller-close
+    fn Close(&self) -> ErrorResult {
+        // Step 2.
+        if !self.can_close_or_enqueue() {
+            return Err(Error::Type("The stream cannot be closed".to_string()));
+        }
+
+        // Step 3.
+        // https://streams.spec.whatwg.org/#readable-stream-default-controller-close
+        self.close_requested.set(true);
+        if self.queue.borrow().is_empty() {
+            self.stream.close();
+        }
+        Ok(())
+    }
+
+    // https://streams.spec.whatwg.org/#rs-default-controller-enqueue
+    #[allow(unsafe_code, unrooted_must_root)]
+    unsafe fn Enqueue(&self, cx: *mut JSContext, chunk: HandleValue) -> ErrorResult {
+        // Step 2.
+        if !self.can_close_or_enqueue() {
+            return Err(Error::Type(
+                "The stream cannot have chunks enqueued".to_string(),
+            ));
+        }
+
+        // Step 3.
+        // https://streams.spec.whatwg.org/#readable-stream-default-controller-enqueue
+        if self.stream.is_locked() && self.stream.num_read_requests() > 0 {
+            // Step 4.
+            self.stream
+                .fulfill_read_request(Chunk::Value(RootedTraceableBox::from_box(Heap::boxed(
+                    chunk.get(),
+                ))));
+        } else {
+            // Step 5.
+            let size = match self.chunk_size(cx, chunk) {
+                Ok(size) => size,
+                Err(()) => {
+                    rooted!(in(cx) let mut error = UndefinedValue());
+                    take_pending_exception(cx, error.handle_mut());
+                    self.error(error.handle());
+                    JS_SetPendingException(cx, error.handle());
+                    return Err(Error::JSFailed);
+                },
+            };
+
+            // https://streams.spec.whatwg.org/#enqueue-value-with-size
+            if size.is_nan() || size < 0. || size.is_infinite() {
+                let error = Error::Range("The chunk size is not a valid number".to_string());
+                rooted!(in(cx) let mut e = UndefinedValue());
+                error.to_jsval(cx, &self.global(), e.handle_mut());
+                self.error(e.handle());
+                JS_SetPendingException(cx, e.handle());
+                return Err(Error::JSFailed);
+            }
+            self.queue.borrow_mut().push_back(ValueWithSize {
+                value: Heap::boxed(chunk.get()),
+                size: size,
+            });
+            self.queue_total_size
+                .set(self.queue_total_size.get() + size);
+        }
+
+        // Step 6.
+        self.call_pull_if_needed();
+        Ok(())
+    }
+
+    // https://streams.spec.whatwg.org/#rs-default-controller-error
+    #[allow(unsafe_code)]
+    unsafe fn Error(&self, _cx: *mut JSContext, e: HandleValue) {
+        self.error(e);
+    }
+}
@@ -0,0 +1,349 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::ReadableStreamDefaultReaderBinding::{
+    self, ReadableStreamDefaultReaderMethods, ReadableStreamReadResult,
+};
+use crate::dom::bindings::error::{Error, ErrorResult, Fallible};
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
+use crate::dom::bindings::root::{DomRoot, MutNullableDom};
+use crate::dom::bindings::trace::{JSTraceable, RootedTraceableBox};
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::promise::Promise;
+use crate::dom::readablestream::{mark_as_handled, Chunk, ReadableStream, ReadableStreamState};
+use dom_struct::dom_struct;
+use js::jsapi::{Heap, JSAutoCompartment, JSContext};
+use js::jsval::UndefinedValue;
+use js::rust::HandleValue;
+use std::collections::VecDeque;
+use std::mem;
+use std::rc::Rc;
+
+/// What native code reading all the bytes of a stream does with them
+pub trait ReadAllBytesHandler: JSTraceable {
+    /// Called with the bytes of all the chunks once the stream is closed
+    fn success(&self, bytes: Vec<u8>);
+    /// Called with the error the stream errored with, or a `TypeError` if
+    /// one of its chunks is not an `Uint8Array`
+    fn failure(&self, cx: *mut JSContext, error: HandleValue);
+}
+
+/// The state of a read loop reading all the bytes of a stream
+///
+/// https://fetch.spec.whatwg.org/#concept-read-all-bytes-from-readablestream
+#[derive(JSTraceable)]
+#[must_root]
+pub struct ReadAllBytes {
+    bytes: Vec<u8>,
+    handler: Box<dyn ReadAllBytesHandler>,
+}
+
+impl ReadAllBytes {
+    pub fn new(handler: Box<dyn ReadAllBytesHandler>) -> ReadAllBytes {
+        ReadAllBytes {
+            bytes: vec![],
+            handler: handler,
+        }
+    }
+
+    /// Appends the bytes of `chunk`, or calls the failure steps and returns
+    /// false if it does not have any
+    #[allow(unsafe_code)]
+    fn append(&mut self, reader: &ReadableStreamDefaultReader, chunk: Chunk) -> bool {
+        let global = reader.global();
+        let cx = global.get_cx();
+        match unsafe { chunk.into_bytes(cx) } {
+            Some(mut bytes) => {
+                self.bytes.append(&mut bytes);
+                true
+            },
+            None => {
+                let _ac = JSAutoCompartment::new(cx, reader.reflector().get_jsobject().get());
+                rooted!(in(cx) let mut error = UndefinedValue());
+                unsafe {
+                    Error::Type("The stream's chunks are not all Uint8Array objects".to_string())
+                        .to_jsval(cx, &global, error.handle_mut());
+                }
+                self.handler.failure(cx, error.handle());
+                false
+            },
+        }
+    }
+}
+
+/// https://streams.spec.whatwg.org/#readablestreamdefaultreader-readrequests
+#[derive(JSTraceable)]
+#[must_root]
+pub enum ReadRequest {
+    /// A call to `read()` by script, resolved with a read result
+    Read(Rc<Promise>),
+    /// Native code reading all the bytes of the stream
+    ReadAllBytes(ReadAllBytes),
+}
+
+impl ReadRequest {
+    /// https://streams.spec.whatwg.org/#readable-stream-fulfill-read-request
+    #[allow(unrooted_must_root, unsafe_code)]
+    pub fn chunk_steps(self, reader: &ReadableStreamDefaultReader, chunk: Chunk) {
+        match self {
+            ReadRequest::Read(promise) => {
+                let global = reader.global();
+                let cx = global.get_cx();
+                let _ac = JSAutoCompartment::new(cx, promise.reflector().get_jsobject().get());
+                rooted!(in(cx) let mut value = UndefinedValue());
+                match unsafe { chunk.to_jsval(cx, value.handle_mut()) } {
+                    Ok(()) => promise.resolve_native(&read_result(value.handle(), false)),
+                    Err(error) => promise.reject_error(error),
+                }
+            },
+            ReadRequest::ReadAllBytes(mut read_all) => {
+                if read_all.append(reader, chunk) {
+                    reader.read_all_bytes(read_all);
+                }
+            },
+        }
+    }
+
+    /// https://streams.spec.whatwg.org/#readable-stream-close
+    /// Step 5.
+    #[allow(unrooted_must_root)]
+    pub fn close_steps(self, reader: &ReadableStreamDefaultReader) {
+        match self {
+            ReadRequest::Read(promise) => {
+                let cx = reader.global().get_cx();
+                rooted!(in(cx) let undefined = UndefinedValue());
+                promise.resolve_native(&read_result(undefined.handle(), true));
+            },
+            ReadRequest::ReadAllBytes(read_all) => read_all.handler.success(read_all.bytes),
+        }
+    }
+
+    /// https://streams.spec.whatwg.org/#readable-stream-error
+    /// Step 8.
+    #[allow(unrooted_must_root)]
+    pub fn error_steps(self, reader: &ReadableStreamDefaultReader, e: HandleValue) {
+        match self {
+            ReadRequest::Read(promise) => promise.reject_native(&e),
+            ReadRequest::ReadAllBytes(read_all) => {
+                read_all.handler.failure(reader.global().get_cx(), e)
+            },
+        }
+    }
+}
+
+/// https://streams.spec.whatwg.org/#readable-stream-create-read-result
+fn read_result(value: HandleValue, done: bool) -> ReadableStreamReadResult {
+    ReadableStreamReadResult {
+        done: done,
+        value: RootedTraceableBox::from_box(Heap::boxed(value.get())),
+    }
+}
+
+#[dom_struct]
+pub struct ReadableStreamDefaultReader {
+    reflector_: Reflector,
+    /// https://streams.spec.whatwg.org/#readablestreamdefaultreader-ownerreadablestream
+    stream: MutNullableDom<ReadableStream>,
+    /// https://streams.spec.whatwg.org/#readablestreamdefaultreader-closedpromise
+    #[ignore_malloc_size_of = "Rc is hard"]
+    closed_promise: DomRefCell<Rc<Promise>>,
+    #[ignore_malloc_size_of = "Rc is hard"]
+    read_requests: DomRefCell<VecDeque<ReadRequest>>,
+}
+
+impl ReadableStreamDefaultReader {
+    #[allow(unrooted_must_root)]
+    fn new_inherited(closed_promise: Rc<Promise>) -> ReadableStreamDefaultReader {
+        ReadableStreamDefaultReader {
+            reflector_: Reflector::new(),
+            stream: Default::default(),
+            closed_promise: DomRefCell::new(closed_promise),
+            read_requests: DomRefCell::new(VecDeque::new()),
+        }
+    }
+
+    // https://streams.spec.whatwg.org/#default-reader-constructor
+    #[allow(unsafe_code)]
+    pub fn Constructor(
+        global: &GlobalScope,
+        stream: &ReadableStream,
+    ) -> Fallible<DomRoot<ReadableStreamDefaultReader>> {
+        // Step 1.
+        if stream.is_locked() {
+            return Err(Error::Type("The stream is already locked".to_string()));
+        }
+
+        // Step 2.
+        // https://streams.spec.whatwg.org/#readable-stream-reader-generic-initialize
+        let cx = global.get_cx();
+        let _ac = JSAutoCompartment::new(cx, global.reflector().get_jsobject().get());
+        let closed_promise = Promise::new(global, &_ac);
+        match stream.state() {
+            ReadableStreamState::Readable => {},
+            ReadableStreamState::Closed => closed_promise.resolve_native(&()),
+            ReadableStreamState::Errored => {
+                closed_promise.reject_native(&stream.stored_error());
+                mark_as_handled(global, &closed_promise);
+            },
+        }
+        let reader = reflect_dom_object(
+            Box::new(ReadableStreamDefaultReader::new_inherited(closed_promise)),
+            global,
+            ReadableStreamDefaultReaderBinding::Wrap,
+        );
+        reader.stream.set(Some(stream));
+        stream.set_reader(Some(&reader));
+        Ok(reader)
+    }
+
+    pub fn closed_promise(&self) -> Rc<Promise> {
+        self.closed_promise.borrow().clone()
+    }
+
+    pub fn num_read_requests(&self) -> usize {
+        self.read_requests.borrow().len()
+    }
+
+    #[allow(unrooted_must_root)]
+    pub fn take_read_request(&self) -> ReadRequest {
+        self.read_requests
+            .borrow_mut()
+            .pop_front()
+            .expect("reader has no read request")
+    }
+
+    #[allow(unrooted_must_root)]
+    pub fn take_read_requests(&self) -> VecDeque<ReadRequest> {
+        mem::replace(&mut *self.read_requests.borrow_mut(), VecDeque::new())
+    }
+
+    /// https://streams.spec.whatwg.org/#readable-stream-default-reader-read
+    #[allow(unrooted_must_root)]
+    fn read(&self, stream: &ReadableStream, request: ReadRequest) {
+        // Step 3.
+        stream.set_disturbed();
+
+        match stream.state() {
+            // Step 4.
+            ReadableStreamState::Closed => request.close_steps(self),
+            // Step 5.
+            ReadableStreamState::Errored => request.error_steps(self, stream.stored_error()),
+            // Step 6.
+            ReadableStreamState::Readable => {
+                let controller = stream.controller();
+                match controller.take_chunk() {
+                    Some(chunk) => request.chunk_steps(self, chunk),
+                    None => {
+                        self.read_requests.borrow_mut().push_back(request);
+                        controller.call_pull_if_needed();
+                    },
+                }
+            },
+        }
+    }
+
+    /// Reads all the chunks queued in the stream, and then waits for more
+    /// unless it is closed or errored
+    ///
+    /// This loops instead of reading each chunk from the chunk steps of the
+    /// previous one, for a long queue not to overflow the stack.
+    #[allow(unrooted_must_root)]
+    pub fn read_all_bytes(&self, mut read_all: ReadAllBytes) {
+        let stream = match self.stream.get() {
+            Some(stream) => stream,
+            None => return,
+        };
+        if stream.state() == ReadableStreamState::Readable {
+            let controller = stream.controller();
+            while let Some(chunk) = controller.take_chunk() {
+                if !read_all.append(self, chunk) {
+                    return;
+                }
+            }
+        }
+        self.read(&stream, ReadRequest::ReadAllBytes(read_all));
+    }
+
+    /// https://streams.spec.whatwg.org/#readable-stream-reader-generic-release
+    #[allow(unsafe_code)]
+    fn release(&self, stream: &ReadableStream) {
+        let global = self.global();
+        let error = Error::Type("The reader was released".to_string());
+        let closed_promise = if stream.state() == ReadableStreamState::Readable {
+            // Step 3.
+            self.closed_promise()
+        } else {
+            // Step 4.
+            let cx = global.get_cx();
+            let _ac = JSAutoCompartment::new(cx, global.reflector().get_jsobject().get());
+            let promise = Promise::new(&global, &_ac);
+            *self.closed_promise.borrow_mut() = promise.clone();
+            promise
+        };
+        closed_promise.reject_error(error);
+
+        // Step 5.
+        mark_as_handled(&global, &closed_promise);
+
+        // Steps 6-7.
+        stream.set_reader(None);
+        self.stream.set(None);
+    }
+}
+
+impl ReadableStreamDefaultReaderMethods for ReadableStreamDefaultReader {
+    // https://streams.spec.whatwg.org/#default-reader-closed
+    fn Closed(&self) -> Rc<Promise> {
+        self.closed_promise()
+    }
+
+    // https://streams.spec.whatwg.org/#default-reader-cancel
+    #[allow(unsafe_code)]
+    unsafe fn Cancel(&self, _cx: *mut JSContext, reason: HandleValue) -> Rc<Promise> {
+        match self.stream.get() {
+            // Step 2.
+            Some(stream) => stream.cancel(reason),
+            // Step 1.
+            None => {
+                let promise = Promise::new_in_current_compartment(&self.global());
+                promise.reject_error(Error::Type("The reader was released".to_string()));
+                promise
+            },
+        }
+    }
+
+    // https://streams.spec.whatwg.org/#default-reader-read
+    #[allow(unsafe_code)]
+    fn Read(&self) -> Rc<Promise> {
+        let promise = unsafe { Promise::new_in_current_compartment(&self.global()) };
+        match self.stream.get() {
+            // Step 2.
+            Some(stream) => self.read(&stream, ReadRequest::Read(promise.clone())),
+            // Step 1.
+            None => promise.reject_error(Error::Type("The reader was released".to_string())),
+        }
+        promise
+    }
+
+    // https://streams.spec.whatwg.org/#default-reader-release-lock
+    fn ReleaseLock(&self) -> ErrorResult {
+        // Step 1.
+        let stream = match self.stream.get() {
+            Some(stream) => stream,
+            None => return Ok(()),
+        };
+
+        // Step 2.
+        if !self.read_requests.borrow().is_empty() {
+            return Err(Error::Type(
+                "The reader still has pending read requests".to_string(),
+            ));
+        }
+
+        // Step 3.
+        self.release(&stream);
+        Ok(())
+    }
+}
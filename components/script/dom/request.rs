@@ -2,7 +2,8 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
-use crate::body::{consume_body, BodyOperations, BodyType};
+use crate::body::{consume_body, extract_body, BodyOperations, BodyType, ExtractedBody};
+use crate::dom::abortsignal::AbortSignal;
 use crate::dom::bindings::cell::DomRefCell;
 use crate::dom::bindings::codegen::Bindings::HeadersBinding::{HeadersInit, HeadersMethods};
 use crate::dom::bindings::codegen::Bindings::RequestBinding;
@@ -17,16 +18,17 @@ use crate::dom::bindings::codegen::Bindings::RequestBinding::RequestMode;
 use crate::dom::bindings::codegen::Bindings::RequestBinding::RequestRedirect;
 use crate::dom::bindings::error::{Error, Fallible};
 use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
-use crate::dom::bindings::root::{DomRoot, MutNullableDom};
+use crate::dom::bindings::root::{Dom, DomRoot, MutNullableDom};
 use crate::dom::bindings::str::{ByteString, DOMString, USVString};
 use crate::dom::bindings::trace::RootedTraceableBox;
 use crate::dom::globalscope::GlobalScope;
 use crate::dom::headers::{Guard, Headers};
 use crate::dom::promise::Promise;
-use crate::dom::xmlhttprequest::Extractable;
+use crate::dom::readablestream::ReadableStream;
 use dom_struct::dom_struct;
 use http::method::InvalidMethod;
 use http::Method as HttpMethod;
+use net_traits::cache_storage::CachedRequest;
 use net_traits::request::CacheMode as NetTraitsRequestCache;
 use net_traits::request::CredentialsMode as NetTraitsRequestCredentials;
 use net_traits::request::Destination as NetTraitsRequestDestination;
@@ -46,27 +48,35 @@ pub struct Request {
     reflector_: Reflector,
     request: DomRefCell<NetTraitsRequest>,
     body_used: Cell<bool>,
+    /// The stream the body gets read from, if script gave one instead of
+    /// bytes.
+    body_stream: MutNullableDom<ReadableStream>,
     headers: MutNullableDom<Headers>,
     mime_type: DomRefCell<Vec<u8>>,
     #[ignore_malloc_size_of = "Rc"]
     body_promise: DomRefCell<Option<(Rc<Promise>, BodyType)>>,
+    /// https://fetch.spec.whatwg.org/#request-signal
+    signal: Dom<AbortSignal>,
 }
 
 impl Request {
-    fn new_inherited(global: &GlobalScope, url: ServoUrl) -> Request {
+    fn new_inherited(global: &GlobalScope, url: ServoUrl, signal: &AbortSignal) -> Request {
         Request {
             reflector_: Reflector::new(),
             request: DomRefCell::new(net_request_from_global(global, url)),
             body_used: Cell::new(false),
+            body_stream: Default::default(),
             headers: Default::default(),
             mime_type: DomRefCell::new("".to_string().into_bytes()),
             body_promise: DomRefCell::new(None),
+            signal: Dom::from_ref(signal),
         }
     }
 
     pub fn new(global: &GlobalScope, url: ServoUrl) -> DomRoot<Request> {
+        let signal = AbortSignal::new(global);
         reflect_dom_object(
-            Box::new(Request::new_inherited(global, url)),
+            Box::new(Request::new_inherited(global, url, &signal)),
             global,
             RequestBinding::Wrap,
         )
@@ -90,6 +100,9 @@ impl Request {
         // Step 4
         let base_url = global.api_base_url();
 
+        // The signal the signal of the new request follows, if any
+        let mut signal: Option<DomRoot<AbortSignal>> = None;
+
         match input {
             // Step 5
             RequestInfo::USVString(USVString(ref usv_string)) => {
@@ -119,6 +132,7 @@ impl Request {
                 }
                 // Step 6.2
                 temporary_request = input_request.request.borrow().clone();
+                signal = Some(input_request.Signal());
             },
         }
 
@@ -170,6 +184,7 @@ impl Request {
             init.redirect.is_some() ||
             init.referrer.is_some() ||
             init.referrerPolicy.is_some() ||
+            init.signal.is_some() ||
             !init.window.handle().is_undefined()
         {
             // Step 13.1
@@ -293,9 +308,16 @@ impl Request {
             request.method = method;
         }
 
+        if let Some(init_signal) = init.signal.as_ref() {
+            signal = init_signal.clone();
+        }
+
         // Step 26
         let r = Request::from_net_request(global, request);
         r.headers.or_init(|| Headers::for_request(&r.global()));
+        if let Some(signal) = signal {
+            r.signal.follow(&signal);
+        }
 
         // Step 27
         let mut headers_copy = r.Headers();
@@ -363,16 +385,20 @@ impl Request {
         r.request.borrow_mut().headers = r.Headers().get_headers_list();
 
         // Step 32
-        let mut input_body = if let RequestInfo::Request(ref input_request) = input {
-            let input_request_request = input_request.request.borrow();
-            input_request_request.body.clone()
-        } else {
-            None
-        };
+        let (mut input_body, mut input_stream) =
+            if let RequestInfo::Request(ref input_request) = input {
+                let input_request_request = input_request.request.borrow();
+                (
+                    input_request_request.body.clone(),
+                    input_request.body_stream.get(),
+                )
+            } else {
+                (None, None)
+            };
 
         // Step 33
         if let Some(init_body_option) = init.body.as_ref() {
-            if init_body_option.is_some() || input_body.is_some() {
+            if init_body_option.is_some() || input_body.is_some() || input_stream.is_some() {
                 let req = r.request.borrow();
                 let req_method = &req.method;
                 match *req_method {
@@ -394,9 +420,18 @@ impl Request {
         // Step 34
         if let Some(Some(ref init_body)) = init.body {
             // Step 34.2
-            let extracted_body_tmp = init_body.extract();
-            input_body = Some(extracted_body_tmp.0);
-            let content_type = extracted_body_tmp.1;
+            let content_type = match extract_body(init_body)? {
+                ExtractedBody::Bytes(bytes, content_type) => {
+                    input_body = Some(bytes);
+                    input_stream = None;
+                    content_type
+                },
+                ExtractedBody::Stream(stream) => {
+                    input_body = None;
+                    input_stream = Some(stream);
+                    None
+                },
+            };
 
             // Step 34.3
             if let Some(contents) = content_type {
@@ -415,13 +450,15 @@ impl Request {
 
         // Step 35
         r.request.borrow_mut().body = input_body;
+        r.body_stream
+            .set(input_stream.as_ref().map(|stream| &**stream));
 
         // Step 36
         let extracted_mime_type = r.Headers().extract_mime_type();
         *r.mime_type.borrow_mut() = extracted_mime_type;
 
         // Step 37
-        // TODO: `ReadableStream` object is not implemented in Servo yet.
+        // TODO: The body of the input request is not marked as disturbed yet.
 
         // Step 38
         Ok(r)
@@ -429,9 +466,7 @@ impl Request {
 
     // https://fetch.spec.whatwg.org/#concept-body-locked
     fn locked(&self) -> bool {
-        // TODO: ReadableStream is unimplemented. Just return false
-        // for now.
-        false
+        request_is_locked(self)
     }
 }
 
@@ -442,6 +477,34 @@ impl Request {
         r
     }
 
+    /// Creates a request object for a request script did not construct,
+    /// such as the request of a fetch event, with immutable headers.
+    pub fn new_immutable(global: &GlobalScope, net_request: NetTraitsRequest) -> DomRoot<Request> {
+        let headers = net_request.headers.clone();
+        let r = Request::from_net_request(global, net_request);
+        r.Headers().set_headers(headers);
+        r.Headers().set_guard(Guard::Immutable);
+        r
+    }
+
+    /// Creates a request object for a request stored in a cache.
+    pub fn new_from_cache(global: &GlobalScope, cached: CachedRequest) -> DomRoot<Request> {
+        let mut net_request = net_request_from_global(global, cached.url);
+        net_request.method = cached.method;
+        net_request.headers = cached.headers;
+        Request::new_immutable(global, net_request)
+    }
+
+    /// The parts of this request to store in a cache.
+    pub fn to_cached(&self) -> CachedRequest {
+        let request = self.request.borrow();
+        CachedRequest {
+            url: request.url(),
+            method: request.method.clone(),
+            headers: self.Headers().get_headers_list(),
+        }
+    }
+
     fn clone_from(r: &Request) -> Fallible<DomRoot<Request>> {
         let req = r.request.borrow();
         let url = req.url();
@@ -461,6 +524,7 @@ impl Request {
             .Headers()
             .fill(Some(HeadersInit::Headers(r.Headers())))?;
         r_clone.Headers().set_guard(headers_guard);
+        r_clone.signal.follow(&r.signal);
         Ok(r_clone)
     }
 
@@ -515,16 +579,20 @@ fn includes_credentials(input: &ServoUrl) -> bool {
     !input.username().is_empty() || input.password().is_some()
 }
 
-// TODO: `Readable Stream` object is not implemented in Servo yet.
 // https://fetch.spec.whatwg.org/#concept-body-disturbed
-fn request_is_disturbed(_input: &Request) -> bool {
-    false
+fn request_is_disturbed(input: &Request) -> bool {
+    input
+        .body_stream
+        .get()
+        .map_or(false, |stream| stream.is_disturbed())
 }
 
-// TODO: `Readable Stream` object is not implemented in Servo yet.
 // https://fetch.spec.whatwg.org/#concept-body-locked
-fn request_is_locked(_input: &Request) -> bool {
-    false
+fn request_is_locked(input: &Request) -> bool {
+    input
+        .body_stream
+        .get()
+        .map_or(false, |stream| stream.is_locked())
 }
 
 impl RequestMethods for Request {
@@ -601,9 +669,14 @@ impl RequestMethods for Request {
         DOMString::from_string(r.integrity_metadata.clone())
     }
 
+    // https://fetch.spec.whatwg.org/#dom-request-signal
+    fn Signal(&self) -> DomRoot<AbortSignal> {
+        DomRoot::from_ref(&self.signal)
+    }
+
     // https://fetch.spec.whatwg.org/#dom-body-bodyused
     fn BodyUsed(&self) -> bool {
-        self.body_used.get()
+        self.body_used.get() || request_is_disturbed(self)
     }
 
     // https://fetch.spec.whatwg.org/#dom-request-clone
@@ -615,6 +688,10 @@ impl RequestMethods for Request {
         if request_is_disturbed(self) {
             return Err(Error::Type("Request is disturbed".to_string()));
         }
+        // FIXME: Cloning the body stream needs it to be teed.
+        if self.body_stream.get().is_some() {
+            return Err(Error::NotSupported);
+        }
 
         // Step 2
         Request::clone_from(self)
@@ -667,6 +744,10 @@ impl BodyOperations for Request {
         Some(body.unwrap_or(vec![]))
     }
 
+    fn get_body_stream(&self) -> Option<DomRoot<ReadableStream>> {
+        self.body_stream.get()
+    }
+
     fn get_mime_type(&self) -> Ref<Vec<u8>> {
         self.mime_type.borrow()
     }
@@ -0,0 +1,239 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::callback::ExceptionHandling;
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::ResizeObserverBinding;
+use crate::dom::bindings::codegen::Bindings::ResizeObserverBinding::ResizeObserverBoxOptions;
+use crate::dom::bindings::codegen::Bindings::ResizeObserverBinding::ResizeObserverCallback;
+use crate::dom::bindings::codegen::Bindings::ResizeObserverBinding::ResizeObserverMethods;
+use crate::dom::bindings::codegen::Bindings::ResizeObserverBinding::ResizeObserverOptions;
+use crate::dom::bindings::codegen::Bindings::WindowBinding::WindowMethods;
+use crate::dom::bindings::error::Fallible;
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::reflector::{reflect_dom_object, Reflector};
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::element::Element;
+use crate::dom::node::Node;
+use crate::dom::resizeobserverentry::ResizeObserverEntry;
+use crate::dom::resizeobserversize::BoxSize;
+use crate::dom::window::Window;
+use dom_struct::dom_struct;
+use euclid::{Point2D, Rect, Size2D};
+use std::rc::Rc;
+
+/// The sizes of the boxes of an element, in CSS pixels except for the
+/// device pixel content box
+pub struct BoxSizes {
+    /// The content box, relative to the padding box
+    pub content_rect: Rect<f64>,
+    pub border_box: BoxSize,
+    pub content_box: BoxSize,
+    pub device_pixel_content_box: BoxSize,
+}
+
+impl BoxSizes {
+    /// https://drafts.csswg.org/resize-observer/#calculate-box-size
+    pub fn of(window: &Window, target: &Element) -> BoxSizes {
+        let sizes = window.box_sizes_query(target.upcast());
+        let rect = &sizes.content_rect;
+        let content_rect = Rect::new(
+            Point2D::new(rect.origin.x.to_f64_px(), rect.origin.y.to_f64_px()),
+            Size2D::new(rect.size.width.to_f64_px(), rect.size.height.to_f64_px()),
+        );
+        let border_box = &sizes.border_box;
+        let (width, height) = (content_rect.size.width, content_rect.size.height);
+        // the device pixel content box is snapped to whole device pixels
+        let ratio = window.device_pixel_ratio().get() as f64;
+        BoxSizes {
+            content_rect,
+            border_box: BoxSize::from_physical(
+                border_box.width.to_f64_px(),
+                border_box.height.to_f64_px(),
+                sizes.vertical,
+            ),
+            content_box: BoxSize::from_physical(width, height, sizes.vertical),
+            device_pixel_content_box: BoxSize::from_physical(
+                (width * ratio).round(),
+                (height * ratio).round(),
+                sizes.vertical,
+            ),
+        }
+    }
+
+    /// The size of the box that `observed_box` picks
+    pub fn observed(&self, observed_box: ResizeObserverBoxOptions) -> BoxSize {
+        match observed_box {
+            ResizeObserverBoxOptions::Border_box => self.border_box,
+            ResizeObserverBoxOptions::Content_box => self.content_box,
+            ResizeObserverBoxOptions::Device_pixel_content_box => self.device_pixel_content_box,
+        }
+    }
+}
+
+/// Where an observation is in the current round of the update loop
+#[derive(Clone, Copy, Debug, JSTraceable, MallocSizeOf, PartialEq)]
+enum ObservationState {
+    /// Its size hasn't changed, or it has already been broadcast
+    Done,
+    /// https://drafts.csswg.org/resize-observer/#dom-resizeobserver-activetargets-slot
+    Active,
+    /// https://drafts.csswg.org/resize-observer/#dom-resizeobserver-skippedtargets-slot
+    Skipped,
+}
+
+/// https://drafts.csswg.org/resize-observer/#resize-observation-interface
+#[derive(JSTraceable, MallocSizeOf)]
+struct ResizeObservation {
+    target: Dom<Element>,
+    observed_box: ResizeObserverBoxOptions,
+    /// None until a size has been broadcast, so that the first update
+    /// always reports one
+    last_reported_size: Option<BoxSize>,
+    state: ObservationState,
+}
+
+impl ResizeObservation {
+    /// https://drafts.csswg.org/resize-observer/#dom-resizeobservation-isactive
+    fn is_active(&self, window: &Window) -> bool {
+        let size = BoxSizes::of(window, &self.target).observed(self.observed_box);
+        self.last_reported_size != Some(size)
+    }
+}
+
+/// The depth of `element` in the tree, the root being at depth one
+pub fn depth(element: &Element) -> usize {
+    element.upcast::<Node>().inclusive_ancestors().count()
+}
+
+#[dom_struct]
+pub struct ResizeObserver {
+    reflector_: Reflector,
+    #[ignore_malloc_size_of = "can't measure Rc values"]
+    callback: Rc<ResizeObserverCallback>,
+    /// https://drafts.csswg.org/resize-observer/#dom-resizeobserver-observationtargets-slot
+    observation_targets: DomRefCell<Vec<ResizeObservation>>,
+}
+
+impl ResizeObserver {
+    fn new_inherited(callback: Rc<ResizeObserverCallback>) -> ResizeObserver {
+        ResizeObserver {
+            reflector_: Reflector::new(),
+            callback,
+            observation_targets: DomRefCell::new(vec![]),
+        }
+    }
+
+    fn new(window: &Window, callback: Rc<ResizeObserverCallback>) -> DomRoot<ResizeObserver> {
+        reflect_dom_object(
+            Box::new(ResizeObserver::new_inherited(callback)),
+            window,
+            ResizeObserverBinding::Wrap,
+        )
+    }
+
+    /// https://drafts.csswg.org/resize-observer/#dom-resizeobserver-resizeobserver
+    pub fn Constructor(
+        window: &Window,
+        callback: Rc<ResizeObserverCallback>,
+    ) -> Fallible<DomRoot<ResizeObserver>> {
+        let observer = ResizeObserver::new(window, callback);
+        window.Document().add_resize_observer(&observer);
+        Ok(observer)
+    }
+
+    /// https://drafts.csswg.org/resize-observer/#gather-active-observations-h
+    ///
+    /// Step 2, for the observations of this observer
+    pub fn gather_active_observations_at_depth(&self, window: &Window, depth_limit: usize) {
+        for observation in self.observation_targets.borrow_mut().iter_mut() {
+            observation.state = if !observation.is_active(window) {
+                ObservationState::Done
+            } else if depth(&observation.target) > depth_limit {
+                ObservationState::Active
+            } else {
+                ObservationState::Skipped
+            };
+        }
+    }
+
+    /// https://drafts.csswg.org/resize-observer/#has-active-observations-h
+    pub fn has_active_observations(&self) -> bool {
+        self.has_observations_in(ObservationState::Active)
+    }
+
+    /// https://drafts.csswg.org/resize-observer/#has-skipped-observations-h
+    pub fn has_skipped_observations(&self) -> bool {
+        self.has_observations_in(ObservationState::Skipped)
+    }
+
+    fn has_observations_in(&self, state: ObservationState) -> bool {
+        self.observation_targets
+            .borrow()
+            .iter()
+            .any(|observation| observation.state == state)
+    }
+
+    /// https://drafts.csswg.org/resize-observer/#broadcast-active-observations
+    ///
+    /// Step 2, for the observations of this observer. Returns the depth of
+    /// the shallowest target that was broadcast, if any.
+    pub fn broadcast_active_observations(&self, window: &Window) -> Option<usize> {
+        let mut shallowest_depth = None;
+        // the callback may observe and unobserve targets, so the entries
+        // are all created before calling it
+        let entries: Vec<DomRoot<ResizeObserverEntry>> = self
+            .observation_targets
+            .borrow_mut()
+            .iter_mut()
+            .filter(|observation| observation.state == ObservationState::Active)
+            .map(|observation| {
+                let sizes = BoxSizes::of(window, &observation.target);
+                let entry = ResizeObserverEntry::new(window, &observation.target, &sizes);
+                observation.last_reported_size = Some(sizes.observed(observation.observed_box));
+                observation.state = ObservationState::Done;
+                let target_depth = depth(&observation.target);
+                shallowest_depth = Some(
+                    shallowest_depth
+                        .map_or(target_depth, |shallowest| target_depth.min(shallowest)),
+                );
+                entry
+            })
+            .collect();
+        if !entries.is_empty() {
+            let _ = self
+                .callback
+                .Call_(self, entries, self, ExceptionHandling::Report);
+        }
+        shallowest_depth
+    }
+}
+
+impl ResizeObserverMethods for ResizeObserver {
+    /// https://drafts.csswg.org/resize-observer/#dom-resizeobserver-observe
+    fn Observe(&self, target: &Element, options: &ResizeObserverOptions) {
+        // observing a target again replaces its observation
+        self.Unobserve(target);
+        self.observation_targets
+            .borrow_mut()
+            .push(ResizeObservation {
+                target: Dom::from_ref(target),
+                observed_box: options.box_,
+                last_reported_size: None,
+                state: ObservationState::Done,
+            });
+    }
+
+    /// https://drafts.csswg.org/resize-observer/#dom-resizeobserver-unobserve
+    fn Unobserve(&self, target: &Element) {
+        self.observation_targets
+            .borrow_mut()
+            .retain(|observation| observation.target != target);
+    }
+
+    /// https://drafts.csswg.org/resize-observer/#dom-resizeobserver-disconnect
+    fn Disconnect(&self) {
+        self.observation_targets.borrow_mut().clear();
+    }
+}
@@ -0,0 +1,137 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::ResizeObserverEntryBinding;
+use crate::dom::bindings::codegen::Bindings::ResizeObserverEntryBinding::ResizeObserverEntryMethods;
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::domrectreadonly::DOMRectReadOnly;
+use crate::dom::element::Element;
+use crate::dom::resizeobserver::BoxSizes;
+use crate::dom::resizeobserversize::ResizeObserverSize;
+use crate::dom::window::Window;
+use dom_struct::dom_struct;
+use js::conversions::ToJSValConvertible;
+use js::jsapi::{Heap, JSContext};
+use js::jsval::{JSVal, UndefinedValue};
+use js::rust::wrappers::JS_FreezeObject;
+
+#[dom_struct]
+pub struct ResizeObserverEntry {
+    reflector_: Reflector,
+    target: Dom<Element>,
+    content_rect: Dom<DOMRectReadOnly>,
+    border_box_size: Dom<ResizeObserverSize>,
+    content_box_size: Dom<ResizeObserverSize>,
+    device_pixel_content_box_size: Dom<ResizeObserverSize>,
+    /// The frozen arrays reflecting the sizes above, created on first access
+    border_box_size_array: Heap<JSVal>,
+    content_box_size_array: Heap<JSVal>,
+    device_pixel_content_box_size_array: Heap<JSVal>,
+}
+
+impl ResizeObserverEntry {
+    fn new_inherited(
+        target: &Element,
+        content_rect: &DOMRectReadOnly,
+        border_box_size: &ResizeObserverSize,
+        content_box_size: &ResizeObserverSize,
+        device_pixel_content_box_size: &ResizeObserverSize,
+    ) -> ResizeObserverEntry {
+        ResizeObserverEntry {
+            reflector_: Reflector::new(),
+            target: Dom::from_ref(target),
+            content_rect: Dom::from_ref(content_rect),
+            border_box_size: Dom::from_ref(border_box_size),
+            content_box_size: Dom::from_ref(content_box_size),
+            device_pixel_content_box_size: Dom::from_ref(device_pixel_content_box_size),
+            border_box_size_array: Heap::default(),
+            content_box_size_array: Heap::default(),
+            device_pixel_content_box_size_array: Heap::default(),
+        }
+    }
+
+    /// https://drafts.csswg.org/resize-observer/#create-and-populate-a-resizeobserverentry
+    pub fn new(
+        window: &Window,
+        target: &Element,
+        sizes: &BoxSizes,
+    ) -> DomRoot<ResizeObserverEntry> {
+        let rect = &sizes.content_rect;
+        let content_rect = DOMRectReadOnly::new(
+            window.upcast(),
+            rect.origin.x,
+            rect.origin.y,
+            rect.size.width,
+            rect.size.height,
+        );
+        let border_box_size = ResizeObserverSize::new(window, sizes.border_box);
+        let content_box_size = ResizeObserverSize::new(window, sizes.content_box);
+        let device_pixel_content_box_size =
+            ResizeObserverSize::new(window, sizes.device_pixel_content_box);
+        reflect_dom_object(
+            Box::new(ResizeObserverEntry::new_inherited(
+                target,
+                &content_rect,
+                &border_box_size,
+                &content_box_size,
+                &device_pixel_content_box_size,
+            )),
+            window,
+            ResizeObserverEntryBinding::Wrap,
+        )
+    }
+
+    /// The frozen array holding just `size`, cached in `array`
+    ///
+    /// Elements that are split into fragments would have one size per
+    /// fragment, but layout only reports the first one.
+    #[allow(unsafe_code)]
+    unsafe fn size_array(&self, size: &ResizeObserverSize, array: &Heap<JSVal>) -> JSVal {
+        if array.get().is_undefined() {
+            let cx = self.global().get_cx();
+            let sizes = vec![DomRoot::from_ref(size)];
+            rooted!(in(cx) let mut jsval = UndefinedValue());
+            sizes.to_jsval(cx, jsval.handle_mut());
+            rooted!(in(cx) let object = jsval.to_object());
+            assert!(JS_FreezeObject(cx, object.handle()));
+            array.set(jsval.get());
+        }
+        array.get()
+    }
+}
+
+impl ResizeObserverEntryMethods for ResizeObserverEntry {
+    /// https://drafts.csswg.org/resize-observer/#dom-resizeobserverentry-target
+    fn Target(&self) -> DomRoot<Element> {
+        DomRoot::from_ref(&*self.target)
+    }
+
+    /// https://drafts.csswg.org/resize-observer/#dom-resizeobserverentry-contentrect
+    fn ContentRect(&self) -> DomRoot<DOMRectReadOnly> {
+        DomRoot::from_ref(&*self.content_rect)
+    }
+
+    /// https://drafts.csswg.org/resize-observer/#dom-resizeobserverentry-borderboxsize
+    #[allow(unsafe_code)]
+    unsafe fn BorderBoxSize(&self, _cx: *mut JSContext) -> JSVal {
+        self.size_array(&self.border_box_size, &self.border_box_size_array)
+    }
+
+    /// https://drafts.csswg.org/resize-observer/#dom-resizeobserverentry-contentboxsize
+    #[allow(unsafe_code)]
+    unsafe fn ContentBoxSize(&self, _cx: *mut JSContext) -> JSVal {
+        self.size_array(&self.content_box_size, &self.content_box_size_array)
+    }
+
+    /// https://drafts.csswg.org/resize-observer/#dom-resizeobserverentry-devicepixelcontentboxsize
+    #[allow(unsafe_code)]
+    unsafe fn DevicePixelContentBoxSize(&self, _cx: *mut JSContext) -> JSVal {
+        self.size_array(
+            &self.device_pixel_content_box_size,
+            &self.device_pixel_content_box_size_array,
+        )
+    }
+}
@@ -0,0 +1,69 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::ResizeObserverSizeBinding;
+use crate::dom::bindings::codegen::Bindings::ResizeObserverSizeBinding::ResizeObserverSizeMethods;
+use crate::dom::bindings::reflector::{reflect_dom_object, Reflector};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::window::Window;
+use dom_struct::dom_struct;
+
+/// The size of a box along the inline and block axes of its writing mode
+#[derive(Clone, Copy, Debug, JSTraceable, MallocSizeOf, PartialEq)]
+pub struct BoxSize {
+    pub inline_size: f64,
+    pub block_size: f64,
+}
+
+impl BoxSize {
+    /// The size of a box that is `width` wide and `height` high
+    pub fn from_physical(width: f64, height: f64, vertical: bool) -> BoxSize {
+        if vertical {
+            BoxSize {
+                inline_size: height,
+                block_size: width,
+            }
+        } else {
+            BoxSize {
+                inline_size: width,
+                block_size: height,
+            }
+        }
+    }
+}
+
+#[dom_struct]
+pub struct ResizeObserverSize {
+    reflector_: Reflector,
+    size: BoxSize,
+}
+
+impl ResizeObserverSize {
+    fn new_inherited(size: BoxSize) -> ResizeObserverSize {
+        ResizeObserverSize {
+            reflector_: Reflector::new(),
+            size,
+        }
+    }
+
+    pub fn new(window: &Window, size: BoxSize) -> DomRoot<ResizeObserverSize> {
+        reflect_dom_object(
+            Box::new(ResizeObserverSize::new_inherited(size)),
+            window,
+            ResizeObserverSizeBinding::Wrap,
+        )
+    }
+}
+
+impl ResizeObserverSizeMethods for ResizeObserverSize {
+    /// https://drafts.csswg.org/resize-observer/#dom-resizeobserversize-inlinesize
+    fn InlineSize(&self) -> f64 {
+        self.size.inline_size
+    }
+
+    /// https://drafts.csswg.org/resize-observer/#dom-resizeobserversize-blocksize
+    fn BlockSize(&self) -> f64 {
+        self.size.block_size
+    }
+}
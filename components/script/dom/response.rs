@@ -2,14 +2,14 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
-use crate::body::{consume_body, consume_body_with_promise, BodyOperations, BodyType};
+use crate::body::{consume_body, consume_body_with_promise, extract_body};
+use crate::body::{BodyOperations, BodyType, ExtractedBody};
 use crate::dom::bindings::cell::DomRefCell;
 use crate::dom::bindings::codegen::Bindings::HeadersBinding::{HeadersInit, HeadersMethods};
 use crate::dom::bindings::codegen::Bindings::ResponseBinding;
 use crate::dom::bindings::codegen::Bindings::ResponseBinding::{
-    ResponseMethods, ResponseType as DOMResponseType,
+    ResponseBodyInit, ResponseMethods, ResponseType as DOMResponseType,
 };
-use crate::dom::bindings::codegen::Bindings::XMLHttpRequestBinding::BodyInit;
 use crate::dom::bindings::error::{Error, Fallible};
 use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
 use crate::dom::bindings::root::{DomRoot, MutNullableDom};
@@ -18,12 +18,17 @@ use crate::dom::globalscope::GlobalScope;
 use crate::dom::headers::{is_obs_text, is_vchar};
 use crate::dom::headers::{Guard, Headers};
 use crate::dom::promise::Promise;
-use crate::dom::xmlhttprequest::Extractable;
+use crate::dom::readablestream::ReadableStream;
+use crate::dom::readablestreamdefaultreader::ReadAllBytesHandler;
 use dom_struct::dom_struct;
 use http::header::HeaderMap as HyperHeaders;
 use hyper::StatusCode;
 use hyper_serde::Serde;
+use ipc_channel::ipc::IpcSender;
+use net_traits::cache_storage::CachedResponse;
 use net_traits::response::ResponseBody as NetTraitsResponseBody;
+use net_traits::response::ResponseType as NetTraitsResponseType;
+use net_traits::NetworkError;
 use servo_url::ServoUrl;
 use std::cell::{Cell, Ref};
 use std::mem;
@@ -46,8 +51,18 @@ pub struct Response {
     url_list: DomRefCell<Vec<ServoUrl>>,
     // For now use the existing NetTraitsResponseBody enum
     body: DomRefCell<NetTraitsResponseBody>,
+    /// The stream the body gets read from, once script asked for it or if
+    /// it gave one to the constructor, at which point `body` stays empty.
+    stream: MutNullableDom<ReadableStream>,
+    /// The sender used to cancel the fetch of this response, for the
+    /// cancellation of its body stream to stop it.
+    #[ignore_malloc_size_of = "channels are hard"]
+    fetch_cancel_chan: DomRefCell<Option<IpcSender<()>>>,
     #[ignore_malloc_size_of = "Rc"]
     body_promise: DomRefCell<Option<(Rc<Promise>, BodyType)>>,
+    /// Whether the fetch of this response got aborted, in which case its
+    /// body errors with an AbortError instead of ever being done.
+    aborted: Cell<bool>,
 }
 
 impl Response {
@@ -63,7 +78,10 @@ impl Response {
             url: DomRefCell::new(None),
             url_list: DomRefCell::new(vec![]),
             body: DomRefCell::new(NetTraitsResponseBody::Empty),
+            stream: Default::default(),
+            fetch_cancel_chan: DomRefCell::new(None),
             body_promise: DomRefCell::new(None),
+            aborted: Cell::new(false),
         }
     }
 
@@ -78,7 +96,7 @@ impl Response {
 
     pub fn Constructor(
         global: &GlobalScope,
-        body: Option<BodyInit>,
+        body: Option<ResponseBodyInit>,
         init: &ResponseBinding::ResponseInit,
     ) -> Fallible<DomRoot<Response>> {
         // Step 1
@@ -125,8 +143,16 @@ impl Response {
             };
 
             // Step 7.3
-            let (extracted_body, content_type) = body.extract();
-            *r.body.borrow_mut() = NetTraitsResponseBody::Done(extracted_body);
+            let content_type = match extract_body(body)? {
+                ExtractedBody::Bytes(extracted_body, content_type) => {
+                    *r.body.borrow_mut() = NetTraitsResponseBody::Done(extracted_body);
+                    content_type
+                },
+                ExtractedBody::Stream(stream) => {
+                    r.stream.set(Some(&stream));
+                    None
+                },
+            };
 
             // Step 7.4
             if let Some(content_type_contents) = content_type {
@@ -210,9 +236,7 @@ impl Response {
 
     // https://fetch.spec.whatwg.org/#concept-body-locked
     fn locked(&self) -> bool {
-        // TODO: ReadableStream is unimplemented. Just return false
-        // for now.
-        false
+        self.stream.get().map_or(false, |stream| stream.is_locked())
     }
 }
 
@@ -224,6 +248,10 @@ impl BodyOperations for Response {
     fn set_body_promise(&self, p: &Rc<Promise>, body_type: BodyType) {
         assert!(self.body_promise.borrow().is_none());
         self.body_used.set(true);
+        if self.aborted.get() {
+            p.reject_error(Error::Abort);
+            return;
+        }
         *self.body_promise.borrow_mut() = Some((p.clone(), body_type));
     }
 
@@ -242,6 +270,10 @@ impl BodyOperations for Response {
         }
     }
 
+    fn get_body_stream(&self) -> Option<DomRoot<ReadableStream>> {
+        self.stream.get()
+    }
+
     fn get_mime_type(&self) -> Ref<Vec<u8>> {
         self.mime_type.borrow()
     }
@@ -323,12 +355,44 @@ impl ResponseMethods for Response {
             .or_init(|| Headers::for_response(&self.global()))
     }
 
+    // https://fetch.spec.whatwg.org/#dom-body-body
+    fn GetBody(&self) -> Option<DomRoot<ReadableStream>> {
+        if let Some(stream) = self.stream.get() {
+            return Some(stream);
+        }
+
+        // The stream only gets created once script asks for it, starting
+        // with the bytes received so far.
+        let body = mem::replace(&mut *self.body.borrow_mut(), NetTraitsResponseBody::Empty);
+        let (bytes, done) = match body {
+            NetTraitsResponseBody::Empty => return None,
+            NetTraitsResponseBody::Receiving(bytes) => (bytes, false),
+            NetTraitsResponseBody::Done(bytes) => (bytes, true),
+        };
+        let cancel_chan = self.fetch_cancel_chan.borrow_mut().take();
+        let stream = ReadableStream::new_native(&self.global(), cancel_chan);
+        if !bytes.is_empty() {
+            stream.enqueue_native(bytes);
+        }
+        if done {
+            stream.close_native();
+        } else if self.aborted.get() {
+            stream.error_native(Error::Abort);
+        }
+        self.stream.set(Some(&stream));
+        Some(stream)
+    }
+
     // https://fetch.spec.whatwg.org/#dom-response-clone
     fn Clone(&self) -> Fallible<DomRoot<Response>> {
         // Step 1
-        if self.is_locked() || self.body_used.get() {
+        if self.is_locked() || self.BodyUsed() {
             return Err(Error::Type("cannot clone a disturbed response".to_string()));
         }
+        // FIXME: Cloning the body stream needs it to be teed.
+        if self.stream.get().is_some() {
+            return Err(Error::NotSupported);
+        }
 
         // Step 2
         let new_response = Response::new(&self.global());
@@ -359,7 +423,10 @@ impl ResponseMethods for Response {
 
     // https://fetch.spec.whatwg.org/#dom-body-bodyused
     fn BodyUsed(&self) -> bool {
-        self.body_used.get()
+        self.body_used.get() ||
+            self.stream
+                .get()
+                .map_or(false, |stream| stream.is_disturbed())
     }
 
     // https://fetch.spec.whatwg.org/#dom-body-text
@@ -412,11 +479,117 @@ impl Response {
         *self.url.borrow_mut() = Some(final_url);
     }
 
+    pub fn set_fetch_cancel_chan(&self, cancel_chan: IpcSender<()>) {
+        *self.fetch_cancel_chan.borrow_mut() = Some(cancel_chan);
+    }
+
+    /// Creates a response object for a response stored in a cache, with
+    /// immutable headers.
+    pub fn new_from_cache(global: &GlobalScope, cached: CachedResponse) -> DomRoot<Response> {
+        let r = Response::new(global);
+        *r.response_type.borrow_mut() = match cached.response_type {
+            NetTraitsResponseType::Basic => DOMResponseType::Basic,
+            NetTraitsResponseType::Cors => DOMResponseType::Cors,
+            NetTraitsResponseType::Default => DOMResponseType::Default,
+            NetTraitsResponseType::Error(_) => DOMResponseType::Error,
+            NetTraitsResponseType::Opaque => DOMResponseType::Opaque,
+            NetTraitsResponseType::OpaqueRedirect => DOMResponseType::Opaqueredirect,
+        };
+        *r.status.borrow_mut() = cached
+            .status
+            .as_ref()
+            .and_then(|&(status, _)| StatusCode::from_u16(status).ok());
+        *r.raw_status.borrow_mut() = cached.status;
+        *r.url.borrow_mut() = cached.url;
+        *r.body.borrow_mut() = NetTraitsResponseBody::Done(cached.body);
+        r.Headers().set_headers(cached.headers);
+        r.Headers().set_guard(Guard::Immutable);
+        r
+    }
+
+    /// The parts of this response to store in a cache, along with its body.
+    pub fn to_cached(&self, body: Vec<u8>) -> CachedResponse {
+        let response_type = match *self.response_type.borrow() {
+            DOMResponseType::Basic => NetTraitsResponseType::Basic,
+            DOMResponseType::Cors => NetTraitsResponseType::Cors,
+            DOMResponseType::Default => NetTraitsResponseType::Default,
+            DOMResponseType::Error => {
+                NetTraitsResponseType::Error(NetworkError::Internal("Error response".to_owned()))
+            },
+            DOMResponseType::Opaque => NetTraitsResponseType::Opaque,
+            DOMResponseType::Opaqueredirect => NetTraitsResponseType::OpaqueRedirect,
+        };
+        CachedResponse {
+            url: self.url.borrow().clone(),
+            response_type: response_type,
+            status: self.raw_status.borrow().clone(),
+            headers: self.Headers().get_headers_list(),
+            body: body,
+        }
+    }
+
+    /// Reads the whole body of this response, calling `handler` with its
+    /// bytes, unless it is disturbed or locked.
+    pub fn read_all_bytes(&self, handler: Box<dyn ReadAllBytesHandler>) -> Fallible<()> {
+        if self.BodyUsed() || self.is_locked() {
+            return Err(Error::Type(
+                "The response body is disturbed or locked".to_string(),
+            ));
+        }
+        match self.GetBody() {
+            Some(stream) => stream.read_all_bytes(handler),
+            None => {
+                handler.success(vec![]);
+                Ok(())
+            },
+        }
+    }
+
+    /// Appends a chunk received by the fetch of this response to its body,
+    /// or enqueues it in its body stream if there is one.
+    pub fn stream_chunk(&self, mut chunk: Vec<u8>) {
+        if let Some(stream) = self.stream.get() {
+            stream.enqueue_native(chunk);
+            return;
+        }
+        let mut body = self.body.borrow_mut();
+        match *body {
+            NetTraitsResponseBody::Receiving(ref mut bytes) => bytes.append(&mut chunk),
+            _ => *body = NetTraitsResponseBody::Receiving(chunk),
+        }
+    }
+
     #[allow(unrooted_must_root)]
-    pub fn finish(&self, body: Vec<u8>) {
+    pub fn finish(&self) {
+        if self.aborted.get() {
+            return;
+        }
+        if let Some(stream) = self.stream.get() {
+            stream.close_native();
+            return;
+        }
+        let body = match mem::replace(&mut *self.body.borrow_mut(), NetTraitsResponseBody::Empty) {
+            NetTraitsResponseBody::Receiving(bytes) => bytes,
+            _ => vec![],
+        };
         *self.body.borrow_mut() = NetTraitsResponseBody::Done(body);
         if let Some((p, body_type)) = self.body_promise.borrow_mut().take() {
             consume_body_with_promise(self, body_type, &p);
         }
     }
+
+    /// Errors the body of this response, as its fetch got aborted, unless
+    /// it was already entirely received.
+    pub fn abort(&self) {
+        if let NetTraitsResponseBody::Done(_) = *self.body.borrow() {
+            return;
+        }
+        self.aborted.set(true);
+        if let Some(stream) = self.stream.get() {
+            stream.error_native(Error::Abort);
+        }
+        if let Some((p, _)) = self.body_promise.borrow_mut().take() {
+            p.reject_error(Error::Abort);
+        }
+    }
 }
@@ -0,0 +1,339 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::RTCDataChannelBinding;
+use crate::dom::bindings::codegen::Bindings::RTCDataChannelBinding::RTCDataChannelMethods;
+use crate::dom::bindings::codegen::Bindings::RTCDataChannelBinding::{
+    RTCDataChannelInit, RTCDataChannelState,
+};
+use crate::dom::bindings::codegen::Bindings::WebSocketBinding::BinaryType;
+use crate::dom::bindings::conversions::ToJSValConvertible;
+use crate::dom::bindings::error::{Error, ErrorResult};
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject};
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::bindings::str::USVString;
+use crate::dom::blob::{Blob, BlobImpl};
+use crate::dom::event::{Event, EventBubbles, EventCancelable};
+use crate::dom::eventtarget::EventTarget;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::messageevent::MessageEvent;
+use crate::dom::rtcpeerconnection::RTCPeerConnection;
+use dom_struct::dom_struct;
+use js::jsapi::{JSAutoCompartment, JSObject};
+use js::jsval::UndefinedValue;
+use js::rust::CustomAutoRooterGuard;
+use js::typedarray::{ArrayBuffer, ArrayBufferView, CreateWith};
+use servo_atoms::Atom;
+use servo_media::webrtc::{
+    DataChannelId, DataChannelInit, DataChannelMessage, DataChannelState, WebRtcError,
+};
+use std::cell::Cell;
+use std::ptr;
+
+#[dom_struct]
+pub struct RTCDataChannel {
+    eventtarget: EventTarget,
+    #[ignore_malloc_size_of = "defined in servo-media"]
+    servo_media_id: DataChannelId,
+    peer_connection: Dom<RTCPeerConnection>,
+    label: USVString,
+    ordered: bool,
+    max_packet_life_time: Option<u16>,
+    max_retransmits: Option<u16>,
+    protocol: USVString,
+    negotiated: bool,
+    id: Option<u16>,
+    ready_state: Cell<RTCDataChannelState>,
+    binary_type: Cell<BinaryType>,
+}
+
+impl RTCDataChannel {
+    fn new_inherited(
+        peer_connection: &RTCPeerConnection,
+        servo_media_id: DataChannelId,
+        label: USVString,
+        options: &RTCDataChannelInit,
+    ) -> RTCDataChannel {
+        RTCDataChannel {
+            eventtarget: EventTarget::new_inherited(),
+            servo_media_id,
+            peer_connection: Dom::from_ref(peer_connection),
+            label,
+            ordered: options.ordered,
+            max_packet_life_time: options.maxPacketLifeTime,
+            max_retransmits: options.maxRetransmits,
+            protocol: options.protocol.clone(),
+            negotiated: options.negotiated,
+            id: options.id,
+            ready_state: Cell::new(RTCDataChannelState::Connecting),
+            binary_type: Cell::new(BinaryType::Arraybuffer),
+        }
+    }
+
+    /// Creates a data channel for one the backend already created, either
+    /// through `RTCPeerConnection.createDataChannel()` or because the other
+    /// peer announced it, and registers it with its peer connection.
+    pub fn new(
+        global: &GlobalScope,
+        peer_connection: &RTCPeerConnection,
+        servo_media_id: DataChannelId,
+        label: USVString,
+        options: &RTCDataChannelInit,
+    ) -> DomRoot<RTCDataChannel> {
+        let channel = reflect_dom_object(
+            Box::new(RTCDataChannel::new_inherited(
+                peer_connection,
+                servo_media_id,
+                label,
+                options,
+            )),
+            global,
+            RTCDataChannelBinding::Wrap,
+        );
+        peer_connection.register_data_channel(servo_media_id, &channel);
+        channel
+    }
+
+    fn fire_simple_event(&self, name: Atom) {
+        let event = Event::new(
+            &self.global(),
+            name,
+            EventBubbles::DoesNotBubble,
+            EventCancelable::NotCancelable,
+        );
+        event.upcast::<Event>().fire(self.upcast());
+    }
+
+    /// https://w3c.github.io/webrtc-pc/#announcing-a-data-channel-as-open
+    pub fn on_open(&self) {
+        if self.ready_state.get() != RTCDataChannelState::Connecting {
+            return;
+        }
+        self.ready_state.set(RTCDataChannelState::Open);
+        self.fire_simple_event(atom!("open"));
+    }
+
+    /// https://w3c.github.io/webrtc-pc/#announcing-a-data-channel-as-closed
+    pub fn on_close(&self) {
+        if self.ready_state.get() == RTCDataChannelState::Closed {
+            return;
+        }
+        self.ready_state.set(RTCDataChannelState::Closed);
+        self.fire_simple_event(atom!("close"));
+        self.peer_connection
+            .unregister_data_channel(&self.servo_media_id);
+    }
+
+    /// https://w3c.github.io/webrtc-pc/#transports
+    pub fn on_error(&self, error: WebRtcError) {
+        warn!("Data channel {} failed: {:?}", self.label.0, error);
+        self.fire_simple_event(atom!("error"));
+    }
+
+    /// https://w3c.github.io/webrtc-pc/#receiving-messages-on-a-data-channel
+    #[allow(unsafe_code)]
+    pub fn on_message(&self, message: DataChannelMessage) {
+        // Step 1.
+        if self.ready_state.get() != RTCDataChannelState::Open {
+            return;
+        }
+
+        // Steps 2-5.
+        let global = self.global();
+        // global.get_cx() returns a valid `JSContext` pointer, so this is safe.
+        unsafe {
+            let cx = global.get_cx();
+            let _ac = JSAutoCompartment::new(cx, self.reflector().get_jsobject().get());
+            rooted!(in(cx) let mut data = UndefinedValue());
+            match message {
+                DataChannelMessage::Text(text) => text.to_jsval(cx, data.handle_mut()),
+                DataChannelMessage::Binary(bytes) => match self.binary_type.get() {
+                    BinaryType::Blob => {
+                        let blob =
+                            Blob::new(&global, BlobImpl::new_from_bytes(bytes), "".to_owned());
+                        blob.to_jsval(cx, data.handle_mut());
+                    },
+                    BinaryType::Arraybuffer => {
+                        rooted!(in(cx) let mut array_buffer = ptr::null_mut::<JSObject>());
+                        assert!(ArrayBuffer::create(
+                            cx,
+                            CreateWith::Slice(&bytes),
+                            array_buffer.handle_mut()
+                        )
+                        .is_ok());
+
+                        (*array_buffer).to_jsval(cx, data.handle_mut());
+                    },
+                },
+            }
+            MessageEvent::dispatch_jsval(
+                self.upcast(),
+                &global,
+                data.handle(),
+                Some(&global.origin().immutable().ascii_serialization()),
+                None,
+            );
+        }
+    }
+
+    pub fn on_state_change(&self, state: DataChannelState) {
+        match state {
+            DataChannelState::Open => self.on_open(),
+            DataChannelState::Closing => {
+                // https://w3c.github.io/webrtc-pc/#data-transport-closing-procedure
+                if self.ready_state.get() != RTCDataChannelState::Closed &&
+                    self.ready_state.get() != RTCDataChannelState::Closing
+                {
+                    self.ready_state.set(RTCDataChannelState::Closing);
+                    self.fire_simple_event(atom!("closing"));
+                }
+            },
+            DataChannelState::Closed => self.on_close(),
+            _ => {},
+        }
+    }
+
+    /// Closes the channel without firing any event, as happens when its
+    /// peer connection gets closed.
+    ///
+    /// https://w3c.github.io/webrtc-pc/#dom-rtcpeerconnection-close (step 7)
+    pub fn close_silently(&self) {
+        self.ready_state.set(RTCDataChannelState::Closed);
+    }
+
+    /// https://w3c.github.io/webrtc-pc/#dom-datachannel-send
+    fn send(&self, message: DataChannelMessage) -> ErrorResult {
+        // Step 2.
+        if self.ready_state.get() != RTCDataChannelState::Open {
+            return Err(Error::InvalidState);
+        }
+        self.peer_connection
+            .send_data_channel_message(&self.servo_media_id, message);
+        Ok(())
+    }
+}
+
+impl RTCDataChannelMethods for RTCDataChannel {
+    /// https://w3c.github.io/webrtc-pc/#dom-rtcdatachannel-onopen
+    event_handler!(open, GetOnopen, SetOnopen);
+
+    /// https://w3c.github.io/webrtc-pc/#dom-rtcdatachannel-onbufferedamountlow
+    event_handler!(
+        bufferedamountlow,
+        GetOnbufferedamountlow,
+        SetOnbufferedamountlow
+    );
+
+    /// https://w3c.github.io/webrtc-pc/#dom-rtcdatachannel-onerror
+    event_handler!(error, GetOnerror, SetOnerror);
+
+    /// https://w3c.github.io/webrtc-pc/#dom-rtcdatachannel-onclosing
+    event_handler!(closing, GetOnclosing, SetOnclosing);
+
+    /// https://w3c.github.io/webrtc-pc/#dom-rtcdatachannel-onclose
+    event_handler!(close, GetOnclose, SetOnclose);
+
+    /// https://w3c.github.io/webrtc-pc/#dom-rtcdatachannel-onmessage
+    event_handler!(message, GetOnmessage, SetOnmessage);
+
+    /// https://w3c.github.io/webrtc-pc/#dom-datachannel-label
+    fn Label(&self) -> USVString {
+        self.label.clone()
+    }
+
+    /// https://w3c.github.io/webrtc-pc/#dom-datachannel-ordered
+    fn Ordered(&self) -> bool {
+        self.ordered
+    }
+
+    /// https://w3c.github.io/webrtc-pc/#dom-datachannel-maxpacketlifetime
+    fn GetMaxPacketLifeTime(&self) -> Option<u16> {
+        self.max_packet_life_time
+    }
+
+    /// https://w3c.github.io/webrtc-pc/#dom-datachannel-maxretransmits
+    fn GetMaxRetransmits(&self) -> Option<u16> {
+        self.max_retransmits
+    }
+
+    /// https://w3c.github.io/webrtc-pc/#dom-datachannel-protocol
+    fn Protocol(&self) -> USVString {
+        self.protocol.clone()
+    }
+
+    /// https://w3c.github.io/webrtc-pc/#dom-datachannel-negotiated
+    fn Negotiated(&self) -> bool {
+        self.negotiated
+    }
+
+    /// https://w3c.github.io/webrtc-pc/#dom-rtcdatachannel-id
+    fn GetId(&self) -> Option<u16> {
+        self.id
+    }
+
+    /// https://w3c.github.io/webrtc-pc/#dom-datachannel-readystate
+    fn ReadyState(&self) -> RTCDataChannelState {
+        self.ready_state.get()
+    }
+
+    /// https://w3c.github.io/webrtc-pc/#dom-rtcdatachannel-close
+    fn Close(&self) {
+        // Steps 2-3.
+        match self.ready_state.get() {
+            RTCDataChannelState::Closing | RTCDataChannelState::Closed => return,
+            _ => {},
+        }
+        self.ready_state.set(RTCDataChannelState::Closing);
+
+        // Step 4, the backend reporting the closed state once done.
+        self.peer_connection
+            .close_data_channel(&self.servo_media_id);
+    }
+
+    /// https://w3c.github.io/webrtc-pc/#dom-datachannel-binarytype
+    fn BinaryType(&self) -> BinaryType {
+        self.binary_type.get()
+    }
+
+    /// https://w3c.github.io/webrtc-pc/#dom-datachannel-binarytype
+    fn SetBinaryType(&self, binary_type: BinaryType) {
+        self.binary_type.set(binary_type)
+    }
+
+    /// https://w3c.github.io/webrtc-pc/#dom-rtcdatachannel-send
+    fn Send(&self, data: USVString) -> ErrorResult {
+        self.send(DataChannelMessage::Text(data.0))
+    }
+
+    /// https://w3c.github.io/webrtc-pc/#dom-rtcdatachannel-send-blob
+    fn Send_(&self, data: &Blob) -> ErrorResult {
+        let bytes = data.get_bytes().unwrap_or(vec![]);
+        self.send(DataChannelMessage::Binary(bytes))
+    }
+
+    /// https://w3c.github.io/webrtc-pc/#dom-rtcdatachannel-send-arraybuffer
+    fn Send__(&self, data: CustomAutoRooterGuard<ArrayBuffer>) -> ErrorResult {
+        self.send(DataChannelMessage::Binary(data.to_vec()))
+    }
+
+    /// https://w3c.github.io/webrtc-pc/#dom-rtcdatachannel-send-arraybufferview
+    fn Send___(&self, data: CustomAutoRooterGuard<ArrayBufferView>) -> ErrorResult {
+        self.send(DataChannelMessage::Binary(data.to_vec()))
+    }
+}
+
+impl<'a> From<&'a RTCDataChannelInit> for DataChannelInit {
+    fn from(init: &'a RTCDataChannelInit) -> DataChannelInit {
+        DataChannelInit {
+            label: String::new(),
+            ordered: init.ordered,
+            max_packet_life_time: init.maxPacketLifeTime,
+            max_retransmits: init.maxRetransmits,
+            protocol: init.protocol.0.clone(),
+            negotiated: init.negotiated,
+            id: init.id,
+        }
+    }
+}
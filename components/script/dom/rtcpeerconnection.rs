@@ -3,6 +3,7 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
 use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::RTCDataChannelBinding::RTCDataChannelInit;
 use crate::dom::bindings::codegen::Bindings::RTCIceCandidateBinding::RTCIceCandidateInit;
 use crate::dom::bindings::codegen::Bindings::RTCPeerConnectionBinding;
 use crate::dom::bindings::codegen::Bindings::RTCPeerConnectionBinding::RTCPeerConnectionMethods;
@@ -20,12 +21,15 @@ use crate::dom::bindings::inheritance::Castable;
 use crate::dom::bindings::refcounted::{Trusted, TrustedPromise};
 use crate::dom::bindings::reflector::reflect_dom_object;
 use crate::dom::bindings::reflector::DomObject;
-use crate::dom::bindings::root::{DomRoot, MutNullableDom};
+use crate::dom::bindings::root::{Dom, DomRoot, MutNullableDom};
+use crate::dom::bindings::str::USVString;
 use crate::dom::event::{Event, EventBubbles, EventCancelable};
 use crate::dom::eventtarget::EventTarget;
 use crate::dom::globalscope::GlobalScope;
 use crate::dom::mediastream::MediaStream;
 use crate::dom::promise::Promise;
+use crate::dom::rtcdatachannel::RTCDataChannel;
+use crate::dom::rtcdatachannelevent::RTCDataChannelEvent;
 use crate::dom::rtcicecandidate::RTCIceCandidate;
 use crate::dom::rtcpeerconnectioniceevent::RTCPeerConnectionIceEvent;
 use crate::dom::rtcsessiondescription::RTCSessionDescription;
@@ -40,12 +44,19 @@ use servo_media::webrtc::{
     BundlePolicy, GatheringState, IceCandidate, IceConnectionState, SdpType, SessionDescription,
     SignalingState, WebRtcController, WebRtcSignaller,
 };
+use servo_media::webrtc::{DataChannelEvent, DataChannelId, DataChannelInit, DataChannelMessage};
 use servo_media::ServoMedia;
 use servo_media_auto::Backend;
 
 use std::cell::Cell;
+use std::collections::HashMap;
 use std::rc::Rc;
 
+/// The longest label and protocol a data channel can have, in bytes
+///
+/// https://w3c.github.io/webrtc-pc/#dom-peerconnection-createdatachannel
+const MAX_DATA_CHANNEL_STRING_LENGTH: usize = 65535;
+
 #[dom_struct]
 pub struct RTCPeerConnection {
     eventtarget: EventTarget,
@@ -59,11 +70,25 @@ pub struct RTCPeerConnection {
     offer_promises: DomRefCell<Vec<Rc<Promise>>>,
     #[ignore_malloc_size_of = "promises are hard"]
     answer_promises: DomRefCell<Vec<Rc<Promise>>>,
-    local_description: MutNullableDom<RTCSessionDescription>,
-    remote_description: MutNullableDom<RTCSessionDescription>,
+    /// https://w3c.github.io/webrtc-pc/#dom-peerconnection-currentlocaldesc
+    current_local_description: MutNullableDom<RTCSessionDescription>,
+    /// https://w3c.github.io/webrtc-pc/#dom-peerconnection-pendinglocaldesc
+    pending_local_description: MutNullableDom<RTCSessionDescription>,
+    /// https://w3c.github.io/webrtc-pc/#dom-peerconnection-currentremotedesc
+    current_remote_description: MutNullableDom<RTCSessionDescription>,
+    /// https://w3c.github.io/webrtc-pc/#dom-peerconnection-pendingremotedesc
+    pending_remote_description: MutNullableDom<RTCSessionDescription>,
     gathering_state: Cell<RTCIceGatheringState>,
     ice_connection_state: Cell<RTCIceConnectionState>,
     signaling_state: Cell<RTCSignalingState>,
+    /// https://w3c.github.io/webrtc-pc/#dfn-negotiation-needed
+    /// Set when the backend asks for negotiation while an offer/answer
+    /// exchange is in progress, so that negotiationneeded fires once the
+    /// connection is stable again.
+    negotiation_needed: Cell<bool>,
+    /// The data channels that are not closed, by their backend id
+    #[ignore_malloc_size_of = "defined in servo-media"]
+    data_channels: DomRefCell<HashMap<DataChannelId, Dom<RTCDataChannel>>>,
 }
 
 struct RTCSignaller {
@@ -130,6 +155,22 @@ impl WebRtcSignaller for RTCSignaller {
 
     fn on_add_stream(&self, _: Box<BackendMediaStream>) {}
 
+    fn on_data_channel_event(
+        &self,
+        channel: DataChannelId,
+        event: DataChannelEvent,
+        _: &WebRtcController,
+    ) {
+        let this = self.trusted.clone();
+        let _ = self.task_source.queue_with_canceller(
+            task!(on_data_channel_event: move || {
+                let this = this.root();
+                this.on_data_channel_event(channel, event);
+            }),
+            &self.canceller,
+        );
+    }
+
     fn close(&self) {
         // do nothing
     }
@@ -144,11 +185,15 @@ impl RTCPeerConnection {
             offer_answer_generation: Cell::new(0),
             offer_promises: DomRefCell::new(vec![]),
             answer_promises: DomRefCell::new(vec![]),
-            local_description: Default::default(),
-            remote_description: Default::default(),
+            current_local_description: Default::default(),
+            pending_local_description: Default::default(),
+            current_remote_description: Default::default(),
+            pending_remote_description: Default::default(),
             gathering_state: Cell::new(RTCIceGatheringState::New),
             ice_connection_state: Cell::new(RTCIceConnectionState::New),
             signaling_state: Cell::new(RTCSignalingState::Stable),
+            negotiation_needed: Cell::new(false),
+            data_channels: DomRefCell::new(HashMap::new()),
         }
     }
 
@@ -226,10 +271,18 @@ impl RTCPeerConnection {
         event.upcast::<Event>().fire(self.upcast());
     }
 
+    /// https://w3c.github.io/webrtc-pc/#dfn-update-the-negotiation-needed-flag
     fn on_negotiation_needed(&self) {
         if self.closed.get() {
             return;
         }
+        // Negotiation is checked again once the signaling state goes back
+        // to stable.
+        if self.signaling_state.get() != RTCSignalingState::Stable {
+            self.negotiation_needed.set(true);
+            return;
+        }
+        self.negotiation_needed.set(false);
         let event = Event::new(
             &self.global(),
             atom!("negotiationneeded"),
@@ -239,6 +292,81 @@ impl RTCPeerConnection {
         event.upcast::<Event>().fire(self.upcast());
     }
 
+    fn on_data_channel_event(&self, id: DataChannelId, event: DataChannelEvent) {
+        if self.closed.get() {
+            return;
+        }
+
+        if let DataChannelEvent::NewChannel = event {
+            // https://w3c.github.io/webrtc-pc/#announce-datachannel-open
+            // XXX The backend does not tell the label and options the other
+            // peer gave the channel.
+            let channel = RTCDataChannel::new(
+                &self.global(),
+                self,
+                id,
+                USVString::from("".to_owned()),
+                &RTCDataChannelInit::empty(),
+            );
+            let event = RTCDataChannelEvent::new(
+                &self.global(),
+                atom!("datachannel"),
+                false,
+                false,
+                &channel,
+            );
+            event.upcast::<Event>().fire(self.upcast());
+            return;
+        }
+
+        let channel = match self.data_channels.borrow().get(&id) {
+            Some(channel) => DomRoot::from_ref(&**channel),
+            None => {
+                warn!("Event for the unknown data channel {:?}", id);
+                return;
+            },
+        };
+        match event {
+            DataChannelEvent::Open => channel.on_open(),
+            DataChannelEvent::Close => channel.on_close(),
+            DataChannelEvent::Error(error) => channel.on_error(error),
+            DataChannelEvent::OnMessage(message) => channel.on_message(message),
+            DataChannelEvent::StateChange(state) => channel.on_state_change(state),
+            DataChannelEvent::NewChannel => unreachable!(),
+        }
+    }
+
+    pub fn register_data_channel(&self, id: DataChannelId, channel: &RTCDataChannel) {
+        if self
+            .data_channels
+            .borrow_mut()
+            .insert(id, Dom::from_ref(channel))
+            .is_some()
+        {
+            warn!("The data channel {:?} got registered twice", id);
+        }
+    }
+
+    pub fn unregister_data_channel(&self, id: &DataChannelId) {
+        self.data_channels.borrow_mut().remove(id);
+    }
+
+    pub fn send_data_channel_message(&self, id: &DataChannelId, message: DataChannelMessage) {
+        self.controller
+            .borrow()
+            .as_ref()
+            .unwrap()
+            .send_data_channel_message(id, message);
+    }
+
+    pub fn close_data_channel(&self, id: &DataChannelId) {
+        self.controller
+            .borrow()
+            .as_ref()
+            .unwrap()
+            .close_data_channel(id);
+    }
+
     /// https://www.w3.org/TR/webrtc/#update-ice-gathering-state
     fn update_gathering_state(&self, state: GatheringState) {
         // step 1
@@ -327,6 +455,87 @@ impl RTCPeerConnection {
             EventCancelable::NotCancelable,
         );
         event.upcast::<Event>().fire(self.upcast());
+
+        if state == RTCSignalingState::Stable && self.negotiation_needed.get() {
+            self.on_negotiation_needed();
+        }
+    }
+
+    /// https://w3c.github.io/webrtc-pc/#set-description
+    ///
+    /// Steps 4.2-4.3, the description being rejected if it is not valid in
+    /// the current signaling state.
+    fn check_description(&self, desc: &RTCSessionDescriptionInit, remote: bool) -> Fallible<()> {
+        if self.closed.get() {
+            return Err(Error::InvalidState);
+        }
+        let (own_offer, other_offer, other_pranswer) = if remote {
+            (
+                RTCSignalingState::Have_remote_offer,
+                RTCSignalingState::Have_local_offer,
+                RTCSignalingState::Have_remote_pranswer,
+            )
+        } else {
+            (
+                RTCSignalingState::Have_local_offer,
+                RTCSignalingState::Have_remote_offer,
+                RTCSignalingState::Have_local_pranswer,
+            )
+        };
+        let state = self.signaling_state.get();
+        let valid = match desc.type_ {
+            RTCSdpType::Offer => state == RTCSignalingState::Stable || state == own_offer,
+            RTCSdpType::Answer | RTCSdpType::Pranswer => {
+                state == other_offer || state == other_pranswer
+            },
+            RTCSdpType::Rollback => {
+                state == RTCSignalingState::Have_local_offer ||
+                    state == RTCSignalingState::Have_remote_offer
+            },
+        };
+        if !valid {
+            return Err(Error::InvalidState);
+        }
+        Ok(())
+    }
+
+    /// https://w3c.github.io/webrtc-pc/#set-description
+    ///
+    /// Step 4.4, once the backend applied the description.
+    fn description_set(&self, desc: &RTCSessionDescriptionInit, remote: bool) {
+        let (current, pending, other_current, other_pending) = if remote {
+            (
+                &self.current_remote_description,
+                &self.pending_remote_description,
+                &self.current_local_description,
+                &self.pending_local_description,
+            )
+        } else {
+            (
+                &self.current_local_description,
+                &self.pending_local_description,
+                &self.current_remote_description,
+                &self.pending_remote_description,
+            )
+        };
+        match desc.type_ {
+            RTCSdpType::Offer | RTCSdpType::Pranswer => {
+                let desc =
+                    RTCSessionDescription::Constructor(&self.global().as_window(), desc).unwrap();
+                pending.set(Some(&desc));
+            },
+            RTCSdpType::Answer => {
+                let desc =
+                    RTCSessionDescription::Constructor(&self.global().as_window(), desc).unwrap();
+                current.set(Some(&desc));
+                pending.set(None);
+                if let Some(offer) = other_pending.get() {
+                    other_current.set(Some(&offer));
+                }
+                other_pending.set(None);
+            },
+            RTCSdpType::Rollback => pending.set(None),
+        }
     }
 
     fn create_offer(&self) {
@@ -421,6 +630,9 @@ impl RTCPeerConnectionMethods for RTCPeerConnection {
         SetOnnegotiationneeded
     );
 
+    /// https://w3c.github.io/webrtc-pc/#dom-rtcpeerconnection-ondatachannel
+    event_handler!(datachannel, GetOndatachannel, SetOndatachannel);
+
     /// https://w3c.github.io/webrtc-pc/#dom-rtcpeerconnection-signalingstatechange
     event_handler!(
         signalingstatechange,
@@ -492,19 +704,46 @@ impl RTCPeerConnectionMethods for RTCPeerConnection {
 
     /// https://w3c.github.io/webrtc-pc/#dom-rtcpeerconnection-localdescription
     fn GetLocalDescription(&self) -> Option<DomRoot<RTCSessionDescription>> {
-        self.local_description.get()
+        self.pending_local_description
+            .get()
+            .or_else(|| self.current_local_description.get())
+    }
+
+    /// https://w3c.github.io/webrtc-pc/#dom-peerconnection-currentlocaldesc
+    fn GetCurrentLocalDescription(&self) -> Option<DomRoot<RTCSessionDescription>> {
+        self.current_local_description.get()
+    }
+
+    /// https://w3c.github.io/webrtc-pc/#dom-peerconnection-pendinglocaldesc
+    fn GetPendingLocalDescription(&self) -> Option<DomRoot<RTCSessionDescription>> {
+        self.pending_local_description.get()
     }
 
     /// https://w3c.github.io/webrtc-pc/#dom-rtcpeerconnection-remotedescription
     fn GetRemoteDescription(&self) -> Option<DomRoot<RTCSessionDescription>> {
-        self.remote_description.get()
+        self.pending_remote_description
+            .get()
+            .or_else(|| self.current_remote_description.get())
+    }
+
+    /// https://w3c.github.io/webrtc-pc/#dom-peerconnection-currentremotedesc
+    fn GetCurrentRemoteDescription(&self) -> Option<DomRoot<RTCSessionDescription>> {
+        self.current_remote_description.get()
+    }
+
+    /// https://w3c.github.io/webrtc-pc/#dom-peerconnection-pendingremotedesc
+    fn GetPendingRemoteDescription(&self) -> Option<DomRoot<RTCSessionDescription>> {
+        self.pending_remote_description.get()
     }
 
     /// https://w3c.github.io/webrtc-pc/#dom-rtcpeerconnection-setlocaldescription
     #[allow(unsafe_code)]
     fn SetLocalDescription(&self, desc: &RTCSessionDescriptionInit) -> Rc<Promise> {
-        // XXXManishearth validate the current state
         let p = unsafe { Promise::new_in_current_compartment(&self.global()) };
+        if let Err(error) = self.check_description(desc, false) {
+            p.reject_error(error);
+            return p;
+        }
         let this = Trusted::new(self);
         let desc: SessionDescription = desc.into();
         let trusted_promise = TrustedPromise::new(p.clone());
@@ -517,28 +756,31 @@ impl RTCPeerConnectionMethods for RTCPeerConnection {
             .borrow_mut()
             .as_ref()
             .unwrap()
-            .set_local_description(desc.clone(), (move || {
+            .set_local_description(
+                desc.clone(),
+                (move || {
                     let _ = task_source.queue_with_canceller(
                         task!(local_description_set: move || {
-                            // XXXManishearth spec actually asks for an intricate
-                            // dance between pending/current local/remote descriptions
                             let this = this.root();
-                            let desc = desc.into();
-                            let desc = RTCSessionDescription::Constructor(&this.global().as_window(), &desc).unwrap();
-                            this.local_description.set(Some(&desc));
+                            this.description_set(&desc.into(), false);
                             trusted_promise.root().resolve_native(&())
                         }),
                         &canceller,
                     );
-            }).into());
+                })
+                .into(),
+            );
         p
     }
 
     /// https://w3c.github.io/webrtc-pc/#dom-rtcpeerconnection-setremotedescription
     #[allow(unsafe_code)]
     fn SetRemoteDescription(&self, desc: &RTCSessionDescriptionInit) -> Rc<Promise> {
-        // XXXManishearth validate the current state
         let p = unsafe { Promise::new_in_current_compartment(&self.global()) };
+        if let Err(error) = self.check_description(desc, true) {
+            p.reject_error(error);
+            return p;
+        }
         let this = Trusted::new(self);
         let desc: SessionDescription = desc.into();
         let trusted_promise = TrustedPromise::new(p.clone());
@@ -551,23 +793,78 @@ impl RTCPeerConnectionMethods for RTCPeerConnection {
             .borrow_mut()
             .as_ref()
             .unwrap()
-            .set_remote_description(desc.clone(), (move || {
+            .set_remote_description(
+                desc.clone(),
+                (move || {
                     let _ = task_source.queue_with_canceller(
                         task!(remote_description_set: move || {
-                            // XXXManishearth spec actually asks for an intricate
-                            // dance between pending/current local/remote descriptions
                             let this = this.root();
-                            let desc = desc.into();
-                            let desc = RTCSessionDescription::Constructor(&this.global().as_window(), &desc).unwrap();
-                            this.remote_description.set(Some(&desc));
+                            this.description_set(&desc.into(), true);
                             trusted_promise.root().resolve_native(&())
                         }),
                         &canceller,
                     );
-            }).into());
+                })
+                .into(),
+            );
         p
     }
 
+    /// https://w3c.github.io/webrtc-pc/#dom-peerconnection-createdatachannel
+    fn CreateDataChannel(
+        &self,
+        label: USVString,
+        options: &RTCDataChannelInit,
+    ) -> Fallible<DomRoot<RTCDataChannel>> {
+        // Step 2.
+        if self.closed.get() {
+            return Err(Error::InvalidState);
+        }
+
+        if label.0.len() > MAX_DATA_CHANNEL_STRING_LENGTH {
+            return Err(Error::Type("The label is too long".to_owned()));
+        }
+        if options.protocol.0.len() > MAX_DATA_CHANNEL_STRING_LENGTH {
+            return Err(Error::Type("The protocol is too long".to_owned()));
+        }
+
+        if options.maxPacketLifeTime.is_some() && options.maxRetransmits.is_some() {
+            return Err(Error::Type(
+                "maxPacketLifeTime and maxRetransmits are both set".to_owned(),
+            ));
+        }
+
+        if options.negotiated && options.id.is_none() {
+            return Err(Error::Type("A negotiated channel needs an id".to_owned()));
+        }
+        if options.id == Some(u16::max_value()) {
+            return Err(Error::Type("65535 is not a valid id".to_owned()));
+        }
+
+        let mut init: DataChannelInit = options.into();
+        init.label = label.0.clone();
+        let id = match self
+            .controller
+            .borrow()
+            .as_ref()
+            .unwrap()
+            .create_data_channel(init)
+        {
+            Ok(id) => id,
+            Err(error) => {
+                warn!("Could not create the data channel ({:?})", error);
+                return Err(Error::Operation);
+            },
+        };
+        Ok(RTCDataChannel::new(
+            &self.global(),
+            self,
+            id,
+            label,
+            options,
+        ))
+    }
+
     // https://w3c.github.io/webrtc-pc/#legacy-interface-extensions
     fn AddStream(&self, stream: &MediaStream) {
         let mut tracks = stream.get_tracks();
@@ -607,8 +904,13 @@ impl RTCPeerConnectionMethods for RTCPeerConnection {
         // Step 5 handled by backend
         self.controller.borrow_mut().as_ref().unwrap().quit();
 
-        // Step 6-10
-        // (no current support for data channels, transports, etc)
+        // Step 7
+        for (_, channel) in self.data_channels.borrow_mut().drain() {
+            channel.close_silently();
+        }
+
+        // Step 6, 8-10
+        // (no current support for transceivers and transports)
 
         // Step 11
         self.ice_connection_state.set(RTCIceConnectionState::Closed);
@@ -98,7 +98,7 @@ impl ServiceWorkerMethods for ServiceWorker {
             return Err(Error::InvalidState);
         }
         // Step 7
-        let data = StructuredCloneData::write(cx, message)?;
+        let data = StructuredCloneData::write(cx, message, None)?;
         let msg_vec = DOMMessage(data.move_to_arraybuffer());
         let _ = self
             .global()
@@ -16,7 +16,9 @@ use crate::dom::event::Event;
 use crate::dom::eventtarget::EventTarget;
 use crate::dom::extendableevent::ExtendableEvent;
 use crate::dom::extendablemessageevent::ExtendableMessageEvent;
+use crate::dom::fetchevent::FetchEvent;
 use crate::dom::globalscope::GlobalScope;
+use crate::dom::request::Request;
 use crate::dom::worker::TrustedWorkerAddress;
 use crate::dom::workerglobalscope::WorkerGlobalScope;
 use crate::script_runtime::{new_rt_and_cx, CommonScriptMsg, Runtime, ScriptChan};
@@ -30,7 +32,7 @@ use ipc_channel::router::ROUTER;
 use js::jsapi::{JSAutoCompartment, JSContext, JS_AddInterruptCallback};
 use js::jsval::UndefinedValue;
 use msg::constellation_msg::PipelineId;
-use net_traits::request::{CredentialsMode, Destination, RequestInit};
+use net_traits::request::{CredentialsMode, Destination, Request as NetTraitsRequest, RequestInit};
 use net_traits::{load_whole_resource, CustomResponseMediator, IpcSend};
 use script_traits::{
     ScopeThings, ServiceWorkerMsg, TimerEvent, WorkerGlobalScopeInit, WorkerScriptLoadOrigin,
@@ -263,11 +265,7 @@ impl ServiceWorkerGlobalScope {
         } = scope_things;
 
         let serialized_worker_url = script_url.to_string();
-        let origin = GlobalScope::current()
-            .expect("No current global object")
-            .origin()
-            .immutable()
-            .clone();
+        let origin = script_url.origin();
         thread::Builder::new()
             .name(format!("ServiceWorker for {}", serialized_worker_url))
             .spawn(move || {
@@ -413,11 +411,22 @@ impl ServiceWorkerGlobalScope {
                 self.upcast::<WorkerGlobalScope>().process_event(msg);
             },
             Response(mediator) => {
-                // TODO XXXcreativcoder This will eventually use a FetchEvent interface to fire event
-                // when we have the Request and Response dom api's implemented
-                // https://w3c.github.io/ServiceWorker/#fetchevent-interface
-                self.upcast::<EventTarget>().fire_event(atom!("fetch"));
-                let _ = mediator.response_chan.send(None);
+                // https://w3c.github.io/ServiceWorker/#handle-fetch
+                // Steps 24.3.2-24.3.3.
+                let CustomResponseMediator {
+                    response_chan,
+                    load_url,
+                    method,
+                    headers,
+                    destination,
+                    ..
+                } = mediator;
+                let mut request = NetTraitsRequest::new(load_url, None, None);
+                request.method = method;
+                request.headers = headers;
+                request.destination = destination;
+                let request = Request::new_immutable(self.upcast(), request);
+                FetchEvent::dispatch(self, &request, response_chan);
             },
             WakeUp => {},
         }
@@ -452,4 +461,13 @@ impl ServiceWorkerGlobalScopeMethods for ServiceWorkerGlobalScope {
 
     // https://w3c.github.io/ServiceWorker/#dom-serviceworkerglobalscope-onmessageerror
     event_handler!(messageerror, GetOnmessageerror, SetOnmessageerror);
+
+    // https://w3c.github.io/ServiceWorker/#dom-serviceworkerglobalscope-oninstall
+    event_handler!(install, GetOninstall, SetOninstall);
+
+    // https://w3c.github.io/ServiceWorker/#dom-serviceworkerglobalscope-onactivate
+    event_handler!(activate, GetOnactivate, SetOnactivate);
+
+    // https://w3c.github.io/ServiceWorker/#dom-serviceworkerglobalscope-onfetch
+    event_handler!(fetch, GetOnfetch, SetOnfetch);
 }
@@ -3,23 +3,31 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
 use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::NotificationBinding::{
+    NotificationOptions, NotificationPermission,
+};
 use crate::dom::bindings::codegen::Bindings::ServiceWorkerBinding::ServiceWorkerState;
 use crate::dom::bindings::codegen::Bindings::ServiceWorkerRegistrationBinding::ServiceWorkerUpdateViaCache;
 use crate::dom::bindings::codegen::Bindings::ServiceWorkerRegistrationBinding::{
     ServiceWorkerRegistrationMethods, Wrap,
 };
+use crate::dom::bindings::error::Error;
 use crate::dom::bindings::reflector::{reflect_dom_object, DomObject};
 use crate::dom::bindings::root::{Dom, DomRoot, MutNullableDom};
-use crate::dom::bindings::str::{ByteString, USVString};
+use crate::dom::bindings::str::{ByteString, DOMString, USVString};
 use crate::dom::eventtarget::EventTarget;
 use crate::dom::globalscope::GlobalScope;
 use crate::dom::navigationpreloadmanager::NavigationPreloadManager;
+use crate::dom::notification::{create_notification, notification_permission, show_notification};
+use crate::dom::promise::Promise;
 use crate::dom::serviceworker::ServiceWorker;
 use crate::dom::workerglobalscope::prepare_workerscope_init;
 use dom_struct::dom_struct;
+use embedder_traits::NotificationId;
 use script_traits::{ScopeThings, WorkerScriptLoadOrigin};
 use servo_url::ServoUrl;
 use std::cell::Cell;
+use std::rc::Rc;
 
 #[dom_struct]
 pub struct ServiceWorkerRegistration {
@@ -183,4 +191,43 @@ impl ServiceWorkerRegistrationMethods for ServiceWorkerRegistration {
         self.navigation_preload
             .or_init(|| NavigationPreloadManager::new(&self.global(), &self))
     }
+
+    #[allow(unsafe_code)]
+    // https://notifications.spec.whatwg.org/#dom-serviceworkerregistration-shownotification
+    fn ShowNotification(&self, title: DOMString, options: &NotificationOptions) -> Rc<Promise> {
+        // Step 1.
+        let global = self.global();
+        let promise = unsafe { Promise::new_in_current_compartment(&global) };
+
+        // Step 2.
+        if self.active.is_none() {
+            promise.reject_error(Error::Type(
+                "The registration has no active worker".to_owned(),
+            ));
+            return promise;
+        }
+
+        // Step 3 - 4.
+        let notification = match create_notification(&global, title, options) {
+            Ok(notification) => notification,
+            Err(error) => {
+                promise.reject_error(error);
+                return promise;
+            },
+        };
+
+        // Step 5 - 6.
+        if notification_permission(&global) != NotificationPermission::Granted {
+            promise.reject_error(Error::Type("The notifications aren't allowed".to_owned()));
+            return promise;
+        }
+
+        // TODO: Fire notificationclick and notificationclose at the service
+        // worker, which needs a way to reach it from here.
+        show_notification(&global, NotificationId::new(), notification, None);
+
+        // Step 7.
+        promise.resolve_native(&());
+        promise
+    }
 }
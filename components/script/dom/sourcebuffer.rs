@@ -0,0 +1,421 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::HTMLMediaElementBinding::HTMLMediaElementMethods;
+use crate::dom::bindings::codegen::Bindings::MediaSourceBinding::{
+    EndOfStreamError, MediaSourceMethods, ReadyState,
+};
+use crate::dom::bindings::codegen::Bindings::SourceBufferBinding::{
+    self, AppendMode, SourceBufferMethods,
+};
+use crate::dom::bindings::codegen::UnionTypes::ArrayBufferViewOrArrayBuffer;
+use crate::dom::bindings::error::{Error, ErrorResult, Fallible};
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::num::Finite;
+use crate::dom::bindings::refcounted::Trusted;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject};
+use crate::dom::bindings::root::{DomRoot, MutNullableDom};
+use crate::dom::eventtarget::EventTarget;
+use crate::dom::mediasource::MediaSource;
+use crate::dom::timeranges::{TimeRanges, TimeRangesContainer};
+use crate::dom::window::Window;
+use crate::task_source::TaskSource;
+use dom_struct::dom_struct;
+use servo_atoms::Atom;
+use std::cell::Cell;
+use std::f64;
+use std::mem;
+
+/// The algorithm that set the `updating` flag of a source buffer.
+#[derive(Clone, Copy, JSTraceable, MallocSizeOf, PartialEq)]
+enum Update {
+    /// <https://w3c.github.io/media-source/#sourcebuffer-buffer-append>
+    Append,
+    /// <https://w3c.github.io/media-source/#sourcebuffer-range-removal>
+    RangeRemoval,
+}
+
+/// A buffer of media segments that get appended to the media element's
+/// player, which demuxes them itself. This is why a media source only takes
+/// a single source buffer for now.
+#[dom_struct]
+pub struct SourceBuffer {
+    eventtarget: EventTarget,
+    /// The media source this buffer belongs to, until it gets removed from
+    /// its `sourceBuffers`.
+    parent_source: MutNullableDom<MediaSource>,
+    /// <https://w3c.github.io/media-source/#dom-sourcebuffer-mode>
+    mode: Cell<AppendMode>,
+    /// <https://w3c.github.io/media-source/#dom-sourcebuffer-updating>
+    update: Cell<Option<Update>>,
+    /// <https://w3c.github.io/media-source/#dom-sourcebuffer-timestampoffset>
+    timestamp_offset: Cell<f64>,
+    /// <https://w3c.github.io/media-source/#dom-sourcebuffer-appendwindowstart>
+    append_window_start: Cell<f64>,
+    /// <https://w3c.github.io/media-source/#dom-sourcebuffer-appendwindowend>
+    append_window_end: Cell<f64>,
+    /// <https://w3c.github.io/media-source/#sourcebuffer-generate-timestamps-flag>
+    generate_timestamps: bool,
+    /// <https://w3c.github.io/media-source/#sourcebuffer-input-buffer>
+    input_buffer: DomRefCell<Vec<u8>>,
+}
+
+impl SourceBuffer {
+    fn new_inherited(parent_source: &MediaSource, generate_timestamps: bool) -> SourceBuffer {
+        SourceBuffer {
+            eventtarget: EventTarget::new_inherited(),
+            parent_source: MutNullableDom::new(Some(parent_source)),
+            mode: Cell::new(if generate_timestamps {
+                AppendMode::Sequence
+            } else {
+                AppendMode::Segments
+            }),
+            update: Cell::new(None),
+            timestamp_offset: Cell::new(0.),
+            append_window_start: Cell::new(0.),
+            append_window_end: Cell::new(f64::INFINITY),
+            generate_timestamps,
+            input_buffer: DomRefCell::new(vec![]),
+        }
+    }
+
+    pub fn new(
+        window: &Window,
+        parent_source: &MediaSource,
+        generate_timestamps: bool,
+    ) -> DomRoot<SourceBuffer> {
+        reflect_dom_object(
+            Box::new(SourceBuffer::new_inherited(
+                parent_source,
+                generate_timestamps,
+            )),
+            window,
+            SourceBufferBinding::Wrap,
+        )
+    }
+
+    pub fn is_updating(&self) -> bool {
+        self.update.get().is_some()
+    }
+
+    /// Called when this buffer gets removed from its media source.
+    ///
+    /// <https://w3c.github.io/media-source/#dom-mediasource-removesourcebuffer>
+    pub fn removed_from_parent(&self) {
+        // Step 3.
+        if self.update.get() == Some(Update::Append) {
+            self.abort_buffer_append();
+        }
+        self.parent_source.set(None);
+    }
+
+    /// The media source of this buffer, or an `InvalidStateError` if it was
+    /// removed from it.
+    fn parent_source(&self) -> Fallible<DomRoot<MediaSource>> {
+        self.parent_source.get().ok_or(Error::InvalidState)
+    }
+
+    /// Sets the `updating` flag and queues an `updatestart` event, then
+    /// queues a task to carry out `update` in the background.
+    fn start_update(&self, update: Update) {
+        self.update.set(Some(update));
+        self.queue_event(atom!("updatestart"));
+
+        let global = self.global();
+        let window = global.as_window();
+        let this = Trusted::new(self);
+        let _ = window.task_manager().media_element_task_source().queue(
+            task!(source_buffer_update: move || {
+                let this = this.root();
+                if this.update.get() != Some(update) {
+                    return;
+                }
+                match update {
+                    Update::Append => this.buffer_append(),
+                    Update::RangeRemoval => this.finish_update(),
+                }
+            }),
+            window.upcast(),
+        );
+    }
+
+    /// <https://w3c.github.io/media-source/#sourcebuffer-buffer-append>
+    fn buffer_append(&self) {
+        // Step 1.
+        // The player parses the segments itself.
+        let data = mem::replace(&mut *self.input_buffer.borrow_mut(), vec![]);
+        let media_element = self
+            .parent_source
+            .get()
+            .and_then(|parent_source| parent_source.media_element());
+        if let Some(media_element) = media_element {
+            // Step 2.
+            if media_element.push_media_source_data(data).is_err() {
+                return self.append_error();
+            }
+        }
+
+        // Steps 3-5.
+        self.finish_update();
+    }
+
+    /// Clears the `updating` flag, queuing an `update` and an `updateend`
+    /// event.
+    fn finish_update(&self) {
+        self.update.set(None);
+        self.queue_event(atom!("update"));
+        self.queue_event(atom!("updateend"));
+    }
+
+    /// <https://w3c.github.io/media-source/#sourcebuffer-append-error>
+    fn append_error(&self) {
+        // Step 1.
+        self.input_buffer.borrow_mut().clear();
+        // Step 2.
+        self.update.set(None);
+        // Steps 3-4.
+        self.queue_event(atom!("error"));
+        self.queue_event(atom!("updateend"));
+        // Step 5.
+        if let Some(parent_source) = self.parent_source.get() {
+            parent_source.end_of_stream(Some(EndOfStreamError::Decode));
+        }
+    }
+
+    /// Aborts the running buffer append, steps 4.1-4.4 of `abort()`.
+    ///
+    /// <https://w3c.github.io/media-source/#dom-sourcebuffer-abort>
+    fn abort_buffer_append(&self) {
+        self.input_buffer.borrow_mut().clear();
+        self.update.set(None);
+        self.queue_event(atom!("abort"));
+        self.queue_event(atom!("updateend"));
+    }
+
+    fn queue_event(&self, name: Atom) {
+        let global = self.global();
+        let window = global.as_window();
+        window
+            .task_manager()
+            .media_element_task_source()
+            .queue_simple_event(self.upcast(), name, window);
+    }
+}
+
+impl SourceBufferMethods for SourceBuffer {
+    // https://w3c.github.io/media-source/#dom-sourcebuffer-mode
+    fn Mode(&self) -> AppendMode {
+        self.mode.get()
+    }
+
+    // https://w3c.github.io/media-source/#dom-sourcebuffer-mode
+    fn SetMode(&self, mode: AppendMode) -> ErrorResult {
+        // Step 1.
+        let parent_source = self.parent_source()?;
+        // Step 2.
+        if self.is_updating() {
+            return Err(Error::InvalidState);
+        }
+        // Step 3.
+        if self.generate_timestamps && mode == AppendMode::Segments {
+            return Err(Error::Type(
+                "Segments mode can't be used with this byte stream format".to_owned(),
+            ));
+        }
+        // Step 4.
+        if parent_source.ReadyState() == ReadyState::Ended {
+            parent_source.reopen();
+        }
+        // Steps 5-7.
+        self.mode.set(mode);
+        Ok(())
+    }
+
+    // https://w3c.github.io/media-source/#dom-sourcebuffer-updating
+    fn Updating(&self) -> bool {
+        self.is_updating()
+    }
+
+    // https://w3c.github.io/media-source/#dom-sourcebuffer-buffered
+    fn GetBuffered(&self) -> Fallible<DomRoot<TimeRanges>> {
+        // Step 1.
+        let parent_source = self.parent_source()?;
+        // Steps 2-4.
+        let buffered = parent_source
+            .media_element()
+            .map_or_else(TimeRangesContainer::new, |media_element| {
+                media_element.buffered_ranges()
+            });
+        Ok(TimeRanges::new(self.global().as_window(), buffered))
+    }
+
+    // https://w3c.github.io/media-source/#dom-sourcebuffer-timestampoffset
+    fn TimestampOffset(&self) -> Finite<f64> {
+        Finite::wrap(self.timestamp_offset.get())
+    }
+
+    // https://w3c.github.io/media-source/#dom-sourcebuffer-timestampoffset
+    fn SetTimestampOffset(&self, offset: Finite<f64>) -> ErrorResult {
+        // Steps 1-2.
+        let parent_source = self.parent_source()?;
+        if self.is_updating() {
+            return Err(Error::InvalidState);
+        }
+        // Step 3.
+        if parent_source.ReadyState() == ReadyState::Ended {
+            parent_source.reopen();
+        }
+        // Steps 4-6.
+        self.timestamp_offset.set(*offset);
+        Ok(())
+    }
+
+    // https://w3c.github.io/media-source/#dom-sourcebuffer-appendwindowstart
+    fn AppendWindowStart(&self) -> Finite<f64> {
+        Finite::wrap(self.append_window_start.get())
+    }
+
+    // https://w3c.github.io/media-source/#dom-sourcebuffer-appendwindowstart
+    fn SetAppendWindowStart(&self, start: Finite<f64>) -> ErrorResult {
+        // Step 1.
+        self.parent_source()?;
+        // Step 2.
+        if self.is_updating() {
+            return Err(Error::InvalidState);
+        }
+        // Step 3.
+        if *start < 0. || *start >= self.append_window_end.get() {
+            return Err(Error::Type(
+                "The append window start is out of range".to_owned(),
+            ));
+        }
+        // Step 4.
+        self.append_window_start.set(*start);
+        Ok(())
+    }
+
+    // https://w3c.github.io/media-source/#dom-sourcebuffer-appendwindowend
+    fn AppendWindowEnd(&self) -> f64 {
+        self.append_window_end.get()
+    }
+
+    // https://w3c.github.io/media-source/#dom-sourcebuffer-appendwindowend
+    fn SetAppendWindowEnd(&self, end: f64) -> ErrorResult {
+        // Step 1.
+        self.parent_source()?;
+        // Step 2.
+        if self.is_updating() {
+            return Err(Error::InvalidState);
+        }
+        // Steps 3-4.
+        if end.is_nan() || end <= self.append_window_start.get() {
+            return Err(Error::Type(
+                "The append window end is out of range".to_owned(),
+            ));
+        }
+        // Step 5.
+        self.append_window_end.set(end);
+        Ok(())
+    }
+
+    // https://w3c.github.io/media-source/#dom-sourcebuffer-onupdatestart
+    event_handler!(updatestart, GetOnupdatestart, SetOnupdatestart);
+
+    // https://w3c.github.io/media-source/#dom-sourcebuffer-onupdate
+    event_handler!(update, GetOnupdate, SetOnupdate);
+
+    // https://w3c.github.io/media-source/#dom-sourcebuffer-onupdateend
+    event_handler!(updateend, GetOnupdateend, SetOnupdateend);
+
+    // https://w3c.github.io/media-source/#dom-sourcebuffer-onerror
+    event_handler!(error, GetOnerror, SetOnerror);
+
+    // https://w3c.github.io/media-source/#dom-sourcebuffer-onabort
+    event_handler!(abort, GetOnabort, SetOnabort);
+
+    // https://w3c.github.io/media-source/#dom-sourcebuffer-appendbuffer
+    fn AppendBuffer(&self, data: ArrayBufferViewOrArrayBuffer) -> ErrorResult {
+        // Step 1.
+        // https://w3c.github.io/media-source/#sourcebuffer-prepare-append
+        let parent_source = self.parent_source()?;
+        if self.is_updating() {
+            return Err(Error::InvalidState);
+        }
+        let media_element = parent_source.media_element();
+        if media_element.map_or(false, |media_element| media_element.GetError().is_some()) {
+            return Err(Error::InvalidState);
+        }
+        if parent_source.ReadyState() == ReadyState::Ended {
+            parent_source.reopen();
+        }
+
+        // Step 2.
+        let data = match data {
+            ArrayBufferViewOrArrayBuffer::ArrayBufferView(ref view) => view.to_vec(),
+            ArrayBufferViewOrArrayBuffer::ArrayBuffer(ref buffer) => buffer.to_vec(),
+        };
+        self.input_buffer.borrow_mut().extend_from_slice(&data);
+
+        // Steps 3-5.
+        self.start_update(Update::Append);
+        Ok(())
+    }
+
+    // https://w3c.github.io/media-source/#dom-sourcebuffer-abort
+    fn Abort(&self) -> ErrorResult {
+        // Step 1.
+        let parent_source = self.parent_source()?;
+        // Step 2.
+        if parent_source.ReadyState() != ReadyState::Open {
+            return Err(Error::InvalidState);
+        }
+        // Step 3.
+        if self.update.get() == Some(Update::RangeRemoval) {
+            return Err(Error::InvalidState);
+        }
+        // Step 4.
+        if self.is_updating() {
+            self.abort_buffer_append();
+        }
+        // Step 5.
+        self.input_buffer.borrow_mut().clear();
+        // Steps 6-7.
+        self.append_window_start.set(0.);
+        self.append_window_end.set(f64::INFINITY);
+        Ok(())
+    }
+
+    // https://w3c.github.io/media-source/#dom-sourcebuffer-remove
+    fn Remove(&self, start: Finite<f64>, end: f64) -> ErrorResult {
+        // Steps 1-2.
+        let parent_source = self.parent_source()?;
+        if self.is_updating() {
+            return Err(Error::InvalidState);
+        }
+        // Step 3.
+        let duration = parent_source.Duration();
+        if duration.is_nan() {
+            return Err(Error::Type("The media source has no duration".to_owned()));
+        }
+        // Step 4.
+        if *start < 0. || *start > duration {
+            return Err(Error::Type("The removal start is out of range".to_owned()));
+        }
+        // Step 5.
+        if end.is_nan() || end <= *start {
+            return Err(Error::Type("The removal end is out of range".to_owned()));
+        }
+        // Step 6.
+        if parent_source.ReadyState() == ReadyState::Ended {
+            parent_source.reopen();
+        }
+        // Step 7.
+        // https://w3c.github.io/media-source/#sourcebuffer-range-removal
+        // FIXME: The player can't evict the coded frames it already got, so
+        // this only goes through the motions.
+        self.start_update(Update::RangeRemoval);
+        Ok(())
+    }
+}
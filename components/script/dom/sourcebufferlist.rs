@@ -0,0 +1,109 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::SourceBufferListBinding::{
+    self, SourceBufferListMethods,
+};
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject};
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::eventtarget::EventTarget;
+use crate::dom::sourcebuffer::SourceBuffer;
+use crate::dom::window::Window;
+use dom_struct::dom_struct;
+use servo_atoms::Atom;
+
+#[dom_struct]
+pub struct SourceBufferList {
+    eventtarget: EventTarget,
+    source_buffers: DomRefCell<Vec<Dom<SourceBuffer>>>,
+}
+
+impl SourceBufferList {
+    fn new_inherited() -> SourceBufferList {
+        SourceBufferList {
+            eventtarget: EventTarget::new_inherited(),
+            source_buffers: DomRefCell::new(vec![]),
+        }
+    }
+
+    pub fn new(window: &Window) -> DomRoot<SourceBufferList> {
+        reflect_dom_object(
+            Box::new(SourceBufferList::new_inherited()),
+            window,
+            SourceBufferListBinding::Wrap,
+        )
+    }
+
+    pub fn source_buffers(&self) -> Vec<DomRoot<SourceBuffer>> {
+        self.source_buffers
+            .borrow()
+            .iter()
+            .map(|source_buffer| DomRoot::from_ref(&**source_buffer))
+            .collect()
+    }
+
+    pub fn contains(&self, source_buffer: &SourceBuffer) -> bool {
+        self.source_buffers
+            .borrow()
+            .iter()
+            .any(|entry| &**entry == source_buffer)
+    }
+
+    /// Appends `source_buffer` to the list, queuing an `addsourcebuffer`
+    /// event.
+    pub fn add(&self, source_buffer: &SourceBuffer) {
+        self.source_buffers
+            .borrow_mut()
+            .push(Dom::from_ref(source_buffer));
+        self.queue_event(atom!("addsourcebuffer"));
+    }
+
+    /// Removes `source_buffer` from the list if it is in there, queuing a
+    /// `removesourcebuffer` event.
+    pub fn remove(&self, source_buffer: &SourceBuffer) {
+        if !self.contains(source_buffer) {
+            return;
+        }
+        self.source_buffers
+            .borrow_mut()
+            .retain(|entry| &**entry != source_buffer);
+        self.queue_event(atom!("removesourcebuffer"));
+    }
+
+    fn queue_event(&self, name: Atom) {
+        let global = self.global();
+        let window = global.as_window();
+        window
+            .task_manager()
+            .media_element_task_source()
+            .queue_simple_event(self.upcast(), name, window);
+    }
+}
+
+impl SourceBufferListMethods for SourceBufferList {
+    // https://w3c.github.io/media-source/#dom-sourcebufferlist-length
+    fn Length(&self) -> u32 {
+        self.source_buffers.borrow().len() as u32
+    }
+
+    // https://w3c.github.io/media-source/#dfn-sourcebufferlist-getter
+    fn IndexedGetter(&self, index: u32) -> Option<DomRoot<SourceBuffer>> {
+        self.source_buffers
+            .borrow()
+            .get(index as usize)
+            .map(|source_buffer| DomRoot::from_ref(&**source_buffer))
+    }
+
+    // https://w3c.github.io/media-source/#dom-sourcebufferlist-onaddsourcebuffer
+    event_handler!(addsourcebuffer, GetOnaddsourcebuffer, SetOnaddsourcebuffer);
+
+    // https://w3c.github.io/media-source/#dom-sourcebufferlist-onremovesourcebuffer
+    event_handler!(
+        removesourcebuffer,
+        GetOnremovesourcebuffer,
+        SetOnremovesourcebuffer
+    );
+}
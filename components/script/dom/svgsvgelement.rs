@@ -3,18 +3,28 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
 use crate::dom::attr::Attr;
+use crate::dom::bindings::cell::DomRefCell;
 use crate::dom::bindings::codegen::Bindings::SVGSVGElementBinding;
 use crate::dom::bindings::inheritance::Castable;
 use crate::dom::bindings::root::{DomRoot, LayoutDom};
 use crate::dom::bindings::str::DOMString;
 use crate::dom::document::Document;
 use crate::dom::element::{AttributeMutation, Element, RawLayoutElementHelpers};
-use crate::dom::node::Node;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::node::{document_from_node, window_from_node, Node, NodeDamage};
 use crate::dom::svggraphicselement::SVGGraphicsElement;
 use crate::dom::virtualmethods::VirtualMethods;
+use crate::svg::SvgPainter;
+use canvas_traits::canvas::{Canvas2dMsg, CanvasId, CanvasMsg};
+use cssparser::RGBA;
 use dom_struct::dom_struct;
+use euclid::{Point2D, Rect, Size2D, Transform2D};
 use html5ever::{LocalName, Prefix};
+use ipc_channel::ipc::IpcSender;
+use profile_traits::ipc as profiled_ipc;
 use script_layout_interface::SVGSVGData;
+use script_traits::ScriptMsg;
+use std::cell::Cell;
 use style::attr::AttrValue;
 
 const DEFAULT_WIDTH: u32 = 300;
@@ -23,6 +33,12 @@ const DEFAULT_HEIGHT: u32 = 150;
 #[dom_struct]
 pub struct SVGSVGElement {
     svggraphicselement: SVGGraphicsElement,
+    /// The canvas paint thread the subtree of the element is painted with,
+    /// once it was painted.
+    #[ignore_malloc_size_of = "Defined in ipc-channel"]
+    canvas: DomRefCell<Option<(IpcSender<CanvasMsg>, CanvasId)>>,
+    /// The size of the bitmap of the canvas, in device pixels.
+    canvas_size: Cell<Size2D<u32>>,
 }
 
 impl SVGSVGElement {
@@ -33,6 +49,8 @@ impl SVGSVGElement {
     ) -> SVGSVGElement {
         SVGSVGElement {
             svggraphicselement: SVGGraphicsElement::new_inherited(local_name, prefix, document),
+            canvas: DomRefCell::new(None),
+            canvas_size: Cell::new(Size2D::zero()),
         }
     }
 
@@ -48,6 +66,86 @@ impl SVGSVGElement {
             SVGSVGElementBinding::Wrap,
         )
     }
+
+    /// Queues the outermost `svg` element `node` is an inclusive descendant
+    /// of, if any, to be painted again before the next reflow.
+    pub fn invalidate_outermost(node: &Node) {
+        let svg = node
+            .inclusive_ancestors()
+            .filter_map(DomRoot::downcast::<SVGSVGElement>)
+            .last();
+        if let Some(svg) = svg {
+            svg.upcast::<Node>().dirty(NodeDamage::OtherNodeDamage);
+            document_from_node(&*svg).invalidate_svg(&svg);
+        }
+    }
+
+    /// The size of the element in CSS pixels, from its `width` and `height`
+    /// attributes.
+    fn size(&self) -> Size2D<u32> {
+        let element = self.upcast::<Element>();
+        Size2D::new(
+            element
+                .get_attribute(&ns!(), &local_name!("width"))
+                .map_or(DEFAULT_WIDTH, |attr| attr.value().as_uint()),
+            element
+                .get_attribute(&ns!(), &local_name!("height"))
+                .map_or(DEFAULT_HEIGHT, |attr| attr.value().as_uint()),
+        )
+    }
+
+    /// Paints the subtree of the element with its canvas paint thread,
+    /// creating it first if needed.
+    pub fn paint(&self) {
+        let window = window_from_node(self);
+        let scale = window.device_pixel_ratio().get();
+        let size = self.size();
+        let canvas_size = Size2D::new(
+            (size.width as f32 * scale).ceil() as u32,
+            (size.height as f32 * scale).ceil() as u32,
+        );
+        if canvas_size.width == 0 || canvas_size.height == 0 {
+            return;
+        }
+
+        let mut canvas = self.canvas.borrow_mut();
+        match *canvas {
+            None => {
+                let global = window.upcast::<GlobalScope>();
+                let (sender, receiver) =
+                    profiled_ipc::channel(global.time_profiler_chan().clone()).unwrap();
+                global
+                    .script_to_constellation_chan()
+                    .send(ScriptMsg::CreateCanvasPaintThread(canvas_size, sender))
+                    .unwrap();
+                *canvas = Some(receiver.recv().unwrap());
+            },
+            Some((ref ipc_renderer, canvas_id)) => {
+                if canvas_size != self.canvas_size.get() {
+                    ipc_renderer
+                        .send(CanvasMsg::Recreate(canvas_size, canvas_id))
+                        .unwrap();
+                }
+            },
+        }
+        self.canvas_size.set(canvas_size);
+
+        let current_color = self
+            .upcast::<Element>()
+            .style()
+            .map_or(RGBA::new(0, 0, 0, 255), |style| style.get_color().color);
+        let clear = vec![
+            Canvas2dMsg::SetTransform(Transform2D::identity()),
+            Canvas2dMsg::ClearRect(Rect::new(Point2D::zero(), canvas_size.to_f32())),
+        ];
+        let commands = SvgPainter::paint(self.upcast(), size.to_f32(), scale, current_color);
+        let (ref ipc_renderer, canvas_id) = *canvas.as_ref().unwrap();
+        for command in clear.into_iter().chain(commands) {
+            ipc_renderer
+                .send(CanvasMsg::Canvas2d(command, canvas_id))
+                .unwrap();
+        }
+    }
 }
 
 pub trait LayoutSVGSVGElementHelpers {
@@ -69,6 +167,7 @@ impl LayoutSVGSVGElementHelpers for LayoutDom<SVGSVGElement> {
             SVGSVGData {
                 width: width_attr.map_or(DEFAULT_WIDTH, |val| val.as_uint()),
                 height: height_attr.map_or(DEFAULT_HEIGHT, |val| val.as_uint()),
+                canvas: SVG.canvas.borrow_for_layout().clone(),
             }
         }
     }
@@ -83,6 +182,16 @@ impl VirtualMethods for SVGSVGElement {
         self.super_type().unwrap().attribute_mutated(attr, mutation);
     }
 
+    fn bind_to_tree(&self, tree_in_doc: bool) {
+        if let Some(ref s) = self.super_type() {
+            s.bind_to_tree(tree_in_doc);
+        }
+
+        if tree_in_doc {
+            SVGSVGElement::invalidate_outermost(self.upcast());
+        }
+    }
+
     fn parse_plain_attribute(&self, name: &LocalName, value: DOMString) -> AttrValue {
         match name {
             &local_name!("width") => AttrValue::from_u32(value.into(), DEFAULT_WIDTH),
@@ -94,3 +203,13 @@ impl VirtualMethods for SVGSVGElement {
         }
     }
 }
+
+impl Drop for SVGSVGElement {
+    fn drop(&mut self) {
+        if let Some((ref ipc_renderer, canvas_id)) = *self.canvas.borrow() {
+            if let Err(err) = ipc_renderer.send(CanvasMsg::Close(canvas_id)) {
+                warn!("Could not close canvas: {}", err)
+            }
+        }
+    }
+}
@@ -12,7 +12,7 @@ use crate::dom::bindings::codegen::Bindings::TestBindingBinding::{
     TestBindingMethods, TestDictionary,
 };
 use crate::dom::bindings::codegen::Bindings::TestBindingBinding::{
-    TestDictionaryDefaults, TestEnum,
+    TestDictionaryDefaults, TestEnum, TestEnumStartingWithDigit,
 };
 use crate::dom::bindings::codegen::UnionTypes;
 use crate::dom::bindings::codegen::UnionTypes::{
@@ -42,7 +42,7 @@ use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
 use crate::dom::bindings::root::DomRoot;
 use crate::dom::bindings::str::{ByteString, DOMString, USVString};
 use crate::dom::bindings::trace::RootedTraceableBox;
-use crate::dom::bindings::weakref::MutableWeakRef;
+use crate::dom::bindings::weakref::{DOMTracker, MutableWeakRef};
 use crate::dom::blob::{Blob, BlobImpl};
 use crate::dom::globalscope::GlobalScope;
 use crate::dom::promise::Promise;
@@ -67,6 +67,7 @@ use std::rc::Rc;
 pub struct TestBinding {
     reflector_: Reflector,
     url: MutableWeakRef<URL>,
+    tracked_urls: DOMTracker<URL>,
 }
 
 impl TestBinding {
@@ -74,6 +75,7 @@ impl TestBinding {
         TestBinding {
             reflector_: Reflector::new(),
             url: MutableWeakRef::new(None),
+            tracked_urls: DOMTracker::new(),
         }
     }
 
@@ -477,6 +479,23 @@ impl TestBindingMethods for TestBinding {
     ) -> UnionTypes::StringOrObject {
         arg
     }
+    fn ReceiveEnumStartingWithDigitIdentity(
+        &self,
+        arg: TestEnumStartingWithDigit,
+    ) -> TestEnumStartingWithDigit {
+        arg
+    }
+    fn ReceiveUnionInterfaceOrDictionaryMember(
+        &self,
+        arg: UnionTypes::BlobOrTestDictionary,
+    ) -> DOMString {
+        match arg {
+            UnionTypes::BlobOrTestDictionary::Blob(_) => DOMString::from("Blob"),
+            UnionTypes::BlobOrTestDictionary::TestDictionary(_) => {
+                DOMString::from("TestDictionary")
+            },
+        }
+    }
 
     fn ReceiveNullableBoolean(&self) -> Option<bool> {
         Some(false)
@@ -1077,6 +1096,15 @@ impl TestBindingMethods for TestBinding {
         self.global().as_window().advance_animation_clock(ms, tick);
     }
 
+    fn TrackWeakly(&self, url: &URL) {
+        self.tracked_urls.track(url);
+    }
+    fn TrackedCount(&self) -> u32 {
+        let mut count = 0;
+        self.tracked_urls.for_each(|_| count += 1);
+        count
+    }
+
     fn Panic(&self) {
         panic!("explicit panic from script")
     }
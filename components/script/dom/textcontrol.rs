@@ -3,7 +3,7 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
 //! This is an abstraction used by `HTMLInputElement` and `HTMLTextAreaElement` to implement the
-//! text control selection DOM API.
+//! text control selection DOM API, and the editing of their value by an input method.
 //!
 //! https://html.spec.whatwg.org/multipage/#textFieldSelection
 
@@ -11,9 +11,12 @@ use crate::dom::bindings::cell::DomRefCell;
 use crate::dom::bindings::codegen::Bindings::HTMLFormElementBinding::SelectionMode;
 use crate::dom::bindings::conversions::DerivedFrom;
 use crate::dom::bindings::error::{Error, ErrorResult};
+use crate::dom::bindings::inheritance::Castable;
 use crate::dom::bindings::str::DOMString;
-use crate::dom::event::{EventBubbles, EventCancelable};
+use crate::dom::compositionevent::CompositionEvent;
+use crate::dom::event::{Event, EventBubbles, EventCancelable};
 use crate::dom::eventtarget::EventTarget;
+use crate::dom::inputevent::InputEvent;
 use crate::dom::node::{window_from_node, Node, NodeDamage};
 use crate::textinput::{SelectionDirection, SelectionState, TextInput};
 use script_traits::ScriptToConstellationChan;
@@ -261,6 +264,50 @@ impl<'a, E: TextControlElement> TextControlSelection<'a, E> {
         Ok(())
     }
 
+    /// Updates the value for a `compositionupdate` or `compositionend` event, replacing the text
+    /// being composed. Returns whether the value was updated.
+    ///
+    /// The `beforeinput` and `input` events of a `compositionupdate` are fired with `isComposing`
+    /// set, the committed text gets an `input` event without it, like in Gecko.
+    ///
+    /// <https://w3c.github.io/uievents/#events-composition-input-events>
+    pub fn handle_composition_event(&self, event: &CompositionEvent) -> bool {
+        let type_ = event.upcast::<Event>().type_();
+        let is_composing = type_ == atom!("compositionupdate");
+        if !is_composing && type_ != atom!("compositionend") {
+            return false;
+        }
+
+        let window = window_from_node(self.element);
+        let target = self.element.upcast::<EventTarget>();
+        let fire = |name: &str| {
+            let input_event = InputEvent::new(
+                &window,
+                DOMString::from(name),
+                true,
+                false,
+                Some(&window),
+                0,
+                Some(DOMString::from(event.data())),
+                is_composing,
+            );
+            input_event.upcast::<Event>().fire(target);
+        };
+
+        if is_composing {
+            fire("beforeinput");
+            self.textinput.borrow_mut().handle_compositionupdate(event);
+        } else {
+            self.textinput.borrow_mut().handle_compositionend(event);
+        }
+        self.element.set_dirty_value_flag(true);
+        self.element
+            .upcast::<Node>()
+            .dirty(NodeDamage::OtherNodeDamage);
+        fire("input");
+        true
+    }
+
     fn start(&self) -> u32 {
         self.textinput.borrow().selection_start_offset() as u32
     }
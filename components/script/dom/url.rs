@@ -5,13 +5,16 @@
 use crate::dom::bindings::cell::DomRefCell;
 use crate::dom::bindings::codegen::Bindings::URLBinding::{self, URLMethods};
 use crate::dom::bindings::error::{Error, ErrorResult, Fallible};
+use crate::dom::bindings::inheritance::Castable;
 use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
 use crate::dom::bindings::root::{DomRoot, MutNullableDom};
 use crate::dom::bindings::str::{DOMString, USVString};
 use crate::dom::blob::Blob;
 use crate::dom::globalscope::GlobalScope;
+use crate::dom::mediasource::MediaSource;
 use crate::dom::urlhelper::UrlHelper;
 use crate::dom::urlsearchparams::URLSearchParams;
+use crate::dom::window::Window;
 use dom_struct::dom_struct;
 use net_traits::blob_url_store::{get_blob_origin, parse_blob_url};
 use net_traits::filemanager_thread::FileManagerThreadMsg;
@@ -91,7 +94,7 @@ impl URL {
                         return Err(Error::Type(format!("could not parse base: {}", error)));
                     },
                 }
-            }
+            },
         };
         // Step 3.
         let parsed_url = match ServoUrl::parse_with_base(parsed_base.as_ref(), &url.0) {
@@ -121,6 +124,18 @@ impl URL {
         DOMString::from(URL::unicode_serialization_blob_url(&origin, &id))
     }
 
+    // https://w3c.github.io/media-source/#dom-url-createobjecturl
+    pub fn CreateObjectURL_(global: &GlobalScope, media_source: &MediaSource) -> DOMString {
+        // Media sources only live in the script thread, so unlike blobs
+        // they are kept track of by their window.
+        let origin = get_blob_origin(&global.get_url());
+        let url = URL::unicode_serialization_blob_url(&origin, &Uuid::new_v4());
+        global
+            .as_window()
+            .add_media_source_url(ServoUrl::parse(&url).expect("infallible"), media_source);
+        DOMString::from(url)
+    }
+
     // https://w3c.github.io/FileAPI/#dfn-revokeObjectURL
     pub fn RevokeObjectURL(global: &GlobalScope, url: DOMString) {
         // If the value provided for the url argument is not a Blob URL OR
@@ -129,6 +144,9 @@ impl URL {
         let origin = get_blob_origin(&global.get_url());
 
         if let Ok(url) = ServoUrl::parse(&url) {
+            if let Some(window) = global.downcast::<Window>() {
+                window.remove_media_source_url(&url);
+            }
             if let Ok((id, _)) = parse_blob_url(&url) {
                 let resource_threads = global.resource_threads();
                 let (tx, rx) = ipc::channel(global.time_profiler_chan().clone()).unwrap();
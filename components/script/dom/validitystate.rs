@@ -4,9 +4,11 @@
 
 use crate::dom::bindings::codegen::Bindings::ValidityStateBinding;
 use crate::dom::bindings::codegen::Bindings::ValidityStateBinding::ValidityStateMethods;
+use crate::dom::bindings::inheritance::Castable;
 use crate::dom::bindings::reflector::{reflect_dom_object, Reflector};
 use crate::dom::bindings::root::{Dom, DomRoot};
 use crate::dom::element::Element;
+use crate::dom::htmlelement::HTMLElement;
 use crate::dom::window::Window;
 use dom_struct::dom_struct;
 
@@ -28,6 +30,7 @@ pub enum ValidityStatus {
 }
 
 bitflags! {
+    #[derive(JSTraceable, MallocSizeOf)]
     pub struct ValidationFlags: u32 {
         const VALUE_MISSING    = 0b0000000001;
         const TYPE_MISMATCH    = 0b0000000010;
@@ -66,61 +69,81 @@ impl ValidityState {
             ValidityStateBinding::Wrap,
         )
     }
+
+    /// The flags set through the internals of a form-associated custom
+    /// element.
+    ///
+    /// <https://html.spec.whatwg.org/multipage/#face-validity-flags>
+    fn custom_element_flags(&self) -> Option<ValidationFlags> {
+        if !self.element.is_form_associated_custom_element() {
+            return None;
+        }
+        self.element
+            .downcast::<HTMLElement>()
+            .and_then(|element| element.element_internals())
+            .map(|internals| internals.validity_flags())
+    }
+
+    fn has_flag(&self, flag: ValidationFlags) -> bool {
+        self.custom_element_flags()
+            .map_or(false, |flags| flags.contains(flag))
+    }
 }
 
 impl ValidityStateMethods for ValidityState {
     // https://html.spec.whatwg.org/multipage/#dom-validitystate-valuemissing
     fn ValueMissing(&self) -> bool {
-        false
+        self.has_flag(ValidationFlags::VALUE_MISSING)
     }
 
     // https://html.spec.whatwg.org/multipage/#dom-validitystate-typemismatch
     fn TypeMismatch(&self) -> bool {
-        false
+        self.has_flag(ValidationFlags::TYPE_MISMATCH)
     }
 
     // https://html.spec.whatwg.org/multipage/#dom-validitystate-patternmismatch
     fn PatternMismatch(&self) -> bool {
-        false
+        self.has_flag(ValidationFlags::PATTERN_MISMATCH)
     }
 
     // https://html.spec.whatwg.org/multipage/#dom-validitystate-toolong
     fn TooLong(&self) -> bool {
-        false
+        self.has_flag(ValidationFlags::TOO_LONG)
     }
 
     // https://html.spec.whatwg.org/multipage/#dom-validitystate-tooshort
     fn TooShort(&self) -> bool {
-        false
+        self.has_flag(ValidationFlags::TOO_SHORT)
     }
 
     // https://html.spec.whatwg.org/multipage/#dom-validitystate-rangeunderflow
     fn RangeUnderflow(&self) -> bool {
-        false
+        self.has_flag(ValidationFlags::RANGE_UNDERFLOW)
     }
 
     // https://html.spec.whatwg.org/multipage/#dom-validitystate-rangeoverflow
     fn RangeOverflow(&self) -> bool {
-        false
+        self.has_flag(ValidationFlags::RANGE_OVERFLOW)
     }
 
     // https://html.spec.whatwg.org/multipage/#dom-validitystate-stepmismatch
     fn StepMismatch(&self) -> bool {
-        false
+        self.has_flag(ValidationFlags::STEP_MISMATCH)
     }
 
     // https://html.spec.whatwg.org/multipage/#dom-validitystate-badinput
     fn BadInput(&self) -> bool {
-        false
+        self.has_flag(ValidationFlags::BAD_INPUT)
     }
 
     // https://html.spec.whatwg.org/multipage/#dom-validitystate-customerror
     fn CustomError(&self) -> bool {
-        false
+        self.has_flag(ValidationFlags::CUSTOM_ERROR)
     }
 
     // https://html.spec.whatwg.org/multipage/#dom-validitystate-valid
     fn Valid(&self) -> bool {
-        false
+        self.custom_element_flags()
+            .map_or(false, |flags| flags.is_empty())
     }
 }
@@ -13,7 +13,6 @@ use crate::dom::bindings::codegen::Bindings::VRLayerBinding::VRLayer;
 use crate::dom::bindings::codegen::Bindings::WebGLRenderingContextBinding::WebGLRenderingContextMethods;
 use crate::dom::bindings::codegen::Bindings::WindowBinding::FrameRequestCallback;
 use crate::dom::bindings::codegen::Bindings::WindowBinding::WindowMethods;
-use crate::dom::bindings::codegen::Bindings::XRRenderStateBinding::XRRenderStateInit;
 use crate::dom::bindings::codegen::Bindings::XRSessionBinding::XRFrameRequestCallback;
 use crate::dom::bindings::codegen::Bindings::XRWebGLLayerBinding::XRWebGLLayerMethods;
 use crate::dom::bindings::error::Error;
@@ -36,7 +35,6 @@ use crate::dom::vrstageparameters::VRStageParameters;
 use crate::dom::webglrenderingcontext::WebGLRenderingContext;
 use crate::dom::xrframe::XRFrame;
 use crate::dom::xrsession::XRSession;
-use crate::dom::xrwebgllayer::XRWebGLLayer;
 use crate::script_runtime::CommonScriptMsg;
 use crate::script_runtime::ScriptThreadEventCategory::WebVREvent;
 use crate::task_source::{TaskSource, TaskSourceName};
@@ -51,7 +49,7 @@ use std::ops::Deref;
 use std::rc::Rc;
 use std::thread;
 use webvr_traits::{WebVRDisplayData, WebVRDisplayEvent, WebVRFrameData, WebVRFutureFrameData};
-use webvr_traits::{WebVRLayer, WebVRMsg};
+use webvr_traits::{WebVRFieldOfView, WebVRHitTestResult, WebVRLayer, WebVRMsg, WebVRRay};
 
 #[dom_struct]
 pub struct VRDisplay {
@@ -77,11 +75,11 @@ pub struct VRDisplay {
     #[ignore_malloc_size_of = "closures are hard"]
     raf_callback_list: DomRefCell<Vec<(u32, Option<Rc<FrameRequestCallback>>)>>,
     #[ignore_malloc_size_of = "closures are hard"]
-    xr_raf_callback_list: DomRefCell<Vec<(u32, Option<Rc<XRFrameRequestCallback>>)>>,
+    xr_raf_callbacks: DomRefCell<FrameCallbacks<Rc<XRFrameRequestCallback>>>,
     /// When there isn't any layer_ctx the RAF thread needs to be "woken up"
     raf_wakeup_sender: DomRefCell<Option<Sender<()>>>,
     #[ignore_malloc_size_of = "Rc is hard"]
-    pending_renderstate_updates: DomRefCell<Vec<(XRRenderStateInit, Rc<Promise>)>>,
+    pending_renderstate_updates: DomRefCell<Vec<Rc<Promise>>>,
     // Compositor VRFrameData synchonization
     frame_data_status: Cell<VRFrameDataStatus>,
     #[ignore_malloc_size_of = "closures are hard"]
@@ -150,7 +148,7 @@ impl VRDisplay {
             layer_ctx: MutNullableDom::default(),
             next_raf_id: Cell::new(1),
             raf_callback_list: DomRefCell::new(vec![]),
-            xr_raf_callback_list: DomRefCell::new(vec![]),
+            xr_raf_callbacks: DomRefCell::new(FrameCallbacks::new()),
             raf_wakeup_sender: DomRefCell::new(None),
             pending_renderstate_updates: DomRefCell::new(vec![]),
             frame_data_status: Cell::new(VRFrameDataStatus::Waiting),
@@ -181,6 +179,70 @@ impl VRDisplay {
     pub fn right_eye_params_offset(&self) -> [f32; 3] {
         self.right_eye_params.get().offset_array()
     }
+
+    pub fn left_eye_params_fov(&self) -> WebVRFieldOfView {
+        self.left_eye_params.get().field_of_view()
+    }
+
+    pub fn right_eye_params_fov(&self) -> WebVRFieldOfView {
+        self.right_eye_params.get().field_of_view()
+    }
+
+    /// The column-major transform from the sitting space of the device
+    /// to its standing space, if the device reports stage parameters
+    pub fn sitting_to_standing_transform(&self) -> Option<[f32; 16]> {
+        self.display
+            .borrow()
+            .stage_parameters
+            .as_ref()
+            .map(|stage| stage.sitting_to_standing_transform)
+    }
+
+    /// The width and depth of the play area, if the device reports stage
+    /// parameters
+    pub fn stage_size(&self) -> Option<(f32, f32)> {
+        self.display
+            .borrow()
+            .stage_parameters
+            .as_ref()
+            .map(|stage| (stage.size_x, stage.size_z))
+    }
+
+    /// Asks the device to render with the given fixed foveation level, or
+    /// just queries the current one when `level` is None
+    ///
+    /// Returns the level the device is using, or None if it doesn't
+    /// support fixed foveation.
+    pub fn set_fixed_foveation(&self, level: Option<f64>) -> Option<f64> {
+        let (sender, receiver) = ipc::channel(self.global().time_profiler_chan().clone()).unwrap();
+        self.webvr_thread()
+            .send(WebVRMsg::SetFixedFoveation(
+                self.global().pipeline_id(),
+                self.DisplayId(),
+                level,
+                sender,
+            ))
+            .unwrap();
+        receiver.recv().unwrap().unwrap_or(None)
+    }
+
+    /// Casts `ray` into the real world, or just checks whether the device
+    /// can when `ray` is None
+    ///
+    /// Returns the hits along the ray, or None if the device doesn't
+    /// support hit testing.
+    pub fn hit_test(&self, ray: Option<WebVRRay>) -> Option<Vec<WebVRHitTestResult>> {
+        let (sender, receiver) = ipc::channel(self.global().time_profiler_chan().clone()).unwrap();
+        self.webvr_thread()
+            .send(WebVRMsg::HitTest(
+                self.global().pipeline_id(),
+                self.DisplayId(),
+                ray,
+                sender,
+            ))
+            .unwrap();
+        receiver.recv().unwrap().unwrap_or(None)
+    }
 }
 
 impl Drop for VRDisplay {
@@ -288,6 +350,9 @@ impl VRDisplayMethods for VRDisplay {
             // Some VRDisplay data might change after calling ResetPose()
             *self.display.borrow_mut() = data;
         }
+        if let Some(session) = self.xr_session.get() {
+            session.notify_reset();
+        }
     }
 
     // https://w3c.github.io/webvr/#dom-vrdisplay-depthnear
@@ -492,6 +557,9 @@ impl VRDisplay {
         } else {
             self.stage_params.set(None);
         }
+        if let Some(session) = self.xr_session.get() {
+            session.update_bounds();
+        }
     }
 
     pub fn request_present<F>(
@@ -623,16 +691,8 @@ impl VRDisplay {
         }
     }
 
-    pub fn queue_renderstate(&self, state: &XRRenderStateInit, promise: Rc<Promise>) {
-        // can't clone dictionaries
-        let new_state = XRRenderStateInit {
-            depthNear: state.depthNear,
-            depthFar: state.depthFar,
-            baseLayer: state.baseLayer.clone(),
-        };
-        self.pending_renderstate_updates
-            .borrow_mut()
-            .push((new_state, promise));
+    pub fn queue_renderstate(&self, promise: Rc<Promise>) {
+        self.pending_renderstate_updates.borrow_mut().push(promise);
 
         if let Some(ref wakeup) = *self.raf_wakeup_sender.borrow() {
             let _ = wakeup.send(());
@@ -643,19 +703,17 @@ impl VRDisplay {
         let mut updates = self.pending_renderstate_updates.borrow_mut();
 
         debug_assert!(updates.is_empty() || self.xr_session.get().is_some());
-        for update in updates.drain(..) {
-            if let Some(near) = update.0.depthNear {
-                self.depth_near.set(*near);
-            }
-            if let Some(far) = update.0.depthFar {
-                self.depth_far.set(*far);
-            }
-            if let Some(ref layer) = update.0.baseLayer {
-                self.xr_session.get().unwrap().set_layer(&layer);
-                let layer = layer.downcast::<XRWebGLLayer>().unwrap();
-                self.layer_ctx.set(Some(&layer.Context()));
+        if let Some(session) = self.xr_session.get() {
+            if let Some(state) = session.apply_pending_render_state() {
+                self.depth_near.set(state.depth_near());
+                self.depth_far.set(state.depth_far());
+                if let Some(layer) = state.presented_layer() {
+                    self.layer_ctx.set(Some(&layer.Context()));
+                }
             }
-            update.1.resolve_native(&());
+        }
+        for promise in updates.drain(..) {
+            promise.resolve_native(&());
         }
     }
 
@@ -785,8 +843,8 @@ impl VRDisplay {
         }
     }
 
-    // Only called when the JSContext is destroyed while presenting.
-    // In this case we don't want to wait for WebVR Thread response.
+    // Only called when the JSContext is destroyed while presenting, or when
+    // an XR session ends. In these cases we don't want to wait for WebVR Thread response.
     fn force_stop_present(&self) {
         self.webvr_thread()
             .send(WebVRMsg::ExitPresent(
@@ -820,20 +878,55 @@ impl VRDisplay {
         let now = self.global().as_window().Performance().Now();
 
         if let Some(session) = self.xr_session.get() {
-            let mut callbacks = mem::replace(&mut *self.xr_raf_callback_list.borrow_mut(), vec![]);
-            if callbacks.is_empty() {
-                return;
-            }
-            self.sync_frame_data();
-            let frame = XRFrame::new(&self.global(), &session, self.frame_data.borrow().clone());
-
-            for (_, callback) in callbacks.drain(..) {
-                if let Some(callback) = callback {
-                    let _ = callback.Call__(Finite::wrap(*now), &frame, ExceptionHandling::Report);
+            session.note_device_frame(*now);
+            if !session.should_run_frame(*now) {
+                // Keep the callbacks for the next frame due at the target frame rate,
+                // but consume this frame's data so that we keep up with the device
+                self.sync_frame_data();
+                let now = *self.global().as_window().Performance().Now();
+                let (data, time) = session.predict_frame_data(&self.frame_data.borrow(), now);
+                session.update_input_sources(&data, time);
+            } else {
+                if !self.xr_raf_callbacks.borrow_mut().start_frame() {
+                    return;
+                }
+                self.sync_frame_data();
+                // the poses handed to content are predicted for the time the
+                // frame will be displayed, rather than the time they were sampled
+                let now = *self.global().as_window().Performance().Now();
+                let (data, time) = session.predict_frame_data(&self.frame_data.borrow(), now);
+                session.update_input_sources(&data, time);
+                session.update_tracked_anchors();
+                session.next_frame();
+                let frame = XRFrame::new(&self.global(), &session, data, time);
+
+                // The frame may only be queried while the callbacks are running
+                frame.set_active(true);
+                frame.set_animation_frame(true);
+                run_frame_callbacks(
+                    |i| self.xr_raf_callbacks.borrow().current(i),
+                    |callback| {
+                        // The timestamp is the frame's predicted display time
+                        callback.Call__(
+                            Finite::wrap(frame.time()),
+                            &frame,
+                            ExceptionHandling::Report,
+                        )
+                    },
+                );
+                self.xr_raf_callbacks.borrow_mut().end_frame();
+                frame.set_active(false);
+                frame.set_animation_frame(false);
+                // frame submission is automatic in XR, but there is nothing to
+                // submit until the layer's context has been restored
+                let context_lost = self
+                    .layer_ctx
+                    .get()
+                    .map_or(true, |ctx| ctx.is_context_lost());
+                if !context_lost {
+                    self.SubmitFrame();
                 }
             }
-            // frame submission is automatic in XR
-            self.SubmitFrame();
         } else {
             self.running_display_raf.set(true);
             let mut callbacks = mem::replace(&mut *self.raf_callback_list.borrow_mut(), vec![]);
@@ -887,21 +980,124 @@ impl VRDisplay {
         });
     }
 
+    /// Stops presenting `session`, which releases the VR compositor that was
+    /// rendering its layer, and drops the callbacks and render state updates
+    /// it still had queued
+    pub fn xr_end(&self, session: &XRSession) {
+        if self
+            .xr_session
+            .get()
+            .map_or(true, |current| &*current != session)
+        {
+            return;
+        }
+        self.xr_session.set(None);
+        self.xr_raf_callbacks.borrow_mut().clear();
+        for promise in self.pending_renderstate_updates.borrow_mut().drain(..) {
+            promise.reject_error(Error::InvalidState);
+        }
+        // a session that ends while paused must not come back on resume
+        self.stopped_on_pause.set(false);
+        if self.presenting.get() {
+            self.force_stop_present();
+        } else {
+            let xr = self.global().as_window().Navigator().Xr();
+            xr.deactivate_session();
+        }
+        self.layer_ctx.set(None);
+    }
+
     pub fn xr_raf(&self, callback: Rc<XRFrameRequestCallback>) -> u32 {
         let raf_id = self.next_raf_id.get();
         self.next_raf_id.set(raf_id + 1);
-        self.xr_raf_callback_list
-            .borrow_mut()
-            .push((raf_id, Some(callback)));
+        self.xr_raf_callbacks.borrow_mut().push(raf_id, callback);
         raf_id
     }
 
     pub fn xr_cancel_raf(&self, handle: i32) {
-        let mut list = self.xr_raf_callback_list.borrow_mut();
-        if let Some(pair) = list.iter_mut().find(|pair| pair.0 == handle as u32) {
+        self.xr_raf_callbacks.borrow_mut().cancel(handle as u32);
+    }
+}
+
+/// The XR frame callbacks of a display
+///
+/// The callbacks being run for the current frame are kept apart from the
+/// ones registered for the next frame, so that callbacks registered while
+/// a frame runs wait for the next one.
+#[derive(JSTraceable)]
+pub struct FrameCallbacks<C> {
+    next: Vec<(u32, Option<C>)>,
+    current: Vec<(u32, Option<C>)>,
+}
+
+impl<C: Clone> FrameCallbacks<C> {
+    pub fn new() -> FrameCallbacks<C> {
+        FrameCallbacks {
+            next: vec![],
+            current: vec![],
+        }
+    }
+
+    /// Registers `callback` for the next frame
+    pub fn push(&mut self, id: u32, callback: C) {
+        self.next.push((id, Some(callback)));
+    }
+
+    /// Cancels a callback, even one of the current frame that hasn't run yet
+    pub fn cancel(&mut self, id: u32) {
+        if let Some(pair) = self
+            .next
+            .iter_mut()
+            .chain(self.current.iter_mut())
+            .find(|pair| pair.0 == id)
+        {
             pair.1 = None;
         }
     }
+
+    /// Moves the callbacks registered so far to the current frame, returning
+    /// whether there are any
+    pub fn start_frame(&mut self) -> bool {
+        mem::swap(&mut self.next, &mut self.current);
+        !self.current.is_empty()
+    }
+
+    /// The callback to run `index`th in the current frame, None once they all
+    /// ran and Some(None) for the ones that got cancelled
+    pub fn current(&self, index: usize) -> Option<Option<C>> {
+        self.current
+            .get(index)
+            .map(|&(_, ref callback)| callback.clone())
+    }
+
+    pub fn end_frame(&mut self) {
+        self.current.clear();
+    }
+
+    pub fn clear(&mut self) {
+        self.next.clear();
+        self.current.clear();
+    }
+}
+
+/// Runs the callbacks of the current frame in order
+///
+/// Callbacks may cancel the ones after them, so each one is looked up with
+/// `current` right before it is called. A callback that throws has its
+/// exception reported to the console, and doesn't keep the ones after it
+/// from running.
+pub fn run_frame_callbacks<C, E, G, F>(current: G, mut call: F)
+where
+    G: Fn(usize) -> Option<Option<C>>,
+    F: FnMut(C) -> Result<(), E>,
+{
+    let mut i = 0;
+    while let Some(callback) = current(i) {
+        if let Some(callback) = callback {
+            let _ = call(callback);
+        }
+        i += 1;
+    }
 }
 
 // WebVR Spec: If the number of values in the leftBounds/rightBounds arrays
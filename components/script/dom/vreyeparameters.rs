@@ -15,7 +15,7 @@ use js::typedarray::{CreateWith, Float32Array};
 use std::default::Default;
 use std::ptr;
 use std::ptr::NonNull;
-use webvr_traits::WebVREyeParameters;
+use webvr_traits::{WebVREyeParameters, WebVRFieldOfView};
 
 #[dom_struct]
 pub struct VREyeParameters {
@@ -65,6 +65,10 @@ impl VREyeParameters {
     pub fn offset_array(&self) -> [f32; 3] {
         self.parameters.borrow().offset
     }
+
+    pub fn field_of_view(&self) -> WebVRFieldOfView {
+        self.parameters.borrow().field_of_view.clone()
+    }
 }
 
 impl VREyeParametersMethods for VREyeParameters {
@@ -3,16 +3,19 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
 use crate::dom::bindings::codegen::Bindings::WebGL2RenderingContextBinding;
+use crate::dom::bindings::codegen::Bindings::WebGL2RenderingContextBinding::WebGL2RenderingContextConstants as constants;
 use crate::dom::bindings::codegen::Bindings::WebGL2RenderingContextBinding::WebGL2RenderingContextMethods;
 use crate::dom::bindings::codegen::Bindings::WebGLRenderingContextBinding::WebGLContextAttributes;
 use crate::dom::bindings::codegen::Bindings::WebGLRenderingContextBinding::WebGLRenderingContextMethods;
 use crate::dom::bindings::codegen::UnionTypes::ArrayBufferViewOrArrayBuffer;
 use crate::dom::bindings::codegen::UnionTypes::Float32ArrayOrUnrestrictedFloatSequence;
-use crate::dom::bindings::codegen::UnionTypes::ImageDataOrHTMLImageElementOrHTMLCanvasElementOrHTMLVideoElement;
+use crate::dom::bindings::codegen::UnionTypes::ImageBitmapOrImageDataOrHTMLImageElementOrHTMLCanvasElementOrHTMLVideoElement;
 use crate::dom::bindings::codegen::UnionTypes::Int32ArrayOrLongSequence;
+use crate::dom::bindings::codegen::UnionTypes::Uint32ArrayOrUnsignedLongSequence;
+use crate::dom::bindings::conversions::ToJSValConvertible;
 use crate::dom::bindings::error::{ErrorResult, Fallible};
 use crate::dom::bindings::reflector::{reflect_dom_object, Reflector};
-use crate::dom::bindings::root::{Dom, DomRoot, LayoutDom};
+use crate::dom::bindings::root::{Dom, DomRoot, LayoutDom, MutNullableDom};
 use crate::dom::bindings::str::DOMString;
 use crate::dom::htmlcanvaselement::HTMLCanvasElement;
 use crate::dom::htmliframeelement::HTMLIFrameElement;
@@ -24,26 +27,44 @@ use crate::dom::webglrenderbuffer::WebGLRenderbuffer;
 use crate::dom::webglrenderingcontext::{
     LayoutCanvasWebGLRenderingContextHelpers, WebGLRenderingContext,
 };
+use crate::dom::webglsampler::{SamplerParameterValue, WebGLSampler};
 use crate::dom::webglshader::WebGLShader;
 use crate::dom::webglshaderprecisionformat::WebGLShaderPrecisionFormat;
 use crate::dom::webgltexture::WebGLTexture;
+use crate::dom::webgltransformfeedback::{ActiveTransformFeedback, WebGLTransformFeedback};
 use crate::dom::webgluniformlocation::WebGLUniformLocation;
 use crate::dom::window::Window;
 /// https://www.khronos.org/registry/webgl/specs/latest/2.0/webgl.idl
-use canvas_traits::webgl::{GLContextAttributes, WebGLVersion};
+use canvas_traits::webgl::WebGLError::*;
+use canvas_traits::webgl::{GLContextAttributes, WebGLCommand, WebGLResult, WebGLVersion};
 use dom_struct::dom_struct;
 use euclid::Size2D;
 use js::jsapi::{JSContext, JSObject};
-use js::jsval::JSVal;
+use js::jsval::{
+    BooleanValue, DoubleValue, Int32Value, JSVal, NullValue, ObjectValue, UInt32Value,
+};
 use js::rust::CustomAutoRooterGuard;
-use js::typedarray::ArrayBufferView;
+use js::typedarray::{ArrayBufferView, CreateWith, Uint32Array};
 use script_layout_interface::HTMLCanvasDataSource;
-use std::ptr::NonNull;
+use std::cell::Cell;
+use std::cmp;
+use std::ptr::{self, NonNull};
 
 #[dom_struct]
 pub struct WebGL2RenderingContext {
     reflector_: Reflector,
     base: Dom<WebGLRenderingContext>,
+    bound_uniform_buffer: MutNullableDom<WebGLBuffer>,
+    bound_transform_feedback_buffer: MutNullableDom<WebGLBuffer>,
+    /// The buffers bound with bindBufferBase and bindBufferRange, per index.
+    indexed_uniform_buffer_bindings: Box<[MutNullableDom<WebGLBuffer>]>,
+    indexed_transform_feedback_buffer_bindings: Box<[MutNullableDom<WebGLBuffer>]>,
+    /// The sampler bound to each texture unit.
+    samplers: Box<[MutNullableDom<WebGLSampler>]>,
+    current_transform_feedback: MutNullableDom<WebGLTransformFeedback>,
+    /// The state of the default transform feedback object, used when no
+    /// transform feedback object is bound.
+    default_transform_feedback: Cell<Option<ActiveTransformFeedback>>,
 }
 
 impl WebGL2RenderingContext {
@@ -54,8 +75,27 @@ impl WebGL2RenderingContext {
         attrs: GLContextAttributes,
     ) -> Option<WebGL2RenderingContext> {
         let base = WebGLRenderingContext::new(window, canvas, WebGLVersion::WebGL2, size, attrs)?;
+        let limits = base.limits();
+        let bindings = |count: u32| {
+            (0..count)
+                .map(|_| Default::default())
+                .collect::<Vec<_>>()
+                .into()
+        };
         Some(WebGL2RenderingContext {
             reflector_: Reflector::new(),
+            bound_uniform_buffer: MutNullableDom::new(None),
+            bound_transform_feedback_buffer: MutNullableDom::new(None),
+            indexed_uniform_buffer_bindings: bindings(limits.max_uniform_buffer_bindings),
+            indexed_transform_feedback_buffer_bindings: bindings(
+                limits.max_transform_feedback_separate_attribs,
+            ),
+            samplers: (0..limits.max_combined_texture_image_units)
+                .map(|_| Default::default())
+                .collect::<Vec<_>>()
+                .into(),
+            current_transform_feedback: MutNullableDom::new(None),
+            default_transform_feedback: Cell::new(None),
             base: Dom::from_ref(&*base),
         })
     }
@@ -81,6 +121,135 @@ impl WebGL2RenderingContext {
     pub fn base_context(&self) -> DomRoot<WebGLRenderingContext> {
         DomRoot::from_ref(&*self.base)
     }
+
+    fn bound_buffer(&self, target: u32) -> WebGLResult<Option<DomRoot<WebGLBuffer>>> {
+        match target {
+            constants::UNIFORM_BUFFER => Ok(self.bound_uniform_buffer.get()),
+            constants::TRANSFORM_FEEDBACK_BUFFER => Ok(self.bound_transform_feedback_buffer.get()),
+            _ => self.base.bound_buffer(target),
+        }
+    }
+
+    /// Clears `slot` if it holds `buffer`.
+    fn unbind_from(&self, slot: &MutNullableDom<WebGLBuffer>, buffer: &WebGLBuffer) {
+        if slot.get().map_or(false, |b| buffer == &*b) {
+            buffer.decrement_attached_counter();
+            slot.set(None);
+        }
+    }
+
+    /// The state of the bound transform feedback object, `None` if it isn't
+    /// active.
+    fn transform_feedback_state(&self) -> Option<ActiveTransformFeedback> {
+        match self.current_transform_feedback.get() {
+            Some(tf) => tf.active().get(),
+            None => self.default_transform_feedback.get(),
+        }
+    }
+
+    fn set_transform_feedback_state(&self, state: Option<ActiveTransformFeedback>) {
+        match self.current_transform_feedback.get() {
+            Some(tf) => tf.active().set(state),
+            None => self.default_transform_feedback.set(state),
+        }
+    }
+
+    /// https://www.khronos.org/registry/webgl/specs/latest/2.0/#3.7.16
+    fn bind_buffer_indexed(
+        &self,
+        target: u32,
+        index: u32,
+        buffer: Option<&WebGLBuffer>,
+        range: Option<(i64, i64)>,
+    ) {
+        let (generic_slot, indexed_slots) = match target {
+            constants::UNIFORM_BUFFER => (
+                &self.bound_uniform_buffer,
+                &self.indexed_uniform_buffer_bindings,
+            ),
+            constants::TRANSFORM_FEEDBACK_BUFFER => (
+                &self.bound_transform_feedback_buffer,
+                &self.indexed_transform_feedback_buffer_bindings,
+            ),
+            _ => return self.base.webgl_error(InvalidEnum),
+        };
+        let indexed_slot = match indexed_slots.get(index as usize) {
+            Some(slot) => slot,
+            None => return self.base.webgl_error(InvalidValue),
+        };
+
+        if let Some(buffer) = buffer {
+            handle_potential_webgl_error!(self.base, self.base.validate_ownership(buffer), return);
+            if buffer.is_marked_for_deletion() {
+                return self.base.webgl_error(InvalidOperation);
+            }
+            handle_potential_webgl_error!(self.base, buffer.set_target(target), return);
+        }
+
+        // The range is ignored when unbinding.
+        if let (Some(_), Some((offset, size))) = (buffer, range) {
+            if offset < 0 || size <= 0 {
+                return self.base.webgl_error(InvalidValue);
+            }
+            let alignment = match target {
+                constants::UNIFORM_BUFFER => {
+                    self.base.limits().uniform_buffer_offset_alignment as i64
+                },
+                _ => 4,
+            };
+            if alignment != 0 && offset % alignment != 0 {
+                return self.base.webgl_error(InvalidValue);
+            }
+            if target == constants::TRANSFORM_FEEDBACK_BUFFER && size % 4 != 0 {
+                return self.base.webgl_error(InvalidValue);
+            }
+        }
+
+        if target == constants::TRANSFORM_FEEDBACK_BUFFER &&
+            self.transform_feedback_state().is_some()
+        {
+            return self.base.webgl_error(InvalidOperation);
+        }
+
+        let id = buffer.map(|b| b.id());
+        self.base.send_command(match range {
+            Some((offset, size)) => WebGLCommand::BindBufferRange(target, index, id, offset, size),
+            None => WebGLCommand::BindBufferBase(target, index, id),
+        });
+
+        // Binding to an index also binds to the generic binding point.
+        for slot in &[generic_slot, indexed_slot] {
+            if let Some(buffer) = buffer {
+                buffer.increment_attached_counter();
+            }
+            if let Some(old) = slot.get() {
+                old.decrement_attached_counter();
+            }
+            slot.set(buffer);
+        }
+    }
+
+    /// Checks that draw calls issued with `mode` can be captured by the active
+    /// transform feedback, if any.
+    ///
+    /// https://www.khronos.org/registry/webgl/specs/latest/2.0/#3.7.15
+    fn validate_transform_feedback_draw(&self, mode: Option<u32>) -> WebGLResult<()> {
+        let state = match self.transform_feedback_state() {
+            Some(state) if !state.paused => state,
+            _ => return Ok(()),
+        };
+        // Strips and loops are captured as their base primitive.
+        let primitive = mode.map(|mode| match mode {
+            constants::LINE_LOOP | constants::LINE_STRIP => constants::LINES,
+            constants::TRIANGLE_STRIP | constants::TRIANGLE_FAN => constants::TRIANGLES,
+            mode => mode,
+        });
+        if primitive == Some(state.primitive_mode) {
+            Ok(())
+        } else {
+            Err(InvalidOperation)
+        }
+    }
 }
 
 impl WebGL2RenderingContextMethods for WebGL2RenderingContext {
@@ -112,12 +281,80 @@ impl WebGL2RenderingContextMethods for WebGL2RenderingContext {
     #[allow(unsafe_code)]
     /// https://www.khronos.org/registry/webgl/specs/latest/1.0/#5.14.5
     unsafe fn GetBufferParameter(&self, _cx: *mut JSContext, target: u32, parameter: u32) -> JSVal {
-        self.base.GetBufferParameter(_cx, target, parameter)
+        let buffer =
+            handle_potential_webgl_error!(self.base, self.bound_buffer(target), return NullValue());
+        self.base.get_buffer_param(buffer, parameter)
     }
 
     #[allow(unsafe_code)]
     /// https://www.khronos.org/registry/webgl/specs/latest/1.0/#5.14.3
     unsafe fn GetParameter(&self, cx: *mut JSContext, parameter: u32) -> JSVal {
+        match parameter {
+            constants::UNIFORM_BUFFER_BINDING => {
+                return optional_root_object_to_js_or_null!(cx, self.bound_uniform_buffer.get());
+            },
+            constants::TRANSFORM_FEEDBACK_BUFFER_BINDING => {
+                return optional_root_object_to_js_or_null!(
+                    cx,
+                    self.bound_transform_feedback_buffer.get()
+                );
+            },
+            constants::TRANSFORM_FEEDBACK_BINDING => {
+                return optional_root_object_to_js_or_null!(
+                    cx,
+                    self.current_transform_feedback.get()
+                );
+            },
+            constants::SAMPLER_BINDING => {
+                let unit = self.base.textures().active_unit_enum() - constants::TEXTURE0;
+                let sampler = self.samplers[unit as usize].get();
+                return optional_root_object_to_js_or_null!(cx, sampler);
+            },
+            constants::TEXTURE_BINDING_3D | constants::TEXTURE_BINDING_2D_ARRAY => {
+                let target = match parameter {
+                    constants::TEXTURE_BINDING_3D => constants::TEXTURE_3D,
+                    _ => constants::TEXTURE_2D_ARRAY,
+                };
+                let texture = self
+                    .base
+                    .textures()
+                    .active_texture_slot(target, WebGLVersion::WebGL2)
+                    .unwrap()
+                    .get();
+                return optional_root_object_to_js_or_null!(cx, texture);
+            },
+            constants::TRANSFORM_FEEDBACK_ACTIVE => {
+                return BooleanValue(self.transform_feedback_state().is_some());
+            },
+            constants::TRANSFORM_FEEDBACK_PAUSED => {
+                return BooleanValue(
+                    self.transform_feedback_state()
+                        .map_or(false, |state| state.paused),
+                );
+            },
+            _ => {},
+        }
+
+        let limit = match parameter {
+            constants::MAX_3D_TEXTURE_SIZE => Some(self.base.limits().max_3d_texture_size),
+            constants::MAX_ARRAY_TEXTURE_LAYERS => {
+                Some(self.base.limits().max_array_texture_layers)
+            },
+            constants::MAX_UNIFORM_BUFFER_BINDINGS => {
+                Some(self.base.limits().max_uniform_buffer_bindings)
+            },
+            constants::UNIFORM_BUFFER_OFFSET_ALIGNMENT => {
+                Some(self.base.limits().uniform_buffer_offset_alignment)
+            },
+            constants::MAX_TRANSFORM_FEEDBACK_SEPARATE_ATTRIBS => {
+                Some(self.base.limits().max_transform_feedback_separate_attribs)
+            },
+            _ => None,
+        };
+        if let Some(limit) = limit {
+            return UInt32Value(limit);
+        }
+
         self.base.GetParameter(cx, parameter)
     }
 
@@ -224,7 +461,12 @@ impl WebGL2RenderingContextMethods for WebGL2RenderingContext {
 
     /// https://www.khronos.org/registry/webgl/specs/latest/1.0/#5.14.5
     fn BindBuffer(&self, target: u32, buffer: Option<&WebGLBuffer>) {
-        self.base.BindBuffer(target, buffer)
+        let slot = match target {
+            constants::UNIFORM_BUFFER => &self.bound_uniform_buffer,
+            constants::TRANSFORM_FEEDBACK_BUFFER => &self.bound_transform_feedback_buffer,
+            _ => return self.base.BindBuffer(target, buffer),
+        };
+        self.base.bind_buffer_maybe(slot, target, buffer);
     }
 
     /// https://www.khronos.org/registry/webgl/specs/latest/1.0/#5.14.6
@@ -249,17 +491,24 @@ impl WebGL2RenderingContextMethods for WebGL2RenderingContext {
 
     /// https://www.khronos.org/registry/webgl/specs/latest/1.0/#5.14.5
     fn BufferData(&self, target: u32, data: Option<ArrayBufferViewOrArrayBuffer>, usage: u32) {
-        self.base.BufferData(target, data, usage)
+        let bound_buffer =
+            handle_potential_webgl_error!(self.base, self.bound_buffer(target), return);
+        self.base.buffer_data(data, usage, bound_buffer)
     }
 
     /// https://www.khronos.org/registry/webgl/specs/latest/1.0/#5.14.5
     fn BufferData_(&self, target: u32, size: i64, usage: u32) {
-        self.base.BufferData_(target, size, usage)
+        let bound_buffer =
+            handle_potential_webgl_error!(self.base, self.bound_buffer(target), return);
+        self.base.buffer_data_(size, usage, bound_buffer)
     }
 
     /// https://www.khronos.org/registry/webgl/specs/latest/1.0/#5.14.5
     fn BufferSubData(&self, target: u32, offset: i64, data: ArrayBufferViewOrArrayBuffer) {
-        self.base.BufferSubData(target, offset, data)
+        let bound_buffer =
+            handle_potential_webgl_error!(self.base, self.bound_buffer(target), return);
+        self.base
+            .buffer_sub_data(target, offset, data, bound_buffer)
     }
 
     /// https://www.khronos.org/registry/webgl/specs/latest/1.0/#5.14.8
@@ -429,7 +678,24 @@ impl WebGL2RenderingContextMethods for WebGL2RenderingContext {
 
     /// https://www.khronos.org/registry/webgl/specs/latest/1.0/#5.14.5
     fn DeleteBuffer(&self, buffer: Option<&WebGLBuffer>) {
-        self.base.DeleteBuffer(buffer)
+        let buffer = match buffer {
+            Some(buffer) => buffer,
+            None => return,
+        };
+        handle_potential_webgl_error!(self.base, self.base.validate_ownership(buffer), return);
+        if buffer.is_marked_for_deletion() {
+            return;
+        }
+        self.unbind_from(&self.bound_uniform_buffer, buffer);
+        self.unbind_from(&self.bound_transform_feedback_buffer, buffer);
+        for slot in self
+            .indexed_uniform_buffer_bindings
+            .iter()
+            .chain(self.indexed_transform_feedback_buffer_bindings.iter())
+        {
+            self.unbind_from(slot, buffer);
+        }
+        self.base.DeleteBuffer(Some(buffer))
     }
 
     /// https://www.khronos.org/registry/webgl/specs/latest/1.0/#5.14.6
@@ -459,11 +725,22 @@ impl WebGL2RenderingContextMethods for WebGL2RenderingContext {
 
     /// https://www.khronos.org/registry/webgl/specs/latest/1.0/#5.14.11
     fn DrawArrays(&self, mode: u32, first: i32, count: i32) {
+        handle_potential_webgl_error!(
+            self.base,
+            self.validate_transform_feedback_draw(Some(mode)),
+            return
+        );
         self.base.DrawArrays(mode, first, count)
     }
 
     /// https://www.khronos.org/registry/webgl/specs/latest/1.0/#5.14.11
     fn DrawElements(&self, mode: u32, count: i32, type_: u32, offset: i64) {
+        // Indexed draws can't be captured by transform feedback.
+        handle_potential_webgl_error!(
+            self.base,
+            self.validate_transform_feedback_draw(None),
+            return
+        );
         self.base.DrawElements(mode, count, type_, offset)
     }
 
@@ -675,6 +952,14 @@ impl WebGL2RenderingContextMethods for WebGL2RenderingContext {
 
     /// https://www.khronos.org/registry/webgl/specs/latest/1.0/#5.14.9
     fn LinkProgram(&self, program: &WebGLProgram) {
+        // The program the active transform feedback captures from can't be
+        // relinked.
+        if self
+            .transform_feedback_state()
+            .map_or(false, |state| state.program == program.id())
+        {
+            return self.base.webgl_error(InvalidOperation);
+        }
         self.base.LinkProgram(program)
     }
 
@@ -827,6 +1112,12 @@ impl WebGL2RenderingContextMethods for WebGL2RenderingContext {
 
     /// https://www.khronos.org/registry/webgl/specs/latest/1.0/#5.14.9
     fn UseProgram(&self, program: Option<&WebGLProgram>) {
+        if self
+            .transform_feedback_state()
+            .map_or(false, |state| !state.paused)
+        {
+            return self.base.webgl_error(InvalidOperation);
+        }
         self.base.UseProgram(program)
     }
 
@@ -928,7 +1219,7 @@ impl WebGL2RenderingContextMethods for WebGL2RenderingContext {
         internal_format: u32,
         format: u32,
         data_type: u32,
-        source: ImageDataOrHTMLImageElementOrHTMLCanvasElementOrHTMLVideoElement,
+        source: ImageBitmapOrImageDataOrHTMLImageElementOrHTMLCanvasElementOrHTMLVideoElement,
     ) -> ErrorResult {
         self.base
             .TexImage2D_(target, level, internal_format, format, data_type, source)
@@ -985,7 +1276,7 @@ impl WebGL2RenderingContextMethods for WebGL2RenderingContext {
         yoffset: i32,
         format: u32,
         data_type: u32,
-        source: ImageDataOrHTMLImageElementOrHTMLCanvasElementOrHTMLVideoElement,
+        source: ImageBitmapOrImageDataOrHTMLImageElementOrHTMLCanvasElementOrHTMLVideoElement,
     ) -> ErrorResult {
         self.base
             .TexSubImage2D_(target, level, xoffset, yoffset, format, data_type, source)
@@ -1044,6 +1335,11 @@ impl WebGL2RenderingContextMethods for WebGL2RenderingContext {
 
     /// https://www.khronos.org/registry/webgl/specs/latest/2.0/#3.7.9
     fn DrawArraysInstanced(&self, mode: u32, first: i32, count: i32, primcount: i32) {
+        handle_potential_webgl_error!(
+            self.base,
+            self.validate_transform_feedback_draw(Some(mode)),
+            return
+        );
         handle_potential_webgl_error!(
             self.base,
             self.base
@@ -1060,6 +1356,11 @@ impl WebGL2RenderingContextMethods for WebGL2RenderingContext {
         offset: i64,
         primcount: i32,
     ) {
+        handle_potential_webgl_error!(
+            self.base,
+            self.validate_transform_feedback_draw(None),
+            return
+        );
         handle_potential_webgl_error!(
             self.base,
             self.base
@@ -1071,6 +1372,585 @@ impl WebGL2RenderingContextMethods for WebGL2RenderingContext {
     fn VertexAttribDivisor(&self, index: u32, divisor: u32) {
         self.base.vertex_attrib_divisor(index, divisor);
     }
+
+    /// https://www.khronos.org/registry/webgl/specs/latest/2.0/#3.7.6
+    fn TexStorage2D(
+        &self,
+        target: u32,
+        levels: i32,
+        internal_format: u32,
+        width: i32,
+        height: i32,
+    ) {
+        handle_potential_webgl_error!(
+            self.base,
+            self.base
+                .tex_storage(target, levels, internal_format, width, height, 1)
+        )
+    }
+
+    /// https://www.khronos.org/registry/webgl/specs/latest/2.0/#3.7.6
+    fn TexStorage3D(
+        &self,
+        target: u32,
+        levels: i32,
+        internal_format: u32,
+        width: i32,
+        height: i32,
+        depth: i32,
+    ) {
+        handle_potential_webgl_error!(
+            self.base,
+            self.base
+                .tex_storage(target, levels, internal_format, width, height, depth)
+        )
+    }
+
+    /// https://www.khronos.org/registry/webgl/specs/latest/2.0/#3.7.6
+    fn TexImage3D(
+        &self,
+        target: u32,
+        level: i32,
+        internal_format: i32,
+        width: i32,
+        height: i32,
+        depth: i32,
+        border: i32,
+        format: u32,
+        data_type: u32,
+        src_data: CustomAutoRooterGuard<Option<ArrayBufferView>>,
+    ) {
+        self.base.tex_image_3d(
+            target,
+            level,
+            internal_format as u32,
+            width,
+            height,
+            depth,
+            border,
+            format,
+            data_type,
+            &*src_data,
+        )
+    }
+
+    /// https://www.khronos.org/registry/webgl/specs/latest/2.0/#3.7.6
+    fn TexSubImage3D(
+        &self,
+        target: u32,
+        level: i32,
+        xoffset: i32,
+        yoffset: i32,
+        zoffset: i32,
+        width: i32,
+        height: i32,
+        depth: i32,
+        format: u32,
+        data_type: u32,
+        src_data: CustomAutoRooterGuard<Option<ArrayBufferView>>,
+        src_offset: u32,
+    ) {
+        self.base.tex_sub_image_3d(
+            target,
+            level,
+            (xoffset, yoffset, zoffset),
+            width,
+            height,
+            depth,
+            format,
+            data_type,
+            &*src_data,
+            src_offset,
+        )
+    }
+
+    /// https://www.khronos.org/registry/webgl/specs/latest/2.0/#3.7.6
+    fn CopyTexSubImage3D(
+        &self,
+        target: u32,
+        level: i32,
+        xoffset: i32,
+        yoffset: i32,
+        zoffset: i32,
+        x: i32,
+        y: i32,
+        width: i32,
+        height: i32,
+    ) {
+        handle_potential_webgl_error!(
+            self.base,
+            self.base.copy_tex_sub_image_3d(
+                target, level, xoffset, yoffset, zoffset, x, y, width, height,
+            )
+        )
+    }
+
+    /// https://www.khronos.org/registry/webgl/specs/latest/2.0/#3.7.7
+    fn GetFragDataLocation(&self, program: &WebGLProgram, name: DOMString) -> i32 {
+        handle_potential_webgl_error!(self.base, self.base.validate_ownership(program), return -1);
+        handle_potential_webgl_error!(self.base, program.get_frag_data_location(name), -1)
+    }
+
+    /// https://www.khronos.org/registry/webgl/specs/latest/2.0/#3.7.8
+    fn Uniform1ui(&self, location: Option<&WebGLUniformLocation>, val: u32) {
+        self.base.with_location(location, |location| {
+            match location.type_() {
+                constants::BOOL | constants::UNSIGNED_INT => {},
+                _ => return Err(InvalidOperation),
+            }
+            self.base
+                .send_command(WebGLCommand::Uniform1ui(location.id(), val));
+            Ok(())
+        });
+    }
+
+    /// https://www.khronos.org/registry/webgl/specs/latest/2.0/#3.7.8
+    fn Uniform2ui(&self, location: Option<&WebGLUniformLocation>, x: u32, y: u32) {
+        self.base.with_location(location, |location| {
+            match location.type_() {
+                constants::BOOL_VEC2 | constants::UNSIGNED_INT_VEC2 => {},
+                _ => return Err(InvalidOperation),
+            }
+            self.base
+                .send_command(WebGLCommand::Uniform2ui(location.id(), x, y));
+            Ok(())
+        });
+    }
+
+    /// https://www.khronos.org/registry/webgl/specs/latest/2.0/#3.7.8
+    fn Uniform3ui(&self, location: Option<&WebGLUniformLocation>, x: u32, y: u32, z: u32) {
+        self.base.with_location(location, |location| {
+            match location.type_() {
+                constants::BOOL_VEC3 | constants::UNSIGNED_INT_VEC3 => {},
+                _ => return Err(InvalidOperation),
+            }
+            self.base
+                .send_command(WebGLCommand::Uniform3ui(location.id(), x, y, z));
+            Ok(())
+        });
+    }
+
+    /// https://www.khronos.org/registry/webgl/specs/latest/2.0/#3.7.8
+    fn Uniform4ui(&self, location: Option<&WebGLUniformLocation>, x: u32, y: u32, z: u32, w: u32) {
+        self.base.with_location(location, |location| {
+            match location.type_() {
+                constants::BOOL_VEC4 | constants::UNSIGNED_INT_VEC4 => {},
+                _ => return Err(InvalidOperation),
+            }
+            self.base
+                .send_command(WebGLCommand::Uniform4ui(location.id(), x, y, z, w));
+            Ok(())
+        });
+    }
+
+    /// https://www.khronos.org/registry/webgl/specs/latest/2.0/#3.7.8
+    fn VertexAttribI4i(&self, index: u32, x: i32, y: i32, z: i32, w: i32) {
+        if index >= self.base.limits().max_vertex_attribs {
+            return self.base.webgl_error(InvalidValue);
+        }
+        self.base
+            .send_command(WebGLCommand::VertexAttribI(index, x, y, z, w));
+    }
+
+    /// https://www.khronos.org/registry/webgl/specs/latest/2.0/#3.7.8
+    fn VertexAttribI4iv(&self, index: u32, v: Int32ArrayOrLongSequence) {
+        let values = match v {
+            Int32ArrayOrLongSequence::Int32Array(v) => v.to_vec(),
+            Int32ArrayOrLongSequence::LongSequence(v) => v,
+        };
+        if values.len() < 4 {
+            return self.base.webgl_error(InvalidValue);
+        }
+        self.VertexAttribI4i(index, values[0], values[1], values[2], values[3]);
+    }
+
+    /// https://www.khronos.org/registry/webgl/specs/latest/2.0/#3.7.8
+    fn VertexAttribI4ui(&self, index: u32, x: u32, y: u32, z: u32, w: u32) {
+        if index >= self.base.limits().max_vertex_attribs {
+            return self.base.webgl_error(InvalidValue);
+        }
+        self.base
+            .send_command(WebGLCommand::VertexAttribUI(index, x, y, z, w));
+    }
+
+    /// https://www.khronos.org/registry/webgl/specs/latest/2.0/#3.7.8
+    fn VertexAttribI4uiv(&self, index: u32, v: Uint32ArrayOrUnsignedLongSequence) {
+        let values = match v {
+            Uint32ArrayOrUnsignedLongSequence::Uint32Array(v) => v.to_vec(),
+            Uint32ArrayOrUnsignedLongSequence::UnsignedLongSequence(v) => v,
+        };
+        if values.len() < 4 {
+            return self.base.webgl_error(InvalidValue);
+        }
+        self.VertexAttribI4ui(index, values[0], values[1], values[2], values[3]);
+    }
+
+    /// https://www.khronos.org/registry/webgl/specs/latest/2.0/#3.7.8
+    fn VertexAttribIPointer(&self, index: u32, size: i32, type_: u32, stride: i32, offset: i64) {
+        handle_potential_webgl_error!(
+            self.base,
+            self.base
+                .current_vao()
+                .vertex_attrib_i_pointer(index, size, type_, stride, offset)
+        );
+    }
+
+    /// https://www.khronos.org/registry/webgl/specs/latest/2.0/#3.7.13
+    fn CreateSampler(&self) -> Option<DomRoot<WebGLSampler>> {
+        WebGLSampler::maybe_new(&self.base)
+    }
+
+    /// https://www.khronos.org/registry/webgl/specs/latest/2.0/#3.7.13
+    fn DeleteSampler(&self, sampler: Option<&WebGLSampler>) {
+        let sampler = match sampler {
+            Some(sampler) => sampler,
+            None => return,
+        };
+        handle_potential_webgl_error!(self.base, self.base.validate_ownership(sampler), return);
+        // Deleting a sampler unbinds it from all the texture units.
+        for slot in self.samplers.iter() {
+            if slot.get().map_or(false, |s| sampler == &*s) {
+                slot.set(None);
+            }
+        }
+        sampler.delete();
+    }
+
+    /// https://www.khronos.org/registry/webgl/specs/latest/2.0/#3.7.13
+    fn IsSampler(&self, sampler: Option<&WebGLSampler>) -> bool {
+        sampler.map_or(false, |sampler| {
+            self.base.validate_ownership(sampler).is_ok() && !sampler.is_deleted()
+        })
+    }
+
+    /// https://www.khronos.org/registry/webgl/specs/latest/2.0/#3.7.13
+    fn BindSampler(&self, unit: u32, sampler: Option<&WebGLSampler>) {
+        let slot = match self.samplers.get(unit as usize) {
+            Some(slot) => slot,
+            None => return self.base.webgl_error(InvalidValue),
+        };
+        if let Some(sampler) = sampler {
+            handle_potential_webgl_error!(self.base, self.base.validate_ownership(sampler), return);
+            if sampler.is_deleted() {
+                return self.base.webgl_error(InvalidOperation);
+            }
+        }
+        self.base
+            .send_command(WebGLCommand::BindSampler(unit, sampler.map(|s| s.id())));
+        slot.set(sampler);
+    }
+
+    /// https://www.khronos.org/registry/webgl/specs/latest/2.0/#3.7.13
+    fn SamplerParameteri(&self, sampler: &WebGLSampler, pname: u32, param: i32) {
+        handle_potential_webgl_error!(self.base, self.base.validate_ownership(sampler), return);
+        handle_potential_webgl_error!(
+            self.base,
+            sampler.set_parameter(pname, SamplerParameterValue::Int(param))
+        );
+    }
+
+    /// https://www.khronos.org/registry/webgl/specs/latest/2.0/#3.7.13
+    fn SamplerParameterf(&self, sampler: &WebGLSampler, pname: u32, param: f32) {
+        handle_potential_webgl_error!(self.base, self.base.validate_ownership(sampler), return);
+        handle_potential_webgl_error!(
+            self.base,
+            sampler.set_parameter(pname, SamplerParameterValue::Float(param))
+        );
+    }
+
+    #[allow(unsafe_code)]
+    /// https://www.khronos.org/registry/webgl/specs/latest/2.0/#3.7.13
+    unsafe fn GetSamplerParameter(
+        &self,
+        _cx: *mut JSContext,
+        sampler: &WebGLSampler,
+        pname: u32,
+    ) -> JSVal {
+        handle_potential_webgl_error!(
+            self.base,
+            self.base.validate_ownership(sampler),
+            return NullValue()
+        );
+        match handle_potential_webgl_error!(
+            self.base,
+            sampler.get_parameter(pname),
+            return NullValue()
+        ) {
+            SamplerParameterValue::Int(value) => Int32Value(value),
+            SamplerParameterValue::Float(value) => DoubleValue(value as f64),
+        }
+    }
+
+    /// https://www.khronos.org/registry/webgl/specs/latest/2.0/#3.7.15
+    fn CreateTransformFeedback(&self) -> Option<DomRoot<WebGLTransformFeedback>> {
+        WebGLTransformFeedback::maybe_new(&self.base)
+    }
+
+    /// https://www.khronos.org/registry/webgl/specs/latest/2.0/#3.7.15
+    fn DeleteTransformFeedback(&self, tf: Option<&WebGLTransformFeedback>) {
+        let tf = match tf {
+            Some(tf) => tf,
+            None => return,
+        };
+        handle_potential_webgl_error!(self.base, self.base.validate_ownership(tf), return);
+        if tf.active().get().is_some() {
+            return self.base.webgl_error(InvalidOperation);
+        }
+        if self
+            .current_transform_feedback
+            .get()
+            .map_or(false, |current| tf == &*current)
+        {
+            self.current_transform_feedback.set(None);
+        }
+        tf.delete();
+    }
+
+    /// https://www.khronos.org/registry/webgl/specs/latest/2.0/#3.7.15
+    fn IsTransformFeedback(&self, tf: Option<&WebGLTransformFeedback>) -> bool {
+        tf.map_or(false, |tf| {
+            self.base.validate_ownership(tf).is_ok() && tf.ever_bound() && !tf.is_deleted()
+        })
+    }
+
+    /// https://www.khronos.org/registry/webgl/specs/latest/2.0/#3.7.15
+    fn BindTransformFeedback(&self, target: u32, tf: Option<&WebGLTransformFeedback>) {
+        if target != constants::TRANSFORM_FEEDBACK {
+            return self.base.webgl_error(InvalidEnum);
+        }
+        if let Some(tf) = tf {
+            handle_potential_webgl_error!(self.base, self.base.validate_ownership(tf), return);
+            if tf.is_deleted() {
+                return self.base.webgl_error(InvalidOperation);
+            }
+        }
+        // The binding can't change while the current transform feedback is
+        // active and not paused.
+        if self
+            .transform_feedback_state()
+            .map_or(false, |state| !state.paused)
+        {
+            return self.base.webgl_error(InvalidOperation);
+        }
+        self.base.send_command(WebGLCommand::BindTransformFeedback(
+            target,
+            tf.map(|tf| tf.id()),
+        ));
+        if let Some(tf) = tf {
+            tf.set_ever_bound();
+        }
+        self.current_transform_feedback.set(tf);
+    }
+
+    /// https://www.khronos.org/registry/webgl/specs/latest/2.0/#3.7.15
+    fn BeginTransformFeedback(&self, primitive_mode: u32) {
+        match primitive_mode {
+            constants::POINTS | constants::LINES | constants::TRIANGLES => {},
+            _ => return self.base.webgl_error(InvalidEnum),
+        }
+        if self.transform_feedback_state().is_some() {
+            return self.base.webgl_error(InvalidOperation);
+        }
+        let program = match self.base.current_program() {
+            Some(program) if program.is_linked() => program,
+            _ => return self.base.webgl_error(InvalidOperation),
+        };
+
+        // All the buffers the varyings are captured into must be bound.
+        let (count, buffer_mode) = program.transform_feedback_varyings_info();
+        let required_buffers = match buffer_mode {
+            constants::SEPARATE_ATTRIBS => count as usize,
+            _ => cmp::min(count, 1) as usize,
+        };
+        if count == 0 ||
+            self.indexed_transform_feedback_buffer_bindings
+                .iter()
+                .take(required_buffers)
+                .filter(|slot| slot.get().is_some())
+                .count() <
+                required_buffers
+        {
+            return self.base.webgl_error(InvalidOperation);
+        }
+
+        self.base
+            .send_command(WebGLCommand::BeginTransformFeedback(primitive_mode));
+        self.set_transform_feedback_state(Some(ActiveTransformFeedback {
+            primitive_mode,
+            program: program.id(),
+            paused: false,
+        }));
+    }
+
+    /// https://www.khronos.org/registry/webgl/specs/latest/2.0/#3.7.15
+    fn EndTransformFeedback(&self) {
+        if self.transform_feedback_state().is_none() {
+            return self.base.webgl_error(InvalidOperation);
+        }
+        self.base.send_command(WebGLCommand::EndTransformFeedback);
+        self.set_transform_feedback_state(None);
+    }
+
+    /// https://www.khronos.org/registry/webgl/specs/latest/2.0/#3.7.15
+    fn PauseTransformFeedback(&self) {
+        let state = match self.transform_feedback_state() {
+            Some(state) if !state.paused => state,
+            _ => return self.base.webgl_error(InvalidOperation),
+        };
+        self.base.send_command(WebGLCommand::PauseTransformFeedback);
+        self.set_transform_feedback_state(Some(ActiveTransformFeedback {
+            paused: true,
+            ..state
+        }));
+    }
+
+    /// https://www.khronos.org/registry/webgl/specs/latest/2.0/#3.7.15
+    fn ResumeTransformFeedback(&self) {
+        let state = match self.transform_feedback_state() {
+            Some(state) if state.paused => state,
+            _ => return self.base.webgl_error(InvalidOperation),
+        };
+        // The program in use must be the one the transform feedback began
+        // with.
+        if self
+            .base
+            .current_program()
+            .map_or(true, |program| program.id() != state.program)
+        {
+            return self.base.webgl_error(InvalidOperation);
+        }
+        self.base
+            .send_command(WebGLCommand::ResumeTransformFeedback);
+        self.set_transform_feedback_state(Some(ActiveTransformFeedback {
+            paused: false,
+            ..state
+        }));
+    }
+
+    /// https://www.khronos.org/registry/webgl/specs/latest/2.0/#3.7.15
+    fn TransformFeedbackVaryings(
+        &self,
+        program: &WebGLProgram,
+        varyings: Vec<DOMString>,
+        buffer_mode: u32,
+    ) {
+        handle_potential_webgl_error!(self.base, self.base.validate_ownership(program), return);
+        handle_potential_webgl_error!(
+            self.base,
+            program.transform_feedback_varyings(varyings, buffer_mode)
+        );
+    }
+
+    /// https://www.khronos.org/registry/webgl/specs/latest/2.0/#3.7.15
+    fn GetTransformFeedbackVarying(
+        &self,
+        program: &WebGLProgram,
+        index: u32,
+    ) -> Option<DomRoot<WebGLActiveInfo>> {
+        handle_potential_webgl_error!(
+            self.base,
+            self.base.validate_ownership(program),
+            return None
+        );
+        handle_potential_webgl_error!(
+            self.base,
+            program.get_transform_feedback_varying(index).map(Some),
+            None
+        )
+    }
+
+    /// https://www.khronos.org/registry/webgl/specs/latest/2.0/#3.7.16
+    fn BindBufferBase(&self, target: u32, index: u32, buffer: Option<&WebGLBuffer>) {
+        self.bind_buffer_indexed(target, index, buffer, None)
+    }
+
+    /// https://www.khronos.org/registry/webgl/specs/latest/2.0/#3.7.16
+    fn BindBufferRange(
+        &self,
+        target: u32,
+        index: u32,
+        buffer: Option<&WebGLBuffer>,
+        offset: i64,
+        size: i64,
+    ) {
+        self.bind_buffer_indexed(target, index, buffer, Some((offset, size)))
+    }
+
+    /// https://www.khronos.org/registry/webgl/specs/latest/2.0/#3.7.16
+    fn GetUniformBlockIndex(&self, program: &WebGLProgram, block_name: DOMString) -> u32 {
+        handle_potential_webgl_error!(
+            self.base,
+            self.base.validate_ownership(program),
+            return constants::INVALID_INDEX
+        );
+        handle_potential_webgl_error!(
+            self.base,
+            program.get_uniform_block_index(block_name),
+            constants::INVALID_INDEX
+        )
+    }
+
+    #[allow(unsafe_code)]
+    /// https://www.khronos.org/registry/webgl/specs/latest/2.0/#3.7.16
+    unsafe fn GetActiveUniformBlockParameter(
+        &self,
+        cx: *mut JSContext,
+        program: &WebGLProgram,
+        block_index: u32,
+        pname: u32,
+    ) -> JSVal {
+        handle_potential_webgl_error!(
+            self.base,
+            self.base.validate_ownership(program),
+            return NullValue()
+        );
+        let values = handle_potential_webgl_error!(
+            self.base,
+            program.get_active_uniform_block_parameter(block_index, pname),
+            return NullValue()
+        );
+        match pname {
+            constants::UNIFORM_BLOCK_BINDING |
+            constants::UNIFORM_BLOCK_DATA_SIZE |
+            constants::UNIFORM_BLOCK_ACTIVE_UNIFORMS => UInt32Value(values[0] as u32),
+            constants::UNIFORM_BLOCK_ACTIVE_UNIFORM_INDICES => {
+                let indices: Vec<u32> = values.iter().map(|&index| index as u32).collect();
+                rooted!(in(cx) let mut rval = ptr::null_mut::<JSObject>());
+                let _ = Uint32Array::create(cx, CreateWith::Slice(&indices), rval.handle_mut())
+                    .unwrap();
+                ObjectValue(rval.get())
+            },
+            _ => BooleanValue(values[0] != 0),
+        }
+    }
+
+    /// https://www.khronos.org/registry/webgl/specs/latest/2.0/#3.7.16
+    fn GetActiveUniformBlockName(
+        &self,
+        program: &WebGLProgram,
+        block_index: u32,
+    ) -> Option<DOMString> {
+        handle_potential_webgl_error!(
+            self.base,
+            self.base.validate_ownership(program),
+            return None
+        );
+        let name = handle_potential_webgl_error!(
+            self.base,
+            program.get_active_uniform_block_name(block_index),
+            return None
+        );
+        Some(DOMString::from(name))
+    }
+
+    /// https://www.khronos.org/registry/webgl/specs/latest/2.0/#3.7.16
+    fn UniformBlockBinding(&self, program: &WebGLProgram, block_index: u32, block_binding: u32) {
+        handle_potential_webgl_error!(self.base, self.base.validate_ownership(program), return);
+        handle_potential_webgl_error!(
+            self.base,
+            program.uniform_block_binding(block_index, block_binding)
+        )
+    }
 }
 
 impl LayoutCanvasWebGLRenderingContextHelpers for LayoutDom<WebGL2RenderingContext> {
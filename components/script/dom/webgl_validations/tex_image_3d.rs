@@ -0,0 +1,722 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use super::tex_image_2d::TexImageValidationError;
+use super::WebGLValidator;
+use crate::dom::bindings::codegen::Bindings::WebGL2RenderingContextBinding::WebGL2RenderingContextConstants as constants;
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::webglrenderingcontext::WebGLRenderingContext;
+use crate::dom::webgltexture::WebGLTexture;
+use canvas_traits::webgl::WebGLError::*;
+use canvas_traits::webgl::{WebGLError, WebGLResult};
+use js::jsapi::Type;
+
+/// The combinations of internal format, format and type accepted by
+/// texImage3D, as per table 3.2 of the OpenGL ES 3.0 specification.
+const FORMAT_COMBINATIONS: &[(u32, u32, &[u32])] = &[
+    (
+        constants::RGB,
+        constants::RGB,
+        &[constants::UNSIGNED_BYTE, constants::UNSIGNED_SHORT_5_6_5],
+    ),
+    (
+        constants::RGBA,
+        constants::RGBA,
+        &[
+            constants::UNSIGNED_BYTE,
+            constants::UNSIGNED_SHORT_4_4_4_4,
+            constants::UNSIGNED_SHORT_5_5_5_1,
+        ],
+    ),
+    (
+        constants::LUMINANCE_ALPHA,
+        constants::LUMINANCE_ALPHA,
+        &[constants::UNSIGNED_BYTE],
+    ),
+    (
+        constants::LUMINANCE,
+        constants::LUMINANCE,
+        &[constants::UNSIGNED_BYTE],
+    ),
+    (
+        constants::ALPHA,
+        constants::ALPHA,
+        &[constants::UNSIGNED_BYTE],
+    ),
+    (constants::R8, constants::RED, &[constants::UNSIGNED_BYTE]),
+    (constants::R8_SNORM, constants::RED, &[constants::BYTE]),
+    (
+        constants::R16F,
+        constants::RED,
+        &[constants::HALF_FLOAT, constants::FLOAT],
+    ),
+    (constants::R32F, constants::RED, &[constants::FLOAT]),
+    (
+        constants::R8UI,
+        constants::RED_INTEGER,
+        &[constants::UNSIGNED_BYTE],
+    ),
+    (constants::R8I, constants::RED_INTEGER, &[constants::BYTE]),
+    (
+        constants::R16UI,
+        constants::RED_INTEGER,
+        &[constants::UNSIGNED_SHORT],
+    ),
+    (constants::R16I, constants::RED_INTEGER, &[constants::SHORT]),
+    (
+        constants::R32UI,
+        constants::RED_INTEGER,
+        &[constants::UNSIGNED_INT],
+    ),
+    (constants::R32I, constants::RED_INTEGER, &[constants::INT]),
+    (constants::RG8, constants::RG, &[constants::UNSIGNED_BYTE]),
+    (constants::RG8_SNORM, constants::RG, &[constants::BYTE]),
+    (
+        constants::RG16F,
+        constants::RG,
+        &[constants::HALF_FLOAT, constants::FLOAT],
+    ),
+    (constants::RG32F, constants::RG, &[constants::FLOAT]),
+    (
+        constants::RG8UI,
+        constants::RG_INTEGER,
+        &[constants::UNSIGNED_BYTE],
+    ),
+    (constants::RG8I, constants::RG_INTEGER, &[constants::BYTE]),
+    (
+        constants::RG16UI,
+        constants::RG_INTEGER,
+        &[constants::UNSIGNED_SHORT],
+    ),
+    (constants::RG16I, constants::RG_INTEGER, &[constants::SHORT]),
+    (
+        constants::RG32UI,
+        constants::RG_INTEGER,
+        &[constants::UNSIGNED_INT],
+    ),
+    (constants::RG32I, constants::RG_INTEGER, &[constants::INT]),
+    (constants::RGB8, constants::RGB, &[constants::UNSIGNED_BYTE]),
+    (
+        constants::SRGB8,
+        constants::RGB,
+        &[constants::UNSIGNED_BYTE],
+    ),
+    (
+        constants::RGB565,
+        constants::RGB,
+        &[constants::UNSIGNED_BYTE, constants::UNSIGNED_SHORT_5_6_5],
+    ),
+    (constants::RGB8_SNORM, constants::RGB, &[constants::BYTE]),
+    (
+        constants::R11F_G11F_B10F,
+        constants::RGB,
+        &[
+            constants::UNSIGNED_INT_10F_11F_11F_REV,
+            constants::HALF_FLOAT,
+            constants::FLOAT,
+        ],
+    ),
+    (
+        constants::RGB9_E5,
+        constants::RGB,
+        &[
+            constants::UNSIGNED_INT_5_9_9_9_REV,
+            constants::HALF_FLOAT,
+            constants::FLOAT,
+        ],
+    ),
+    (
+        constants::RGB16F,
+        constants::RGB,
+        &[constants::HALF_FLOAT, constants::FLOAT],
+    ),
+    (constants::RGB32F, constants::RGB, &[constants::FLOAT]),
+    (
+        constants::RGB8UI,
+        constants::RGB_INTEGER,
+        &[constants::UNSIGNED_BYTE],
+    ),
+    (constants::RGB8I, constants::RGB_INTEGER, &[constants::BYTE]),
+    (
+        constants::RGB16UI,
+        constants::RGB_INTEGER,
+        &[constants::UNSIGNED_SHORT],
+    ),
+    (
+        constants::RGB16I,
+        constants::RGB_INTEGER,
+        &[constants::SHORT],
+    ),
+    (
+        constants::RGB32UI,
+        constants::RGB_INTEGER,
+        &[constants::UNSIGNED_INT],
+    ),
+    (constants::RGB32I, constants::RGB_INTEGER, &[constants::INT]),
+    (
+        constants::RGBA8,
+        constants::RGBA,
+        &[constants::UNSIGNED_BYTE],
+    ),
+    (
+        constants::SRGB8_ALPHA8,
+        constants::RGBA,
+        &[constants::UNSIGNED_BYTE],
+    ),
+    (constants::RGBA8_SNORM, constants::RGBA, &[constants::BYTE]),
+    (
+        constants::RGB5_A1,
+        constants::RGBA,
+        &[
+            constants::UNSIGNED_BYTE,
+            constants::UNSIGNED_SHORT_5_5_5_1,
+            constants::UNSIGNED_INT_2_10_10_10_REV,
+        ],
+    ),
+    (
+        constants::RGBA4,
+        constants::RGBA,
+        &[constants::UNSIGNED_BYTE, constants::UNSIGNED_SHORT_4_4_4_4],
+    ),
+    (
+        constants::RGB10_A2,
+        constants::RGBA,
+        &[constants::UNSIGNED_INT_2_10_10_10_REV],
+    ),
+    (
+        constants::RGBA16F,
+        constants::RGBA,
+        &[constants::HALF_FLOAT, constants::FLOAT],
+    ),
+    (constants::RGBA32F, constants::RGBA, &[constants::FLOAT]),
+    (
+        constants::RGBA8UI,
+        constants::RGBA_INTEGER,
+        &[constants::UNSIGNED_BYTE],
+    ),
+    (
+        constants::RGBA8I,
+        constants::RGBA_INTEGER,
+        &[constants::BYTE],
+    ),
+    (
+        constants::RGB10_A2UI,
+        constants::RGBA_INTEGER,
+        &[constants::UNSIGNED_INT_2_10_10_10_REV],
+    ),
+    (
+        constants::RGBA16UI,
+        constants::RGBA_INTEGER,
+        &[constants::UNSIGNED_SHORT],
+    ),
+    (
+        constants::RGBA16I,
+        constants::RGBA_INTEGER,
+        &[constants::SHORT],
+    ),
+    (
+        constants::RGBA32I,
+        constants::RGBA_INTEGER,
+        &[constants::INT],
+    ),
+    (
+        constants::RGBA32UI,
+        constants::RGBA_INTEGER,
+        &[constants::UNSIGNED_INT],
+    ),
+    (
+        constants::DEPTH_COMPONENT16,
+        constants::DEPTH_COMPONENT,
+        &[constants::UNSIGNED_SHORT, constants::UNSIGNED_INT],
+    ),
+    (
+        constants::DEPTH_COMPONENT24,
+        constants::DEPTH_COMPONENT,
+        &[constants::UNSIGNED_INT],
+    ),
+    (
+        constants::DEPTH_COMPONENT32F,
+        constants::DEPTH_COMPONENT,
+        &[constants::FLOAT],
+    ),
+    (
+        constants::DEPTH24_STENCIL8,
+        constants::DEPTH_STENCIL,
+        &[constants::UNSIGNED_INT_24_8],
+    ),
+    (
+        constants::DEPTH32F_STENCIL8,
+        constants::DEPTH_STENCIL,
+        &[constants::FLOAT_32_UNSIGNED_INT_24_8_REV],
+    ),
+];
+
+/// Returns whether `internal_format` is one of the sized internal formats
+/// accepted by texStorage2D and texStorage3D.
+pub fn is_sized_internal_format(internal_format: u32) -> bool {
+    match internal_format {
+        constants::RGB |
+        constants::RGBA |
+        constants::LUMINANCE_ALPHA |
+        constants::LUMINANCE |
+        constants::ALPHA => false,
+        _ => FORMAT_COMBINATIONS
+            .iter()
+            .any(|&(internal, _, _)| internal == internal_format),
+    }
+}
+
+fn is_depth_format(format: u32) -> bool {
+    format == constants::DEPTH_COMPONENT || format == constants::DEPTH_STENCIL
+}
+
+fn format_components(format: u32) -> Option<u32> {
+    Some(match format {
+        constants::RED |
+        constants::RED_INTEGER |
+        constants::ALPHA |
+        constants::LUMINANCE |
+        constants::DEPTH_COMPONENT => 1,
+        constants::RG | constants::RG_INTEGER | constants::LUMINANCE_ALPHA => 2,
+        constants::RGB | constants::RGB_INTEGER => 3,
+        constants::RGBA | constants::RGBA_INTEGER => 4,
+        // The depth and stencil components are packed in a single element.
+        constants::DEPTH_STENCIL => 1,
+        _ => return None,
+    })
+}
+
+/// Returns the size in bytes of an element of `data_type`, and whether that
+/// element packs all the components of a pixel.
+fn type_size(data_type: u32) -> Option<(u32, bool)> {
+    Some(match data_type {
+        constants::UNSIGNED_BYTE | constants::BYTE => (1, false),
+        constants::UNSIGNED_SHORT | constants::SHORT | constants::HALF_FLOAT => (2, false),
+        constants::UNSIGNED_INT | constants::INT | constants::FLOAT => (4, false),
+        constants::UNSIGNED_SHORT_5_6_5 |
+        constants::UNSIGNED_SHORT_4_4_4_4 |
+        constants::UNSIGNED_SHORT_5_5_5_1 => (2, true),
+        constants::UNSIGNED_INT_2_10_10_10_REV |
+        constants::UNSIGNED_INT_10F_11F_11F_REV |
+        constants::UNSIGNED_INT_5_9_9_9_REV |
+        constants::UNSIGNED_INT_24_8 => (4, true),
+        constants::FLOAT_32_UNSIGNED_INT_24_8_REV => (8, true),
+        _ => return None,
+    })
+}
+
+/// Validates the combination of `internal_format`, `format` and `data_type`
+/// and returns the size in bytes of a pixel.
+pub fn validate_format_combination(
+    internal_format: u32,
+    format: u32,
+    data_type: u32,
+) -> WebGLResult<u32> {
+    let components = format_components(format).ok_or(InvalidEnum)?;
+    let (element_size, packed) = type_size(data_type).ok_or(InvalidEnum)?;
+    let types = FORMAT_COMBINATIONS
+        .iter()
+        .find(|&&(internal, _, _)| internal == internal_format)
+        .map(|&(_, expected_format, types)| (expected_format, types));
+    match types {
+        None => Err(InvalidEnum),
+        Some((expected_format, types)) => {
+            if expected_format != format || !types.contains(&data_type) {
+                return Err(InvalidOperation);
+            }
+            Ok(if packed {
+                element_size
+            } else {
+                element_size * components
+            })
+        },
+    }
+}
+
+/// Returns the type of the typed arrays accepted as pixel data for
+/// `data_type`, if any.
+pub fn array_type_for_data_type(data_type: u32) -> Option<Type> {
+    Some(match data_type {
+        constants::UNSIGNED_BYTE => Type::Uint8,
+        constants::BYTE => Type::Int8,
+        constants::SHORT => Type::Int16,
+        constants::INT => Type::Int32,
+        constants::FLOAT => Type::Float32,
+        constants::UNSIGNED_SHORT |
+        constants::UNSIGNED_SHORT_5_6_5 |
+        constants::UNSIGNED_SHORT_4_4_4_4 |
+        constants::UNSIGNED_SHORT_5_5_5_1 |
+        constants::HALF_FLOAT => Type::Uint16,
+        constants::UNSIGNED_INT |
+        constants::UNSIGNED_INT_2_10_10_10_REV |
+        constants::UNSIGNED_INT_10F_11F_11F_REV |
+        constants::UNSIGNED_INT_5_9_9_9_REV |
+        constants::UNSIGNED_INT_24_8 => Type::Uint32,
+        _ => return None,
+    })
+}
+
+/// Returns the size in bytes of an element of `data_type`.
+pub fn element_size(data_type: u32) -> Option<u32> {
+    type_size(data_type).map(|(size, _)| size)
+}
+
+/// Returns the number of bytes of pixel data needed for an image of the
+/// given size. Rows are padded to `unpacking_alignment`, except for the last
+/// one.
+pub fn image_data_len(
+    width: u32,
+    height: u32,
+    depth: u32,
+    bytes_per_pixel: u32,
+    unpacking_alignment: u32,
+) -> usize {
+    if width == 0 || height == 0 || depth == 0 {
+        return 0;
+    }
+    let row_len = width as usize * bytes_per_pixel as usize;
+    let alignment = unpacking_alignment as usize;
+    let padded_row_len = (row_len + alignment - 1) / alignment * alignment;
+    padded_row_len * (height as usize * depth as usize - 1) + row_len
+}
+
+/// Returns the bound texture of the active texture unit for a texImage3D
+/// target.
+fn texture_for_target(
+    context: &WebGLRenderingContext,
+    target: u32,
+) -> WebGLResult<DomRoot<WebGLTexture>> {
+    match target {
+        constants::TEXTURE_3D | constants::TEXTURE_2D_ARRAY => {},
+        _ => return Err(InvalidEnum),
+    }
+    context
+        .textures()
+        .active_texture_slot(target, context.webgl_version())?
+        .get()
+        .ok_or(InvalidOperation)
+}
+
+fn validation_error(
+    context: &WebGLRenderingContext,
+    error: WebGLError,
+    reason: TexImageValidationError,
+) -> TexImageValidationError {
+    context.webgl_error(error);
+    reason
+}
+
+pub struct TexImage3DValidator<'a> {
+    context: &'a WebGLRenderingContext,
+    target: u32,
+    level: i32,
+    internal_format: u32,
+    width: i32,
+    height: i32,
+    depth: i32,
+    border: i32,
+    format: u32,
+    data_type: u32,
+}
+
+impl<'a> TexImage3DValidator<'a> {
+    pub fn new(
+        context: &'a WebGLRenderingContext,
+        target: u32,
+        level: i32,
+        internal_format: u32,
+        width: i32,
+        height: i32,
+        depth: i32,
+        border: i32,
+        format: u32,
+        data_type: u32,
+    ) -> Self {
+        TexImage3DValidator {
+            context,
+            target,
+            level,
+            internal_format,
+            width,
+            height,
+            depth,
+            border,
+            format,
+            data_type,
+        }
+    }
+}
+
+/// The validated result of a TexImage3DValidator-validated call.
+pub struct TexImage3DValidatorResult {
+    pub texture: DomRoot<WebGLTexture>,
+    pub target: u32,
+    pub level: u32,
+    pub internal_format: u32,
+    pub width: u32,
+    pub height: u32,
+    pub depth: u32,
+    pub format: u32,
+    pub data_type: u32,
+    /// The size in bytes of a single pixel of the data.
+    pub bytes_per_pixel: u32,
+}
+
+/// TexImage3D validator as per
+/// <https://www.khronos.org/registry/OpenGL-Refpages/es3.0/html/glTexImage3D.xhtml>
+impl<'a> WebGLValidator for TexImage3DValidator<'a> {
+    type ValidatedOutput = TexImage3DValidatorResult;
+    type Error = TexImageValidationError;
+
+    fn validate(self) -> Result<Self::ValidatedOutput, TexImageValidationError> {
+        let context = self.context;
+
+        // GL_INVALID_ENUM is generated if target is not GL_TEXTURE_3D or
+        // GL_TEXTURE_2D_ARRAY, and GL_INVALID_OPERATION if no texture is
+        // bound to it.
+        let texture = texture_for_target(context, self.target).map_err(|error| {
+            let reason = if error == InvalidEnum {
+                TexImageValidationError::InvalidTextureTarget(self.target)
+            } else {
+                TexImageValidationError::TextureTargetNotBound(self.target)
+            };
+            validation_error(context, error, reason)
+        })?;
+
+        // GL_INVALID_OPERATION is generated if the texture has an immutable
+        // format.
+        if texture.is_immutable() {
+            return Err(validation_error(
+                context,
+                InvalidOperation,
+                TexImageValidationError::TextureFormatMismatch,
+            ));
+        }
+
+        // GL_INVALID_VALUE is generated if level is less than 0.
+        if self.level < 0 {
+            return Err(validation_error(
+                context,
+                InvalidValue,
+                TexImageValidationError::NegativeLevel,
+            ));
+        }
+
+        // GL_INVALID_VALUE is generated if width, height or depth is less
+        // than 0.
+        if self.width < 0 || self.height < 0 || self.depth < 0 {
+            return Err(validation_error(
+                context,
+                InvalidValue,
+                TexImageValidationError::NegativeDimension,
+            ));
+        }
+
+        let level = self.level as u32;
+        let width = self.width as u32;
+        let height = self.height as u32;
+        let depth = self.depth as u32;
+
+        // GL_INVALID_VALUE is generated if level is greater than log_2(max),
+        // and if the dimensions are greater than what the implementation
+        // allows for the target.
+        let limits = context.limits();
+        let (max_size, max_depth) = if self.target == constants::TEXTURE_3D {
+            (
+                limits.max_3d_texture_size,
+                limits.max_3d_texture_size >> level,
+            )
+        } else {
+            (limits.max_tex_size, limits.max_array_texture_layers)
+        };
+        if max_size == 0 || level > 31 - max_size.leading_zeros() {
+            return Err(validation_error(
+                context,
+                InvalidValue,
+                TexImageValidationError::LevelTooHigh,
+            ));
+        }
+        if width > max_size >> level || height > max_size >> level || depth > max_depth {
+            return Err(validation_error(
+                context,
+                InvalidValue,
+                TexImageValidationError::TextureTooBig,
+            ));
+        }
+
+        // GL_INVALID_VALUE is generated if border is not 0.
+        if self.border != 0 {
+            return Err(validation_error(
+                context,
+                InvalidValue,
+                TexImageValidationError::InvalidBorder,
+            ));
+        }
+
+        // GL_INVALID_OPERATION is generated if target is GL_TEXTURE_3D and
+        // format is GL_DEPTH_COMPONENT or GL_DEPTH_STENCIL.
+        if self.target == constants::TEXTURE_3D && is_depth_format(self.format) {
+            return Err(validation_error(
+                context,
+                InvalidOperation,
+                TexImageValidationError::InvalidTextureFormat,
+            ));
+        }
+
+        let bytes_per_pixel =
+            validate_format_combination(self.internal_format, self.format, self.data_type)
+                .map_err(|error| {
+                    validation_error(
+                        context,
+                        error,
+                        TexImageValidationError::InvalidTypeForFormat,
+                    )
+                })?;
+
+        Ok(TexImage3DValidatorResult {
+            texture,
+            target: self.target,
+            level,
+            internal_format: self.internal_format,
+            width,
+            height,
+            depth,
+            format: self.format,
+            data_type: self.data_type,
+            bytes_per_pixel,
+        })
+    }
+}
+
+pub struct TexSubImage3DValidator<'a> {
+    context: &'a WebGLRenderingContext,
+    target: u32,
+    level: i32,
+    offset: (i32, i32, i32),
+    width: i32,
+    height: i32,
+    depth: i32,
+    format: u32,
+    data_type: u32,
+}
+
+impl<'a> TexSubImage3DValidator<'a> {
+    pub fn new(
+        context: &'a WebGLRenderingContext,
+        target: u32,
+        level: i32,
+        offset: (i32, i32, i32),
+        width: i32,
+        height: i32,
+        depth: i32,
+        format: u32,
+        data_type: u32,
+    ) -> Self {
+        TexSubImage3DValidator {
+            context,
+            target,
+            level,
+            offset,
+            width,
+            height,
+            depth,
+            format,
+            data_type,
+        }
+    }
+}
+
+/// The validated result of a TexSubImage3DValidator-validated call.
+pub struct TexSubImage3DValidatorResult {
+    pub target: u32,
+    pub level: u32,
+    pub width: u32,
+    pub height: u32,
+    pub depth: u32,
+    /// The size in bytes of a single pixel of the data.
+    pub bytes_per_pixel: u32,
+}
+
+/// TexSubImage3D validator as per
+/// <https://www.khronos.org/registry/OpenGL-Refpages/es3.0/html/glTexSubImage3D.xhtml>
+impl<'a> WebGLValidator for TexSubImage3DValidator<'a> {
+    type ValidatedOutput = TexSubImage3DValidatorResult;
+    type Error = TexImageValidationError;
+
+    fn validate(self) -> Result<Self::ValidatedOutput, TexImageValidationError> {
+        let context = self.context;
+
+        let texture = texture_for_target(context, self.target).map_err(|error| {
+            let reason = if error == InvalidEnum {
+                TexImageValidationError::InvalidTextureTarget(self.target)
+            } else {
+                TexImageValidationError::TextureTargetNotBound(self.target)
+            };
+            validation_error(context, error, reason)
+        })?;
+
+        if self.level < 0 {
+            return Err(validation_error(
+                context,
+                InvalidValue,
+                TexImageValidationError::NegativeLevel,
+            ));
+        }
+        let level = self.level as u32;
+
+        // GL_INVALID_OPERATION is generated if the texture array has not
+        // been defined by a previous texImage3D or texStorage3D operation.
+        let image_info = texture.image_info_3d(level).ok_or_else(|| {
+            validation_error(
+                context,
+                InvalidOperation,
+                TexImageValidationError::TextureTargetNotBound(self.target),
+            )
+        })?;
+
+        if self.width < 0 || self.height < 0 || self.depth < 0 {
+            return Err(validation_error(
+                context,
+                InvalidValue,
+                TexImageValidationError::NegativeDimension,
+            ));
+        }
+
+        // GL_INVALID_VALUE is generated if the offsets are negative or if
+        // the region goes beyond the image of the level.
+        let (xoffset, yoffset, zoffset) = self.offset;
+        let fits = |offset: i32, size: i32, image_size: u32| {
+            offset >= 0 && (offset as i64 + size as i64) <= image_size as i64
+        };
+        if !fits(xoffset, self.width, image_info.width) ||
+            !fits(yoffset, self.height, image_info.height) ||
+            !fits(zoffset, self.depth, image_info.depth)
+        {
+            return Err(validation_error(
+                context,
+                InvalidValue,
+                TexImageValidationError::TextureTooBig,
+            ));
+        }
+
+        let bytes_per_pixel =
+            validate_format_combination(image_info.internal_format, self.format, self.data_type)
+                .map_err(|error| {
+                    validation_error(
+                        context,
+                        error,
+                        TexImageValidationError::InvalidTypeForFormat,
+                    )
+                })?;
+
+        Ok(TexSubImage3DValidatorResult {
+            target: self.target,
+            level,
+            width: self.width as u32,
+            height: self.height as u32,
+            depth: self.depth as u32,
+            bytes_per_pixel,
+        })
+    }
+}
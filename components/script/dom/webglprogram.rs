@@ -4,6 +4,7 @@
 
 // https://www.khronos.org/registry/webgl/specs/latest/1.0/webgl.idl
 use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::WebGL2RenderingContextBinding::WebGL2RenderingContextConstants as constants2;
 use crate::dom::bindings::codegen::Bindings::WebGLProgramBinding;
 use crate::dom::bindings::codegen::Bindings::WebGLRenderingContextBinding::WebGLRenderingContextConstants as constants;
 use crate::dom::bindings::inheritance::Castable;
@@ -437,6 +438,197 @@ impl WebGLProgram {
     pub fn link_generation(&self) -> u64 {
         self.link_generation.get()
     }
+
+    /// glGetFragDataLocation
+    pub fn get_frag_data_location(&self, name: DOMString) -> WebGLResult<i32> {
+        if !self.is_linked() || self.is_deleted() {
+            return Err(WebGLError::InvalidOperation);
+        }
+
+        if !validate_glsl_name(&name)? {
+            return Ok(-1);
+        }
+        if name.starts_with("gl_") {
+            return Ok(-1);
+        }
+
+        let (sender, receiver) = webgl_channel().unwrap();
+        self.upcast::<WebGLObject>()
+            .context()
+            .send_command(WebGLCommand::GetFragDataLocation(
+                self.id,
+                name.into(),
+                sender,
+            ));
+        Ok(receiver.recv().unwrap())
+    }
+
+    /// glTransformFeedbackVaryings
+    pub fn transform_feedback_varyings(
+        &self,
+        varyings: Vec<DOMString>,
+        buffer_mode: u32,
+    ) -> WebGLResult<()> {
+        if self.is_deleted() {
+            return Err(WebGLError::InvalidValue);
+        }
+        let context = self.upcast::<WebGLObject>().context();
+        match buffer_mode {
+            constants2::INTERLEAVED_ATTRIBS => {},
+            constants2::SEPARATE_ATTRIBS => {
+                let max = context.limits().max_transform_feedback_separate_attribs as usize;
+                if varyings.len() > max {
+                    return Err(WebGLError::InvalidValue);
+                }
+            },
+            _ => return Err(WebGLError::InvalidEnum),
+        }
+        for varying in &varyings {
+            validate_glsl_name(varying)?;
+        }
+
+        context.send_command(WebGLCommand::TransformFeedbackVaryings(
+            self.id,
+            varyings.into_iter().map(String::from).collect(),
+            buffer_mode,
+        ));
+        Ok(())
+    }
+
+    /// Returns the number of transform feedback varyings of the program
+    /// as of its last link, and their buffer mode.
+    pub fn transform_feedback_varyings_info(&self) -> (u32, u32) {
+        let (sender, receiver) = webgl_channel().unwrap();
+        self.upcast::<WebGLObject>().context().send_command(
+            WebGLCommand::GetProgramTransformFeedbackVaryings(self.id, sender),
+        );
+        let (count, buffer_mode) = receiver.recv().unwrap();
+        (count as u32, buffer_mode)
+    }
+
+    /// glGetTransformFeedbackVarying
+    pub fn get_transform_feedback_varying(
+        &self,
+        index: u32,
+    ) -> WebGLResult<DomRoot<WebGLActiveInfo>> {
+        if self.is_deleted() {
+            return Err(WebGLError::InvalidValue);
+        }
+        let (count, _) = self.transform_feedback_varyings_info();
+        if index >= count {
+            return Err(WebGLError::InvalidValue);
+        }
+
+        let (sender, receiver) = webgl_channel().unwrap();
+        self.upcast::<WebGLObject>().context().send_command(
+            WebGLCommand::GetTransformFeedbackVarying(self.id, index, sender),
+        );
+        let (size, type_, name) = receiver.recv().unwrap();
+        Ok(WebGLActiveInfo::new(
+            self.global().as_window(),
+            size,
+            type_,
+            name.into(),
+        ))
+    }
+
+    fn active_uniform_block_count(&self) -> u32 {
+        let (sender, receiver) = webgl_channel().unwrap();
+        self.upcast::<WebGLObject>()
+            .context()
+            .send_command(WebGLCommand::GetProgramActiveUniformBlocks(self.id, sender));
+        receiver.recv().unwrap() as u32
+    }
+
+    /// glGetUniformBlockIndex
+    pub fn get_uniform_block_index(&self, name: DOMString) -> WebGLResult<u32> {
+        if self.is_deleted() {
+            return Err(WebGLError::InvalidValue);
+        }
+        if !self.is_linked() || !validate_glsl_name(&name)? {
+            return Ok(constants2::INVALID_INDEX);
+        }
+
+        let (sender, receiver) = webgl_channel().unwrap();
+        self.upcast::<WebGLObject>()
+            .context()
+            .send_command(WebGLCommand::GetUniformBlockIndex(
+                self.id,
+                name.into(),
+                sender,
+            ));
+        Ok(receiver.recv().unwrap())
+    }
+
+    /// glGetActiveUniformBlockName
+    pub fn get_active_uniform_block_name(&self, index: u32) -> WebGLResult<String> {
+        if self.is_deleted() {
+            return Err(WebGLError::InvalidValue);
+        }
+        if !self.is_linked() {
+            return Err(WebGLError::InvalidOperation);
+        }
+        if index >= self.active_uniform_block_count() {
+            return Err(WebGLError::InvalidValue);
+        }
+
+        let (sender, receiver) = webgl_channel().unwrap();
+        self.upcast::<WebGLObject>().context().send_command(
+            WebGLCommand::GetActiveUniformBlockName(self.id, index, sender),
+        );
+        Ok(receiver.recv().unwrap())
+    }
+
+    /// glGetActiveUniformBlockiv
+    pub fn get_active_uniform_block_parameter(
+        &self,
+        index: u32,
+        pname: u32,
+    ) -> WebGLResult<Vec<i32>> {
+        if self.is_deleted() {
+            return Err(WebGLError::InvalidValue);
+        }
+        if !self.is_linked() {
+            return Err(WebGLError::InvalidOperation);
+        }
+        match pname {
+            constants2::UNIFORM_BLOCK_BINDING |
+            constants2::UNIFORM_BLOCK_DATA_SIZE |
+            constants2::UNIFORM_BLOCK_ACTIVE_UNIFORMS |
+            constants2::UNIFORM_BLOCK_ACTIVE_UNIFORM_INDICES |
+            constants2::UNIFORM_BLOCK_REFERENCED_BY_VERTEX_SHADER |
+            constants2::UNIFORM_BLOCK_REFERENCED_BY_FRAGMENT_SHADER => {},
+            _ => return Err(WebGLError::InvalidEnum),
+        }
+        if index >= self.active_uniform_block_count() {
+            return Err(WebGLError::InvalidValue);
+        }
+
+        let (sender, receiver) = webgl_channel().unwrap();
+        self.upcast::<WebGLObject>().context().send_command(
+            WebGLCommand::GetActiveUniformBlockParameter(self.id, index, pname, sender),
+        );
+        Ok(receiver.recv().unwrap())
+    }
+
+    /// glUniformBlockBinding
+    pub fn uniform_block_binding(&self, index: u32, binding: u32) -> WebGLResult<()> {
+        if self.is_deleted() {
+            return Err(WebGLError::InvalidValue);
+        }
+        if !self.is_linked() {
+            return Err(WebGLError::InvalidOperation);
+        }
+        let context = self.upcast::<WebGLObject>().context();
+        if index >= self.active_uniform_block_count() ||
+            binding >= context.limits().max_uniform_buffer_bindings
+        {
+            return Err(WebGLError::InvalidValue);
+        }
+
+        context.send_command(WebGLCommand::UniformBlockBinding(self.id, index, binding));
+        Ok(())
+    }
 }
 
 impl Drop for WebGLProgram {
@@ -5,6 +5,7 @@
 use crate::dom::bindings::codegen::Bindings::ANGLEInstancedArraysBinding::ANGLEInstancedArraysConstants;
 use crate::dom::bindings::codegen::Bindings::EXTBlendMinmaxBinding::EXTBlendMinmaxConstants;
 use crate::dom::bindings::codegen::Bindings::OESVertexArrayObjectBinding::OESVertexArrayObjectConstants;
+use crate::dom::bindings::codegen::Bindings::WebGL2RenderingContextBinding::WebGL2RenderingContextConstants;
 use crate::dom::bindings::codegen::Bindings::WebGLRenderingContextBinding;
 use crate::dom::bindings::codegen::Bindings::WebGLRenderingContextBinding::TexImageSource;
 use crate::dom::bindings::codegen::Bindings::WebGLRenderingContextBinding::WebGLContextAttributes;
@@ -29,6 +30,13 @@ use crate::dom::webgl_validations::tex_image_2d::{
     CommonTexImage2DValidator, CommonTexImage2DValidatorResult,
 };
 use crate::dom::webgl_validations::tex_image_2d::{TexImage2DValidator, TexImage2DValidatorResult};
+use crate::dom::webgl_validations::tex_image_3d::{
+    array_type_for_data_type, element_size, image_data_len, is_sized_internal_format,
+};
+use crate::dom::webgl_validations::tex_image_3d::{TexImage3DValidator, TexImage3DValidatorResult};
+use crate::dom::webgl_validations::tex_image_3d::{
+    TexSubImage3DValidator, TexSubImage3DValidatorResult,
+};
 use crate::dom::webgl_validations::types::TexImageTarget;
 use crate::dom::webgl_validations::WebGLValidator;
 use crate::dom::webglactiveinfo::WebGLActiveInfo;
@@ -42,7 +50,7 @@ use crate::dom::webglprogram::WebGLProgram;
 use crate::dom::webglrenderbuffer::WebGLRenderbuffer;
 use crate::dom::webglshader::WebGLShader;
 use crate::dom::webglshaderprecisionformat::WebGLShaderPrecisionFormat;
-use crate::dom::webgltexture::{TexParameterValue, WebGLTexture};
+use crate::dom::webgltexture::{ImageInfo3D, TexParameterValue, WebGLTexture};
 use crate::dom::webgluniformlocation::WebGLUniformLocation;
 use crate::dom::webglvertexarrayobjectoes::WebGLVertexArrayObjectOES;
 use crate::dom::window::Window;
@@ -97,16 +105,6 @@ macro_rules! handle_object_deletion {
     };
 }
 
-macro_rules! optional_root_object_to_js_or_null {
-    ($cx: expr, $binding:expr) => {{
-        rooted!(in($cx) let mut rval = NullValue());
-        if let Some(object) = $binding {
-            object.to_jsval($cx, rval.handle_mut());
-        }
-        rval.get()
-    }};
-}
-
 fn has_invalid_blend_constants(arg1: u32, arg2: u32) -> bool {
     match (arg1, arg2) {
         (constants::CONSTANT_COLOR, constants::CONSTANT_ALPHA) => true,
@@ -255,7 +253,15 @@ impl WebGLRenderingContext {
         &self.limits
     }
 
-    fn current_vao(&self) -> DomRoot<WebGLVertexArrayObjectOES> {
+    pub fn webgl_version(&self) -> WebGLVersion {
+        self.webgl_version
+    }
+
+    pub fn current_program(&self) -> Option<DomRoot<WebGLProgram>> {
+        self.current_program.get()
+    }
+
+    pub fn current_vao(&self) -> DomRoot<WebGLVertexArrayObjectOES> {
         self.current_vao.or_init(|| {
             DomRoot::from_ref(
                 self.default_vao
@@ -294,7 +300,7 @@ impl WebGLRenderingContext {
         // Send a command to re-bind the TEXTURE_2D, if any.
         if let Some(texture) = self
             .textures
-            .active_texture_slot(constants::TEXTURE_2D)
+            .active_texture_slot(constants::TEXTURE_2D, self.webgl_version)
             .unwrap()
             .get()
         {
@@ -348,6 +354,13 @@ impl WebGLRenderingContext {
         self.size.get()
     }
 
+    /// Context loss isn't reported to content yet, so like
+    /// getContextAttributes() we assume that the context is lost once its
+    /// WebGL thread can't be reached anymore
+    pub fn is_context_lost(&self) -> bool {
+        self.GetContextAttributes().is_none()
+    }
+
     // Helper function for validating framebuffer completeness in
     // calls touching the framebuffer.  From the GLES 2.0.25 spec,
     // page 119:
@@ -377,7 +390,7 @@ impl WebGLRenderingContext {
         }
     }
 
-    fn validate_ownership<T>(&self, object: &T) -> WebGLResult<()>
+    pub fn validate_ownership<T>(&self, object: &T) -> WebGLResult<()>
     where
         T: DerivedFrom<WebGLObject>,
     {
@@ -387,7 +400,7 @@ impl WebGLRenderingContext {
         Ok(())
     }
 
-    fn with_location<F>(&self, location: Option<&WebGLUniformLocation>, f: F)
+    pub fn with_location<F>(&self, location: Option<&WebGLUniformLocation>, f: F)
     where
         F: FnOnce(&WebGLUniformLocation) -> WebGLResult<()>,
     {
@@ -404,13 +417,41 @@ impl WebGLRenderingContext {
         handle_potential_webgl_error!(self, f(location));
     }
 
+    /// Whether uniforms of type `type_` are samplers in this context.
+    fn is_sampler_type(&self, type_: u32) -> bool {
+        match (type_, self.webgl_version) {
+            (constants::SAMPLER_2D, _) | (constants::SAMPLER_CUBE, _) => true,
+            (WebGL2RenderingContextConstants::SAMPLER_3D, WebGLVersion::WebGL2) |
+            (WebGL2RenderingContextConstants::SAMPLER_2D_ARRAY, WebGLVersion::WebGL2) |
+            (WebGL2RenderingContextConstants::SAMPLER_2D_SHADOW, WebGLVersion::WebGL2) |
+            (WebGL2RenderingContextConstants::SAMPLER_CUBE_SHADOW, WebGLVersion::WebGL2) |
+            (WebGL2RenderingContextConstants::SAMPLER_2D_ARRAY_SHADOW, WebGLVersion::WebGL2) |
+            (WebGL2RenderingContextConstants::INT_SAMPLER_2D, WebGLVersion::WebGL2) |
+            (WebGL2RenderingContextConstants::INT_SAMPLER_3D, WebGLVersion::WebGL2) |
+            (WebGL2RenderingContextConstants::INT_SAMPLER_CUBE, WebGLVersion::WebGL2) |
+            (WebGL2RenderingContextConstants::INT_SAMPLER_2D_ARRAY, WebGLVersion::WebGL2) |
+            (WebGL2RenderingContextConstants::UNSIGNED_INT_SAMPLER_2D, WebGLVersion::WebGL2) |
+            (WebGL2RenderingContextConstants::UNSIGNED_INT_SAMPLER_3D, WebGLVersion::WebGL2) |
+            (WebGL2RenderingContextConstants::UNSIGNED_INT_SAMPLER_CUBE, WebGLVersion::WebGL2) |
+            (
+                WebGL2RenderingContextConstants::UNSIGNED_INT_SAMPLER_2D_ARRAY,
+                WebGLVersion::WebGL2,
+            ) => true,
+            _ => false,
+        }
+    }
+
     pub fn textures(&self) -> &Textures {
         &self.textures
     }
 
     fn tex_parameter(&self, target: u32, param: u32, value: TexParameterValue) {
-        let texture_slot =
-            handle_potential_webgl_error!(self, self.textures.active_texture_slot(target), return);
+        let texture_slot = handle_potential_webgl_error!(
+            self,
+            self.textures
+                .active_texture_slot(target, self.webgl_version),
+            return
+        );
         let texture =
             handle_potential_webgl_error!(self, texture_slot.get().ok_or(InvalidOperation), return);
 
@@ -539,6 +580,18 @@ impl WebGLRenderingContext {
 
     fn get_image_pixels(&self, source: TexImageSource) -> Fallible<Option<TexPixels>> {
         Ok(Some(match source {
+            TexImageSource::ImageBitmap(bitmap) => {
+                if !bitmap.origin_is_clean() {
+                    return Err(Error::Security);
+                }
+                let data = bitmap.bitmap_data().ok_or(Error::InvalidState)?;
+                TexPixels::new(
+                    IpcSharedMemory::from_bytes(&data),
+                    bitmap.get_size(),
+                    PixelFormat::BGRA8,
+                    true,
+                )
+            },
             TexImageSource::ImageData(image_data) => TexPixels::new(
                 image_data.to_shared_memory(),
                 image_data.get_size(),
@@ -667,7 +720,8 @@ impl WebGLRenderingContext {
                 format,
                 level,
                 Some(data_type)
-            )
+            ),
+            return
         );
 
         let settings = self.texture_unpacking_settings.get();
@@ -741,8 +795,7 @@ impl WebGLRenderingContext {
         }
 
         // NB: format and internal_format must match.
-        if format != image_info.internal_format().unwrap() ||
-            data_type != image_info.data_type().unwrap()
+        if Some(format) != image_info.internal_format() || Some(data_type) != image_info.data_type()
         {
             return self.webgl_error(InvalidOperation);
         }
@@ -966,6 +1019,333 @@ impl WebGLRenderingContext {
         self.send_command(WebGLCommand::VertexAttribDivisor { index, divisor });
     }
 
+    /// https://www.khronos.org/registry/webgl/specs/latest/2.0/#3.7.6
+    #[allow(unsafe_code)]
+    pub fn tex_image_3d(
+        &self,
+        target: u32,
+        level: i32,
+        internal_format: u32,
+        width: i32,
+        height: i32,
+        depth: i32,
+        border: i32,
+        format: u32,
+        data_type: u32,
+        data: &Option<ArrayBufferView>,
+    ) {
+        let validator = TexImage3DValidator::new(
+            self,
+            target,
+            level,
+            internal_format,
+            width,
+            height,
+            depth,
+            border,
+            format,
+            data_type,
+        );
+        let TexImage3DValidatorResult {
+            texture,
+            target,
+            level,
+            internal_format,
+            width,
+            height,
+            depth,
+            format,
+            data_type,
+            bytes_per_pixel,
+        } = match validator.validate() {
+            Ok(result) => result,
+            Err(_) => return, // NB: The validator sets the correct error for us.
+        };
+
+        let unpacking_alignment = self.texture_unpacking_alignment.get();
+        let expected_byte_length =
+            image_data_len(width, height, depth, bytes_per_pixel, unpacking_alignment);
+
+        // If data is null, a buffer of sufficient size initialized to 0 is
+        // passed.
+        let buff = match *data {
+            None => IpcSharedMemory::from_bytes(&vec![0u8; expected_byte_length]),
+            Some(ref data) => {
+                let data = handle_potential_webgl_error!(
+                    self,
+                    self.validate_tex_image_3d_data(data, data_type, 0, expected_byte_length),
+                    return
+                );
+                IpcSharedMemory::from_bytes(unsafe { &data.as_slice()[..expected_byte_length] })
+            },
+        };
+
+        texture.initialize_3d(
+            level,
+            ImageInfo3D {
+                width,
+                height,
+                depth,
+                internal_format,
+            },
+        );
+        self.send_command(WebGLCommand::TexImage3D {
+            target,
+            level,
+            internal_format,
+            width,
+            height,
+            depth,
+            format,
+            data_type,
+            unpacking_alignment,
+            data: buff.into(),
+        });
+    }
+
+    /// https://www.khronos.org/registry/webgl/specs/latest/2.0/#3.7.6
+    #[allow(unsafe_code)]
+    pub fn tex_sub_image_3d(
+        &self,
+        target: u32,
+        level: i32,
+        offset: (i32, i32, i32),
+        width: i32,
+        height: i32,
+        depth: i32,
+        format: u32,
+        data_type: u32,
+        data: &Option<ArrayBufferView>,
+        src_offset: u32,
+    ) {
+        let data = match *data {
+            Some(ref data) => data,
+            None => return self.webgl_error(InvalidValue),
+        };
+        let validator = TexSubImage3DValidator::new(
+            self, target, level, offset, width, height, depth, format, data_type,
+        );
+        let TexSubImage3DValidatorResult {
+            target,
+            level,
+            width,
+            height,
+            depth,
+            bytes_per_pixel,
+        } = match validator.validate() {
+            Ok(result) => result,
+            Err(_) => return, // NB: The validator sets the correct error for us.
+        };
+
+        let unpacking_alignment = self.texture_unpacking_alignment.get();
+        let expected_byte_length =
+            image_data_len(width, height, depth, bytes_per_pixel, unpacking_alignment);
+        let data = handle_potential_webgl_error!(
+            self,
+            self.validate_tex_image_3d_data(data, data_type, src_offset, expected_byte_length),
+            return
+        );
+        let start = src_offset as usize * element_size(data_type).unwrap_or(1) as usize;
+        let buff = IpcSharedMemory::from_bytes(unsafe {
+            &data.as_slice()[start..start + expected_byte_length]
+        });
+
+        let (xoffset, yoffset, zoffset) = offset;
+        self.send_command(WebGLCommand::TexSubImage3D {
+            target,
+            level,
+            xoffset,
+            yoffset,
+            zoffset,
+            width,
+            height,
+            depth,
+            format,
+            data_type,
+            unpacking_alignment,
+            data: buff.into(),
+        });
+    }
+
+    /// Checks the pixel data passed to texImage3D and texSubImage3D, which
+    /// must be a typed array matching `data_type` holding at least
+    /// `byte_length` bytes after the first `src_offset` elements.
+    #[allow(unsafe_code)]
+    fn validate_tex_image_3d_data<'a>(
+        &self,
+        data: &'a ArrayBufferView,
+        data_type: u32,
+        src_offset: u32,
+        byte_length: usize,
+    ) -> WebGLResult<&'a ArrayBufferView> {
+        // The flips and premultiplications are only done for DOM sources.
+        let settings = self.texture_unpacking_settings.get();
+        if settings.intersects(TextureUnpacking::FLIP_Y_AXIS | TextureUnpacking::PREMULTIPLY_ALPHA)
+        {
+            return Err(InvalidOperation);
+        }
+        let array_type = data.get_array_type();
+        if array_type_for_data_type(data_type) != Some(array_type) &&
+            !(array_type == Type::Uint8Clamped && data_type == constants::UNSIGNED_BYTE)
+        {
+            return Err(InvalidOperation);
+        }
+        let len = unsafe { data.as_slice().len() };
+        let start = src_offset as usize * element_size(data_type).unwrap_or(1) as usize;
+        if start > len {
+            return Err(InvalidValue);
+        }
+        if len - start < byte_length {
+            return Err(InvalidOperation);
+        }
+        Ok(data)
+    }
+
+    /// https://www.khronos.org/registry/webgl/specs/latest/2.0/#3.7.6
+    pub fn copy_tex_sub_image_3d(
+        &self,
+        target: u32,
+        level: i32,
+        xoffset: i32,
+        yoffset: i32,
+        zoffset: i32,
+        x: i32,
+        y: i32,
+        width: i32,
+        height: i32,
+    ) -> WebGLResult<()> {
+        self.validate_framebuffer()?;
+        match target {
+            WebGL2RenderingContextConstants::TEXTURE_3D |
+            WebGL2RenderingContextConstants::TEXTURE_2D_ARRAY => {},
+            _ => return Err(InvalidEnum),
+        }
+        let texture = self
+            .textures
+            .active_texture_slot(target, self.webgl_version)?
+            .get()
+            .ok_or(InvalidOperation)?;
+        if level < 0 || width < 0 || height < 0 {
+            return Err(InvalidValue);
+        }
+        let image_info = texture
+            .image_info_3d(level as u32)
+            .ok_or(InvalidOperation)?;
+
+        // GL_INVALID_VALUE is generated if the offsets are negative, or if
+        // the copied region goes beyond the image of the level.
+        if xoffset < 0 ||
+            xoffset as u32 + width as u32 > image_info.width ||
+            yoffset < 0 ||
+            yoffset as u32 + height as u32 > image_info.height ||
+            zoffset < 0 ||
+            zoffset as u32 >= image_info.depth
+        {
+            return Err(InvalidValue);
+        }
+
+        self.send_command(WebGLCommand::CopyTexSubImage3D(
+            target, level, xoffset, yoffset, zoffset, x, y, width, height,
+        ));
+        Ok(())
+    }
+
+    /// https://www.khronos.org/registry/webgl/specs/latest/2.0/#3.7.6
+    pub fn tex_storage(
+        &self,
+        target: u32,
+        levels: i32,
+        internal_format: u32,
+        width: i32,
+        height: i32,
+        depth: i32,
+    ) -> WebGLResult<()> {
+        let is_3d = match target {
+            constants::TEXTURE_2D | constants::TEXTURE_CUBE_MAP => false,
+            WebGL2RenderingContextConstants::TEXTURE_3D |
+            WebGL2RenderingContextConstants::TEXTURE_2D_ARRAY => true,
+            _ => return Err(InvalidEnum),
+        };
+        let texture = self
+            .textures
+            .active_texture_slot(target, self.webgl_version)?
+            .get()
+            .ok_or(InvalidOperation)?;
+        if texture.is_immutable() {
+            return Err(InvalidOperation);
+        }
+        if levels < 1 || width < 1 || height < 1 || depth < 1 {
+            return Err(InvalidValue);
+        }
+        if !is_sized_internal_format(internal_format) {
+            return Err(InvalidEnum);
+        }
+
+        let (levels, width, height, depth) =
+            (levels as u32, width as u32, height as u32, depth as u32);
+        let (max_size, max_depth) = match target {
+            constants::TEXTURE_2D => (self.limits.max_tex_size, 1),
+            constants::TEXTURE_CUBE_MAP => {
+                if width != height {
+                    return Err(InvalidValue);
+                }
+                (self.limits.max_cube_map_tex_size, 1)
+            },
+            WebGL2RenderingContextConstants::TEXTURE_3D => (
+                self.limits.max_3d_texture_size,
+                self.limits.max_3d_texture_size,
+            ),
+            _ => (
+                self.limits.max_tex_size,
+                self.limits.max_array_texture_layers,
+            ),
+        };
+        if width > max_size || height > max_size || depth > max_depth {
+            return Err(InvalidValue);
+        }
+
+        // GL_INVALID_OPERATION is generated if levels is greater than
+        // floor(log_2(max(width, height, depth))) + 1.
+        let largest = if target == WebGL2RenderingContextConstants::TEXTURE_3D {
+            cmp::max(cmp::max(width, height), depth)
+        } else {
+            cmp::max(width, height)
+        };
+        if levels > 32 - largest.leading_zeros() {
+            return Err(InvalidOperation);
+        }
+
+        if is_3d {
+            if target == WebGL2RenderingContextConstants::TEXTURE_3D &&
+                (internal_format == WebGL2RenderingContextConstants::DEPTH_COMPONENT16 ||
+                    internal_format == WebGL2RenderingContextConstants::DEPTH_COMPONENT24 ||
+                    internal_format == WebGL2RenderingContextConstants::DEPTH_COMPONENT32F ||
+                    internal_format == WebGL2RenderingContextConstants::DEPTH24_STENCIL8 ||
+                    internal_format == WebGL2RenderingContextConstants::DEPTH32F_STENCIL8)
+            {
+                return Err(InvalidOperation);
+            }
+            self.send_command(WebGLCommand::TexStorage3D(
+                target,
+                levels,
+                internal_format,
+                width,
+                height,
+                depth,
+            ));
+        } else {
+            self.send_command(WebGLCommand::TexStorage2D(
+                target,
+                levels,
+                internal_format,
+                width,
+                height,
+            ));
+        }
+        texture.storage(target, levels, internal_format, width, height, depth);
+        Ok(())
+    }
+
     // Used by HTMLCanvasElement.toDataURL
     //
     // This emits errors quite liberally, but the spec says that this operation
@@ -1005,6 +1385,111 @@ impl WebGLRenderingContext {
         }
     }
 
+    /// Binds `buffer` to `target`, storing it in `slot`.
+    pub fn bind_buffer_maybe(
+        &self,
+        slot: &MutNullableDom<WebGLBuffer>,
+        target: u32,
+        buffer: Option<&WebGLBuffer>,
+    ) {
+        if let Some(buffer) = buffer {
+            handle_potential_webgl_error!(self, self.validate_ownership(buffer), return);
+            if buffer.is_marked_for_deletion() {
+                return self.webgl_error(InvalidOperation);
+            }
+            handle_potential_webgl_error!(self, buffer.set_target(target), return);
+            buffer.increment_attached_counter();
+        }
+        self.send_command(WebGLCommand::BindBuffer(target, buffer.map(|b| b.id())));
+        if let Some(old) = slot.get() {
+            old.decrement_attached_counter();
+        }
+        slot.set(buffer);
+    }
+
+    #[allow(unsafe_code)]
+    pub fn buffer_data(
+        &self,
+        data: Option<ArrayBufferViewOrArrayBuffer>,
+        usage: u32,
+        bound_buffer: Option<DomRoot<WebGLBuffer>>,
+    ) {
+        let data = handle_potential_webgl_error!(self, data.ok_or(InvalidValue), return);
+        let bound_buffer =
+            handle_potential_webgl_error!(self, bound_buffer.ok_or(InvalidOperation), return);
+
+        let data = unsafe {
+            // Safe because we don't do anything with JS until the end of the method.
+            match data {
+                ArrayBufferViewOrArrayBuffer::ArrayBuffer(ref data) => data.as_slice(),
+                ArrayBufferViewOrArrayBuffer::ArrayBufferView(ref data) => data.as_slice(),
+            }
+        };
+        handle_potential_webgl_error!(self, bound_buffer.buffer_data(data, usage));
+    }
+
+    pub fn buffer_data_(&self, size: i64, usage: u32, bound_buffer: Option<DomRoot<WebGLBuffer>>) {
+        let bound_buffer =
+            handle_potential_webgl_error!(self, bound_buffer.ok_or(InvalidOperation), return);
+
+        if size < 0 {
+            return self.webgl_error(InvalidValue);
+        }
+
+        // FIXME: Allocating a buffer based on user-requested size is
+        // not great, but we don't have a fallible allocation to try.
+        let data = vec![0u8; size as usize];
+        handle_potential_webgl_error!(self, bound_buffer.buffer_data(&data, usage));
+    }
+
+    #[allow(unsafe_code)]
+    pub fn buffer_sub_data(
+        &self,
+        target: u32,
+        offset: i64,
+        data: ArrayBufferViewOrArrayBuffer,
+        bound_buffer: Option<DomRoot<WebGLBuffer>>,
+    ) {
+        let bound_buffer =
+            handle_potential_webgl_error!(self, bound_buffer.ok_or(InvalidOperation), return);
+
+        if offset < 0 {
+            return self.webgl_error(InvalidValue);
+        }
+
+        let data = unsafe {
+            // Safe because we don't do anything with JS until the end of the method.
+            match data {
+                ArrayBufferViewOrArrayBuffer::ArrayBuffer(ref data) => data.as_slice(),
+                ArrayBufferViewOrArrayBuffer::ArrayBufferView(ref data) => data.as_slice(),
+            }
+        };
+        if (offset as u64) + data.len() as u64 > bound_buffer.capacity() as u64 {
+            return self.webgl_error(InvalidValue);
+        }
+        let (sender, receiver) = ipc::bytes_channel().unwrap();
+        self.send_command(WebGLCommand::BufferSubData(
+            target,
+            offset as isize,
+            receiver,
+        ));
+        sender.send(data).unwrap();
+    }
+
+    pub fn get_buffer_param(&self, buffer: Option<DomRoot<WebGLBuffer>>, parameter: u32) -> JSVal {
+        let buffer =
+            handle_potential_webgl_error!(self, buffer.ok_or(InvalidOperation), return NullValue());
+
+        match parameter {
+            constants::BUFFER_SIZE => Int32Value(buffer.capacity() as i32),
+            constants::BUFFER_USAGE => Int32Value(buffer.usage() as i32),
+            _ => {
+                self.webgl_error(InvalidEnum);
+                NullValue()
+            },
+        }
+    }
+
     pub fn create_vertex_array(&self) -> Option<DomRoot<WebGLVertexArrayObjectOES>> {
         let (sender, receiver) = webgl_channel().unwrap();
         self.send_command(WebGLCommand::CreateVertexArray(sender));
@@ -1150,21 +1635,9 @@ impl WebGLRenderingContextMethods for WebGLRenderingContext {
     #[allow(unsafe_code)]
     // https://www.khronos.org/registry/webgl/specs/latest/1.0/#5.14.5
     unsafe fn GetBufferParameter(&self, _cx: *mut JSContext, target: u32, parameter: u32) -> JSVal {
-        let buffer = handle_potential_webgl_error!(
-            self,
-            self.bound_buffer(target)
-                .and_then(|buf| buf.ok_or(InvalidOperation)),
-            return NullValue()
-        );
-
-        match parameter {
-            constants::BUFFER_SIZE => Int32Value(buffer.capacity() as i32),
-            constants::BUFFER_USAGE => Int32Value(buffer.usage() as i32),
-            _ => {
-                self.webgl_error(InvalidEnum);
-                NullValue()
-            },
-        }
+        let buffer =
+            handle_potential_webgl_error!(self, self.bound_buffer(target), return NullValue());
+        self.get_buffer_param(buffer, parameter)
     }
 
     #[allow(unsafe_code)]
@@ -1198,7 +1671,7 @@ impl WebGLRenderingContextMethods for WebGLRenderingContext {
             constants::TEXTURE_BINDING_2D => {
                 let texture = self
                     .textures
-                    .active_texture_slot(constants::TEXTURE_2D)
+                    .active_texture_slot(constants::TEXTURE_2D, self.webgl_version)
                     .unwrap()
                     .get();
                 return optional_root_object_to_js_or_null!(cx, texture);
@@ -1206,7 +1679,7 @@ impl WebGLRenderingContextMethods for WebGLRenderingContext {
             constants::TEXTURE_BINDING_CUBE_MAP => {
                 let texture = self
                     .textures
-                    .active_texture_slot(constants::TEXTURE_CUBE_MAP)
+                    .active_texture_slot(constants::TEXTURE_CUBE_MAP, self.webgl_version)
                     .unwrap()
                     .get();
                 return optional_root_object_to_js_or_null!(cx, texture);
@@ -1384,7 +1857,8 @@ impl WebGLRenderingContextMethods for WebGLRenderingContext {
     unsafe fn GetTexParameter(&self, _cx: *mut JSContext, target: u32, pname: u32) -> JSVal {
         let texture_slot = handle_potential_webgl_error!(
             self,
-            self.textures.active_texture_slot(target),
+            self.textures
+                .active_texture_slot(target, self.webgl_version),
             return NullValue()
         );
         let texture = handle_potential_webgl_error!(
@@ -1575,10 +2049,6 @@ impl WebGLRenderingContextMethods for WebGLRenderingContext {
 
     // https://www.khronos.org/registry/webgl/specs/latest/1.0/#5.14.5
     fn BindBuffer(&self, target: u32, buffer: Option<&WebGLBuffer>) {
-        if let Some(buffer) = buffer {
-            handle_potential_webgl_error!(self, self.validate_ownership(buffer), return);
-        }
-
         let current_vao;
         let slot = match target {
             constants::ARRAY_BUFFER => &self.bound_buffer_array,
@@ -1588,19 +2058,7 @@ impl WebGLRenderingContextMethods for WebGLRenderingContext {
             },
             _ => return self.webgl_error(InvalidEnum),
         };
-
-        if let Some(buffer) = buffer {
-            if buffer.is_marked_for_deletion() {
-                return self.webgl_error(InvalidOperation);
-            }
-            handle_potential_webgl_error!(self, buffer.set_target(target), return);
-            buffer.increment_attached_counter();
-        }
-        self.send_command(WebGLCommand::BindBuffer(target, buffer.map(|b| b.id())));
-        if let Some(old) = slot.get() {
-            old.decrement_attached_counter();
-        }
-        slot.set(buffer);
+        self.bind_buffer_maybe(slot, target, buffer);
     }
 
     // https://www.khronos.org/registry/webgl/specs/latest/1.0/#5.14.6
@@ -1670,8 +2128,12 @@ impl WebGLRenderingContextMethods for WebGLRenderingContext {
             handle_potential_webgl_error!(self, self.validate_ownership(texture), return);
         }
 
-        let texture_slot =
-            handle_potential_webgl_error!(self, self.textures.active_texture_slot(target), return);
+        let texture_slot = handle_potential_webgl_error!(
+            self,
+            self.textures
+                .active_texture_slot(target, self.webgl_version),
+            return
+        );
 
         if let Some(texture) = texture {
             handle_potential_webgl_error!(self, texture.bind(target), return);
@@ -1683,76 +2145,33 @@ impl WebGLRenderingContextMethods for WebGLRenderingContext {
 
     // https://www.khronos.org/registry/webgl/specs/latest/1.0/#5.14.8
     fn GenerateMipmap(&self, target: u32) {
-        let texture_slot =
-            handle_potential_webgl_error!(self, self.textures.active_texture_slot(target), return);
+        let texture_slot = handle_potential_webgl_error!(
+            self,
+            self.textures
+                .active_texture_slot(target, self.webgl_version),
+            return
+        );
         let texture =
             handle_potential_webgl_error!(self, texture_slot.get().ok_or(InvalidOperation), return);
         handle_potential_webgl_error!(self, texture.generate_mipmap());
     }
 
     // https://www.khronos.org/registry/webgl/specs/latest/1.0/#5.14.5
-    #[allow(unsafe_code)]
     fn BufferData(&self, target: u32, data: Option<ArrayBufferViewOrArrayBuffer>, usage: u32) {
-        let data = handle_potential_webgl_error!(self, data.ok_or(InvalidValue), return);
-
         let bound_buffer = handle_potential_webgl_error!(self, self.bound_buffer(target), return);
-        let bound_buffer =
-            handle_potential_webgl_error!(self, bound_buffer.ok_or(InvalidOperation), return);
-
-        let data = unsafe {
-            // Safe because we don't do anything with JS until the end of the method.
-            match data {
-                ArrayBufferViewOrArrayBuffer::ArrayBuffer(ref data) => data.as_slice(),
-                ArrayBufferViewOrArrayBuffer::ArrayBufferView(ref data) => data.as_slice(),
-            }
-        };
-        handle_potential_webgl_error!(self, bound_buffer.buffer_data(data, usage));
+        self.buffer_data(data, usage, bound_buffer)
     }
 
     // https://www.khronos.org/registry/webgl/specs/latest/1.0/#5.14.5
     fn BufferData_(&self, target: u32, size: i64, usage: u32) {
         let bound_buffer = handle_potential_webgl_error!(self, self.bound_buffer(target), return);
-        let bound_buffer =
-            handle_potential_webgl_error!(self, bound_buffer.ok_or(InvalidOperation), return);
-
-        if size < 0 {
-            return self.webgl_error(InvalidValue);
-        }
-
-        // FIXME: Allocating a buffer based on user-requested size is
-        // not great, but we don't have a fallible allocation to try.
-        let data = vec![0u8; size as usize];
-        handle_potential_webgl_error!(self, bound_buffer.buffer_data(&data, usage));
+        self.buffer_data_(size, usage, bound_buffer)
     }
 
     // https://www.khronos.org/registry/webgl/specs/latest/1.0/#5.14.5
-    #[allow(unsafe_code)]
     fn BufferSubData(&self, target: u32, offset: i64, data: ArrayBufferViewOrArrayBuffer) {
         let bound_buffer = handle_potential_webgl_error!(self, self.bound_buffer(target), return);
-        let bound_buffer =
-            handle_potential_webgl_error!(self, bound_buffer.ok_or(InvalidOperation), return);
-
-        if offset < 0 {
-            return self.webgl_error(InvalidValue);
-        }
-
-        let data = unsafe {
-            // Safe because we don't do anything with JS until the end of the method.
-            match data {
-                ArrayBufferViewOrArrayBuffer::ArrayBuffer(ref data) => data.as_slice(),
-                ArrayBufferViewOrArrayBuffer::ArrayBufferView(ref data) => data.as_slice(),
-            }
-        };
-        if (offset as u64) + data.len() as u64 > bound_buffer.capacity() as u64 {
-            return self.webgl_error(InvalidValue);
-        }
-        let (sender, receiver) = ipc::bytes_channel().unwrap();
-        self.send_command(WebGLCommand::BufferSubData(
-            target,
-            offset as isize,
-            receiver,
-        ));
-        sender.send(data).unwrap();
+        self.buffer_sub_data(target, offset, data, bound_buffer)
     }
 
     // https://www.khronos.org/registry/webgl/specs/latest/1.0/#5.14.8
@@ -1835,7 +2254,8 @@ impl WebGLRenderingContextMethods for WebGLRenderingContext {
                 internal_format,
                 level as u32,
                 None
-            )
+            ),
+            return
         );
 
         let msg = WebGLCommand::CopyTexImage2D(
@@ -2964,7 +3384,7 @@ impl WebGLRenderingContextMethods for WebGLRenderingContext {
         self.with_location(location, |location| {
             match location.type_() {
                 constants::BOOL | constants::INT => {},
-                constants::SAMPLER_2D | constants::SAMPLER_CUBE => {
+                type_ if self.is_sampler_type(type_) => {
                     if val < 0 || val as u32 >= self.limits.max_combined_texture_image_units {
                         return Err(InvalidValue);
                     }
@@ -2980,10 +3400,8 @@ impl WebGLRenderingContextMethods for WebGLRenderingContext {
     fn Uniform1iv(&self, location: Option<&WebGLUniformLocation>, val: Int32ArrayOrLongSequence) {
         self.with_location(location, |location| {
             match location.type_() {
-                constants::BOOL |
-                constants::INT |
-                constants::SAMPLER_2D |
-                constants::SAMPLER_CUBE => {},
+                constants::BOOL | constants::INT => {},
+                type_ if self.is_sampler_type(type_) => {},
                 _ => return Err(InvalidOperation),
             }
             let val = match val {
@@ -2997,7 +3415,7 @@ impl WebGLRenderingContextMethods for WebGLRenderingContext {
                 return Err(InvalidOperation);
             }
             match location.type_() {
-                constants::SAMPLER_2D | constants::SAMPLER_CUBE => {
+                type_ if self.is_sampler_type(type_) => {
                     for &v in val
                         .iter()
                         .take(cmp::min(location.size().unwrap_or(1) as usize, val.len()))
@@ -3748,7 +4166,7 @@ impl WebGLRenderingContextMethods for WebGLRenderingContext {
         let texture = handle_potential_webgl_error!(
             self,
             self.textures
-                .active_texture_slot(constants::TEXTURE_2D)
+                .active_texture_slot(constants::TEXTURE_2D, self.webgl_version)
                 .unwrap()
                 .get()
                 .ok_or(InvalidOperation),
@@ -4105,7 +4523,7 @@ impl Textures {
         }
     }
 
-    fn active_unit_enum(&self) -> u32 {
+    pub fn active_unit_enum(&self) -> u32 {
         self.active_unit.get() + constants::TEXTURE0
     }
 
@@ -4118,11 +4536,21 @@ impl Textures {
         Ok(())
     }
 
-    fn active_texture_slot(&self, target: u32) -> WebGLResult<&MutNullableDom<WebGLTexture>> {
+    pub fn active_texture_slot(
+        &self,
+        target: u32,
+        webgl_version: WebGLVersion,
+    ) -> WebGLResult<&MutNullableDom<WebGLTexture>> {
         let active_unit = self.active_unit();
-        match target {
-            constants::TEXTURE_2D => Ok(&active_unit.tex_2d),
-            constants::TEXTURE_CUBE_MAP => Ok(&active_unit.tex_cube_map),
+        match (target, webgl_version) {
+            (constants::TEXTURE_2D, _) => Ok(&active_unit.tex_2d),
+            (constants::TEXTURE_CUBE_MAP, _) => Ok(&active_unit.tex_cube_map),
+            (WebGL2RenderingContextConstants::TEXTURE_3D, WebGLVersion::WebGL2) => {
+                Ok(&active_unit.tex_3d)
+            },
+            (WebGL2RenderingContextConstants::TEXTURE_2D_ARRAY, WebGLVersion::WebGL2) => {
+                Ok(&active_unit.tex_2d_array)
+            },
             _ => Err(InvalidEnum),
         }
     }
@@ -4160,6 +4588,8 @@ impl Textures {
 struct TextureUnit {
     tex_2d: MutNullableDom<WebGLTexture>,
     tex_cube_map: MutNullableDom<WebGLTexture>,
+    tex_3d: MutNullableDom<WebGLTexture>,
+    tex_2d_array: MutNullableDom<WebGLTexture>,
 }
 
 impl TextureUnit {
@@ -4167,6 +4597,11 @@ impl TextureUnit {
         let fields = [
             (&self.tex_2d, constants::TEXTURE_2D),
             (&self.tex_cube_map, constants::TEXTURE_CUBE_MAP),
+            (&self.tex_3d, WebGL2RenderingContextConstants::TEXTURE_3D),
+            (
+                &self.tex_2d_array,
+                WebGL2RenderingContextConstants::TEXTURE_2D_ARRAY,
+            ),
         ];
         for &(slot, target) in &fields {
             if slot.get().map_or(false, |t| texture == &*t) {
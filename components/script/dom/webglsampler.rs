@@ -0,0 +1,170 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+// https://www.khronos.org/registry/webgl/specs/latest/2.0/webgl2.idl
+use crate::dom::bindings::codegen::Bindings::WebGL2RenderingContextBinding::WebGL2RenderingContextConstants as constants;
+use crate::dom::bindings::codegen::Bindings::WebGLSamplerBinding;
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::webglobject::WebGLObject;
+use crate::dom::webglrenderingcontext::WebGLRenderingContext;
+use canvas_traits::webgl::{webgl_channel, WebGLCommand, WebGLError, WebGLResult, WebGLSamplerId};
+use dom_struct::dom_struct;
+use std::cell::Cell;
+
+#[dom_struct]
+pub struct WebGLSampler {
+    webgl_object: WebGLObject,
+    id: WebGLSamplerId,
+    is_deleted: Cell<bool>,
+}
+
+/// A sampler parameter value, `samplerParameterf` and `samplerParameteri`
+/// accept the same parameters.
+#[derive(Clone, Copy)]
+pub enum SamplerParameterValue {
+    Int(i32),
+    Float(f32),
+}
+
+impl WebGLSampler {
+    fn new_inherited(context: &WebGLRenderingContext, id: WebGLSamplerId) -> Self {
+        Self {
+            webgl_object: WebGLObject::new_inherited(context),
+            id,
+            is_deleted: Cell::new(false),
+        }
+    }
+
+    pub fn maybe_new(context: &WebGLRenderingContext) -> Option<DomRoot<Self>> {
+        let (sender, receiver) = webgl_channel().unwrap();
+        context.send_command(WebGLCommand::CreateSampler(sender));
+        receiver
+            .recv()
+            .unwrap()
+            .map(|id| WebGLSampler::new(context, id))
+    }
+
+    pub fn new(context: &WebGLRenderingContext, id: WebGLSamplerId) -> DomRoot<Self> {
+        reflect_dom_object(
+            Box::new(WebGLSampler::new_inherited(context, id)),
+            &*context.global(),
+            WebGLSamplerBinding::Wrap,
+        )
+    }
+}
+
+impl WebGLSampler {
+    pub fn id(&self) -> WebGLSamplerId {
+        self.id
+    }
+
+    pub fn is_deleted(&self) -> bool {
+        self.is_deleted.get()
+    }
+
+    pub fn delete(&self) {
+        if self.is_deleted.get() {
+            return;
+        }
+        self.is_deleted.set(true);
+        self.upcast::<WebGLObject>()
+            .context()
+            .send_command(WebGLCommand::DeleteSampler(self.id));
+    }
+
+    pub fn set_parameter(&self, pname: u32, value: SamplerParameterValue) -> WebGLResult<()> {
+        if self.is_deleted() {
+            return Err(WebGLError::InvalidOperation);
+        }
+        let int = match value {
+            SamplerParameterValue::Int(int) => int as u32,
+            SamplerParameterValue::Float(float) => float as u32,
+        };
+        match pname {
+            constants::TEXTURE_MIN_FILTER => match int {
+                constants::NEAREST |
+                constants::LINEAR |
+                constants::NEAREST_MIPMAP_NEAREST |
+                constants::LINEAR_MIPMAP_NEAREST |
+                constants::NEAREST_MIPMAP_LINEAR |
+                constants::LINEAR_MIPMAP_LINEAR => {},
+                _ => return Err(WebGLError::InvalidEnum),
+            },
+            constants::TEXTURE_MAG_FILTER => match int {
+                constants::NEAREST | constants::LINEAR => {},
+                _ => return Err(WebGLError::InvalidEnum),
+            },
+            constants::TEXTURE_WRAP_S | constants::TEXTURE_WRAP_T | constants::TEXTURE_WRAP_R => {
+                match int {
+                    constants::CLAMP_TO_EDGE | constants::MIRRORED_REPEAT | constants::REPEAT => {},
+                    _ => return Err(WebGLError::InvalidEnum),
+                }
+            },
+            constants::TEXTURE_COMPARE_MODE => match int {
+                constants::NONE | constants::COMPARE_REF_TO_TEXTURE => {},
+                _ => return Err(WebGLError::InvalidEnum),
+            },
+            constants::TEXTURE_COMPARE_FUNC => match int {
+                constants::LEQUAL |
+                constants::GEQUAL |
+                constants::LESS |
+                constants::GREATER |
+                constants::EQUAL |
+                constants::NOTEQUAL |
+                constants::ALWAYS |
+                constants::NEVER => {},
+                _ => return Err(WebGLError::InvalidEnum),
+            },
+            constants::TEXTURE_MIN_LOD | constants::TEXTURE_MAX_LOD => {},
+            _ => return Err(WebGLError::InvalidEnum),
+        }
+
+        let command = match value {
+            SamplerParameterValue::Int(int) => {
+                WebGLCommand::SetSamplerParameterInt(self.id, pname, int)
+            },
+            SamplerParameterValue::Float(float) => {
+                WebGLCommand::SetSamplerParameterFloat(self.id, pname, float)
+            },
+        };
+        self.upcast::<WebGLObject>().context().send_command(command);
+        Ok(())
+    }
+
+    pub fn get_parameter(&self, pname: u32) -> WebGLResult<SamplerParameterValue> {
+        if self.is_deleted() {
+            return Err(WebGLError::InvalidOperation);
+        }
+        let context = self.upcast::<WebGLObject>().context();
+        match pname {
+            constants::TEXTURE_MIN_FILTER |
+            constants::TEXTURE_MAG_FILTER |
+            constants::TEXTURE_WRAP_S |
+            constants::TEXTURE_WRAP_T |
+            constants::TEXTURE_WRAP_R |
+            constants::TEXTURE_COMPARE_MODE |
+            constants::TEXTURE_COMPARE_FUNC => {
+                let (sender, receiver) = webgl_channel().unwrap();
+                context.send_command(WebGLCommand::GetSamplerParameterInt(self.id, pname, sender));
+                Ok(SamplerParameterValue::Int(receiver.recv().unwrap()))
+            },
+            constants::TEXTURE_MIN_LOD | constants::TEXTURE_MAX_LOD => {
+                let (sender, receiver) = webgl_channel().unwrap();
+                context.send_command(WebGLCommand::GetSamplerParameterFloat(
+                    self.id, pname, sender,
+                ));
+                Ok(SamplerParameterValue::Float(receiver.recv().unwrap()))
+            },
+            _ => Err(WebGLError::InvalidEnum),
+        }
+    }
+}
+
+impl Drop for WebGLSampler {
+    fn drop(&mut self) {
+        self.delete();
+    }
+}
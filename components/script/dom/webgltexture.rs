@@ -6,6 +6,7 @@
 
 use crate::dom::bindings::cell::DomRefCell;
 use crate::dom::bindings::codegen::Bindings::EXTTextureFilterAnisotropicBinding::EXTTextureFilterAnisotropicConstants;
+use crate::dom::bindings::codegen::Bindings::WebGL2RenderingContextBinding::WebGL2RenderingContextConstants;
 use crate::dom::bindings::codegen::Bindings::WebGLRenderingContextBinding::WebGLRenderingContextConstants as constants;
 use crate::dom::bindings::codegen::Bindings::WebGLTextureBinding;
 use crate::dom::bindings::inheritance::Castable;
@@ -48,6 +49,12 @@ pub struct WebGLTexture {
     mag_filter: Cell<u32>,
     /// True if this texture is used for the DOMToTexture feature.
     attached_to_dom: Cell<bool>,
+    /// Stores information about the mipmap levels of 3D and 2D array
+    /// textures.
+    image_info_3d: DomRefCell<Vec<Option<ImageInfo3D>>>,
+    /// The number of levels of the texture, if it was allocated by
+    /// texStorage2D or texStorage3D.
+    immutable_levels: Cell<Option<u32>>,
 }
 
 impl WebGLTexture {
@@ -63,6 +70,8 @@ impl WebGLTexture {
             mag_filter: Cell::new(constants::LINEAR),
             image_info_array: DomRefCell::new([ImageInfo::new(); MAX_LEVEL_COUNT * MAX_FACE_COUNT]),
             attached_to_dom: Cell::new(false),
+            image_info_3d: DomRefCell::new(vec![None; MAX_LEVEL_COUNT]),
+            immutable_levels: Cell::new(None),
         }
     }
 
@@ -102,7 +111,9 @@ impl WebGLTexture {
         } else {
             // This is the first time binding
             let face_count = match target {
-                constants::TEXTURE_2D => 1,
+                constants::TEXTURE_2D |
+                WebGL2RenderingContextConstants::TEXTURE_3D |
+                WebGL2RenderingContextConstants::TEXTURE_2D_ARRAY => 1,
                 constants::TEXTURE_CUBE_MAP => 6,
                 _ => return Err(WebGLError::InvalidEnum),
             };
@@ -127,6 +138,12 @@ impl WebGLTexture {
         level: u32,
         data_type: Option<TexDataType>,
     ) -> WebGLResult<()> {
+        // The images of a texture allocated by texStorage2D can't be
+        // redefined.
+        if self.is_immutable() {
+            return Err(WebGLError::InvalidOperation);
+        }
+
         let image_info = ImageInfo {
             width: width,
             height: height,
@@ -141,6 +158,76 @@ impl WebGLTexture {
         Ok(())
     }
 
+    /// Records the size and internal format of a level of a 3D or 2D array
+    /// texture.
+    pub fn initialize_3d(&self, level: u32, image_info: ImageInfo3D) {
+        if let Some(info) = self.image_info_3d.borrow_mut().get_mut(level as usize) {
+            *info = Some(image_info);
+        }
+    }
+
+    pub fn image_info_3d(&self, level: u32) -> Option<ImageInfo3D> {
+        self.image_info_3d
+            .borrow()
+            .get(level as usize)
+            .cloned()
+            .and_then(|info| info)
+    }
+
+    pub fn is_immutable(&self) -> bool {
+        self.immutable_levels.get().is_some()
+    }
+
+    /// Allocates the levels of the texture for texStorage2D and
+    /// texStorage3D, after which the texture has an immutable format.
+    pub fn storage(
+        &self,
+        target: u32,
+        levels: u32,
+        internal_format: u32,
+        mut width: u32,
+        mut height: u32,
+        mut depth: u32,
+    ) {
+        for level in 0..levels {
+            match target {
+                WebGL2RenderingContextConstants::TEXTURE_3D |
+                WebGL2RenderingContextConstants::TEXTURE_2D_ARRAY => {
+                    self.initialize_3d(
+                        level,
+                        ImageInfo3D {
+                            width,
+                            height,
+                            depth,
+                            internal_format,
+                        },
+                    );
+                },
+                _ => {
+                    // Only the sized formats with an equivalent WebGL 1
+                    // format and type can be updated with texSubImage2D.
+                    if let Some((format, data_type)) = unsized_format(internal_format) {
+                        let image_info = ImageInfo {
+                            width,
+                            height,
+                            depth: 1,
+                            internal_format: Some(format),
+                            is_initialized: true,
+                            data_type: Some(data_type),
+                        };
+                        self.set_image_infos_at_level(level, image_info);
+                    }
+                },
+            }
+            width = cmp::max(1, width / 2);
+            height = cmp::max(1, height / 2);
+            if target == WebGL2RenderingContextConstants::TEXTURE_3D {
+                depth = cmp::max(1, depth / 2);
+            }
+        }
+        self.immutable_levels.set(Some(levels));
+    }
+
     pub fn generate_mipmap(&self) -> WebGLResult<()> {
         let target = match self.target.get() {
             Some(target) => target,
@@ -408,6 +495,28 @@ impl Drop for WebGLTexture {
     }
 }
 
+fn unsized_format(internal_format: u32) -> Option<(TexFormat, TexDataType)> {
+    Some(match internal_format {
+        WebGL2RenderingContextConstants::RGBA8 => (TexFormat::RGBA, TexDataType::UnsignedByte),
+        WebGL2RenderingContextConstants::RGB8 => (TexFormat::RGB, TexDataType::UnsignedByte),
+        constants::RGBA4 => (TexFormat::RGBA, TexDataType::UnsignedShort4444),
+        constants::RGB5_A1 => (TexFormat::RGBA, TexDataType::UnsignedShort5551),
+        constants::RGB565 => (TexFormat::RGB, TexDataType::UnsignedShort565),
+        WebGL2RenderingContextConstants::RGBA32F => (TexFormat::RGBA, TexDataType::Float),
+        WebGL2RenderingContextConstants::RGB32F => (TexFormat::RGB, TexDataType::Float),
+        _ => return None,
+    })
+}
+
+/// The size and internal format of a level of a 3D or 2D array texture.
+#[derive(Clone, Copy, Debug, JSTraceable, MallocSizeOf, PartialEq)]
+pub struct ImageInfo3D {
+    pub width: u32,
+    pub height: u32,
+    pub depth: u32,
+    pub internal_format: u32,
+}
+
 #[derive(Clone, Copy, Debug, JSTraceable, MallocSizeOf, PartialEq)]
 pub struct ImageInfo {
     width: u32,
@@ -0,0 +1,101 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+// https://www.khronos.org/registry/webgl/specs/latest/2.0/webgl2.idl
+use crate::dom::bindings::codegen::Bindings::WebGLTransformFeedbackBinding;
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::webglobject::WebGLObject;
+use crate::dom::webglrenderingcontext::WebGLRenderingContext;
+use canvas_traits::webgl::WebGLTransformFeedbackId;
+use canvas_traits::webgl::{webgl_channel, WebGLCommand, WebGLProgramId};
+use dom_struct::dom_struct;
+use std::cell::Cell;
+
+#[dom_struct]
+pub struct WebGLTransformFeedback {
+    webgl_object: WebGLObject,
+    id: WebGLTransformFeedbackId,
+    ever_bound: Cell<bool>,
+    is_deleted: Cell<bool>,
+    active: Cell<Option<ActiveTransformFeedback>>,
+}
+
+/// The state of a transform feedback object between `beginTransformFeedback`
+/// and `endTransformFeedback`.
+#[derive(Clone, Copy, JSTraceable, MallocSizeOf)]
+pub struct ActiveTransformFeedback {
+    pub primitive_mode: u32,
+    /// The program in use when the transform feedback began.
+    pub program: WebGLProgramId,
+    pub paused: bool,
+}
+
+impl WebGLTransformFeedback {
+    fn new_inherited(context: &WebGLRenderingContext, id: WebGLTransformFeedbackId) -> Self {
+        Self {
+            webgl_object: WebGLObject::new_inherited(context),
+            id,
+            ever_bound: Cell::new(false),
+            is_deleted: Cell::new(false),
+            active: Cell::new(None),
+        }
+    }
+
+    pub fn maybe_new(context: &WebGLRenderingContext) -> Option<DomRoot<Self>> {
+        let (sender, receiver) = webgl_channel().unwrap();
+        context.send_command(WebGLCommand::CreateTransformFeedback(sender));
+        receiver
+            .recv()
+            .unwrap()
+            .map(|id| WebGLTransformFeedback::new(context, id))
+    }
+
+    pub fn new(context: &WebGLRenderingContext, id: WebGLTransformFeedbackId) -> DomRoot<Self> {
+        reflect_dom_object(
+            Box::new(WebGLTransformFeedback::new_inherited(context, id)),
+            &*context.global(),
+            WebGLTransformFeedbackBinding::Wrap,
+        )
+    }
+}
+
+impl WebGLTransformFeedback {
+    pub fn id(&self) -> WebGLTransformFeedbackId {
+        self.id
+    }
+
+    pub fn ever_bound(&self) -> bool {
+        self.ever_bound.get()
+    }
+
+    pub fn set_ever_bound(&self) {
+        self.ever_bound.set(true);
+    }
+
+    pub fn is_deleted(&self) -> bool {
+        self.is_deleted.get()
+    }
+
+    pub fn delete(&self) {
+        if self.is_deleted.get() {
+            return;
+        }
+        self.is_deleted.set(true);
+        self.upcast::<WebGLObject>()
+            .context()
+            .send_command(WebGLCommand::DeleteTransformFeedback(self.id));
+    }
+
+    pub fn active(&self) -> &Cell<Option<ActiveTransformFeedback>> {
+        &self.active
+    }
+}
+
+impl Drop for WebGLTransformFeedback {
+    fn drop(&mut self) {
+        self.delete();
+    }
+}
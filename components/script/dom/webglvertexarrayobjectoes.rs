@@ -104,6 +104,31 @@ impl WebGLVertexArrayObjectOES {
         normalized: bool,
         stride: i32,
         offset: i64,
+    ) -> WebGLResult<()> {
+        self.set_vertex_attrib_pointer(index, size, type_, normalized, stride, offset, false)
+    }
+
+    /// glVertexAttribIPointer, only available in WebGL 2.
+    pub fn vertex_attrib_i_pointer(
+        &self,
+        index: u32,
+        size: i32,
+        type_: u32,
+        stride: i32,
+        offset: i64,
+    ) -> WebGLResult<()> {
+        self.set_vertex_attrib_pointer(index, size, type_, false, stride, offset, true)
+    }
+
+    fn set_vertex_attrib_pointer(
+        &self,
+        index: u32,
+        size: i32,
+        type_: u32,
+        normalized: bool,
+        stride: i32,
+        offset: i64,
+        integer: bool,
     ) -> WebGLResult<()> {
         let mut attribs = self.vertex_attribs.borrow_mut();
         let data = attribs
@@ -122,7 +147,8 @@ impl WebGLVertexArrayObjectOES {
         let bytes_per_component: i32 = match type_ {
             constants::BYTE | constants::UNSIGNED_BYTE => 1,
             constants::SHORT | constants::UNSIGNED_SHORT => 2,
-            constants::FLOAT => 4,
+            constants::FLOAT if !integer => 4,
+            constants::INT | constants::UNSIGNED_INT if integer => 4,
             _ => return Err(WebGLError::InvalidEnum),
         };
         if offset % bytes_per_component as i64 > 0 || stride % bytes_per_component > 0 {
@@ -139,14 +165,12 @@ impl WebGLVertexArrayObjectOES {
             },
             _ => {},
         }
-        context.send_command(WebGLCommand::VertexAttribPointer(
-            index,
-            size,
-            type_,
-            normalized,
-            stride,
-            offset as u32,
-        ));
+        let command = if integer {
+            WebGLCommand::VertexAttribIPointer(index, size, type_, stride, offset as u32)
+        } else {
+            WebGLCommand::VertexAttribPointer(index, size, type_, normalized, stride, offset as u32)
+        };
+        context.send_command(command);
         if let Some(old) = data.buffer() {
             old.decrement_attached_counter();
         }
@@ -20,8 +20,6 @@ use crate::dom::event::{Event, EventBubbles, EventCancelable};
 use crate::dom::eventtarget::EventTarget;
 use crate::dom::globalscope::GlobalScope;
 use crate::dom::messageevent::MessageEvent;
-use crate::script_runtime::CommonScriptMsg;
-use crate::script_runtime::ScriptThreadEventCategory::WebSocketEvent;
 use crate::task::{TaskCanceller, TaskOnce};
 use crate::task_source::websocket::WebsocketTaskSource;
 use crate::task_source::TaskSource;
@@ -108,11 +106,11 @@ pub struct WebSocket {
     url: ServoUrl,
     ready_state: Cell<WebSocketRequestState>,
     buffered_amount: Cell<u64>,
-    clearing_buffer: Cell<bool>, //Flag to tell if there is a running thread to clear buffered_amount
     #[ignore_malloc_size_of = "Defined in std"]
     sender: IpcSender<WebSocketDomAction>,
     binary_type: Cell<BinaryType>,
-    protocol: DomRefCell<String>, //Subprotocol selected by server
+    protocol: DomRefCell<String>,   //Subprotocol selected by server
+    extensions: DomRefCell<String>, //Extensions accepted by server
 }
 
 impl WebSocket {
@@ -122,10 +120,10 @@ impl WebSocket {
             url: url,
             ready_state: Cell::new(WebSocketRequestState::Connecting),
             buffered_amount: Cell::new(0),
-            clearing_buffer: Cell::new(false),
             sender: sender,
             binary_type: Cell::new(BinaryType::Blob),
             protocol: DomRefCell::new("".to_owned()),
+            extensions: DomRefCell::new("".to_owned()),
         }
     }
 
@@ -220,10 +218,14 @@ impl WebSocket {
         thread::spawn(move || {
             while let Ok(event) = dom_event_receiver.recv() {
                 match event {
-                    WebSocketNetworkEvent::ConnectionEstablished { protocol_in_use } => {
+                    WebSocketNetworkEvent::ConnectionEstablished {
+                        protocol_in_use,
+                        extensions_in_use,
+                    } => {
                         let open_thread = ConnectionEstablishedTask {
                             address: address.clone(),
                             protocol_in_use,
+                            extensions_in_use,
                         };
                         task_source
                             .queue_with_canceller(open_thread, &canceller)
@@ -238,6 +240,15 @@ impl WebSocket {
                             .queue_with_canceller(message_thread, &canceller)
                             .unwrap();
                     },
+                    WebSocketNetworkEvent::MessageSent(byte_len) => {
+                        let sent_task = BufferedAmountTask {
+                            address: address.clone(),
+                            byte_len,
+                        };
+                        task_source
+                            .queue_with_canceller(sent_task, &canceller)
+                            .unwrap();
+                    },
                     WebSocketNetworkEvent::Fail => {
                         fail_the_websocket_connection(address.clone(), &task_source, &canceller);
                     },
@@ -268,36 +279,12 @@ impl WebSocket {
             WebSocketRequestState::Closing | WebSocketRequestState::Closed => true,
         };
 
-        let address = Trusted::new(self);
-
         match data_byte_len.checked_add(self.buffered_amount.get()) {
             None => panic!(),
             Some(new_amount) => self.buffered_amount.set(new_amount),
         };
 
-        if return_after_buffer {
-            return Ok(false);
-        }
-
-        if !self.clearing_buffer.get() && self.ready_state.get() == WebSocketRequestState::Open {
-            self.clearing_buffer.set(true);
-
-            let task = Box::new(BufferedAmountTask { address: address });
-
-            let pipeline_id = self.global().pipeline_id();
-            self.global()
-                .script_chan()
-                // TODO: Use a dedicated `websocket-task-source` task source instead.
-                .send(CommonScriptMsg::Task(
-                    WebSocketEvent,
-                    task,
-                    Some(pipeline_id),
-                    WebsocketTaskSource::NAME,
-                ))
-                .unwrap();
-        }
-
-        Ok(true)
+        Ok(!return_after_buffer)
     }
 
     pub fn origin(&self) -> ImmutableOrigin {
@@ -333,6 +320,11 @@ impl WebSocketMethods for WebSocket {
         self.buffered_amount.get()
     }
 
+    // https://html.spec.whatwg.org/multipage/#dom-websocket-extensions
+    fn Extensions(&self) -> DOMString {
+        DOMString::from(self.extensions.borrow().clone())
+    }
+
     // https://html.spec.whatwg.org/multipage/#dom-websocket-binarytype
     fn BinaryType(&self) -> BinaryType {
         self.binary_type.get()
@@ -461,6 +453,7 @@ impl WebSocketMethods for WebSocket {
 struct ConnectionEstablishedTask {
     address: Trusted<WebSocket>,
     protocol_in_use: Option<String>,
+    extensions_in_use: Option<String>,
 }
 
 impl TaskOnce for ConnectionEstablishedTask {
@@ -472,7 +465,9 @@ impl TaskOnce for ConnectionEstablishedTask {
         ws.ready_state.set(WebSocketRequestState::Open);
 
         // Step 2: Extensions.
-        // TODO: Set extensions to extensions in use.
+        if let Some(extensions) = self.extensions_in_use {
+            *ws.extensions.borrow_mut() = extensions;
+        };
 
         // Step 3.
         if let Some(protocol_name) = self.protocol_in_use {
@@ -484,21 +479,23 @@ impl TaskOnce for ConnectionEstablishedTask {
     }
 }
 
+/// Task queued when the network sent a message, given its length in bytes.
 struct BufferedAmountTask {
     address: Trusted<WebSocket>,
+    byte_len: u64,
 }
 
 impl TaskOnce for BufferedAmountTask {
     // See https://html.spec.whatwg.org/multipage/#dom-websocket-bufferedamount
     //
-    // To be compliant with standards, we need to reset bufferedAmount only when the event loop
-    // reaches step 1.  In our implementation, the bytes will already have been sent on a background
-    // thread.
+    // The bytes of a message stop counting towards bufferedAmount once the
+    // network thread handed the message over to the connection, which
+    // holds back further messages while too many bytes are in flight.
     fn run_once(self) {
         let ws = self.address.root();
 
-        ws.buffered_amount.set(0);
-        ws.clearing_buffer.set(false);
+        let amount = ws.buffered_amount.get().saturating_sub(self.byte_len);
+        ws.buffered_amount.set(amount);
     }
 }
 
@@ -8,8 +8,12 @@ use crate::dom::bindings::codegen::Bindings::DocumentBinding::{
 };
 use crate::dom::bindings::codegen::Bindings::FunctionBinding::Function;
 use crate::dom::bindings::codegen::Bindings::HistoryBinding::HistoryBinding::HistoryMethods;
+use crate::dom::bindings::codegen::Bindings::ImageBitmapBinding::{
+    ImageBitmapOptions, ImageBitmapSource,
+};
 use crate::dom::bindings::codegen::Bindings::MediaQueryListBinding::MediaQueryListBinding::MediaQueryListMethods;
 use crate::dom::bindings::codegen::Bindings::PermissionStatusBinding::PermissionState;
+use crate::dom::bindings::codegen::Bindings::PermissionStatusBinding::PermissionStatusMethods;
 use crate::dom::bindings::codegen::Bindings::RequestBinding::RequestInit;
 use crate::dom::bindings::codegen::Bindings::WindowBinding::{
     self, FrameRequestCallback, WindowMethods,
@@ -28,6 +32,7 @@ use crate::dom::bindings::trace::RootedTraceableBox;
 use crate::dom::bindings::utils::{GlobalStaticData, WindowProxyHandler};
 use crate::dom::bindings::weakref::DOMTracker;
 use crate::dom::bluetooth::BluetoothExtraPermissionData;
+use crate::dom::cachestorage::CacheStorage;
 use crate::dom::crypto::Crypto;
 use crate::dom::cssstyledeclaration::{CSSModificationAccess, CSSStyleDeclaration, CSSStyleOwner};
 use crate::dom::customelementregistry::CustomElementRegistry;
@@ -38,13 +43,17 @@ use crate::dom::eventtarget::EventTarget;
 use crate::dom::globalscope::GlobalScope;
 use crate::dom::hashchangeevent::HashChangeEvent;
 use crate::dom::history::History;
+use crate::dom::idbfactory::IDBFactory;
+use crate::dom::imagebitmap::ImageBitmap;
 use crate::dom::location::Location;
 use crate::dom::mediaquerylist::{MediaQueryList, MediaQueryListMatchState};
 use crate::dom::mediaquerylistevent::MediaQueryListEvent;
+use crate::dom::mediasource::MediaSource;
 use crate::dom::messageevent::MessageEvent;
 use crate::dom::navigator::Navigator;
 use crate::dom::node::{document_from_node, from_untrusted_node_address, Node, NodeDamage};
 use crate::dom::performance::Performance;
+use crate::dom::permissionstatus::PermissionStatus;
 use crate::dom::promise::Promise;
 use crate::dom::screen::Screen;
 use crate::dom::storage::Storage;
@@ -94,13 +103,14 @@ use profile_traits::ipc as ProfiledIpc;
 use profile_traits::mem::ProfilerChan as MemProfilerChan;
 use profile_traits::time::ProfilerChan as TimeProfilerChan;
 use script_layout_interface::message::{Msg, QueryMsg, Reflow, ReflowGoal, ScriptReflow};
-use script_layout_interface::rpc::{ContentBoxResponse, ContentBoxesResponse, LayoutRPC};
+use script_layout_interface::rpc::{BoxSizesResponse, LayoutRPC};
+use script_layout_interface::rpc::{ContentBoxResponse, ContentBoxesResponse};
 use script_layout_interface::rpc::{
     NodeScrollIdResponse, ResolvedStyleResponse, TextIndexResponse,
 };
 use script_layout_interface::{PendingImageState, TrustedNodeAddress};
 use script_traits::webdriver_msg::{WebDriverJSError, WebDriverJSResult};
-use script_traits::{ConstellationControlMsg, DocumentState, LoadData};
+use script_traits::{ConstellationControlMsg, DocumentState, LoadData, PermissionGrant};
 use script_traits::{ScriptMsg, ScriptToConstellationChan, ScrollState, TimerEvent, TimerEventId};
 use script_traits::{TimerSchedulerMsg, WindowSizeData, WindowSizeType};
 use selectors::attr::CaseSensitivity;
@@ -123,6 +133,7 @@ use style::dom::OpaqueNode;
 use style::error_reporting::{ContextualParseError, ParseErrorReporter};
 use style::media_queries;
 use style::parser::ParserContext as CssParserContext;
+use style::properties::longhands::scroll_behavior;
 use style::properties::{ComputedValues, PropertyId};
 use style::selector_parser::PseudoElement;
 use style::str::HTML_SPACE_CHARACTERS;
@@ -263,8 +274,8 @@ pub struct Window {
     #[ignore_malloc_size_of = "channels are hard"]
     webvr_chan: Option<IpcSender<WebVRMsg>>,
 
-    /// A map for storing the previous permission state read results.
-    permission_state_invocation_results: DomRefCell<HashMap<String, PermissionState>>,
+    /// The permission statuses to update when the stored permissions change.
+    permission_statuses: DOMTracker<PermissionStatus>,
 
     /// All of the elements that have an outstanding image request that was
     /// initiated by layout during a reflow. They are stored in the script thread
@@ -293,6 +304,16 @@ pub struct Window {
     /// Indicate whether a SetDocumentStatus message has been sent after a reflow is complete.
     /// It is used to avoid sending idle message more than once, which is unneccessary.
     has_sent_idle_message: Cell<bool>,
+
+    /// The number of trusted activation-triggering events whose listeners
+    /// are currently running, see Event::is_activation_triggering()
+    user_activation_depth: Cell<u32>,
+
+    /// The media sources that object URLs were created for, which have to
+    /// be kept around until the URLs are revoked.
+    ///
+    /// <https://w3c.github.io/media-source/#dom-url-createobjecturl>
+    media_source_urls: DomRefCell<HashMap<ServoUrl, Dom<MediaSource>>>,
 }
 
 impl Window {
@@ -308,6 +329,40 @@ impl Window {
         self.exists_mut_observer.set(true);
     }
 
+    /// https://html.spec.whatwg.org/multipage/#triggered-by-user-activation
+    pub fn is_triggered_by_user_activation(&self) -> bool {
+        self.user_activation_depth.get() > 0
+    }
+
+    /// Called around the listeners of a trusted activation-triggering event
+    pub fn enter_user_activation(&self) {
+        self.user_activation_depth
+            .set(self.user_activation_depth.get() + 1);
+    }
+
+    pub fn exit_user_activation(&self) {
+        self.user_activation_depth
+            .set(self.user_activation_depth.get() - 1);
+    }
+
+    pub fn add_media_source_url(&self, url: ServoUrl, media_source: &MediaSource) {
+        self.media_source_urls
+            .borrow_mut()
+            .insert(url, Dom::from_ref(media_source));
+    }
+
+    pub fn remove_media_source_url(&self, url: &ServoUrl) {
+        self.media_source_urls.borrow_mut().remove(url);
+    }
+
+    /// The media source the given object URL was created for, if any.
+    pub fn media_source_for_url(&self, url: &ServoUrl) -> Option<DomRoot<MediaSource>> {
+        self.media_source_urls
+            .borrow()
+            .get(url)
+            .map(|media_source| DomRoot::from_ref(&**media_source))
+    }
+
     #[allow(unsafe_code)]
     pub fn clear_js_runtime_for_script_deallocation(&self) {
         unsafe {
@@ -411,10 +466,29 @@ impl Window {
         Worklet::new(self, WorkletGlobalScopeType::Paint)
     }
 
-    pub fn permission_state_invocation_results(
-        &self,
-    ) -> &DomRefCell<HashMap<String, PermissionState>> {
-        &self.permission_state_invocation_results
+    pub fn track_permission_status(&self, status: &PermissionStatus) {
+        self.permission_statuses.track(status);
+    }
+
+    /// Updates the statuses of the permission to use the feature `name`,
+    /// which the user changed for the origin of this window.
+    ///
+    /// <https://w3c.github.io/permissions/#permissionstatus-update-steps>
+    pub fn permission_changed(&self, name: &str, grant: Option<PermissionGrant>) {
+        let state = match grant {
+            Some(PermissionGrant::Granted) => PermissionState::Granted,
+            Some(PermissionGrant::Denied) => PermissionState::Denied,
+            None => PermissionState::Prompt,
+        };
+        self.permission_statuses.for_each(|status| {
+            if status.get_query().as_str() != name || status.State() == state {
+                return;
+            }
+            status.set_state(state);
+            self.task_manager()
+                .dom_manipulation_task_source()
+                .queue_simple_event(status.upcast(), atom!("change"), self);
+        });
     }
 
     pub fn pending_image_notification(&self, response: PendingImageResponse) {
@@ -541,6 +615,19 @@ impl WindowMethods for Window {
         receiver.recv().unwrap();
     }
 
+    // https://html.spec.whatwg.org/multipage/#dom-print
+    fn Print(&self) {
+        // TODO: Wait for the document to be ready for post-load tasks.
+        // https://html.spec.whatwg.org/multipage/#printing-steps
+        // Step 3.
+        self.upcast::<EventTarget>()
+            .fire_event(atom!("beforeprint"));
+        // Step 4.
+        self.send_to_embedder(EmbedderMsg::Print);
+        // Step 5.
+        self.upcast::<EventTarget>().fire_event(atom!("afterprint"));
+    }
+
     // https://html.spec.whatwg.org/multipage/#dom-window-stop
     fn Stop(&self) {
         // TODO: Cancel ongoing navigation.
@@ -655,6 +742,16 @@ impl WindowMethods for Window {
         self.upcast::<GlobalScope>().crypto()
     }
 
+    // https://w3c.github.io/ServiceWorker/#dom-windoworworkerglobalscope-caches
+    fn Caches(&self) -> DomRoot<CacheStorage> {
+        self.upcast::<GlobalScope>().caches()
+    }
+
+    // https://w3c.github.io/IndexedDB/#dom-windoworworkerglobalscope-indexeddb
+    fn IndexedDB(&self) -> DomRoot<IDBFactory> {
+        self.upcast::<GlobalScope>().indexeddb()
+    }
+
     // https://html.spec.whatwg.org/multipage/#dom-frameelement
     fn GetFrameElement(&self) -> Option<DomRoot<Element>> {
         // Steps 1-3.
@@ -763,6 +860,29 @@ impl WindowMethods for Window {
         self.ClearTimeout(handle);
     }
 
+    // https://html.spec.whatwg.org/multipage/#dom-createimagebitmap
+    fn CreateImageBitmap(
+        &self,
+        image: ImageBitmapSource,
+        options: &ImageBitmapOptions,
+    ) -> Rc<Promise> {
+        ImageBitmap::create(self.upcast(), image, None, options)
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-createimagebitmap
+    fn CreateImageBitmap_(
+        &self,
+        image: ImageBitmapSource,
+        sx: i32,
+        sy: i32,
+        sw: i32,
+        sh: i32,
+        options: &ImageBitmapOptions,
+    ) -> Rc<Promise> {
+        let crop = Rect::new(Point2D::new(sx, sy), Size2D::new(sw, sh));
+        ImageBitmap::create(self.upcast(), image, Some(crop), options)
+    }
+
     // https://html.spec.whatwg.org/multipage/#dom-window
     fn Window(&self) -> DomRoot<WindowProxy> {
         self.window_proxy()
@@ -871,7 +991,7 @@ impl WindowMethods for Window {
 
         // Step 1-2, 6-8.
         // TODO(#12717): Should implement the `transfer` argument.
-        let data = StructuredCloneData::write(cx, message)?;
+        let data = StructuredCloneData::write(cx, message, None)?;
 
         // Step 9.
         self.post_message(origin, &*source.window_proxy(), data);
@@ -1023,8 +1143,13 @@ impl WindowMethods for Window {
         // Step 1
         let x = options.left.unwrap_or(0.0f64);
         let y = options.top.unwrap_or(0.0f64);
-        self.ScrollBy_(x, y);
-        self.scroll(x, y, options.parent.behavior);
+        // Step 3
+        let left = x + self.ScrollX() as f64;
+        // Step 4
+        let top = y + self.ScrollY() as f64;
+
+        // Step 5
+        self.scroll(left, top, options.parent.behavior);
     }
 
     // https://drafts.csswg.org/cssom-view/#dom-window-scrollby
@@ -1285,7 +1410,6 @@ impl Window {
         let global_scope = self.upcast::<GlobalScope>();
         let x = x.to_f32().unwrap_or(0.0f32);
         let y = y.to_f32().unwrap_or(0.0f32);
-        self.update_viewport_for_scroll(x, y);
         self.perform_a_scroll(
             x,
             y,
@@ -1301,20 +1425,63 @@ impl Window {
         x: f32,
         y: f32,
         scroll_id: ExternalScrollId,
-        _behavior: ScrollBehavior,
-        _element: Option<&Element>,
+        behavior: ScrollBehavior,
+        element: Option<&Element>,
     ) {
-        // TODO Step 1
-        // TODO(mrobinson, #18709): Add smooth scrolling support to WebRender so that we can
-        // properly process ScrollBehavior here.
+        let scroll_state = ScrollState {
+            scroll_id,
+            scroll_offset: Vector2D::new(-x, -y),
+        };
+
+        // Step 1
+        if self.is_smooth_scroll(behavior, element) {
+            // The compositor animates the scroll, and the offsets are
+            // reported back to us as it goes.
+            self.send_to_constellation(ScriptMsg::ScrollSmoothly(scroll_state));
+            return;
+        }
+
+        // The scroll offsets are immediatly updated since later calls
+        // to topScroll and others may access the properties before
+        // webrender has a chance to update the offsets.
+        if scroll_id == self.upcast::<GlobalScope>().pipeline_id().root_scroll_id() {
+            self.update_viewport_for_scroll(x, y);
+        } else if let Some(element) = element {
+            self.scroll_offsets
+                .borrow_mut()
+                .insert(element.upcast::<Node>().to_opaque(), Vector2D::new(x, y));
+        }
         self.layout_chan
-            .send(Msg::UpdateScrollStateFromScript(ScrollState {
-                scroll_id,
-                scroll_offset: Vector2D::new(-x, -y),
-            }))
+            .send(Msg::UpdateScrollStateFromScript(scroll_state))
             .unwrap();
     }
 
+    /// Whether a scroll of the box of `element`, or of the viewport if there is
+    /// none, is smooth.
+    ///
+    /// <https://drafts.csswg.org/cssom-view/#perform-a-scroll>
+    fn is_smooth_scroll(&self, behavior: ScrollBehavior, element: Option<&Element>) -> bool {
+        match behavior {
+            ScrollBehavior::Smooth => true,
+            ScrollBehavior::Instant => false,
+            ScrollBehavior::Auto => {
+                // The scroll-behavior of the root element applies to the
+                // viewport.
+                let element = match element {
+                    Some(element) => DomRoot::from_ref(element),
+                    None => match self.Document().GetDocumentElement() {
+                        Some(element) => element,
+                        None => return false,
+                    },
+                };
+                element.style().map_or(false, |style| {
+                    style.get_box().clone_scroll_behavior() ==
+                        scroll_behavior::computed_value::T::Smooth
+                })
+            },
+        }
+    }
+
     pub fn update_viewport_for_scroll(&self, x: f32, y: f32) {
         let size = self.current_viewport.get().size;
         let new_viewport = Rect::new(Point2D::new(Au::from_f32_px(x), Au::from_f32_px(y)), size);
@@ -1401,6 +1568,7 @@ impl Window {
         let document = self.Document();
 
         let stylesheets_changed = document.flush_stylesheets_for_reflow();
+        document.flush_dirty_svgs();
 
         // Send new document and relevant styles to layout.
         let needs_display = reflow_goal.needs_display();
@@ -1513,6 +1681,10 @@ impl Window {
                     (!for_display && self.Document().needs_paint()) ||
                     self.suppress_reflow.get()
             );
+
+            if for_display && issued_reflow {
+                self.Document().update_accessibility_tree();
+            }
         } else {
             debug!(
                 "Document doesn't need reflow - skipping it (reason {:?})",
@@ -1584,6 +1756,13 @@ impl Window {
         rects
     }
 
+    pub fn box_sizes_query(&self, node: &Node) -> BoxSizesResponse {
+        if !self.layout_reflow(QueryMsg::BoxSizesQuery(node.to_opaque())) {
+            return BoxSizesResponse::empty();
+        }
+        self.layout_rpc.box_sizes()
+    }
+
     pub fn client_rect_query(&self, node: &Node) -> Rect<i32> {
         if !self.layout_reflow(QueryMsg::NodeGeometryQuery(node.to_opaque())) {
             return Rect::zero();
@@ -1611,13 +1790,6 @@ impl Window {
             return;
         }
 
-        // The scroll offsets are immediatly updated since later calls
-        // to topScroll and others may access the properties before
-        // webrender has a chance to update the offsets.
-        self.scroll_offsets
-            .borrow_mut()
-            .insert(node.to_opaque(), Vector2D::new(x_ as f32, y_ as f32));
-
         let NodeScrollIdResponse(scroll_id) = self.layout_rpc.node_scroll_id();
 
         // Step 12
@@ -1626,7 +1798,7 @@ impl Window {
             y_.to_f32().unwrap_or(0.0f32),
             scroll_id,
             behavior,
-            None,
+            node.downcast::<Element>(),
         );
     }
 
@@ -2086,7 +2258,7 @@ impl Window {
             test_runner: Default::default(),
             webgl_chan,
             webvr_chan,
-            permission_state_invocation_results: Default::default(),
+            permission_statuses: DOMTracker::new(),
             pending_layout_images: Default::default(),
             unminified_js_dir: Default::default(),
             test_worklet: Default::default(),
@@ -2095,6 +2267,8 @@ impl Window {
             exists_mut_observer: Cell::new(false),
             webrender_api_sender,
             has_sent_idle_message: Cell::new(false),
+            user_activation_depth: Cell::new(0),
+            media_source_urls: Default::default(),
         });
 
         unsafe { WindowBinding::Wrap(runtime.cx(), win) }
@@ -2,6 +2,7 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
+use crate::dom::audioworkletglobalscope::AudioWorkletGlobalScope;
 use crate::dom::bindings::inheritance::Castable;
 use crate::dom::bindings::root::DomRoot;
 use crate::dom::globalscope::GlobalScope;
@@ -162,6 +163,8 @@ pub enum WorkletGlobalScopeType {
     Test,
     /// A paint worklet
     Paint,
+    /// An audio worklet, rendering at the given sample rate
+    Audio(f32),
 }
 
 impl WorkletGlobalScopeType {
@@ -189,6 +192,16 @@ impl WorkletGlobalScopeType {
                 executor,
                 init,
             )),
+            WorkletGlobalScopeType::Audio(sample_rate) => {
+                DomRoot::upcast(AudioWorkletGlobalScope::new(
+                    runtime,
+                    pipeline_id,
+                    base_url,
+                    executor,
+                    init,
+                    sample_rate,
+                ))
+            },
         }
     }
 }
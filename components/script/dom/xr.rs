@@ -4,13 +4,17 @@
 
 use crate::dom::bindings::cell::DomRefCell;
 use crate::dom::bindings::codegen::Bindings::VRDisplayBinding::VRDisplayMethods;
+use crate::dom::bindings::codegen::Bindings::WindowBinding::WindowMethods;
 use crate::dom::bindings::codegen::Bindings::XRBinding;
 use crate::dom::bindings::codegen::Bindings::XRBinding::XRSessionCreationOptions;
 use crate::dom::bindings::codegen::Bindings::XRBinding::{XRMethods, XRSessionMode};
+use crate::dom::bindings::codegen::Bindings::XRSessionBinding::XRReferenceSpaceType;
 use crate::dom::bindings::error::Error;
 use crate::dom::bindings::inheritance::Castable;
 use crate::dom::bindings::reflector::{reflect_dom_object, DomObject};
 use crate::dom::bindings::root::{Dom, DomRoot, MutNullableDom};
+use crate::dom::bindings::str::DOMString;
+use crate::dom::bindings::weakref::WeakRef;
 use crate::dom::event::Event;
 use crate::dom::eventtarget::EventTarget;
 use crate::dom::gamepad::Gamepad;
@@ -19,15 +23,20 @@ use crate::dom::globalscope::GlobalScope;
 use crate::dom::promise::Promise;
 use crate::dom::vrdisplay::VRDisplay;
 use crate::dom::vrdisplayevent::VRDisplayEvent;
+use crate::dom::xrrigidtransform::{TransformKey, XRRigidTransform};
 use crate::dom::xrsession::XRSession;
 use dom_struct::dom_struct;
 use ipc_channel::ipc::IpcSender;
 use profile_traits::ipc;
 use std::cell::Cell;
+use std::collections::HashMap;
 use std::rc::Rc;
 use webvr_traits::{WebVRDisplayData, WebVRDisplayEvent, WebVREvent, WebVRMsg};
 use webvr_traits::{WebVRGamepadData, WebVRGamepadEvent, WebVRGamepadState};
 
+/// How many XRRigidTransforms may be interned per window
+const MAX_INTERNED_TRANSFORMS: usize = 1024;
+
 #[dom_struct]
 pub struct XR {
     eventtarget: EventTarget,
@@ -35,6 +44,12 @@ pub struct XR {
     gamepads: DomRefCell<Vec<Dom<Gamepad>>>,
     pending_immersive_session: Cell<bool>,
     active_immersive_session: MutNullableDom<VRDisplay>,
+    /// The XRRigidTransforms constructed by script, when they are interned.
+    /// They are held weakly, so that the transforms script is done with can
+    /// still be collected
+    #[ignore_malloc_size_of = "TransformKey doesn't implement MallocSizeOf"]
+    interned_transforms: DomRefCell<HashMap<TransformKey, WeakRef<XRRigidTransform>>>,
+    device_presence: DomRefCell<DevicePresence>,
 }
 
 impl XR {
@@ -45,6 +60,8 @@ impl XR {
             gamepads: DomRefCell::new(Vec::new()),
             pending_immersive_session: Cell::new(false),
             active_immersive_session: Default::default(),
+            interned_transforms: DomRefCell::new(HashMap::new()),
+            device_presence: DomRefCell::new(DevicePresence::new()),
         }
     }
 
@@ -62,15 +79,55 @@ impl XR {
         self.pending_immersive_session.set(true)
     }
 
+    /// Returns the interned transform for `key`, creating it with `create`
+    /// if there is none yet
+    ///
+    /// Once MAX_INTERNED_TRANSFORMS live transforms are interned, new ones
+    /// are no longer remembered, so that pages can't grow the cache forever.
+    pub fn intern_transform<F>(&self, key: TransformKey, create: F) -> DomRoot<XRRigidTransform>
+    where
+        F: FnOnce() -> DomRoot<XRRigidTransform>,
+    {
+        let interned = self
+            .interned_transforms
+            .borrow()
+            .get(&key)
+            .and_then(WeakRef::root);
+        if let Some(transform) = interned {
+            return transform;
+        }
+        let transform = create();
+        let mut interned = self.interned_transforms.borrow_mut();
+        if interned.len() >= MAX_INTERNED_TRANSFORMS {
+            // Make room by forgetting the transforms that were collected
+            interned.retain(|_, transform| transform.is_alive());
+        }
+        if interned.len() < MAX_INTERNED_TRANSFORMS {
+            interned.insert(key, WeakRef::new(&transform));
+        }
+        transform
+    }
+
     pub fn set_active_immersive_session(&self, session: &VRDisplay) {
         // XXXManishearth when we support non-immersive (inline) sessions we should
         // ensure they never reach these codepaths
         self.pending_immersive_session.set(false);
-        self.active_immersive_session.set(Some(session))
+        self.active_immersive_session.set(Some(session));
+        // The page's rAF callbacks don't run while the device is being presented to
+        self.global()
+            .as_window()
+            .Document()
+            .suspend_animation_frames();
     }
 
     pub fn deactivate_session(&self) {
         self.pending_immersive_session.set(false);
+        if self.active_immersive_session.get().is_some() {
+            self.global()
+                .as_window()
+                .Document()
+                .resume_animation_frames();
+        }
         self.active_immersive_session.set(None)
     }
 }
@@ -82,15 +139,22 @@ impl Drop for XR {
 }
 
 impl XRMethods for XR {
+    // https://immersive-web.github.io/webxr/#dom-xr-ondevicechange
+    event_handler!(devicechange, GetOndevicechange, SetOndevicechange);
+
     /// https://immersive-web.github.io/webxr/#dom-xr-supportssessionmode
     #[allow(unsafe_code)]
     fn SupportsSessionMode(&self, mode: XRSessionMode) -> Rc<Promise> {
-        // XXXManishearth this should select an XR device first
         let promise = unsafe { Promise::new_in_current_compartment(&self.global()) };
-        if mode == XRSessionMode::Immersive_vr {
+        let supported = match self.get_displays() {
+            Ok(displays) => displays
+                .first()
+                .map_or(false, |display| display_supports_mode(display, mode)),
+            Err(_) => false,
+        };
+        if supported {
             promise.resolve_native(&());
         } else {
-            // XXXManishearth support other modes
             promise.reject_error(Error::NotSupported);
         }
 
@@ -101,11 +165,21 @@ impl XRMethods for XR {
     #[allow(unsafe_code)]
     fn RequestSession(&self, options: &XRSessionCreationOptions) -> Rc<Promise> {
         let promise = unsafe { Promise::new_in_current_compartment(&self.global()) };
-        if options.mode != XRSessionMode::Immersive_vr {
+        // Unknown modes are already rejected with a TypeError when the
+        // options are converted by the bindings, inline sessions aren't
+        // supported yet
+        if options.mode == XRSessionMode::Inline {
             promise.reject_error(Error::NotSupported);
             return promise;
         }
 
+        // Presenting to a headset takes over the user's display, so pages
+        // may only do it in response to user input
+        if !self.global().as_window().is_triggered_by_user_activation() {
+            promise.reject_error(Error::Security);
+            return promise;
+        }
+
         if self.pending_or_active_session() {
             promise.reject_error(Error::InvalidState);
             return promise;
@@ -126,11 +200,25 @@ impl XRMethods for XR {
         // XXXManishearth filter for displays which can_present
         if displays.is_empty() {
             promise.reject_error(Error::Security);
+            return promise;
         }
 
+        let display = &displays[0];
+        if !display_supports_mode(display, options.mode) {
+            promise.reject_error(Error::NotSupported);
+            return promise;
+        }
+        let enabled_features = match enabled_features(display, options) {
+            Some(features) => features,
+            None => {
+                promise.reject_error(Error::NotSupported);
+                return promise;
+            },
+        };
+
         self.set_pending();
 
-        let session = XRSession::new(&self.global(), &displays[0]);
+        let session = XRSession::new(&self.global(), display, options.mode, enabled_features);
         session.xr_present(promise.clone());
         promise
     }
@@ -204,18 +292,31 @@ impl XR {
         }
     }
 
+    /// https://immersive-web.github.io/webxr/#ondevicechange
+    fn notify_device_change(&self) {
+        let global = self.global();
+        let window = global.as_window();
+        window
+            .task_manager()
+            .dom_manipulation_task_source()
+            .queue_simple_event(self.upcast(), atom!("devicechange"), window);
+    }
+
     fn handle_display_event(&self, event: WebVRDisplayEvent) {
-        match event {
+        let display = match event {
             WebVRDisplayEvent::Connect(ref display) => {
                 let display = self.sync_display(&display);
                 display.handle_webvr_event(&event);
                 self.notify_display_event(&display, &event);
+                Some(display)
             },
             WebVRDisplayEvent::Disconnect(id) => {
-                if let Some(display) = self.find_display(id) {
+                let display = self.find_display(id);
+                if let Some(ref display) = display {
                     display.handle_webvr_event(&event);
                     self.notify_display_event(&display, &event);
                 }
+                display
             },
             WebVRDisplayEvent::Activate(ref display, _) |
             WebVRDisplayEvent::Deactivate(ref display, _) |
@@ -225,15 +326,27 @@ impl XR {
             WebVRDisplayEvent::Change(ref display) => {
                 let display = self.sync_display(&display);
                 display.handle_webvr_event(&event);
+                Some(display)
             },
             WebVRDisplayEvent::Pause(id) |
             WebVRDisplayEvent::Resume(id) |
             WebVRDisplayEvent::Exit(id) => {
-                if let Some(display) = self.find_display(id) {
+                let display = self.find_display(id);
+                if let Some(ref display) = display {
                     display.handle_webvr_event(&event);
                 }
+                display
             },
         };
+        if let Some(display) = display {
+            let changed = self
+                .device_presence
+                .borrow_mut()
+                .set_connected(display.DisplayId(), display.IsConnected());
+            if changed {
+                self.notify_device_change();
+            }
+        }
     }
 
     fn handle_gamepad_event(&self, event: WebVRGamepadEvent) {
@@ -339,3 +452,166 @@ impl XR {
             .collect()
     }
 }
+
+/// https://immersive-web.github.io/webxr/#feature-descriptor
+#[derive(Clone, Copy, Debug, JSTraceable, MallocSizeOf, PartialEq)]
+pub enum XRFeature {
+    /// Reference space features are named after their XRReferenceSpaceType
+    ReferenceSpace(XRReferenceSpaceType),
+    /// https://immersive-web.github.io/webxr-hand-input/#feature-descriptor-hand-tracking
+    HandTracking,
+    /// https://immersive-web.github.io/raw-camera-access/#feature-descriptor-camera-access
+    CameraAccess,
+    /// https://immersive-web.github.io/marker-tracking/#feature-descriptor-image-tracking
+    ImageTracking,
+    /// https://immersive-web.github.io/anchors/#feature-descriptor-anchors
+    Anchors,
+}
+
+/// Parses a feature descriptor, as passed to requestSession()
+fn feature_from_descriptor(descriptor: &DOMString) -> Option<XRFeature> {
+    let space = match &**descriptor {
+        "identity" => XRReferenceSpaceType::Identity,
+        "stationary" => XRReferenceSpaceType::Stationary,
+        "bounded" => XRReferenceSpaceType::Bounded,
+        "unbounded" => XRReferenceSpaceType::Unbounded,
+        "hand-tracking" => return Some(XRFeature::HandTracking),
+        "camera-access" => return Some(XRFeature::CameraAccess),
+        "image-tracking" => return Some(XRFeature::ImageTracking),
+        "anchors" => return Some(XRFeature::Anchors),
+        _ => return None,
+    };
+    Some(XRFeature::ReferenceSpace(space))
+}
+
+impl XRFeature {
+    /// The feature descriptor naming this feature, the inverse of
+    /// feature_from_descriptor()
+    pub fn descriptor(&self) -> &'static str {
+        match *self {
+            XRFeature::ReferenceSpace(XRReferenceSpaceType::Identity) => "identity",
+            XRFeature::ReferenceSpace(XRReferenceSpaceType::Stationary) => "stationary",
+            XRFeature::ReferenceSpace(XRReferenceSpaceType::Bounded) => "bounded",
+            XRFeature::ReferenceSpace(XRReferenceSpaceType::Unbounded) => "unbounded",
+            XRFeature::HandTracking => "hand-tracking",
+            XRFeature::CameraAccess => "camera-access",
+            XRFeature::ImageTracking => "image-tracking",
+            XRFeature::Anchors => "anchors",
+        }
+    }
+}
+
+/// Whether `display` is capable of providing the given feature
+fn display_supports_feature(display: &VRDisplay, feature: XRFeature) -> bool {
+    match feature {
+        XRFeature::ReferenceSpace(XRReferenceSpaceType::Identity) |
+        XRFeature::ReferenceSpace(XRReferenceSpaceType::Stationary) => true,
+        // bounded spaces share their origin with the floor, which
+        // requires the device to know where the floor is
+        XRFeature::ReferenceSpace(XRReferenceSpaceType::Bounded) => {
+            display.sitting_to_standing_transform().is_some()
+        },
+        // unbounded spaces aren't implemented yet
+        XRFeature::ReferenceSpace(XRReferenceSpaceType::Unbounded) => false,
+        // WebVR devices only report controllers, never hand joints
+        XRFeature::HandTracking => false,
+        // camera frames only exist for AR devices, which WebVR doesn't have
+        XRFeature::CameraAccess => false,
+        // likewise, WebVR devices have no camera to look for images with
+        XRFeature::ImageTracking => false,
+        // WebVR devices track relative to an origin that doesn't drift, so
+        // anchors can be kept at a fixed pose relative to it
+        XRFeature::Anchors => true,
+    }
+}
+
+/// Whether the display can run sessions of the given mode
+///
+/// WebVR displays have no camera to show the real world through, so they
+/// can only run immersive-vr sessions.
+fn display_supports_mode(_display: &VRDisplay, mode: XRSessionMode) -> bool {
+    mode == XRSessionMode::Immersive_vr
+}
+
+/// https://immersive-web.github.io/webxr/#resolve-the-requested-features
+///
+/// Returns None if a required feature can't be granted
+fn enabled_features(
+    display: &VRDisplay,
+    options: &XRSessionCreationOptions,
+) -> Option<Vec<XRFeature>> {
+    let no_features = vec![];
+    resolve_features(
+        options.mode,
+        options.requiredFeatures.as_ref().unwrap_or(&no_features),
+        options.optionalFeatures.as_ref().unwrap_or(&no_features),
+        |feature| display_supports_feature(display, feature),
+    )
+}
+
+/// Keeps track of the connected displays, to tell when XR devices become
+/// available or stop being available
+#[derive(Clone, Debug, Default, JSTraceable, MallocSizeOf)]
+pub struct DevicePresence {
+    connected: Vec<u32>,
+}
+
+impl DevicePresence {
+    pub fn new() -> DevicePresence {
+        Default::default()
+    }
+
+    /// Whether any display is connected
+    pub fn has_device(&self) -> bool {
+        !self.connected.is_empty()
+    }
+
+    /// Records whether the display `display_id` is connected, returning
+    /// whether that changed whether any display is
+    pub fn set_connected(&mut self, display_id: u32, connected: bool) -> bool {
+        let had_device = self.has_device();
+        self.connected.retain(|&id| id != display_id);
+        if connected {
+            self.connected.push(display_id);
+        }
+        self.has_device() != had_device
+    }
+}
+
+/// Grants the features a session of the given mode gets by default, every
+/// required feature, and the optional features that are supported
+///
+/// Returns None if a required feature is unknown or unsupported, in which
+/// case the session must not be created.
+pub fn resolve_features<F>(
+    mode: XRSessionMode,
+    required: &[DOMString],
+    optional: &[DOMString],
+    is_supported: F,
+) -> Option<Vec<XRFeature>>
+where
+    F: Fn(XRFeature) -> bool,
+{
+    // every session gets the identity space, immersive ones also get the
+    // stationary spaces
+    let mut features = vec![XRFeature::ReferenceSpace(XRReferenceSpaceType::Identity)];
+    if mode != XRSessionMode::Inline {
+        features.push(XRFeature::ReferenceSpace(XRReferenceSpaceType::Stationary));
+    }
+    for descriptor in required {
+        let feature =
+            feature_from_descriptor(descriptor).filter(|feature| is_supported(*feature))?;
+        if !features.contains(&feature) {
+            features.push(feature);
+        }
+    }
+    for descriptor in optional {
+        let feature = feature_from_descriptor(descriptor).filter(|feature| is_supported(*feature));
+        if let Some(feature) = feature {
+            if !features.contains(&feature) {
+                features.push(feature);
+            }
+        }
+    }
+    Some(features)
+}
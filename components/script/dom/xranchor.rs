@@ -0,0 +1,68 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::XRAnchorBinding;
+use crate::dom::bindings::codegen::Bindings::XRAnchorBinding::XRAnchorMethods;
+use crate::dom::bindings::error::{Error, Fallible};
+use crate::dom::bindings::reflector::{reflect_dom_object, Reflector};
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::xrsession::XRSession;
+use crate::dom::xrspace::{NativePose, XRSpace};
+use dom_struct::dom_struct;
+use std::cell::Cell;
+
+#[dom_struct]
+pub struct XRAnchor {
+    reflector_: Reflector,
+    session: Dom<XRSession>,
+    anchor_space: Dom<XRSpace>,
+    /// https://immersive-web.github.io/anchors/#xranchor-deleted
+    deleted: Cell<bool>,
+}
+
+impl XRAnchor {
+    fn new_inherited(session: &XRSession, anchor_space: &XRSpace) -> XRAnchor {
+        XRAnchor {
+            reflector_: Reflector::new(),
+            session: Dom::from_ref(session),
+            anchor_space: Dom::from_ref(anchor_space),
+            deleted: Cell::new(false),
+        }
+    }
+
+    /// Creates an anchor that stays at `pose`, relative to the same origin
+    /// as the poses from `XRSpace::get_pose()`
+    pub fn new(global: &GlobalScope, session: &XRSession, pose: NativePose) -> DomRoot<XRAnchor> {
+        let anchor_space = XRSpace::new_fixedspace(global, session, pose);
+        reflect_dom_object(
+            Box::new(XRAnchor::new_inherited(session, &anchor_space)),
+            global,
+            XRAnchorBinding::Wrap,
+        )
+    }
+
+    /// Marks the anchor as deleted, without removing it from its session
+    pub fn set_deleted(&self) {
+        self.deleted.set(true);
+    }
+}
+
+impl XRAnchorMethods for XRAnchor {
+    /// https://immersive-web.github.io/anchors/#dom-xranchor-anchorspace
+    fn GetAnchorSpace(&self) -> Fallible<DomRoot<XRSpace>> {
+        if self.deleted.get() {
+            return Err(Error::InvalidState);
+        }
+        Ok(DomRoot::from_ref(&self.anchor_space))
+    }
+
+    /// https://immersive-web.github.io/anchors/#dom-xranchor-delete
+    fn Delete(&self) {
+        if self.deleted.get() {
+            return;
+        }
+        self.session.delete_anchor(self);
+    }
+}
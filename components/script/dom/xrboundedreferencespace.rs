@@ -0,0 +1,158 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::XRBoundedReferenceSpaceBinding;
+use crate::dom::bindings::codegen::Bindings::XRBoundedReferenceSpaceBinding::XRBoundedReferenceSpaceMethods;
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::dompointreadonly::DOMPointReadOnly;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::xrreferencespace::XRReferenceSpace;
+use crate::dom::xrrigidtransform::XRRigidTransform;
+use crate::dom::xrsession::XRSession;
+use crate::dom::xrspace::{NativePose, XRSpace};
+use crate::dom::xrstationaryreferencespace::floor_pose;
+use dom_struct::dom_struct;
+use euclid::Vector3D;
+use js::conversions::ToJSValConvertible;
+use js::jsapi::{Heap, JSContext};
+use js::jsval::{JSVal, UndefinedValue};
+use js::rust::wrappers::JS_FreezeObject;
+use std::cell::Cell;
+use webvr_traits::WebVRFrameData;
+
+#[dom_struct]
+pub struct XRBoundedReferenceSpace {
+    xrreferencespace: XRReferenceSpace,
+    /// The corners of the play area on the floor, as last reported by the
+    /// device
+    #[ignore_malloc_size_of = "defined in euclid"]
+    bounds: DomRefCell<Vec<Vector3D<f64>>>,
+    /// The frozen array reflecting `bounds`, created on first access and
+    /// replaced whenever the bounds change
+    bounds_geometry: Heap<JSVal>,
+    /// The pose of the floor, as last reported by the device
+    #[ignore_malloc_size_of = "defined in euclid"]
+    floor: Cell<NativePose>,
+}
+
+impl XRBoundedReferenceSpace {
+    fn new_inherited(session: &XRSession, transform: &XRRigidTransform) -> XRBoundedReferenceSpace {
+        XRBoundedReferenceSpace {
+            xrreferencespace: XRReferenceSpace::new_inherited(session, transform),
+            bounds: DomRefCell::new(session_bounds(session)),
+            bounds_geometry: Heap::default(),
+            // bounded spaces are only handed out by devices that know where
+            // the floor is
+            floor: Cell::new(session_floor(session).unwrap_or_else(NativePose::identity)),
+        }
+    }
+
+    pub fn new(global: &GlobalScope, session: &XRSession) -> DomRoot<XRBoundedReferenceSpace> {
+        let transform = XRRigidTransform::identity(global);
+        XRBoundedReferenceSpace::new_with_offset(global, session, &transform)
+    }
+
+    pub fn new_with_offset(
+        global: &GlobalScope,
+        session: &XRSession,
+        transform: &XRRigidTransform,
+    ) -> DomRoot<XRBoundedReferenceSpace> {
+        reflect_dom_object(
+            Box::new(XRBoundedReferenceSpace::new_inherited(session, transform)),
+            global,
+            XRBoundedReferenceSpaceBinding::Wrap,
+        )
+    }
+
+    /// Picks up the bounds the device currently reports, and lets content
+    /// know with a reset event if they have changed
+    pub fn update_bounds(&self) {
+        let session = self.upcast::<XRSpace>().session();
+        // Should the device lose track of the floor, it is left where it
+        // was rather than emulated
+        if let Some(floor) = session_floor(session) {
+            self.floor.set(floor);
+        }
+        let bounds = session_bounds(session);
+        if *self.bounds.borrow() == bounds {
+            return;
+        }
+        *self.bounds.borrow_mut() = bounds;
+        // content holding on to the old array keeps the old bounds, the
+        // next access gets a new one
+        self.bounds_geometry.set(UndefinedValue());
+
+        let global = self.global();
+        let window = global.as_window();
+        window
+            .task_manager()
+            .dom_manipulation_task_source()
+            .queue_simple_event(self.upcast(), atom!("reset"), window);
+    }
+
+    /// Gets pose represented by this space
+    ///
+    /// Does not apply originOffset, use get_pose on XRReferenceSpace instead
+    pub fn get_unoffset_pose(&self, _: &WebVRFrameData) -> NativePose {
+        // bounded spaces have their origin on the floor, in the middle of
+        // the play area
+        self.floor.get()
+    }
+}
+
+fn session_floor(session: &XRSession) -> Option<NativePose> {
+    floor_pose(session.display().sitting_to_standing_transform(), false)
+}
+
+fn session_bounds(session: &XRSession) -> Vec<Vector3D<f64>> {
+    match session.display().stage_size() {
+        Some((size_x, size_z)) => rectangular_bounds(size_x as f64, size_z as f64),
+        None => vec![],
+    }
+}
+
+/// The corners of a play area of the given width and depth centered on the
+/// origin, in the clockwise order the bounds geometry has when looking down
+/// onto the floor
+///
+/// Devices report a size of zero when they don't know the play area, which
+/// leaves the bounds empty.
+pub fn rectangular_bounds(size_x: f64, size_z: f64) -> Vec<Vector3D<f64>> {
+    if !(size_x > 0. && size_z > 0.) {
+        return vec![];
+    }
+    let (x, z) = (size_x / 2., size_z / 2.);
+    vec![
+        Vector3D::new(-x, 0., -z),
+        Vector3D::new(x, 0., -z),
+        Vector3D::new(x, 0., z),
+        Vector3D::new(-x, 0., z),
+    ]
+}
+
+impl XRBoundedReferenceSpaceMethods for XRBoundedReferenceSpace {
+    /// https://immersive-web.github.io/webxr/#dom-xrboundedreferencespace-boundsgeometry
+    #[allow(unsafe_code)]
+    unsafe fn BoundsGeometry(&self, _cx: *mut JSContext) -> JSVal {
+        if self.bounds_geometry.get().is_undefined() {
+            let global = self.global();
+            let cx = global.get_cx();
+            let points: Vec<DomRoot<DOMPointReadOnly>> = self
+                .bounds
+                .borrow()
+                .iter()
+                .map(|point| DOMPointReadOnly::new(&global, point.x, point.y, point.z, 1.))
+                .collect();
+            rooted!(in(cx) let mut jsval = UndefinedValue());
+            points.to_jsval(cx, jsval.handle_mut());
+            rooted!(in(cx) let array = jsval.to_object());
+            assert!(JS_FreezeObject(cx, array.handle()));
+            self.bounds_geometry.set(jsval.get());
+        }
+        self.bounds_geometry.get()
+    }
+}
@@ -0,0 +1,47 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::XRCameraBinding;
+use crate::dom::bindings::codegen::Bindings::XRCameraBinding::XRCameraMethods;
+use crate::dom::bindings::reflector::{reflect_dom_object, Reflector};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::globalscope::GlobalScope;
+use dom_struct::dom_struct;
+
+#[dom_struct]
+pub struct XRCamera {
+    reflector_: Reflector,
+    width: u32,
+    height: u32,
+}
+
+impl XRCamera {
+    fn new_inherited(width: u32, height: u32) -> XRCamera {
+        XRCamera {
+            reflector_: Reflector::new(),
+            width,
+            height,
+        }
+    }
+
+    pub fn new(global: &GlobalScope, width: u32, height: u32) -> DomRoot<XRCamera> {
+        reflect_dom_object(
+            Box::new(XRCamera::new_inherited(width, height)),
+            global,
+            XRCameraBinding::Wrap,
+        )
+    }
+}
+
+impl XRCameraMethods for XRCamera {
+    /// https://immersive-web.github.io/raw-camera-access/#dom-xrcamera-width
+    fn Width(&self) -> u32 {
+        self.width
+    }
+
+    /// https://immersive-web.github.io/raw-camera-access/#dom-xrcamera-height
+    fn Height(&self) -> u32 {
+        self.height
+    }
+}
@@ -0,0 +1,80 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::XRCompositionLayerBinding::XRCompositionLayerMethods;
+use crate::dom::xrlayer::XRLayer;
+use dom_struct::dom_struct;
+use std::cell::Cell;
+
+/// The state shared by the layers that the device composites, such as
+/// projection and quad layers
+#[dom_struct]
+pub struct XRCompositionLayer {
+    xrlayer: XRLayer,
+    state: Cell<CompositionLayerState>,
+}
+
+impl XRCompositionLayer {
+    pub fn new_inherited() -> XRCompositionLayer {
+        XRCompositionLayer {
+            xrlayer: XRLayer::new_inherited(),
+            state: Cell::new(CompositionLayerState::new()),
+        }
+    }
+
+    pub fn blend_texture_source_alpha(&self) -> bool {
+        self.state.get().blend_texture_source_alpha
+    }
+
+    /// Called once content has been submitted for this layer
+    pub fn clear_needs_redraw(&self) {
+        let mut state = self.state.get();
+        state.needs_redraw = false;
+        self.state.set(state);
+    }
+}
+
+impl XRCompositionLayerMethods for XRCompositionLayer {
+    /// https://immersive-web.github.io/layers/#dom-xrcompositionlayer-blendtexturesourcealpha
+    fn BlendTextureSourceAlpha(&self) -> bool {
+        self.state.get().blend_texture_source_alpha
+    }
+
+    /// https://immersive-web.github.io/layers/#dom-xrcompositionlayer-blendtexturesourcealpha
+    fn SetBlendTextureSourceAlpha(&self, value: bool) {
+        let mut state = self.state.get();
+        state.set_blend_texture_source_alpha(value);
+        self.state.set(state);
+    }
+
+    /// https://immersive-web.github.io/layers/#dom-xrcompositionlayer-needsredraw
+    fn NeedsRedraw(&self) -> bool {
+        self.state.get().needs_redraw
+    }
+}
+
+/// The attributes of an XRCompositionLayer
+#[derive(Clone, Copy, Debug, JSTraceable, MallocSizeOf, PartialEq)]
+pub struct CompositionLayerState {
+    pub blend_texture_source_alpha: bool,
+    /// Whether the layer has to be drawn again before it can be composited,
+    /// e.g. because it was just created or its state changed
+    pub needs_redraw: bool,
+}
+
+impl CompositionLayerState {
+    pub fn new() -> CompositionLayerState {
+        CompositionLayerState {
+            blend_texture_source_alpha: true,
+            needs_redraw: true,
+        }
+    }
+
+    pub fn set_blend_texture_source_alpha(&mut self, value: bool) {
+        if self.blend_texture_source_alpha != value {
+            self.blend_texture_source_alpha = value;
+            self.needs_redraw = true;
+        }
+    }
+}
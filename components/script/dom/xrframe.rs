@@ -2,19 +2,39 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
+use crate::dom::bindings::cell::DomRefCell;
 use crate::dom::bindings::codegen::Bindings::XRFrameBinding;
 use crate::dom::bindings::codegen::Bindings::XRFrameBinding::XRFrameMethods;
-use crate::dom::bindings::error::Error;
+use crate::dom::bindings::codegen::Bindings::XRReferenceSpaceBinding::XRReferenceSpaceMethods;
+use crate::dom::bindings::error::{Error, Fallible};
 use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::num::Finite;
 use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
 use crate::dom::bindings::root::{Dom, DomRoot};
 use crate::dom::globalscope::GlobalScope;
+use crate::dom::promise::Promise;
+use crate::dom::xr::XRFeature;
+use crate::dom::xranchor::XRAnchor;
+use crate::dom::xrhittestresult::XRHitTestResult;
+use crate::dom::xrhittestsource::XRHitTestSource;
+use crate::dom::xrimagetrackingresult::{TrackedImage, XRImageTrackingResult};
+use crate::dom::xrjointpose::XRJointPose;
+use crate::dom::xrjointspace::XRJointSpace;
 use crate::dom::xrpose::XRPose;
 use crate::dom::xrreferencespace::XRReferenceSpace;
+use crate::dom::xrrigidtransform::XRRigidTransform;
 use crate::dom::xrsession::XRSession;
-use crate::dom::xrspace::XRSpace;
+use crate::dom::xrspace::{relative_pose, relative_velocity, typed_pose, ApiSpace, XRSpace};
 use crate::dom::xrviewerpose::XRViewerPose;
 use dom_struct::dom_struct;
+use js::conversions::ToJSValConvertible;
+use js::jsapi::JSContext;
+use js::jsval::{JSVal, UndefinedValue};
+use js::rust::CustomAutoRooterGuard;
+use js::typedarray::Float32Array;
+use std::cell::Cell;
+use std::f32;
+use std::rc::Rc;
 use webvr_traits::WebVRFrameData;
 
 #[dom_struct]
@@ -23,14 +43,29 @@ pub struct XRFrame {
     session: Dom<XRSession>,
     #[ignore_malloc_size_of = "defined in rust-webvr"]
     data: WebVRFrameData,
+    /// The predicted display time of this frame, as passed to the
+    /// XRFrameRequestCallbacks
+    time: f64,
+    /// https://immersive-web.github.io/webxr/#xrframe-active
+    active: Cell<bool>,
+    /// https://immersive-web.github.io/webxr/#xrframe-animationframe
+    animation_frame: Cell<bool>,
+    /// The viewer poses handed out during this frame, along with the
+    /// reference space and the originOffset they were computed for
+    viewer_poses:
+        DomRefCell<FrameCache<(Dom<XRReferenceSpace>, Dom<XRRigidTransform>), Dom<XRViewerPose>>>,
 }
 
 impl XRFrame {
-    fn new_inherited(session: &XRSession, data: WebVRFrameData) -> XRFrame {
+    fn new_inherited(session: &XRSession, data: WebVRFrameData, time: f64) -> XRFrame {
         XRFrame {
             reflector_: Reflector::new(),
             session: Dom::from_ref(session),
             data,
+            time,
+            active: Cell::new(false),
+            animation_frame: Cell::new(false),
+            viewer_poses: DomRefCell::new(FrameCache::new()),
         }
     }
 
@@ -38,13 +73,97 @@ impl XRFrame {
         global: &GlobalScope,
         session: &XRSession,
         data: WebVRFrameData,
+        time: f64,
     ) -> DomRoot<XRFrame> {
         reflect_dom_object(
-            Box::new(XRFrame::new_inherited(session, data)),
+            Box::new(XRFrame::new_inherited(session, data, time)),
             global,
             XRFrameBinding::Wrap,
         )
     }
+
+    /// The time at which this frame is predicted to be displayed
+    pub fn time(&self) -> f64 {
+        self.time
+    }
+
+    pub fn set_active(&self, active: bool) {
+        self.active.set(active);
+        if !active {
+            self.viewer_poses.borrow_mut().clear();
+        }
+    }
+
+    pub fn set_animation_frame(&self, animation_frame: bool) {
+        self.animation_frame.set(animation_frame);
+    }
+
+    fn state(&self) -> FrameState {
+        FrameState {
+            active: self.active.get(),
+            animation_frame: self.animation_frame.get(),
+            session_ended: self.session.is_ended(),
+        }
+    }
+}
+
+/// What decides which methods of an XRFrame can be called
+#[derive(Clone, Copy, Debug, Default)]
+pub struct FrameState {
+    /// https://immersive-web.github.io/webxr/#xrframe-active
+    pub active: bool,
+    /// https://immersive-web.github.io/webxr/#xrframe-animationframe
+    pub animation_frame: bool,
+    /// Whether the session of the frame has ended
+    pub session_ended: bool,
+}
+
+impl FrameState {
+    /// Poses and the other per-frame state can only be queried while the
+    /// frame is active. A session that ends during the callbacks stops
+    /// producing them right away, rather than at the end of the frame.
+    pub fn can_query(&self) -> bool {
+        self.active && !self.session_ended
+    }
+
+    /// Viewer poses are only available in the frames passed to rAF
+    /// callbacks, not in the ones of input source events
+    pub fn can_query_viewer_pose(&self) -> bool {
+        self.can_query() && self.animation_frame
+    }
+}
+
+/// The objects an XRFrame handed out while it was active, so that asking
+/// for the same thing again gives the same object back
+#[derive(JSTraceable, MallocSizeOf)]
+pub struct FrameCache<K, V> {
+    entries: Vec<(K, V)>,
+}
+
+impl<K, V> FrameCache<K, V> {
+    pub fn new() -> FrameCache<K, V> {
+        FrameCache { entries: vec![] }
+    }
+
+    /// The value cached for the first key that `matches`
+    pub fn find<F>(&self, matches: F) -> Option<&V>
+    where
+        F: Fn(&K) -> bool,
+    {
+        self.entries
+            .iter()
+            .find(|(key, _)| matches(key))
+            .map(|(_, value)| value)
+    }
+
+    pub fn insert(&mut self, key: K, value: V) {
+        self.entries.push((key, value));
+    }
+
+    /// Forgets everything, once the frame is no longer active
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
 }
 
 impl XRFrameMethods for XRFrame {
@@ -53,21 +172,42 @@ impl XRFrameMethods for XRFrame {
         DomRoot::from_ref(&self.session)
     }
 
+    /// https://immersive-web.github.io/webxr/#dom-xrframe-predicteddisplaytime
+    fn PredictedDisplayTime(&self) -> Finite<f64> {
+        Finite::wrap(self.time)
+    }
+
     /// https://immersive-web.github.io/webxr/#dom-xrframe-getviewerpose
     fn GetViewerPose(
         &self,
         reference: &XRReferenceSpace,
     ) -> Result<Option<DomRoot<XRViewerPose>>, Error> {
+        if !self.state().can_query_viewer_pose() {
+            return Err(Error::InvalidState);
+        }
         if self.session != reference.upcast::<XRSpace>().session() {
             return Err(Error::InvalidState);
         }
+
+        // Repeated calls for the same space within a frame get the same
+        // pose, unless content has moved the space's origin in between
+        let offset = reference.OriginOffset();
+        let cached = self
+            .viewer_poses
+            .borrow()
+            .find(|(space, space_offset)| *space == reference && *space_offset == &*offset)
+            .map(|pose| DomRoot::from_ref(&**pose));
+        if let Some(pose) = cached {
+            return Ok(Some(pose));
+        }
+
         let pose = reference.get_viewer_pose(&self.data);
-        Ok(Some(XRViewerPose::new(
-            &self.global(),
-            &self.session,
-            pose,
-            &self.data,
-        )))
+        let pose = XRViewerPose::new(&self.global(), &self.session, pose, &self.data);
+        self.viewer_poses.borrow_mut().insert(
+            (Dom::from_ref(reference), Dom::from_ref(&*offset)),
+            Dom::from_ref(&*pose),
+        );
+        Ok(Some(pose))
     }
 
     /// https://immersive-web.github.io/webxr/#dom-xrframe-getpose
@@ -76,12 +216,191 @@ impl XRFrameMethods for XRFrame {
         space: &XRSpace,
         relative_to: &XRSpace,
     ) -> Result<Option<DomRoot<XRPose>>, Error> {
+        if !self.state().can_query() {
+            return Err(Error::InvalidState);
+        }
         if self.session != space.session() || self.session != relative_to.session() {
             return Err(Error::InvalidState);
         }
+        // joints the device lost track of have no pose to give
+        if !is_tracked(space) || !is_tracked(relative_to) {
+            return Ok(None);
+        }
+        let space_velocity = space.get_velocity(&self.data);
+        let base_velocity = relative_to.get_velocity(&self.data);
         let space = space.get_pose(&self.data);
         let relative_to = relative_to.get_pose(&self.data);
-        let pose = relative_to.inverse().pre_mul(&space);
-        Ok(Some(XRPose::new(&self.global(), pose)))
+        let pose = relative_pose(&space, &relative_to);
+        // the relative velocity is only known when both spaces report one
+        let velocity = match (space_velocity, base_velocity) {
+            (Some(a), Some(b)) => Some(relative_velocity(&space, &a, &relative_to, &b)),
+            _ => None,
+        };
+        Ok(Some(XRPose::new(&self.global(), pose, velocity)))
     }
+
+    /// https://immersive-web.github.io/webxr-hand-input/#dom-xrframe-getjointpose
+    fn GetJointPose(
+        &self,
+        joint: &XRJointSpace,
+        base_space: &XRSpace,
+    ) -> Fallible<Option<DomRoot<XRJointPose>>> {
+        if !self.state().can_query() {
+            return Err(Error::InvalidState);
+        }
+        if self.session != joint.upcast::<XRSpace>().session() ||
+            self.session != base_space.session()
+        {
+            return Err(Error::InvalidState);
+        }
+        let (pose, radius) = match joint.tracked_pose() {
+            Some(tracked) => tracked,
+            None => return Ok(None),
+        };
+        if !is_tracked(base_space) {
+            return Ok(None);
+        }
+        let pose = relative_pose(&typed_pose(&pose), &base_space.get_pose(&self.data));
+        Ok(Some(XRJointPose::new(&self.global(), pose, radius)))
+    }
+
+    /// https://immersive-web.github.io/webxr-hand-input/#dom-xrframe-filljointradii
+    #[allow(unsafe_code)]
+    fn FillJointRadii(
+        &self,
+        joints: Vec<DomRoot<XRJointSpace>>,
+        mut radii: CustomAutoRooterGuard<Float32Array>,
+    ) -> Fallible<bool> {
+        if !self.state().can_query() {
+            return Err(Error::InvalidState);
+        }
+        if joints
+            .iter()
+            .any(|joint| self.session != joint.upcast::<XRSpace>().session())
+        {
+            return Err(Error::InvalidState);
+        }
+        if radii.len() < joints.len() {
+            return Err(Error::Type(
+                "radii must have room for every joint".to_owned(),
+            ));
+        }
+        // Invariant to maintain: No JS code that may touch the array should
+        // run whilst we're writing to it
+        let radii = unsafe { radii.as_mut_slice() };
+        let mut all_tracked = true;
+        for (joint, radius) in joints.iter().zip(radii.iter_mut()) {
+            *radius = match joint.tracked_pose() {
+                Some((_, tracked_radius)) => tracked_radius,
+                None => {
+                    all_tracked = false;
+                    f32::NAN
+                },
+            };
+        }
+        Ok(all_tracked)
+    }
+
+    #[allow(unsafe_code)]
+    /// https://immersive-web.github.io/marker-tracking/#dom-xrframe-getimagetrackingresults
+    unsafe fn GetImageTrackingResults(&self, cx: *mut JSContext) -> Fallible<JSVal> {
+        if !self.state().can_query() {
+            return Err(Error::InvalidState);
+        }
+        if !self.session.is_feature_enabled(XRFeature::ImageTracking) {
+            return Err(Error::NotSupported);
+        }
+        let results: Vec<DomRoot<XRImageTrackingResult>> = tracked_images(&self.data)
+            .iter()
+            .map(|image| XRImageTrackingResult::new(&self.global(), &self.session, image))
+            .collect();
+        rooted!(in(cx) let mut jsval = UndefinedValue());
+        results.to_jsval(cx, jsval.handle_mut());
+        Ok(jsval.get())
+    }
+
+    #[allow(unsafe_code)]
+    /// https://immersive-web.github.io/hit-test/#dom-xrframe-gethittestresults
+    unsafe fn GetHitTestResults(
+        &self,
+        cx: *mut JSContext,
+        source: &XRHitTestSource,
+    ) -> Fallible<JSVal> {
+        if !self.state().can_query() {
+            return Err(Error::InvalidState);
+        }
+        if self.session != source.session() {
+            return Err(Error::InvalidState);
+        }
+        // cancelled sources stop hitting anything
+        let hits = if source.is_cancelled() {
+            vec![]
+        } else {
+            let ray = source.ray(&self.data);
+            self.session
+                .display()
+                .hit_test(Some(ray))
+                .unwrap_or_default()
+        };
+        let global = self.global();
+        let results: Vec<DomRoot<XRHitTestResult>> = hits
+            .iter()
+            .map(|hit| XRHitTestResult::new(&global, &self.session, self, hit))
+            .collect();
+        rooted!(in(cx) let mut jsval = UndefinedValue());
+        results.to_jsval(cx, jsval.handle_mut());
+        Ok(jsval.get())
+    }
+
+    /// https://immersive-web.github.io/anchors/#dom-xrframe-createanchor
+    #[allow(unsafe_code)]
+    fn CreateAnchor(&self, pose: &XRRigidTransform, space: &XRSpace) -> Rc<Promise> {
+        let p = unsafe { Promise::new_in_current_compartment(&self.global()) };
+        if !self.state().can_query() {
+            p.reject_error(Error::InvalidState);
+            return p;
+        }
+        if !self.session.is_feature_enabled(XRFeature::Anchors) {
+            p.reject_error(Error::NotSupported);
+            return p;
+        }
+        if self.session != space.session() {
+            p.reject_error(Error::InvalidState);
+            return p;
+        }
+        // the anchor stays where the pose puts it relative to the space
+        // now, even if the space moves later on
+        let pose = typed_pose::<ApiSpace, ApiSpace>(&pose.transform());
+        let anchor_pose = pose.post_mul(&space.get_pose(&self.data));
+        let anchor = XRAnchor::new(&self.global(), &self.session, anchor_pose);
+        self.session.add_anchor(&anchor, p.clone());
+        p
+    }
+
+    #[allow(unsafe_code)]
+    /// https://immersive-web.github.io/anchors/#dom-xrframe-trackedanchors
+    unsafe fn GetTrackedAnchors(&self, cx: *mut JSContext) -> Fallible<JSVal> {
+        if !self.state().can_query() {
+            return Err(Error::InvalidState);
+        }
+        let anchors = self.session.tracked_anchors();
+        rooted!(in(cx) let mut jsval = UndefinedValue());
+        anchors.to_jsval(cx, jsval.handle_mut());
+        Ok(jsval.get())
+    }
+}
+
+/// Whether the device currently knows where `space` is, which only joints
+/// can be lost track of
+fn is_tracked(space: &XRSpace) -> bool {
+    match space.downcast::<XRJointSpace>() {
+        Some(joint) => joint.tracked_pose().is_some(),
+        None => true,
+    }
+}
+
+/// The images of the session's trackedImages that the device reported with
+/// the frame data, which WebVR devices never do
+fn tracked_images(_data: &WebVRFrameData) -> Vec<TrackedImage> {
+    vec![]
 }
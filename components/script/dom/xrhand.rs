@@ -0,0 +1,90 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::XRHandBinding;
+use crate::dom::bindings::codegen::Bindings::XRHandBinding::{XRHandJoint, XRHandMethods};
+use crate::dom::bindings::reflector::{reflect_dom_object, Reflector};
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::xrinputsource::XRInputSource;
+use crate::dom::xrjointspace::XRJointSpace;
+use crate::dom::xrsession::XRSession;
+use dom_struct::dom_struct;
+
+/// https://immersive-web.github.io/webxr-hand-input/#skeleton-joints-section
+///
+/// In the order of XRHandJoint, so that a joint is at its own index.
+pub const JOINTS: [XRHandJoint; 25] = [
+    XRHandJoint::Wrist,
+    XRHandJoint::Thumb_metacarpal,
+    XRHandJoint::Thumb_phalanx_proximal,
+    XRHandJoint::Thumb_phalanx_distal,
+    XRHandJoint::Thumb_tip,
+    XRHandJoint::Index_finger_metacarpal,
+    XRHandJoint::Index_finger_phalanx_proximal,
+    XRHandJoint::Index_finger_phalanx_intermediate,
+    XRHandJoint::Index_finger_phalanx_distal,
+    XRHandJoint::Index_finger_tip,
+    XRHandJoint::Middle_finger_metacarpal,
+    XRHandJoint::Middle_finger_phalanx_proximal,
+    XRHandJoint::Middle_finger_phalanx_intermediate,
+    XRHandJoint::Middle_finger_phalanx_distal,
+    XRHandJoint::Middle_finger_tip,
+    XRHandJoint::Ring_finger_metacarpal,
+    XRHandJoint::Ring_finger_phalanx_proximal,
+    XRHandJoint::Ring_finger_phalanx_intermediate,
+    XRHandJoint::Ring_finger_phalanx_distal,
+    XRHandJoint::Ring_finger_tip,
+    XRHandJoint::Pinky_finger_metacarpal,
+    XRHandJoint::Pinky_finger_phalanx_proximal,
+    XRHandJoint::Pinky_finger_phalanx_intermediate,
+    XRHandJoint::Pinky_finger_phalanx_distal,
+    XRHandJoint::Pinky_finger_tip,
+];
+
+#[dom_struct]
+pub struct XRHand {
+    reflector_: Reflector,
+    source: Dom<XRInputSource>,
+    /// One space per joint, in the order of `JOINTS`
+    joints: Vec<Dom<XRJointSpace>>,
+}
+
+impl XRHand {
+    fn new_inherited(source: &XRInputSource, joints: &[DomRoot<XRJointSpace>]) -> XRHand {
+        XRHand {
+            reflector_: Reflector::new(),
+            source: Dom::from_ref(source),
+            joints: joints.iter().map(|joint| Dom::from_ref(&**joint)).collect(),
+        }
+    }
+
+    pub fn new(
+        global: &GlobalScope,
+        session: &XRSession,
+        source: &XRInputSource,
+    ) -> DomRoot<XRHand> {
+        let joints: Vec<DomRoot<XRJointSpace>> = JOINTS
+            .iter()
+            .map(|&joint| XRJointSpace::new(global, session, source, joint))
+            .collect();
+        reflect_dom_object(
+            Box::new(XRHand::new_inherited(source, &joints)),
+            global,
+            XRHandBinding::Wrap,
+        )
+    }
+}
+
+impl XRHandMethods for XRHand {
+    /// https://immersive-web.github.io/webxr-hand-input/#dom-xrhand-size
+    fn Size(&self) -> u32 {
+        JOINTS.len() as u32
+    }
+
+    /// https://immersive-web.github.io/webxr-hand-input/#dom-xrhand-get
+    fn Get(&self, key: XRHandJoint) -> DomRoot<XRJointSpace> {
+        DomRoot::from_ref(&self.joints[key as usize])
+    }
+}
@@ -0,0 +1,81 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::XRFrameBinding::XRFrameMethods;
+use crate::dom::bindings::codegen::Bindings::XRHitTestResultBinding;
+use crate::dom::bindings::codegen::Bindings::XRHitTestResultBinding::XRHitTestResultMethods;
+use crate::dom::bindings::error::Fallible;
+use crate::dom::bindings::reflector::{reflect_dom_object, Reflector};
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::geometry::normalized_vector;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::xrframe::XRFrame;
+use crate::dom::xrpose::XRPose;
+use crate::dom::xrsession::XRSession;
+use crate::dom::xrspace::{typed_pose, XRSpace};
+use dom_struct::dom_struct;
+use euclid::{Angle, RigidTransform3D, Rotation3D, Vector3D};
+use std::f64::consts::PI;
+use webvr_traits::WebVRHitTestResult;
+
+#[dom_struct]
+pub struct XRHitTestResult {
+    reflector_: Reflector,
+    /// The frame the hit test ran for, which the result is only valid
+    /// during
+    frame: Dom<XRFrame>,
+    /// Stays at the pose of the hit
+    hit_space: Dom<XRSpace>,
+}
+
+impl XRHitTestResult {
+    fn new_inherited(frame: &XRFrame, hit_space: &XRSpace) -> XRHitTestResult {
+        XRHitTestResult {
+            reflector_: Reflector::new(),
+            frame: Dom::from_ref(frame),
+            hit_space: Dom::from_ref(hit_space),
+        }
+    }
+
+    pub fn new(
+        global: &GlobalScope,
+        session: &XRSession,
+        frame: &XRFrame,
+        hit: &WebVRHitTestResult,
+    ) -> DomRoot<XRHitTestResult> {
+        let hit_space = XRSpace::new_fixedspace(global, session, typed_pose(&hit_pose(hit)));
+        reflect_dom_object(
+            Box::new(XRHitTestResult::new_inherited(frame, &hit_space)),
+            global,
+            XRHitTestResultBinding::Wrap,
+        )
+    }
+}
+
+/// The pose of a hit: at the point that was hit, with its Y axis along the
+/// normal of the surface
+pub fn hit_pose(hit: &WebVRHitTestResult) -> RigidTransform3D<f64> {
+    let [x, y, z] = hit.position;
+    let [nx, ny, nz] = hit.normal;
+    let up = Vector3D::new(0., 1., 0.);
+    let normal = Vector3D::new(nx, ny, nz);
+    let axis = up.cross(normal);
+    let cos_angle = up.dot(normal);
+    let rotation = if cos_angle > -1. && cos_angle < 1. {
+        Rotation3D::around_axis(normalized_vector(axis), Angle::radians(cos_angle.acos()))
+    } else if cos_angle == -1. {
+        // any axis perpendicular to Y turns it upside down
+        Rotation3D::around_x(Angle::radians(PI))
+    } else {
+        Rotation3D::identity()
+    };
+    RigidTransform3D::new(rotation, Vector3D::new(x, y, z))
+}
+
+impl XRHitTestResultMethods for XRHitTestResult {
+    /// https://immersive-web.github.io/hit-test/#dom-xrhittestresult-getpose
+    fn GetPose(&self, base_space: &XRSpace) -> Fallible<Option<DomRoot<XRPose>>> {
+        self.frame.GetPose(&self.hit_space, base_space)
+    }
+}
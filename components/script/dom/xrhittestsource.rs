@@ -0,0 +1,86 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::XRHitTestSourceBinding;
+use crate::dom::bindings::codegen::Bindings::XRHitTestSourceBinding::XRHitTestSourceMethods;
+use crate::dom::bindings::error::{Error, Fallible};
+use crate::dom::bindings::reflector::{reflect_dom_object, Reflector};
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::xrray::XRRay;
+use crate::dom::xrsession::XRSession;
+use crate::dom::xrspace::XRSpace;
+use dom_struct::dom_struct;
+use euclid::TypedVector3D;
+use std::cell::Cell;
+use webvr_traits::{WebVRFrameData, WebVRRay};
+
+#[dom_struct]
+pub struct XRHitTestSource {
+    reflector_: Reflector,
+    session: Dom<XRSession>,
+    /// The space the offset ray is cast from
+    space: Dom<XRSpace>,
+    offset_ray: Dom<XRRay>,
+    cancelled: Cell<bool>,
+}
+
+impl XRHitTestSource {
+    fn new_inherited(session: &XRSession, space: &XRSpace, offset_ray: &XRRay) -> XRHitTestSource {
+        XRHitTestSource {
+            reflector_: Reflector::new(),
+            session: Dom::from_ref(session),
+            space: Dom::from_ref(space),
+            offset_ray: Dom::from_ref(offset_ray),
+            cancelled: Cell::new(false),
+        }
+    }
+
+    pub fn new(
+        global: &GlobalScope,
+        session: &XRSession,
+        space: &XRSpace,
+        offset_ray: &XRRay,
+    ) -> DomRoot<XRHitTestSource> {
+        reflect_dom_object(
+            Box::new(XRHitTestSource::new_inherited(session, space, offset_ray)),
+            global,
+            XRHitTestSourceBinding::Wrap,
+        )
+    }
+
+    pub fn session(&self) -> &XRSession {
+        &self.session
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.get()
+    }
+
+    /// The offset ray, carried along with the space it is relative to, in
+    /// the sitting space of the device
+    pub fn ray(&self, data: &WebVRFrameData) -> WebVRRay {
+        let pose = self.space.get_pose(data);
+        // the offset ray is in the coordinates of the space
+        let origin = TypedVector3D::from_untyped(&self.offset_ray.origin());
+        let direction = TypedVector3D::from_untyped(&self.offset_ray.direction());
+        let origin = pose.rotation.rotate_vector3d(&origin) + pose.translation;
+        let direction = pose.rotation.rotate_vector3d(&direction);
+        WebVRRay {
+            origin: [origin.x, origin.y, origin.z],
+            direction: [direction.x, direction.y, direction.z],
+        }
+    }
+}
+
+impl XRHitTestSourceMethods for XRHitTestSource {
+    /// https://immersive-web.github.io/hit-test/#dom-xrhittestsource-cancel
+    fn Cancel(&self) -> Fallible<()> {
+        if self.cancelled.get() {
+            return Err(Error::InvalidState);
+        }
+        self.cancelled.set(true);
+        Ok(())
+    }
+}
@@ -0,0 +1,110 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::XRImageTrackingResultBinding;
+use crate::dom::bindings::codegen::Bindings::XRImageTrackingResultBinding::XRImageTrackingResultMethods;
+use crate::dom::bindings::codegen::Bindings::XRImageTrackingResultBinding::XRImageTrackingState;
+use crate::dom::bindings::num::Finite;
+use crate::dom::bindings::reflector::{reflect_dom_object, Reflector};
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::xrsession::XRSession;
+use crate::dom::xrspace::{typed_pose, XRSpace};
+use dom_struct::dom_struct;
+use euclid::RigidTransform3D;
+
+#[dom_struct]
+pub struct XRImageTrackingResult {
+    reflector_: Reflector,
+    image_space: Dom<XRSpace>,
+    /// The index of the image in the trackedImages the session was
+    /// requested with
+    index: u32,
+    tracking_state: XRImageTrackingState,
+    measured_width: f32,
+}
+
+impl XRImageTrackingResult {
+    fn new_inherited(
+        image_space: &XRSpace,
+        index: u32,
+        tracking_state: XRImageTrackingState,
+        measured_width: f32,
+    ) -> XRImageTrackingResult {
+        XRImageTrackingResult {
+            reflector_: Reflector::new(),
+            image_space: Dom::from_ref(image_space),
+            index,
+            tracking_state,
+            measured_width,
+        }
+    }
+
+    pub fn new(
+        global: &GlobalScope,
+        session: &XRSession,
+        image: &TrackedImage,
+    ) -> DomRoot<XRImageTrackingResult> {
+        let image_space = XRSpace::new_fixedspace(global, session, typed_pose(&image.pose));
+        reflect_dom_object(
+            Box::new(XRImageTrackingResult::new_inherited(
+                &image_space,
+                image.index,
+                image.tracking_state(),
+                image.measured_width,
+            )),
+            global,
+            XRImageTrackingResultBinding::Wrap,
+        )
+    }
+}
+
+/// An image of the session's trackedImages, as reported by the device
+#[derive(Clone, Copy, Debug)]
+pub struct TrackedImage {
+    pub index: u32,
+    /// Where the image is, or was last seen
+    pub pose: RigidTransform3D<f64>,
+    /// Whether the device saw the image in this frame
+    pub seen: bool,
+    /// Whether the device has any idea where the image is, for images it
+    /// didn't see in this frame
+    pub pose_known: bool,
+    pub measured_width: f32,
+}
+
+impl TrackedImage {
+    /// https://immersive-web.github.io/marker-tracking/#enumdef-xrimagetrackingstate
+    pub fn tracking_state(&self) -> XRImageTrackingState {
+        if self.seen {
+            XRImageTrackingState::Tracked
+        } else if self.pose_known {
+            XRImageTrackingState::Emulated
+        } else {
+            XRImageTrackingState::Untracked
+        }
+    }
+}
+
+impl XRImageTrackingResultMethods for XRImageTrackingResult {
+    /// https://immersive-web.github.io/marker-tracking/#dom-xrimagetrackingresult-imagespace
+    fn ImageSpace(&self) -> DomRoot<XRSpace> {
+        DomRoot::from_ref(&self.image_space)
+    }
+
+    /// https://immersive-web.github.io/marker-tracking/#dom-xrimagetrackingresult-index
+    fn Index(&self) -> u32 {
+        self.index
+    }
+
+    /// https://immersive-web.github.io/marker-tracking/#dom-xrimagetrackingresult-trackingstate
+    fn TrackingState(&self) -> XRImageTrackingState {
+        self.tracking_state
+    }
+
+    /// https://immersive-web.github.io/marker-tracking/#dom-xrimagetrackingresult-measuredwidthinmeters
+    fn MeasuredWidthInMeters(&self) -> Finite<f32> {
+        Finite::wrap(self.measured_width)
+    }
+}
@@ -0,0 +1,310 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::GamepadBinding::GamepadMethods;
+use crate::dom::bindings::codegen::Bindings::GamepadButtonListBinding::GamepadButtonListMethods;
+use crate::dom::bindings::codegen::Bindings::XRHandBinding::XRHandJoint;
+use crate::dom::bindings::codegen::Bindings::XRInputSourceBinding;
+use crate::dom::bindings::codegen::Bindings::XRInputSourceBinding::{
+    XRHandedness, XRInputSourceMethods, XRTargetRayMode,
+};
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
+use crate::dom::bindings::root::{Dom, DomRoot, MutNullableDom};
+use crate::dom::bindings::str::DOMString;
+use crate::dom::gamepad::Gamepad;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::xr::XRFeature;
+use crate::dom::xrhand::XRHand;
+use crate::dom::xrsession::XRSession;
+use crate::dom::xrspace::{velocity_at, SpaceVelocity, XRSpace};
+use dom_struct::dom_struct;
+use euclid::{Angle, RigidTransform3D, Rotation3D, Vector3D};
+use js::conversions::ToJSValConvertible;
+use js::jsapi::{Heap, JSContext};
+use js::jsval::{JSVal, UndefinedValue};
+use js::rust::wrappers::JS_FreezeObject;
+use std::cell::Cell;
+use webvr_traits::{WebVRGamepadHand, WebVRPose};
+
+/// The profile that every WebVR gamepad falls back to, since they all
+/// expose at least a primary button
+const GENERIC_PROFILE: &str = "generic-trigger";
+
+/// How far the pointing ray of a controller is tilted down from the axis of
+/// its handle, for devices that don't tell us. This matches the angle most
+/// tracked controllers are designed to be pointed at.
+const TARGET_RAY_TILT_DEGREES: f64 = 30.;
+
+/// The spaces an input source exposes
+#[derive(Clone, Copy, Debug, JSTraceable, MallocSizeOf, PartialEq)]
+pub enum XRInputSpace {
+    /// https://immersive-web.github.io/webxr/#dom-xrinputsource-targetrayspace
+    TargetRay,
+    /// https://immersive-web.github.io/webxr/#dom-xrinputsource-gripspace
+    Grip,
+}
+
+#[dom_struct]
+pub struct XRInputSource {
+    reflector_: Reflector,
+    session: Dom<XRSession>,
+    gamepad: Dom<Gamepad>,
+    handedness: XRHandedness,
+    target_ray_mode: XRTargetRayMode,
+    profiles: Heap<JSVal>,
+    /// Whether the primary action was in progress the last time we looked
+    primary_pressed: Cell<bool>,
+    hand: MutNullableDom<XRHand>,
+    target_ray_space: MutNullableDom<XRSpace>,
+    grip_space: MutNullableDom<XRSpace>,
+    /// The pose of the target ray relative to the grip
+    #[ignore_malloc_size_of = "defined in euclid"]
+    target_ray_offset: RigidTransform3D<f64>,
+}
+
+impl XRInputSource {
+    fn new_inherited(
+        session: &XRSession,
+        gamepad: &Gamepad,
+        handedness: XRHandedness,
+        target_ray_mode: XRTargetRayMode,
+        target_ray_offset: RigidTransform3D<f64>,
+    ) -> XRInputSource {
+        XRInputSource {
+            reflector_: Reflector::new(),
+            session: Dom::from_ref(session),
+            gamepad: Dom::from_ref(gamepad),
+            handedness,
+            target_ray_mode,
+            profiles: Heap::default(),
+            primary_pressed: Cell::new(false),
+            hand: MutNullableDom::default(),
+            target_ray_space: MutNullableDom::default(),
+            grip_space: MutNullableDom::default(),
+            target_ray_offset,
+        }
+    }
+
+    /// Creates an input source with the given profile ids, which must be
+    /// ordered from the most specific to the least specific one
+    ///
+    /// `target_ray_offset` is the pose of the target ray relative to the
+    /// grip, as reported by the device.
+    #[allow(unsafe_code)]
+    pub fn new(
+        global: &GlobalScope,
+        session: &XRSession,
+        gamepad: &Gamepad,
+        handedness: XRHandedness,
+        target_ray_mode: XRTargetRayMode,
+        target_ray_offset: RigidTransform3D<f64>,
+        profiles: &[DOMString],
+    ) -> DomRoot<XRInputSource> {
+        let source = reflect_dom_object(
+            Box::new(XRInputSource::new_inherited(
+                session,
+                gamepad,
+                handedness,
+                target_ray_mode,
+                target_ray_offset,
+            )),
+            global,
+            XRInputSourceBinding::Wrap,
+        );
+
+        let cx = global.get_cx();
+        unsafe {
+            rooted!(in(cx) let mut jsval = UndefinedValue());
+            profiles.to_jsval(cx, jsval.handle_mut());
+            rooted!(in(cx) let array = jsval.to_object());
+            assert!(JS_FreezeObject(cx, array.handle()));
+            source.profiles.set(jsval.get());
+        }
+
+        source
+    }
+
+    /// Creates an input source for a WebVR gamepad
+    pub fn new_from_gamepad(
+        global: &GlobalScope,
+        session: &XRSession,
+        gamepad: &Gamepad,
+    ) -> DomRoot<XRInputSource> {
+        let handedness = match gamepad.hand() {
+            WebVRGamepadHand::Unknown => XRHandedness::None,
+            WebVRGamepadHand::Left => XRHandedness::Left,
+            WebVRGamepadHand::Right => XRHandedness::Right,
+        };
+        let profiles = profiles_from_gamepad_id(&gamepad.Id());
+        let mapping =
+            xr_gamepad_mapping(XRTargetRayMode::Tracked_pointer, gamepad.Buttons().Length());
+        gamepad.set_mapping_type(mapping);
+        gamepad.enable_haptic_actuator();
+        // WebVR doesn't report where controllers point, only where they are
+        XRInputSource::new(
+            global,
+            session,
+            gamepad,
+            handedness,
+            XRTargetRayMode::Tracked_pointer,
+            default_target_ray_offset(),
+            &profiles,
+        )
+    }
+
+    pub fn gamepad(&self) -> &Gamepad {
+        &self.gamepad
+    }
+
+    /// Records the current state of the primary button, returning the
+    /// previous one
+    pub fn swap_primary_pressed(&self, pressed: bool) -> bool {
+        self.primary_pressed.replace(pressed)
+    }
+
+    /// The current pose of one of the spaces of this input source
+    pub fn pose(&self, space: XRInputSpace) -> RigidTransform3D<f64> {
+        let grip = grip_pose_from_vr(&self.gamepad.vr_pose());
+        match space {
+            XRInputSpace::Grip => grip,
+            XRInputSpace::TargetRay => target_ray_from_grip(&grip, &self.target_ray_offset),
+        }
+    }
+
+    /// The current velocity of one of the spaces of this input source, if
+    /// the controller reports one
+    pub fn velocity(&self, space: XRInputSpace) -> Option<SpaceVelocity> {
+        let vr_pose = self.gamepad.vr_pose();
+        let grip = SpaceVelocity::from_vr(vr_pose.linear_velocity, vr_pose.angular_velocity)?;
+        match space {
+            XRInputSpace::Grip => Some(grip),
+            // the target ray is rigidly attached to the grip
+            XRInputSpace::TargetRay => {
+                let from = grip_pose_from_vr(&vr_pose).translation;
+                let to = self.pose(space).translation;
+                Some(velocity_at(&grip, from, to))
+            },
+        }
+    }
+
+    /// The current pose and radius of one of the joints of the hand
+    /// holding this input source, None if the device isn't tracking it
+    pub fn joint_pose(&self, _joint: XRHandJoint) -> Option<(RigidTransform3D<f64>, f32)> {
+        // WebVR gamepads only report the pose of the controller, never the
+        // joints of the hand around it
+        None
+    }
+}
+
+/// The mapping the gamepad of an input source reports
+///
+/// The "xr-standard" layout asks for the primary button at index 0, which
+/// is where input sources already look for it when firing select events.
+/// Only tracked pointers use that layout, and a gamepad without any button
+/// has no primary button to put there.
+pub fn xr_gamepad_mapping(target_ray_mode: XRTargetRayMode, button_count: u32) -> &'static str {
+    match target_ray_mode {
+        XRTargetRayMode::Tracked_pointer if button_count > 0 => "xr-standard",
+        _ => "",
+    }
+}
+
+/// The grip of a WebVR controller sits wherever the device tracks it from
+pub fn grip_pose_from_vr(pose: &WebVRPose) -> RigidTransform3D<f64> {
+    let pos = pose.position.unwrap_or([0., 0., 0.]);
+    let translation = Vector3D::new(pos[0] as f64, pos[1] as f64, pos[2] as f64);
+    let rotation = match pose.orientation {
+        Some(o) => Rotation3D::quaternion(o[0] as f64, o[1] as f64, o[2] as f64, o[3] as f64),
+        None => Rotation3D::identity(),
+    };
+    RigidTransform3D::new(rotation, translation)
+}
+
+/// The pose of the target ray relative to the grip for devices that don't
+/// report one: the ray starts at the grip, but points forward and down from
+/// the handle
+pub fn default_target_ray_offset() -> RigidTransform3D<f64> {
+    let tilt = Rotation3D::around_x(Angle::degrees(-TARGET_RAY_TILT_DEGREES));
+    RigidTransform3D::new(tilt, Vector3D::zero())
+}
+
+/// Places the target ray, given its pose relative to the grip
+pub fn target_ray_from_grip(
+    grip: &RigidTransform3D<f64>,
+    offset: &RigidTransform3D<f64>,
+) -> RigidTransform3D<f64> {
+    offset.post_mul(grip)
+}
+
+/// WebVR only gives us a human readable controller name, turn it into the
+/// lowercase, hyphenated form used by profile ids and follow it with the
+/// generic fallback.
+pub fn profiles_from_gamepad_id(id: &str) -> Vec<DOMString> {
+    let specific = id
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(|word| word.to_ascii_lowercase())
+        .collect::<Vec<_>>()
+        .join("-");
+    let mut profiles = vec![];
+    if !specific.is_empty() && specific != GENERIC_PROFILE {
+        profiles.push(DOMString::from(specific));
+    }
+    profiles.push(DOMString::from(GENERIC_PROFILE));
+    profiles
+}
+
+impl XRInputSourceMethods for XRInputSource {
+    /// https://immersive-web.github.io/webxr/#dom-xrinputsource-handedness
+    fn Handedness(&self) -> XRHandedness {
+        self.handedness
+    }
+
+    /// https://immersive-web.github.io/webxr/#dom-xrinputsource-targetraymode
+    fn TargetRayMode(&self) -> XRTargetRayMode {
+        self.target_ray_mode
+    }
+
+    #[allow(unsafe_code)]
+    /// https://immersive-web.github.io/webxr/#dom-xrinputsource-profiles
+    unsafe fn Profiles(&self, _cx: *mut JSContext) -> JSVal {
+        self.profiles.get()
+    }
+
+    /// https://immersive-web.github.io/webxr/#dom-xrinputsource-targetrayspace
+    fn TargetRaySpace(&self) -> DomRoot<XRSpace> {
+        self.target_ray_space.or_init(|| {
+            XRSpace::new_inputspace(&self.global(), &self.session, self, XRInputSpace::TargetRay)
+        })
+    }
+
+    /// https://immersive-web.github.io/webxr/#dom-xrinputsource-gripspace
+    fn GetGripSpace(&self) -> Option<DomRoot<XRSpace>> {
+        // only tracked controllers have something to hold on to
+        if self.target_ray_mode != XRTargetRayMode::Tracked_pointer {
+            return None;
+        }
+        Some(self.grip_space.or_init(|| {
+            XRSpace::new_inputspace(&self.global(), &self.session, self, XRInputSpace::Grip)
+        }))
+    }
+
+    /// https://immersive-web.github.io/webxr-gamepads-module/#dom-xrinputsource-gamepad
+    fn GetGamepad(&self) -> Option<DomRoot<Gamepad>> {
+        Some(DomRoot::from_ref(&*self.gamepad))
+    }
+
+    /// https://immersive-web.github.io/webxr-hand-input/#dom-xrinputsource-hand
+    fn GetHand(&self) -> Option<DomRoot<XRHand>> {
+        // apps must not see a hand unless they asked for hand tracking
+        // and the device granted it
+        if !self.session.is_feature_enabled(XRFeature::HandTracking) {
+            return None;
+        }
+        Some(
+            self.hand
+                .or_init(|| XRHand::new(&self.global(), &self.session, self)),
+        )
+    }
+}
@@ -0,0 +1,141 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::EventBinding::EventBinding::EventMethods;
+use crate::dom::bindings::codegen::Bindings::XRInputSourcesChangeEventBinding::{
+    self, XRInputSourcesChangeEventMethods,
+};
+use crate::dom::bindings::error::Fallible;
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject};
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::bindings::str::DOMString;
+use crate::dom::event::Event;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::window::Window;
+use crate::dom::xrinputsource::XRInputSource;
+use crate::dom::xrsession::XRSession;
+use dom_struct::dom_struct;
+use js::conversions::ToJSValConvertible;
+use js::jsapi::{Heap, JSContext};
+use js::jsval::{JSVal, UndefinedValue};
+use js::rust::wrappers::JS_FreezeObject;
+use servo_atoms::Atom;
+
+#[dom_struct]
+pub struct XRInputSourcesChangeEvent {
+    event: Event,
+    session: Dom<XRSession>,
+    added: Vec<Dom<XRInputSource>>,
+    removed: Vec<Dom<XRInputSource>>,
+    /// The frozen arrays reflecting `added` and `removed`, created on first
+    /// access
+    added_array: Heap<JSVal>,
+    removed_array: Heap<JSVal>,
+}
+
+impl XRInputSourcesChangeEvent {
+    fn new_inherited(
+        session: &XRSession,
+        added: &[DomRoot<XRInputSource>],
+        removed: &[DomRoot<XRInputSource>],
+    ) -> XRInputSourcesChangeEvent {
+        XRInputSourcesChangeEvent {
+            event: Event::new_inherited(),
+            session: Dom::from_ref(session),
+            added: added
+                .iter()
+                .map(|source| Dom::from_ref(&**source))
+                .collect(),
+            removed: removed
+                .iter()
+                .map(|source| Dom::from_ref(&**source))
+                .collect(),
+            added_array: Heap::default(),
+            removed_array: Heap::default(),
+        }
+    }
+
+    pub fn new(
+        global: &GlobalScope,
+        type_: Atom,
+        bubbles: bool,
+        cancelable: bool,
+        session: &XRSession,
+        added: &[DomRoot<XRInputSource>],
+        removed: &[DomRoot<XRInputSource>],
+    ) -> DomRoot<XRInputSourcesChangeEvent> {
+        let ev = reflect_dom_object(
+            Box::new(XRInputSourcesChangeEvent::new_inherited(
+                session, added, removed,
+            )),
+            global,
+            XRInputSourcesChangeEventBinding::Wrap,
+        );
+        {
+            let event = ev.upcast::<Event>();
+            event.init_event(type_, bubbles, cancelable);
+        }
+        ev
+    }
+
+    pub fn Constructor(
+        window: &Window,
+        type_: DOMString,
+        init: &XRInputSourcesChangeEventBinding::XRInputSourcesChangeEventInit,
+    ) -> Fallible<DomRoot<XRInputSourcesChangeEvent>> {
+        Ok(XRInputSourcesChangeEvent::new(
+            &window.global(),
+            Atom::from(type_),
+            init.parent.bubbles,
+            init.parent.cancelable,
+            &init.session,
+            &init.added,
+            &init.removed,
+        ))
+    }
+
+    /// Reflects `sources` as a frozen array, which is created in `array`
+    /// the first time
+    #[allow(unsafe_code)]
+    unsafe fn frozen_sources(&self, sources: &[Dom<XRInputSource>], array: &Heap<JSVal>) -> JSVal {
+        if array.get().is_undefined() {
+            let cx = self.global().get_cx();
+            let sources: Vec<DomRoot<XRInputSource>> = sources
+                .iter()
+                .map(|source| DomRoot::from_ref(&**source))
+                .collect();
+            rooted!(in(cx) let mut jsval = UndefinedValue());
+            sources.to_jsval(cx, jsval.handle_mut());
+            rooted!(in(cx) let object = jsval.to_object());
+            assert!(JS_FreezeObject(cx, object.handle()));
+            array.set(jsval.get());
+        }
+        array.get()
+    }
+}
+
+impl XRInputSourcesChangeEventMethods for XRInputSourcesChangeEvent {
+    /// https://immersive-web.github.io/webxr/#dom-xrinputsourceschangeevent-session
+    fn Session(&self) -> DomRoot<XRSession> {
+        DomRoot::from_ref(&*self.session)
+    }
+
+    /// https://immersive-web.github.io/webxr/#dom-xrinputsourceschangeevent-added
+    #[allow(unsafe_code)]
+    unsafe fn Added(&self, _cx: *mut JSContext) -> JSVal {
+        self.frozen_sources(&self.added, &self.added_array)
+    }
+
+    /// https://immersive-web.github.io/webxr/#dom-xrinputsourceschangeevent-removed
+    #[allow(unsafe_code)]
+    unsafe fn Removed(&self, _cx: *mut JSContext) -> JSVal {
+        self.frozen_sources(&self.removed, &self.removed_array)
+    }
+
+    /// https://dom.spec.whatwg.org/#dom-event-istrusted
+    fn IsTrusted(&self) -> bool {
+        self.event.IsTrusted()
+    }
+}
@@ -0,0 +1,50 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::XRJointPoseBinding;
+use crate::dom::bindings::codegen::Bindings::XRJointPoseBinding::XRJointPoseMethods;
+use crate::dom::bindings::num::Finite;
+use crate::dom::bindings::reflector::reflect_dom_object;
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::xrpose::XRPose;
+use crate::dom::xrrigidtransform::XRRigidTransform;
+use dom_struct::dom_struct;
+use euclid::RigidTransform3D;
+
+#[dom_struct]
+pub struct XRJointPose {
+    pose: XRPose,
+    /// The radius of the joint, in meters
+    radius: f32,
+}
+
+impl XRJointPose {
+    fn new_inherited(transform: &XRRigidTransform, radius: f32) -> XRJointPose {
+        XRJointPose {
+            pose: XRPose::new_inherited(transform, None),
+            radius,
+        }
+    }
+
+    pub fn new(
+        global: &GlobalScope,
+        pose: RigidTransform3D<f64>,
+        radius: f32,
+    ) -> DomRoot<XRJointPose> {
+        let transform = XRRigidTransform::new(global, pose);
+        reflect_dom_object(
+            Box::new(XRJointPose::new_inherited(&transform, radius)),
+            global,
+            XRJointPoseBinding::Wrap,
+        )
+    }
+}
+
+impl XRJointPoseMethods for XRJointPose {
+    /// https://immersive-web.github.io/webxr-hand-input/#dom-xrjointpose-radius
+    fn Radius(&self) -> Finite<f32> {
+        Finite::wrap(self.radius)
+    }
+}
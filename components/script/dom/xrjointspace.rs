@@ -0,0 +1,73 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::XRHandBinding::XRHandJoint;
+use crate::dom::bindings::codegen::Bindings::XRJointSpaceBinding;
+use crate::dom::bindings::codegen::Bindings::XRJointSpaceBinding::XRJointSpaceMethods;
+use crate::dom::bindings::reflector::reflect_dom_object;
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::xrinputsource::{XRInputSource, XRInputSpace};
+use crate::dom::xrsession::XRSession;
+use crate::dom::xrspace::XRSpace;
+use dom_struct::dom_struct;
+use euclid::RigidTransform3D;
+
+#[dom_struct]
+pub struct XRJointSpace {
+    xrspace: XRSpace,
+    source: Dom<XRInputSource>,
+    joint: XRHandJoint,
+}
+
+impl XRJointSpace {
+    fn new_inherited(
+        session: &XRSession,
+        source: &XRInputSource,
+        joint: XRHandJoint,
+    ) -> XRJointSpace {
+        XRJointSpace {
+            xrspace: XRSpace::new_inherited(session),
+            source: Dom::from_ref(source),
+            joint,
+        }
+    }
+
+    pub fn new(
+        global: &GlobalScope,
+        session: &XRSession,
+        source: &XRInputSource,
+        joint: XRHandJoint,
+    ) -> DomRoot<XRJointSpace> {
+        reflect_dom_object(
+            Box::new(XRJointSpace::new_inherited(session, source, joint)),
+            global,
+            XRJointSpaceBinding::Wrap,
+        )
+    }
+
+    /// The current pose and radius of the joint, None if the device isn't
+    /// tracking it
+    pub fn tracked_pose(&self) -> Option<(RigidTransform3D<f64>, f32)> {
+        self.source.joint_pose(self.joint)
+    }
+
+    /// Gets pose represented by this space
+    ///
+    /// Joints that aren't tracked are taken to be at the grip of their hand,
+    /// check `tracked_pose()` first before handing out poses
+    pub fn get_pose(&self) -> RigidTransform3D<f64> {
+        match self.tracked_pose() {
+            Some((pose, _)) => pose,
+            None => self.source.pose(XRInputSpace::Grip),
+        }
+    }
+}
+
+impl XRJointSpaceMethods for XRJointSpace {
+    /// https://immersive-web.github.io/webxr-hand-input/#dom-xrjointspace-jointname
+    fn JointName(&self) -> XRHandJoint {
+        self.joint
+    }
+}
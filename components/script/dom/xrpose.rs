@@ -4,36 +4,57 @@
 
 use crate::dom::bindings::codegen::Bindings::XRPoseBinding;
 use crate::dom::bindings::codegen::Bindings::XRPoseBinding::XRPoseMethods;
-use crate::dom::bindings::reflector::{reflect_dom_object, Reflector};
-use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
+use crate::dom::bindings::root::{Dom, DomRoot, MutNullableDom};
+use crate::dom::dompointreadonly::DOMPointReadOnly;
 use crate::dom::globalscope::GlobalScope;
 use crate::dom::xrrigidtransform::XRRigidTransform;
+use crate::dom::xrspace::SpaceVelocity;
 use dom_struct::dom_struct;
-use euclid::RigidTransform3D;
+use euclid::{RigidTransform3D, Vector3D};
 
 #[dom_struct]
 pub struct XRPose {
     reflector_: Reflector,
     transform: Dom<XRRigidTransform>,
+    /// In the coordinates of the space the pose is relative to
+    #[ignore_malloc_size_of = "defined in euclid"]
+    velocity: Option<SpaceVelocity>,
+    linear_velocity: MutNullableDom<DOMPointReadOnly>,
+    angular_velocity: MutNullableDom<DOMPointReadOnly>,
 }
 
 impl XRPose {
-    pub fn new_inherited(transform: &XRRigidTransform) -> XRPose {
+    pub fn new_inherited(transform: &XRRigidTransform, velocity: Option<SpaceVelocity>) -> XRPose {
         XRPose {
             reflector_: Reflector::new(),
             transform: Dom::from_ref(transform),
+            velocity,
+            linear_velocity: MutNullableDom::default(),
+            angular_velocity: MutNullableDom::default(),
         }
     }
 
-    #[allow(unused)]
-    pub fn new(global: &GlobalScope, transform: RigidTransform3D<f64>) -> DomRoot<XRPose> {
+    pub fn new(
+        global: &GlobalScope,
+        transform: RigidTransform3D<f64>,
+        velocity: Option<SpaceVelocity>,
+    ) -> DomRoot<XRPose> {
         let transform = XRRigidTransform::new(global, transform);
         reflect_dom_object(
-            Box::new(XRPose::new_inherited(&transform)),
+            Box::new(XRPose::new_inherited(&transform, velocity)),
             global,
             XRPoseBinding::Wrap,
         )
     }
+
+    fn velocity_point(
+        &self,
+        point: &MutNullableDom<DOMPointReadOnly>,
+        vector: Vector3D<f64>,
+    ) -> DomRoot<DOMPointReadOnly> {
+        point.or_init(|| DOMPointReadOnly::new(&self.global(), vector.x, vector.y, vector.z, 0.))
+    }
 }
 
 impl XRPoseMethods for XRPose {
@@ -41,4 +62,16 @@ impl XRPoseMethods for XRPose {
     fn Transform(&self) -> DomRoot<XRRigidTransform> {
         DomRoot::from_ref(&self.transform)
     }
+
+    /// https://immersive-web.github.io/webxr/#dom-xrpose-linearvelocity
+    fn GetLinearVelocity(&self) -> Option<DomRoot<DOMPointReadOnly>> {
+        let velocity = self.velocity?;
+        Some(self.velocity_point(&self.linear_velocity, velocity.linear))
+    }
+
+    /// https://immersive-web.github.io/webxr/#dom-xrpose-angularvelocity
+    fn GetAngularVelocity(&self) -> Option<DomRoot<DOMPointReadOnly>> {
+        let velocity = self.velocity?;
+        Some(self.velocity_point(&self.angular_velocity, velocity.angular))
+    }
 }
@@ -0,0 +1,137 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::XRQuadLayerBinding;
+use crate::dom::bindings::codegen::Bindings::XRQuadLayerBinding::XRQuadLayerMethods;
+use crate::dom::bindings::error::{Error, Fallible};
+use crate::dom::bindings::num::Finite;
+use crate::dom::bindings::reflector::reflect_dom_object;
+use crate::dom::bindings::root::{Dom, DomRoot, MutDom};
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::xrcompositionlayer::XRCompositionLayer;
+use crate::dom::xrrigidtransform::XRRigidTransform;
+use crate::dom::xrsession::XRSession;
+use crate::dom::xrspace::XRSpace;
+use dom_struct::dom_struct;
+use std::cell::Cell;
+use std::ptr;
+
+#[dom_struct]
+pub struct XRQuadLayer {
+    composition_layer: XRCompositionLayer,
+    session: Dom<XRSession>,
+    space: MutDom<XRSpace>,
+    /// The position of the center of the quad in `space`
+    transform: MutDom<XRRigidTransform>,
+    /// The size of the quad, in meters
+    width: Cell<f32>,
+    height: Cell<f32>,
+}
+
+impl XRQuadLayer {
+    fn new_inherited(
+        session: &XRSession,
+        space: &XRSpace,
+        transform: &XRRigidTransform,
+        width: f32,
+        height: f32,
+    ) -> XRQuadLayer {
+        XRQuadLayer {
+            composition_layer: XRCompositionLayer::new_inherited(),
+            session: Dom::from_ref(session),
+            space: MutDom::new(space),
+            transform: MutDom::new(transform),
+            width: Cell::new(width),
+            height: Cell::new(height),
+        }
+    }
+
+    /// https://immersive-web.github.io/layers/#dom-xrwebglbinding-createquadlayer
+    pub fn new(
+        global: &GlobalScope,
+        session: &XRSession,
+        space: &XRSpace,
+        transform: &XRRigidTransform,
+        width: f32,
+        height: f32,
+    ) -> Fallible<DomRoot<XRQuadLayer>> {
+        check_space(session, space)?;
+        check_dimension("width", width)?;
+        check_dimension("height", height)?;
+        Ok(reflect_dom_object(
+            Box::new(XRQuadLayer::new_inherited(
+                session, space, transform, width, height,
+            )),
+            global,
+            XRQuadLayerBinding::Wrap,
+        ))
+    }
+}
+
+/// Layers can only be positioned in spaces of their own session
+fn check_space(session: &XRSession, space: &XRSpace) -> Fallible<()> {
+    if !ptr::eq(space.session(), session) {
+        return Err(Error::InvalidState);
+    }
+    Ok(())
+}
+
+/// Quads have to have an area to be composited
+pub fn check_dimension(name: &str, value: f32) -> Fallible<()> {
+    if value <= 0. {
+        return Err(Error::Type(format!(
+            "The {} of an XRQuadLayer must be positive, not {}",
+            name, value
+        )));
+    }
+    Ok(())
+}
+
+impl XRQuadLayerMethods for XRQuadLayer {
+    /// https://immersive-web.github.io/layers/#dom-xrquadlayer-space
+    fn Space(&self) -> DomRoot<XRSpace> {
+        self.space.get()
+    }
+
+    /// https://immersive-web.github.io/layers/#dom-xrquadlayer-space
+    fn SetSpace(&self, space: &XRSpace) -> Fallible<()> {
+        check_space(&self.session, space)?;
+        self.space.set(space);
+        Ok(())
+    }
+
+    /// https://immersive-web.github.io/layers/#dom-xrquadlayer-transform
+    fn Transform(&self) -> DomRoot<XRRigidTransform> {
+        self.transform.get()
+    }
+
+    /// https://immersive-web.github.io/layers/#dom-xrquadlayer-transform
+    fn SetTransform(&self, transform: &XRRigidTransform) {
+        self.transform.set(transform);
+    }
+
+    /// https://immersive-web.github.io/layers/#dom-xrquadlayer-width
+    fn Width(&self) -> Finite<f32> {
+        Finite::wrap(self.width.get())
+    }
+
+    /// https://immersive-web.github.io/layers/#dom-xrquadlayer-width
+    fn SetWidth(&self, width: Finite<f32>) -> Fallible<()> {
+        check_dimension("width", *width)?;
+        self.width.set(*width);
+        Ok(())
+    }
+
+    /// https://immersive-web.github.io/layers/#dom-xrquadlayer-height
+    fn Height(&self) -> Finite<f32> {
+        Finite::wrap(self.height.get())
+    }
+
+    /// https://immersive-web.github.io/layers/#dom-xrquadlayer-height
+    fn SetHeight(&self, height: Finite<f32>) -> Fallible<()> {
+        check_dimension("height", *height)?;
+        self.height.set(*height);
+        Ok(())
+    }
+}
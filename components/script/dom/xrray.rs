@@ -0,0 +1,169 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::DOMPointBinding::DOMPointInit;
+use crate::dom::bindings::codegen::Bindings::XRRayBinding;
+use crate::dom::bindings::codegen::Bindings::XRRayBinding::{XRRayDirectionInit, XRRayMethods};
+use crate::dom::bindings::error::{Error, Fallible};
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
+use crate::dom::bindings::root::{DomRoot, MutNullableDom};
+use crate::dom::dompointreadonly::DOMPointReadOnly;
+use crate::dom::geometry::normalized_vector;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::vrframedata::create_typed_array;
+use crate::dom::window::Window;
+use crate::dom::xrrigidtransform::{rigid_transform_to_column_major, XRRigidTransform};
+use dom_struct::dom_struct;
+use euclid::{Angle, RigidTransform3D, Rotation3D, Vector3D};
+use js::jsapi::{Heap, JSContext, JSObject};
+use std::f64::consts::PI;
+use std::ptr::NonNull;
+
+#[dom_struct]
+pub struct XRRay {
+    reflector_: Reflector,
+    #[ignore_malloc_size_of = "defined in euclid"]
+    origin: Vector3D<f64>,
+    /// Always normalized
+    #[ignore_malloc_size_of = "defined in euclid"]
+    direction: Vector3D<f64>,
+    origin_point: MutNullableDom<DOMPointReadOnly>,
+    direction_point: MutNullableDom<DOMPointReadOnly>,
+    matrix: Heap<*mut JSObject>,
+}
+
+impl XRRay {
+    fn new_inherited(origin: Vector3D<f64>, direction: Vector3D<f64>) -> XRRay {
+        XRRay {
+            reflector_: Reflector::new(),
+            origin,
+            direction,
+            origin_point: MutNullableDom::default(),
+            direction_point: MutNullableDom::default(),
+            matrix: Heap::default(),
+        }
+    }
+
+    pub fn new(
+        global: &GlobalScope,
+        origin: Vector3D<f64>,
+        direction: Vector3D<f64>,
+    ) -> DomRoot<XRRay> {
+        reflect_dom_object(
+            Box::new(XRRay::new_inherited(origin, direction)),
+            global,
+            XRRayBinding::Wrap,
+        )
+    }
+
+    pub fn origin(&self) -> Vector3D<f64> {
+        self.origin
+    }
+
+    pub fn direction(&self) -> Vector3D<f64> {
+        self.direction
+    }
+
+    /// https://immersive-web.github.io/hit-test/#dom-xrray-xrray
+    pub fn Constructor(
+        window: &Window,
+        origin: &DOMPointInit,
+        direction: &XRRayDirectionInit,
+    ) -> Fallible<DomRoot<Self>> {
+        let direction_vector =
+            normalized_vector(Vector3D::new(*direction.x, *direction.y, *direction.z));
+        if direction_vector == Vector3D::zero() {
+            return Err(Error::Type(
+                "the direction of an XRRay can't be zero".to_owned(),
+            ));
+        }
+        if *direction.w != 0. {
+            return Err(Error::Type(format!(
+                "the direction of an XRRay must have a w value of 0, not {}",
+                *direction.w
+            )));
+        }
+        if origin.w != 1. {
+            return Err(Error::Type(format!(
+                "the origin of an XRRay must have a w value of 1, not {}",
+                origin.w
+            )));
+        }
+        let origin = Vector3D::new(origin.x, origin.y, origin.z);
+        Ok(XRRay::new(&window.global(), origin, direction_vector))
+    }
+
+    /// https://immersive-web.github.io/hit-test/#dom-xrray-xrray-transform
+    pub fn Constructor_(window: &Window, transform: &XRRigidTransform) -> Fallible<DomRoot<Self>> {
+        let (origin, direction) = ray_from_transform(&transform.transform());
+        Ok(XRRay::new(&window.global(), origin, direction))
+    }
+}
+
+/// The ray a transform points along: it starts at the translation of the
+/// transform, towards where the transform rotates -Z to
+///
+/// Both are read off the matrix of the transform, and the direction is
+/// normalized again so that rounding errors in the rotation don't carry
+/// over to the ray.
+pub fn ray_from_transform(transform: &RigidTransform3D<f64>) -> (Vector3D<f64>, Vector3D<f64>) {
+    let matrix = rigid_transform_to_column_major(transform);
+    // the fourth column is the image of (0, 0, 0, 1), the third one the
+    // image of (0, 0, 1, 0)
+    let origin = Vector3D::new(matrix[12], matrix[13], matrix[14]);
+    let direction = normalized_vector(Vector3D::new(-matrix[8], -matrix[9], -matrix[10]));
+    (origin, direction)
+}
+
+/// https://immersive-web.github.io/hit-test/#dom-xrray-matrix
+///
+/// The transform that takes the ray starting at the origin along -Z to the
+/// given ray
+pub fn ray_transform(origin: Vector3D<f64>, direction: Vector3D<f64>) -> RigidTransform3D<f64> {
+    let z = Vector3D::new(0., 0., -1.);
+    let axis = z.cross(direction);
+    let cos_angle = z.dot(direction);
+    let rotation = if cos_angle > -1. && cos_angle < 1. {
+        Rotation3D::around_axis(normalized_vector(axis), Angle::radians(cos_angle.acos()))
+    } else if cos_angle == -1. {
+        // any axis perpendicular to -Z turns it around
+        Rotation3D::around_x(Angle::radians(PI))
+    } else {
+        Rotation3D::identity()
+    };
+    RigidTransform3D::new(rotation, origin)
+}
+
+impl XRRayMethods for XRRay {
+    /// https://immersive-web.github.io/hit-test/#dom-xrray-origin
+    fn Origin(&self) -> DomRoot<DOMPointReadOnly> {
+        self.origin_point.or_init(|| {
+            let o = &self.origin;
+            DOMPointReadOnly::new(&self.global(), o.x, o.y, o.z, 1.0)
+        })
+    }
+
+    /// https://immersive-web.github.io/hit-test/#dom-xrray-direction
+    fn Direction(&self) -> DomRoot<DOMPointReadOnly> {
+        self.direction_point.or_init(|| {
+            let d = &self.direction;
+            DOMPointReadOnly::new(&self.global(), d.x, d.y, d.z, 0.0)
+        })
+    }
+
+    /// https://immersive-web.github.io/hit-test/#dom-xrray-matrix
+    #[allow(unsafe_code)]
+    unsafe fn Matrix(&self, _cx: *mut JSContext) -> NonNull<JSObject> {
+        if self.matrix.get().is_null() {
+            let cx = self.global().get_cx();
+            let transform = ray_transform(self.origin, self.direction);
+            let arr: Vec<f32> = rigid_transform_to_column_major(&transform)
+                .iter()
+                .map(|&x| x as f32)
+                .collect();
+            create_typed_array(cx, &arr, &self.matrix);
+        }
+        NonNull::new(self.matrix.get()).unwrap()
+    }
+}
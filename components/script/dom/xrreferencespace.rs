@@ -4,16 +4,19 @@
 
 use crate::dom::bindings::codegen::Bindings::XRReferenceSpaceBinding;
 use crate::dom::bindings::codegen::Bindings::XRReferenceSpaceBinding::XRReferenceSpaceMethods;
+use crate::dom::bindings::codegen::Bindings::XRSessionBinding::XRReferenceSpaceType;
 use crate::dom::bindings::inheritance::Castable;
-use crate::dom::bindings::reflector::reflect_dom_object;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject};
 use crate::dom::bindings::root::{DomRoot, MutDom};
+use crate::dom::geometry::normalized_rotation;
 use crate::dom::globalscope::GlobalScope;
+use crate::dom::xrboundedreferencespace::XRBoundedReferenceSpace;
 use crate::dom::xrrigidtransform::XRRigidTransform;
 use crate::dom::xrsession::XRSession;
-use crate::dom::xrspace::XRSpace;
+use crate::dom::xrspace::{relative_pose, typed_pose, ApiSpace, NativePose, XRSpace};
 use crate::dom::xrstationaryreferencespace::XRStationaryReferenceSpace;
 use dom_struct::dom_struct;
-use euclid::RigidTransform3D;
+use euclid::{RigidTransform3D, Rotation3D, Vector3D};
 use webvr_traits::WebVRFrameData;
 
 #[dom_struct]
@@ -33,15 +36,64 @@ impl XRReferenceSpace {
     #[allow(unused)]
     pub fn identity(global: &GlobalScope, session: &XRSession) -> DomRoot<XRReferenceSpace> {
         let transform = XRRigidTransform::identity(global);
+        XRReferenceSpace::new(global, session, &transform)
+    }
+
+    fn new(
+        global: &GlobalScope,
+        session: &XRSession,
+        transform: &XRRigidTransform,
+    ) -> DomRoot<XRReferenceSpace> {
         reflect_dom_object(
-            Box::new(XRReferenceSpace::new_inherited(session, &transform)),
+            Box::new(XRReferenceSpace::new_inherited(session, transform)),
             global,
             XRReferenceSpaceBinding::Wrap,
         )
     }
 }
 
+/// Composes `offset` on top of the `base` origin offset, such that `offset` is
+/// applied first
+///
+/// Both orientations are renormalized, so that neither a slightly denormalized
+/// offset nor the error accumulated over a long chain of offset spaces can
+/// make the resulting transform non-rigid.
+pub fn compose_offsets(
+    base: &RigidTransform3D<f64>,
+    offset: &RigidTransform3D<f64>,
+) -> RigidTransform3D<f64> {
+    let offset = RigidTransform3D::new(normalize(&offset.rotation), offset.translation);
+    let composed = offset.post_mul(base);
+    RigidTransform3D::new(normalize(&composed.rotation), composed.translation)
+}
+
+/// Applies the `origin_offset` of a space to the `pose` of its native
+/// origin, such as the floor of a floor-level space
+pub fn offset_pose(pose: &NativePose, origin_offset: &RigidTransform3D<f64>) -> NativePose {
+    // This may change, see https://github.com/immersive-web/webxr/issues/567
+    // originOffset takes the coordinates of this space to those of the
+    // unoffset space
+    let offset = typed_pose::<ApiSpace, ApiSpace>(origin_offset);
+    offset.post_mul(pose)
+}
+
+/// Whether `origin_offset` leaves the space it is applied to where it is
+pub fn is_identity_offset(origin_offset: &RigidTransform3D<f64>) -> bool {
+    let rotation = &origin_offset.rotation;
+    rotation.i == 0. &&
+        rotation.j == 0. &&
+        rotation.k == 0. &&
+        origin_offset.translation == Vector3D::zero()
+}
+
+fn normalize(rotation: &Rotation3D<f64>) -> Rotation3D<f64> {
+    normalized_rotation(rotation.i, rotation.j, rotation.k, rotation.r)
+}
+
 impl XRReferenceSpaceMethods for XRReferenceSpace {
+    // https://immersive-web.github.io/webxr/#dom-xrreferencespace-onreset
+    event_handler!(reset, GetOnreset, SetOnreset);
+
     /// https://immersive-web.github.io/webxr/#dom-xrreferencespace-originoffset
     fn SetOriginOffset(&self, transform: &XRRigidTransform) {
         self.transform.set(transform);
@@ -51,34 +103,62 @@ impl XRReferenceSpaceMethods for XRReferenceSpace {
     fn OriginOffset(&self) -> DomRoot<XRRigidTransform> {
         self.transform.get()
     }
+
+    /// https://immersive-web.github.io/webxr/#dom-xrreferencespace-getoffsetreferencespace
+    fn GetOffsetReferenceSpace(
+        &self,
+        origin_offset: &XRRigidTransform,
+    ) -> DomRoot<XRReferenceSpace> {
+        let global = self.global();
+        let session = self.upcast::<XRSpace>().session();
+        let offset = compose_offsets(
+            &self.transform.get().transform(),
+            &origin_offset.transform(),
+        );
+        let offset = XRRigidTransform::new(&global, offset);
+        let space = if let Some(stationary) = self.downcast::<XRStationaryReferenceSpace>() {
+            DomRoot::upcast(XRStationaryReferenceSpace::new_with_offset(
+                &global,
+                session,
+                stationary.subtype(),
+                &offset,
+            ))
+        } else if self.is::<XRBoundedReferenceSpace>() {
+            DomRoot::upcast(XRBoundedReferenceSpace::new_with_offset(
+                &global, session, &offset,
+            ))
+        } else {
+            XRReferenceSpace::new(&global, session, &offset)
+        };
+        session.add_reference_space(&space);
+        space
+    }
 }
 
 impl XRReferenceSpace {
-    /// Gets pose of the viewer with respect to this space
-    ///
-    /// This is equivalent to `get_pose(self).inverse() * get_pose(viewerSpace)`, however
-    /// we specialize it to be efficient
-    pub fn get_viewer_pose(&self, base_pose: &WebVRFrameData) -> RigidTransform3D<f64> {
-        let pose = self.get_unoffset_viewer_pose(base_pose);
+    /// Whether content left the originOffset of this space alone
+    pub fn has_identity_offset(&self) -> bool {
+        is_identity_offset(&self.transform.get().transform())
+    }
 
-        // This may change, see https://github.com/immersive-web/webxr/issues/567
-        let offset = self.transform.get().transform();
-        let inverse = offset.inverse();
-        inverse.pre_mul(&pose)
+    /// The type of reference space this is
+    pub fn ty(&self) -> XRReferenceSpaceType {
+        if self.is::<XRStationaryReferenceSpace>() {
+            XRReferenceSpaceType::Stationary
+        } else if self.is::<XRBoundedReferenceSpace>() {
+            XRReferenceSpaceType::Bounded
+        } else {
+            XRReferenceSpaceType::Identity
+        }
     }
 
     /// Gets pose of the viewer with respect to this space
     ///
-    /// Does not apply originOffset, use get_viewer_pose instead if you need it
-    pub fn get_unoffset_viewer_pose(&self, base_pose: &WebVRFrameData) -> RigidTransform3D<f64> {
-        if let Some(stationary) = self.downcast::<XRStationaryReferenceSpace>() {
-            stationary.get_unoffset_viewer_pose(base_pose)
-        } else {
-            // non-subclassed XRReferenceSpaces exist, obtained via the "identity"
-            // type. These poses are equivalent to the viewer pose and follow the headset
-            // around, so the viewer is always at an identity transform with respect to them
-            RigidTransform3D::identity()
-        }
+    /// This is computed exactly like `getPose(viewerSpace, self)`, so that
+    /// `getViewerPose()` and `getPose()` agree on where the viewer is
+    pub fn get_viewer_pose(&self, base_pose: &WebVRFrameData) -> RigidTransform3D<f64> {
+        let viewer_pose = XRSpace::viewer_pose_from_frame_data(base_pose);
+        relative_pose(&viewer_pose, &self.get_pose(base_pose))
     }
 
     /// Gets pose represented by this space
@@ -86,20 +166,22 @@ impl XRReferenceSpace {
     /// The reference origin used is common between all
     /// get_pose calls for spaces from the same device, so this can be used to compare
     /// with other spaces
-    pub fn get_pose(&self, base_pose: &WebVRFrameData) -> RigidTransform3D<f64> {
+    ///
+    /// originOffset is always applied on top of the current native origin, so
+    /// it stays put relative to that origin when the device is recentered
+    pub fn get_pose(&self, base_pose: &WebVRFrameData) -> NativePose {
         let pose = self.get_unoffset_pose(base_pose);
-
-        // This may change, see https://github.com/immersive-web/webxr/issues/567
-        let offset = self.transform.get().transform();
-        offset.post_mul(&pose)
+        offset_pose(&pose, &self.transform.get().transform())
     }
 
     /// Gets pose represented by this space
     ///
     /// Does not apply originOffset, use get_viewer_pose instead if you need it
-    pub fn get_unoffset_pose(&self, base_pose: &WebVRFrameData) -> RigidTransform3D<f64> {
+    pub fn get_unoffset_pose(&self, base_pose: &WebVRFrameData) -> NativePose {
         if let Some(stationary) = self.downcast::<XRStationaryReferenceSpace>() {
             stationary.get_unoffset_pose(base_pose)
+        } else if let Some(bounded) = self.downcast::<XRBoundedReferenceSpace>() {
+            bounded.get_unoffset_pose(base_pose)
         } else {
             // non-subclassed XRReferenceSpaces exist, obtained via the "identity"
             // type. These are equivalent to the viewer pose and follow the headset
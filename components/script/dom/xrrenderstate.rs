@@ -2,15 +2,22 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
+use crate::dom::bindings::cell::DomRefCell;
 use crate::dom::bindings::codegen::Bindings::XRRenderStateBinding::{self, XRRenderStateMethods};
+use crate::dom::bindings::inheritance::Castable;
 use crate::dom::bindings::num::Finite;
-use crate::dom::bindings::reflector::reflect_dom_object;
 use crate::dom::bindings::reflector::Reflector;
-use crate::dom::bindings::root::{DomRoot, MutNullableDom};
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject};
+use crate::dom::bindings::root::{Dom, DomRoot, MutNullableDom};
 use crate::dom::globalscope::GlobalScope;
 use crate::dom::xrlayer::XRLayer;
+use crate::dom::xrwebgllayer::XRWebGLLayer;
 
 use dom_struct::dom_struct;
+use js::conversions::ToJSValConvertible;
+use js::jsapi::{Heap, JSContext};
+use js::jsval::{JSVal, UndefinedValue};
+use js::rust::wrappers::JS_FreezeObject;
 use std::cell::Cell;
 
 #[dom_struct]
@@ -19,6 +26,12 @@ pub struct XRRenderState {
     depth_near: Cell<f64>,
     depth_far: Cell<f64>,
     layer: MutNullableDom<XRLayer>,
+    /// The layers of the layers model, only one of this and the base layer
+    /// can be set
+    layers: DomRefCell<Vec<Dom<XRLayer>>>,
+    /// The frozen array reflecting `layers`, created on first access. Active
+    /// render states are never modified, so it can't go stale.
+    layers_array: Heap<JSVal>,
 }
 
 impl XRRenderState {
@@ -26,12 +39,15 @@ impl XRRenderState {
         depth_near: f64,
         depth_far: f64,
         layer: Option<&XRLayer>,
+        layers: &[&XRLayer],
     ) -> XRRenderState {
         XRRenderState {
             reflector_: Reflector::new(),
             depth_near: Cell::new(depth_near),
             depth_far: Cell::new(depth_far),
             layer: MutNullableDom::new(layer),
+            layers: DomRefCell::new(layers.iter().map(|layer| Dom::from_ref(*layer)).collect()),
+            layers_array: Heap::default(),
         }
     }
 
@@ -40,13 +56,123 @@ impl XRRenderState {
         depth_near: f64,
         depth_far: f64,
         layer: Option<&XRLayer>,
+        layers: &[&XRLayer],
     ) -> DomRoot<XRRenderState> {
         reflect_dom_object(
-            Box::new(XRRenderState::new_inherited(depth_near, depth_far, layer)),
+            Box::new(XRRenderState::new_inherited(
+                depth_near, depth_far, layer, layers,
+            )),
             global,
             XRRenderStateBinding::Wrap,
         )
     }
+
+    pub fn clone_object(&self) -> DomRoot<Self> {
+        let layers = self.layers.borrow();
+        let layers: Vec<&XRLayer> = layers.iter().map(|layer| &**layer).collect();
+        XRRenderState::new(
+            &self.global(),
+            self.depth_near.get(),
+            self.depth_far.get(),
+            self.layer.get().as_ref().map(|x| &**x),
+            &layers,
+        )
+    }
+
+    pub fn depth_near(&self) -> f64 {
+        self.depth_near.get()
+    }
+
+    pub fn depth_far(&self) -> f64 {
+        self.depth_far.get()
+    }
+
+    pub fn layer(&self) -> Option<DomRoot<XRLayer>> {
+        self.layer.get()
+    }
+
+    pub fn fields(&self) -> RenderStateFields<DomRoot<XRLayer>> {
+        RenderStateFields {
+            depth_near: self.depth_near.get(),
+            depth_far: self.depth_far.get(),
+            base_layer: self.layer.get(),
+            layers: self
+                .layers
+                .borrow()
+                .iter()
+                .map(|layer| DomRoot::from_ref(&**layer))
+                .collect(),
+        }
+    }
+
+    pub fn set_fields(&self, fields: &RenderStateFields<DomRoot<XRLayer>>) {
+        self.depth_near.set(fields.depth_near);
+        self.depth_far.set(fields.depth_far);
+        self.layer
+            .set(fields.base_layer.as_ref().map(|layer| &**layer));
+        *self.layers.borrow_mut() = fields
+            .layers
+            .iter()
+            .map(|layer| Dom::from_ref(&**layer))
+            .collect();
+    }
+
+    /// The WebGL layer that gets presented on the display: either the base
+    /// layer, or the first WebGL layer of the layers model. The WebVR
+    /// compositor can only show a single WebGL layer, so the composition
+    /// layers aren't presented.
+    pub fn presented_layer(&self) -> Option<DomRoot<XRWebGLLayer>> {
+        if let Some(layer) = self.layer.get() {
+            return layer.downcast::<XRWebGLLayer>().map(DomRoot::from_ref);
+        }
+        self.layers
+            .borrow()
+            .iter()
+            .filter_map(|layer| layer.downcast::<XRWebGLLayer>())
+            .next()
+            .map(DomRoot::from_ref)
+    }
+}
+
+/// The values of a render state, generic over the layer type so that
+/// merging updates can be done without DOM objects
+#[derive(Clone, Debug, PartialEq)]
+pub struct RenderStateFields<L> {
+    pub depth_near: f64,
+    pub depth_far: f64,
+    pub base_layer: Option<L>,
+    pub layers: Vec<L>,
+}
+
+/// The fields set by a single updateRenderState() call, None for the ones
+/// it leaves alone
+#[derive(Clone, Debug, PartialEq)]
+pub struct RenderStateUpdate<L> {
+    pub depth_near: Option<f64>,
+    pub depth_far: Option<f64>,
+    pub base_layer: Option<L>,
+    pub layers: Option<Vec<L>>,
+}
+
+/// Applies an update to a pending render state. Only the fields present in
+/// the update are changed, so later calls overwrite the values set by
+/// earlier ones without resetting the others.
+pub fn merge_render_state<L>(state: &mut RenderStateFields<L>, update: RenderStateUpdate<L>) {
+    if let Some(near) = update.depth_near {
+        state.depth_near = near;
+    }
+    if let Some(far) = update.depth_far {
+        state.depth_far = far;
+    }
+    // The base layer and the layers model replace each other
+    if let Some(layer) = update.base_layer {
+        state.base_layer = Some(layer);
+        state.layers.clear();
+    }
+    if let Some(layers) = update.layers {
+        state.base_layer = None;
+        state.layers = layers;
+    }
 }
 
 impl XRRenderStateMethods for XRRenderState {
@@ -64,4 +190,31 @@ impl XRRenderStateMethods for XRRenderState {
     fn GetBaseLayer(&self) -> Option<DomRoot<XRLayer>> {
         self.layer.get()
     }
+
+    /// https://immersive-web.github.io/webxr/#dom-xrrenderstate-passthroughfullyobscured
+    fn GetPassthroughFullyObscured(&self) -> Option<bool> {
+        // WebVR displays are fully virtual, they have no passthrough that
+        // could be obscured
+        None
+    }
+
+    /// https://immersive-web.github.io/layers/#dom-xrrenderstate-layers
+    #[allow(unsafe_code)]
+    unsafe fn Layers(&self, _cx: *mut JSContext) -> JSVal {
+        if self.layers_array.get().is_undefined() {
+            let cx = self.global().get_cx();
+            let layers: Vec<DomRoot<XRLayer>> = self
+                .layers
+                .borrow()
+                .iter()
+                .map(|layer| DomRoot::from_ref(&**layer))
+                .collect();
+            rooted!(in(cx) let mut jsval = UndefinedValue());
+            layers.to_jsval(cx, jsval.handle_mut());
+            rooted!(in(cx) let array = jsval.to_object());
+            assert!(JS_FreezeObject(cx, array.handle()));
+            self.layers_array.set(jsval.get());
+        }
+        self.layers_array.get()
+    }
 }
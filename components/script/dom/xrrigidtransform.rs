@@ -3,6 +3,8 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
 use crate::dom::bindings::codegen::Bindings::DOMPointBinding::DOMPointInit;
+use crate::dom::bindings::codegen::Bindings::NavigatorBinding::NavigatorMethods;
+use crate::dom::bindings::codegen::Bindings::WindowBinding::WindowMethods;
 use crate::dom::bindings::codegen::Bindings::XRRigidTransformBinding;
 use crate::dom::bindings::codegen::Bindings::XRRigidTransformBinding::XRRigidTransformMethods;
 use crate::dom::bindings::error::Error;
@@ -10,15 +12,41 @@ use crate::dom::bindings::error::Fallible;
 use crate::dom::bindings::reflector::DomObject;
 use crate::dom::bindings::reflector::{reflect_dom_object, Reflector};
 use crate::dom::bindings::root::{DomRoot, MutNullableDom};
+use crate::dom::dommatrixreadonly::transform3d_to_column_major;
 use crate::dom::dompointreadonly::DOMPointReadOnly;
+use crate::dom::geometry::{all_finite, normalized_rotation, normalized_vector};
 use crate::dom::globalscope::GlobalScope;
 use crate::dom::vrframedata::create_typed_array;
 use crate::dom::window::Window;
 use dom_struct::dom_struct;
-use euclid::{RigidTransform3D, Rotation3D, Vector3D};
+use euclid::{RigidTransform3D, Rotation3D, Transform3D, Vector3D};
 use js::jsapi::{Heap, JSContext, JSObject};
 use std::ptr::NonNull;
 
+/// Identifies the XRRigidTransforms that were constructed from the same
+/// position and orientation
+///
+/// The elements are compared bit for bit: transforms that are merely close
+/// would read back different values from the ones script passed in.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct TransformKey([u64; 7]);
+
+unsafe_no_jsmanaged_fields!(TransformKey);
+
+impl TransformKey {
+    pub fn new(position: &DOMPointInit, orientation: &DOMPointInit) -> TransformKey {
+        TransformKey([
+            position.x.to_bits(),
+            position.y.to_bits(),
+            position.z.to_bits(),
+            orientation.x.to_bits(),
+            orientation.y.to_bits(),
+            orientation.z.to_bits(),
+            orientation.w.to_bits(),
+        ])
+    }
+}
+
 #[dom_struct]
 pub struct XRRigidTransform {
     reflector_: Reflector,
@@ -26,6 +54,8 @@ pub struct XRRigidTransform {
     orientation: MutNullableDom<DOMPointReadOnly>,
     #[ignore_malloc_size_of = "defined in euclid"]
     transform: RigidTransform3D<f64>,
+    /// Created on first access, so that repeated reads return the same
+    /// object
     inverse: MutNullableDom<XRRigidTransform>,
     matrix: Heap<*mut JSObject>,
 }
@@ -64,6 +94,23 @@ impl XRRigidTransform {
         position: &DOMPointInit,
         orientation: &DOMPointInit,
     ) -> Fallible<DomRoot<Self>> {
+        let elements = [
+            position.x,
+            position.y,
+            position.z,
+            position.w,
+            orientation.x,
+            orientation.y,
+            orientation.z,
+            orientation.w,
+        ];
+        if !all_finite(&elements) {
+            return Err(Error::Type(
+                "XRRigidTransform must be constructed from finite values".to_owned(),
+            ));
+        }
+        // Positions aren't divided by w, a point that isn't normalized would
+        // silently end up at the wrong translation
         if position.w != 1.0 {
             return Err(Error::Type(format!(
                 "XRRigidTransform must be constructed with a position that has a w value of of 1.0, not {}",
@@ -71,16 +118,97 @@ impl XRRigidTransform {
             )));
         }
 
-        let translate = Vector3D::new(position.x as f64, position.y as f64, position.z as f64);
-        let rotate = Rotation3D::unit_quaternion(
-            orientation.x as f64,
-            orientation.y as f64,
-            orientation.z as f64,
-            orientation.w as f64,
-        );
-        let transform = RigidTransform3D::new(rotate, translate);
-        Ok(XRRigidTransform::new(&window.global(), transform))
+        // a quaternion of length zero has no orientation to normalize to
+        if elements[4..].iter().all(|&element| element == 0.) {
+            return Err(Error::InvalidState);
+        }
+
+        // The euclid transform is all that render loops need, the position
+        // and orientation points are only created once script reads them
+        let translate = Vector3D::new(position.x, position.y, position.z);
+        let rotate =
+            normalized_rotation(orientation.x, orientation.y, orientation.z, orientation.w);
+        let create =
+            || XRRigidTransform::from_position_rotation(&window.global(), translate, rotate);
+        // XRRigidTransforms are immutable, so pages that build the same
+        // transforms over and over can share them
+        if pref!(dom.webxr.intern_transforms) {
+            let key = TransformKey::new(position, orientation);
+            return Ok(window.Navigator().Xr().intern_transform(key, create));
+        }
+        Ok(create())
+    }
+
+    /// Creates a transform that rotates by `rotation` and then translates by
+    /// `translation`, without going through DOMPoints
+    ///
+    /// The position and orientation points are only created if script asks
+    /// for them.
+    pub fn from_position_rotation(
+        global: &GlobalScope,
+        translation: Vector3D<f64>,
+        rotation: Rotation3D<f64>,
+    ) -> DomRoot<XRRigidTransform> {
+        XRRigidTransform::new(global, RigidTransform3D::new(rotation, translation))
+    }
+
+    /// Creates one XRRigidTransform per matrix, for when the device hands us a
+    /// whole set of joints at once
+    pub fn batch_from_matrices(
+        global: &GlobalScope,
+        matrices: &[Transform3D<f64>],
+    ) -> Vec<DomRoot<XRRigidTransform>> {
+        matrices
+            .iter()
+            .map(|matrix| XRRigidTransform::new(global, rigid_transform_from_matrix(matrix)))
+            .collect()
+    }
+}
+
+/// The column-major elements of the matrix of `transform`
+pub fn rigid_transform_to_column_major(transform: &RigidTransform3D<f64>) -> [f64; 16] {
+    transform3d_to_column_major(&transform.to_transform())
+}
+
+/// Decomposes a matrix into a rotation followed by a translation
+///
+/// Any scale or shear in the upper 3x3 is removed by orthonormalizing it
+/// first, so that matrices that have drifted slightly due to floating point
+/// error still produce a valid unit quaternion.
+pub fn rigid_transform_from_matrix(matrix: &Transform3D<f64>) -> RigidTransform3D<f64> {
+    let translation = Vector3D::new(matrix.m41, matrix.m42, matrix.m43);
+
+    // Gram-Schmidt on the rows of the rotation part
+    let x = normalized_vector(Vector3D::new(matrix.m11, matrix.m12, matrix.m13));
+    let y = Vector3D::new(matrix.m21, matrix.m22, matrix.m23);
+    let y = normalized_vector(y - x * y.dot(x));
+    // a matrix that flattens the rotation part has no orientation left
+    if x == Vector3D::zero() || y == Vector3D::zero() {
+        return RigidTransform3D::new(Rotation3D::identity(), translation);
     }
+    let z = x.cross(y);
+    let (m11, m12, m13) = (x.x, x.y, x.z);
+    let (m21, m22, m23) = (y.x, y.y, y.z);
+    let (m31, m32, m33) = (z.x, z.y, z.z);
+
+    // Pick the largest of the four components to divide by, to keep the
+    // conversion numerically stable
+    let trace = m11 + m22 + m33;
+    let (i, j, k, r) = if trace > 0. {
+        let s = (1. + trace).sqrt() * 2.;
+        ((m23 - m32) / s, (m31 - m13) / s, (m12 - m21) / s, s / 4.)
+    } else if m11 > m22 && m11 > m33 {
+        let s = (1. + m11 - m22 - m33).sqrt() * 2.;
+        (s / 4., (m12 + m21) / s, (m13 + m31) / s, (m23 - m32) / s)
+    } else if m22 > m33 {
+        let s = (1. - m11 + m22 - m33).sqrt() * 2.;
+        ((m12 + m21) / s, s / 4., (m23 + m32) / s, (m31 - m13) / s)
+    } else {
+        let s = (1. - m11 - m22 + m33).sqrt() * 2.;
+        ((m13 + m31) / s, (m23 + m32) / s, s / 4., (m12 - m21) / s)
+    };
+
+    RigidTransform3D::new(normalized_rotation(i, j, k, r), translation)
 }
 
 impl XRRigidTransformMethods for XRRigidTransform {
@@ -93,24 +221,27 @@ impl XRRigidTransformMethods for XRRigidTransform {
     }
     // https://immersive-web.github.io/webxr/#dom-xrrigidtransform-orientation
     fn Orientation(&self) -> DomRoot<DOMPointReadOnly> {
-        self.position.or_init(|| {
+        self.orientation.or_init(|| {
             let r = &self.transform.rotation;
             DOMPointReadOnly::new(&self.global(), r.i, r.j, r.k, r.r)
         })
     }
     // https://immersive-web.github.io/webxr/#dom-xrrigidtransform-inverse
     fn Inverse(&self) -> DomRoot<XRRigidTransform> {
-        self.inverse
-            .or_init(|| XRRigidTransform::new(&self.global(), self.transform.inverse()))
+        self.inverse.or_init(|| {
+            let inverse = XRRigidTransform::new(&self.global(), self.transform.inverse());
+            // inverting twice gives back this very transform, rather than a
+            // copy that merely has the same values
+            inverse.inverse.set(Some(self));
+            inverse
+        })
     }
     // https://immersive-web.github.io/webxr/#dom-xrrigidtransform-matrix
     #[allow(unsafe_code)]
     unsafe fn Matrix(&self, _cx: *mut JSContext) -> NonNull<JSObject> {
         if self.matrix.get().is_null() {
             let cx = self.global().get_cx();
-            // According to the spec all matrices are column-major,
-            // however euclid uses row vectors so we use .to_row_major_array()
-            let arr = self.transform.to_transform().cast().to_row_major_array();
+            let arr: Vec<f32> = self.matrix_f64_array().iter().map(|&x| x as f32).collect();
             create_typed_array(cx, &arr, &self.matrix);
         }
         NonNull::new(self.matrix.get()).unwrap()
@@ -122,4 +253,43 @@ impl XRRigidTransform {
     pub fn transform(&self) -> RigidTransform3D<f64> {
         self.transform
     }
+
+    /// The column-major matrix of this transform, at full precision, for
+    /// consumers that shouldn't lose precision to the Float32Array of `matrix`
+    pub fn matrix_f64_array(&self) -> [f64; 16] {
+        rigid_transform_to_column_major(&self.transform)
+    }
+
+    /// Creates the transform that is `t` of the way from this one to `other`,
+    /// see interpolate_rigid_transforms()
+    pub fn lerp(
+        &self,
+        global: &GlobalScope,
+        other: &XRRigidTransform,
+        t: f64,
+    ) -> DomRoot<XRRigidTransform> {
+        XRRigidTransform::new(
+            global,
+            interpolate_rigid_transforms(&self.transform, &other.transform, t),
+        )
+    }
+
+    /// Like lerp(), but writes the result to `out` instead of creating a new
+    /// XRRigidTransform, for animations that interpolate poses every frame
+    pub fn lerp_into(&self, other: &XRRigidTransform, t: f64, out: &mut RigidTransform3D<f64>) {
+        *out = interpolate_rigid_transforms(&self.transform, &other.transform, t);
+    }
+}
+
+/// Interpolates the position of two transforms linearly, and their
+/// orientation along the shortest arc between them
+pub fn interpolate_rigid_transforms(
+    from: &RigidTransform3D<f64>,
+    to: &RigidTransform3D<f64>,
+    t: f64,
+) -> RigidTransform3D<f64> {
+    RigidTransform3D::new(
+        from.rotation.slerp(&to.rotation, t),
+        from.translation.lerp(to.translation, t),
+    )
 }
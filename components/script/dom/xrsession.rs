@@ -2,8 +2,15 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::GamepadBinding::GamepadMethods;
+use crate::dom::bindings::codegen::Bindings::GamepadButtonBinding::GamepadButtonMethods;
+use crate::dom::bindings::codegen::Bindings::GamepadButtonListBinding::GamepadButtonListMethods;
+use crate::dom::bindings::codegen::Bindings::NavigatorBinding::NavigatorMethods;
 use crate::dom::bindings::codegen::Bindings::VRDisplayBinding::VRDisplayMethods;
+use crate::dom::bindings::codegen::Bindings::WindowBinding::WindowMethods;
 use crate::dom::bindings::codegen::Bindings::XRBinding::XRSessionMode;
+use crate::dom::bindings::codegen::Bindings::XRHitTestSourceBinding::XRHitTestOptionsInit;
 use crate::dom::bindings::codegen::Bindings::XRRenderStateBinding::XRRenderStateInit;
 use crate::dom::bindings::codegen::Bindings::XRSessionBinding;
 use crate::dom::bindings::codegen::Bindings::XRSessionBinding::XREnvironmentBlendMode;
@@ -11,43 +18,157 @@ use crate::dom::bindings::codegen::Bindings::XRSessionBinding::XRFrameRequestCal
 use crate::dom::bindings::codegen::Bindings::XRSessionBinding::XRReferenceSpaceOptions;
 use crate::dom::bindings::codegen::Bindings::XRSessionBinding::XRReferenceSpaceType;
 use crate::dom::bindings::codegen::Bindings::XRSessionBinding::XRSessionMethods;
+use crate::dom::bindings::codegen::Bindings::XRStationaryReferenceSpaceBinding::XRStationaryReferenceSpaceSubtype;
 use crate::dom::bindings::error::Error;
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::num::Finite;
+use crate::dom::bindings::refcounted::Trusted;
 use crate::dom::bindings::reflector::{reflect_dom_object, DomObject};
-use crate::dom::bindings::root::{Dom, DomRoot, MutNullableDom};
+use crate::dom::bindings::root::{Dom, DomRoot, MutDom, MutNullableDom};
+use crate::dom::bindings::str::DOMString;
+use crate::dom::bindings::weakref::DOMTracker;
+use crate::dom::event::Event;
 use crate::dom::eventtarget::EventTarget;
 use crate::dom::globalscope::GlobalScope;
 use crate::dom::promise::Promise;
 use crate::dom::vrdisplay::VRDisplay;
-use crate::dom::xrlayer::XRLayer;
+use crate::dom::xr::XRFeature;
+use crate::dom::xranchor::XRAnchor;
+use crate::dom::xrboundedreferencespace::XRBoundedReferenceSpace;
+use crate::dom::xrframe::XRFrame;
+use crate::dom::xrhittestsource::XRHitTestSource;
+use crate::dom::xrinputsource::XRInputSource;
+use crate::dom::xrinputsourceevent::XRInputSourceEvent;
+use crate::dom::xrinputsourceschangeevent::XRInputSourcesChangeEvent;
+use crate::dom::xrray::XRRay;
 use crate::dom::xrreferencespace::XRReferenceSpace;
-use crate::dom::xrrenderstate::XRRenderState;
-use crate::dom::xrspace::XRSpace;
-use crate::dom::xrstationaryreferencespace::XRStationaryReferenceSpace;
+use crate::dom::xrrenderstate::{merge_render_state, RenderStateUpdate, XRRenderState};
+use crate::dom::xrsessionevent::XRSessionEvent;
+use crate::dom::xrspace::{self, XRSpace};
+use crate::dom::xrstationaryreferencespace::{floor_pose, XRStationaryReferenceSpace};
+use crate::dom::xrview::{MAX_VIEWPORT_SCALE, MIN_VIEWPORT_SCALE};
+use crate::dom::xrwebgllayer::XRWebGLLayer;
+use crate::task_source::TaskSource;
 use dom_struct::dom_struct;
+use euclid::Vector3D;
+use js::conversions::ToJSValConvertible;
+use js::jsapi::{Heap, JSContext};
+use js::jsval::{JSVal, UndefinedValue};
+use js::rust::wrappers::JS_FreezeObject;
+use servo_atoms::Atom;
+use std::cell::Cell;
+use std::mem;
 use std::rc::Rc;
+use webvr_traits::WebVRFrameData;
 
 #[dom_struct]
 pub struct XRSession {
     eventtarget: EventTarget,
     display: Dom<VRDisplay>,
-    base_layer: MutNullableDom<XRLayer>,
+    mode: XRSessionMode,
     blend_mode: XREnvironmentBlendMode,
+    active_render_state: MutDom<XRRenderState>,
+    /// The render state that will be applied at the start of the next frame,
+    /// accumulating all updateRenderState() calls made in the meantime
+    pending_render_state: MutNullableDom<XRRenderState>,
+    /// Every input source this session has seen, so that a controller keeps
+    /// the same XRInputSource for as long as the session lives
+    input_sources: DomRefCell<Vec<Dom<XRInputSource>>>,
+    /// The gamepad ids of the input sources content was told about at the
+    /// last frame, in the order they are listed in `inputSources`
+    active_input_sources: DomRefCell<Vec<u32>>,
+    /// The frozen array reflecting `active_input_sources`, created on first
+    /// access and replaced whenever the input sources change
+    input_sources_array: Heap<JSVal>,
+    /// When the input sources that are no longer connected were lost
+    lost_input_sources: DomRefCell<LostInputSources>,
+    /// The reference spaces handed out by this session, which need to be
+    /// told when the device is recentered. They are held weakly, so that
+    /// the spaces content is done with don't pile up
+    stationary_spaces: DOMTracker<XRStationaryReferenceSpace>,
+    bounded_spaces: DOMTracker<XRBoundedReferenceSpace>,
+    /// The viewer space handed out by requestReferenceSpace()
+    viewer_space: DomRefCell<ViewerSpaceCache<Dom<XRReferenceSpace>>>,
+    frame_rate_limiter: DomRefCell<FrameRateLimiter>,
+    /// Maps the timestamps of the device poses to the time of the page
+    pose_clock: DomRefCell<PoseClock>,
+    /// The frame rate content asked for with updateTargetFrameRate(), if any
+    target_frame_rate: Cell<Option<f32>>,
+    /// Measures the rate at which the device actually delivers frames
+    device_frame_rate: DomRefCell<DeviceFrameRate>,
+    /// The frame rate content is currently rendered at, if known
+    frame_rate: Cell<Option<f32>>,
+    /// The viewport scale suggested to content for new views, if any
+    recommended_viewport_scale: Cell<Option<f64>>,
+    /// The features granted when the session was requested
+    enabled_features: Vec<XRFeature>,
+    /// https://immersive-web.github.io/webxr/#ended
+    ended: Cell<bool>,
+    /// https://immersive-web.github.io/anchors/#xrsession-set-of-tracked-anchors
+    tracked_anchors: DomRefCell<Vec<Dom<XRAnchor>>>,
+    /// The anchors created since the last frame, which start being tracked
+    /// at the next one
+    #[ignore_malloc_size_of = "Rc is hard"]
+    pending_anchors: DomRefCell<Vec<(Dom<XRAnchor>, Rc<Promise>)>>,
+    frames: Cell<FrameCounter>,
+    /// Where the floor of floor-level and bounded spaces was when they were
+    /// last reset
+    floor_origin: Cell<FloorOrigin>,
 }
 
 impl XRSession {
-    fn new_inherited(display: &VRDisplay) -> XRSession {
+    fn new_inherited(
+        display: &VRDisplay,
+        mode: XRSessionMode,
+        render_state: &XRRenderState,
+        enabled_features: Vec<XRFeature>,
+    ) -> XRSession {
         XRSession {
             eventtarget: EventTarget::new_inherited(),
             display: Dom::from_ref(display),
-            base_layer: Default::default(),
-            // we don't yet support any AR devices
-            blend_mode: XREnvironmentBlendMode::Opaque,
+            mode,
+            blend_mode: environment_blend_mode(mode),
+            active_render_state: MutDom::new(render_state),
+            pending_render_state: MutNullableDom::new(None),
+            input_sources: DomRefCell::new(vec![]),
+            active_input_sources: DomRefCell::new(vec![]),
+            input_sources_array: Heap::default(),
+            lost_input_sources: DomRefCell::new(LostInputSources::new(
+                LOST_INPUT_SOURCE_GRACE_PERIOD_MS,
+            )),
+            stationary_spaces: DOMTracker::new(),
+            bounded_spaces: DOMTracker::new(),
+            viewer_space: DomRefCell::new(ViewerSpaceCache::new()),
+            frame_rate_limiter: DomRefCell::new(FrameRateLimiter::new()),
+            pose_clock: DomRefCell::new(PoseClock::new()),
+            target_frame_rate: Cell::new(None),
+            device_frame_rate: DomRefCell::new(DeviceFrameRate::new()),
+            frame_rate: Cell::new(None),
+            recommended_viewport_scale: Cell::new(None),
+            enabled_features,
+            ended: Cell::new(false),
+            tracked_anchors: DomRefCell::new(vec![]),
+            pending_anchors: DomRefCell::new(vec![]),
+            frames: Cell::new(FrameCounter::default()),
+            floor_origin: Cell::new(FloorOrigin::default()),
         }
     }
 
-    pub fn new(global: &GlobalScope, display: &VRDisplay) -> DomRoot<XRSession> {
+    pub fn new(
+        global: &GlobalScope,
+        display: &VRDisplay,
+        mode: XRSessionMode,
+        enabled_features: Vec<XRFeature>,
+    ) -> DomRoot<XRSession> {
+        let render_state =
+            XRRenderState::new(global, *display.DepthNear(), *display.DepthFar(), None, &[]);
         reflect_dom_object(
-            Box::new(XRSession::new_inherited(display)),
+            Box::new(XRSession::new_inherited(
+                display,
+                mode,
+                &render_state,
+                enabled_features,
+            )),
             global,
             XRSessionBinding::Wrap,
         )
@@ -61,26 +182,407 @@ impl XRSession {
         &self.display
     }
 
-    pub fn set_layer(&self, layer: &XRLayer) {
-        self.base_layer.set(Some(layer))
+    pub fn is_feature_enabled(&self, feature: XRFeature) -> bool {
+        self.enabled_features.contains(&feature)
+    }
+
+    pub fn is_ended(&self) -> bool {
+        self.ended.get()
+    }
+
+    /// Returns the input sources for the gamepads currently connected to
+    /// this session's display, creating any that are new
+    fn sync_input_sources(&self) -> Vec<DomRoot<XRInputSource>> {
+        let global = self.global();
+        let gamepads = global.as_window().Navigator().Xr().get_gamepads();
+        let display_id = self.display.DisplayId();
+        gamepads
+            .iter()
+            .filter(|gamepad| gamepad.DisplayId() == display_id && gamepad.Connected())
+            .map(|gamepad| {
+                let existing = self
+                    .input_sources
+                    .borrow()
+                    .iter()
+                    .find(|source| source.gamepad().gamepad_id() == gamepad.gamepad_id())
+                    .map(|source| DomRoot::from_ref(&**source));
+                existing.unwrap_or_else(|| {
+                    let source = XRInputSource::new_from_gamepad(&global, self, gamepad);
+                    self.input_sources
+                        .borrow_mut()
+                        .push(Dom::from_ref(&*source));
+                    source
+                })
+            })
+            .collect()
+    }
+
+    /// Returns the connected input sources, followed by the ones that were
+    /// lost within the last LOST_INPUT_SOURCE_GRACE_PERIOD_MS
+    fn tracked_input_sources(&self) -> Vec<DomRoot<XRInputSource>> {
+        let mut sources = self.sync_input_sources();
+        let lost = self.lost_input_sources.borrow();
+        sources.extend(
+            self.input_sources
+                .borrow()
+                .iter()
+                .filter(|source| lost.is_recently_lost(source.gamepad().gamepad_id()))
+                .map(|source| DomRoot::from_ref(&**source)),
+        );
+        sources
+    }
+
+    /// Fires the inputsourceschange event if input sources were connected
+    /// or lost since the last frame, and the select events for the input
+    /// sources whose primary button was pressed or released since then
+    pub fn update_input_sources(&self, data: &WebVRFrameData, now: f64) {
+        let sources = self.sync_input_sources();
+        {
+            let mut lost = self.lost_input_sources.borrow_mut();
+            for source in self.input_sources.borrow().iter() {
+                let id = source.gamepad().gamepad_id();
+                let connected = sources.iter().any(|s| s.gamepad().gamepad_id() == id);
+                lost.update(id, connected, now);
+            }
+        }
+        self.update_active_input_sources(&sources);
+        for source in sources {
+            let pressed = source
+                .gamepad()
+                .Buttons()
+                .Item(0)
+                .map_or(false, |button| button.Pressed());
+            let was_pressed = source.swap_primary_pressed(pressed);
+            if pressed && !was_pressed {
+                self.fire_input_source_event(atom!("selectstart"), &source, data, now);
+            } else if !pressed && was_pressed {
+                self.fire_input_source_event(atom!("select"), &source, data, now);
+                self.fire_input_source_event(atom!("selectend"), &source, data, now);
+            }
+        }
+    }
+
+    /// Makes `sources` the input sources listed in `inputSources`, and tells
+    /// content which of them are new and which ones are gone
+    fn update_active_input_sources(&self, sources: &[DomRoot<XRInputSource>]) {
+        let ids: Vec<u32> = sources
+            .iter()
+            .map(|source| source.gamepad().gamepad_id())
+            .collect();
+        let previous = mem::replace(&mut *self.active_input_sources.borrow_mut(), ids.clone());
+        if previous == ids {
+            return;
+        }
+        self.input_sources_array.set(UndefinedValue());
+
+        let added: Vec<DomRoot<XRInputSource>> = sources
+            .iter()
+            .filter(|source| !previous.contains(&source.gamepad().gamepad_id()))
+            .cloned()
+            .collect();
+        let removed: Vec<DomRoot<XRInputSource>> = self
+            .input_sources
+            .borrow()
+            .iter()
+            .filter(|source| {
+                let id = source.gamepad().gamepad_id();
+                previous.contains(&id) && !ids.contains(&id)
+            })
+            .map(|source| DomRoot::from_ref(&**source))
+            .collect();
+        if added.is_empty() && removed.is_empty() {
+            return;
+        }
+        let global = self.global();
+        let event = XRInputSourcesChangeEvent::new(
+            &global,
+            atom!("inputsourceschange"),
+            false,
+            false,
+            self,
+            &added,
+            &removed,
+        );
+        event.upcast::<Event>().fire(self.upcast());
+    }
+
+    /// https://immersive-web.github.io/webxr/#fire-an-input-source-event
+    fn fire_input_source_event(
+        &self,
+        type_: Atom,
+        source: &XRInputSource,
+        data: &WebVRFrameData,
+        now: f64,
+    ) {
+        let global = self.global();
+        let frame = XRFrame::new(&global, self, data.clone(), now);
+        let event = XRInputSourceEvent::new(&global, type_, false, false, &frame, source);
+        // The frame is only usable while the event is being dispatched, any
+        // XRFrame the content holds onto afterwards is inactive
+        frame.set_active(true);
+        event.upcast::<Event>().fire(self.upcast());
+        frame.set_active(false);
+    }
+
+    /// Extrapolates the latest device pose to the time at which the frame
+    /// rendered from it is expected to be displayed
+    ///
+    /// The pose is extrapolated from the time the device sampled it rather
+    /// than from `now`, so the latency of getting it to us is made up for
+    /// as well. Returns the predicted frame data along with the display time.
+    pub fn predict_frame_data(&self, data: &WebVRFrameData, now: f64) -> (WebVRFrameData, f64) {
+        let interval = self.frame_rate_limiter.borrow().interval();
+        let display_time = predicted_display_time(now, interval);
+        let sample_time = self
+            .pose_clock
+            .borrow_mut()
+            .sample_time(data.timestamp, now);
+        let dt = prediction_interval(sample_time, display_time);
+        let predicted = xrspace::extrapolate_frame_data(data, dt / 1000.);
+        (predicted, display_time)
+    }
+
+    /// Starts a new animation frame, making the views of the previous one
+    /// stale
+    pub fn next_frame(&self) {
+        let mut frames = self.frames.get();
+        frames.next_frame();
+        self.frames.set(frames);
+    }
+
+    /// The animation frame that is running, or that ran last
+    pub fn frame_id(&self) -> u64 {
+        self.frames.get().current()
+    }
+
+    pub fn is_current_frame(&self, frame_id: u64) -> bool {
+        self.frames.get().is_current(frame_id)
+    }
+
+    /// Whether the device frame at `now` should run the rAF callbacks,
+    /// given the target frame rate requested by content
+    pub fn should_run_frame(&self, now: f64) -> bool {
+        self.frame_rate_limiter.borrow_mut().should_run(now)
+    }
+
+    /// Called for every frame the device delivers, picks up changes of
+    /// the device frame rate (e.g. when it gets throttled)
+    pub fn note_device_frame(&self, now: f64) {
+        let (device_rate, full_rate) = {
+            let mut device = self.device_frame_rate.borrow_mut();
+            (device.observe(now), device.full_rate())
+        };
+        if let (Some(device_rate), Some(full_rate)) = (device_rate, full_rate) {
+            self.set_recommended_viewport_scale(viewport_scale_for_frame_rate(
+                device_rate,
+                full_rate,
+            ));
+            self.set_frame_rate(negotiated_frame_rate(
+                self.target_frame_rate.get(),
+                Some(device_rate),
+            ));
+        }
+    }
+
+    /// Changes the rate at which content renders, be it at the request of
+    /// content or because the device can no longer keep up with it
+    ///
+    /// Content is told with a frameratechange event when the rate changes.
+    fn set_frame_rate(&self, rate: Option<f32>) {
+        if self.frame_rate.replace(rate) != rate {
+            let global = self.global();
+            let window = global.as_window();
+            window
+                .task_manager()
+                .dom_manipulation_task_source()
+                .queue_simple_event(self.upcast(), atom!("frameratechange"), window);
+        }
+    }
+
+    pub fn recommended_viewport_scale(&self) -> Option<f64> {
+        self.recommended_viewport_scale.get()
+    }
+
+    /// Publishes a viewport scale hint for the views of coming frames
+    fn set_recommended_viewport_scale(&self, scale: Option<f64>) {
+        self.recommended_viewport_scale.set(scale);
+    }
+
+    /// Keeps track of a reference space created for this session, so that it
+    /// gets reset events
+    ///
+    /// Identity spaces follow the viewer rather than the native origin, so
+    /// recentering the device doesn't affect them
+    pub fn add_reference_space(&self, space: &XRReferenceSpace) {
+        if let Some(stationary) = space.downcast::<XRStationaryReferenceSpace>() {
+            self.stationary_spaces.track(stationary);
+        } else if let Some(bounded) = space.downcast::<XRBoundedReferenceSpace>() {
+            self.bounded_spaces.track(bounded);
+        }
+    }
+
+    /// Called when the device has been recentered
+    ///
+    /// The poses of the reference spaces are computed against the native
+    /// origin every frame, so they pick up the new origin on their own; all
+    /// that is left is letting content know
+    pub fn notify_reset(&self) {
+        self.queue_resets(OriginChange::Recenter);
+        // The spaces standing on the floor were reset along with the
+        // others, wherever the floor is now
+        let mut floor_origin = FloorOrigin::default();
+        floor_origin.update(self.display.sitting_to_standing_transform());
+        self.floor_origin.set(floor_origin);
+    }
+
+    /// Queues a reset event on each reference space whose native origin
+    /// was moved by `change`
+    fn queue_resets(&self, change: OriginChange) {
+        // The reference spaces can't produce poses anymore once the session
+        // has ended, so they don't need to hear about it either
+        if self.ended.get() {
+            return;
+        }
+        let global = self.global();
+        let window = global.as_window();
+        let reset = |space: &XRReferenceSpace| {
+            let subtype = space
+                .downcast::<XRStationaryReferenceSpace>()
+                .map(|space| space.subtype());
+            if !fires_reset(space.ty(), subtype, change) {
+                return;
+            }
+            window
+                .task_manager()
+                .dom_manipulation_task_source()
+                .queue_simple_event(space.upcast(), atom!("reset"), window);
+        };
+        self.stationary_spaces
+            .for_each(|space| reset(space.upcast()));
+        self.bounded_spaces.for_each(|space| reset(space.upcast()));
+    }
+
+    /// Called when the device data has been updated, in case the play
+    /// area has changed
+    pub fn update_bounds(&self) {
+        if self.ended.get() {
+            return;
+        }
+        let mut floor_origin = self.floor_origin.get();
+        if floor_origin.update(self.display.sitting_to_standing_transform()) {
+            self.queue_resets(OriginChange::FloorMoved);
+        }
+        self.floor_origin.set(floor_origin);
+        self.bounded_spaces.for_each(|space| space.update_bounds());
+    }
+
+    /// Queues an anchor for tracking, `promise` is resolved with it once
+    /// it is tracked
+    pub fn add_anchor(&self, anchor: &XRAnchor, promise: Rc<Promise>) {
+        self.pending_anchors
+            .borrow_mut()
+            .push((Dom::from_ref(anchor), promise));
+    }
+
+    /// Starts tracking the anchors created since the last frame
+    ///
+    /// https://immersive-web.github.io/anchors/#update-anchors
+    pub fn update_tracked_anchors(&self) {
+        let pending: Vec<(DomRoot<XRAnchor>, Rc<Promise>)> = self
+            .pending_anchors
+            .borrow()
+            .iter()
+            .map(|(anchor, promise)| (DomRoot::from_ref(&**anchor), promise.clone()))
+            .collect();
+        self.pending_anchors.borrow_mut().clear();
+        for (anchor, promise) in pending {
+            self.tracked_anchors
+                .borrow_mut()
+                .push(Dom::from_ref(&*anchor));
+            promise.resolve_native(&anchor);
+        }
+    }
+
+    /// The anchors tracked in the current frame
+    pub fn tracked_anchors(&self) -> Vec<DomRoot<XRAnchor>> {
+        self.tracked_anchors
+            .borrow()
+            .iter()
+            .map(|anchor| DomRoot::from_ref(&**anchor))
+            .collect()
+    }
+
+    /// https://immersive-web.github.io/anchors/#dom-xranchor-delete
+    pub fn delete_anchor(&self, anchor: &XRAnchor) {
+        anchor.set_deleted();
+        self.tracked_anchors
+            .borrow_mut()
+            .retain(|tracked| *tracked != anchor);
+        // an anchor deleted before it was tracked never gets created
+        let promise = self
+            .pending_anchors
+            .borrow()
+            .iter()
+            .find(|(pending, _)| *pending == anchor)
+            .map(|(_, promise)| promise.clone());
+        if let Some(promise) = promise {
+            self.pending_anchors
+                .borrow_mut()
+                .retain(|(pending, _)| *pending != anchor);
+            promise.reject_error(Error::InvalidState);
+        }
+    }
+
+    /// https://immersive-web.github.io/webxr/#apply-the-pending-render-state
+    ///
+    /// Returns the new active render state if there were pending changes
+    pub fn apply_pending_render_state(&self) -> Option<DomRoot<XRRenderState>> {
+        let pending = self.pending_render_state.take()?;
+        self.active_render_state.set(&pending);
+        Some(pending)
     }
 }
 
 impl XRSessionMethods for XRSession {
+    // https://immersive-web.github.io/webxr/#eventdef-xrsession-select
+    event_handler!(select, GetOnselect, SetOnselect);
+
+    // https://immersive-web.github.io/webxr/#eventdef-xrsession-inputsourceschange
+    event_handler!(
+        inputsourceschange,
+        GetOninputsourceschange,
+        SetOninputsourceschange
+    );
+
+    // https://immersive-web.github.io/webxr/#eventdef-xrsession-selectstart
+    event_handler!(selectstart, GetOnselectstart, SetOnselectstart);
+
+    // https://immersive-web.github.io/webxr/#eventdef-xrsession-end
+    event_handler!(end, GetOnend, SetOnend);
+
+    // https://immersive-web.github.io/webxr/#eventdef-xrsession-selectend
+    event_handler!(selectend, GetOnselectend, SetOnselectend);
+
+    // https://immersive-web.github.io/webxr/#eventdef-xrsession-squeeze
+    event_handler!(squeeze, GetOnsqueeze, SetOnsqueeze);
+
+    // https://immersive-web.github.io/webxr/#eventdef-xrsession-squeezestart
+    event_handler!(squeezestart, GetOnsqueezestart, SetOnsqueezestart);
+
+    // https://immersive-web.github.io/webxr/#eventdef-xrsession-squeezeend
+    event_handler!(squeezeend, GetOnsqueezeend, SetOnsqueezeend);
+
+    // https://immersive-web.github.io/webxr/#eventdef-xrsession-frameratechange
+    event_handler!(frameratechange, GetOnframeratechange, SetOnframeratechange);
+
     /// https://immersive-web.github.io/webxr/#dom-xrsession-mode
     fn Mode(&self) -> XRSessionMode {
-        XRSessionMode::Immersive_vr
+        self.mode
     }
 
     // https://immersive-web.github.io/webxr/#dom-xrsession-renderstate
     fn RenderState(&self) -> DomRoot<XRRenderState> {
-        // XXXManishearth maybe cache this
-        XRRenderState::new(
-            &self.global(),
-            *self.display.DepthNear(),
-            *self.display.DepthFar(),
-            self.base_layer.get().as_ref().map(|l| &**l),
-        )
+        self.active_render_state.get()
     }
 
     // https://immersive-web.github.io/webxr/#dom-xrsession-viewerspace
@@ -88,16 +590,131 @@ impl XRSessionMethods for XRSession {
         XRSpace::new_viewerspace(&self.global(), &self)
     }
 
-    /// https://immersive-web.github.io/webxr/#dom-xrsession-requestanimationframe
+    /// https://immersive-web.github.io/webxr/#dom-xrsession-updaterenderstate
     #[allow(unsafe_code)]
     fn UpdateRenderState(&self, init: &XRRenderStateInit) -> Rc<Promise> {
         let p = unsafe { Promise::new_in_current_compartment(&self.global()) };
-        self.display.queue_renderstate(init, p.clone());
+
+        if self.ended.get() {
+            p.reject_error(Error::InvalidState);
+            return p;
+        }
+
+        // composition layers such as quad layers can't be the base layer
+        if let Some(ref layer) = init.baseLayer {
+            if !layer.is::<XRWebGLLayer>() {
+                p.reject_error(Error::Type(format!(
+                    "the base layer must be an XRWebGLLayer"
+                )));
+                return p;
+            }
+        }
+
+        // https://immersive-web.github.io/layers/#updaterenderstatechanges
+        if let Some(ref layers) = init.layers {
+            if init.baseLayer.is_some() {
+                p.reject_error(Error::NotSupported);
+                return p;
+            }
+            for (i, layer) in layers.iter().enumerate() {
+                if layers[..i].iter().any(|other| other == layer) {
+                    p.reject_error(Error::Type("the same layer can't be used twice".to_owned()));
+                    return p;
+                }
+            }
+        }
+
+        let mut state = self
+            .pending_render_state
+            .get()
+            .unwrap_or_else(|| self.active_render_state.get())
+            .fields();
+        merge_render_state(
+            &mut state,
+            RenderStateUpdate {
+                depth_near: init.depthNear.map(|near| *near),
+                depth_far: init.depthFar.map(|far| *far),
+                base_layer: init
+                    .baseLayer
+                    .as_ref()
+                    .map(|layer| DomRoot::from_ref(&**layer)),
+                layers: init.layers.as_ref().map(|layers| {
+                    layers
+                        .iter()
+                        .map(|layer| DomRoot::from_ref(&**layer))
+                        .collect()
+                }),
+            },
+        );
+
+        // The depth range is checked as a whole, so that an update that only
+        // sets one of the planes can't leave a degenerate range behind
+        if let Err(error) = validate_depth_range(state.depth_near, state.depth_far) {
+            p.reject_error(error);
+            return p;
+        }
+
+        self.pending_render_state
+            .or_init(|| self.active_render_state.get().clone_object())
+            .set_fields(&state);
+
+        self.display.queue_renderstate(p.clone());
+        p
+    }
+
+    /// https://immersive-web.github.io/webxr/#dom-xrsession-framerate
+    fn GetFrameRate(&self) -> Option<Finite<f32>> {
+        self.frame_rate.get().map(Finite::wrap)
+    }
+
+    /// https://immersive-web.github.io/webxr/#dom-xrsession-issystemkeyboardsupported
+    fn IsSystemKeyboardSupported(&self) -> bool {
+        // WebVR devices have no way of raising a keyboard of their own
+        false
+    }
+
+    #[allow(unsafe_code)]
+    /// https://immersive-web.github.io/webxr/#dom-xrsession-enabledfeatures
+    unsafe fn EnabledFeatures(&self, cx: *mut JSContext) -> JSVal {
+        let features: Vec<DOMString> = self
+            .enabled_features
+            .iter()
+            .map(|feature| DOMString::from(feature.descriptor()))
+            .collect();
+        rooted!(in(cx) let mut jsval = UndefinedValue());
+        features.to_jsval(cx, jsval.handle_mut());
+        jsval.get()
+    }
+
+    /// https://immersive-web.github.io/webxr/#dom-xrsession-updatetargetframerate
+    #[allow(unsafe_code)]
+    fn UpdateTargetFrameRate(&self, rate: Finite<f32>) -> Rc<Promise> {
+        let p = unsafe { Promise::new_in_current_compartment(&self.global()) };
+        if *rate <= 0. {
+            p.reject_error(Error::Type(format!(
+                "the target frame rate must be positive, not {}",
+                *rate
+            )));
+        } else {
+            self.target_frame_rate.set(Some(*rate));
+            self.frame_rate_limiter
+                .borrow_mut()
+                .set_frame_rate(Some(*rate as f64));
+            self.set_frame_rate(negotiated_frame_rate(
+                Some(*rate),
+                self.device_frame_rate.borrow().rate(),
+            ));
+            p.resolve_native(&());
+        }
         p
     }
 
     /// https://immersive-web.github.io/webxr/#dom-xrsession-requestanimationframe
     fn RequestAnimationFrame(&self, callback: Rc<XRFrameRequestCallback>) -> i32 {
+        // an ended session has no more frames to run callbacks for
+        if self.ended.get() {
+            return 0;
+        }
         self.display.xr_raf(callback) as i32
     }
 
@@ -111,34 +728,105 @@ impl XRSessionMethods for XRSession {
         self.blend_mode
     }
 
+    #[allow(unsafe_code)]
+    /// https://immersive-web.github.io/webxr/#dom-xrsession-getinputsources
+    unsafe fn GetInputSources(&self, cx: *mut JSContext) -> JSVal {
+        rooted!(in(cx) let mut sources = UndefinedValue());
+        self.sync_input_sources().to_jsval(cx, sources.handle_mut());
+        sources.get()
+    }
+
+    #[allow(unsafe_code)]
+    /// https://immersive-web.github.io/webxr/#dom-xrsession-inputsources
+    unsafe fn InputSources(&self, cx: *mut JSContext) -> JSVal {
+        if self.input_sources_array.get().is_undefined() {
+            let input_sources = self.input_sources.borrow();
+            let sources: Vec<DomRoot<XRInputSource>> = self
+                .active_input_sources
+                .borrow()
+                .iter()
+                .filter_map(|&id| {
+                    input_sources
+                        .iter()
+                        .find(|source| source.gamepad().gamepad_id() == id)
+                        .map(|source| DomRoot::from_ref(&**source))
+                })
+                .collect();
+            rooted!(in(cx) let mut jsval = UndefinedValue());
+            sources.to_jsval(cx, jsval.handle_mut());
+            rooted!(in(cx) let array = jsval.to_object());
+            assert!(JS_FreezeObject(cx, array.handle()));
+            self.input_sources_array.set(jsval.get());
+        }
+        self.input_sources_array.get()
+    }
+
+    #[allow(unsafe_code)]
+    /// Non-standard: the input sources along with the ones that were lost
+    /// recently, so that content can keep showing them while they're
+    /// momentarily out of tracking
+    unsafe fn GetTrackedSources(&self, cx: *mut JSContext) -> JSVal {
+        rooted!(in(cx) let mut sources = UndefinedValue());
+        self.tracked_input_sources()
+            .to_jsval(cx, sources.handle_mut());
+        sources.get()
+    }
+
     /// https://immersive-web.github.io/webxr/#dom-xrsession-requestreferencespace
     #[allow(unsafe_code)]
     fn RequestReferenceSpace(&self, options: &XRReferenceSpaceOptions) -> Rc<Promise> {
         let p = unsafe { Promise::new_in_current_compartment(&self.global()) };
 
+        if self.ended.get() {
+            p.reject_error(Error::InvalidState);
+            return p;
+        }
+
         // https://immersive-web.github.io/webxr/#create-a-reference-space
 
         // XXXManishearth reject based on session type
         // https://github.com/immersive-web/webxr/blob/master/spatial-tracking-explainer.md#practical-usage-guidelines
 
+        // Even capable devices only hand out the spaces that were asked for
+        // when the session was requested
+        if !self.is_feature_enabled(XRFeature::ReferenceSpace(options.type_)) {
+            p.reject_error(Error::NotSupported);
+            return p;
+        }
+
         match options.type_ {
             XRReferenceSpaceType::Identity => {
-                p.resolve_native(&XRReferenceSpace::identity(&self.global(), self));
+                // Requests share a space, until content moves its origin
+                // and would move it for every other caller as well
+                let cached = self
+                    .viewer_space
+                    .borrow()
+                    .get(|space| space.has_identity_offset())
+                    .map(|space| DomRoot::from_ref(&**space));
+                let space = cached.unwrap_or_else(|| {
+                    let space = XRReferenceSpace::identity(&self.global(), self);
+                    self.viewer_space.borrow_mut().set(Dom::from_ref(&*space));
+                    space
+                });
+                p.resolve_native(&*space);
             },
             XRReferenceSpaceType::Stationary => {
                 if let Some(subtype) = options.subtype {
-                    p.resolve_native(&XRStationaryReferenceSpace::new(
-                        &self.global(),
-                        self,
-                        subtype,
-                    ));
+                    let space = XRStationaryReferenceSpace::new(&self.global(), self, subtype);
+                    self.add_reference_space(space.upcast());
+                    p.resolve_native(&space);
                 } else {
                     p.reject_error(Error::Type(format!(
                         "stationary XRReferenceSpaces must specify a subtype"
                     )))
                 }
             },
-            XRReferenceSpaceType::Bounded | XRReferenceSpaceType::Unbounded => {
+            XRReferenceSpaceType::Bounded => {
+                let space = XRBoundedReferenceSpace::new(&self.global(), self);
+                self.add_reference_space(space.upcast());
+                p.resolve_native(&space);
+            },
+            XRReferenceSpaceType::Unbounded => {
                 // XXXManishearth eventually support these
                 p.reject_error(Error::NotSupported)
             },
@@ -146,4 +834,496 @@ impl XRSessionMethods for XRSession {
 
         p
     }
+
+    /// https://immersive-web.github.io/webxr/#dom-xrsession-end
+    #[allow(unsafe_code)]
+    fn End(&self) -> Rc<Promise> {
+        let p = unsafe { Promise::new_in_current_compartment(&self.global()) };
+
+        if self.ended.get() {
+            p.reject_error(Error::InvalidState);
+            return p;
+        }
+
+        // https://immersive-web.github.io/webxr/#shut-down-the-session
+        self.ended.set(true);
+        self.pending_render_state.set(None);
+        // anchors stop being tracked along with the session
+        for anchor in self.tracked_anchors.borrow().iter() {
+            anchor.set_deleted();
+        }
+        self.tracked_anchors.borrow_mut().clear();
+        for (anchor, promise) in self.pending_anchors.borrow().iter() {
+            anchor.set_deleted();
+            promise.reject_error(Error::InvalidState);
+        }
+        self.pending_anchors.borrow_mut().clear();
+        self.display.xr_end(self);
+
+        // Let content know with an end event, once the promise has resolved
+        let global = self.global();
+        let this = Trusted::new(self);
+        let (source, canceller) = global
+            .as_window()
+            .task_manager()
+            .dom_manipulation_task_source_with_canceller();
+        let _ = source.queue_with_canceller(
+            task!(xr_session_end: move || {
+                let this = this.root();
+                let event = XRSessionEvent::new(
+                    &this.global(),
+                    atom!("end"),
+                    false,
+                    false,
+                    &this,
+                );
+                event.upcast::<Event>().fire(this.upcast());
+            }),
+            &canceller,
+        );
+
+        p.resolve_native(&());
+        p
+    }
+
+    /// https://immersive-web.github.io/hit-test/#dom-xrsession-requesthittestsource
+    #[allow(unsafe_code)]
+    fn RequestHitTestSource(&self, options: &XRHitTestOptionsInit) -> Rc<Promise> {
+        let p = unsafe { Promise::new_in_current_compartment(&self.global()) };
+
+        if self.ended.get() || self != options.space.session() {
+            p.reject_error(Error::InvalidState);
+            return p;
+        }
+        if self.display.hit_test(None).is_none() {
+            p.reject_error(Error::NotSupported);
+            return p;
+        }
+
+        let global = self.global();
+        // rays without an offset are cast straight ahead of the space
+        let offset_ray = match options.offsetRay {
+            Some(ref ray) => DomRoot::from_ref(&**ray),
+            None => XRRay::new(&global, Vector3D::zero(), Vector3D::new(0., 0., -1.)),
+        };
+        let source = XRHitTestSource::new(&global, self, &options.space, &offset_ray);
+        p.resolve_native(&source);
+        p
+    }
+}
+
+/// How the layers of a session of the given mode are composited with the
+/// real world
+///
+/// VR headsets show nothing but the layers. AR devices show them over the
+/// camera image, using their alpha to blend the two.
+pub fn environment_blend_mode(mode: XRSessionMode) -> XREnvironmentBlendMode {
+    match mode {
+        XRSessionMode::Immersive_ar => XREnvironmentBlendMode::Alpha_blend,
+        _ => XREnvironmentBlendMode::Opaque,
+    }
+}
+
+/// Checks that `depthNear` and `depthFar` describe a usable depth range
+///
+/// A range with equal planes would make the projection matrices singular,
+/// filling `XRView.projectionMatrix` with NaNs.
+pub fn validate_depth_range(near: f64, far: f64) -> Result<(), Error> {
+    if near < 0. {
+        return Err(Error::Range(format!(
+            "depthNear must not be negative, not {}",
+            near
+        )));
+    }
+    if near == far {
+        return Err(Error::Range(format!(
+            "depthNear and depthFar must differ, both are {}",
+            near
+        )));
+    }
+    Ok(())
+}
+
+/// We aren't told the refresh rate of the device, so when content hasn't
+/// picked a frame rate, assume frames are displayed at the usual 60Hz
+const NOMINAL_FRAME_INTERVAL_MS: f64 = 1000. / 60.;
+
+/// The time at which a frame started at `now` is expected to be displayed,
+/// a frame interval later: the one of the target frame rate if there is
+/// one, or the nominal one
+pub fn predicted_display_time(now: f64, frame_interval: Option<f64>) -> f64 {
+    now + frame_interval.unwrap_or(NOMINAL_FRAME_INTERVAL_MS)
+}
+
+/// How early a device frame may arrive and still count as being on time,
+/// to absorb jitter in the device's frame timestamps
+const FRAME_TIME_SLACK_MS: f64 = 1.;
+
+/// Counts the animation frames of a session, so that the views created
+/// during one can be told apart from those of earlier frames
+#[derive(Clone, Copy, Debug, Default, JSTraceable, MallocSizeOf)]
+pub struct FrameCounter {
+    current: u64,
+}
+
+impl FrameCounter {
+    /// The animation frame that is running, or that ran last
+    pub fn current(&self) -> u64 {
+        self.current
+    }
+
+    pub fn next_frame(&mut self) {
+        self.current += 1;
+    }
+
+    /// Whether `frame_id` is the frame that is running, rather than an
+    /// earlier one
+    pub fn is_current(&self, frame_id: u64) -> bool {
+        frame_id == self.current
+    }
+}
+
+/// Picks the device frames that run rAF callbacks, so that content only
+/// renders at the target frame rate instead of at the rate of the device
+#[derive(Clone, Copy, Debug, Default, JSTraceable, MallocSizeOf)]
+pub struct FrameRateLimiter {
+    /// The time between two rendered frames, in milliseconds, or None to
+    /// render every device frame
+    interval: Option<f64>,
+    /// The time at which the next frame is due
+    next_frame: Option<f64>,
+}
+
+impl FrameRateLimiter {
+    pub fn new() -> FrameRateLimiter {
+        Default::default()
+    }
+
+    /// Sets the target frame rate in frames per second
+    pub fn set_frame_rate(&mut self, rate: Option<f64>) {
+        self.interval = rate.map(|rate| 1000. / rate);
+        self.next_frame = None;
+    }
+
+    /// The time between two rendered frames, in milliseconds, if a target
+    /// frame rate was set
+    pub fn interval(&self) -> Option<f64> {
+        self.interval
+    }
+
+    /// Whether the device frame at `now` (in milliseconds) should be rendered
+    pub fn should_run(&mut self, now: f64) -> bool {
+        let interval = match self.interval {
+            Some(interval) => interval,
+            None => return true,
+        };
+        match self.next_frame {
+            Some(next) if now + FRAME_TIME_SLACK_MS < next => false,
+            Some(next) if now - next < interval => {
+                // stick to the cadence rather than to the device timestamps,
+                // so that jitter doesn't accumulate
+                self.next_frame = Some(next + interval);
+                true
+            },
+            _ => {
+                // first frame, or we fell behind by more than a frame
+                self.next_frame = Some(now + interval);
+                true
+            },
+        }
+    }
+}
+
+/// How many device frames the device frame rate is measured over
+const DEVICE_FRAME_RATE_SAMPLE_FRAMES: u32 = 30;
+
+/// How much the measured device frame rate has to move, in frames per
+/// second, before it counts as a change rather than as jitter
+const DEVICE_FRAME_RATE_CHANGE_THRESHOLD: f32 = 2.;
+
+/// Gaps between device frames longer than this, in milliseconds, are
+/// pauses (e.g. while no callbacks were queued), not a lower frame rate
+const MAX_DEVICE_FRAME_GAP_MS: f64 = 250.;
+
+/// Measures the rate at which the device delivers frames, which drops when
+/// e.g. the device is throttled
+#[derive(Clone, Copy, Debug, Default, JSTraceable, MallocSizeOf)]
+pub struct DeviceFrameRate {
+    /// When the current measurement started
+    sample_start: Option<f64>,
+    /// When the last frame was delivered
+    last_frame: Option<f64>,
+    /// The frames delivered since `sample_start`
+    frames: u32,
+    /// The last rate that was measured, in frames per second
+    rate: Option<f32>,
+    /// The highest rate that was measured, which is what the device
+    /// delivers when it isn't throttled
+    full_rate: Option<f32>,
+}
+
+impl DeviceFrameRate {
+    pub fn new() -> DeviceFrameRate {
+        Default::default()
+    }
+
+    /// The device frame rate, once it has been measured
+    pub fn rate(&self) -> Option<f32> {
+        self.rate
+    }
+
+    /// The rate the device delivers frames at when it isn't throttled, once
+    /// it has been measured
+    pub fn full_rate(&self) -> Option<f32> {
+        self.full_rate
+    }
+
+    /// Records a device frame delivered at `now` (in milliseconds), and
+    /// returns the new frame rate if it has changed
+    pub fn observe(&mut self, now: f64) -> Option<f32> {
+        let paused = self
+            .last_frame
+            .map_or(true, |last| now - last > MAX_DEVICE_FRAME_GAP_MS);
+        self.last_frame = Some(now);
+        let start = match self.sample_start {
+            Some(start) if !paused => start,
+            _ => {
+                self.sample_start = Some(now);
+                self.frames = 0;
+                return None;
+            },
+        };
+        self.frames += 1;
+        if self.frames < DEVICE_FRAME_RATE_SAMPLE_FRAMES {
+            return None;
+        }
+        let rate = (self.frames as f64 * 1000. / (now - start)).round() as f32;
+        self.sample_start = Some(now);
+        self.frames = 0;
+        match self.rate {
+            Some(known) if (known - rate).abs() < DEVICE_FRAME_RATE_CHANGE_THRESHOLD => None,
+            _ => {
+                self.rate = Some(rate);
+                self.full_rate = Some(self.full_rate.map_or(rate, |full| full.max(rate)));
+                self.rate
+            },
+        }
+    }
+}
+
+/// The viewport scale to recommend to content while the device delivers
+/// `rate` frames per second out of the `full_rate` it is capable of
+///
+/// The time it takes to render a frame goes with the number of pixels,
+/// which goes with the square of the viewport scale.
+pub fn viewport_scale_for_frame_rate(rate: f32, full_rate: f32) -> Option<f64> {
+    if rate >= full_rate {
+        return None;
+    }
+    Some(
+        (rate as f64 / full_rate as f64)
+            .sqrt()
+            .max(MIN_VIEWPORT_SCALE)
+            .min(MAX_VIEWPORT_SCALE),
+    )
+}
+
+/// The rate content ends up rendered at, given the target frame rate it
+/// asked for and the rate the device delivers frames at
+pub fn negotiated_frame_rate(target: Option<f32>, device: Option<f32>) -> Option<f32> {
+    match (target, device) {
+        (Some(target), Some(device)) => Some(target.min(device)),
+        (target, device) => target.or(device),
+    }
+}
+
+/// How far ahead poses are extrapolated at most, so that a device clock
+/// that jumps around can't send poses flying off
+const MAX_POSE_PREDICTION_MS: f64 = 100.;
+
+/// How long the pose sampled at `sample_time` needs to be extrapolated for
+/// to be displayed at `display_time`, both in milliseconds
+pub fn prediction_interval(sample_time: f64, display_time: f64) -> f64 {
+    (display_time - sample_time)
+        .max(0.)
+        .min(MAX_POSE_PREDICTION_MS)
+}
+
+/// Maps the timestamps of device poses onto the clock of the page
+///
+/// Devices stamp poses with a clock of their own, whose epoch we aren't
+/// told. A pose can't have been sampled after it was received, so the
+/// smallest difference seen between the two clocks is the closest estimate
+/// of their offset; it is only off by the latency of the quickest pose.
+#[derive(Clone, Copy, Debug, Default, JSTraceable, MallocSizeOf)]
+pub struct PoseClock {
+    /// The page time minus the device time, in milliseconds
+    offset: Option<f64>,
+}
+
+impl PoseClock {
+    pub fn new() -> PoseClock {
+        Default::default()
+    }
+
+    /// The page time at which the device sampled a pose it stamped with
+    /// `timestamp`, given that it was received at `now`
+    pub fn sample_time(&mut self, timestamp: f64, now: f64) -> f64 {
+        // devices that don't stamp their poses leave the timestamp at zero
+        if timestamp == 0. {
+            return now;
+        }
+        let offset = now - timestamp;
+        let offset = self.offset.map_or(offset, |known| known.min(offset));
+        self.offset = Some(offset);
+        timestamp + offset
+    }
+}
+
+/// How long an input source that stopped being connected is still listed
+/// by getTrackedSources()
+const LOST_INPUT_SOURCE_GRACE_PERIOD_MS: f64 = 1000.;
+
+/// Remembers when input sources were lost, so that they can still be
+/// enumerated for a short grace period afterwards
+#[derive(Clone, Debug, JSTraceable, MallocSizeOf)]
+pub struct LostInputSources {
+    /// How long a lost source is considered recently lost, in milliseconds
+    grace_period: f64,
+    /// The gamepad ids of the sources that aren't connected, along with
+    /// the time they were lost at
+    lost: Vec<(u32, f64)>,
+    /// The time of the latest update
+    now: f64,
+}
+
+impl LostInputSources {
+    pub fn new(grace_period: f64) -> LostInputSources {
+        LostInputSources {
+            grace_period,
+            lost: vec![],
+            now: 0.,
+        }
+    }
+
+    /// Records whether the source with the given gamepad id is connected as
+    /// of `now` (in milliseconds)
+    pub fn update(&mut self, id: u32, connected: bool, now: f64) {
+        self.now = now;
+        let index = self.lost.iter().position(|&(lost, _)| lost == id);
+        match (index, connected) {
+            (Some(index), true) => {
+                self.lost.remove(index);
+            },
+            (None, false) => self.lost.push((id, now)),
+            _ => {},
+        }
+    }
+
+    /// Whether the source with the given gamepad id was lost less than the
+    /// grace period before the last update
+    pub fn is_recently_lost(&self, id: u32) -> bool {
+        self.lost
+            .iter()
+            .any(|&(lost, since)| lost == id && self.now - since < self.grace_period)
+    }
+}
+
+/// The space requests for a `viewer` reference space resolve with, which
+/// is shared for as long as content leaves its originOffset alone
+#[derive(JSTraceable, MallocSizeOf)]
+pub struct ViewerSpaceCache<T> {
+    space: Option<T>,
+}
+
+impl<T> ViewerSpaceCache<T> {
+    pub fn new() -> ViewerSpaceCache<T> {
+        ViewerSpaceCache { space: None }
+    }
+
+    /// The cached space, unless `is_unmoved` says content has moved its
+    /// origin since, in which case the next request gets a fresh space
+    pub fn get<F>(&self, is_unmoved: F) -> Option<&T>
+    where
+        F: Fn(&T) -> bool,
+    {
+        self.space.as_ref().filter(|space| is_unmoved(space))
+    }
+
+    /// Caches the space created for the latest request
+    pub fn set(&mut self, space: T) {
+        self.space = Some(space);
+    }
+}
+
+/// What moved the native origin of some of the reference spaces
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum OriginChange {
+    /// The device was explicitly recentered
+    Recenter,
+    /// The device reported that the floor is somewhere else now
+    FloorMoved,
+}
+
+/// Whether a reference space of type `ty`, and of the stationary `subtype`
+/// if it is a stationary one, gets a reset event for `change`
+///
+/// Identity spaces follow the viewer rather than a native origin, and
+/// eye-level spaces keep the origin they had at creation until the device
+/// is recentered. Tracking the viewer never moves a native origin.
+pub fn fires_reset(
+    ty: XRReferenceSpaceType,
+    subtype: Option<XRStationaryReferenceSpaceSubtype>,
+    change: OriginChange,
+) -> bool {
+    match (ty, change) {
+        (XRReferenceSpaceType::Identity, _) => false,
+        (_, OriginChange::Recenter) => true,
+        (XRReferenceSpaceType::Stationary, OriginChange::FloorMoved) => {
+            subtype == Some(XRStationaryReferenceSpaceSubtype::Floor_level)
+        },
+        (XRReferenceSpaceType::Bounded, OriginChange::FloorMoved) => true,
+        (XRReferenceSpaceType::Unbounded, OriginChange::FloorMoved) => false,
+    }
+}
+
+/// How far the floor may move from where it was at the last reset before
+/// it counts as moved, rather than as noise in the device's stage data, in
+/// meters
+const FLOOR_MOVE_TOLERANCE: f64 = 0.01;
+
+/// Compares the floor the device reports with each update of its data to
+/// the floor as of the last reset, so that only an actual move of the floor
+/// resets the spaces standing on it
+#[derive(Clone, Copy, Debug, Default, JSTraceable, MallocSizeOf)]
+pub struct FloorOrigin {
+    /// Whether the floor was reported yet
+    known: bool,
+    /// The offset of the floor from the eye-level origin
+    x: f64,
+    y: f64,
+    z: f64,
+}
+
+impl FloorOrigin {
+    /// Records the floor given by the sitting to standing transform of the
+    /// device, returning whether it moved since the last reset
+    pub fn update(&mut self, sitting_to_standing: Option<[f32; 16]>) -> bool {
+        let offset = match floor_pose(sitting_to_standing, true) {
+            Some(pose) => pose.translation,
+            None => return false,
+        };
+        let distance =
+            Vector3D::new(offset.x - self.x, offset.y - self.y, offset.z - self.z).length();
+        let moved = self.known && distance > FLOOR_MOVE_TOLERANCE;
+        if moved || !self.known {
+            *self = FloorOrigin {
+                known: true,
+                x: offset.x,
+                y: offset.y,
+                z: offset.z,
+            };
+        }
+        moved
+    }
 }
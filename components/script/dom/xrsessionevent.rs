@@ -0,0 +1,77 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::EventBinding::EventMethods;
+use crate::dom::bindings::codegen::Bindings::XRSessionEventBinding;
+use crate::dom::bindings::codegen::Bindings::XRSessionEventBinding::XRSessionEventInit;
+use crate::dom::bindings::codegen::Bindings::XRSessionEventBinding::XRSessionEventMethods;
+use crate::dom::bindings::error::Fallible;
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::reflector::reflect_dom_object;
+use crate::dom::bindings::reflector::DomObject;
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::bindings::str::DOMString;
+use crate::dom::event::Event;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::window::Window;
+use crate::dom::xrsession::XRSession;
+use dom_struct::dom_struct;
+use servo_atoms::Atom;
+
+#[dom_struct]
+pub struct XRSessionEvent {
+    event: Event,
+    session: Dom<XRSession>,
+}
+
+impl XRSessionEvent {
+    fn new_inherited(session: &XRSession) -> XRSessionEvent {
+        XRSessionEvent {
+            event: Event::new_inherited(),
+            session: Dom::from_ref(session),
+        }
+    }
+
+    pub fn new(
+        global: &GlobalScope,
+        ty: Atom,
+        bubbles: bool,
+        cancelable: bool,
+        session: &XRSession,
+    ) -> DomRoot<XRSessionEvent> {
+        let event = reflect_dom_object(
+            Box::new(XRSessionEvent::new_inherited(session)),
+            global,
+            XRSessionEventBinding::Wrap,
+        );
+        event.upcast::<Event>().init_event(ty, bubbles, cancelable);
+        event
+    }
+
+    pub fn Constructor(
+        window: &Window,
+        ty: DOMString,
+        init: &XRSessionEventInit,
+    ) -> Fallible<DomRoot<XRSessionEvent>> {
+        Ok(XRSessionEvent::new(
+            &window.global(),
+            ty.into(),
+            init.parent.bubbles,
+            init.parent.cancelable,
+            &init.session,
+        ))
+    }
+}
+
+impl XRSessionEventMethods for XRSessionEvent {
+    /// https://immersive-web.github.io/webxr/#dom-xrsessioneventinit-session
+    fn Session(&self) -> DomRoot<XRSession> {
+        DomRoot::from_ref(&*self.session)
+    }
+
+    /// https://dom.spec.whatwg.org/#dom-event-istrusted
+    fn IsTrusted(&self) -> bool {
+        self.event.IsTrusted()
+    }
+}
@@ -2,23 +2,70 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
+use crate::dom::bindings::codegen::Bindings::XRSessionBinding::XRReferenceSpaceType;
 use crate::dom::bindings::codegen::Bindings::XRSpaceBinding;
 use crate::dom::bindings::inheritance::Castable;
 use crate::dom::bindings::reflector::reflect_dom_object;
 use crate::dom::bindings::root::{Dom, DomRoot};
 use crate::dom::eventtarget::EventTarget;
 use crate::dom::globalscope::GlobalScope;
+use crate::dom::xrinputsource::{XRInputSource, XRInputSpace};
+use crate::dom::xrjointspace::XRJointSpace;
 use crate::dom::xrreferencespace::XRReferenceSpace;
 use crate::dom::xrsession::XRSession;
 use dom_struct::dom_struct;
-use euclid::{RigidTransform3D, Rotation3D, Vector3D};
+use euclid::{RigidTransform3D, Rotation3D, Transform3D, Vector3D};
+use euclid::{TypedRigidTransform3D, TypedRotation3D, TypedVector3D};
 use webvr_traits::WebVRFrameData;
 
+/// The coordinates of the device, which the poses of all spaces are
+/// expressed in: the eye-level origin WebVR reports poses relative to
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Native;
+
+/// The coordinates of a single XRSpace, such as those content gets poses
+/// relative to
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ApiSpace;
+
+/// Where a space is, taking its coordinates to those of the device
+pub type NativePose = TypedRigidTransform3D<f64, ApiSpace, Native>;
+
+/// Tags a pose with the spaces it goes between
+///
+/// Only for poses that come from outside the typed code, such as those
+/// reported by the device or set by content, which are untyped.
+pub fn typed_pose<Src, Dst>(pose: &RigidTransform3D<f64>) -> TypedRigidTransform3D<f64, Src, Dst> {
+    let r = &pose.rotation;
+    TypedRigidTransform3D::new(
+        TypedRotation3D::unit_quaternion(r.i, r.j, r.k, r.r),
+        TypedVector3D::from_untyped(&pose.translation),
+    )
+}
+
+/// Drops the spaces a pose goes between, for handing it out to content
+pub fn untyped_pose<Src, Dst>(
+    pose: &TypedRigidTransform3D<f64, Src, Dst>,
+) -> RigidTransform3D<f64> {
+    let r = &pose.rotation;
+    RigidTransform3D::new(
+        Rotation3D::unit_quaternion(r.i, r.j, r.k, r.r),
+        pose.translation.to_untyped(),
+    )
+}
+
 #[dom_struct]
 pub struct XRSpace {
     eventtarget: EventTarget,
     session: Dom<XRSession>,
     is_viewerspace: bool,
+    /// For the spaces of an input source, the source and which of its
+    /// spaces this is
+    input_source: Option<(Dom<XRInputSource>, XRInputSpace)>,
+    /// For spaces that stay put relative to the origin, such as those of
+    /// tracked images and anchors, where they are
+    #[ignore_malloc_size_of = "defined in euclid"]
+    fixed_pose: Option<NativePose>,
 }
 
 impl XRSpace {
@@ -27,6 +74,8 @@ impl XRSpace {
             eventtarget: EventTarget::new_inherited(),
             session: Dom::from_ref(session),
             is_viewerspace: false,
+            input_source: None,
+            fixed_pose: None,
         }
     }
 
@@ -35,6 +84,8 @@ impl XRSpace {
             eventtarget: EventTarget::new_inherited(),
             session: Dom::from_ref(session),
             is_viewerspace: true,
+            input_source: None,
+            fixed_pose: None,
         }
     }
 
@@ -45,6 +96,57 @@ impl XRSpace {
             XRSpaceBinding::Wrap,
         )
     }
+
+    fn new_inputspace_inner(
+        session: &XRSession,
+        source: &XRInputSource,
+        space: XRInputSpace,
+    ) -> XRSpace {
+        XRSpace {
+            eventtarget: EventTarget::new_inherited(),
+            session: Dom::from_ref(session),
+            is_viewerspace: false,
+            input_source: Some((Dom::from_ref(source), space)),
+            fixed_pose: None,
+        }
+    }
+
+    pub fn new_inputspace(
+        global: &GlobalScope,
+        session: &XRSession,
+        source: &XRInputSource,
+        space: XRInputSpace,
+    ) -> DomRoot<XRSpace> {
+        reflect_dom_object(
+            Box::new(XRSpace::new_inputspace_inner(session, source, space)),
+            global,
+            XRSpaceBinding::Wrap,
+        )
+    }
+
+    fn new_fixedspace_inner(session: &XRSession, pose: NativePose) -> XRSpace {
+        XRSpace {
+            eventtarget: EventTarget::new_inherited(),
+            session: Dom::from_ref(session),
+            is_viewerspace: false,
+            input_source: None,
+            fixed_pose: Some(pose),
+        }
+    }
+
+    /// Creates a space that stays at `pose`, relative to the same origin as
+    /// the poses from `get_pose()`
+    pub fn new_fixedspace(
+        global: &GlobalScope,
+        session: &XRSession,
+        pose: NativePose,
+    ) -> DomRoot<XRSpace> {
+        reflect_dom_object(
+            Box::new(XRSpace::new_fixedspace_inner(session, pose)),
+            global,
+            XRSpaceBinding::Wrap,
+        )
+    }
 }
 
 impl XRSpace {
@@ -53,30 +155,249 @@ impl XRSpace {
     /// The reference origin used is common between all
     /// get_pose calls for spaces from the same device, so this can be used to compare
     /// with other spaces
-    pub fn get_pose(&self, base_pose: &WebVRFrameData) -> RigidTransform3D<f64> {
+    pub fn get_pose(&self, base_pose: &WebVRFrameData) -> NativePose {
         if let Some(reference) = self.downcast::<XRReferenceSpace>() {
             reference.get_pose(base_pose)
+        } else if let Some(joint) = self.downcast::<XRJointSpace>() {
+            typed_pose(&joint.get_pose())
         } else if self.is_viewerspace {
             XRSpace::viewer_pose_from_frame_data(base_pose)
+        } else if let Some((ref source, space)) = self.input_source {
+            typed_pose(&source.pose(space))
+        } else if let Some(pose) = self.fixed_pose {
+            pose
+        } else {
+            unreachable!()
+        }
+    }
+
+    /// Gets the velocity of this space, if the device tracks it
+    ///
+    /// Like the pose from `get_pose()`, it is expressed with respect to the
+    /// common reference origin. Spaces that are fixed to that origin have no
+    /// velocity at all.
+    pub fn get_velocity(&self, base_pose: &WebVRFrameData) -> Option<SpaceVelocity> {
+        if let Some(reference) = self.downcast::<XRReferenceSpace>() {
+            let viewer = XRSpace::viewer_velocity_from_frame_data(base_pose);
+            let from = XRSpace::viewer_pose_from_frame_data(base_pose).translation;
+            let to = reference.get_pose(base_pose).translation;
+            reference_space_velocity(reference.ty(), viewer, from.to_untyped(), to.to_untyped())
+        } else if self.is::<XRJointSpace>() {
+            // devices only report where joints are, not how they move
+            None
+        } else if self.is_viewerspace {
+            XRSpace::viewer_velocity_from_frame_data(base_pose)
+        } else if let Some((ref source, space)) = self.input_source {
+            source.velocity(space)
+        } else if self.fixed_pose.is_some() {
+            Some(SpaceVelocity::zero())
         } else {
             unreachable!()
         }
     }
 
-    pub fn viewer_pose_from_frame_data(data: &WebVRFrameData) -> RigidTransform3D<f64> {
+    fn viewer_velocity_from_frame_data(data: &WebVRFrameData) -> Option<SpaceVelocity> {
+        SpaceVelocity::from_vr(data.pose.linear_velocity, data.pose.angular_velocity)
+    }
+
+    pub fn viewer_pose_from_frame_data(data: &WebVRFrameData) -> NativePose {
         let pos = data.pose.position.unwrap_or([0., 0., 0.]);
-        let translation = Vector3D::new(pos[0] as f64, pos[1] as f64, pos[2] as f64);
+        let translation = TypedVector3D::new(pos[0] as f64, pos[1] as f64, pos[2] as f64);
         let orient = data.pose.orientation.unwrap_or([0., 0., 0., 0.]);
-        let rotation = Rotation3D::quaternion(
+        let rotation = TypedRotation3D::quaternion(
             orient[0] as f64,
             orient[1] as f64,
             orient[2] as f64,
             orient[3] as f64,
         );
-        RigidTransform3D::new(rotation, translation)
+        NativePose::new(rotation, translation)
     }
 
     pub fn session(&self) -> &XRSession {
         &self.session
     }
 }
+
+/// Computes the transform of space `a` relative to space `b`, i.e. `b⁻¹ · a`
+///
+/// Both transforms must be expressed with respect to the same origin, as
+/// returned by `XRSpace::get_pose()`.
+pub(crate) fn relative_transform(a: Transform3D<f64>, b: Transform3D<f64>) -> Transform3D<f64> {
+    match b.inverse() {
+        Some(b_inverse) => a.post_mul(&b_inverse),
+        // Poses are rigid transforms, which are always invertible. Should we
+        // ever get a degenerate one, treat it as the origin rather than panicking.
+        None => a,
+    }
+}
+
+/// Computes the pose of space `a` relative to space `b`, i.e. `b⁻¹ · a`
+///
+/// This is the rigid counterpart of `relative_transform()`; every pose of one
+/// space relative to another goes through here so that they all agree. The
+/// result is in the coordinates of `b`, as handed out to content.
+pub(crate) fn relative_pose(a: &NativePose, b: &NativePose) -> RigidTransform3D<f64> {
+    let relative: TypedRigidTransform3D<f64, ApiSpace, ApiSpace> = b.inverse().pre_mul(a);
+    untyped_pose(&relative)
+}
+
+/// The velocity of a space, in the coordinates its pose is expressed in
+#[derive(Clone, Copy, Debug, JSTraceable, PartialEq)]
+pub struct SpaceVelocity {
+    /// How fast the origin of the space moves, in meters per second
+    pub linear: Vector3D<f64>,
+    /// How fast the space rotates, in radians per second, as an axis scaled
+    /// by the rate of rotation
+    pub angular: Vector3D<f64>,
+}
+
+impl SpaceVelocity {
+    pub fn zero() -> SpaceVelocity {
+        SpaceVelocity {
+            linear: Vector3D::zero(),
+            angular: Vector3D::zero(),
+        }
+    }
+
+    /// Reads the velocities of a WebVR pose, a device that only reports one
+    /// of them is taken not to move in the other way
+    pub fn from_vr(linear: Option<[f32; 3]>, angular: Option<[f32; 3]>) -> Option<SpaceVelocity> {
+        if linear.is_none() && angular.is_none() {
+            return None;
+        }
+        let to_vector = |v: Option<[f32; 3]>| {
+            let v = v.unwrap_or([0., 0., 0.]);
+            Vector3D::new(v[0] as f64, v[1] as f64, v[2] as f64)
+        };
+        Some(SpaceVelocity {
+            linear: to_vector(linear),
+            angular: to_vector(angular),
+        })
+    }
+}
+
+/// Moves a velocity measured at the point `from` to the point `to` of the
+/// same rigid body, e.g. from the grip of a controller to its target ray
+pub(crate) fn velocity_at(
+    velocity: &SpaceVelocity,
+    from: Vector3D<f64>,
+    to: Vector3D<f64>,
+) -> SpaceVelocity {
+    SpaceVelocity {
+        linear: velocity.linear + velocity.angular.cross(to - from),
+        angular: velocity.angular,
+    }
+}
+
+/// The velocity of a reference space of type `ty`, whose origin is at
+/// `origin` while the viewer is at `viewer_position`
+///
+/// Only identity spaces follow the headset around, the origins of the other
+/// reference spaces, such as the floor of bounded ones, stay put.
+pub fn reference_space_velocity(
+    ty: XRReferenceSpaceType,
+    viewer: Option<SpaceVelocity>,
+    viewer_position: Vector3D<f64>,
+    origin: Vector3D<f64>,
+) -> Option<SpaceVelocity> {
+    match ty {
+        XRReferenceSpaceType::Identity => Some(velocity_at(&viewer?, viewer_position, origin)),
+        XRReferenceSpaceType::Stationary |
+        XRReferenceSpaceType::Bounded |
+        XRReferenceSpaceType::Unbounded => Some(SpaceVelocity::zero()),
+    }
+}
+
+/// Computes the velocity of space `a` relative to space `b`, in the
+/// coordinates of `b`, matching the pose returned by `relative_pose(a, b)`
+///
+/// Poses and velocities must be expressed with respect to the same origin.
+/// As `b` may itself be rotating, the motion its rotation gives to points
+/// at the position of `a` is taken out as well.
+pub(crate) fn relative_velocity(
+    a: &NativePose,
+    a_velocity: &SpaceVelocity,
+    b: &NativePose,
+    b_velocity: &SpaceVelocity,
+) -> SpaceVelocity {
+    // velocities are in the coordinates of the device, like the poses
+    let offset = (a.translation - b.translation).to_untyped();
+    let linear = a_velocity.linear - b_velocity.linear - b_velocity.angular.cross(offset);
+    let angular = a_velocity.angular - b_velocity.angular;
+    let to_b = b.rotation.inverse();
+    let to_b = |v: Vector3D<f64>| to_b.rotate_vector3d(&TypedVector3D::from_untyped(&v));
+    SpaceVelocity {
+        linear: to_b(linear).to_untyped(),
+        angular: to_b(angular).to_untyped(),
+    }
+}
+
+/// Below this rotation angle (in radians) the exponential map is computed
+/// with its Taylor expansion, avoiding a division by a vanishing |ω|
+const SMALL_ROTATION: f64 = 1e-6;
+
+/// Advances a pose by `dt` seconds, given its linear velocity (in meters per
+/// second) and angular velocity (in radians per second, as an axis scaled by
+/// the rate of rotation)
+///
+/// Both velocities are expressed in the same space as the pose.
+pub(crate) fn extrapolate<Src, Dst>(
+    transform: &TypedRigidTransform3D<f64, Src, Dst>,
+    linear_velocity: Vector3D<f64>,
+    angular_velocity: Vector3D<f64>,
+    dt: f64,
+) -> TypedRigidTransform3D<f64, Src, Dst> {
+    let translation = transform.translation + TypedVector3D::from_untyped(&(linear_velocity * dt));
+
+    // exp(ω·dt / 2) = (sin(|ω|·dt / 2) ω / |ω|, cos(|ω|·dt / 2))
+    let speed = angular_velocity.length();
+    let half_angle = speed * dt / 2.;
+    let scale = if speed * dt < SMALL_ROTATION {
+        // sin(|ω|·dt / 2) / |ω| ≈ dt / 2 · (1 - (|ω|·dt)² / 24)
+        dt / 2. * (1. - half_angle * half_angle / 6.)
+    } else {
+        half_angle.sin() / speed
+    };
+    let axis = angular_velocity * scale;
+    let (w, q) = (half_angle.cos(), &transform.rotation);
+    // the angular velocity is in the space of the pose, so the extra
+    // rotation is applied after the current one
+    let rotation = TypedRotation3D::quaternion(
+        w * q.i + axis.x * q.r + axis.y * q.k - axis.z * q.j,
+        w * q.j - axis.x * q.k + axis.y * q.r + axis.z * q.i,
+        w * q.k + axis.x * q.j - axis.y * q.i + axis.z * q.r,
+        w * q.r - axis.x * q.i - axis.y * q.j - axis.z * q.k,
+    )
+    .normalize();
+
+    TypedRigidTransform3D::new(rotation, translation)
+}
+
+/// Predicts the device pose `dt` seconds after `data` was sampled, using the
+/// velocities reported by the device
+///
+/// Frame data without velocities is returned as is.
+pub fn extrapolate_frame_data(data: &WebVRFrameData, dt: f64) -> WebVRFrameData {
+    let mut predicted = data.clone();
+    let pose = &mut predicted.pose;
+    let velocity = match XRSpace::viewer_velocity_from_frame_data(data) {
+        Some(velocity) => velocity,
+        None => return predicted,
+    };
+    let transform = extrapolate(
+        &XRSpace::viewer_pose_from_frame_data(data),
+        velocity.linear,
+        velocity.angular,
+        dt,
+    );
+
+    if pose.position.is_some() {
+        let t = transform.translation;
+        pose.position = Some([t.x as f32, t.y as f32, t.z as f32]);
+    }
+    if pose.orientation.is_some() {
+        let r = transform.rotation;
+        pose.orientation = Some([r.i as f32, r.j as f32, r.k as f32, r.r as f32]);
+    }
+    predicted
+}
@@ -4,17 +4,22 @@
 
 use crate::dom::bindings::codegen::Bindings::XRStationaryReferenceSpaceBinding;
 use crate::dom::bindings::codegen::Bindings::XRStationaryReferenceSpaceBinding::XRStationaryReferenceSpaceSubtype;
+use crate::dom::bindings::inheritance::Castable;
 use crate::dom::bindings::reflector::reflect_dom_object;
 use crate::dom::bindings::root::DomRoot;
 use crate::dom::globalscope::GlobalScope;
 use crate::dom::xrreferencespace::XRReferenceSpace;
 use crate::dom::xrrigidtransform::XRRigidTransform;
 use crate::dom::xrsession::XRSession;
-use crate::dom::xrspace::XRSpace;
+use crate::dom::xrspace::{NativePose, XRSpace};
 use dom_struct::dom_struct;
-use euclid::RigidTransform3D;
+use euclid::{TypedRotation3D, TypedVector3D};
 use webvr_traits::WebVRFrameData;
 
+/// The height of the viewer's eyes above the floor, used for floor-level
+/// spaces when the device does not report stage parameters
+const DEFAULT_EMULATED_HEIGHT: f64 = 1.6;
+
 #[dom_struct]
 pub struct XRStationaryReferenceSpace {
     xrreferencespace: XRReferenceSpace,
@@ -40,33 +45,73 @@ impl XRStationaryReferenceSpace {
         ty: XRStationaryReferenceSpaceSubtype,
     ) -> DomRoot<XRStationaryReferenceSpace> {
         let transform = XRRigidTransform::identity(global);
+        XRStationaryReferenceSpace::new_with_offset(global, session, ty, &transform)
+    }
+
+    pub fn new_with_offset(
+        global: &GlobalScope,
+        session: &XRSession,
+        ty: XRStationaryReferenceSpaceSubtype,
+        transform: &XRRigidTransform,
+    ) -> DomRoot<XRStationaryReferenceSpace> {
         reflect_dom_object(
             Box::new(XRStationaryReferenceSpace::new_inherited(
-                session, ty, &transform,
+                session, ty, transform,
             )),
             global,
             XRStationaryReferenceSpaceBinding::Wrap,
         )
     }
-}
 
-impl XRStationaryReferenceSpace {
-    /// Gets pose of the viewer with respect to this space
-    ///
-    /// Does not apply originOffset, use get_viewer_pose on XRReferenceSpace instead
-    pub fn get_unoffset_viewer_pose(&self, base_pose: &WebVRFrameData) -> RigidTransform3D<f64> {
-        // XXXManishearth add floor-level transform for floor-level and disable position in position-disabled
-        XRSpace::viewer_pose_from_frame_data(base_pose)
+    pub fn subtype(&self) -> XRStationaryReferenceSpaceSubtype {
+        self.ty
     }
+}
 
+impl XRStationaryReferenceSpace {
     /// Gets pose represented by this space
     ///
     /// Does not apply originOffset, use get_pose on XRReferenceSpace instead
-    pub fn get_unoffset_pose(&self, _: &WebVRFrameData) -> RigidTransform3D<f64> {
-        // XXXManishearth add floor-level transform for floor-level and disable position in position-disabled
+    pub fn get_unoffset_pose(&self, _: &WebVRFrameData) -> NativePose {
+        // XXXManishearth disable position in position-disabled
+        match self.ty {
+            XRStationaryReferenceSpaceSubtype::Floor_level => self.floor_pose(),
+            // The eye-level pose is basically whatever the headset pose was at t=0, which
+            // for most devices is (0, 0, 0)
+            _ => NativePose::identity(),
+        }
+    }
 
-        // The eye-level pose is basically whatever the headset pose was at t=0, which
-        // for most devices is (0, 0, 0)
-        RigidTransform3D::identity()
+    /// Gets the pose of the floor with respect to the eye-level origin
+    fn floor_pose(&self) -> NativePose {
+        let display = self.upcast::<XRSpace>().session().display();
+        floor_pose(display.sitting_to_standing_transform(), true)
+            .unwrap_or_else(NativePose::identity)
     }
 }
+
+/// Gets the pose of the floor with respect to the eye-level origin, given
+/// the sitting to standing transform of the device
+///
+/// Devices that don't know where the floor is don't report that transform.
+/// Floor-level spaces may then emulate a floor at the height of an average
+/// viewer's eyes below the origin, which `emulate_height` asks for. Bounded
+/// spaces may not, since their bounds would be made up just as well.
+pub fn floor_pose(
+    sitting_to_standing: Option<[f32; 16]>,
+    emulate_height: bool,
+) -> Option<NativePose> {
+    // The sitting to standing transform moves points from eye-level space to floor-level
+    // space, so the floor origin sits at its inverse. We only support the translation
+    // component here, devices don't rotate the floor with respect to the seated origin.
+    let offset = match sitting_to_standing {
+        Some(transform) => TypedVector3D::new(
+            -transform[12] as f64,
+            -transform[13] as f64,
+            -transform[14] as f64,
+        ),
+        None if emulate_height => TypedVector3D::new(0., -DEFAULT_EMULATED_HEIGHT, 0.),
+        None => return None,
+    };
+    Some(NativePose::new(TypedRotation3D::identity(), offset))
+}
@@ -2,20 +2,33 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
+use crate::dom::bindings::codegen::Bindings::VRDisplayBinding::{VRDisplayMethods, VREye};
+use crate::dom::bindings::codegen::Bindings::VREyeParametersBinding::VREyeParametersMethods;
+use crate::dom::bindings::codegen::Bindings::XRBinding::XRSessionMode;
+use crate::dom::bindings::codegen::Bindings::XRSessionBinding::XRSessionMethods;
 use crate::dom::bindings::codegen::Bindings::XRViewBinding;
 use crate::dom::bindings::codegen::Bindings::XRViewBinding::{XREye, XRViewMethods};
-use crate::dom::bindings::reflector::{reflect_dom_object, Reflector};
-use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::bindings::num::Finite;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
+use crate::dom::bindings::root::{Dom, DomRoot, MutNullableDom};
 use crate::dom::globalscope::GlobalScope;
 use crate::dom::vrframedata::create_typed_array;
+use crate::dom::xr::XRFeature;
+use crate::dom::xrcamera::XRCamera;
 use crate::dom::xrrigidtransform::XRRigidTransform;
 use crate::dom::xrsession::XRSession;
 use dom_struct::dom_struct;
 use euclid::{RigidTransform3D, Vector3D};
 use js::jsapi::{Heap, JSContext, JSObject};
+use std::cell::Cell;
 use std::ptr::NonNull;
 use webvr_traits::WebVRFrameData;
 
+/// Views can't be rendered at more than the full resolution of the device
+pub const MAX_VIEWPORT_SCALE: f64 = 1.;
+/// The smallest scale we let content pick, so that viewports never collapse
+pub const MIN_VIEWPORT_SCALE: f64 = 0.1;
+
 #[dom_struct]
 pub struct XRView {
     reflector_: Reflector,
@@ -24,6 +37,11 @@ pub struct XRView {
     proj: Heap<*mut JSObject>,
     view: Heap<*mut JSObject>,
     transform: Dom<XRRigidTransform>,
+    recommended_viewport_scale: Option<f64>,
+    requested_viewport_scale: Cell<f64>,
+    camera: MutNullableDom<XRCamera>,
+    /// The animation frame this view was created for
+    frame_id: u64,
 }
 
 impl XRView {
@@ -35,6 +53,10 @@ impl XRView {
             proj: Heap::default(),
             view: Heap::default(),
             transform: Dom::from_ref(transform),
+            recommended_viewport_scale: session.recommended_viewport_scale(),
+            requested_viewport_scale: Cell::new(MAX_VIEWPORT_SCALE),
+            camera: MutNullableDom::default(),
+            frame_id: session.frame_id(),
         }
     }
 
@@ -49,21 +71,22 @@ impl XRView {
         let vr_display = session.display();
 
         // XXXManishearth compute and cache projection matrices on the Display
-        let (proj, offset) = if eye == XREye::Left {
+        let (proj, offset, fov) = if eye == XREye::Left {
             (
                 &data.left_projection_matrix,
                 vr_display.left_eye_params_offset(),
+                vr_display.left_eye_params_fov(),
             )
         } else {
             (
                 &data.right_projection_matrix,
                 vr_display.right_eye_params_offset(),
+                vr_display.right_eye_params_fov(),
             )
         };
 
         let offset = Vector3D::new(offset[0] as f64, offset[1] as f64, offset[2] as f64);
-        let transform = pose.post_mul(&offset.into());
-        let transform = XRRigidTransform::new(global, transform);
+        let transform = XRRigidTransform::new(global, eye_transform(pose, offset));
 
         let ret = reflect_dom_object(
             Box::new(XRView::new_inherited(session, &transform, eye)),
@@ -71,9 +94,28 @@ impl XRView {
             XRViewBinding::Wrap,
         );
 
+        // The device may still be rendering with the clip planes of an older
+        // render state, make sure content sees the ones it asked for
+        let render_state = session.RenderState();
+        let (near, far) = (render_state.depth_near(), render_state.depth_far());
+        // Devices that don't hand us projection matrices still describe the
+        // frustum of each eye, which need not be symmetric on canted displays
+        let proj = if proj.iter().all(|&value| value == 0.) {
+            projection_from_fov(
+                fov.up_degrees,
+                fov.right_degrees,
+                fov.down_degrees,
+                fov.left_degrees,
+                near,
+                far,
+            )
+        } else {
+            projection_with_clip_planes(proj, near, far)
+        };
+
         let cx = global.get_cx();
         unsafe {
-            create_typed_array(cx, proj, &ret.proj);
+            create_typed_array(cx, &proj, &ret.proj);
         }
         ret
     }
@@ -81,6 +123,89 @@ impl XRView {
     pub fn session(&self) -> &XRSession {
         &self.session
     }
+
+    /// Whether this view belongs to the animation frame of its session
+    /// that is running, rather than to an earlier one
+    pub fn is_current(&self) -> bool {
+        self.session.is_current_frame(self.frame_id)
+    }
+
+    /// The fraction of the full viewport this view should be rendered to
+    pub fn viewport_scale(&self) -> f64 {
+        self.requested_viewport_scale.get()
+    }
+}
+
+/// Whether the views of a session of the given mode have a camera, which
+/// only AR sessions that were granted camera access do
+pub fn has_camera(mode: XRSessionMode, camera_access: bool) -> bool {
+    mode == XRSessionMode::Immersive_ar && camera_access
+}
+
+/// Keeps a viewport scale requested by content within what the device
+/// can render
+pub fn clamp_viewport_scale(scale: f64) -> f64 {
+    scale.max(MIN_VIEWPORT_SCALE).min(MAX_VIEWPORT_SCALE)
+}
+
+/// The pose of an eye, given the pose of the head and the offset of the eye
+/// from the point between the eyes
+///
+/// The eye offset is expressed in the coordinate space of the head, so it
+/// must be applied before the head pose, otherwise the interpupillary
+/// distance would stay aligned with the world axes as the head rotates. A
+/// view without an offset gets the head pose exactly, not just up to
+/// rounding errors.
+pub(crate) fn eye_transform(
+    head: &RigidTransform3D<f64>,
+    offset: Vector3D<f64>,
+) -> RigidTransform3D<f64> {
+    if offset == Vector3D::zero() {
+        return *head;
+    }
+    head.pre_mul(&offset.into())
+}
+
+/// Replaces the near and far clip planes of a column-major perspective
+/// projection matrix
+///
+/// Only the third row of a perspective projection depends on the clip
+/// planes; the field of view, including any asymmetry, is kept as is.
+pub(crate) fn projection_with_clip_planes(proj: &[f32; 16], near: f64, far: f64) -> [f32; 16] {
+    let mut proj = *proj;
+    proj[10] = ((far + near) / (near - far)) as f32;
+    proj[14] = (2. * far * near / (near - far)) as f32;
+    proj
+}
+
+/// Builds a column-major perspective projection matrix from the angles
+/// between the center of view and each edge of the frustum, in degrees
+///
+/// The frustum may be asymmetric, in which case the projection is off-center
+/// (m31 and m32 are non-zero). A symmetric frustum gives the usual
+/// perspective projection.
+pub(crate) fn projection_from_fov(
+    up: f64,
+    right: f64,
+    down: f64,
+    left: f64,
+    near: f64,
+    far: f64,
+) -> [f32; 16] {
+    let up = up.to_radians().tan();
+    let right = right.to_radians().tan();
+    let down = down.to_radians().tan();
+    let left = left.to_radians().tan();
+    let x_scale = 2. / (left + right);
+    let y_scale = 2. / (up + down);
+
+    let mut proj = [0.; 16];
+    proj[0] = x_scale as f32;
+    proj[5] = y_scale as f32;
+    proj[8] = ((right - left) * x_scale / 2.) as f32;
+    proj[9] = ((up - down) * y_scale / 2.) as f32;
+    proj[11] = -1.;
+    projection_with_clip_planes(&proj, near, far)
 }
 
 impl XRViewMethods for XRView {
@@ -99,4 +224,33 @@ impl XRViewMethods for XRView {
     fn Transform(&self) -> DomRoot<XRRigidTransform> {
         DomRoot::from_ref(&self.transform)
     }
+
+    /// https://immersive-web.github.io/webxr/#dom-xrview-recommendedviewportscale
+    fn GetRecommendedViewportScale(&self) -> Option<Finite<f64>> {
+        self.recommended_viewport_scale.map(Finite::wrap)
+    }
+
+    /// https://immersive-web.github.io/raw-camera-access/#dom-xrview-camera
+    fn GetCamera(&self) -> Option<DomRoot<XRCamera>> {
+        let camera_access = self.session.is_feature_enabled(XRFeature::CameraAccess);
+        if !has_camera(self.session.Mode(), camera_access) {
+            return None;
+        }
+        Some(self.camera.or_init(|| {
+            let eye = match self.eye {
+                XREye::Left => VREye::Left,
+                XREye::Right => VREye::Right,
+            };
+            let params = self.session.display().GetEyeParameters(eye);
+            XRCamera::new(&self.global(), params.RenderWidth(), params.RenderHeight())
+        }))
+    }
+
+    /// https://immersive-web.github.io/webxr/#dom-xrview-requestviewportscale
+    fn RequestViewportScale(&self, scale: Option<Finite<f64>>) {
+        if let Some(scale) = scale {
+            self.requested_viewport_scale
+                .set(clamp_viewport_scale(*scale));
+        }
+    }
 }
@@ -28,7 +28,7 @@ pub struct XRViewerPose {
 impl XRViewerPose {
     fn new_inherited(transform: &XRRigidTransform) -> XRViewerPose {
         XRViewerPose {
-            pose: XRPose::new_inherited(transform),
+            pose: XRPose::new_inherited(transform, None),
             views: Heap::default(),
         }
     }
@@ -0,0 +1,73 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::XRQuadLayerBinding::XRQuadLayerInit;
+use crate::dom::bindings::codegen::Bindings::XRWebGLBindingBinding;
+use crate::dom::bindings::codegen::Bindings::XRWebGLBindingBinding::XRWebGLBindingMethods;
+use crate::dom::bindings::error::Fallible;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::webglrenderingcontext::WebGLRenderingContext;
+use crate::dom::window::Window;
+use crate::dom::xrquadlayer::XRQuadLayer;
+use crate::dom::xrrigidtransform::XRRigidTransform;
+use crate::dom::xrsession::XRSession;
+use dom_struct::dom_struct;
+
+#[dom_struct]
+pub struct XRWebGLBinding {
+    reflector_: Reflector,
+    session: Dom<XRSession>,
+    context: Dom<WebGLRenderingContext>,
+}
+
+impl XRWebGLBinding {
+    fn new_inherited(session: &XRSession, context: &WebGLRenderingContext) -> XRWebGLBinding {
+        XRWebGLBinding {
+            reflector_: Reflector::new(),
+            session: Dom::from_ref(session),
+            context: Dom::from_ref(context),
+        }
+    }
+
+    pub fn new(
+        global: &GlobalScope,
+        session: &XRSession,
+        context: &WebGLRenderingContext,
+    ) -> DomRoot<XRWebGLBinding> {
+        reflect_dom_object(
+            Box::new(XRWebGLBinding::new_inherited(session, context)),
+            global,
+            XRWebGLBindingBinding::Wrap,
+        )
+    }
+
+    pub fn Constructor(
+        global: &Window,
+        session: &XRSession,
+        context: &WebGLRenderingContext,
+    ) -> Fallible<DomRoot<Self>> {
+        Ok(XRWebGLBinding::new(&global.global(), session, context))
+    }
+}
+
+impl XRWebGLBindingMethods for XRWebGLBinding {
+    /// https://immersive-web.github.io/layers/#dom-xrwebglbinding-createquadlayer
+    fn CreateQuadLayer(&self, init: &XRQuadLayerInit) -> Fallible<DomRoot<XRQuadLayer>> {
+        let global = self.global();
+        let transform = match init.transform {
+            Some(ref transform) => DomRoot::from_ref(&**transform),
+            None => XRRigidTransform::identity(&global),
+        };
+        XRQuadLayer::new(
+            &global,
+            &self.session,
+            &init.space,
+            &transform,
+            *init.width,
+            *init.height,
+        )
+    }
+}
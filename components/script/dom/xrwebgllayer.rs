@@ -6,10 +6,12 @@ use crate::dom::bindings::codegen::Bindings::XRViewBinding::{XREye, XRViewMethod
 use crate::dom::bindings::codegen::Bindings::XRWebGLLayerBinding;
 use crate::dom::bindings::codegen::Bindings::XRWebGLLayerBinding::XRWebGLLayerInit;
 use crate::dom::bindings::codegen::Bindings::XRWebGLLayerBinding::XRWebGLLayerMethods;
-use crate::dom::bindings::error::Fallible;
+use crate::dom::bindings::error::{Error, Fallible};
+use crate::dom::bindings::num::Finite;
 use crate::dom::bindings::reflector::{reflect_dom_object, DomObject};
 use crate::dom::bindings::root::{Dom, DomRoot};
 use crate::dom::globalscope::GlobalScope;
+use crate::dom::webglframebuffer::WebGLFramebuffer;
 use crate::dom::webglrenderingcontext::WebGLRenderingContext;
 use crate::dom::window::Window;
 use crate::dom::xrlayer::XRLayer;
@@ -17,6 +19,8 @@ use crate::dom::xrsession::XRSession;
 use crate::dom::xrview::XRView;
 use crate::dom::xrviewport::XRViewport;
 use dom_struct::dom_struct;
+use euclid::{Point2D, Rect, Size2D};
+use std::cell::Cell;
 
 #[dom_struct]
 pub struct XRWebGLLayer {
@@ -25,8 +29,14 @@ pub struct XRWebGLLayer {
     depth: bool,
     stencil: bool,
     alpha: bool,
+    /// Whether both eyes are rendered in a single pass, to the layers of
+    /// an array framebuffer
+    multiview: bool,
     context: Dom<WebGLRenderingContext>,
     session: Dom<XRSession>,
+    /// The foveation level in effect on the device, None if the device
+    /// doesn't support fixed foveation
+    fixed_foveation: Cell<Option<f64>>,
 }
 
 impl XRWebGLLayer {
@@ -34,6 +44,7 @@ impl XRWebGLLayer {
         session: &XRSession,
         context: &WebGLRenderingContext,
         init: &XRWebGLLayerInit,
+        fixed_foveation: Option<f64>,
     ) -> XRWebGLLayer {
         XRWebGLLayer {
             xrlayer: XRLayer::new_inherited(),
@@ -41,8 +52,10 @@ impl XRWebGLLayer {
             depth: init.depth,
             stencil: init.stencil,
             alpha: init.alpha,
+            multiview: init.multiview && supports_multiview(context),
             context: Dom::from_ref(context),
             session: Dom::from_ref(session),
+            fixed_foveation: Cell::new(fixed_foveation),
         }
     }
 
@@ -52,8 +65,14 @@ impl XRWebGLLayer {
         context: &WebGLRenderingContext,
         init: &XRWebGLLayerInit,
     ) -> DomRoot<XRWebGLLayer> {
+        let fixed_foveation = session.display().set_fixed_foveation(None);
         reflect_dom_object(
-            Box::new(XRWebGLLayer::new_inherited(session, context, init)),
+            Box::new(XRWebGLLayer::new_inherited(
+                session,
+                context,
+                init,
+                fixed_foveation,
+            )),
             global,
             XRWebGLLayerBinding::Wrap,
         )
@@ -65,8 +84,17 @@ impl XRWebGLLayer {
         context: &WebGLRenderingContext,
         init: &XRWebGLLayerInit,
     ) -> Fallible<DomRoot<Self>> {
+        if session.is_ended() {
+            return Err(Error::InvalidState);
+        }
         Ok(XRWebGLLayer::new(&global.global(), session, context, init))
     }
+
+    /// Whether the layer still has a framebuffer to render to, which it
+    /// loses along with its context or when its session ends
+    fn has_framebuffer(&self) -> bool {
+        !self.context.is_context_lost() && !self.session.is_ended()
+    }
 }
 
 impl XRWebGLLayerMethods for XRWebGLLayer {
@@ -95,28 +123,119 @@ impl XRWebGLLayerMethods for XRWebGLLayer {
         DomRoot::from_ref(&self.context)
     }
 
+    /// https://immersive-web.github.io/webxr/#dom-xrwebgllayer-framebuffer
+    fn GetFramebuffer(&self) -> Option<DomRoot<WebGLFramebuffer>> {
+        // FIXME the layer should have an opaque framebuffer of its own,
+        // which needs the WebGL thread to share its texture with the
+        // compositor. Until then content renders to the default
+        // framebuffer, which the eyes are cut out of.
+        None
+    }
+
+    /// https://immersive-web.github.io/webxr/#dom-xrwebgllayer-framebufferwidth
+    fn FramebufferWidth(&self) -> u32 {
+        framebuffer_size(self.context.size(), self.has_framebuffer()).width
+    }
+
+    /// https://immersive-web.github.io/webxr/#dom-xrwebgllayer-framebufferheight
+    fn FramebufferHeight(&self) -> u32 {
+        framebuffer_size(self.context.size(), self.has_framebuffer()).height
+    }
+
+    /// https://immersive-web.github.io/layers/#dom-xrwebgllayer-fixedfoveation
+    fn GetFixedFoveation(&self) -> Option<Finite<f32>> {
+        self.fixed_foveation
+            .get()
+            .map(|level| Finite::wrap(level as f32))
+    }
+
+    /// https://immersive-web.github.io/layers/#dom-xrwebgllayer-fixedfoveation
+    fn SetFixedFoveation(&self, value: Option<Finite<f32>>) {
+        let value = value.map(|value| *value as f64);
+        let level = match fixed_foveation_request(self.fixed_foveation.get(), value) {
+            Some(level) => level,
+            None => return,
+        };
+        // the device may not support every level, so keep the one it settled on
+        let effective = self.session.display().set_fixed_foveation(Some(level));
+        self.fixed_foveation.set(effective);
+    }
+
     /// https://immersive-web.github.io/webxr/#dom-xrwebgllayer-getviewport
     fn GetViewport(&self, view: &XRView) -> Option<DomRoot<XRViewport>> {
-        if self.session != view.session() {
+        if self.session != view.session() || !self.has_framebuffer() {
+            return None;
+        }
+        // views of earlier frames may have been created for other viewports
+        if !view.is_current() {
             return None;
         }
 
-        let size = self.context.size();
-
-        let x = if view.Eye() == XREye::Left {
-            0
-        } else {
-            size.width / 2
-        };
-        // XXXManishearth this assumes the WebVR default of canvases being cut in half
-        // which need not be generally true for all devices, and will not work in
-        // inline VR mode
+        let viewport = eye_viewport(
+            view.Eye(),
+            self.context.size(),
+            view.viewport_scale(),
+            self.multiview,
+        );
         Some(XRViewport::new(
             &self.global(),
-            x,
-            0,
-            size.width / 2,
-            size.height,
+            viewport.origin.x,
+            viewport.origin.y,
+            viewport.size.width,
+            viewport.size.height,
         ))
     }
 }
+
+/// Whether `context` can render to the layers of an array framebuffer
+///
+/// OVR_multiview2 is a WebGL 2 extension, which we don't expose yet, so this
+/// is always false for now.
+fn supports_multiview(context: &WebGLRenderingContext) -> bool {
+    context
+        .extension_manager()
+        .get_suported_extensions()
+        .contains(&"OVR_multiview2")
+}
+
+/// The foveation level to ask the device for when content sets
+/// fixedFoveation to `value`, if any, given the level currently in effect
+pub fn fixed_foveation_request(current: Option<f64>, value: Option<f64>) -> Option<f64> {
+    // there's nothing to forward the level to if the device can't foveate
+    current?;
+    value.map(|value| value.max(0.).min(1.))
+}
+
+/// The size of the framebuffer of a layer whose context has the given size,
+/// which is empty once the layer has no framebuffer anymore
+pub(crate) fn framebuffer_size(context_size: Size2D<u32>, has_framebuffer: bool) -> Size2D<u32> {
+    if has_framebuffer {
+        context_size
+    } else {
+        Size2D::zero()
+    }
+}
+
+/// The part of a framebuffer of the given size that `eye` renders to
+///
+/// With multiview each eye has its own layer of the framebuffer and gets
+/// all of it, otherwise the eyes are side by side. The viewport keeps its
+/// origin and shrinks with the requested scale.
+pub(crate) fn eye_viewport(
+    eye: XREye,
+    size: Size2D<u32>,
+    scale: f64,
+    multiview: bool,
+) -> Rect<u32> {
+    // XXXManishearth this assumes the WebVR default of canvases being cut in half
+    // which need not be generally true for all devices, and will not work in
+    // inline VR mode
+    let (x, width) = match (multiview, eye) {
+        (true, _) => (0, size.width as f64),
+        (false, XREye::Left) => (0, size.width as f64 / 2.),
+        (false, XREye::Right) => (size.width / 2, size.width as f64 / 2.),
+    };
+    let width = (width * scale) as u32;
+    let height = (size.height as f64 * scale) as u32;
+    Rect::new(Point2D::new(x, 0), Size2D::new(width, height))
+}
@@ -2,20 +2,25 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
+use crate::body::BodyOperations;
+use crate::dom::abortsignal::AbortAlgorithm;
+use crate::dom::bindings::codegen::Bindings::AbortSignalBinding::AbortSignalMethods;
 use crate::dom::bindings::codegen::Bindings::RequestBinding::RequestInfo;
 use crate::dom::bindings::codegen::Bindings::RequestBinding::RequestInit;
+use crate::dom::bindings::codegen::Bindings::RequestBinding::RequestMethods;
 use crate::dom::bindings::codegen::Bindings::ResponseBinding::ResponseBinding::ResponseMethods;
 use crate::dom::bindings::codegen::Bindings::ResponseBinding::ResponseType as DOMResponseType;
 use crate::dom::bindings::error::Error;
 use crate::dom::bindings::inheritance::Castable;
 use crate::dom::bindings::refcounted::{Trusted, TrustedPromise};
 use crate::dom::bindings::reflector::DomObject;
-use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::root::{Dom, DomRoot};
 use crate::dom::bindings::trace::RootedTraceableBox;
 use crate::dom::globalscope::GlobalScope;
 use crate::dom::headers::Guard;
 use crate::dom::performanceresourcetiming::InitiatorType;
 use crate::dom::promise::Promise;
+use crate::dom::readablestreamdefaultreader::ReadAllBytesHandler;
 use crate::dom::request::Request;
 use crate::dom::response::Response;
 use crate::dom::serviceworkerglobalscope::ServiceWorkerGlobalScope;
@@ -23,7 +28,8 @@ use crate::network_listener::{self, NetworkListener, PreInvoke, ResourceTimingLi
 use crate::task_source::TaskSourceName;
 use ipc_channel::ipc;
 use ipc_channel::router::ROUTER;
-use js::jsapi::JSAutoCompartment;
+use js::jsapi::{JSAutoCompartment, JSContext};
+use js::rust::HandleValue;
 use net_traits::request::RequestInit as NetTraitsRequestInit;
 use net_traits::request::{Request as NetTraitsRequest, ServiceWorkersMode};
 use net_traits::CoreResourceMsg::Fetch as NetTraitsFetch;
@@ -31,14 +37,12 @@ use net_traits::{FetchChannels, FetchResponseListener, NetworkError};
 use net_traits::{FetchMetadata, FilteredMetadata, Metadata};
 use net_traits::{ResourceFetchTiming, ResourceTimingType};
 use servo_url::ServoUrl;
-use std::mem;
 use std::rc::Rc;
 use std::sync::{Arc, Mutex};
 
 struct FetchContext {
     fetch_promise: Option<TrustedPromise>,
     response_object: Trusted<Response>,
-    body: Vec<u8>,
     resource_timing: ResourceFetchTiming,
 }
 
@@ -133,38 +137,116 @@ pub fn Fetch(
     input: RequestInfo,
     init: RootedTraceableBox<RequestInit>,
 ) -> Rc<Promise> {
-    let core_resource_thread = global.core_resource_thread();
-
     // Step 1
     let promise = unsafe { Promise::new_in_current_compartment(global) };
     let response = Response::new(global);
 
     // Step 2
-    let request = match Request::Constructor(global, input, init) {
+    let request_object = match Request::Constructor(global, input, init) {
         Err(e) => {
             promise.reject_error(e);
             return promise;
         },
-        Ok(r) => r.get_request(),
+        Ok(r) => r,
     };
+
+    // The network stack only sends whole request bodies, so a body stream
+    // gets read until it is closed before the request starts.
+    match request_object.get_body_stream() {
+        Some(stream) => {
+            let handler = Box::new(SendRequestBody {
+                promise: promise.clone(),
+                request: Dom::from_ref(&*request_object),
+                response: Dom::from_ref(&*response),
+            });
+            if let Err(e) = stream.read_all_bytes(handler) {
+                promise.reject_error(e);
+            }
+        },
+        None => fetch_request(global, &promise, &request_object, &response, None),
+    }
+
+    promise
+}
+
+/// Starts the fetch of a request whose body stream was read entirely
+#[derive(JSTraceable)]
+#[must_root]
+struct SendRequestBody {
+    promise: Rc<Promise>,
+    request: Dom<Request>,
+    response: Dom<Response>,
+}
+
+impl ReadAllBytesHandler for SendRequestBody {
+    #[allow(unrooted_must_root)]
+    fn success(&self, bytes: Vec<u8>) {
+        fetch_request(
+            &self.request.global(),
+            &self.promise,
+            &self.request,
+            &self.response,
+            Some(bytes),
+        );
+    }
+
+    fn failure(&self, _cx: *mut JSContext, _error: HandleValue) {
+        self.promise
+            .reject_error(Error::Type("The request body stream errored".to_string()));
+    }
+}
+
+// https://fetch.spec.whatwg.org/#fetch-method
+// Steps 3-9.
+#[allow(unrooted_must_root)]
+fn fetch_request(
+    global: &GlobalScope,
+    promise: &Rc<Promise>,
+    request_object: &Request,
+    response: &Response,
+    body: Option<Vec<u8>>,
+) {
+    let core_resource_thread = global.core_resource_thread();
+
+    // Step 3
+    let mut request = request_object.get_request();
+    if body.is_some() {
+        request.body = body;
+    }
+
+    // Step 4
+    let signal = request_object.Signal();
+    if signal.Aborted() {
+        promise.reject_error(Error::Abort);
+        return;
+    }
+
     let timing_type = request.timing_type();
 
     let mut request_init = request_init_from_request(request);
 
-    // Step 3
+    // Step 5
     if global.downcast::<ServiceWorkerGlobalScope>().is_some() {
         request_init.service_workers_mode = ServiceWorkersMode::None;
     }
 
-    // Step 4
+    // Step 6
     response.Headers().set_guard(Guard::Immutable);
 
-    // Step 5
+    // Step 8
+    let (cancel_sender, cancel_receiver) = ipc::channel().unwrap();
+    response.set_fetch_cancel_chan(cancel_sender.clone());
+    signal.add_abort_algorithm(AbortAlgorithm::Fetch {
+        promise: promise.clone(),
+        response: Dom::from_ref(response),
+        cancel_chan: cancel_sender,
+    });
+
+    // Step 9
     let (action_sender, action_receiver) = ipc::channel().unwrap();
     let fetch_context = Arc::new(Mutex::new(FetchContext {
         fetch_promise: Some(TrustedPromise::new(promise.clone())),
-        response_object: Trusted::new(&*response),
-        body: vec![],
+        response_object: Trusted::new(response),
         resource_timing: ResourceFetchTiming::new(timing_type),
     }));
     let listener = NetworkListener {
@@ -182,11 +264,9 @@ pub fn Fetch(
     core_resource_thread
         .send(NetTraitsFetch(
             request_init,
-            FetchChannels::ResponseMsg(action_sender, None),
+            FetchChannels::ResponseMsg(action_sender, Some(cancel_receiver)),
         ))
         .unwrap();
-
-    promise
 }
 
 impl PreInvoke for FetchContext {}
@@ -253,8 +333,12 @@ impl FetchResponseListener for FetchContext {
         self.fetch_promise = Some(TrustedPromise::new(promise));
     }
 
-    fn process_response_chunk(&mut self, mut chunk: Vec<u8>) {
-        self.body.append(&mut chunk);
+    fn process_response_chunk(&mut self, chunk: Vec<u8>) {
+        let response = self.response_object.root();
+        let global = response.global();
+        let cx = global.get_cx();
+        let _ac = JSAutoCompartment::new(cx, global.reflector().get_jsobject().get());
+        response.stream_chunk(chunk);
     }
 
     fn process_response_eof(&mut self, _response: Result<ResourceFetchTiming, NetworkError>) {
@@ -262,7 +346,7 @@ impl FetchResponseListener for FetchContext {
         let global = response.global();
         let cx = global.get_cx();
         let _ac = JSAutoCompartment::new(cx, global.reflector().get_jsobject().get());
-        response.finish(mem::replace(&mut self.body, vec![]));
+        response.finish();
         // TODO
         // ... trailerObject is not supported in Servo yet.
     }
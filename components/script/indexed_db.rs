@@ -0,0 +1,392 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! The conversions between keys and JS values of IndexedDB, and the routing
+//! of the replies of the IndexedDB thread.
+
+#![allow(unsafe_code)]
+
+use crate::dom::bindings::conversions::jsstring_to_str;
+use crate::dom::bindings::error::{Error, Fallible};
+use crate::dom::bindings::refcounted::Trusted;
+use crate::dom::bindings::reflector::DomObject;
+use crate::task_source::{TaskSource, TaskSourceName};
+use ipc_channel::ipc::{self, IpcSender};
+use ipc_channel::router::ROUTER;
+use js::conversions::ToJSValConvertible;
+use js::jsapi::{ClippedTime, JSContext, JSObject, JS_NewPlainObject, NewDateObject};
+use js::jsapi::{JS_GetStringLength, JSPROP_ENUMERATE};
+use js::jsval::{DoubleValue, ObjectValue, UndefinedValue};
+use js::rust::wrappers::{DateGetMsecSinceEpoch, JS_DefineProperty, JS_GetArrayLength};
+use js::rust::wrappers::{JS_GetElement, JS_GetProperty, JS_HasElement, JS_HasOwnProperty};
+use js::rust::wrappers::{JS_IsArrayObject, JS_ObjectIsDate};
+use js::rust::{HandleObject, HandleValue, MutableHandleValue};
+use js::typedarray::{ArrayBuffer, ArrayBufferView, CreateWith};
+use net_traits::indexeddb_thread::{IndexedDBKeyType, KeyPath};
+use serde::{Deserialize, Serialize};
+use std::ffi::CString;
+use std::ptr;
+
+/// A key, converted to a JS value like the members of the arrays of array
+/// keys are
+struct KeyValue<'a>(&'a IndexedDBKeyType);
+
+impl<'a> ToJSValConvertible for KeyValue<'a> {
+    unsafe fn to_jsval(&self, cx: *mut JSContext, rval: MutableHandleValue) {
+        key_type_to_jsval(cx, self.0, rval);
+    }
+}
+
+/// https://w3c.github.io/IndexedDB/#convert-a-key-to-a-value
+pub unsafe fn key_type_to_jsval(
+    cx: *mut JSContext,
+    key: &IndexedDBKeyType,
+    mut rval: MutableHandleValue,
+) {
+    match *key {
+        IndexedDBKeyType::Number(number) => rval.set(DoubleValue(number)),
+        IndexedDBKeyType::Date(time) => {
+            rooted!(in(cx) let date = NewDateObject(cx, ClippedTime { t: time }));
+            rval.set(ObjectValue(date.get()));
+        },
+        IndexedDBKeyType::String(ref string) => string.to_jsval(cx, rval),
+        IndexedDBKeyType::Binary(ref bytes) => {
+            rooted!(in(cx) let mut buffer = ptr::null_mut::<JSObject>());
+            assert!(ArrayBuffer::create(cx, CreateWith::Slice(bytes), buffer.handle_mut()).is_ok());
+            rval.set(ObjectValue(buffer.get()));
+        },
+        IndexedDBKeyType::Array(ref keys) => keys
+            .iter()
+            .map(KeyValue)
+            .collect::<Vec<_>>()
+            .to_jsval(cx, rval),
+    }
+}
+
+/// https://w3c.github.io/IndexedDB/#convert-a-value-to-a-key
+pub unsafe fn convert_value_to_key(
+    cx: *mut JSContext,
+    input: HandleValue,
+    seen: Option<Vec<*mut JSObject>>,
+) -> Fallible<IndexedDBKeyType> {
+    // Step 1.
+    let mut seen = seen.unwrap_or_default();
+
+    // Step 2.
+    if input.is_number() {
+        let number = input.to_number();
+        if number.is_nan() {
+            return Err(Error::Data);
+        }
+        return Ok(IndexedDBKeyType::Number(number));
+    }
+
+    if input.is_string() {
+        let string = jsstring_to_str(cx, input.to_string());
+        return Ok(IndexedDBKeyType::String(string.into()));
+    }
+
+    if !input.is_object() {
+        return Err(Error::Data);
+    }
+    rooted!(in(cx) let object = input.to_object());
+
+    let mut is_date = false;
+    if !JS_ObjectIsDate(cx, object.handle(), &mut is_date) {
+        return Err(Error::JSFailed);
+    }
+    if is_date {
+        let mut time = 0.;
+        if !DateGetMsecSinceEpoch(cx, object.handle(), &mut time) {
+            return Err(Error::JSFailed);
+        }
+        if time.is_nan() {
+            return Err(Error::Data);
+        }
+        return Ok(IndexedDBKeyType::Date(time));
+    }
+
+    // FIXME: The bytes of shared array buffers are copied too, while they
+    // should not be keys.
+    typedarray!(in(cx) let buffer: ArrayBuffer = object.get());
+    if let Ok(buffer) = buffer {
+        return Ok(IndexedDBKeyType::Binary(buffer.to_vec()));
+    }
+    typedarray!(in(cx) let view: ArrayBufferView = object.get());
+    if let Ok(view) = view {
+        return Ok(IndexedDBKeyType::Binary(view.to_vec()));
+    }
+
+    let mut is_array = false;
+    if !JS_IsArrayObject(cx, input, &mut is_array) {
+        return Err(Error::JSFailed);
+    }
+    if !is_array {
+        return Err(Error::Data);
+    }
+
+    // Steps 1-3 of the array case.
+    let mut length = 0;
+    if !JS_GetArrayLength(cx, object.handle(), &mut length) {
+        return Err(Error::JSFailed);
+    }
+    seen.push(object.get());
+    let mut keys = Vec::with_capacity(length as usize);
+
+    // Step 5 of the array case.
+    for index in 0..length {
+        let mut has_entry = false;
+        if !JS_HasElement(cx, object.handle(), index, &mut has_entry) {
+            return Err(Error::JSFailed);
+        }
+        if !has_entry {
+            return Err(Error::Data);
+        }
+        rooted!(in(cx) let mut entry = UndefinedValue());
+        if !JS_GetElement(cx, object.handle(), index, entry.handle_mut()) {
+            return Err(Error::JSFailed);
+        }
+        if entry.is_object() && seen.contains(&entry.to_object()) {
+            return Err(Error::Data);
+        }
+        keys.push(convert_value_to_key(
+            cx,
+            entry.handle(),
+            Some(seen.clone()),
+        )?);
+    }
+
+    // Step 6 of the array case.
+    Ok(IndexedDBKeyType::Array(keys))
+}
+
+/// Whether a string is an IdentifierName, not counting the ones with
+/// Unicode escape sequences
+fn is_identifier_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(c) if c.is_alphabetic() || c == '$' || c == '_' => {},
+        _ => return false,
+    }
+    chars.all(|c| c.is_alphanumeric() || c == '$' || c == '_' || c == '\u{200C}' || c == '\u{200D}')
+}
+
+/// https://w3c.github.io/IndexedDB/#valid-key-path
+pub fn is_valid_key_path(key_path: &KeyPath) -> bool {
+    let is_valid = |path: &str| path.is_empty() || path.split('.').all(is_identifier_name);
+    match *key_path {
+        KeyPath::String(ref path) => is_valid(path),
+        KeyPath::Sequence(ref paths) => {
+            !paths.is_empty() && paths.iter().all(|path| is_valid(path))
+        },
+    }
+}
+
+/// Whether `object` has an own property called `name`
+unsafe fn has_own_property(cx: *mut JSContext, object: HandleObject, name: &str) -> Fallible<bool> {
+    let name = match CString::new(name) {
+        Ok(name) => name,
+        Err(_) => return Ok(false),
+    };
+    let mut found = false;
+    if !JS_HasOwnProperty(cx, object, name.as_ptr(), &mut found) {
+        return Err(Error::JSFailed);
+    }
+    Ok(found)
+}
+
+/// Gets the property called `name` of `object`
+unsafe fn get_property(
+    cx: *mut JSContext,
+    object: HandleObject,
+    name: &str,
+    rval: MutableHandleValue,
+) -> Fallible<()> {
+    let name = CString::new(name).map_err(|_| Error::JSFailed)?;
+    if !JS_GetProperty(cx, object, name.as_ptr(), rval) {
+        return Err(Error::JSFailed);
+    }
+    Ok(())
+}
+
+/// Defines an enumerable property called `name` on `object`
+unsafe fn define_property(
+    cx: *mut JSContext,
+    object: HandleObject,
+    name: &str,
+    value: HandleValue,
+) -> Fallible<()> {
+    let name = CString::new(name).map_err(|_| Error::JSFailed)?;
+    if !JS_DefineProperty(cx, object, name.as_ptr(), value, JSPROP_ENUMERATE as u32) {
+        return Err(Error::JSFailed);
+    }
+    Ok(())
+}
+
+/// https://w3c.github.io/IndexedDB/#evaluate-a-key-path-on-a-value
+/// Returns whether the evaluation succeeded.
+unsafe fn evaluate_key_path_on_value(
+    cx: *mut JSContext,
+    value: HandleValue,
+    key_path: &str,
+    mut rval: MutableHandleValue,
+) -> Fallible<bool> {
+    // Step 3.
+    rooted!(in(cx) let mut current = value.get());
+    if !key_path.is_empty() {
+        // FIXME: The size and type of blobs and the name and lastModified of
+        // files are not evaluated.
+        for identifier in key_path.split('.') {
+            if current.is_string() && identifier == "length" {
+                let length = JS_GetStringLength(current.to_string());
+                current.set(DoubleValue(length as f64));
+                continue;
+            }
+            if !current.is_object() {
+                return Ok(false);
+            }
+            rooted!(in(cx) let object = current.to_object());
+            if !has_own_property(cx, object.handle(), identifier)? {
+                return Ok(false);
+            }
+            get_property(cx, object.handle(), identifier, current.handle_mut())?;
+        }
+    }
+
+    // Step 5.
+    rval.set(current.get());
+    Ok(true)
+}
+
+/// https://w3c.github.io/IndexedDB/#extract-a-key-from-a-value-using-a-key-path
+/// Returns `None` if evaluating the key path failed.
+pub unsafe fn extract_key(
+    cx: *mut JSContext,
+    value: HandleValue,
+    key_path: &KeyPath,
+) -> Fallible<Option<IndexedDBKeyType>> {
+    match *key_path {
+        KeyPath::String(ref path) => {
+            // Steps 1-2.
+            rooted!(in(cx) let mut result = UndefinedValue());
+            if !evaluate_key_path_on_value(cx, value, path, result.handle_mut())? {
+                return Ok(None);
+            }
+
+            // Steps 3-4.
+            convert_value_to_key(cx, result.handle(), None).map(Some)
+        },
+        KeyPath::Sequence(ref paths) => {
+            let mut keys = Vec::with_capacity(paths.len());
+            for path in paths {
+                match extract_key(cx, value, &KeyPath::String(path.clone()))? {
+                    Some(key) => keys.push(key),
+                    None => return Ok(None),
+                }
+            }
+            Ok(Some(IndexedDBKeyType::Array(keys)))
+        },
+    }
+}
+
+/// https://w3c.github.io/IndexedDB/#check-that-a-key-could-be-injected-into-a-value
+pub unsafe fn can_inject_key_into_value(
+    cx: *mut JSContext,
+    value: HandleValue,
+    key_path: &str,
+) -> Fallible<bool> {
+    // Steps 1-2.
+    let mut identifiers: Vec<&str> = key_path.split('.').collect();
+    identifiers.pop();
+
+    // Steps 3-4.
+    rooted!(in(cx) let mut current = value.get());
+    for identifier in identifiers {
+        if !current.is_object() {
+            return Ok(false);
+        }
+        rooted!(in(cx) let object = current.to_object());
+        if !has_own_property(cx, object.handle(), identifier)? {
+            return Ok(true);
+        }
+        get_property(cx, object.handle(), identifier, current.handle_mut())?;
+    }
+
+    // Step 5.
+    Ok(current.is_object())
+}
+
+/// https://w3c.github.io/IndexedDB/#inject-a-key-into-a-value-using-a-key-path
+pub unsafe fn inject_key_into_value(
+    cx: *mut JSContext,
+    value: HandleValue,
+    key: &IndexedDBKeyType,
+    key_path: &str,
+) -> Fallible<()> {
+    // Steps 1-3.
+    let mut identifiers: Vec<&str> = key_path.split('.').collect();
+    let last = match identifiers.pop() {
+        Some(last) => last,
+        None => return Ok(()),
+    };
+
+    // Step 4.
+    rooted!(in(cx) let mut current = value.get());
+    for identifier in identifiers {
+        if !current.is_object() {
+            return Ok(());
+        }
+        rooted!(in(cx) let object = current.to_object());
+        if !has_own_property(cx, object.handle(), identifier)? {
+            rooted!(in(cx) let child = JS_NewPlainObject(cx));
+            rooted!(in(cx) let child_value = ObjectValue(child.get()));
+            define_property(cx, object.handle(), identifier, child_value.handle())?;
+        }
+        get_property(cx, object.handle(), identifier, current.handle_mut())?;
+    }
+
+    // Steps 5-7.
+    if !current.is_object() {
+        return Ok(());
+    }
+    rooted!(in(cx) let object = current.to_object());
+    rooted!(in(cx) let mut key_value = UndefinedValue());
+    key_type_to_jsval(cx, key, key_value.handle_mut());
+    define_property(cx, object.handle(), last, key_value.handle())
+}
+
+/// Returns a sender for a reply of the IndexedDB thread, which gets handed
+/// to `callback` along with `object` in a task on the global of `object`
+pub fn route_reply<T, R, F>(object: &T, callback: F) -> IpcSender<R>
+where
+    T: DomObject + 'static,
+    R: for<'de> Deserialize<'de> + Serialize + Send + 'static,
+    F: FnOnce(&T, R) + Send + 'static,
+{
+    let (sender, receiver) = ipc::channel().unwrap();
+    let global = object.global();
+    // FIXME: This should be the database access task source.
+    let task_source = global.dom_manipulation_task_source();
+    let canceller = global.task_canceller(TaskSourceName::DOMManipulation);
+    let trusted = Trusted::new(object);
+    let mut callback = Some(callback);
+    ROUTER.add_route(
+        receiver.to_opaque(),
+        Box::new(move |message| {
+            let callback = match callback.take() {
+                Some(callback) => callback,
+                None => return,
+            };
+            let reply: R = message.to().unwrap();
+            let object = trusted.clone();
+            let _ = task_source.queue_with_canceller(
+                task!(handle_indexeddb_reply: move || {
+                    callback(&*object.root(), reply);
+                }),
+                &canceller,
+            );
+        }),
+    );
+    sender
+}
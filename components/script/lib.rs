@@ -50,6 +50,7 @@ extern crate style;
 
 #[macro_use]
 mod task;
+mod accessibility;
 mod body;
 pub mod clipboard_provider;
 mod devtools;
@@ -58,6 +59,7 @@ pub mod document_loader;
 mod dom;
 pub mod fetch;
 mod image_listener;
+mod indexed_db;
 mod layout_image;
 mod mem;
 mod microtask;
@@ -68,6 +70,7 @@ pub mod script_thread;
 mod serviceworker_manager;
 mod serviceworkerjob;
 mod stylesheet_loader;
+mod svg;
 mod task_manager;
 mod task_queue;
 mod task_source;
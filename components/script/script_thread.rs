@@ -47,6 +47,7 @@ use crate::dom::document::{
 };
 use crate::dom::element::Element;
 use crate::dom::event::{Event, EventBubbles, EventCancelable};
+use crate::dom::eventtarget::EventTarget;
 use crate::dom::globalscope::GlobalScope;
 use crate::dom::htmlanchorelement::HTMLAnchorElement;
 use crate::dom::htmliframeelement::{HTMLIFrameElement, NavigationType};
@@ -122,12 +123,13 @@ use profile_traits::time::{self as profile_time, profile, ProfilerCategory};
 use script_layout_interface::message::{self, LayoutThreadInit, Msg, ReflowGoal};
 use script_traits::webdriver_msg::WebDriverScriptCommand;
 use script_traits::CompositorEvent::{
-    CompositionEvent, KeyboardEvent, MouseButtonEvent, MouseMoveEvent, ResizeEvent, TouchEvent,
+    CompositionEvent, GamepadEvent, KeyboardEvent, MouseButtonEvent, MouseMoveEvent, ResizeEvent,
+    TouchEvent,
 };
 use script_traits::{CompositorEvent, ConstellationControlMsg};
 use script_traits::{DiscardBrowsingContext, DocumentActivity, EventResult};
 use script_traits::{InitialScriptState, JsEvalResult, LayoutMsg, LoadData};
-use script_traits::{MouseButton, MouseEventType, NewLayoutInfo};
+use script_traits::{MouseButton, MouseEventType, NewLayoutInfo, PermissionGrant};
 use script_traits::{Painter, ProgressiveWebMetricType, ScriptMsg, ScriptThreadFactory};
 use script_traits::{ScriptToConstellationChan, TimerEvent, TimerSchedulerMsg};
 use script_traits::{TimerSource, TouchEventType, TouchId, UntrustedNodeAddress};
@@ -1393,6 +1395,10 @@ impl ScriptThread {
                 // minimize unnecessary work.
                 window.reflow(ReflowGoal::Full, ReflowReason::MissingExplicitReflow);
             }
+            // Step 16
+            document.update_resize_observations();
+            // Step 19
+            document.update_intersection_observations();
         }
 
         true
@@ -1496,6 +1502,7 @@ impl ScriptThread {
                         child: _,
                     } => Some(id),
                     DispatchStorageEvent(id, ..) => Some(id),
+                    PermissionChanged(id, ..) => Some(id),
                     ReportCSSError(id, ..) => Some(id),
                     Reload(id, ..) => Some(id),
                     WebVREvents(id, ..) => Some(id),
@@ -1715,6 +1722,9 @@ impl ScriptThread {
                 old_value,
                 new_value,
             ) => self.handle_storage_event(pipeline_id, storage, url, key, old_value, new_value),
+            ConstellationControlMsg::PermissionChanged(pipeline_id, name, grant) => {
+                self.handle_permission_changed(pipeline_id, name, grant)
+            },
             ConstellationControlMsg::ReportCSSError(pipeline_id, filename, line, column, msg) => {
                 self.handle_css_error_reporting(pipeline_id, filename, line, column, msg)
             },
@@ -1904,6 +1914,14 @@ impl ScriptThread {
             WebDriverScriptCommand::GetElementCSS(node_id, name, reply) => {
                 webdriver_handlers::handle_get_css(&*documents, pipeline_id, node_id, name, reply)
             },
+            WebDriverScriptCommand::GetElementInViewCenterPoint(node_id, reply) => {
+                webdriver_handlers::handle_get_element_in_view_center_point(
+                    &*documents,
+                    pipeline_id,
+                    node_id,
+                    reply,
+                )
+            },
             WebDriverScriptCommand::GetElementRect(node_id, reply) => {
                 webdriver_handlers::handle_get_rect(&*documents, pipeline_id, node_id, reply)
             },
@@ -1998,15 +2016,33 @@ impl ScriptThread {
             },
         };
 
+        let old_viewport_origin = window.current_viewport().origin;
+        let mut scrolled_nodes = vec![];
         let mut scroll_offsets = HashMap::new();
         for &(node_address, ref scroll_offset) in scroll_states {
             if node_address == UntrustedNodeAddress(ptr::null()) {
                 window.update_viewport_for_scroll(-scroll_offset.x, -scroll_offset.y);
             } else {
+                let node =
+                    unsafe { from_untrusted_node_address(self.js_runtime.rt(), node_address) };
+                if window.scroll_offset_query(&node) != -*scroll_offset {
+                    scrolled_nodes.push(node);
+                }
                 scroll_offsets.insert(OpaqueNode(node_address.0 as usize), -*scroll_offset);
             }
         }
-        window.set_scroll_offsets(scroll_offsets)
+        window.set_scroll_offsets(scroll_offsets);
+
+        // https://drafts.csswg.org/cssom-view/#scrolling-events
+        if window.current_viewport().origin != old_viewport_origin {
+            window
+                .Document()
+                .upcast::<EventTarget>()
+                .fire_bubbling_event(atom!("scroll"));
+        }
+        for node in scrolled_nodes {
+            node.upcast::<EventTarget>().fire_event(atom!("scroll"));
+        }
     }
 
     fn handle_new_layout(&self, new_layout_info: NewLayoutInfo, origin: MutableOrigin) {
@@ -2656,6 +2692,21 @@ impl ScriptThread {
         storage.queue_storage_event(url, key, old_value, new_value);
     }
 
+    /// Updates the `PermissionStatus` objects of a window whose stored
+    /// permission changed.
+    fn handle_permission_changed(
+        &self,
+        pipeline_id: PipelineId,
+        name: String,
+        grant: Option<PermissionGrant>,
+    ) {
+        let window = match { self.documents.borrow().find_window(pipeline_id) } {
+            None => return warn!("Permission change sent to closed pipeline {}.", pipeline_id),
+            Some(window) => window,
+        };
+        window.permission_changed(&name, grant);
+    }
+
     /// Notify the containing document of a child iframe that has completed loading.
     fn handle_iframe_load_event(
         &self,
@@ -3157,6 +3208,17 @@ impl ScriptThread {
                 };
                 document.dispatch_composition_event(composition_event);
             },
+
+            GamepadEvent(gamepad_event) => {
+                let document = match { self.documents.borrow().find_document(pipeline_id) } {
+                    Some(document) => document,
+                    None => return warn!("Message sent to closed pipeline {}.", pipeline_id),
+                };
+                document
+                    .window()
+                    .Navigator()
+                    .handle_gamepad_event(gamepad_event);
+            },
         }
     }
 
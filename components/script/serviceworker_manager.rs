@@ -178,7 +178,10 @@ impl ServiceWorkerManager {
 
     fn handle_message_from_resource(&mut self, mediator: CustomResponseMediator) -> bool {
         if serviceworker_enabled() {
-            if let Some(scope) = self.get_matching_scope(&mediator.load_url) {
+            // A navigation is handled by the service worker controlling the
+            // URL it goes to, a subresource fetch by that of its client.
+            let client_url = mediator.client_url.as_ref().unwrap_or(&mediator.load_url);
+            if let Some(scope) = self.get_matching_scope(client_url) {
                 if self.active_workers.contains_key(&scope) {
                     if let Some(sender) = self.active_workers.get(&scope) {
                         let _ = sender.send(ServiceWorkerScriptMsg::Response(mediator));
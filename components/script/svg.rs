@@ -0,0 +1,827 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Painting of inline `<svg>` elements, whose subtree is turned into the
+//! drawing commands of a canvas paint thread that layout gets the image of
+//! the element from.
+//!
+//! Only a subset of SVG is supported: the `svg`, `g` and `use` containers,
+//! the basic shapes and paths, filled and stroked with colors or with linear
+//! and radial gradients. Presentation attributes and the declarations of
+//! `style` attributes apply, but the stylesheets of the document don't.
+
+use crate::dom::bindings::codegen::Bindings::DocumentBinding::DocumentMethods;
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::str::DOMString;
+use crate::dom::document::Document;
+use crate::dom::element::Element;
+use crate::dom::node::{document_from_node, Node};
+use crate::dom::path2d::segments_from_svg_path;
+use canvas_traits::canvas::RadialGradientStyle;
+use canvas_traits::canvas::{Canvas2dMsg, CanvasGradientStop, FillOrStrokeStyle};
+use canvas_traits::canvas::{LineCapStyle, LineJoinStyle, LinearGradientStyle, PathSegment};
+use cssparser::Color as CSSColor;
+use cssparser::{Parser, ParserInput, RGBA};
+use euclid::{vec2, Point2D, Rect, Size2D, Transform2D};
+use html5ever::LocalName;
+use std::f32::consts::PI;
+use std::str::FromStr;
+
+/// How many `use` elements can be followed while drawing one element, so
+/// that elements referencing themselves stop being drawn.
+const MAX_USE_DEPTH: u32 = 8;
+
+/// How many `href` attributes are followed to find the stops of a gradient.
+const MAX_GRADIENT_HREF_DEPTH: u32 = 8;
+
+/// The distance from the corners of a quarter of a circle of radius 1 to the
+/// control points of the cubic Bézier curve approximating it.
+const KAPPA: f32 = 0.552_284_8;
+
+/// How a shape is filled or stroked.
+///
+/// <https://svgwg.org/svg2-draft/painting.html#SpecifyingPaint>
+#[derive(Clone)]
+enum Paint {
+    None,
+    Color(RGBA),
+    /// The id of a gradient, and the color to use if there is no such
+    /// gradient.
+    Server(String, Option<RGBA>),
+}
+
+/// The properties an element inherits from its ancestors.
+#[derive(Clone)]
+struct PaintState {
+    fill: Paint,
+    fill_opacity: f32,
+    stroke: Paint,
+    stroke_opacity: f32,
+    stroke_width: f32,
+    line_cap: LineCapStyle,
+    line_join: LineJoinStyle,
+    miter_limit: f32,
+    /// The product of the `opacity` of the ancestors. Groups aren't drawn
+    /// into an offscreen surface, so this applies to each shape instead.
+    opacity: f32,
+    /// The transform from the user space of the element to device pixels.
+    transform: Transform2D<f32>,
+    /// The size of the nearest viewport, which percentages resolve against.
+    viewport: Size2D<f32>,
+}
+
+/// Turns the subtree of an `svg` element into canvas drawing commands.
+pub struct SvgPainter {
+    document: DomRoot<Document>,
+    /// The value of `currentColor`.
+    current_color: RGBA,
+    /// The viewport of the `svg` element, which the percentages of gradients
+    /// in `userSpaceOnUse` units resolve against, rather than the nearest
+    /// one of the shapes they paint.
+    viewport: Size2D<f32>,
+    commands: Vec<Canvas2dMsg>,
+}
+
+impl SvgPainter {
+    /// The commands that draw the subtree of `svg`, which is `size` CSS
+    /// pixels large, into a canvas of `scale` device pixels per CSS pixel.
+    pub fn paint(
+        svg: &Element,
+        size: Size2D<f32>,
+        scale: f32,
+        current_color: RGBA,
+    ) -> Vec<Canvas2dMsg> {
+        let mut painter = SvgPainter {
+            document: document_from_node(svg),
+            current_color: current_color,
+            viewport: size,
+            commands: vec![],
+        };
+        let black = RGBA::new(0, 0, 0, 255);
+        let state = PaintState {
+            fill: Paint::Color(black),
+            fill_opacity: 1.0,
+            stroke: Paint::None,
+            stroke_opacity: 1.0,
+            stroke_width: 1.0,
+            line_cap: LineCapStyle::Butt,
+            line_join: LineJoinStyle::Miter,
+            miter_limit: 4.0,
+            opacity: 1.0,
+            transform: Transform2D::row_major(scale, 0.0, 0.0, scale, 0.0, 0.0),
+            viewport: size,
+        };
+        let state = painter.inherit(svg, &state);
+        let state = PaintState {
+            transform: state.transform.pre_mul(&view_box_transform(svg, size)),
+            ..state
+        };
+        painter.draw_children(svg, &state, 0);
+        painter.commands
+    }
+
+    fn draw_children(&mut self, element: &Element, state: &PaintState, use_depth: u32) {
+        for child in element
+            .upcast::<Node>()
+            .children()
+            .filter_map(DomRoot::downcast::<Element>)
+        {
+            self.draw_element(&child, state, use_depth);
+        }
+    }
+
+    fn draw_element(&mut self, element: &Element, state: &PaintState, use_depth: u32) {
+        if element.namespace() != &ns!(svg) || property(element, "display") == Some("none".into()) {
+            return;
+        }
+        let state = self.inherit(element, state);
+        let viewport = state.viewport;
+        let x = length(element, "x", "0", viewport.width);
+        let y = length(element, "y", "0", viewport.height);
+        match &**element.local_name() {
+            "g" | "a" => self.draw_children(element, &state, use_depth),
+            "svg" => {
+                let size = Size2D::new(
+                    length(element, "width", "100%", viewport.width),
+                    length(element, "height", "100%", viewport.height),
+                );
+                let state = PaintState {
+                    transform: state
+                        .transform
+                        .pre_translate(vec2(x, y))
+                        .pre_mul(&view_box_transform(element, size)),
+                    viewport: size,
+                    ..state
+                };
+                self.draw_children(element, &state, use_depth);
+            },
+            "use" => {
+                if use_depth >= MAX_USE_DEPTH {
+                    return;
+                }
+                let referenced = match self.referenced_element(element) {
+                    Some(referenced) => referenced,
+                    None => return,
+                };
+                let state = PaintState {
+                    transform: state.transform.pre_translate(vec2(x, y)),
+                    ..state
+                };
+                if &**referenced.local_name() == "symbol" {
+                    // https://svgwg.org/svg2-draft/struct.html#UseElement
+                    let size = Size2D::new(
+                        length(element, "width", "100%", viewport.width),
+                        length(element, "height", "100%", viewport.height),
+                    );
+                    let state = self.inherit(&referenced, &state);
+                    let state = PaintState {
+                        transform: state
+                            .transform
+                            .pre_mul(&view_box_transform(&referenced, size)),
+                        viewport: size,
+                        ..state
+                    };
+                    self.draw_children(&referenced, &state, use_depth + 1);
+                } else {
+                    self.draw_element(&referenced, &state, use_depth + 1);
+                }
+            },
+            "rect" => {
+                let width = length(element, "width", "0", viewport.width);
+                let height = length(element, "height", "0", viewport.height);
+                if width <= 0.0 || height <= 0.0 {
+                    return;
+                }
+                let rect = Rect::new(Point2D::new(x, y), Size2D::new(width, height));
+                let rx = attribute(element, "rx").and_then(|rx| parse_length(&rx, viewport.width));
+                let ry = attribute(element, "ry").and_then(|ry| parse_length(&ry, viewport.height));
+                let (rx, ry) = match (rx, ry) {
+                    (Some(rx), Some(ry)) => (rx, ry),
+                    (Some(r), None) | (None, Some(r)) => (r, r),
+                    (None, None) => (0.0, 0.0),
+                };
+                let rx = rx.max(0.0).min(width / 2.0);
+                let ry = ry.max(0.0).min(height / 2.0);
+                self.draw_shape(&state, rounded_rect(&rect, rx, ry), rect);
+            },
+            "circle" => {
+                let cx = length(element, "cx", "0", viewport.width);
+                let cy = length(element, "cy", "0", viewport.height);
+                let r = length(element, "r", "0", normalized_diagonal(viewport));
+                self.draw_ellipse(&state, Point2D::new(cx, cy), r, r);
+            },
+            "ellipse" => {
+                let cx = length(element, "cx", "0", viewport.width);
+                let cy = length(element, "cy", "0", viewport.height);
+                let rx = length(element, "rx", "0", viewport.width);
+                let ry = length(element, "ry", "0", viewport.height);
+                self.draw_ellipse(&state, Point2D::new(cx, cy), rx, ry);
+            },
+            "line" => {
+                let from = Point2D::new(
+                    length(element, "x1", "0", viewport.width),
+                    length(element, "y1", "0", viewport.height),
+                );
+                let to = Point2D::new(
+                    length(element, "x2", "0", viewport.width),
+                    length(element, "y2", "0", viewport.height),
+                );
+                let bounds = Rect::from_points(&[from, to]);
+                self.draw_shape(
+                    &state,
+                    vec![PathSegment::MoveTo(from), PathSegment::LineTo(to)],
+                    bounds,
+                );
+            },
+            "polyline" | "polygon" => {
+                let numbers = attribute(element, "points")
+                    .and_then(|points| parse_numbers(&points))
+                    .unwrap_or_default();
+                let points: Vec<_> = numbers
+                    .chunks(2)
+                    .filter(|pair| pair.len() == 2)
+                    .map(|pair| Point2D::new(pair[0], pair[1]))
+                    .collect();
+                if points.is_empty() {
+                    return;
+                }
+                let mut segments = vec![PathSegment::MoveTo(points[0])];
+                segments.extend(points[1..].iter().map(|&point| PathSegment::LineTo(point)));
+                if &**element.local_name() == "polygon" {
+                    segments.push(PathSegment::ClosePath);
+                }
+                self.draw_shape(&state, segments, Rect::from_points(&points));
+            },
+            "path" => {
+                let segments = segments_from_svg_path(&attribute(element, "d").unwrap_or_default());
+                let bounds = path_bounds(&segments);
+                self.draw_shape(&state, segments, bounds);
+            },
+            // Paint servers, `defs` and `symbol` are only drawn when
+            // referenced, and the other elements aren't supported.
+            _ => {},
+        }
+    }
+
+    /// The state of `element`, whose parent has `state`.
+    fn inherit(&self, element: &Element, state: &PaintState) -> PaintState {
+        let mut state = state.clone();
+        let number = |name| property(element, name).and_then(|value| parse_number(&value));
+        if let Some(fill) = property(element, "fill").and_then(|fill| self.parse_paint(&fill)) {
+            state.fill = fill;
+        }
+        if let Some(stroke) =
+            property(element, "stroke").and_then(|stroke| self.parse_paint(&stroke))
+        {
+            state.stroke = stroke;
+        }
+        if let Some(opacity) = number("fill-opacity") {
+            state.fill_opacity = clamp_unit(opacity);
+        }
+        if let Some(opacity) = number("stroke-opacity") {
+            state.stroke_opacity = clamp_unit(opacity);
+        }
+        if let Some(width) = property(element, "stroke-width")
+            .and_then(|width| parse_length(&width, normalized_diagonal(state.viewport)))
+        {
+            state.stroke_width = width.max(0.0);
+        }
+        if let Some(cap) = property(element, "stroke-linecap")
+            .and_then(|cap| LineCapStyle::from_str(cap.trim()).ok())
+        {
+            state.line_cap = cap;
+        }
+        if let Some(join) = property(element, "stroke-linejoin")
+            .and_then(|join| LineJoinStyle::from_str(join.trim()).ok())
+        {
+            state.line_join = join;
+        }
+        if let Some(limit) = number("stroke-miterlimit") {
+            if limit >= 1.0 {
+                state.miter_limit = limit;
+            }
+        }
+        if let Some(opacity) = number("opacity") {
+            state.opacity *= clamp_unit(opacity);
+        }
+        if let Some(transform) = attribute(element, "transform") {
+            state.transform = state.transform.pre_mul(&parse_transform(&transform));
+        }
+        state
+    }
+
+    fn draw_ellipse(&mut self, state: &PaintState, center: Point2D<f32>, rx: f32, ry: f32) {
+        if rx <= 0.0 || ry <= 0.0 {
+            return;
+        }
+        let segments = vec![
+            PathSegment::MoveTo(Point2D::new(center.x + rx, center.y)),
+            PathSegment::Ellipse(center, rx, ry, 0.0, 0.0, 2.0 * PI, false),
+            PathSegment::ClosePath,
+        ];
+        let bounds = Rect::new(
+            Point2D::new(center.x - rx, center.y - ry),
+            Size2D::new(2.0 * rx, 2.0 * ry),
+        );
+        self.draw_shape(state, segments, bounds);
+    }
+
+    /// Fills and then strokes `segments`, whose bounding box is `bounds`.
+    fn draw_shape(&mut self, state: &PaintState, segments: Vec<PathSegment>, bounds: Rect<f32>) {
+        if segments.is_empty() {
+            return;
+        }
+        self.commands
+            .push(Canvas2dMsg::SetTransform(state.transform));
+        if let Some(style) = self.paint_style(&state.fill, &bounds) {
+            self.commands.push(Canvas2dMsg::SetGlobalAlpha(
+                state.opacity * state.fill_opacity,
+            ));
+            self.commands.push(Canvas2dMsg::SetFillStyle(style));
+            self.commands.push(Canvas2dMsg::FillPath(segments.clone()));
+        }
+        if state.stroke_width > 0.0 {
+            if let Some(style) = self.paint_style(&state.stroke, &bounds) {
+                self.commands.push(Canvas2dMsg::SetGlobalAlpha(
+                    state.opacity * state.stroke_opacity,
+                ));
+                self.commands.push(Canvas2dMsg::SetStrokeStyle(style));
+                self.commands
+                    .push(Canvas2dMsg::SetLineWidth(state.stroke_width));
+                self.commands.push(Canvas2dMsg::SetLineCap(state.line_cap));
+                self.commands
+                    .push(Canvas2dMsg::SetLineJoin(state.line_join));
+                self.commands
+                    .push(Canvas2dMsg::SetMiterLimit(state.miter_limit));
+                self.commands.push(Canvas2dMsg::StrokePath(segments));
+            }
+        }
+    }
+
+    /// <https://svgwg.org/svg2-draft/painting.html#SpecifyingPaint>
+    fn parse_paint(&self, value: &str) -> Option<Paint> {
+        let value = value.trim();
+        if value == "none" {
+            return Some(Paint::None);
+        }
+        if value.starts_with("url(") {
+            let end = value.find(')')?;
+            let url = value[4..end].trim().trim_matches(|c| c == '"' || c == '\'');
+            let fallback = value[end + 1..].trim();
+            let fallback = if fallback.is_empty() || fallback == "none" {
+                None
+            } else {
+                Some(self.parse_color(fallback)?)
+            };
+            return Some(Paint::Server(
+                url.trim_start_matches('#').to_owned(),
+                fallback,
+            ));
+        }
+        self.parse_color(value).map(Paint::Color)
+    }
+
+    fn parse_color(&self, value: &str) -> Option<RGBA> {
+        let mut input = ParserInput::new(value);
+        let mut parser = Parser::new(&mut input);
+        let color = CSSColor::parse(&mut parser).ok()?;
+        if !parser.is_exhausted() {
+            return None;
+        }
+        match color {
+            CSSColor::RGBA(rgba) => Some(rgba),
+            CSSColor::CurrentColor => Some(self.current_color),
+        }
+    }
+
+    /// The canvas style painting with `paint` a shape whose bounding box is
+    /// `bounds`, if it is painted at all.
+    fn paint_style(&self, paint: &Paint, bounds: &Rect<f32>) -> Option<FillOrStrokeStyle> {
+        match *paint {
+            Paint::None => None,
+            Paint::Color(color) => Some(FillOrStrokeStyle::Color(color)),
+            Paint::Server(ref id, fallback) => {
+                let server = self
+                    .document
+                    .GetElementById(DOMString::from(id.clone()))
+                    .filter(|server| server.namespace() == &ns!(svg));
+                match server {
+                    Some(ref gradient) if is_gradient(gradient) => {
+                        self.gradient_style(gradient, bounds)
+                    },
+                    _ => fallback.map(FillOrStrokeStyle::Color),
+                }
+            },
+        }
+    }
+
+    /// The canvas style of a linear or radial gradient element, mapped to
+    /// the user space of a shape whose bounding box is `bounds`.
+    ///
+    /// Canvas gradients can't be transformed, so their vectors and centers
+    /// are mapped to the user space instead, which only differs from the
+    /// gradient being transformed for skews and non-uniform scales.
+    ///
+    /// <https://svgwg.org/svg2-draft/pservers.html#Gradients>
+    fn gradient_style(&self, gradient: &Element, bounds: &Rect<f32>) -> Option<FillOrStrokeStyle> {
+        let stops = self.gradient_stops(gradient, 0);
+        match stops.len() {
+            0 => return None,
+            1 => return Some(FillOrStrokeStyle::Color(stops[0].color)),
+            _ => {},
+        }
+
+        let bounding_box_units = attribute(gradient, "gradientUnits")
+            .map_or(true, |units| units.trim() != "userSpaceOnUse");
+        let (units, reference) = if bounding_box_units {
+            if bounds.size.width <= 0.0 || bounds.size.height <= 0.0 {
+                return None;
+            }
+            let units = Transform2D::row_major(
+                bounds.size.width,
+                0.0,
+                0.0,
+                bounds.size.height,
+                bounds.origin.x,
+                bounds.origin.y,
+            );
+            (units, Size2D::new(1.0, 1.0))
+        } else {
+            (Transform2D::identity(), self.viewport)
+        };
+        let gradient_transform = attribute(gradient, "gradientTransform")
+            .map_or(Transform2D::identity(), |transform| {
+                parse_transform(&transform)
+            });
+        let to_user_space = units.pre_mul(&gradient_transform);
+        let point = |x: &str, x_default: &str, y: &str, y_default: &str| {
+            to_user_space.transform_point(&Point2D::new(
+                length(gradient, x, x_default, reference.width),
+                length(gradient, y, y_default, reference.height),
+            ))
+        };
+
+        if &**gradient.local_name() == "linearGradient" {
+            let from = point("x1", "0%", "y1", "0%");
+            let to = point("x2", "100%", "y2", "0%");
+            return Some(FillOrStrokeStyle::LinearGradient(LinearGradientStyle::new(
+                from.x as f64,
+                from.y as f64,
+                to.x as f64,
+                to.y as f64,
+                stops,
+            )));
+        }
+
+        let center = point("cx", "50%", "cy", "50%");
+        let cx = attribute(gradient, "cx").unwrap_or_else(|| "50%".to_owned());
+        let cy = attribute(gradient, "cy").unwrap_or_else(|| "50%".to_owned());
+        let focus = point("fx", &cx, "fy", &cy);
+        let scale = (to_user_space.m11 * to_user_space.m22 - to_user_space.m12 * to_user_space.m21)
+            .abs()
+            .sqrt();
+        let r = length(gradient, "r", "50%", normalized_diagonal(reference)) * scale;
+        if r <= 0.0 {
+            return Some(FillOrStrokeStyle::Color(stops[stops.len() - 1].color));
+        }
+        Some(FillOrStrokeStyle::RadialGradient(RadialGradientStyle::new(
+            focus.x as f64,
+            focus.y as f64,
+            0.0,
+            center.x as f64,
+            center.y as f64,
+            r as f64,
+            stops,
+        )))
+    }
+
+    /// The stops of `gradient`, or of the gradient it references if it has
+    /// none.
+    ///
+    /// <https://svgwg.org/svg2-draft/pservers.html#StopElement>
+    fn gradient_stops(&self, gradient: &Element, href_depth: u32) -> Vec<CanvasGradientStop> {
+        let mut stops = vec![];
+        let mut last_offset = 0.0;
+        for stop in gradient
+            .upcast::<Node>()
+            .children()
+            .filter_map(DomRoot::downcast::<Element>)
+            .filter(|child| child.namespace() == &ns!(svg) && &**child.local_name() == "stop")
+        {
+            let offset = attribute(&stop, "offset")
+                .and_then(|offset| parse_length(&offset, 1.0))
+                .map_or(0.0, clamp_unit)
+                .max(last_offset);
+            last_offset = offset;
+            let mut color = property(&stop, "stop-color")
+                .and_then(|color| self.parse_color(&color))
+                .unwrap_or(RGBA::new(0, 0, 0, 255));
+            if let Some(opacity) = property(&stop, "stop-opacity").and_then(|o| parse_number(&o)) {
+                color.alpha = (color.alpha as f32 * clamp_unit(opacity)).round() as u8;
+            }
+            stops.push(CanvasGradientStop {
+                offset: offset as f64,
+                color: color,
+            });
+        }
+        if stops.is_empty() && href_depth < MAX_GRADIENT_HREF_DEPTH {
+            if let Some(referenced) = self.referenced_element(gradient) {
+                if is_gradient(&referenced) {
+                    return self.gradient_stops(&referenced, href_depth + 1);
+                }
+            }
+        }
+        stops
+    }
+
+    /// The element the `href` attribute of `element` references by id.
+    fn referenced_element(&self, element: &Element) -> Option<DomRoot<Element>> {
+        let href = element
+            .get_attribute(&ns!(), &local_name!("href"))
+            .or_else(|| element.get_attribute(&ns!(xlink), &local_name!("href")))?;
+        let href = String::from(&**href.value());
+        let id = href.trim();
+        if !id.starts_with('#') {
+            return None;
+        }
+        self.document
+            .GetElementById(DOMString::from(&id[1..]))
+            .filter(|referenced| referenced.namespace() == &ns!(svg))
+    }
+}
+
+fn is_gradient(element: &Element) -> bool {
+    match &**element.local_name() {
+        "linearGradient" | "radialGradient" => true,
+        _ => false,
+    }
+}
+
+/// The value of the presentation attribute `name` of `element`, which the
+/// declarations of its `style` attribute override.
+fn property(element: &Element, name: &str) -> Option<String> {
+    if let Some(style) = element.get_attribute(&ns!(), &local_name!("style")) {
+        let style = style.value();
+        let value = style.split(';').rev().find_map(|declaration| {
+            let mut parts = declaration.splitn(2, ':');
+            match (parts.next(), parts.next()) {
+                (Some(property), Some(value)) if property.trim() == name => {
+                    Some(value.trim().to_owned())
+                },
+                _ => None,
+            }
+        });
+        if value.is_some() {
+            return value;
+        }
+    }
+    attribute(element, name)
+}
+
+fn attribute(element: &Element, name: &str) -> Option<String> {
+    element
+        .get_attribute(&ns!(), &LocalName::from(name))
+        .map(|attr| String::from(&**attr.value()))
+}
+
+/// The length of the attribute `name` of `element`, percentages resolving
+/// against `reference`, or `default` if it is missing or invalid.
+fn length(element: &Element, name: &str, default: &str, reference: f32) -> f32 {
+    attribute(element, name)
+        .and_then(|value| parse_length(&value, reference))
+        .or_else(|| parse_length(default, reference))
+        .unwrap_or(0.0)
+}
+
+/// The length `value` in CSS pixels, percentages resolving against
+/// `reference`. Only unitless, `px` and `%` lengths are supported.
+///
+/// <https://svgwg.org/svg2-draft/types.html#InterfaceSVGLength>
+fn parse_length(value: &str, reference: f32) -> Option<f32> {
+    let value = value.trim();
+    if value.ends_with('%') {
+        return parse_number(&value[..value.len() - 1])
+            .map(|percentage| percentage * reference / 100.0);
+    }
+    if value.ends_with("px") {
+        return parse_number(&value[..value.len() - 2]);
+    }
+    parse_number(value)
+}
+
+fn parse_number(value: &str) -> Option<f32> {
+    f32::from_str(value.trim())
+        .ok()
+        .filter(|number| number.is_finite())
+}
+
+fn clamp_unit(value: f32) -> f32 {
+    value.max(0.0).min(1.0)
+}
+
+/// What percentages of lengths which are neither horizontal nor vertical
+/// resolve against.
+///
+/// <https://svgwg.org/svg2-draft/coords.html#Units>
+fn normalized_diagonal(viewport: Size2D<f32>) -> f32 {
+    ((viewport.width * viewport.width + viewport.height * viewport.height) / 2.0).sqrt()
+}
+
+/// The numbers of a list separated with whitespace or commas, like the
+/// `points` of polygons and the `viewBox` of viewports, where a sign or a
+/// second decimal point also start a number.
+fn parse_numbers(string: &str) -> Option<Vec<f32>> {
+    let bytes = string.as_bytes();
+    let mut numbers = vec![];
+    let mut index = 0;
+    loop {
+        while index < bytes.len() && (bytes[index].is_ascii_whitespace() || bytes[index] == b',') {
+            index += 1;
+        }
+        if index == bytes.len() {
+            return Some(numbers);
+        }
+        let start = index;
+        if bytes[index] == b'+' || bytes[index] == b'-' {
+            index += 1;
+        }
+        let mut seen_point = false;
+        while index < bytes.len() &&
+            (bytes[index].is_ascii_digit() || (bytes[index] == b'.' && !seen_point))
+        {
+            seen_point |= bytes[index] == b'.';
+            index += 1;
+        }
+        if index < bytes.len() && (bytes[index] == b'e' || bytes[index] == b'E') {
+            index += 1;
+            if index < bytes.len() && (bytes[index] == b'+' || bytes[index] == b'-') {
+                index += 1;
+            }
+            while index < bytes.len() && bytes[index].is_ascii_digit() {
+                index += 1;
+            }
+        }
+        numbers.push(parse_number(&string[start..index])?);
+    }
+}
+
+/// The transform a `transform` attribute describes, which is the identity
+/// if it is invalid.
+///
+/// <https://svgwg.org/svg2-draft/coords.html#TransformProperty>
+fn parse_transform(string: &str) -> Transform2D<f32> {
+    let mut transform = Transform2D::identity();
+    let mut rest = string;
+    loop {
+        rest = rest.trim_start_matches(|c: char| c.is_whitespace() || c == ',');
+        if rest.is_empty() {
+            return transform;
+        }
+        let (open, close) = match (rest.find('('), rest.find(')')) {
+            (Some(open), Some(close)) if open < close => (open, close),
+            _ => return Transform2D::identity(),
+        };
+        let arguments = match parse_numbers(&rest[open + 1..close]) {
+            Some(arguments) => arguments,
+            None => return Transform2D::identity(),
+        };
+        let function = match (rest[..open].trim(), &*arguments) {
+            ("matrix", &[a, b, c, d, e, f]) => Transform2D::row_major(a, b, c, d, e, f),
+            ("translate", &[x]) => Transform2D::row_major(1.0, 0.0, 0.0, 1.0, x, 0.0),
+            ("translate", &[x, y]) => Transform2D::row_major(1.0, 0.0, 0.0, 1.0, x, y),
+            ("scale", &[s]) => Transform2D::row_major(s, 0.0, 0.0, s, 0.0, 0.0),
+            ("scale", &[x, y]) => Transform2D::row_major(x, 0.0, 0.0, y, 0.0, 0.0),
+            ("rotate", &[angle]) => rotation(angle),
+            ("rotate", &[angle, cx, cy]) => Transform2D::row_major(1.0, 0.0, 0.0, 1.0, cx, cy)
+                .pre_mul(&rotation(angle))
+                .pre_translate(vec2(-cx, -cy)),
+            ("skewX", &[angle]) => {
+                Transform2D::row_major(1.0, 0.0, angle.to_radians().tan(), 1.0, 0.0, 0.0)
+            },
+            ("skewY", &[angle]) => {
+                Transform2D::row_major(1.0, angle.to_radians().tan(), 0.0, 1.0, 0.0, 0.0)
+            },
+            _ => return Transform2D::identity(),
+        };
+        transform = transform.pre_mul(&function);
+        rest = &rest[close + 1..];
+    }
+}
+
+/// A rotation of `angle` degrees, clockwise in the y-down user space.
+fn rotation(angle: f32) -> Transform2D<f32> {
+    let (sin, cos) = angle.to_radians().sin_cos();
+    Transform2D::row_major(cos, sin, -sin, cos, 0.0, 0.0)
+}
+
+/// The transform from the coordinates of the `viewBox` of `element` to a
+/// viewport of `size`, honoring its `preserveAspectRatio`.
+///
+/// <https://svgwg.org/svg2-draft/coords.html#ComputingAViewportsTransform>
+fn view_box_transform(element: &Element, size: Size2D<f32>) -> Transform2D<f32> {
+    let view_box = match attribute(element, "viewBox").and_then(|value| parse_numbers(&value)) {
+        Some(ref numbers) if numbers.len() == 4 && numbers[2] > 0.0 && numbers[3] > 0.0 => {
+            Rect::new(
+                Point2D::new(numbers[0], numbers[1]),
+                Size2D::new(numbers[2], numbers[3]),
+            )
+        },
+        _ => return Transform2D::identity(),
+    };
+    let aspect_ratio = attribute(element, "preserveAspectRatio").unwrap_or_default();
+    let mut aspect_ratio = aspect_ratio.split_whitespace();
+    let align = aspect_ratio.next().unwrap_or("xMidYMid");
+    let slice = aspect_ratio.next() == Some("slice");
+
+    let mut scale_x = size.width / view_box.size.width;
+    let mut scale_y = size.height / view_box.size.height;
+    if align != "none" {
+        let scale = if slice {
+            scale_x.max(scale_y)
+        } else {
+            scale_x.min(scale_y)
+        };
+        scale_x = scale;
+        scale_y = scale;
+    }
+    let mut translate_x = -view_box.origin.x * scale_x;
+    let mut translate_y = -view_box.origin.y * scale_y;
+    let extra_width = size.width - view_box.size.width * scale_x;
+    let extra_height = size.height - view_box.size.height * scale_y;
+    if align.contains("xMid") {
+        translate_x += extra_width / 2.0;
+    } else if align.contains("xMax") {
+        translate_x += extra_width;
+    }
+    if align.contains("YMid") {
+        translate_y += extra_height / 2.0;
+    } else if align.contains("YMax") {
+        translate_y += extra_height;
+    }
+    Transform2D::row_major(scale_x, 0.0, 0.0, scale_y, translate_x, translate_y)
+}
+
+/// The segments of `rect` with corners rounded by ellipses of radii `rx`
+/// and `ry`.
+///
+/// <https://svgwg.org/svg2-draft/shapes.html#RectElement>
+fn rounded_rect(rect: &Rect<f32>, rx: f32, ry: f32) -> Vec<PathSegment> {
+    if rx <= 0.0 || ry <= 0.0 {
+        return vec![PathSegment::Rect(*rect)];
+    }
+    let (left, top) = (rect.min_x(), rect.min_y());
+    let (right, bottom) = (rect.max_x(), rect.max_y());
+    let (kx, ky) = (rx * KAPPA, ry * KAPPA);
+    vec![
+        PathSegment::MoveTo(Point2D::new(left + rx, top)),
+        PathSegment::LineTo(Point2D::new(right - rx, top)),
+        PathSegment::BezierCurveTo(
+            Point2D::new(right - rx + kx, top),
+            Point2D::new(right, top + ry - ky),
+            Point2D::new(right, top + ry),
+        ),
+        PathSegment::LineTo(Point2D::new(right, bottom - ry)),
+        PathSegment::BezierCurveTo(
+            Point2D::new(right, bottom - ry + ky),
+            Point2D::new(right - rx + kx, bottom),
+            Point2D::new(right - rx, bottom),
+        ),
+        PathSegment::LineTo(Point2D::new(left + rx, bottom)),
+        PathSegment::BezierCurveTo(
+            Point2D::new(left + rx - kx, bottom),
+            Point2D::new(left, bottom - ry + ky),
+            Point2D::new(left, bottom - ry),
+        ),
+        PathSegment::LineTo(Point2D::new(left, top + ry)),
+        PathSegment::BezierCurveTo(
+            Point2D::new(left, top + ry - ky),
+            Point2D::new(left + rx - kx, top),
+            Point2D::new(left + rx, top),
+        ),
+        PathSegment::ClosePath,
+    ]
+}
+
+/// The bounding box of the points and control points of `segments`, which
+/// contains the path they describe.
+fn path_bounds(segments: &[PathSegment]) -> Rect<f32> {
+    let mut points = vec![];
+    for segment in segments {
+        match *segment {
+            PathSegment::MoveTo(point) | PathSegment::LineTo(point) => points.push(point),
+            PathSegment::QuadraticCurveTo(control, point) => points.extend(&[control, point]),
+            PathSegment::BezierCurveTo(control1, control2, point) => {
+                points.extend(&[control1, control2, point])
+            },
+            PathSegment::Ellipse(center, rx, ry, ..) => {
+                let radius = rx.max(ry);
+                points.push(Point2D::new(center.x - radius, center.y - radius));
+                points.push(Point2D::new(center.x + radius, center.y + radius));
+            },
+            _ => {},
+        }
+    }
+    Rect::from_points(&points)
+}
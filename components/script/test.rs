@@ -11,10 +11,62 @@ pub use crate::dom::bindings::refcounted::TrustedPromise;
 pub use crate::dom::bindings::root::Dom;
 pub use crate::dom::node::Node;
 
+pub mod accessibility {
+    pub use crate::accessibility::{aria_role, is_named_from_content, remove_unreachable_nodes};
+    pub use embedder_traits::{AccessibilityNode, AccessibilityNodeId, AccessibilityRole};
+}
+
+pub mod animationeffect {
+    pub use crate::dom::animationeffect::{Phase, Timing};
+    pub use crate::dom::bindings::codegen::Bindings::AnimationEffectBinding::{
+        FillMode, PlaybackDirection,
+    };
+}
+
 pub mod area {
     pub use crate::dom::htmlareaelement::{Area, Shape};
 }
 
+pub mod document {
+    pub use crate::dom::document::AnimationFrameSuspension;
+}
+
+pub mod dommatrixreadonly {
+    pub use crate::dom::dommatrixreadonly::{
+        column_major_to_transform3d, entries_to_matrix, matrices_equal, matrix_to_string,
+        multiply_matrices, number_to_string, transform3d_to_column_major, DOMMatrixReadOnly,
+    };
+}
+
+pub mod dompointreadonly {
+    pub use crate::dom::dompointreadonly::DOMPointReadOnly;
+}
+
+pub mod gamepad {
+    pub use crate::dom::gamepad::sync_axes;
+}
+
+pub mod gamepadbutton {
+    pub use crate::dom::gamepadbutton::button_state;
+}
+
+pub mod gamepadhapticactuator {
+    pub use crate::dom::gamepadhapticactuator::{pulse_parameters, PlayingPulse};
+}
+
+pub mod geometry {
+    pub use crate::dom::geometry::{
+        all_finite, inverse_or_nan, normalized_rotation, normalized_vector, perspective_divide,
+    };
+}
+
+pub mod intersectionobserver {
+    pub use crate::dom::intersectionobserver::{
+        edge_inclusive_intersection, expand_by_margin, intersection_ratio, parse_root_margin,
+        threshold_index, MarginLength,
+    };
+}
+
 pub mod size_of {
     use crate::dom::characterdata::CharacterData;
     use crate::dom::element::Element;
@@ -66,3 +118,201 @@ pub mod srcset {
 pub mod timeranges {
     pub use crate::dom::timeranges::TimeRangesContainer;
 }
+
+pub mod vrdisplay {
+    pub use crate::dom::vrdisplay::{run_frame_callbacks, FrameCallbacks};
+}
+
+pub mod xr {
+    pub use crate::dom::bindings::codegen::Bindings::XRBinding::XRSessionMode;
+    pub use crate::dom::bindings::codegen::Bindings::XRSessionBinding::XRReferenceSpaceType;
+    pub use crate::dom::xr::{resolve_features, DevicePresence, XRFeature};
+}
+
+pub mod xrboundedreferencespace {
+    pub use crate::dom::xrboundedreferencespace::rectangular_bounds;
+}
+
+pub mod xrcompositionlayer {
+    pub use crate::dom::xrcompositionlayer::CompositionLayerState;
+}
+
+pub mod xrframe {
+    pub use crate::dom::xrframe::{FrameCache, FrameState};
+}
+
+pub mod xrhand {
+    pub use crate::dom::bindings::codegen::Bindings::XRHandBinding::XRHandJoint;
+    pub use crate::dom::xrhand::JOINTS;
+}
+
+pub mod xrhittestresult {
+    pub use crate::dom::xrhittestresult::hit_pose;
+    pub use webvr_traits::WebVRHitTestResult;
+}
+
+pub mod xrimagetrackingresult {
+    pub use crate::dom::bindings::codegen::Bindings::XRImageTrackingResultBinding::XRImageTrackingState;
+    pub use crate::dom::xrimagetrackingresult::TrackedImage;
+}
+
+pub mod xrinputsource {
+    pub use crate::dom::bindings::codegen::Bindings::XRInputSourceBinding::XRTargetRayMode;
+    pub use crate::dom::xrinputsource::{
+        default_target_ray_offset, profiles_from_gamepad_id, target_ray_from_grip,
+        xr_gamepad_mapping,
+    };
+}
+
+pub mod xrquadlayer {
+    pub use crate::dom::xrquadlayer::check_dimension;
+}
+
+pub mod xrray {
+    pub use crate::dom::xrray::{ray_from_transform, ray_transform};
+}
+
+pub mod xrreferencespace {
+    pub use crate::dom::xrreferencespace::{compose_offsets, is_identity_offset};
+    use crate::dom::xrspace::{typed_pose, untyped_pose};
+    use euclid::RigidTransform3D;
+
+    pub fn offset_pose(
+        pose: &RigidTransform3D<f64>,
+        origin_offset: &RigidTransform3D<f64>,
+    ) -> RigidTransform3D<f64> {
+        untyped_pose(&crate::dom::xrreferencespace::offset_pose(
+            &typed_pose(pose),
+            origin_offset,
+        ))
+    }
+}
+
+pub mod xrrenderstate {
+    pub use crate::dom::xrrenderstate::{merge_render_state, RenderStateFields, RenderStateUpdate};
+}
+
+pub mod xrrigidtransform {
+    pub use crate::dom::xrrigidtransform::{
+        interpolate_rigid_transforms, rigid_transform_from_matrix, rigid_transform_to_column_major,
+    };
+}
+
+pub mod xrsession {
+    pub use crate::dom::bindings::codegen::Bindings::XRBinding::XRSessionMode;
+    pub use crate::dom::bindings::codegen::Bindings::XRSessionBinding::XREnvironmentBlendMode;
+    pub use crate::dom::bindings::codegen::Bindings::XRStationaryReferenceSpaceBinding::XRStationaryReferenceSpaceSubtype;
+    pub use crate::dom::bindings::error::Error;
+    pub use crate::dom::xrsession::{
+        environment_blend_mode, fires_reset, negotiated_frame_rate, predicted_display_time,
+        prediction_interval, validate_depth_range, viewport_scale_for_frame_rate, DeviceFrameRate,
+        FloorOrigin, FrameCounter, FrameRateLimiter, LostInputSources, OriginChange, PoseClock,
+        ViewerSpaceCache,
+    };
+}
+
+pub mod xrspace {
+    use crate::dom::xrspace::typed_pose;
+    pub use crate::dom::xrspace::{
+        reference_space_velocity, untyped_pose, ApiSpace, Native, NativePose, SpaceVelocity,
+    };
+    use euclid::{RigidTransform3D, Transform3D, Vector3D};
+
+    pub fn relative_transform(a: Transform3D<f64>, b: Transform3D<f64>) -> Transform3D<f64> {
+        crate::dom::xrspace::relative_transform(a, b)
+    }
+
+    pub fn relative_pose(
+        a: &RigidTransform3D<f64>,
+        b: &RigidTransform3D<f64>,
+    ) -> RigidTransform3D<f64> {
+        crate::dom::xrspace::relative_pose(&typed_pose(a), &typed_pose(b))
+    }
+
+    pub fn extrapolate(
+        transform: &RigidTransform3D<f64>,
+        linear_velocity: Vector3D<f64>,
+        angular_velocity: Vector3D<f64>,
+        dt: f64,
+    ) -> RigidTransform3D<f64> {
+        crate::dom::xrspace::extrapolate(transform, linear_velocity, angular_velocity, dt)
+    }
+
+    pub fn velocity_at(
+        velocity: &SpaceVelocity,
+        from: Vector3D<f64>,
+        to: Vector3D<f64>,
+    ) -> SpaceVelocity {
+        crate::dom::xrspace::velocity_at(velocity, from, to)
+    }
+
+    pub fn relative_velocity(
+        a: &RigidTransform3D<f64>,
+        a_velocity: &SpaceVelocity,
+        b: &RigidTransform3D<f64>,
+        b_velocity: &SpaceVelocity,
+    ) -> SpaceVelocity {
+        crate::dom::xrspace::relative_velocity(
+            &typed_pose(a),
+            a_velocity,
+            &typed_pose(b),
+            b_velocity,
+        )
+    }
+}
+
+pub mod xrstationaryreferencespace {
+    use crate::dom::xrspace::untyped_pose;
+    use euclid::RigidTransform3D;
+
+    pub fn floor_pose(
+        sitting_to_standing: Option<[f32; 16]>,
+        emulate_height: bool,
+    ) -> Option<RigidTransform3D<f64>> {
+        crate::dom::xrstationaryreferencespace::floor_pose(sitting_to_standing, emulate_height)
+            .map(|pose| untyped_pose(&pose))
+    }
+}
+
+pub mod xrview {
+    pub use crate::dom::xrview::{
+        clamp_viewport_scale, has_camera, MAX_VIEWPORT_SCALE, MIN_VIEWPORT_SCALE,
+    };
+    use euclid::{RigidTransform3D, Vector3D};
+
+    pub fn eye_transform(
+        head: &RigidTransform3D<f64>,
+        offset: Vector3D<f64>,
+    ) -> RigidTransform3D<f64> {
+        crate::dom::xrview::eye_transform(head, offset)
+    }
+
+    pub fn projection_from_fov(
+        up: f64,
+        right: f64,
+        down: f64,
+        left: f64,
+        near: f64,
+        far: f64,
+    ) -> [f32; 16] {
+        crate::dom::xrview::projection_from_fov(up, right, down, left, near, far)
+    }
+
+    pub fn projection_with_clip_planes(proj: &[f32; 16], near: f64, far: f64) -> [f32; 16] {
+        crate::dom::xrview::projection_with_clip_planes(proj, near, far)
+    }
+}
+
+pub mod xrwebgllayer {
+    pub use crate::dom::bindings::codegen::Bindings::XRViewBinding::XREye;
+    pub use crate::dom::xrwebgllayer::fixed_foveation_request;
+    use euclid::{Rect, Size2D};
+
+    pub fn eye_viewport(eye: XREye, size: Size2D<u32>, scale: f64, multiview: bool) -> Rect<u32> {
+        crate::dom::xrwebgllayer::eye_viewport(eye, size, scale, multiview)
+    }
+
+    pub fn framebuffer_size(context_size: Size2D<u32>, has_framebuffer: bool) -> Size2D<u32> {
+        crate::dom::xrwebgllayer::framebuffer_size(context_size, has_framebuffer)
+    }
+}
@@ -101,6 +101,10 @@ pub struct TextInput<T: ClipboardProvider> {
     /// <https://html.spec.whatwg.org/multipage/#attr-fe-maxlength>
     max_length: Option<usize>,
     min_length: Option<usize>,
+
+    /// The UTF-8 byte range of the text being composed by the input method, if a composition is
+    /// in progress.
+    composition: Option<Range<usize>>,
 }
 
 /// Resulting action to be taken by the owner of a text input that is handling an event.
@@ -182,6 +186,7 @@ impl<T: ClipboardProvider> TextInput<T> {
             max_length: max_length,
             min_length: min_length,
             selection_direction: selection_direction,
+            composition: None,
         };
         i.set_content(initial);
         i
@@ -853,11 +858,54 @@ impl<T: ClipboardProvider> TextInput<T> {
             .unwrap()
     }
 
+    /// Replace the text being composed, or the selection if the composition just started, with
+    /// the new composition string.
+    pub fn handle_compositionupdate(&mut self, event: &CompositionEvent) -> KeyReaction {
+        self.update_composition(event.data());
+        KeyReaction::DispatchInput
+    }
+
+    /// Commit the composition string, replacing the text that was being composed.
     pub fn handle_compositionend(&mut self, event: &CompositionEvent) -> KeyReaction {
-        self.insert_string(event.data());
+        self.end_composition(event.data());
         KeyReaction::DispatchInput
     }
 
+    /// Replace the text being composed, or the selection if no composition is in progress, with
+    /// `data`, which is composed from then on.
+    pub fn update_composition(&mut self, data: &str) {
+        let start = match self.composition.take() {
+            Some(range) => {
+                self.set_selection_range(
+                    range.start as u32,
+                    range.end as u32,
+                    SelectionDirection::None,
+                );
+                range.start
+            },
+            None => self.selection_start_offset(),
+        };
+        self.insert_string(data);
+        self.composition = Some(start..self.text_point_to_offset(&self.edit_point));
+    }
+
+    /// Replace the text being composed, if any, with `data`, ending the composition.
+    pub fn end_composition(&mut self, data: &str) {
+        if let Some(range) = self.composition.take() {
+            self.set_selection_range(
+                range.start as u32,
+                range.end as u32,
+                SelectionDirection::None,
+            );
+        }
+        self.insert_string(data);
+    }
+
+    /// The UTF-8 byte range of the text being composed by the input method, if any.
+    pub fn composition(&self) -> Option<Range<usize>> {
+        self.composition.clone()
+    }
+
     /// Whether the content is empty.
     pub fn is_empty(&self) -> bool {
         self.lines.len() <= 1 && self.lines.get(0).map_or(true, |line| line.is_empty())
@@ -917,6 +965,8 @@ impl<T: ClipboardProvider> TextInput<T> {
         };
 
         self.edit_point = self.edit_point.constrain_to(&self.lines);
+        // The text being composed is gone with the old content.
+        self.composition = None;
 
         if let Some(origin) = self.selection_origin {
             self.selection_origin = Some(origin.constrain_to(&self.lines));
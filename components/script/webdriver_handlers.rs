@@ -3,6 +3,7 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
 use crate::dom::bindings::codegen::Bindings::CSSStyleDeclarationBinding::CSSStyleDeclarationMethods;
+use crate::dom::bindings::codegen::Bindings::DOMRectBinding::DOMRectMethods;
 use crate::dom::bindings::codegen::Bindings::DocumentBinding::DocumentMethods;
 use crate::dom::bindings::codegen::Bindings::ElementBinding::ElementMethods;
 use crate::dom::bindings::codegen::Bindings::HTMLElementBinding::HTMLElementMethods;
@@ -413,6 +414,52 @@ pub fn handle_get_rect(
         .unwrap();
 }
 
+// https://w3c.github.io/webdriver/#dfn-in-view-center-point
+pub fn handle_get_element_in_view_center_point(
+    documents: &Documents,
+    pipeline: PipelineId,
+    element_id: String,
+    reply: IpcSender<Result<Option<(i64, i64)>, ()>>,
+) {
+    reply
+        .send(
+            match find_node_by_unique_id(documents, pipeline, element_id) {
+                Some(node) => match node.downcast::<Element>() {
+                    Some(element) => {
+                        let window = window_from_node(&*node);
+                        // Step 1.
+                        Ok(element.GetClientRects().first().and_then(|rect| {
+                            // Steps 2-5.
+                            let left = rect.X().min(rect.X() + rect.Width()).max(0.);
+                            let right = rect
+                                .X()
+                                .max(rect.X() + rect.Width())
+                                .min(window.InnerWidth() as f64);
+                            let top = rect.Y().min(rect.Y() + rect.Height()).max(0.);
+                            let bottom = rect
+                                .Y()
+                                .max(rect.Y() + rect.Height())
+                                .min(window.InnerHeight() as f64);
+                            // The element is not in view if clipping it by
+                            // the viewport leaves nothing.
+                            if left > right || top > bottom {
+                                return None;
+                            }
+                            // Steps 6-8.
+                            Some((
+                                ((left + right) / 2.).floor() as i64,
+                                ((top + bottom) / 2.).floor() as i64,
+                            ))
+                        }))
+                    },
+                    None => Err(()),
+                },
+                None => Err(()),
+            },
+        )
+        .unwrap();
+}
+
 pub fn handle_get_text(
     documents: &Documents,
     pipeline: PipelineId,
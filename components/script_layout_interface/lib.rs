@@ -104,6 +104,7 @@ pub enum LayoutElementType {
 pub enum HTMLCanvasDataSource {
     WebGL(webrender_api::ImageKey),
     Image(Option<IpcSender<CanvasMsg>>),
+    WebGPU(webrender_api::ImageKey),
 }
 
 pub struct HTMLCanvasData {
@@ -116,6 +117,9 @@ pub struct HTMLCanvasData {
 pub struct SVGSVGData {
     pub width: u32,
     pub height: u32,
+    /// The canvas paint thread the subtree of the element was painted with,
+    /// if it was painted.
+    pub canvas: Option<(IpcSender<CanvasMsg>, CanvasId)>,
 }
 
 /// The address of a node known to be valid. These are sent from script to layout.
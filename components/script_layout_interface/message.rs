@@ -22,9 +22,11 @@ use servo_url::ServoUrl;
 use std::sync::Arc;
 use style::context::QuirksMode;
 use style::dom::OpaqueNode;
+use style::font_face::EffectiveSources;
 use style::properties::PropertyId;
 use style::selector_parser::PseudoElement;
 use style::stylesheets::Stylesheet;
+use style::values::computed::font::FamilyName;
 
 /// Asynchronous messages that script can send to layout.
 pub enum Msg {
@@ -87,8 +89,9 @@ pub enum Msg {
     /// Set the final Url.
     SetFinalUrl(ServoUrl),
 
-    /// Tells layout about the new scrolling offsets of each scrollable stacking context.
-    SetScrollStates(Vec<ScrollState>),
+    /// Tells layout about the new scrolling offsets of each scrollable stacking context, and
+    /// whether the user has finished scrolling.
+    SetScrollStates(Vec<ScrollState>, bool),
 
     /// Tells layout about a single new scrolling offset from the script. The rest will
     /// remain untouched and layout won't forward this back to script.
@@ -102,6 +105,19 @@ pub enum Msg {
 
     /// Request the current number of animations that are running.
     GetRunningAnimations(IpcSender<usize>),
+
+    /// Loads a font face script created, and tells whether it could be loaded
+    /// once done.
+    LoadWebFont(FamilyName, WebFontSource, IpcSender<bool>),
+}
+
+/// Where the font of a font face script created comes from.
+#[derive(Debug)]
+pub enum WebFontSource {
+    /// The sources of its `src` descriptor.
+    Sources(EffectiveSources),
+    /// The data it was created with.
+    Data(Vec<u8>),
 }
 
 #[derive(Debug, PartialEq)]
@@ -114,6 +130,7 @@ pub enum NodesFromPointQueryType {
 pub enum QueryMsg {
     ContentBoxQuery(OpaqueNode),
     ContentBoxesQuery(OpaqueNode),
+    BoxSizesQuery(OpaqueNode),
     NodeGeometryQuery(OpaqueNode),
     NodeScrollGeometryQuery(OpaqueNode),
     OffsetParentQuery(OpaqueNode),
@@ -149,6 +166,7 @@ impl ReflowGoal {
                 QueryMsg::ElementInnerTextQuery(_) => true,
                 QueryMsg::ContentBoxQuery(_) |
                 QueryMsg::ContentBoxesQuery(_) |
+                QueryMsg::BoxSizesQuery(_) |
                 QueryMsg::NodeGeometryQuery(_) |
                 QueryMsg::NodeScrollGeometryQuery(_) |
                 QueryMsg::NodeScrollIdQuery(_) |
@@ -170,6 +188,7 @@ impl ReflowGoal {
                 QueryMsg::ElementInnerTextQuery(_) => true,
                 QueryMsg::ContentBoxQuery(_) |
                 QueryMsg::ContentBoxesQuery(_) |
+                QueryMsg::BoxSizesQuery(_) |
                 QueryMsg::NodeGeometryQuery(_) |
                 QueryMsg::NodeScrollGeometryQuery(_) |
                 QueryMsg::NodeScrollIdQuery(_) |
@@ -3,7 +3,7 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
 use app_units::Au;
-use euclid::{Point2D, Rect};
+use euclid::{Point2D, Rect, Size2D};
 use script_traits::UntrustedNodeAddress;
 use servo_arc::Arc;
 use style::properties::longhands::overflow_x;
@@ -23,6 +23,8 @@ pub trait LayoutRPC {
     fn content_box(&self) -> ContentBoxResponse;
     /// Requests the dimensions of all the content boxes, as in the `getClientRects()` call.
     fn content_boxes(&self) -> ContentBoxesResponse;
+    /// Requests the sizes of the boxes of this node, as reported by `ResizeObserver`.
+    fn box_sizes(&self) -> BoxSizesResponse;
     /// Requests the geometry of this node. Used by APIs such as `clientTop`.
     fn node_geometry(&self) -> NodeGeometryResponse;
     /// Requests the scroll geometry of this node. Used by APIs such as `scrollTop`.
@@ -46,6 +48,25 @@ pub struct ContentBoxResponse(pub Option<Rect<Au>>);
 
 pub struct ContentBoxesResponse(pub Vec<Rect<Au>>);
 
+#[derive(Clone, Copy)]
+pub struct BoxSizesResponse {
+    /// The content box, relative to the padding box.
+    pub content_rect: Rect<Au>,
+    pub border_box: Size2D<Au>,
+    /// Whether the inline axis is vertical.
+    pub vertical: bool,
+}
+
+impl BoxSizesResponse {
+    pub fn empty() -> BoxSizesResponse {
+        BoxSizesResponse {
+            content_rect: Rect::zero(),
+            border_box: Size2D::zero(),
+            vertical: false,
+        }
+    }
+}
+
 pub struct NodeGeometryResponse {
     pub client_rect: Rect<i32>,
 }
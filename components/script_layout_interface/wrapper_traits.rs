@@ -272,6 +272,10 @@ pub trait ThreadSafeLayoutNode:
     /// If the insertion point is within this node, returns it. Otherwise, returns `None`.
     fn selection(&self) -> Option<Range<ByteIndex>>;
 
+    /// If an input method is composing text within this node, returns the range of that text.
+    /// Otherwise, returns `None`.
+    fn composition(&self) -> Option<Range<ByteIndex>>;
+
     /// If this is an image element, returns its URL. If this is not an image element, fails.
     fn image_url(&self) -> Option<ServoUrl>;
 
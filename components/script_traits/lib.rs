@@ -24,7 +24,7 @@ use bluetooth_traits::BluetoothRequest;
 use canvas_traits::webgl::WebGLPipeline;
 use crossbeam_channel::{Receiver, RecvTimeoutError, Sender};
 use devtools_traits::{DevtoolScriptControlMsg, ScriptToDevtoolsControlMsg, WorkerId};
-use embedder_traits::Cursor;
+use embedder_traits::{Cursor, SessionHistory};
 use euclid::{Length, Point2D, Rect, TypedScale, TypedSize2D, Vector2D};
 use gfx_traits::Epoch;
 use http::HeaderMap;
@@ -63,7 +63,7 @@ pub use crate::script_msg::{
     DOMMessage, SWManagerMsg, SWManagerSenders, ScopeThings, ServiceWorkerMsg,
 };
 pub use crate::script_msg::{
-    EventResult, IFrameSize, IFrameSizeMsg, LayoutMsg, LogEntry, ScriptMsg,
+    EventResult, IFrameSize, IFrameSizeMsg, LayoutMsg, LogEntry, PermissionGrant, ScriptMsg,
 };
 
 /// The address of a node. Layout sends these back. They must be validated via
@@ -106,8 +106,9 @@ pub enum LayoutControlMsg {
     GetCurrentEpoch(IpcSender<Epoch>),
     /// Asks layout to run another step in its animation.
     TickAnimations,
-    /// Tells layout about the new scrolling offsets of each scrollable stacking context.
-    SetScrollStates(Vec<ScrollState>),
+    /// Tells layout about the new scrolling offsets of each scrollable stacking context, and
+    /// whether the user has finished scrolling.
+    SetScrollStates(Vec<ScrollState>, bool),
     /// Requests the current load state of Web fonts. `true` is returned if fonts are still loading
     /// and `false` is returned if all fonts have loaded.
     GetWebFontLoadState(IpcSender<bool>),
@@ -345,6 +346,9 @@ pub enum ConstellationControlMsg {
         Option<String>,
         Option<String>,
     ),
+    /// Notifies the script thread that the stored permission of the origin of
+    /// a pipeline to use the named feature changed.
+    PermissionChanged(PipelineId, String, Option<PermissionGrant>),
     /// Report an error from a CSS parser for the given pipeline
     ReportCSSError(PipelineId, String, u32, u32, String),
     /// Reload the given page.
@@ -386,6 +390,7 @@ impl fmt::Debug for ConstellationControlMsg {
             WebFontLoaded(..) => "WebFontLoaded",
             DispatchIFrameLoadEvent { .. } => "DispatchIFrameLoadEvent",
             DispatchStorageEvent(..) => "DispatchStorageEvent",
+            PermissionChanged(..) => "PermissionChanged",
             ReportCSSError(..) => "ReportCSSError",
             Reload(..) => "Reload",
             WebVREvents(..) => "WebVREvents",
@@ -486,6 +491,38 @@ pub enum CompositorEvent {
     KeyboardEvent(KeyboardEvent),
     /// An event from the IME is dispatched.
     CompositionEvent(CompositionEvent),
+    /// The state of a gamepad changed.
+    GamepadEvent(GamepadEvent),
+}
+
+/// The index the embedder gave to a gamepad, which stays the same while
+/// the gamepad is connected.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, MallocSizeOf, PartialEq, Serialize)]
+pub struct GamepadIndex(pub usize);
+
+/// An event from a gamepad the embedder polls.
+///
+/// The buttons and the axes of the gamepad are reported with the indices of
+/// the standard gamepad layout.
+///
+/// <https://w3c.github.io/gamepad/#remapping>
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub enum GamepadEvent {
+    /// A gamepad with the given name got connected.
+    Connected(GamepadIndex, String),
+    /// A gamepad got disconnected.
+    Disconnected(GamepadIndex),
+    /// A button or an axis of a connected gamepad changed.
+    Updated(GamepadIndex, GamepadUpdateType),
+}
+
+/// A change of a gamepad input.
+#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+pub enum GamepadUpdateType {
+    /// The axis at the given index moved, the value is between -1 and 1.
+    Axis(usize, f64),
+    /// The button at the given index changed, the value is between 0 and 1.
+    Button(usize, f64),
 }
 
 /// Requests a TimerEvent-Message be sent after the given duration.
@@ -727,6 +764,12 @@ pub enum WebDriverCommandMsg {
     ),
     /// Take a screenshot of the window.
     TakeScreenshot(TopLevelBrowsingContextId, IpcSender<Option<Image>>),
+    /// Act as if a mouse button changed state at the given point of the
+    /// viewport, in CSS pixels.
+    MouseButtonAction(MouseEventType, MouseButton, f32, f32),
+    /// Act as if the mouse moved to the given point of the viewport, in CSS
+    /// pixels.
+    MouseMoveAction(f32, f32),
 }
 
 /// Messages to the constellation.
@@ -747,6 +790,10 @@ pub enum ConstellationMsg {
     IsReadyToSaveImage(HashMap<PipelineId, Epoch>),
     /// Inform the constellation of a key event.
     Keyboard(KeyboardEvent),
+    /// Inform the constellation of an input method composition event.
+    IMEComposition(CompositionEvent),
+    /// Inform the constellation of a gamepad event.
+    Gamepad(GamepadEvent),
     /// Whether to allow script to navigate.
     AllowNavigationResponse(PipelineId, bool),
     /// Request to load a page.
@@ -771,6 +818,8 @@ pub enum ConstellationMsg {
     WebVREvents(Vec<PipelineId>, Vec<WebVREvent>),
     /// Create a new top level browsing context.
     NewBrowser(ServoUrl, TopLevelBrowsingContextId),
+    /// Create a new top level browsing context with a saved session history.
+    RestoreBrowser(SessionHistory, TopLevelBrowsingContextId),
     /// Close a top level browsing context.
     CloseBrowser(TopLevelBrowsingContextId),
     /// Panic a top level browsing context.
@@ -799,6 +848,8 @@ impl fmt::Debug for ConstellationMsg {
             GetFocusTopLevelBrowsingContext(..) => "GetFocusTopLevelBrowsingContext",
             IsReadyToSaveImage(..) => "IsReadyToSaveImage",
             Keyboard(..) => "Keyboard",
+            IMEComposition(..) => "IMEComposition",
+            Gamepad(..) => "Gamepad",
             AllowNavigationResponse(..) => "AllowNavigationResponse",
             LoadUrl(..) => "LoadUrl",
             TraverseHistory(..) => "TraverseHistory",
@@ -809,6 +860,7 @@ impl fmt::Debug for ConstellationMsg {
             LogEntry(..) => "LogEntry",
             WebVREvents(..) => "WebVREvents",
             NewBrowser(..) => "NewBrowser",
+            RestoreBrowser(..) => "RestoreBrowser",
             CloseBrowser(..) => "CloseBrowser",
             SendError(..) => "SendError",
             SelectBrowser(..) => "SelectBrowser",
@@ -9,6 +9,7 @@ use crate::IFrameLoadInfo;
 use crate::IFrameLoadInfoWithData;
 use crate::LayoutControlMsg;
 use crate::LoadData;
+use crate::ScrollState;
 use crate::WindowSizeType;
 use crate::WorkerGlobalScopeInit;
 use crate::WorkerScriptLoadOrigin;
@@ -28,6 +29,7 @@ use servo_url::ServoUrl;
 use std::fmt;
 use style_traits::viewport::ViewportConstraints;
 use style_traits::CSSPixel;
+use webgpu::{wgt, WebGPUResponseResult};
 use webrender_api::{DeviceIntPoint, DeviceIntSize};
 
 /// A particular iframe's size, associated with a browsing context.
@@ -90,6 +92,13 @@ pub enum EventResult {
 /// A log entry reported to the constellation
 /// We don't report all log entries, just serious ones.
 /// We need a separate type for this because `LogLevel` isn't serializable.
+/// What the user answered when asked for the permission to use a feature.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum PermissionGrant {
+    Granted,
+    Denied,
+}
+
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub enum LogEntry {
     /// Panic, with a reason and backtrace
@@ -196,6 +205,8 @@ pub enum ScriptMsg {
     SetFinalUrl(ServoUrl),
     /// Script has handled a touch event, and either prevented or allowed default actions.
     TouchEventProcessed(EventResult),
+    /// Script has asked for a scroll frame to be scrolled smoothly to the given offset.
+    ScrollSmoothly(ScrollState),
     /// A log entry, with the top-level browsing context id and thread name
     LogEntry(Option<String>, LogEntry),
     /// Discard the document.
@@ -215,6 +226,14 @@ pub enum ScriptMsg {
     GetScreenSize(IpcSender<(DeviceIntSize)>),
     /// Get the available screen size (pixel)
     GetScreenAvailSize(IpcSender<(DeviceIntSize)>),
+    /// Requests a WebGPU adapter, starting the WebGPU thread if needed
+    RequestAdapter(IpcSender<WebGPUResponseResult>, wgt::PowerPreference),
+    /// Gets the permission stored for the origin of this pipeline to use the
+    /// named feature, if the user answered.
+    GetPermission(String, IpcSender<Option<PermissionGrant>>),
+    /// Stores the permission of the origin of this pipeline to use the named
+    /// feature, or forgets it, and tells the pipelines of the same origin.
+    SetPermission(String, Option<PermissionGrant>),
 }
 
 impl fmt::Debug for ScriptMsg {
@@ -250,6 +269,7 @@ impl fmt::Debug for ScriptMsg {
             SetDocumentState(..) => "SetDocumentState",
             SetFinalUrl(..) => "SetFinalUrl",
             TouchEventProcessed(..) => "TouchEventProcessed",
+            ScrollSmoothly(..) => "ScrollSmoothly",
             LogEntry(..) => "LogEntry",
             DiscardDocument => "DiscardDocument",
             DiscardTopLevelBrowsingContext => "DiscardTopLevelBrowsingContext",
@@ -259,6 +279,9 @@ impl fmt::Debug for ScriptMsg {
             GetClientWindow(..) => "GetClientWindow",
             GetScreenSize(..) => "GetScreenSize",
             GetScreenAvailSize(..) => "GetScreenAvailSize",
+            RequestAdapter(..) => "RequestAdapter",
+            GetPermission(..) => "GetPermission",
+            SetPermission(..) => "SetPermission",
         };
         write!(formatter, "ScriptMsg::{}", variant)
     }
@@ -80,7 +80,7 @@ use constellation::content_process_sandbox_profile;
 use constellation::{Constellation, InitialConstellationState, UnprivilegedPipelineContent};
 use constellation::{FromCompositorLogger, FromScriptLogger};
 use crossbeam_channel::{unbounded, Sender};
-use embedder_traits::{EmbedderMsg, EmbedderProxy, EmbedderReceiver, EventLoopWaker};
+use embedder_traits::{EmbedderClipboard, EmbedderMsg, EmbedderProxy, EmbedderReceiver, EventLoopWaker};
 use env_logger::Builder as EnvLoggerBuilder;
 #[cfg(all(
     not(target_os = "windows"),
@@ -199,6 +199,7 @@ where
         let time_profiler_chan = profile_time::Profiler::create(
             &opts.time_profiling,
             opts.time_profiler_trace_path.clone(),
+            opts.time_profiler_trace_format,
         );
         let mem_profiler_chan = profile_mem::Profiler::create(opts.mem_profiler_period);
         let debugger_chan = opts.debugger_port.map(|port| debugger::start_server(port));
@@ -286,6 +287,7 @@ where
             webrender_api_sender,
             window.gl(),
             webvr_services,
+            window.create_clipboard(),
         );
 
         // Send the constellation's swmanager sender to service worker manager thread
@@ -397,6 +399,23 @@ where
                 }
             },
 
+            WindowEvent::IMEComposition(composition_event) => {
+                let msg = ConstellationMsg::IMEComposition(composition_event);
+                if let Err(e) = self.constellation_chan.send(msg) {
+                    warn!(
+                        "Sending composition event to constellation failed ({:?}).",
+                        e
+                    );
+                }
+            },
+
+            WindowEvent::Gamepad(gamepad_event) => {
+                let msg = ConstellationMsg::Gamepad(gamepad_event);
+                if let Err(e) = self.constellation_chan.send(msg) {
+                    warn!("Sending gamepad event to constellation failed ({:?}).", e);
+                }
+            },
+
             WindowEvent::Quit => {
                 self.compositor.maybe_start_shutting_down();
             },
@@ -435,6 +454,10 @@ where
                 self.compositor.capture_webrender();
             },
 
+            WindowEvent::PrintToPDF(path) => {
+                self.compositor.print_to_pdf(&path);
+            },
+
             WindowEvent::NewBrowser(url, browser_id) => {
                 let msg = ConstellationMsg::NewBrowser(url, browser_id);
                 if let Err(e) = self.constellation_chan.send(msg) {
@@ -445,6 +468,16 @@ where
                 }
             },
 
+            WindowEvent::RestoreBrowser(session_history, browser_id) => {
+                let msg = ConstellationMsg::RestoreBrowser(session_history, browser_id);
+                if let Err(e) = self.constellation_chan.send(msg) {
+                    warn!(
+                        "Sending RestoreBrowser message to constellation failed ({:?}).",
+                        e
+                    );
+                }
+            },
+
             WindowEvent::SelectBrowser(ctx) => {
                 let msg = ConstellationMsg::SelectBrowser(ctx);
                 if let Err(e) = self.constellation_chan.send(msg) {
@@ -586,6 +619,7 @@ fn create_constellation(
     webrender_api_sender: webrender_api::RenderApiSender,
     window_gl: Rc<dyn gl::Gl>,
     webvr_services: Option<VRServiceManager>,
+    clipboard: Option<Box<dyn EmbedderClipboard>>,
 ) -> (Sender<ConstellationMsg>, SWManagerSenders) {
     let bluetooth_thread: IpcSender<BluetoothRequest> =
         BluetoothThreadFactory::new(embedder_proxy.clone());
@@ -662,6 +696,7 @@ fn create_constellation(
         webrender_api_sender,
         webgl_threads,
         webvr_chan,
+        clipboard,
     };
     let (constellation_chan, from_swmanager_sender) = Constellation::<
         script_layout_interface::message::Msg,
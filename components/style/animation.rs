@@ -359,50 +359,7 @@ impl PropertyAnimation {
     /// Update the given animation at a given point of progress.
     pub fn update(&self, style: &mut ComputedValues, time: f64) {
         let epsilon = 1. / (200. * (self.duration.seconds() as f64));
-        let progress = match self.timing_function {
-            GenericTimingFunction::CubicBezier { x1, y1, x2, y2 } => {
-                Bezier::new(x1, y1, x2, y2).solve(time, epsilon)
-            },
-            GenericTimingFunction::Steps(steps, pos) => {
-                let mut current_step = (time * (steps as f64)).floor() as i32;
-
-                if pos == StepPosition::Start ||
-                    pos == StepPosition::JumpStart ||
-                    pos == StepPosition::JumpBoth
-                {
-                    current_step = current_step + 1;
-                }
-
-                // FIXME: We should update current_step according to the "before flag".
-                // In order to get the before flag, we have to know the current animation phase
-                // and whether the iteration is reversed. For now, we skip this calculation.
-                // (i.e. Treat before_flag is unset,)
-                // https://drafts.csswg.org/css-easing/#step-timing-function-algo
-
-                if time >= 0.0 && current_step < 0 {
-                    current_step = 0;
-                }
-
-                let jumps = match pos {
-                    StepPosition::JumpBoth => steps + 1,
-                    StepPosition::JumpNone => steps - 1,
-                    StepPosition::JumpStart |
-                    StepPosition::JumpEnd |
-                    StepPosition::Start |
-                    StepPosition::End => steps,
-                };
-
-                if time <= 1.0 && current_step > jumps {
-                    current_step = jumps;
-                }
-
-                (current_step as f64) / (jumps as f64)
-            },
-            GenericTimingFunction::Keyword(keyword) => {
-                let (x1, x2, y1, y2) = keyword.to_bezier();
-                Bezier::new(x1, x2, y1, y2).solve(time, epsilon)
-            },
-        };
+        let progress = timing_function_output(&self.timing_function, time, epsilon);
 
         self.property.update(style, progress);
     }
@@ -419,6 +376,56 @@ impl PropertyAnimation {
     }
 }
 
+/// The output progress of `timing_function` at the input progress `time`
+///
+/// Bézier curves are solved to within `epsilon`.
+pub fn timing_function_output(timing_function: &TimingFunction, time: f64, epsilon: f64) -> f64 {
+    match *timing_function {
+        GenericTimingFunction::CubicBezier { x1, y1, x2, y2 } => {
+            Bezier::new(x1, y1, x2, y2).solve(time, epsilon)
+        },
+        GenericTimingFunction::Steps(steps, pos) => {
+            let mut current_step = (time * (steps as f64)).floor() as i32;
+
+            if pos == StepPosition::Start ||
+                pos == StepPosition::JumpStart ||
+                pos == StepPosition::JumpBoth
+            {
+                current_step = current_step + 1;
+            }
+
+            // FIXME: We should update current_step according to the "before flag".
+            // In order to get the before flag, we have to know the current animation phase
+            // and whether the iteration is reversed. For now, we skip this calculation.
+            // (i.e. Treat before_flag is unset,)
+            // https://drafts.csswg.org/css-easing/#step-timing-function-algo
+
+            if time >= 0.0 && current_step < 0 {
+                current_step = 0;
+            }
+
+            let jumps = match pos {
+                StepPosition::JumpBoth => steps + 1,
+                StepPosition::JumpNone => steps - 1,
+                StepPosition::JumpStart |
+                StepPosition::JumpEnd |
+                StepPosition::Start |
+                StepPosition::End => steps,
+            };
+
+            if time <= 1.0 && current_step > jumps {
+                current_step = jumps;
+            }
+
+            (current_step as f64) / (jumps as f64)
+        },
+        GenericTimingFunction::Keyword(keyword) => {
+            let (x1, x2, y1, y2) = keyword.to_bezier();
+            Bezier::new(x1, x2, y1, y2).solve(time, epsilon)
+        },
+    }
+}
+
 /// Inserts transitions into the queue of running animations as applicable for
 /// the given style difference. This is called from the layout worker threads.
 /// Returns true if any animations were kicked off and false otherwise.
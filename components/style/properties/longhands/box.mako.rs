@@ -412,7 +412,6 @@ ${helpers.predefined_type(
 ${helpers.single_keyword(
     "scroll-behavior",
     "auto smooth",
-    products="gecko",
     spec="https://drafts.csswg.org/cssom-view/#propdef-scroll-behavior",
     animation_value_type="discrete",
 )}
@@ -421,7 +420,7 @@ ${helpers.predefined_type(
     "scroll-snap-align",
     "ScrollSnapAlign",
     "computed::ScrollSnapAlign::none()",
-    products="gecko",
+    servo_pref="layout.scroll-snap.enabled",
     gecko_pref="layout.css.scroll-snap-v1.enabled",
     spec="https://drafts.csswg.org/css-scroll-snap-1/#scroll-snap-align",
     animation_value_type="discrete",
@@ -432,8 +431,8 @@ ${helpers.predefined_type(
         "scroll-snap-type-" + axis,
         "ScrollSnapType",
         "computed::ScrollSnapType::None",
-        products="gecko",
         needs_context=False,
+        servo_pref="layout.scroll-snap.enabled",
         gecko_pref="layout.css.scroll-snap.enabled",
         spec="Nonstandard (https://developer.mozilla.org/en-US/docs/Web/CSS/scroll-snap-type-x)",
         animation_value_type="discrete",
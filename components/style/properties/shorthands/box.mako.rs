@@ -303,32 +303,65 @@ macro_rules! try_parse_one {
     }
 </%helpers:shorthand>
 
-<%helpers:shorthand name="scroll-snap-type" products="gecko"
+<%helpers:shorthand name="scroll-snap-type"
+                    servo_pref="layout.scroll-snap.enabled"
                     gecko_pref="layout.css.scroll-snap.enabled"
                     sub_properties="scroll-snap-type-x scroll-snap-type-y"
                     spec="https://drafts.csswg.org/css-scroll-snap/#propdef-scroll-snap-type">
     use crate::properties::longhands::scroll_snap_type_x;
+    use crate::values::specified::ScrollSnapType;
 
     pub fn parse_value<'i, 't>(
         context: &ParserContext,
         input: &mut Parser<'i, 't>,
     ) -> Result<Longhands, ParseError<'i>> {
-        let result = scroll_snap_type_x::parse(context, input)?;
+        // The old single keyword syntax, which applies to both axes.
+        if let Ok(result) = input.try(|i| scroll_snap_type_x::parse(context, i)) {
+            return Ok(expanded! {
+                scroll_snap_type_x: result,
+                scroll_snap_type_y: result,
+            })
+        }
+
+        // [ x | y | block | inline | both ] [ mandatory | proximity ]?
+        //
+        // FIXME: block and inline should follow the writing mode of the scroll
+        // container, we assume horizontal-tb for now.
+        let (x, y) = try_match_ident_ignore_ascii_case! { input,
+            "x" | "inline" => (true, false),
+            "y" | "block" => (false, true),
+            "both" => (true, true),
+        };
+        let strictness = input
+            .try(|i| scroll_snap_type_x::parse(context, i))
+            .unwrap_or(ScrollSnapType::Proximity);
+        if strictness == ScrollSnapType::None {
+            return Err(input.new_custom_error(StyleParseErrorKind::UnspecifiedError));
+        }
+        let axis = |snaps: bool| if snaps { strictness } else { ScrollSnapType::None };
         Ok(expanded! {
-            scroll_snap_type_x: result,
-            scroll_snap_type_y: result,
+            scroll_snap_type_x: axis(x),
+            scroll_snap_type_y: axis(y),
         })
     }
 
     impl<'a> ToCss for LonghandsToSerialize<'a>  {
-        // Serializes into the single keyword value if both scroll-snap-type-x and scroll-snap-type-y are same.
-        // Otherwise into an empty string. This is done to match Gecko's behaviour.
+        // Serializes into the single keyword value if both scroll-snap-type-x and scroll-snap-type-y are same,
+        // to match Gecko's behaviour, and into the axis that snaps if only one of them does.
         fn to_css<W>(&self, dest: &mut CssWriter<W>) -> fmt::Result where W: fmt::Write {
             if self.scroll_snap_type_x == self.scroll_snap_type_y {
-                self.scroll_snap_type_x.to_css(dest)
-            } else {
-                Ok(())
+                return self.scroll_snap_type_x.to_css(dest);
+            }
+            let (axis, strictness) = match (*self.scroll_snap_type_x, *self.scroll_snap_type_y) {
+                (x, ScrollSnapType::None) => ("x", x),
+                (ScrollSnapType::None, y) => ("y", y),
+                _ => return Ok(()),
+            };
+            dest.write_str(axis)?;
+            if strictness == ScrollSnapType::Mandatory {
+                dest.write_str(" mandatory")?;
             }
+            Ok(())
         }
     }
 </%helpers:shorthand>
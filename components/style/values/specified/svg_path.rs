@@ -40,7 +40,7 @@ impl SVGPathData {
 
     /// Create a normalized copy of this path by converting each relative command to an absolute
     /// command.
-    fn normalize(&self) -> Self {
+    pub fn normalize(&self) -> Self {
         let mut state = PathTraversalState {
             subpath_start: CoordPair::new(0.0, 0.0),
             pos: CoordPair::new(0.0, 0.0),
@@ -52,6 +52,21 @@ impl SVGPathData {
             .collect::<Vec<_>>();
         SVGPathData(result.into_boxed_slice())
     }
+
+    /// Parse an unquoted SVG path string, the way the `d` attribute of SVG paths is.
+    pub fn parse_path_string(path_string: &str) -> Result<Self, ()> {
+        if path_string.is_empty() {
+            return Err(());
+        }
+
+        // Parse the svg path string as multiple sub-paths.
+        let mut path_parser = PathParser::new(path_string);
+        while skip_wsp(&mut path_parser.chars) {
+            path_parser.parse_subpath()?;
+        }
+
+        Ok(SVGPathData::new(path_parser.path.into_boxed_slice()))
+    }
 }
 
 impl ToCss for SVGPathData {
@@ -83,20 +98,9 @@ impl Parse for SVGPathData {
     ) -> Result<Self, ParseError<'i>> {
         let location = input.current_source_location();
         let path_string = input.expect_string()?.as_ref();
-        if path_string.is_empty() {
-            // Treat an empty string as invalid, so we will not set it.
-            return Err(location.new_custom_error(StyleParseErrorKind::UnspecifiedError));
-        }
-
-        // Parse the svg path string as multiple sub-paths.
-        let mut path_parser = PathParser::new(path_string);
-        while skip_wsp(&mut path_parser.chars) {
-            if path_parser.parse_subpath().is_err() {
-                return Err(location.new_custom_error(StyleParseErrorKind::UnspecifiedError));
-            }
-        }
-
-        Ok(SVGPathData::new(path_parser.path.into_boxed_slice()))
+        // An empty string is treated as invalid, so we will not set it.
+        SVGPathData::parse_path_string(path_string)
+            .map_err(|()| location.new_custom_error(StyleParseErrorKind::UnspecifiedError))
     }
 }
 
@@ -503,7 +507,7 @@ impl IsAbsolute {
     ToCss,
 )]
 #[repr(C)]
-pub struct CoordPair(CSSFloat, CSSFloat);
+pub struct CoordPair(pub CSSFloat, pub CSSFloat);
 
 impl CoordPair {
     /// Create a CoordPair.
@@ -516,7 +520,7 @@ impl CoordPair {
 /// The EllipticalArc flag type.
 #[derive(Clone, Copy, Debug, MallocSizeOf, PartialEq, SpecifiedValueInfo)]
 #[repr(C)]
-pub struct ArcFlag(bool);
+pub struct ArcFlag(pub bool);
 
 impl ToCss for ArcFlag {
     #[inline]
@@ -0,0 +1,469 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! The dispatching of the action sequences of the Perform Actions command,
+//! and the state of the input sources they act through.
+//!
+//! <https://w3c.github.io/webdriver/#actions>
+
+use crate::Handler;
+use ipc_channel::ipc;
+use keyboard_types::webdriver::{Event as WebDriverInputEvent, KeyInputState};
+use script_traits::webdriver_msg::WebDriverScriptCommand;
+use script_traits::{ConstellationMsg, MouseButton, MouseEventType, WebDriverCommandMsg};
+use std::cmp;
+use std::collections::HashSet;
+use std::thread;
+use std::time::{Duration, Instant};
+use webdriver::actions::{ActionSequence, ActionsType, GeneralAction, KeyAction};
+use webdriver::actions::{KeyActionItem, NullActionItem, PointerAction, PointerActionItem};
+use webdriver::actions::{PointerMoveAction, PointerOrigin, PointerType};
+use webdriver::error::{ErrorStatus, WebDriverError, WebDriverResult};
+
+/// The time between two moves of the pointer during a pointer move action
+/// with a duration, in milliseconds.
+const POINTER_MOVE_INTERVAL: u64 = 17;
+
+/// <https://w3c.github.io/webdriver/#dfn-input-source-state>
+pub enum InputSourceState {
+    Null,
+    Key(KeyInputState),
+    Pointer(PointerInputState),
+}
+
+/// <https://w3c.github.io/webdriver/#dfn-pointer-input-state>
+pub struct PointerInputState {
+    pressed: HashSet<u64>,
+    x: i64,
+    y: i64,
+}
+
+impl PointerInputState {
+    fn new() -> PointerInputState {
+        PointerInputState {
+            pressed: HashSet::new(),
+            x: 0,
+            y: 0,
+        }
+    }
+}
+
+/// An action that undoes a key or button press, run by the Release Actions
+/// command.
+///
+/// <https://w3c.github.io/webdriver/#dfn-input-cancel-list>
+pub enum CancelAction {
+    KeyUp(String, char),
+    PointerUp(String, u64),
+}
+
+/// The action of a single input source in a tick.
+enum TickAction<'a> {
+    Pause,
+    KeyDown(char),
+    KeyUp(char),
+    PointerDown(u64),
+    PointerUp(u64),
+    PointerMove(&'a PointerMoveAction),
+    PointerCancel,
+}
+
+impl Handler {
+    /// <https://w3c.github.io/webdriver/#dfn-dispatch-actions>
+    pub fn dispatch_actions(&mut self, sequences: &[ActionSequence]) -> WebDriverResult<()> {
+        // https://w3c.github.io/webdriver/#dfn-extract-an-action-sequence
+        let mut sources = Vec::with_capacity(sequences.len());
+        for sequence in sequences {
+            let id = match sequence.id {
+                Some(ref id) => id.clone(),
+                None => return Err(invalid_argument("Action sequence without an id")),
+            };
+            self.check_input_source(&id, &sequence.actions)?;
+            sources.push((id, &sequence.actions));
+        }
+
+        // https://w3c.github.io/webdriver/#dfn-dispatch-actions-inner
+        let ticks = sources
+            .iter()
+            .map(|&(_, actions)| tick_count(actions))
+            .max()
+            .unwrap_or(0);
+        for tick in 0..ticks {
+            let start = Instant::now();
+            let mut duration = 0;
+            for &(ref id, actions) in &sources {
+                let action = match tick_action(actions, tick)? {
+                    Some(action) => action,
+                    None => continue,
+                };
+                duration = cmp::max(duration, tick_action_duration(actions, tick));
+                self.dispatch_tick_action(id, action)?;
+            }
+            let duration = Duration::from_millis(duration);
+            let elapsed = start.elapsed();
+            if elapsed < duration {
+                thread::sleep(duration - elapsed);
+            }
+        }
+        Ok(())
+    }
+
+    /// Undoes the key and button presses of the previous actions and forgets
+    /// the state of the input sources.
+    ///
+    /// <https://w3c.github.io/webdriver/#release-actions>
+    pub fn release_actions(&mut self) -> WebDriverResult<()> {
+        // Step 2.
+        let cancel_list = {
+            let session = self.session_mut()?;
+            session
+                .input_cancel_list
+                .drain(..)
+                .rev()
+                .collect::<Vec<_>>()
+        };
+        for action in cancel_list {
+            match action {
+                CancelAction::KeyUp(id, key) => self.dispatch_key_up(&id, key)?,
+                CancelAction::PointerUp(id, button) => self.dispatch_pointer_up(&id, button)?,
+            }
+        }
+        // Step 3.
+        self.session_mut()?.input_state_table.clear();
+        Ok(())
+    }
+
+    /// Creates the state of the input source with the given id if it has
+    /// none, checking that it has the type of its actions otherwise.
+    ///
+    /// <https://w3c.github.io/webdriver/#dfn-process-an-input-source-action-sequence>
+    fn check_input_source(&mut self, id: &str, actions: &ActionsType) -> WebDriverResult<()> {
+        if let ActionsType::Pointer { ref parameters, .. } = *actions {
+            match parameters.pointer_type {
+                PointerType::Mouse => {},
+                _ => {
+                    return Err(WebDriverError::new(
+                        ErrorStatus::UnsupportedOperation,
+                        "Only mouse pointers are supported",
+                    ));
+                },
+            }
+        }
+        let session = self.session_mut()?;
+        let state = session
+            .input_state_table
+            .entry(id.to_owned())
+            .or_insert_with(|| match *actions {
+                ActionsType::Null { .. } => InputSourceState::Null,
+                ActionsType::Key { .. } => InputSourceState::Key(KeyInputState::new()),
+                ActionsType::Pointer { .. } => InputSourceState::Pointer(PointerInputState::new()),
+            });
+        match (&*state, actions) {
+            (&InputSourceState::Null, &ActionsType::Null { .. }) |
+            (&InputSourceState::Key(_), &ActionsType::Key { .. }) |
+            (&InputSourceState::Pointer(_), &ActionsType::Pointer { .. }) => Ok(()),
+            _ => Err(invalid_argument(
+                "Input source used with actions of another type",
+            )),
+        }
+    }
+
+    /// <https://w3c.github.io/webdriver/#dfn-dispatch-tick-actions>
+    fn dispatch_tick_action(&mut self, id: &str, action: TickAction) -> WebDriverResult<()> {
+        match action {
+            TickAction::Pause | TickAction::PointerCancel => Ok(()),
+            TickAction::KeyDown(key) => self.dispatch_key_down(id, key),
+            TickAction::KeyUp(key) => self.dispatch_key_up(id, key),
+            TickAction::PointerDown(button) => self.dispatch_pointer_down(id, button),
+            TickAction::PointerUp(button) => self.dispatch_pointer_up(id, button),
+            TickAction::PointerMove(action) => self.dispatch_pointer_move(id, action),
+        }
+    }
+
+    /// <https://w3c.github.io/webdriver/#dfn-dispatch-a-keydown-action>
+    fn dispatch_key_down(&mut self, id: &str, key: char) -> WebDriverResult<()> {
+        let session = self.session_mut()?;
+        let browsing_context_id = session.browsing_context_id;
+        let event = match session.input_state_table.get_mut(id) {
+            Some(&mut InputSourceState::Key(ref mut state)) => state.dispatch_keydown(key),
+            _ => return Ok(()),
+        };
+        session
+            .input_cancel_list
+            .push(CancelAction::KeyUp(id.to_owned(), key));
+        let cmd_msg = WebDriverCommandMsg::SendKeys(
+            browsing_context_id,
+            vec![WebDriverInputEvent::Keyboard(event)],
+        );
+        self.constellation_chan
+            .send(ConstellationMsg::WebDriverCommand(cmd_msg))
+            .unwrap();
+        Ok(())
+    }
+
+    /// <https://w3c.github.io/webdriver/#dfn-dispatch-a-keyup-action>
+    fn dispatch_key_up(&mut self, id: &str, key: char) -> WebDriverResult<()> {
+        let session = self.session_mut()?;
+        let browsing_context_id = session.browsing_context_id;
+        let event = match session.input_state_table.get_mut(id) {
+            Some(&mut InputSourceState::Key(ref mut state)) => state.dispatch_keyup(key),
+            _ => None,
+        };
+        // The key was not pressed.
+        let event = match event {
+            Some(event) => event,
+            None => return Ok(()),
+        };
+        let cmd_msg = WebDriverCommandMsg::SendKeys(
+            browsing_context_id,
+            vec![WebDriverInputEvent::Keyboard(event)],
+        );
+        self.constellation_chan
+            .send(ConstellationMsg::WebDriverCommand(cmd_msg))
+            .unwrap();
+        Ok(())
+    }
+
+    /// <https://w3c.github.io/webdriver/#dfn-dispatch-a-pointerdown-action>
+    fn dispatch_pointer_down(&mut self, id: &str, button: u64) -> WebDriverResult<()> {
+        let session = self.session_mut()?;
+        let (x, y) = match session.input_state_table.get_mut(id) {
+            Some(&mut InputSourceState::Pointer(ref mut state)) => {
+                // Step 2.
+                if !state.pressed.insert(button) {
+                    return Ok(());
+                }
+                (state.x, state.y)
+            },
+            _ => return Ok(()),
+        };
+        session
+            .input_cancel_list
+            .push(CancelAction::PointerUp(id.to_owned(), button));
+        self.send_mouse_button_event(MouseEventType::MouseDown, button, x, y);
+        Ok(())
+    }
+
+    /// <https://w3c.github.io/webdriver/#dfn-dispatch-a-pointerup-action>
+    fn dispatch_pointer_up(&mut self, id: &str, button: u64) -> WebDriverResult<()> {
+        let session = self.session_mut()?;
+        let (x, y) = match session.input_state_table.get_mut(id) {
+            Some(&mut InputSourceState::Pointer(ref mut state)) => {
+                // Step 2.
+                if !state.pressed.remove(&button) {
+                    return Ok(());
+                }
+                (state.x, state.y)
+            },
+            _ => return Ok(()),
+        };
+        self.send_mouse_button_event(MouseEventType::MouseUp, button, x, y);
+        // Like embedders do, follow the release of a button by a click, as
+        // script does not synthesize it.
+        self.send_mouse_button_event(MouseEventType::Click, button, x, y);
+        Ok(())
+    }
+
+    /// <https://w3c.github.io/webdriver/#dfn-dispatch-a-pointermove-action>
+    fn dispatch_pointer_move(
+        &mut self,
+        id: &str,
+        action: &PointerMoveAction,
+    ) -> WebDriverResult<()> {
+        let (start_x, start_y) = match self.session()?.input_state_table.get(id) {
+            Some(&InputSourceState::Pointer(ref state)) => (state.x, state.y),
+            _ => return Ok(()),
+        };
+        // Steps 1-7.
+        let (offset_x, offset_y) = (action.x.unwrap_or(0), action.y.unwrap_or(0));
+        let (x, y) = match action.origin {
+            PointerOrigin::Viewport => (offset_x, offset_y),
+            PointerOrigin::Pointer => (start_x + offset_x, start_y + offset_y),
+            PointerOrigin::Element(ref element) => {
+                let (center_x, center_y) = self.element_in_view_center_point(&element.id)?;
+                (center_x + offset_x, center_y + offset_y)
+            },
+        };
+        // Step 8.
+        let (width, height) = self.viewport_size()?;
+        if x < 0 || y < 0 || x > width || y > height {
+            return Err(WebDriverError::new(
+                ErrorStatus::MoveTargetOutOfBounds,
+                "The pointer would move out of the viewport",
+            ));
+        }
+        // Steps 9-11, moving the pointer in steps until the duration elapsed.
+        let duration = action.duration.unwrap_or(0);
+        let start = Instant::now();
+        loop {
+            let elapsed = start.elapsed();
+            let elapsed = elapsed.as_secs() * 1000 + u64::from(elapsed.subsec_millis());
+            let (current_x, current_y) =
+                pointer_move_position((start_x, start_y), (x, y), elapsed, duration);
+            let moved = match self.session_mut()?.input_state_table.get_mut(id) {
+                Some(&mut InputSourceState::Pointer(ref mut state)) => {
+                    let moved = (state.x, state.y) != (current_x, current_y);
+                    state.x = current_x;
+                    state.y = current_y;
+                    moved
+                },
+                _ => false,
+            };
+            if moved {
+                let cmd_msg =
+                    WebDriverCommandMsg::MouseMoveAction(current_x as f32, current_y as f32);
+                self.constellation_chan
+                    .send(ConstellationMsg::WebDriverCommand(cmd_msg))
+                    .unwrap();
+            }
+            if elapsed >= duration {
+                return Ok(());
+            }
+            thread::sleep(Duration::from_millis(POINTER_MOVE_INTERVAL));
+        }
+    }
+
+    fn send_mouse_button_event(&self, event_type: MouseEventType, button: u64, x: i64, y: i64) {
+        let button = match button {
+            0 => MouseButton::Left,
+            1 => MouseButton::Middle,
+            2 => MouseButton::Right,
+            // Script has no events for the other buttons.
+            _ => return,
+        };
+        let cmd_msg =
+            WebDriverCommandMsg::MouseButtonAction(event_type, button, x as f32, y as f32);
+        self.constellation_chan
+            .send(ConstellationMsg::WebDriverCommand(cmd_msg))
+            .unwrap();
+    }
+
+    /// <https://w3c.github.io/webdriver/#dfn-get-coordinates-relative-to-an-origin>
+    /// Steps for an element origin.
+    fn element_in_view_center_point(&self, element_id: &str) -> WebDriverResult<(i64, i64)> {
+        let (sender, receiver) = ipc::channel().unwrap();
+        let cmd =
+            WebDriverScriptCommand::GetElementInViewCenterPoint(element_id.to_owned(), sender);
+        self.browsing_context_script_command(cmd)?;
+        match receiver.recv().unwrap() {
+            Ok(Some(point)) => Ok(point),
+            Ok(None) => Err(WebDriverError::new(
+                ErrorStatus::MoveTargetOutOfBounds,
+                "The element is not in view",
+            )),
+            Err(_) => Err(WebDriverError::new(
+                ErrorStatus::StaleElementReference,
+                "Unable to find element in document",
+            )),
+        }
+    }
+
+    fn viewport_size(&self) -> WebDriverResult<(i64, i64)> {
+        let (sender, receiver) = ipc::channel().unwrap();
+        let top_level_browsing_context_id = self.session()?.top_level_browsing_context_id;
+        let cmd_msg = WebDriverCommandMsg::GetWindowSize(top_level_browsing_context_id, sender);
+        self.constellation_chan
+            .send(ConstellationMsg::WebDriverCommand(cmd_msg))
+            .unwrap();
+        let viewport = receiver.recv().unwrap().initial_viewport;
+        Ok((viewport.width as i64, viewport.height as i64))
+    }
+}
+
+fn invalid_argument(message: &'static str) -> WebDriverError {
+    WebDriverError::new(ErrorStatus::InvalidArgument, message)
+}
+
+pub fn tick_count(actions: &ActionsType) -> usize {
+    match *actions {
+        ActionsType::Null { ref actions } => actions.len(),
+        ActionsType::Key { ref actions } => actions.len(),
+        ActionsType::Pointer { ref actions, .. } => actions.len(),
+    }
+}
+
+/// The action of an input source in the given tick, if its sequence is
+/// that long.
+fn tick_action(actions: &ActionsType, tick: usize) -> WebDriverResult<Option<TickAction>> {
+    let action = match *actions {
+        ActionsType::Null { ref actions } => match actions.get(tick) {
+            Some(&NullActionItem::General(GeneralAction::Pause(_))) => TickAction::Pause,
+            None => return Ok(None),
+        },
+        ActionsType::Key { ref actions } => match actions.get(tick) {
+            Some(&KeyActionItem::General(GeneralAction::Pause(_))) => TickAction::Pause,
+            Some(&KeyActionItem::Key(KeyAction::Down(ref action))) => {
+                TickAction::KeyDown(single_key(&action.value)?)
+            },
+            Some(&KeyActionItem::Key(KeyAction::Up(ref action))) => {
+                TickAction::KeyUp(single_key(&action.value)?)
+            },
+            None => return Ok(None),
+        },
+        ActionsType::Pointer { ref actions, .. } => match actions.get(tick) {
+            Some(&PointerActionItem::General(GeneralAction::Pause(_))) => TickAction::Pause,
+            Some(&PointerActionItem::Pointer(PointerAction::Down(ref action))) => {
+                TickAction::PointerDown(action.button)
+            },
+            Some(&PointerActionItem::Pointer(PointerAction::Up(ref action))) => {
+                TickAction::PointerUp(action.button)
+            },
+            Some(&PointerActionItem::Pointer(PointerAction::Move(ref action))) => {
+                TickAction::PointerMove(action)
+            },
+            Some(&PointerActionItem::Pointer(PointerAction::Cancel)) => TickAction::PointerCancel,
+            None => return Ok(None),
+        },
+    };
+    Ok(Some(action))
+}
+
+/// <https://w3c.github.io/webdriver/#dfn-computing-the-tick-duration>
+/// The duration of the action of an input source in the given tick.
+pub fn tick_action_duration(actions: &ActionsType, tick: usize) -> u64 {
+    let duration = match *actions {
+        ActionsType::Null { ref actions } => match actions.get(tick) {
+            Some(&NullActionItem::General(GeneralAction::Pause(ref pause))) => pause.duration,
+            None => None,
+        },
+        ActionsType::Key { ref actions } => match actions.get(tick) {
+            Some(&KeyActionItem::General(GeneralAction::Pause(ref pause))) => pause.duration,
+            _ => None,
+        },
+        ActionsType::Pointer { ref actions, .. } => match actions.get(tick) {
+            Some(&PointerActionItem::General(GeneralAction::Pause(ref pause))) => pause.duration,
+            Some(&PointerActionItem::Pointer(PointerAction::Move(ref action))) => action.duration,
+            _ => None,
+        },
+    };
+    duration.unwrap_or(0)
+}
+
+/// Where a pointer moving from `start` to `target` in `duration`
+/// milliseconds is once `elapsed` milliseconds passed.
+pub fn pointer_move_position(
+    start: (i64, i64),
+    target: (i64, i64),
+    elapsed: u64,
+    duration: u64,
+) -> (i64, i64) {
+    if elapsed >= duration {
+        return target;
+    }
+    let ratio = elapsed as f64 / duration as f64;
+    (
+        start.0 + ((target.0 - start.0) as f64 * ratio) as i64,
+        start.1 + ((target.1 - start.1) as f64 * ratio) as i64,
+    )
+}
+
+/// The key a key action presses or releases, which must be a single code
+/// point.
+fn single_key(value: &str) -> WebDriverResult<char> {
+    let mut chars = value.chars();
+    match (chars.next(), chars.next()) {
+        (Some(key), None) => Ok(key),
+        _ => Err(invalid_argument("A key action must have a single key")),
+    }
+}
@@ -11,6 +11,9 @@ extern crate log;
 #[macro_use]
 extern crate serde;
 
+pub mod actions;
+
+use crate::actions::{CancelAction, InputSourceState};
 use base64;
 use crossbeam_channel::Sender;
 use euclid::TypedSize2D;
@@ -32,16 +35,17 @@ use serde_json::{self, Value};
 use servo_config::{prefs, prefs::PrefValue};
 use servo_url::ServoUrl;
 use std::borrow::ToOwned;
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fmt;
 use std::net::{SocketAddr, SocketAddrV4};
 use std::thread;
 use std::time::Duration;
 use uuid::Uuid;
 use webdriver::command::{
-    AddCookieParameters, GetParameters, JavascriptCommandParameters, LocatorParameters,
+    ActionsParameters, AddCookieParameters, GetParameters, JavascriptCommandParameters,
 };
-use webdriver::command::{SendKeysParameters, SwitchToFrameParameters, TimeoutsParameters};
+use webdriver::command::{LocatorParameters, SendKeysParameters};
+use webdriver::command::{SwitchToFrameParameters, TimeoutsParameters};
 use webdriver::command::{
     WebDriverCommand, WebDriverExtensionCommand, WebDriverMessage, WindowRectParameters,
 };
@@ -117,6 +121,13 @@ struct WebDriverSession {
     /// Time to wait for the element location strategy when retrieving elements, and when
     /// waiting for an element to become interactable.
     implicit_wait_timeout: u64,
+
+    /// The state of the input sources of the actions performed, by id.
+    input_state_table: HashMap<String, InputSourceState>,
+
+    /// The actions undoing the key and button presses of the actions
+    /// performed, run when the actions are released.
+    input_cancel_list: Vec<CancelAction>,
 }
 
 impl WebDriverSession {
@@ -132,6 +143,9 @@ impl WebDriverSession {
             script_timeout: 30_000,
             load_timeout: 300_000,
             implicit_wait_timeout: 0,
+
+            input_state_table: HashMap::new(),
+            input_cancel_list: Vec::new(),
         }
     }
 }
@@ -1089,6 +1103,21 @@ impl Handler {
         Ok(WebDriverResponse::Void)
     }
 
+    // https://w3c.github.io/webdriver/#perform-actions
+    fn handle_perform_actions(
+        &mut self,
+        parameters: &ActionsParameters,
+    ) -> WebDriverResult<WebDriverResponse> {
+        self.dispatch_actions(&parameters.actions)?;
+        Ok(WebDriverResponse::Void)
+    }
+
+    // https://w3c.github.io/webdriver/#release-actions
+    fn handle_release_actions(&mut self) -> WebDriverResult<WebDriverResponse> {
+        self.release_actions()?;
+        Ok(WebDriverResponse::Void)
+    }
+
     fn handle_take_screenshot(&self) -> WebDriverResult<WebDriverResponse> {
         let mut img = None;
         let top_level_id = self.session()?.top_level_browsing_context_id;
@@ -1261,6 +1290,8 @@ impl WebDriverHandler<ServoExtensionRoute> for Handler {
             WebDriverCommand::ElementSendKeys(ref element, ref keys) => {
                 self.handle_element_send_keys(element, keys)
             },
+            WebDriverCommand::PerformActions(ref x) => self.handle_perform_actions(x),
+            WebDriverCommand::ReleaseActions => self.handle_release_actions(),
             WebDriverCommand::SetTimeouts(ref x) => self.handle_set_timeouts(x),
             WebDriverCommand::TakeScreenshot => self.handle_take_screenshot(),
             WebDriverCommand::Extension(ref extension) => match *extension {
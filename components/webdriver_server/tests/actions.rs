@@ -0,0 +1,55 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use webdriver::actions::ActionSequence;
+use webdriver_server::actions::{pointer_move_position, tick_action_duration, tick_count};
+
+fn sequence(json: &str) -> ActionSequence {
+    serde_json::from_str(json).unwrap()
+}
+
+fn max_tick_duration(sequences: &[ActionSequence], tick: usize) -> u64 {
+    sequences
+        .iter()
+        .map(|sequence| tick_action_duration(&sequence.actions, tick))
+        .max()
+        .unwrap_or(0)
+}
+
+#[test]
+fn test_ticks_last_as_long_as_their_longest_action() {
+    let sequences = vec![
+        sequence(
+            r#"{"type": "key", "id": "keyboard", "actions": [
+                {"type": "keyDown", "value": "a"},
+                {"type": "pause", "duration": 50},
+                {"type": "keyUp", "value": "a"}
+            ]}"#,
+        ),
+        sequence(
+            r#"{"type": "pointer", "id": "mouse", "parameters": {"pointerType": "mouse"},
+                "actions": [
+                {"type": "pointerMove", "duration": 100, "x": 10, "y": 20},
+                {"type": "pause", "duration": 20}
+            ]}"#,
+        ),
+    ];
+    assert_eq!(tick_count(&sequences[0].actions), 3);
+    assert_eq!(tick_count(&sequences[1].actions), 2);
+    assert_eq!(max_tick_duration(&sequences, 0), 100);
+    assert_eq!(max_tick_duration(&sequences, 1), 50);
+    // Key actions take no time, and the pointer has no action left.
+    assert_eq!(max_tick_duration(&sequences, 2), 0);
+}
+
+#[test]
+fn test_pointer_moves_in_steps_until_the_duration_elapsed() {
+    let (start, target) = ((10, 100), (110, 0));
+    assert_eq!(pointer_move_position(start, target, 0, 100), start);
+    assert_eq!(pointer_move_position(start, target, 25, 100), (35, 75));
+    assert_eq!(pointer_move_position(start, target, 100, 100), target);
+    assert_eq!(pointer_move_position(start, target, 150, 100), target);
+    // A move without a duration goes straight to its target.
+    assert_eq!(pointer_move_position(start, target, 0, 0), target);
+}
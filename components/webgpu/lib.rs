@@ -0,0 +1,482 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+#[macro_use]
+extern crate bitflags;
+#[macro_use]
+extern crate log;
+#[macro_use]
+extern crate serde;
+pub extern crate wgpu_native as wgpu;
+
+mod wgpu_thread;
+pub mod wgt;
+
+use crate::wgpu_thread::WGPU;
+use ipc_channel::ipc::{self, IpcSender, IpcSharedMemory};
+use malloc_size_of::{MallocSizeOf, MallocSizeOfOps};
+use std::thread;
+use webrender_api::ImageKey;
+use wgpu::{
+    AdapterId, BindGroupId, BindGroupLayoutId, BufferId, CommandBufferId, CommandEncoderId,
+    ComputePipelineId, DeviceId, PipelineLayoutId, QueueId, RenderPipelineId, SamplerId,
+    ShaderModuleId, TextureId, TextureViewId,
+};
+
+/// A channel to the WebGPU thread, which owns the adapters and devices
+/// requested by content and everything created with them.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct WebGPU(pub IpcSender<WebGPURequest>);
+
+impl WebGPU {
+    /// Starts a new WebGPU thread, which presents to canvases through the
+    /// given WebRender API, and returns a channel to it.
+    pub fn new(webrender_api_sender: webrender_api::RenderApiSender) -> Option<WebGPU> {
+        let (sender, receiver) = match ipc::channel() {
+            Ok(channel) => channel,
+            Err(e) => {
+                warn!("Failed to create a channel to the WebGPU thread ({})", e);
+                return None;
+            },
+        };
+        let thread_sender = sender.clone();
+        let result = thread::Builder::new()
+            .name("WebGPUThread".to_owned())
+            .spawn(move || {
+                WGPU::new(receiver, thread_sender, webrender_api_sender).run();
+            });
+        if let Err(e) = result {
+            warn!("Failed to spawn the WebGPU thread ({})", e);
+            return None;
+        }
+        Some(WebGPU(sender))
+    }
+
+    /// Sends an exit message to the WebGPU thread, which releases every
+    /// device it owns.
+    pub fn exit(&self) -> Result<(), &'static str> {
+        self.0
+            .send(WebGPURequest::Exit)
+            .map_err(|_| "Failed to send Exit message")
+    }
+}
+
+/// How the memory of a buffer gets mapped to the content.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum HostMap {
+    Read,
+    Write,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct BufferDescriptor {
+    pub size: u64,
+    pub usage: wgt::BufferUsage,
+    pub mapped_at_creation: bool,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct TextureDescriptor {
+    /// The depth of a 2D texture is its number of array layers.
+    pub size: wgt::Extent3d,
+    pub mip_level_count: u32,
+    pub sample_count: u32,
+    pub dimension: wgt::TextureDimension,
+    pub format: wgt::TextureFormat,
+    pub usage: wgt::TextureUsage,
+}
+
+/// A texture view descriptor whose defaults were resolved from the
+/// texture it is created from.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct TextureViewDescriptor {
+    pub format: wgt::TextureFormat,
+    pub dimension: wgt::TextureViewDimension,
+    pub aspect: wgt::TextureAspect,
+    pub base_mip_level: u32,
+    pub level_count: u32,
+    pub base_array_layer: u32,
+    pub array_layer_count: u32,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct SamplerDescriptor {
+    pub address_mode_u: wgt::AddressMode,
+    pub address_mode_v: wgt::AddressMode,
+    pub address_mode_w: wgt::AddressMode,
+    pub mag_filter: wgt::FilterMode,
+    pub min_filter: wgt::FilterMode,
+    pub mipmap_filter: wgt::FilterMode,
+    pub lod_min_clamp: f32,
+    pub lod_max_clamp: f32,
+    /// Only set for comparison samplers.
+    pub compare: Option<wgt::CompareFunction>,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum BindingType {
+    UniformBuffer,
+    StorageBuffer,
+    ReadonlyStorageBuffer,
+    Sampler,
+    ComparisonSampler,
+    SampledTexture,
+    ReadonlyStorageTexture,
+    WriteonlyStorageTexture,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct BindGroupLayoutEntry {
+    pub binding: u32,
+    pub visibility: wgt::ShaderStage,
+    pub ty: BindingType,
+    pub has_dynamic_offset: bool,
+    pub multisampled: bool,
+    pub view_dimension: wgt::TextureViewDimension,
+    pub texture_component_type: wgt::TextureComponentType,
+    pub storage_texture_format: wgt::TextureFormat,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub enum BindingResource {
+    Buffer {
+        buffer_id: BufferId,
+        offset: u64,
+        size: u64,
+    },
+    Sampler(SamplerId),
+    TextureView(TextureViewId),
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct BindGroupEntry {
+    pub binding: u32,
+    pub resource: BindingResource,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ProgrammableStage {
+    pub module: ShaderModuleId,
+    pub entry_point: String,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct VertexBufferLayoutDescriptor {
+    pub array_stride: u64,
+    pub step_mode: wgt::InputStepMode,
+    pub attributes: Vec<wgt::VertexAttributeDescriptor>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct VertexStateDescriptor {
+    pub index_format: wgt::IndexFormat,
+    pub vertex_buffers: Vec<VertexBufferLayoutDescriptor>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct RenderPipelineDescriptor {
+    pub layout: PipelineLayoutId,
+    pub vertex_stage: ProgrammableStage,
+    pub fragment_stage: Option<ProgrammableStage>,
+    pub primitive_topology: wgt::PrimitiveTopology,
+    pub rasterization_state: wgt::RasterizationStateDescriptor,
+    pub color_states: Vec<wgt::ColorStateDescriptor>,
+    pub depth_stencil_state: Option<wgt::DepthStencilStateDescriptor>,
+    pub vertex_state: VertexStateDescriptor,
+    pub sample_count: u32,
+    pub sample_mask: u32,
+    pub alpha_to_coverage_enabled: bool,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct BufferCopyView {
+    pub buffer: BufferId,
+    pub offset: u64,
+    pub bytes_per_row: u32,
+    pub rows_per_image: u32,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct TextureCopyView {
+    pub texture: TextureId,
+    pub mip_level: u32,
+    /// The z coordinate of the origin of a 2D texture is its array layer.
+    pub origin: wgt::Origin3d,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct RenderPassColorAttachment {
+    pub attachment: TextureViewId,
+    pub resolve_target: Option<TextureViewId>,
+    pub load_op: wgt::LoadOp,
+    pub store_op: wgt::StoreOp,
+    pub clear_color: wgt::Color,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct RenderPassDepthStencilAttachment {
+    pub attachment: TextureViewId,
+    pub depth_load_op: wgt::LoadOp,
+    pub depth_store_op: wgt::StoreOp,
+    pub clear_depth: f32,
+    pub stencil_load_op: wgt::LoadOp,
+    pub stencil_store_op: wgt::StoreOp,
+    pub clear_stencil: u32,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct RenderPassDescriptor {
+    pub color_attachments: Vec<RenderPassColorAttachment>,
+    pub depth_stencil_attachment: Option<RenderPassDepthStencilAttachment>,
+}
+
+/// A command recorded by a compute pass encoder, the WebGPU thread runs
+/// them when the pass ends.
+#[derive(Debug, Deserialize, Serialize)]
+pub enum ComputePassCommand {
+    SetPipeline(ComputePipelineId),
+    SetBindGroup {
+        index: u32,
+        bind_group_id: BindGroupId,
+        offsets: Vec<u32>,
+    },
+    Dispatch(u32, u32, u32),
+}
+
+/// A command recorded by a render pass encoder, the WebGPU thread runs
+/// them when the pass ends.
+#[derive(Debug, Deserialize, Serialize)]
+pub enum RenderPassCommand {
+    SetPipeline(RenderPipelineId),
+    SetBindGroup {
+        index: u32,
+        bind_group_id: BindGroupId,
+        offsets: Vec<u32>,
+    },
+    SetVertexBuffer {
+        slot: u32,
+        buffer_id: BufferId,
+        offset: u64,
+        size: u64,
+    },
+    SetIndexBuffer {
+        buffer_id: BufferId,
+        offset: u64,
+        size: u64,
+    },
+    SetViewport {
+        x: f32,
+        y: f32,
+        width: f32,
+        height: f32,
+        min_depth: f32,
+        max_depth: f32,
+    },
+    SetScissorRect {
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    },
+    SetBlendColor(wgt::Color),
+    SetStencilReference(u32),
+    Draw {
+        vertex_count: u32,
+        instance_count: u32,
+        first_vertex: u32,
+        first_instance: u32,
+    },
+    DrawIndexed {
+        index_count: u32,
+        instance_count: u32,
+        first_index: u32,
+        base_vertex: i32,
+        first_instance: u32,
+    },
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub enum WebGPUResponse {
+    RequestAdapter {
+        adapter_name: String,
+        adapter_id: WebGPUAdapter,
+        channel: WebGPU,
+    },
+    RequestDevice {
+        device_id: WebGPUDevice,
+        queue_id: WebGPUQueue,
+        limits: wgt::Limits,
+    },
+    /// The contents of the mapped range of a buffer.
+    BufferMapAsync(IpcSharedMemory),
+}
+
+pub type WebGPUResponseResult = Result<WebGPUResponse, String>;
+
+#[derive(Debug, Deserialize, Serialize)]
+pub enum WebGPURequest {
+    BufferMapAsync {
+        sender: IpcSender<WebGPUResponseResult>,
+        buffer_id: BufferId,
+        device_id: DeviceId,
+        host_map: HostMap,
+        offset: u64,
+        size: u64,
+    },
+    CommandEncoderFinish {
+        sender: IpcSender<WebGPUCommandBuffer>,
+        command_encoder_id: CommandEncoderId,
+    },
+    CopyBufferToBuffer {
+        command_encoder_id: CommandEncoderId,
+        source_id: BufferId,
+        source_offset: u64,
+        destination_id: BufferId,
+        destination_offset: u64,
+        size: u64,
+    },
+    CopyBufferToTexture {
+        command_encoder_id: CommandEncoderId,
+        source: BufferCopyView,
+        destination: TextureCopyView,
+        copy_size: wgt::Extent3d,
+    },
+    CopyTextureToBuffer {
+        command_encoder_id: CommandEncoderId,
+        source: TextureCopyView,
+        destination: BufferCopyView,
+        copy_size: wgt::Extent3d,
+    },
+    CreateBindGroup {
+        sender: IpcSender<WebGPUBindGroup>,
+        device_id: DeviceId,
+        layout_id: BindGroupLayoutId,
+        entries: Vec<BindGroupEntry>,
+    },
+    CreateBindGroupLayout {
+        sender: IpcSender<WebGPUBindGroupLayout>,
+        device_id: DeviceId,
+        entries: Vec<BindGroupLayoutEntry>,
+    },
+    CreateBuffer {
+        sender: IpcSender<WebGPUBuffer>,
+        device_id: DeviceId,
+        descriptor: BufferDescriptor,
+    },
+    CreateCommandEncoder {
+        sender: IpcSender<WebGPUCommandEncoder>,
+        device_id: DeviceId,
+    },
+    CreateComputePipeline {
+        sender: IpcSender<WebGPUComputePipeline>,
+        device_id: DeviceId,
+        layout_id: PipelineLayoutId,
+        compute_stage: ProgrammableStage,
+    },
+    CreatePipelineLayout {
+        sender: IpcSender<WebGPUPipelineLayout>,
+        device_id: DeviceId,
+        bind_group_layouts: Vec<BindGroupLayoutId>,
+    },
+    CreateRenderPipeline {
+        sender: IpcSender<WebGPURenderPipeline>,
+        device_id: DeviceId,
+        descriptor: RenderPipelineDescriptor,
+    },
+    CreateSampler {
+        sender: IpcSender<WebGPUSampler>,
+        device_id: DeviceId,
+        descriptor: SamplerDescriptor,
+    },
+    CreateShaderModule {
+        sender: IpcSender<WebGPUShaderModule>,
+        device_id: DeviceId,
+        code: Vec<u32>,
+    },
+    /// Creates the buffer the frames of a swap chain get read back to, and
+    /// the image they get presented to.
+    CreateSwapChain {
+        sender: IpcSender<ImageKey>,
+        device_id: DeviceId,
+        queue_id: QueueId,
+        width: u32,
+        height: u32,
+    },
+    CreateTexture {
+        sender: IpcSender<WebGPUTexture>,
+        device_id: DeviceId,
+        descriptor: TextureDescriptor,
+    },
+    CreateTextureView {
+        sender: IpcSender<WebGPUTextureView>,
+        texture_id: TextureId,
+        descriptor: TextureViewDescriptor,
+    },
+    DestroyBuffer(BufferId),
+    DestroySwapChain(ImageKey),
+    DestroyTexture(TextureId),
+    Exit,
+    RequestAdapter {
+        sender: IpcSender<WebGPUResponseResult>,
+        power_preference: wgt::PowerPreference,
+    },
+    RequestDevice {
+        sender: IpcSender<WebGPUResponseResult>,
+        adapter_id: WebGPUAdapter,
+        limits: wgt::Limits,
+    },
+    RunComputePass {
+        command_encoder_id: CommandEncoderId,
+        commands: Vec<ComputePassCommand>,
+    },
+    RunRenderPass {
+        command_encoder_id: CommandEncoderId,
+        descriptor: RenderPassDescriptor,
+        commands: Vec<RenderPassCommand>,
+    },
+    Submit {
+        queue_id: QueueId,
+        command_buffers: Vec<CommandBufferId>,
+    },
+    /// Presents the contents of a texture to the image of a swap chain.
+    SwapChainPresent {
+        image_key: ImageKey,
+        texture_id: TextureId,
+    },
+    /// Unmaps a buffer, after writing `data` at the given offset for the
+    /// mappings that were writable.
+    UnmapBuffer {
+        buffer_id: BufferId,
+        data: Option<(u64, IpcSharedMemory)>,
+    },
+}
+
+macro_rules! webgpu_resource {
+    ($name:ident, $id:ty) => {
+        #[derive(Clone, Copy, Debug, Deserialize, Hash, PartialEq, Serialize)]
+        pub struct $name(pub $id);
+
+        impl MallocSizeOf for $name {
+            fn size_of(&self, _ops: &mut MallocSizeOfOps) -> usize {
+                0
+            }
+        }
+    };
+}
+
+webgpu_resource!(WebGPUAdapter, AdapterId);
+webgpu_resource!(WebGPUBindGroup, BindGroupId);
+webgpu_resource!(WebGPUBindGroupLayout, BindGroupLayoutId);
+webgpu_resource!(WebGPUBuffer, BufferId);
+webgpu_resource!(WebGPUCommandBuffer, CommandBufferId);
+webgpu_resource!(WebGPUCommandEncoder, CommandEncoderId);
+webgpu_resource!(WebGPUComputePipeline, ComputePipelineId);
+webgpu_resource!(WebGPUDevice, DeviceId);
+webgpu_resource!(WebGPUPipelineLayout, PipelineLayoutId);
+webgpu_resource!(WebGPUQueue, QueueId);
+webgpu_resource!(WebGPURenderPipeline, RenderPipelineId);
+webgpu_resource!(WebGPUSampler, SamplerId);
+webgpu_resource!(WebGPUShaderModule, ShaderModuleId);
+webgpu_resource!(WebGPUTexture, TextureId);
+webgpu_resource!(WebGPUTextureView, TextureViewId);
@@ -0,0 +1,1332 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::wgt;
+use crate::{
+    BindGroupEntry, BindGroupLayoutEntry, BindingResource, BindingType, BufferCopyView,
+    ComputePassCommand, HostMap, ProgrammableStage, RenderPassCommand, RenderPassDescriptor,
+    TextureCopyView, WebGPU, WebGPUAdapter, WebGPUBindGroup, WebGPUBindGroupLayout, WebGPUBuffer,
+    WebGPUCommandBuffer, WebGPUCommandEncoder, WebGPUComputePipeline, WebGPUDevice,
+    WebGPUPipelineLayout, WebGPUQueue, WebGPURenderPipeline, WebGPURequest, WebGPUResponse,
+    WebGPUSampler, WebGPUShaderModule, WebGPUTexture, WebGPUTextureView,
+};
+use gfx_hal::Instance as _;
+use ipc_channel::ipc::{IpcReceiver, IpcSender, IpcSharedMemory};
+use std::collections::HashMap;
+use std::ffi::CString;
+use std::ptr;
+use std::slice;
+use std::thread;
+use webrender_api::{DeviceIntSize, RenderApi, RenderApiSender, Transaction};
+use webrender_api::{DirtyRect, ImageData, ImageDescriptor, ImageFormat, ImageKey};
+use wgpu::{BufferId, BufferMapAsyncStatus, DeviceId, InstanceId, QueueId};
+use wgpu::{ComputePassId, RenderPassId, ShaderModuleId, TextureId};
+
+/// The rows of the buffers textures get copied to must be aligned to this.
+const BYTES_PER_ROW_ALIGNMENT: u32 = 256;
+
+/// A fragment shader that writes no output, wgpu needs one for the render
+/// pipelines that have no fragment stage.
+#[rustfmt::skip]
+const EMPTY_FRAGMENT_SHADER: [u32; 32] = [
+    // Magic number, version 1.0, generator, bound of the ids, schema.
+    0x0723_0203, 0x0001_0000, 0, 5, 0,
+    // OpCapability Shader
+    0x0002_0011, 1,
+    // OpMemoryModel Logical GLSL450
+    0x0003_000e, 0, 1,
+    // OpEntryPoint Fragment %1 "main"
+    0x0005_000f, 4, 1, 0x6e69_616d, 0,
+    // OpExecutionMode %1 OriginUpperLeft
+    0x0003_0010, 1, 7,
+    // %2 = OpTypeVoid
+    0x0002_0013, 2,
+    // %3 = OpTypeFunction %2
+    0x0003_0021, 3, 2,
+    // %1 = OpFunction %2 None %3
+    0x0005_0036, 2, 1, 0, 3,
+    // %4 = OpLabel
+    0x0002_00f8, 4,
+    // OpReturn
+    0x0001_00fd,
+    // OpFunctionEnd
+    0x0001_0038,
+];
+
+/// The memory a buffer is currently mapped to.
+struct Mapping {
+    pointer: *mut u8,
+    /// The offset in the buffer of the start of the mapping.
+    offset: u64,
+}
+
+/// A swap chain presenting the frames of a GPUCanvasContext.
+struct SwapChain {
+    queue_id: QueueId,
+    /// The buffer frames get read back to, its rows are aligned to
+    /// `BYTES_PER_ROW_ALIGNMENT`.
+    buffer_id: BufferId,
+    descriptor: ImageDescriptor,
+}
+
+/// The state of a buffer mapping, filled by the mapping callbacks.
+struct MapResult {
+    status: Option<BufferMapAsyncStatus>,
+    data: *mut u8,
+}
+
+#[allow(unsafe_code)]
+extern "C" fn map_read_callback(status: BufferMapAsyncStatus, data: *const u8, userdata: *mut u8) {
+    let result = unsafe { &mut *(userdata as *mut MapResult) };
+    result.status = Some(status);
+    result.data = data as *mut u8;
+}
+
+#[allow(unsafe_code)]
+extern "C" fn map_write_callback(status: BufferMapAsyncStatus, data: *mut u8, userdata: *mut u8) {
+    let result = unsafe { &mut *(userdata as *mut MapResult) };
+    result.status = Some(status);
+    result.data = data;
+}
+
+/// The WebGPU thread, it owns the wgpu instance every adapter and device
+/// comes from, and runs the requests of the content on it.
+pub(crate) struct WGPU {
+    receiver: IpcReceiver<WebGPURequest>,
+    /// A channel to this thread, handed to the content with the adapters.
+    sender: IpcSender<WebGPURequest>,
+    /// Only set when there is an adapter to get from it, wgpu panics when
+    /// it finds none.
+    instance_id: Option<InstanceId>,
+    /// The buffers currently mapped, either at their creation or by the
+    /// content. The ids of wgpu cannot be hashed as keys of a map.
+    mappings: Vec<(BufferId, Mapping)>,
+    /// The fragment shaders render pipelines without a fragment stage get,
+    /// one per device.
+    empty_fragment_shaders: Vec<(DeviceId, ShaderModuleId)>,
+    /// Channel used to add and update the images swap chains present to.
+    webrender_api: RenderApi,
+    swap_chains: HashMap<ImageKey, SwapChain>,
+}
+
+impl WGPU {
+    pub fn new(
+        receiver: IpcReceiver<WebGPURequest>,
+        sender: IpcSender<WebGPURequest>,
+        webrender_api_sender: RenderApiSender,
+    ) -> WGPU {
+        let instance_id = if wgpu::create_instance().enumerate_adapters().is_empty() {
+            warn!("No adapter is available to WebGPU");
+            None
+        } else {
+            Some(wgpu::wgpu_create_instance())
+        };
+        WGPU {
+            receiver,
+            sender,
+            instance_id,
+            mappings: Vec::new(),
+            empty_fragment_shaders: Vec::new(),
+            webrender_api: webrender_api_sender.create_api(),
+            swap_chains: HashMap::new(),
+        }
+    }
+
+    pub fn run(mut self) {
+        loop {
+            let msg = match self.receiver.recv() {
+                Ok(msg) => msg,
+                Err(e) => return warn!("Failed to receive a WebGPU request ({:?})", e),
+            };
+            if self.handle_msg(msg) {
+                return;
+            }
+        }
+    }
+
+    /// Handles a request of the content, returns whether the thread
+    /// should exit.
+    #[allow(unsafe_code)]
+    fn handle_msg(&mut self, msg: WebGPURequest) -> bool {
+        trace!("processing {:?}", msg);
+        match msg {
+            WebGPURequest::BufferMapAsync {
+                sender,
+                buffer_id,
+                device_id,
+                host_map,
+                offset,
+                size,
+            } => {
+                // The queue of a device has the same id as the device itself.
+                let result = match self.map_buffer(device_id, buffer_id, host_map, offset, size) {
+                    Some(pointer) => {
+                        let data = unsafe { slice::from_raw_parts(pointer, size as usize) };
+                        let data = IpcSharedMemory::from_bytes(data);
+                        self.mappings.push((buffer_id, Mapping { pointer, offset }));
+                        Ok(WebGPUResponse::BufferMapAsync(data))
+                    },
+                    None => Err("Failed to map the buffer".to_owned()),
+                };
+                if let Err(e) = sender.send(result) {
+                    warn!(
+                        "Failed to send the mapping of buffer {:?} ({})",
+                        buffer_id, e
+                    );
+                }
+            },
+            WebGPURequest::CommandEncoderFinish {
+                sender,
+                command_encoder_id,
+            } => {
+                let command_buffer_id = wgpu::wgpu_command_encoder_finish(command_encoder_id);
+                if let Err(e) = sender.send(WebGPUCommandBuffer(command_buffer_id)) {
+                    warn!("Failed to send the command buffer ({})", e);
+                }
+            },
+            WebGPURequest::CopyBufferToBuffer {
+                command_encoder_id,
+                source_id,
+                source_offset,
+                destination_id,
+                destination_offset,
+                size,
+            } => {
+                wgpu::wgpu_command_buffer_copy_buffer_to_buffer(
+                    command_encoder_id,
+                    source_id,
+                    source_offset as u32,
+                    destination_id,
+                    destination_offset as u32,
+                    size as u32,
+                );
+            },
+            WebGPURequest::CopyBufferToTexture {
+                command_encoder_id,
+                source,
+                destination,
+                copy_size,
+            } => {
+                wgpu::wgpu_command_buffer_copy_buffer_to_texture(
+                    command_encoder_id,
+                    &buffer_copy_view(&source),
+                    &texture_copy_view(&destination),
+                    extent(copy_size),
+                );
+            },
+            WebGPURequest::CopyTextureToBuffer {
+                command_encoder_id,
+                source,
+                destination,
+                copy_size,
+            } => {
+                wgpu::wgpu_command_buffer_copy_texture_to_buffer(
+                    command_encoder_id,
+                    &texture_copy_view(&source),
+                    &buffer_copy_view(&destination),
+                    extent(copy_size),
+                );
+            },
+            WebGPURequest::CreateBindGroup {
+                sender,
+                device_id,
+                layout_id,
+                entries,
+            } => {
+                let bindings = entries.iter().map(binding).collect::<Vec<_>>();
+                let descriptor = wgpu::BindGroupDescriptor {
+                    layout: layout_id,
+                    bindings: bindings.as_ptr(),
+                    bindings_length: bindings.len(),
+                };
+                let bind_group_id = wgpu::wgpu_device_create_bind_group(device_id, &descriptor);
+                if let Err(e) = sender.send(WebGPUBindGroup(bind_group_id)) {
+                    warn!("Failed to send the bind group ({})", e);
+                }
+            },
+            WebGPURequest::CreateBindGroupLayout {
+                sender,
+                device_id,
+                entries,
+            } => {
+                let bindings = entries
+                    .iter()
+                    .map(bind_group_layout_binding)
+                    .collect::<Vec<_>>();
+                let descriptor = wgpu::BindGroupLayoutDescriptor {
+                    bindings: bindings.as_ptr(),
+                    bindings_length: bindings.len(),
+                };
+                let bind_group_layout_id =
+                    wgpu::wgpu_device_create_bind_group_layout(device_id, &descriptor);
+                if let Err(e) = sender.send(WebGPUBindGroupLayout(bind_group_layout_id)) {
+                    warn!("Failed to send the bind group layout ({})", e);
+                }
+            },
+            WebGPURequest::CreateBuffer {
+                sender,
+                device_id,
+                descriptor,
+            } => {
+                let wgpu_descriptor = wgpu::BufferDescriptor {
+                    size: descriptor.size as u32,
+                    usage: buffer_usage(descriptor.usage),
+                };
+                let buffer_id = if descriptor.mapped_at_creation {
+                    let mut pointer = ptr::null_mut();
+                    let buffer_id = wgpu::wgpu_device_create_buffer_mapped(
+                        device_id,
+                        &wgpu_descriptor,
+                        &mut pointer,
+                    );
+                    if !pointer.is_null() {
+                        self.mappings
+                            .push((buffer_id, Mapping { pointer, offset: 0 }));
+                    }
+                    buffer_id
+                } else {
+                    wgpu::wgpu_device_create_buffer(device_id, &wgpu_descriptor)
+                };
+                if let Err(e) = sender.send(WebGPUBuffer(buffer_id)) {
+                    warn!("Failed to send the buffer ({})", e);
+                }
+            },
+            WebGPURequest::CreateCommandEncoder { sender, device_id } => {
+                let command_encoder_id = wgpu::wgpu_device_create_command_encoder(
+                    device_id,
+                    &wgpu::CommandEncoderDescriptor { todo: 0 },
+                );
+                if let Err(e) = sender.send(WebGPUCommandEncoder(command_encoder_id)) {
+                    warn!("Failed to send the command encoder ({})", e);
+                }
+            },
+            WebGPURequest::CreateComputePipeline {
+                sender,
+                device_id,
+                layout_id,
+                compute_stage,
+            } => {
+                let entry_point = entry_point(&compute_stage);
+                let descriptor = wgpu::ComputePipelineDescriptor {
+                    layout: layout_id,
+                    compute_stage: wgpu::PipelineStageDescriptor {
+                        module: compute_stage.module,
+                        entry_point: entry_point.as_ptr(),
+                    },
+                };
+                let compute_pipeline_id =
+                    wgpu::wgpu_device_create_compute_pipeline(device_id, &descriptor);
+                if let Err(e) = sender.send(WebGPUComputePipeline(compute_pipeline_id)) {
+                    warn!("Failed to send the compute pipeline ({})", e);
+                }
+            },
+            WebGPURequest::CreatePipelineLayout {
+                sender,
+                device_id,
+                bind_group_layouts,
+            } => {
+                let descriptor = wgpu::PipelineLayoutDescriptor {
+                    bind_group_layouts: bind_group_layouts.as_ptr(),
+                    bind_group_layouts_length: bind_group_layouts.len(),
+                };
+                let pipeline_layout_id =
+                    wgpu::wgpu_device_create_pipeline_layout(device_id, &descriptor);
+                if let Err(e) = sender.send(WebGPUPipelineLayout(pipeline_layout_id)) {
+                    warn!("Failed to send the pipeline layout ({})", e);
+                }
+            },
+            WebGPURequest::CreateRenderPipeline {
+                sender,
+                device_id,
+                descriptor,
+            } => {
+                if descriptor.sample_mask != !0 || descriptor.alpha_to_coverage_enabled {
+                    warn!("Sample masks and alpha to coverage are not supported by wgpu");
+                }
+                let vertex_entry_point = entry_point(&descriptor.vertex_stage);
+                let (fragment_module, fragment_entry_point) = match descriptor.fragment_stage {
+                    Some(ref stage) => (stage.module, entry_point(stage)),
+                    None => (
+                        self.empty_fragment_shader(device_id),
+                        CString::new("main").unwrap(),
+                    ),
+                };
+                let color_states = descriptor
+                    .color_states
+                    .iter()
+                    .map(color_state)
+                    .collect::<Vec<_>>();
+                let depth_stencil_state = descriptor
+                    .depth_stencil_state
+                    .as_ref()
+                    .map(depth_stencil_state);
+                let attributes = descriptor
+                    .vertex_state
+                    .vertex_buffers
+                    .iter()
+                    .map(|buffer| {
+                        buffer
+                            .attributes
+                            .iter()
+                            .map(|attribute| wgpu::VertexAttributeDescriptor {
+                                offset: attribute.offset as u32,
+                                format: vertex_format(attribute.format),
+                                attribute_index: attribute.shader_location,
+                            })
+                            .collect::<Vec<_>>()
+                    })
+                    .collect::<Vec<_>>();
+                let vertex_buffers = descriptor
+                    .vertex_state
+                    .vertex_buffers
+                    .iter()
+                    .zip(&attributes)
+                    .map(|(buffer, attributes)| wgpu::VertexBufferDescriptor {
+                        stride: buffer.array_stride as u32,
+                        step_mode: match buffer.step_mode {
+                            wgt::InputStepMode::Vertex => wgpu::InputStepMode::Vertex,
+                            wgt::InputStepMode::Instance => wgpu::InputStepMode::Instance,
+                        },
+                        attributes: attributes.as_ptr(),
+                        attributes_count: attributes.len(),
+                    })
+                    .collect::<Vec<_>>();
+                let wgpu_descriptor = wgpu::RenderPipelineDescriptor {
+                    layout: descriptor.layout,
+                    vertex_stage: wgpu::PipelineStageDescriptor {
+                        module: descriptor.vertex_stage.module,
+                        entry_point: vertex_entry_point.as_ptr(),
+                    },
+                    fragment_stage: wgpu::PipelineStageDescriptor {
+                        module: fragment_module,
+                        entry_point: fragment_entry_point.as_ptr(),
+                    },
+                    primitive_topology: primitive_topology(descriptor.primitive_topology),
+                    rasterization_state: rasterization_state(&descriptor.rasterization_state),
+                    color_states: color_states.as_ptr(),
+                    color_states_length: color_states.len(),
+                    depth_stencil_state: depth_stencil_state
+                        .as_ref()
+                        .map_or(ptr::null(), |state| state as *const _),
+                    vertex_buffer_state: wgpu::VertexBufferStateDescriptor {
+                        index_format: match descriptor.vertex_state.index_format {
+                            wgt::IndexFormat::Uint16 => wgpu::IndexFormat::Uint16,
+                            wgt::IndexFormat::Uint32 => wgpu::IndexFormat::Uint32,
+                        },
+                        vertex_buffers: vertex_buffers.as_ptr(),
+                        vertex_buffers_count: vertex_buffers.len(),
+                    },
+                    sample_count: descriptor.sample_count,
+                };
+                let render_pipeline_id =
+                    wgpu::wgpu_device_create_render_pipeline(device_id, &wgpu_descriptor);
+                if let Err(e) = sender.send(WebGPURenderPipeline(render_pipeline_id)) {
+                    warn!("Failed to send the render pipeline ({})", e);
+                }
+            },
+            WebGPURequest::CreateSampler {
+                sender,
+                device_id,
+                descriptor,
+            } => {
+                let wgpu_descriptor = wgpu::SamplerDescriptor {
+                    r_address_mode: address_mode(descriptor.address_mode_u),
+                    s_address_mode: address_mode(descriptor.address_mode_v),
+                    t_address_mode: address_mode(descriptor.address_mode_w),
+                    mag_filter: filter_mode(descriptor.mag_filter),
+                    min_filter: filter_mode(descriptor.min_filter),
+                    mipmap_filter: filter_mode(descriptor.mipmap_filter),
+                    lod_min_clamp: descriptor.lod_min_clamp,
+                    lod_max_clamp: descriptor.lod_max_clamp,
+                    max_anisotropy: 1,
+                    // Samplers that always pass are the ones doing no comparison.
+                    compare_function: descriptor
+                        .compare
+                        .map_or(wgpu::CompareFunction::Always, compare_function),
+                    border_color: wgpu::BorderColor::TransparentBlack,
+                };
+                let sampler_id = wgpu::wgpu_device_create_sampler(device_id, &wgpu_descriptor);
+                if let Err(e) = sender.send(WebGPUSampler(sampler_id)) {
+                    warn!("Failed to send the sampler ({})", e);
+                }
+            },
+            WebGPURequest::CreateShaderModule {
+                sender,
+                device_id,
+                code,
+            } => {
+                let shader_module_id = create_shader_module(device_id, &code);
+                if let Err(e) = sender.send(WebGPUShaderModule(shader_module_id)) {
+                    warn!("Failed to send the shader module ({})", e);
+                }
+            },
+            WebGPURequest::CreateSwapChain {
+                sender,
+                device_id,
+                queue_id,
+                width,
+                height,
+            } => {
+                let stride = aligned_bytes_per_row(width);
+                let buffer_descriptor = wgpu::BufferDescriptor {
+                    size: stride * height,
+                    usage: wgpu::BufferUsageFlags::MAP_READ | wgpu::BufferUsageFlags::TRANSFER_DST,
+                };
+                let buffer_id = wgpu::wgpu_device_create_buffer(device_id, &buffer_descriptor);
+                let descriptor = ImageDescriptor {
+                    size: DeviceIntSize::new(width as i32, height as i32),
+                    stride: Some(stride as i32),
+                    format: ImageFormat::BGRA8,
+                    offset: 0,
+                    is_opaque: false,
+                    allow_mipmaps: false,
+                };
+                let image_key = self.webrender_api.generate_image_key();
+                let mut txn = Transaction::new();
+                // Each pixel is fully-transparent black until the first frame
+                // gets presented.
+                txn.add_image(
+                    image_key,
+                    descriptor,
+                    ImageData::new(vec![0; stride as usize * height as usize]),
+                    None,
+                );
+                self.webrender_api.update_resources(txn.resource_updates);
+                self.swap_chains.insert(
+                    image_key,
+                    SwapChain {
+                        queue_id,
+                        buffer_id,
+                        descriptor,
+                    },
+                );
+                if let Err(e) = sender.send(image_key) {
+                    warn!("Failed to send the image of the swap chain ({})", e);
+                }
+            },
+            WebGPURequest::CreateTexture {
+                sender,
+                device_id,
+                descriptor,
+            } => {
+                if descriptor.mip_level_count != 1 || descriptor.sample_count != 1 {
+                    warn!("Textures with several mip levels or samples are not supported by wgpu");
+                }
+                let (size, array_size) = match descriptor.dimension {
+                    wgt::TextureDimension::D3 => (descriptor.size, 1),
+                    _ => (
+                        wgt::Extent3d {
+                            depth: 1,
+                            ..descriptor.size
+                        },
+                        descriptor.size.depth,
+                    ),
+                };
+                let wgpu_descriptor = wgpu::TextureDescriptor {
+                    size: extent(size),
+                    array_size,
+                    dimension: match descriptor.dimension {
+                        wgt::TextureDimension::D1 => wgpu::TextureDimension::D1,
+                        wgt::TextureDimension::D2 => wgpu::TextureDimension::D2,
+                        wgt::TextureDimension::D3 => wgpu::TextureDimension::D3,
+                    },
+                    format: texture_format(descriptor.format),
+                    usage: wgpu::TextureUsageFlags::from_bits_truncate(descriptor.usage.bits()),
+                };
+                let texture_id = wgpu::wgpu_device_create_texture(device_id, &wgpu_descriptor);
+                if let Err(e) = sender.send(WebGPUTexture(texture_id)) {
+                    warn!("Failed to send the texture ({})", e);
+                }
+            },
+            WebGPURequest::CreateTextureView {
+                sender,
+                texture_id,
+                descriptor,
+            } => {
+                let wgpu_descriptor = wgpu::TextureViewDescriptor {
+                    format: texture_format(descriptor.format),
+                    dimension: texture_view_dimension(descriptor.dimension),
+                    aspect: texture_aspect(descriptor.format, descriptor.aspect),
+                    base_mip_level: descriptor.base_mip_level,
+                    level_count: descriptor.level_count,
+                    base_array_layer: descriptor.base_array_layer,
+                    array_count: descriptor.array_layer_count,
+                };
+                let texture_view_id = wgpu::wgpu_texture_create_view(texture_id, &wgpu_descriptor);
+                if let Err(e) = sender.send(WebGPUTextureView(texture_view_id)) {
+                    warn!("Failed to send the texture view ({})", e);
+                }
+            },
+            WebGPURequest::DestroyBuffer(buffer_id) => {
+                self.take_mapping(buffer_id);
+                wgpu::wgpu_buffer_destroy(buffer_id);
+            },
+            WebGPURequest::DestroySwapChain(image_key) => {
+                if let Some(swap_chain) = self.swap_chains.remove(&image_key) {
+                    wgpu::wgpu_buffer_destroy(swap_chain.buffer_id);
+                    let mut txn = Transaction::new();
+                    txn.delete_image(image_key);
+                    self.webrender_api.update_resources(txn.resource_updates);
+                }
+            },
+            WebGPURequest::DestroyTexture(texture_id) => {
+                wgpu::wgpu_texture_destroy(texture_id);
+            },
+            WebGPURequest::Exit => return true,
+            WebGPURequest::RequestAdapter {
+                sender,
+                power_preference,
+            } => {
+                let result = match self.instance_id {
+                    Some(instance_id) => {
+                        let descriptor = wgpu::AdapterDescriptor {
+                            power_preference: match power_preference {
+                                wgt::PowerPreference::Default => wgpu::PowerPreference::Default,
+                                wgt::PowerPreference::LowPower => wgpu::PowerPreference::LowPower,
+                                wgt::PowerPreference::HighPerformance => {
+                                    wgpu::PowerPreference::HighPerformance
+                                },
+                            },
+                        };
+                        let adapter_id = wgpu::wgpu_instance_get_adapter(instance_id, &descriptor);
+                        // wgpu does not tell the names of its adapters.
+                        Ok(WebGPUResponse::RequestAdapter {
+                            adapter_name: "wgpu".to_owned(),
+                            adapter_id: WebGPUAdapter(adapter_id),
+                            channel: WebGPU(self.sender.clone()),
+                        })
+                    },
+                    None => Err("Failed to find a suitable adapter".to_owned()),
+                };
+                if let Err(e) = sender.send(result) {
+                    warn!("Failed to send the adapter ({})", e);
+                }
+            },
+            WebGPURequest::RequestDevice {
+                sender,
+                adapter_id,
+                limits,
+            } => {
+                let descriptor = wgpu::DeviceDescriptor {
+                    extensions: wgpu::Extensions {
+                        anisotropic_filtering: false,
+                    },
+                };
+                let device_id = wgpu::wgpu_adapter_create_device(adapter_id.0, &descriptor);
+                let result = Ok(WebGPUResponse::RequestDevice {
+                    device_id: WebGPUDevice(device_id),
+                    queue_id: WebGPUQueue(wgpu::wgpu_device_get_queue(device_id)),
+                    limits,
+                });
+                if let Err(e) = sender.send(result) {
+                    warn!("Failed to send the device ({})", e);
+                }
+            },
+            WebGPURequest::RunComputePass {
+                command_encoder_id,
+                commands,
+            } => {
+                let pass_id = wgpu::wgpu_command_encoder_begin_compute_pass(command_encoder_id);
+                for command in commands {
+                    run_compute_pass_command(pass_id, command);
+                }
+                wgpu::wgpu_compute_pass_end_pass(pass_id);
+            },
+            WebGPURequest::RunRenderPass {
+                command_encoder_id,
+                descriptor,
+                commands,
+            } => {
+                let pass_id = begin_render_pass(command_encoder_id, &descriptor);
+                let mut vertex_buffers = Vec::new();
+                for command in commands {
+                    run_render_pass_command(pass_id, &mut vertex_buffers, command);
+                }
+                wgpu::wgpu_render_pass_end_pass(pass_id);
+            },
+            WebGPURequest::Submit {
+                queue_id,
+                command_buffers,
+            } => {
+                wgpu::wgpu_queue_submit(queue_id, command_buffers.as_ptr(), command_buffers.len());
+            },
+            WebGPURequest::SwapChainPresent {
+                image_key,
+                texture_id,
+            } => self.present(image_key, texture_id),
+            WebGPURequest::UnmapBuffer { buffer_id, data } => {
+                let mapping = match self.take_mapping(buffer_id) {
+                    Some(mapping) => mapping,
+                    None => return false,
+                };
+                if let Some((offset, data)) = data {
+                    unsafe {
+                        let destination = mapping.pointer.add((offset - mapping.offset) as usize);
+                        ptr::copy_nonoverlapping(data.as_ptr(), destination, data.len());
+                    }
+                }
+                wgpu::wgpu_buffer_unmap(buffer_id);
+            },
+        }
+        false
+    }
+
+    fn take_mapping(&mut self, buffer_id: BufferId) -> Option<Mapping> {
+        let index = self.mappings.iter().position(|&(id, _)| id == buffer_id)?;
+        Some(self.mappings.swap_remove(index).1)
+    }
+
+    fn empty_fragment_shader(&mut self, device_id: DeviceId) -> ShaderModuleId {
+        if let Some(&(_, shader_module_id)) = self
+            .empty_fragment_shaders
+            .iter()
+            .find(|&&(id, _)| id == device_id)
+        {
+            return shader_module_id;
+        }
+        let shader_module_id = create_shader_module(device_id, &EMPTY_FRAGMENT_SHADER);
+        self.empty_fragment_shaders
+            .push((device_id, shader_module_id));
+        shader_module_id
+    }
+
+    /// Maps a range of a buffer and waits for the mapping to be done,
+    /// returning the memory it got mapped to.
+    fn map_buffer(
+        &self,
+        queue_id: QueueId,
+        buffer_id: BufferId,
+        host_map: HostMap,
+        offset: u64,
+        size: u64,
+    ) -> Option<*mut u8> {
+        let mut result = MapResult {
+            status: None,
+            data: ptr::null_mut(),
+        };
+        let userdata = &mut result as *mut MapResult as *mut u8;
+        match host_map {
+            HostMap::Read => wgpu::wgpu_buffer_map_read_async(
+                buffer_id,
+                offset as u32,
+                size as u32,
+                map_read_callback,
+                userdata,
+            ),
+            HostMap::Write => wgpu::wgpu_buffer_map_write_async(
+                buffer_id,
+                offset as u32,
+                size as u32,
+                map_write_callback,
+                userdata,
+            ),
+        }
+        // wgpu only maps buffers the GPU is done with when something gets
+        // submitted to their queue, this submits nothing until then.
+        let no_command_buffers: [wgpu::CommandBufferId; 0] = [];
+        while result.status.is_none() {
+            wgpu::wgpu_queue_submit(queue_id, no_command_buffers.as_ptr(), 0);
+            thread::yield_now();
+        }
+        match result.status {
+            Some(BufferMapAsyncStatus::Success) => Some(result.data),
+            _ => None,
+        }
+    }
+
+    /// Copies a texture to the buffer of a swap chain, then reads it back
+    /// to the image of the swap chain.
+    #[allow(unsafe_code)]
+    fn present(&mut self, image_key: ImageKey, texture_id: TextureId) {
+        let (queue_id, buffer_id, descriptor) = match self.swap_chains.get(&image_key) {
+            Some(swap_chain) => (
+                swap_chain.queue_id,
+                swap_chain.buffer_id,
+                swap_chain.descriptor,
+            ),
+            None => return warn!("Presenting to an unknown swap chain {:?}", image_key),
+        };
+        let width = descriptor.size.width as u32;
+        let height = descriptor.size.height as u32;
+        let stride = aligned_bytes_per_row(width);
+        let size = stride as u64 * height as u64;
+
+        // The queue of a device has the same id as the device itself.
+        let command_encoder_id = wgpu::wgpu_device_create_command_encoder(
+            queue_id,
+            &wgpu::CommandEncoderDescriptor { todo: 0 },
+        );
+        let source = wgpu::TextureCopyView {
+            texture: texture_id,
+            level: 0,
+            slice: 0,
+            origin: wgpu::Origin3d {
+                x: 0.,
+                y: 0.,
+                z: 0.,
+            },
+        };
+        let destination = wgpu::BufferCopyView {
+            buffer: buffer_id,
+            offset: 0,
+            row_pitch: stride,
+            image_height: 0,
+        };
+        let copy_size = wgpu::Extent3d {
+            width,
+            height,
+            depth: 1,
+        };
+        wgpu::wgpu_command_buffer_copy_texture_to_buffer(
+            command_encoder_id,
+            &source,
+            &destination,
+            copy_size,
+        );
+        let command_buffer_id = wgpu::wgpu_command_encoder_finish(command_encoder_id);
+        wgpu::wgpu_queue_submit(queue_id, &command_buffer_id, 1);
+
+        let pointer = match self.map_buffer(queue_id, buffer_id, HostMap::Read, 0, size) {
+            Some(pointer) => pointer,
+            None => return warn!("Failed to read back the frame of {:?}", image_key),
+        };
+        let data = unsafe { slice::from_raw_parts(pointer, size as usize) }.to_vec();
+        wgpu::wgpu_buffer_unmap(buffer_id);
+
+        let mut txn = Transaction::new();
+        txn.update_image(image_key, descriptor, ImageData::new(data), &DirtyRect::All);
+        self.webrender_api.update_resources(txn.resource_updates);
+    }
+}
+
+/// The number of bytes of the rows of a BGRA8 image of the given width,
+/// once they are aligned to `BYTES_PER_ROW_ALIGNMENT`.
+fn aligned_bytes_per_row(width: u32) -> u32 {
+    let bytes_per_row = width * 4;
+    let remainder = bytes_per_row % BYTES_PER_ROW_ALIGNMENT;
+    if remainder == 0 {
+        bytes_per_row
+    } else {
+        bytes_per_row + BYTES_PER_ROW_ALIGNMENT - remainder
+    }
+}
+
+fn entry_point(stage: &ProgrammableStage) -> CString {
+    // The content cannot create entry points with nul bytes in them.
+    CString::new(stage.entry_point.clone()).unwrap_or_default()
+}
+
+fn create_shader_module(device_id: DeviceId, code: &[u32]) -> ShaderModuleId {
+    let descriptor = wgpu::ShaderModuleDescriptor {
+        code: wgpu::ByteArray {
+            bytes: code.as_ptr() as *const u8,
+            length: code.len() * 4,
+        },
+    };
+    wgpu::wgpu_device_create_shader_module(device_id, &descriptor)
+}
+
+fn buffer_usage(usage: wgt::BufferUsage) -> wgpu::BufferUsageFlags {
+    // wgpu has no usage for the buffers of indirect draws.
+    wgpu::BufferUsageFlags::from_bits_truncate(usage.bits())
+}
+
+fn extent(extent: wgt::Extent3d) -> wgpu::Extent3d {
+    wgpu::Extent3d {
+        width: extent.width,
+        height: extent.height,
+        depth: extent.depth,
+    }
+}
+
+fn color(color: wgt::Color) -> wgpu::Color {
+    wgpu::Color {
+        r: color.r as f32,
+        g: color.g as f32,
+        b: color.b as f32,
+        a: color.a as f32,
+    }
+}
+
+fn buffer_copy_view(view: &BufferCopyView) -> wgpu::BufferCopyView {
+    wgpu::BufferCopyView {
+        buffer: view.buffer,
+        offset: view.offset as u32,
+        row_pitch: view.bytes_per_row,
+        image_height: view.rows_per_image,
+    }
+}
+
+/// The copy view of a texture, with the array layer of 2D textures moved
+/// out of the z coordinate of its origin.
+fn texture_copy_view(view: &TextureCopyView) -> wgpu::TextureCopyView {
+    wgpu::TextureCopyView {
+        texture: view.texture,
+        level: view.mip_level,
+        slice: view.origin.z,
+        origin: wgpu::Origin3d {
+            x: view.origin.x as f32,
+            y: view.origin.y as f32,
+            z: 0.,
+        },
+    }
+}
+
+fn bind_group_layout_binding(entry: &BindGroupLayoutEntry) -> wgpu::BindGroupLayoutBinding {
+    if entry.has_dynamic_offset || entry.multisampled {
+        warn!("Dynamic offsets and multisampled textures are not supported by wgpu");
+    }
+    // wgpu does not tell read-only storage buffers and comparison samplers
+    // apart from the others, and has no storage textures.
+    let ty = match entry.ty {
+        BindingType::UniformBuffer => wgpu::BindingType::UniformBuffer,
+        BindingType::StorageBuffer | BindingType::ReadonlyStorageBuffer => {
+            wgpu::BindingType::StorageBuffer
+        },
+        BindingType::Sampler | BindingType::ComparisonSampler => wgpu::BindingType::Sampler,
+        BindingType::SampledTexture => wgpu::BindingType::SampledTexture,
+        BindingType::ReadonlyStorageTexture | BindingType::WriteonlyStorageTexture => {
+            warn!("Storage textures are not supported by wgpu");
+            wgpu::BindingType::SampledTexture
+        },
+    };
+    wgpu::BindGroupLayoutBinding {
+        binding: entry.binding,
+        visibility: wgpu::ShaderStageFlags::from_bits_truncate(entry.visibility.bits()),
+        ty,
+    }
+}
+
+fn binding(entry: &BindGroupEntry) -> wgpu::Binding {
+    let resource = match entry.resource {
+        BindingResource::Buffer {
+            buffer_id,
+            offset,
+            size,
+        } => wgpu::BindingResource::Buffer(wgpu::BufferBinding {
+            buffer: buffer_id,
+            offset: offset as u32,
+            size: size as u32,
+        }),
+        BindingResource::Sampler(sampler_id) => wgpu::BindingResource::Sampler(sampler_id),
+        BindingResource::TextureView(texture_view_id) => {
+            wgpu::BindingResource::TextureView(texture_view_id)
+        },
+    };
+    wgpu::Binding {
+        binding: entry.binding,
+        resource,
+    }
+}
+
+fn begin_render_pass(
+    command_encoder_id: wgpu::CommandEncoderId,
+    descriptor: &RenderPassDescriptor,
+) -> RenderPassId {
+    let color_attachments = descriptor
+        .color_attachments
+        .iter()
+        .map(|attachment| {
+            if attachment.resolve_target.is_some() {
+                warn!("Resolve targets are not supported by wgpu");
+            }
+            wgpu::RenderPassColorAttachmentDescriptor {
+                attachment: attachment.attachment,
+                load_op: load_op(attachment.load_op),
+                store_op: store_op(attachment.store_op),
+                clear_color: color(attachment.clear_color),
+            }
+        })
+        .collect::<Vec<_>>();
+    let depth_stencil_attachment = descriptor
+        .depth_stencil_attachment
+        .as_ref()
+        .map(
+            |attachment| wgpu::RenderPassDepthStencilAttachmentDescriptor {
+                attachment: attachment.attachment,
+                depth_load_op: load_op(attachment.depth_load_op),
+                depth_store_op: store_op(attachment.depth_store_op),
+                clear_depth: attachment.clear_depth,
+                stencil_load_op: load_op(attachment.stencil_load_op),
+                stencil_store_op: store_op(attachment.stencil_store_op),
+                clear_stencil: attachment.clear_stencil,
+            },
+        );
+    let wgpu_descriptor = wgpu::RenderPassDescriptor {
+        color_attachments: color_attachments.as_ptr(),
+        color_attachments_length: color_attachments.len(),
+        depth_stencil_attachment: depth_stencil_attachment
+            .as_ref()
+            .map_or(ptr::null(), |attachment| attachment as *const _),
+    };
+    wgpu::wgpu_command_encoder_begin_render_pass(command_encoder_id, wgpu_descriptor)
+}
+
+fn run_compute_pass_command(pass_id: ComputePassId, command: ComputePassCommand) {
+    match command {
+        ComputePassCommand::SetPipeline(pipeline_id) => {
+            wgpu::wgpu_compute_pass_set_pipeline(pass_id, pipeline_id)
+        },
+        ComputePassCommand::SetBindGroup {
+            index,
+            bind_group_id,
+            offsets,
+        } => {
+            if !offsets.is_empty() {
+                warn!("Dynamic offsets are not supported by wgpu");
+            }
+            wgpu::wgpu_compute_pass_set_bind_group(pass_id, index, bind_group_id)
+        },
+        ComputePassCommand::Dispatch(x, y, z) => wgpu::wgpu_compute_pass_dispatch(pass_id, x, y, z),
+    }
+}
+
+/// Runs a command of a render pass, `vertex_buffers` holds the vertex
+/// buffers set so far, as wgpu sets them all at once from the first slot.
+fn run_render_pass_command(
+    pass_id: RenderPassId,
+    vertex_buffers: &mut Vec<Option<(BufferId, u32)>>,
+    command: RenderPassCommand,
+) {
+    match command {
+        RenderPassCommand::SetPipeline(pipeline_id) => {
+            wgpu::wgpu_render_pass_set_pipeline(pass_id, pipeline_id)
+        },
+        RenderPassCommand::SetBindGroup {
+            index,
+            bind_group_id,
+            offsets,
+        } => {
+            if !offsets.is_empty() {
+                warn!("Dynamic offsets are not supported by wgpu");
+            }
+            wgpu::wgpu_render_pass_set_bind_group(pass_id, index, bind_group_id)
+        },
+        RenderPassCommand::SetVertexBuffer {
+            slot,
+            buffer_id,
+            offset,
+            size: _,
+        } => {
+            let slot = slot as usize;
+            if vertex_buffers.len() <= slot {
+                vertex_buffers.resize(slot + 1, None);
+            }
+            vertex_buffers[slot] = Some((buffer_id, offset as u32));
+            let (buffers, offsets): (Vec<_>, Vec<_>) = vertex_buffers
+                .iter()
+                .take_while(|buffer| buffer.is_some())
+                .flatten()
+                .cloned()
+                .unzip();
+            if slot < buffers.len() {
+                wgpu::wgpu_render_pass_set_vertex_buffers(
+                    pass_id,
+                    buffers.as_ptr(),
+                    offsets.as_ptr(),
+                    buffers.len(),
+                );
+            }
+        },
+        RenderPassCommand::SetIndexBuffer {
+            buffer_id,
+            offset,
+            size: _,
+        } => wgpu::wgpu_render_pass_set_index_buffer(pass_id, buffer_id, offset as u32),
+        RenderPassCommand::SetViewport { .. } |
+        RenderPassCommand::SetScissorRect { .. } |
+        RenderPassCommand::SetBlendColor(_) |
+        RenderPassCommand::SetStencilReference(_) => {
+            warn!("{:?} is not supported by wgpu", command)
+        },
+        RenderPassCommand::Draw {
+            vertex_count,
+            instance_count,
+            first_vertex,
+            first_instance,
+        } => wgpu::wgpu_render_pass_draw(
+            pass_id,
+            vertex_count,
+            instance_count,
+            first_vertex,
+            first_instance,
+        ),
+        RenderPassCommand::DrawIndexed {
+            index_count,
+            instance_count,
+            first_index,
+            base_vertex,
+            first_instance,
+        } => wgpu::wgpu_render_pass_draw_indexed(
+            pass_id,
+            index_count,
+            instance_count,
+            first_index,
+            base_vertex,
+            first_instance,
+        ),
+    }
+}
+
+fn load_op(load_op: wgt::LoadOp) -> wgpu::LoadOp {
+    match load_op {
+        wgt::LoadOp::Clear => wgpu::LoadOp::Clear,
+        wgt::LoadOp::Load => wgpu::LoadOp::Load,
+    }
+}
+
+fn store_op(store_op: wgt::StoreOp) -> wgpu::StoreOp {
+    // Storing the attachments is a valid way to discard them.
+    match store_op {
+        wgt::StoreOp::Clear | wgt::StoreOp::Store => wgpu::StoreOp::Store,
+    }
+}
+
+fn texture_format(format: wgt::TextureFormat) -> wgpu::TextureFormat {
+    use wgpu::TextureFormat as F;
+    match format {
+        wgt::TextureFormat::R8Unorm => F::R8Unorm,
+        wgt::TextureFormat::R8Snorm => F::R8Snorm,
+        wgt::TextureFormat::R8Uint => F::R8Uint,
+        wgt::TextureFormat::R8Sint => F::R8Sint,
+        wgt::TextureFormat::R16Uint => F::R16Uint,
+        wgt::TextureFormat::R16Sint => F::R16Sint,
+        wgt::TextureFormat::R16Float => F::R16Float,
+        wgt::TextureFormat::Rg8Unorm => F::Rg8Unorm,
+        wgt::TextureFormat::Rg8Snorm => F::Rg8Snorm,
+        wgt::TextureFormat::Rg8Uint => F::Rg8Uint,
+        wgt::TextureFormat::Rg8Sint => F::Rg8Sint,
+        wgt::TextureFormat::R32Uint => F::R32Uint,
+        wgt::TextureFormat::R32Sint => F::R32Sint,
+        wgt::TextureFormat::R32Float => F::R32Float,
+        wgt::TextureFormat::Rg16Uint => F::Rg16Uint,
+        wgt::TextureFormat::Rg16Sint => F::Rg16Sint,
+        wgt::TextureFormat::Rg16Float => F::Rg16Float,
+        wgt::TextureFormat::Rgba8Unorm => F::Rgba8Unorm,
+        wgt::TextureFormat::Rgba8UnormSrgb => F::Rgba8UnormSrgb,
+        wgt::TextureFormat::Rgba8Snorm => F::Rgba8Snorm,
+        wgt::TextureFormat::Rgba8Uint => F::Rgba8Uint,
+        wgt::TextureFormat::Rgba8Sint => F::Rgba8Sint,
+        wgt::TextureFormat::Bgra8Unorm => F::Bgra8Unorm,
+        wgt::TextureFormat::Bgra8UnormSrgb => F::Bgra8UnormSrgb,
+        wgt::TextureFormat::Rgb10a2Unorm => F::Rgb10a2Unorm,
+        wgt::TextureFormat::Rg11b10Float => F::Rg11b10Float,
+        wgt::TextureFormat::Rg32Uint => F::Rg32Uint,
+        wgt::TextureFormat::Rg32Sint => F::Rg32Sint,
+        wgt::TextureFormat::Rg32Float => F::Rg32Float,
+        wgt::TextureFormat::Rgba16Uint => F::Rgba16Uint,
+        wgt::TextureFormat::Rgba16Sint => F::Rgba16Sint,
+        wgt::TextureFormat::Rgba16Float => F::Rgba16Float,
+        wgt::TextureFormat::Rgba32Uint => F::Rgba32Uint,
+        wgt::TextureFormat::Rgba32Sint => F::Rgba32Sint,
+        wgt::TextureFormat::Rgba32Float => F::Rgba32Float,
+        // Depth24Plus leaves the precision of the depth to the implementation.
+        wgt::TextureFormat::Depth32Float | wgt::TextureFormat::Depth24Plus => F::D32Float,
+        wgt::TextureFormat::Depth24PlusStencil8 => F::D24UnormS8Uint,
+    }
+}
+
+fn texture_view_dimension(dimension: wgt::TextureViewDimension) -> wgpu::TextureViewDimension {
+    match dimension {
+        wgt::TextureViewDimension::D1 => wgpu::TextureViewDimension::D1,
+        wgt::TextureViewDimension::D2 => wgpu::TextureViewDimension::D2,
+        wgt::TextureViewDimension::D2Array => wgpu::TextureViewDimension::D2Array,
+        wgt::TextureViewDimension::Cube => wgpu::TextureViewDimension::Cube,
+        wgt::TextureViewDimension::CubeArray => wgpu::TextureViewDimension::CubeArray,
+        wgt::TextureViewDimension::D3 => wgpu::TextureViewDimension::D3,
+    }
+}
+
+/// The aspects of a texture of the given format a view is restricted to.
+fn texture_aspect(
+    format: wgt::TextureFormat,
+    aspect: wgt::TextureAspect,
+) -> wgpu::TextureAspectFlags {
+    let all = match format {
+        wgt::TextureFormat::Depth32Float | wgt::TextureFormat::Depth24Plus => {
+            wgpu::TextureAspectFlags::DEPTH
+        },
+        wgt::TextureFormat::Depth24PlusStencil8 => {
+            wgpu::TextureAspectFlags::DEPTH | wgpu::TextureAspectFlags::STENCIL
+        },
+        _ => wgpu::TextureAspectFlags::COLOR,
+    };
+    match aspect {
+        wgt::TextureAspect::All => all,
+        wgt::TextureAspect::DepthOnly => all & wgpu::TextureAspectFlags::DEPTH,
+        wgt::TextureAspect::StencilOnly => all & wgpu::TextureAspectFlags::STENCIL,
+    }
+}
+
+fn address_mode(address_mode: wgt::AddressMode) -> wgpu::AddressMode {
+    match address_mode {
+        wgt::AddressMode::ClampToEdge => wgpu::AddressMode::ClampToEdge,
+        wgt::AddressMode::Repeat => wgpu::AddressMode::Repeat,
+        wgt::AddressMode::MirrorRepeat => wgpu::AddressMode::MirrorRepeat,
+    }
+}
+
+fn filter_mode(filter_mode: wgt::FilterMode) -> wgpu::FilterMode {
+    match filter_mode {
+        wgt::FilterMode::Nearest => wgpu::FilterMode::Nearest,
+        wgt::FilterMode::Linear => wgpu::FilterMode::Linear,
+    }
+}
+
+fn compare_function(compare: wgt::CompareFunction) -> wgpu::CompareFunction {
+    match compare {
+        wgt::CompareFunction::Never => wgpu::CompareFunction::Never,
+        wgt::CompareFunction::Less => wgpu::CompareFunction::Less,
+        wgt::CompareFunction::Equal => wgpu::CompareFunction::Equal,
+        wgt::CompareFunction::LessEqual => wgpu::CompareFunction::LessEqual,
+        wgt::CompareFunction::Greater => wgpu::CompareFunction::Greater,
+        wgt::CompareFunction::NotEqual => wgpu::CompareFunction::NotEqual,
+        wgt::CompareFunction::GreaterEqual => wgpu::CompareFunction::GreaterEqual,
+        wgt::CompareFunction::Always => wgpu::CompareFunction::Always,
+    }
+}
+
+fn primitive_topology(topology: wgt::PrimitiveTopology) -> wgpu::PrimitiveTopology {
+    match topology {
+        wgt::PrimitiveTopology::PointList => wgpu::PrimitiveTopology::PointList,
+        wgt::PrimitiveTopology::LineList => wgpu::PrimitiveTopology::LineList,
+        wgt::PrimitiveTopology::LineStrip => wgpu::PrimitiveTopology::LineStrip,
+        wgt::PrimitiveTopology::TriangleList => wgpu::PrimitiveTopology::TriangleList,
+        wgt::PrimitiveTopology::TriangleStrip => wgpu::PrimitiveTopology::TriangleStrip,
+    }
+}
+
+fn rasterization_state(
+    state: &wgt::RasterizationStateDescriptor,
+) -> wgpu::RasterizationStateDescriptor {
+    wgpu::RasterizationStateDescriptor {
+        front_face: match state.front_face {
+            wgt::FrontFace::Ccw => wgpu::FrontFace::Ccw,
+            wgt::FrontFace::Cw => wgpu::FrontFace::Cw,
+        },
+        cull_mode: match state.cull_mode {
+            wgt::CullMode::None => wgpu::CullMode::None,
+            wgt::CullMode::Front => wgpu::CullMode::Front,
+            wgt::CullMode::Back => wgpu::CullMode::Back,
+        },
+        depth_bias: state.depth_bias,
+        depth_bias_slope_scale: state.depth_bias_slope_scale,
+        depth_bias_clamp: state.depth_bias_clamp,
+    }
+}
+
+fn blend_descriptor(descriptor: &wgt::BlendDescriptor) -> wgpu::BlendDescriptor {
+    wgpu::BlendDescriptor {
+        src_factor: blend_factor(descriptor.src_factor),
+        dst_factor: blend_factor(descriptor.dst_factor),
+        operation: match descriptor.operation {
+            wgt::BlendOperation::Add => wgpu::BlendOperation::Add,
+            wgt::BlendOperation::Subtract => wgpu::BlendOperation::Subtract,
+            wgt::BlendOperation::ReverseSubtract => wgpu::BlendOperation::ReverseSubtract,
+            wgt::BlendOperation::Min => wgpu::BlendOperation::Min,
+            wgt::BlendOperation::Max => wgpu::BlendOperation::Max,
+        },
+    }
+}
+
+fn blend_factor(factor: wgt::BlendFactor) -> wgpu::BlendFactor {
+    use wgpu::BlendFactor as F;
+    match factor {
+        wgt::BlendFactor::Zero => F::Zero,
+        wgt::BlendFactor::One => F::One,
+        wgt::BlendFactor::SrcColor => F::SrcColor,
+        wgt::BlendFactor::OneMinusSrcColor => F::OneMinusSrcColor,
+        wgt::BlendFactor::SrcAlpha => F::SrcAlpha,
+        wgt::BlendFactor::OneMinusSrcAlpha => F::OneMinusSrcAlpha,
+        wgt::BlendFactor::DstColor => F::DstColor,
+        wgt::BlendFactor::OneMinusDstColor => F::OneMinusDstColor,
+        wgt::BlendFactor::DstAlpha => F::DstAlpha,
+        wgt::BlendFactor::OneMinusDstAlpha => F::OneMinusDstAlpha,
+        wgt::BlendFactor::SrcAlphaSaturated => F::SrcAlphaSaturated,
+        wgt::BlendFactor::BlendColor => F::BlendColor,
+        wgt::BlendFactor::OneMinusBlendColor => F::OneMinusBlendColor,
+    }
+}
+
+fn color_state(state: &wgt::ColorStateDescriptor) -> wgpu::ColorStateDescriptor {
+    wgpu::ColorStateDescriptor {
+        format: texture_format(state.format),
+        alpha: blend_descriptor(&state.alpha_blend),
+        color: blend_descriptor(&state.color_blend),
+        write_mask: wgpu::ColorWriteFlags::from_bits_truncate(state.write_mask.bits()),
+    }
+}
+
+fn stencil_face(face: &wgt::StencilStateFaceDescriptor) -> wgpu::StencilStateFaceDescriptor {
+    wgpu::StencilStateFaceDescriptor {
+        compare: compare_function(face.compare),
+        fail_op: stencil_operation(face.fail_op),
+        depth_fail_op: stencil_operation(face.depth_fail_op),
+        pass_op: stencil_operation(face.pass_op),
+    }
+}
+
+fn stencil_operation(operation: wgt::StencilOperation) -> wgpu::StencilOperation {
+    use wgpu::StencilOperation as O;
+    match operation {
+        wgt::StencilOperation::Keep => O::Keep,
+        wgt::StencilOperation::Zero => O::Zero,
+        wgt::StencilOperation::Replace => O::Replace,
+        wgt::StencilOperation::Invert => O::Invert,
+        wgt::StencilOperation::IncrementClamp => O::IncrementClamp,
+        wgt::StencilOperation::DecrementClamp => O::DecrementClamp,
+        wgt::StencilOperation::IncrementWrap => O::IncrementWrap,
+        wgt::StencilOperation::DecrementWrap => O::DecrementWrap,
+    }
+}
+
+fn depth_stencil_state(
+    state: &wgt::DepthStencilStateDescriptor,
+) -> wgpu::DepthStencilStateDescriptor {
+    wgpu::DepthStencilStateDescriptor {
+        format: texture_format(state.format),
+        depth_write_enabled: state.depth_write_enabled,
+        depth_compare: compare_function(state.depth_compare),
+        stencil_front: stencil_face(&state.stencil_front),
+        stencil_back: stencil_face(&state.stencil_back),
+        stencil_read_mask: state.stencil_read_mask,
+        stencil_write_mask: state.stencil_write_mask,
+    }
+}
+
+fn vertex_format(format: wgt::VertexFormat) -> wgpu::VertexFormat {
+    use wgpu::VertexFormat as F;
+    match format {
+        wgt::VertexFormat::Uchar2 => F::Uchar2,
+        wgt::VertexFormat::Uchar4 => F::Uchar4,
+        wgt::VertexFormat::Char2 => F::Char2,
+        wgt::VertexFormat::Char4 => F::Char4,
+        wgt::VertexFormat::Uchar2Norm => F::Uchar2Norm,
+        wgt::VertexFormat::Uchar4Norm => F::Uchar4Norm,
+        wgt::VertexFormat::Char2Norm => F::Char2Norm,
+        wgt::VertexFormat::Char4Norm => F::Char4Norm,
+        wgt::VertexFormat::Ushort2 => F::Ushort2,
+        wgt::VertexFormat::Ushort4 => F::Ushort4,
+        wgt::VertexFormat::Short2 => F::Short2,
+        wgt::VertexFormat::Short4 => F::Short4,
+        wgt::VertexFormat::Ushort2Norm => F::Ushort2Norm,
+        wgt::VertexFormat::Ushort4Norm => F::Ushort4Norm,
+        wgt::VertexFormat::Short2Norm => F::Short2Norm,
+        wgt::VertexFormat::Short4Norm => F::Short4Norm,
+        wgt::VertexFormat::Half2 => F::Half2,
+        wgt::VertexFormat::Half4 => F::Half4,
+        wgt::VertexFormat::Float => F::Float,
+        wgt::VertexFormat::Float2 => F::Float2,
+        wgt::VertexFormat::Float3 => F::Float3,
+        wgt::VertexFormat::Float4 => F::Float4,
+        wgt::VertexFormat::Uint => F::Uint,
+        wgt::VertexFormat::Uint2 => F::Uint2,
+        wgt::VertexFormat::Uint3 => F::Uint3,
+        wgt::VertexFormat::Uint4 => F::Uint4,
+        wgt::VertexFormat::Int => F::Int,
+        wgt::VertexFormat::Int2 => F::Int2,
+        wgt::VertexFormat::Int3 => F::Int3,
+        wgt::VertexFormat::Int4 => F::Int4,
+    }
+}
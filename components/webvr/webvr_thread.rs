@@ -16,7 +16,7 @@ use std::collections::hash_map::Entry;
 use std::collections::{HashMap, HashSet};
 use std::{thread, time};
 use webvr_traits::webvr::*;
-use webvr_traits::{WebVRMsg, WebVRResult};
+use webvr_traits::{WebVRHitTestResult, WebVRMsg, WebVRRay, WebVRResult};
 
 /// WebVRThread owns native VRDisplays, handles their life cycle inside Servo and
 /// acts a doorman for untrusted VR requests from DOM Objects. These are the key components
@@ -128,6 +128,15 @@ impl WebVRThread {
                 WebVRMsg::GetGamepads(synced_ids, sender) => {
                     self.handle_get_gamepads(synced_ids, sender);
                 },
+                WebVRMsg::HapticPulse(gamepad_id, intensity, duration, sender) => {
+                    self.handle_haptic_pulse(gamepad_id, intensity, duration, sender);
+                },
+                WebVRMsg::SetFixedFoveation(pipeline_id, display_id, level, sender) => {
+                    self.handle_fixed_foveation(pipeline_id, display_id, level, sender);
+                },
+                WebVRMsg::HitTest(pipeline_id, display_id, ray, sender) => {
+                    self.handle_hit_test(pipeline_id, display_id, ray, sender);
+                },
                 WebVRMsg::Exit => break,
             }
         }
@@ -273,6 +282,85 @@ impl WebVRThread {
         sender.send(Ok(data)).unwrap();
     }
 
+    fn handle_haptic_pulse(
+        &mut self,
+        gamepad_id: u32,
+        intensity: f64,
+        duration: f64,
+        sender: IpcSender<WebVRResult<()>>,
+    ) {
+        let connected = self.service.get_gamepads().iter().any(|gamepad| {
+            let gamepad = gamepad.borrow();
+            gamepad.id() == gamepad_id && gamepad.state().connected
+        });
+        if !connected {
+            sender.send(Err("Gamepad not found".into())).unwrap();
+            return;
+        }
+        // None of the rust-webvr backends expose haptic actuators yet, so the
+        // pulse can't be felt. Script still resolves the pulse promise by
+        // itself once the duration elapses.
+        debug!(
+            "Gamepad {} haptic pulse ({}, {}ms) is not supported by the VR backend",
+            gamepad_id, intensity, duration
+        );
+        sender.send(Ok(())).unwrap();
+    }
+
+    fn handle_fixed_foveation(
+        &mut self,
+        pipeline: PipelineId,
+        display_id: u32,
+        level: Option<f64>,
+        sender: IpcSender<WebVRResult<Option<f64>>>,
+    ) {
+        match self.access_check(pipeline, display_id) {
+            Ok(_) => {
+                // None of the rust-webvr backends can render with fixed foveation yet
+                debug!(
+                    "Display {} fixed foveation ({:?}) is not supported by the VR backend",
+                    display_id, level
+                );
+                sender.send(Ok(None)).unwrap();
+            },
+            Err(msg) => sender.send(Err(msg.into())).unwrap(),
+        }
+    }
+
+    fn handle_hit_test(
+        &mut self,
+        pipeline: PipelineId,
+        display_id: u32,
+        ray: Option<WebVRRay>,
+        sender: IpcSender<WebVRResult<Option<Vec<WebVRHitTestResult>>>>,
+    ) {
+        match self.access_check(pipeline, display_id) {
+            // None of the rust-webvr backends can see the real world, the
+            // test devices hit their floor instead so that hit testing can
+            // be exercised
+            Ok(display) if pref!(dom.webvr.test) => {
+                let floor_height = display
+                    .borrow()
+                    .data()
+                    .stage_parameters
+                    .map_or(0., |stage| -stage.sitting_to_standing_transform[13] as f64);
+                let hits = ray
+                    .and_then(|ray| floor_hit(&ray, floor_height))
+                    .into_iter()
+                    .collect();
+                sender.send(Ok(Some(hits))).unwrap();
+            },
+            Ok(_) => {
+                debug!(
+                    "Display {} hit testing is not supported by the VR backend",
+                    display_id
+                );
+                sender.send(Ok(None)).unwrap();
+            },
+            Err(msg) => sender.send(Err(msg.into())).unwrap(),
+        }
+    }
+
     fn poll_events(&mut self, sender: IpcSender<bool>) {
         loop {
             let events = self.service.poll_events();
@@ -448,3 +536,18 @@ impl WebVRCompositorHandler {
         self.webvr_thread_sender = Some(sender);
     }
 }
+
+/// Where `ray` hits the horizontal floor `floor_height` meters above the
+/// origin of the sitting space, if it points down at it
+fn floor_hit(ray: &WebVRRay, floor_height: f64) -> Option<WebVRHitTestResult> {
+    let [x, y, z] = ray.origin;
+    let [dx, dy, dz] = ray.direction;
+    if dy >= 0. || y < floor_height {
+        return None;
+    }
+    let distance = (floor_height - y) / dy;
+    Some(WebVRHitTestResult {
+        position: [x + dx * distance, floor_height, z + dz * distance],
+        normal: [0., 1., 0.],
+    })
+}
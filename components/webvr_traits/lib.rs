@@ -9,7 +9,7 @@ extern crate serde;
 
 mod webvr_traits;
 
-pub use crate::webvr_traits::{WebVRMsg, WebVRResult};
+pub use crate::webvr_traits::{WebVRHitTestResult, WebVRMsg, WebVRRay, WebVRResult};
 pub use rust_webvr_api as webvr;
 pub use rust_webvr_api::VRDisplayCapabilities as WebVRDisplayCapabilities;
 pub use rust_webvr_api::VRDisplayData as WebVRDisplayData;
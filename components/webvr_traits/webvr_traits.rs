@@ -30,5 +30,41 @@ pub enum WebVRMsg {
         Vec<u32>,
         IpcSender<WebVRResult<Vec<(Option<VRGamepadData>, VRGamepadState)>>>,
     ),
+    // Plays a pulse of the given intensity and duration (in milliseconds) on a gamepad.
+    // Replies once the pulse was handed to the gamepad, or with an error if it is gone.
+    HapticPulse(u32, f64, f64, IpcSender<WebVRResult<()>>),
+    // Sets the fixed foveation level of a display, or just queries it when None.
+    // Replies with the level in effect, or None if the display doesn't support it.
+    SetFixedFoveation(
+        PipelineId,
+        u32,
+        Option<f64>,
+        IpcSender<WebVRResult<Option<f64>>>,
+    ),
+    // Casts a ray into the real world, or just checks hit testing support when None.
+    // Replies with the hits along the ray, or None if the display doesn't support it.
+    HitTest(
+        PipelineId,
+        u32,
+        Option<WebVRRay>,
+        IpcSender<WebVRResult<Option<Vec<WebVRHitTestResult>>>>,
+    ),
     Exit,
 }
+
+/// A ray in the sitting space of a display
+#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+pub struct WebVRRay {
+    pub origin: [f64; 3],
+    /// Normalized
+    pub direction: [f64; 3],
+}
+
+/// A point where a ray hit a real world surface, in the sitting space of
+/// the display
+#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+pub struct WebVRHitTestResult {
+    pub position: [f64; 3],
+    /// The normal of the surface at `position`, normalized
+    pub normal: [f64; 3],
+}
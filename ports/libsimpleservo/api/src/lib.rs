@@ -15,7 +15,7 @@ use servo::compositing::windowing::{
 use servo::embedder_traits::resources::{self, Resource, ResourceReaderMethods};
 use servo::embedder_traits::EmbedderMsg;
 use servo::euclid::{TypedPoint2D, TypedRect, TypedScale, TypedSize2D, TypedVector2D};
-use servo::keyboard_types::{Key, KeyState, KeyboardEvent};
+use servo::keyboard_types::{CompositionEvent, CompositionState, Key, KeyState, KeyboardEvent};
 use servo::msg::constellation_msg::TraversalDirection;
 use servo::script_traits::{TouchEventType, TouchId};
 use servo::servo_config::opts;
@@ -440,6 +440,17 @@ impl ServoGlue {
         self.process_event(WindowEvent::Keyboard(key_event))
     }
 
+    /// Start, update or end the composition of `data` by the input method
+    /// of the platform.
+    pub fn ime_composition(
+        &mut self,
+        state: CompositionState,
+        data: String,
+    ) -> Result<(), &'static str> {
+        let composition_event = CompositionEvent { state, data };
+        self.process_event(WindowEvent::IMEComposition(composition_event))
+    }
+
     fn process_event(&mut self, event: WindowEvent) -> Result<(), &'static str> {
         self.events.push(event);
         if !self.batch_mode {
@@ -539,7 +550,11 @@ impl ServoGlue {
                 EmbedderMsg::ShowIME(..) |
                 EmbedderMsg::HideIME |
                 EmbedderMsg::Panic(..) |
-                EmbedderMsg::ReportProfile(..) => {},
+                EmbedderMsg::ReportProfile(..) |
+                EmbedderMsg::ShowNotification(..) |
+                EmbedderMsg::CloseNotification(..) |
+                EmbedderMsg::UpdateAccessibilityTree(..) |
+                EmbedderMsg::Print => {},
             }
         }
         Ok(())
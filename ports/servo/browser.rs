@@ -7,7 +7,7 @@ use crate::glutin_app::window::{Window, LINE_HEIGHT};
 use euclid::{TypedPoint2D, TypedVector2D};
 use keyboard_types::{Key, KeyboardEvent, Modifiers, ShortcutMatcher};
 use servo::compositing::windowing::{WebRenderDebugOption, WindowEvent};
-use servo::embedder_traits::{EmbedderMsg, FilterPattern};
+use servo::embedder_traits::{EmbedderMsg, FilterPattern, NotificationEvent, SessionHistory};
 use servo::msg::constellation_msg::{TopLevelBrowsingContextId as BrowserId};
 use servo::msg::constellation_msg::TraversalDirection;
 use servo::net_traits::pub_domains::is_reg_domain;
@@ -17,13 +17,14 @@ use servo::servo_config::pref;
 use servo::servo_url::ServoUrl;
 use servo::webrender_api::ScrollLocation;
 use std::env;
-use std::fs::File;
+use std::fs::{self, File};
 use std::io::Write;
 use std::mem;
+use std::path::PathBuf;
 use std::rc::Rc;
 use std::thread;
 use std::time::Duration;
-use tinyfiledialogs::{self, MessageBoxIcon};
+use tinyfiledialogs::{self, MessageBoxIcon, OkCancel};
 
 pub struct Browser {
     current_url: Option<ServoUrl>,
@@ -349,6 +350,9 @@ impl Browser {
                     self.loading_state = Some(LoadingState::Loading);
                 },
                 EmbedderMsg::HistoryChanged(urls, current) => {
+                    if browser_id.is_some() && browser_id == self.browser_id {
+                        save_session_history(&urls, current);
+                    }
                     self.current_url = Some(urls[current].clone());
                 },
                 EmbedderMsg::SetFullscreenState(state) => {
@@ -410,7 +414,42 @@ impl Browser {
                     if let Err(e) = result {
                         error!("Failed to store profile: {}", e);
                     }
-                }
+                },
+                EmbedderMsg::ShowNotification(_id, notification, listener) => {
+                    if !opts::get().headless {
+                        // The dialog doesn't block the event loop, the page
+                        // learns what the user did with it from the listener.
+                        let _ = thread::Builder::new()
+                            .name("display notification".to_owned())
+                            .spawn(move || {
+                                let response = tinyfiledialogs::message_box_ok_cancel(
+                                    &notification.title,
+                                    &notification.body,
+                                    MessageBoxIcon::Info,
+                                    OkCancel::Ok,
+                                );
+                                if let Some(listener) = listener {
+                                    if let OkCancel::Ok = response {
+                                        let _ = listener.send(NotificationEvent::Click);
+                                    }
+                                    let _ = listener.send(NotificationEvent::Close);
+                                }
+                            });
+                    }
+                },
+                EmbedderMsg::CloseNotification(_id) => {
+                    // The dialogs showing the notifications can't be taken
+                    // down by the program.
+                },
+                EmbedderMsg::UpdateAccessibilityTree(_update) => {
+                    // TODO: Expose the tree to the accessibility APIs of the
+                    // platform.
+                },
+                EmbedderMsg::Print => {
+                    if let Some(path) = get_pdf_path() {
+                        self.event_queue.push(WindowEvent::PrintToPDF(path.into()));
+                    }
+                },
             }
         }
     }
@@ -482,6 +521,15 @@ fn get_selected_files(patterns: Vec<FilterPattern>, multiple_files: bool) -> Opt
         .expect("Thread spawning failed")
 }
 
+fn get_pdf_path() -> Option<String> {
+    thread::Builder::new()
+        .name("Print to PDF".to_owned())
+        .spawn(|| tinyfiledialogs::save_file_dialog("Print to PDF", "page.pdf"))
+        .unwrap()
+        .join()
+        .expect("Thread spawning failed")
+}
+
 fn sanitize_url(request: &str) -> Option<ServoUrl> {
     let request = request.trim();
     ServoUrl::parse(&request)
@@ -498,3 +546,37 @@ fn sanitize_url(request: &str) -> Option<ServoUrl> {
             ServoUrl::parse(&url).ok()
         })
 }
+
+/// The file of the config directory where the session history of the browser
+/// is saved, if restoring it on startup is enabled.
+fn session_history_path() -> Option<PathBuf> {
+    if !pref!(shell.restore_session.enabled) {
+        return None;
+    }
+    opts::get().config_dir.as_ref().map(|dir| dir.join("session"))
+}
+
+/// The session history saved by the last run, its first line being the index
+/// of the current entry and the other lines the URLs of the entries.
+pub fn saved_session_history() -> Option<SessionHistory> {
+    let contents = fs::read_to_string(session_history_path()?).ok()?;
+    let mut lines = contents.lines();
+    let current = lines.next()?.parse().ok()?;
+    let urls = lines.map(ServoUrl::parse).collect::<Result<_, _>>().ok()?;
+    Some(SessionHistory { urls, current })
+}
+
+fn save_session_history(urls: &[ServoUrl], current: usize) {
+    let path = match session_history_path() {
+        Some(path) => path,
+        None => return,
+    };
+    let mut contents = current.to_string();
+    for url in urls {
+        contents.push('\n');
+        contents.push_str(url.as_str());
+    }
+    if let Err(e) = fs::write(&path, contents) {
+        warn!("Failed to save the session history to {:?}: {}", path, e);
+    }
+}
@@ -0,0 +1,95 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! The clipboards given to Servo by the windows.
+
+use clipboard::{ClipboardContext, ClipboardProvider};
+use servo::embedder_traits::EmbedderClipboard;
+use std::sync::mpsc::{self, Sender};
+use std::thread;
+
+enum ClipboardRequest {
+    GetText(Sender<Result<String, String>>),
+    SetText(String, Sender<Result<(), String>>),
+}
+
+/// The clipboard of the system.
+///
+/// The clipboard contexts aren't `Send` on every platform, so the context
+/// lives on a thread of its own. This thread also keeps the ownership of the
+/// clipboard on the platforms where the contents go away with their owner.
+pub struct SystemClipboard {
+    sender: Sender<ClipboardRequest>,
+}
+
+impl SystemClipboard {
+    pub fn new() -> Result<SystemClipboard, String> {
+        let (sender, receiver) = mpsc::channel();
+        let (init_sender, init_receiver) = mpsc::channel();
+        thread::Builder::new()
+            .name("Clipboard".to_owned())
+            .spawn(move || {
+                let mut context: ClipboardContext = match ClipboardProvider::new() {
+                    Ok(context) => context,
+                    Err(e) => {
+                        let _ = init_sender.send(Err(e.to_string()));
+                        return;
+                    },
+                };
+                let _ = init_sender.send(Ok(()));
+                for request in receiver {
+                    match request {
+                        ClipboardRequest::GetText(sender) => {
+                            let _ = sender.send(context.get_contents().map_err(|e| e.to_string()));
+                        },
+                        ClipboardRequest::SetText(text, sender) => {
+                            let _ = sender.send(context.set_contents(text).map_err(|e| e.to_string()));
+                        },
+                    }
+                }
+            })
+            .expect("Thread spawning failed");
+        match init_receiver.recv() {
+            Ok(Ok(())) => Ok(SystemClipboard { sender }),
+            Ok(Err(e)) => Err(e),
+            Err(e) => Err(e.to_string()),
+        }
+    }
+}
+
+impl EmbedderClipboard for SystemClipboard {
+    fn get_text(&mut self) -> Result<String, String> {
+        let (sender, receiver) = mpsc::channel();
+        self.sender
+            .send(ClipboardRequest::GetText(sender))
+            .map_err(|e| e.to_string())?;
+        receiver.recv().map_err(|e| e.to_string())?
+    }
+
+    fn set_text(&mut self, text: String) -> Result<(), String> {
+        let (sender, receiver) = mpsc::channel();
+        self.sender
+            .send(ClipboardRequest::SetText(text, sender))
+            .map_err(|e| e.to_string())?;
+        receiver.recv().map_err(|e| e.to_string())?
+    }
+}
+
+/// A clipboard of its own, for the headless windows, which don't share the
+/// clipboard of the system.
+#[derive(Default)]
+pub struct HeadlessClipboard {
+    text: String,
+}
+
+impl EmbedderClipboard for HeadlessClipboard {
+    fn get_text(&mut self) -> Result<String, String> {
+        Ok(self.text.clone())
+    }
+
+    fn set_text(&mut self, text: String) -> Result<(), String> {
+        self.text = text;
+        Ok(())
+    }
+}
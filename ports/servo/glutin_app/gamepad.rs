@@ -0,0 +1,127 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Gamepad polling using gilrs.
+
+use gilrs::{Axis, Button, EventType, Gilrs};
+use servo::embedder_traits::EventLoopWaker;
+use servo::script_traits::{GamepadEvent, GamepadIndex, GamepadUpdateType};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+use std::time::Duration;
+
+/// How often gilrs is polled, fast enough to catch every frame at 120Hz.
+const POLL_INTERVAL: Duration = Duration::from_millis(8);
+
+/// Starts polling the gamepads on their own thread, waking `waker` whenever
+/// there are events to read from the returned receiver.
+pub fn start_polling(waker: Box<dyn EventLoopWaker>) -> Receiver<GamepadEvent> {
+    let (sender, receiver) = mpsc::channel();
+    thread::Builder::new()
+        .name("GamepadPoller".to_owned())
+        .spawn(move || {
+            // gilrs isn't Send on every platform, so it's created on the
+            // thread that uses it.
+            let gilrs = match Gilrs::new() {
+                Ok(gilrs) => gilrs,
+                Err(e) => return warn!("Failed to start polling gamepads ({}).", e),
+            };
+            poll(gilrs, sender, waker);
+        })
+        .expect("Thread spawning failed");
+    receiver
+}
+
+fn poll(mut gilrs: Gilrs, sender: Sender<GamepadEvent>, waker: Box<dyn EventLoopWaker>) {
+    // The gamepads plugged in before Servo started don't get connection
+    // events.
+    let mut events: Vec<_> = gilrs
+        .gamepads()
+        .map(|(id, gamepad)| {
+            GamepadEvent::Connected(GamepadIndex(id.into()), gamepad.name().into())
+        })
+        .collect();
+    loop {
+        while let Some(event) = gilrs.next_event() {
+            let index = GamepadIndex(event.id.into());
+            let event = match event.event {
+                EventType::Connected => {
+                    let name = gilrs.gamepad(event.id).name().into();
+                    GamepadEvent::Connected(index, name)
+                },
+                EventType::Disconnected => GamepadEvent::Disconnected(index),
+                // Digital buttons also report their changes as 0 or 1.
+                EventType::ButtonChanged(button, value, _) => match standard_button(button) {
+                    Some(button) => GamepadEvent::Updated(
+                        index,
+                        GamepadUpdateType::Button(button, value as f64),
+                    ),
+                    None => continue,
+                },
+                EventType::AxisChanged(axis, value, _) => match standard_axis(axis) {
+                    Some((axis, direction)) => GamepadEvent::Updated(
+                        index,
+                        GamepadUpdateType::Axis(axis, direction * value as f64),
+                    ),
+                    None => continue,
+                },
+                _ => continue,
+            };
+            events.push(event);
+        }
+
+        if !events.is_empty() {
+            for event in events.drain(..) {
+                if sender.send(event).is_err() {
+                    // The window is gone.
+                    return;
+                }
+            }
+            waker.wake();
+        }
+        thread::sleep(POLL_INTERVAL);
+    }
+}
+
+/// The index of `button` in the standard gamepad layout.
+///
+/// https://w3c.github.io/gamepad/#remapping
+fn standard_button(button: Button) -> Option<usize> {
+    let index = match button {
+        Button::South => 0,
+        Button::East => 1,
+        Button::West => 2,
+        Button::North => 3,
+        Button::LeftTrigger => 4,
+        Button::RightTrigger => 5,
+        Button::LeftTrigger2 => 6,
+        Button::RightTrigger2 => 7,
+        Button::Select => 8,
+        Button::Start => 9,
+        Button::LeftThumb => 10,
+        Button::RightThumb => 11,
+        Button::DPadUp => 12,
+        Button::DPadDown => 13,
+        Button::DPadLeft => 14,
+        Button::DPadRight => 15,
+        Button::Mode => 16,
+        _ => return None,
+    };
+    Some(index)
+}
+
+/// The index of `axis` in the standard gamepad layout, and the direction
+/// its values have to be multiplied by.
+///
+/// gilrs reports up as positive on the vertical axes of the sticks, the
+/// standard layout as negative.
+fn standard_axis(axis: Axis) -> Option<(usize, f64)> {
+    match axis {
+        Axis::LeftStickX => Some((0, 1.)),
+        Axis::LeftStickY => Some((1, -1.)),
+        Axis::RightStickX => Some((2, 1.)),
+        Axis::RightStickY => Some((3, -1.)),
+        _ => None,
+    }
+}
@@ -4,6 +4,8 @@
 
 //! A simple application that uses glutin to open a window for Servo to display in.
 
+pub mod clipboard;
+pub mod gamepad;
 pub mod keyutils;
 pub mod window;
 
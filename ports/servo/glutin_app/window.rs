@@ -13,8 +13,8 @@ use keyboard_types::{Key, KeyboardEvent, KeyState};
 use rust_webvr::GlWindowVRService;
 use servo::compositing::windowing::{AnimationState, MouseWindowEvent, WindowEvent};
 use servo::compositing::windowing::{EmbedderCoordinates, WindowMethods};
-use servo::embedder_traits::{Cursor, EventLoopWaker};
-use servo::script_traits::TouchEventType;
+use servo::embedder_traits::{Cursor, EmbedderClipboard, EventLoopWaker};
+use servo::script_traits::{GamepadEvent, TouchEventType};
 use servo::servo_config::{opts, pref};
 use servo::servo_geometry::DeviceIndependentPixel;
 use servo::style_traits::DevicePixel;
@@ -29,8 +29,11 @@ use std::os::raw::c_void;
 use std::ptr;
 use std::rc::Rc;
 use std::sync::Arc;
+use std::sync::mpsc::Receiver;
 use std::thread;
 use std::time;
+use super::clipboard::{HeadlessClipboard, SystemClipboard};
+use super::gamepad;
 use super::keyutils::keyboard_event_from_winit;
 #[cfg(target_os = "windows")]
 use winapi;
@@ -154,6 +157,7 @@ pub struct Window {
     fullscreen: Cell<bool>,
     gl: Rc<dyn gl::Gl>,
     suspended: Cell<bool>,
+    gamepad_events: Option<Receiver<GamepadEvent>>,
 }
 
 #[cfg(not(target_os = "windows"))]
@@ -268,7 +272,7 @@ impl Window {
         gl.clear(gl::COLOR_BUFFER_BIT);
         gl.finish();
 
-        let window = Window {
+        let mut window = Window {
             kind: window_kind,
             event_queue: RefCell::new(vec![]),
             mouse_down_button: Cell::new(None),
@@ -281,15 +285,24 @@ impl Window {
             inner_size: Cell::new(inner_size),
             screen_size,
             suspended: Cell::new(false),
+            gamepad_events: None,
         };
 
+        if pref!(dom.gamepad.enabled) && !opts::get().headless {
+            window.gamepad_events = Some(gamepad::start_polling(window.create_event_loop_waker()));
+        }
+
         window.present();
 
         Rc::new(window)
     }
 
     pub fn get_events(&self) -> Vec<WindowEvent> {
-        mem::replace(&mut *self.event_queue.borrow_mut(), Vec::new())
+        let mut events = mem::replace(&mut *self.event_queue.borrow_mut(), Vec::new());
+        if let Some(ref gamepad_events) = self.gamepad_events {
+            events.extend(gamepad_events.try_iter().map(WindowEvent::Gamepad));
+        }
+        events
     }
 
     pub fn page_height(&self) -> f32 {
@@ -782,6 +795,19 @@ impl WindowMethods for Window {
         Box::new(GlutinEventLoopWaker::new(&self))
     }
 
+    fn create_clipboard(&self) -> Option<Box<dyn EmbedderClipboard>> {
+        match self.kind {
+            WindowKind::Window(..) => match SystemClipboard::new() {
+                Ok(clipboard) => Some(Box::new(clipboard)),
+                Err(e) => {
+                    warn!("Error creating clipboard context ({})", e);
+                    None
+                },
+            },
+            WindowKind::Headless(..) => Some(Box::new(HeadlessClipboard::default())),
+        }
+    }
+
     fn set_animation_state(&self, state: AnimationState) {
         self.animation_state.set(state);
     }
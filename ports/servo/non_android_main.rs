@@ -136,11 +136,23 @@ pub fn main() {
     };
     let blank_url = ServoUrl::parse("about:blank").ok();
 
-    let target_url = cmdline_url.or(pref_url).or(blank_url).unwrap();
-
     let mut servo = Servo::new(window.clone());
     let browser_id = BrowserId::new();
-    servo.handle_events(vec![WindowEvent::NewBrowser(target_url, browser_id)]);
+
+    // Restore the session of the last run, unless a url was provided.
+    let session_history = if cmdline_url.is_none() {
+        browser::saved_session_history()
+    } else {
+        None
+    };
+    let event = match session_history {
+        Some(session_history) => WindowEvent::RestoreBrowser(session_history, browser_id),
+        None => {
+            let target_url = cmdline_url.or(pref_url).or(blank_url).unwrap();
+            WindowEvent::NewBrowser(target_url, browser_id)
+        },
+    };
+    servo.handle_events(vec![event]);
 
     servo.setup_logging();
 
@@ -3,16 +3,17 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
 use ipc_channel::ipc;
+use profile::chrome_trace::ChromeTrace;
 use profile::time;
 use profile_traits::ipc as ProfiledIpc;
-use profile_traits::time::{ProfilerCategory, ProfilerData, ProfilerMsg};
-use servo_config::opts::OutputOptions;
-use std::thread;
+use profile_traits::time::{ProfilerCategory, ProfilerData, ProfilerMsg, ProfilerThread};
+use servo_config::opts::{OutputOptions, TraceFormat};
 use std::time::Duration;
+use std::{env, fs, thread};
 
 #[test]
 fn time_profiler_smoke_test() {
-    let chan = time::Profiler::create(&None, None);
+    let chan = time::Profiler::create(&None, None, TraceFormat::Html);
     assert!(true, "Can create the profiler thread");
 
     let (ipcchan, _ipcport) = ipc::channel().unwrap();
@@ -45,7 +46,7 @@ fn time_profiler_stats_test() {
 
 #[test]
 fn channel_profiler_test() {
-    let chan = time::Profiler::create(&Some(OutputOptions::Stdout(5.0)), None);
+    let chan = time::Profiler::create(&Some(OutputOptions::Stdout(5.0)), None, TraceFormat::Html);
     let (profiled_sender, profiled_receiver) = ProfiledIpc::channel(chan.clone()).unwrap();
     thread::spawn(move || {
         thread::sleep(Duration::from_secs(2));
@@ -70,7 +71,7 @@ fn channel_profiler_test() {
 
 #[test]
 fn bytes_channel_profiler_test() {
-    let chan = time::Profiler::create(&Some(OutputOptions::Stdout(5.0)), None);
+    let chan = time::Profiler::create(&Some(OutputOptions::Stdout(5.0)), None, TraceFormat::Html);
     let (profiled_sender, profiled_receiver) = ProfiledIpc::bytes_channel(chan.clone()).unwrap();
     thread::spawn(move || {
         thread::sleep(Duration::from_secs(2));
@@ -93,6 +94,41 @@ fn bytes_channel_profiler_test() {
     };
 }
 
+#[test]
+fn chrome_trace_test() {
+    let path = env::temp_dir().join("servo-chrome-trace-test.json");
+    let layout = ProfilerThread {
+        process_id: 1,
+        name: "LayoutThread".to_owned(),
+    };
+    let compositor = ProfilerThread {
+        process_id: 2,
+        name: "Compositor".to_owned(),
+    };
+    {
+        let mut trace = ChromeTrace::new(&path).unwrap();
+        trace.write_one(
+            &(ProfilerCategory::LayoutPerform, None),
+            (1000, 3000),
+            Some(&layout),
+        );
+        trace.write_one(
+            &(ProfilerCategory::TimeToFirstPaint, None),
+            (2000, 5000),
+            Some(&compositor),
+        );
+    }
+    let trace = fs::read_to_string(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+
+    assert!(trace.starts_with("[\n"));
+    assert!(trace.ends_with("\n]\n"));
+    assert!(trace.contains(r#""ph":"M","ts":0.0,"pid":1,"tid":0,"args":{"name":"LayoutThread"}"#));
+    assert!(trace.contains(r#""cat":"layout","ph":"X","ts":1.0,"dur":2.0,"pid":1,"tid":0"#));
+    assert!(trace.contains(r#""ph":"b","ts":2.0,"pid":2,"tid":0,"id":0"#));
+    assert!(trace.contains(r#""ph":"e","ts":5.0,"pid":2,"tid":0,"id":0"#));
+}
+
 #[cfg(debug_assertions)]
 #[test]
 #[should_panic]
@@ -0,0 +1,62 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use script::test::accessibility::{
+    aria_role, is_named_from_content, remove_unreachable_nodes, AccessibilityNode,
+    AccessibilityNodeId, AccessibilityRole,
+};
+use std::collections::HashMap;
+
+fn node(role: AccessibilityRole, children: &[u64]) -> AccessibilityNode {
+    AccessibilityNode {
+        role,
+        name: None,
+        value: None,
+        level: None,
+        bounds: None,
+        focusable: false,
+        disabled: false,
+        checked: None,
+        children: children.iter().map(|&id| AccessibilityNodeId(id)).collect(),
+    }
+}
+
+#[test]
+fn first_known_aria_role_is_used() {
+    assert_eq!(aria_role("button"), Some(AccessibilityRole::Button));
+    assert_eq!(
+        aria_role("  unknown switch checkbox "),
+        Some(AccessibilityRole::CheckBox)
+    );
+    assert_eq!(aria_role("unknown"), None);
+    assert_eq!(aria_role(""), None);
+}
+
+#[test]
+fn only_some_roles_are_named_from_content() {
+    assert!(is_named_from_content(AccessibilityRole::Button));
+    assert!(is_named_from_content(AccessibilityRole::Heading));
+    assert!(!is_named_from_content(AccessibilityRole::TextInput));
+    assert!(!is_named_from_content(AccessibilityRole::Document));
+}
+
+#[test]
+fn nodes_removed_from_the_tree_are_forgotten() {
+    let mut nodes: HashMap<_, _> = vec![
+        (1, node(AccessibilityRole::Document, &[2])),
+        (2, node(AccessibilityRole::List, &[3])),
+        (3, node(AccessibilityRole::ListItem, &[])),
+        // A list item which was removed from the list, and its text.
+        (4, node(AccessibilityRole::ListItem, &[5])),
+        (5, node(AccessibilityRole::StaticText, &[])),
+    ]
+    .into_iter()
+    .map(|(id, node)| (AccessibilityNodeId(id), node))
+    .collect();
+
+    remove_unreachable_nodes(&mut nodes, AccessibilityNodeId(1));
+    let mut ids: Vec<u64> = nodes.keys().map(|id| id.0).collect();
+    ids.sort();
+    assert_eq!(ids, vec![1, 2, 3]);
+}
@@ -0,0 +1,93 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use script::test::animationeffect::{FillMode, Phase, PlaybackDirection, Timing};
+
+fn timing(delay: f64, duration: f64, iterations: f64) -> Timing {
+    let mut timing = Timing::new(Some(duration));
+    timing.delay = delay;
+    timing.iterations = iterations;
+    timing
+}
+
+/// The progress at `local_time`, rounded to cover the imprecision of solving
+/// the easing function
+fn progress(timing: &Timing, local_time: f64) -> Option<(f64, f64)> {
+    let (progress, iteration) = timing.progress(local_time, 1.)?;
+    Some(((progress * 1000.).round() / 1000., iteration))
+}
+
+#[test]
+fn end_time_adds_up_delays_and_iterations() {
+    let mut timing = timing(100., 1000., 2.);
+    assert_eq!(timing.active_duration(), 2000.);
+    assert_eq!(timing.end_time(), 2100.);
+    timing.end_delay = -3000.;
+    assert_eq!(timing.end_time(), 0.);
+    assert_eq!(Timing::new(None).end_time(), 0.);
+}
+
+#[test]
+fn phases_follow_the_direction_of_playback() {
+    let timing = timing(100., 1000., 1.);
+    assert_eq!(timing.phase(50., 1.), Phase::Before);
+    assert_eq!(timing.phase(100., 1.), Phase::Active);
+    assert_eq!(timing.phase(100., -1.), Phase::Before);
+    assert_eq!(timing.phase(600., 1.), Phase::Active);
+    assert_eq!(timing.phase(1100., 1.), Phase::After);
+    assert_eq!(timing.phase(1100., -1.), Phase::Active);
+}
+
+#[test]
+fn active_time_depends_on_the_fill_mode() {
+    let mut timing = timing(100., 1000., 1.);
+    assert_eq!(timing.active_time(50., 1.), None);
+    assert_eq!(timing.active_time(600., 1.), Some(500.));
+    assert_eq!(timing.active_time(2000., 1.), None);
+
+    timing.fill = FillMode::Both;
+    assert_eq!(timing.active_time(50., 1.), Some(0.));
+    assert_eq!(timing.active_time(2000., 1.), Some(1000.));
+
+    timing.fill = FillMode::Forwards;
+    assert_eq!(timing.active_time(50., 1.), None);
+    assert_eq!(timing.active_time(2000., 1.), Some(1000.));
+}
+
+#[test]
+fn progress_counts_iterations() {
+    let timing = timing(0., 1000., 3.);
+    assert_eq!(progress(&timing, 0.), Some((0., 0.)));
+    assert_eq!(progress(&timing, 250.), Some((0.25, 0.)));
+    assert_eq!(progress(&timing, 1500.), Some((0.5, 1.)));
+    assert_eq!(progress(&timing, 3000.), None);
+
+    let mut filled = timing.clone();
+    filled.fill = FillMode::Forwards;
+    // the last iteration ends at the end, not at the start of another one
+    assert_eq!(progress(&filled, 3000.), Some((1., 2.)));
+}
+
+#[test]
+fn progress_follows_the_playback_direction() {
+    let mut timing = timing(0., 1000., 2.);
+    timing.direction = PlaybackDirection::Reverse;
+    assert_eq!(progress(&timing, 250.), Some((0.75, 0.)));
+
+    timing.direction = PlaybackDirection::Alternate;
+    assert_eq!(progress(&timing, 250.), Some((0.25, 0.)));
+    assert_eq!(progress(&timing, 1250.), Some((0.75, 1.)));
+
+    timing.direction = PlaybackDirection::Alternate_reverse;
+    assert_eq!(progress(&timing, 250.), Some((0.75, 0.)));
+    assert_eq!(progress(&timing, 1250.), Some((0.25, 1.)));
+}
+
+#[test]
+fn zero_duration_effects_jump_to_the_end() {
+    let mut timing = timing(0., 0., 1.);
+    timing.fill = FillMode::Both;
+    assert_eq!(progress(&timing, -1.), Some((0., 0.)));
+    assert_eq!(progress(&timing, 0.), Some((1., 0.)));
+}
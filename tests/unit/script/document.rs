@@ -0,0 +1,35 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use script::test::document::AnimationFrameSuspension;
+
+#[test]
+fn animation_frames_dont_run_during_immersive_sessions() {
+    let mut suspension = AnimationFrameSuspension::default();
+    assert!(suspension.can_run());
+
+    assert!(suspension.suspend());
+    assert!(!suspension.can_run());
+    // a second session doesn't suspend them any further
+    assert!(!suspension.suspend());
+    assert!(!suspension.can_run());
+}
+
+#[test]
+fn animation_frames_requested_meanwhile_run_once_the_session_ends() {
+    let mut suspension = AnimationFrameSuspension::default();
+    suspension.suspend();
+    assert!(suspension.resume(true));
+    assert!(suspension.can_run());
+    // resuming the running callbacks doesn't schedule them again
+    assert!(!suspension.resume(true));
+}
+
+#[test]
+fn resuming_without_callbacks_schedules_nothing() {
+    let mut suspension = AnimationFrameSuspension::default();
+    suspension.suspend();
+    assert!(!suspension.resume(false));
+    assert!(suspension.can_run());
+}
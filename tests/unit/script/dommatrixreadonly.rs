@@ -0,0 +1,248 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use euclid::{Angle, Point3D, Transform3D};
+use script::test::dommatrixreadonly::{
+    column_major_to_transform3d, entries_to_matrix, matrices_equal, matrix_to_string,
+    multiply_matrices, number_to_string, transform3d_to_column_major, DOMMatrixReadOnly,
+};
+use std::f64::consts::FRAC_PI_2;
+use std::f64::{INFINITY, MAX, NAN};
+
+fn assert_approx_eq(actual: f64, expected: f64) {
+    assert!(
+        (actual - expected).abs() < 1e-9,
+        "expected {}, got {}",
+        expected,
+        actual
+    );
+}
+
+#[test]
+fn perspective_maps_near_plane_to_minus_one() {
+    let projection = DOMMatrixReadOnly::perspective(FRAC_PI_2, 1.5, 0.1, 100.);
+    let near = projection
+        .transform_point3d(&Point3D::new(0.3, -0.2, -0.1))
+        .unwrap();
+    assert_approx_eq(near.z, -1.);
+}
+
+#[test]
+fn perspective_maps_far_plane_to_one() {
+    let projection = DOMMatrixReadOnly::perspective(FRAC_PI_2, 1.5, 0.1, 100.);
+    let far = projection
+        .transform_point3d(&Point3D::new(0., 0., -100.))
+        .unwrap();
+    assert_approx_eq(far.z, 1.);
+}
+
+#[test]
+fn perspective_applies_field_of_view_and_aspect() {
+    // with a 90 degree field of view, the top of the frustum is as far
+    // above the center as the point is in front of the camera
+    let projection = DOMMatrixReadOnly::perspective(FRAC_PI_2, 2., 1., 10.);
+    let corner = projection
+        .transform_point3d(&Point3D::new(4., 2., -2.))
+        .unwrap();
+    assert_approx_eq(corner.x, 1.);
+    assert_approx_eq(corner.y, 1.);
+}
+
+#[test]
+fn orthographic_maps_box_to_clip_space() {
+    let projection = DOMMatrixReadOnly::orthographic(-2., 2., -1., 1., 0.5, 10.);
+    let near = projection
+        .transform_point3d(&Point3D::new(-2., 1., -0.5))
+        .unwrap();
+    assert_approx_eq(near.x, -1.);
+    assert_approx_eq(near.y, 1.);
+    assert_approx_eq(near.z, -1.);
+    let far = projection
+        .transform_point3d(&Point3D::new(2., -1., -10.))
+        .unwrap();
+    assert_approx_eq(far.x, 1.);
+    assert_approx_eq(far.y, -1.);
+    assert_approx_eq(far.z, 1.);
+}
+
+fn matrix(entries: &[f64]) -> (bool, Transform3D<f64>) {
+    entries_to_matrix(entries).unwrap()
+}
+
+#[test]
+fn matrices_with_the_same_entries_are_equal() {
+    let (a_is_2d, a) = matrix(&[1., 2., 3., 4., 5., 6.]);
+    let (b_is_2d, b) = matrix(&[1., 2., 3., 4., 5., 6.]);
+    assert!(matrices_equal((a_is_2d, &a), (b_is_2d, &b)));
+
+    let (c_is_2d, c) = matrix(&[1., 2., 3., 4., 5., 7.]);
+    assert!(!matrices_equal((a_is_2d, &a), (c_is_2d, &c)));
+}
+
+#[test]
+fn matrices_with_different_is_2d_are_not_equal() {
+    let (a_is_2d, a) = matrix(&[1., 0., 0., 1., 0., 0.]);
+    let (b_is_2d, b) = matrix(&[
+        1., 0., 0., 0., 0., 1., 0., 0., 0., 0., 1., 0., 0., 0., 0., 1.,
+    ]);
+    assert_eq!(a, b);
+    assert!(!matrices_equal((a_is_2d, &a), (b_is_2d, &b)));
+}
+
+#[test]
+fn matrices_with_nan_are_never_equal() {
+    let (is_2d, nan) = matrix(&[NAN; 16]);
+    assert!(!matrices_equal((is_2d, &nan), (is_2d, &nan)));
+}
+
+/// A deterministic stream of matrices with arbitrary entries, so these tests
+/// cover many matrices without pulling in a property testing crate
+fn arbitrary_matrices() -> Vec<[f64; 16]> {
+    let mut state: u64 = 0x2545_f491_4f6c_dd1d;
+    let mut next = move || {
+        state = state
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        (state >> 11) as f64 / (1u64 << 53) as f64 * 200. - 100.
+    };
+    (0..100)
+        .map(|_| {
+            let mut entries = [0.; 16];
+            for entry in entries.iter_mut() {
+                *entry = next();
+            }
+            entries
+        })
+        .collect()
+}
+
+#[test]
+fn column_major_conversions_are_inverses() {
+    for entries in arbitrary_matrices() {
+        let matrix = column_major_to_transform3d(&entries);
+        assert_eq!(transform3d_to_column_major(&matrix), entries);
+        assert_eq!(
+            column_major_to_transform3d(&transform3d_to_column_major(&matrix)),
+            matrix
+        );
+    }
+}
+
+#[test]
+fn column_major_translation_is_in_the_last_column() {
+    let translation = Transform3D::<f64>::create_translation(1., 2., 3.);
+    let entries = transform3d_to_column_major(&translation);
+    assert_eq!(&entries[12..15], &[1., 2., 3.]);
+
+    // Transforming a point must agree with multiplying the column-major matrix
+    // against a column vector
+    let point = Point3D::new(4., 5., 6.);
+    for mut entries in arbitrary_matrices() {
+        // keep w at 1 so that the point isn't clipped
+        entries[3] = 0.;
+        entries[7] = 0.;
+        entries[11] = 0.;
+        entries[15] = 1.;
+        let matrix = column_major_to_transform3d(&entries);
+        let row = |i: usize| {
+            entries[i] * point.x +
+                entries[4 + i] * point.y +
+                entries[8 + i] * point.z +
+                entries[12 + i]
+        };
+        let actual = matrix.transform_point3d(&point).unwrap();
+        assert_approx_eq(actual.x, row(0));
+        assert_approx_eq(actual.y, row(1));
+        assert_approx_eq(actual.z, row(2));
+    }
+}
+
+#[test]
+fn singular_matrix_is_not_invertible() {
+    let matrix = DOMMatrixReadOnly::new_inherited(false, Transform3D::create_scale(2., 0., 1.));
+    assert_eq!(matrix.determinant(), 0.);
+    assert!(!matrix.is_invertible(1e-9));
+}
+
+#[test]
+fn rotation_is_invertible() {
+    let rotation = Transform3D::create_rotation(0., 1., 0., Angle::radians(0.7));
+    let matrix = DOMMatrixReadOnly::new_inherited(false, rotation);
+    assert_approx_eq(matrix.determinant(), 1.);
+    assert!(matrix.is_invertible(1e-9));
+}
+
+#[test]
+fn nan_matrix_is_not_invertible() {
+    let mut entries = [0.; 16];
+    entries[0] = NAN;
+    let matrix = DOMMatrixReadOnly::new_inherited(false, column_major_to_transform3d(&entries));
+    assert!(!matrix.is_invertible(1e-9));
+}
+
+#[test]
+fn number_to_string_matches_ecmascript() {
+    assert_eq!(number_to_string(-0.), "0");
+    assert_eq!(number_to_string(1. / 3.), "0.3333333333333333");
+    assert_eq!(number_to_string(1. / 300000.), "0.0000033333333333333333");
+    assert_eq!(number_to_string(1. / 300000000.), "3.3333333333333334e-9");
+    assert_eq!(number_to_string(100000. + 1. / 3.), "100000.33333333333");
+    assert_eq!(number_to_string(2f64.powi(53) + 2.), "9007199254740994");
+    assert_eq!(number_to_string(1e21), "1e+21");
+    assert_eq!(number_to_string(MAX), "1.7976931348623157e+308");
+    assert_eq!(number_to_string(-2.5), "-2.5");
+}
+
+#[test]
+fn matrix_to_string_serializes_2d_and_3d_matrices() {
+    let (is_2d, matrix) = entries_to_matrix(&[1., 2., 3., 4., 5., 6.]).unwrap();
+    assert_eq!(
+        String::from(matrix_to_string(is_2d, &matrix).unwrap()),
+        "matrix(1, 2, 3, 4, 5, 6)"
+    );
+    let entries: Vec<f64> = (1..17).map(f64::from).collect();
+    let (is_2d, matrix) = entries_to_matrix(&entries).unwrap();
+    assert_eq!(
+        String::from(matrix_to_string(is_2d, &matrix).unwrap()),
+        "matrix3d(1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16)"
+    );
+}
+
+#[test]
+fn matrix_to_string_rejects_non_finite_elements() {
+    let (is_2d, matrix) = entries_to_matrix(&[1., 0., 0., 1., INFINITY, 0.]).unwrap();
+    assert!(is_2d);
+    assert!(matrix_to_string(is_2d, &matrix).is_err());
+    let (is_2d, matrix) = entries_to_matrix(&[1., 0., 0., 1., 0., NAN]).unwrap();
+    assert!(matrix_to_string(is_2d, &matrix).is_err());
+}
+
+#[test]
+fn determinant_of_non_finite_matrix_is_not_finite() {
+    let (_, matrix) = entries_to_matrix(&[INFINITY, 0., 0., 1., 0., 0.]).unwrap();
+    assert!(!matrix.determinant().is_finite());
+}
+
+#[test]
+fn multiplication_order() {
+    let t = Transform3D::create_translation(1., 0., 0.);
+    let r = Transform3D::create_rotation(0., 0., 1., Angle::degrees(90.));
+    let origin = Point3D::origin();
+
+    // t · r rotates first, which leaves the origin in place
+    let p = multiply_matrices(&t, &r)
+        .transform_point3d(&origin)
+        .unwrap();
+    assert_approx_eq(p.x, 1.);
+    assert_approx_eq(p.y, 0.);
+    assert_approx_eq(p.z, 0.);
+
+    // r · t translates first, then rotates the translated origin
+    let p = multiply_matrices(&r, &t)
+        .transform_point3d(&origin)
+        .unwrap();
+    assert_approx_eq(p.x, 0.);
+    assert_approx_eq(p.y, 1.);
+    assert_approx_eq(p.z, 0.);
+}
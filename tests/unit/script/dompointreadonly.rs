@@ -0,0 +1,64 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use euclid::{Point3D, Vector3D};
+use script::test::dompointreadonly::DOMPointReadOnly;
+
+#[test]
+fn position_converts_to_point() {
+    let position = DOMPointReadOnly::new_inherited(1., -2., 3., 1.);
+    assert!(!position.is_vector());
+    assert_eq!(position.as_point3d(), Point3D::new(1., -2., 3.));
+}
+
+#[test]
+fn homogeneous_position_is_divided_by_w() {
+    let position = DOMPointReadOnly::new_inherited(2., 4., -6., 2.);
+    assert_eq!(position.as_point3d(), Point3D::new(1., 2., -3.));
+}
+
+#[test]
+fn direction_converts_to_vector() {
+    let direction = DOMPointReadOnly::new_inherited(0., 0., -1., 0.);
+    assert!(direction.is_vector());
+    assert_eq!(direction.as_vector3d(), Vector3D::new(0., 0., -1.));
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic]
+fn position_does_not_convert_to_vector() {
+    DOMPointReadOnly::new_inherited(1., 2., 3., 1.).as_vector3d();
+}
+
+#[test]
+fn vectors_add_up_to_a_vector() {
+    let a = DOMPointReadOnly::new_inherited(1., 2., 3., 0.);
+    let b = DOMPointReadOnly::new_inherited(-1., 0.5, 1., 0.);
+    assert_eq!(a.add(&b), [0., 2.5, 4., 0.]);
+}
+
+#[test]
+fn vector_moves_homogeneous_position() {
+    let position = DOMPointReadOnly::new_inherited(2., 4., 6., 2.);
+    let direction = DOMPointReadOnly::new_inherited(1., 0., -1., 0.);
+    assert_eq!(position.add(&direction), [4., 4., 4., 2.]);
+    assert_eq!(direction.add(&position), [4., 4., 4., 2.]);
+    assert_eq!(position.sub(&direction), [0., 4., 8., 2.]);
+}
+
+#[test]
+fn positions_subtract_to_a_vector() {
+    let a = DOMPointReadOnly::new_inherited(2., 4., 6., 2.);
+    let b = DOMPointReadOnly::new_inherited(1., 1., 1., 1.);
+    assert_eq!(a.sub(&b), [0., 1., 2., 0.]);
+}
+
+#[test]
+fn scale_keeps_w() {
+    let direction = DOMPointReadOnly::new_inherited(1., -2., 3., 0.);
+    assert_eq!(direction.scale(2.), [2., -4., 6., 0.]);
+    let position = DOMPointReadOnly::new_inherited(1., -2., 3., 1.);
+    assert_eq!(position.scale(0.5), [0.5, -1., 1.5, 1.]);
+}
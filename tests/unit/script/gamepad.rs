@@ -0,0 +1,21 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use script::test::gamepad::sync_axes;
+
+#[test]
+fn small_axis_values_are_not_clamped() {
+    // a thumbstick resting slightly off center
+    let device_axes = [0.001, -0.0004, 0.02, -1.];
+    let mut axes = [0.; 4];
+    assert!(sync_axes(&mut axes, &device_axes));
+    assert_eq!(axes, device_axes);
+}
+
+#[test]
+fn axes_of_another_count_are_not_synced() {
+    let mut axes = [0.5, 0.5];
+    assert!(!sync_axes(&mut axes, &[0.1, 0.2, 0.3, 0.4]));
+    assert_eq!(axes, [0.5, 0.5]);
+}
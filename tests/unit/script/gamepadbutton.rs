@@ -0,0 +1,25 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use script::test::gamepadbutton::button_state;
+
+#[test]
+fn touched_buttons_are_not_pressed() {
+    let (pressed, touched, value) = button_state(false, true);
+    assert!(!pressed);
+    assert!(touched);
+    assert_eq!(value, 0.);
+}
+
+#[test]
+fn pressed_buttons_are_touched() {
+    assert_eq!(button_state(true, true), (true, true, 1.));
+    // devices without touch sensors never report buttons as touched
+    assert_eq!(button_state(true, false), (true, true, 1.));
+}
+
+#[test]
+fn released_buttons() {
+    assert_eq!(button_state(false, false), (false, false, 0.));
+}
@@ -0,0 +1,36 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use script::test::gamepadhapticactuator::{pulse_parameters, PlayingPulse};
+
+#[test]
+fn pulse_completes_after_its_duration() {
+    let (intensity, duration) = pulse_parameters(0.5, 100.);
+    assert_eq!((intensity, duration), (0.5, 100.));
+
+    let mut pulse = PlayingPulse::new();
+    let (id, preempted) = pulse.start("first");
+    assert_eq!(preempted, None);
+    assert_eq!(pulse.complete(id), Some("first"));
+    // a pulse only completes once
+    assert_eq!(pulse.complete(id), None);
+}
+
+#[test]
+fn overlapping_pulse_preempts_the_first() {
+    let mut pulse = PlayingPulse::new();
+    let (first, _) = pulse.start("first");
+    let (second, preempted) = pulse.start("second");
+    assert_eq!(preempted, Some("first"));
+    // the timer of the first pulse may still fire
+    assert_eq!(pulse.complete(first), None);
+    assert_eq!(pulse.complete(second), Some("second"));
+}
+
+#[test]
+fn out_of_range_pulses_are_clamped() {
+    assert_eq!(pulse_parameters(1.5, 100.), (1., 100.));
+    assert_eq!(pulse_parameters(-0.5, 100.), (0., 100.));
+    assert_eq!(pulse_parameters(0.5, -10.), (0.5, 0.));
+}
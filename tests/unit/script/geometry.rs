@@ -0,0 +1,102 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use euclid::{Angle, RigidTransform3D, Rotation3D, Transform3D, Vector3D};
+use script::test::geometry::{
+    all_finite, inverse_or_nan, normalized_rotation, normalized_vector, perspective_divide,
+};
+use script::test::xrray::ray_from_transform;
+use script::test::xrreferencespace::compose_offsets;
+use std::f64::{INFINITY, NAN};
+
+fn is_nan_matrix(matrix: &Transform3D<f64>) -> bool {
+    matrix
+        .to_row_major_array()
+        .iter()
+        .all(|value| value.is_nan())
+}
+
+#[test]
+fn all_finite_rejects_nan_and_infinities() {
+    assert!(all_finite(&[]));
+    assert!(all_finite(&[0., -1., 1e300]));
+    assert!(!all_finite(&[0., NAN]));
+    assert!(!all_finite(&[-INFINITY, 0.]));
+}
+
+#[test]
+fn inverse_of_invertible_matrix() {
+    let matrix = Transform3D::create_translation(1., 2., 3.);
+    let inverse = inverse_or_nan(&matrix);
+    assert_eq!(inverse, Transform3D::create_translation(-1., -2., -3.));
+}
+
+#[test]
+fn inverse_of_singular_matrix_is_nan() {
+    let matrix = Transform3D::create_scale(1., 0., 1.);
+    assert!(is_nan_matrix(&inverse_or_nan(&matrix)));
+}
+
+#[test]
+fn inverse_of_non_finite_matrix_is_nan() {
+    let mut matrix = Transform3D::identity();
+    matrix.m41 = NAN;
+    assert!(is_nan_matrix(&inverse_or_nan(&matrix)));
+    matrix.m41 = INFINITY;
+    assert!(is_nan_matrix(&inverse_or_nan(&matrix)));
+}
+
+#[test]
+fn perspective_divide_by_w() {
+    let point = perspective_divide(2., 4., 6., 2.);
+    assert_eq!((point.x, point.y, point.z), (1., 2., 3.));
+}
+
+#[test]
+fn perspective_divide_of_direction_is_nan() {
+    let point = perspective_divide(1., 0., 0., 0.);
+    assert!(point.x.is_nan() && point.y.is_nan() && point.z.is_nan());
+}
+
+#[test]
+fn normalized_vector_has_unit_length() {
+    let vector = normalized_vector(Vector3D::new(3., 0., 4.));
+    assert_eq!(vector, Vector3D::new(0.6, 0., 0.8));
+}
+
+#[test]
+fn degenerate_vector_stays_zero() {
+    for vector in &[
+        Vector3D::zero(),
+        Vector3D::new(NAN, 0., 0.),
+        Vector3D::new(0., INFINITY, 0.),
+    ] {
+        assert_eq!(normalized_vector(*vector), Vector3D::zero());
+    }
+}
+
+#[test]
+fn zero_quaternion_is_identity() {
+    let r = normalized_rotation(0., 0., 0., 0.);
+    assert_eq!((r.i, r.j, r.k, r.r), (0., 0., 0., 1.));
+}
+
+#[test]
+fn reference_space_offsets_route_through_normalized_rotation() {
+    // a zero quaternion would otherwise turn the composed offset into NaNs
+    let zero = Rotation3D::unit_quaternion(0., 0., 0., 0.);
+    let offset = RigidTransform3D::new(zero, Vector3D::new(1., 0., 0.));
+    let composed = compose_offsets(&RigidTransform3D::identity(), &offset);
+    let r = &composed.rotation;
+    assert_eq!((r.i, r.j, r.k, r.r), (0., 0., 0., 1.));
+    assert_eq!(composed.translation, Vector3D::new(1., 0., 0.));
+}
+
+#[test]
+fn rays_route_through_normalized_vector() {
+    let rotation = Rotation3D::around_y(Angle::degrees(90.));
+    let transform = RigidTransform3D::new(rotation, Vector3D::zero());
+    let (_, direction) = ray_from_transform(&transform);
+    assert!((direction.length() - 1.).abs() < 1e-12);
+}
@@ -0,0 +1,91 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use euclid::{Point2D, Rect, Size2D};
+use script::test::intersectionobserver::MarginLength::{Percentage, Pixels};
+use script::test::intersectionobserver::{
+    edge_inclusive_intersection, expand_by_margin, intersection_ratio, parse_root_margin,
+    threshold_index,
+};
+
+fn rect(x: f64, y: f64, width: f64, height: f64) -> Rect<f64> {
+    Rect::new(Point2D::new(x, y), Size2D::new(width, height))
+}
+
+#[test]
+fn root_margins_expand_like_css_margins() {
+    assert_eq!(
+        parse_root_margin("10px"),
+        Some([Pixels(10.), Pixels(10.), Pixels(10.), Pixels(10.)])
+    );
+    assert_eq!(
+        parse_root_margin("10px 5%"),
+        Some([Pixels(10.), Percentage(5.), Pixels(10.), Percentage(5.)])
+    );
+    assert_eq!(
+        parse_root_margin(" 1px  2px 3px "),
+        Some([Pixels(1.), Pixels(2.), Pixels(3.), Pixels(2.)])
+    );
+    assert_eq!(
+        parse_root_margin("1PX -2px 3px 4.5%"),
+        Some([Pixels(1.), Pixels(-2.), Pixels(3.), Percentage(4.5)])
+    );
+}
+
+#[test]
+fn root_margins_only_take_pixels_and_percentages() {
+    assert_eq!(parse_root_margin(""), None);
+    assert_eq!(parse_root_margin("0"), None);
+    assert_eq!(parse_root_margin("1em"), None);
+    assert_eq!(parse_root_margin("1px 2px 3px 4px 5px"), None);
+    assert_eq!(parse_root_margin("infpx"), None);
+}
+
+#[test]
+fn percentage_margins_are_of_the_root_size() {
+    let margin = [Percentage(10.), Pixels(5.), Pixels(0.), Percentage(50.)];
+    let root = rect(0., 0., 200., 100.);
+    assert_eq!(
+        expand_by_margin(&root, &margin),
+        rect(-100., -10., 305., 110.)
+    );
+}
+
+#[test]
+fn edge_adjacent_rects_intersect() {
+    let root = rect(0., 0., 100., 100.);
+    assert_eq!(
+        edge_inclusive_intersection(&rect(50., 50., 100., 100.), &root),
+        Some(rect(50., 50., 50., 50.))
+    );
+    assert_eq!(
+        edge_inclusive_intersection(&rect(100., 0., 10., 10.), &root),
+        Some(rect(100., 0., 0., 10.))
+    );
+    assert_eq!(
+        edge_inclusive_intersection(&rect(101., 0., 10., 10.), &root),
+        None
+    );
+}
+
+#[test]
+fn empty_targets_are_fully_visible_when_intersecting() {
+    let target = rect(10., 10., 0., 0.);
+    assert_eq!(intersection_ratio(&target, Some(&target)), 1.);
+    assert_eq!(intersection_ratio(&target, None), 0.);
+    let target = rect(0., 0., 10., 10.);
+    assert_eq!(
+        intersection_ratio(&target, Some(&rect(0., 0., 10., 5.))),
+        0.5
+    );
+}
+
+#[test]
+fn threshold_index_counts_the_thresholds_reached() {
+    let thresholds = [0., 0.5, 1.];
+    assert_eq!(threshold_index(&thresholds, 0.), 1);
+    assert_eq!(threshold_index(&thresholds, 0.25), 1);
+    assert_eq!(threshold_index(&thresholds, 0.5), 2);
+    assert_eq!(threshold_index(&thresholds, 1.), 3);
+}
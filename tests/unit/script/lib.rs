@@ -2,6 +2,24 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
+#[cfg(test)]
+mod accessibility;
+#[cfg(test)]
+mod animationeffect;
+#[cfg(test)]
+mod document;
+#[cfg(test)]
+mod dommatrixreadonly;
+#[cfg(test)]
+mod dompointreadonly;
+#[cfg(test)]
+mod gamepad;
+#[cfg(test)]
+mod gamepadbutton;
+#[cfg(test)]
+mod gamepadhapticactuator;
+#[cfg(test)]
+mod geometry;
 #[cfg(test)]
 mod headers;
 #[cfg(test)]
@@ -9,6 +27,8 @@ mod htmlareaelement;
 #[cfg(test)]
 mod htmlimageelement;
 #[cfg(test)]
+mod intersectionobserver;
+#[cfg(test)]
 mod origin;
 #[cfg(all(test, target_pointer_width = "64"))]
 mod size_of;
@@ -16,6 +36,44 @@ mod size_of;
 mod textinput;
 #[cfg(test)]
 mod timeranges;
+#[cfg(test)]
+mod vrdisplay;
+#[cfg(test)]
+mod xr;
+#[cfg(test)]
+mod xrboundedreferencespace;
+#[cfg(test)]
+mod xrcompositionlayer;
+#[cfg(test)]
+mod xrframe;
+#[cfg(test)]
+mod xrhand;
+#[cfg(test)]
+mod xrhittestresult;
+#[cfg(test)]
+mod xrimagetrackingresult;
+#[cfg(test)]
+mod xrinputsource;
+#[cfg(test)]
+mod xrquadlayer;
+#[cfg(test)]
+mod xrray;
+#[cfg(test)]
+mod xrreferencespace;
+#[cfg(test)]
+mod xrrenderstate;
+#[cfg(test)]
+mod xrrigidtransform;
+#[cfg(test)]
+mod xrsession;
+#[cfg(test)]
+mod xrspace;
+#[cfg(test)]
+mod xrstationaryreferencespace;
+#[cfg(test)]
+mod xrview;
+#[cfg(test)]
+mod xrwebgllayer;
 
 /**
 ```compile_fail,E0277
@@ -29,3 +87,25 @@ fn main() {
 ```
 */
 pub fn trustedpromise_does_not_impl_clone() {}
+
+/**
+```compile_fail,E0308
+extern crate euclid;
+extern crate script;
+
+use euclid::TypedRigidTransform3D;
+use script::test::xrspace::{ApiSpace, NativePose};
+
+// The pose of a relative to b is b⁻¹ · a, the other way round takes the
+// device's coordinates to themselves
+fn relative_pose(
+    a: &NativePose,
+    b: &NativePose,
+) -> TypedRigidTransform3D<f64, ApiSpace, ApiSpace> {
+    a.pre_mul(&b.inverse())
+}
+
+fn main() {}
+```
+*/
+pub fn xr_poses_cannot_be_composed_in_the_wrong_order() {}
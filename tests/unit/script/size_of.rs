@@ -32,8 +32,8 @@ macro_rules! sizeof_checker (
 sizeof_checker!(size_event_target, EventTarget, 40);
 sizeof_checker!(size_node, Node, 184);
 sizeof_checker!(size_element, Element, 432);
-sizeof_checker!(size_htmlelement, HTMLElement, 448);
-sizeof_checker!(size_div, HTMLDivElement, 448);
-sizeof_checker!(size_span, HTMLSpanElement, 448);
+sizeof_checker!(size_htmlelement, HTMLElement, 456);
+sizeof_checker!(size_div, HTMLDivElement, 456);
+sizeof_checker!(size_span, HTMLSpanElement, 456);
 sizeof_checker!(size_text, Text, 216);
 sizeof_checker!(size_characterdata, CharacterData, 216);
@@ -713,3 +713,35 @@ fn test_select_all() {
     assert_eq!(TextPoint { line: 0, index: 0 }, textinput.selection_start());
     assert_eq!(TextPoint { line: 0, index: 3 }, textinput.selection_end());
 }
+
+#[test]
+fn test_composition_replaces_the_text_being_composed() {
+    let mut textinput = text_input(Lines::Single, "abcd");
+    textinput.set_selection_range(2, 2, SelectionDirection::None);
+    textinput.update_composition("n");
+    assert_eq!(textinput.get_content(), "abncd");
+    assert_eq!(textinput.composition(), Some(2..3));
+
+    // Each update replaces what was composed so far.
+    textinput.update_composition("日本");
+    assert_eq!(textinput.get_content(), "ab日本cd");
+    assert_eq!(textinput.composition(), Some(2..8));
+
+    textinput.end_composition("日本語");
+    assert_eq!(textinput.get_content(), "ab日本語cd");
+    assert_eq!(textinput.composition(), None);
+    assert_eq!(textinput.selection_start_offset(), 11);
+}
+
+#[test]
+fn test_composition_starts_by_replacing_the_selection() {
+    let mut textinput = text_input(Lines::Single, "abcd");
+    textinput.set_selection_range(1, 3, SelectionDirection::Forward);
+    textinput.update_composition("x");
+    assert_eq!(textinput.get_content(), "axd");
+    assert_eq!(textinput.composition(), Some(1..2));
+
+    // The text being composed is gone with the old content.
+    textinput.set_content(DOMString::from("efgh"));
+    assert_eq!(textinput.composition(), None);
+}
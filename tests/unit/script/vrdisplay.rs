@@ -0,0 +1,81 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use script::test::vrdisplay::{run_frame_callbacks, FrameCallbacks};
+use std::cell::RefCell;
+
+/// Runs the callbacks of a frame the way the display does, returning the
+/// ones that ran. Callbacks named "again" register themselves again, and
+/// those named "throw" fail.
+fn run_frame(
+    callbacks: &RefCell<FrameCallbacks<&'static str>>,
+    next_id: &mut u32,
+) -> Vec<&'static str> {
+    let mut ran = vec![];
+    if !callbacks.borrow_mut().start_frame() {
+        return ran;
+    }
+    run_frame_callbacks(
+        |i| callbacks.borrow().current(i),
+        |callback| {
+            ran.push(callback);
+            match callback {
+                "again" => {
+                    *next_id += 1;
+                    callbacks.borrow_mut().push(*next_id, callback);
+                },
+                "throw" => return Err(()),
+                _ => {},
+            }
+            Ok(())
+        },
+    );
+    callbacks.borrow_mut().end_frame();
+    ran
+}
+
+#[test]
+fn callbacks_registered_during_a_frame_run_in_the_next_one() {
+    let callbacks = RefCell::new(FrameCallbacks::new());
+    let mut next_id = 1;
+    callbacks.borrow_mut().push(next_id, "again");
+    for _ in 0..3 {
+        assert_eq!(run_frame(&callbacks, &mut next_id), vec!["again"]);
+    }
+}
+
+#[test]
+fn callbacks_can_be_cancelled_later_in_the_same_frame() {
+    let mut callbacks = FrameCallbacks::new();
+    callbacks.push(1, "first");
+    callbacks.push(2, "second");
+    assert!(callbacks.start_frame());
+    assert_eq!(callbacks.current(0), Some(Some("first")));
+    // the first callback cancels the second one
+    callbacks.cancel(2);
+    assert_eq!(callbacks.current(1), Some(None));
+    assert_eq!(callbacks.current(2), None);
+    callbacks.end_frame();
+    assert!(!callbacks.start_frame());
+}
+
+#[test]
+fn frames_without_callbacks_are_skipped() {
+    let callbacks = RefCell::new(FrameCallbacks::new());
+    let mut next_id = 0;
+    assert!(run_frame(&callbacks, &mut next_id).is_empty());
+}
+
+#[test]
+fn throwing_callbacks_dont_stop_the_frame() {
+    let callbacks = RefCell::new(FrameCallbacks::new());
+    callbacks.borrow_mut().push(1, "throw");
+    callbacks.borrow_mut().push(2, "second");
+    callbacks.borrow_mut().push(3, "third");
+    let mut next_id = 3;
+    assert_eq!(
+        run_frame(&callbacks, &mut next_id),
+        vec!["throw", "second", "third"]
+    );
+}
@@ -0,0 +1,131 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use script::test::xr::{
+    resolve_features, DevicePresence, XRFeature, XRReferenceSpaceType, XRSessionMode,
+};
+use script::test::DOMString;
+
+fn descriptors(names: &[&str]) -> Vec<DOMString> {
+    names.iter().map(|name| DOMString::from(*name)).collect()
+}
+
+/// A device that can do everything but hand tracking
+fn supported(feature: XRFeature) -> bool {
+    feature != XRFeature::HandTracking
+}
+
+#[test]
+fn immersive_sessions_get_the_default_spaces() {
+    let features = resolve_features(XRSessionMode::Immersive_vr, &[], &[], supported).unwrap();
+    assert_eq!(
+        features,
+        vec![
+            XRFeature::ReferenceSpace(XRReferenceSpaceType::Identity),
+            XRFeature::ReferenceSpace(XRReferenceSpaceType::Stationary),
+        ]
+    );
+}
+
+#[test]
+fn inline_sessions_only_get_the_identity_space() {
+    let features = resolve_features(XRSessionMode::Inline, &[], &[], supported).unwrap();
+    assert_eq!(
+        features,
+        vec![XRFeature::ReferenceSpace(XRReferenceSpaceType::Identity)]
+    );
+}
+
+#[test]
+fn unsupported_required_feature_rejects_the_session() {
+    let required = descriptors(&["bounded", "hand-tracking"]);
+    assert!(resolve_features(XRSessionMode::Immersive_vr, &required, &[], supported).is_none());
+}
+
+#[test]
+fn unknown_required_feature_rejects_the_session() {
+    let required = descriptors(&["not-a-feature"]);
+    assert!(resolve_features(XRSessionMode::Immersive_vr, &required, &[], supported).is_none());
+}
+
+#[test]
+fn optional_features_are_granted_when_supported() {
+    let optional = descriptors(&["hand-tracking", "bounded", "not-a-feature", "identity"]);
+    let features =
+        resolve_features(XRSessionMode::Immersive_vr, &[], &optional, supported).unwrap();
+    assert_eq!(
+        features,
+        vec![
+            XRFeature::ReferenceSpace(XRReferenceSpaceType::Identity),
+            XRFeature::ReferenceSpace(XRReferenceSpaceType::Stationary),
+            XRFeature::ReferenceSpace(XRReferenceSpaceType::Bounded),
+        ]
+    );
+    let names: Vec<_> = features
+        .iter()
+        .map(|feature| feature.descriptor())
+        .collect();
+    assert_eq!(names, vec!["identity", "stationary", "bounded"]);
+}
+
+#[test]
+fn anchors_can_be_required() {
+    let required = descriptors(&["anchors"]);
+    let features =
+        resolve_features(XRSessionMode::Immersive_vr, &required, &[], supported).unwrap();
+    assert!(features.contains(&XRFeature::Anchors));
+    assert_eq!(XRFeature::Anchors.descriptor(), "anchors");
+}
+
+#[test]
+fn connecting_and_disconnecting_a_device_are_changes() {
+    let mut presence = DevicePresence::new();
+    assert!(!presence.has_device());
+    assert!(presence.set_connected(1, true));
+    assert!(presence.has_device());
+    // display updates that don't change anything aren't
+    assert!(!presence.set_connected(1, true));
+    assert!(presence.set_connected(1, false));
+    assert!(!presence.has_device());
+    assert!(!presence.set_connected(1, false));
+}
+
+#[test]
+fn devices_stay_available_while_any_is_connected() {
+    let mut presence = DevicePresence::new();
+    assert!(presence.set_connected(1, true));
+    assert!(!presence.set_connected(2, true));
+    assert!(!presence.set_connected(1, false));
+    assert!(presence.has_device());
+    assert!(presence.set_connected(2, false));
+}
+
+#[test]
+fn capable_devices_only_grant_the_spaces_that_were_requested() {
+    let bounded = XRFeature::ReferenceSpace(XRReferenceSpaceType::Bounded);
+    // requestReferenceSpace() rejects spaces missing from the enabled features
+    let features = resolve_features(XRSessionMode::Immersive_vr, &[], &[], supported).unwrap();
+    assert!(!features.contains(&bounded));
+
+    let required = descriptors(&["bounded"]);
+    let features =
+        resolve_features(XRSessionMode::Immersive_vr, &required, &[], supported).unwrap();
+    assert!(features.contains(&bounded));
+}
+
+#[test]
+fn hands_are_only_exposed_when_hand_tracking_is_granted() {
+    // XRInputSource.hand is null unless the session has hand tracking
+    let hand_tracking = descriptors(&["hand-tracking"]);
+    let features =
+        resolve_features(XRSessionMode::Immersive_vr, &[], &hand_tracking, |_| true).unwrap();
+    assert!(features.contains(&XRFeature::HandTracking));
+
+    let features = resolve_features(XRSessionMode::Immersive_vr, &[], &[], |_| true).unwrap();
+    assert!(!features.contains(&XRFeature::HandTracking));
+    // nor when the device can't track hands
+    let features =
+        resolve_features(XRSessionMode::Immersive_vr, &[], &hand_tracking, supported).unwrap();
+    assert!(!features.contains(&XRFeature::HandTracking));
+}
@@ -0,0 +1,47 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use euclid::Vector3D;
+use script::test::xrboundedreferencespace::rectangular_bounds;
+
+#[test]
+fn play_area_is_centered_on_the_origin() {
+    let bounds = rectangular_bounds(4., 2.);
+    assert_eq!(
+        bounds,
+        vec![
+            Vector3D::new(-2., 0., -1.),
+            Vector3D::new(2., 0., -1.),
+            Vector3D::new(2., 0., 1.),
+            Vector3D::new(-2., 0., 1.),
+        ]
+    );
+}
+
+#[test]
+fn bounds_are_clockwise_seen_from_above() {
+    let bounds = rectangular_bounds(3., 5.);
+    // looking down from above, a clockwise turn between two consecutive
+    // edges has their cross product point down the Y axis
+    for i in 0..bounds.len() {
+        let a = bounds[i];
+        let b = bounds[(i + 1) % bounds.len()];
+        let c = bounds[(i + 2) % bounds.len()];
+        assert!((b - a).cross(c - b).y < 0.);
+    }
+}
+
+#[test]
+fn resized_play_area_gets_new_bounds() {
+    let before = rectangular_bounds(2., 2.);
+    let after = rectangular_bounds(3., 2.);
+    assert_ne!(before, after);
+    assert_eq!(after[1], Vector3D::new(1.5, 0., -1.));
+}
+
+#[test]
+fn unknown_play_area_has_no_bounds() {
+    assert!(rectangular_bounds(0., 0.).is_empty());
+    assert!(rectangular_bounds(2., 0.).is_empty());
+}
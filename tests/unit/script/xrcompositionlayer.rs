@@ -0,0 +1,43 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use script::test::xrcompositionlayer::CompositionLayerState;
+
+/// A layer whose content has been submitted since it was created
+fn drawn() -> CompositionLayerState {
+    CompositionLayerState {
+        needs_redraw: false,
+        ..CompositionLayerState::new()
+    }
+}
+
+#[test]
+fn new_layers_need_drawing() {
+    let state = CompositionLayerState::new();
+    assert!(state.blend_texture_source_alpha);
+    assert!(state.needs_redraw);
+}
+
+#[test]
+fn toggling_blend_texture_source_alpha_needs_a_redraw() {
+    let mut state = drawn();
+    state.set_blend_texture_source_alpha(false);
+    assert!(!state.blend_texture_source_alpha);
+    assert!(state.needs_redraw);
+
+    let mut state = CompositionLayerState {
+        blend_texture_source_alpha: false,
+        ..drawn()
+    };
+    state.set_blend_texture_source_alpha(true);
+    assert!(state.blend_texture_source_alpha);
+    assert!(state.needs_redraw);
+}
+
+#[test]
+fn setting_the_same_value_needs_no_redraw() {
+    let mut state = drawn();
+    state.set_blend_texture_source_alpha(true);
+    assert_eq!(state, drawn());
+}
@@ -0,0 +1,99 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use script::test::xrframe::{FrameCache, FrameState};
+use std::rc::Rc;
+
+/// The state of a frame while its rAF callbacks run
+fn animation_frame() -> FrameState {
+    FrameState {
+        active: true,
+        animation_frame: true,
+        session_ended: false,
+    }
+}
+
+#[test]
+fn poses_can_only_be_queried_during_the_callbacks() {
+    // before the callbacks run
+    let mut state = FrameState::default();
+    assert!(!state.can_query());
+    assert!(!state.can_query_viewer_pose());
+
+    state = animation_frame();
+    assert!(state.can_query());
+    assert!(state.can_query_viewer_pose());
+
+    // after the callbacks, for frames content held onto
+    state.active = false;
+    state.animation_frame = false;
+    assert!(!state.can_query());
+    assert!(!state.can_query_viewer_pose());
+}
+
+#[test]
+fn input_source_event_frames_have_no_viewer_pose() {
+    let state = FrameState {
+        active: true,
+        animation_frame: false,
+        session_ended: false,
+    };
+    assert!(state.can_query());
+    assert!(!state.can_query_viewer_pose());
+}
+
+#[test]
+fn ending_the_session_stops_pose_queries_right_away() {
+    let state = FrameState {
+        session_ended: true,
+        ..animation_frame()
+    };
+    assert!(!state.can_query());
+    assert!(!state.can_query_viewer_pose());
+}
+
+#[test]
+fn input_source_event_frames_expire_after_dispatch() {
+    // the event's frame is only active while the event is being fired
+    let mut state = FrameState {
+        active: true,
+        animation_frame: false,
+        session_ended: false,
+    };
+    assert!(state.can_query());
+
+    // so content can't keep using it once the handlers return
+    state.active = false;
+    assert!(!state.can_query());
+    assert!(!state.can_query_viewer_pose());
+}
+
+/// A cache of viewer poses, by reference space and originOffset
+fn viewer_poses() -> FrameCache<(&'static str, u32), Rc<&'static str>> {
+    let mut cache = FrameCache::new();
+    cache.insert(("local", 0), Rc::new("local pose"));
+    cache.insert(("local-floor", 0), Rc::new("floor pose"));
+    cache
+}
+
+#[test]
+fn viewer_poses_are_kept_for_the_frame() {
+    let cache = viewer_poses();
+    let find = |space, offset| cache.find(|&key| key == (space, offset));
+    let first = find("local", 0).unwrap();
+    let second = find("local", 0).unwrap();
+    assert!(Rc::ptr_eq(first, second));
+
+    let floor = find("local-floor", 0).unwrap();
+    assert!(!Rc::ptr_eq(first, floor));
+    // moving the origin of a space needs a new pose
+    assert!(find("local", 1).is_none());
+}
+
+#[test]
+fn viewer_poses_are_forgotten_with_the_frame() {
+    let mut cache = viewer_poses();
+    cache.clear();
+    assert!(cache.find(|_| true).is_none());
+}
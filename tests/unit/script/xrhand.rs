@@ -0,0 +1,20 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use script::test::xrhand::{XRHandJoint, JOINTS};
+
+#[test]
+fn hands_have_25_joints() {
+    assert_eq!(JOINTS.len(), 25);
+    assert_eq!(JOINTS[0], XRHandJoint::Wrist);
+    assert_eq!(JOINTS[24], XRHandJoint::Pinky_finger_tip);
+}
+
+#[test]
+fn joints_are_at_their_own_index() {
+    // XRHand.get() looks the joint spaces up by their joint
+    for (index, &joint) in JOINTS.iter().enumerate() {
+        assert_eq!(joint as usize, index);
+    }
+}
@@ -0,0 +1,41 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use euclid::Vector3D;
+use script::test::xrhittestresult::{hit_pose, WebVRHitTestResult};
+
+fn assert_vector_approx_eq(actual: Vector3D<f64>, expected: Vector3D<f64>) {
+    assert!(
+        (actual - expected).length() < 1e-9,
+        "expected {:?}, got {:?}",
+        expected,
+        actual
+    );
+}
+
+#[test]
+fn hit_pose_sits_at_the_hit() {
+    let hit = WebVRHitTestResult {
+        position: [1., -1.5, 2.],
+        normal: [0., 1., 0.],
+    };
+    let pose = hit_pose(&hit);
+    assert_vector_approx_eq(pose.translation, Vector3D::new(1., -1.5, 2.));
+    let up = pose.rotation.rotate_vector3d(&Vector3D::new(0., 1., 0.));
+    assert_vector_approx_eq(up, Vector3D::new(0., 1., 0.));
+}
+
+#[test]
+fn hit_pose_points_up_along_the_normal() {
+    for &normal in &[[1., 0., 0.], [0., 0., -1.], [0., -1., 0.]] {
+        let hit = WebVRHitTestResult {
+            position: [0., 0., 0.],
+            normal,
+        };
+        let up = hit_pose(&hit)
+            .rotation
+            .rotate_vector3d(&Vector3D::new(0., 1., 0.));
+        assert_vector_approx_eq(up, Vector3D::new(normal[0], normal[1], normal[2]));
+    }
+}
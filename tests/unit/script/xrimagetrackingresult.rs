@@ -0,0 +1,37 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use euclid::{RigidTransform3D, Rotation3D, Vector3D};
+use script::test::xrimagetrackingresult::{TrackedImage, XRImageTrackingState};
+
+/// A poster half a meter wide, on the wall in front of the viewer
+fn poster() -> TrackedImage {
+    TrackedImage {
+        index: 0,
+        pose: RigidTransform3D::new(Rotation3D::identity(), Vector3D::new(0., 1.5, -2.)),
+        seen: true,
+        pose_known: true,
+        measured_width: 0.5,
+    }
+}
+
+#[test]
+fn images_seen_in_the_frame_are_tracked() {
+    assert_eq!(poster().tracking_state(), XRImageTrackingState::Tracked);
+}
+
+#[test]
+fn images_out_of_sight_are_emulated_or_untracked() {
+    let hidden = TrackedImage {
+        seen: false,
+        ..poster()
+    };
+    assert_eq!(hidden.tracking_state(), XRImageTrackingState::Emulated);
+
+    let lost = TrackedImage {
+        pose_known: false,
+        ..hidden
+    };
+    assert_eq!(lost.tracking_state(), XRImageTrackingState::Untracked);
+}
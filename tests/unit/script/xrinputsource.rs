@@ -0,0 +1,124 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use euclid::{Angle, Point3D, RigidTransform3D, Rotation3D, Vector3D};
+use script::test::xrinputsource::{
+    default_target_ray_offset, profiles_from_gamepad_id, target_ray_from_grip, xr_gamepad_mapping,
+    XRTargetRayMode,
+};
+
+fn assert_approx_eq(actual: f64, expected: f64) {
+    assert!(
+        (actual - expected).abs() < 1e-9,
+        "expected {}, got {}",
+        expected,
+        actual
+    );
+}
+
+#[test]
+fn target_ray_starts_at_the_grip() {
+    let grip = RigidTransform3D::new(
+        Rotation3D::around_y(Angle::degrees(45.)),
+        Vector3D::new(0.2, 1.1, -0.4),
+    );
+    let ray = target_ray_from_grip(&grip, &default_target_ray_offset());
+    let origin = ray
+        .to_transform()
+        .transform_point3d(&Point3D::origin())
+        .unwrap();
+    assert_approx_eq(origin.x, 0.2);
+    assert_approx_eq(origin.y, 1.1);
+    assert_approx_eq(origin.z, -0.4);
+}
+
+#[test]
+fn target_ray_points_down_from_the_grip() {
+    let grip = RigidTransform3D::new(
+        Rotation3D::around_y(Angle::degrees(90.)),
+        Vector3D::new(0., 1., 0.),
+    );
+    let ray = target_ray_from_grip(&grip, &default_target_ray_offset());
+    assert_ne!(ray.to_transform(), grip.to_transform());
+
+    // Both spaces point down their -Z axis, the grip towards -X here
+    let forward = Vector3D::new(0., 0., -1.);
+    let grip_forward = grip.to_transform().transform_vector3d(&forward);
+    let ray_forward = ray.to_transform().transform_vector3d(&forward);
+    assert_approx_eq(grip_forward.y, 0.);
+    assert!(ray_forward.y < 0., "the ray should point downwards");
+    assert_eq!(
+        ray_forward.x.signum(),
+        grip_forward.x.signum(),
+        "the ray should still point the same way"
+    );
+    assert_approx_eq(ray_forward.z, 0.);
+}
+
+#[test]
+fn target_ray_is_tilted_by_the_device_offset() {
+    let grip = RigidTransform3D::new(
+        Rotation3D::around_y(Angle::degrees(-60.)),
+        Vector3D::new(0.3, 1.2, -0.2),
+    );
+    let forward = Vector3D::new(0., 0., -1.);
+    let grip_forward = grip.to_transform().transform_vector3d(&forward);
+
+    let tilt_between = |offset: &RigidTransform3D<f64>| {
+        let ray = target_ray_from_grip(&grip, offset);
+        let ray_forward = ray.to_transform().transform_vector3d(&forward);
+        ray_forward.dot(grip_forward).acos().to_degrees()
+    };
+
+    assert_approx_eq(tilt_between(&default_target_ray_offset()), 30.);
+    let offset =
+        RigidTransform3D::new(Rotation3D::around_x(Angle::degrees(-45.)), Vector3D::zero());
+    assert_approx_eq(tilt_between(&offset), 45.);
+}
+
+#[test]
+fn tracked_pointers_use_the_standard_mapping() {
+    assert_eq!(
+        xr_gamepad_mapping(XRTargetRayMode::Tracked_pointer, 4),
+        "xr-standard"
+    );
+    assert_eq!(
+        xr_gamepad_mapping(XRTargetRayMode::Tracked_pointer, 1),
+        "xr-standard"
+    );
+}
+
+#[test]
+fn other_gamepads_have_no_mapping() {
+    // without buttons there is no primary button for index 0
+    assert_eq!(xr_gamepad_mapping(XRTargetRayMode::Tracked_pointer, 0), "");
+    assert_eq!(xr_gamepad_mapping(XRTargetRayMode::Gaze, 2), "");
+    assert_eq!(xr_gamepad_mapping(XRTargetRayMode::Screen, 2), "");
+}
+
+fn profiles(id: &str) -> Vec<String> {
+    profiles_from_gamepad_id(id)
+        .iter()
+        .map(|profile| profile.to_string())
+        .collect()
+}
+
+#[test]
+fn profiles_go_from_specific_to_generic() {
+    assert_eq!(
+        profiles("Oculus Touch (Left)"),
+        vec!["oculus-touch-left", "generic-trigger"]
+    );
+    assert_eq!(
+        profiles("HTC Vive MV"),
+        vec!["htc-vive-mv", "generic-trigger"]
+    );
+}
+
+#[test]
+fn unnamed_gamepads_only_get_the_generic_profile() {
+    assert_eq!(profiles(""), vec!["generic-trigger"]);
+    assert_eq!(profiles(" - "), vec!["generic-trigger"]);
+    assert_eq!(profiles("Generic Trigger"), vec!["generic-trigger"]);
+}
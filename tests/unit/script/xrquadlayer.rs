@@ -0,0 +1,17 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use script::test::xrquadlayer::check_dimension;
+
+#[test]
+fn quads_can_have_any_positive_size() {
+    assert!(check_dimension("width", 1.5).is_ok());
+    assert!(check_dimension("height", 0.001).is_ok());
+}
+
+#[test]
+fn empty_and_negative_quads_are_rejected() {
+    assert!(check_dimension("width", 0.).is_err());
+    assert!(check_dimension("height", -1.).is_err());
+}
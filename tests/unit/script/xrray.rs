@@ -0,0 +1,55 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use euclid::{Angle, Point3D, RigidTransform3D, Rotation3D, Vector3D};
+use script::test::xrray::{ray_from_transform, ray_transform};
+
+fn assert_vector_approx_eq(actual: Vector3D<f64>, expected: Vector3D<f64>) {
+    assert!(
+        (actual - expected).length() < 1e-9,
+        "expected {:?}, got {:?}",
+        expected,
+        actual
+    );
+}
+
+#[test]
+fn ray_follows_the_transform() {
+    // turning left by 90 degrees takes -Z to -X
+    let transform = RigidTransform3D::new(
+        Rotation3D::around_y(Angle::degrees(90.)),
+        Vector3D::new(1., 1.5, -2.),
+    );
+    let (origin, direction) = ray_from_transform(&transform);
+    assert_vector_approx_eq(origin, Vector3D::new(1., 1.5, -2.));
+    assert_vector_approx_eq(direction, Vector3D::new(-1., 0., 0.));
+    assert!((direction.length() - 1.).abs() < 1e-12);
+}
+
+#[test]
+fn ray_direction_is_normalized() {
+    // a quaternion that is slightly off unit length still gives a unit ray
+    let rotation = Rotation3D::quaternion(0., 0.3827, 0., 0.9239);
+    let transform = RigidTransform3D::new(rotation, Vector3D::zero());
+    let (_, direction) = ray_from_transform(&transform);
+    assert!((direction.length() - 1.).abs() < 1e-12);
+}
+
+#[test]
+fn ray_transform_maps_the_default_ray() {
+    let origin = Vector3D::new(0.5, 1., 0.);
+    let directions = [
+        Vector3D::new(0., 0., -1.),
+        Vector3D::new(0., 0., 1.),
+        Vector3D::new(1., 0., 0.),
+        Vector3D::new(1., 2., -3.).normalize(),
+    ];
+    for direction in directions.iter() {
+        let transform = ray_transform(origin, *direction).to_transform();
+        let start = transform.transform_point3d(&Point3D::origin()).unwrap();
+        let along = transform.transform_vector3d(&Vector3D::new(0., 0., -1.));
+        assert_vector_approx_eq(start.to_vector(), origin);
+        assert_vector_approx_eq(along, *direction);
+    }
+}
@@ -0,0 +1,96 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use euclid::{Angle, RigidTransform3D, Rotation3D, Transform3D, Vector3D};
+use script::test::xrreferencespace::{compose_offsets, is_identity_offset, offset_pose};
+use script::test::xrspace::relative_pose;
+
+fn assert_matrix_approx_eq(actual: &Transform3D<f64>, expected: &Transform3D<f64>) {
+    let actual = actual.to_row_major_array();
+    let expected = expected.to_row_major_array();
+    for (a, e) in actual.iter().zip(expected.iter()) {
+        assert!(
+            (a - e).abs() < 1e-9,
+            "expected {:?}, got {:?}",
+            expected,
+            actual
+        );
+    }
+}
+
+#[test]
+fn chained_offsets_stay_rigid() {
+    // A slightly denormalized quaternion, as if it had drifted
+    let rotation = Rotation3D::around_y(Angle::degrees(36.));
+    let drifted = Rotation3D::unit_quaternion(
+        rotation.i * 1.001,
+        rotation.j * 1.001,
+        rotation.k * 1.001,
+        rotation.r * 1.001,
+    );
+    let step = RigidTransform3D::new(drifted, Vector3D::zero());
+
+    let mut offset = RigidTransform3D::identity();
+    for _ in 0..10 {
+        offset = compose_offsets(&offset, &step);
+        let r = &offset.rotation;
+        let norm = (r.i * r.i + r.j * r.j + r.k * r.k + r.r * r.r).sqrt();
+        assert!(
+            (norm - 1.).abs() < 1e-12,
+            "rotation norm drifted to {}",
+            norm
+        );
+    }
+
+    // Ten 36 degree turns bring us all the way around
+    assert_matrix_approx_eq(&offset.to_transform(), &Transform3D::identity());
+}
+
+#[test]
+fn offsets_are_applied_before_the_base_offset() {
+    let base = RigidTransform3D::new(
+        Rotation3D::around_y(Angle::degrees(90.)),
+        Vector3D::new(1., 0., 0.),
+    );
+    let offset = RigidTransform3D::new(Rotation3D::identity(), Vector3D::new(0., 0., -2.));
+    let composed = compose_offsets(&base, &offset);
+    let expected = offset.to_transform().post_mul(&base.to_transform());
+    assert_matrix_approx_eq(&composed.to_transform(), &expected);
+}
+
+#[test]
+fn origin_offset_is_kept_across_a_reset() {
+    let offset = RigidTransform3D::new(
+        Rotation3D::around_y(Angle::degrees(30.)),
+        Vector3D::new(0., 0., -1.),
+    );
+    let before = RigidTransform3D::new(Rotation3D::identity(), Vector3D::new(0., 1.6, 0.));
+    // recentering moves the native origin of the space
+    let after = RigidTransform3D::new(
+        Rotation3D::around_y(Angle::degrees(90.)),
+        Vector3D::new(2., 1.6, 0.),
+    );
+    for native in &[before, after] {
+        let space = offset_pose(native, &offset);
+        // the offset space moves along with the new origin
+        let expected = offset.to_transform().post_mul(&native.to_transform());
+        assert_matrix_approx_eq(&space.to_transform(), &expected);
+        // and is still offset from it the same way
+        assert_matrix_approx_eq(
+            &relative_pose(&space, native).to_transform(),
+            &offset.to_transform(),
+        );
+    }
+}
+
+#[test]
+fn only_identity_offsets_leave_the_space_unmoved() {
+    assert!(is_identity_offset(&RigidTransform3D::identity()));
+
+    let turned = RigidTransform3D::new(Rotation3D::around_y(Angle::radians(0.5)), Vector3D::zero());
+    assert!(!is_identity_offset(&turned));
+
+    let moved = RigidTransform3D::new(Rotation3D::identity(), Vector3D::new(0., 1.5, 0.));
+    assert!(!is_identity_offset(&moved));
+}
@@ -0,0 +1,104 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use script::test::xrrenderstate::{merge_render_state, RenderStateFields, RenderStateUpdate};
+
+fn initial_state() -> RenderStateFields<&'static str> {
+    RenderStateFields {
+        depth_near: 0.1,
+        depth_far: 1000.,
+        base_layer: None,
+        layers: vec![],
+    }
+}
+
+fn no_update() -> RenderStateUpdate<&'static str> {
+    RenderStateUpdate {
+        depth_near: None,
+        depth_far: None,
+        base_layer: None,
+        layers: None,
+    }
+}
+
+#[test]
+fn partial_updates_are_merged() {
+    let mut state = initial_state();
+    merge_render_state(
+        &mut state,
+        RenderStateUpdate {
+            base_layer: Some("webgl"),
+            ..no_update()
+        },
+    );
+    merge_render_state(
+        &mut state,
+        RenderStateUpdate {
+            depth_far: Some(50.),
+            ..no_update()
+        },
+    );
+    assert_eq!(
+        state,
+        RenderStateFields {
+            depth_near: 0.1,
+            depth_far: 50.,
+            base_layer: Some("webgl"),
+            layers: vec![],
+        }
+    );
+}
+
+#[test]
+fn later_updates_win() {
+    let mut state = initial_state();
+    merge_render_state(
+        &mut state,
+        RenderStateUpdate {
+            depth_near: Some(0.5),
+            depth_far: Some(10.),
+            ..no_update()
+        },
+    );
+    merge_render_state(
+        &mut state,
+        RenderStateUpdate {
+            depth_near: Some(1.),
+            ..no_update()
+        },
+    );
+    assert_eq!(state.depth_near, 1.);
+    assert_eq!(state.depth_far, 10.);
+}
+
+#[test]
+fn base_layer_and_layers_replace_each_other() {
+    let mut state = initial_state();
+    merge_render_state(
+        &mut state,
+        RenderStateUpdate {
+            base_layer: Some("webgl"),
+            ..no_update()
+        },
+    );
+    merge_render_state(
+        &mut state,
+        RenderStateUpdate {
+            layers: Some(vec!["projection", "quad"]),
+            ..no_update()
+        },
+    );
+    assert_eq!(state.base_layer, None);
+    assert_eq!(state.layers, vec!["projection", "quad"]);
+
+    merge_render_state(
+        &mut state,
+        RenderStateUpdate {
+            base_layer: Some("webgl"),
+            ..no_update()
+        },
+    );
+    assert_eq!(state.base_layer, Some("webgl"));
+    assert!(state.layers.is_empty());
+}
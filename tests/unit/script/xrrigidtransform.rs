@@ -0,0 +1,158 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use euclid::{Angle, RigidTransform3D, Rotation3D, Transform3D, Vector3D};
+use script::test::geometry::normalized_rotation;
+use script::test::xrrigidtransform::{
+    interpolate_rigid_transforms, rigid_transform_from_matrix, rigid_transform_to_column_major,
+};
+
+fn assert_matrix_approx_eq(actual: &Transform3D<f64>, expected: &Transform3D<f64>) {
+    let actual = actual.to_row_major_array();
+    let expected = expected.to_row_major_array();
+    for (a, e) in actual.iter().zip(expected.iter()) {
+        assert!(
+            (a - e).abs() < 1e-9,
+            "expected {:?}, got {:?}",
+            expected,
+            actual
+        );
+    }
+}
+
+#[test]
+fn decomposed_matrices_round_trip() {
+    let matrices = [
+        Transform3D::create_translation(0.1, 1.6, -0.3),
+        Transform3D::create_rotation(0., 1., 0., Angle::degrees(180.))
+            .post_translate(Vector3D::new(-1., 0., 2.)),
+        Transform3D::create_rotation(1., 2., 3., Angle::radians(-2.5))
+            .post_translate(Vector3D::new(0.5, 0.25, 0.125)),
+    ];
+    for matrix in matrices.iter() {
+        let rigid = rigid_transform_from_matrix(matrix);
+        assert_matrix_approx_eq(&rigid.to_transform(), matrix);
+    }
+}
+
+#[test]
+fn decomposition_removes_scale() {
+    let rotation = Transform3D::create_rotation(0., 0., 1., Angle::degrees(30.));
+    let scaled = Transform3D::create_scale(2., 2., 2.).post_mul(&rotation);
+    let rigid = rigid_transform_from_matrix(&scaled);
+    assert_matrix_approx_eq(&rigid.to_transform(), &rotation);
+}
+
+#[test]
+fn column_major_export_keeps_full_precision() {
+    let rigid = RigidTransform3D::new(
+        Rotation3D::around_axis(Vector3D::new(1., 2., 3.), Angle::radians(0.1)),
+        Vector3D::new(0.1, 1.6000000000000001, -1e-10),
+    );
+    let m = rigid.to_transform();
+    let expected = [
+        m.m11, m.m12, m.m13, m.m14, m.m21, m.m22, m.m23, m.m24, m.m31, m.m32, m.m33, m.m34, m.m41,
+        m.m42, m.m43, m.m44,
+    ];
+    assert_eq!(rigid_transform_to_column_major(&rigid), expected);
+}
+
+#[test]
+fn interpolation_keeps_endpoints() {
+    let from = RigidTransform3D::new(
+        Rotation3D::around_y(Angle::degrees(10.)),
+        Vector3D::new(1., 2., 3.),
+    );
+    let to = RigidTransform3D::new(
+        Rotation3D::around_x(Angle::degrees(-70.)),
+        Vector3D::new(-1., 0., 0.5),
+    );
+    assert_matrix_approx_eq(
+        &interpolate_rigid_transforms(&from, &to, 0.).to_transform(),
+        &from.to_transform(),
+    );
+    assert_matrix_approx_eq(
+        &interpolate_rigid_transforms(&from, &to, 1.).to_transform(),
+        &to.to_transform(),
+    );
+}
+
+#[test]
+fn interpolation_halfway() {
+    let from = RigidTransform3D::new(Rotation3D::identity(), Vector3D::new(0., 1., 0.));
+    let to = RigidTransform3D::new(
+        Rotation3D::around_y(Angle::degrees(90.)),
+        Vector3D::new(2., 1., -4.),
+    );
+    let expected = RigidTransform3D::new(
+        Rotation3D::around_y(Angle::degrees(45.)),
+        Vector3D::new(1., 1., -2.),
+    );
+    assert_matrix_approx_eq(
+        &interpolate_rigid_transforms(&from, &to, 0.5).to_transform(),
+        &expected.to_transform(),
+    );
+}
+
+#[test]
+fn interpolation_takes_the_shortest_arc() {
+    // -q is the same orientation as q, and must not send the interpolation
+    // the long way around
+    let from = RigidTransform3D::new(Rotation3D::identity(), Vector3D::zero());
+    let to = Rotation3D::around_y(Angle::degrees(90.));
+    let negated = RigidTransform3D::new(
+        Rotation3D::quaternion(-to.i, -to.j, -to.k, -to.r),
+        Vector3D::zero(),
+    );
+    let expected =
+        RigidTransform3D::new(Rotation3D::around_y(Angle::degrees(45.)), Vector3D::zero());
+    assert_matrix_approx_eq(
+        &interpolate_rigid_transforms(&from, &negated, 0.5).to_transform(),
+        &expected.to_transform(),
+    );
+}
+
+#[test]
+fn orientation_is_normalized() {
+    let rotation = normalized_rotation(1.1, 2.1, 3.1, 1.);
+    let r = &rotation;
+    let norm = (r.i * r.i + r.j * r.j + r.k * r.k + r.r * r.r).sqrt();
+    assert!((norm - 1.).abs() < 1e-12);
+    // scaling a quaternion doesn't change the rotation it describes
+    let scaled = normalized_rotation(2.2, 4.2, 6.2, 2.);
+    assert_matrix_approx_eq(&scaled.to_transform(), &rotation.to_transform());
+}
+
+#[test]
+fn degenerate_orientation_is_identity() {
+    for &(x, y, z, w) in &[(0., 0., 0., 0.), (std::f64::NAN, 0., 0., 1.)] {
+        let r = normalized_rotation(x, y, z, w);
+        assert_eq!((r.i, r.j, r.k, r.r), (0., 0., 0., 1.));
+    }
+}
+
+#[test]
+fn flattened_matrix_has_no_orientation() {
+    let matrix = Transform3D::create_scale(0., 0., 0.).post_translate(Vector3D::new(1., 2., 3.));
+    let transform = rigid_transform_from_matrix(&matrix);
+    let r = &transform.rotation;
+    assert_eq!((r.i, r.j, r.k, r.r), (0., 0., 0., 1.));
+    assert_eq!(transform.translation, Vector3D::new(1., 2., 3.));
+}
+
+#[test]
+fn euclid_rotations_give_the_matrix_of_the_equivalent_dom_points() {
+    // what from_position_rotation() is handed by internal code
+    let rotation = Rotation3D::around_axis(Vector3D::new(0., 1., 0.), Angle::degrees(90.));
+    let translation = Vector3D::new(1., 2., 3.);
+    let direct = RigidTransform3D::new(rotation, translation);
+
+    // and what the constructor makes of the same pose given as DOMPointInits,
+    // with an orientation that isn't normalized yet
+    let half = 45f64.to_radians();
+    let orientation = normalized_rotation(0., 2. * half.sin(), 0., 2. * half.cos());
+    let from_points = RigidTransform3D::new(orientation, Vector3D::new(1., 2., 3.));
+
+    assert_matrix_approx_eq(&direct.to_transform(), &from_points.to_transform());
+}
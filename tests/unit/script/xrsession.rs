@@ -0,0 +1,358 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use euclid::{RigidTransform3D, Vector3D};
+use script::test::xr::XRReferenceSpaceType;
+use script::test::xrsession::{
+    environment_blend_mode, fires_reset, negotiated_frame_rate, predicted_display_time,
+    prediction_interval, validate_depth_range, viewport_scale_for_frame_rate, DeviceFrameRate,
+    Error, FloorOrigin, FrameCounter, FrameRateLimiter, LostInputSources, OriginChange, PoseClock,
+    ViewerSpaceCache, XREnvironmentBlendMode, XRSessionMode, XRStationaryReferenceSpaceSubtype,
+};
+use script::test::xrspace::extrapolate;
+use std::cell::Cell;
+use std::rc::Rc;
+
+/// Counts the frames that get rendered during one second of a 90Hz device
+fn rendered_frames(limiter: &mut FrameRateLimiter) -> usize {
+    (0..90)
+        .map(|frame| frame as f64 * 1000. / 90.)
+        .filter(|&now| limiter.should_run(now))
+        .count()
+}
+
+#[test]
+fn every_frame_runs_without_a_target_rate() {
+    let mut limiter = FrameRateLimiter::new();
+    assert_eq!(rendered_frames(&mut limiter), 90);
+}
+
+#[test]
+fn lowering_the_target_rate_skips_frames() {
+    let mut limiter = FrameRateLimiter::new();
+    limiter.set_frame_rate(Some(45.));
+    assert_eq!(rendered_frames(&mut limiter), 45);
+
+    limiter.set_frame_rate(Some(30.));
+    assert_eq!(rendered_frames(&mut limiter), 30);
+}
+
+#[test]
+fn target_rate_above_device_rate_runs_every_frame() {
+    let mut limiter = FrameRateLimiter::new();
+    limiter.set_frame_rate(Some(120.));
+    assert_eq!(rendered_frames(&mut limiter), 90);
+}
+
+#[test]
+fn clearing_the_target_rate_restores_every_frame() {
+    let mut limiter = FrameRateLimiter::new();
+    limiter.set_frame_rate(Some(45.));
+    limiter.set_frame_rate(None);
+    assert_eq!(rendered_frames(&mut limiter), 90);
+}
+
+/// Delivers one second worth of device frames at `rate`, starting at
+/// `start`, and returns the frame rate changes that got reported
+fn deliver_frames(device: &mut DeviceFrameRate, rate: f64, start: f64) -> Vec<f32> {
+    (0..rate as usize)
+        .filter_map(|frame| device.observe(start + frame as f64 * 1000. / rate))
+        .collect()
+}
+
+#[test]
+fn device_frame_rate_is_measured() {
+    let mut device = DeviceFrameRate::new();
+    assert_eq!(device.rate(), None);
+    assert_eq!(deliver_frames(&mut device, 90., 0.), vec![90.]);
+    assert_eq!(device.rate(), Some(90.));
+    assert_eq!(deliver_frames(&mut device, 90., 1000.), vec![]);
+}
+
+#[test]
+fn throttled_device_reports_a_frame_rate_change() {
+    let mut device = DeviceFrameRate::new();
+    deliver_frames(&mut device, 90., 0.);
+    assert_eq!(deliver_frames(&mut device, 60., 1000.), vec![60.]);
+    assert_eq!(device.rate(), Some(60.));
+}
+
+#[test]
+fn pauses_between_device_frames_are_not_a_frame_rate_change() {
+    let mut device = DeviceFrameRate::new();
+    deliver_frames(&mut device, 90., 0.);
+    // no frames for two seconds, e.g. because content stopped requesting them
+    assert_eq!(deliver_frames(&mut device, 90., 3000.), vec![]);
+    assert_eq!(device.rate(), Some(90.));
+}
+
+#[test]
+fn throttled_device_recommends_a_smaller_viewport_scale() {
+    let mut device = DeviceFrameRate::new();
+    deliver_frames(&mut device, 90., 0.);
+    deliver_frames(&mut device, 45., 1000.);
+    assert_eq!(device.full_rate(), Some(90.));
+
+    let scale = viewport_scale_for_frame_rate(device.rate().unwrap(), 90.).unwrap();
+    // half the frame rate leaves time for half the pixels
+    assert!((scale * scale - 0.5).abs() < 1e-9, "got {}", scale);
+    assert_eq!(viewport_scale_for_frame_rate(90., 90.), None);
+    assert_eq!(viewport_scale_for_frame_rate(0., 90.), Some(0.1));
+}
+
+#[test]
+fn negotiated_frame_rate_is_capped_by_the_device() {
+    assert_eq!(negotiated_frame_rate(None, None), None);
+    assert_eq!(negotiated_frame_rate(Some(72.), None), Some(72.));
+    assert_eq!(negotiated_frame_rate(None, Some(90.)), Some(90.));
+    assert_eq!(negotiated_frame_rate(Some(72.), Some(90.)), Some(72.));
+    assert_eq!(negotiated_frame_rate(Some(72.), Some(60.)), Some(60.));
+}
+
+#[test]
+fn equal_depth_planes_are_rejected() {
+    match validate_depth_range(1., 1.) {
+        Err(Error::Range(_)) => {},
+        result => panic!("expected a RangeError, got {:?}", result),
+    }
+}
+
+#[test]
+fn negative_depth_near_is_rejected() {
+    match validate_depth_range(-0.5, 100.) {
+        Err(Error::Range(_)) => {},
+        result => panic!("expected a RangeError, got {:?}", result),
+    }
+}
+
+#[test]
+fn distinct_depth_planes_are_accepted() {
+    assert!(validate_depth_range(0.1, 1000.).is_ok());
+    assert!(validate_depth_range(0., 1.).is_ok());
+}
+
+#[test]
+fn lost_sources_are_kept_for_the_grace_period() {
+    let mut lost = LostInputSources::new(1000.);
+    lost.update(3, true, 0.);
+    assert!(!lost.is_recently_lost(3));
+
+    lost.update(3, false, 100.);
+    assert!(lost.is_recently_lost(3));
+    lost.update(3, false, 1099.);
+    assert!(lost.is_recently_lost(3));
+
+    lost.update(3, false, 1100.);
+    assert!(!lost.is_recently_lost(3));
+}
+
+#[test]
+fn reconnected_sources_are_no_longer_lost() {
+    let mut lost = LostInputSources::new(1000.);
+    lost.update(3, false, 0.);
+    lost.update(3, true, 500.);
+    assert!(!lost.is_recently_lost(3));
+
+    // losing it again restarts the grace period
+    lost.update(3, false, 2000.);
+    lost.update(3, false, 2500.);
+    assert!(lost.is_recently_lost(3));
+}
+
+#[test]
+fn pose_clock_finds_the_offset_of_the_device_clock() {
+    let mut clock = PoseClock::new();
+    // the device clock runs 5s ahead, and poses take 2ms then 1ms to arrive
+    assert_eq!(clock.sample_time(6000., 1002.), 1002.);
+    assert_eq!(clock.sample_time(6010., 1011.), 1011.);
+    // the quickest pose sets the offset for the slower ones
+    assert_eq!(clock.sample_time(6020., 1025.), 1021.);
+}
+
+#[test]
+fn pose_clock_ignores_missing_timestamps() {
+    let mut clock = PoseClock::new();
+    assert_eq!(clock.sample_time(0., 1000.), 1000.);
+    assert_eq!(clock.sample_time(0., 1016.), 1016.);
+}
+
+#[test]
+fn frames_are_displayed_a_frame_interval_later() {
+    // without a target frame rate, frames are assumed to be shown at 60Hz
+    assert!((predicted_display_time(1000., None) - (1000. + 1000. / 60.)).abs() < 1e-9);
+
+    let mut limiter = FrameRateLimiter::new();
+    limiter.set_frame_rate(Some(45.));
+    let display_time = predicted_display_time(1000., limiter.interval());
+    assert!((display_time - (1000. + 1000. / 45.)).abs() < 1e-9);
+}
+
+#[test]
+fn prediction_interval_is_bounded() {
+    assert_eq!(prediction_interval(1000., 1020.), 20.);
+    // a pose from the future isn't extrapolated backwards
+    assert_eq!(prediction_interval(1030., 1020.), 0.);
+    // nor one from long ago extrapolated for as long
+    assert_eq!(prediction_interval(0., 1020.), 100.);
+}
+
+#[test]
+fn viewer_pose_is_extrapolated_to_the_display_time() {
+    let mut clock = PoseClock::new();
+    clock.sample_time(6000., 1000.);
+    // sampled 4ms before it was received, to be displayed 16ms later
+    let now = 1014.;
+    let sample_time = clock.sample_time(6010., now);
+    let dt = prediction_interval(sample_time, now + 16.);
+    assert_eq!(dt, 20.);
+
+    let pose = RigidTransform3D::identity();
+    let linear = Vector3D::new(1., 0., 0.);
+    let predicted = extrapolate(&pose, linear, Vector3D::zero(), dt / 1000.);
+    // the viewer moved on by 20ms at 1m/s, not by the 16ms until display
+    assert!((predicted.translation.x - 0.02).abs() < 1e-9);
+}
+
+#[test]
+fn only_ar_sessions_blend_with_the_real_world() {
+    assert_eq!(
+        environment_blend_mode(XRSessionMode::Immersive_vr),
+        XREnvironmentBlendMode::Opaque
+    );
+    assert_eq!(
+        environment_blend_mode(XRSessionMode::Inline),
+        XREnvironmentBlendMode::Opaque
+    );
+    assert_eq!(
+        environment_blend_mode(XRSessionMode::Immersive_ar),
+        XREnvironmentBlendMode::Alpha_blend
+    );
+}
+
+#[test]
+fn views_of_earlier_frames_are_stale() {
+    let mut frames = FrameCounter::default();
+    frames.next_frame();
+    // a view created during this frame, which getViewport() accepts
+    let view = frames.current();
+    assert!(frames.is_current(view));
+
+    frames.next_frame();
+    assert!(!frames.is_current(view));
+    assert!(frames.is_current(frames.current()));
+}
+
+/// A viewer space, which content may move by setting its originOffset
+struct ViewerSpace {
+    moved: Cell<bool>,
+}
+
+/// Resolves a request for a viewer space the way requestReferenceSpace() does
+fn request_viewer_space(cache: &mut ViewerSpaceCache<Rc<ViewerSpace>>) -> Rc<ViewerSpace> {
+    if let Some(space) = cache.get(|space| !space.moved.get()) {
+        return space.clone();
+    }
+    let space = Rc::new(ViewerSpace {
+        moved: Cell::new(false),
+    });
+    cache.set(space.clone());
+    space
+}
+
+#[test]
+fn viewer_space_requests_resolve_to_the_same_space() {
+    let mut cache = ViewerSpaceCache::new();
+    let first = request_viewer_space(&mut cache);
+    let second = request_viewer_space(&mut cache);
+    assert!(Rc::ptr_eq(&first, &second));
+}
+
+#[test]
+fn moving_the_viewer_space_leaves_later_requests_alone() {
+    let mut cache = ViewerSpaceCache::new();
+    let moved = request_viewer_space(&mut cache);
+    moved.moved.set(true);
+
+    let fresh = request_viewer_space(&mut cache);
+    assert!(!Rc::ptr_eq(&moved, &fresh));
+    assert!(!fresh.moved.get());
+    assert!(Rc::ptr_eq(&fresh, &request_viewer_space(&mut cache)));
+}
+
+/// The sitting to standing transform of a device whose eye-level origin is
+/// `height` meters above the floor
+fn standing_at(height: f32) -> Option<[f32; 16]> {
+    Some([
+        1., 0., 0., 0., //
+        0., 1., 0., 0., //
+        0., 0., 1., 0., //
+        0., height, 0., 1.,
+    ])
+}
+
+/// Tracking noise of up to two millimeters, a different one each frame
+fn jitter(frame: usize) -> f32 {
+    [0.002, -0.0015, 0.0005, -0.002, 0.001][frame % 5]
+}
+
+/// Which spaces of a session get a reset event for `change`, in the order
+/// identity, eye-level, floor-level and bounded
+fn resets(change: OriginChange) -> [bool; 4] {
+    [
+        fires_reset(XRReferenceSpaceType::Identity, None, change),
+        fires_reset(
+            XRReferenceSpaceType::Stationary,
+            Some(XRStationaryReferenceSpaceSubtype::Eye_level),
+            change,
+        ),
+        fires_reset(
+            XRReferenceSpaceType::Stationary,
+            Some(XRStationaryReferenceSpaceSubtype::Floor_level),
+            change,
+        ),
+        fires_reset(XRReferenceSpaceType::Bounded, None, change),
+    ]
+}
+
+#[test]
+fn jittery_stage_data_does_not_reset_stationary_spaces() {
+    let mut floor = FloorOrigin::default();
+    for frame in 0..90 {
+        assert!(!floor.update(standing_at(1.6 + jitter(frame))));
+    }
+}
+
+#[test]
+fn devices_without_a_floor_never_move_it() {
+    let mut floor = FloorOrigin::default();
+    assert!(!floor.update(None));
+    assert!(!floor.update(None));
+}
+
+#[test]
+fn moving_the_floor_resets_the_spaces_standing_on_it() {
+    let mut floor = FloorOrigin::default();
+    assert!(!floor.update(standing_at(1.6)));
+    assert!(floor.update(standing_at(1.2)));
+    assert_eq!(resets(OriginChange::FloorMoved), [false, false, true, true]);
+
+    // The floor is compared to where it is now from then on
+    for frame in 0..90 {
+        assert!(!floor.update(standing_at(1.2 + jitter(frame))));
+    }
+}
+
+#[test]
+fn slow_drift_of_the_floor_is_measured_from_the_last_reset() {
+    let mut floor = FloorOrigin::default();
+    let moves = (0..30)
+        .filter(|&update| floor.update(standing_at(1.6 + update as f32 * 0.0005)))
+        .count();
+    assert_eq!(moves, 1);
+}
+
+#[test]
+fn recentering_resets_every_space_with_a_native_origin() {
+    assert_eq!(resets(OriginChange::Recenter), [false, true, true, true]);
+}
@@ -0,0 +1,264 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use euclid::{Angle, Point3D, RigidTransform3D, Rotation3D, Transform3D, Vector3D};
+use euclid::{TypedRigidTransform3D, TypedRotation3D, TypedVector3D};
+use script::test::xr::XRReferenceSpaceType;
+use script::test::xrspace::{
+    extrapolate, reference_space_velocity, relative_pose, relative_transform, relative_velocity,
+    untyped_pose, velocity_at, ApiSpace, NativePose, SpaceVelocity,
+};
+
+fn assert_approx_eq(actual: f64, expected: f64) {
+    assert!(
+        (actual - expected).abs() < 1e-9,
+        "expected {}, got {}",
+        expected,
+        actual
+    );
+}
+
+#[test]
+fn space_relative_to_itself_is_identity() {
+    let a = Transform3D::create_rotation(0., 1., 0., Angle::radians(0.7))
+        .post_translate(Vector3D::new(1., 2., 3.));
+    let relative = relative_transform(a, a);
+    let expected = Transform3D::<f64>::identity().to_row_major_array();
+    for (actual, expected) in relative.to_row_major_array().iter().zip(expected.iter()) {
+        assert_approx_eq(*actual, *expected);
+    }
+}
+
+#[test]
+fn relative_transform_removes_base_offset() {
+    // b is one meter to the right of the origin and rotated a quarter turn
+    // to the left, a is one meter straight ahead of b
+    let b = Transform3D::create_rotation(0., 1., 0., Angle::degrees(90.))
+        .post_translate(Vector3D::new(1., 0., 0.));
+    let a = Transform3D::create_translation(0., 0., -1.).post_mul(&b);
+    let relative = relative_transform(a, b);
+    let origin = relative
+        .transform_point3d(&Point3D::new(0., 0., 0.))
+        .unwrap();
+    assert_approx_eq(origin.x, 0.);
+    assert_approx_eq(origin.y, 0.);
+    assert_approx_eq(origin.z, -1.);
+}
+
+#[test]
+fn non_invertible_base_is_treated_as_origin() {
+    let a = Transform3D::create_translation(1., 2., 3.);
+    let b = Transform3D::create_scale(0., 0., 0.);
+    assert_eq!(relative_transform(a, b), a);
+}
+
+#[test]
+fn viewer_pose_matches_pose_relative_to_reference_space() {
+    let viewer = RigidTransform3D::new(
+        Rotation3D::around_x(Angle::degrees(-20.)),
+        Vector3D::new(0.1, 1.6, -0.3),
+    );
+    // a floor-level space with an originOffset applied on top of it
+    let floor = RigidTransform3D::new(Rotation3D::identity(), Vector3D::new(0., -1.4, 0.));
+    let offset = RigidTransform3D::new(
+        Rotation3D::around_y(Angle::degrees(45.)),
+        Vector3D::new(2., 0., 1.),
+    );
+    let reference = offset.post_mul(&floor);
+
+    // the viewer pose composed by hand: offset⁻¹ · floor⁻¹ · viewer
+    let specialized = offset.inverse().pre_mul(&floor.inverse().pre_mul(&viewer));
+    let general = relative_transform(viewer.to_transform(), reference.to_transform());
+    let pose = relative_pose(&viewer, &reference)
+        .to_transform()
+        .to_row_major_array();
+
+    for expected in &[specialized.to_transform(), general] {
+        for (actual, expected) in pose.iter().zip(expected.to_row_major_array().iter()) {
+            assert_approx_eq(*actual, *expected);
+        }
+    }
+}
+
+#[test]
+fn extrapolate_integrates_velocities() {
+    // a pose half a turn around the x axis, moving along x and spinning a
+    // quarter turn per second around y
+    let rotation = Rotation3D::around_x(Angle::degrees(180.));
+    let pose = RigidTransform3D::new(rotation, Vector3D::new(1., 2., 3.));
+    let linear = Vector3D::new(2., 0., 0.);
+    let angular = Vector3D::new(0., std::f64::consts::FRAC_PI_2, 0.);
+    let predicted = extrapolate(&pose, linear, angular, 0.5);
+
+    assert_approx_eq(predicted.translation.x, 2.);
+    assert_approx_eq(predicted.translation.y, 2.);
+    assert_approx_eq(predicted.translation.z, 3.);
+
+    let expected = rotation.post_rotate(&Rotation3D::around_y(Angle::degrees(45.)));
+    let point = Point3D::new(1., 2., 3.);
+    let actual = predicted.rotation.rotate_point3d(&point);
+    let expected = expected.rotate_point3d(&point);
+    assert_approx_eq(actual.x, expected.x);
+    assert_approx_eq(actual.y, expected.y);
+    assert_approx_eq(actual.z, expected.z);
+}
+
+#[test]
+fn extrapolate_without_rotation() {
+    let rotation = Rotation3D::around_z(Angle::degrees(30.));
+    let pose = RigidTransform3D::new(rotation, Vector3D::new(0., 0., 0.));
+    let predicted = extrapolate(&pose, Vector3D::new(0., 0., -1.), Vector3D::zero(), 1e-9);
+
+    assert_approx_eq(predicted.translation.z, -1e-9);
+    assert_approx_eq(predicted.rotation.i, rotation.i);
+    assert_approx_eq(predicted.rotation.j, rotation.j);
+    assert_approx_eq(predicted.rotation.k, rotation.k);
+    assert_approx_eq(predicted.rotation.r, rotation.r);
+}
+
+fn assert_vector_approx_eq(actual: Vector3D<f64>, expected: Vector3D<f64>) {
+    assert_approx_eq(actual.x, expected.x);
+    assert_approx_eq(actual.y, expected.y);
+    assert_approx_eq(actual.z, expected.z);
+}
+
+#[test]
+fn velocity_at_adds_rotation_of_lever_arm() {
+    // spinning a radian per second around y, a point one meter ahead moves
+    // to the left
+    let velocity = SpaceVelocity {
+        linear: Vector3D::new(0., 0.5, 0.),
+        angular: Vector3D::new(0., 1., 0.),
+    };
+    let moved = velocity_at(
+        &velocity,
+        Vector3D::new(1., 0., 0.),
+        Vector3D::new(1., 0., -1.),
+    );
+    assert_vector_approx_eq(moved.linear, Vector3D::new(-1., 0.5, 0.));
+    assert_vector_approx_eq(moved.angular, velocity.angular);
+}
+
+#[test]
+fn only_identity_spaces_pick_up_viewer_motion() {
+    let viewer = SpaceVelocity {
+        linear: Vector3D::new(0.5, 0., 0.),
+        angular: Vector3D::new(0., 1., 0.),
+    };
+    let (viewer_position, origin) = (Vector3D::new(0., 1.6, 0.), Vector3D::zero());
+    let velocity = |ty| reference_space_velocity(ty, Some(viewer), viewer_position, origin);
+
+    // the floor of a bounded space stays put however the headset moves
+    assert_eq!(
+        velocity(XRReferenceSpaceType::Bounded),
+        Some(SpaceVelocity::zero())
+    );
+    assert_eq!(
+        velocity(XRReferenceSpaceType::Stationary),
+        Some(SpaceVelocity::zero())
+    );
+    assert_eq!(
+        velocity(XRReferenceSpaceType::Identity),
+        Some(velocity_at(&viewer, viewer_position, origin))
+    );
+    // the velocity is unknown when the device doesn't track the viewer's
+    let untracked = reference_space_velocity(XRReferenceSpaceType::Identity, None, origin, origin);
+    assert_eq!(untracked, None);
+}
+
+#[test]
+fn velocity_relative_to_stationary_origin_is_unchanged() {
+    let pose = RigidTransform3D::new(
+        Rotation3D::around_x(Angle::degrees(30.)),
+        Vector3D::new(0.2, 1.1, -0.4),
+    );
+    let velocity = SpaceVelocity {
+        linear: Vector3D::new(1., 2., 3.),
+        angular: Vector3D::new(0., 0., 4.),
+    };
+    let relative = relative_velocity(
+        &pose,
+        &velocity,
+        &RigidTransform3D::identity(),
+        &SpaceVelocity::zero(),
+    );
+    assert_vector_approx_eq(relative.linear, velocity.linear);
+    assert_vector_approx_eq(relative.angular, velocity.angular);
+}
+
+#[test]
+fn fixed_point_seen_from_rotating_base() {
+    // the base spins a radian per second around y, so a point fixed one
+    // meter ahead of it seems to move to the right and spin the other way
+    let base = RigidTransform3D::identity();
+    let base_velocity = SpaceVelocity {
+        linear: Vector3D::zero(),
+        angular: Vector3D::new(0., 1., 0.),
+    };
+    let point = RigidTransform3D::new(Rotation3D::identity(), Vector3D::new(0., 0., -1.));
+    let relative = relative_velocity(&point, &SpaceVelocity::zero(), &base, &base_velocity);
+    assert_vector_approx_eq(relative.linear, Vector3D::new(1., 0., 0.));
+    assert_vector_approx_eq(relative.angular, Vector3D::new(0., -1., 0.));
+}
+
+#[test]
+fn relative_velocity_matches_motion_of_relative_pose() {
+    let a = RigidTransform3D::new(
+        Rotation3D::around_x(Angle::degrees(20.)),
+        Vector3D::new(1., 0., -1.),
+    );
+    let a_velocity = SpaceVelocity {
+        linear: Vector3D::new(0., 0.5, 2.),
+        angular: Vector3D::new(1., 0., 0.4),
+    };
+    let b = RigidTransform3D::new(
+        Rotation3D::around_y(Angle::degrees(90.)),
+        Vector3D::new(1., 0.2, 0.),
+    );
+    let b_velocity = SpaceVelocity {
+        linear: Vector3D::new(0., 0., 1.),
+        angular: Vector3D::new(0.2, 1., 0.),
+    };
+    let relative = relative_velocity(&a, &a_velocity, &b, &b_velocity);
+
+    // moving both spaces for a short while moves their relative pose by the
+    // relative velocity, up to second order terms
+    let dt = 1e-6;
+    let later = relative_pose(
+        &extrapolate(&a, a_velocity.linear, a_velocity.angular, dt),
+        &extrapolate(&b, b_velocity.linear, b_velocity.angular, dt),
+    );
+    let expected = extrapolate(
+        &relative_pose(&a, &b),
+        relative.linear,
+        relative.angular,
+        dt,
+    );
+    assert_vector_approx_eq(later.translation, expected.translation);
+    assert_approx_eq(later.rotation.i, expected.rotation.i);
+    assert_approx_eq(later.rotation.j, expected.rotation.j);
+    assert_approx_eq(later.rotation.k, expected.rotation.k);
+    assert_approx_eq(later.rotation.r, expected.rotation.r);
+}
+
+#[test]
+fn typed_relative_poses_match_the_untyped_ones() {
+    let a: NativePose = TypedRigidTransform3D::new(
+        TypedRotation3D::around_y(Angle::degrees(30.)),
+        TypedVector3D::new(1., 1.6, -2.),
+    );
+    let b: NativePose = TypedRigidTransform3D::new(
+        TypedRotation3D::around_x(Angle::degrees(-10.)),
+        TypedVector3D::new(0., 1., 0.5),
+    );
+    // b⁻¹ · a is the only way the units line up, see the compile_fail test
+    // in lib.rs for the other way round
+    let typed: TypedRigidTransform3D<f64, ApiSpace, ApiSpace> = b.inverse().pre_mul(&a);
+    let untyped = relative_pose(&untyped_pose(&a), &untyped_pose(&b));
+    assert_vector_approx_eq(typed.translation.to_untyped(), untyped.translation);
+    assert_approx_eq(typed.rotation.i, untyped.rotation.i);
+    assert_approx_eq(typed.rotation.j, untyped.rotation.j);
+    assert_approx_eq(typed.rotation.k, untyped.rotation.k);
+    assert_approx_eq(typed.rotation.r, untyped.rotation.r);
+}
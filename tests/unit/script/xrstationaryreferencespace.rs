@@ -0,0 +1,65 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use euclid::{RigidTransform3D, Rotation3D, Vector3D};
+use script::test::xrreferencespace::{compose_offsets, offset_pose};
+use script::test::xrspace::relative_pose;
+use script::test::xrstationaryreferencespace::floor_pose;
+
+/// The height of the viewer above the floor, for a viewer standing at the
+/// eye-level origin
+fn eye_height(floor: &RigidTransform3D<f64>, origin_offset: &RigidTransform3D<f64>) -> f64 {
+    let space = offset_pose(floor, origin_offset);
+    relative_pose(&RigidTransform3D::identity(), &space)
+        .translation
+        .y
+}
+
+fn raised_by(height: f64) -> RigidTransform3D<f64> {
+    RigidTransform3D::new(Rotation3D::identity(), Vector3D::new(0., height, 0.))
+}
+
+fn sitting_to_standing(height: f32) -> [f32; 16] {
+    let mut transform = [0.; 16];
+    transform[0] = 1.;
+    transform[5] = 1.;
+    transform[10] = 1.;
+    transform[15] = 1.;
+    transform[13] = height;
+    transform
+}
+
+#[test]
+fn local_floor_emulates_the_eye_height() {
+    let floor = floor_pose(None, true).unwrap();
+    let height = eye_height(&floor, &RigidTransform3D::identity());
+    assert!((height - 1.6).abs() < 1e-9, "got {}", height);
+}
+
+#[test]
+fn bounded_floor_is_never_emulated() {
+    assert!(floor_pose(None, false).is_none());
+}
+
+#[test]
+fn reported_floor_is_used() {
+    for &emulate_height in &[true, false] {
+        let floor = floor_pose(Some(sitting_to_standing(1.2)), emulate_height).unwrap();
+        let height = eye_height(&floor, &RigidTransform3D::identity());
+        assert!((height - 1.2).abs() < 1e-9, "got {}", height);
+    }
+}
+
+#[test]
+fn offset_spaces_keep_the_floor() {
+    let floor = floor_pose(None, true).unwrap();
+    // an offset space whose origin is half a meter above the floor
+    let height = eye_height(&floor, &raised_by(0.5));
+    assert!((height - 1.1).abs() < 1e-9, "got {}", height);
+
+    // offsetting the offset space again goes on from there
+    let offset = compose_offsets(&raised_by(0.5), &raised_by(0.5));
+    let height = eye_height(&floor, &offset);
+    assert!((height - 0.6).abs() < 1e-9, "got {}", height);
+}
@@ -0,0 +1,152 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use euclid::{Angle, RigidTransform3D, Rotation3D, Vector3D};
+use script::test::dommatrixreadonly::DOMMatrixReadOnly;
+use script::test::xr::XRSessionMode;
+use script::test::xrview::{
+    clamp_viewport_scale, eye_transform, has_camera, projection_from_fov,
+    projection_with_clip_planes, MAX_VIEWPORT_SCALE, MIN_VIEWPORT_SCALE,
+};
+use std::f64::consts::FRAC_PI_2;
+
+fn projection(near: f64, far: f64) -> [f32; 16] {
+    let proj = DOMMatrixReadOnly::perspective(FRAC_PI_2, 1.5, near, far).to_row_major_array();
+    let mut result = [0.; 16];
+    for (result, value) in result.iter_mut().zip(proj.iter()) {
+        *result = *value as f32;
+    }
+    result
+}
+
+#[test]
+fn changing_the_far_plane_updates_the_projection() {
+    let old = projection(0.1, 100.);
+    let new = projection_with_clip_planes(&old, 0.1, 1000.);
+    let expected = projection(0.1, 1000.);
+    assert!(new[10] != old[10] && new[14] != old[14]);
+    for (actual, expected) in new.iter().zip(expected.iter()) {
+        assert!(
+            (actual - expected).abs() < 1e-6,
+            "expected {}, got {}",
+            expected,
+            actual
+        );
+    }
+}
+
+#[test]
+fn field_of_view_is_kept() {
+    let old = projection(0.5, 10.);
+    let new = projection_with_clip_planes(&old, 1., 20.);
+    for &i in &[0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 11, 12, 13, 15] {
+        assert_eq!(new[i], old[i]);
+    }
+}
+
+#[test]
+fn symmetric_fov_gives_the_standard_perspective() {
+    // 45 degrees to each edge is a 90 degree vertical field of view
+    let proj = projection_from_fov(45., 45., 45., 45., 0.1, 100.);
+    let expected = DOMMatrixReadOnly::perspective(FRAC_PI_2, 1., 0.1, 100.).to_row_major_array();
+    for (actual, expected) in proj.iter().zip(expected.iter()) {
+        assert!(
+            (*actual as f64 - expected).abs() < 1e-6,
+            "expected {:?}, got {:?}",
+            expected,
+            proj
+        );
+    }
+}
+
+#[test]
+fn asymmetric_fov_gives_an_off_center_projection() {
+    // a canted right eye sees further to the right than to the left
+    let proj = projection_from_fov(40., 55., 45., 35., 0.1, 100.);
+    let (m31, m32) = (proj[8], proj[9]);
+    assert!(
+        m31 > 0.,
+        "expected the frustum to lean right, m31 = {}",
+        m31
+    );
+    assert!(m32 < 0., "expected the frustum to lean down, m32 = {}", m32);
+
+    // the right edge of the frustum still maps to the right edge of clip space
+    let right = 55f64.to_radians().tan();
+    let x = proj[0] as f64 * right + proj[8] as f64 * -1.;
+    assert!((x - 1.).abs() < 1e-6, "expected 1, got {}", x);
+}
+
+fn head() -> RigidTransform3D<f64> {
+    RigidTransform3D::new(
+        Rotation3D::around_axis(
+            Vector3D::new(0.3, 1., -0.2).normalize(),
+            Angle::degrees(37.),
+        ),
+        Vector3D::new(0.1, 1.6, -0.3),
+    )
+}
+
+fn components(transform: &RigidTransform3D<f64>) -> [f64; 7] {
+    let (t, r) = (&transform.translation, &transform.rotation);
+    [t.x, t.y, t.z, r.i, r.j, r.k, r.r]
+}
+
+#[test]
+fn view_without_offset_is_the_head() {
+    let head = head();
+    let view = eye_transform(&head, Vector3D::zero());
+    assert_eq!(components(&view), components(&head));
+}
+
+#[test]
+fn eye_offset_follows_head_rotation() {
+    let head = head();
+    let offset = Vector3D::new(-0.032, 0., 0.);
+    let eye = eye_transform(&head, offset);
+    let expected = head.translation + head.rotation.rotate_vector3d(&offset);
+    assert!((eye.translation - expected).length() < 1e-12);
+    assert_eq!(components(&eye)[3..], components(&head)[3..]);
+}
+
+#[test]
+fn head_is_between_the_eyes() {
+    let head = head();
+    let left = eye_transform(&head, Vector3D::new(-0.032, 0., 0.));
+    let right = eye_transform(&head, Vector3D::new(0.032, 0., 0.));
+    let middle = (left.translation + right.translation) / 2.;
+    assert!((middle - head.translation).length() < 1e-12);
+}
+
+#[test]
+fn eyes_are_the_ipd_apart() {
+    let ipd = 0.064;
+    let head = RigidTransform3D::new(Rotation3D::identity(), Vector3D::new(0.1, 1.6, -0.3));
+    let left = eye_transform(&head, Vector3D::new(-ipd / 2., 0., 0.));
+    let right = eye_transform(&head, Vector3D::new(ipd / 2., 0., 0.));
+    assert!((right.translation.x - left.translation.x - ipd).abs() < 1e-12);
+    assert_eq!(left.translation.y, right.translation.y);
+    assert_eq!(left.translation.z, right.translation.z);
+
+    // a turned head keeps the distance between the eyes
+    let head = head();
+    let left = eye_transform(&head, Vector3D::new(-ipd / 2., 0., 0.));
+    let right = eye_transform(&head, Vector3D::new(ipd / 2., 0., 0.));
+    assert!(((right.translation - left.translation).length() - ipd).abs() < 1e-12);
+}
+
+#[test]
+fn requested_viewport_scales_are_clamped() {
+    assert_eq!(clamp_viewport_scale(0.5), 0.5);
+    assert_eq!(clamp_viewport_scale(2.), MAX_VIEWPORT_SCALE);
+    assert_eq!(clamp_viewport_scale(0.), MIN_VIEWPORT_SCALE);
+}
+
+#[test]
+fn only_ar_sessions_with_camera_access_have_a_camera() {
+    assert!(has_camera(XRSessionMode::Immersive_ar, true));
+    assert!(!has_camera(XRSessionMode::Immersive_ar, false));
+    assert!(!has_camera(XRSessionMode::Immersive_vr, true));
+    assert!(!has_camera(XRSessionMode::Inline, true));
+}
@@ -0,0 +1,69 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use euclid::{Point2D, Rect, Size2D};
+use script::test::xrwebgllayer::{eye_viewport, fixed_foveation_request, framebuffer_size, XREye};
+
+#[test]
+fn eyes_are_side_by_side_without_multiview() {
+    let size = Size2D::new(2000, 1000);
+    assert_eq!(
+        eye_viewport(XREye::Left, size, 1., false),
+        Rect::new(Point2D::new(0, 0), Size2D::new(1000, 1000))
+    );
+    assert_eq!(
+        eye_viewport(XREye::Right, size, 1., false),
+        Rect::new(Point2D::new(1000, 0), Size2D::new(1000, 1000))
+    );
+}
+
+#[test]
+fn multiview_eyes_get_the_full_framebuffer() {
+    let size = Size2D::new(2000, 1000);
+    let full = Rect::new(Point2D::new(0, 0), size);
+    assert_eq!(eye_viewport(XREye::Left, size, 1., true), full);
+    assert_eq!(eye_viewport(XREye::Right, size, 1., true), full);
+}
+
+#[test]
+fn viewports_shrink_with_the_viewport_scale() {
+    let size = Size2D::new(2000, 1000);
+    assert_eq!(
+        eye_viewport(XREye::Right, size, 0.5, false),
+        Rect::new(Point2D::new(1000, 0), Size2D::new(500, 500))
+    );
+    assert_eq!(
+        eye_viewport(XREye::Right, size, 0.5, true),
+        Rect::new(Point2D::new(0, 0), Size2D::new(1000, 500))
+    );
+}
+
+#[test]
+fn lost_contexts_have_an_empty_framebuffer() {
+    let size = Size2D::new(2000, 1000);
+    assert_eq!(framebuffer_size(size, true), size);
+
+    let lost = framebuffer_size(size, false);
+    assert_eq!((lost.width, lost.height), (0, 0));
+    // and viewports of it are empty rather than out of bounds
+    for &eye in &[XREye::Left, XREye::Right] {
+        let viewport = eye_viewport(eye, lost, 1., false);
+        assert_eq!(viewport, Rect::new(Point2D::new(0, 0), Size2D::new(0, 0)));
+    }
+}
+
+#[test]
+fn foveation_levels_are_forwarded_clamped() {
+    assert_eq!(fixed_foveation_request(Some(0.), Some(1.)), Some(1.));
+    assert_eq!(fixed_foveation_request(Some(0.5), Some(2.)), Some(1.));
+    assert_eq!(fixed_foveation_request(Some(0.5), Some(-1.)), Some(0.));
+    // setting null leaves the level alone
+    assert_eq!(fixed_foveation_request(Some(0.5), None), None);
+}
+
+#[test]
+fn unsupported_layers_dont_forward_foveation_levels() {
+    // they keep reporting null
+    assert_eq!(fixed_foveation_request(None, Some(1.)), None);
+}
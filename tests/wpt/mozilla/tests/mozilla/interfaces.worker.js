@@ -7,6 +7,8 @@ importScripts("interfaces.js");
 
 // IMPORTANT: Do not change the list below without review from a DOM peer!
 test_interfaces([
+  "AbortController",
+  "AbortSignal",
   "Blob",
   "CloseEvent",
   "DOMMatrix",
@@ -19,6 +21,7 @@ test_interfaces([
   "CustomEvent",
   "DedicatedWorkerGlobalScope",
   "DOMException",
+  "DOMStringList",
   "ErrorEvent",
   "Event",
   "EventSource",
@@ -42,6 +45,10 @@ test_interfaces([
   "PerformanceResourceTiming",
   "ProgressEvent",
   "PromiseRejectionEvent",
+  "ReadableByteStreamController",
+  "ReadableStream",
+  "ReadableStreamDefaultController",
+  "ReadableStreamDefaultReader",
   "Request",
   "Response",
   "TextDecoder",
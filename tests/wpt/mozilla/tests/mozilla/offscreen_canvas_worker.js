@@ -0,0 +1,8 @@
+onmessage = function(e) {
+  var canvas = e.data.canvas;
+  var ctx = canvas.getContext("2d");
+  ctx.fillStyle = "rgb(0, 255, 0)";
+  ctx.fillRect(0, 0, canvas.width, canvas.height);
+  ctx.commit();
+  postMessage("committed");
+};
@@ -0,0 +1,11 @@
+if (sampleRate <= 0 || currentTime !== 0) {
+    throw new Error("Unexpected rendering state.");
+}
+registerProcessor("gain", class extends AudioWorkletProcessor {
+    static get parameterDescriptors() {
+        return [{ name: "gain", defaultValue: 1, minValue: 0, maxValue: 1 }];
+    }
+    process(inputs, outputs, parameters) {
+        return true;
+    }
+});
@@ -0,0 +1,8 @@
+registerProcessor("gain", class extends AudioWorkletProcessor {
+    static get parameterDescriptors() {
+        return [{ name: "gain", defaultValue: 2, minValue: 0, maxValue: 1 }];
+    }
+    process(inputs, outputs, parameters) {
+        return true;
+    }
+});
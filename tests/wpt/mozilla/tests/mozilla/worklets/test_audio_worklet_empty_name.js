@@ -0,0 +1,5 @@
+registerProcessor("", class extends AudioWorkletProcessor {
+    process(inputs, outputs, parameters) {
+        return true;
+    }
+});